@@ -3,7 +3,7 @@ use serde::{Serialize, Deserialize};
 use tracing::{info, debug, error};
 use chrono::{DateTime, Utc, Duration};
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult, ErrorPayload};
 use crate::services::{
     analytics::AnalyticsService,
     ml_engine::{
@@ -25,7 +25,7 @@ pub async fn get_advanced_analytics(
     recommendation_engine: State<'_, RecommendationEngine>,
     inference_engine: State<'_, InferenceEngine>,
     metrics_collector: State<'_, MetricsCollector>
-) -> AppResult<AdvancedAnalyticsResponse> {
+) -> Result<AdvancedAnalyticsResponse, ErrorPayload> {
     info!("Getting advanced analytics for time range: {}", time_range);
 
     // Parse time range
@@ -70,7 +70,7 @@ pub async fn get_advanced_analytics(
 pub async fn get_realtime_analytics(
     metrics_collector: State<'_, MetricsCollector>,
     analytics_service: State<'_, AnalyticsService>
-) -> AppResult<RealtimeAnalyticsResponse> {
+) -> Result<RealtimeAnalyticsResponse, ErrorPayload> {
     debug!("Getting real-time analytics data");
 
     // Get current system metrics
@@ -103,7 +103,7 @@ pub async fn get_predictive_insights(
     forecast_days: u32,
     inference_engine: State<'_, InferenceEngine>,
     analytics_service: State<'_, AnalyticsService>
-) -> AppResult<PredictiveInsightsResponse> {
+) -> Result<PredictiveInsightsResponse, ErrorPayload> {
     info!("Getting predictive insights for {} days", forecast_days);
 
     // Get historical data for prediction
@@ -135,7 +135,7 @@ pub async fn get_anomaly_detection(
     sensitivity: f64,
     inference_engine: State<'_, InferenceEngine>,
     metrics_collector: State<'_, MetricsCollector>
-) -> AppResult<AnomalyDetectionResponse> {
+) -> Result<AnomalyDetectionResponse, ErrorPayload> {
     info!("Running anomaly detection with sensitivity: {}", sensitivity);
 
     let (start_time, end_time) = parse_time_range(&time_range)?;
@@ -328,7 +328,7 @@ fn parse_time_range(time_range: &str) -> AppResult<(DateTime<Utc>, DateTime<Utc>
         "7d" => end_time - Duration::days(7),
         "30d" => end_time - Duration::days(30),
         "90d" => end_time - Duration::days(90),
-        _ => return Err(crate::error::ResearchError::invalid_request(
+        _ => return Err(AppError::validation("time_range",
             format!("Invalid time range: {}", time_range)
         ).into()),
     };
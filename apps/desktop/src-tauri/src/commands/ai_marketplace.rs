@@ -2,7 +2,7 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, debug, error};
 
-use crate::error::{AppResult, ResearchError};
+use crate::error::{AppError, ErrorPayload, ResearchError};
 use crate::services::ServiceManager;
 use crate::models::ai_marketplace::*;
 
@@ -13,7 +13,7 @@ pub async fn register_marketplace_user(
     username: String,
     email: String,
     display_name: String,
-) -> Result<MarketplaceUser, String> {
+) -> Result<MarketplaceUser, ErrorPayload> {
     info!("API: Registering marketplace user: {}", username);
     
     match service_manager.ai_marketplace_service.register_user(username, email, display_name).await {
@@ -23,7 +23,7 @@ pub async fn register_marketplace_user(
         }
         Err(e) => {
             error!("Failed to register user: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -34,11 +34,11 @@ pub async fn publish_ai_agent(
     service_manager: State<'_, ServiceManager>,
     creator_id: String,
     agent: AIAgentMarketplace,
-) -> Result<AIAgentMarketplace, String> {
+) -> Result<AIAgentMarketplace, ErrorPayload> {
     info!("API: Publishing AI agent: {}", agent.name);
     
     let user_id = Uuid::parse_str(&creator_id)
-        .map_err(|e| format!("Invalid creator ID: {}", e))?;
+        .map_err(|e| AppError::validation("creator_id", format!("Invalid creator ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.publish_agent(user_id, agent).await {
         Ok(published_agent) => {
@@ -47,7 +47,7 @@ pub async fn publish_ai_agent(
         }
         Err(e) => {
             error!("Failed to publish agent: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -58,11 +58,11 @@ pub async fn publish_research_methodology(
     service_manager: State<'_, ServiceManager>,
     creator_id: String,
     methodology: ResearchMethodologyMarketplace,
-) -> Result<ResearchMethodologyMarketplace, String> {
+) -> Result<ResearchMethodologyMarketplace, ErrorPayload> {
     info!("API: Publishing research methodology: {}", methodology.name);
     
     let user_id = Uuid::parse_str(&creator_id)
-        .map_err(|e| format!("Invalid creator ID: {}", e))?;
+        .map_err(|e| AppError::validation("creator_id", format!("Invalid creator ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.publish_methodology(user_id, methodology).await {
         Ok(published_methodology) => {
@@ -71,7 +71,7 @@ pub async fn publish_research_methodology(
         }
         Err(e) => {
             error!("Failed to publish methodology: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -81,7 +81,7 @@ pub async fn publish_research_methodology(
 pub async fn search_marketplace(
     service_manager: State<'_, ServiceManager>,
     query: MarketplaceSearchQuery,
-) -> Result<MarketplaceSearchResult, String> {
+) -> Result<MarketplaceSearchResult, ErrorPayload> {
     debug!("API: Searching marketplace with query: {}", query.query);
     
     match service_manager.ai_marketplace_service.search_marketplace(query).await {
@@ -91,7 +91,7 @@ pub async fn search_marketplace(
         }
         Err(e) => {
             error!("Failed to search marketplace: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -102,11 +102,11 @@ pub async fn install_ai_agent(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
     request: AgentInstallationRequest,
-) -> Result<AgentInstallationResult, String> {
+) -> Result<AgentInstallationResult, ErrorPayload> {
     info!("API: Installing agent: {} for user: {}", request.agent_id, user_id);
     
     let uid = Uuid::parse_str(&user_id)
-        .map_err(|e| format!("Invalid user ID: {}", e))?;
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.install_agent(uid, request).await {
         Ok(result) => {
@@ -115,23 +115,109 @@ pub async fn install_ai_agent(
         }
         Err(e) => {
             error!("Failed to install agent: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// Uninstall an agent or methodology; refused with a warning when other installed
+/// content depends on it, unless forced
+#[tauri::command]
+pub async fn uninstall_marketplace_content(
+    service_manager: State<'_, ServiceManager>,
+    user_id: String,
+    kind: ContentKind,
+    content_id: String,
+    force: bool,
+) -> Result<UninstallResult, ErrorPayload> {
+    info!("API: Uninstalling {} {} for user: {}", kind.as_str(), content_id, user_id);
+
+    let uid = Uuid::parse_str(&user_id)
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
+    let cid = Uuid::parse_str(&content_id)
+        .map_err(|e| AppError::validation("content_id", format!("Invalid content ID: {}", e)))?;
+
+    match service_manager.ai_marketplace_service.uninstall_content(uid, kind, cid, force).await {
+        Ok(result) => {
+            info!("Uninstall completed, removed: {}", result.removed);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to uninstall content: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the marketplace content a user has installed
+#[tauri::command]
+pub async fn get_installed_marketplace_content(
+    service_manager: State<'_, ServiceManager>,
+    user_id: String,
+) -> Result<Vec<InstalledContent>, ErrorPayload> {
+    debug!("API: Getting installed content for user: {}", user_id);
+
+    let uid = Uuid::parse_str(&user_id)
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
+
+    service_manager.ai_marketplace_service.get_installed_content(uid).await
+        .map_err(|e| {
+            error!("Failed to get installed content: {}", e);
+            e.into()
+        })
+}
+
+/// Run an installed AI agent in the sandbox
+#[tauri::command]
+pub async fn run_installed_agent(
+    service_manager: State<'_, ServiceManager>,
+    user_id: String,
+    request: AgentRunRequest,
+) -> Result<AgentRunResult, ErrorPayload> {
+    info!("API: Running agent: {} for user: {}", request.agent_id, user_id);
+
+    let uid = Uuid::parse_str(&user_id)
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
+
+    match service_manager.ai_marketplace_service.run_installed_agent(uid, request).await {
+        Ok(result) => {
+            info!("Agent run completed with success: {}", result.success);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to run agent: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get sandbox resource usage per agent, for one agent if given
+#[tauri::command]
+pub async fn get_agent_resource_usage(
+    service_manager: State<'_, ServiceManager>,
+    agent_id: Option<String>,
+) -> Result<Vec<AgentResourceUsage>, ErrorPayload> {
+    debug!("API: Getting agent resource usage");
+
+    let agent_uuid = agent_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| AppError::validation("agent_id", format!("Invalid agent ID: {}", e))))
+        .transpose()?;
+
+    Ok(service_manager.ai_marketplace_service.get_agent_resource_usage(agent_uuid).await)
+}
+
 /// Submit a rating/review
 #[tauri::command]
 pub async fn submit_community_rating(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
     rating: CommunityRating,
-) -> Result<CommunityRating, String> {
+) -> Result<CommunityRating, ErrorPayload> {
     info!("API: Submitting rating for {:?}: {} by user: {}", 
            rating.target_type, rating.target_id, user_id);
     
     let uid = Uuid::parse_str(&user_id)
-        .map_err(|e| format!("Invalid user ID: {}", e))?;
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.submit_rating(uid, rating).await {
         Ok(submitted_rating) => {
@@ -140,7 +226,7 @@ pub async fn submit_community_rating(
         }
         Err(e) => {
             error!("Failed to submit rating: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -150,17 +236,17 @@ pub async fn submit_community_rating(
 pub async fn get_marketplace_user_analytics(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
-) -> Result<MarketplaceAnalytics, String> {
+) -> Result<MarketplaceAnalytics, ErrorPayload> {
     debug!("API: Getting analytics for user: {}", user_id);
     
     let uid = Uuid::parse_str(&user_id)
-        .map_err(|e| format!("Invalid user ID: {}", e))?;
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.get_user_analytics(uid).await {
         Ok(analytics) => Ok(analytics),
         Err(e) => {
             error!("Failed to get user analytics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -169,14 +255,14 @@ pub async fn get_marketplace_user_analytics(
 #[tauri::command]
 pub async fn get_featured_agents(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<AIAgentMarketplace>, String> {
+) -> Result<Vec<AIAgentMarketplace>, ErrorPayload> {
     debug!("API: Getting featured agents");
     
     match service_manager.ai_marketplace_service.get_featured_agents().await {
         Ok(featured_agents) => Ok(featured_agents),
         Err(e) => {
             error!("Failed to get featured agents: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -185,14 +271,14 @@ pub async fn get_featured_agents(
 #[tauri::command]
 pub async fn get_trending_methodologies(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchMethodologyMarketplace>, String> {
+) -> Result<Vec<ResearchMethodologyMarketplace>, ErrorPayload> {
     debug!("API: Getting trending methodologies");
     
     match service_manager.ai_marketplace_service.get_trending_methodologies().await {
         Ok(trending_methodologies) => Ok(trending_methodologies),
         Err(e) => {
             error!("Failed to get trending methodologies: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -202,11 +288,11 @@ pub async fn get_trending_methodologies(
 pub async fn get_user_marketplace_content(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
-) -> Result<UserMarketplaceContent, String> {
+) -> Result<UserMarketplaceContent, ErrorPayload> {
     debug!("API: Getting content for user: {}", user_id);
     
     let uid = Uuid::parse_str(&user_id)
-        .map_err(|e| format!("Invalid user ID: {}", e))?;
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     
     match service_manager.ai_marketplace_service.get_user_content(uid).await {
         Ok((agents, methodologies)) => Ok(UserMarketplaceContent {
@@ -215,7 +301,7 @@ pub async fn get_user_marketplace_content(
         }),
         Err(e) => {
             error!("Failed to get user content: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -224,7 +310,7 @@ pub async fn get_user_marketplace_content(
 #[tauri::command]
 pub async fn get_marketplace_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<MarketplaceStatistics, String> {
+) -> Result<MarketplaceStatistics, ErrorPayload> {
     debug!("API: Getting marketplace statistics");
     
     // This would aggregate statistics across the marketplace
@@ -265,7 +351,7 @@ pub struct MarketplaceStatistics {
 #[tauri::command]
 pub async fn get_agent_categories(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<AgentCategoryInfo>, String> {
+) -> Result<Vec<AgentCategoryInfo>, ErrorPayload> {
     debug!("API: Getting agent categories");
     
     // Return available agent categories with counts
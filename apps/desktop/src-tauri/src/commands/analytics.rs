@@ -3,14 +3,14 @@ use tracing::{info, error};
 use serde::{Serialize, Deserialize};
 
 use crate::services::ServiceManager;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, ErrorPayload};
 
 /// Get comprehensive analytics dashboard data
 #[tauri::command]
 pub async fn get_analytics_dashboard_data(
     service_manager: State<'_, ServiceManager>,
     time_range: Option<String>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting analytics dashboard data for time range: {:?}", time_range);
 
     let analytics = service_manager.analytics.read().await;
@@ -18,11 +18,11 @@ pub async fn get_analytics_dashboard_data(
     match analytics.get_dashboard_data().await {
         Ok(dashboard_data) => {
             info!("Successfully retrieved analytics dashboard data");
-            Ok(serde_json::to_value(dashboard_data).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(dashboard_data).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to get analytics dashboard data: {}", e);
-            Err(format!("Failed to get analytics dashboard data: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -32,7 +32,7 @@ pub async fn get_analytics_dashboard_data(
 pub async fn get_usage_analytics(
     service_manager: State<'_, ServiceManager>,
     period: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting usage analytics for period: {}", period);
 
     let analytics = service_manager.analytics.read().await;
@@ -51,11 +51,11 @@ pub async fn get_usage_analytics(
     match analytics.get_usage_analytics(time_period).await {
         Ok(usage_data) => {
             info!("Successfully retrieved usage analytics data");
-            Ok(serde_json::to_value(usage_data).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(usage_data).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to get usage analytics: {}", e);
-            Err(format!("Failed to get usage analytics: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -64,7 +64,7 @@ pub async fn get_usage_analytics(
 #[tauri::command]
 pub async fn get_performance_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting current performance metrics");
 
     let analytics = service_manager.analytics.read().await;
@@ -72,11 +72,11 @@ pub async fn get_performance_metrics(
     match analytics.get_performance_metrics().await {
         Ok(performance_metrics) => {
             info!("Successfully retrieved performance metrics");
-            Ok(serde_json::to_value(performance_metrics).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(performance_metrics).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to get performance metrics: {}", e);
-            Err(format!("Failed to get performance metrics: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -85,7 +85,7 @@ pub async fn get_performance_metrics(
 #[tauri::command]
 pub async fn get_performance_trends(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting performance trends analysis");
 
     // This would typically call a method on the performance monitor
@@ -130,7 +130,7 @@ pub async fn get_performance_trends(
 #[tauri::command]
 pub async fn get_predictive_analytics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting predictive analytics data");
 
     let analytics = service_manager.analytics.read().await;
@@ -138,11 +138,11 @@ pub async fn get_predictive_analytics(
     match analytics.get_predictive_analytics().await {
         Ok(predictive_data) => {
             info!("Successfully retrieved predictive analytics data");
-            Ok(serde_json::to_value(predictive_data).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(predictive_data).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to get predictive analytics: {}", e);
-            Err(format!("Failed to get predictive analytics: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -152,7 +152,7 @@ pub async fn get_predictive_analytics(
 pub async fn generate_business_report(
     service_manager: State<'_, ServiceManager>,
     report_type: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Generating business report of type: {}", report_type);
 
     let analytics = service_manager.analytics.read().await;
@@ -170,11 +170,11 @@ pub async fn generate_business_report(
     match analytics.generate_business_report(business_report_type).await {
         Ok(report) => {
             info!("Successfully generated business report");
-            Ok(serde_json::to_value(report).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(report).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to generate business report: {}", e);
-            Err(format!("Failed to generate business report: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -183,7 +183,7 @@ pub async fn generate_business_report(
 #[tauri::command]
 pub async fn get_optimization_recommendations(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting optimization recommendations");
 
     let analytics = service_manager.analytics.read().await;
@@ -191,11 +191,11 @@ pub async fn get_optimization_recommendations(
     match analytics.get_optimization_recommendations().await {
         Ok(recommendations) => {
             info!("Successfully retrieved optimization recommendations");
-            Ok(serde_json::to_value(recommendations).map_err(|e| e.to_string())?)
+            Ok(serde_json::to_value(recommendations).map_err(AppError::from)?)
         }
         Err(e) => {
             error!("Failed to get optimization recommendations: {}", e);
-            Err(format!("Failed to get optimization recommendations: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -206,7 +206,7 @@ pub async fn record_analytics_event(
     service_manager: State<'_, ServiceManager>,
     event_type: String,
     metadata: Option<serde_json::Value>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Recording analytics event: {}", event_type);
 
     let analytics = service_manager.analytics.read().await;
@@ -224,6 +224,7 @@ pub async fn record_analytics_event(
         "SystemStartup" => crate::services::analytics::EventType::SystemStartup,
         "SystemShutdown" => crate::services::analytics::EventType::SystemShutdown,
         "PerformanceAlert" => crate::services::analytics::EventType::PerformanceAlert,
+        "ScalingDecision" => crate::services::analytics::EventType::ScalingDecision,
         "DashboardViewed" => crate::services::analytics::EventType::DashboardViewed,
         "ReportGenerated" => crate::services::analytics::EventType::ReportGenerated,
         "ConfigurationChanged" => crate::services::analytics::EventType::ConfigurationChanged,
@@ -232,7 +233,7 @@ pub async fn record_analytics_event(
         "ErrorOccurred" => crate::services::analytics::EventType::ErrorOccurred,
         _ => {
             error!("Unknown event type: {}", event_type);
-            return Err(format!("Unknown event type: {}", event_type));
+            return Err(AppError::validation("event_type", format!("Unknown event type: {}", event_type)).into());
         }
     };
 
@@ -258,7 +259,7 @@ pub async fn record_analytics_event(
         }
         Err(e) => {
             error!("Failed to record analytics event: {}", e);
-            Err(format!("Failed to record analytics event: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -267,7 +268,7 @@ pub async fn record_analytics_event(
 #[tauri::command]
 pub async fn get_analytics_config(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting analytics configuration");
 
     // Return default analytics configuration
@@ -280,7 +281,7 @@ pub async fn get_analytics_config(
         }
         Err(e) => {
             error!("Failed to serialize analytics configuration: {}", e);
-            Err(format!("Failed to get analytics configuration: {}", e))
+            Err(AppError::from(e).into())
         }
     }
 }
@@ -290,13 +291,13 @@ pub async fn get_analytics_config(
 pub async fn update_analytics_config(
     service_manager: State<'_, ServiceManager>,
     config: serde_json::Value,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Updating analytics configuration");
 
     let analytics_config: crate::services::analytics::AnalyticsConfig = 
         serde_json::from_value(config).map_err(|e| {
             error!("Failed to parse analytics configuration: {}", e);
-            format!("Invalid configuration format: {}", e)
+            AppError::validation("config", format!("Invalid configuration format: {}", e))
         })?;
 
     let mut analytics = service_manager.analytics.write().await;
@@ -308,7 +309,7 @@ pub async fn update_analytics_config(
         }
         Err(e) => {
             error!("Failed to update analytics configuration: {}", e);
-            Err(format!("Failed to update analytics configuration: {}", e))
+            Err(e.into())
         }
     }
 }
@@ -320,7 +321,7 @@ pub async fn export_analytics_data(
     export_type: String,
     time_period: String,
     format: String,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Exporting analytics data: type={}, period={}, format={}", export_type, time_period, format);
 
     // This would implement actual data export functionality
@@ -338,7 +339,7 @@ pub async fn export_analytics_data(
 #[tauri::command]
 pub async fn get_analytics_health(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting analytics health status");
 
     let analytics = service_manager.analytics.read().await;
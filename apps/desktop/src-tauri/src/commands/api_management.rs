@@ -2,23 +2,54 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, error};
 
-use crate::error::AppResult;
-use crate::models::{ApiKey, CreateApiKeyRequest, UpdateApiKeyRequest, ApiKeyTestResult, ApiKeyImport};
-use crate::services::{ServiceManager, api_manager::{ImportResult, UsageStatus, RateLimitAlert, UsageForecast, RateLimitConfig, KeyPerformanceMetrics, KeyHealth, RotationAnalytics, RotationConfig, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig, ServiceMetrics}};
+use crate::error::{AppError, ErrorPayload};
+use crate::models::{Page, PageRequest, ApiKey, CreateApiKeyRequest, UpdateApiKeyRequest, ApiKeyTestResult, ApiKeyImport, ApiKeyFilter, ApiKeyStatus, UsageDivergence, Notification, NotificationPreferences};
+use crate::services::{ServiceManager, api_manager::{ImportResult, BulkOperationResult, UsageStatus, RateLimitAlert, UsageForecast, RateLimitConfig, RateLimitSimulation, QuotaConfig, KeyPerformanceMetrics, KeyHealth, RotationAnalytics, RotationConfig, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig, ServiceMetrics, RecordedExchange, ModelRoutingPolicy, TenantKeyUsage, UsageReconciliationReport, DivergenceConfig, EgressProfile, ContentCacheConfig, ContentCacheStats, CrawlPolicyConfig, KeyScope, run_scoped}};
+
+/// The API keys the caller may see and spend, from the enterprise session it signed in
+/// with. The scope is never taken from the caller's arguments.
+pub(crate) async fn caller_scope(service_manager: &ServiceManager, session_id: Option<String>) -> Result<KeyScope, ErrorPayload> {
+    let session_id = session_id
+        .map(|id| Uuid::parse_str(&id).map_err(|_| AppError::validation("session_id", "Invalid session ID")))
+        .transpose()?;
+    Ok(service_manager.enterprise.read().await.key_scope(session_id).await?)
+}
 
 /// Get all API keys
 #[tauri::command]
 pub async fn get_api_keys(
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ApiKey>, String> {
+) -> Result<Vec<ApiKey>, ErrorPayload> {
     info!("Getting all API keys");
     
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let api_manager = service_manager.inner().api_manager.read().await;
-    match api_manager.get_all_keys().await {
+    match run_scoped(scope, api_manager.get_all_keys()).await {
         Ok(keys) => Ok(keys),
         Err(e) => {
             error!("Failed to get API keys: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Get a page of API keys, newest first
+#[tauri::command]
+pub async fn list_api_keys(
+    page: PageRequest,
+    session_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Page<ApiKey>, ErrorPayload> {
+    info!("Listing API keys (limit {})", page.limit());
+
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match run_scoped(scope, api_manager.list_keys(&page)).await {
+        Ok(keys) => Ok(keys),
+        Err(e) => {
+            error!("Failed to list API keys: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -27,19 +58,21 @@ pub async fn get_api_keys(
 #[tauri::command]
 pub async fn add_api_key(
     request: CreateApiKeyRequest,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ApiKey, String> {
+) -> Result<ApiKey, ErrorPayload> {
     info!("Adding new API key for service: {:?}", request.service);
     
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let mut api_manager = service_manager.inner().api_manager.write().await;
-    match api_manager.add_key(request).await {
+    match run_scoped(scope, api_manager.add_key(request)).await {
         Ok(key) => {
             info!("API key added successfully: {}", key.id);
             Ok(key)
         }
         Err(e) => {
             error!("Failed to add API key: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -49,22 +82,24 @@ pub async fn add_api_key(
 pub async fn update_api_key(
     key_id: String,
     request: UpdateApiKeyRequest,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ApiKey, String> {
+) -> Result<ApiKey, ErrorPayload> {
     info!("Updating API key: {}", key_id);
     
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
     
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let mut api_manager = service_manager.inner().api_manager.write().await;
-    match api_manager.update_key(key_uuid, request).await {
+    match run_scoped(scope, api_manager.update_key(key_uuid, request)).await {
         Ok(key) => {
             info!("API key updated successfully: {}", key.id);
             Ok(key)
         }
         Err(e) => {
             error!("Failed to update API key: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -73,22 +108,24 @@ pub async fn update_api_key(
 #[tauri::command]
 pub async fn delete_api_key(
     key_id: String,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Deleting API key: {}", key_id);
     
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
     
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let mut api_manager = service_manager.inner().api_manager.write().await;
-    match api_manager.delete_key(key_uuid).await {
+    match run_scoped(scope, api_manager.delete_key(key_uuid)).await {
         Ok(_) => {
             info!("API key deleted successfully: {}", key_id);
             Ok(())
         }
         Err(e) => {
             error!("Failed to delete API key: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -97,22 +134,24 @@ pub async fn delete_api_key(
 #[tauri::command]
 pub async fn test_api_key(
     key_id: String,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ApiKeyTestResult, String> {
+) -> Result<ApiKeyTestResult, ErrorPayload> {
     info!("Testing API key: {}", key_id);
     
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
     
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let api_manager = service_manager.inner().api_manager.read().await;
-    match api_manager.test_key(key_uuid).await {
+    match run_scoped(scope, api_manager.test_key(key_uuid)).await {
         Ok(result) => {
             info!("API key test completed: {} - {}", key_id, if result.success { "SUCCESS" } else { "FAILED" });
             Ok(result)
         }
         Err(e) => {
             error!("Failed to test API key: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -122,7 +161,7 @@ pub async fn test_api_key(
 pub async fn import_api_keys(
     keys: Vec<ApiKeyImport>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ImportResult, String> {
+) -> Result<ImportResult, ErrorPayload> {
     info!("Importing {} API keys", keys.len());
     
     let mut api_manager = service_manager.inner().api_manager.write().await;
@@ -134,7 +173,80 @@ pub async fn import_api_keys(
         }
         Err(e) => {
             error!("Failed to import API keys: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Set the status of every API key matching a filter
+#[tauri::command]
+pub async fn bulk_update_api_key_status(
+    filter: ApiKeyFilter,
+    status: ApiKeyStatus,
+    session_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BulkOperationResult, ErrorPayload> {
+    info!("Bulk updating API key status to {:?}", status);
+
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
+    let mut api_manager = service_manager.inner().api_manager.write().await;
+    match run_scoped(scope, api_manager.bulk_update_status(filter, status)).await {
+        Ok(result) => {
+            info!("Bulk status update completed: {} successful, {} failed",
+                  result.successful_count, result.failed_count);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to bulk update API key status: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Delete every API key matching a filter
+#[tauri::command]
+pub async fn bulk_delete_api_keys(
+    filter: ApiKeyFilter,
+    session_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BulkOperationResult, ErrorPayload> {
+    info!("Bulk deleting API keys");
+
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
+    let mut api_manager = service_manager.inner().api_manager.write().await;
+    match run_scoped(scope, api_manager.bulk_delete(filter)).await {
+        Ok(result) => {
+            info!("Bulk delete completed: {} successful, {} failed",
+                  result.successful_count, result.failed_count);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to bulk delete API keys: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Test every API key matching a filter
+#[tauri::command]
+pub async fn bulk_test_api_keys(
+    filter: ApiKeyFilter,
+    session_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BulkOperationResult, ErrorPayload> {
+    info!("Bulk testing API keys");
+
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match run_scoped(scope, api_manager.bulk_test(filter)).await {
+        Ok(result) => {
+            info!("Bulk test completed: {} passed, {} failed",
+                  result.successful_count, result.failed_count);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to bulk test API keys: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -143,7 +255,7 @@ pub async fn import_api_keys(
 #[tauri::command]
 pub async fn export_api_keys(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<crate::models::ApiKeyExport>, String> {
+) -> Result<Vec<crate::models::ApiKeyExport>, ErrorPayload> {
     info!("Exporting API keys");
     
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -154,7 +266,7 @@ pub async fn export_api_keys(
         }
         Err(e) => {
             error!("Failed to export API keys: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -164,7 +276,7 @@ pub async fn export_api_keys(
 pub async fn import_api_keys_csv(
     csv_content: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ImportResult, String> {
+) -> Result<ImportResult, ErrorPayload> {
     info!("Importing API keys from CSV content");
 
     let mut api_manager = service_manager.inner().api_manager.write().await;
@@ -176,7 +288,7 @@ pub async fn import_api_keys_csv(
         }
         Err(e) => {
             error!("Failed to import API keys from CSV: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -186,7 +298,7 @@ pub async fn import_api_keys_csv(
 pub async fn import_api_keys_json(
     json_content: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ImportResult, String> {
+) -> Result<ImportResult, ErrorPayload> {
     info!("Importing API keys from JSON content");
 
     let mut api_manager = service_manager.inner().api_manager.write().await;
@@ -198,7 +310,7 @@ pub async fn import_api_keys_json(
         }
         Err(e) => {
             error!("Failed to import API keys from JSON: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -207,7 +319,7 @@ pub async fn import_api_keys_json(
 #[tauri::command]
 pub async fn export_api_keys_csv(
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Exporting API keys to CSV format");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -218,7 +330,7 @@ pub async fn export_api_keys_csv(
         }
         Err(e) => {
             error!("Failed to export API keys to CSV: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -227,7 +339,7 @@ pub async fn export_api_keys_csv(
 #[tauri::command]
 pub async fn export_api_keys_json(
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Exporting API keys to JSON format");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -238,7 +350,7 @@ pub async fn export_api_keys_json(
         }
         Err(e) => {
             error!("Failed to export API keys to JSON: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -249,11 +361,11 @@ pub async fn get_api_key_usage_stats(
     key_id: String,
     days: u32,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, u32, u32, u32, f64)>, String> {
+) -> Result<Vec<(String, u32, u32, u32, f64)>, ErrorPayload> {
     info!("Getting usage statistics for API key: {}", key_id);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.get_key_usage_stats(key_uuid, days).await {
@@ -263,16 +375,272 @@ pub async fn get_api_key_usage_stats(
         }
         Err(e) => {
             error!("Failed to get usage statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// Parse a report's `YYYY-MM-DD` date range
+fn parse_date_range(from: &str, to: &str) -> Result<(chrono::NaiveDate, chrono::NaiveDate), ErrorPayload> {
+    let parse = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| AppError::validation("date", format!("Invalid date {}: {}", date, e)));
+    Ok((parse(from)?, parse(to)?))
+}
+
+/// Get per provider and per key usage over a date range, with provider-reported counts
+#[tauri::command]
+pub async fn get_usage_reconciliation_report(
+    from: String,
+    to: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<UsageReconciliationReport, ErrorPayload> {
+    info!("Getting usage reconciliation report for {} to {}", from, to);
+
+    let (from_date, to_date) = parse_date_range(&from, &to)?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.get_usage_reconciliation_report(from_date, to_date).await {
+        Ok(report) => {
+            info!("Usage reconciliation report covers {} keys", report.keys.len());
+            Ok(report)
+        }
+        Err(e) => {
+            error!("Failed to build usage reconciliation report: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Export the usage reconciliation report to CSV format
+#[tauri::command]
+pub async fn export_usage_reconciliation_csv(
+    from: String,
+    to: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, ErrorPayload> {
+    info!("Exporting usage reconciliation report to CSV for {} to {}", from, to);
+
+    let (from_date, to_date) = parse_date_range(&from, &to)?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.export_usage_reconciliation_csv(from_date, to_date).await.map_err(|e| {
+        error!("Failed to export usage reconciliation report to CSV: {}", e);
+        e.into()
+    })
+}
+
+/// Export the usage reconciliation report to JSON format
+#[tauri::command]
+pub async fn export_usage_reconciliation_json(
+    from: String,
+    to: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, ErrorPayload> {
+    info!("Exporting usage reconciliation report to JSON for {} to {}", from, to);
+
+    let (from_date, to_date) = parse_date_range(&from, &to)?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.export_usage_reconciliation_json(from_date, to_date).await.map_err(|e| {
+        error!("Failed to export usage reconciliation report to JSON: {}", e);
+        e.into()
+    })
+}
+
+/// Compare counted and provider-reported usage of every key now
+#[tauri::command]
+pub async fn check_usage_divergence(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<UsageDivergence>, ErrorPayload> {
+    info!("Checking usage divergence");
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.check_usage_divergence().await {
+        Ok(divergences) => {
+            let exceeded = divergences.iter().filter(|d| d.threshold_exceeded).count();
+            info!("{} of {} keys diverge past the threshold", exceeded, divergences.len());
+            Ok(divergences)
+        }
+        Err(e) => {
+            error!("Failed to check usage divergence: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get recorded usage divergence checks of the last `days`, optionally for one key
+#[tauri::command]
+pub async fn get_usage_divergence_history(
+    days: u32,
+    key_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<UsageDivergence>, ErrorPayload> {
+    info!("Getting usage divergence history for the last {} days", days);
+
+    let key_uuid = key_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e))))
+        .transpose()?;
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.get_usage_divergence_history(days, key_uuid).await.map_err(|e| {
+        error!("Failed to get usage divergence history: {}", e);
+        e.into()
+    })
+}
+
+/// Get the usage divergence alerting configuration
+#[tauri::command]
+pub async fn get_divergence_config(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<DivergenceConfig, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_divergence_config().await)
+}
+
+/// Update the usage divergence alerting configuration
+#[tauri::command]
+pub async fn update_divergence_config(
+    config: DivergenceConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating usage divergence config");
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.update_divergence_config(config).await.map_err(|e| {
+        error!("Failed to update usage divergence config: {}", e);
+        e.into()
+    })
+}
+
+/// Get a user's notification preferences
+#[tauri::command]
+pub async fn get_notification_preferences(
+    user_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<NotificationPreferences, ErrorPayload> {
+    let user_uuid = Uuid::parse_str(&user_id)
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.get_notification_preferences(user_uuid).await.map_err(|e| {
+        error!("Failed to get notification preferences: {}", e);
+        e.into()
+    })
+}
+
+/// Update a user's notification preferences
+#[tauri::command]
+pub async fn update_notification_preferences(
+    preferences: NotificationPreferences,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating notification preferences for user {}", preferences.user_id);
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.update_notification_preferences(preferences).await.map_err(|e| {
+        error!("Failed to update notification preferences: {}", e);
+        e.into()
+    })
+}
+
+/// Get a user's in-app notifications, newest first
+#[tauri::command]
+pub async fn get_notification_inbox(
+    user_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<Notification>, ErrorPayload> {
+    let user_uuid = Uuid::parse_str(&user_id)
+        .map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.get_notification_inbox(user_uuid).await.map_err(|e| {
+        error!("Failed to get notification inbox: {}", e);
+        e.into()
+    })
+}
+
+/// Get the egress profiles workflows can send provider requests through
+#[tauri::command]
+pub async fn get_egress_profiles(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<EgressProfile>, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_egress_profiles().await)
+}
+
+/// Replace the egress profiles
+#[tauri::command]
+pub async fn update_egress_profiles(
+    profiles: Vec<EgressProfile>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating {} egress profiles", profiles.len());
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.update_egress_profiles(profiles).await.map_err(|e| {
+        error!("Failed to update egress profiles: {}", e);
+        e.into()
+    })
+}
+
+/// Get the content cache's hit rate and the provider requests it saved
+#[tauri::command]
+pub async fn get_content_cache_stats(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ContentCacheStats, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_content_cache_stats())
+}
+
+/// Get the content cache configuration
+#[tauri::command]
+pub async fn get_content_cache_config(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ContentCacheConfig, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_content_cache_config())
+}
+
+/// Replace the content cache configuration
+#[tauri::command]
+pub async fn update_content_cache_config(
+    config: ContentCacheConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating content cache configuration");
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.update_content_cache_config(config).map_err(|e| {
+        error!("Failed to update content cache configuration: {}", e);
+        e.into()
+    })
+}
+
+/// Get the robots.txt handling and per-host limits page extraction follows
+#[tauri::command]
+pub async fn get_crawl_policy_config(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<CrawlPolicyConfig, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_crawl_policy_config())
+}
+
+/// Replace the crawl policy configuration
+#[tauri::command]
+pub async fn update_crawl_policy_config(
+    config: CrawlPolicyConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating crawl policy configuration");
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.update_crawl_policy_config(config).map_err(|e| {
+        error!("Failed to update crawl policy configuration: {}", e);
+        e.into()
+    })
+}
+
 /// Get all API keys with their current status
 #[tauri::command]
 pub async fn get_api_keys_with_status(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(ApiKey, bool)>, String> {
+) -> Result<Vec<(ApiKey, bool)>, ErrorPayload> {
     info!("Getting all API keys with status");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -283,7 +651,7 @@ pub async fn get_api_keys_with_status(
         }
         Err(e) => {
             error!("Failed to get API keys with status: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -293,11 +661,11 @@ pub async fn get_api_keys_with_status(
 pub async fn can_make_request(
     key_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     info!("Checking if request can be made for API key: {}", key_id);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.can_make_request(key_uuid).await {
@@ -307,7 +675,7 @@ pub async fn can_make_request(
         }
         Err(e) => {
             error!("Failed to check request permission: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -317,11 +685,11 @@ pub async fn can_make_request(
 pub async fn get_key_usage_status(
     key_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<UsageStatus, String> {
+) -> Result<UsageStatus, ErrorPayload> {
     info!("Getting usage status for API key: {}", key_id);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.get_key_usage_status(key_uuid).await {
@@ -331,7 +699,7 @@ pub async fn get_key_usage_status(
         }
         Err(e) => {
             error!("Failed to get usage status: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -342,11 +710,11 @@ pub async fn record_api_request(
     key_id: String,
     success: bool,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<RateLimitAlert>, String> {
+) -> Result<Option<RateLimitAlert>, ErrorPayload> {
     info!("Recording API request for key: {} (success: {})", key_id, success);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.record_api_request(key_uuid, success).await {
@@ -358,17 +726,32 @@ pub async fn record_api_request(
         }
         Err(e) => {
             error!("Failed to record API request: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// Get the requests a tenant has sent with each API key; without a tenant, the system's own
+#[tauri::command]
+pub async fn get_tenant_key_usage(
+    tenant_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<TenantKeyUsage>, ErrorPayload> {
+    let tenant_uuid = tenant_id.as_deref()
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|e| AppError::validation("tenant_id", format!("Invalid tenant ID: {}", e)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_tenant_key_usage(tenant_uuid).await)
+}
+
 /// Enable or disable emergency stop
 #[tauri::command]
 pub async fn set_emergency_stop(
     enabled: bool,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Setting emergency stop: {}", enabled);
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -379,7 +762,7 @@ pub async fn set_emergency_stop(
         }
         Err(e) => {
             error!("Failed to set emergency stop: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -388,7 +771,7 @@ pub async fn set_emergency_stop(
 #[tauri::command]
 pub async fn is_emergency_stop_enabled(
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     let api_manager = service_manager.inner().api_manager.read().await;
     let enabled = api_manager.is_emergency_stop_enabled().await;
     Ok(enabled)
@@ -399,7 +782,7 @@ pub async fn is_emergency_stop_enabled(
 pub async fn get_recent_alerts(
     limit: usize,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<RateLimitAlert>, String> {
+) -> Result<Vec<RateLimitAlert>, ErrorPayload> {
     info!("Getting {} recent rate limit alerts", limit);
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -414,11 +797,11 @@ pub async fn get_recent_alerts(
 pub async fn generate_usage_forecast(
     key_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<UsageForecast, String> {
+) -> Result<UsageForecast, ErrorPayload> {
     info!("Generating usage forecast for API key: {}", key_id);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.generate_usage_forecast(key_uuid).await {
@@ -428,7 +811,7 @@ pub async fn generate_usage_forecast(
         }
         Err(e) => {
             error!("Failed to generate usage forecast: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -437,7 +820,7 @@ pub async fn generate_usage_forecast(
 #[tauri::command]
 pub async fn get_usage_analytics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, UsageStatus, UsageForecast)>, String> {
+) -> Result<Vec<(String, UsageStatus, UsageForecast)>, ErrorPayload> {
     info!("Getting usage analytics for all API keys");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -452,7 +835,7 @@ pub async fn get_usage_analytics(
         }
         Err(e) => {
             error!("Failed to get usage analytics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -461,7 +844,7 @@ pub async fn get_usage_analytics(
 #[tauri::command]
 pub async fn check_all_thresholds(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<RateLimitAlert>, String> {
+) -> Result<Vec<RateLimitAlert>, ErrorPayload> {
     info!("Checking all API keys for threshold violations");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -472,7 +855,7 @@ pub async fn check_all_thresholds(
         }
         Err(e) => {
             error!("Failed to check thresholds: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -481,7 +864,7 @@ pub async fn check_all_thresholds(
 #[tauri::command]
 pub async fn generate_usage_report(
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Generating automated usage report");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -492,7 +875,7 @@ pub async fn generate_usage_report(
         }
         Err(e) => {
             error!("Failed to generate usage report: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -501,15 +884,17 @@ pub async fn generate_usage_report(
 #[tauri::command]
 pub async fn select_best_key_for_service(
     service: String,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<ApiKey>, String> {
+) -> Result<Option<ApiKey>, ErrorPayload> {
     info!("Selecting best API key for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let api_manager = service_manager.inner().api_manager.read().await;
-    match api_manager.select_best_key_for_service(service_provider).await {
+    match run_scoped(scope, api_manager.select_best_key_for_service(service_provider)).await {
         Ok(key) => {
             if let Some(ref key) = key {
                 info!("Selected key {} for service {}", key.id, service);
@@ -520,7 +905,7 @@ pub async fn select_best_key_for_service(
         }
         Err(e) => {
             error!("Failed to select best key for service {}: {}", service, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -532,11 +917,11 @@ pub async fn record_key_performance(
     success: bool,
     response_time_ms: u32,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     debug!("Recording performance for key: {} (success: {}, time: {}ms)", key_id, success, response_time_ms);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.record_key_performance(key_uuid, success, response_time_ms).await {
@@ -546,7 +931,7 @@ pub async fn record_key_performance(
         }
         Err(e) => {
             error!("Failed to record key performance: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -556,11 +941,11 @@ pub async fn record_key_performance(
 pub async fn get_key_performance_metrics(
     key_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<KeyPerformanceMetrics>, String> {
+) -> Result<Option<KeyPerformanceMetrics>, ErrorPayload> {
     info!("Getting performance metrics for API key: {}", key_id);
 
     let key_uuid = Uuid::parse_str(&key_id)
-        .map_err(|e| format!("Invalid key ID: {}", e))?;
+        .map_err(|e| AppError::validation("key_id", format!("Invalid key ID: {}", e)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     let metrics = api_manager.get_key_performance_metrics(key_uuid).await;
@@ -573,11 +958,11 @@ pub async fn get_key_performance_metrics(
 pub async fn get_service_performance_metrics(
     service: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<KeyPerformanceMetrics>, String> {
+) -> Result<Vec<KeyPerformanceMetrics>, ErrorPayload> {
     info!("Getting performance metrics for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     let metrics = api_manager.get_service_performance_metrics(service_provider).await;
@@ -590,7 +975,7 @@ pub async fn get_service_performance_metrics(
 #[tauri::command]
 pub async fn get_all_performance_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, KeyPerformanceMetrics)>, String> {
+) -> Result<Vec<(String, KeyPerformanceMetrics)>, ErrorPayload> {
     info!("Getting all performance metrics");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -608,7 +993,7 @@ pub async fn get_all_performance_metrics(
 #[tauri::command]
 pub async fn get_rotation_analytics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<RotationAnalytics, String> {
+) -> Result<RotationAnalytics, ErrorPayload> {
     info!("Getting rotation analytics");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -622,7 +1007,7 @@ pub async fn get_rotation_analytics(
 #[tauri::command]
 pub async fn perform_health_check(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, KeyHealth)>, String> {
+) -> Result<Vec<(String, KeyHealth)>, ErrorPayload> {
     info!("Performing health check on all API keys");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -637,7 +1022,7 @@ pub async fn perform_health_check(
         }
         Err(e) => {
             error!("Failed to perform health check: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -646,7 +1031,7 @@ pub async fn perform_health_check(
 #[tauri::command]
 pub async fn reactivate_cooled_down_keys(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Reactivating cooled down keys");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -661,7 +1046,7 @@ pub async fn reactivate_cooled_down_keys(
         }
         Err(e) => {
             error!("Failed to reactivate cooled down keys: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -670,7 +1055,7 @@ pub async fn reactivate_cooled_down_keys(
 #[tauri::command]
 pub async fn get_keys_needing_attention(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, KeyPerformanceMetrics)>, String> {
+) -> Result<Vec<(String, KeyPerformanceMetrics)>, ErrorPayload> {
     info!("Getting keys that need attention");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -688,7 +1073,7 @@ pub async fn get_keys_needing_attention(
 #[tauri::command]
 pub async fn generate_rotation_report(
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Generating rotation report");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -699,7 +1084,7 @@ pub async fn generate_rotation_report(
         }
         Err(e) => {
             error!("Failed to generate rotation report: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -709,22 +1094,24 @@ pub async fn generate_rotation_report(
 pub async fn make_service_request(
     service: String,
     request: ServiceRequest,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ServiceResponse, String> {
+) -> Result<ServiceResponse, ErrorPayload> {
     info!("Making service request to: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let api_manager = service_manager.inner().api_manager.read().await;
-    match api_manager.make_service_request(service_provider, request).await {
+    match run_scoped(scope, api_manager.make_service_request(service_provider, request)).await {
         Ok(response) => {
             info!("Service request completed successfully for: {}", service);
             Ok(response)
         }
         Err(e) => {
             error!("Service request failed for {}: {}", service, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -734,11 +1121,11 @@ pub async fn make_service_request(
 pub async fn check_service_health(
     service: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ServiceHealth, String> {
+) -> Result<ServiceHealth, ErrorPayload> {
     info!("Checking health for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.check_service_health(service_provider).await {
@@ -748,7 +1135,7 @@ pub async fn check_service_health(
         }
         Err(e) => {
             error!("Health check failed for {}: {}", service, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -758,11 +1145,11 @@ pub async fn check_service_health(
 pub async fn get_service_metrics(
     service: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<ServiceMetrics>, String> {
+) -> Result<Option<ServiceMetrics>, ErrorPayload> {
     info!("Getting metrics for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     let metrics = api_manager.get_service_metrics(service_provider).await;
@@ -774,7 +1161,7 @@ pub async fn get_service_metrics(
 #[tauri::command]
 pub async fn get_all_service_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, ServiceMetrics)>, String> {
+) -> Result<Vec<(String, ServiceMetrics)>, ErrorPayload> {
     info!("Getting all service metrics");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -793,11 +1180,11 @@ pub async fn get_all_service_metrics(
 pub async fn get_service_config(
     service: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<ServiceConfig>, String> {
+) -> Result<Option<ServiceConfig>, ErrorPayload> {
     info!("Getting configuration for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     let config = api_manager.get_service_config(service_provider).await;
@@ -809,7 +1196,7 @@ pub async fn get_service_config(
 #[tauri::command]
 pub async fn get_all_service_configs(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, ServiceConfig)>, String> {
+) -> Result<Vec<(String, ServiceConfig)>, ErrorPayload> {
     info!("Getting all service configurations");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -829,11 +1216,11 @@ pub async fn update_service_config(
     service: String,
     config: ServiceConfig,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Updating configuration for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     match api_manager.update_service_config(service_provider, config).await {
@@ -843,7 +1230,141 @@ pub async fn update_service_config(
         }
         Err(e) => {
             error!("Failed to update configuration for {}: {}", service, e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the monthly quota configuration for a service
+#[tauri::command]
+pub async fn get_quota_config(
+    service: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<QuotaConfig, ErrorPayload> {
+    info!("Getting quota configuration for service: {}", service);
+
+    let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_quota_config(service_provider).await)
+}
+
+/// Update the monthly quota configuration for a service
+#[tauri::command]
+pub async fn update_quota_config(
+    service: String,
+    config: QuotaConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating quota configuration for service: {}", service);
+
+    let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.update_quota_config(service_provider, config).await {
+        Ok(()) => {
+            info!("Quota configuration updated successfully for service: {}", service);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to update quota configuration for {}: {}", service, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Replay recorded usage against a proposed rate limit configuration without applying it
+#[tauri::command]
+pub async fn simulate_rate_limit_config(
+    service: String,
+    config: RateLimitConfig,
+    lookback_days: u32,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<RateLimitSimulation, ErrorPayload> {
+    info!("Simulating rate limit configuration for service: {} over {} days", service, lookback_days);
+
+    let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.simulate_rate_limit_config(service_provider, config, lookback_days).await {
+        Ok(simulation) => {
+            info!(
+                "Simulation for {}: {} of {} requests throttled, {} blocked, {} alerts",
+                service, simulation.throttled_requests, simulation.total_requests,
+                simulation.blocked_requests, simulation.alerts_fired()
+            );
+            Ok(simulation)
+        }
+        Err(e) => {
+            error!("Failed to simulate rate limit configuration for {}: {}", service, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the redacted provider responses recorded for a workflow
+#[tauri::command]
+pub async fn get_provider_recording(
+    workflow_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<RecordedExchange>, ErrorPayload> {
+    let workflow_uuid = Uuid::parse_str(&workflow_id)
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    api_manager.get_provider_recording(workflow_uuid).await.map_err(|e| {
+        error!("Failed to get provider recording for workflow {}: {}", workflow_id, e);
+        e.into()
+    })
+}
+
+/// Delete the provider responses recorded for a workflow
+#[tauri::command]
+pub async fn delete_provider_recording(
+    workflow_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<bool, ErrorPayload> {
+    info!("Deleting provider recording for workflow: {}", workflow_id);
+
+    let workflow_uuid = Uuid::parse_str(&workflow_id)
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.delete_provider_recording(workflow_uuid).await {
+        Ok(deleted) => Ok(deleted),
+        Err(e) => {
+            error!("Failed to delete provider recording for workflow {}: {}", workflow_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the models each AI model role falls back through
+#[tauri::command]
+pub async fn get_model_routing_policy(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ModelRoutingPolicy, ErrorPayload> {
+    let api_manager = service_manager.inner().api_manager.read().await;
+    Ok(api_manager.get_model_routing_policy().await)
+}
+
+/// Update the models each AI model role falls back through
+#[tauri::command]
+pub async fn update_model_routing_policy(
+    policy: ModelRoutingPolicy,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating model routing policy for {} roles", policy.roles.len());
+
+    let api_manager = service_manager.inner().api_manager.read().await;
+    match api_manager.update_model_routing_policy(policy).await {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            error!("Failed to update model routing policy: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -853,11 +1374,11 @@ pub async fn update_service_config(
 pub async fn get_service_endpoints(
     service: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Getting endpoints for service: {}", service);
 
     let service_provider = crate::models::api_key::ServiceProvider::from_str(&service)
-        .ok_or_else(|| format!("Invalid service: {}", service))?;
+        .ok_or_else(|| AppError::validation("service", format!("Invalid service: {}", service)))?;
 
     let api_manager = service_manager.inner().api_manager.read().await;
     let endpoints = api_manager.get_service_endpoints(service_provider).await;
@@ -870,7 +1391,7 @@ pub async fn get_service_endpoints(
 #[tauri::command]
 pub async fn get_registered_services(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Getting list of registered services");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -888,7 +1409,7 @@ pub async fn get_registered_services(
 #[tauri::command]
 pub async fn generate_service_status_report(
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Generating service status report");
 
     let api_manager = service_manager.inner().api_manager.read().await;
@@ -899,7 +1420,7 @@ pub async fn generate_service_status_report(
         }
         Err(e) => {
             error!("Failed to generate service status report: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
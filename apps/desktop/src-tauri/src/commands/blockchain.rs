@@ -2,6 +2,7 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, debug, error};
 
+use crate::error::{AppError, ErrorPayload};
 use crate::services::ServiceManager;
 use crate::models::blockchain::*;
 
@@ -9,11 +10,11 @@ use crate::models::blockchain::*;
 pub async fn submit_peer_review(
     service_manager: State<'_, ServiceManager>,
     review: PeerReview,
-) -> Result<PeerReview, String> {
+) -> Result<PeerReview, ErrorPayload> {
     info!("API: Submitting peer review for workflow: {}", review.research_workflow_id);
     match service_manager.blockchain_service.submit_peer_review(review).await {
         Ok(submitted_review) => Ok(submitted_review),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -21,11 +22,11 @@ pub async fn submit_peer_review(
 pub async fn validate_research(
     service_manager: State<'_, ServiceManager>,
     validation: ResearchValidation,
-) -> Result<ResearchValidation, String> {
+) -> Result<ResearchValidation, ErrorPayload> {
     info!("API: Validating research workflow: {}", validation.research_workflow_id);
     match service_manager.blockchain_service.validate_research(validation).await {
         Ok(validated_research) => Ok(validated_research),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -34,12 +35,12 @@ pub async fn distribute_token_rewards(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
     reward: TokenReward,
-) -> Result<TokenReward, String> {
+) -> Result<TokenReward, ErrorPayload> {
     info!("API: Distributing reward to user: {}", user_id);
-    let uid = Uuid::parse_str(&user_id).map_err(|e| format!("Invalid user ID: {}", e))?;
+    let uid = Uuid::parse_str(&user_id).map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     match service_manager.blockchain_service.distribute_rewards(uid, reward).await {
         Ok(distributed_reward) => Ok(distributed_reward),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -47,11 +48,11 @@ pub async fn distribute_token_rewards(
 pub async fn create_blockchain_transaction(
     service_manager: State<'_, ServiceManager>,
     transaction: BlockchainTransaction,
-) -> Result<BlockchainTransaction, String> {
+) -> Result<BlockchainTransaction, ErrorPayload> {
     debug!("API: Creating blockchain transaction: {:?}", transaction.transaction_type);
     match service_manager.blockchain_service.create_transaction(transaction).await {
         Ok(created_transaction) => Ok(created_transaction),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -59,22 +60,22 @@ pub async fn create_blockchain_transaction(
 pub async fn get_audit_trail(
     service_manager: State<'_, ServiceManager>,
     resource: String,
-) -> Result<Vec<AuditTrailEntry>, String> {
+) -> Result<Vec<AuditTrailEntry>, ErrorPayload> {
     debug!("API: Getting audit trail for resource: {}", resource);
     match service_manager.blockchain_service.get_audit_trail(resource).await {
         Ok(audit_trail) => Ok(audit_trail),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
 #[tauri::command]
 pub async fn get_blockchain_network_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<NetworkStatistics, String> {
+) -> Result<NetworkStatistics, ErrorPayload> {
     debug!("API: Getting blockchain network statistics");
     match service_manager.blockchain_service.get_network_statistics().await {
         Ok(statistics) => Ok(statistics),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -82,9 +83,9 @@ pub async fn get_blockchain_network_statistics(
 pub async fn get_user_token_balance(
     service_manager: State<'_, ServiceManager>,
     user_id: String,
-) -> Result<UserTokenBalance, String> {
+) -> Result<UserTokenBalance, ErrorPayload> {
     debug!("API: Getting token balance for user: {}", user_id);
-    let uid = Uuid::parse_str(&user_id).map_err(|e| format!("Invalid user ID: {}", e))?;
+    let uid = Uuid::parse_str(&user_id).map_err(|e| AppError::validation("user_id", format!("Invalid user ID: {}", e)))?;
     
     // Mock response for now
     Ok(UserTokenBalance {
@@ -102,9 +103,9 @@ pub async fn get_user_token_balance(
 pub async fn get_research_validation_status(
     service_manager: State<'_, ServiceManager>,
     workflow_id: String,
-) -> Result<ValidationStatusInfo, String> {
+) -> Result<ValidationStatusInfo, ErrorPayload> {
     debug!("API: Getting validation status for workflow: {}", workflow_id);
-    let wid = Uuid::parse_str(&workflow_id).map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let wid = Uuid::parse_str(&workflow_id).map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
     
     // Mock response for now
     Ok(ValidationStatusInfo {
@@ -1,6 +1,7 @@
 use tauri::State;
 use tracing::{info, error};
 
+use crate::error::ErrorPayload;
 use crate::services::ServiceManager;
 use crate::services::bmad_integration::{
     BMadResearchRequest, BMadResearchResponse, DocumentationModeRequest, 
@@ -12,7 +13,7 @@ use crate::services::bmad_integration::{
 pub async fn execute_research_enhanced_documentation_mode(
     request: DocumentationModeRequest,
     service_manager: State<'_, ServiceManager>,
-) -> Result<DocumentationModeResponse, String> {
+) -> Result<DocumentationModeResponse, ErrorPayload> {
     info!("API: Executing research-enhanced documentation mode");
     
     let bmad_integration = service_manager.bmad_integration.read().await;
@@ -23,7 +24,7 @@ pub async fn execute_research_enhanced_documentation_mode(
         }
         Err(e) => {
             error!("Research-enhanced documentation mode failed: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -33,7 +34,7 @@ pub async fn execute_research_enhanced_documentation_mode(
 pub async fn conduct_agent_research(
     request: BMadResearchRequest,
     service_manager: State<'_, ServiceManager>,
-) -> Result<BMadResearchResponse, String> {
+) -> Result<BMadResearchResponse, ErrorPayload> {
     info!("API: Conducting agent research for agent: {}", request.agent_id);
     
     let bmad_integration = service_manager.bmad_integration.read().await;
@@ -44,7 +45,7 @@ pub async fn conduct_agent_research(
         }
         Err(e) => {
             error!("Agent research failed: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -53,7 +54,7 @@ pub async fn conduct_agent_research(
 #[tauri::command]
 pub async fn get_integration_health_status(
     service_manager: State<'_, ServiceManager>,
-) -> Result<IntegrationHealthStatus, String> {
+) -> Result<IntegrationHealthStatus, ErrorPayload> {
     info!("API: Getting BMAD integration health status");
     
     let bmad_integration = service_manager.bmad_integration.read().await;
@@ -64,14 +65,14 @@ pub async fn get_integration_health_status(
         }
         Err(e) => {
             error!("Integration health check failed: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
 /// Get research methodologies
 #[tauri::command]
-pub async fn get_research_methodologies() -> Result<Vec<String>, String> {
+pub async fn get_research_methodologies() -> Result<Vec<String>, ErrorPayload> {
     info!("API: Getting available research methodologies");
     
     Ok(vec![
@@ -84,7 +85,7 @@ pub async fn get_research_methodologies() -> Result<Vec<String>, String> {
 
 /// Get research types
 #[tauri::command]
-pub async fn get_research_types() -> Result<Vec<String>, String> {
+pub async fn get_research_types() -> Result<Vec<String>, ErrorPayload> {
     info!("API: Getting available research types");
     
     Ok(vec![
@@ -101,7 +102,7 @@ pub async fn get_research_types() -> Result<Vec<String>, String> {
 
 /// Get research depth levels
 #[tauri::command]
-pub async fn get_research_depth_levels() -> Result<Vec<String>, String> {
+pub async fn get_research_depth_levels() -> Result<Vec<String>, ErrorPayload> {
     info!("API: Getting available research depth levels");
     
     Ok(vec![
@@ -116,12 +117,11 @@ pub async fn get_research_depth_levels() -> Result<Vec<String>, String> {
 #[tauri::command]
 pub async fn get_integration_config(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("API: Getting BMAD integration configuration");
     
     let bmad_integration = service_manager.bmad_integration.read().await;
-    let health_status = bmad_integration.health_check().await
-        .map_err(|e| e.to_string())?;
+    let health_status = bmad_integration.health_check().await?;
     
     Ok(serde_json::json!({
         "version": "2.1.0",
@@ -160,12 +160,11 @@ pub async fn get_integration_config(
 #[tauri::command]
 pub async fn test_bmad_integration(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("API: Testing BMAD integration connectivity");
     
     let bmad_integration = service_manager.bmad_integration.read().await;
-    let health_status = bmad_integration.health_check().await
-        .map_err(|e| e.to_string())?;
+    let health_status = bmad_integration.health_check().await?;
     
     let test_successful = health_status.overall_status == "healthy";
     
@@ -184,7 +183,7 @@ pub async fn test_bmad_integration(
 
 /// Get BMAD agent information
 #[tauri::command]
-pub async fn get_bmad_agents() -> Result<Vec<serde_json::Value>, String> {
+pub async fn get_bmad_agents() -> Result<Vec<serde_json::Value>, ErrorPayload> {
     info!("API: Getting BMAD agent information");
     
     Ok(vec![
@@ -247,12 +246,11 @@ pub async fn get_bmad_agents() -> Result<Vec<serde_json::Value>, String> {
 #[tauri::command]
 pub async fn get_integration_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("API: Getting BMAD integration statistics");
     
     let bmad_integration = service_manager.bmad_integration.read().await;
-    let health_status = bmad_integration.health_check().await
-        .map_err(|e| e.to_string())?;
+    let health_status = bmad_integration.health_check().await?;
     
     Ok(serde_json::json!({
         "service_status": health_status.overall_status,
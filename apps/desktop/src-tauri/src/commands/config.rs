@@ -1,19 +1,21 @@
 use tauri::State;
 use tracing::{info, error};
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload, ResearchError};
 use crate::models::SystemConfiguration;
 use crate::services::ServiceManager;
+use crate::services::data_persistence::{BackupManifest, DatabaseKeySource, MasterKeyRotationReport, RedactionConfig, RedactionReport};
 
 /// Get system configuration
 #[tauri::command]
 pub async fn get_configuration(
     service_manager: State<'_, ServiceManager>,
-) -> Result<SystemConfiguration, String> {
+) -> Result<SystemConfiguration, ErrorPayload> {
     info!("Getting system configuration");
     
     // TODO: Implement actual configuration retrieval
-    Err("Not implemented".to_string())
+    Err(AppError::internal("Not implemented").into())
 }
 
 /// Update system configuration
@@ -21,20 +23,188 @@ pub async fn get_configuration(
 pub async fn update_configuration(
     config: SystemConfiguration,
     service_manager: State<'_, ServiceManager>,
-) -> Result<SystemConfiguration, String> {
+) -> Result<SystemConfiguration, ErrorPayload> {
     info!("Updating system configuration");
     
     // TODO: Implement actual configuration update
-    Err("Not implemented".to_string())
+    Err(AppError::internal("Not implemented").into())
 }
 
 /// Reset configuration to defaults
 #[tauri::command]
 pub async fn reset_configuration(
     service_manager: State<'_, ServiceManager>,
-) -> Result<SystemConfiguration, String> {
+) -> Result<SystemConfiguration, ErrorPayload> {
     info!("Resetting system configuration to defaults");
     
     // TODO: Implement actual configuration reset
-    Err("Not implemented".to_string())
+    Err(AppError::internal("Not implemented").into())
+}
+
+/// Re-encrypt the local database under a new key
+#[tauri::command]
+pub async fn rotate_database_key(
+    old_key: DatabaseKeySource,
+    new_key: DatabaseKeySource,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Rotating database encryption key");
+
+    let mut data_persistence = service_manager.inner().data_persistence.write().await;
+    match data_persistence.rotate_database_key(old_key, new_key).await {
+        Ok(()) => {
+            info!("Database encryption key rotated successfully");
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to rotate database encryption key: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Switch to a new master key and re-encrypt all stored API keys under it
+#[tauri::command]
+pub async fn rotate_master_key(
+    new_key: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<MasterKeyRotationReport, ErrorPayload> {
+    info!("Rotating master encryption key");
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    match data_persistence.rotate_master_key(&new_key).await {
+        Ok(report) => {
+            info!("Master key rotated to {}, {} API keys re-encrypted", report.key_version, report.reencrypted);
+            Ok(report)
+        }
+        Err(e) => {
+            error!("Failed to rotate master encryption key: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Create an encrypted, verified backup of the database and key vault
+#[tauri::command]
+pub async fn create_backup(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BackupManifest, ErrorPayload> {
+    info!("Creating database backup");
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    match data_persistence.create_backup().await {
+        Ok(manifest) => {
+            info!("Backup created: {}", manifest.id);
+            Ok(manifest)
+        }
+        Err(e) => {
+            error!("Failed to create backup: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// List available backups, newest first
+#[tauri::command]
+pub async fn list_backups(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<BackupManifest>, ErrorPayload> {
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    data_persistence.list_backups().map_err(|e| {
+        error!("Failed to list backups: {}", e);
+        e.into()
+    })
+}
+
+/// Verify a backup's checksums and test-open its snapshots
+#[tauri::command]
+pub async fn verify_backup(
+    backup_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BackupManifest, ErrorPayload> {
+    info!("Verifying backup: {}", backup_id);
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    match data_persistence.verify_backup(&backup_id).await {
+        Ok(manifest) => Ok(manifest),
+        Err(e) => {
+            error!("Backup {} failed verification: {}", backup_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Restore a verified backup over the live database and key vault
+#[tauri::command]
+pub async fn restore_backup(
+    backup_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<BackupManifest, ErrorPayload> {
+    info!("Restoring backup: {}", backup_id);
+
+    let data_persistence = service_manager.inner().data_persistence.write().await;
+    match data_persistence.restore_backup(&backup_id).await {
+        Ok(manifest) => {
+            info!("Backup restored: {}", backup_id);
+            Ok(manifest)
+        }
+        Err(e) => {
+            error!("Failed to restore backup {}: {}", backup_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the categories of personal data scrubbed from results before storage and export
+#[tauri::command]
+pub async fn get_redaction_config(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<RedactionConfig, ErrorPayload> {
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    Ok(data_persistence.get_redaction_config())
+}
+
+/// Update the categories of personal data scrubbed from results
+#[tauri::command]
+pub async fn update_redaction_config(
+    config: RedactionConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Updating result redaction configuration");
+
+    let mut data_persistence = service_manager.inner().data_persistence.write().await;
+    data_persistence.set_redaction_config(config).await.map_err(|e| {
+        error!("Failed to update redaction config: {}", e);
+        e.into()
+    })
+}
+
+/// Dry run: report what redaction would replace in a workflow's results without changing them
+#[tauri::command]
+pub async fn preview_result_redaction(
+    workflow_id: String,
+    config: Option<RedactionConfig>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<RedactionReport, ErrorPayload> {
+    let workflow_uuid = Uuid::parse_str(&workflow_id)
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
+
+    let workflow = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        match research_engine.get_workflow(workflow_uuid).await {
+            Ok(Some(workflow)) => workflow,
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => {
+                error!("Failed to get workflow {}: {}", workflow_id, e);
+                return Err(e.into());
+            }
+        }
+    };
+    let results = workflow.results
+        .ok_or_else(|| AppError::validation("workflow_id", format!("Workflow {} has no results", workflow_id)))?;
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    let report = data_persistence.preview_redaction(&results, config.as_ref());
+    info!("Redaction dry run for workflow {}: {} matches", workflow_id, report.total_matches);
+    Ok(report)
 }
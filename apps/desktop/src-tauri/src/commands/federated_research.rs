@@ -2,7 +2,7 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, debug, error};
 
-use crate::error::{AppResult, ResearchError};
+use crate::error::{AppError, ErrorPayload, ResearchError};
 use crate::services::ServiceManager;
 use crate::models::federated_research::*;
 
@@ -11,7 +11,7 @@ use crate::models::federated_research::*;
 pub async fn register_federated_organization(
     service_manager: State<'_, ServiceManager>,
     request: CreateFederatedOrganizationRequest,
-) -> Result<FederatedOrganization, String> {
+) -> Result<FederatedOrganization, ErrorPayload> {
     info!("API: Registering federated organization: {}", request.name);
     
     match service_manager.federated_research_service.register_organization(request).await {
@@ -21,7 +21,7 @@ pub async fn register_federated_organization(
         }
         Err(e) => {
             error!("Failed to register organization: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -32,11 +32,11 @@ pub async fn create_research_partnership(
     service_manager: State<'_, ServiceManager>,
     organization_id: String,
     request: CreateResearchPartnershipRequest,
-) -> Result<ResearchPartnership, String> {
+) -> Result<ResearchPartnership, ErrorPayload> {
     info!("API: Creating research partnership for organization: {}", organization_id);
     
     let org_id = Uuid::parse_str(&organization_id)
-        .map_err(|e| format!("Invalid organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("organization_id", format!("Invalid organization ID: {}", e)))?;
     
     match service_manager.federated_research_service.create_partnership(org_id, request).await {
         Ok(partnership) => {
@@ -45,7 +45,7 @@ pub async fn create_research_partnership(
         }
         Err(e) => {
             error!("Failed to create partnership: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -55,7 +55,7 @@ pub async fn create_research_partnership(
 pub async fn share_research_session(
     service_manager: State<'_, ServiceManager>,
     request: ShareResearchSessionRequest,
-) -> Result<SharedResearchSession, String> {
+) -> Result<SharedResearchSession, ErrorPayload> {
     info!("API: Sharing research session: {}", request.workflow_id);
     
     match service_manager.federated_research_service.share_research_session(request).await {
@@ -65,7 +65,7 @@ pub async fn share_research_session(
         }
         Err(e) => {
             error!("Failed to share research session: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -75,7 +75,7 @@ pub async fn share_research_session(
 pub async fn execute_federated_query(
     service_manager: State<'_, ServiceManager>,
     query: FederatedResearchQuery,
-) -> Result<Vec<FederatedResearchResponse>, String> {
+) -> Result<Vec<FederatedResearchResponse>, ErrorPayload> {
     info!("API: Executing federated research query: {}", query.id);
     
     match service_manager.federated_research_service.execute_federated_query(query).await {
@@ -85,7 +85,7 @@ pub async fn execute_federated_query(
         }
         Err(e) => {
             error!("Failed to execute federated query: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -95,17 +95,17 @@ pub async fn execute_federated_query(
 pub async fn get_organization_metrics(
     service_manager: State<'_, ServiceManager>,
     organization_id: String,
-) -> Result<FederatedResearchMetrics, String> {
+) -> Result<FederatedResearchMetrics, ErrorPayload> {
     debug!("API: Getting metrics for organization: {}", organization_id);
     
     let org_id = Uuid::parse_str(&organization_id)
-        .map_err(|e| format!("Invalid organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("organization_id", format!("Invalid organization ID: {}", e)))?;
     
     match service_manager.federated_research_service.get_organization_metrics(org_id).await {
         Ok(metrics) => Ok(metrics),
         Err(e) => {
             error!("Failed to get organization metrics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -116,11 +116,11 @@ pub async fn update_privacy_controls(
     service_manager: State<'_, ServiceManager>,
     organization_id: String,
     controls: PrivacyControls,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("API: Updating privacy controls for organization: {}", organization_id);
     
     let org_id = Uuid::parse_str(&organization_id)
-        .map_err(|e| format!("Invalid organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("organization_id", format!("Invalid organization ID: {}", e)))?;
     
     match service_manager.federated_research_service.update_privacy_controls(org_id, controls).await {
         Ok(()) => {
@@ -129,7 +129,7 @@ pub async fn update_privacy_controls(
         }
         Err(e) => {
             error!("Failed to update privacy controls: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -139,17 +139,17 @@ pub async fn update_privacy_controls(
 pub async fn get_active_partnerships(
     service_manager: State<'_, ServiceManager>,
     organization_id: String,
-) -> Result<Vec<ResearchPartnership>, String> {
+) -> Result<Vec<ResearchPartnership>, ErrorPayload> {
     debug!("API: Getting active partnerships for organization: {}", organization_id);
     
     let org_id = Uuid::parse_str(&organization_id)
-        .map_err(|e| format!("Invalid organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("organization_id", format!("Invalid organization ID: {}", e)))?;
     
     match service_manager.federated_research_service.get_active_partnerships(org_id).await {
         Ok(partnerships) => Ok(partnerships),
         Err(e) => {
             error!("Failed to get active partnerships: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -159,14 +159,14 @@ pub async fn get_active_partnerships(
 pub async fn validate_federated_auth_token(
     service_manager: State<'_, ServiceManager>,
     token: String,
-) -> Result<FederatedAuthToken, String> {
+) -> Result<FederatedAuthToken, ErrorPayload> {
     debug!("API: Validating federated authentication token");
     
     match service_manager.federated_research_service.validate_auth_token(&token).await {
         Ok(token_info) => Ok(token_info),
         Err(e) => {
             error!("Failed to validate auth token: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -177,11 +177,11 @@ pub async fn create_cross_org_collaboration(
     service_manager: State<'_, ServiceManager>,
     lead_organization_id: String,
     collaboration_request: CrossOrgCollaboration,
-) -> Result<CrossOrgCollaboration, String> {
+) -> Result<CrossOrgCollaboration, ErrorPayload> {
     info!("API: Creating cross-organization collaboration");
     
     let lead_org_id = Uuid::parse_str(&lead_organization_id)
-        .map_err(|e| format!("Invalid lead organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("lead_organization_id", format!("Invalid lead organization ID: {}", e)))?;
     
     match service_manager.federated_research_service
         .create_collaboration(lead_org_id, collaboration_request).await {
@@ -191,7 +191,7 @@ pub async fn create_cross_org_collaboration(
         }
         Err(e) => {
             error!("Failed to create collaboration: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -200,7 +200,7 @@ pub async fn create_cross_org_collaboration(
 #[tauri::command]
 pub async fn get_federated_research_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<FederatedResearchStatistics, String> {
+) -> Result<FederatedResearchStatistics, ErrorPayload> {
     debug!("API: Getting federated research statistics");
     
     // This would aggregate statistics across all organizations
@@ -236,11 +236,11 @@ pub async fn test_federated_connection(
     service_manager: State<'_, ServiceManager>,
     organization_id: String,
     target_endpoint: String,
-) -> Result<FederatedConnectionTest, String> {
+) -> Result<FederatedConnectionTest, ErrorPayload> {
     info!("API: Testing federated connection to: {}", target_endpoint);
     
     let org_id = Uuid::parse_str(&organization_id)
-        .map_err(|e| format!("Invalid organization ID: {}", e))?;
+        .map_err(|e| AppError::validation("organization_id", format!("Invalid organization ID: {}", e)))?;
     
     // Simulate connection test
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -2,6 +2,7 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, debug, error};
 
+use crate::error::{AppError, ErrorPayload};
 use crate::services::ServiceManager;
 use crate::models::knowledge_graph::*;
 
@@ -9,11 +10,11 @@ use crate::models::knowledge_graph::*;
 pub async fn create_knowledge_node(
     service_manager: State<'_, ServiceManager>,
     node: KnowledgeNode,
-) -> Result<KnowledgeNode, String> {
+) -> Result<KnowledgeNode, ErrorPayload> {
     info!("API: Creating knowledge node: {}", node.name);
     match service_manager.knowledge_graph_service.create_knowledge_node(node).await {
         Ok(created_node) => Ok(created_node),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -21,11 +22,11 @@ pub async fn create_knowledge_node(
 pub async fn create_knowledge_relationship(
     service_manager: State<'_, ServiceManager>,
     relationship: KnowledgeRelationship,
-) -> Result<KnowledgeRelationship, String> {
+) -> Result<KnowledgeRelationship, ErrorPayload> {
     debug!("API: Creating relationship: {:?}", relationship.relationship_type);
     match service_manager.knowledge_graph_service.create_relationship(relationship).await {
         Ok(created_relationship) => Ok(created_relationship),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -33,11 +34,11 @@ pub async fn create_knowledge_relationship(
 pub async fn register_data_source(
     service_manager: State<'_, ServiceManager>,
     source: DataSource,
-) -> Result<DataSource, String> {
+) -> Result<DataSource, ErrorPayload> {
     info!("API: Registering data source: {}", source.source_name);
     match service_manager.knowledge_graph_service.register_data_source(source).await {
         Ok(registered_source) => Ok(registered_source),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -45,11 +46,11 @@ pub async fn register_data_source(
 pub async fn traverse_knowledge_graph(
     service_manager: State<'_, ServiceManager>,
     request: GraphTraversalRequest,
-) -> Result<GraphTraversalResult, String> {
+) -> Result<GraphTraversalResult, ErrorPayload> {
     debug!("API: Traversing graph from node: {}", request.start_node_id);
     match service_manager.knowledge_graph_service.traverse_graph(request).await {
         Ok(result) => Ok(result),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -57,11 +58,11 @@ pub async fn traverse_knowledge_graph(
 pub async fn create_graph_visualization(
     service_manager: State<'_, ServiceManager>,
     visualization: GraphVisualization,
-) -> Result<GraphVisualization, String> {
+) -> Result<GraphVisualization, ErrorPayload> {
     info!("API: Creating graph visualization: {}", visualization.name);
     match service_manager.knowledge_graph_service.create_visualization(visualization).await {
         Ok(created_visualization) => Ok(created_visualization),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -69,23 +70,46 @@ pub async fn create_graph_visualization(
 pub async fn extract_knowledge_from_source(
     service_manager: State<'_, ServiceManager>,
     source_id: String,
-) -> Result<Vec<KnowledgeNode>, String> {
+) -> Result<Vec<KnowledgeNode>, ErrorPayload> {
     info!("API: Extracting knowledge from source: {}", source_id);
-    let sid = Uuid::parse_str(&source_id).map_err(|e| format!("Invalid source ID: {}", e))?;
+    let sid = Uuid::parse_str(&source_id).map_err(|e| AppError::validation("source_id", format!("Invalid source ID: {}", e)))?;
     match service_manager.knowledge_graph_service.extract_knowledge_from_source(sid).await {
         Ok(nodes) => Ok(nodes),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
+    }
+}
+
+/// Extract the structure of a document, e.g. an academic PDF, and link it and the
+/// works it cites into the graph
+#[tauri::command]
+pub async fn extract_document_structure(
+    service_manager: State<'_, ServiceManager>,
+    content_base64: String,
+    format: DataFormat,
+    source_id: Option<String>,
+) -> Result<ExtractedDocument, ErrorPayload> {
+    use base64::Engine;
+
+    info!("API: Extracting document structure: {:?}", source_id);
+    let content = base64::engine::general_purpose::STANDARD.decode(content_base64.trim())
+        .map_err(|e| AppError::validation("content_base64", format!("Invalid document content: {}", e)))?;
+    match service_manager.knowledge_graph_service.extract_document(source_id, format, content).await {
+        Ok(document) => Ok(document),
+        Err(e) => {
+            error!("Failed to extract document structure: {}", e);
+            Err(e.into())
+        }
     }
 }
 
 #[tauri::command]
 pub async fn get_knowledge_graph_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<KnowledgeGraphStatistics, String> {
+) -> Result<KnowledgeGraphStatistics, ErrorPayload> {
     debug!("API: Getting knowledge graph statistics");
     match service_manager.knowledge_graph_service.get_graph_statistics().await {
         Ok(statistics) => Ok(statistics),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -94,11 +118,11 @@ pub async fn search_knowledge_nodes(
     service_manager: State<'_, ServiceManager>,
     query: String,
     node_types: Option<Vec<NodeType>>,
-) -> Result<Vec<KnowledgeNode>, String> {
+) -> Result<Vec<KnowledgeNode>, ErrorPayload> {
     debug!("API: Searching nodes with query: {}", query);
     match service_manager.knowledge_graph_service.search_nodes(query, node_types).await {
         Ok(nodes) => Ok(nodes),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -107,12 +131,12 @@ pub async fn get_node_neighbors(
     service_manager: State<'_, ServiceManager>,
     node_id: String,
     max_depth: u32,
-) -> Result<Vec<KnowledgeNode>, String> {
+) -> Result<Vec<KnowledgeNode>, ErrorPayload> {
     debug!("API: Getting neighbors for node: {} with depth: {}", node_id, max_depth);
-    let nid = Uuid::parse_str(&node_id).map_err(|e| format!("Invalid node ID: {}", e))?;
+    let nid = Uuid::parse_str(&node_id).map_err(|e| AppError::validation("node_id", format!("Invalid node ID: {}", e)))?;
     match service_manager.knowledge_graph_service.get_node_neighbors(nid, max_depth).await {
         Ok(neighbors) => Ok(neighbors),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -120,7 +144,7 @@ pub async fn get_node_neighbors(
 pub async fn get_knowledge_insights(
     service_manager: State<'_, ServiceManager>,
     topic: String,
-) -> Result<KnowledgeInsights, String> {
+) -> Result<KnowledgeInsights, ErrorPayload> {
     debug!("API: Getting knowledge insights for topic: {}", topic);
     
     // Mock response for now - would analyze the knowledge graph for insights
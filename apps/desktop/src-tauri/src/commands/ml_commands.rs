@@ -3,7 +3,9 @@ use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use tracing::{info, debug, error};
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload};
+use crate::models::workflow_rating::QueryDomain;
+use crate::services::ServiceManager;
 use crate::services::ml_engine::{
     inference_engine::{InferenceEngine, InferenceRequest, InferenceOptions, ModelType},
     model_training::{ModelTrainer, TrainingConfig, TrainingStatus},
@@ -19,12 +21,12 @@ pub async fn start_model_training(
     training_config: TrainingConfigRequest,
     user_id: String,
     trainer: State<'_, ModelTrainer>
-) -> AppResult<TrainingJobResponse> {
+) -> Result<TrainingJobResponse, ErrorPayload> {
     info!("Starting model training: {} ({})", model_name, model_type);
 
     let job_id = Uuid::new_v4();
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
 
     let model_type_enum = match model_type.as_str() {
         "research_pattern_predictor" => ModelType::ResearchPatternPredictor,
@@ -32,7 +34,7 @@ pub async fn start_model_training(
         "performance_optimizer" => ModelType::PerformanceOptimizer,
         "recommendation_engine" => ModelType::RecommendationEngine,
         "anomaly_detector" => ModelType::AnomalyDetector,
-        _ => return Err(crate::error::ResearchError::invalid_request(
+        _ => return Err(AppError::validation("model_type",
             format!("Unsupported model type: {}", model_type)
         ).into()),
     };
@@ -69,11 +71,11 @@ pub async fn start_model_training(
 pub async fn get_training_job_status(
     job_id: String,
     trainer: State<'_, ModelTrainer>
-) -> AppResult<Option<TrainingJobResponse>> {
+) -> Result<Option<TrainingJobResponse>, ErrorPayload> {
     debug!("Getting training job status: {}", job_id);
 
     let job_uuid = Uuid::parse_str(&job_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid job ID".to_string()))?;
+        .map_err(|_| AppError::validation("job_id", "Invalid job ID"))?;
 
     if let Some(job) = trainer.get_job_status(job_uuid).await? {
         Ok(Some(TrainingJobResponse {
@@ -96,7 +98,7 @@ pub async fn perform_ml_inference(
     input_data: serde_json::Value,
     options: Option<InferenceOptionsRequest>,
     inference_engine: State<'_, InferenceEngine>
-) -> AppResult<InferenceResponse> {
+) -> Result<InferenceResponse, ErrorPayload> {
     info!("Performing ML inference with model: {}", model_name);
 
     let request = InferenceRequest {
@@ -129,7 +131,7 @@ pub async fn analyze_research_patterns(
     data_points: Vec<serde_json::Value>,
     analysis_type: String,
     pattern_analyzer: State<'_, PatternAnalyzer>
-) -> AppResult<PatternAnalysisResponse> {
+) -> Result<PatternAnalysisResponse, ErrorPayload> {
     info!("Analyzing research patterns: {} data points", data_points.len());
 
     let analysis_input = AnalysisInput {
@@ -166,15 +168,27 @@ pub async fn analyze_research_patterns(
 pub async fn generate_recommendations(
     user_id: String,
     context: RecommendationContextRequest,
-    recommendation_engine: State<'_, RecommendationEngine>
-) -> AppResult<RecommendationResponse> {
+    recommendation_engine: State<'_, RecommendationEngine>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<RecommendationResponse, ErrorPayload> {
     info!("Generating recommendations for user: {}", user_id);
 
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
+
+    // Only the user's own ratings, in the domain of the query they are asking about
+    let methodology_ratings = match &context.query {
+        Some(query) => {
+            let data_persistence = service_manager.inner().data_persistence.read().await;
+            data_persistence.get_workflow_ratings(&user_uuid.to_string(), QueryDomain::classify(query)).await?
+        }
+        None => Vec::new(),
+    };
 
     let rec_context = RecommendationContext {
         current_methodology: context.current_methodology,
+        query: context.query,
+        methodology_ratings,
         query_complexity: context.query_complexity,
         recent_performance: context.recent_performance,
         budget_constraints: context.budget_constraints,
@@ -213,7 +227,7 @@ pub async fn get_ml_metrics(
     trainer: State<'_, ModelTrainer>,
     pattern_analyzer: State<'_, PatternAnalyzer>,
     recommendation_engine: State<'_, RecommendationEngine>
-) -> AppResult<MLMetricsResponse> {
+) -> Result<MLMetricsResponse, ErrorPayload> {
     debug!("Getting ML engine metrics");
 
     let training_metrics = trainer.get_training_metrics().await?;
@@ -306,6 +320,8 @@ pub struct PatternInsightResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RecommendationContextRequest {
     pub current_methodology: Option<String>,
+    #[serde(default)]
+    pub query: Option<String>,
     pub query_complexity: Option<f64>,
     pub recent_performance: Option<f64>,
     pub budget_constraints: Option<f64>,
@@ -3,7 +3,7 @@ use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use tracing::{info, debug, error};
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload};
 use crate::services::mobile_platform::{
     MobilePlatformService,
     DeviceRegistration,
@@ -18,14 +18,14 @@ use crate::services::mobile_platform::{
 pub async fn register_mobile_device(
     device_info: DeviceRegistrationRequest,
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<DeviceRegistrationResponse> {
+) -> Result<DeviceRegistrationResponse, ErrorPayload> {
     info!("Registering mobile device: {}", device_info.device_name);
 
     let platform = match device_info.platform.as_str() {
         "ios" => MobilePlatform::iOS,
         "android" => MobilePlatform::Android,
         "web" => MobilePlatform::Web,
-        _ => return Err(crate::error::ResearchError::invalid_request(
+        _ => return Err(AppError::validation("platform",
             format!("Unsupported platform: {}", device_info.platform)
         ).into()),
     };
@@ -60,14 +60,14 @@ pub async fn start_mobile_session(
     device_id: String,
     user_id: String,
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<MobileSessionResponse> {
+) -> Result<MobileSessionResponse, ErrorPayload> {
     info!("Starting mobile session for device: {}", device_id);
 
     let device_uuid = Uuid::parse_str(&device_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid device ID".to_string()))?;
+        .map_err(|_| AppError::validation("device_id", "Invalid device ID"))?;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
 
     let session = mobile_service.start_mobile_session(device_uuid, user_uuid).await?;
 
@@ -86,11 +86,11 @@ pub async fn start_mobile_session(
 pub async fn execute_mobile_research(
     request: MobileResearchRequestData,
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<MobileResearchResponseData> {
+) -> Result<MobileResearchResponseData, ErrorPayload> {
     info!("Executing mobile research: {}", request.query);
 
     let session_uuid = Uuid::parse_str(&request.session_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid session ID".to_string()))?;
+        .map_err(|_| AppError::validation("session_id", "Invalid session ID"))?;
 
     let mobile_request = MobileResearchRequest {
         request_id: Uuid::new_v4(),
@@ -120,11 +120,11 @@ pub async fn execute_mobile_research(
 pub async fn sync_offline_data(
     device_id: String,
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<SyncResultResponse> {
+) -> Result<SyncResultResponse, ErrorPayload> {
     info!("Syncing offline data for device: {}", device_id);
 
     let device_uuid = Uuid::parse_str(&device_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid device ID".to_string()))?;
+        .map_err(|_| AppError::validation("device_id", "Invalid device ID"))?;
 
     let sync_result = mobile_service.sync_offline_data(device_uuid).await?;
 
@@ -143,14 +143,14 @@ pub async fn get_mobile_dashboard(
     device_id: String,
     user_id: String,
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<MobileDashboardResponse> {
+) -> Result<MobileDashboardResponse, ErrorPayload> {
     debug!("Getting mobile dashboard for device: {}", device_id);
 
     let device_uuid = Uuid::parse_str(&device_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid device ID".to_string()))?;
+        .map_err(|_| AppError::validation("device_id", "Invalid device ID"))?;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
 
     let dashboard = mobile_service.get_mobile_dashboard(device_uuid, user_uuid).await?;
 
@@ -210,7 +210,7 @@ pub async fn get_mobile_dashboard(
 #[tauri::command]
 pub async fn get_mobile_platform_metrics(
     mobile_service: State<'_, MobilePlatformService>
-) -> AppResult<MobilePlatformMetricsResponse> {
+) -> Result<MobilePlatformMetricsResponse, ErrorPayload> {
     debug!("Getting mobile platform metrics");
 
     let metrics = mobile_service.get_platform_metrics().await?;
@@ -1,6 +1,8 @@
 pub mod api_management;
 pub mod research_workflow;
 pub mod template_management;
+pub mod research_schedule;
+pub mod prompt_templates;
 pub mod research;
 pub mod config;
 pub mod monitoring;
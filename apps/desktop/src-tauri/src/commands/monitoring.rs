@@ -4,15 +4,15 @@ use sysinfo::{System, SystemExt, CpuExt, DiskExt, NetworkExt, ProcessExt};
 use chrono::Utc;
 use std::collections::HashMap;
 
-use crate::error::AppResult;
-use crate::models::{MonitoringMetrics, ApiUsageMetrics, SystemPerformanceMetrics, NetworkIoMetrics, ResearchStatistics, ErrorCounts};
+use crate::error::{AppResult, ErrorPayload};
+use crate::models::{Page, PageRequest, MonitoringMetrics, ApiUsageMetrics, SystemPerformanceMetrics, NetworkIoMetrics, ResearchStatistics, ErrorCounts};
 use crate::services::{ServiceManager, ServiceHealthStatus};
 
 /// Get system metrics
 #[tauri::command]
 pub async fn get_system_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<MonitoringMetrics, String> {
+) -> Result<MonitoringMetrics, ErrorPayload> {
     info!("Getting system metrics");
 
     match collect_system_metrics(&service_manager).await {
@@ -22,7 +22,7 @@ pub async fn get_system_metrics(
         }
         Err(e) => {
             error!("Failed to collect system metrics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -31,7 +31,7 @@ pub async fn get_system_metrics(
 #[tauri::command]
 pub async fn get_api_usage_stats(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting API usage statistics");
 
     match collect_api_usage_stats(&service_manager).await {
@@ -41,7 +41,7 @@ pub async fn get_api_usage_stats(
         }
         Err(e) => {
             error!("Failed to collect API usage statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -50,14 +50,14 @@ pub async fn get_api_usage_stats(
 #[tauri::command]
 pub async fn get_service_health(
     service_manager: State<'_, ServiceManager>,
-) -> Result<ServiceHealthStatus, String> {
+) -> Result<ServiceHealthStatus, ErrorPayload> {
     info!("Getting service health status");
     
     match service_manager.health_check().await {
         Ok(status) => Ok(status),
         Err(e) => {
             error!("Failed to get service health: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -67,7 +67,7 @@ pub async fn get_service_health(
 pub async fn get_audit_logs(
     limit: Option<u32>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<crate::services::security::AuditEvent>, String> {
+) -> Result<Vec<crate::services::security::AuditEvent>, ErrorPayload> {
     info!("Getting audit logs with limit: {:?}", limit);
 
     let security = service_manager.security.read().await;
@@ -75,7 +75,54 @@ pub async fn get_audit_logs(
         Ok(logs) => Ok(logs),
         Err(e) => {
             error!("Failed to get audit logs: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Get a page of audit logs, newest first
+#[tauri::command]
+pub async fn list_audit_logs(
+    page: PageRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Page<crate::services::security::AuditEvent>, ErrorPayload> {
+    info!("Listing audit logs (limit {})", page.limit());
+
+    let security = service_manager.security.read().await;
+    match security.get_audit_logs_page(&page).await {
+        Ok(logs) => Ok(logs),
+        Err(e) => {
+            error!("Failed to list audit logs: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get every circuit breaker with its state, failure count and time to the next probe
+#[tauri::command]
+pub async fn get_circuit_breaker_states(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<crate::services::api_manager::CircuitBreakerStatus>, ErrorPayload> {
+    debug!("Getting circuit breaker states");
+
+    let api_manager = service_manager.api_manager.read().await;
+    Ok(api_manager.get_circuit_breaker_states().await)
+}
+
+/// Close a circuit breaker by hand
+#[tauri::command]
+pub async fn reset_circuit_breaker(
+    id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<crate::services::api_manager::CircuitBreakerStatus, ErrorPayload> {
+    info!("Resetting circuit breaker: {}", id);
+
+    let api_manager = service_manager.api_manager.read().await;
+    match api_manager.reset_circuit_breaker(&id).await {
+        Ok(status) => Ok(status),
+        Err(e) => {
+            error!("Failed to reset circuit breaker {}: {}", id, e);
+            Err(e.into())
         }
     }
 }
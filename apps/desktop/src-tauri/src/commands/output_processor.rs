@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use tauri::State;
 use tracing::{info, error, debug, warn};
 use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload, ResearchError};
+use crate::models::execution_metrics::WorkflowExecutionMetrics;
 use crate::services::ServiceManager;
+use crate::services::data_persistence::redaction;
 use crate::services::output_processor::{
     OutputFormat, OutputRequest, OutputResult, OutputOptions, OutputStatistics,
     OutputTemplate, OutputStyling, OutputLayout, Margins,
@@ -11,22 +14,24 @@ use crate::services::output_processor::{
     ExportRequest, ExportResult, ExportTemplateType, ExportOptions, ExportStatistics,
     ExportJob, ExportJobStatus, ExportDestination, ExportDestinationType,
     ComprehensiveAnalysisRequest, ComprehensiveAnalysisResult, AnalysisType, AnalysisOptions,
-    AnalysisFilters, ComparisonResult, ClusterResult, BenchmarkResult, AnalysisStatistics
+    AnalysisFilters, ComparisonResult, ClusterResult, BenchmarkResult, AnalysisStatistics,
+    IncrementalAnalysisSummary
 };
 
-/// Format research workflow results
+/// Format research workflow results, also writing them to `destination` when given
 #[tauri::command]
 pub async fn format_workflow_results(
     workflow_id: String,
     format: String,
     template_id: Option<String>,
     options: Option<OutputOptions>,
+    destination: Option<ExportDestination>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<OutputResult, String> {
+) -> Result<OutputResult, ErrorPayload> {
     info!("Formatting workflow results: {} as {}", workflow_id, format);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let output_format = match format.to_lowercase().as_str() {
         "markdown" | "md" => OutputFormat::Markdown,
@@ -37,7 +42,7 @@ pub async fn format_workflow_results(
         "xml" => OutputFormat::XML,
         "docx" => OutputFormat::DOCX,
         "txt" => OutputFormat::TXT,
-        _ => return Err(format!("Unsupported output format: {}", format)),
+        _ => return Err(AppError::validation("format", format!("Unsupported output format: {}", format)).into()),
     };
 
     let request = OutputRequest {
@@ -46,6 +51,7 @@ pub async fn format_workflow_results(
         template_id,
         options: options.unwrap_or_default(),
         custom_template: None,
+        destination,
     };
 
     // Get the workflow from research engine
@@ -53,8 +59,8 @@ pub async fn format_workflow_results(
         let research_engine = service_manager.inner().research_engine.read().await;
         match research_engine.get_workflow(workflow_uuid).await {
             Ok(Some(workflow)) => workflow,
-            Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-            Err(e) => return Err(format!("Failed to get workflow: {}", e)),
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => return Err(e.into()),
         }
     };
 
@@ -67,7 +73,7 @@ pub async fn format_workflow_results(
         }
         Err(e) => {
             error!("Failed to format workflow results {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -77,7 +83,7 @@ pub async fn format_workflow_results(
 pub async fn format_batch_workflows(
     requests: Vec<serde_json::Value>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<OutputResult>, String> {
+) -> Result<Vec<OutputResult>, ErrorPayload> {
     info!("Formatting batch of {} workflows", requests.len());
 
     let mut output_requests = Vec::new();
@@ -86,7 +92,7 @@ pub async fn format_batch_workflows(
     // Parse requests
     for request_json in requests {
         let request: OutputRequest = serde_json::from_value(request_json)
-            .map_err(|e| format!("Invalid request format: {}", e))?;
+            .map_err(|e| AppError::validation("requests", format!("Invalid request format: {}", e)))?;
         workflow_ids.push(request.workflow_id);
         output_requests.push(request);
     }
@@ -98,8 +104,8 @@ pub async fn format_batch_workflows(
         for workflow_id in workflow_ids {
             match research_engine.get_workflow(workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
@@ -114,7 +120,7 @@ pub async fn format_batch_workflows(
         }
         Err(e) => {
             error!("Failed to format batch workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -123,7 +129,7 @@ pub async fn format_batch_workflows(
 #[tauri::command]
 pub async fn get_supported_formats(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     debug!("Getting supported output formats");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -136,7 +142,7 @@ pub async fn get_supported_formats(
 #[tauri::command]
 pub async fn get_output_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<OutputStatistics, String> {
+) -> Result<OutputStatistics, ErrorPayload> {
     info!("Getting output processing statistics");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -147,7 +153,7 @@ pub async fn get_output_statistics(
         }
         Err(e) => {
             error!("Failed to get output statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -157,7 +163,7 @@ pub async fn get_output_statistics(
 pub async fn get_output_templates(
     format: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<OutputTemplate>, String> {
+) -> Result<Vec<OutputTemplate>, ErrorPayload> {
     info!("Getting output templates");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -168,7 +174,7 @@ pub async fn get_output_templates(
         }
         Err(e) => {
             error!("Failed to get output templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -178,7 +184,7 @@ pub async fn get_output_templates(
 pub async fn create_output_template(
     template: OutputTemplate,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Creating output template: {}", template.name);
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -189,7 +195,7 @@ pub async fn create_output_template(
         }
         Err(e) => {
             error!("Failed to create output template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -200,7 +206,7 @@ pub async fn update_output_template(
     template_id: String,
     template: OutputTemplate,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Updating output template: {}", template_id);
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -211,7 +217,7 @@ pub async fn update_output_template(
         }
         Err(e) => {
             error!("Failed to update output template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -221,7 +227,7 @@ pub async fn update_output_template(
 pub async fn delete_output_template(
     template_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Deleting output template: {}", template_id);
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -232,7 +238,7 @@ pub async fn delete_output_template(
         }
         Err(e) => {
             error!("Failed to delete output template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -242,19 +248,19 @@ pub async fn delete_output_template(
 pub async fn get_format_recommendations(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Getting format recommendations for workflow: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     // Get the workflow
     let workflow = {
         let research_engine = service_manager.inner().research_engine.read().await;
         match research_engine.get_workflow(workflow_uuid).await {
             Ok(Some(workflow)) => workflow,
-            Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-            Err(e) => return Err(format!("Failed to get workflow: {}", e)),
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => return Err(e.into()),
         }
     };
 
@@ -269,7 +275,7 @@ pub async fn get_format_recommendations(
 pub async fn validate_output_request(
     request: OutputRequest,
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     debug!("Validating output request");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -277,7 +283,7 @@ pub async fn validate_output_request(
         Ok(()) => Ok(true),
         Err(e) => {
             warn!("Output request validation failed: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -287,7 +293,7 @@ pub async fn validate_output_request(
 pub async fn get_format_file_extension(
     format: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     let output_format = match format.to_lowercase().as_str() {
         "markdown" | "md" => OutputFormat::Markdown,
         "html" => OutputFormat::HTML,
@@ -297,13 +303,13 @@ pub async fn get_format_file_extension(
         "xml" => OutputFormat::XML,
         "docx" => OutputFormat::DOCX,
         "txt" => OutputFormat::TXT,
-        _ => return Err(format!("Unsupported output format: {}", format)),
+        _ => return Err(AppError::validation("format", format!("Unsupported output format: {}", format)).into()),
     };
 
     let output_processor = service_manager.inner().output_processor.read().await;
     match output_processor.get_file_extension(output_format).await {
         Ok(extension) => Ok(extension.to_string()),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -312,7 +318,7 @@ pub async fn get_format_file_extension(
 pub async fn get_format_mime_type(
     format: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     let output_format = match format.to_lowercase().as_str() {
         "markdown" | "md" => OutputFormat::Markdown,
         "html" => OutputFormat::HTML,
@@ -322,13 +328,13 @@ pub async fn get_format_mime_type(
         "xml" => OutputFormat::XML,
         "docx" => OutputFormat::DOCX,
         "txt" => OutputFormat::TXT,
-        _ => return Err(format!("Unsupported output format: {}", format)),
+        _ => return Err(AppError::validation("format", format!("Unsupported output format: {}", format)).into()),
     };
 
     let output_processor = service_manager.inner().output_processor.read().await;
     match output_processor.get_mime_type(output_format).await {
         Ok(mime_type) => Ok(mime_type.to_string()),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -343,11 +349,11 @@ pub async fn generate_workflow_chart(
     chart_type: String,
     output_format: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ChartResult, String> {
+) -> Result<ChartResult, ErrorPayload> {
     info!("Generating {} chart for workflow: {}", chart_type, workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let chart_type_enum = match chart_type.to_lowercase().as_str() {
         "bar" => ChartType::Bar,
@@ -358,7 +364,7 @@ pub async fn generate_workflow_chart(
         "network" => ChartType::Network,
         "heatmap" => ChartType::Heatmap,
         "histogram" => ChartType::Histogram,
-        _ => return Err(format!("Unsupported chart type: {}", chart_type)),
+        _ => return Err(AppError::validation("chart_type", format!("Unsupported chart type: {}", chart_type)).into()),
     };
 
     let output_format_enum = match output_format.to_lowercase().as_str() {
@@ -367,7 +373,7 @@ pub async fn generate_workflow_chart(
         "png" => ChartOutputFormat::PNG,
         "canvas" => ChartOutputFormat::Canvas,
         "pdf" => ChartOutputFormat::PDF,
-        _ => return Err(format!("Unsupported chart output format: {}", output_format)),
+        _ => return Err(AppError::validation("output_format", format!("Unsupported chart output format: {}", output_format)).into()),
     };
 
     // Get the workflow
@@ -375,8 +381,8 @@ pub async fn generate_workflow_chart(
         let research_engine = service_manager.inner().research_engine.read().await;
         match research_engine.get_workflow(workflow_uuid).await {
             Ok(Some(workflow)) => workflow,
-            Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-            Err(e) => return Err(format!("Failed to get workflow: {}", e)),
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => return Err(e.into()),
         }
     };
 
@@ -396,7 +402,7 @@ pub async fn generate_workflow_chart(
         }
         Err(e) => {
             error!("Failed to generate chart for workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -408,13 +414,13 @@ pub async fn generate_multiple_charts(
     chart_types: Vec<String>,
     output_format: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ChartResult>, String> {
+) -> Result<Vec<ChartResult>, ErrorPayload> {
     info!("Generating {} charts for workflow: {}", chart_types.len(), workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
-    let chart_type_enums: Result<Vec<ChartType>, String> = chart_types.iter()
+    let chart_type_enums: Result<Vec<ChartType>, AppError> = chart_types.iter()
         .map(|ct| match ct.to_lowercase().as_str() {
             "bar" => Ok(ChartType::Bar),
             "line" => Ok(ChartType::Line),
@@ -424,7 +430,7 @@ pub async fn generate_multiple_charts(
             "network" => Ok(ChartType::Network),
             "heatmap" => Ok(ChartType::Heatmap),
             "histogram" => Ok(ChartType::Histogram),
-            _ => Err(format!("Unsupported chart type: {}", ct)),
+            _ => Err(AppError::validation("chart_types", format!("Unsupported chart type: {}", ct))),
         })
         .collect();
 
@@ -436,7 +442,7 @@ pub async fn generate_multiple_charts(
         "png" => ChartOutputFormat::PNG,
         "canvas" => ChartOutputFormat::Canvas,
         "pdf" => ChartOutputFormat::PDF,
-        _ => return Err(format!("Unsupported chart output format: {}", output_format)),
+        _ => return Err(AppError::validation("output_format", format!("Unsupported chart output format: {}", output_format)).into()),
     };
 
     // Get the workflow
@@ -444,8 +450,8 @@ pub async fn generate_multiple_charts(
         let research_engine = service_manager.inner().research_engine.read().await;
         match research_engine.get_workflow(workflow_uuid).await {
             Ok(Some(workflow)) => workflow,
-            Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-            Err(e) => return Err(format!("Failed to get workflow: {}", e)),
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => return Err(e.into()),
         }
     };
 
@@ -457,7 +463,7 @@ pub async fn generate_multiple_charts(
         }
         Err(e) => {
             error!("Failed to generate charts for workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -467,19 +473,19 @@ pub async fn generate_multiple_charts(
 pub async fn get_chart_recommendations(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Getting chart recommendations for workflow: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     // Get the workflow
     let workflow = {
         let research_engine = service_manager.inner().research_engine.read().await;
         match research_engine.get_workflow(workflow_uuid).await {
             Ok(Some(workflow)) => workflow,
-            Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-            Err(e) => return Err(format!("Failed to get workflow: {}", e)),
+            Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+            Err(e) => return Err(e.into()),
         }
     };
 
@@ -493,7 +499,7 @@ pub async fn get_chart_recommendations(
 #[tauri::command]
 pub async fn get_supported_chart_types(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     debug!("Getting supported chart types");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -506,7 +512,7 @@ pub async fn get_supported_chart_types(
 #[tauri::command]
 pub async fn get_supported_chart_formats(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, ErrorPayload> {
     debug!("Getting supported chart output formats");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -519,7 +525,7 @@ pub async fn get_supported_chart_formats(
 #[tauri::command]
 pub async fn get_visualization_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<VisualizationStatistics, String> {
+) -> Result<VisualizationStatistics, ErrorPayload> {
     info!("Getting visualization statistics");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -530,7 +536,7 @@ pub async fn get_visualization_statistics(
         }
         Err(e) => {
             error!("Failed to get visualization statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -539,7 +545,7 @@ pub async fn get_visualization_statistics(
 #[tauri::command]
 pub async fn clear_visualization_cache(
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Clearing visualization cache");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -550,7 +556,7 @@ pub async fn clear_visualization_cache(
         }
         Err(e) => {
             error!("Failed to clear visualization cache: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -567,7 +573,7 @@ pub async fn export_workflows(
     destination_type: String,
     destination_path: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ExportResult, String> {
+) -> Result<ExportResult, ErrorPayload> {
     info!("Exporting {} workflows", workflow_ids.len());
 
     // Parse workflow IDs
@@ -575,7 +581,7 @@ pub async fn export_workflows(
     for id_str in workflow_ids {
         match Uuid::parse_str(&id_str) {
             Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
         }
     }
 
@@ -583,8 +589,9 @@ pub async fn export_workflows(
     let dest_type = match destination_type.to_lowercase().as_str() {
         "local" | "filesystem" => ExportDestinationType::LocalFileSystem,
         "s3" => ExportDestinationType::S3,
+        "gcs" | "gs" => ExportDestinationType::GoogleCloudStorage,
         "email" => ExportDestinationType::Email,
-        _ => return Err(format!("Unsupported destination type: {}", destination_type)),
+        _ => return Err(AppError::validation("destination_type", format!("Unsupported destination type: {}", destination_type)).into()),
     };
 
     // Get workflows
@@ -594,13 +601,22 @@ pub async fn export_workflows(
         for workflow_id in &parsed_ids {
             match research_engine.get_workflow(*workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
     };
 
+    // Results stored before redaction was configured may still hold personal data
+    let workflows = {
+        let data_persistence = service_manager.inner().data_persistence.read().await;
+        let redaction_config = data_persistence.get_redaction_config();
+        workflows.iter()
+            .map(|workflow| redaction::redact_workflow(&redaction_config, workflow).into_owned())
+            .collect::<Vec<_>>()
+    };
+
     // Create export request
     let request = ExportRequest {
         id: Uuid::new_v4(),
@@ -636,7 +652,7 @@ pub async fn export_workflows(
         }
         Err(e) => {
             error!("Failed to export workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -645,7 +661,7 @@ pub async fn export_workflows(
 #[tauri::command]
 pub async fn get_export_templates(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ExportTemplateType>, String> {
+) -> Result<Vec<ExportTemplateType>, ErrorPayload> {
     info!("Getting export templates");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -656,7 +672,7 @@ pub async fn get_export_templates(
         }
         Err(e) => {
             error!("Failed to get export templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -665,7 +681,7 @@ pub async fn get_export_templates(
 #[tauri::command]
 pub async fn get_export_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<ExportStatistics, String> {
+) -> Result<ExportStatistics, ErrorPayload> {
     info!("Getting export statistics");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -676,7 +692,7 @@ pub async fn get_export_statistics(
         }
         Err(e) => {
             error!("Failed to get export statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -691,7 +707,7 @@ pub async fn perform_comprehensive_analysis(
     workflow_ids: Vec<String>,
     analysis_types: Vec<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ComprehensiveAnalysisResult, String> {
+) -> Result<ComprehensiveAnalysisResult, ErrorPayload> {
     info!("Performing comprehensive analysis on {} workflows", workflow_ids.len());
 
     // Parse workflow IDs
@@ -699,7 +715,7 @@ pub async fn perform_comprehensive_analysis(
     for id_str in workflow_ids {
         match Uuid::parse_str(&id_str) {
             Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
         }
     }
 
@@ -713,7 +729,7 @@ pub async fn perform_comprehensive_analysis(
             "performance" => AnalysisType::Performance,
             "trend" => AnalysisType::Trend,
             "quality" => AnalysisType::Quality,
-            _ => return Err(format!("Unsupported analysis type: {}", type_str)),
+            _ => return Err(AppError::validation("analysis_types", format!("Unsupported analysis type: {}", type_str)).into()),
         };
         parsed_types.push(analysis_type);
     }
@@ -725,8 +741,8 @@ pub async fn perform_comprehensive_analysis(
         for workflow_id in &parsed_ids {
             match research_engine.get_workflow(*workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
@@ -750,7 +766,7 @@ pub async fn perform_comprehensive_analysis(
         }
         Err(e) => {
             error!("Failed to perform comprehensive analysis: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -760,7 +776,7 @@ pub async fn perform_comprehensive_analysis(
 pub async fn compare_workflows(
     workflow_ids: Vec<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ComparisonResult, String> {
+) -> Result<ComparisonResult, ErrorPayload> {
     info!("Comparing {} workflows", workflow_ids.len());
 
     // Parse workflow IDs
@@ -768,12 +784,12 @@ pub async fn compare_workflows(
     for id_str in workflow_ids {
         match Uuid::parse_str(&id_str) {
             Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
         }
     }
 
     if parsed_ids.len() < 2 {
-        return Err("At least 2 workflows are required for comparison".to_string());
+        return Err(AppError::validation("workflow_ids", "At least 2 workflows are required for comparison").into());
     }
 
     // Get workflows
@@ -783,8 +799,8 @@ pub async fn compare_workflows(
         for workflow_id in &parsed_ids {
             match research_engine.get_workflow(*workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
@@ -798,7 +814,7 @@ pub async fn compare_workflows(
         }
         Err(e) => {
             error!("Failed to compare workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -808,7 +824,7 @@ pub async fn compare_workflows(
 pub async fn analyze_workflow_similarity(
     workflow_ids: Vec<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ClusterResult, String> {
+) -> Result<ClusterResult, ErrorPayload> {
     info!("Analyzing similarity among {} workflows", workflow_ids.len());
 
     // Parse workflow IDs
@@ -816,7 +832,7 @@ pub async fn analyze_workflow_similarity(
     for id_str in workflow_ids {
         match Uuid::parse_str(&id_str) {
             Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
         }
     }
 
@@ -827,8 +843,8 @@ pub async fn analyze_workflow_similarity(
         for workflow_id in &parsed_ids {
             match research_engine.get_workflow(*workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
@@ -842,7 +858,7 @@ pub async fn analyze_workflow_similarity(
         }
         Err(e) => {
             error!("Failed to analyze workflow similarity: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -852,7 +868,7 @@ pub async fn analyze_workflow_similarity(
 pub async fn analyze_workflow_performance(
     workflow_ids: Vec<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<BenchmarkResult, String> {
+) -> Result<BenchmarkResult, ErrorPayload> {
     info!("Analyzing performance of {} workflows", workflow_ids.len());
 
     // Parse workflow IDs
@@ -860,7 +876,7 @@ pub async fn analyze_workflow_performance(
     for id_str in workflow_ids {
         match Uuid::parse_str(&id_str) {
             Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
         }
     }
 
@@ -871,22 +887,165 @@ pub async fn analyze_workflow_performance(
         for workflow_id in &parsed_ids {
             match research_engine.get_workflow(*workflow_id).await {
                 Ok(Some(workflow)) => workflows.push(workflow),
-                Ok(None) => return Err(format!("Workflow not found: {}", workflow_id)),
-                Err(e) => return Err(format!("Failed to get workflow {}: {}", workflow_id, e)),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
             }
         }
         workflows
     };
 
+    // Resource use recorded as the workflows ran; workflows without any are estimated
+    let actuals = load_execution_metrics(&service_manager, &parsed_ids).await;
+
     let output_processor = service_manager.inner().output_processor.read().await;
-    match output_processor.analyze_workflow_performance(&workflows).await {
+    match output_processor.analyze_workflow_performance_with_actuals(&workflows, &actuals).await {
         Ok(result) => {
             info!("Successfully analyzed workflow performance");
             Ok(result)
         }
         Err(e) => {
             error!("Failed to analyze workflow performance: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Resource use recorded as each workflow ran. Workflows whose metrics fail to load are
+/// left out, so their performance is estimated.
+async fn load_execution_metrics(service_manager: &ServiceManager, workflow_ids: &[Uuid]) -> HashMap<Uuid, WorkflowExecutionMetrics> {
+    let data_persistence = service_manager.data_persistence.read().await;
+    let mut actuals = HashMap::new();
+    for workflow_id in workflow_ids {
+        match data_persistence.get_workflow_execution_metrics(*workflow_id).await {
+            Ok(metrics) => {
+                actuals.insert(*workflow_id, metrics);
+            }
+            Err(e) => warn!("Failed to load execution metrics of workflow {}: {}", workflow_id, e),
+        }
+    }
+    actuals
+}
+
+/// Get the resource use recorded for each step of a workflow, with its totals
+#[tauri::command]
+pub async fn get_workflow_execution_metrics(
+    workflow_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<WorkflowExecutionMetrics, ErrorPayload> {
+    let workflow_id = Uuid::parse_str(&workflow_id)
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID {}: {}", workflow_id, e)))?;
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    match data_persistence.get_workflow_execution_metrics(workflow_id).await {
+        Ok(metrics) => Ok(metrics),
+        Err(e) => {
+            error!("Failed to get execution metrics of workflow {}: {}", workflow_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Render a performance diagnostic report explaining each workflow's benchmark score
+#[tauri::command]
+pub async fn get_performance_diagnostics(
+    workflow_ids: Vec<String>,
+    format: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<OutputResult, ErrorPayload> {
+    info!("Rendering performance diagnostics for {} workflows", workflow_ids.len());
+
+    let output_format = match format.to_lowercase().as_str() {
+        "markdown" | "md" => OutputFormat::Markdown,
+        "html" => OutputFormat::HTML,
+        "json" => OutputFormat::JSON,
+        "txt" => OutputFormat::TXT,
+        _ => return Err(AppError::validation("format", format!("Unsupported diagnostic report format: {}", format)).into()),
+    };
+
+    // Parse workflow IDs
+    let mut parsed_ids = Vec::new();
+    for id_str in workflow_ids {
+        match Uuid::parse_str(&id_str) {
+            Ok(id) => parsed_ids.push(id),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
+        }
+    }
+
+    // Get workflows
+    let workflows = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        let mut workflows = Vec::new();
+        for workflow_id in &parsed_ids {
+            match research_engine.get_workflow(*workflow_id).await {
+                Ok(Some(workflow)) => workflows.push(workflow),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        workflows
+    };
+
+    let output_processor = service_manager.inner().output_processor.read().await;
+    match output_processor.render_performance_diagnostics(&workflows, output_format).await {
+        Ok(result) => {
+            info!("Successfully rendered performance diagnostics");
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to render performance diagnostics: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Render a "what's new" report of how a later run of a query differs from an earlier one
+#[tauri::command]
+pub async fn diff_workflow_results(
+    workflow_a_id: String,
+    workflow_b_id: String,
+    format: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<OutputResult, ErrorPayload> {
+    info!("Diffing results of workflow {} against {}", workflow_b_id, workflow_a_id);
+
+    let output_format = match format.to_lowercase().as_str() {
+        "markdown" | "md" => OutputFormat::Markdown,
+        "html" => OutputFormat::HTML,
+        "json" => OutputFormat::JSON,
+        "txt" => OutputFormat::TXT,
+        _ => return Err(AppError::validation("format", format!("Unsupported what's new report format: {}", format)).into()),
+    };
+
+    let workflow_a_uuid = Uuid::parse_str(&workflow_a_id)
+        .map_err(|e| AppError::validation("workflow_a_id", format!("Invalid workflow ID {}: {}", workflow_a_id, e)))?;
+    let workflow_b_uuid = Uuid::parse_str(&workflow_b_id)
+        .map_err(|e| AppError::validation("workflow_b_id", format!("Invalid workflow ID {}: {}", workflow_b_id, e)))?;
+
+    // Get workflows
+    let (workflow_a, workflow_b) = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        let mut workflows = Vec::new();
+        for workflow_id in [workflow_a_uuid, workflow_b_uuid] {
+            match research_engine.get_workflow(workflow_id).await {
+                Ok(Some(workflow)) => workflows.push(workflow),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let workflow_b = workflows.pop().expect("two workflows were fetched");
+        let workflow_a = workflows.pop().expect("two workflows were fetched");
+        (workflow_a, workflow_b)
+    };
+
+    let output_processor = service_manager.inner().output_processor.read().await;
+    match output_processor.diff_results(&workflow_a, &workflow_b, output_format).await {
+        Ok(result) => {
+            info!("Successfully rendered what's new report for workflow {}", workflow_b_id);
+            Ok(result)
+        }
+        Err(e) => {
+            error!("Failed to diff workflow results: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -895,7 +1054,7 @@ pub async fn analyze_workflow_performance(
 #[tauri::command]
 pub async fn get_analysis_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<AnalysisStatistics, String> {
+) -> Result<AnalysisStatistics, ErrorPayload> {
     info!("Getting analysis statistics");
 
     let output_processor = service_manager.inner().output_processor.read().await;
@@ -906,7 +1065,85 @@ pub async fn get_analysis_statistics(
         }
         Err(e) => {
             error!("Failed to get analysis statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Fold newly-completed workflows into the incremental analysis
+#[tauri::command]
+pub async fn update_incremental_analysis(
+    workflow_ids: Vec<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<IncrementalAnalysisSummary, ErrorPayload> {
+    info!("Updating incremental analysis with {} workflows", workflow_ids.len());
+
+    // Parse workflow IDs
+    let mut parsed_ids = Vec::new();
+    for id_str in workflow_ids {
+        match Uuid::parse_str(&id_str) {
+            Ok(id) => parsed_ids.push(id),
+            Err(e) => return Err(AppError::validation("workflow_ids", format!("Invalid workflow ID {}: {}", id_str, e)).into()),
+        }
+    }
+
+    // Get workflows
+    let workflows = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        let mut workflows = Vec::new();
+        for workflow_id in &parsed_ids {
+            match research_engine.get_workflow(*workflow_id).await {
+                Ok(Some(workflow)) => workflows.push(workflow),
+                Ok(None) => return Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        workflows
+    };
+
+    let actuals = load_execution_metrics(&service_manager, &parsed_ids).await;
+
+    let output_processor = service_manager.inner().output_processor.read().await;
+    match output_processor.update_analysis(&workflows, &actuals).await {
+        Ok(summary) => {
+            info!("Incremental analysis now covers {} workflows", summary.workflow_count);
+            Ok(summary)
+        }
+        Err(e) => {
+            error!("Failed to update incremental analysis: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Rebuild the incremental analysis over all stored workflows
+#[tauri::command]
+pub async fn recompute_incremental_analysis(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<IncrementalAnalysisSummary, ErrorPayload> {
+    info!("Recomputing incremental analysis over all workflows");
+
+    let workflows = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        research_engine.get_all_workflows().await
+            .map_err(|e| {
+                error!("Failed to get workflows: {}", e);
+                ErrorPayload::from(e)
+            })?
+    };
+
+    let workflow_ids: Vec<Uuid> = workflows.iter().map(|workflow| workflow.id).collect();
+    let actuals = load_execution_metrics(&service_manager, &workflow_ids).await;
+
+    let output_processor = service_manager.inner().output_processor.read().await;
+    match output_processor.recompute_analysis(&workflows, &actuals).await {
+        Ok(summary) => {
+            info!("Recomputed incremental analysis over {} workflows", summary.workflow_count);
+            Ok(summary)
+        }
+        Err(e) => {
+            error!("Failed to recompute incremental analysis: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::error::{AppError, ErrorPayload};
 use crate::services::ServiceManager;
 use crate::services::performance::{ComprehensivePerformanceMetrics, OptimizationRecommendation};
 use tauri::State;
@@ -7,7 +8,7 @@ use tracing::{info, debug, error};
 #[tauri::command]
 pub async fn get_performance_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<ComprehensivePerformanceMetrics, String> {
+) -> Result<ComprehensivePerformanceMetrics, ErrorPayload> {
     info!("Getting comprehensive performance metrics");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -18,7 +19,7 @@ pub async fn get_performance_metrics(
         }
         Err(e) => {
             error!("Failed to get performance metrics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -27,7 +28,7 @@ pub async fn get_performance_metrics(
 #[tauri::command]
 pub async fn get_optimization_recommendations(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<OptimizationRecommendation>, String> {
+) -> Result<Vec<OptimizationRecommendation>, ErrorPayload> {
     info!("Getting performance optimization recommendations");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -38,7 +39,7 @@ pub async fn get_optimization_recommendations(
         }
         Err(e) => {
             error!("Failed to get optimization recommendations: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -47,7 +48,7 @@ pub async fn get_optimization_recommendations(
 #[tauri::command]
 pub async fn clear_performance_caches(
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Clearing all performance caches");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -58,7 +59,7 @@ pub async fn clear_performance_caches(
         }
         Err(e) => {
             error!("Failed to clear performance caches: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -67,7 +68,7 @@ pub async fn clear_performance_caches(
 #[tauri::command]
 pub async fn get_cache_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::performance::CacheStatistics, String> {
+) -> Result<crate::services::performance::CacheStatistics, ErrorPayload> {
     debug!("Getting cache statistics");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -82,7 +83,7 @@ pub async fn get_cache_statistics(
 #[tauri::command]
 pub async fn get_deduplication_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::performance::DeduplicationStatistics, String> {
+) -> Result<crate::services::performance::DeduplicationStatistics, ErrorPayload> {
     debug!("Getting request deduplication statistics");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -97,7 +98,7 @@ pub async fn get_deduplication_statistics(
 #[tauri::command]
 pub async fn get_background_processing_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::performance::BackgroundProcessingStatistics, String> {
+) -> Result<crate::services::performance::BackgroundProcessingStatistics, ErrorPayload> {
     debug!("Getting background processing statistics");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -116,7 +117,7 @@ pub async fn submit_background_task(
     task_data: serde_json::Value,
     priority: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<String, String> {
+) -> Result<String, ErrorPayload> {
     info!("Submitting background task: {} ({})", task_name, task_type);
     
     let task_priority = match priority.as_deref() {
@@ -150,7 +151,7 @@ pub async fn submit_background_task(
         }
         Err(e) => {
             error!("Failed to submit background task: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -160,11 +161,11 @@ pub async fn submit_background_task(
 pub async fn get_background_task_status(
     task_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, ErrorPayload> {
     debug!("Getting background task status: {}", task_id);
     
     let task_uuid = uuid::Uuid::parse_str(&task_id)
-        .map_err(|e| format!("Invalid task ID: {}", e))?;
+        .map_err(|e| AppError::validation("task_id", format!("Invalid task ID: {}", e)))?;
     
     let performance_service = service_manager.inner().performance.read().await;
     let background_processor = performance_service.background_processor();
@@ -190,7 +191,7 @@ pub async fn get_background_task_status(
         }
         Err(e) => {
             error!("Failed to get background task status: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -200,11 +201,11 @@ pub async fn get_background_task_status(
 pub async fn cancel_background_task(
     task_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     info!("Cancelling background task: {}", task_id);
     
     let task_uuid = uuid::Uuid::parse_str(&task_id)
-        .map_err(|e| format!("Invalid task ID: {}", e))?;
+        .map_err(|e| AppError::validation("task_id", format!("Invalid task ID: {}", e)))?;
     
     let performance_service = service_manager.inner().performance.read().await;
     let background_processor = performance_service.background_processor();
@@ -220,7 +221,7 @@ pub async fn cancel_background_task(
         }
         Err(e) => {
             error!("Failed to cancel background task: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -229,7 +230,7 @@ pub async fn cancel_background_task(
 #[tauri::command]
 pub async fn get_connection_pool_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::performance::PoolStatistics, String> {
+) -> Result<crate::services::performance::PoolStatistics, ErrorPayload> {
     debug!("Getting connection pool statistics");
     
     let performance_service = service_manager.inner().performance.read().await;
@@ -244,7 +245,7 @@ pub async fn get_connection_pool_statistics(
 #[tauri::command]
 pub async fn performance_health_check(
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     debug!("Performing performance health check");
     
     let performance_service = service_manager.inner().performance.read().await;
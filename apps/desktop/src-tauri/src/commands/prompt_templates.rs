@@ -0,0 +1,97 @@
+use tauri::State;
+use tracing::{info, error};
+
+use crate::error::ErrorPayload;
+use crate::models::prompt_template::{
+    ExperimentComparison, PromptExperiment, PromptTemplate,
+    SavePromptTemplateRequest, StartPromptExperimentRequest,
+};
+use crate::services::ServiceManager;
+
+/// Get the latest version of every prompt template
+#[tauri::command]
+pub async fn get_prompt_templates(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<PromptTemplate>, ErrorPayload> {
+    let research_engine = service_manager.inner().research_engine.read().await;
+    Ok(research_engine.prompt_library().list_templates().await)
+}
+
+/// Get every version of a prompt template, oldest first
+#[tauri::command]
+pub async fn get_prompt_template_versions(
+    prompt_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<PromptTemplate>, ErrorPayload> {
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.prompt_library().get_versions(&prompt_id).await.map_err(|e| {
+        error!("Failed to get versions of prompt {}: {}", prompt_id, e);
+        e.into()
+    })
+}
+
+/// Save a new version of a prompt template
+#[tauri::command]
+pub async fn save_prompt_template(
+    request: SavePromptTemplateRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<PromptTemplate, ErrorPayload> {
+    info!("Saving prompt template: {}", request.id);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.prompt_library().save_template(request).await {
+        Ok(template) => {
+            info!("Saved prompt template {}", template.label());
+            Ok(template)
+        }
+        Err(e) => {
+            error!("Failed to save prompt template: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Start an A/B experiment between two versions of a prompt
+#[tauri::command]
+pub async fn start_prompt_experiment(
+    request: StartPromptExperimentRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<PromptExperiment, ErrorPayload> {
+    info!("Starting prompt experiment on: {}", request.prompt_id);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.prompt_library().start_experiment(request).await {
+        Ok(experiment) => Ok(experiment),
+        Err(e) => {
+            error!("Failed to start prompt experiment: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Stop a prompt experiment, keeping its metrics
+#[tauri::command]
+pub async fn stop_prompt_experiment(
+    prompt_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<PromptExperiment, ErrorPayload> {
+    info!("Stopping prompt experiment on: {}", prompt_id);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.prompt_library().stop_experiment(&prompt_id).await {
+        Ok(experiment) => Ok(experiment),
+        Err(e) => {
+            error!("Failed to stop prompt experiment on {}: {}", prompt_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Compare the quality metrics of each prompt experiment's control and variant
+#[tauri::command]
+pub async fn get_prompt_experiments(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<ExperimentComparison>, ErrorPayload> {
+    let research_engine = service_manager.inner().research_engine.read().await;
+    Ok(research_engine.prompt_library().get_experiments().await)
+}
@@ -2,6 +2,7 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, debug, error};
 
+use crate::error::ErrorPayload;
 use crate::services::ServiceManager;
 use crate::models::quantum_ready::*;
 
@@ -9,11 +10,11 @@ use crate::models::quantum_ready::*;
 pub async fn register_quantum_algorithm(
     service_manager: State<'_, ServiceManager>,
     algorithm: QuantumAlgorithm,
-) -> Result<QuantumAlgorithm, String> {
+) -> Result<QuantumAlgorithm, ErrorPayload> {
     info!("API: Registering quantum algorithm: {}", algorithm.name);
     match service_manager.quantum_ready_service.register_algorithm(algorithm).await {
         Ok(registered_algorithm) => Ok(registered_algorithm),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -21,11 +22,11 @@ pub async fn register_quantum_algorithm(
 pub async fn register_compute_resource(
     service_manager: State<'_, ServiceManager>,
     resource: ComputeResource,
-) -> Result<ComputeResource, String> {
+) -> Result<ComputeResource, ErrorPayload> {
     info!("API: Registering compute resource: {}", resource.provider);
     match service_manager.quantum_ready_service.register_compute_resource(resource).await {
         Ok(registered_resource) => Ok(registered_resource),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -33,11 +34,11 @@ pub async fn register_compute_resource(
 pub async fn assess_quantum_readiness(
     service_manager: State<'_, ServiceManager>,
     system_component: String,
-) -> Result<QuantumReadinessAssessment, String> {
+) -> Result<QuantumReadinessAssessment, ErrorPayload> {
     info!("API: Assessing quantum readiness for: {}", system_component);
     match service_manager.quantum_ready_service.assess_quantum_readiness(system_component).await {
         Ok(assessment) => Ok(assessment),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -45,11 +46,11 @@ pub async fn assess_quantum_readiness(
 pub async fn plan_quantum_migration(
     service_manager: State<'_, ServiceManager>,
     current_protocols: Vec<String>,
-) -> Result<Vec<MigrationPath>, String> {
+) -> Result<Vec<MigrationPath>, ErrorPayload> {
     info!("API: Planning quantum migration for {} protocols", current_protocols.len());
     match service_manager.quantum_ready_service.plan_quantum_migration(current_protocols).await {
         Ok(migration_paths) => Ok(migration_paths),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -60,12 +61,12 @@ pub async fn execute_hybrid_crypto_operation(
     data: Vec<u8>,
     classical_algorithm: String,
     quantum_safe_algorithm: String,
-) -> Result<HybridCryptoOperation, String> {
+) -> Result<HybridCryptoOperation, ErrorPayload> {
     debug!("API: Executing hybrid crypto operation: {:?}", operation_type);
     match service_manager.quantum_ready_service
         .execute_hybrid_crypto_operation(operation_type, data, classical_algorithm, quantum_safe_algorithm).await {
         Ok(operation_result) => Ok(operation_result),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
@@ -73,21 +74,21 @@ pub async fn execute_hybrid_crypto_operation(
 pub async fn get_available_quantum_algorithms(
     service_manager: State<'_, ServiceManager>,
     algorithm_type: Option<AlgorithmType>,
-) -> Result<Vec<QuantumAlgorithm>, String> {
+) -> Result<Vec<QuantumAlgorithm>, ErrorPayload> {
     debug!("API: Getting available quantum algorithms");
     match service_manager.quantum_ready_service.get_available_algorithms(algorithm_type).await {
         Ok(algorithms) => Ok(algorithms),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
 
 #[tauri::command]
 pub async fn get_quantum_readiness_summary(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::quantum_ready::QuantumReadinessSummary, String> {
+) -> Result<crate::services::quantum_ready::QuantumReadinessSummary, ErrorPayload> {
     debug!("API: Getting quantum readiness summary");
     match service_manager.quantum_ready_service.get_readiness_summary().await {
         Ok(summary) => Ok(summary),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.into())
     }
 }
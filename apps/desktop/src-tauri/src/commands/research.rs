@@ -2,27 +2,31 @@ use tauri::State;
 use uuid::Uuid;
 use tracing::{info, error};
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload, ResearchError};
 use crate::models::{ResearchWorkflow, CreateWorkflowRequest};
 use crate::services::ServiceManager;
+use crate::services::api_manager::run_scoped;
+use super::api_management::caller_scope;
 
 /// Create a new research workflow
 #[tauri::command]
 pub async fn create_research_workflow(
     request: CreateWorkflowRequest,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchWorkflow, String> {
+) -> Result<ResearchWorkflow, ErrorPayload> {
     info!("Creating research workflow: {}", request.name);
 
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let research_engine = service_manager.inner().research_engine.read().await;
-    match research_engine.create_workflow(request).await {
+    match run_scoped(scope, research_engine.create_workflow(request)).await {
         Ok(workflow) => {
             info!("Research workflow created successfully: {}", workflow.id);
             Ok(workflow)
         }
         Err(e) => {
             error!("Failed to create research workflow: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -32,11 +36,11 @@ pub async fn create_research_workflow(
 pub async fn execute_research(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Executing research workflow: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.start_workflow_execution(workflow_uuid).await {
@@ -46,7 +50,7 @@ pub async fn execute_research(
         }
         Err(e) => {
             error!("Failed to execute research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -56,11 +60,11 @@ pub async fn execute_research(
 pub async fn get_research_status(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchWorkflow, String> {
+) -> Result<ResearchWorkflow, ErrorPayload> {
     info!("Getting research status: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow(workflow_uuid).await {
@@ -70,11 +74,11 @@ pub async fn get_research_status(
         }
         Ok(None) => {
             error!("Research workflow not found: {}", workflow_id);
-            Err(format!("Workflow not found: {}", workflow_id))
+            Err(AppError::from(ResearchError::workflow_not_found(workflow_id.to_string())).into())
         }
         Err(e) => {
             error!("Failed to get research status {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -84,11 +88,11 @@ pub async fn get_research_status(
 pub async fn cancel_research(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Cancelling research workflow: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.cancel_workflow(workflow_uuid).await {
@@ -98,7 +102,7 @@ pub async fn cancel_research(
         }
         Err(e) => {
             error!("Failed to cancel research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -108,11 +112,11 @@ pub async fn cancel_research(
 pub async fn get_research_results(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<crate::models::ResearchResults>, String> {
+) -> Result<Option<crate::models::ResearchResults>, ErrorPayload> {
     info!("Getting research results: {}", workflow_id);
 
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID: {}", e)))?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow_results(workflow_uuid).await {
@@ -126,7 +130,7 @@ pub async fn get_research_results(
         }
         Err(e) => {
             error!("Failed to get research results {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
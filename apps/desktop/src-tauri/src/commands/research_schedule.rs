@@ -0,0 +1,150 @@
+use tauri::State;
+use tracing::{info, error};
+use uuid::Uuid;
+
+use crate::error::{AppError, ErrorPayload};
+use crate::models::research_schedule::{CreateScheduleRequest, ResearchSchedule, ScheduleRun};
+use crate::services::ServiceManager;
+
+/// Create a recurring research schedule
+#[tauri::command]
+pub async fn create_research_schedule(
+    request: CreateScheduleRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ResearchSchedule, ErrorPayload> {
+    info!("Creating research schedule: {}", request.name);
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    match research_scheduler.create_schedule(request).await {
+        Ok(schedule) => {
+            info!("Created research schedule with ID: {}", schedule.id);
+            Ok(schedule)
+        }
+        Err(e) => {
+            error!("Failed to create research schedule: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get all research schedules with their next run time and last run status
+#[tauri::command]
+pub async fn get_research_schedules(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<ResearchSchedule>, ErrorPayload> {
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    research_scheduler.get_schedules().await.map_err(|e| {
+        error!("Failed to get research schedules: {}", e);
+        e.into()
+    })
+}
+
+/// Pause a research schedule
+#[tauri::command]
+pub async fn pause_research_schedule(
+    schedule_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ResearchSchedule, ErrorPayload> {
+    info!("Pausing research schedule: {}", schedule_id);
+
+    let schedule_uuid = parse_schedule_id(&schedule_id)?;
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    match research_scheduler.pause_schedule(schedule_uuid).await {
+        Ok(schedule) => Ok(schedule),
+        Err(e) => {
+            error!("Failed to pause research schedule {}: {}", schedule_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Resume a paused research schedule
+#[tauri::command]
+pub async fn resume_research_schedule(
+    schedule_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ResearchSchedule, ErrorPayload> {
+    info!("Resuming research schedule: {}", schedule_id);
+
+    let schedule_uuid = parse_schedule_id(&schedule_id)?;
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    match research_scheduler.resume_schedule(schedule_uuid).await {
+        Ok(schedule) => {
+            info!("Resumed research schedule {}, next run {:?}", schedule_id, schedule.next_run_at);
+            Ok(schedule)
+        }
+        Err(e) => {
+            error!("Failed to resume research schedule {}: {}", schedule_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Delete a research schedule and its run history
+#[tauri::command]
+pub async fn delete_research_schedule(
+    schedule_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Deleting research schedule: {}", schedule_id);
+
+    let schedule_uuid = parse_schedule_id(&schedule_id)?;
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    match research_scheduler.delete_schedule(schedule_uuid).await {
+        Ok(()) => {
+            info!("Deleted research schedule: {}", schedule_id);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to delete research schedule {}: {}", schedule_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Run a research schedule immediately
+#[tauri::command]
+pub async fn run_research_schedule_now(
+    schedule_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ScheduleRun, ErrorPayload> {
+    info!("Running research schedule now: {}", schedule_id);
+
+    let schedule_uuid = parse_schedule_id(&schedule_id)?;
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    match research_scheduler.run_schedule_now(schedule_uuid).await {
+        Ok(run) => {
+            info!("Started run {} of research schedule {}", run.id, schedule_id);
+            Ok(run)
+        }
+        Err(e) => {
+            error!("Failed to run research schedule {}: {}", schedule_id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Get the most recent runs of a research schedule, newest first
+#[tauri::command]
+pub async fn get_research_schedule_runs(
+    schedule_id: String,
+    limit: Option<u32>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<ScheduleRun>, ErrorPayload> {
+    let schedule_uuid = parse_schedule_id(&schedule_id)?;
+
+    let research_scheduler = service_manager.inner().research_scheduler.read().await;
+    research_scheduler.get_schedule_runs(schedule_uuid, limit.unwrap_or(20)).await.map_err(|e| {
+        error!("Failed to get runs of research schedule {}: {}", schedule_id, e);
+        e.into()
+    })
+}
+
+fn parse_schedule_id(schedule_id: &str) -> Result<Uuid, ErrorPayload> {
+    Uuid::parse_str(schedule_id)
+        .map_err(|e| AppError::validation("schedule_id", format!("Invalid schedule ID {}: {}", schedule_id, e)).into())
+}
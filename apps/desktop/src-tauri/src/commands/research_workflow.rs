@@ -1,10 +1,22 @@
-use tauri::State;
+use tauri::{Emitter, State, Window};
 use tracing::{info, error};
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::research_workflow::{ResearchWorkflow, ResearchMethodology, WorkflowStatus, WorkflowParameters};
+use crate::error::{AppError, AppResult, ErrorPayload};
+use crate::models::research_workflow::{ResearchWorkflow, ResearchMethodology, WorkflowStatus, WorkflowParameters, CreateWorkflowRequest};
+use crate::models::DataRegion;
+use crate::models::pagination::{Page, PageRequest};
+use crate::models::workflow_rating::{MethodologyRecommendation, WorkflowRating};
 use crate::services::ServiceManager;
+use crate::services::api_manager::run_scoped;
+use super::api_management::caller_scope;
+use crate::services::data_persistence::{WorkflowOrganization, WorkflowSearchFilters, WorkflowSearchMatch};
+use crate::services::research_engine::preflight::QuotaCheck;
+use crate::services::research_engine::explain_plan::WorkflowPlan;
+use crate::services::research_engine::result_stream::PartialResults;
+use crate::services::research_engine::workflow_bundle::ImportedBundle;
+use crate::services::research_engine::bulk_rerun::{RerunFilter, RerunSummary};
+use crate::services::research_engine::load_test::{LoadTestConfig, LoadTestReport};
 use crate::services::research_engine::{
     QueuedWorkflow, WorkflowPriority, QueueStats, ConcurrencyConfig,
     WorkflowProgress, QueueProgress, StepProgress, QueueManagementResult, QueueManagementStatus,
@@ -18,50 +30,55 @@ pub async fn create_research_workflow(
     query: String,
     methodology: String,
     created_by: String,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchWorkflow, String> {
+) -> Result<ResearchWorkflow, ErrorPayload> {
     info!("Creating research workflow: {}", name);
 
     let methodology_enum = match methodology.to_lowercase().as_str() {
         "don_lim" => ResearchMethodology::DonLim,
         "nick_scamara" => ResearchMethodology::NickScamara,
         "hybrid" => ResearchMethodology::Hybrid,
-        _ => return Err(format!("Invalid methodology: {}", methodology)),
+        "document_analysis" => ResearchMethodology::DocumentAnalysis,
+        _ => return Err(AppError::validation("methodology", format!("Invalid methodology: {}", methodology)).into()),
     };
 
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let research_engine = service_manager.inner().research_engine.read().await;
-    match research_engine.create_workflow(name, query, methodology_enum, created_by).await {
+    match run_scoped(scope, research_engine.create_workflow(name, query, methodology_enum, created_by)).await {
         Ok(workflow) => {
             info!("Created research workflow with ID: {}", workflow.id);
             Ok(workflow)
         }
         Err(e) => {
             error!("Failed to create research workflow: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
-/// Start executing a research workflow
+/// Start executing a research workflow. The start is refused when the configured API
+/// keys lack the quota the workflow needs, unless `ignore_quota` is set.
 #[tauri::command]
 pub async fn start_research_workflow(
     workflow_id: String,
+    ignore_quota: Option<bool>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Starting research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
+    let quota_check = if ignore_quota.unwrap_or(false) { QuotaCheck::Override } else { QuotaCheck::Enforce };
     let research_engine = service_manager.inner().research_engine.read().await;
-    match research_engine.start_workflow_execution(workflow_uuid).await {
+    match research_engine.start_workflow_execution_with(workflow_uuid, quota_check).await {
         Ok(()) => {
             info!("Started research workflow: {}", workflow_id);
             Ok(())
         }
         Err(e) => {
             error!("Failed to start research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -71,11 +88,10 @@ pub async fn start_research_workflow(
 pub async fn pause_research_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Pausing research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.pause_workflow_execution(workflow_uuid).await {
@@ -85,7 +101,7 @@ pub async fn pause_research_workflow(
         }
         Err(e) => {
             error!("Failed to pause research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -95,11 +111,10 @@ pub async fn pause_research_workflow(
 pub async fn resume_research_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Resuming research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.resume_workflow_execution(workflow_uuid).await {
@@ -109,7 +124,7 @@ pub async fn resume_research_workflow(
         }
         Err(e) => {
             error!("Failed to resume research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -119,11 +134,10 @@ pub async fn resume_research_workflow(
 pub async fn cancel_research_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Cancelling research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.cancel_workflow_execution(workflow_uuid).await {
@@ -133,7 +147,7 @@ pub async fn cancel_research_workflow(
         }
         Err(e) => {
             error!("Failed to cancel research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -143,18 +157,17 @@ pub async fn cancel_research_workflow(
 pub async fn get_research_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<ResearchWorkflow>, String> {
+) -> Result<Option<ResearchWorkflow>, ErrorPayload> {
     info!("Getting research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow(workflow_uuid).await {
         Ok(workflow) => Ok(workflow),
         Err(e) => {
             error!("Failed to get research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -163,7 +176,7 @@ pub async fn get_research_workflow(
 #[tauri::command]
 pub async fn get_all_research_workflows(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchWorkflow>, String> {
+) -> Result<Vec<ResearchWorkflow>, ErrorPayload> {
     info!("Getting all research workflows");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -174,7 +187,25 @@ pub async fn get_all_research_workflows(
         }
         Err(e) => {
             error!("Failed to get research workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Get a page of research workflows, newest first
+#[tauri::command]
+pub async fn list_research_workflows(
+    page: PageRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Page<ResearchWorkflow>, ErrorPayload> {
+    info!("Listing research workflows (limit {})", page.limit());
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.list_workflows(&page).await {
+        Ok(workflows) => Ok(workflows),
+        Err(e) => {
+            error!("Failed to list research workflows: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -184,7 +215,7 @@ pub async fn get_all_research_workflows(
 pub async fn get_research_workflows_by_status(
     status: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchWorkflow>, String> {
+) -> Result<Vec<ResearchWorkflow>, ErrorPayload> {
     info!("Getting research workflows by status: {}", status);
 
     let workflow_status = match status.to_lowercase().as_str() {
@@ -195,7 +226,7 @@ pub async fn get_research_workflows_by_status(
         "completed" => WorkflowStatus::Completed,
         "failed" => WorkflowStatus::Failed,
         "cancelled" => WorkflowStatus::Cancelled,
-        _ => return Err(format!("Invalid workflow status: {}", status)),
+        _ => return Err(AppError::validation("status", format!("Invalid workflow status: {}", status)).into()),
     };
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -206,7 +237,7 @@ pub async fn get_research_workflows_by_status(
         }
         Err(e) => {
             error!("Failed to get research workflows by status {}: {}", status, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -216,11 +247,10 @@ pub async fn get_research_workflows_by_status(
 pub async fn delete_research_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Deleting research workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.delete_workflow(workflow_uuid).await {
@@ -230,7 +260,223 @@ pub async fn delete_research_workflow(
         }
         Err(e) => {
             error!("Failed to delete research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
+        }
+    }
+}
+
+/// Full-text search across stored research workflows
+#[tauri::command]
+pub async fn search_workflows(
+    query: String,
+    filters: Option<WorkflowSearchFilters>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<WorkflowSearchMatch>, ErrorPayload> {
+    info!("Searching research workflows with query: {}", query);
+
+    let filters = filters.unwrap_or_default();
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    match data_persistence.search_workflows(&query, &filters).await {
+        Ok(matches) => {
+            info!("Found {} workflows matching query: {}", matches.len(), query);
+            Ok(matches)
+        }
+        Err(e) => {
+            error!("Failed to search workflows: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// List stored research workflows by folder, tags and the other search filters
+#[tauri::command]
+pub async fn browse_research_workflows(
+    filters: WorkflowSearchFilters,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<WorkflowSearchMatch>, ErrorPayload> {
+    info!("Listing research workflows in folder {:?} with tags {:?}", filters.folder, filters.tags);
+
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    data_persistence.browse_workflows(&filters).await.map_err(|e| {
+        error!("Failed to list workflows: {}", e);
+        e.into()
+    })
+}
+
+/// Every folder and tag in use, with workflow counts
+#[tauri::command]
+pub async fn get_workflow_organization(
+    data_region: Option<DataRegion>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<WorkflowOrganization, ErrorPayload> {
+    let data_persistence = service_manager.inner().data_persistence.read().await;
+    data_persistence.get_workflow_organization(data_region.unwrap_or_default()).await.map_err(|e| {
+        error!("Failed to get workflow folders and tags: {}", e);
+        e.into()
+    })
+}
+
+/// Add and remove tags on a research workflow
+#[tauri::command]
+pub async fn tag_research_workflow(
+    workflow_id: String,
+    add: Vec<String>,
+    remove: Vec<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ResearchWorkflow, ErrorPayload> {
+    info!("Tagging research workflow: {}", workflow_id);
+
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.tag_workflow(workflow_uuid, &add, &remove).await.map_err(|e| {
+        error!("Failed to tag workflow {}: {}", workflow_id, e);
+        e.into()
+    })
+}
+
+/// Move a research workflow into a folder; no folder moves it to the top level
+#[tauri::command]
+pub async fn move_research_workflow(
+    workflow_id: String,
+    folder: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ResearchWorkflow, ErrorPayload> {
+    info!("Moving research workflow {} to folder {:?}", workflow_id, folder);
+
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.move_workflow(workflow_uuid, folder.as_deref()).await.map_err(|e| {
+        error!("Failed to move workflow {}: {}", workflow_id, e);
+        e.into()
+    })
+}
+
+/// Show what a workflow would do, use and cost if created and started now, without running it
+#[tauri::command]
+pub async fn explain_workflow(
+    request: CreateWorkflowRequest,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<WorkflowPlan, ErrorPayload> {
+    info!("Explaining research workflow: {}", request.name);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.explain_workflow(request).await.map_err(|e| {
+        error!("Failed to explain workflow: {}", e);
+        e.into()
+    })
+}
+
+/// Rate how well a completed research workflow's methodology worked, from 1 to 5.
+/// `rated_by` is the user rating it, who must be the one who ran it.
+#[tauri::command]
+pub async fn rate_workflow(
+    workflow_id: String,
+    rated_by: String,
+    rating: u8,
+    notes: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<WorkflowRating, ErrorPayload> {
+    info!("Rating research workflow {}: {}", workflow_id, rating);
+
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.rate_workflow(workflow_uuid, &rated_by, rating, notes).await.map_err(|e| {
+        error!("Failed to rate workflow {}: {}", workflow_id, e);
+        e.into()
+    })
+}
+
+/// Recommend a methodology for a query from `user`'s own ratings of similar workflows
+#[tauri::command]
+pub async fn recommend_methodology(
+    query: String,
+    user: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<MethodologyRecommendation, ErrorPayload> {
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.recommend_methodology(&query, &user).await.map_err(|e| {
+        error!("Failed to recommend a methodology: {}", e);
+        e.into()
+    })
+}
+
+/// Export a workflow, its results and recorded provider responses as a shareable bundle
+#[tauri::command]
+pub async fn export_workflow_bundle(
+    workflow_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, ErrorPayload> {
+    info!("Exporting bundle of research workflow: {}", workflow_id);
+
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    research_engine.export_workflow_bundle(workflow_uuid).await.map_err(|e| {
+        error!("Failed to export bundle of workflow {}: {}", workflow_id, e);
+        e.into()
+    })
+}
+
+/// Import a workflow bundle, optionally re-running it from its recorded provider responses
+#[tauri::command]
+pub async fn import_workflow_bundle(
+    bundle_content: String,
+    rerun: bool,
+    session_id: Option<String>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<ImportedBundle, ErrorPayload> {
+    info!("Importing research workflow bundle");
+
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match run_scoped(scope, research_engine.import_workflow_bundle(&bundle_content, rerun)).await {
+        Ok(imported) => {
+            info!("Imported research workflow: {}", imported.workflow.id);
+            Ok(imported)
+        }
+        Err(e) => {
+            error!("Failed to import research workflow bundle: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Re-queue the failed workflows matching `filter`, such as those a since-recovered
+/// provider failed
+#[tauri::command]
+pub async fn rerun_failed_workflows(
+    filter: RerunFilter,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<RerunSummary, ErrorPayload> {
+    info!("Re-running failed workflows matching {:?}", filter);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.rerun_failed_workflows(filter).await {
+        Ok(summary) => Ok(summary),
+        Err(e) => {
+            error!("Failed to re-run failed workflows: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Measure capacity by running workflows against the mock providers
+#[tauri::command]
+pub async fn run_load_test(
+    config: LoadTestConfig,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<LoadTestReport, ErrorPayload> {
+    info!("Running load test with {:?}", config);
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    match research_engine.run_load_test(config).await {
+        Ok(report) => Ok(report),
+        Err(e) => {
+            error!("Failed to run load test: {}", e);
+            Err(e.into())
         }
     }
 }
@@ -240,9 +486,8 @@ pub async fn delete_research_workflow(
 pub async fn get_workflow_status(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<String>, String> {
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+) -> Result<Option<String>, ErrorPayload> {
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow_status(workflow_uuid).await {
@@ -250,7 +495,7 @@ pub async fn get_workflow_status(
         Ok(None) => Ok(None),
         Err(e) => {
             error!("Failed to get workflow status {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -260,16 +505,15 @@ pub async fn get_workflow_status(
 pub async fn get_workflow_progress(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<f64>, String> {
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+) -> Result<Option<f64>, ErrorPayload> {
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow_progress(workflow_uuid).await {
         Ok(progress) => Ok(progress),
         Err(e) => {
             error!("Failed to get workflow progress {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -279,25 +523,91 @@ pub async fn get_workflow_progress(
 pub async fn get_workflow_results(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<crate::models::research_workflow::ResearchResults>, String> {
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+) -> Result<Option<crate::models::research_workflow::ResearchResults>, ErrorPayload> {
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow_results(workflow_uuid).await {
         Ok(results) => Ok(results),
         Err(e) => {
             error!("Failed to get workflow results {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// Get the results a running workflow has so far
+#[tauri::command]
+pub async fn get_partial_workflow_results(
+    workflow_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Option<PartialResults>, ErrorPayload> {
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let research_engine = service_manager.inner().research_engine.read().await;
+    Ok(research_engine.get_partial_workflow_results(workflow_uuid))
+}
+
+/// Stream a workflow's results to the window as its steps complete. Each snapshot is
+/// emitted as a `workflow-results://<id>` event, starting with the current one, until
+/// the final snapshot.
+#[tauri::command]
+pub async fn subscribe_workflow_results(
+    workflow_id: String,
+    window: Window,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
+
+    let (mut receiver, current) = {
+        let research_engine = service_manager.inner().research_engine.read().await;
+        research_engine.subscribe_workflow_results(workflow_uuid).await?
+    };
+    let event = format!("workflow-results://{}", workflow_uuid);
+
+    tokio::spawn(async move {
+        use tokio::sync::broadcast::error::RecvError;
+
+        let mut last_sequence = 0;
+        if let Some(snapshot) = current {
+            last_sequence = snapshot.sequence;
+            if window.emit(&event, &snapshot).is_err() || snapshot.is_final {
+                return;
+            }
+        }
+        loop {
+            let snapshot = match receiver.recv().await {
+                Ok(snapshot) if snapshot.workflow_id == workflow_uuid => snapshot,
+                Ok(_) => continue,
+                // Snapshots are whole, so skipping the missed ones loses nothing
+                Err(RecvError::Lagged(missed)) => {
+                    info!("Results stream for workflow {} skipped {} snapshots", workflow_uuid, missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if snapshot.sequence <= last_sequence {
+                continue;
+            }
+            last_sequence = snapshot.sequence;
+            if let Err(e) = window.emit(&event, &snapshot) {
+                error!("Failed to emit results of workflow {}: {}", workflow_uuid, e);
+                break;
+            }
+            if snapshot.is_final {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
 /// Get workflow statistics
 #[tauri::command]
 pub async fn get_workflow_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::research_engine::WorkflowStatistics, String> {
+) -> Result<crate::services::research_engine::WorkflowStatistics, ErrorPayload> {
     info!("Getting workflow statistics");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -308,7 +618,7 @@ pub async fn get_workflow_statistics(
         }
         Err(e) => {
             error!("Failed to get workflow statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -324,18 +634,17 @@ pub async fn enqueue_research_workflow(
     priority: String,
     estimated_duration_minutes: Option<u32>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Enqueuing research workflow: {} with priority: {}", workflow_id, priority);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let workflow_priority = match priority.to_lowercase().as_str() {
         "low" => WorkflowPriority::Low,
         "normal" => WorkflowPriority::Normal,
         "high" => WorkflowPriority::High,
         "critical" => WorkflowPriority::Critical,
-        _ => return Err(format!("Invalid priority: {}", priority)),
+        _ => return Err(AppError::validation("priority", format!("Invalid priority: {}", priority)).into()),
     };
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -346,7 +655,7 @@ pub async fn enqueue_research_workflow(
         }
         Err(e) => {
             error!("Failed to enqueue research workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -355,7 +664,7 @@ pub async fn enqueue_research_workflow(
 #[tauri::command]
 pub async fn get_queue_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueStats, String> {
+) -> Result<QueueStats, ErrorPayload> {
     info!("Getting queue statistics");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -366,7 +675,7 @@ pub async fn get_queue_statistics(
         }
         Err(e) => {
             error!("Failed to get queue statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -375,7 +684,7 @@ pub async fn get_queue_statistics(
 #[tauri::command]
 pub async fn get_active_queue_workflows(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<QueuedWorkflow>, String> {
+) -> Result<Vec<QueuedWorkflow>, ErrorPayload> {
     info!("Getting active queue workflows");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -386,7 +695,7 @@ pub async fn get_active_queue_workflows(
         }
         Err(e) => {
             error!("Failed to get active queue workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -395,7 +704,7 @@ pub async fn get_active_queue_workflows(
 #[tauri::command]
 pub async fn get_queued_workflows(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<QueuedWorkflow>, String> {
+) -> Result<Vec<QueuedWorkflow>, ErrorPayload> {
     info!("Getting queued workflows");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -406,7 +715,7 @@ pub async fn get_queued_workflows(
         }
         Err(e) => {
             error!("Failed to get queued workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -416,7 +725,7 @@ pub async fn get_queued_workflows(
 pub async fn get_workflow_queue_history(
     limit: Option<usize>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<QueuedWorkflow>, String> {
+) -> Result<Vec<QueuedWorkflow>, ErrorPayload> {
     info!("Getting workflow queue history");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -427,7 +736,7 @@ pub async fn get_workflow_queue_history(
         }
         Err(e) => {
             error!("Failed to get workflow queue history: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -437,11 +746,10 @@ pub async fn get_workflow_queue_history(
 pub async fn cancel_queued_workflow(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     info!("Cancelling queued workflow: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.cancel_queued_workflow(workflow_uuid).await {
@@ -455,7 +763,7 @@ pub async fn cancel_queued_workflow(
         }
         Err(e) => {
             error!("Failed to cancel queued workflow {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -465,7 +773,7 @@ pub async fn cancel_queued_workflow(
 pub async fn update_queue_concurrency(
     max_concurrent: usize,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Updating queue concurrency to: {}", max_concurrent);
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -476,7 +784,7 @@ pub async fn update_queue_concurrency(
         }
         Err(e) => {
             error!("Failed to update queue concurrency: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -485,7 +793,7 @@ pub async fn update_queue_concurrency(
 #[tauri::command]
 pub async fn get_queue_concurrency_config(
     service_manager: State<'_, ServiceManager>,
-) -> Result<ConcurrencyConfig, String> {
+) -> Result<ConcurrencyConfig, ErrorPayload> {
     info!("Getting queue concurrency configuration");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -496,7 +804,7 @@ pub async fn get_queue_concurrency_config(
         }
         Err(e) => {
             error!("Failed to get queue concurrency configuration: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -505,7 +813,7 @@ pub async fn get_queue_concurrency_config(
 #[tauri::command]
 pub async fn start_queue_processing(
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Starting queue processing");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -516,7 +824,7 @@ pub async fn start_queue_processing(
         }
         Err(e) => {
             error!("Failed to start queue processing: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -525,7 +833,7 @@ pub async fn start_queue_processing(
 #[tauri::command]
 pub async fn stop_queue_processing(
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Stopping queue processing");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -536,7 +844,7 @@ pub async fn stop_queue_processing(
         }
         Err(e) => {
             error!("Failed to stop queue processing: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -550,11 +858,10 @@ pub async fn stop_queue_processing(
 pub async fn get_workflow_progress_detailed(
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<WorkflowProgress>, String> {
+) -> Result<Option<WorkflowProgress>, ErrorPayload> {
     info!("Getting detailed workflow progress: {}", workflow_id);
 
-    let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|e| format!("Invalid workflow ID: {}", e))?;
+    let workflow_uuid = parse_workflow_id(&workflow_id)?;
 
     let research_engine = service_manager.inner().research_engine.read().await;
     match research_engine.get_workflow_progress_detailed(workflow_uuid).await {
@@ -568,7 +875,7 @@ pub async fn get_workflow_progress_detailed(
         }
         Err(e) => {
             error!("Failed to get workflow progress {}: {}", workflow_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -577,7 +884,7 @@ pub async fn get_workflow_progress_detailed(
 #[tauri::command]
 pub async fn get_queue_progress_overview(
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueProgress, String> {
+) -> Result<QueueProgress, ErrorPayload> {
     info!("Getting queue progress overview");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -588,7 +895,7 @@ pub async fn get_queue_progress_overview(
         }
         Err(e) => {
             error!("Failed to get queue progress overview: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -598,7 +905,7 @@ pub async fn get_queue_progress_overview(
 pub async fn get_progress_history(
     hours: Option<u32>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<WorkflowProgress>, String> {
+) -> Result<Vec<WorkflowProgress>, ErrorPayload> {
     let hours_str = hours.map(|h| h.to_string()).unwrap_or_else(|| "24".to_string());
     info!("Getting progress history for last {} hours", hours_str);
 
@@ -610,7 +917,7 @@ pub async fn get_progress_history(
         }
         Err(e) => {
             error!("Failed to get progress history: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -619,7 +926,7 @@ pub async fn get_progress_history(
 #[tauri::command]
 pub async fn get_real_time_monitoring_data(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting real-time monitoring data");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -646,7 +953,7 @@ pub async fn get_real_time_monitoring_data(
         }
         _ => {
             error!("Failed to retrieve some monitoring data components");
-            Err("Failed to retrieve complete monitoring data".to_string())
+            Err(AppError::internal("Failed to retrieve complete monitoring data").into())
         }
     }
 }
@@ -660,7 +967,7 @@ pub async fn get_real_time_monitoring_data(
 pub async fn pause_queue_gracefully(
     reason: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementResult, String> {
+) -> Result<QueueManagementResult, ErrorPayload> {
     info!("Pausing queue gracefully: {}", reason);
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -671,7 +978,7 @@ pub async fn pause_queue_gracefully(
         }
         Err(e) => {
             error!("Failed to pause queue: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -681,7 +988,7 @@ pub async fn pause_queue_gracefully(
 pub async fn resume_queue(
     reason: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementResult, String> {
+) -> Result<QueueManagementResult, ErrorPayload> {
     info!("Resuming queue: {}", reason);
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -692,7 +999,7 @@ pub async fn resume_queue(
         }
         Err(e) => {
             error!("Failed to resume queue: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -702,7 +1009,7 @@ pub async fn resume_queue(
 pub async fn emergency_stop_queue(
     reason: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementResult, String> {
+) -> Result<QueueManagementResult, ErrorPayload> {
     warn!("Emergency stopping queue: {}", reason);
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -713,7 +1020,7 @@ pub async fn emergency_stop_queue(
         }
         Err(e) => {
             error!("Failed to emergency stop queue: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -723,7 +1030,7 @@ pub async fn emergency_stop_queue(
 pub async fn clear_queue(
     reason: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementResult, String> {
+) -> Result<QueueManagementResult, ErrorPayload> {
     warn!("Clearing queue: {}", reason);
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -734,7 +1041,7 @@ pub async fn clear_queue(
         }
         Err(e) => {
             error!("Failed to clear queue: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -745,16 +1052,13 @@ pub async fn cancel_multiple_workflows(
     workflow_ids: Vec<String>,
     reason: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementResult, String> {
+) -> Result<QueueManagementResult, ErrorPayload> {
     info!("Cancelling {} workflows: {}", workflow_ids.len(), reason);
 
     // Parse workflow IDs
     let mut parsed_ids = Vec::new();
     for id_str in workflow_ids {
-        match Uuid::parse_str(&id_str) {
-            Ok(id) => parsed_ids.push(id),
-            Err(e) => return Err(format!("Invalid workflow ID {}: {}", id_str, e)),
-        }
+        parsed_ids.push(parse_workflow_id(&id_str)?);
     }
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -765,7 +1069,7 @@ pub async fn cancel_multiple_workflows(
         }
         Err(e) => {
             error!("Failed to cancel multiple workflows: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -774,7 +1078,7 @@ pub async fn cancel_multiple_workflows(
 #[tauri::command]
 pub async fn get_queue_management_status(
     service_manager: State<'_, ServiceManager>,
-) -> Result<QueueManagementStatus, String> {
+) -> Result<QueueManagementStatus, ErrorPayload> {
     info!("Getting queue management status");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -785,7 +1089,7 @@ pub async fn get_queue_management_status(
         }
         Err(e) => {
             error!("Failed to get queue management status: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -798,7 +1102,7 @@ pub async fn get_queue_management_status(
 #[tauri::command]
 pub async fn get_resource_status(
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResourceStatus, String> {
+) -> Result<ResourceStatus, ErrorPayload> {
     info!("Getting resource status");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -809,7 +1113,7 @@ pub async fn get_resource_status(
         }
         Err(e) => {
             error!("Failed to get resource status: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -819,7 +1123,7 @@ pub async fn get_resource_status(
 pub async fn update_resource_limits(
     limits: ResourceLimits,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Updating resource limits");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -830,7 +1134,7 @@ pub async fn update_resource_limits(
         }
         Err(e) => {
             error!("Failed to update resource limits: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -840,7 +1144,7 @@ pub async fn update_resource_limits(
 pub async fn get_resource_metrics(
     hours: Option<u32>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResourceMetrics, String> {
+) -> Result<ResourceMetrics, ErrorPayload> {
     let hours_str = hours.map(|h| h.to_string()).unwrap_or_else(|| "24".to_string());
     info!("Getting resource metrics for last {} hours", hours_str);
 
@@ -852,7 +1156,7 @@ pub async fn get_resource_metrics(
         }
         Err(e) => {
             error!("Failed to get resource metrics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -862,7 +1166,7 @@ pub async fn get_resource_metrics(
 pub async fn can_allocate_workflow_resources(
     requirements: ResourceLimits,
     service_manager: State<'_, ServiceManager>,
-) -> Result<bool, String> {
+) -> Result<bool, ErrorPayload> {
     info!("Checking resource availability");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -873,7 +1177,7 @@ pub async fn can_allocate_workflow_resources(
         }
         Err(e) => {
             error!("Failed to check resource availability: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -882,7 +1186,7 @@ pub async fn can_allocate_workflow_resources(
 #[tauri::command]
 pub async fn record_resource_usage(
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     debug!("Recording resource usage");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -893,7 +1197,7 @@ pub async fn record_resource_usage(
         }
         Err(e) => {
             error!("Failed to record resource usage: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -902,7 +1206,7 @@ pub async fn record_resource_usage(
 #[tauri::command]
 pub async fn get_resource_dashboard_data(
     service_manager: State<'_, ServiceManager>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting comprehensive resource dashboard data");
 
     let research_engine = service_manager.inner().research_engine.read().await;
@@ -927,7 +1231,12 @@ pub async fn get_resource_dashboard_data(
         }
         _ => {
             error!("Failed to retrieve some resource dashboard data components");
-            Err("Failed to retrieve complete resource dashboard data".to_string())
+            Err(AppError::internal("Failed to retrieve complete resource dashboard data").into())
         }
     }
 }
+
+fn parse_workflow_id(workflow_id: &str) -> Result<Uuid, ErrorPayload> {
+    Uuid::parse_str(workflow_id)
+        .map_err(|e| AppError::validation("workflow_id", format!("Invalid workflow ID {}: {}", workflow_id, e)).into())
+}
@@ -2,19 +2,21 @@ use tauri::State;
 use tracing::{info, error};
 use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload};
 use crate::models::research_template::{
     ResearchTemplate, TemplateCategory, TemplateExecutionContext, TemplateMetrics
 };
 use crate::models::research_workflow::ResearchWorkflow;
 use crate::services::{ServiceManager, template_manager::TemplateStatistics};
+use crate::services::api_manager::run_scoped;
+use super::api_management::caller_scope;
 
 /// Create a new research template
 #[tauri::command]
 pub async fn create_research_template(
     template: ResearchTemplate,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchTemplate, String> {
+) -> Result<ResearchTemplate, ErrorPayload> {
     info!("Creating research template: {}", template.name);
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -25,7 +27,7 @@ pub async fn create_research_template(
         }
         Err(e) => {
             error!("Failed to create research template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -35,18 +37,18 @@ pub async fn create_research_template(
 pub async fn get_research_template(
     template_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<ResearchTemplate>, String> {
+) -> Result<Option<ResearchTemplate>, ErrorPayload> {
     info!("Getting research template: {}", template_id);
 
     let template_uuid = Uuid::parse_str(&template_id)
-        .map_err(|e| format!("Invalid template ID: {}", e))?;
+        .map_err(|e| AppError::validation("template_id", format!("Invalid template ID: {}", e)))?;
 
     let template_manager = service_manager.inner().template_manager.read().await;
     match template_manager.get_template(template_uuid).await {
         Ok(template) => Ok(template),
         Err(e) => {
             error!("Failed to get research template {}: {}", template_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -55,7 +57,7 @@ pub async fn get_research_template(
 #[tauri::command]
 pub async fn get_all_research_templates(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Getting all research templates");
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -66,7 +68,7 @@ pub async fn get_all_research_templates(
         }
         Err(e) => {
             error!("Failed to get research templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -76,7 +78,7 @@ pub async fn get_all_research_templates(
 pub async fn get_research_templates_by_category(
     category: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Getting research templates by category: {}", category);
 
     let template_category = match category.to_lowercase().as_str() {
@@ -90,7 +92,7 @@ pub async fn get_research_templates_by_category(
         "medical" => TemplateCategory::Medical,
         "financial" => TemplateCategory::Financial,
         "custom" => TemplateCategory::Custom,
-        _ => return Err(format!("Invalid template category: {}", category)),
+        _ => return Err(AppError::validation("category", format!("Invalid template category: {}", category)).into()),
     };
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -101,7 +103,7 @@ pub async fn get_research_templates_by_category(
         }
         Err(e) => {
             error!("Failed to get templates by category {}: {}", category, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -110,7 +112,7 @@ pub async fn get_research_templates_by_category(
 #[tauri::command]
 pub async fn get_featured_research_templates(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Getting featured research templates");
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -121,7 +123,7 @@ pub async fn get_featured_research_templates(
         }
         Err(e) => {
             error!("Failed to get featured templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -130,7 +132,7 @@ pub async fn get_featured_research_templates(
 #[tauri::command]
 pub async fn get_public_research_templates(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Getting public research templates");
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -141,7 +143,7 @@ pub async fn get_public_research_templates(
         }
         Err(e) => {
             error!("Failed to get public templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -151,7 +153,7 @@ pub async fn get_public_research_templates(
 pub async fn search_research_templates(
     query: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Searching research templates with query: {}", query);
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -162,7 +164,7 @@ pub async fn search_research_templates(
         }
         Err(e) => {
             error!("Failed to search templates: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -172,7 +174,7 @@ pub async fn search_research_templates(
 pub async fn update_research_template(
     template: ResearchTemplate,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchTemplate, String> {
+) -> Result<ResearchTemplate, ErrorPayload> {
     info!("Updating research template: {}", template.id);
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -183,7 +185,7 @@ pub async fn update_research_template(
         }
         Err(e) => {
             error!("Failed to update research template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -193,11 +195,11 @@ pub async fn update_research_template(
 pub async fn delete_research_template(
     template_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Deleting research template: {}", template_id);
 
     let template_uuid = Uuid::parse_str(&template_id)
-        .map_err(|e| format!("Invalid template ID: {}", e))?;
+        .map_err(|e| AppError::validation("template_id", format!("Invalid template ID: {}", e)))?;
 
     let template_manager = service_manager.inner().template_manager.read().await;
     match template_manager.delete_template(template_uuid).await {
@@ -207,7 +209,7 @@ pub async fn delete_research_template(
         }
         Err(e) => {
             error!("Failed to delete research template {}: {}", template_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -216,19 +218,21 @@ pub async fn delete_research_template(
 #[tauri::command]
 pub async fn execute_research_template(
     context: TemplateExecutionContext,
+    session_id: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> Result<ResearchWorkflow, String> {
+) -> Result<ResearchWorkflow, ErrorPayload> {
     info!("Executing research template: {}", context.template_id);
 
+    let scope = caller_scope(service_manager.inner(), session_id).await?;
     let template_manager = service_manager.inner().template_manager.read().await;
-    match template_manager.execute_template(context).await {
+    match run_scoped(scope, template_manager.execute_template(context)).await {
         Ok(workflow) => {
             info!("Template executed successfully, created workflow: {}", workflow.id);
             Ok(workflow)
         }
         Err(e) => {
             error!("Failed to execute template: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -238,7 +242,7 @@ pub async fn execute_research_template(
 pub async fn preview_template_execution(
     context: TemplateExecutionContext,
     service_manager: State<'_, ServiceManager>,
-) -> Result<crate::services::template_manager::template_executor::WorkflowPreview, String> {
+) -> Result<crate::services::template_manager::template_executor::WorkflowPreview, ErrorPayload> {
     info!("Previewing template execution: {}", context.template_id);
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -249,7 +253,7 @@ pub async fn preview_template_execution(
         }
         Err(e) => {
             error!("Failed to preview template execution: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -260,11 +264,11 @@ pub async fn rate_research_template(
     template_id: String,
     rating: f64,
     service_manager: State<'_, ServiceManager>,
-) -> Result<(), String> {
+) -> Result<(), ErrorPayload> {
     info!("Rating research template {} with score: {}", template_id, rating);
 
     let template_uuid = Uuid::parse_str(&template_id)
-        .map_err(|e| format!("Invalid template ID: {}", e))?;
+        .map_err(|e| AppError::validation("template_id", format!("Invalid template ID: {}", e)))?;
 
     let template_manager = service_manager.inner().template_manager.read().await;
     match template_manager.rate_template(template_uuid, rating).await {
@@ -274,7 +278,7 @@ pub async fn rate_research_template(
         }
         Err(e) => {
             error!("Failed to rate research template {}: {}", template_id, e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -284,9 +288,9 @@ pub async fn rate_research_template(
 pub async fn get_template_metrics(
     template_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Option<TemplateMetrics>, String> {
+) -> Result<Option<TemplateMetrics>, ErrorPayload> {
     let template_uuid = Uuid::parse_str(&template_id)
-        .map_err(|e| format!("Invalid template ID: {}", e))?;
+        .map_err(|e| AppError::validation("template_id", format!("Invalid template ID: {}", e)))?;
 
     let template_manager = service_manager.inner().template_manager.read().await;
     let metrics = template_manager.get_template_metrics(template_uuid).await;
@@ -297,7 +301,7 @@ pub async fn get_template_metrics(
 #[tauri::command]
 pub async fn get_all_template_metrics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<(String, TemplateMetrics)>, String> {
+) -> Result<Vec<(String, TemplateMetrics)>, ErrorPayload> {
     info!("Getting all template metrics");
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -316,7 +320,7 @@ pub async fn get_all_template_metrics(
 pub async fn get_template_recommendations(
     limit: usize,
     service_manager: State<'_, ServiceManager>,
-) -> Result<Vec<ResearchTemplate>, String> {
+) -> Result<Vec<ResearchTemplate>, ErrorPayload> {
     info!("Getting template recommendations (limit: {})", limit);
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -327,7 +331,7 @@ pub async fn get_template_recommendations(
         }
         Err(e) => {
             error!("Failed to get template recommendations: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
@@ -336,7 +340,7 @@ pub async fn get_template_recommendations(
 #[tauri::command]
 pub async fn get_template_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> Result<TemplateStatistics, String> {
+) -> Result<TemplateStatistics, ErrorPayload> {
     info!("Getting template statistics");
 
     let template_manager = service_manager.inner().template_manager.read().await;
@@ -347,7 +351,7 @@ pub async fn get_template_statistics(
         }
         Err(e) => {
             error!("Failed to get template statistics: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
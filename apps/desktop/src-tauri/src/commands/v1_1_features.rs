@@ -3,7 +3,7 @@ use uuid::Uuid;
 use tracing::{info, error};
 use std::collections::HashMap;
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload};
 use crate::services::ServiceManager;
 use crate::services::api_manager::{ModelConfiguration, ModelRecommendation, ModelPerformanceMetrics};
 use crate::services::output_processor::export::enhanced_export::{
@@ -22,7 +22,7 @@ use crate::services::mobile_api::{
 #[tauri::command]
 pub async fn get_latest_ai_models(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<Vec<String>> {
+) -> Result<Vec<String>, ErrorPayload> {
     info!("Getting latest AI models");
     
     let api_manager = service_manager.api_manager.read().await;
@@ -43,7 +43,7 @@ pub async fn get_latest_ai_models(
 #[tauri::command]
 pub async fn get_model_configurations(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<Vec<ModelConfiguration>> {
+) -> Result<Vec<ModelConfiguration>, ErrorPayload> {
     info!("Getting model configurations");
     
     let api_manager = service_manager.api_manager.read().await;
@@ -58,7 +58,7 @@ pub async fn get_model_recommendations(
     use_case: String,
     budget_limit: Option<f64>,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<Vec<ModelRecommendation>> {
+) -> Result<Vec<ModelRecommendation>, ErrorPayload> {
     info!("Getting model recommendations for use case: {}", use_case);
     
     let api_manager = service_manager.api_manager.read().await;
@@ -72,7 +72,7 @@ pub async fn get_model_recommendations(
 pub async fn update_model_configuration(
     config: ModelConfiguration,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Updating model configuration: {}", config.model_id);
     
     let api_manager = service_manager.api_manager.write().await;
@@ -86,12 +86,12 @@ pub async fn update_model_configuration(
 #[tauri::command]
 pub async fn get_analytics_dashboard(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting analytics dashboard");
     
     let analytics = service_manager.analytics.read().await;
     let dashboard_data = analytics.get_dashboard_data().await?;
-    Ok(serde_json::to_value(dashboard_data)?)
+    Ok(serde_json::to_value(dashboard_data).map_err(AppError::from)?)
 }
 
 /// Get usage analytics for time period
@@ -99,7 +99,7 @@ pub async fn get_analytics_dashboard(
 pub async fn get_usage_analytics(
     period: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting usage analytics for period: {}", period);
     
     let analytics = service_manager.analytics.read().await;
@@ -115,7 +115,7 @@ pub async fn get_usage_analytics(
     };
     
     let usage_data = analytics.get_usage_analytics(time_period).await?;
-    Ok(serde_json::to_value(usage_data)?)
+    Ok(serde_json::to_value(usage_data).map_err(AppError::from)?)
 }
 
 /// Generate business intelligence report
@@ -123,7 +123,7 @@ pub async fn get_usage_analytics(
 pub async fn generate_business_report(
     report_type: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Generating business report: {}", report_type);
     
     let analytics = service_manager.analytics.read().await;
@@ -137,7 +137,7 @@ pub async fn generate_business_report(
     };
     
     let report = analytics.generate_business_report(business_report_type).await?;
-    Ok(serde_json::to_value(report)?)
+    Ok(serde_json::to_value(report).map_err(AppError::from)?)
 }
 
 /// V1.1.0 Enhanced Export Commands
@@ -151,7 +151,7 @@ pub async fn export_workflows_enhanced(
     filename: String,
     options: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<EnhancedExportResult> {
+) -> Result<EnhancedExportResult, ErrorPayload> {
     info!("Exporting {} workflows to format: {}", workflow_ids.len(), format);
     
     let output_processor = service_manager.output_processor.read().await;
@@ -201,7 +201,7 @@ pub async fn export_workflows_enhanced(
 pub async fn get_export_job_status(
     job_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting export job status: {}", job_id);
     
     let output_processor = service_manager.output_processor.read().await;
@@ -232,15 +232,15 @@ pub async fn start_collaboration_session(
     workflow_id: String,
     session_type: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<CollaborationSession> {
+) -> Result<CollaborationSession, ErrorPayload> {
     info!("Starting collaboration session for user {} on workflow {}", user_id, workflow_id);
     
     let collaboration = service_manager.collaboration.read().await;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid workflow ID".to_string()))?;
+        .map_err(|_| AppError::validation("workflow_id", "Invalid workflow ID"))?;
     
     let session_type_enum = match session_type.as_str() {
         "read_only" => SessionType::ReadOnly,
@@ -251,7 +251,7 @@ pub async fn start_collaboration_session(
         _ => SessionType::ReadOnly,
     };
     
-    collaboration.start_session(user_uuid, workflow_uuid, session_type_enum).await
+    collaboration.start_session(user_uuid, workflow_uuid, session_type_enum).await.map_err(ErrorPayload::from)
 }
 
 /// End collaboration session
@@ -259,26 +259,26 @@ pub async fn start_collaboration_session(
 pub async fn end_collaboration_session(
     session_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Ending collaboration session: {}", session_id);
     
     let collaboration = service_manager.collaboration.read().await;
     
     let session_uuid = Uuid::parse_str(&session_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid session ID".to_string()))?;
+        .map_err(|_| AppError::validation("session_id", "Invalid session ID"))?;
     
-    collaboration.end_session(session_uuid).await
+    collaboration.end_session(session_uuid).await.map_err(ErrorPayload::from)
 }
 
 /// Get collaboration statistics
 #[tauri::command]
 pub async fn get_collaboration_stats(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<CollaborationStats> {
+) -> Result<CollaborationStats, ErrorPayload> {
     info!("Getting collaboration statistics");
     
     let collaboration = service_manager.collaboration.read().await;
-    collaboration.get_collaboration_stats().await
+    collaboration.get_collaboration_stats().await.map_err(ErrorPayload::from)
 }
 
 /// V1.1.0 Mobile API Commands
@@ -288,15 +288,15 @@ pub async fn get_collaboration_stats(
 pub async fn get_mobile_dashboard(
     user_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<MobileDashboard> {
+) -> Result<MobileDashboard, ErrorPayload> {
     info!("Getting mobile dashboard for user: {}", user_id);
     
     let mobile_api = service_manager.mobile_api.read().await;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
-    mobile_api.get_dashboard(user_uuid).await
+    mobile_api.get_dashboard(user_uuid).await.map_err(ErrorPayload::from)
 }
 
 /// Start workflow from mobile
@@ -307,13 +307,13 @@ pub async fn start_workflow_mobile(
     query: String,
     methodology: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Starting workflow from mobile: {}", workflow_name);
     
     let mobile_api = service_manager.mobile_api.read().await;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     let workflow_id = mobile_api.start_workflow_mobile(user_uuid, workflow_name, query, methodology).await?;
     Ok(workflow_id.to_string())
@@ -325,17 +325,17 @@ pub async fn stop_workflow_mobile(
     user_id: String,
     workflow_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Stopping workflow from mobile: {}", workflow_id);
     
     let mobile_api = service_manager.mobile_api.read().await;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid workflow ID".to_string()))?;
+        .map_err(|_| AppError::validation("workflow_id", "Invalid workflow ID"))?;
     
-    mobile_api.stop_workflow_mobile(user_uuid, workflow_uuid).await
+    mobile_api.stop_workflow_mobile(user_uuid, workflow_uuid).await.map_err(ErrorPayload::from)
 }
 
 /// Get user workflows for mobile
@@ -343,24 +343,24 @@ pub async fn stop_workflow_mobile(
 pub async fn get_user_workflows_mobile(
     user_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<Vec<MobileWorkflowSummary>> {
+) -> Result<Vec<MobileWorkflowSummary>, ErrorPayload> {
     info!("Getting workflows for mobile user: {}", user_id);
     
     let mobile_api = service_manager.mobile_api.read().await;
     
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
-    mobile_api.get_user_workflows_mobile(user_uuid).await
+    mobile_api.get_user_workflows_mobile(user_uuid).await.map_err(ErrorPayload::from)
 }
 
 /// Get active mobile connections
 #[tauri::command]
 pub async fn get_active_mobile_connections(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<Vec<MobileConnection>> {
+) -> Result<Vec<MobileConnection>, ErrorPayload> {
     info!("Getting active mobile connections");
     
     let mobile_api = service_manager.mobile_api.read().await;
-    mobile_api.get_active_connections().await
+    mobile_api.get_active_connections().await.map_err(ErrorPayload::from)
 }
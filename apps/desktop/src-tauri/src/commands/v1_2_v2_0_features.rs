@@ -3,7 +3,7 @@ use uuid::Uuid;
 use tracing::{info, error};
 use std::collections::HashMap;
 
-use crate::error::AppResult;
+use crate::error::{AppError, ErrorPayload};
 use crate::services::ServiceManager;
 
 /// V1.2.0 Plugin System Commands
@@ -14,14 +14,14 @@ pub async fn install_plugin(
     plugin_id: String,
     user_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Installing plugin: {} for user: {}", plugin_id, user_id);
     
     let plugin_system = service_manager.plugin_system.read().await;
     let plugin_uuid = Uuid::parse_str(&plugin_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid plugin ID".to_string()))?;
+        .map_err(|_| AppError::validation("plugin_id", "Invalid plugin ID"))?;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     let instance = plugin_system.install_plugin(plugin_uuid, user_uuid).await?;
     Ok(instance.id.to_string())
@@ -33,12 +33,12 @@ pub async fn execute_plugin(
     plugin_id: String,
     context: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Executing plugin: {}", plugin_id);
     
     let plugin_system = service_manager.plugin_system.read().await;
     let plugin_uuid = Uuid::parse_str(&plugin_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid plugin ID".to_string()))?;
+        .map_err(|_| AppError::validation("plugin_id", "Invalid plugin ID"))?;
     
     // Create execution context from JSON
     let execution_context = crate::services::plugin_system::PluginExecutionContext {
@@ -58,7 +58,7 @@ pub async fn execute_plugin(
     };
     
     let result = plugin_system.execute_plugin(plugin_uuid, execution_context).await?;
-    Ok(serde_json::to_value(result)?)
+    Ok(serde_json::to_value(result).map_err(AppError::from)?)
 }
 
 /// Get marketplace plugins
@@ -68,7 +68,7 @@ pub async fn get_marketplace_plugins(
     search_query: Option<String>,
     limit: Option<u32>,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting marketplace plugins");
     
     let plugin_system = service_manager.plugin_system.read().await;
@@ -86,7 +86,7 @@ pub async fn get_marketplace_plugins(
     });
     
     let plugins = plugin_system.get_marketplace_plugins(category_enum, search_query, limit).await?;
-    Ok(serde_json::to_value(plugins)?)
+    Ok(serde_json::to_value(plugins).map_err(AppError::from)?)
 }
 
 /// V1.2.0 Workflow Engine Commands
@@ -98,15 +98,15 @@ pub async fn create_workflow(
     name: String,
     description: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Creating workflow: {} for user: {}", name, user_id);
     
     let workflow_engine = service_manager.workflow_engine.read().await;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     let definition = workflow_engine.create_workflow(user_uuid, name, description).await?;
-    Ok(serde_json::to_value(definition)?)
+    Ok(serde_json::to_value(definition).map_err(AppError::from)?)
 }
 
 /// Execute workflow
@@ -116,14 +116,14 @@ pub async fn execute_workflow(
     user_id: String,
     parameters: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Executing workflow: {} for user: {}", workflow_id, user_id);
     
     let workflow_engine = service_manager.workflow_engine.read().await;
     let workflow_uuid = Uuid::parse_str(&workflow_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid workflow ID".to_string()))?;
+        .map_err(|_| AppError::validation("workflow_id", "Invalid workflow ID"))?;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     let request = crate::services::workflow_engine::WorkflowExecutionRequest {
         workflow_id: workflow_uuid,
@@ -145,7 +145,7 @@ pub async fn get_workflow_templates(
     category: Option<String>,
     search_query: Option<String>,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting workflow templates");
     
     let workflow_engine = service_manager.workflow_engine.read().await;
@@ -161,7 +161,7 @@ pub async fn get_workflow_templates(
     });
     
     let templates = workflow_engine.get_workflow_templates(category_enum, search_query).await?;
-    Ok(serde_json::to_value(templates)?)
+    Ok(serde_json::to_value(templates).map_err(AppError::from)?)
 }
 
 /// V1.2.0 ML Engine Commands
@@ -173,12 +173,12 @@ pub async fn train_ml_model(
     model_type: String,
     user_id: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Training ML model: {} for user: {}", model_name, user_id);
     
     let ml_engine = service_manager.ml_engine.read().await;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     // Create default training config
     let training_config = crate::services::ml_engine::model_training::TrainingConfig::default();
@@ -194,7 +194,7 @@ pub async fn make_ml_prediction(
     model_id: String,
     features: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Making ML prediction with model: {}", model_id);
     
     let ml_engine = service_manager.ml_engine.read().await;
@@ -210,7 +210,7 @@ pub async fn make_ml_prediction(
     };
     
     let result = ml_engine.predict(request).await?;
-    Ok(serde_json::to_value(result)?)
+    Ok(serde_json::to_value(result).map_err(AppError::from)?)
 }
 
 /// V1.2.0 Cloud Sync Commands
@@ -223,14 +223,14 @@ pub async fn start_cloud_sync(
     provider: String,
     sync_type: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Starting cloud sync for user: {} on device: {}", user_id, device_id);
     
     let cloud_sync = service_manager.cloud_sync.read().await;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     let device_uuid = Uuid::parse_str(&device_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid device ID".to_string()))?;
+        .map_err(|_| AppError::validation("device_id", "Invalid device ID"))?;
     
     let provider_type = match provider.as_str() {
         "aws" => crate::services::cloud_sync::CloudProviderType::AWS,
@@ -271,18 +271,18 @@ pub async fn create_enterprise_user(
     user_request: serde_json::Value,
     created_by: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Creating enterprise user");
     
     let enterprise = service_manager.enterprise.read().await;
     let created_by_uuid = Uuid::parse_str(&created_by)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid creator ID".to_string()))?;
+        .map_err(|_| AppError::validation("creator_id", "Invalid creator ID"))?;
     
     let request: crate::services::enterprise::EnterpriseUserRequest = 
-        serde_json::from_value(user_request)?;
+        serde_json::from_value(user_request).map_err(AppError::from)?;
     
     let user = enterprise.create_user(request, created_by_uuid).await?;
-    Ok(serde_json::to_value(user)?)
+    Ok(serde_json::to_value(user).map_err(AppError::from)?)
 }
 
 /// Check access permissions
@@ -293,12 +293,12 @@ pub async fn check_enterprise_access(
     resource_id: String,
     action: String,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Checking enterprise access for user: {}", user_id);
     
     let enterprise = service_manager.enterprise.read().await;
     let user_uuid = Uuid::parse_str(&user_id)
-        .map_err(|_| crate::error::ResearchError::invalid_request("Invalid user ID".to_string()))?;
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
     
     let request = crate::services::enterprise::AccessRequest {
         user_id: user_uuid,
@@ -310,7 +310,74 @@ pub async fn check_enterprise_access(
     };
     
     let decision = enterprise.check_access(request).await?;
-    Ok(serde_json::to_value(decision)?)
+    Ok(serde_json::to_value(decision).map_err(AppError::from)?)
+}
+
+/// Revoke a role, signing the user out everywhere
+#[tauri::command]
+pub async fn revoke_enterprise_role(
+    revocation_request: serde_json::Value,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Revoking enterprise role");
+
+    let enterprise = service_manager.enterprise.read().await;
+    let request: crate::services::enterprise::RoleRevocationRequest =
+        serde_json::from_value(revocation_request).map_err(AppError::from)?;
+
+    enterprise.revoke_role(request).await.map_err(ErrorPayload::from)
+}
+
+/// List a user's active sessions
+#[tauri::command]
+pub async fn list_enterprise_sessions(
+    user_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<serde_json::Value, ErrorPayload> {
+    info!("Listing enterprise sessions for user: {}", user_id);
+
+    let enterprise = service_manager.enterprise.read().await;
+    let user_uuid = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
+
+    let sessions = enterprise.list_user_sessions(user_uuid).await?;
+    Ok(serde_json::to_value(sessions).map_err(AppError::from)?)
+}
+
+/// Revoke one session, on behalf of the user signed in to `caller_session_id`
+#[tauri::command]
+pub async fn revoke_enterprise_session(
+    session_id: String,
+    caller_session_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<(), ErrorPayload> {
+    info!("Revoking enterprise session: {}", session_id);
+
+    let enterprise = service_manager.enterprise.read().await;
+    let session_uuid = Uuid::parse_str(&session_id)
+        .map_err(|_| AppError::validation("session_id", "Invalid session ID"))?;
+    let caller_session_uuid = Uuid::parse_str(&caller_session_id)
+        .map_err(|_| AppError::validation("caller_session_id", "Invalid session ID"))?;
+
+    enterprise.revoke_session(session_uuid, caller_session_uuid).await.map_err(ErrorPayload::from)
+}
+
+/// Log a user out everywhere, on behalf of the user signed in to `caller_session_id`
+#[tauri::command]
+pub async fn revoke_all_enterprise_sessions(
+    user_id: String,
+    caller_session_id: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<usize, ErrorPayload> {
+    info!("Revoking all enterprise sessions for user: {}", user_id);
+
+    let enterprise = service_manager.enterprise.read().await;
+    let user_uuid = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::validation("user_id", "Invalid user ID"))?;
+    let caller_session_uuid = Uuid::parse_str(&caller_session_id)
+        .map_err(|_| AppError::validation("caller_session_id", "Invalid session ID"))?;
+
+    enterprise.log_out_everywhere(user_uuid, caller_session_uuid).await.map_err(ErrorPayload::from)
 }
 
 /// V2.0.0 Distributed System Commands
@@ -320,14 +387,14 @@ pub async fn check_enterprise_access(
 pub async fn join_cluster(
     node_config: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Joining cluster");
     
     let distributed = service_manager.distributed.read().await;
     let node: crate::services::distributed::ClusterNode = 
-        serde_json::from_value(node_config)?;
+        serde_json::from_value(node_config).map_err(AppError::from)?;
     
-    distributed.join_cluster(node).await
+    distributed.join_cluster(node).await.map_err(ErrorPayload::from)
 }
 
 /// Deploy service to cluster
@@ -335,12 +402,12 @@ pub async fn join_cluster(
 pub async fn deploy_service_to_cluster(
     deployment_request: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Deploying service to cluster");
     
     let distributed = service_manager.distributed.read().await;
     let request: crate::services::distributed::DeploymentRequest = 
-        serde_json::from_value(deployment_request)?;
+        serde_json::from_value(deployment_request).map_err(AppError::from)?;
     
     let deployment_id = distributed.deploy_service(request).await?;
     Ok(deployment_id.to_string())
@@ -353,14 +420,14 @@ pub async fn deploy_service_to_cluster(
 pub async fn register_ai_agent(
     agent: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Registering AI agent");
     
     let ai_orchestration = service_manager.ai_orchestration.read().await;
     let agent_config: crate::services::ai_orchestration::AIAgent = 
-        serde_json::from_value(agent)?;
+        serde_json::from_value(agent).map_err(AppError::from)?;
     
-    ai_orchestration.register_agent(agent_config).await
+    ai_orchestration.register_agent(agent_config).await.map_err(ErrorPayload::from)
 }
 
 /// Submit AI task
@@ -368,12 +435,12 @@ pub async fn register_ai_agent(
 pub async fn submit_ai_task(
     task: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Submitting AI task");
     
     let ai_orchestration = service_manager.ai_orchestration.read().await;
     let task_config: crate::services::ai_orchestration::AITask = 
-        serde_json::from_value(task)?;
+        serde_json::from_value(task).map_err(AppError::from)?;
     
     let task_id = ai_orchestration.submit_task(task_config).await?;
     Ok(task_id.to_string())
@@ -386,12 +453,12 @@ pub async fn submit_ai_task(
 pub async fn start_realtime_collaboration(
     collaboration_request: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<String> {
+) -> Result<String, ErrorPayload> {
     info!("Starting real-time collaboration");
     
     let realtime_collaboration = service_manager.realtime_collaboration.read().await;
     let request: crate::services::realtime_collaboration::CollaborationRequest = 
-        serde_json::from_value(collaboration_request)?;
+        serde_json::from_value(collaboration_request).map_err(AppError::from)?;
     
     let session_id = realtime_collaboration.start_collaboration(request).await?;
     Ok(session_id.to_string())
@@ -402,24 +469,24 @@ pub async fn start_realtime_collaboration(
 pub async fn send_collaboration_message(
     message: serde_json::Value,
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<()> {
+) -> Result<(), ErrorPayload> {
     info!("Sending collaboration message");
     
     let realtime_collaboration = service_manager.realtime_collaboration.read().await;
     let chat_message: crate::services::realtime_collaboration::ChatMessage = 
-        serde_json::from_value(message)?;
+        serde_json::from_value(message).map_err(AppError::from)?;
     
-    realtime_collaboration.send_chat_message(chat_message).await
+    realtime_collaboration.send_chat_message(chat_message).await.map_err(ErrorPayload::from)
 }
 
 /// Get collaboration statistics
 #[tauri::command]
 pub async fn get_collaboration_statistics(
     service_manager: State<'_, ServiceManager>,
-) -> AppResult<serde_json::Value> {
+) -> Result<serde_json::Value, ErrorPayload> {
     info!("Getting collaboration statistics");
     
     let realtime_collaboration = service_manager.realtime_collaboration.read().await;
     let stats = realtime_collaboration.get_collaboration_stats().await?;
-    Ok(serde_json::to_value(stats)?)
+    Ok(serde_json::to_value(stats).map_err(AppError::from)?)
 }
@@ -9,6 +9,12 @@ pub enum ApiError {
     
     #[error("API key not found: {key_id}")]
     KeyNotFound { key_id: String },
+
+    #[error("API key {key_id} expired at {expired_at}")]
+    KeyExpired { key_id: String, expired_at: String },
+    
+    #[error("API key {key_id} is not accessible to {caller}")]
+    KeyAccessDenied { key_id: String, caller: String },
     
     #[error("Rate limit exceeded for {service}: {current}/{limit}")]
     RateLimitExceeded {
@@ -47,6 +53,9 @@ pub enum ApiError {
     
     #[error("Health check failed for {service}: {message}")]
     HealthCheckFailed { service: String, message: String },
+
+    #[error("{service} refused the request under its content policy: {reason}")]
+    ContentPolicyRejected { service: String, reason: String },
 }
 
 impl ApiError {
@@ -66,6 +75,13 @@ impl ApiError {
         }
     }
     
+    /// Create a new quota exceeded error
+    pub fn quota_exceeded(service: impl Into<String>) -> Self {
+        Self::QuotaExceeded {
+            service: service.into(),
+        }
+    }
+
     /// Create a new request failed error
     pub fn request_failed(
         service: impl Into<String>,
@@ -94,6 +110,30 @@ impl ApiError {
         }
     }
     
+    /// Create a new key expired error
+    pub fn key_expired(key_id: impl Into<String>, expired_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self::KeyExpired {
+            key_id: key_id.into(),
+            expired_at: expired_at.to_rfc3339(),
+        }
+    }
+
+    /// Create a new key access denied error
+    pub fn key_access_denied(key_id: impl Into<String>, caller: impl Into<String>) -> Self {
+        Self::KeyAccessDenied {
+            key_id: key_id.into(),
+            caller: caller.into(),
+        }
+    }
+
+    /// Create a new content policy rejection error
+    pub fn content_policy_rejected(service: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::ContentPolicyRejected {
+            service: service.into(),
+            reason: reason.into(),
+        }
+    }
+
     /// Check if this error indicates the service is temporarily unavailable
     pub fn is_temporary(&self) -> bool {
         match self {
@@ -119,4 +159,9 @@ impl ApiError {
             ApiError::InvalidKey { .. } | ApiError::AuthenticationFailed { .. }
         )
     }
+
+    /// Check if the provider refused the query itself; retrying it anywhere will not help
+    pub fn is_content_policy_rejection(&self) -> bool {
+        matches!(self, ApiError::ContentPolicyRejected { .. })
+    }
 }
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, AppError, PerformanceError, ResearchError, SecurityError, StorageError};
+
+/// Stable, machine-readable error codes clients can branch on. New codes may be added;
+/// existing ones keep their meaning. See docs/api/errors.md.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    RateLimited,
+    QuotaExceeded,
+    ContentPolicyRejected,
+    ProviderUnavailable,
+    ProviderError,
+    Timeout,
+    NetworkError,
+    OfflineMode,
+    Cancelled,
+    ExecutionFailed,
+    ResourceExhausted,
+    ConfigurationError,
+    StorageError,
+    SecurityError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ErrorCode::ContentPolicyRejected => "CONTENT_POLICY_REJECTED",
+            ErrorCode::ProviderUnavailable => "PROVIDER_UNAVAILABLE",
+            ErrorCode::ProviderError => "PROVIDER_ERROR",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::NetworkError => "NETWORK_ERROR",
+            ErrorCode::OfflineMode => "OFFLINE_MODE",
+            ErrorCode::Cancelled => "CANCELLED",
+            ErrorCode::ExecutionFailed => "EXECUTION_FAILED",
+            ErrorCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            ErrorCode::ConfigurationError => "CONFIGURATION_ERROR",
+            ErrorCode::StorageError => "STORAGE_ERROR",
+            ErrorCode::SecurityError => "SECURITY_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error as clients receive it from commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    pub code: ErrorCode,
+    pub message: String,
+    /// The same request may succeed later, so backing off and retrying is worthwhile
+    pub retryable: bool,
+    /// The error's `kind`, such as `Api.RateLimitExceeded`, and its fields
+    pub details: serde_json::Value,
+}
+
+impl std::fmt::Display for ErrorPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl From<AppError> for ErrorPayload {
+    fn from(error: AppError) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            retryable: error.is_retryable(),
+            details: details(&error),
+        }
+    }
+}
+
+fn api_code(error: &ApiError) -> ErrorCode {
+    match error {
+        ApiError::InvalidKey { .. } | ApiError::KeyExpired { .. } | ApiError::AuthenticationFailed { .. } => ErrorCode::Unauthorized,
+        ApiError::KeyNotFound { .. } => ErrorCode::NotFound,
+        ApiError::KeyAccessDenied { .. } => ErrorCode::Forbidden,
+        ApiError::RateLimitExceeded { .. } => ErrorCode::RateLimited,
+        ApiError::QuotaExceeded { .. } => ErrorCode::QuotaExceeded,
+        ApiError::ServiceUnavailable { .. } | ApiError::HealthCheckFailed { .. } => ErrorCode::ProviderUnavailable,
+        ApiError::ConnectionTimeout { .. } => ErrorCode::Timeout,
+        ApiError::RequestFailed { status_code, .. } => match status_code {
+            401 | 403 => ErrorCode::Unauthorized,
+            429 => ErrorCode::RateLimited,
+            500.. => ErrorCode::ProviderUnavailable,
+            _ => ErrorCode::ProviderError,
+        },
+        ApiError::InvalidResponse { .. } => ErrorCode::ProviderError,
+        ApiError::InvalidConfiguration { .. } => ErrorCode::ConfigurationError,
+        ApiError::RotationFailed { .. } => ErrorCode::InternalError,
+        ApiError::ContentPolicyRejected { .. } => ErrorCode::ContentPolicyRejected,
+    }
+}
+
+fn research_code(error: &ResearchError) -> ErrorCode {
+    match error {
+        ResearchError::WorkflowNotFound { .. }
+        | ResearchError::TemplateNotFound { .. }
+        | ResearchError::ScheduleNotFound { .. }
+        | ResearchError::PromptNotFound { .. } => ErrorCode::NotFound,
+        ResearchError::InvalidWorkflowConfig { .. }
+        | ResearchError::InvalidTemplate { .. }
+        | ResearchError::InvalidQuery { .. }
+        | ResearchError::InvalidSchedule { .. }
+        | ResearchError::InvalidPromptExperiment { .. }
+        | ResearchError::UnsupportedMethodology { .. }
+        | ResearchError::IncompatibleDependencies { .. } => ErrorCode::InvalidRequest,
+        ResearchError::ExecutionFailed { .. } => ErrorCode::ExecutionFailed,
+        ResearchError::ResultProcessingFailed { .. } => ErrorCode::InternalError,
+        ResearchError::WorkflowTimeout { .. } => ErrorCode::Timeout,
+        ResearchError::WorkflowCancelled { .. } => ErrorCode::Cancelled,
+        ResearchError::QueueFull | ResearchError::ResourceLimitExceeded { .. } => ErrorCode::ResourceExhausted,
+        ResearchError::InsufficientQuota { .. } => ErrorCode::QuotaExceeded,
+        ResearchError::IdempotencyKeyConflict { .. } => ErrorCode::Conflict,
+        ResearchError::DependencyFailed { .. } => ErrorCode::ProviderUnavailable,
+    }
+}
+
+fn storage_code(error: &StorageError) -> ErrorCode {
+    match error {
+        StorageError::FileNotFound { .. } => ErrorCode::NotFound,
+        StorageError::ResidencyViolation { .. } => ErrorCode::Forbidden,
+        StorageError::DiskFull { .. } | StorageError::QuotaExceeded { .. } => ErrorCode::ResourceExhausted,
+        _ => ErrorCode::StorageError,
+    }
+}
+
+fn security_code(error: &SecurityError) -> ErrorCode {
+    match error {
+        SecurityError::AuthenticationRequired | SecurityError::SessionExpired | SecurityError::InvalidMasterPassword => {
+            ErrorCode::Unauthorized
+        }
+        SecurityError::SessionLimitExceeded => ErrorCode::ResourceExhausted,
+        _ => ErrorCode::SecurityError,
+    }
+}
+
+fn performance_code(error: &PerformanceError) -> ErrorCode {
+    match error {
+        PerformanceError::QueueFull { .. } | PerformanceError::ResourceExhaustion { .. } => ErrorCode::ResourceExhausted,
+        PerformanceError::TimeoutExceeded { .. } => ErrorCode::Timeout,
+        PerformanceError::ServiceDegraded { .. } => ErrorCode::ProviderUnavailable,
+        _ => ErrorCode::InternalError,
+    }
+}
+
+impl AppError {
+    /// The stable code clients see for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Api(e) => api_code(e),
+            AppError::Research(e) => research_code(e),
+            AppError::Storage(e) => storage_code(e),
+            AppError::Security(e) => security_code(e),
+            AppError::Performance(e) => performance_code(e),
+            AppError::Configuration { .. } => ErrorCode::ConfigurationError,
+            AppError::Validation { .. } => ErrorCode::InvalidRequest,
+            AppError::ServiceUnavailable { .. } => ErrorCode::ProviderUnavailable,
+            AppError::RateLimitExceeded { .. } => ErrorCode::RateLimited,
+            AppError::Authentication { .. } => ErrorCode::Unauthorized,
+            AppError::PermissionDenied { .. } => ErrorCode::Forbidden,
+            AppError::NotFound { .. } => ErrorCode::NotFound,
+            AppError::Internal { .. } | AppError::Serialization { .. } => ErrorCode::InternalError,
+            AppError::ExternalService { .. } => ErrorCode::ProviderError,
+            AppError::Timeout { .. } => ErrorCode::Timeout,
+            AppError::Network { .. } => ErrorCode::NetworkError,
+            AppError::Io { .. } => ErrorCode::StorageError,
+            AppError::Offline { .. } => ErrorCode::OfflineMode,
+        }
+    }
+}
+
+/// The variant path of `error` as `kind`, alongside the innermost variant's fields
+fn details(error: &AppError) -> serde_json::Value {
+    let mut value = serde_json::to_value(error).unwrap_or(serde_json::Value::Null);
+    let mut kind = Vec::new();
+    loop {
+        match value {
+            // Unit variants serialize as their name
+            serde_json::Value::String(name) => {
+                kind.push(name);
+                value = serde_json::Value::Object(serde_json::Map::new());
+                break;
+            }
+            // Variants wrap their content in a single key named after them; fields are snake_case
+            serde_json::Value::Object(ref mut map)
+                if map.len() == 1 && map.keys().all(|k| k.starts_with(|c: char| c.is_ascii_uppercase())) =>
+            {
+                let (name, inner) = map.iter_mut().next().map(|(k, v)| (k.clone(), v.take())).unwrap_or_default();
+                kind.push(name);
+                value = inner;
+            }
+            _ => break,
+        }
+    }
+
+    let mut details = match value {
+        serde_json::Value::Object(fields) => fields,
+        serde_json::Value::Null => serde_json::Map::new(),
+        other => serde_json::Map::from_iter([("value".to_string(), other)]),
+    };
+    details.insert("kind".to_string(), serde_json::Value::String(kind.join(".")));
+    serde_json::Value::Object(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_carries_a_stable_code_retryability_and_fields() {
+        let payload = ErrorPayload::from(AppError::from(ApiError::rate_limit_exceeded("serpapi", 100, 100)));
+        assert_eq!(payload.code, ErrorCode::RateLimited);
+        assert!(payload.retryable);
+        assert_eq!(payload.details["kind"], "Api.RateLimitExceeded");
+        assert_eq!(payload.details["service"], "serpapi");
+        assert_eq!(serde_json::to_value(&payload).unwrap()["code"], "RATE_LIMITED");
+
+        let payload = ErrorPayload::from(AppError::from(ApiError::invalid_key("tavily")));
+        assert_eq!(payload.code, ErrorCode::Unauthorized);
+        assert!(!payload.retryable);
+
+        let payload = ErrorPayload::from(AppError::validation("rating", "must be between 1 and 5"));
+        assert_eq!(payload.code, ErrorCode::InvalidRequest);
+        assert_eq!(payload.details["field"], "rating");
+        assert_eq!(payload.message, "Validation error: rating: must be between 1 and 5");
+
+        let payload = ErrorPayload::from(AppError::from(ResearchError::QueueFull));
+        assert_eq!(payload.code, ErrorCode::ResourceExhausted);
+        assert!(payload.retryable);
+        assert_eq!(payload.details, serde_json::json!({ "kind": "Research.QueueFull" }));
+    }
+}
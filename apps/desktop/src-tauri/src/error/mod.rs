@@ -6,12 +6,14 @@ pub mod research_error;
 pub mod storage_error;
 pub mod security_error;
 pub mod performance_error;
+pub mod error_payload;
 
 pub use api_error::ApiError;
 pub use research_error::ResearchError;
 pub use storage_error::StorageError;
 pub use security_error::SecurityError;
 pub use performance_error::{PerformanceError, CacheError, DeduplicationError, BackgroundProcessingError, ConnectionPoolError};
+pub use error_payload::{ErrorCode, ErrorPayload};
 
 /// Main application error type
 #[derive(Error, Debug, Serialize, Deserialize)]
@@ -69,6 +71,9 @@ pub enum AppError {
     
     #[error("IO error: {message}")]
     Io { message: String },
+
+    #[error("Offline mode: {operation} is disabled while outbound network access is turned off")]
+    Offline { operation: String },
 }
 
 impl AppError {
@@ -116,39 +121,45 @@ impl AppError {
         }
     }
     
+    /// Create a new offline mode error for an operation that needs the network
+    pub fn offline(operation: impl Into<String>) -> Self {
+        Self::Offline {
+            operation: operation.into(),
+        }
+    }
+
+    /// Check if this error comes from offline mode refusing an outbound call
+    pub fn is_offline(&self) -> bool {
+        matches!(self, AppError::Offline { .. })
+    }
+    
     /// Get the error code for this error
     pub fn error_code(&self) -> &'static str {
-        match self {
-            AppError::Api(_) => "API_ERROR",
-            AppError::Research(_) => "RESEARCH_ERROR",
-            AppError::Storage(_) => "STORAGE_ERROR",
-            AppError::Security(_) => "SECURITY_ERROR",
-            AppError::Performance(_) => "PERFORMANCE_ERROR",
-            AppError::Configuration { .. } => "CONFIGURATION_ERROR",
-            AppError::Validation { .. } => "VALIDATION_ERROR",
-            AppError::ServiceUnavailable { .. } => "SERVICE_UNAVAILABLE",
-            AppError::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
-            AppError::Authentication { .. } => "AUTHENTICATION_ERROR",
-            AppError::PermissionDenied { .. } => "PERMISSION_DENIED",
-            AppError::NotFound { .. } => "NOT_FOUND",
-            AppError::Internal { .. } => "INTERNAL_ERROR",
-            AppError::ExternalService { .. } => "EXTERNAL_SERVICE_ERROR",
-            AppError::Timeout { .. } => "TIMEOUT_ERROR",
-            AppError::Network { .. } => "NETWORK_ERROR",
-            AppError::Serialization { .. } => "SERIALIZATION_ERROR",
-            AppError::Io { .. } => "IO_ERROR",
-        }
+        self.code().as_str()
     }
     
     /// Check if this error is retryable
     pub fn is_retryable(&self) -> bool {
-        matches!(
-            self,
-            AppError::Network { .. }
-                | AppError::Timeout { .. }
-                | AppError::ServiceUnavailable { .. }
-                | AppError::ExternalService { .. }
-        )
+        match self {
+            AppError::Api(e) => e.is_temporary() || matches!(e, ApiError::RateLimitExceeded { .. }),
+            AppError::Research(e) => e.is_retryable(),
+            AppError::Storage(e) => matches!(e, StorageError::LockFailed { .. } | StorageError::TransactionFailed { .. }),
+            AppError::Performance(e) => matches!(
+                e,
+                PerformanceError::QueueFull { .. }
+                    | PerformanceError::ResourceExhaustion { .. }
+                    | PerformanceError::TimeoutExceeded { .. }
+                    | PerformanceError::ServiceDegraded { .. }
+            ),
+            _ => matches!(
+                self,
+                AppError::Network { .. }
+                    | AppError::Timeout { .. }
+                    | AppError::ServiceUnavailable { .. }
+                    | AppError::ExternalService { .. }
+                    | AppError::RateLimitExceeded { .. }
+            ),
+        }
     }
     
     /// Check if this error should be logged as an error level
@@ -47,6 +47,27 @@ pub enum ResearchError {
         limit: u32,
     },
     
+    #[error("Schedule not found: {schedule_id}")]
+    ScheduleNotFound { schedule_id: String },
+
+    #[error("Invalid schedule: {message}")]
+    InvalidSchedule { message: String },
+
+    #[error("Prompt not found: {prompt_id}")]
+    PromptNotFound { prompt_id: String },
+
+    #[error("Invalid prompt experiment: {message}")]
+    InvalidPromptExperiment { message: String },
+
+    #[error("Insufficient quota: {message}")]
+    InsufficientQuota { message: String },
+
+    #[error("Idempotency key {key} was already used for a different request")]
+    IdempotencyKeyConflict { key: String },
+
+    #[error("Incompatible dependencies: {message}")]
+    IncompatibleDependencies { message: String },
+    
     #[error("Dependency failed: {dependency}: {message}")]
     DependencyFailed {
         dependency: String,
@@ -77,6 +98,55 @@ impl ResearchError {
         }
     }
 
+    /// Create a new schedule not found error
+    pub fn schedule_not_found(schedule_id: impl Into<String>) -> Self {
+        Self::ScheduleNotFound {
+            schedule_id: schedule_id.into(),
+        }
+    }
+
+    /// Create a new invalid schedule error
+    pub fn invalid_schedule(message: impl Into<String>) -> Self {
+        Self::InvalidSchedule {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new prompt not found error
+    pub fn prompt_not_found(prompt_id: impl Into<String>) -> Self {
+        Self::PromptNotFound {
+            prompt_id: prompt_id.into(),
+        }
+    }
+
+    /// Create a new invalid prompt experiment error
+    pub fn invalid_prompt_experiment(message: impl Into<String>) -> Self {
+        Self::InvalidPromptExperiment {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new insufficient quota error
+    pub fn insufficient_quota(message: impl Into<String>) -> Self {
+        Self::InsufficientQuota {
+            message: message.into(),
+        }
+    }
+
+    /// Create a new idempotency key conflict error
+    pub fn idempotency_key_conflict(key: impl Into<String>) -> Self {
+        Self::IdempotencyKeyConflict {
+            key: key.into(),
+        }
+    }
+
+    /// Create a new incompatible dependencies error
+    pub fn incompatible_dependencies(message: impl Into<String>) -> Self {
+        Self::IncompatibleDependencies {
+            message: message.into(),
+        }
+    }
+
     /// Create a new resource limit exceeded error
     pub fn resource_limit_exceeded(message: impl Into<String>) -> Self {
         Self::ResourceLimitExceeded {
@@ -93,6 +163,7 @@ impl ResearchError {
             ResearchError::WorkflowTimeout { .. }
                 | ResearchError::QueueFull
                 | ResearchError::ResourceLimitExceeded { .. }
+                | ResearchError::InsufficientQuota { .. }
                 | ResearchError::DependencyFailed { .. }
         )
     }
@@ -103,6 +174,8 @@ impl ResearchError {
             self,
             ResearchError::InvalidWorkflowConfig { .. }
                 | ResearchError::InvalidTemplate { .. }
+                | ResearchError::InvalidSchedule { .. }
+                | ResearchError::InvalidPromptExperiment { .. }
                 | ResearchError::UnsupportedMethodology { .. }
         )
     }
@@ -43,6 +43,9 @@ pub enum StorageError {
     
     #[error("Storage quota exceeded: {current}/{limit} bytes")]
     QuotaExceeded { current: u64, limit: u64 },
+
+    #[error("Data residency violation: {message}")]
+    ResidencyViolation { message: String },
 }
 
 impl StorageError {
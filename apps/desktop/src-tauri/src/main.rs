@@ -3,7 +3,6 @@
 
 use tauri::Manager;
 use tracing::{info, error};
-use tracing_subscriber;
 
 mod commands;
 mod services;
@@ -12,17 +11,14 @@ mod utils;
 mod error;
 
 use commands::*;
-use services::ServiceManager;
+use services::{Service, ServiceManager};
+use services::data_persistence::DatabaseBackendKind;
 use error::AppResult;
 
-/// Initialize the application logging system
-fn init_logging() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info".into())
-        )
-        .init();
+/// Initialize the application logging system, exporting spans to an OTLP
+/// collector when one is configured
+fn init_logging() -> utils::TelemetryGuard {
+    utils::init_tracing()
 }
 
 /// Initialize the application services
@@ -37,8 +33,8 @@ async fn init_services() -> AppResult<ServiceManager> {
 
 #[tokio::main]
 async fn main() {
-    // Initialize logging
-    init_logging();
+    // Initialize logging; the guard flushes pending spans on exit
+    let _telemetry = init_logging();
     info!("Starting Free Deep Research System...");
 
     // Initialize services
@@ -60,22 +56,44 @@ async fn main() {
 
             // API Management commands
             api_management::get_api_keys,
+            api_management::list_api_keys,
             api_management::add_api_key,
             api_management::update_api_key,
             api_management::delete_api_key,
             api_management::test_api_key,
             api_management::import_api_keys,
+            api_management::bulk_update_api_key_status,
+            api_management::bulk_delete_api_keys,
+            api_management::bulk_test_api_keys,
             api_management::export_api_keys,
             api_management::import_api_keys_csv,
             api_management::import_api_keys_json,
             api_management::export_api_keys_csv,
             api_management::export_api_keys_json,
             api_management::get_api_key_usage_stats,
+            api_management::get_usage_reconciliation_report,
+            api_management::export_usage_reconciliation_csv,
+            api_management::export_usage_reconciliation_json,
+            api_management::check_usage_divergence,
+            api_management::get_usage_divergence_history,
+            api_management::get_divergence_config,
+            api_management::update_divergence_config,
+            api_management::get_notification_preferences,
+            api_management::update_notification_preferences,
+            api_management::get_notification_inbox,
+            api_management::get_egress_profiles,
+            api_management::update_egress_profiles,
+            api_management::get_content_cache_stats,
+            api_management::get_content_cache_config,
+            api_management::update_content_cache_config,
+            api_management::get_crawl_policy_config,
+            api_management::update_crawl_policy_config,
             api_management::get_api_keys_with_status,
             // Rate limiting commands
             api_management::can_make_request,
             api_management::get_key_usage_status,
             api_management::record_api_request,
+            api_management::get_tenant_key_usage,
             api_management::set_emergency_stop,
             api_management::is_emergency_stop_enabled,
             api_management::get_recent_alerts,
@@ -102,6 +120,13 @@ async fn main() {
             api_management::get_service_config,
             api_management::get_all_service_configs,
             api_management::update_service_config,
+            api_management::get_quota_config,
+            api_management::update_quota_config,
+            api_management::simulate_rate_limit_config,
+            api_management::get_provider_recording,
+            api_management::delete_provider_recording,
+            api_management::get_model_routing_policy,
+            api_management::update_model_routing_policy,
             api_management::get_service_endpoints,
             api_management::get_registered_services,
             api_management::generate_service_status_report,
@@ -113,11 +138,26 @@ async fn main() {
             commands::research_workflow::cancel_research_workflow,
             commands::research_workflow::get_research_workflow,
             commands::research_workflow::get_all_research_workflows,
+            commands::research_workflow::list_research_workflows,
             commands::research_workflow::get_research_workflows_by_status,
             commands::research_workflow::delete_research_workflow,
+            commands::research_workflow::search_workflows,
+            commands::research_workflow::browse_research_workflows,
+            commands::research_workflow::get_workflow_organization,
+            commands::research_workflow::tag_research_workflow,
+            commands::research_workflow::move_research_workflow,
+            commands::research_workflow::explain_workflow,
+            commands::research_workflow::rate_workflow,
+            commands::research_workflow::recommend_methodology,
+            commands::research_workflow::export_workflow_bundle,
+            commands::research_workflow::import_workflow_bundle,
+            commands::research_workflow::rerun_failed_workflows,
+            commands::research_workflow::run_load_test,
             commands::research_workflow::get_workflow_status,
             commands::research_workflow::get_workflow_progress,
             commands::research_workflow::get_workflow_results,
+            commands::research_workflow::get_partial_workflow_results,
+            commands::research_workflow::subscribe_workflow_results,
             commands::research_workflow::get_workflow_statistics,
             // Queue management commands
             commands::research_workflow::enqueue_research_workflow,
@@ -180,7 +220,12 @@ async fn main() {
             commands::output_processor::compare_workflows,
             commands::output_processor::analyze_workflow_similarity,
             commands::output_processor::analyze_workflow_performance,
+            commands::output_processor::get_workflow_execution_metrics,
+            commands::output_processor::get_performance_diagnostics,
+            commands::output_processor::diff_workflow_results,
             commands::output_processor::get_analysis_statistics,
+            commands::output_processor::update_incremental_analysis,
+            commands::output_processor::recompute_incremental_analysis,
             // Template management commands
             commands::template_management::create_research_template,
             commands::template_management::get_research_template,
@@ -198,6 +243,19 @@ async fn main() {
             commands::template_management::get_all_template_metrics,
             commands::template_management::get_template_recommendations,
             commands::template_management::get_template_statistics,
+            commands::research_schedule::create_research_schedule,
+            commands::research_schedule::get_research_schedules,
+            commands::research_schedule::pause_research_schedule,
+            commands::research_schedule::resume_research_schedule,
+            commands::research_schedule::delete_research_schedule,
+            commands::research_schedule::run_research_schedule_now,
+            commands::research_schedule::get_research_schedule_runs,
+            commands::prompt_templates::get_prompt_templates,
+            commands::prompt_templates::get_prompt_template_versions,
+            commands::prompt_templates::save_prompt_template,
+            commands::prompt_templates::start_prompt_experiment,
+            commands::prompt_templates::stop_prompt_experiment,
+            commands::prompt_templates::get_prompt_experiments,
             
             // Research commands
             research::create_research_workflow,
@@ -210,12 +268,24 @@ async fn main() {
             commands::config::get_configuration,
             commands::config::update_configuration,
             commands::config::reset_configuration,
+            commands::config::rotate_database_key,
+            commands::config::rotate_master_key,
+            commands::config::create_backup,
+            commands::config::list_backups,
+            commands::config::verify_backup,
+            commands::config::restore_backup,
+            commands::config::get_redaction_config,
+            commands::config::update_redaction_config,
+            commands::config::preview_result_redaction,
             
             // Monitoring commands
             monitoring::get_system_metrics,
             monitoring::get_api_usage_stats,
             monitoring::get_service_health,
             monitoring::get_audit_logs,
+            monitoring::list_audit_logs,
+            monitoring::get_circuit_breaker_states,
+            monitoring::reset_circuit_breaker,
 
             // Analytics commands
             analytics::get_analytics_dashboard_data,
@@ -244,6 +314,12 @@ async fn main() {
             performance::get_connection_pool_statistics,
             performance::performance_health_check,
 
+            // Enterprise session commands
+            commands::v1_2_v2_0_features::revoke_enterprise_role,
+            commands::v1_2_v2_0_features::list_enterprise_sessions,
+            commands::v1_2_v2_0_features::revoke_enterprise_session,
+            commands::v1_2_v2_0_features::revoke_all_enterprise_sessions,
+
             // V3.0.0 Commands - Global Intelligence Network
             // Federated Research commands
             federated_research::register_federated_organization,
@@ -264,6 +340,10 @@ async fn main() {
             ai_marketplace::publish_research_methodology,
             ai_marketplace::search_marketplace,
             ai_marketplace::install_ai_agent,
+            ai_marketplace::uninstall_marketplace_content,
+            ai_marketplace::get_installed_marketplace_content,
+            ai_marketplace::run_installed_agent,
+            ai_marketplace::get_agent_resource_usage,
             ai_marketplace::submit_community_rating,
             ai_marketplace::get_marketplace_user_analytics,
             ai_marketplace::get_featured_agents,
@@ -306,6 +386,7 @@ async fn main() {
             knowledge_graph::traverse_knowledge_graph,
             knowledge_graph::create_graph_visualization,
             knowledge_graph::extract_knowledge_from_source,
+            knowledge_graph::extract_document_structure,
             knowledge_graph::get_knowledge_graph_statistics,
             knowledge_graph::search_knowledge_nodes,
             knowledge_graph::get_node_neighbors,
@@ -389,12 +470,50 @@ async fn system_health_check(
         }
     }
 
-    // Check database connectivity (if available)
-    health_components.insert("database".to_string(), serde_json::json!({
-        "status": "healthy",
-        "type": "sqlite",
-        "details": "Local SQLite database operational"
-    }));
+    // Check database connectivity
+    {
+        let data_persistence = service_manager.data_persistence.read().await;
+        let backend = match data_persistence.backend_kind() {
+            DatabaseBackendKind::Sqlite => "sqlite",
+            DatabaseBackendKind::Postgres => "postgres",
+        };
+        match data_persistence.health_check().await {
+            Ok(()) => {
+                health_components.insert("database".to_string(), serde_json::json!({
+                    "status": "healthy",
+                    "type": backend,
+                    "details": format!("{} database operational", backend)
+                }));
+            }
+            Err(e) => {
+                overall_status = "unhealthy";
+                health_components.insert("database".to_string(), serde_json::json!({
+                    "status": "unhealthy",
+                    "type": backend,
+                    "error": e.to_string()
+                }));
+            }
+        }
+    }
+
+    // Report backup freshness; a failed or stale backup degrades the system status
+    {
+        let data_persistence = service_manager.data_persistence.read().await;
+        if let Some(backup_status) = data_persistence.backup_status() {
+            let degraded = backup_status.is_degraded(chrono::Utc::now(), chrono::Duration::hours(48));
+            if degraded && overall_status == "healthy" {
+                overall_status = "degraded";
+            }
+            health_components.insert("backups".to_string(), serde_json::json!({
+                "status": if degraded { "degraded" } else { "healthy" },
+                "last_success_at": backup_status.last_success_at.map(|at| at.to_rfc3339()),
+                "last_backup_id": backup_status.last_backup_id,
+                "last_error": backup_status.last_error,
+                "last_upload_error": backup_status.last_upload_error,
+                "backup_count": backup_status.backup_count
+            }));
+        }
+    }
 
     // Check API services
     health_components.insert("api_services".to_string(), serde_json::json!({
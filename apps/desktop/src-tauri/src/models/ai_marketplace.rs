@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// Marketplace user model
@@ -37,6 +38,9 @@ pub struct AIAgentMarketplace {
     pub rating_count: u32,
     pub tags: Vec<String>,
     pub requirements: SystemRequirements,
+    /// Agents and methodologies the agent needs installed alongside it
+    #[serde(default)]
+    pub dependencies: Vec<ContentDependency>,
     pub status: AgentStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -80,6 +84,16 @@ pub struct ResourceRequirements {
     pub disk_space_mb: u32,
     pub network_access: bool,
     pub special_permissions: Vec<String>,
+    /// Hosts the agent may fetch from when `network_access` is set; a host also
+    /// allows its subdomains
+    #[serde(default)]
+    pub network_allowlist: Vec<String>,
+    /// CPU time the agent needs per run; capped by the sandbox's own limit
+    #[serde(default)]
+    pub max_cpu_time_seconds: Option<u32>,
+    /// Wall-clock time the agent needs per run; capped by the sandbox's own limit
+    #[serde(default)]
+    pub max_wall_clock_seconds: Option<u32>,
 }
 
 /// Pricing model
@@ -114,6 +128,10 @@ pub enum AgentStatus {
     UnderReview,
 }
 
+fn initial_version() -> String {
+    "1.0.0".to_string()
+}
+
 /// Research methodology in marketplace
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResearchMethodologyMarketplace {
@@ -122,6 +140,11 @@ pub struct ResearchMethodologyMarketplace {
     pub description: String,
     pub creator_id: Uuid,
     pub methodology_config: MethodologyConfiguration,
+    #[serde(default = "initial_version")]
+    pub version: String,
+    /// Agents and methodologies the methodology needs installed alongside it
+    #[serde(default)]
+    pub dependencies: Vec<ContentDependency>,
     pub category: MethodologyCategory,
     pub complexity_level: u8, // 1-5 scale
     pub estimated_time_minutes: Option<u32>,
@@ -306,6 +329,67 @@ pub struct AgentInstallationResult {
     pub error_message: Option<String>,
     pub installed_version: Option<String>,
     pub installation_path: Option<String>,
+    /// Dependencies installed along with the agent, in install order
+    #[serde(default)]
+    pub installed_dependencies: Vec<InstalledContent>,
+}
+
+/// Kind of marketplace content a dependency refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentKind {
+    Agent,
+    Methodology,
+}
+
+impl ContentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentKind::Agent => "agent",
+            ContentKind::Methodology => "methodology",
+        }
+    }
+}
+
+/// One entry of a published item's dependency manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentDependency {
+    pub kind: ContentKind,
+    pub id: Uuid,
+    /// Versions that work, e.g. `^1.2`, `>=1.0, <2.0` or `*`
+    pub version_requirement: String,
+}
+
+/// Marketplace content installed for a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledContent {
+    pub user_id: Uuid,
+    pub kind: ContentKind,
+    pub content_id: Uuid,
+    pub name: String,
+    pub version: String,
+    /// Installed to satisfy another item's manifest rather than by the user
+    pub installed_as_dependency: bool,
+    pub dependencies: Vec<ContentDependency>,
+    /// Directory an agent was installed to; agents run from there
+    #[serde(default)]
+    pub installation_path: Option<String>,
+    pub installed_at: DateTime<Utc>,
+}
+
+impl InstalledContent {
+    pub fn depends_on(&self, kind: ContentKind, id: Uuid) -> bool {
+        self.dependencies.iter().any(|dependency| dependency.kind == kind && dependency.id == id)
+    }
+}
+
+/// Outcome of uninstalling marketplace content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UninstallResult {
+    pub removed: bool,
+    /// Installed content that depends on the item
+    pub dependents: Vec<InstalledContent>,
+    pub warning: Option<String>,
 }
 
 /// Marketplace analytics
@@ -320,3 +404,143 @@ pub struct MarketplaceAnalytics {
     pub contribution_streak_days: u32,
     pub last_contribution: DateTime<Utc>,
 }
+
+/// Resources one run of an installed agent may use before it is terminated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxLimits {
+    pub cpu_time_ms: u64,
+    pub memory_mb: u32,
+    pub wall_clock_seconds: u32,
+    /// Hosts the agent may fetch from; empty for no network
+    pub network_allowlist: Vec<String>,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            cpu_time_ms: 60_000,
+            memory_mb: 512,
+            wall_clock_seconds: 120,
+            network_allowlist: Vec::new(),
+        }
+    }
+}
+
+impl SandboxLimits {
+    /// The limits for an agent: what its requirements ask for, never more than `ceiling`.
+    /// Network access is limited to the agent's declared hosts.
+    pub fn for_agent(requirements: &ResourceRequirements, ceiling: &SandboxLimits) -> Self {
+        let cpu_time_ms = requirements.max_cpu_time_seconds
+            .map_or(ceiling.cpu_time_ms, |seconds| (seconds as u64 * 1000).min(ceiling.cpu_time_ms));
+        let wall_clock_seconds = requirements.max_wall_clock_seconds
+            .map_or(ceiling.wall_clock_seconds, |seconds| seconds.min(ceiling.wall_clock_seconds));
+        let memory_mb = match requirements.min_memory_mb {
+            0 => ceiling.memory_mb,
+            requested => requested.min(ceiling.memory_mb),
+        };
+        let network_allowlist = if requirements.network_access {
+            requirements.network_allowlist.iter()
+                .map(|host| host.trim().trim_start_matches("*.").to_ascii_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self { cpu_time_ms, memory_mb, wall_clock_seconds, network_allowlist }
+    }
+
+    pub fn wall_clock(&self) -> Duration {
+        Duration::from_secs(self.wall_clock_seconds as u64)
+    }
+
+    /// The limit `usage` breaches, if any
+    pub fn check_usage(&self, usage: &SandboxUsage) -> Option<SandboxViolation> {
+        if usage.cpu_time_ms > self.cpu_time_ms {
+            Some(SandboxViolation::CpuTime { limit_ms: self.cpu_time_ms, used_ms: usage.cpu_time_ms })
+        } else if usage.peak_memory_mb > self.memory_mb {
+            Some(SandboxViolation::Memory { limit_mb: self.memory_mb, used_mb: usage.peak_memory_mb })
+        } else {
+            None
+        }
+    }
+
+    /// Whether the agent may fetch `url`: http(s), to an allowlisted host or one of its subdomains
+    pub fn check_url(&self, url: &str) -> Result<(), SandboxViolation> {
+        let denied = || SandboxViolation::Network { target: url.chars().take(200).collect() };
+        let parsed = url::Url::parse(url).map_err(|_| denied())?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(denied());
+        }
+        let host = parsed.host_str().ok_or_else(denied)?.to_ascii_lowercase();
+        let allowed = self.network_allowlist.iter().any(|allowed| {
+            host == *allowed || host.strip_suffix(allowed.as_str()).is_some_and(|prefix| prefix.ends_with('.'))
+        });
+        if allowed { Ok(()) } else { Err(denied()) }
+    }
+}
+
+/// A sandbox limit an agent run breached, ending the run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum SandboxViolation {
+    CpuTime { limit_ms: u64, used_ms: u64 },
+    Memory { limit_mb: u32, used_mb: u32 },
+    WallClock { limit_seconds: u32 },
+    /// A fetch outside the agent's network allowlist
+    Network { target: String },
+}
+
+impl std::fmt::Display for SandboxViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SandboxViolation::CpuTime { limit_ms, used_ms } => write!(f, "used {}ms of CPU time, over its {}ms limit", used_ms, limit_ms),
+            SandboxViolation::Memory { limit_mb, used_mb } => write!(f, "used {}MB of memory, over its {}MB limit", used_mb, limit_mb),
+            SandboxViolation::WallClock { limit_seconds } => write!(f, "ran longer than its {}s limit", limit_seconds),
+            SandboxViolation::Network { target } => write!(f, "tried to reach {}, which is not on its network allowlist", target),
+        }
+    }
+}
+
+/// Resources one agent run used, sampled while it ran
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxUsage {
+    pub cpu_time_ms: u64,
+    pub peak_memory_mb: u32,
+    pub wall_clock_ms: u64,
+    pub network_requests: u32,
+}
+
+/// Request to run an installed agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRunRequest {
+    pub agent_id: Uuid,
+    pub input: serde_json::Value,
+}
+
+/// Outcome of one sandboxed agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRunResult {
+    pub agent_id: Uuid,
+    pub success: bool,
+    pub output: Option<serde_json::Value>,
+    pub exit_code: Option<i32>,
+    /// The limit the agent breached, if it was terminated for one
+    pub violation: Option<SandboxViolation>,
+    pub usage: SandboxUsage,
+    pub error_message: Option<String>,
+}
+
+/// Resource usage of one agent across its runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentResourceUsage {
+    pub agent_id: Uuid,
+    pub runs: u32,
+    pub violations: u32,
+    pub total_cpu_time_ms: u64,
+    pub peak_memory_mb: u32,
+    pub total_wall_clock_ms: u64,
+    pub network_requests: u64,
+    pub last_violation: Option<SandboxViolation>,
+    pub last_run_at: DateTime<Utc>,
+}
@@ -1,10 +1,15 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 
 /// Supported API service providers
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+///
+/// Serializes as its lowercase name, e.g. `"serpapi"`; `Debug` prints the variant name
+/// of the built-in providers and the name of custom ones, which is what storage keeps.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ServiceProvider {
     OpenRouter,
     SerpApi,
@@ -12,9 +17,212 @@ pub enum ServiceProvider {
     Firecrawl,
     Tavily,
     Exa,
+    /// A provider added through a provider adapter
+    Custom(CustomProvider),
+}
+
+/// What the key management needs to know about a custom provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomProviderSpec {
+    /// Lowercase identifier used in storage, configs and workflow steps
+    pub name: String,
+    pub display_name: String,
+    pub rate_limit: u32,
+    pub reset_period: ResetPeriod,
+}
+
+/// Handle to a registered custom provider. Handles compare and hash by name, so one
+/// taken before the provider was re-registered still finds its keys and configs.
+#[derive(Clone, Copy)]
+pub struct CustomProvider(&'static CustomProviderSpec);
+
+/// Custom providers by name. Specs are leaked so handles stay `Copy`; a provider is
+/// registered once at startup, or again when its limits change.
+static CUSTOM_PROVIDERS: Lazy<RwLock<HashMap<String, CustomProvider>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Most unregistered custom provider names given a handle. Client input is deserialized
+/// the same way as stored data, so the placeholders leaked for them must stay bounded.
+const MAX_UNREGISTERED_PROVIDERS: usize = 64;
+
+/// Handles to custom providers named before their adapter registered
+static UNREGISTERED_PROVIDERS: Lazy<RwLock<UnregisteredProviders>> =
+    Lazy::new(|| RwLock::new(UnregisteredProviders::new(MAX_UNREGISTERED_PROVIDERS)));
+
+/// Placeholder handles by name, each spec leaked once, up to `capacity` names
+#[derive(Debug)]
+struct UnregisteredProviders {
+    by_name: HashMap<String, CustomProvider>,
+    capacity: usize,
+}
+
+impl UnregisteredProviders {
+    fn new(capacity: usize) -> Self {
+        Self { by_name: HashMap::new(), capacity }
+    }
+
+    /// The placeholder for `name`, or `None` once `capacity` other names have one
+    fn intern(&mut self, name: &str) -> Option<CustomProvider> {
+        if let Some(provider) = self.by_name.get(name) {
+            return Some(*provider);
+        }
+        if self.by_name.len() >= self.capacity {
+            return None;
+        }
+        let provider = CustomProvider(Box::leak(Box::new(CustomProviderSpec {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            rate_limit: 0,
+            reset_period: ResetPeriod::Monthly,
+        })));
+        self.by_name.insert(name.to_string(), provider);
+        Some(provider)
+    }
+}
+
+impl CustomProvider {
+    /// Register `spec`, replacing any earlier spec of the same name
+    pub(crate) fn define(spec: CustomProviderSpec) -> Self {
+        let mut providers = CUSTOM_PROVIDERS.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = providers.get(&spec.name).filter(|existing| *existing.0 == spec) {
+            return *existing;
+        }
+        let provider = CustomProvider(Box::leak(Box::new(spec)));
+        providers.insert(provider.0.name.clone(), provider);
+        provider
+    }
+
+    /// The custom provider registered as `name`
+    pub fn lookup(name: &str) -> Option<Self> {
+        CUSTOM_PROVIDERS.read().unwrap_or_else(|e| e.into_inner()).get(name).copied()
+    }
+
+    /// Whether `name` can name a custom provider: lowercase letters, digits, '-' or '_',
+    /// and not a built-in provider's name
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            && !ServiceProvider::BUILT_IN.iter().any(|built_in| built_in.name() == name)
+    }
+
+    /// The custom provider `name`, registered or not. Until its adapter registers, the
+    /// handle has no rate limit and is left out of `all`; once it does, the handle finds
+    /// the registered spec like any other. `None` once `MAX_UNREGISTERED_PROVIDERS`
+    /// unregistered names have been seen.
+    pub fn deferred(name: &str) -> Option<Self> {
+        if let Some(provider) = Self::lookup(name) {
+            return Some(provider);
+        }
+        if !Self::is_valid_name(name) {
+            return None;
+        }
+        UNREGISTERED_PROVIDERS.write().unwrap_or_else(|e| e.into_inner()).intern(name)
+    }
+
+    /// Whether the provider's adapter is registered
+    pub fn is_registered(&self) -> bool {
+        Self::lookup(self.name()).is_some()
+    }
+
+    /// Every registered custom provider, by name
+    pub fn all() -> Vec<Self> {
+        let mut providers: Vec<Self> = CUSTOM_PROVIDERS.read().unwrap_or_else(|e| e.into_inner()).values().copied().collect();
+        providers.sort_by(|a, b| a.name().cmp(b.name()));
+        providers
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.0.name
+    }
+
+    /// The provider's spec as currently registered
+    pub fn spec(&self) -> &'static CustomProviderSpec {
+        Self::lookup(self.name()).unwrap_or(*self).0
+    }
+}
+
+impl PartialEq for CustomProvider {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for CustomProvider {}
+
+impl std::hash::Hash for CustomProvider {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+impl std::fmt::Debug for CustomProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::fmt::Debug for ServiceProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceProvider::OpenRouter => f.write_str("OpenRouter"),
+            ServiceProvider::SerpApi => f.write_str("SerpApi"),
+            ServiceProvider::Jina => f.write_str("Jina"),
+            ServiceProvider::Firecrawl => f.write_str("Firecrawl"),
+            ServiceProvider::Tavily => f.write_str("Tavily"),
+            ServiceProvider::Exa => f.write_str("Exa"),
+            ServiceProvider::Custom(provider) => f.write_str(provider.name()),
+        }
+    }
+}
+
+impl Serialize for ServiceProvider {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Stored workflows, usage and configs may name a custom provider whose adapter is not
+/// registered yet, or no longer is; they still load, with a handle that finds the
+/// provider once it registers
+impl<'de> Deserialize<'de> for ServiceProvider {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ServiceProvider::from_str(&name)
+            .or_else(|| CustomProvider::deferred(&name).map(ServiceProvider::Custom))
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown service provider '{}'", name)))
+    }
 }
 
 impl ServiceProvider {
+    /// The built-in providers
+    pub const BUILT_IN: [ServiceProvider; 6] = [
+        ServiceProvider::OpenRouter,
+        ServiceProvider::SerpApi,
+        ServiceProvider::Jina,
+        ServiceProvider::Firecrawl,
+        ServiceProvider::Tavily,
+        ServiceProvider::Exa,
+    ];
+
+    /// The built-in providers followed by every registered custom one
+    pub fn all() -> Vec<ServiceProvider> {
+        Self::BUILT_IN.into_iter()
+            .chain(CustomProvider::all().into_iter().map(ServiceProvider::Custom))
+            .collect()
+    }
+
+    /// Lowercase name the service is stored and configured under
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceProvider::OpenRouter => "openrouter",
+            ServiceProvider::SerpApi => "serpapi",
+            ServiceProvider::Jina => "jina",
+            ServiceProvider::Firecrawl => "firecrawl",
+            ServiceProvider::Tavily => "tavily",
+            ServiceProvider::Exa => "exa",
+            ServiceProvider::Custom(provider) => provider.name(),
+        }
+    }
+
     /// Get the display name for the service
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -24,6 +232,7 @@ impl ServiceProvider {
             ServiceProvider::Firecrawl => "Firecrawl",
             ServiceProvider::Tavily => "Tavily",
             ServiceProvider::Exa => "Exa AI",
+            ServiceProvider::Custom(provider) => &provider.spec().display_name,
         }
     }
 
@@ -36,6 +245,7 @@ impl ServiceProvider {
             ServiceProvider::Firecrawl => 500,  // 500 requests/month
             ServiceProvider::Tavily => 1000,    // 1000 searches/month
             ServiceProvider::Exa => 1000,       // 1000 searches/month
+            ServiceProvider::Custom(provider) => provider.spec().rate_limit,
         }
     }
 
@@ -48,10 +258,11 @@ impl ServiceProvider {
             ServiceProvider::Firecrawl => ResetPeriod::Monthly,
             ServiceProvider::Tavily => ResetPeriod::Monthly,
             ServiceProvider::Exa => ResetPeriod::Monthly,
+            ServiceProvider::Custom(provider) => provider.spec().reset_period.clone(),
         }
     }
 
-    /// Parse from string representation
+    /// Parse from string representation, including the names of registered custom providers
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "openrouter" => Some(ServiceProvider::OpenRouter),
@@ -60,7 +271,7 @@ impl ServiceProvider {
             "firecrawl" => Some(ServiceProvider::Firecrawl),
             "tavily" => Some(ServiceProvider::Tavily),
             "exa" => Some(ServiceProvider::Exa),
-            _ => None,
+            name => CustomProvider::lookup(name).map(ServiceProvider::Custom),
         }
     }
 }
@@ -124,6 +335,20 @@ pub struct ApiKey {
     pub status: ApiKeyStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Hard cap on requests per billing cycle; `None` leaves the key uncapped
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+    /// Requests made in the billing cycle starting at `quota_period_start`
+    #[serde(default)]
+    pub quota_usage: u32,
+    #[serde(default = "Utc::now")]
+    pub quota_period_start: DateTime<Utc>,
+    /// When the provider stops accepting the key, if it expires at all
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Tenant owning the key; `None` puts it in the shared system pool
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
 }
 
 impl ApiKey {
@@ -150,17 +375,35 @@ impl ApiKey {
             status: ApiKeyStatus::Active,
             created_at: now,
             updated_at: now,
+            monthly_quota: None,
+            quota_usage: 0,
+            quota_period_start: now,
+            expires_at: None,
+            tenant_id: None,
         }
     }
     
-    /// Check if the key is available for use
-    pub fn is_available(&self) -> bool {
-        matches!(self.status, ApiKeyStatus::Active) && self.usage_count < self.rate_limit
+    /// Check if the key is available for use at `now`
+    pub fn is_available(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.status, ApiKeyStatus::Active)
+            && self.usage_count < self.rate_limit
+            && !self.is_expired(now)
+    }
+
+    /// Whether the key's expiry date has passed
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.map_or(false, |expires_at| expires_at <= now)
+    }
+
+    /// Whether the key is still valid but expires within `days` of `now`
+    pub fn expires_within(&self, now: DateTime<Utc>, days: u32) -> bool {
+        self.expires_at.map_or(false, |expires_at| {
+            now < expires_at && expires_at <= now + Duration::days(days as i64)
+        })
     }
     
-    /// Check if the key needs to be reset
-    pub fn needs_reset(&self) -> bool {
-        let now = Utc::now();
+    /// Check if the key's reset period has rolled over by `now`
+    pub fn needs_reset(&self, now: DateTime<Utc>) -> bool {
         match self.reset_period {
             ResetPeriod::Daily => {
                 now.date_naive() > self.last_reset.date_naive()
@@ -172,10 +415,10 @@ impl ApiKey {
     }
     
     /// Reset the usage count
-    pub fn reset_usage(&mut self) {
+    pub fn reset_usage(&mut self, now: DateTime<Utc>) {
         self.usage_count = 0;
-        self.last_reset = Utc::now();
-        self.updated_at = Utc::now();
+        self.last_reset = now;
+        self.updated_at = now;
         if matches!(self.status, ApiKeyStatus::Exhausted) {
             self.status = ApiKeyStatus::Active;
         }
@@ -205,6 +448,25 @@ impl ApiKey {
     pub fn remaining_requests(&self) -> u32 {
         self.rate_limit.saturating_sub(self.usage_count)
     }
+
+    /// Requests counted against the quota in the billing cycle starting at `cycle_start`
+    pub fn quota_used_in(&self, cycle_start: DateTime<Utc>) -> u32 {
+        if self.quota_period_start >= cycle_start {
+            self.quota_usage
+        } else {
+            0
+        }
+    }
+
+    /// Count a request against the quota, starting afresh once a new billing cycle has begun
+    pub fn record_quota_usage(&mut self, cycle_start: DateTime<Utc>) {
+        if self.quota_period_start < cycle_start {
+            self.quota_usage = 0;
+            self.quota_period_start = cycle_start;
+        }
+        self.quota_usage += 1;
+        self.updated_at = Utc::now();
+    }
 }
 
 /// API key creation request
@@ -214,6 +476,13 @@ pub struct CreateApiKeyRequest {
     pub name: String,
     pub api_key: String,
     pub rate_limit: Option<u32>,
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Tenant the key is added for; `None` adds it to the system pool
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
 }
 
 /// API key update request
@@ -223,6 +492,16 @@ pub struct UpdateApiKeyRequest {
     pub api_key: Option<String>,
     pub rate_limit: Option<u32>,
     pub status: Option<ApiKeyStatus>,
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+    /// Remove the key's monthly quota; takes precedence over `monthly_quota`
+    #[serde(default)]
+    pub clear_monthly_quota: bool,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Mark the key as never expiring; takes precedence over `expires_at`
+    #[serde(default)]
+    pub clear_expires_at: bool,
 }
 
 /// API key import data
@@ -232,6 +511,13 @@ pub struct ApiKeyImport {
     pub name: String,
     pub api_key: String,
     pub rate_limit: Option<u32>,
+    #[serde(default)]
+    pub monthly_quota: Option<u32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Tenant the key is added for; `None` adds it to the system pool
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
 }
 
 /// API key export data
@@ -241,6 +527,8 @@ pub struct ApiKeyExport {
     pub name: String,
     pub rate_limit: u32,
     pub usage_count: u32,
+    pub monthly_quota: Option<u32>,
+    pub expires_at: Option<DateTime<Utc>>,
     pub status: ApiKeyStatus,
     pub created_at: DateTime<Utc>,
 }
@@ -254,3 +542,144 @@ pub struct ApiKeyTestResult {
     pub response_time_ms: Option<u64>,
     pub tested_at: DateTime<Utc>,
 }
+
+/// Selects API keys for a bulk operation; unset criteria match every key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeyFilter {
+    pub service: Option<ServiceProvider>,
+    pub status: Option<ApiKeyStatus>,
+    /// Case-insensitive name pattern; `*` matches any run of characters and a
+    /// pattern without `*` matches names containing it
+    pub name_pattern: Option<String>,
+}
+
+impl ApiKeyFilter {
+    /// Whether every criterion is unset, so the filter would select all keys
+    pub fn is_empty(&self) -> bool {
+        self.service.is_none()
+            && self.status.is_none()
+            && self.name_pattern.as_deref().map_or(true, |p| p.trim().is_empty())
+    }
+
+    pub fn matches(&self, api_key: &ApiKey) -> bool {
+        self.service.map_or(true, |service| api_key.service == service)
+            && self.status.as_ref().map_or(true, |status| &api_key.status == status)
+            && self.name_pattern.as_deref().map_or(true, |pattern| name_matches(pattern, &api_key.name))
+    }
+}
+
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let name = name.to_lowercase();
+
+    if !pattern.contains('*') {
+        return name.contains(&pattern);
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+
+    // Match the inner segments left to right between the anchored ends
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Outcome of a bulk operation for one API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkKeyOutcome {
+    pub key_id: Uuid,
+    pub name: String,
+    pub success: bool,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matches_by_service_status_and_name() {
+        let mut key = ApiKey::new(ServiceProvider::Tavily, "Tavily Prod EU".to_string(), "enc".to_string());
+        key.status = ApiKeyStatus::Error;
+
+        let filter = |service, status, name_pattern: Option<&str>| ApiKeyFilter {
+            service,
+            status,
+            name_pattern: name_pattern.map(str::to_string),
+        };
+
+        assert!(filter(None, None, None).matches(&key));
+        assert!(filter(Some(ServiceProvider::Tavily), Some(ApiKeyStatus::Error), None).matches(&key));
+        assert!(!filter(Some(ServiceProvider::Exa), None, None).matches(&key));
+        assert!(!filter(None, Some(ApiKeyStatus::Active), None).matches(&key));
+
+        assert!(filter(None, None, Some("prod")).matches(&key));
+        assert!(filter(None, None, Some("tavily*eu")).matches(&key));
+        assert!(!filter(None, None, Some("tavily*us")).matches(&key));
+        assert!(!filter(None, None, Some("prod*")).matches(&key));
+        assert!(filter(None, None, Some("*")).matches(&key));
+    }
+
+    #[test]
+    fn test_providers_named_before_their_adapter_registers_still_load() {
+        let stored: ServiceProvider = serde_json::from_value(serde_json::json!("deferred-search")).unwrap();
+        let ServiceProvider::Custom(provider) = stored else { panic!("expected a custom provider, got {:?}", stored) };
+        assert!(!provider.is_registered());
+        assert_eq!(stored.default_rate_limit(), 0);
+        assert!(!ServiceProvider::all().contains(&stored));
+        // Stored keys of the provider are still skipped until it registers
+        assert!(ServiceProvider::from_str("deferred-search").is_none());
+
+        let registered = CustomProvider::define(CustomProviderSpec {
+            name: "deferred-search".to_string(),
+            display_name: "Deferred Search".to_string(),
+            rate_limit: 200,
+            reset_period: ResetPeriod::Daily,
+        });
+        assert!(provider.is_registered());
+        assert_eq!(ServiceProvider::Custom(registered), stored);
+        assert_eq!((stored.display_name(), stored.default_rate_limit()), ("Deferred Search", 200));
+
+        assert!(serde_json::from_value::<ServiceProvider>(serde_json::json!("Not a provider!")).is_err());
+    }
+
+    #[test]
+    fn test_unregistered_provider_names_are_bounded() {
+        let mut unregistered = UnregisteredProviders::new(2);
+        let first = unregistered.intern("bounded-a").unwrap();
+        assert!(unregistered.intern("bounded-b").is_some());
+        assert!(unregistered.intern("bounded-c").is_none());
+        // Names already seen keep their handle
+        assert_eq!(unregistered.intern("bounded-a"), Some(first));
+        assert_eq!(unregistered.by_name.len(), 2);
+    }
+
+    #[test]
+    fn test_expired_keys_are_unavailable() {
+        let now = Utc::now();
+        let mut key = ApiKey::new(ServiceProvider::Exa, "Exa".to_string(), "enc".to_string());
+        assert!(!key.is_expired(now));
+        assert!(!key.expires_within(now, 14));
+
+        key.expires_at = Some(now + Duration::days(10));
+        assert!(key.expires_within(now, 14));
+        assert!(!key.expires_within(now, 7));
+        assert!(key.is_available(now));
+
+        key.expires_at = Some(now - Duration::minutes(1));
+        assert!(key.is_expired(now));
+        assert!(!key.expires_within(now, 14));
+        assert!(!key.is_available(now));
+        // Whether it is available goes by the time it is asked about
+        assert!(key.is_available(now - Duration::minutes(2)));
+    }
+}
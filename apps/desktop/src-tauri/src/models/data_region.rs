@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::StorageError;
+
+/// Where a tenant's data must be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataRegion {
+    /// No residency requirement; stored in the primary database
+    #[default]
+    Global,
+    Eu,
+    Uk,
+    Us,
+    Apac,
+}
+
+impl DataRegion {
+    /// Regions that require their own database
+    pub const PINNED: [DataRegion; 4] = [DataRegion::Eu, DataRegion::Uk, DataRegion::Us, DataRegion::Apac];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            DataRegion::Global => "global",
+            DataRegion::Eu => "eu",
+            DataRegion::Uk => "uk",
+            DataRegion::Us => "us",
+            DataRegion::Apac => "apac",
+        }
+    }
+
+    /// Suffix of the region's environment variables, e.g. `_EU`
+    pub fn env_suffix(&self) -> String {
+        format!("_{}", self.code().to_uppercase())
+    }
+
+    /// Whether a cloud provider region such as `eu-central-1` lies inside this region
+    pub fn contains_cloud_region(&self, cloud_region: &str) -> bool {
+        let cloud_region = cloud_region.trim().to_lowercase();
+        match self {
+            DataRegion::Global => true,
+            DataRegion::Eu => cloud_region.starts_with("eu-"),
+            DataRegion::Uk => cloud_region == "eu-west-2" || cloud_region.starts_with("uk"),
+            DataRegion::Us => cloud_region.starts_with("us-"),
+            DataRegion::Apac => cloud_region.starts_with("ap-"),
+        }
+    }
+}
+
+impl std::fmt::Display for DataRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl std::str::FromStr for DataRegion {
+    type Err = StorageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "global" => Ok(DataRegion::Global),
+            "eu" => Ok(DataRegion::Eu),
+            "uk" => Ok(DataRegion::Uk),
+            "us" => Ok(DataRegion::Us),
+            "apac" => Ok(DataRegion::Apac),
+            other => Err(StorageError::ResidencyViolation {
+                message: format!("Unknown data region: {}", other)
+            }),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What one attempt at a workflow step actually used, recorded as the attempt finishes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepExecutionMetrics {
+    pub workflow_id: Uuid,
+    pub step_id: Uuid,
+    pub step_number: u32,
+    pub step_name: String,
+    /// Provider that served the step, or the one it asked for when it failed
+    pub service_provider: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub wall_time_ms: u64,
+    /// Provider requests the attempt made, failed ones included
+    pub provider_calls: u32,
+    pub failed_provider_calls: u32,
+    /// Tokens the AI providers reported using
+    pub tokens: u64,
+    /// Size of the provider response bodies received
+    pub bytes_fetched: u64,
+    pub succeeded: bool,
+}
+
+/// A workflow's step actuals, with their totals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowExecutionMetrics {
+    pub workflow_id: Uuid,
+    /// From the first step starting to the last one finishing
+    pub wall_time_ms: u64,
+    /// Summed wall time of all step attempts; above `wall_time_ms` when steps overlapped
+    pub step_time_ms: u64,
+    pub provider_calls: u32,
+    pub failed_provider_calls: u32,
+    pub tokens: u64,
+    pub bytes_fetched: u64,
+    /// Attempts that failed and were retried or gave up
+    pub failed_attempts: u32,
+    /// Every step attempt, in the order they started
+    pub steps: Vec<StepExecutionMetrics>,
+}
+
+impl WorkflowExecutionMetrics {
+    pub fn from_steps(workflow_id: Uuid, mut steps: Vec<StepExecutionMetrics>) -> Self {
+        steps.sort_by_key(|step| (step.started_at, step.step_number));
+
+        let wall_time_ms = match (steps.iter().map(|s| s.started_at).min(), steps.iter().map(|s| s.completed_at).max()) {
+            (Some(started), Some(completed)) => (completed - started).num_milliseconds().max(0) as u64,
+            _ => 0,
+        };
+
+        Self {
+            workflow_id,
+            wall_time_ms,
+            step_time_ms: steps.iter().map(|s| s.wall_time_ms).sum(),
+            provider_calls: steps.iter().map(|s| s.provider_calls).sum(),
+            failed_provider_calls: steps.iter().map(|s| s.failed_provider_calls).sum(),
+            tokens: steps.iter().map(|s| s.tokens).sum(),
+            bytes_fetched: steps.iter().map(|s| s.bytes_fetched).sum(),
+            failed_attempts: steps.iter().filter(|s| !s.succeeded).count() as u32,
+            steps,
+        }
+    }
+
+    /// Whether any step attempt was recorded
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
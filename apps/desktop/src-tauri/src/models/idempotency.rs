@@ -0,0 +1,77 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long a workflow creation request's idempotency key stays bound to its workflow
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// Longest idempotency key a client may send
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// A client-chosen idempotency key and the workflow its first request created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    pub workflow_id: Uuid,
+    /// Digest of the request the key was first sent with; a retry must match it
+    pub request_fingerprint: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    pub fn new(key: String, workflow_id: Uuid, request_fingerprint: String) -> Self {
+        let now = Utc::now();
+        Self {
+            key,
+            workflow_id,
+            request_fingerprint,
+            created_at: now,
+            expires_at: now + Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS),
+        }
+    }
+
+    /// Whether the key has left its window and may be reused for a new workflow
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// SHA-256 digest of a request's JSON form, leaving out its idempotency key. Object keys
+/// serialize in sorted order, so equal requests always produce the same digest.
+pub fn request_fingerprint<T: Serialize>(request: &T) -> String {
+    let mut value = serde_json::to_value(request).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("idempotency_key");
+    }
+    let digest = ring::digest::digest(&ring::digest::SHA256, value.to_string().as_bytes());
+    digest.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::CreateWorkflowRequest;
+
+    fn request(query: &str, idempotency_key: Option<&str>) -> CreateWorkflowRequest {
+        CreateWorkflowRequest {
+            name: "Battery research".to_string(),
+            query: query.to_string(),
+            template_id: None,
+            parameters: None,
+            data_region: Default::default(),
+            idempotency_key: idempotency_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_the_key_and_records_expire_after_the_window() {
+        let first = request_fingerprint(&request("solid-state batteries", Some("retry-1")));
+        assert_eq!(first, request_fingerprint(&request("solid-state batteries", Some("retry-2"))));
+        assert_ne!(first, request_fingerprint(&request("sodium-ion batteries", Some("retry-1"))));
+
+        let record = IdempotencyRecord::new("retry-1".to_string(), Uuid::new_v4(), first);
+        assert!(!record.is_expired(Utc::now()));
+        assert!(record.is_expired(Utc::now() + Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS)));
+    }
+}
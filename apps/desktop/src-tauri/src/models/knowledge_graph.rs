@@ -551,3 +551,68 @@ pub struct TraversalStatistics {
     pub memory_used_mb: f64,
     pub paths_found: u32,
 }
+
+/// Structure extracted from a document such as an academic paper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedDocument {
+    /// Name of the extractor that produced it
+    pub extractor: String,
+    pub title: Option<String>,
+    pub abstract_text: Option<String>,
+    pub sections: Vec<DocumentSection>,
+    pub figures: Vec<DocumentFigure>,
+    pub citations: Vec<Citation>,
+    pub quality: ExtractionQuality,
+    /// Knowledge graph node of the document, once linked
+    pub node_id: Option<Uuid>,
+}
+
+/// One section of a document, under its heading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSection {
+    /// Section number as printed, e.g. `3.2`
+    pub number: Option<String>,
+    pub heading: String,
+    /// 1 for top-level sections, 2 for subsections, ...
+    pub level: u32,
+    pub text: String,
+}
+
+/// A figure by its caption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFigure {
+    pub label: String,
+    pub caption: String,
+}
+
+/// One entry of a document's reference list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    /// Position in the reference list, from 1
+    pub index: u32,
+    /// The entry as printed
+    pub raw: String,
+    pub authors: Vec<String>,
+    pub title: Option<String>,
+    pub year: Option<u16>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub arxiv_id: Option<String>,
+    pub url: Option<String>,
+    /// Knowledge graph node of the cited work, once linked
+    pub node_id: Option<Uuid>,
+}
+
+/// How much of a document's structure extraction recovered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionQuality {
+    /// 0.0 to 1.0
+    pub score: f64,
+    pub page_count: u32,
+    pub text_chars: usize,
+    pub two_column_pages: u32,
+    /// The document has too little text to be parsed, e.g. a scanned PDF, and
+    /// should be run through OCR first
+    pub needs_ocr: bool,
+    pub warnings: Vec<String>,
+}
@@ -2,9 +2,20 @@ pub mod api_key;
 pub mod audit;
 pub mod research_workflow;
 pub mod research_template;
+pub mod research_schedule;
+pub mod prompt_template;
+pub mod idempotency;
+pub mod saga;
+pub mod usage_report;
+pub mod notification;
+pub mod workflow_rating;
 pub mod configuration;
 pub mod metrics;
 pub mod security;
+pub mod data_region;
+pub mod pagination;
+pub mod execution_metrics;
+pub mod research_insight;
 
 // V3.0.0 Models - Global Intelligence Network
 pub mod federated_research;
@@ -17,9 +28,19 @@ pub mod knowledge_graph;
 pub use api_key::*;
 pub use research_workflow::*;
 pub use research_template::*;
+pub use research_schedule::*;
+pub use prompt_template::*;
+pub use idempotency::*;
+pub use saga::*;
+pub use usage_report::*;
+pub use notification::*;
+pub use workflow_rating::*;
 pub use configuration::*;
 pub use metrics::*;
 pub use security::*;
+pub use data_region::DataRegion;
+pub use pagination::{Page, PageRequest};
+pub use research_insight::{InsightCategory, ResearchInsight};
 
 // V3.0.0 Model exports
 pub use federated_research::*;
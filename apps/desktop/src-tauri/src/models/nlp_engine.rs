@@ -177,7 +177,7 @@ pub struct SentimentAnalysis {
 }
 
 /// Sentiment
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Sentiment {
     VeryNegative,
@@ -399,7 +399,7 @@ pub struct NLPProcessingRequest {
 }
 
 /// Processing type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProcessingType {
     Tokenization,
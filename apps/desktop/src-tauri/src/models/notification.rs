@@ -0,0 +1,135 @@
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where a notification is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    InApp,
+    Email,
+    Webhook,
+}
+
+/// How often held notifications are sent as one digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn period(&self) -> Duration {
+        match self {
+            DigestFrequency::Daily => Duration::days(1),
+            DigestFrequency::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Whether non-critical notifications go out as they happen or batched into a digest.
+/// Critical ones are always sent immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode", content = "frequency")]
+pub enum DeliveryMode {
+    Immediate,
+    Digest(DigestFrequency),
+}
+
+/// Hours of the user's day when non-critical notifications are held back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// First quiet hour, 0-23, in the user's local time
+    pub start_hour: u8,
+    /// Hour quiet time ends; before `start_hour` when it runs past midnight
+    pub end_hour: u8,
+    /// The user's offset from UTC
+    pub utc_offset_minutes: i32,
+}
+
+impl QuietHours {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let hour = (at + Duration::minutes(self.utc_offset_minutes as i64)).hour() as u8;
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Which alerts a user is notified of, where and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub user_id: Uuid,
+    /// Tenant whose alerts the user receives; `None` for alerts about system pool keys
+    pub tenant_id: Option<Uuid>,
+    /// Alert type names, e.g. `QuotaExhausted`; empty for every type
+    pub alert_types: Vec<String>,
+    pub channels: Vec<NotificationChannel>,
+    /// Address the email channel sends to
+    #[serde(default)]
+    pub email: Option<String>,
+    /// URL the webhook channel posts to
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    pub quiet_hours: Option<QuietHours>,
+    pub delivery: DeliveryMode,
+    /// When held notifications were last sent
+    pub last_digest_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationPreferences {
+    /// Every alert, in the app, as it happens
+    pub fn new(user_id: Uuid, tenant_id: Option<Uuid>) -> Self {
+        Self {
+            user_id,
+            tenant_id,
+            alert_types: Vec::new(),
+            channels: vec![NotificationChannel::InApp],
+            email: None,
+            webhook_url: None,
+            quiet_hours: None,
+            delivery: DeliveryMode::Immediate,
+            last_digest_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn wants(&self, alert_type: &str) -> bool {
+        self.alert_types.is_empty() || self.alert_types.iter().any(|wanted| wanted == alert_type)
+    }
+
+    pub fn in_quiet_hours(&self, at: DateTime<Utc>) -> bool {
+        self.quiet_hours.map_or(false, |quiet_hours| quiet_hours.contains(at))
+    }
+
+    /// Whether notifications held for the user may be sent now: outside quiet hours
+    /// and, for digests, once the digest period has passed since the last one
+    pub fn held_due(&self, at: DateTime<Utc>) -> bool {
+        if self.in_quiet_hours(at) {
+            return false;
+        }
+        match self.delivery {
+            DeliveryMode::Immediate => true,
+            DeliveryMode::Digest(frequency) => self.last_digest_at
+                .map_or(true, |last| at - last >= frequency.period()),
+        }
+    }
+}
+
+/// An alert as delivered to users, independent of the subsystem that raised it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    /// Name of the alert type, matched against `NotificationPreferences::alert_types`
+    pub alert_type: String,
+    /// Critical notifications ignore quiet hours and digests
+    pub critical: bool,
+    pub message: String,
+    pub api_key_id: Option<Uuid>,
+    pub service: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,179 @@
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Items in a page when the request does not say
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+/// Most items a single page may hold
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Which page of a list to read: up to `limit` items after the page `cursor` ended
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageRequest {
+    pub limit: Option<u32>,
+    /// `next_cursor` of the previous page; `None` for the first page
+    pub cursor: Option<String>,
+}
+
+impl PageRequest {
+    pub fn first(limit: u32) -> Self {
+        Self { limit: Some(limit), cursor: None }
+    }
+
+    /// The page size, between 1 and `MAX_PAGE_SIZE`
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Where the previous page ended
+    pub fn after(&self) -> AppResult<Option<PagePosition>> {
+        self.cursor.as_deref().filter(|cursor| !cursor.is_empty()).map(PagePosition::decode).transpose()
+    }
+}
+
+/// The sort key of an item in a paged list. Lists are ordered newest first by creation
+/// time, then ID, and a cursor is the key of the last item returned; items inserted
+/// while paging sort before every cursor already handed out, so later pages neither
+/// skip nor repeat items.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PagePosition {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl PagePosition {
+    pub fn new(created_at: DateTime<Utc>, id: impl ToString) -> Self {
+        Self { created_at, id: id.to_string() }
+    }
+
+    /// The opaque cursor clients pass back
+    pub fn encode(&self) -> String {
+        let key = format!("{}|{}", self.created_at.to_rfc3339(), self.id);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(key)
+    }
+
+    pub fn decode(cursor: &str) -> AppResult<Self> {
+        let invalid = || AppError::validation("cursor", "is not a cursor returned by this list");
+        let key = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let key = String::from_utf8(key).map_err(|_| invalid())?;
+        let (created_at, id) = key.split_once('|').ok_or_else(invalid)?;
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?.with_timezone(&Utc),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass as `cursor` to read the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Items in the whole list as of this page. A hint: inserts and deletes while paging
+    /// change it.
+    pub total_count: u64,
+}
+
+impl<T> Page<T> {
+    /// The page from `rows`, read in list order after the cursor and up to `limit + 1`
+    /// of them; the extra row only says whether a next page exists
+    pub fn from_rows(mut rows: Vec<T>, limit: u32, total_count: u64, position: impl Fn(&T) -> PagePosition) -> Self {
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let next_cursor = if has_more { rows.last().map(|last| position(last).encode()) } else { None };
+        Self { items: rows, next_cursor, total_count }
+    }
+
+    /// As `from_rows`, for rows read from storage that `convert` may leave out. The
+    /// cursor still moves past rows left out, so they neither end paging early nor stall it.
+    pub fn from_stored_rows<R>(
+        mut rows: Vec<R>,
+        limit: u32,
+        total_count: u64,
+        position: impl Fn(&R) -> AppResult<PagePosition>,
+        mut convert: impl FnMut(R) -> AppResult<Option<T>>,
+    ) -> AppResult<Self> {
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let next_cursor = match rows.last() {
+            Some(last) if has_more => Some(position(last)?.encode()),
+            _ => None,
+        };
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.extend(convert(row)?);
+        }
+        Ok(Self { items, next_cursor, total_count })
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page { items: self.items.into_iter().map(f).collect(), next_cursor: self.next_cursor, total_count: self.total_count }
+    }
+}
+
+/// Page through a list held in memory, in the same order and with the same cursors as
+/// the lists paged in the database
+pub fn paginate<T>(mut items: Vec<T>, request: &PageRequest, position: impl Fn(&T) -> PagePosition) -> AppResult<Page<T>> {
+    let after = request.after()?;
+    let limit = request.limit();
+    let total_count = items.len() as u64;
+
+    items.sort_by_key(|item| std::cmp::Reverse(position(item)));
+    let rows = items.into_iter()
+        .filter(|item| after.as_ref().map_or(true, |after| position(item) < *after))
+        .take(limit as usize + 1)
+        .collect();
+    Ok(Page::from_rows(rows, limit, total_count, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_pages_are_stable_when_items_are_inserted_while_paging() {
+        let start = Utc::now();
+        let item = |minutes: i64, id: &str| PagePosition::new(start + Duration::minutes(minutes), id);
+        // Two items share a creation time and are told apart by ID
+        let mut items = vec![item(0, "a"), item(1, "b"), item(2, "c"), item(2, "d"), item(3, "e")];
+
+        let first = paginate(items.clone(), &PageRequest::first(2), Clone::clone).unwrap();
+        assert_eq!(first.items, vec![item(3, "e"), item(2, "d")]);
+        assert_eq!(first.total_count, 5);
+
+        // A newer item arriving between pages does not shift the next one
+        items.push(item(4, "f"));
+        let request = PageRequest { limit: Some(2), cursor: first.next_cursor };
+        let second = paginate(items.clone(), &request, Clone::clone).unwrap();
+        assert_eq!(second.items, vec![item(2, "c"), item(1, "b")]);
+
+        let request = PageRequest { limit: Some(2), cursor: second.next_cursor };
+        let last = paginate(items, &request, Clone::clone).unwrap();
+        assert_eq!(last.items, vec![item(0, "a")]);
+        assert_eq!(last.next_cursor, None);
+
+        assert_eq!(PagePosition::decode(&item(1, "b").encode()).unwrap(), item(1, "b"));
+        assert!(PageRequest { limit: None, cursor: Some("not a cursor".to_string()) }.after().is_err());
+        assert_eq!(PageRequest { limit: Some(10_000), cursor: None }.limit(), MAX_PAGE_SIZE);
+    }
+    #[test]
+    fn test_stored_rows_left_out_still_move_the_cursor() {
+        let start = Utc::now();
+        let rows: Vec<(i64, Option<&str>)> = vec![(3, Some("c")), (2, None), (1, Some("a"))];
+        let position = |row: &(i64, Option<&str>)| Ok(PagePosition::new(start + Duration::minutes(row.0), row.0));
+
+        // The second row is left out, yet a third row was read, so a next page exists
+        let page = Page::from_stored_rows(rows[..3].to_vec(), 2, 3, position, |row| Ok(row.1)).unwrap();
+        assert_eq!(page.items, vec!["c"]);
+        assert_eq!(page.next_cursor, Some(PagePosition::new(start + Duration::minutes(2), 2).encode()));
+
+        let last = Page::from_stored_rows(rows[2..].to_vec(), 2, 3, position, |row| Ok(row.1)).unwrap();
+        assert_eq!(last.items, vec!["a"]);
+        assert_eq!(last.next_cursor, None);
+    }
+}
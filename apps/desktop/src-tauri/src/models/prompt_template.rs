@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::ResearchResults;
+
+/// A named system prompt for LLM steps. Every edit is saved as a new version;
+/// earlier versions stay available to steps that pin them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    /// Stable name steps reference the prompt by, e.g. "academic_analysis"
+    pub id: String,
+    pub version: u32,
+    pub description: String,
+    /// Prompt text with `{{variable}}` placeholders
+    pub template: String,
+    /// Values used for placeholders a step does not supply
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PromptTemplate {
+    pub fn new(id: &str, version: u32, description: &str, template: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            version,
+            description: description.to_string(),
+            template: template.to_string(),
+            defaults: HashMap::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Placeholder names in the order they first appear
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for (_, name, _) in placeholders(&self.template) {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Substitute every placeholder from `variables`, falling back to the template's defaults.
+    /// Fails when a placeholder has neither.
+    pub fn render(&self, variables: &HashMap<String, String>) -> AppResult<String> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut last = 0;
+        for (start, name, end) in placeholders(&self.template) {
+            let value = variables.get(name)
+                .or_else(|| self.defaults.get(name))
+                .ok_or_else(|| AppError::validation(
+                    name.to_string(),
+                    format!("Prompt '{}' v{} needs a value for '{}'", self.id, self.version, name),
+                ))?;
+            rendered.push_str(&self.template[last..start]);
+            rendered.push_str(value);
+            last = end;
+        }
+        rendered.push_str(&self.template[last..]);
+        Ok(rendered)
+    }
+
+    /// Label recorded on steps, e.g. "academic_analysis@3"
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.id, self.version)
+    }
+}
+
+/// `{{name}}` placeholders as (start, trimmed name, end) byte positions
+fn placeholders(template: &str) -> Vec<(usize, &str, usize)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = template[offset..].find("{{") {
+        let start = offset + open;
+        let Some(close) = template[start..].find("}}") else { break };
+        let end = start + close + 2;
+        found.push((start, template[start + 2..end - 2].trim(), end));
+        offset = end;
+    }
+    found
+}
+
+/// Request to save a new version of a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavePromptTemplateRequest {
+    pub id: String,
+    pub description: String,
+    pub template: String,
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+}
+
+/// Which side of a prompt experiment served a run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExperimentArm {
+    Control,
+    Variant,
+}
+
+impl ExperimentArm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExperimentArm::Control => "control",
+            ExperimentArm::Variant => "variant",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "control" => Some(ExperimentArm::Control),
+            "variant" => Some(ExperimentArm::Variant),
+            _ => None,
+        }
+    }
+}
+
+/// Running quality totals for one arm of an experiment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArmMetrics {
+    pub runs: u32,
+    pub completed: u32,
+    pub total_word_count: u64,
+    pub total_source_count: u64,
+    /// Sum of the highest share of the report any one source accounts for
+    pub total_source_overlap: f64,
+    pub total_execution_time_ms: u64,
+}
+
+impl ArmMetrics {
+    /// Add one finished run; `results` is `None` for runs that failed
+    pub fn record(&mut self, results: Option<&ResearchResults>) {
+        self.runs += 1;
+        if let Some(results) = results {
+            self.completed += 1;
+            self.total_word_count += results.word_count as u64;
+            self.total_source_count += results.source_count as u64;
+            self.total_execution_time_ms += results.execution_time_ms;
+            // Written by the research engine's overlap check when it is enabled
+            self.total_source_overlap += results.metadata.get("source_overlap")
+                .and_then(|report| report.get("max_overlap_ratio"))
+                .and_then(|ratio| ratio.as_f64())
+                .unwrap_or(0.0);
+        }
+    }
+
+    pub fn summary(&self) -> ArmSummary {
+        let per_completed = |total: f64| if self.completed == 0 { 0.0 } else { total / self.completed as f64 };
+        ArmSummary {
+            runs: self.runs,
+            completion_rate: if self.runs == 0 { 0.0 } else { self.completed as f64 / self.runs as f64 },
+            avg_word_count: per_completed(self.total_word_count as f64),
+            avg_source_count: per_completed(self.total_source_count as f64),
+            avg_source_overlap: per_completed(self.total_source_overlap),
+            avg_execution_time_ms: per_completed(self.total_execution_time_ms as f64),
+        }
+    }
+}
+
+/// Averages of one arm's quality metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmSummary {
+    pub runs: u32,
+    pub completion_rate: f64,
+    pub avg_word_count: f64,
+    pub avg_source_count: f64,
+    pub avg_source_overlap: f64,
+    pub avg_execution_time_ms: f64,
+}
+
+/// A/B test routing a share of runs of a prompt to another of its versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptExperiment {
+    pub prompt_id: String,
+    pub control_version: u32,
+    pub variant_version: u32,
+    /// Share of runs, from 0.0 to 1.0, served by the variant
+    pub variant_share: f64,
+    pub active: bool,
+    pub control: ArmMetrics,
+    pub variant: ArmMetrics,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+impl PromptExperiment {
+    /// The arm serving a workflow. Routing is keyed by the workflow so every
+    /// step of one run sees the same version.
+    pub fn arm_for(&self, workflow_id: Uuid) -> ExperimentArm {
+        let bucket = (workflow_id.as_u128() % 10_000) as f64 / 10_000.0;
+        if bucket < self.variant_share {
+            ExperimentArm::Variant
+        } else {
+            ExperimentArm::Control
+        }
+    }
+
+    pub fn version_for(&self, arm: ExperimentArm) -> u32 {
+        match arm {
+            ExperimentArm::Control => self.control_version,
+            ExperimentArm::Variant => self.variant_version,
+        }
+    }
+
+    pub fn metrics_mut(&mut self, arm: ExperimentArm) -> &mut ArmMetrics {
+        match arm {
+            ExperimentArm::Control => &mut self.control,
+            ExperimentArm::Variant => &mut self.variant,
+        }
+    }
+
+    pub fn comparison(&self) -> ExperimentComparison {
+        ExperimentComparison {
+            prompt_id: self.prompt_id.clone(),
+            control_version: self.control_version,
+            variant_version: self.variant_version,
+            variant_share: self.variant_share,
+            active: self.active,
+            control: self.control.summary(),
+            variant: self.variant.summary(),
+        }
+    }
+}
+
+/// Request to start an A/B test between two versions of a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartPromptExperimentRequest {
+    pub prompt_id: String,
+    /// Defaults to the latest version
+    pub control_version: Option<u32>,
+    pub variant_version: u32,
+    pub variant_share: f64,
+}
+
+/// Side-by-side quality metrics of an experiment's arms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentComparison {
+    pub prompt_id: String,
+    pub control_version: u32,
+    pub variant_version: u32,
+    pub variant_share: f64,
+    pub active: bool,
+    pub control: ArmSummary,
+    pub variant: ArmSummary,
+}
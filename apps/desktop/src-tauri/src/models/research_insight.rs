@@ -0,0 +1,27 @@
+use serde::{Serialize, Deserialize};
+
+/// Result metadata key holding the insights an analysis step returned as structured output
+pub const INSIGHTS_METADATA_KEY: &str = "insights";
+
+/// Category of an insight extracted from a research report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InsightCategory {
+    KeyFinding,
+    Trend,
+    Contradiction,
+    GapInKnowledge,
+    Recommendation,
+}
+
+/// A single insight from a research report, categorized by the report section it appeared in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResearchInsight {
+    pub insight: String,
+    pub category: InsightCategory,
+    /// From 0.0 to 1.0; only reported by structured analysis output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// URLs of the sources backing the insight
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supporting_sources: Vec<String>,
+}
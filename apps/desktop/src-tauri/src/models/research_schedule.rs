@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::models::research_workflow::ResearchResults;
+
+/// What a schedule runs each time it fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleTarget {
+    /// Re-run a saved workflow's query and parameters as a fresh workflow
+    Workflow { workflow_id: Uuid },
+    /// Execute a research template with fixed parameters
+    Template {
+        template_id: Uuid,
+        #[serde(default)]
+        parameters: HashMap<String, serde_json::Value>,
+    },
+}
+
+/// Outcome of a scheduled run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleRunStatus {
+    Running,
+    Completed,
+    Failed,
+    /// The schedule fired while its previous run was still in flight
+    Skipped,
+}
+
+impl ScheduleRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleRunStatus::Running => "running",
+            ScheduleRunStatus::Completed => "completed",
+            ScheduleRunStatus::Failed => "failed",
+            ScheduleRunStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// A research workflow or template run on a cron schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResearchSchedule {
+    pub id: Uuid,
+    pub name: String,
+    pub target: ScheduleTarget,
+    /// Standard five-field cron expression in UTC, e.g. "0 9 * * Mon"; a
+    /// leading seconds field is also accepted. Name weekdays rather than
+    /// numbering them, since numbered weekdays start from Sunday = 1
+    pub cron_expression: String,
+    pub paused: bool,
+    /// Compare each run's results with the previous completed run
+    pub diff_against_previous: bool,
+    /// Endpoint that receives a JSON summary of every finished run
+    pub webhook_url: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_run_status: Option<ScheduleRunStatus>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ResearchSchedule {
+    /// Create a schedule from a request, with its first run time worked out
+    pub fn new(request: CreateScheduleRequest, now: DateTime<Utc>) -> Result<Self, String> {
+        let next_run_at = next_cron_run(&request.cron_expression, now)?;
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            name: request.name,
+            target: request.target,
+            cron_expression: request.cron_expression,
+            paused: false,
+            diff_against_previous: request.diff_against_previous,
+            webhook_url: request.webhook_url,
+            next_run_at: Some(next_run_at),
+            last_run_at: None,
+            last_run_status: None,
+            created_by: request.created_by,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Whether the schedule should fire at `now`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.paused && self.next_run_at.map_or(false, |next| next <= now)
+    }
+
+    /// Move the next run past `now`; missed runs are skipped, not replayed
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Result<(), String> {
+        self.next_run_at = Some(next_cron_run(&self.cron_expression, now)?);
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// Request to create a research schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    pub target: ScheduleTarget,
+    pub cron_expression: String,
+    #[serde(default = "default_diff_against_previous")]
+    pub diff_against_previous: bool,
+    pub webhook_url: Option<String>,
+    pub created_by: String,
+}
+
+fn default_diff_against_previous() -> bool {
+    true
+}
+
+/// One firing of a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    pub workflow_id: Option<Uuid>,
+    pub status: ScheduleRunStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    /// Changes since the previous completed run, when diffing is enabled
+    pub diff: Option<ResultDiff>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub delivery_error: Option<String>,
+}
+
+impl ScheduleRun {
+    pub fn new(schedule_id: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            schedule_id,
+            workflow_id: None,
+            status: ScheduleRunStatus::Running,
+            started_at: now,
+            finished_at: None,
+            error_message: None,
+            diff: None,
+            delivered_at: None,
+            delivery_error: None,
+        }
+    }
+
+    pub fn finish(&mut self, status: ScheduleRunStatus, error_message: Option<String>, now: DateTime<Utc>) {
+        self.status = status;
+        self.error_message = error_message;
+        self.finished_at = Some(now);
+    }
+
+    /// Whether the run is still marked running more than `timeout` after it started,
+    /// as when its workflow was lost to a crash
+    pub fn is_stale(&self, now: DateTime<Utc>, timeout: chrono::Duration) -> bool {
+        self.status == ScheduleRunStatus::Running && now - self.started_at > timeout
+    }
+}
+
+/// What changed between two runs' results
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResultDiff {
+    pub previous_run_id: Option<Uuid>,
+    pub new_sources: Vec<String>,
+    pub removed_sources: Vec<String>,
+    /// Paragraphs of the new report that the previous report did not contain
+    pub new_findings: Vec<String>,
+}
+
+impl ResultDiff {
+    /// Compare `current` with `previous`; with no previous results everything is new
+    pub fn between(previous_run_id: Option<Uuid>, previous: Option<&ResearchResults>, current: &ResearchResults) -> Self {
+        let previous_sources: HashSet<&str> = previous
+            .map(|results| results.sources.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        let current_sources: HashSet<&str> = current.sources.iter().map(String::as_str).collect();
+        let previous_paragraphs: HashSet<String> = previous
+            .map(|results| paragraphs(&results.content).collect())
+            .unwrap_or_default();
+
+        Self {
+            previous_run_id,
+            new_sources: current.sources.iter()
+                .filter(|source| !previous_sources.contains(source.as_str()))
+                .cloned()
+                .collect(),
+            removed_sources: previous
+                .map(|results| results.sources.iter()
+                    .filter(|source| !current_sources.contains(source.as_str()))
+                    .cloned()
+                    .collect())
+                .unwrap_or_default(),
+            new_findings: paragraphs(&current.content)
+                .filter(|paragraph| !previous_paragraphs.contains(paragraph))
+                .collect(),
+        }
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.new_sources.is_empty() || !self.removed_sources.is_empty() || !self.new_findings.is_empty()
+    }
+}
+
+/// Non-empty paragraphs with whitespace normalized, so reflowed text still matches
+fn paragraphs(content: &str) -> impl Iterator<Item = String> + '_ {
+    content.split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+}
+
+/// First time after `after` that a cron expression fires
+pub fn next_cron_run(expression: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let fields = expression.split_whitespace().count();
+    // The cron crate wants a seconds field; standard five-field expressions fire on the minute
+    let expression = match fields {
+        5 => format!("0 {}", expression.trim()),
+        6 | 7 => expression.trim().to_string(),
+        _ => return Err(format!("Cron expression must have five fields, got {}: '{}'", fields, expression)),
+    };
+
+    let schedule = cron::Schedule::from_str(&expression)
+        .map_err(|e| format!("Invalid cron expression '{}': {}", expression, e))?;
+    schedule.after(&after)
+        .next()
+        .ok_or_else(|| format!("Cron expression '{}' never fires", expression))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::models::research_workflow::ResearchMethodology;
+
+    #[test]
+    fn test_next_cron_run_accepts_five_fields() {
+        // Wednesday 2024-05-01 10:30 UTC; next Monday 09:00 is 2024-05-06
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap();
+        assert_eq!(
+            next_cron_run("0 9 * * Mon", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap()
+        );
+        assert!(next_cron_run("every monday", now).is_err());
+        assert!(next_cron_run("0 9 * *", now).is_err());
+    }
+
+    #[test]
+    fn test_result_diff_highlights_new_material() {
+        let results = |content: &str, sources: &[&str]| ResearchResults {
+            content: content.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            metadata: HashMap::new(),
+            word_count: 0,
+            source_count: sources.len() as u32,
+            methodology_used: ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        };
+
+        let previous = results("Finding one.\n\nFinding two.", &["https://a.example", "https://b.example"]);
+        let current = results("Finding  one.\n\nFinding three.", &["https://b.example", "https://c.example"]);
+
+        let diff = ResultDiff::between(None, Some(&previous), &current);
+        assert_eq!(diff.new_sources, vec!["https://c.example".to_string()]);
+        assert_eq!(diff.removed_sources, vec!["https://a.example".to_string()]);
+        assert_eq!(diff.new_findings, vec!["Finding three.".to_string()]);
+        assert!(!ResultDiff::between(None, Some(&current), &current).has_changes());
+    }
+
+    #[test]
+    fn test_run_is_stale_only_while_running_past_timeout() {
+        let started = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let timeout = chrono::Duration::hours(6);
+        let mut run = ScheduleRun::new(Uuid::new_v4(), started);
+
+        assert!(!run.is_stale(started + chrono::Duration::hours(5), timeout));
+        assert!(run.is_stale(started + chrono::Duration::hours(7), timeout));
+
+        run.finish(ScheduleRunStatus::Completed, None, started + chrono::Duration::hours(1));
+        assert!(!run.is_stale(started + chrono::Duration::hours(7), timeout));
+    }
+}
@@ -291,6 +291,9 @@ pub struct ResearchTemplate {
     pub methodology: ResearchMethodology,
     pub parameters: Vec<TemplateParameter>,
     pub steps: Vec<TemplateStep>,
+    /// Research query generated from parameters, e.g. "latest research on {{topic}} since {{year}}"
+    #[serde(default)]
+    pub query_pattern: Option<String>,
     pub workflow_config: WorkflowParameters,
     pub tags: Vec<String>,
     pub author: String,
@@ -325,6 +328,7 @@ impl ResearchTemplate {
             methodology,
             parameters: Vec::new(),
             steps: Vec::new(),
+            query_pattern: None,
             workflow_config: WorkflowParameters::default(),
             tags: Vec::new(),
             author,
@@ -387,6 +391,74 @@ impl ResearchTemplate {
         result
     }
 
+    /// Placeholder names in the query pattern, in order of first appearance
+    pub fn query_placeholders(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut rest = self.query_pattern.as_deref().unwrap_or_default();
+
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else { break };
+            let name = after[..end].trim().to_string();
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+            rest = &after[end + 2..];
+        }
+
+        names
+    }
+
+    /// Query placeholders that no template parameter defines
+    pub fn undeclared_query_placeholders(&self) -> Vec<String> {
+        self.query_placeholders()
+            .into_iter()
+            .filter(|name| !self.parameters.iter().any(|param| &param.id == name))
+            .collect()
+    }
+
+    /// Fill the query pattern from `parameters`, which should already have
+    /// defaults applied. Returns `Ok(None)` when the template has no pattern.
+    pub fn render_query(&self, parameters: &HashMap<String, serde_json::Value>) -> Result<Option<String>, Vec<String>> {
+        let Some(pattern) = &self.query_pattern else {
+            return Ok(None);
+        };
+
+        let mut errors = Vec::new();
+        let mut query = String::new();
+        let mut rest = pattern.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else { break };
+            query.push_str(&rest[..start]);
+            rest = &after[end + 2..];
+
+            let name = after[..end].trim();
+            let error = match self.parameters.iter().find(|param| param.id == name) {
+                Some(template_param) => match parameters.get(name).map(query_value).filter(|value| !value.trim().is_empty()) {
+                    Some(value) => {
+                        query.push_str(&value);
+                        continue;
+                    }
+                    None => format!("Parameter '{}' is required by the query pattern", template_param.name),
+                },
+                None => format!("Query placeholder '{}' has no matching parameter", name),
+            };
+            // A placeholder used more than once is only reported once
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        query.push_str(rest);
+
+        if errors.is_empty() {
+            Ok(Some(query.split_whitespace().collect::<Vec<_>>().join(" ")))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Update rating
     pub fn update_rating(&mut self, new_rating: f64) {
         let total_rating = self.rating * self.rating_count as f64 + new_rating;
@@ -407,6 +479,20 @@ impl ResearchTemplate {
     }
 }
 
+/// A parameter value as it reads inside a query: strings unquoted, lists comma-separated
+fn query_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items.iter()
+            .map(query_value)
+            .filter(|item| !item.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
 /// Template execution context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateExecutionContext {
@@ -478,3 +564,39 @@ impl TemplateMetrics {
         self.performance_score = (self.success_rate * 0.7) + (time_score * 0.3);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_query_fills_placeholders() {
+        let mut template = ResearchTemplate::new(
+            "Recent research".to_string(),
+            "Latest work on a topic".to_string(),
+            TemplateCategory::Academic,
+            ResearchMethodology::Hybrid,
+            "tests".to_string(),
+        );
+        template.query_pattern = Some("latest research on {{topic}} since {{ year }}".to_string());
+        template.add_parameter(TemplateParameter::new("topic".to_string(), "Topic".to_string(), String::new(), ParameterType::String, true));
+        template.add_parameter(TemplateParameter::new("year".to_string(), "Year".to_string(), String::new(), ParameterType::Number, false));
+        assert!(template.undeclared_query_placeholders().is_empty());
+
+        let parameters = HashMap::from([
+            ("topic".to_string(), serde_json::json!("solid-state batteries")),
+            ("year".to_string(), serde_json::json!(2022)),
+        ]);
+        assert_eq!(
+            template.render_query(&parameters).unwrap().as_deref(),
+            Some("latest research on solid-state batteries since 2022")
+        );
+
+        // Optional parameters still have to be supplied once the pattern uses them
+        let errors = template.render_query(&HashMap::from([("topic".to_string(), serde_json::json!("x"))])).unwrap_err();
+        assert_eq!(errors, vec!["Parameter 'Year' is required by the query pattern".to_string()]);
+
+        template.query_pattern = Some("{{topic}} in {{region}}".to_string());
+        assert_eq!(template.undeclared_query_placeholders(), vec!["region".to_string()]);
+    }
+}
@@ -4,12 +4,14 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Research methodology types
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum ResearchMethodology {
     DonLim,
     NickScamara,
     Hybrid,
+    /// Hybrid research that also reads the text of scanned documents and images
+    DocumentAnalysis,
 }
 
 impl ResearchMethodology {
@@ -19,6 +21,7 @@ impl ResearchMethodology {
             ResearchMethodology::DonLim => "Don Lim (OpenRouter + SerpApi + Jina AI)",
             ResearchMethodology::NickScamara => "Nick Scamara (Firecrawl + AI SDK)",
             ResearchMethodology::Hybrid => "Hybrid (Combined Methodologies)",
+            ResearchMethodology::DocumentAnalysis => "Document Analysis (Hybrid + OCR)",
         }
     }
     
@@ -28,6 +31,19 @@ impl ResearchMethodology {
             ResearchMethodology::DonLim => "Cost-optimized approach using OpenRouter.ai, SerpApi, and Jina AI for comprehensive research",
             ResearchMethodology::NickScamara => "Professional interface approach using Firecrawl and AI SDK for advanced web scraping",
             ResearchMethodology::Hybrid => "Intelligent combination of both methodologies for maximum research coverage",
+            ResearchMethodology::DocumentAnalysis => "Hybrid research that also extracts text from scanned documents and images for document-heavy topics",
+        }
+    }
+
+    /// How long a step may run when it sets no timeout of its own
+    pub fn default_step_timeout_seconds(&self) -> u32 {
+        match self {
+            ResearchMethodology::DonLim => 180,
+            // Scraping full pages through Firecrawl is the slowest provider call
+            ResearchMethodology::NickScamara => 300,
+            ResearchMethodology::Hybrid => 300,
+            // Images are read one after another, each allowed up to 30 seconds
+            ResearchMethodology::DocumentAnalysis => 420,
         }
     }
 }
@@ -88,6 +104,12 @@ pub struct WorkflowStep {
     pub max_retries: u32,
     pub depends_on: Vec<Uuid>, // Step dependencies
     pub metadata: HashMap<String, String>,
+    /// Longest the step may run before it is aborted; `None` uses the methodology default
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+    /// The last failure cannot be fixed by retrying, e.g. the provider refused the query
+    #[serde(default)]
+    pub non_retryable: bool,
 }
 
 impl WorkflowStep {
@@ -117,9 +139,23 @@ impl WorkflowStep {
             max_retries: 3,
             depends_on: Vec::new(),
             metadata: HashMap::new(),
+            timeout_seconds: None,
+            non_retryable: false,
         }
     }
 
+    /// The timeout the step runs under in a workflow using `methodology`
+    pub fn effective_timeout_seconds(&self, methodology: &ResearchMethodology) -> u32 {
+        self.timeout_seconds.unwrap_or_else(|| methodology.default_step_timeout_seconds())
+    }
+
+    /// Seconds the step has been running, or ran for once finished
+    pub fn elapsed_seconds(&self) -> Option<f64> {
+        let started = self.started_at?;
+        let ended = self.completed_at.unwrap_or_else(Utc::now);
+        Some((ended - started).num_milliseconds() as f64 / 1000.0)
+    }
+
     /// Mark step as started
     pub fn start(&mut self) {
         self.status = StepStatus::Running;
@@ -148,6 +184,12 @@ impl WorkflowStep {
         }
     }
 
+    /// Mark step as failed in a way retrying will not fix
+    pub fn fail_permanently(&mut self, error: String) {
+        self.fail(error);
+        self.non_retryable = true;
+    }
+
     /// Check if step can be executed (dependencies met)
     pub fn can_execute(&self, completed_steps: &[Uuid]) -> bool {
         self.status == StepStatus::Pending &&
@@ -156,7 +198,7 @@ impl WorkflowStep {
 
     /// Check if step should be retried
     pub fn should_retry(&self) -> bool {
-        self.status == StepStatus::Failed && self.retry_count < self.max_retries
+        self.status == StepStatus::Failed && !self.non_retryable && self.retry_count < self.max_retries
     }
 
     /// Increment retry count
@@ -180,6 +222,219 @@ pub struct WorkflowParameters {
     pub save_intermediate_results: bool,
     pub enable_caching: bool,
     pub custom_parameters: HashMap<String, serde_json::Value>,
+    /// Capture provider responses for debugging, or replay a captured run
+    #[serde(default)]
+    pub provider_recording: ProviderRecording,
+    /// Check the synthesized report for text copied verbatim from its sources
+    #[serde(default)]
+    pub overlap_check: OverlapCheck,
+    /// Surface claims the sources disagree on as contradiction insights
+    #[serde(default)]
+    pub contradiction_check: ContradictionCheck,
+    /// What happens to the completed steps' output when a step fails for good
+    #[serde(default)]
+    pub failure_mode: FailureMode,
+    /// Retries allowed across all steps together; once spent, a failing step fails the
+    /// workflow. `None` leaves each step to retry up to its own `max_retries`.
+    #[serde(default)]
+    pub retry_budget: Option<u32>,
+    /// Named egress profile every provider request of the workflow goes out through;
+    /// `None` uses the default clients
+    #[serde(default)]
+    pub egress_profile: Option<String>,
+    /// Share the run of an identical workflow already in flight, and its results,
+    /// instead of running the pipeline again. Off runs every workflow independently.
+    #[serde(default)]
+    pub share_in_flight_runs: bool,
+    /// How recent the sources web searches return must be
+    #[serde(default)]
+    pub time_range: SearchTimeRange,
+    /// Only sources published after this; combined with `time_range`, the later cutoff wins
+    #[serde(default)]
+    pub published_after: Option<DateTime<Utc>>,
+    /// Keep insights below a minimum confidence out of the report
+    #[serde(default)]
+    pub insight_confidence: InsightConfidenceFilter,
+}
+
+impl WorkflowParameters {
+    pub fn search_recency(&self) -> SearchRecency {
+        SearchRecency { time_range: self.time_range, published_after: self.published_after }
+    }
+}
+
+/// Relative window a web search's sources must have been published in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchTimeRange {
+    #[default]
+    Any,
+    #[serde(alias = "last_day")]
+    PastDay,
+    #[serde(alias = "last_week")]
+    PastWeek,
+    #[serde(alias = "last_month")]
+    PastMonth,
+    #[serde(alias = "last_year")]
+    PastYear,
+}
+
+impl SearchTimeRange {
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        match self {
+            SearchTimeRange::Any => None,
+            SearchTimeRange::PastDay => Some(chrono::Duration::days(1)),
+            SearchTimeRange::PastWeek => Some(chrono::Duration::weeks(1)),
+            SearchTimeRange::PastMonth => Some(chrono::Duration::days(31)),
+            SearchTimeRange::PastYear => Some(chrono::Duration::days(366)),
+        }
+    }
+}
+
+/// The recency a workflow asks of its web search sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchRecency {
+    #[serde(default)]
+    pub time_range: SearchTimeRange,
+    #[serde(default)]
+    pub published_after: Option<DateTime<Utc>>,
+}
+
+impl SearchRecency {
+    pub fn is_any(&self) -> bool {
+        self.time_range == SearchTimeRange::Any && self.published_after.is_none()
+    }
+
+    /// Oldest publication date a source may have, whichever of the range and the date is stricter
+    pub fn cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let relative = self.time_range.duration().map(|window| now - window);
+        match (relative, self.published_after) {
+            (Some(relative), Some(after)) => Some(relative.max(after)),
+            (relative, after) => relative.or(after),
+        }
+    }
+
+    /// The relative range when it is the stricter of the two, so providers can be asked for
+    /// it as a range rather than a date
+    pub fn relative_range(&self, now: DateTime<Utc>) -> Option<SearchTimeRange> {
+        let window = self.time_range.duration()?;
+        match self.published_after {
+            Some(after) if after > now - window => None,
+            _ => Some(self.time_range),
+        }
+    }
+}
+
+/// How a workflow ends when one of its steps fails with no retries left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureMode {
+    /// Fail the workflow and keep no results
+    #[default]
+    Strict,
+    /// Fail the workflow but keep results built from the steps that completed, marked partial.
+    /// A step refused on content-policy grounds still fails the workflow outright.
+    Lenient,
+}
+
+/// Whether a workflow's provider calls are recorded to disk, served from an earlier
+/// recording or answered by the mock providers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ProviderRecording {
+    #[default]
+    Off,
+    /// Persist the redacted provider response of every call, per step
+    Record,
+    /// Feed the responses recorded by an earlier workflow back through the
+    /// pipeline instead of calling live APIs
+    Replay { source_workflow_id: Uuid },
+    /// Answer every call with the built-in mock integrations after `latency_ms`, failing
+    /// `failure_percent` of them, without spending keys or quota. Used by load tests.
+    Mock { latency_ms: u32, failure_percent: u8 },
+}
+
+/// Where insights below a workflow's minimum confidence go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LowConfidenceInsights {
+    /// Drop them from the report
+    #[default]
+    Omit,
+    /// Move them to a "Low Confidence Insights" section at the end of the report
+    Separate,
+}
+
+/// Minimum confidence an insight needs to appear among the report's insights.
+/// Insights without a confidence, such as those parsed from report text, always pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InsightConfidenceFilter {
+    /// From 0.0 to 1.0; `None` keeps every insight
+    pub min_confidence: Option<f32>,
+    pub low_confidence: LowConfidenceInsights,
+}
+
+impl InsightConfidenceFilter {
+    pub fn is_active(&self) -> bool {
+        self.min_confidence.is_some_and(|min| min > 0.0)
+    }
+
+    /// Whether an insight with `confidence` is kept among the report's insights
+    pub fn keeps(&self, confidence: Option<f32>) -> bool {
+        match (self.min_confidence, confidence) {
+            (Some(min), Some(confidence)) => confidence >= min,
+            _ => true,
+        }
+    }
+}
+
+/// Settings for the n-gram overlap check between a report and its sources
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlapCheck {
+    pub enabled: bool,
+    /// Words per n-gram compared between the report and each source
+    pub ngram_size: usize,
+    /// Share of a span's n-grams found in one source above which the span counts as copied
+    pub threshold: f64,
+    /// Shortest run of report words that is flagged
+    pub min_span_words: usize,
+    /// Ask the model to rewrite flagged spans in its own words
+    pub paraphrase_flagged: bool,
+}
+
+impl Default for OverlapCheck {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ngram_size: 6,
+            threshold: 0.8,
+            min_span_words: 12,
+            paraphrase_flagged: false,
+        }
+    }
+}
+
+/// Settings for the check of a finished workflow's sources for claims that disagree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContradictionCheck {
+    pub enabled: bool,
+    /// Sentences of each source, from its start, the NLP engine reads for entities and sentiment
+    pub max_sentences_per_source: usize,
+    /// Figures stated about one entity that are compared with each other; later ones are left out
+    pub max_figures_per_entity: usize,
+}
+
+impl Default for ContradictionCheck {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_sentences_per_source: 50,
+            max_figures_per_entity: 20,
+        }
+    }
 }
 
 impl Default for WorkflowParameters {
@@ -196,6 +451,16 @@ impl Default for WorkflowParameters {
             save_intermediate_results: true,
             enable_caching: true,
             custom_parameters: HashMap::new(),
+            provider_recording: ProviderRecording::Off,
+            overlap_check: OverlapCheck::default(),
+            contradiction_check: ContradictionCheck::default(),
+            failure_mode: FailureMode::default(),
+            retry_budget: None,
+            egress_profile: None,
+            share_in_flight_runs: false,
+            time_range: SearchTimeRange::Any,
+            published_after: None,
+            insight_confidence: InsightConfidenceFilter::default(),
         }
     }
 }
@@ -210,6 +475,20 @@ pub struct ResearchResults {
     pub source_count: u32,
     pub methodology_used: ResearchMethodology,
     pub execution_time_ms: u64,
+    /// Set when some steps failed and the results cover only the steps that completed
+    #[serde(default)]
+    pub partial: bool,
+    #[serde(default)]
+    pub failed_steps: Vec<FailedStep>,
+}
+
+/// A step missing from partial results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedStep {
+    pub step_id: Uuid,
+    pub step_number: u32,
+    pub name: String,
+    pub error: Option<String>,
 }
 
 /// Research workflow model
@@ -231,9 +510,31 @@ pub struct ResearchWorkflow {
     pub updated_at: DateTime<Utc>,
     pub created_by: String,
     pub tags: Vec<String>,
+    /// Folder path such as `Energy/Batteries`; `None` keeps the workflow at the top level
+    #[serde(default)]
+    pub folder: Option<String>,
     pub metadata: HashMap<String, String>,
 }
 
+/// Separator between the segments of a folder path
+pub const FOLDER_SEPARATOR: char = '/';
+
+/// A tag trimmed and lowercased with inner whitespace collapsed, or `None` if blank
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// A folder path with its segments trimmed and empty segments dropped, or `None` if
+/// nothing is left
+pub fn normalize_folder(folder: &str) -> Option<String> {
+    let segments: Vec<&str> = folder.split(FOLDER_SEPARATOR)
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    (!segments.is_empty()).then(|| segments.join(&FOLDER_SEPARATOR.to_string()))
+}
+
 impl ResearchWorkflow {
     /// Create a new research workflow
     pub fn new(name: String, query: String, parameters: WorkflowParameters, created_by: String) -> Self {
@@ -256,10 +557,31 @@ impl ResearchWorkflow {
             updated_at: now,
             created_by,
             tags: Vec::new(),
+            folder: None,
             metadata: HashMap::new(),
         }
     }
 
+    /// Add and remove tags, keeping the set normalized, free of duplicates and sorted
+    pub fn retag(&mut self, add: &[String], remove: &[String]) {
+        let remove: Vec<String> = remove.iter().filter_map(|tag| normalize_tag(tag)).collect();
+        let mut tags: Vec<String> = self.tags.iter()
+            .chain(add)
+            .filter_map(|tag| normalize_tag(tag))
+            .filter(|tag| !remove.contains(tag))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        self.tags = tags;
+        self.updated_at = Utc::now();
+    }
+
+    /// Move the workflow into `folder`, or to the top level with `None`
+    pub fn move_to_folder(&mut self, folder: Option<&str>) {
+        self.folder = folder.and_then(normalize_folder);
+        self.updated_at = Utc::now();
+    }
+
     /// Add a step to the workflow
     pub fn add_step(&mut self, mut step: WorkflowStep) {
         step.workflow_id = self.id;
@@ -313,6 +635,27 @@ impl ResearchWorkflow {
         self.completed_at = Some(Utc::now());
         self.updated_at = Utc::now();
     }
+
+    /// Fail the workflow, keeping `results` built from the steps that completed
+    pub fn fail_with_partial_results(&mut self, mut results: ResearchResults, error_message: String) {
+        results.partial = true;
+        results.failed_steps = self.failed_steps();
+        self.results = Some(results);
+        self.fail(error_message);
+    }
+
+    /// Steps that failed, in step order
+    pub fn failed_steps(&self) -> Vec<FailedStep> {
+        self.steps.iter()
+            .filter(|step| step.status == StepStatus::Failed)
+            .map(|step| FailedStep {
+                step_id: step.id,
+                step_number: step.step_number,
+                name: step.name.clone(),
+                error: step.error_message.clone(),
+            })
+            .collect()
+    }
     
     /// Cancel the workflow
     pub fn cancel(&mut self) {
@@ -355,13 +698,35 @@ impl ResearchWorkflow {
             .collect()
     }
 
-    /// Get failed steps that can be retried
+    /// Get failed steps that can be retried; none once the retry budget is spent
     pub fn get_retryable_steps(&self) -> Vec<&WorkflowStep> {
+        if self.retry_budget_exhausted() {
+            return Vec::new();
+        }
         self.steps.iter()
             .filter(|step| step.should_retry())
             .collect()
     }
 
+    /// First step whose failure retrying will not fix
+    pub fn permanently_failed_step(&self) -> Option<&WorkflowStep> {
+        self.steps.iter().find(|step| step.status == StepStatus::Failed && step.non_retryable)
+    }
+
+    /// Retries made so far across all steps
+    pub fn retries_used(&self) -> u32 {
+        self.steps.iter().map(|step| step.retry_count).sum()
+    }
+
+    /// Retries left in the workflow's retry budget, if it has one
+    pub fn remaining_retry_budget(&self) -> Option<u32> {
+        self.parameters.retry_budget.map(|budget| budget.saturating_sub(self.retries_used()))
+    }
+
+    pub fn retry_budget_exhausted(&self) -> bool {
+        self.remaining_retry_budget() == Some(0)
+    }
+
     /// Get running steps
     pub fn get_running_steps(&self) -> Vec<&WorkflowStep> {
         self.steps.iter()
@@ -413,6 +778,13 @@ pub struct CreateWorkflowRequest {
     pub query: String,
     pub template_id: Option<Uuid>,
     pub parameters: Option<WorkflowParameters>,
+    /// Region the workflow's data must stay in, from the requesting tenant
+    #[serde(default)]
+    pub data_region: crate::models::DataRegion,
+    /// Client-chosen key that makes retries of this request return the workflow the
+    /// first attempt created instead of creating another
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 /// Research workflow update request
@@ -451,3 +823,112 @@ impl ResearchTemplate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_results_record_the_failed_steps() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid-scale storage costs".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        for name in ["search", "extract", "synthesize"] {
+            workflow.add_step(WorkflowStep::new(workflow.id, 0, name.to_string(), String::new()));
+        }
+        workflow.steps[0].complete(HashMap::new());
+        workflow.steps[1].complete(HashMap::new());
+        workflow.steps[2].fail("model unavailable".to_string());
+
+        let results = ResearchResults {
+            content: "Sources found so far".to_string(),
+            sources: vec!["https://example.org/storage".to_string()],
+            metadata: HashMap::new(),
+            word_count: 4,
+            source_count: 1,
+            methodology_used: ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        };
+        workflow.fail_with_partial_results(results, "Workflow failed with non-retryable errors".to_string());
+
+        assert_eq!(workflow.status, WorkflowStatus::Failed);
+        let results = workflow.results.as_ref().unwrap();
+        assert!(results.partial);
+        assert_eq!(results.failed_steps.len(), 1);
+        assert_eq!(results.failed_steps[0].name, "synthesize");
+        assert_eq!(results.failed_steps[0].step_number, 3);
+        assert_eq!(results.failed_steps[0].error.as_deref(), Some("model unavailable"));
+
+        // Persisted before these fields existed: not partial, and strict by default
+        let mut stored = serde_json::to_value(results).unwrap();
+        stored.as_object_mut().unwrap().retain(|key, _| key != "partial" && key != "failed_steps");
+        assert!(!serde_json::from_value::<ResearchResults>(stored).unwrap().partial);
+        assert_eq!(WorkflowParameters::default().failure_mode, FailureMode::Strict);
+    }
+
+    #[test]
+    fn test_step_timeout_falls_back_to_the_methodology_default() {
+        let mut step = WorkflowStep::new(Uuid::new_v4(), 1, "Web Scraping".to_string(), String::new());
+        assert_eq!(step.effective_timeout_seconds(&ResearchMethodology::NickScamara), 300);
+        assert_eq!(step.elapsed_seconds(), None);
+
+        step.timeout_seconds = Some(45);
+        step.started_at = Some(Utc::now() - chrono::Duration::seconds(30));
+        assert_eq!(step.effective_timeout_seconds(&ResearchMethodology::NickScamara), 45);
+        assert!(step.elapsed_seconds().unwrap() >= 30.0);
+    }
+
+    #[test]
+    fn test_tags_and_folders_are_normalized() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid-scale storage costs".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        workflow.retag(&["Energy ".to_string(), "grid  storage".to_string(), "energy".to_string(), " ".to_string()], &[]);
+        assert_eq!(workflow.tags, vec!["energy", "grid storage"]);
+        workflow.retag(&["policy".to_string()], &["ENERGY".to_string()]);
+        assert_eq!(workflow.tags, vec!["grid storage", "policy"]);
+
+        workflow.move_to_folder(Some(" Energy // Batteries/ "));
+        assert_eq!(workflow.folder.as_deref(), Some("Energy/Batteries"));
+        workflow.move_to_folder(Some("/"));
+        assert_eq!(workflow.folder, None);
+    }
+
+    #[test]
+    fn test_retry_budget_is_shared_by_every_step() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid-scale storage costs".to_string(),
+            WorkflowParameters { retry_budget: Some(2), ..WorkflowParameters::default() },
+            "tester".to_string(),
+        );
+        for name in ["search", "extract"] {
+            workflow.add_step(WorkflowStep::new(workflow.id, 0, name.to_string(), String::new()));
+        }
+
+        workflow.steps[0].fail("timeout".to_string());
+        workflow.steps[0].increment_retry();
+        workflow.steps[1].fail("timeout".to_string());
+        assert_eq!(workflow.remaining_retry_budget(), Some(1));
+        assert_eq!(workflow.get_retryable_steps().len(), 1);
+
+        workflow.steps[1].increment_retry();
+        workflow.steps[1].fail("timeout".to_string());
+        // The step has retries of its own left, but the workflow has none
+        assert!(workflow.steps[1].should_retry());
+        assert!(workflow.retry_budget_exhausted());
+        assert!(workflow.get_retryable_steps().is_empty());
+
+        workflow.parameters.retry_budget = None;
+        assert_eq!(workflow.remaining_retry_budget(), None);
+        assert_eq!(workflow.get_retryable_steps().len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where a saga is in running its steps or undoing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SagaStatus {
+    Running,
+    Compensating,
+    Completed,
+    Compensated,
+    /// A compensation failed; its side effects need manual cleanup
+    CompensationFailed,
+}
+
+impl SagaStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SagaStatus::Running => "running",
+            SagaStatus::Compensating => "compensating",
+            SagaStatus::Completed => "completed",
+            SagaStatus::Compensated => "compensated",
+            SagaStatus::CompensationFailed => "compensation_failed",
+        }
+    }
+
+    /// Whether the saga has nothing left to run or undo
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, SagaStatus::Running | SagaStatus::Compensating)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SagaStepStatus {
+    Pending,
+    /// Executing; if the saga is recovered in this state the step may have partly applied
+    Running,
+    Completed,
+    Failed,
+    Compensated,
+    CompensationFailed,
+}
+
+/// One step of a saga and how far it got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SagaStepRecord {
+    pub name: String,
+    pub status: SagaStepStatus,
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Progress of a multi-service operation on a workflow, persisted after every step so
+/// an operation interrupted by a restart can still be compensated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SagaState {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    pub status: SagaStatus,
+    /// Steps in execution order
+    pub steps: Vec<SagaStepRecord>,
+    /// Why the saga stopped running its steps
+    pub failure: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SagaState {
+    pub fn new(workflow_id: Uuid, step_names: &[&str]) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            workflow_id,
+            status: SagaStatus::Running,
+            steps: step_names.iter()
+                .map(|name| SagaStepRecord {
+                    name: name.to_string(),
+                    status: SagaStepStatus::Pending,
+                    error: None,
+                    updated_at: now,
+                })
+                .collect(),
+            failure: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn set_step_status(&mut self, index: usize, status: SagaStepStatus, error: Option<String>) {
+        let now = Utc::now();
+        if let Some(step) = self.steps.get_mut(index) {
+            step.status = status;
+            step.error = error;
+            step.updated_at = now;
+        }
+        self.updated_at = now;
+    }
+
+    pub fn set_status(&mut self, status: SagaStatus) {
+        self.status = status;
+        self.updated_at = Utc::now();
+    }
+
+    /// Indexes of steps whose side effects may be in place and not yet undone, latest first
+    pub fn steps_to_compensate(&self) -> Vec<usize> {
+        (0..self.steps.len())
+            .rev()
+            .filter(|&index| matches!(self.steps[index].status, SagaStepStatus::Running | SagaStepStatus::Completed))
+            .collect()
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Requests we counted for one key over a date range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyUsageTotals {
+    pub api_key_id: Uuid,
+    /// Service name as stored with the usage stats, e.g. `serpapi`
+    pub service: String,
+    pub request_count: u32,
+    pub success_count: u32,
+    pub error_count: u32,
+}
+
+/// A usage counter a provider reported for a key, one per response carrying it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderUsageSnapshot {
+    pub api_key_id: Uuid,
+    pub service: String,
+    /// Day the report was seen, `YYYY-MM-DD`
+    pub date_bucket: String,
+    /// Requests the provider says the key has used in its current window
+    pub reported_used: u32,
+    pub reported_limit: Option<u32>,
+    pub observed_at: DateTime<Utc>,
+}
+
+/// How far our count of a key's requests was from the provider's over a date range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageDivergence {
+    pub api_key_id: Uuid,
+    pub service: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub counted: u32,
+    pub reported: u32,
+    /// Our count less the provider's; negative when the provider counted more
+    pub delta: i64,
+    /// `delta` as a percentage of the provider's count
+    pub divergence_percent: f64,
+    pub threshold_exceeded: bool,
+    pub checked_at: DateTime<Utc>,
+}
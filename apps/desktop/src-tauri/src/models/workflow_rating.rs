@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::{ResearchMethodology, ResearchWorkflow};
+
+/// Lowest and highest rating a user can give a workflow
+pub const MIN_RATING: u8 = 1;
+pub const MAX_RATING: u8 = 5;
+
+/// Longest note a rating may carry
+pub const MAX_RATING_NOTES_LEN: usize = 2000;
+
+/// Broad subject area of a research query; ratings are compared within a domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryDomain {
+    Medical,
+    Legal,
+    Finance,
+    Technology,
+    Science,
+    General,
+}
+
+impl QueryDomain {
+    const KEYWORDS: &'static [(QueryDomain, &'static [&'static str])] = &[
+        (QueryDomain::Medical, &["clinical", "disease", "drug", "health", "medical", "patient", "therapy", "treatment", "vaccine"]),
+        (QueryDomain::Legal, &["compliance", "court", "law", "legal", "legislation", "regulation", "statute"]),
+        (QueryDomain::Finance, &["finance", "financial", "investment", "market", "pricing", "revenue", "stock", "valuation"]),
+        (QueryDomain::Technology, &["ai", "cloud", "database", "hardware", "programming", "security", "software"]),
+        (QueryDomain::Science, &["biology", "chemistry", "climate", "experiment", "physics", "quantum", "scientific"]),
+    ];
+
+    /// The domain whose keywords the query mentions most; ties go to the first listed
+    pub fn classify(query: &str) -> Self {
+        let words: Vec<String> = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut best = (QueryDomain::General, 0);
+        for (domain, keywords) in Self::KEYWORDS {
+            let hits = words.iter().filter(|w| keywords.contains(&w.as_str())).count();
+            if hits > best.1 {
+                best = (*domain, hits);
+            }
+        }
+        best.0
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryDomain::Medical => "medical",
+            QueryDomain::Legal => "legal",
+            QueryDomain::Finance => "finance",
+            QueryDomain::Technology => "technology",
+            QueryDomain::Science => "science",
+            QueryDomain::General => "general",
+        }
+    }
+}
+
+/// A user's rating of how well a workflow's methodology worked for its query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRating {
+    pub workflow_id: Uuid,
+    pub rated_by: String,
+    pub methodology: ResearchMethodology,
+    pub query_domain: QueryDomain,
+    /// From `MIN_RATING` to `MAX_RATING`
+    pub rating: u8,
+    pub notes: Option<String>,
+    pub rated_at: DateTime<Utc>,
+}
+
+impl WorkflowRating {
+    /// A rating of `workflow` by `rated_by`, who must be the user who created it
+    pub fn new(workflow: &ResearchWorkflow, rated_by: &str, rating: u8, notes: Option<String>) -> AppResult<Self> {
+        if workflow.created_by != rated_by {
+            return Err(AppError::validation("rated_by", "only the user who ran a workflow can rate it"));
+        }
+        if !(MIN_RATING..=MAX_RATING).contains(&rating) {
+            return Err(AppError::validation("rating", format!("must be between {} and {}", MIN_RATING, MAX_RATING)));
+        }
+        let notes = notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if notes.as_ref().is_some_and(|n| n.len() > MAX_RATING_NOTES_LEN) {
+            return Err(AppError::validation("notes", format!("must be at most {} characters", MAX_RATING_NOTES_LEN)));
+        }
+
+        Ok(Self {
+            workflow_id: workflow.id,
+            rated_by: rated_by.to_string(),
+            methodology: workflow.parameters.methodology.clone(),
+            query_domain: QueryDomain::classify(&workflow.query),
+            rating,
+            notes,
+            rated_at: Utc::now(),
+        })
+    }
+}
+
+/// How one methodology has been rated within a domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodologyRatingSummary {
+    pub methodology: ResearchMethodology,
+    pub rating_count: u32,
+    pub mean_rating: f64,
+    /// Mean rating pulled toward the scale's midpoint while there are few ratings
+    pub adjusted_score: f64,
+}
+
+/// The methodology suggested for a query, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodologyRecommendation {
+    pub methodology: ResearchMethodology,
+    pub query_domain: QueryDomain,
+    pub default_methodology: ResearchMethodology,
+    /// The user's ratings changed the recommendation from the default
+    pub from_ratings: bool,
+    pub rationale: String,
+    /// Best rated first
+    pub ratings: Vec<MethodologyRatingSummary>,
+}
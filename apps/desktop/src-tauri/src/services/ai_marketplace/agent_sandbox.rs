@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::RwLock;
+use tracing::{info, debug, warn};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::ai_marketplace::{AgentResourceUsage, AgentRunResult, SandboxLimits, SandboxUsage, SandboxViolation};
+use crate::services::DataPersistenceService;
+use crate::utils::air_gap;
+
+/// How often a running agent's CPU and memory use is sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Largest response body passed back to an agent's fetch
+const MAX_FETCH_BODY_BYTES: usize = 1024 * 1024;
+
+/// Proxy the agent's environment points at, so that HTTP clients honouring the proxy
+/// variables fail fast instead of waiting to time out. Isolation does not rest on it:
+/// on Linux the agent runs in a network namespace of its own with no interfaces up.
+const BLACKHOLE_PROXY: &str = "http://127.0.0.1:9";
+
+/// Setting the agents' resource usage is saved under
+const USAGE_SETTING: &str = "agent_sandbox_usage";
+
+/// Lines an agent writes to stdout. Anything else it prints is taken as logging.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+enum AgentMessage {
+    /// Ask the host to fetch a URL; answered with a `fetch_result` line on stdin
+    Fetch { url: String },
+    Result { output: serde_json::Value },
+}
+
+/// Lines the host writes to the agent's stdin
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+enum HostMessage<'a> {
+    Input { input: &'a serde_json::Value },
+    /// Redirects are not followed; `location` is where one points, for the agent to fetch
+    /// in turn if it is allowlisted too
+    FetchResult { url: &'a str, status: Option<u16>, location: Option<String>, body: Option<String>, error: Option<String> },
+}
+
+/// Runs installed marketplace agents as child processes under CPU time, memory,
+/// wall-clock and network limits, terminating an agent that breaches one.
+///
+/// An agent reads its input as one JSON line on stdin and answers with a
+/// `{"type":"result","output":...}` line. It has no network of its own: it asks
+/// the host with `{"type":"fetch","url":...}`, which is served only for allowlisted hosts.
+///
+/// On Unix the CPU and memory limits are also set as rlimits, so the kernel stops an
+/// agent between samples, and the agent runs in its own process group, which is killed
+/// as a whole so that nothing it spawned outlives the run.
+pub struct AgentSandbox {
+    ceiling: RwLock<SandboxLimits>,
+    http_client: reqwest::Client,
+    usage: RwLock<HashMap<Uuid, AgentResourceUsage>>,
+    data_persistence: Option<Arc<RwLock<DataPersistenceService>>>,
+}
+
+impl AgentSandbox {
+    pub fn new(ceiling: SandboxLimits) -> AppResult<Self> {
+        // A redirect could lead off the allowlist, so the agent follows them itself
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::configuration(format!("Failed to create agent sandbox HTTP client: {}", e)))?;
+        Ok(Self { ceiling: RwLock::new(ceiling), http_client, usage: RwLock::new(HashMap::new()), data_persistence: None })
+    }
+
+    /// Keep the agents' resource usage in the database, starting from what was saved
+    pub async fn with_persistence(mut self, data_persistence: Arc<RwLock<DataPersistenceService>>) -> Self {
+        match data_persistence.read().await.get_setting::<Vec<AgentResourceUsage>>(USAGE_SETTING).await {
+            Ok(saved) => {
+                let saved = saved.unwrap_or_default();
+                self.usage = RwLock::new(saved.into_iter().map(|agent| (agent.agent_id, agent)).collect());
+            }
+            Err(e) => warn!("Failed to load agent resource usage, starting afresh: {}", e),
+        }
+        self.data_persistence = Some(data_persistence);
+        self
+    }
+
+    /// The most any agent may use, whatever its requirements ask for
+    pub async fn get_ceiling(&self) -> SandboxLimits {
+        self.ceiling.read().await.clone()
+    }
+
+    pub async fn update_ceiling(&self, ceiling: SandboxLimits) {
+        *self.ceiling.write().await = ceiling;
+    }
+
+    /// Run `program args` in `working_dir` within `limits`, feeding it `input`
+    pub async fn run(
+        &self,
+        agent_id: Uuid,
+        program: &str,
+        args: &[String],
+        working_dir: &Path,
+        input: &serde_json::Value,
+        limits: &SandboxLimits,
+    ) -> AppResult<AgentRunResult> {
+        info!("Running agent {} in sandbox ({}ms CPU, {}MB, {}s)", agent_id, limits.cpu_time_ms, limits.memory_mb, limits.wall_clock_seconds);
+
+        // The agent sees none of the host's environment, API keys included
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(working_dir)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        for variable in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "http_proxy", "https_proxy", "all_proxy"] {
+            command.env(variable, BLACKHOLE_PROXY);
+        }
+        #[cfg(unix)]
+        confine(&mut command, limits);
+
+        let mut child = command.spawn()
+            .map_err(|e| AppError::io(format!("Failed to start agent {}: {}", agent_id, e)))?;
+
+        let mut stdin = child.stdin.take()
+            .ok_or_else(|| AppError::internal("Agent stdin was not captured"))?;
+        let stdout = child.stdout.take()
+            .ok_or_else(|| AppError::internal("Agent stdout was not captured"))?;
+        let mut lines = BufReader::new(stdout).lines();
+
+        let input_line = serde_json::to_string(&HostMessage::Input { input })?;
+        stdin.write_all(format!("{}\n", input_line).as_bytes()).await?;
+
+        let pid = child.id().map(Pid::from_u32);
+        let mut system = System::new();
+        let mut usage = SandboxUsage::default();
+        let mut output = None;
+        let mut stdout_open = true;
+        let started = Instant::now();
+        let deadline = tokio::time::sleep(limits.wall_clock());
+        tokio::pin!(deadline);
+        let mut sampler = tokio::time::interval(SAMPLE_INTERVAL);
+
+        let outcome: Result<std::process::ExitStatus, SandboxViolation> = loop {
+            tokio::select! {
+                status = child.wait() => match status {
+                    Ok(status) => break Ok(status),
+                    Err(e) => return Err(AppError::io(format!("Failed to wait for agent {}: {}", agent_id, e))),
+                },
+                line = lines.next_line(), if stdout_open => match line {
+                    Ok(Some(line)) => match serde_json::from_str::<AgentMessage>(&line) {
+                        Ok(AgentMessage::Result { output: result }) => output = Some(result),
+                        Ok(AgentMessage::Fetch { url }) => {
+                            if let Err(violation) = limits.check_url(&url) {
+                                break Err(violation);
+                            }
+                            usage.network_requests += 1;
+                            // A slow host must not carry the agent past its wall-clock limit
+                            let reply = tokio::select! {
+                                reply = self.fetch(&url) => reply,
+                                _ = &mut deadline => break Err(SandboxViolation::WallClock { limit_seconds: limits.wall_clock_seconds }),
+                            };
+                            if stdin.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+                                debug!("Agent {} closed stdin before its fetch result", agent_id);
+                            }
+                        }
+                        Err(_) => debug!("Agent {}: {}", agent_id, line),
+                    },
+                    _ => stdout_open = false,
+                },
+                _ = sampler.tick() => {
+                    if let Some(pid) = pid {
+                        system.refresh_processes_specifics(
+                            ProcessesToUpdate::Some(&[pid]),
+                            true,
+                            ProcessRefreshKind::new().with_cpu().with_memory(),
+                        );
+                        if let Some(process) = system.process(pid) {
+                            // cpu_usage is the percentage of one core since the last sample
+                            usage.cpu_time_ms += (process.cpu_usage() as f64 / 100.0 * SAMPLE_INTERVAL.as_millis() as f64) as u64;
+                            usage.peak_memory_mb = usage.peak_memory_mb.max((process.memory() / (1024 * 1024)) as u32);
+                        }
+                    }
+                    if let Some(violation) = limits.check_usage(&usage) {
+                        break Err(violation);
+                    }
+                },
+                _ = &mut deadline => break Err(SandboxViolation::WallClock { limit_seconds: limits.wall_clock_seconds }),
+            }
+        };
+
+        let (exit_code, violation) = match outcome {
+            Ok(status) => {
+                // The result line may still be buffered when the agent exits
+                if output.is_none() && stdout_open {
+                    let drain = async {
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if let Ok(AgentMessage::Result { output: result }) = serde_json::from_str(&line) {
+                                return Some(result);
+                            }
+                        }
+                        None
+                    };
+                    output = tokio::time::timeout(Duration::from_secs(1), drain).await.ok().flatten();
+                }
+                (status.code(), cpu_limit_signal(&status, limits, &usage))
+            }
+            Err(violation) => {
+                warn!("Terminating agent {}: it {}", agent_id, violation);
+                kill_group(pid);
+                if let Err(e) = child.kill().await {
+                    warn!("Failed to terminate agent {}: {}", agent_id, e);
+                }
+                (None, Some(violation))
+            }
+        };
+        // Whatever the agent started in the background goes with it. A group id stays
+        // reserved while the group has members, so this reaches only the agent's processes.
+        kill_group(pid);
+        usage.wall_clock_ms = started.elapsed().as_millis() as u64;
+
+        let success = violation.is_none() && exit_code == Some(0) && output.is_some();
+        let error_message = match (&violation, exit_code) {
+            (Some(violation), _) => Some(format!("Agent terminated: it {}", violation)),
+            (None, Some(0)) if output.is_none() => Some("Agent exited without a result".to_string()),
+            (None, Some(0)) => None,
+            (None, code) => Some(format!("Agent exited with status {}", code.map_or("unknown".to_string(), |code| code.to_string()))),
+        };
+
+        self.record_usage(agent_id, &usage, violation.as_ref()).await;
+        Ok(AgentRunResult { agent_id, success, output, exit_code, violation, usage, error_message })
+    }
+
+    /// Resource usage of every agent run so far, or of one agent
+    pub async fn get_resource_usage(&self, agent_id: Option<Uuid>) -> Vec<AgentResourceUsage> {
+        let usage = self.usage.read().await;
+        let mut listed: Vec<AgentResourceUsage> = usage.values()
+            .filter(|agent| agent_id.map_or(true, |id| agent.agent_id == id))
+            .cloned()
+            .collect();
+        listed.sort_by(|a, b| b.total_cpu_time_ms.cmp(&a.total_cpu_time_ms));
+        listed
+    }
+
+    async fn record_usage(&self, agent_id: Uuid, run: &SandboxUsage, violation: Option<&SandboxViolation>) {
+        let mut usage = self.usage.write().await;
+        let agent = usage.entry(agent_id).or_insert_with(|| AgentResourceUsage {
+            agent_id,
+            runs: 0,
+            violations: 0,
+            total_cpu_time_ms: 0,
+            peak_memory_mb: 0,
+            total_wall_clock_ms: 0,
+            network_requests: 0,
+            last_violation: None,
+            last_run_at: Utc::now(),
+        });
+        agent.runs += 1;
+        agent.total_cpu_time_ms += run.cpu_time_ms;
+        agent.peak_memory_mb = agent.peak_memory_mb.max(run.peak_memory_mb);
+        agent.total_wall_clock_ms += run.wall_clock_ms;
+        agent.network_requests += run.network_requests as u64;
+        agent.last_run_at = Utc::now();
+        if let Some(violation) = violation {
+            agent.violations += 1;
+            agent.last_violation = Some(violation.clone());
+        }
+
+        if let Some(data_persistence) = &self.data_persistence {
+            let snapshot: Vec<AgentResourceUsage> = usage.values().cloned().collect();
+            drop(usage);
+            if let Err(e) = data_persistence.read().await.save_setting(USAGE_SETTING, &snapshot).await {
+                warn!("Failed to save agent resource usage: {}", e);
+            }
+        }
+    }
+
+    /// Fetch an allowlisted URL for the agent, as a `fetch_result` line
+    async fn fetch(&self, url: &str) -> String {
+        if let Err(e) = air_gap::ensure_online("agent network access") {
+            let reply = HostMessage::FetchResult { url, status: None, location: None, body: None, error: Some(e.to_string()) };
+            return serde_json::to_string(&reply).unwrap_or_default();
+        }
+        let result = async {
+            let response = self.http_client.get(url).send().await?;
+            let status = response.status().as_u16();
+            let location = response.headers().get(reqwest::header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| response.url().join(location).ok())
+                .map(|location| location.to_string());
+            let bytes = response.bytes().await?;
+            let body = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_FETCH_BODY_BYTES)]).into_owned();
+            Ok::<_, reqwest::Error>((status, location, body))
+        }.await;
+
+        let reply = match result {
+            Ok((status, location, body)) => HostMessage::FetchResult { url, status: Some(status), location, body: Some(body), error: None },
+            Err(e) => HostMessage::FetchResult { url, status: None, location: None, body: None, error: Some(e.to_string()) },
+        };
+        serde_json::to_string(&reply).unwrap_or_default()
+    }
+}
+
+/// Run the agent in its own process group under CPU time and memory rlimits, and on
+/// Linux in new user and network namespaces, so only the host's fetches reach the
+/// network. The agent does not start if it cannot be isolated.
+#[cfg(unix)]
+fn confine(command: &mut Command, limits: &SandboxLimits) {
+    // The kernel sends SIGXCPU at the soft limit and SIGKILL at the hard one; the
+    // sampler reports the violation first in the usual case
+    let cpu_seconds = limits.cpu_time_ms.div_ceil(1000) as libc::rlim_t + 1;
+    let memory_bytes = limits.memory_mb as libc::rlim_t * 1024 * 1024;
+
+    command.process_group(0);
+    // Only async-signal-safe calls between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            let cpu = libc::rlimit { rlim_cur: cpu_seconds, rlim_max: cpu_seconds + 1 };
+            let memory = libc::rlimit { rlim_cur: memory_bytes, rlim_max: memory_bytes };
+            if libc::setrlimit(libc::RLIMIT_CPU, &cpu) != 0 || libc::setrlimit(libc::RLIMIT_DATA, &memory) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            #[cfg(target_os = "linux")]
+            {
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+/// The CPU time violation if the kernel stopped the agent at its CPU rlimit
+fn cpu_limit_signal(status: &std::process::ExitStatus, limits: &SandboxLimits, usage: &SandboxUsage) -> Option<SandboxViolation> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if status.signal() == Some(libc::SIGXCPU) {
+            return Some(SandboxViolation::CpuTime { limit_ms: limits.cpu_time_ms, used_ms: usage.cpu_time_ms.max(limits.cpu_time_ms) });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (status, limits, usage);
+    None
+}
+
+/// Kill every process of the agent's group
+fn kill_group(pid: Option<Pid>) {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = pid {
+            // The group may already be gone; that is the outcome wanted
+            unsafe {
+                libc::kill(-(pid.as_u32() as libc::pid_t), libc::SIGKILL);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ai_marketplace::ResourceRequirements;
+
+    fn requirements(network_access: bool) -> ResourceRequirements {
+        ResourceRequirements {
+            min_memory_mb: 2048,
+            min_cpu_cores: 1,
+            gpu_required: false,
+            disk_space_mb: 10,
+            network_access,
+            special_permissions: Vec::new(),
+            network_allowlist: vec!["*.arxiv.org".to_string(), "example.com".to_string()],
+            max_cpu_time_seconds: Some(5),
+            max_wall_clock_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_agent_limits_are_capped_and_enforced() {
+        let ceiling = SandboxLimits::default();
+        let limits = SandboxLimits::for_agent(&requirements(true), &ceiling);
+        assert_eq!(limits.memory_mb, ceiling.memory_mb);
+        assert_eq!(limits.cpu_time_ms, 5_000);
+        assert_eq!(limits.wall_clock_seconds, ceiling.wall_clock_seconds);
+
+        assert!(limits.check_url("https://export.arxiv.org/api/query").is_ok());
+        assert!(limits.check_url("https://example.com/").is_ok());
+        assert!(limits.check_url("https://notexample.com/").is_err());
+        assert!(limits.check_url("file:///etc/passwd").is_err());
+        assert!(SandboxLimits::for_agent(&requirements(false), &ceiling).check_url("https://example.com/").is_err());
+
+        let mut usage = SandboxUsage { cpu_time_ms: 4_000, peak_memory_mb: 100, ..Default::default() };
+        assert_eq!(limits.check_usage(&usage), None);
+        usage.peak_memory_mb = 600;
+        assert_eq!(limits.check_usage(&usage), Some(SandboxViolation::Memory { limit_mb: 512, used_mb: 600 }));
+        usage.cpu_time_ms = 5_001;
+        assert!(matches!(limits.check_usage(&usage), Some(SandboxViolation::CpuTime { .. })));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_agent_over_its_wall_clock_is_terminated() {
+        let sandbox = AgentSandbox::new(SandboxLimits::default()).unwrap();
+        let limits = SandboxLimits { wall_clock_seconds: 1, ..SandboxLimits::default() };
+        let agent_id = Uuid::new_v4();
+        let result = sandbox.run(agent_id, "sh", &["-c".to_string(), "sleep 10".to_string()], Path::new("."), &serde_json::json!({}), &limits)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.violation, Some(SandboxViolation::WallClock { limit_seconds: 1 }));
+        assert_eq!(sandbox.get_resource_usage(Some(agent_id)).await[0].violations, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_agent_waiting_on_a_slow_fetch_is_terminated_at_its_wall_clock() {
+        // Connections are accepted by the kernel but never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://127.0.0.1:{}/slow", listener.local_addr().unwrap().port());
+
+        let sandbox = AgentSandbox::new(SandboxLimits::default()).unwrap();
+        let limits = SandboxLimits {
+            wall_clock_seconds: 1,
+            network_allowlist: vec!["127.0.0.1".to_string()],
+            ..SandboxLimits::default()
+        };
+        let script = format!("echo '{{\"type\":\"fetch\",\"url\":\"{}\"}}'; sleep 10", url);
+        let started = Instant::now();
+        let result = sandbox.run(Uuid::new_v4(), "sh", &["-c".to_string(), script], Path::new("."), &serde_json::json!({}), &limits)
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5), "the fetch held the run for {:?}", started.elapsed());
+        assert_eq!(result.violation, Some(SandboxViolation::WallClock { limit_seconds: 1 }));
+        assert_eq!(result.usage.network_requests, 1);
+        drop(listener);
+    }
+}
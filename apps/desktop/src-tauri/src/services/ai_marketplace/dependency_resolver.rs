@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::ai_marketplace::{
+    AIAgentMarketplace, ContentDependency, ContentKind, InstalledContent, ResearchMethodologyMarketplace,
+};
+
+/// Most items one install may pull in, dependencies of dependencies included
+pub const MAX_DEPENDENCIES: usize = 100;
+
+/// A `major.minor.patch` version; missing parts are 0 and pre-release or build
+/// suffixes are ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { major, minor, patch })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    /// Compatible: no change to the first non-zero part
+    Caret,
+    /// Patch updates only
+    Tilde,
+}
+
+/// Comma-separated comparators a version must all satisfy, as in Cargo: `^1.2`,
+/// `~1.2.3`, `>=1.0, <2.0`, `=1.4.0` or `*`. A bare version means `^`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    comparators: Vec<(Op, Version)>,
+}
+
+impl VersionRequirement {
+    pub fn parse(requirement: &str) -> Result<Self, String> {
+        let mut comparators = Vec::new();
+        for comparator in requirement.split(',').map(str::trim).filter(|comparator| !comparator.is_empty()) {
+            if comparator == "*" {
+                continue;
+            }
+            let (op, version) = [
+                (">=", Op::GreaterEq),
+                ("<=", Op::LessEq),
+                (">", Op::Greater),
+                ("<", Op::Less),
+                ("=", Op::Exact),
+                ("^", Op::Caret),
+                ("~", Op::Tilde),
+            ]
+            .iter()
+            .find_map(|(prefix, op)| comparator.strip_prefix(prefix).map(|version| (*op, version)))
+            .unwrap_or((Op::Caret, comparator));
+            let version = Version::parse(version)
+                .ok_or_else(|| format!("'{}' is not a valid version requirement", requirement))?;
+            comparators.push((op, version));
+        }
+        Ok(Self { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|(op, required)| match op {
+            Op::Exact => version == required,
+            Op::Greater => version > required,
+            Op::GreaterEq => version >= required,
+            Op::Less => version < required,
+            Op::LessEq => version <= required,
+            Op::Caret => version >= required && *version < caret_bound(required),
+            Op::Tilde => version >= required && *version < Version { major: required.major, minor: required.minor + 1, patch: 0 },
+        })
+    }
+}
+
+fn caret_bound(version: &Version) -> Version {
+    if version.major > 0 {
+        Version { major: version.major + 1, minor: 0, patch: 0 }
+    } else if version.minor > 0 {
+        Version { major: 0, minor: version.minor + 1, patch: 0 }
+    } else {
+        Version { major: 0, minor: 0, patch: version.patch + 1 }
+    }
+}
+
+/// A published item as the resolver sees it
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub kind: ContentKind,
+    pub id: Uuid,
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<ContentDependency>,
+}
+
+impl CatalogEntry {
+    pub fn agent(agent: &AIAgentMarketplace) -> Self {
+        Self {
+            kind: ContentKind::Agent,
+            id: agent.id,
+            name: agent.name.clone(),
+            version: agent.version.clone(),
+            dependencies: agent.dependencies.clone(),
+        }
+    }
+
+    pub fn methodology(methodology: &ResearchMethodologyMarketplace) -> Self {
+        Self {
+            kind: ContentKind::Methodology,
+            id: methodology.id,
+            name: methodology.name.clone(),
+            version: methodology.version.clone(),
+            dependencies: methodology.dependencies.clone(),
+        }
+    }
+
+    fn key(&self) -> (ContentKind, Uuid) {
+        (self.kind, self.id)
+    }
+}
+
+/// The requested item and everything it transitively depends on. Look up each
+/// `next_unresolved` item and `add` it until there are none left, then `plan`.
+pub struct DependencyGraph {
+    root: (ContentKind, Uuid),
+    entries: HashMap<(ContentKind, Uuid), CatalogEntry>,
+}
+
+impl DependencyGraph {
+    pub fn new(root: CatalogEntry) -> Self {
+        let key = root.key();
+        Self { root: key, entries: HashMap::from([(key, root)]) }
+    }
+
+    /// A dependency not looked up yet
+    pub fn next_unresolved(&self) -> Option<(ContentKind, Uuid)> {
+        self.entries.values()
+            .flat_map(|entry| &entry.dependencies)
+            .map(|dependency| (dependency.kind, dependency.id))
+            .find(|key| !self.entries.contains_key(key))
+    }
+
+    pub fn add(&mut self, entry: CatalogEntry) {
+        self.entries.insert(entry.key(), entry);
+    }
+
+    /// Items in the graph, the requested one included
+    pub fn item_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// What to install for a user who has `installed`, dependencies before their
+    /// dependents and the requested item last. Content already installed is kept as
+    /// it is, so it has to satisfy every requirement on it; the requested item is
+    /// (re)installed at its published version, which its installed dependents must
+    /// accept. Every conflict is returned, each explained in a sentence.
+    pub fn plan(&self, installed: &[InstalledContent]) -> Result<Vec<CatalogEntry>, Vec<String>> {
+        let installed_versions: HashMap<(ContentKind, Uuid), &InstalledContent> = installed.iter()
+            .map(|content| ((content.kind, content.content_id), content))
+            .collect();
+        let mut conflicts = Vec::new();
+
+        // The version each item will have once the install is done
+        let version_of = |key: &(ContentKind, Uuid)| -> Option<(String, bool)> {
+            match installed_versions.get(key) {
+                Some(content) if *key != self.root => Some((content.version.clone(), true)),
+                _ => self.entries.get(key).map(|entry| (entry.version.clone(), false)),
+            }
+        };
+
+        let mut requirements: Vec<(String, &ContentDependency)> = self.entries.values()
+            .flat_map(|entry| entry.dependencies.iter().map(move |dependency| (entry.name.clone(), dependency)))
+            .collect();
+        // Installed content that depends on the requested item must accept its new version
+        requirements.extend(installed.iter()
+            .filter(|content| (content.kind, content.content_id) != self.root)
+            .flat_map(|content| content.dependencies.iter()
+                .filter(|dependency| (dependency.kind, dependency.id) == self.root)
+                .map(move |dependency| (format!("installed {}", content.name), dependency))));
+
+        for (dependent, dependency) in requirements {
+            let key = (dependency.kind, dependency.id);
+            let name = self.name_of(&key, installed);
+            let requirement = match VersionRequirement::parse(&dependency.version_requirement) {
+                Ok(requirement) => requirement,
+                Err(e) => {
+                    conflicts.push(format!("{} declares an invalid requirement on {}: {}", dependent, name, e));
+                    continue;
+                }
+            };
+            let Some((version, already_installed)) = version_of(&key) else {
+                continue;
+            };
+            match Version::parse(&version) {
+                Some(parsed) if requirement.matches(&parsed) => {}
+                Some(_) if already_installed => conflicts.push(format!(
+                    "{} needs {} {} but version {} is installed", dependent, name, dependency.version_requirement, version)),
+                Some(_) => conflicts.push(format!(
+                    "{} needs {} {} but the marketplace has version {}", dependent, name, dependency.version_requirement, version)),
+                None => conflicts.push(format!("{} has an invalid version '{}'", name, version)),
+            }
+        }
+
+        // Dependencies first, by depth-first walk from the requested item
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        let mut path = Vec::new();
+        self.visit(self.root, &mut path, &mut done, &mut order, &mut conflicts, installed);
+
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+        Ok(order.into_iter()
+            .filter(|key| *key == self.root || !installed_versions.contains_key(key))
+            .filter_map(|key| self.entries.get(&key).cloned())
+            .collect())
+    }
+
+    fn visit(
+        &self,
+        key: (ContentKind, Uuid),
+        path: &mut Vec<(ContentKind, Uuid)>,
+        done: &mut HashSet<(ContentKind, Uuid)>,
+        order: &mut Vec<(ContentKind, Uuid)>,
+        conflicts: &mut Vec<String>,
+        installed: &[InstalledContent],
+    ) {
+        if done.contains(&key) {
+            return;
+        }
+        if let Some(start) = path.iter().position(|visited| *visited == key) {
+            let cycle: Vec<String> = path[start..].iter().chain([&key]).map(|key| self.name_of(key, installed)).collect();
+            conflicts.push(format!("dependencies form a cycle: {}", cycle.join(" -> ")));
+            return;
+        }
+        let Some(entry) = self.entries.get(&key) else {
+            return;
+        };
+
+        path.push(key);
+        for dependency in &entry.dependencies {
+            self.visit((dependency.kind, dependency.id), path, done, order, conflicts, installed);
+        }
+        path.pop();
+        done.insert(key);
+        order.push(key);
+    }
+
+    fn name_of(&self, key: &(ContentKind, Uuid), installed: &[InstalledContent]) -> String {
+        self.entries.get(key)
+            .map(|entry| entry.name.clone())
+            .or_else(|| installed.iter()
+                .find(|content| (content.kind, content.content_id) == *key)
+                .map(|content| content.name.clone()))
+            .unwrap_or_else(|| format!("{} {}", key.0.as_str(), key.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(kind: ContentKind, name: &str, version: &str, dependencies: Vec<ContentDependency>) -> CatalogEntry {
+        CatalogEntry { kind, id: Uuid::new_v4(), name: name.to_string(), version: version.to_string(), dependencies }
+    }
+
+    fn on(entry: &CatalogEntry, requirement: &str) -> ContentDependency {
+        ContentDependency { kind: entry.kind, id: entry.id, version_requirement: requirement.to_string() }
+    }
+
+    #[test]
+    fn test_version_requirements() {
+        let matches = |requirement: &str, version: &str| {
+            VersionRequirement::parse(requirement).unwrap().matches(&Version::parse(version).unwrap())
+        };
+        assert!(matches("^1.2", "1.9.0"));
+        assert!(!matches("^1.2", "2.0.0"));
+        assert!(!matches("^0.2.1", "0.3.0"));
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches(">=1.0, <2.0", "1.5.0-beta"));
+        assert!(matches("*", "0.0.1"));
+        assert!(VersionRequirement::parse(">=one").is_err());
+    }
+
+    #[test]
+    fn test_install_plan_orders_dependencies_and_explains_conflicts() {
+        let methodology = entry(ContentKind::Methodology, "Systematic review", "2.1.0", Vec::new());
+        let scraper = entry(ContentKind::Agent, "Scraper", "1.4.0", vec![on(&methodology, "^2.0")]);
+        let root = entry(ContentKind::Agent, "Literature bundle", "1.0.0", vec![on(&scraper, "^1.2"), on(&methodology, ">=2.1")]);
+
+        let mut graph = DependencyGraph::new(root.clone());
+        while let Some((_, id)) = graph.next_unresolved() {
+            graph.add([&methodology, &scraper].into_iter().find(|entry| entry.id == id).unwrap().clone());
+        }
+        let plan = graph.plan(&[]).unwrap();
+        let names: Vec<&str> = plan.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["Systematic review", "Scraper", "Literature bundle"]);
+
+        // An older installed methodology is kept, and breaks both requirements on it
+        let installed = InstalledContent {
+            user_id: Uuid::new_v4(),
+            kind: ContentKind::Methodology,
+            content_id: methodology.id,
+            name: methodology.name.clone(),
+            version: "1.3.0".to_string(),
+            installed_as_dependency: false,
+            dependencies: Vec::new(),
+            installation_path: None,
+            installed_at: Utc::now(),
+        };
+        let conflicts = graph.plan(&[installed]).unwrap_err();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts.iter().any(|conflict| conflict == "Scraper needs Systematic review ^2.0 but version 1.3.0 is installed"));
+
+        // Mutual dependencies are refused
+        let mut looping = scraper.clone();
+        looping.dependencies = vec![on(&root, "*")];
+        let mut graph = DependencyGraph::new(root.clone());
+        graph.add(looping);
+        graph.add(methodology.clone());
+        let conflicts = graph.plan(&[]).unwrap_err();
+        assert!(conflicts[0].starts_with("dependencies form a cycle: Literature bundle -> Scraper -> Literature bundle"));
+    }
+}
@@ -16,6 +16,8 @@ pub mod rating_system;
 pub mod search_engine;
 pub mod installation_manager;
 pub mod analytics_tracker;
+pub mod agent_sandbox;
+pub mod dependency_resolver;
 
 use user_manager::UserManager;
 use agent_manager::AgentManager;
@@ -24,6 +26,8 @@ use rating_system::RatingSystem;
 use search_engine::MarketplaceSearchEngine;
 use installation_manager::InstallationManager;
 use analytics_tracker::AnalyticsTracker;
+use agent_sandbox::AgentSandbox;
+use dependency_resolver::{CatalogEntry, DependencyGraph, MAX_DEPENDENCIES};
 
 /// AI Marketplace Service for community platform and agent sharing
 pub struct AIMarketplaceService {
@@ -36,6 +40,7 @@ pub struct AIMarketplaceService {
     search_engine: Arc<RwLock<MarketplaceSearchEngine>>,
     installation_manager: Arc<RwLock<InstallationManager>>,
     analytics_tracker: Arc<RwLock<AnalyticsTracker>>,
+    agent_sandbox: Arc<AgentSandbox>,
 }
 
 impl AIMarketplaceService {
@@ -78,6 +83,8 @@ impl AIMarketplaceService {
             AnalyticsTracker::new(data_persistence.clone()).await?
         ));
 
+        let agent_sandbox = Arc::new(AgentSandbox::new(SandboxLimits::default())?.with_persistence(data_persistence.clone()).await);
+
         Ok(Self {
             data_persistence,
             security,
@@ -88,6 +95,7 @@ impl AIMarketplaceService {
             search_engine,
             installation_manager,
             analytics_tracker,
+            agent_sandbox,
         })
     }
 
@@ -181,7 +189,9 @@ impl AIMarketplaceService {
         Ok(results)
     }
 
-    /// Install an AI agent
+    /// Install an AI agent along with the agents and methodologies it transitively
+    /// depends on. Nothing is installed if any requirement cannot be met, and the
+    /// dependencies already installed are removed again if a later install fails.
     pub async fn install_agent(
         &self,
         user_id: Uuid,
@@ -192,31 +202,270 @@ impl AIMarketplaceService {
         // Get agent details
         let agent_manager = self.agent_manager.read().await;
         let agent = agent_manager.get_agent(request.agent_id).await?;
+        drop(agent_manager);
         
-        if agent.status != AgentStatus::Published {
+        if !matches!(agent.status, AgentStatus::Published) {
             return Err(ResearchError::InvalidInput {
                 message: "Agent is not available for installation".to_string(),
             }.into());
         }
 
+        // Resolve the dependency manifest against what the user has installed
+        let root = CatalogEntry::agent(&agent);
+        let mut graph = DependencyGraph::new(root.clone());
+        while let Some((kind, id)) = graph.next_unresolved() {
+            if graph.item_count() >= MAX_DEPENDENCIES {
+                return Err(ResearchError::incompatible_dependencies(format!(
+                    "{} pulls in more than {} dependencies", agent.name, MAX_DEPENDENCIES)).into());
+            }
+            let entry = self.catalog_entry(kind, id).await.map_err(|e| ResearchError::incompatible_dependencies(
+                format!("{} {} needed by {} is not available: {}", kind.as_str(), id, agent.name, e)))?;
+            graph.add(entry);
+        }
+        let installed = self.data_persistence.read().await.get_marketplace_installations(user_id).await?;
+        let plan = graph.plan(&installed)
+            .map_err(|conflicts| ResearchError::incompatible_dependencies(conflicts.join("; ")))?;
+
+        // Dependencies first, so the agent never lands without them. Whatever was
+        // installed is rolled back if a later step fails.
+        let mut installed_dependencies = Vec::new();
+        let mut rollback = Vec::new();
+        let dependencies: AppResult<()> = async {
+            for entry in plan.iter().filter(|entry| entry.id != root.id || entry.kind != root.kind) {
+                let mut installation_path = None;
+                if entry.kind == ContentKind::Agent {
+                    let dependency = self.agent_manager.read().await.get_agent(entry.id).await?;
+                    let dependency_request = AgentInstallationRequest {
+                        agent_id: entry.id,
+                        installation_path: None,
+                        configuration_overrides: HashMap::new(),
+                        auto_update: request.auto_update,
+                    };
+                    let result = self.installation_manager.write().await
+                        .install_agent(user_id, dependency, dependency_request).await?;
+                    if !result.success {
+                        return Err(ResearchError::incompatible_dependencies(format!(
+                            "failed to install {}, needed by {}: {}",
+                            entry.name, agent.name, result.error_message.unwrap_or_default())).into());
+                    }
+                    installation_path = result.installation_path;
+                }
+                rollback.push((entry.kind, entry.id));
+                let installation = self.record_installation(user_id, entry, true, installation_path).await?;
+                info!("Installed {} {} as a dependency of {}", entry.name, entry.version, agent.name);
+                installed_dependencies.push(installation);
+            }
+            Ok(())
+        }.await;
+        if let Err(e) = dependencies {
+            self.roll_back_installations(user_id, &rollback).await;
+            return Err(e);
+        }
+
         // Perform installation
         let installation_manager = self.installation_manager.write().await;
-        let result = installation_manager.install_agent(user_id, agent, request).await?;
-        
-        if result.success {
-            // Update download count
-            let mut agent_manager = self.agent_manager.write().await;
-            agent_manager.increment_download_count(agent.id).await?;
-            
-            // Track installation analytics
-            let analytics_tracker = self.analytics_tracker.write().await;
-            analytics_tracker.track_agent_installation(agent.id, user_id).await?;
+        let installation = installation_manager.install_agent(user_id, agent.clone(), request).await;
+        drop(installation_manager);
+        let mut result = match installation {
+            Ok(result) if result.success => result,
+            Ok(result) => {
+                self.roll_back_installations(user_id, &rollback).await;
+                return Ok(result);
+            }
+            Err(e) => {
+                self.roll_back_installations(user_id, &rollback).await;
+                return Err(e);
+            }
+        };
+
+        rollback.push((root.kind, root.id));
+        if let Err(e) = self.record_installation(user_id, &root, false, result.installation_path.clone()).await {
+            self.roll_back_installations(user_id, &rollback).await;
+            return Err(e);
         }
+
+        // Update download count
+        let mut agent_manager = self.agent_manager.write().await;
+        agent_manager.increment_download_count(agent.id).await?;
+        drop(agent_manager);
+
+        // Track installation analytics
+        let analytics_tracker = self.analytics_tracker.write().await;
+        analytics_tracker.track_agent_installation(agent.id, user_id).await?;
+        drop(analytics_tracker);
+        result.installed_dependencies = installed_dependencies;
         
         info!("Agent installation completed with success: {}", result.success);
         Ok(result)
     }
 
+    /// Uninstall an agent or methodology. Content other installed items depend on is
+    /// left in place with a warning naming them, unless `force` is set.
+    pub async fn uninstall_content(
+        &self,
+        user_id: Uuid,
+        kind: ContentKind,
+        content_id: Uuid,
+        force: bool,
+    ) -> AppResult<UninstallResult> {
+        info!("Uninstalling {} {} for user: {}", kind.as_str(), content_id, user_id);
+
+        let installed = self.data_persistence.read().await.get_marketplace_installations(user_id).await?;
+        let Some(content) = installed.iter().find(|content| content.kind == kind && content.content_id == content_id) else {
+            return Err(ResearchError::InvalidInput {
+                message: format!("{} {} is not installed", kind.as_str(), content_id),
+            }.into());
+        };
+        let dependents: Vec<InstalledContent> = installed.iter()
+            .filter(|other| other.depends_on(kind, content_id))
+            .cloned()
+            .collect();
+        let names = dependents.iter().map(|dependent| dependent.name.as_str()).collect::<Vec<_>>().join(", ");
+
+        if !dependents.is_empty() && !force {
+            warn!("Not uninstalling {}: {} depend on it", content.name, names);
+            return Ok(UninstallResult {
+                removed: false,
+                warning: Some(format!("{} is needed by {}; uninstall those first or force the uninstall", content.name, names)),
+                dependents,
+            });
+        }
+
+        if kind == ContentKind::Agent {
+            let installation_manager = self.installation_manager.write().await;
+            installation_manager.uninstall_agent(user_id, content_id).await?;
+        }
+        self.data_persistence.read().await.delete_marketplace_installation(user_id, kind, content_id).await?;
+
+        let warning = (!dependents.is_empty())
+            .then(|| format!("{} was uninstalled but {} depend on it and may no longer work", content.name, names));
+        if let Some(warning) = &warning {
+            warn!("{}", warning);
+        }
+        Ok(UninstallResult { removed: true, dependents, warning })
+    }
+
+    /// The content the user has installed from the marketplace
+    pub async fn get_installed_content(&self, user_id: Uuid) -> AppResult<Vec<InstalledContent>> {
+        self.data_persistence.read().await.get_marketplace_installations(user_id).await
+    }
+
+    async fn catalog_entry(&self, kind: ContentKind, id: Uuid) -> AppResult<CatalogEntry> {
+        match kind {
+            ContentKind::Agent => {
+                let agent_manager = self.agent_manager.read().await;
+                Ok(CatalogEntry::agent(&agent_manager.get_agent(id).await?))
+            }
+            ContentKind::Methodology => {
+                let methodology_manager = self.methodology_manager.read().await;
+                Ok(CatalogEntry::methodology(&methodology_manager.get_methodology(id).await?))
+            }
+        }
+    }
+
+/// Remove what a failed install put in place, latest first. Failures are logged,
+    /// since the install's own error is the one reported.
+    async fn roll_back_installations(&self, user_id: Uuid, installed: &[(ContentKind, Uuid)]) {
+        for (kind, content_id) in installed.iter().rev() {
+            if *kind == ContentKind::Agent {
+                let installation_manager = self.installation_manager.write().await;
+                if let Err(e) = installation_manager.uninstall_agent(user_id, *content_id).await {
+                    error!("Failed to roll back install of agent {}: {}", content_id, e);
+                }
+            }
+            if let Err(e) = self.data_persistence.read().await.delete_marketplace_installation(user_id, *kind, *content_id).await {
+                error!("Failed to remove install record of {} {}: {}", kind.as_str(), content_id, e);
+            }
+        }
+        if !installed.is_empty() {
+            warn!("Rolled back {} installs after a failed agent install", installed.len());
+        }
+    }
+
+        async fn record_installation(
+        &self,
+        user_id: Uuid,
+        entry: &CatalogEntry,
+        as_dependency: bool,
+        installation_path: Option<String>,
+    ) -> AppResult<InstalledContent> {
+        let installation = InstalledContent {
+            user_id,
+            kind: entry.kind,
+            content_id: entry.id,
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            installed_as_dependency: as_dependency,
+            dependencies: entry.dependencies.clone(),
+            installation_path,
+            installed_at: Utc::now(),
+        };
+        self.data_persistence.read().await.save_marketplace_installation(&installation).await?;
+        Ok(installation)
+    }
+
+    /// Run an agent the user installed in the sandbox, from the directory it was
+    /// installed to. An agent that breaches its limits is terminated and the violation
+    /// reported in the result.
+    pub async fn run_installed_agent(
+        &self,
+        user_id: Uuid,
+        request: AgentRunRequest,
+    ) -> AppResult<AgentRunResult> {
+        info!("Running agent: {} for user: {}", request.agent_id, user_id);
+
+        let agent_manager = self.agent_manager.read().await;
+        let agent = agent_manager.get_agent(request.agent_id).await?;
+        drop(agent_manager);
+
+        // Suspended or removed agents do not run, installed or not
+        if !matches!(agent.status, AgentStatus::Published) {
+            return Err(ResearchError::InvalidInput {
+                message: "Agent is not available to run".to_string(),
+            }.into());
+        }
+
+        let entrypoint = agent.agent_config.parameters.get("entrypoint")
+            .and_then(|entrypoint| entrypoint.as_str())
+            .ok_or_else(|| ResearchError::InvalidInput {
+                message: format!("Agent {} declares no entrypoint", agent.id),
+            })?
+            .to_string();
+
+        // Run only what this user installed, from where it was installed to
+        let installed = self.data_persistence.read().await.get_marketplace_installations(user_id).await?;
+        let installation_path = installed.into_iter()
+            .find(|content| content.kind == ContentKind::Agent && content.content_id == agent.id)
+            .ok_or_else(|| ResearchError::InvalidInput {
+                message: format!("Agent {} is not installed", agent.id),
+            })?
+            .installation_path
+            .ok_or_else(|| ResearchError::InvalidInput {
+                message: format!("Agent {} has no installation directory", agent.id),
+            })?;
+
+        let ceiling = self.agent_sandbox.get_ceiling().await;
+        let limits = SandboxLimits::for_agent(&agent.agent_config.resource_requirements, &ceiling);
+        let result = self.agent_sandbox.run(
+            agent.id,
+            &agent.agent_config.execution_environment,
+            &[entrypoint],
+            std::path::Path::new(&installation_path),
+            &request.input,
+            &limits,
+        ).await?;
+
+        if let Some(violation) = &result.violation {
+            warn!("Agent {} breached its sandbox limits: it {}", agent.id, violation);
+        }
+        Ok(result)
+    }
+
+    /// Resource usage of sandboxed agent runs, for every agent or one
+    pub async fn get_agent_resource_usage(&self, agent_id: Option<Uuid>) -> Vec<AgentResourceUsage> {
+        self.agent_sandbox.get_resource_usage(agent_id).await
+    }
+
     /// Submit a rating/review
     pub async fn submit_rating(
         &self,
@@ -365,6 +365,7 @@ pub enum EventType {
     SystemStartup,
     SystemShutdown,
     PerformanceAlert,
+    ScalingDecision,
 
     // User Interface Events
     DashboardViewed,
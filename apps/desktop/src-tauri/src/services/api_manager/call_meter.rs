@@ -0,0 +1,120 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+use super::response_schema::NormalizedPayload;
+use super::service_integration::ServiceResponse;
+
+tokio::task_local! {
+    static CALL_METER: Arc<CallMeter>;
+}
+
+/// Counts the provider calls made by one unit of work, typically a workflow step
+#[derive(Debug, Default)]
+pub struct CallMeter {
+    calls: AtomicU32,
+    failed_calls: AtomicU32,
+    tokens: AtomicU64,
+    bytes_fetched: AtomicU64,
+}
+
+/// What a meter counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallCounts {
+    pub calls: u32,
+    pub failed_calls: u32,
+    pub tokens: u64,
+    pub bytes_fetched: u64,
+}
+
+impl CallMeter {
+    fn record(&self, result: &AppResult<ServiceResponse>) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok(response) => {
+                if !response.success {
+                    self.failed_calls.fetch_add(1, Ordering::Relaxed);
+                }
+                self.tokens.fetch_add(tokens_used(response), Ordering::Relaxed);
+                self.bytes_fetched.fetch_add(response.body.len() as u64, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.failed_calls.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn counts(&self) -> CallCounts {
+        CallCounts {
+            calls: self.calls.load(Ordering::Relaxed),
+            failed_calls: self.failed_calls.load(Ordering::Relaxed),
+            tokens: self.tokens.load(Ordering::Relaxed),
+            bytes_fetched: self.bytes_fetched.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tokens an AI provider reported for the call, from its normalized payload or, failing
+/// that, the `total_tokens` metadata the integrations set
+fn tokens_used(response: &ServiceResponse) -> u64 {
+    match &response.normalized {
+        Some(NormalizedPayload::Completion { total_tokens: Some(tokens), .. }) => *tokens,
+        _ => response.metadata.get("total_tokens").and_then(|tokens| tokens.parse().ok()).unwrap_or(0),
+    }
+}
+
+/// Count the provider calls `future` makes into `meter`
+pub async fn meter_scope<F: Future>(meter: Arc<CallMeter>, future: F) -> F::Output {
+    CALL_METER.scope(meter, future).await
+}
+
+/// Count a provider call against the running task's meter, if it has one
+pub fn record(result: &AppResult<ServiceResponse>) {
+    let _ = CALL_METER.try_with(|meter| meter.record(result));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+    use crate::models::api_key::ServiceProvider;
+    use crate::services::api_manager::mock_providers::{self, MockSettings};
+    use crate::services::api_manager::service_integration::ServiceRequest;
+
+    fn request(service: ServiceProvider, endpoint: &str) -> ServiceRequest {
+        ServiceRequest {
+            request_id: Uuid::new_v4(),
+            service,
+            endpoint: endpoint.to_string(),
+            method: "POST".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            timeout_ms: 1000,
+            retry_count: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_counted_against_the_meter_they_run_under() {
+        let settings = MockSettings { latency_ms: 0, failure_percent: 0 };
+        let meter = Arc::new(CallMeter::default());
+        meter_scope(meter.clone(), async {
+            record(&mock_providers::respond(ServiceProvider::Exa, &request(ServiceProvider::Exa, "/search"), settings).await);
+            let failing = MockSettings { latency_ms: 0, failure_percent: 100 };
+            record(&mock_providers::respond(ServiceProvider::Tavily, &request(ServiceProvider::Tavily, "/search"), failing).await);
+        }).await;
+
+        // Failed calls count too, but fetch nothing
+        let counts = meter.counts();
+        assert_eq!((counts.calls, counts.failed_calls), (2, 1));
+        assert!(counts.bytes_fetched > 0);
+
+        // Calls made outside a meter are not counted anywhere
+        record(&mock_providers::respond(ServiceProvider::Exa, &request(ServiceProvider::Exa, "/search"), settings).await);
+        assert_eq!(meter.counts().calls, 2);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use parking_lot::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{info, debug};
+
+use crate::error::{AppError, AppResult};
+use crate::models::api_key::ServiceProvider;
+
+/// The in-flight request slots of one provider
+struct ProviderSlots {
+    semaphore: Arc<Semaphore>,
+    max: u32,
+    in_flight: Arc<AtomicU32>,
+}
+
+impl ProviderSlots {
+    fn new(max: u32, in_flight: Arc<AtomicU32>) -> Self {
+        Self { semaphore: Arc::new(Semaphore::new(max as usize)), max, in_flight }
+    }
+}
+
+/// A slot held for one in-flight request, given back when dropped
+pub struct ConcurrencyPermit {
+    _permit: OwnedSemaphorePermit,
+    in_flight: Arc<AtomicU32>,
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Bounds how many requests are in flight to each provider at once. The rate limiter
+/// caps requests per window; this stops a burst within the window from tripping a
+/// provider's concurrency limit.
+#[derive(Default)]
+pub struct ConcurrencyLimiter {
+    providers: Mutex<HashMap<ServiceProvider, ProviderSlots>>,
+}
+
+impl ConcurrencyLimiter {
+    /// Allow at most `max` requests to `service` in flight at once. Requests already
+    /// in flight keep their slots, so after lowering the limit it can be exceeded
+    /// until they finish.
+    pub fn set_limit(&self, service: ServiceProvider, max: u32) -> AppResult<()> {
+        if max == 0 {
+            return Err(AppError::validation("max_concurrent_requests", "must be at least 1"));
+        }
+
+        let mut providers = self.providers.lock();
+        let in_flight = match providers.get(&service) {
+            Some(slots) if slots.max == max => return Ok(()),
+            Some(slots) => slots.in_flight.clone(),
+            None => Arc::new(AtomicU32::new(0)),
+        };
+        info!("Allowing {} concurrent requests to {:?}", max, service);
+        providers.insert(service, ProviderSlots::new(max, in_flight));
+        Ok(())
+    }
+
+    /// Wait for a free slot for `service`. A provider without a limit is not bounded.
+    pub async fn acquire(&self, service: ServiceProvider) -> Option<ConcurrencyPermit> {
+        let (semaphore, in_flight) = {
+            let providers = self.providers.lock();
+            let slots = providers.get(&service)?;
+            (slots.semaphore.clone(), slots.in_flight.clone())
+        };
+
+        if semaphore.available_permits() == 0 {
+            debug!("All concurrent request slots for {:?} are taken; waiting", service);
+        }
+        // The semaphore is never closed; a limit change swaps it, and waiters on the
+        // old one are served as its requests finish
+        let permit = semaphore.acquire_owned().await.ok()?;
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Some(ConcurrencyPermit { _permit: permit, in_flight })
+    }
+
+    /// Requests to `service` in flight now, and the most allowed at once
+    pub fn usage(&self, service: ServiceProvider) -> Option<(u32, u32)> {
+        let providers = self.providers.lock();
+        providers.get(&service).map(|slots| (slots.in_flight.load(Ordering::SeqCst), slots.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_in_flight_requests_are_bounded_per_provider() {
+        let limiter = Arc::new(ConcurrencyLimiter::default());
+        limiter.set_limit(ServiceProvider::Firecrawl, 2).unwrap();
+        assert!(limiter.set_limit(ServiceProvider::Firecrawl, 0).is_err());
+
+        let first = limiter.acquire(ServiceProvider::Firecrawl).await.unwrap();
+        let _second = limiter.acquire(ServiceProvider::Firecrawl).await.unwrap();
+        assert_eq!(limiter.usage(ServiceProvider::Firecrawl), Some((2, 2)));
+
+        // The third waits for a slot; other providers are not held up
+        let waiting = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(ServiceProvider::Firecrawl).await.is_some() }
+        });
+        assert!(limiter.acquire(ServiceProvider::Tavily).await.is_none());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        assert!(tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap());
+        assert_eq!(limiter.usage(ServiceProvider::Firecrawl), Some((1, 2)));
+
+        // Raising the limit keeps the count of requests already in flight
+        limiter.set_limit(ServiceProvider::Firecrawl, 4).unwrap();
+        assert_eq!(limiter.usage(ServiceProvider::Firecrawl), Some((1, 4)));
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Serialize, Deserialize};
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use super::crawl_policy::CrawlPolicy;
+use super::egress;
+use super::response_recorder;
+use super::web_search::link_key;
+
+/// Timeout of the conditional requests sent to a source to check whether it changed
+const REVALIDATION_TIMEOUT_MS: u32 = 5000;
+
+/// How long extracted content may be reused, and how many pages are kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentCacheConfig {
+    pub enabled: bool,
+    /// Content older than this is extracted again even if the page says it is unchanged
+    pub max_age_secs: u64,
+    /// Least recently used pages are dropped beyond this
+    pub max_entries: usize,
+}
+
+impl Default for ContentCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_age_secs: 24 * 60 * 60,
+            max_entries: 500,
+        }
+    }
+}
+
+impl ContentCacheConfig {
+    fn validate(&self) -> AppResult<()> {
+        if self.max_entries == 0 {
+            return Err(AppError::validation("max_entries", "must be at least 1; disable the cache instead"));
+        }
+        Ok(())
+    }
+}
+
+/// What a source said about the version of a page, for asking later whether it changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+        Self { etag: header(ETAG), last_modified: header(LAST_MODIFIED) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Whether a response's validators describe the same version as these. A weak ETag
+    /// matches its strong form, as `If-None-Match` compares them.
+    fn same_version(&self, other: &Validators) -> bool {
+        let weak = |etag: &str| etag.trim_start_matches("W/").to_string();
+        match (&self.etag, &other.etag) {
+            (Some(ours), Some(theirs)) => weak(ours) == weak(theirs),
+            (Some(_), None) | (None, Some(_)) => false,
+            (None, None) => self.last_modified.is_some() && self.last_modified == other.last_modified,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedContent {
+    content: serde_json::Value,
+    validators: Validators,
+    fetched_at: DateTime<Utc>,
+    last_used: DateTime<Utc>,
+}
+
+/// Extracted content and whether it came from the cache
+#[derive(Debug, Clone)]
+pub struct CachedExtraction {
+    pub content: serde_json::Value,
+    pub from_cache: bool,
+}
+
+/// How well the content cache is doing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentCacheStats {
+    pub entries: usize,
+    pub lookups: u64,
+    /// Lookups served from the cache, whether or not the source was asked
+    pub hits: u64,
+    /// Conditional requests sent to sources
+    pub revalidations: u64,
+    /// Revalidations that found the page had changed
+    pub changed: u64,
+    /// Cached pages dropped for being older than the maximum age
+    pub expired: u64,
+    /// Extractions made by workflows that turned the cache off
+    pub bypassed: u64,
+    pub hit_rate: f64,
+    /// Provider requests the cache took the place of
+    pub provider_calls_saved: HashMap<ServiceProvider, u64>,
+}
+
+enum Revalidation {
+    Unchanged(Validators),
+    Changed,
+    /// The source could not be asked; the content is extracted again to be safe
+    Failed,
+}
+
+/// Extracted page content keyed by canonical URL, so workflows covering overlapping
+/// topics do not spend Firecrawl or Jina quota on the same pages again.
+///
+/// A page whose source sent an ETag or Last-Modified is checked with a conditional
+/// request each time it is reused, which costs no provider quota; one without is reused
+/// as-is. Either way nothing older than the maximum age is served. Those requests go
+/// through the crawl policy like extraction does.
+pub struct ContentCache {
+    config: Mutex<ContentCacheConfig>,
+    /// By provider and canonical URL, since each provider shapes its content differently
+    entries: Mutex<HashMap<(ServiceProvider, String), CachedContent>>,
+    stats: Mutex<ContentCacheStats>,
+    crawl_policy: Arc<CrawlPolicy>,
+    client: reqwest::Client,
+}
+
+impl Default for ContentCache {
+    fn default() -> Self {
+        Self::new(ContentCacheConfig::default(), Arc::new(CrawlPolicy::default()))
+    }
+}
+
+impl ContentCache {
+    pub fn new(config: ContentCacheConfig, crawl_policy: Arc<CrawlPolicy>) -> Self {
+        Self {
+            config: Mutex::new(config),
+            entries: Mutex::new(HashMap::new()),
+            stats: Mutex::new(ContentCacheStats::default()),
+            crawl_policy,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn config(&self) -> ContentCacheConfig {
+        self.config.lock().clone()
+    }
+
+    /// Replace the configuration, dropping what no longer fits
+    pub fn set_config(&self, config: ContentCacheConfig) -> AppResult<()> {
+        config.validate()?;
+        if !config.enabled {
+            self.entries.lock().clear();
+        }
+        *self.config.lock() = config;
+        self.evict();
+        Ok(())
+    }
+
+    pub fn stats(&self) -> ContentCacheStats {
+        let mut stats = self.stats.lock().clone();
+        stats.entries = self.entries.lock().len();
+        stats.hit_rate = if stats.lookups == 0 { 0.0 } else { stats.hits as f64 / stats.lookups as f64 };
+        stats
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    /// The content of `url` from the cache when it is still current, otherwise from
+    /// `extract`, which asks `provider` for it. `bypass` skips the cache for workflows
+    /// that turned caching off, without dropping what others cached.
+    pub async fn get_or_extract<F, Fut>(
+        &self,
+        url: &str,
+        provider: ServiceProvider,
+        bypass: bool,
+        extract: F,
+    ) -> AppResult<CachedExtraction>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<serde_json::Value>>,
+    {
+        let config = self.config();
+        if bypass || !config.enabled {
+            if bypass {
+                self.stats.lock().bypassed += 1;
+            }
+            return Ok(CachedExtraction { content: extract().await?, from_cache: false });
+        }
+
+        let key = (provider, link_key(url));
+        self.stats.lock().lookups += 1;
+        if let Some(content) = self.lookup(&key, url, &config).await {
+            return Ok(CachedExtraction { content, from_cache: true });
+        }
+
+        // Asked first, so a page that changes during extraction is stored with the older
+        // validators and extracted again on its next lookup
+        let validators = self.fetch_validators(url).await;
+        let content = extract().await?;
+        let now = Utc::now();
+        self.entries.lock().insert(key, CachedContent {
+            content: content.clone(),
+            validators,
+            fetched_at: now,
+            last_used: now,
+        });
+        self.evict();
+        Ok(CachedExtraction { content, from_cache: false })
+    }
+
+    async fn lookup(&self, key: &(ServiceProvider, String), url: &str, config: &ContentCacheConfig) -> Option<serde_json::Value> {
+        let cached = self.entries.lock().get(key).cloned()?;
+
+        let age = Utc::now().signed_duration_since(cached.fetched_at);
+        if age.num_seconds() >= config.max_age_secs as i64 {
+            self.entries.lock().remove(key);
+            self.stats.lock().expired += 1;
+            return None;
+        }
+
+        let validators = if cached.validators.is_empty() {
+            cached.validators
+        } else {
+            self.stats.lock().revalidations += 1;
+            match self.revalidate(url, &cached.validators).await {
+                Revalidation::Unchanged(validators) => validators,
+                Revalidation::Changed => {
+                    debug!("Cached content of {} is out of date", url);
+                    self.entries.lock().remove(key);
+                    self.stats.lock().changed += 1;
+                    return None;
+                }
+                Revalidation::Failed => return None,
+            }
+        };
+
+        if let Some(entry) = self.entries.lock().get_mut(key) {
+            entry.validators = validators;
+            entry.last_used = Utc::now();
+        }
+        let mut stats = self.stats.lock();
+        stats.hits += 1;
+        *stats.provider_calls_saved.entry(key.0).or_default() += 1;
+        Some(cached.content)
+    }
+
+    /// Send a HEAD request for `url`, conditional on `validators` when given. Like an
+    /// extraction it is only sent where robots.txt allows, in turn with the host's other
+    /// requests, and never for mocked pages.
+    async fn head(&self, url: &str, validators: Option<&Validators>) -> Result<reqwest::Response, String> {
+        if response_recorder::is_mocked() {
+            return Err("mocked pages are not fetched from their host".to_string());
+        }
+        self.crawl_policy.check(url).await.map_err(|skipped| skipped.reason.to_string())?;
+        let client = egress::client_for(&self.client, REVALIDATION_TIMEOUT_MS).map_err(|e| e.to_string())?;
+        let mut request = client.head(url);
+        if let Some(etag) = validators.and_then(|v| v.etag.as_ref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = validators.and_then(|v| v.last_modified.as_ref()) {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let _slot = self.crawl_policy.host_slot(url).await;
+        request.send().await.map_err(|e| e.to_string())
+    }
+
+    /// Ask the source whether the page changed since `validators` were sent. HEAD keeps
+    /// it to headers even from servers that ignore the conditions.
+    async fn revalidate(&self, url: &str, validators: &Validators) -> Revalidation {
+        match self.head(url, Some(validators)).await {
+            Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                let refreshed = Validators::from_headers(response.headers());
+                Revalidation::Unchanged(if refreshed.is_empty() { validators.clone() } else { refreshed })
+            }
+            Ok(response) if response.status().is_success() => {
+                let current = Validators::from_headers(response.headers());
+                if validators.same_version(&current) {
+                    Revalidation::Unchanged(current)
+                } else {
+                    Revalidation::Changed
+                }
+            }
+            Ok(response) => {
+                debug!("Could not revalidate {}: {}", url, response.status());
+                Revalidation::Failed
+            }
+            Err(e) => {
+                debug!("Could not revalidate {}: {}", url, e);
+                Revalidation::Failed
+            }
+        }
+    }
+
+    /// The validators the source sends for a page, or none if it cannot be asked
+    async fn fetch_validators(&self, url: &str) -> Validators {
+        match self.head(url, None).await {
+            Ok(response) if response.status().is_success() => Validators::from_headers(response.headers()),
+            _ => Validators::default(),
+        }
+    }
+
+    fn evict(&self) {
+        let max_entries = self.config.lock().max_entries;
+        let mut entries = self.entries.lock();
+        while entries.len() > max_entries {
+            let Some(oldest) = entries.iter().min_by_key(|(_, cached)| cached.last_used).map(|(key, _)| key.clone()) else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::ProviderRecording;
+
+    #[tokio::test]
+    async fn test_repeated_extractions_are_served_from_the_cache() {
+        // Mocked pages are not fetched from their host, so they get no validators and are
+        // reused as-is
+        let mock = ProviderRecording::Mock { latency_ms: 0, failure_percent: 0 };
+        response_recorder::step_scope(uuid::Uuid::new_v4(), 1, mock, cached_extractions()).await;
+
+        let etag = Validators { etag: Some("\"v1\"".to_string()), last_modified: None };
+        assert!(etag.same_version(&Validators { etag: Some("W/\"v1\"".to_string()), last_modified: None }));
+        assert!(!etag.same_version(&Validators { etag: Some("\"v2\"".to_string()), last_modified: None }));
+        assert!(!Validators::default().same_version(&Validators::default()));
+    }
+
+    async fn cached_extractions() {
+        let cache = ContentCache::new(
+            ContentCacheConfig { max_entries: 2, ..ContentCacheConfig::default() },
+            Arc::new(CrawlPolicy::default()),
+        );
+        let page = |n: u32| async move { Ok::<_, AppError>(serde_json::json!({ "markdown": format!("page {}", n) })) };
+        let url = "https://example.org/article?utm_source=feed";
+
+        let first = cache.get_or_extract(url, ServiceProvider::Firecrawl, false, || page(1)).await.unwrap();
+        assert!(!first.from_cache);
+        let again = cache.get_or_extract("https://example.org/article/", ServiceProvider::Firecrawl, false, || page(2)).await.unwrap();
+        assert!(again.from_cache);
+        assert_eq!(again.content["markdown"], "page 1");
+
+        // Workflows that turned caching off always extract, and other providers keep their own
+        let bypassed = cache.get_or_extract(url, ServiceProvider::Firecrawl, true, || page(3)).await.unwrap();
+        assert_eq!(bypassed.content["markdown"], "page 3");
+        assert!(!cache.get_or_extract(url, ServiceProvider::Jina, false, || page(4)).await.unwrap().from_cache);
+
+        let stats = cache.stats();
+        assert_eq!((stats.lookups, stats.hits, stats.bypassed), (3, 1, 1));
+        assert_eq!(stats.provider_calls_saved[&ServiceProvider::Firecrawl], 1);
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+
+        cache.get_or_extract("https://example.org/other", ServiceProvider::Firecrawl, false, || page(5)).await.unwrap();
+        // The Firecrawl copy of the article was used least recently
+        assert_eq!(cache.stats().entries, 2);
+        assert!(!cache.get_or_extract(url, ServiceProvider::Firecrawl, false, || page(6)).await.unwrap().from_cache);
+
+        cache.set_config(ContentCacheConfig { max_age_secs: 0, ..cache.config() }).unwrap();
+        assert!(!cache.get_or_extract("https://example.org/other", ServiceProvider::Firecrawl, false, || page(7)).await.unwrap().from_cache);
+        assert_eq!(cache.stats().expired, 1);
+        assert!(cache.set_config(ContentCacheConfig { max_entries: 0, ..ContentCacheConfig::default() }).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::{ApiError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use super::service_integration::ServiceResponse;
+
+/// Error codes and types OpenRouter passes on from the upstream model's provider for a
+/// refusal on content or safety grounds: OpenAI's and Azure OpenAI's. Matched exactly.
+const OPENROUTER_POLICY_CODES: &[&str] = &[
+    "content_policy_violation",
+    "content_filter",
+];
+
+/// Phrases in error messages that mark a content or safety refusal
+const OPENROUTER_POLICY_PHRASES: &[&str] = &[
+    "content policy",
+    "content management policy",
+    "usage policies",
+    "flagged by moderation",
+    "flagged for moderation",
+    "safety system",
+    "safety filter",
+];
+
+/// Longest provider reason passed on to the user
+const MAX_REASON_CHARS: usize = 300;
+
+/// The provider's reason if `response` refuses the query on content-policy or safety
+/// grounds rather than failing. Completions cut off by a content filter count too,
+/// even when the HTTP status is a success. Only OpenRouter, the one provider that
+/// generates content, refuses queries this way.
+pub fn rejection_reason(response: &ServiceResponse) -> Option<String> {
+    if response.service != ServiceProvider::OpenRouter {
+        return None;
+    }
+    let body: Option<serde_json::Value> = serde_json::from_str(&response.body).ok();
+
+    if let Some(body) = &body {
+        let filtered_choice = body.get("choices")
+            .and_then(|choices| choices.as_array())
+            .is_some_and(|choices| choices.iter().any(|choice| {
+                choice.get("finish_reason").and_then(|reason| reason.as_str()) == Some("content_filter")
+            }));
+        if filtered_choice {
+            return Some("the response was withheld by the provider's content filter".to_string());
+        }
+    }
+
+    if response.success {
+        return None;
+    }
+
+    let error = body.as_ref().and_then(|body| body.get("error"));
+    let field = |name: &str| error
+        .and_then(|error| error.get(name))
+        .and_then(|value| value.as_str())
+        .map(str::to_ascii_lowercase);
+    let coded = [field("code"), field("type")].into_iter()
+        .flatten()
+        .any(|code| OPENROUTER_POLICY_CODES.contains(&code.as_str()));
+    // OpenRouter's own moderation answers 403 with the flagged input or reasons attached
+    let moderated = response.status_code == 403
+        && error.and_then(|error| error.get("metadata"))
+            .is_some_and(|metadata| metadata.get("flagged_input").is_some() || metadata.get("reasons").is_some());
+
+    let message = error
+        .and_then(|error| error.get("message").and_then(|message| message.as_str()).or_else(|| error.as_str()))
+        .map(str::to_string)
+        .or_else(|| response.error_message.clone())
+        .unwrap_or_else(|| response.body.clone());
+    let lowered = message.to_ascii_lowercase();
+    if !coded && !moderated && !OPENROUTER_POLICY_PHRASES.iter().any(|phrase| lowered.contains(phrase)) {
+        return None;
+    }
+
+    // OpenRouter lists the moderation categories that were flagged
+    let reasons = error
+        .and_then(|error| error.pointer("/metadata/reasons"))
+        .and_then(|reasons| reasons.as_array())
+        .map(|reasons| reasons.iter().filter_map(|reason| reason.as_str()).collect::<Vec<_>>().join(", "))
+        .filter(|reasons| !reasons.is_empty());
+
+    let reason = match reasons {
+        Some(reasons) => format!("{} ({})", message.trim(), reasons),
+        None => message.trim().to_string(),
+    };
+    Some(reason.chars().take(MAX_REASON_CHARS).collect())
+}
+
+/// Turn a content-policy refusal into `ApiError::ContentPolicyRejected`, leaving other
+/// results as they are
+pub fn reject_refusals(service: ServiceProvider, result: AppResult<ServiceResponse>) -> AppResult<ServiceResponse> {
+    match result {
+        Ok(response) => match rejection_reason(&response) {
+            Some(reason) => Err(ApiError::content_policy_rejected(service.display_name(), reason).into()),
+            None => Ok(response),
+        },
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn response(status_code: u16, body: &str) -> ServiceResponse {
+        ServiceResponse {
+            request_id: Uuid::new_v4(),
+            service: ServiceProvider::OpenRouter,
+            status_code,
+            headers: HashMap::new(),
+            body: body.to_string(),
+            response_time_ms: 120,
+            success: (200..300).contains(&status_code),
+            error_message: None,
+            metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_refusals_are_told_apart_from_other_failures() {
+        let flagged = response(403, r#"{"error":{"code":403,"message":"Your input was flagged by moderation","metadata":{"reasons":["violence"]}}}"#);
+        assert_eq!(rejection_reason(&flagged).as_deref(), Some("Your input was flagged by moderation (violence)"));
+
+        let coded = response(400, r#"{"error":{"code":"content_policy_violation","message":"Request blocked"}}"#);
+        assert_eq!(rejection_reason(&coded).as_deref(), Some("Request blocked"));
+
+        let filtered = response(200, r#"{"choices":[{"finish_reason":"content_filter","message":{"content":""}}]}"#);
+        assert!(rejection_reason(&filtered).is_some());
+
+        // A revoked key, an ordinary completion, an outage of a moderation or safety service
+        // and a search provider's error are not refusals
+        assert!(rejection_reason(&response(403, r#"{"error":{"message":"Invalid API key"}}"#)).is_none());
+        assert!(rejection_reason(&response(503, r#"{"error":{"code":"moderation_unavailable","message":"Try again"}}"#)).is_none());
+        assert!(rejection_reason(&response(500, r#"{"error":{"type":"safety_check_timeout","message":"Try again"}}"#)).is_none());
+        let mut search = response(400, r#"{"error":{"code":"content_policy_violation","message":"Blocked"}}"#);
+        search.service = ServiceProvider::SerpApi;
+        assert!(rejection_reason(&search).is_none());
+        assert!(rejection_reason(&response(200, r#"{"choices":[{"finish_reason":"stop"}]}"#)).is_none());
+
+        let error = reject_refusals(ServiceProvider::OpenRouter, Ok(coded)).unwrap_err();
+        assert!(matches!(error, crate::error::AppError::Api(ApiError::ContentPolicyRejected { .. })));
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tracing::{debug, info};
+
+use crate::error::{AppError, AppResult};
+use super::egress;
+use super::response_recorder;
+
+const ROBOTS_TIMEOUT_MS: u32 = 10000;
+
+/// How long a robots.txt that could not be fetched is remembered before asking again
+const UNREACHABLE_RETRY_SECS: i64 = 300;
+
+/// Longest `Crawl-delay` honoured, so one site cannot stall a workflow
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(30);
+
+/// Robots.txt files are only read up to this size, as RFC 9309 allows
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+/// How closely robots.txt is followed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RobotsMode {
+    /// Not fetched or checked
+    Ignore,
+    /// Only explicit disallow rules skip a URL; a site whose robots.txt cannot be
+    /// fetched is crawled
+    Lenient,
+    /// As RFC 9309 asks: a site whose robots.txt exists but cannot be fetched is not
+    /// crawled until it can be
+    #[default]
+    Respect,
+}
+
+/// Robots.txt handling and how hard any one host is hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlPolicyConfig {
+    pub robots: RobotsMode,
+    /// Product token robots.txt groups are matched against
+    pub user_agent: String,
+    /// Minimum gap between requests to the same host; a longer `Crawl-delay` wins
+    pub min_host_delay_ms: u64,
+    pub max_concurrent_per_host: usize,
+    pub robots_cache_secs: u64,
+}
+
+impl Default for CrawlPolicyConfig {
+    fn default() -> Self {
+        Self {
+            robots: RobotsMode::default(),
+            user_agent: "FreeDeepResearchBot".to_string(),
+            min_host_delay_ms: 1000,
+            max_concurrent_per_host: 2,
+            robots_cache_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+impl CrawlPolicyConfig {
+    fn validate(&self) -> AppResult<()> {
+        if self.max_concurrent_per_host == 0 {
+            return Err(AppError::validation("max_concurrent_per_host", "must be at least 1"));
+        }
+        if self.user_agent.trim().is_empty() {
+            return Err(AppError::validation("user_agent", "must not be empty"));
+        }
+        Ok(())
+    }
+}
+
+/// Why a URL was not fetched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// A robots.txt rule forbids it
+    DisallowedByRobots { rule: String },
+    /// The host's robots.txt could not be fetched, so whether it may be crawled is unknown
+    RobotsUnavailable { detail: String },
+    InvalidUrl,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::DisallowedByRobots { rule } => write!(f, "disallowed by robots.txt rule '{}'", rule),
+            SkipReason::RobotsUnavailable { detail } => write!(f, "robots.txt unavailable: {}", detail),
+            SkipReason::InvalidUrl => write!(f, "not a crawlable URL"),
+        }
+    }
+}
+
+/// A URL left out of a workflow's extraction, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedUrl {
+    pub url: String,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RobotsRule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RobotsGroup {
+    agents: Vec<String>,
+    rules: Vec<RobotsRule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// The rules of one robots.txt
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    groups: Vec<RobotsGroup>,
+}
+
+impl RobotsRules {
+    pub fn parse(text: &str) -> Self {
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        // Consecutive user-agent lines share the group that follows them
+        let mut collecting_agents = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !collecting_agents {
+                        groups.push(RobotsGroup::default());
+                        collecting_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    collecting_agents = false;
+                    // An empty Disallow allows everything, the same as no rule
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.rules.push(RobotsRule { allow: key == "allow", pattern: value.to_string() });
+                    }
+                }
+                "crawl-delay" => {
+                    collecting_agents = false;
+                    if let (Some(group), Ok(secs)) = (groups.last_mut(), value.parse::<f64>()) {
+                        if secs.is_finite() && secs >= 0.0 {
+                            group.crawl_delay = Some(Duration::from_secs_f64(secs).min(MAX_CRAWL_DELAY));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self { groups }
+    }
+
+    /// The groups addressed to `user_agent`, or the `*` groups if none are
+    fn groups_for(&self, user_agent: &str) -> Vec<&RobotsGroup> {
+        let token = user_agent.to_ascii_lowercase();
+        let named: Vec<&RobotsGroup> = self.groups.iter()
+            .filter(|group| group.agents.iter().any(|agent| *agent == token))
+            .collect();
+        if !named.is_empty() {
+            return named;
+        }
+        self.groups.iter().filter(|group| group.agents.iter().any(|agent| agent == "*")).collect()
+    }
+
+    /// Whether `user_agent` may fetch `path` (path and query), or the rule forbidding it.
+    /// The longest matching rule wins, and an allow wins a tie.
+    pub fn check(&self, user_agent: &str, path: &str) -> Result<(), String> {
+        if path == "/robots.txt" {
+            return Ok(());
+        }
+        let best = self.groups_for(user_agent).into_iter()
+            .flat_map(|group| group.rules.iter())
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow));
+        match best {
+            Some(rule) if !rule.allow => Err(format!("Disallow: {}", rule.pattern)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent).into_iter().filter_map(|group| group.crawl_delay).max()
+    }
+}
+
+/// Robots.txt path matching: `*` matches any run of characters and a trailing `$`
+/// anchors the end; otherwise a pattern matches as a prefix
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[derive(Debug, Clone)]
+enum RobotsEntry {
+    Rules(Arc<RobotsRules>),
+    Unreachable(String),
+}
+
+#[derive(Debug, Clone)]
+struct CachedRobots {
+    entry: RobotsEntry,
+    expires_at: DateTime<Utc>,
+}
+
+struct HostState {
+    slots: Arc<Semaphore>,
+    /// Permits `slots` was made with
+    capacity: usize,
+    next_request: Arc<tokio::sync::Mutex<Instant>>,
+}
+
+/// Held while a request to a host is in flight
+pub struct HostSlot {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Keeps extraction polite: URLs robots.txt forbids are skipped, and each host gets a
+/// gap between requests and a cap on how many run at once
+pub struct CrawlPolicy {
+    config: Mutex<CrawlPolicyConfig>,
+    robots: Mutex<HashMap<String, CachedRobots>>,
+    hosts: Mutex<HashMap<String, HostState>>,
+    client: reqwest::Client,
+}
+
+impl Default for CrawlPolicy {
+    fn default() -> Self {
+        Self::new(CrawlPolicyConfig::default())
+    }
+}
+
+impl CrawlPolicy {
+    pub fn new(config: CrawlPolicyConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            robots: Mutex::new(HashMap::new()),
+            hosts: Mutex::new(HashMap::new()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn config(&self) -> CrawlPolicyConfig {
+        self.config.lock().clone()
+    }
+
+    /// Replace the configuration. Cached robots.txt files are dropped, since they were
+    /// read for the old user agent. Hosts keep their place in the queue, and pick up the
+    /// new concurrency cap once no request to them is in flight.
+    pub fn set_config(&self, config: CrawlPolicyConfig) -> AppResult<()> {
+        config.validate()?;
+        info!("Crawl policy set to {:?} robots.txt handling, {}ms between requests per host", config.robots, config.min_host_delay_ms);
+        *self.config.lock() = config;
+        self.robots.lock().clear();
+        Ok(())
+    }
+
+    /// Whether `url` may be fetched, fetching the host's robots.txt on first use
+    pub async fn check(&self, url: &str) -> Result<(), SkippedUrl> {
+        let skipped = |reason| SkippedUrl { url: url.to_string(), reason };
+        let Some((origin, path)) = split_url(url) else {
+            return Err(skipped(SkipReason::InvalidUrl));
+        };
+        let config = self.config();
+        // Mocked pages are never fetched from their host
+        if config.robots == RobotsMode::Ignore || response_recorder::is_mocked() {
+            return Ok(());
+        }
+
+        match self.robots_for(&origin).await {
+            RobotsEntry::Rules(rules) => rules.check(&config.user_agent, &path)
+                .map_err(|rule| skipped(SkipReason::DisallowedByRobots { rule })),
+            RobotsEntry::Unreachable(detail) if config.robots == RobotsMode::Respect => {
+                Err(skipped(SkipReason::RobotsUnavailable { detail }))
+            }
+            RobotsEntry::Unreachable(_) => Ok(()),
+        }
+    }
+
+    /// The URLs of `urls` that may be fetched, adding the others to `skipped`
+    pub async fn retain_allowed(&self, urls: Vec<String>, skipped: &mut Vec<SkippedUrl>) -> Vec<String> {
+        let mut allowed = Vec::with_capacity(urls.len());
+        for url in urls {
+            match self.check(&url).await {
+                Ok(()) => allowed.push(url),
+                Err(skip) => skipped.push(skip),
+            }
+        }
+        allowed
+    }
+
+    /// Wait for a turn to send a request to `url`'s host
+    pub async fn host_slot(&self, url: &str) -> HostSlot {
+        if response_recorder::is_mocked() {
+            return HostSlot { _permit: None };
+        }
+        let config = self.config();
+        let origin = split_url(url).map(|(origin, _)| origin).unwrap_or_default();
+        let crawl_delay = match self.robots.lock().get(&origin).map(|cached| cached.entry.clone()) {
+            Some(RobotsEntry::Rules(rules)) if config.robots != RobotsMode::Ignore => rules.crawl_delay(&config.user_agent),
+            _ => None,
+        };
+        let delay = Duration::from_millis(config.min_host_delay_ms).max(crawl_delay.unwrap_or_default());
+
+        let (slots, next_request) = {
+            let mut hosts = self.hosts.lock();
+            let host = hosts.entry(origin).or_insert_with(|| HostState {
+                slots: Arc::new(Semaphore::new(config.max_concurrent_per_host)),
+                capacity: config.max_concurrent_per_host,
+                next_request: Arc::new(tokio::sync::Mutex::new(Instant::now())),
+            });
+            // Swapped only while every permit is back, so requests in flight or waiting
+            // under the old cap cannot run alongside a full set under the new one
+            if host.capacity != config.max_concurrent_per_host && host.slots.available_permits() == host.capacity {
+                host.slots = Arc::new(Semaphore::new(config.max_concurrent_per_host));
+                host.capacity = config.max_concurrent_per_host;
+            }
+            (host.slots.clone(), host.next_request.clone())
+        };
+        let permit = slots.acquire_owned().await.expect("host semaphore is never closed");
+
+        let mut next = next_request.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = Instant::now().max(*next) + delay;
+        HostSlot { _permit: Some(permit) }
+    }
+
+    async fn robots_for(&self, origin: &str) -> RobotsEntry {
+        if let Some(cached) = self.robots.lock().get(origin) {
+            if cached.expires_at > Utc::now() {
+                return cached.entry.clone();
+            }
+        }
+
+        let entry = self.fetch_robots(origin).await;
+        let ttl = match &entry {
+            RobotsEntry::Rules(_) => self.config().robots_cache_secs as i64,
+            RobotsEntry::Unreachable(_) => UNREACHABLE_RETRY_SECS,
+        };
+        self.robots.lock().insert(origin.to_string(), CachedRobots {
+            entry: entry.clone(),
+            expires_at: Utc::now() + chrono::Duration::seconds(ttl),
+        });
+        entry
+    }
+
+    /// A host's robots.txt. Per RFC 9309 a missing one (4xx) allows everything, and
+    /// one that errors (5xx) or cannot be reached leaves it unknown.
+    async fn fetch_robots(&self, origin: &str) -> RobotsEntry {
+        let robots_url = format!("{}/robots.txt", origin);
+        let client = match egress::client_for(&self.client, ROBOTS_TIMEOUT_MS) {
+            Ok(client) => client,
+            Err(e) => return RobotsEntry::Unreachable(e.to_string()),
+        };
+        let user_agent = self.config().user_agent;
+        let response = match client.get(&robots_url).header(reqwest::header::USER_AGENT, user_agent).send().await {
+            Ok(response) => response,
+            Err(e) => return RobotsEntry::Unreachable(e.to_string()),
+        };
+
+        let status = response.status();
+        if status.is_client_error() {
+            return RobotsEntry::Rules(Arc::new(RobotsRules::default()));
+        }
+        if !status.is_success() {
+            return RobotsEntry::Unreachable(format!("{} answered {}", robots_url, status));
+        }
+        match response.bytes().await {
+            Ok(body) => {
+                let body = &body[..body.len().min(MAX_ROBOTS_BYTES)];
+                debug!("Fetched {} ({} bytes)", robots_url, body.len());
+                RobotsEntry::Rules(Arc::new(RobotsRules::parse(&String::from_utf8_lossy(body))))
+            }
+            Err(e) => RobotsEntry::Unreachable(e.to_string()),
+        }
+    }
+}
+
+/// `scheme://host[:port]` and the path with query of an http(s) URL
+fn split_url(url: &str) -> Option<(String, String)> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+    let origin = parsed.origin().ascii_serialization();
+    let path = match parsed.query() {
+        Some(query) => format!("{}?{}", parsed.path(), query),
+        None => parsed.path().to_string(),
+    };
+    Some((origin, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_robots_rules_and_host_spacing() {
+        let rules = RobotsRules::parse(
+            "User-agent: *\n\
+             Disallow: /private\n\
+             Allow: /private/reports\n\
+             Disallow: /*.pdf$\n\
+             Crawl-delay: 2\n\
+             \n\
+             User-agent: FreeDeepResearchBot\n\
+             User-agent: otherbot\n\
+             Disallow: /search # no result pages\n\
+             Disallow:\n",
+        );
+        // The bot has its own group, so the `*` rules do not apply to it
+        assert_eq!(rules.check("FreeDeepResearchBot", "/search?q=x"), Err("Disallow: /search".to_string()));
+        assert_eq!(rules.check("FreeDeepResearchBot", "/private"), Ok(()));
+        assert_eq!(rules.crawl_delay("FreeDeepResearchBot"), None);
+
+        assert!(rules.check("somebot", "/private/notes").is_err());
+        assert_eq!(rules.check("somebot", "/private/reports/2026"), Ok(()));
+        assert!(rules.check("somebot", "/papers/study.pdf").is_err());
+        assert_eq!(rules.check("somebot", "/papers/study.pdf?download=1"), Ok(()));
+        assert_eq!(rules.check("somebot", "/robots.txt"), Ok(()));
+        assert_eq!(rules.crawl_delay("somebot"), Some(Duration::from_secs(2)));
+
+        assert!(pattern_matches("/a/*/c", "/a/b/c/d"));
+        assert!(!pattern_matches("/a/*/c$", "/a/b/c/d"));
+
+        let policy = CrawlPolicy::new(CrawlPolicyConfig { min_host_delay_ms: 50, ..CrawlPolicyConfig::default() });
+        assert_eq!(policy.check("ftp://example.com/file").await.unwrap_err().reason, SkipReason::InvalidUrl);
+        policy.set_config(CrawlPolicyConfig { robots: RobotsMode::Ignore, min_host_delay_ms: 50, ..CrawlPolicyConfig::default() }).unwrap();
+        assert!(policy.check("http://127.0.0.1:9/page").await.is_ok());
+
+        let start = Instant::now();
+        let in_flight = policy.host_slot("http://127.0.0.1:9/a").await;
+        // Changing the policy while a request is in flight keeps the host's spacing
+        policy.set_config(CrawlPolicyConfig {
+            robots: RobotsMode::Ignore,
+            min_host_delay_ms: 50,
+            max_concurrent_per_host: 1,
+            ..CrawlPolicyConfig::default()
+        }).unwrap();
+        drop(in_flight);
+        drop(policy.host_slot("http://127.0.0.1:9/b").await);
+        drop(policy.host_slot("http://localhost:9/c").await);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(policy.hosts.lock()["http://127.0.0.1:9"].capacity, 1);
+        assert!(policy.set_config(CrawlPolicyConfig { max_concurrent_per_host: 0, ..CrawlPolicyConfig::default() }).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::error::{ApiError, AppError, AppResult};
+use crate::utils::air_gap;
+
+/// User agent of every provider request
+const USER_AGENT: &str = "Free-Deep-Research-System/1.0.0";
+
+tokio::task_local! {
+    static CURRENT_EGRESS: Arc<EgressClients>;
+}
+
+/// A named proxy that provider requests can be sent out through, e.g. to reach
+/// sources only available from one region
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressProfile {
+    pub name: String,
+    /// HTTP(S) proxy every request of the profile goes through, e.g. `http://proxy.eu.example:3128`
+    pub proxy_url: String,
+    /// Region the proxy egresses from, for display
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+impl EgressProfile {
+    fn validate(&self) -> AppResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::validation("egress_profile", "profile name must not be empty"));
+        }
+        let parsed = url::Url::parse(&self.proxy_url)
+            .map_err(|e| AppError::validation("proxy_url", format!("Invalid proxy URL for egress profile '{}': {}", self.name, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::validation("proxy_url", format!("Proxy URL for egress profile '{}' must use http or https", self.name)));
+        }
+        Ok(())
+    }
+}
+
+/// The HTTP clients of one egress profile. reqwest fixes timeouts per client, so
+/// there is one client per request timeout, each built on first use.
+pub struct EgressClients {
+    profile: EgressProfile,
+    clients: Mutex<HashMap<Duration, reqwest::Client>>,
+}
+
+impl EgressClients {
+    pub fn new(profile: EgressProfile) -> AppResult<Self> {
+        profile.validate()?;
+        Ok(Self { profile, clients: Mutex::new(HashMap::new()) })
+    }
+
+    pub fn profile(&self) -> &EgressProfile {
+        &self.profile
+    }
+
+    /// A client going out through the profile's proxy, with `timeout` per request
+    pub fn client(&self, timeout: Duration) -> AppResult<reqwest::Client> {
+        let mut clients = self.clients.lock();
+        if let Some(client) = clients.get(&timeout) {
+            return Ok(client.clone());
+        }
+
+        let invalid = |e: reqwest::Error| -> AppError {
+            ApiError::invalid_configuration(format!("egress profile {}", self.profile.name), e.to_string()).into()
+        };
+        let client = base_builder(timeout)
+            .proxy(reqwest::Proxy::all(&self.profile.proxy_url).map_err(invalid)?)
+            .build()
+            .map_err(invalid)?;
+        clients.insert(timeout, client.clone());
+        Ok(client)
+    }
+}
+
+/// The builder integrations make their default client with, so a profile's clients send
+/// requests the same way apart from the proxy
+pub fn client_builder(timeout_ms: u32) -> reqwest::ClientBuilder {
+    base_builder(Duration::from_millis(timeout_ms as u64))
+}
+
+fn base_builder(timeout: Duration) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+}
+
+/// Run `future` with its provider requests sent through `clients`, or through each
+/// integration's own client when `None`. Task-local, so work spawned onto other
+/// tasks has to be scoped again.
+pub async fn egress_scope<F: Future>(clients: Option<Arc<EgressClients>>, future: F) -> F::Output {
+    match clients {
+        Some(clients) => CURRENT_EGRESS.scope(clients, future).await,
+        None => future.await,
+    }
+}
+
+/// Name of the egress profile the running task's requests go out through
+pub fn current_profile() -> Option<String> {
+    CURRENT_EGRESS.try_with(|clients| clients.profile.name.clone()).ok()
+}
+
+/// The client a provider request is sent on: the scoped egress profile's if there is
+/// one, otherwise the integration's `default`. None at all in offline mode, so every
+/// integration fails before building a request.
+pub fn client_for(default: &reqwest::Client, timeout_ms: u32) -> AppResult<reqwest::Client> {
+    air_gap::ensure_online("provider requests")?;
+    match CURRENT_EGRESS.try_with(Arc::clone) {
+        Ok(clients) => clients.client(Duration::from_millis(timeout_ms as u64)),
+        Err(_) => Ok(default.clone()),
+    }
+}
+
+/// Setting the egress profiles are saved under
+pub const EGRESS_PROFILES_SETTING: &str = "egress_profiles";
+
+/// The egress profiles workflows can select by name
+#[derive(Default)]
+pub struct EgressRegistry {
+    profiles: RwLock<HashMap<String, Arc<EgressClients>>>,
+}
+
+impl EgressRegistry {
+    /// Every profile, by name
+    pub async fn get_profiles(&self) -> Vec<EgressProfile> {
+        let profiles = self.profiles.read().await;
+        let mut listed: Vec<EgressProfile> = profiles.values().map(|clients| clients.profile.clone()).collect();
+        listed.sort_by(|a, b| a.name.cmp(&b.name));
+        listed
+    }
+
+    /// Replace the profiles. Unchanged profiles keep their clients and connections.
+    pub async fn set_profiles(&self, profiles: Vec<EgressProfile>) -> AppResult<()> {
+        let mut current = self.profiles.write().await;
+        let mut updated = HashMap::new();
+        for profile in profiles {
+            if updated.contains_key(&profile.name) {
+                return Err(AppError::validation("egress_profile", format!("duplicate egress profile '{}'", profile.name)));
+            }
+            let clients = match current.get(&profile.name) {
+                Some(existing) if existing.profile == profile => existing.clone(),
+                _ => Arc::new(EgressClients::new(profile.clone())?),
+            };
+            updated.insert(profile.name, clients);
+        }
+
+        info!("Configured {} egress profiles", updated.len());
+        *current = updated;
+        Ok(())
+    }
+
+    /// The clients of the named profile. An unknown name is an error rather than the
+    /// default egress, since the workflow asked not to go out that way.
+    pub async fn resolve(&self, name: &str) -> AppResult<Arc<EgressClients>> {
+        self.profiles.read().await.get(name).cloned()
+            .ok_or_else(|| ApiError::invalid_configuration("egress_profile".to_string(), format!("Unknown egress profile '{}'", name)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, proxy_url: &str) -> EgressProfile {
+        EgressProfile { name: name.to_string(), proxy_url: proxy_url.to_string(), region: Some("eu-west".to_string()) }
+    }
+
+    #[tokio::test]
+    async fn test_workflow_requests_use_the_selected_profile() {
+        let registry = EgressRegistry::default();
+        registry.set_profiles(vec![profile("eu", "http://proxy.eu.example:3128")]).await.unwrap();
+        assert!(registry.resolve("apac").await.is_err());
+        assert!(registry.set_profiles(vec![profile("eu", "ftp://proxy.eu.example")]).await.is_err());
+        assert!(registry.set_profiles(vec![profile("eu", "http://a:1"), profile("eu", "http://b:1")]).await.is_err());
+
+        let eu = registry.resolve("eu").await.unwrap();
+        assert_eq!(current_profile(), None);
+        let scoped = egress_scope(Some(eu.clone()), async {
+            client_for(&reqwest::Client::new(), 5_000).unwrap();
+            current_profile()
+        }).await;
+        assert_eq!(scoped.as_deref(), Some("eu"));
+        assert_eq!(eu.clients.lock().len(), 1);
+        // The profile's clients are keyed by the caller's timeout
+        egress_scope(Some(eu.clone()), async { client_for(&reqwest::Client::new(), 30_000).unwrap() }).await;
+        assert!(eu.clients.lock().contains_key(&Duration::from_millis(30_000)));
+
+        // Reconfiguring with the same profile keeps its clients
+        registry.set_profiles(vec![profile("eu", "http://proxy.eu.example:3128")]).await.unwrap();
+        assert!(Arc::ptr_eq(&eu, &registry.resolve("eu").await.unwrap()));
+    }
+}
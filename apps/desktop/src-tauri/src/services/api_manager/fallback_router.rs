@@ -0,0 +1,664 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::api_key::ServiceProvider;
+use crate::models::research_workflow::{SearchRecency, SearchTimeRange};
+use super::provider_adapter;
+use super::response_recorder;
+use super::response_schema::{self, NormalizedPayload, SearchHit};
+use super::service_integration::{ServiceRequest, ServiceResponse};
+
+/// Step type served by the web search providers
+pub const WEB_SEARCH: &str = "web_search";
+
+/// Circuit breaker thresholds shared by every provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects requests before letting a probe through
+    pub open_duration_seconds: i64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration_seconds: 60,
+        }
+    }
+}
+
+/// How the providers of a chain serve a step
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ChainMode {
+    /// Try one provider at a time, in chain order
+    #[default]
+    Sequential,
+    /// Send the request to several providers at once and keep the first acceptable response
+    Race(RaceConfig),
+}
+
+/// Settings for racing a chain's providers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaceConfig {
+    /// Requests in flight at once; every one past the first is an extra request
+    pub width: usize,
+    /// Lowest `response_quality` a response needs to win the race
+    pub min_quality: f64,
+    /// Estimated spend across every request of one race; providers that would
+    /// take it over are left out
+    pub max_total_cost: Option<f64>,
+}
+
+impl Default for RaceConfig {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            min_quality: 0.5,
+            max_total_cost: Some(0.03),
+        }
+    }
+}
+
+impl RaceConfig {
+    /// The cap a request costing `cost` would break after `spent` has gone on the race so far
+    pub fn exceeds_total_cost(&self, spent: f64, cost: f64) -> Option<AttemptOutcome> {
+        let ceiling = self.max_total_cost?;
+        (spent + cost > ceiling).then(|| AttemptOutcome::OverCostCeiling { cost: spent + cost, ceiling })
+    }
+}
+
+/// Ordered providers to try for one step type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackChain {
+    pub providers: Vec<ServiceProvider>,
+    /// Providers whose estimated cost per request is above this are skipped
+    pub max_cost_per_request: Option<f64>,
+    #[serde(default)]
+    pub mode: ChainMode,
+}
+
+/// Fallback chains per step type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackConfig {
+    pub chains: HashMap<String, FallbackChain>,
+    /// Estimated cost in USD of a single request to each provider
+    pub provider_costs: HashMap<ServiceProvider, f64>,
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for FallbackConfig {
+    fn default() -> Self {
+        let mut chains = HashMap::new();
+        chains.insert(WEB_SEARCH.to_string(), FallbackChain {
+            providers: vec![ServiceProvider::SerpApi, ServiceProvider::Tavily, ServiceProvider::Exa],
+            max_cost_per_request: Some(0.02),
+            mode: ChainMode::Sequential,
+        });
+
+        let provider_costs = HashMap::from([
+            (ServiceProvider::SerpApi, 0.01),
+            (ServiceProvider::Tavily, 0.008),
+            (ServiceProvider::Exa, 0.005),
+            (ServiceProvider::Jina, 0.0002),
+            (ServiceProvider::Firecrawl, 0.003),
+            (ServiceProvider::OpenRouter, 0.01),
+        ]);
+
+        Self {
+            chains,
+            provider_costs,
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+/// Circuit state of a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CircuitState {
+    Closed,
+    Open { until: DateTime<Utc> },
+    /// Cooldown has passed; a single request goes through as a probe
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    state: CircuitState,
+    last_failure_at: Option<DateTime<Utc>>,
+    /// When the half-open probe was let through; other requests wait for its outcome
+    probe_started_at: Option<DateTime<Utc>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self { consecutive_failures: 0, state: CircuitState::Closed, last_failure_at: None, probe_started_at: None }
+    }
+}
+
+/// What a circuit breaker guards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerKind {
+    Provider,
+}
+
+/// A circuit breaker as operators see it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    /// Identifies the breaker to reset, such as `provider:serpapi`
+    pub id: String,
+    pub kind: BreakerKind,
+    pub name: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    /// Seconds until an open circuit lets a probe through
+    pub next_probe_in_seconds: Option<i64>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+const PROVIDER_BREAKER_PREFIX: &str = "provider:";
+
+/// The id of `provider`'s breaker
+pub fn provider_breaker_id(provider: ServiceProvider) -> String {
+    let name = serde_json::to_value(provider).ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", provider).to_lowercase());
+    format!("{}{}", PROVIDER_BREAKER_PREFIX, name)
+}
+
+/// The provider whose breaker `id` is
+pub fn parse_provider_breaker_id(id: &str) -> Option<ServiceProvider> {
+    let name = id.strip_prefix(PROVIDER_BREAKER_PREFIX)?;
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Why a provider in the chain did not serve the step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AttemptOutcome {
+    Served,
+    CircuitOpen,
+    OverCostCeiling { cost: f64, ceiling: f64 },
+    RateLimited(String),
+    NoAvailableKey,
+    Unsupported,
+    Failed(String),
+    /// Answered, but below the race's quality threshold
+    BelowQuality { quality: f64, threshold: f64 },
+    /// Still in flight when another provider won the race
+    Cancelled,
+}
+
+/// One provider tried while serving a step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderAttempt {
+    pub provider: ServiceProvider,
+    pub outcome: AttemptOutcome,
+}
+
+/// Response from whichever provider in the chain served the step
+#[derive(Debug, Clone)]
+pub struct FallbackResponse {
+    pub served_by: ServiceProvider,
+    pub response: ServiceResponse,
+    pub attempts: Vec<ProviderAttempt>,
+}
+
+/// Per-step-type provider chains guarded by per-provider circuit breakers
+pub struct FallbackRouter {
+    config: RwLock<FallbackConfig>,
+    breakers: RwLock<HashMap<ServiceProvider, CircuitBreaker>>,
+}
+
+impl FallbackRouter {
+    pub fn new(config: FallbackConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            breakers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_config(&self) -> FallbackConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn update_config(&self, config: FallbackConfig) {
+        info!("Updating provider fallback chains");
+        *self.config.write().await = config;
+    }
+
+    /// Providers to try for `step_type`, falling back to `primary` alone when no chain is configured
+    pub async fn chain_for(&self, step_type: &str, primary: ServiceProvider) -> Vec<ServiceProvider> {
+        let config = self.config.read().await;
+        match config.chains.get(step_type) {
+            Some(chain) if !chain.providers.is_empty() => chain.providers.clone(),
+            _ => vec![primary],
+        }
+    }
+
+    pub async fn mode_for(&self, step_type: &str) -> ChainMode {
+        let config = self.config.read().await;
+        config.chains.get(step_type).map(|chain| chain.mode.clone()).unwrap_or_default()
+    }
+
+    /// Estimated cost of one request to `provider`, zero when none is configured
+    pub async fn provider_cost(&self, provider: ServiceProvider) -> f64 {
+        self.config.read().await.provider_costs.get(&provider).copied().unwrap_or(0.0)
+    }
+
+    /// The cost ceiling a provider would break, if any
+    pub async fn exceeds_cost_ceiling(&self, step_type: &str, provider: ServiceProvider) -> Option<AttemptOutcome> {
+        let config = self.config.read().await;
+        let ceiling = config.chains.get(step_type)?.max_cost_per_request?;
+        let cost = *config.provider_costs.get(&provider)?;
+        (cost > ceiling).then(|| AttemptOutcome::OverCostCeiling { cost, ceiling })
+    }
+
+    pub async fn circuit_state(&self, provider: ServiceProvider, now: DateTime<Utc>) -> CircuitState {
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(provider).or_default();
+        if let CircuitState::Open { until } = breaker.state {
+            if until <= now {
+                breaker.state = CircuitState::HalfOpen;
+            }
+        }
+        breaker.state.clone()
+    }
+
+    /// Whether a request to `provider` may go ahead. A half-open circuit lets one
+    /// request through as its probe and holds the rest until that probe is
+    /// recorded; a probe that never reports back is replaced after another
+    /// cooldown. Mocked calls never reach the provider, so its circuit neither
+    /// stops them nor hears how they went.
+    pub async fn allow_request(&self, provider: ServiceProvider, now: DateTime<Utc>) -> bool {
+        if response_recorder::is_mocked() {
+            return true;
+        }
+        let open_duration = Duration::seconds(self.config.read().await.circuit_breaker.open_duration_seconds);
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(provider).or_default();
+        if let CircuitState::Open { until } = breaker.state {
+            if until <= now {
+                breaker.state = CircuitState::HalfOpen;
+            }
+        }
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open { .. } => false,
+            CircuitState::HalfOpen => {
+                if breaker.probe_started_at.map_or(false, |started| now < started + open_duration) {
+                    return false;
+                }
+                breaker.probe_started_at = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Let another request probe `provider` when the one let through said
+    /// nothing about its health, such as a missing key or a cancelled race entry
+    pub async fn release_probe(&self, provider: ServiceProvider) {
+        if let Some(breaker) = self.breakers.write().await.get_mut(&provider) {
+            breaker.probe_started_at = None;
+        }
+    }
+
+    pub async fn record_success(&self, provider: ServiceProvider) {
+        if response_recorder::is_mocked() {
+            return;
+        }
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(provider).or_default();
+        if breaker.state != CircuitState::Closed {
+            info!("Circuit closed for provider {:?}", provider);
+        }
+        *breaker = CircuitBreaker::default();
+    }
+
+    pub async fn record_failure(&self, provider: ServiceProvider, now: DateTime<Utc>) {
+        if response_recorder::is_mocked() {
+            return;
+        }
+        let config = self.config.read().await.circuit_breaker.clone();
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(provider).or_default();
+        breaker.consecutive_failures += 1;
+        breaker.last_failure_at = Some(now);
+
+        // A failed probe reopens straight away
+        if breaker.state == CircuitState::HalfOpen || breaker.consecutive_failures >= config.failure_threshold {
+            let until = now + Duration::seconds(config.open_duration_seconds);
+            warn!("Circuit opened for provider {:?} until {}", provider, until);
+            breaker.state = CircuitState::Open { until };
+            breaker.probe_started_at = None;
+        }
+    }
+
+    /// Every provider breaker that has seen a request, with its state at `now`
+    pub async fn breaker_statuses(&self, now: DateTime<Utc>) -> Vec<CircuitBreakerStatus> {
+        let failure_threshold = self.config.read().await.circuit_breaker.failure_threshold;
+        let mut breakers = self.breakers.write().await;
+        let mut statuses: Vec<_> = breakers.iter_mut()
+            .map(|(provider, breaker)| {
+                if let CircuitState::Open { until } = breaker.state {
+                    if until <= now {
+                        breaker.state = CircuitState::HalfOpen;
+                    }
+                }
+                CircuitBreakerStatus {
+                    id: provider_breaker_id(*provider),
+                    kind: BreakerKind::Provider,
+                    name: provider.display_name().to_string(),
+                    state: breaker.state.clone(),
+                    consecutive_failures: breaker.consecutive_failures,
+                    failure_threshold,
+                    next_probe_in_seconds: match breaker.state {
+                        CircuitState::Open { until } => Some((until - now).num_seconds().max(0)),
+                        _ => None,
+                    },
+                    last_failure_at: breaker.last_failure_at,
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.id.cmp(&b.id));
+        statuses
+    }
+
+    /// Close `provider`'s circuit and forget its failures, whatever state it was in
+    pub async fn reset_breaker(&self, provider: ServiceProvider) {
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(provider).or_default();
+        warn!("Circuit for provider {:?} reset by hand from {:?}", provider, breaker.state);
+        *breaker = CircuitBreaker::default();
+    }
+}
+
+/// How usable a response is, from 0.0 to 1.0: the share of search hits with a link and
+/// snippet, or whether a completion, page or link list came back non-empty
+pub fn response_quality(response: &ServiceResponse) -> f64 {
+    let non_empty = |present: bool| if present { 1.0 } else { 0.0 };
+    match &response.normalized {
+        Some(NormalizedPayload::SearchResults { hits }) if hits.is_empty() => 0.0,
+        Some(NormalizedPayload::SearchResults { hits }) => {
+            let usable = hits.iter()
+                .filter(|hit| !hit.link.is_empty() && !hit.snippet.trim().is_empty())
+                .count();
+            usable as f64 / hits.len() as f64
+        }
+        Some(NormalizedPayload::Completion { content, .. }) => non_empty(!content.trim().is_empty()),
+        Some(NormalizedPayload::ScrapedPage { markdown, .. }) => non_empty(!markdown.trim().is_empty()),
+        Some(NormalizedPayload::Links { links }) => non_empty(!links.is_empty()),
+        Some(NormalizedPayload::Embeddings { vectors, .. }) => non_empty(!vectors.is_empty()),
+        None => non_empty(!response.body.trim().is_empty()),
+    }
+}
+
+/// Build a web search request in the shape `provider` expects
+pub fn web_search_request(provider: ServiceProvider, query: &str, num_results: u32) -> Option<ServiceRequest> {
+    web_search_page_request(provider, query, num_results, 0)
+}
+
+/// Convert a search response into SerpApi's `organic_results` shape so
+/// downstream steps do not depend on which provider served the search.
+/// Bodies that fail the provider's schema are returned unchanged.
+pub fn normalize_search_results(provider: ServiceProvider, body: serde_json::Value) -> serde_json::Value {
+    match response_schema::validate_response(provider, "/search", &body.to_string()) {
+        Ok(Some(NormalizedPayload::SearchResults { hits })) => NormalizedPayload::search_results_json(&hits),
+        _ => body,
+    }
+}
+
+/// Search hits in `response`, normalizing its body when it was stored before
+/// responses carried a normalized payload, as older recordings were
+pub fn search_hits(response: &ServiceResponse) -> Vec<SearchHit> {
+    match &response.normalized {
+        Some(NormalizedPayload::SearchResults { hits }) => hits.clone(),
+        Some(_) => Vec::new(),
+        None => match response_schema::validate_response(response.service, "/search", &response.body) {
+            Ok(Some(NormalizedPayload::SearchResults { hits })) => hits,
+            _ => Vec::new(),
+        },
+    }
+}
+
+/// Build a request for the web search results after the first `offset`; `None` when
+/// `provider` cannot search the web, or cannot skip results and `offset` is not zero
+pub fn web_search_page_request(provider: ServiceProvider, query: &str, num_results: u32, offset: u32) -> Option<ServiceRequest> {
+    if let ServiceProvider::Custom(custom) = provider {
+        let adapter = provider_adapter::adapter_for(custom)?;
+        let paginates = adapter.page_support().map_or(false, |support| support.paginates);
+        if offset > 0 && !paginates {
+            return None;
+        }
+        return adapter.search_request(provider, query, num_results, offset);
+    }
+    if offset > 0 && provider != ServiceProvider::SerpApi {
+        return None;
+    }
+    let mut request = ServiceRequest {
+        request_id: Uuid::new_v4(),
+        service: provider,
+        endpoint: "/search".to_string(),
+        method: "POST".to_string(),
+        headers: HashMap::new(),
+        body: None,
+        timeout_ms: 15000,
+        retry_count: 0,
+        metadata: HashMap::new(),
+    };
+
+    match provider {
+        ServiceProvider::SerpApi => {
+            request.method = "GET".to_string();
+            let mut query_params = format!("q={}&engine=google&num={}", urlencoding::encode(query), num_results);
+            if offset > 0 {
+                query_params.push_str(&format!("&start={}", offset));
+            }
+            request.metadata.insert("query_params".to_string(), query_params);
+        }
+        ServiceProvider::Tavily => {
+            request.timeout_ms = 25000;
+            request.body = Some(serde_json::json!({
+                "query": query,
+                "search_depth": "basic",
+                "max_results": num_results,
+            }).to_string());
+        }
+        ServiceProvider::Exa => {
+            request.timeout_ms = 20000;
+            request.body = Some(serde_json::json!({
+                "query": query,
+                "num_results": num_results,
+                "use_autoprompt": true,
+            }).to_string());
+        }
+        _ => return None,
+    }
+
+    Some(request)
+}
+
+/// Ask `request`'s provider for sources no older than `recency` allows, in the provider's
+/// own terms: Google's `tbs`, Tavily's `time_range` or `start_date`, Exa's
+/// `startPublishedDate`. Providers may ignore the hint, so results still need filtering.
+pub fn restrict_web_search(request: &mut ServiceRequest, recency: &SearchRecency, now: DateTime<Utc>) {
+    let Some(cutoff) = recency.cutoff(now) else { return };
+    let relative = recency.relative_range(now);
+
+    match request.service {
+        ServiceProvider::SerpApi => {
+            let tbs = match relative {
+                Some(SearchTimeRange::PastDay) => "qdr:d".to_string(),
+                Some(SearchTimeRange::PastWeek) => "qdr:w".to_string(),
+                Some(SearchTimeRange::PastMonth) => "qdr:m".to_string(),
+                Some(SearchTimeRange::PastYear) => "qdr:y".to_string(),
+                _ => format!("cdr:1,cd_min:{}", cutoff.format("%m/%d/%Y")),
+            };
+            if let Some(query_params) = request.metadata.get_mut("query_params") {
+                query_params.push_str(&format!("&tbs={}", urlencoding::encode(&tbs)));
+            }
+        }
+        ServiceProvider::Tavily | ServiceProvider::Exa => {
+            let Some(mut body) = request.body.as_deref().and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok()) else {
+                return;
+            };
+            let hint = match (request.service, relative) {
+                (ServiceProvider::Tavily, Some(SearchTimeRange::PastDay)) => ("time_range", serde_json::json!("day")),
+                (ServiceProvider::Tavily, Some(SearchTimeRange::PastWeek)) => ("time_range", serde_json::json!("week")),
+                (ServiceProvider::Tavily, Some(SearchTimeRange::PastMonth)) => ("time_range", serde_json::json!("month")),
+                (ServiceProvider::Tavily, Some(SearchTimeRange::PastYear)) => ("time_range", serde_json::json!("year")),
+                (ServiceProvider::Tavily, _) => ("start_date", serde_json::json!(cutoff.format("%Y-%m-%d").to_string())),
+                _ => ("startPublishedDate", serde_json::json!(cutoff.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))),
+            };
+            body[hint.0] = hint.1;
+            request.body = Some(body.to_string());
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_circuit_opens_and_probes_after_cooldown() {
+        let router = FallbackRouter::new(FallbackConfig {
+            circuit_breaker: CircuitBreakerConfig { failure_threshold: 2, open_duration_seconds: 30 },
+            ..Default::default()
+        });
+        let now = Utc::now();
+
+        router.record_failure(ServiceProvider::SerpApi, now).await;
+        assert!(router.allow_request(ServiceProvider::SerpApi, now).await);
+        router.record_failure(ServiceProvider::SerpApi, now).await;
+        assert!(!router.allow_request(ServiceProvider::SerpApi, now).await);
+        assert!(router.allow_request(ServiceProvider::Tavily, now).await);
+
+        let later = now + Duration::seconds(30);
+        assert_eq!(router.circuit_state(ServiceProvider::SerpApi, later).await, CircuitState::HalfOpen);
+
+        // Only one probe goes through while half-open
+        assert!(router.allow_request(ServiceProvider::SerpApi, later).await);
+        assert!(!router.allow_request(ServiceProvider::SerpApi, later).await);
+
+        // A failed probe reopens immediately
+        router.record_failure(ServiceProvider::SerpApi, later).await;
+        assert!(!router.allow_request(ServiceProvider::SerpApi, later).await);
+
+        router.record_success(ServiceProvider::SerpApi).await;
+        assert_eq!(router.circuit_state(ServiceProvider::SerpApi, later).await, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_statuses_show_time_to_probe_and_reset_by_id() {
+        let router = FallbackRouter::new(FallbackConfig {
+            circuit_breaker: CircuitBreakerConfig { failure_threshold: 1, open_duration_seconds: 30 },
+            ..Default::default()
+        });
+        let now = Utc::now();
+        router.record_failure(ServiceProvider::Tavily, now).await;
+        router.record_success(ServiceProvider::Exa).await;
+
+        let statuses = router.breaker_statuses(now + Duration::seconds(10)).await;
+        assert_eq!(statuses.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["provider:exa", "provider:tavily"]);
+        assert_eq!(statuses[0].state, CircuitState::Closed);
+        assert_eq!(statuses[1].consecutive_failures, 1);
+        assert_eq!(statuses[1].next_probe_in_seconds, Some(20));
+        assert_eq!(statuses[1].last_failure_at, Some(now));
+
+        let provider = parse_provider_breaker_id(&statuses[1].id).unwrap();
+        assert_eq!(provider, ServiceProvider::Tavily);
+        assert!(parse_provider_breaker_id("partner:tavily").is_none());
+        router.reset_breaker(provider).await;
+        assert!(router.allow_request(ServiceProvider::Tavily, now).await);
+        assert_eq!(router.breaker_statuses(now).await[1].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cost_ceiling_skips_expensive_providers() {
+        let mut config = FallbackConfig::default();
+        config.chains.get_mut(WEB_SEARCH).unwrap().max_cost_per_request = Some(0.009);
+        let router = FallbackRouter::new(config);
+
+        assert!(router.exceeds_cost_ceiling(WEB_SEARCH, ServiceProvider::SerpApi).await.is_some());
+        assert!(router.exceeds_cost_ceiling(WEB_SEARCH, ServiceProvider::Tavily).await.is_none());
+        assert_eq!(
+            router.chain_for("content_extraction", ServiceProvider::Jina).await,
+            vec![ServiceProvider::Jina]
+        );
+    }
+
+    #[test]
+    fn test_race_quality_and_total_cost_cap() {
+        let response = |hits: Vec<SearchHit>| ServiceResponse {
+            request_id: Uuid::new_v4(),
+            service: ServiceProvider::Tavily,
+            status_code: 200,
+            headers: HashMap::new(),
+            body: "{}".to_string(),
+            response_time_ms: 120,
+            success: true,
+            error_message: None,
+            metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: Some(NormalizedPayload::SearchResults { hits }),
+        };
+        let hit = |snippet: &str| SearchHit {
+            title: "Result".to_string(),
+            link: "https://example.org".to_string(),
+            snippet: snippet.to_string(),
+            published: None,
+        };
+
+        assert_eq!(response_quality(&response(vec![])), 0.0);
+        assert_eq!(response_quality(&response(vec![hit("A summary"), hit(" ")])), 0.5);
+
+        let race = RaceConfig { max_total_cost: Some(0.015), ..Default::default() };
+        assert!(race.exceeds_total_cost(0.0, 0.01).is_none());
+        assert!(matches!(
+            race.exceeds_total_cost(0.01, 0.008),
+            Some(AttemptOutcome::OverCostCeiling { ceiling, .. }) if ceiling == 0.015
+        ));
+
+        let chain: FallbackChain = serde_json::from_str(
+            r#"{"providers": ["tavily", "exa"], "max_cost_per_request": null, "mode": {"mode": "race", "width": 3}}"#
+        ).unwrap();
+        assert_eq!(chain.mode, ChainMode::Race(RaceConfig { width: 3, ..Default::default() }));
+    }
+
+    #[test]
+    fn test_search_results_normalized_from_stored_body() {
+        let body = serde_json::json!({ "results": [{ "title": "A", "url": "https://a.example", "content": "a" }] });
+        let normalized = normalize_search_results(ServiceProvider::Tavily, body.clone());
+        assert_eq!(normalized["organic_results"][0]["link"], "https://a.example");
+
+        // A response recorded before normalization still yields its hits
+        let response = ServiceResponse {
+            request_id: Uuid::new_v4(),
+            service: ServiceProvider::Tavily,
+            status_code: 200,
+            headers: HashMap::new(),
+            body: body.to_string(),
+            response_time_ms: 120,
+            success: true,
+            error_message: None,
+            metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: None,
+        };
+        assert_eq!(search_hits(&response).len(), 1);
+
+        let malformed = serde_json::json!({ "unexpected": true });
+        assert_eq!(normalize_search_results(ServiceProvider::Exa, malformed.clone()), malformed);
+    }
+}
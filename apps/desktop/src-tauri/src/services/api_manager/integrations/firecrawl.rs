@@ -5,7 +5,10 @@ use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
+use crate::services::api_manager::egress;
+use crate::utils::inject_trace_context;
 use crate::services::api_manager::service_integration::{
     ServiceIntegration, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig
 };
@@ -27,15 +30,19 @@ pub struct FirecrawlIntegration {
 impl FirecrawlIntegration {
     pub fn new() -> Self {
         let config = ServiceConfig::default_for_service(ServiceProvider::Firecrawl);
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(config.default_timeout_ms as u64))
+        let http_client = egress::client_builder(config.default_timeout_ms)
             .build()
             .expect("Failed to create HTTP client");
 
         Self { config, http_client }
     }
 
-    async fn test_api_key_internal(&self, api_key: &ApiKey) -> AppResult<String> {
+    /// The client for the running workflow's egress profile, or this integration's own
+    fn client(&self) -> AppResult<reqwest::Client> {
+        egress::client_for(&self.http_client, self.config.default_timeout_ms)
+    }
+
+    async fn test_api_key_internal(&self, api_key: &SecretString) -> AppResult<String> {
         debug!("Testing Firecrawl API key");
 
         let request = FirecrawlScrapeRequest {
@@ -45,9 +52,9 @@ impl FirecrawlIntegration {
         };
 
         let url = format!("{}/scrape", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .post(&url)
-            .header("Authorization", format!("Bearer {}", api_key.encrypted_key))
+            .bearer_auth(api_key.expose())
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -69,7 +76,7 @@ impl ServiceIntegration for FirecrawlIntegration {
         ServiceProvider::Firecrawl
     }
 
-    async fn make_request(&self, mut request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    async fn make_request(&self, mut request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making Firecrawl request: {}", request.endpoint);
 
         let start_time = std::time::Instant::now();
@@ -84,18 +91,19 @@ impl ServiceIntegration for FirecrawlIntegration {
             error_message: None,
             metadata: HashMap::new(),
             timestamp: Utc::now(),
+            normalized: None,
         };
 
         self.transform_request(&mut request).await?;
 
         let url = format!("{}{}", self.config.base_url, request.endpoint);
         let mut req_builder = match request.method.as_str() {
-            "GET" => self.http_client.get(&url),
-            "POST" => self.http_client.post(&url),
+            "GET" => self.client()?.get(&url),
+            "POST" => self.client()?.post(&url),
             _ => return Err(ApiError::invalid_configuration("method".to_string(), "Unsupported HTTP method".to_string())),
         };
 
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key.encrypted_key));
+        req_builder = req_builder.bearer_auth(api_key.expose());
         req_builder = req_builder.header("Content-Type", "application/json");
 
         for (key, value) in &request.headers {
@@ -106,7 +114,7 @@ impl ServiceIntegration for FirecrawlIntegration {
             req_builder = req_builder.body(body.clone());
         }
 
-        match req_builder.send().await {
+        match inject_trace_context(req_builder).send().await {
             Ok(http_response) => {
                 response.status_code = http_response.status().as_u16();
                 response.success = http_response.status().is_success();
@@ -138,7 +146,7 @@ impl ServiceIntegration for FirecrawlIntegration {
         Ok(response)
     }
 
-    async fn health_check(&self, api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(ServiceHealth::Healthy),
             Err(_) => Ok(ServiceHealth::Unhealthy),
@@ -151,14 +159,13 @@ impl ServiceIntegration for FirecrawlIntegration {
 
     async fn update_config(&mut self, config: ServiceConfig) -> AppResult<()> {
         self.config = config;
-        self.http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(self.config.default_timeout_ms as u64))
+        self.http_client = egress::client_builder(self.config.default_timeout_ms)
             .build()
             .map_err(|e| ApiError::invalid_configuration("timeout".to_string(), e.to_string()))?;
         Ok(())
     }
 
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool> {
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
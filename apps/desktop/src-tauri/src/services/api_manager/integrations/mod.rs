@@ -14,7 +14,36 @@ pub use exa::ExaIntegration;
 
 use crate::error::AppResult;
 use crate::models::api_key::ServiceProvider;
-use crate::services::api_manager::service_integration::{ServiceIntegration, ServiceIntegrationManager};
+use crate::services::api_manager::provider_adapter::{self, AdapterIntegration};
+use crate::services::api_manager::service_integration::{MockServiceIntegration, ServiceIntegration, ServiceIntegrationManager};
+use crate::services::security::SecretString;
+
+/// Attach a JSON body for services that take the API key in the body, then
+/// scrub the key from the intermediate value once the request owns its copy
+pub(crate) fn json_with_api_key(
+    builder: reqwest::RequestBuilder,
+    body: serde_json::Value,
+    api_key: &SecretString,
+) -> reqwest::RequestBuilder {
+    json_with_api_key_field(builder, body, "api_key", api_key)
+}
+
+/// [`json_with_api_key`] for services that name the body field something else
+pub(crate) fn json_with_api_key_field(
+    builder: reqwest::RequestBuilder,
+    mut body: serde_json::Value,
+    field: &str,
+    api_key: &SecretString,
+) -> reqwest::RequestBuilder {
+    use zeroize::Zeroize;
+
+    body[field] = serde_json::Value::String(api_key.expose().to_string());
+    let builder = builder.json(&body);
+    if let Some(serde_json::Value::String(key)) = body.get_mut(field) {
+        key.zeroize();
+    }
+    builder
+}
 
 /// Create all service integrations and register them
 pub async fn create_all_integrations() -> AppResult<ServiceIntegrationManager> {
@@ -44,6 +73,11 @@ pub async fn create_all_integrations() -> AppResult<ServiceIntegrationManager> {
     let exa = Box::new(ExaIntegration::new());
     manager.register_integration(exa).await?;
 
+    // Register the integrations of custom provider adapters
+    for (service, adapter) in provider_adapter::registered_adapters() {
+        manager.register_integration(Box::new(AdapterIntegration::new(service, adapter))).await?;
+    }
+
     Ok(manager)
 }
 
@@ -56,5 +90,9 @@ pub fn create_integration_for_service(service: ServiceProvider) -> Box<dyn Servi
         ServiceProvider::Firecrawl => Box::new(FirecrawlIntegration::new()),
         ServiceProvider::Tavily => Box::new(TavilyIntegration::new()),
         ServiceProvider::Exa => Box::new(ExaIntegration::new()),
+        ServiceProvider::Custom(provider) => match provider_adapter::adapter_for(provider) {
+            Some(adapter) => Box::new(AdapterIntegration::new(service, adapter)),
+            None => Box::new(MockServiceIntegration::new(service)),
+        },
     }
 }
@@ -6,7 +6,10 @@ use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
+use crate::services::api_manager::egress;
+use crate::utils::inject_trace_context;
 use crate::services::api_manager::service_integration::{
     ServiceIntegration, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig
 };
@@ -134,8 +137,7 @@ impl OpenRouterIntegration {
     /// Create a new OpenRouter integration
     pub fn new() -> Self {
         let config = ServiceConfig::default_for_service(ServiceProvider::OpenRouter);
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(config.default_timeout_ms as u64))
+        let http_client = egress::client_builder(config.default_timeout_ms)
             .build()
             .expect("Failed to create HTTP client");
 
@@ -145,6 +147,11 @@ impl OpenRouterIntegration {
         }
     }
 
+    /// The client for the running workflow's egress profile, or this integration's own
+    fn client(&self) -> AppResult<reqwest::Client> {
+        egress::client_for(&self.http_client, self.config.default_timeout_ms)
+    }
+
     /// Create a chat completion request
     pub fn create_chat_request(
         &self,
@@ -164,13 +171,13 @@ impl OpenRouterIntegration {
     }
 
     /// Get available models with detailed information
-    pub async fn get_models(&self, api_key: &ApiKey) -> AppResult<Vec<String>> {
+    pub async fn get_models(&self, api_key: &SecretString) -> AppResult<Vec<String>> {
         debug!("Getting available models from OpenRouter");
 
         let url = format!("{}/models", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .get(&url)
-            .header("Authorization", format!("Bearer {}", api_key.encrypted_key))
+            .bearer_auth(api_key.expose())
             .header("Content-Type", "application/json")
             .send()
             .await
@@ -196,13 +203,13 @@ impl OpenRouterIntegration {
     }
 
     /// Get detailed model information
-    pub async fn get_models_detailed(&self, api_key: &ApiKey) -> AppResult<Vec<OpenRouterModel>> {
+    pub async fn get_models_detailed(&self, api_key: &SecretString) -> AppResult<Vec<OpenRouterModel>> {
         debug!("Getting detailed model information from OpenRouter");
 
         let url = format!("{}/models", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .get(&url)
-            .header("Authorization", format!("Bearer {}", api_key.encrypted_key))
+            .bearer_auth(api_key.expose())
             .header("Content-Type", "application/json")
             .send()
             .await
@@ -228,7 +235,7 @@ impl OpenRouterIntegration {
     }
 
     /// Get latest models (Claude 3.5 Sonnet, GPT-4 Turbo, etc.)
-    pub async fn get_latest_models(&self, api_key: &ApiKey) -> AppResult<Vec<OpenRouterModel>> {
+    pub async fn get_latest_models(&self, api_key: &SecretString) -> AppResult<Vec<OpenRouterModel>> {
         let all_models = self.get_models_detailed(api_key).await?;
 
         // Filter for latest high-performance models
@@ -251,13 +258,13 @@ impl OpenRouterIntegration {
     }
 
     /// Make a chat completion request
-    pub async fn chat_completion(&self, api_key: &ApiKey, request: OpenRouterRequest) -> AppResult<OpenRouterResponse> {
+    pub async fn chat_completion(&self, api_key: &SecretString, request: OpenRouterRequest) -> AppResult<OpenRouterResponse> {
         debug!("Making chat completion request to OpenRouter");
 
         let url = format!("{}/chat/completions", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .post(&url)
-            .header("Authorization", format!("Bearer {}", api_key.encrypted_key))
+            .bearer_auth(api_key.expose())
             .header("Content-Type", "application/json")
             .header("HTTP-Referer", "https://github.com/your-repo") // Required by OpenRouter
             .header("X-Title", "Free Deep Research System") // Required by OpenRouter
@@ -280,7 +287,7 @@ impl OpenRouterIntegration {
     }
 
     /// Test the API key with a simple request
-    async fn test_api_key_internal(&self, api_key: &ApiKey) -> AppResult<String> {
+    async fn test_api_key_internal(&self, api_key: &SecretString) -> AppResult<String> {
         debug!("Testing OpenRouter API key");
 
         // Try to get models list as a simple test
@@ -303,7 +310,7 @@ impl ServiceIntegration for OpenRouterIntegration {
         ServiceProvider::OpenRouter
     }
 
-    async fn make_request(&self, mut request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    async fn make_request(&self, mut request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making OpenRouter request: {}", request.endpoint);
 
         let start_time = std::time::Instant::now();
@@ -318,6 +325,7 @@ impl ServiceIntegration for OpenRouterIntegration {
             error_message: None,
             metadata: HashMap::new(),
             timestamp: Utc::now(),
+            normalized: None,
         };
 
         // Transform request for OpenRouter specifics
@@ -325,15 +333,15 @@ impl ServiceIntegration for OpenRouterIntegration {
 
         let url = format!("{}{}", self.config.base_url, request.endpoint);
         let mut req_builder = match request.method.as_str() {
-            "GET" => self.http_client.get(&url),
-            "POST" => self.http_client.post(&url),
-            "PUT" => self.http_client.put(&url),
-            "DELETE" => self.http_client.delete(&url),
+            "GET" => self.client()?.get(&url),
+            "POST" => self.client()?.post(&url),
+            "PUT" => self.client()?.put(&url),
+            "DELETE" => self.client()?.delete(&url),
             _ => return Err(ApiError::invalid_configuration("method".to_string(), "Unsupported HTTP method".to_string())),
         };
 
         // Add headers
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key.encrypted_key));
+        req_builder = req_builder.bearer_auth(api_key.expose());
         req_builder = req_builder.header("Content-Type", "application/json");
         req_builder = req_builder.header("HTTP-Referer", "https://github.com/your-repo");
         req_builder = req_builder.header("X-Title", "Free Deep Research System");
@@ -348,7 +356,7 @@ impl ServiceIntegration for OpenRouterIntegration {
         }
 
         // Make the request
-        match req_builder.send().await {
+        match inject_trace_context(req_builder).send().await {
             Ok(http_response) => {
                 response.status_code = http_response.status().as_u16();
                 response.success = http_response.status().is_success();
@@ -382,7 +390,7 @@ impl ServiceIntegration for OpenRouterIntegration {
         Ok(response)
     }
 
-    async fn health_check(&self, api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth> {
         debug!("Performing OpenRouter health check");
 
         match self.test_api_key_internal(api_key).await {
@@ -403,15 +411,14 @@ impl ServiceIntegration for OpenRouterIntegration {
         self.config = config;
         
         // Recreate HTTP client with new timeout
-        self.http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(self.config.default_timeout_ms as u64))
+        self.http_client = egress::client_builder(self.config.default_timeout_ms)
             .build()
             .map_err(|e| ApiError::invalid_configuration("timeout".to_string(), e.to_string()))?;
 
         Ok(())
     }
 
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool> {
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
@@ -5,7 +5,10 @@ use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
+use crate::services::api_manager::egress;
+use crate::utils::inject_trace_context;
 use crate::services::api_manager::service_integration::{
     ServiceIntegration, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig
 };
@@ -61,8 +64,7 @@ impl SerpApiIntegration {
     /// Create a new SerpApi integration
     pub fn new() -> Self {
         let config = ServiceConfig::default_for_service(ServiceProvider::SerpApi);
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(config.default_timeout_ms as u64))
+        let http_client = egress::client_builder(config.default_timeout_ms)
             .build()
             .expect("Failed to create HTTP client");
 
@@ -72,12 +74,16 @@ impl SerpApiIntegration {
         }
     }
 
+    /// The client for the running workflow's egress profile, or this integration's own
+    fn client(&self) -> AppResult<reqwest::Client> {
+        egress::client_for(&self.http_client, self.config.default_timeout_ms)
+    }
+
     /// Perform a Google search
-    pub async fn google_search(&self, api_key: &ApiKey, params: SerpApiSearchParams) -> AppResult<SerpApiResponse> {
+    pub async fn google_search(&self, api_key: &SecretString, params: SerpApiSearchParams) -> AppResult<SerpApiResponse> {
         debug!("Performing Google search via SerpApi: {}", params.q);
 
         let mut query_params = vec![
-            ("api_key", api_key.encrypted_key.clone()),
             ("q", params.q),
             ("engine", params.engine.unwrap_or_else(|| "google".to_string())),
         ];
@@ -109,8 +115,9 @@ impl SerpApiIntegration {
         }
 
         let url = format!("{}/search", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .get(&url)
+            .query(&[("api_key", api_key.expose())])
             .query(&query_params)
             .send()
             .await
@@ -135,13 +142,13 @@ impl SerpApiIntegration {
     }
 
     /// Get account information
-    pub async fn get_account_info(&self, api_key: &ApiKey) -> AppResult<serde_json::Value> {
+    pub async fn get_account_info(&self, api_key: &SecretString) -> AppResult<serde_json::Value> {
         debug!("Getting SerpApi account information");
 
         let url = format!("{}/account", self.config.base_url);
-        let response = self.http_client
+        let response = self.client()?
             .get(&url)
-            .query(&[("api_key", &api_key.encrypted_key)])
+            .query(&[("api_key", api_key.expose())])
             .send()
             .await
             .map_err(|e| ApiError::external_service_error("SerpApi".to_string(), e.to_string()))?;
@@ -159,7 +166,7 @@ impl SerpApiIntegration {
     }
 
     /// Test the API key with account info request
-    async fn test_api_key_internal(&self, api_key: &ApiKey) -> AppResult<String> {
+    async fn test_api_key_internal(&self, api_key: &SecretString) -> AppResult<String> {
         debug!("Testing SerpApi API key");
 
         match self.get_account_info(api_key).await {
@@ -196,7 +203,7 @@ impl ServiceIntegration for SerpApiIntegration {
         ServiceProvider::SerpApi
     }
 
-    async fn make_request(&self, mut request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    async fn make_request(&self, mut request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making SerpApi request: {}", request.endpoint);
 
         let start_time = std::time::Instant::now();
@@ -211,6 +218,7 @@ impl ServiceIntegration for SerpApiIntegration {
             error_message: None,
             metadata: HashMap::new(),
             timestamp: Utc::now(),
+            normalized: None,
         };
 
         // Transform request for SerpApi specifics
@@ -218,13 +226,14 @@ impl ServiceIntegration for SerpApiIntegration {
 
         let url = format!("{}{}", self.config.base_url, request.endpoint);
         let mut req_builder = match request.method.as_str() {
-            "GET" => self.http_client.get(&url),
-            "POST" => self.http_client.post(&url),
+            "GET" => self.client()?.get(&url),
+            "POST" => self.client()?.post(&url),
             _ => return Err(ApiError::invalid_configuration("method".to_string(), "Unsupported HTTP method".to_string())),
         };
 
-        // Add API key to query parameters
-        let mut query_params = vec![("api_key", api_key.encrypted_key.clone())];
+        // Add API key to query parameters without an intermediate owned copy
+        req_builder = req_builder.query(&[("api_key", api_key.expose())]);
+        let mut query_params: Vec<(&str, String)> = Vec::new();
         
         // Parse existing query parameters from request metadata
         if let Some(query_string) = request.metadata.get("query_params") {
@@ -248,7 +257,7 @@ impl ServiceIntegration for SerpApiIntegration {
         }
 
         // Make the request
-        match req_builder.send().await {
+        match inject_trace_context(req_builder).send().await {
             Ok(http_response) => {
                 response.status_code = http_response.status().as_u16();
                 response.success = http_response.status().is_success();
@@ -282,7 +291,7 @@ impl ServiceIntegration for SerpApiIntegration {
         Ok(response)
     }
 
-    async fn health_check(&self, api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth> {
         debug!("Performing SerpApi health check");
 
         match self.test_api_key_internal(api_key).await {
@@ -303,15 +312,14 @@ impl ServiceIntegration for SerpApiIntegration {
         self.config = config;
         
         // Recreate HTTP client with new timeout
-        self.http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(self.config.default_timeout_ms as u64))
+        self.http_client = egress::client_builder(self.config.default_timeout_ms)
             .build()
             .map_err(|e| ApiError::invalid_configuration("timeout".to_string(), e.to_string()))?;
 
         Ok(())
     }
 
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool> {
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
@@ -5,7 +5,11 @@ use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
+use crate::services::api_manager::egress;
+use crate::utils::inject_trace_context;
+use super::json_with_api_key;
 use crate::services::api_manager::service_integration::{
     ServiceIntegration, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig
 };
@@ -29,15 +33,19 @@ pub struct TavilyIntegration {
 impl TavilyIntegration {
     pub fn new() -> Self {
         let config = ServiceConfig::default_for_service(ServiceProvider::Tavily);
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(config.default_timeout_ms as u64))
+        let http_client = egress::client_builder(config.default_timeout_ms)
             .build()
             .expect("Failed to create HTTP client");
 
         Self { config, http_client }
     }
 
-    async fn test_api_key_internal(&self, api_key: &ApiKey) -> AppResult<String> {
+    /// The client for the running workflow's egress profile, or this integration's own
+    fn client(&self) -> AppResult<reqwest::Client> {
+        egress::client_for(&self.http_client, self.config.default_timeout_ms)
+    }
+
+    async fn test_api_key_internal(&self, api_key: &SecretString) -> AppResult<String> {
         debug!("Testing Tavily API key");
 
         let request = TavilySearchRequest {
@@ -49,15 +57,14 @@ impl TavilyIntegration {
         };
 
         let url = format!("{}/search", self.config.base_url);
-        let response = self.http_client
+        let builder = self.client()?
             .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "api_key": api_key.encrypted_key,
+            .header("Content-Type", "application/json");
+        let response = json_with_api_key(builder, serde_json::json!({
                 "query": request.query,
                 "search_depth": request.search_depth,
                 "max_results": request.max_results
-            }))
+            }), api_key)
             .send()
             .await
             .map_err(|e| ApiError::external_service_error("Tavily".to_string(), e.to_string()))?;
@@ -77,7 +84,7 @@ impl ServiceIntegration for TavilyIntegration {
         ServiceProvider::Tavily
     }
 
-    async fn make_request(&self, mut request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    async fn make_request(&self, mut request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making Tavily request: {}", request.endpoint);
 
         let start_time = std::time::Instant::now();
@@ -92,14 +99,15 @@ impl ServiceIntegration for TavilyIntegration {
             error_message: None,
             metadata: HashMap::new(),
             timestamp: Utc::now(),
+            normalized: None,
         };
 
         self.transform_request(&mut request).await?;
 
         let url = format!("{}{}", self.config.base_url, request.endpoint);
         let mut req_builder = match request.method.as_str() {
-            "GET" => self.http_client.get(&url),
-            "POST" => self.http_client.post(&url),
+            "GET" => self.client()?.get(&url),
+            "POST" => self.client()?.post(&url),
             _ => return Err(ApiError::invalid_configuration("method".to_string(), "Unsupported HTTP method".to_string())),
         };
 
@@ -111,13 +119,12 @@ impl ServiceIntegration for TavilyIntegration {
 
         // For Tavily, API key goes in the body
         if let Some(body) = &request.body {
-            let mut body_json: serde_json::Value = serde_json::from_str(body)
+            let body_json: serde_json::Value = serde_json::from_str(body)
                 .unwrap_or_else(|_| serde_json::json!({}));
-            body_json["api_key"] = serde_json::Value::String(api_key.encrypted_key.clone());
-            req_builder = req_builder.json(&body_json);
+            req_builder = json_with_api_key(req_builder, body_json, api_key);
         }
 
-        match req_builder.send().await {
+        match inject_trace_context(req_builder).send().await {
             Ok(http_response) => {
                 response.status_code = http_response.status().as_u16();
                 response.success = http_response.status().is_success();
@@ -149,7 +156,7 @@ impl ServiceIntegration for TavilyIntegration {
         Ok(response)
     }
 
-    async fn health_check(&self, api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(ServiceHealth::Healthy),
             Err(_) => Ok(ServiceHealth::Unhealthy),
@@ -162,14 +169,13 @@ impl ServiceIntegration for TavilyIntegration {
 
     async fn update_config(&mut self, config: ServiceConfig) -> AppResult<()> {
         self.config = config;
-        self.http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(self.config.default_timeout_ms as u64))
+        self.http_client = egress::client_builder(self.config.default_timeout_ms)
             .build()
             .map_err(|e| ApiError::invalid_configuration("timeout".to_string(), e.to_string()))?;
         Ok(())
     }
 
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool> {
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
         match self.test_api_key_internal(api_key).await {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
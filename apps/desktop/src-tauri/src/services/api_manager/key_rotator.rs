@@ -9,6 +9,11 @@ use serde::{Serialize, Deserialize};
 use crate::error::{AppResult, ApiError};
 use crate::models::api_key::{ServiceProvider, ApiKey};
 use crate::services::DataPersistenceService;
+use crate::utils::clock::{self, SharedClock};
+use super::tenant_scope::KeyScope;
+
+/// Failovers kept in the rotation analytics, newest last
+const MAX_RECENT_FAILOVERS: usize = 50;
 
 /// Health status for an individual API key
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,6 +25,65 @@ pub enum KeyHealth {
     Cooldown,
 }
 
+/// How a failed request reflects on the key that made it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureCategory {
+    /// The provider rejected the key itself, e.g. it was revoked or expired
+    Auth,
+    /// The key is out of quota for now
+    RateLimit,
+    /// The provider is struggling; the key is probably fine
+    Transient,
+    Other,
+}
+
+impl FailureCategory {
+    pub fn of(error: &ApiError) -> Self {
+        match error {
+            ApiError::RequestFailed { status_code: 401 | 403, .. } => FailureCategory::Auth,
+            ApiError::RequestFailed { status_code: 429, .. } => FailureCategory::RateLimit,
+            e if e.is_auth_error() => FailureCategory::Auth,
+            e if e.is_rate_limit() => FailureCategory::RateLimit,
+            e if e.is_temporary() => FailureCategory::Transient,
+            _ => FailureCategory::Other,
+        }
+    }
+
+    /// Whether another key of the same service may succeed where this one failed
+    pub fn warrants_failover(&self) -> bool {
+        matches!(self, FailureCategory::Auth | FailureCategory::RateLimit)
+    }
+}
+
+/// A request that moved to another key of its service after the first one failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyFailover {
+    pub service: ServiceProvider,
+    pub request_id: Uuid,
+    pub from_key_id: Uuid,
+    pub to_key_id: Uuid,
+    pub reason: FailureCategory,
+    pub failed_over_at: DateTime<Utc>,
+}
+
+/// Why a key was taken out of rotation until it passes a test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDemotion {
+    pub reason: String,
+    pub auth_failures: u32,
+    pub demoted_at: DateTime<Utc>,
+}
+
+/// A key that has entered its expiry reminder window or expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyExpiryNotice {
+    pub api_key_id: Uuid,
+    pub name: String,
+    pub service: ServiceProvider,
+    pub expires_at: DateTime<Utc>,
+    pub expired: bool,
+}
+
 /// Performance metrics for an API key
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyPerformanceMetrics {
@@ -37,6 +101,15 @@ pub struct KeyPerformanceMetrics {
     pub priority_score: f64,
     pub last_used: Option<DateTime<Utc>>,
     pub cooldown_until: Option<DateTime<Utc>>,
+    /// Authentication failures since the key last succeeded
+    #[serde(default)]
+    pub consecutive_auth_failures: u32,
+    /// Set while the key is demoted for repeated authentication failures
+    #[serde(default)]
+    pub demotion: Option<KeyDemotion>,
+    /// The key's expiry date, refreshed by each expiry sweep
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl KeyPerformanceMetrics {
@@ -57,21 +130,25 @@ impl KeyPerformanceMetrics {
             priority_score: 100.0,
             last_used: api_key.last_used,
             cooldown_until: None,
+            consecutive_auth_failures: 0,
+            demotion: None,
+            expires_at: api_key.expires_at,
         }
     }
 
     /// Update metrics after a request
-    pub fn update_after_request(&mut self, success: bool, response_time_ms: u32) {
+    pub fn update_after_request(&mut self, success: bool, response_time_ms: u32, now: DateTime<Utc>) {
         self.total_requests += 1;
-        self.last_used = Some(Utc::now());
+        self.last_used = Some(now);
 
         if success {
             self.successful_requests += 1;
-            self.last_success = Some(Utc::now());
+            self.last_success = Some(now);
             self.consecutive_failures = 0;
+            self.consecutive_auth_failures = 0;
         } else {
             self.failed_requests += 1;
-            self.last_failure = Some(Utc::now());
+            self.last_failure = Some(now);
             self.consecutive_failures += 1;
         }
 
@@ -92,17 +169,67 @@ impl KeyPerformanceMetrics {
         }
 
         // Update health status
-        self.update_health_status();
+        self.update_health_status(now);
         
         // Update priority score
-        self.update_priority_score();
+        self.update_priority_score(now);
+    }
+
+    /// Record a failed request, demoting the key once authentication failures
+    /// reach `auth_failure_threshold`. Rate limits put the key in cooldown and
+    /// transient errors count towards the usual failure thresholds; neither demotes.
+    /// Returns the demotion if this failure caused one.
+    pub fn record_failure(
+        &mut self,
+        category: FailureCategory,
+        response_time_ms: u32,
+        config: &RotationConfig,
+        now: DateTime<Utc>,
+    ) -> Option<KeyDemotion> {
+        match category {
+            FailureCategory::Auth => self.consecutive_auth_failures += 1,
+            FailureCategory::RateLimit => {
+                self.cooldown_until = Some(now + Duration::minutes(config.cooldown_duration_minutes as i64));
+            }
+            FailureCategory::Transient | FailureCategory::Other => {}
+        }
+
+        let demoted = self.demotion.is_none()
+            && category == FailureCategory::Auth
+            && self.consecutive_auth_failures >= config.auth_failure_demotion_threshold.max(1);
+        if demoted {
+            self.demotion = Some(KeyDemotion {
+                reason: format!("{} consecutive authentication failures; the key may be revoked or expired", self.consecutive_auth_failures),
+                auth_failures: self.consecutive_auth_failures,
+                demoted_at: now,
+            });
+        }
+
+        self.update_after_request(false, response_time_ms, now);
+        if demoted { self.demotion.clone() } else { None }
+    }
+
+    /// Return a demoted key to rotation after it passes a test
+    pub fn promote(&mut self, now: DateTime<Utc>) {
+        self.demotion = None;
+        self.consecutive_auth_failures = 0;
+        self.consecutive_failures = 0;
+        self.cooldown_until = None;
+        self.health_status = KeyHealth::Healthy;
+        self.update_priority_score(now);
     }
 
     /// Update health status based on performance metrics
-    fn update_health_status(&mut self) {
+    fn update_health_status(&mut self, now: DateTime<Utc>) {
+        // Demoted keys stay unhealthy until promoted
+        if self.demotion.is_some() {
+            self.health_status = KeyHealth::Unhealthy;
+            return;
+        }
+
         // Check if in cooldown
         if let Some(cooldown_until) = self.cooldown_until {
-            if Utc::now() < cooldown_until {
+            if now < cooldown_until {
                 self.health_status = KeyHealth::Cooldown;
                 return;
             } else {
@@ -114,7 +241,7 @@ impl KeyPerformanceMetrics {
         if self.consecutive_failures >= 5 {
             self.health_status = KeyHealth::Failed;
             // Set cooldown for 30 minutes
-            self.cooldown_until = Some(Utc::now() + Duration::minutes(30));
+            self.cooldown_until = Some(now + Duration::minutes(30));
         } else if self.consecutive_failures >= 3 || self.success_rate < 50.0 {
             self.health_status = KeyHealth::Unhealthy;
         } else if self.success_rate < 80.0 || self.average_response_time_ms > 5000.0 {
@@ -125,7 +252,7 @@ impl KeyPerformanceMetrics {
     }
 
     /// Update priority score for rotation selection
-    fn update_priority_score(&mut self) {
+    fn update_priority_score(&mut self, now: DateTime<Utc>) {
         let mut score = 100.0;
 
         // Success rate factor (0-40 points)
@@ -141,7 +268,7 @@ impl KeyPerformanceMetrics {
 
         // Recency factor (0-20 points) - prefer less recently used keys
         if let Some(last_used) = self.last_used {
-            let hours_since_use = (Utc::now() - last_used).num_hours() as f64;
+            let hours_since_use = (now - last_used).num_hours() as f64;
             let recency_score = (hours_since_use / 24.0).min(1.0) * 20.0;
             score += recency_score;
         } else {
@@ -162,6 +289,9 @@ impl KeyPerformanceMetrics {
 
     /// Check if key is available for use
     pub fn is_available(&self) -> bool {
+        if self.demotion.is_some() {
+            return false;
+        }
         match self.health_status {
             KeyHealth::Healthy | KeyHealth::Degraded => true,
             KeyHealth::Unhealthy => self.consecutive_failures < 3,
@@ -196,6 +326,28 @@ pub struct RotationConfig {
     pub max_response_time_threshold_ms: u32,
     pub enable_automatic_reactivation: bool,
     pub load_balancing_weight_factor: f64,
+    /// Consecutive authentication failures that demote a key
+    #[serde(default = "default_auth_failure_demotion_threshold")]
+    pub auth_failure_demotion_threshold: u32,
+    /// Days before a key's expiry date that rotation reminders start
+    #[serde(default = "default_expiry_reminder_days")]
+    pub expiry_reminder_days: u32,
+    /// Further keys one request may move to when its key is rate limited or rejected;
+    /// 0 turns failover off
+    #[serde(default = "default_max_failover_rotations")]
+    pub max_failover_rotations: u32,
+}
+
+fn default_auth_failure_demotion_threshold() -> u32 {
+    3
+}
+
+fn default_expiry_reminder_days() -> u32 {
+    14
+}
+
+fn default_max_failover_rotations() -> u32 {
+    2
 }
 
 impl Default for RotationConfig {
@@ -209,6 +361,9 @@ impl Default for RotationConfig {
             max_response_time_threshold_ms: 5000,
             enable_automatic_reactivation: true,
             load_balancing_weight_factor: 1.0,
+            auth_failure_demotion_threshold: default_auth_failure_demotion_threshold(),
+            expiry_reminder_days: default_expiry_reminder_days(),
+            max_failover_rotations: default_max_failover_rotations(),
         }
     }
 }
@@ -227,6 +382,11 @@ pub struct RotationAnalytics {
     pub keys_failed: u32,
     pub last_rotation: Option<DateTime<Utc>>,
     pub rotation_frequency_per_hour: f64,
+    #[serde(default)]
+    pub total_failovers: u32,
+    /// Latest failovers, up to `MAX_RECENT_FAILOVERS`
+    #[serde(default)]
+    pub recent_failovers: Vec<KeyFailover>,
 }
 
 /// Intelligent key rotator for managing API key selection and health
@@ -235,7 +395,11 @@ pub struct KeyRotator {
     performance_metrics: Arc<RwLock<HashMap<Uuid, KeyPerformanceMetrics>>>,
     rotation_config: Arc<RwLock<HashMap<ServiceProvider, RotationConfig>>>,
     analytics: Arc<RwLock<RotationAnalytics>>,
-    last_selected_key: Arc<RwLock<HashMap<ServiceProvider, Uuid>>>,
+    /// Last key handed out per service and tenant, so each tenant rotates through its own keys
+    last_selected_key: Arc<RwLock<HashMap<(ServiceProvider, Option<Uuid>), Uuid>>>,
+    /// Expiry notices already sent: the expiry date each was for and whether it was the expired notice
+    expiry_notices: Arc<RwLock<HashMap<Uuid, (DateTime<Utc>, bool)>>>,
+    clock: SharedClock,
 }
 
 impl KeyRotator {
@@ -246,14 +410,7 @@ impl KeyRotator {
         let mut rotation_config = HashMap::new();
         
         // Initialize default configurations for all services
-        for service in [
-            ServiceProvider::OpenRouter,
-            ServiceProvider::SerpApi,
-            ServiceProvider::Jina,
-            ServiceProvider::Firecrawl,
-            ServiceProvider::Tavily,
-            ServiceProvider::Exa,
-        ] {
+        for service in ServiceProvider::all() {
             rotation_config.insert(service, RotationConfig::default());
         }
 
@@ -269,6 +426,8 @@ impl KeyRotator {
             keys_failed: 0,
             last_rotation: None,
             rotation_frequency_per_hour: 0.0,
+            total_failovers: 0,
+            recent_failovers: Vec::new(),
         };
 
         let rotator = Self {
@@ -277,6 +436,8 @@ impl KeyRotator {
             rotation_config: Arc::new(RwLock::new(rotation_config)),
             analytics: Arc::new(RwLock::new(analytics)),
             last_selected_key: Arc::new(RwLock::new(HashMap::new())),
+            expiry_notices: Arc::new(RwLock::new(HashMap::new())),
+            clock: clock::system_clock(),
         };
 
         // Initialize performance metrics for existing keys
@@ -286,6 +447,12 @@ impl KeyRotator {
         Ok(rotator)
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Initialize performance metrics for existing API keys
     async fn initialize_performance_metrics(&self) -> AppResult<()> {
         debug!("Initializing performance metrics for existing API keys");
@@ -306,9 +473,26 @@ impl KeyRotator {
         Ok(())
     }
 
-    /// Select the best available API key for a service using intelligent rotation
-    pub async fn select_best_key(&self, service: ServiceProvider) -> AppResult<Option<ApiKey>> {
-        debug!("Selecting best API key for service: {:?}", service);
+    /// Select the best available API key for a service using intelligent rotation. Only keys
+    /// `scope` owns are considered, then the system pool if the scope opted in to it.
+    pub async fn select_best_key(&self, service: ServiceProvider, scope: &KeyScope) -> AppResult<Option<ApiKey>> {
+        self.select_best_key_excluding(service, scope, &[]).await
+    }
+
+    /// Select the best available key as `select_best_key` does, passing over the keys in
+    /// `excluded`, such as those a request has already failed with
+    pub async fn select_best_key_excluding(&self, service: ServiceProvider, scope: &KeyScope, excluded: &[Uuid]) -> AppResult<Option<ApiKey>> {
+        self.choose_key(service, scope, excluded, true).await
+    }
+
+    /// The key `select_best_key` would pick now, without counting it as selected, so the
+    /// rotation moves on only when a request really uses a key
+    pub async fn preview_best_key(&self, service: ServiceProvider, scope: &KeyScope) -> AppResult<Option<ApiKey>> {
+        self.choose_key(service, scope, &[], false).await
+    }
+
+    async fn choose_key(&self, service: ServiceProvider, scope: &KeyScope, excluded: &[Uuid], commit: bool) -> AppResult<Option<ApiKey>> {
+        debug!("Selecting best API key for service {:?} for {}", service, scope.label());
 
         let start_time = std::time::Instant::now();
 
@@ -317,9 +501,11 @@ impl KeyRotator {
         let all_keys = data_persistence.get_all_api_keys().await?;
         drop(data_persistence);
 
-        let service_keys: Vec<_> = all_keys.into_iter()
-            .filter(|key| key.service == service && key.is_available())
-            .collect();
+        let now = self.clock.now();
+        let (owned, shared): (Vec<_>, Vec<_>) = all_keys.into_iter()
+            .filter(|key| key.service == service && key.is_available(now) && scope.permits(key) && !excluded.contains(&key.id))
+            .partition(|key| scope.owns(key));
+        let service_keys = if owned.is_empty() { shared } else { owned };
 
         if service_keys.is_empty() {
             debug!("No available keys found for service: {:?}", service);
@@ -357,13 +543,17 @@ impl KeyRotator {
 
         // Select key based on rotation strategy
         let selected_key = match rotation_config.strategy {
-            RotationStrategy::RoundRobin => self.select_round_robin(&mut available_keys, service).await,
+            RotationStrategy::RoundRobin => self.select_round_robin(&mut available_keys, service, scope.tenant_id).await,
             RotationStrategy::PriorityBased => self.select_priority_based(&mut available_keys),
             RotationStrategy::LeastRecentlyUsed => self.select_least_recently_used(&mut available_keys),
             RotationStrategy::HealthAware => self.select_health_aware(&mut available_keys),
             RotationStrategy::LoadBalanced => self.select_load_balanced(&mut available_keys),
         };
 
+        if !commit {
+            return Ok(selected_key);
+        }
+
         // Update analytics
         let rotation_time = start_time.elapsed().as_millis() as f64;
         self.update_rotation_analytics(selected_key.is_some(), rotation_time).await;
@@ -371,7 +561,7 @@ impl KeyRotator {
         // Update last selected key
         if let Some(ref key) = selected_key {
             let mut last_selected = self.last_selected_key.write().await;
-            last_selected.insert(service, key.id);
+            last_selected.insert((service, scope.tenant_id), key.id);
         }
 
         debug!("Selected key for service {:?}: {:?}", service,
@@ -381,10 +571,10 @@ impl KeyRotator {
     }
 
     /// Round-robin selection
-    async fn select_round_robin(&self, keys: &mut [(ApiKey, KeyPerformanceMetrics)], service: ServiceProvider) -> Option<ApiKey> {
+    async fn select_round_robin(&self, keys: &mut [(ApiKey, KeyPerformanceMetrics)], service: ServiceProvider, tenant_id: Option<Uuid>) -> Option<ApiKey> {
         let last_selected = self.last_selected_key.read().await;
 
-        if let Some(last_key_id) = last_selected.get(&service) {
+        if let Some(last_key_id) = last_selected.get(&(service, tenant_id)) {
             // Find the next key after the last selected one
             if let Some(current_index) = keys.iter().position(|(key, _)| key.id == *last_key_id) {
                 let next_index = (current_index + 1) % keys.len();
@@ -479,16 +669,17 @@ impl KeyRotator {
         debug!("Recording request performance for key: {} (success: {}, time: {}ms)",
                api_key_id, success, response_time_ms);
 
+        let now = self.clock.now();
         let mut metrics = self.performance_metrics.write().await;
 
         if let Some(key_metrics) = metrics.get_mut(&api_key_id) {
-            key_metrics.update_after_request(success, response_time_ms);
+            key_metrics.update_after_request(success, response_time_ms, now);
         } else {
             // Create new metrics if not found
             let data_persistence = self.data_persistence.read().await;
             if let Some(api_key) = data_persistence.get_api_key_by_id(api_key_id).await? {
                 let mut new_metrics = KeyPerformanceMetrics::new(&api_key);
-                new_metrics.update_after_request(success, response_time_ms);
+                new_metrics.update_after_request(success, response_time_ms, now);
                 metrics.insert(api_key_id, new_metrics);
             }
             drop(data_persistence);
@@ -498,6 +689,64 @@ impl KeyRotator {
         Ok(())
     }
 
+    /// Record a failed request by its error category; returns the demotion if
+    /// this failure took the key out of rotation
+    pub async fn record_request_error(&self, api_key_id: Uuid, error: &ApiError, response_time_ms: u32) -> AppResult<Option<KeyDemotion>> {
+        let category = FailureCategory::of(error);
+        debug!("Recording {:?} failure for key: {}", category, api_key_id);
+
+        let mut metrics = self.performance_metrics.write().await;
+        if !metrics.contains_key(&api_key_id) {
+            let data_persistence = self.data_persistence.read().await;
+            match data_persistence.get_api_key_by_id(api_key_id).await? {
+                Some(api_key) => {
+                    metrics.insert(api_key_id, KeyPerformanceMetrics::new(&api_key));
+                }
+                None => return Ok(None),
+            }
+        }
+
+        let key_metrics = match metrics.get_mut(&api_key_id) {
+            Some(key_metrics) => key_metrics,
+            None => return Ok(None),
+        };
+        let config = self.get_rotation_config(key_metrics.service).await;
+        let demotion = key_metrics.record_failure(category, response_time_ms, &config, self.clock.now());
+
+        if let Some(ref demotion) = demotion {
+            warn!("API key {} demoted: {}", api_key_id, demotion.reason);
+        }
+        Ok(demotion)
+    }
+
+    /// Record that a request moved to another key of its service
+    pub async fn record_failover(&self, failover: KeyFailover) {
+        info!("Request {} failed over from key {} to {} for {:?} after a {:?} failure",
+              failover.request_id, failover.from_key_id, failover.to_key_id, failover.service, failover.reason);
+
+        let mut analytics = self.analytics.write().await;
+        analytics.total_failovers += 1;
+        analytics.recent_failovers.push(failover);
+        let excess = analytics.recent_failovers.len().saturating_sub(MAX_RECENT_FAILOVERS);
+        analytics.recent_failovers.drain(..excess);
+    }
+
+    /// Return a key to rotation after a successful test; returns whether it had been demoted
+    pub async fn promote_key(&self, api_key_id: Uuid) -> bool {
+        let mut metrics = self.performance_metrics.write().await;
+        match metrics.get_mut(&api_key_id) {
+            Some(key_metrics) => {
+                let was_demoted = key_metrics.demotion.is_some();
+                key_metrics.promote(self.clock.now());
+                if was_demoted {
+                    info!("API key {} promoted back into rotation", api_key_id);
+                }
+                was_demoted
+            }
+            None => false,
+        }
+    }
+
     /// Update rotation analytics
     async fn update_rotation_analytics(&self, success: bool, rotation_time_ms: f64) {
         let mut analytics = self.analytics.write().await;
@@ -518,7 +767,7 @@ impl KeyRotator {
                 / analytics.total_rotations as f64;
         }
 
-        analytics.last_rotation = Some(Utc::now());
+        analytics.last_rotation = Some(self.clock.now());
 
         // Update health statistics
         let metrics = self.performance_metrics.read().await;
@@ -571,6 +820,12 @@ impl KeyRotator {
         Ok(())
     }
 
+    /// Rotate the keys of a service added after startup with the default configuration,
+    /// keeping any it was given since
+    pub async fn register_rotation_config(&self, service: ServiceProvider) {
+        self.rotation_config.write().await.entry(service).or_default();
+    }
+
     /// Get rotation configuration for a service
     pub async fn get_rotation_config(&self, service: ServiceProvider) -> RotationConfig {
         let configs = self.rotation_config.read().await;
@@ -581,12 +836,13 @@ impl KeyRotator {
     pub async fn perform_health_check(&self) -> AppResult<HashMap<Uuid, KeyHealth>> {
         debug!("Performing health check on all API keys");
 
+        let now = self.clock.now();
         let mut health_status = HashMap::new();
         let mut metrics = self.performance_metrics.write().await;
 
         for (key_id, key_metrics) in metrics.iter_mut() {
             // Update health status based on current metrics
-            key_metrics.update_health_status();
+            key_metrics.update_health_status(now);
             health_status.insert(*key_id, key_metrics.health_status.clone());
 
             // Log health changes
@@ -615,17 +871,18 @@ impl KeyRotator {
     pub async fn reactivate_cooled_down_keys(&self) -> AppResult<Vec<Uuid>> {
         debug!("Checking for keys to reactivate from cooldown");
 
+        let now = self.clock.now();
         let mut reactivated_keys = Vec::new();
         let mut metrics = self.performance_metrics.write().await;
 
         for (key_id, key_metrics) in metrics.iter_mut() {
             if key_metrics.health_status == KeyHealth::Cooldown {
                 if let Some(cooldown_until) = key_metrics.cooldown_until {
-                    if Utc::now() >= cooldown_until {
+                    if now >= cooldown_until {
                         key_metrics.cooldown_until = None;
                         key_metrics.consecutive_failures = 0;
                         key_metrics.health_status = KeyHealth::Healthy;
-                        key_metrics.update_priority_score();
+                        key_metrics.update_priority_score(now);
                         reactivated_keys.push(*key_id);
 
                         info!("Reactivated API key {} from cooldown", key_id);
@@ -641,15 +898,79 @@ impl KeyRotator {
         Ok(reactivated_keys)
     }
 
-    /// Get keys that need attention (unhealthy, failed, or in cooldown)
+    /// Get keys that need attention (unhealthy, failed, in cooldown, demoted or
+    /// expiring); demoted keys carry the reason in `demotion`
     pub async fn get_keys_needing_attention(&self) -> Vec<(Uuid, KeyPerformanceMetrics)> {
+        let now = self.clock.now();
         let metrics = self.performance_metrics.read().await;
+        let config = self.rotation_config.read().await;
         metrics.iter()
-            .filter(|(_, m)| matches!(m.health_status, KeyHealth::Unhealthy | KeyHealth::Failed | KeyHealth::Cooldown))
+            .filter(|(_, m)| {
+                let reminder_days = config.get(&m.service).map_or(default_expiry_reminder_days(), |c| c.expiry_reminder_days);
+                m.demotion.is_some()
+                    || m.expires_at.map_or(false, |expires_at| expires_at <= now + Duration::days(reminder_days as i64))
+                    || matches!(m.health_status, KeyHealth::Unhealthy | KeyHealth::Failed | KeyHealth::Cooldown)
+            })
             .map(|(id, m)| (*id, m.clone()))
             .collect()
     }
 
+    /// Sweep every key's expiry date and return the keys that have newly
+    /// entered their reminder window or expired since the last sweep.
+    ///
+    /// Each key gets one reminder and one expiry notice per expiry date, so
+    /// moving the date (or rotating in a new key) starts the reminders afresh.
+    pub async fn check_key_expiry(&self) -> AppResult<Vec<KeyExpiryNotice>> {
+        debug!("Checking API key expiry dates");
+
+        let data_persistence = self.data_persistence.read().await;
+        let all_keys = data_persistence.get_all_api_keys().await?;
+        drop(data_persistence);
+
+        let now = self.clock.now();
+        let config = self.rotation_config.read().await.clone();
+        let mut metrics = self.performance_metrics.write().await;
+        let mut sent = self.expiry_notices.write().await;
+        let mut notices = Vec::new();
+
+        for api_key in &all_keys {
+            let reminder_days = config.get(&api_key.service).map_or(default_expiry_reminder_days(), |c| c.expiry_reminder_days);
+            let expired = api_key.is_expired(now);
+            let (expires_at, due) = match api_key.expires_at {
+                Some(expires_at) if expired || api_key.expires_within(now, reminder_days) => (expires_at, expired),
+                _ => {
+                    sent.remove(&api_key.id);
+                    if let Some(m) = metrics.get_mut(&api_key.id) {
+                        m.expires_at = api_key.expires_at;
+                    }
+                    continue;
+                }
+            };
+
+            // Track expiring keys even before their first request so they show up as needing attention
+            metrics.entry(api_key.id)
+                .or_insert_with(|| KeyPerformanceMetrics::new(api_key))
+                .expires_at = Some(expires_at);
+
+            if sent.get(&api_key.id) == Some(&(expires_at, due)) {
+                continue;
+            }
+            sent.insert(api_key.id, (expires_at, due));
+            notices.push(KeyExpiryNotice {
+                api_key_id: api_key.id,
+                name: api_key.name.clone(),
+                service: api_key.service,
+                expires_at,
+                expired,
+            });
+        }
+
+        if !notices.is_empty() {
+            info!("{} API keys are expiring or have expired", notices.len());
+        }
+        Ok(notices)
+    }
+
     /// Generate rotation report
     pub async fn generate_rotation_report(&self) -> AppResult<String> {
         debug!("Generating rotation report");
@@ -659,7 +980,7 @@ impl KeyRotator {
 
         let mut report = String::new();
         report.push_str("# Key Rotation Report\n\n");
-        report.push_str(&format!("Generated: {}\n\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+        report.push_str(&format!("Generated: {}\n\n", self.clock.now().format("%Y-%m-%d %H:%M:%S UTC")));
 
         // Rotation statistics
         report.push_str("## Rotation Statistics\n\n");
@@ -692,6 +1013,13 @@ impl KeyRotator {
                 metrics.api_key_id.to_string().chars().take(8).collect::<String>(),
                 metrics.service));
             report.push_str(&format!("- Health: {:?}\n", metrics.health_status));
+            if let Some(ref demotion) = metrics.demotion {
+                report.push_str(&format!("- Demoted: {} (since {})\n",
+                    demotion.reason, demotion.demoted_at.format("%Y-%m-%d %H:%M:%S UTC")));
+            }
+            if let Some(expires_at) = metrics.expires_at {
+                report.push_str(&format!("- Expires: {}\n", expires_at.format("%Y-%m-%d %H:%M:%S UTC")));
+            }
             report.push_str(&format!("- Success Rate: {:.1}%\n", metrics.success_rate));
             report.push_str(&format!("- Average Response Time: {:.1}ms\n", metrics.average_response_time_ms));
             report.push_str(&format!("- Total Requests: {}\n", metrics.total_requests));
@@ -742,6 +1070,78 @@ impl Clone for KeyRotator {
             rotation_config: self.rotation_config.clone(),
             analytics: self.analytics.clone(),
             last_selected_key: self.last_selected_key.clone(),
+            expiry_notices: self.expiry_notices.clone(),
+            clock: self.clock.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::{Clock, ManualClock};
+
+    fn metrics() -> KeyPerformanceMetrics {
+        KeyPerformanceMetrics::new(&ApiKey::new(ServiceProvider::SerpApi, "test".to_string(), "ciphertext".to_string()))
+    }
+
+    #[test]
+    fn test_auth_failures_demote_but_rate_limits_only_cool_down() {
+        let config = RotationConfig::default();
+        let now = Utc::now();
+
+        let mut rate_limited = metrics();
+        for _ in 0..config.auth_failure_demotion_threshold {
+            let error = ApiError::rate_limit_exceeded("serpapi", 100, 100);
+            assert!(rate_limited.record_failure(FailureCategory::of(&error), 100, &config, now).is_none());
+        }
+        assert!(rate_limited.demotion.is_none());
+        assert_eq!(rate_limited.health_status, KeyHealth::Cooldown);
+
+        let mut revoked = metrics();
+        let error = ApiError::request_failed("serpapi", 401, "Invalid API key");
+        assert_eq!(FailureCategory::of(&error), FailureCategory::Auth);
+        assert!(revoked.record_failure(FailureCategory::Auth, 100, &config, now).is_none());
+        assert!(revoked.record_failure(FailureCategory::Auth, 100, &config, now).is_none());
+        assert!(revoked.record_failure(FailureCategory::Auth, 100, &config, now).is_some());
+        assert!(!revoked.is_available());
+
+        revoked.promote(now);
+        assert!(revoked.is_available());
+        assert_eq!(revoked.health_status, KeyHealth::Healthy);
+    }
+
+    #[test]
+    fn test_rate_limit_cooldown_ends_when_the_clock_passes_it() {
+        let config = RotationConfig::default();
+        let clock = ManualClock::new(Utc::now());
+        let mut key = metrics();
+
+        let error = ApiError::rate_limit_exceeded("serpapi", 100, 100);
+        key.record_failure(FailureCategory::of(&error), 100, &config, clock.now());
+        assert_eq!(key.health_status, KeyHealth::Cooldown);
+
+        clock.advance(Duration::minutes(config.cooldown_duration_minutes as i64 - 1));
+        key.update_health_status(clock.now());
+        assert_eq!(key.health_status, KeyHealth::Cooldown);
+
+        clock.advance(Duration::minutes(1));
+        key.update_health_status(clock.now());
+        assert!(key.cooldown_until.is_none());
+        assert!(key.is_available());
+    }
+
+    #[test]
+    fn test_only_key_specific_failures_fail_over() {
+        assert!(FailureCategory::of(&ApiError::request_failed("serpapi", 429, "Too many requests")).warrants_failover());
+        assert!(FailureCategory::of(&ApiError::request_failed("serpapi", 403, "Forbidden")).warrants_failover());
+        assert!(FailureCategory::of(&ApiError::quota_exceeded("serpapi")).warrants_failover());
+        assert!(!FailureCategory::of(&ApiError::request_failed("serpapi", 400, "Bad query")).warrants_failover());
+
+        // Configs saved before failover existed get the default bound
+        let mut saved = serde_json::to_value(RotationConfig::default()).unwrap();
+        saved.as_object_mut().unwrap().remove("max_failover_rotations");
+        let config: RotationConfig = serde_json::from_value(saved).unwrap();
+        assert_eq!(config.max_failover_rotations, 2);
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use crate::error::{ApiError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use super::response_schema;
+use super::service_integration::{MockServiceIntegration, ServiceRequest, ServiceResponse};
+
+/// Metadata key marking a response as served by the mock providers
+pub const MOCK_RESPONSE_KEY: &str = "mock_response";
+
+/// How the mock providers answer the calls of a workflow run in mock mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockSettings {
+    pub latency_ms: u32,
+    /// Share of calls, from 0 to 100, that fail as if the provider were unavailable
+    pub failure_percent: u8,
+}
+
+impl MockSettings {
+    /// Whether the call drawing `roll`, from 0.0 up to 1.0, fails
+    fn fails(&self, roll: f64) -> bool {
+        roll * 100.0 < self.failure_percent.min(100) as f64
+    }
+}
+
+/// Answer `request` with the built-in mock integration for `service`, as a live call
+/// would: after the configured latency, and with the body checked against the
+/// provider's schema so steps get the same normalized payload
+pub async fn respond(service: ServiceProvider, request: &ServiceRequest, settings: MockSettings) -> AppResult<ServiceResponse> {
+    tokio::time::sleep(Duration::from_millis(settings.latency_ms as u64)).await;
+    if settings.fails(rand::random::<f64>()) {
+        return Err(ApiError::request_failed(format!("{:?}", service), 503, "Simulated mock provider failure").into());
+    }
+
+    let mut response = MockServiceIntegration::new(service).generate_mock_response(request);
+    response.response_time_ms = settings.latency_ms;
+    response.metadata.insert(MOCK_RESPONSE_KEY.to_string(), "true".to_string());
+    response.normalized = response_schema::validate_response(service, &request.endpoint, &response.body)
+        .map_err(|violation| ApiError::InvalidResponse {
+            service: format!("{:?}", service),
+            message: format!("mock response does not match the schema at {}", violation),
+        })?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn request(service: ServiceProvider, endpoint: &str) -> ServiceRequest {
+        ServiceRequest {
+            request_id: Uuid::new_v4(),
+            service,
+            endpoint: endpoint.to_string(),
+            method: "POST".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            timeout_ms: 1000,
+            retry_count: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_responses_pass_the_provider_schemas() {
+        let settings = MockSettings { latency_ms: 0, failure_percent: 0 };
+        for (service, endpoint) in [
+            (ServiceProvider::SerpApi, "/search"),
+            (ServiceProvider::Tavily, "/search"),
+            (ServiceProvider::Exa, "/search"),
+            (ServiceProvider::Jina, "/embeddings"),
+            (ServiceProvider::Firecrawl, "/scrape"),
+            (ServiceProvider::Firecrawl, "/map"),
+            (ServiceProvider::OpenRouter, "/chat/completions"),
+        ] {
+            let response = respond(service, &request(service, endpoint), settings).await.unwrap();
+            assert!(response.normalized.is_some(), "{:?} {} was not normalized", service, endpoint);
+            assert_eq!(response.service, service);
+        }
+
+        let flaky = MockSettings { latency_ms: 0, failure_percent: 25 };
+        assert!(flaky.fails(0.1));
+        assert!(!flaky.fails(0.25));
+        assert!(!MockSettings { latency_ms: 0, failure_percent: 0 }.fails(0.0));
+        let failing = MockSettings { latency_ms: 0, failure_percent: 100 };
+        assert!(respond(ServiceProvider::Exa, &request(ServiceProvider::Exa, "/search"), failing).await.is_err());
+    }
+}
@@ -1,17 +1,33 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, debug};
+use tracing::{info, debug, warn, error, info_span, Instrument};
 
-use crate::error::{AppResult, ApiError};
-use crate::models::{ApiKey, CreateApiKeyRequest, UpdateApiKeyRequest, ApiKeyTestResult, ApiKeyImport, ApiKeyExport};
+use crate::error::{AppError, AppResult, ApiError};
+use crate::models::{ApiKey, CreateApiKeyRequest, UpdateApiKeyRequest, ApiKeyTestResult, ApiKeyImport, ApiKeyExport, ApiKeyFilter, ApiKeyStatus, BulkKeyOutcome};
 use crate::services::{Service, DataPersistenceService, SecurityService, MonitoringService};
+use crate::services::security::SecretString;
+use crate::models::pagination::{Page, PageRequest};
+use crate::utils::air_gap;
+use crate::utils::clock::{self, SharedClock};
 use uuid::Uuid;
 
 pub mod rate_limiter;
-pub use rate_limiter::{RateLimiter, RateLimitConfig, UsageStatus, LimitStatus, RateLimitAlert, AlertType, UsageForecast};
+pub use rate_limiter::{RateLimiter, RateLimitConfig, UsageStatus, LimitStatus, RateLimitAlert, AlertType, UsageForecast, TenantKeyUsage};
+
+pub mod notifications;
+pub use notifications::{NotificationDispatcher, NotificationSink, InAppSink, WebhookSink, EmailSink, RecipientDirectory, DeliveryFailure};
+
+pub mod usage_quota;
+pub use usage_quota::{QuotaConfig, QuotaUsage, ServiceCapacity};
+
+pub mod usage_reconciliation;
+pub use usage_reconciliation::{UsageReconciliationReport, UsageReconciliationRow, UsageReconciler, DivergenceConfig};
+
+pub mod rate_limit_simulation;
+pub use rate_limit_simulation::{RateLimitSimulation, DailyUsage};
 
 pub mod key_rotator;
-pub use key_rotator::{KeyRotator, KeyPerformanceMetrics, KeyHealth, RotationStrategy, RotationConfig, RotationAnalytics};
+pub use key_rotator::{KeyRotator, KeyPerformanceMetrics, KeyHealth, RotationStrategy, RotationConfig, RotationAnalytics, FailureCategory, KeyDemotion, KeyExpiryNotice, KeyFailover};
 
 pub mod model_manager;
 pub use model_manager::{ModelManager, ModelConfiguration, ModelPerformanceMetrics, ModelRecommendation, ModelTier};
@@ -19,9 +35,50 @@ pub use model_manager::{ModelManager, ModelConfiguration, ModelPerformanceMetric
 pub mod service_integration;
 pub use service_integration::{ServiceIntegrationManager, ServiceRequest, ServiceResponse, ServiceHealth, ServiceConfig, ServiceMetrics};
 
+pub mod response_schema;
+pub use response_schema::{NormalizedPayload, SearchHit, SchemaViolation};
+
 pub mod integrations;
 pub use integrations::create_all_integrations;
 
+pub mod fallback_router;
+pub use fallback_router::{FallbackRouter, FallbackConfig, FallbackChain, ChainMode, RaceConfig, FallbackResponse, ProviderAttempt, AttemptOutcome, CircuitState, CircuitBreakerConfig, CircuitBreakerStatus, BreakerKind};
+
+pub mod web_search;
+pub use web_search::{WebSearch, WebSearchOutcome, PageSupport, MAX_SEARCH_RESULTS};
+
+pub mod content_cache;
+pub use content_cache::{ContentCache, ContentCacheConfig, ContentCacheStats, CachedExtraction};
+
+pub mod crawl_policy;
+pub use crawl_policy::{CrawlPolicy, CrawlPolicyConfig, RobotsMode, SkippedUrl, SkipReason};
+
+pub mod model_router;
+pub use model_router::{ModelRouter, ModelRoutingPolicy, ModelAttempt, ModelFallbackResponse, ContextBudget, StructuredOutputMode};
+
+pub mod content_policy;
+
+pub mod response_recorder;
+pub use response_recorder::{ResponseRecorder, RecordedExchange};
+
+pub mod call_meter;
+pub use call_meter::{CallCounts, CallMeter};
+
+pub mod mock_providers;
+pub use mock_providers::MockSettings;
+
+pub mod tenant_scope;
+pub use tenant_scope::{KeyScope, run_scoped};
+
+pub mod egress;
+pub use egress::{EgressProfile, EgressRegistry, EgressClients};
+
+pub mod concurrency;
+pub use concurrency::{ConcurrencyLimiter, ConcurrencyPermit};
+
+pub mod provider_adapter;
+pub use provider_adapter::{ProviderAdapter, ProviderAuth, HttpRequest, AdapterIntegration, register_adapter};
+
 /// Result of API key import operation
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct ImportResult {
@@ -30,11 +87,36 @@ pub struct ImportResult {
     pub errors: Vec<String>,
 }
 
+/// Result of a bulk API key operation, with the outcome for every matched key
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct BulkOperationResult {
+    pub successful_count: u32,
+    pub failed_count: u32,
+    pub errors: Vec<String>,
+    pub results: Vec<BulkKeyOutcome>,
+}
+
+impl BulkOperationResult {
+    fn from_outcomes(results: Vec<BulkKeyOutcome>) -> Self {
+        let successful_count = results.iter().filter(|r| r.success).count() as u32;
+        let errors = results.iter()
+            .filter(|r| !r.success)
+            .map(|r| format!("'{}': {}", r.name, r.message))
+            .collect::<Vec<_>>();
+
+        Self {
+            successful_count,
+            failed_count: errors.len() as u32,
+            errors,
+            results,
+        }
+    }
+}
+
 // TODO: Implement these modules
 // pub mod key_manager;
 // pub mod rate_limiter;
 // pub mod health_monitor;
-// pub mod fallback_router;
 
 /// API Manager Service that handles all external API interactions
 pub struct ApiManagerService {
@@ -45,6 +127,14 @@ pub struct ApiManagerService {
     key_rotator: Arc<KeyRotator>,
     service_integration: Arc<RwLock<ServiceIntegrationManager>>,
     model_manager: Arc<RwLock<ModelManager>>,
+    fallback_router: Arc<FallbackRouter>,
+    model_router: Arc<ModelRouter>,
+    response_recorder: Arc<ResponseRecorder>,
+    usage_reconciler: Arc<UsageReconciler>,
+    egress_registry: Arc<EgressRegistry>,
+    content_cache: Arc<ContentCache>,
+    crawl_policy: Arc<CrawlPolicy>,
+    clock: SharedClock,
 }
 
 impl ApiManagerService {
@@ -53,18 +143,68 @@ impl ApiManagerService {
         data_persistence: Arc<RwLock<DataPersistenceService>>,
         security: Arc<RwLock<SecurityService>>,
         monitoring: Arc<RwLock<MonitoringService>>,
+    ) -> AppResult<Self> {
+        Self::new_with_clock(data_persistence, security, monitoring, clock::system_clock()).await
+    }
+
+    /// Create an API manager service whose rate limits, resets and cooldowns run on `clock`
+    pub async fn new_with_clock(
+        data_persistence: Arc<RwLock<DataPersistenceService>>,
+        security: Arc<RwLock<SecurityService>>,
+        monitoring: Arc<RwLock<MonitoringService>>,
+        clock: SharedClock,
     ) -> AppResult<Self> {
         info!("Initializing API manager service...");
 
         // Initialize rate limiter
-        let rate_limiter = Arc::new(RateLimiter::new(data_persistence.clone()).await?);
+        let rate_limiter = Arc::new(RateLimiter::new(data_persistence.clone()).await?.with_clock(clock.clone()));
 
         // Initialize key rotator
-        let key_rotator = Arc::new(KeyRotator::new(data_persistence.clone()).await?);
+        let key_rotator = Arc::new(KeyRotator::new(data_persistence.clone()).await?.with_clock(clock.clone()));
 
         // Initialize service integration manager
         let service_integration = Arc::new(RwLock::new(create_all_integrations().await?));
 
+        // Initialize provider fallback chains, with the custom providers that can serve them
+        let mut fallback_config = FallbackConfig::default();
+        for (service, adapter) in provider_adapter::registered_adapters() {
+            provider_adapter::join_fallback_chains(&mut fallback_config, service, adapter.as_ref());
+        }
+        let fallback_router = Arc::new(FallbackRouter::new(fallback_config));
+
+        // Initialize model routing for AI steps
+        let model_router = Arc::new(ModelRouter::new(ModelRoutingPolicy::default()));
+
+        // Initialize provider response recorder for recorded and replayed workflows
+        let response_recorder = Arc::new(ResponseRecorder::new()?);
+
+        // Initialize usage reconciliation against provider-reported counts
+        let usage_reconciler = Arc::new(UsageReconciler::new(
+            data_persistence.clone(),
+            rate_limiter.clone(),
+            fallback_router.clone(),
+        ).await);
+
+        // Initialize the egress profiles workflows can send provider requests through
+        let egress_registry = Arc::new(EgressRegistry::default());
+        // Workflows name their profile, so losing the profiles on restart would refuse them
+        match data_persistence.read().await.get_setting::<Vec<EgressProfile>>(egress::EGRESS_PROFILES_SETTING).await {
+            Ok(Some(profiles)) => {
+                if let Err(e) = egress_registry.set_profiles(profiles).await {
+                    warn!("Failed to restore egress profiles: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load egress profiles: {}", e),
+        }
+
+        // Initialize robots.txt checks and per-host request spacing for extraction
+        let crawl_policy = Arc::new(CrawlPolicy::default());
+
+        // Initialize the cache of extracted page content shared across workflows, which
+        // checks pages with the same politeness as extraction
+        let content_cache = Arc::new(ContentCache::new(ContentCacheConfig::default(), crawl_policy.clone()));
+
         let service = Self {
             data_persistence,
             security,
@@ -72,24 +212,76 @@ impl ApiManagerService {
             rate_limiter,
             key_rotator,
             service_integration,
+            fallback_router,
+            model_router,
+            response_recorder,
+            usage_reconciler,
+            egress_registry,
+            content_cache,
+            crawl_policy,
+            clock,
         };
 
         info!("API manager service initialized successfully");
         Ok(service)
     }
     
-    /// Get all API keys
+    /// Get all API keys the calling tenant may use
     pub async fn get_all_keys(&self) -> AppResult<Vec<ApiKey>> {
-        debug!("Getting all API keys");
+        let scope = KeyScope::current();
+        debug!("Getting all API keys for {}", scope.label());
 
         let data_persistence = self.data_persistence.read().await;
-        let api_keys = data_persistence.get_all_api_keys().await?;
+        let api_keys: Vec<_> = data_persistence.get_all_api_keys().await?
+            .into_iter()
+            .filter(|key| scope.permits(key))
+            .collect();
         drop(data_persistence);
 
         debug!("Retrieved {} API keys", api_keys.len());
         Ok(api_keys)
     }
 
+    /// A page of the API keys the calling tenant may use, newest first
+    pub async fn list_keys(&self, page: &PageRequest) -> AppResult<Page<ApiKey>> {
+        let scope = KeyScope::current();
+        let after = page.after()?;
+        // The system owns the keys of no tenant, so it always sees the system pool
+        let include_system_pool = scope.use_system_pool || scope.tenant_id.is_none();
+        self.data_persistence.read().await
+            .get_api_keys_page(scope.tenant_id, include_system_pool, after.as_ref(), page.limit())
+            .await
+    }
+
+    /// A key owned by the calling tenant. Keys of other tenants, and system keys seen
+    /// from a tenant, are denied and the attempt is audited.
+    async fn owned_key(&self, key_id: Uuid, action: &str) -> AppResult<ApiKey> {
+        let data_persistence = self.data_persistence.read().await;
+        let api_key = data_persistence.get_api_key_by_id(key_id).await?
+            .ok_or_else(|| ApiError::key_not_found(key_id.to_string()))?;
+        drop(data_persistence);
+
+        let scope = KeyScope::current();
+        if !scope.owns(&api_key) {
+            self.audit_denied_key_access(&scope, &key_id.to_string(), action).await;
+            return Err(ApiError::key_access_denied(key_id.to_string(), scope.label()).into());
+        }
+        Ok(api_key)
+    }
+
+    async fn audit_denied_key_access(&self, scope: &KeyScope, target: &str, action: &str) {
+        warn!("Denied {} of API key {} to {}", action, target, scope.label());
+
+        let monitoring = self.monitoring.read().await;
+        if let Err(e) = monitoring.log_audit_event(
+            "api_key_access_denied".to_string(),
+            format!("{} attempted to {} an API key it does not own", scope.label(), action),
+            Some(target.to_string())
+        ).await {
+            error!("Failed to log audit event: {}", e);
+        }
+    }
+
     /// Add a new API key
     pub async fn add_key(&mut self, request: CreateApiKeyRequest) -> AppResult<ApiKey> {
         debug!("Adding new API key for service: {:?}", request.service);
@@ -109,6 +301,15 @@ impl ApiManagerService {
             ).into());
         }
 
+        // Tenants may only add keys to their own pool
+        let scope = KeyScope::current();
+        let tenant_id = request.tenant_id.or(scope.tenant_id);
+        if scope.tenant_id.is_some() && tenant_id != scope.tenant_id {
+            let owner = KeyScope { tenant_id, use_system_pool: false }.label();
+            self.audit_denied_key_access(&scope, &owner, "add").await;
+            return Err(ApiError::key_access_denied(owner, scope.label()).into());
+        }
+
         // Encrypt the API key
         let security = self.security.read().await;
         let encrypted_key = security.encrypt_string(&request.api_key).await?;
@@ -126,6 +327,11 @@ impl ApiManagerService {
             last_used: None,
             last_reset: chrono::Utc::now(),
             status: crate::models::api_key::ApiKeyStatus::Active,
+            monthly_quota: request.monthly_quota,
+            quota_usage: 0,
+            quota_period_start: chrono::Utc::now(),
+            expires_at: request.expires_at,
+            tenant_id,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -155,11 +361,7 @@ impl ApiManagerService {
         debug!("Updating API key: {}", key_id);
 
         // Get existing key
-        let mut api_keys = self.get_all_keys().await?;
-        let api_key_index = api_keys.iter().position(|k| k.id == key_id)
-            .ok_or_else(|| ApiError::key_not_found(key_id.to_string()))?;
-
-        let mut api_key = api_keys.remove(api_key_index);
+        let mut api_key = self.owned_key(key_id, "update").await?;
 
         // Update fields
         if let Some(name) = request.name {
@@ -194,6 +396,18 @@ impl ApiManagerService {
             api_key.status = status;
         }
 
+        if request.clear_monthly_quota {
+            api_key.monthly_quota = None;
+        } else if let Some(monthly_quota) = request.monthly_quota {
+            api_key.monthly_quota = Some(monthly_quota);
+        }
+
+        if request.clear_expires_at {
+            api_key.expires_at = None;
+        } else if let Some(expires_at) = request.expires_at {
+            api_key.expires_at = Some(expires_at);
+        }
+
         api_key.updated_at = chrono::Utc::now();
 
         // Store updated key
@@ -210,13 +424,11 @@ impl ApiManagerService {
         debug!("Deleting API key: {}", key_id);
 
         // Get the API key first to get its name for logging
-        let mut data_persistence = self.data_persistence.write().await;
-        let api_key = data_persistence.get_api_key_by_id(key_id).await?
-            .ok_or_else(|| ApiError::key_not_found(key_id.to_string()))?;
-
+        let api_key = self.owned_key(key_id, "delete").await?;
         let key_name = api_key.name.clone();
 
         // Delete from database
+        let mut data_persistence = self.data_persistence.write().await;
         data_persistence.delete_api_key(key_id).await?;
         drop(data_persistence);
 
@@ -238,15 +450,14 @@ impl ApiManagerService {
     /// Test an API key connection
     pub async fn test_key(&self, key_id: Uuid) -> AppResult<ApiKeyTestResult> {
         debug!("Testing API key: {}", key_id);
+        air_gap::ensure_online("API key testing")?;
 
         // Get the API key
-        let api_keys = self.get_all_keys().await?;
-        let api_key = api_keys.iter().find(|k| k.id == key_id)
-            .ok_or_else(|| ApiError::key_not_found(key_id.to_string()))?;
+        let api_key = &self.owned_key(key_id, "test").await?;
 
-        // Decrypt the API key
+        // Decrypt the API key; the plaintext is scrubbed when `decrypted_key` drops
         let security = self.security.read().await;
-        let decrypted_key = security.decrypt_string(&api_key.encrypted_key).await?;
+        let decrypted_key = security.decrypt_secret(&api_key.encrypted_key).await?;
         drop(security);
 
         let start_time = std::time::Instant::now();
@@ -254,22 +465,25 @@ impl ApiManagerService {
         // Test the key based on service type
         let test_result = match api_key.service {
             crate::models::api_key::ServiceProvider::OpenRouter => {
-                self.test_openrouter_key(&decrypted_key).await
+                self.test_openrouter_key(decrypted_key.expose()).await
             }
             crate::models::api_key::ServiceProvider::SerpApi => {
-                self.test_serpapi_key(&decrypted_key).await
+                self.test_serpapi_key(decrypted_key.expose()).await
             }
             crate::models::api_key::ServiceProvider::Jina => {
-                self.test_jina_key(&decrypted_key).await
+                self.test_jina_key(decrypted_key.expose()).await
             }
             crate::models::api_key::ServiceProvider::Firecrawl => {
-                self.test_firecrawl_key(&decrypted_key).await
+                self.test_firecrawl_key(decrypted_key.expose()).await
             }
             crate::models::api_key::ServiceProvider::Tavily => {
-                self.test_tavily_key(&decrypted_key).await
+                self.test_tavily_key(decrypted_key.expose()).await
             }
             crate::models::api_key::ServiceProvider::Exa => {
-                self.test_exa_key(&decrypted_key).await
+                self.test_exa_key(decrypted_key.expose()).await
+            }
+            crate::models::api_key::ServiceProvider::Custom(_) => {
+                self.test_custom_key(api_key.service, &decrypted_key).await
             }
         };
 
@@ -306,6 +520,18 @@ impl ApiManagerService {
         }
         drop(data_persistence);
 
+        // A passing test is proof the key works again
+        if result.success && self.key_rotator.promote_key(key_id).await {
+            let monitoring = self.monitoring.read().await;
+            if let Err(e) = monitoring.log_audit_event(
+                "api_key_promoted".to_string(),
+                format!("API key '{}' passed a test and is back in rotation", api_key.name),
+                Some(key_id.to_string())
+            ).await {
+                error!("Failed to log audit event: {}", e);
+            }
+        }
+
         debug!("API key test completed: {} (success: {})", key_id, result.success);
         Ok(result)
     }
@@ -429,6 +655,38 @@ impl ApiManagerService {
         Ok("Exa API key is valid and working (mock validation)".to_string())
     }
 
+    /// Test a custom provider's API key with its adapter's health check request
+    async fn test_custom_key(&self, service: crate::models::api_key::ServiceProvider, api_key: &SecretString) -> AppResult<String> {
+        debug!("Testing {:?} API key", service);
+
+        if self.service_integration.read().await.validate_service_api_key(service, api_key).await? {
+            Ok(format!("{} API key is valid", service.display_name()))
+        } else {
+            Err(ApiError::authentication_failed(service.name().to_string(),
+                format!("{} rejected the API key", service.display_name())).into())
+        }
+    }
+
+    /// Register a custom provider with the running service: it gets an integration, rate
+    /// limits, a monthly quota and a rotation config, and joins the fallback chains it can
+    /// serve. Its keys are added and rotated like any other provider's.
+    pub async fn register_provider_adapter(&self, adapter: Arc<dyn ProviderAdapter>) -> AppResult<crate::models::api_key::ServiceProvider> {
+        let service = provider_adapter::register_adapter(adapter.clone())?;
+
+        let integration = Box::new(AdapterIntegration::new(service, adapter.clone()));
+        self.service_integration.write().await.register_integration(integration).await?;
+        self.rate_limiter.update_config(service, RateLimitConfig::default_for_service(service)).await?;
+        self.rate_limiter.register_quota_config(service).await?;
+        self.key_rotator.register_rotation_config(service).await;
+
+        let mut fallback_config = self.fallback_router.get_config().await;
+        provider_adapter::join_fallback_chains(&mut fallback_config, service, adapter.as_ref());
+        self.fallback_router.update_config(fallback_config).await;
+
+        info!("Provider adapter registered: {}", service.display_name());
+        Ok(service)
+    }
+
     /// Import API keys from CSV content
     pub async fn import_keys_from_csv(&mut self, csv_content: &str) -> AppResult<ImportResult> {
         debug!("Importing API keys from CSV content");
@@ -488,6 +746,9 @@ impl ApiManagerService {
                         name: name.to_string(),
                         api_key: api_key.to_string(),
                         rate_limit,
+                        monthly_quota: None,
+                        expires_at: None,
+                        tenant_id: None,
                     };
 
                     match self.add_key(create_request).await {
@@ -543,6 +804,9 @@ impl ApiManagerService {
                 name: key_import.name.clone(),
                 api_key: key_import.api_key,
                 rate_limit: key_import.rate_limit,
+                monthly_quota: key_import.monthly_quota,
+                expires_at: key_import.expires_at,
+                tenant_id: key_import.tenant_id,
             };
 
             match self.add_key(create_request).await {
@@ -568,6 +832,134 @@ impl ApiManagerService {
         })
     }
 
+    /// Keys of the calling tenant matching a bulk operation filter
+    async fn keys_matching(&self, filter: &ApiKeyFilter) -> AppResult<Vec<ApiKey>> {
+        let scope = KeyScope::current();
+        Ok(self.get_all_keys().await?
+            .into_iter()
+            .filter(|k| scope.owns(k) && filter.matches(k))
+            .collect())
+    }
+
+    /// Set the status of every key matching `filter` in a single transaction
+    pub async fn bulk_update_status(&mut self, filter: ApiKeyFilter, status: ApiKeyStatus) -> AppResult<BulkOperationResult> {
+        let api_keys = self.keys_matching(&filter).await?;
+        debug!("Setting status {:?} on {} API keys", status, api_keys.len());
+
+        let key_ids: Vec<Uuid> = api_keys.iter().map(|k| k.id).collect();
+        let mut data_persistence = self.data_persistence.write().await;
+        let stored = data_persistence.update_api_key_statuses(&key_ids, &status).await;
+        drop(data_persistence);
+
+        // The update is all-or-nothing, so every key shares its outcome
+        let outcome = stored.as_ref().map_or_else(|e| e.to_string(), |_| format!("Status set to {:?}", status));
+        let results = api_keys.iter()
+            .map(|k| BulkKeyOutcome {
+                key_id: k.id,
+                name: k.name.clone(),
+                success: stored.is_ok(),
+                message: outcome.clone(),
+            })
+            .collect();
+
+        if stored.is_ok() {
+            let monitoring = self.monitoring.read().await;
+            for api_key in &api_keys {
+                if let Err(e) = monitoring.log_audit_event(
+                    "api_key_status_changed".to_string(),
+                    format!("API key '{}' status changed from {:?} to {:?} by bulk update", api_key.name, api_key.status, status),
+                    Some(api_key.id.to_string())
+                ).await {
+                    error!("Failed to log audit event: {}", e);
+                }
+            }
+        }
+
+        info!("Bulk status update {}: {} keys", if stored.is_ok() { "completed" } else { "rolled back" }, api_keys.len());
+        Ok(BulkOperationResult::from_outcomes(results))
+    }
+
+    /// Delete every key matching `filter` in a single transaction.
+    ///
+    /// An empty filter is refused rather than deleting every key.
+    pub async fn bulk_delete(&mut self, filter: ApiKeyFilter) -> AppResult<BulkOperationResult> {
+        if filter.is_empty() {
+            return Err(ApiError::invalid_configuration(
+                "bulk_delete".to_string(),
+                "Bulk delete requires a service, status or name pattern".to_string()
+            ).into());
+        }
+
+        let api_keys = self.keys_matching(&filter).await?;
+        debug!("Deleting {} API keys", api_keys.len());
+
+        let key_ids: Vec<Uuid> = api_keys.iter().map(|k| k.id).collect();
+        let mut data_persistence = self.data_persistence.write().await;
+        let deleted = data_persistence.delete_api_keys(&key_ids).await;
+        drop(data_persistence);
+
+        let outcome = deleted.as_ref().map_or_else(|e| e.to_string(), |_| "Deleted".to_string());
+        let results = api_keys.iter()
+            .map(|k| BulkKeyOutcome {
+                key_id: k.id,
+                name: k.name.clone(),
+                success: deleted.is_ok(),
+                message: outcome.clone(),
+            })
+            .collect();
+
+        if deleted.is_ok() {
+            let monitoring = self.monitoring.read().await;
+            for api_key in &api_keys {
+                if let Err(e) = monitoring.log_audit_event(
+                    "api_key_deleted".to_string(),
+                    format!("API key '{}' deleted by bulk delete", api_key.name),
+                    Some(api_key.id.to_string())
+                ).await {
+                    error!("Failed to log audit event: {}", e);
+                }
+            }
+        }
+
+        info!("Bulk delete {}: {} keys", if deleted.is_ok() { "completed" } else { "rolled back" }, api_keys.len());
+        Ok(BulkOperationResult::from_outcomes(results))
+    }
+
+    /// Test every key matching `filter`; each key is tested independently
+    pub async fn bulk_test(&self, filter: ApiKeyFilter) -> AppResult<BulkOperationResult> {
+        let api_keys = self.keys_matching(&filter).await?;
+        debug!("Testing {} API keys", api_keys.len());
+
+        let mut results = Vec::with_capacity(api_keys.len());
+        for api_key in &api_keys {
+            let (success, message) = match self.test_key(api_key.id).await {
+                Ok(result) => (result.success, result.message),
+                Err(e) => (false, e.to_string()),
+            };
+
+            let monitoring = self.monitoring.read().await;
+            if let Err(e) = monitoring.log_audit_event(
+                "api_key_tested".to_string(),
+                format!("API key '{}' {} bulk test: {}", api_key.name, if success { "passed" } else { "failed" }, message),
+                Some(api_key.id.to_string())
+            ).await {
+                error!("Failed to log audit event: {}", e);
+            }
+            drop(monitoring);
+
+            results.push(BulkKeyOutcome {
+                key_id: api_key.id,
+                name: api_key.name.clone(),
+                success,
+                message,
+            });
+        }
+
+        let result = BulkOperationResult::from_outcomes(results);
+        info!("Bulk test completed: {} passed, {} failed", result.successful_count, result.failed_count);
+        Ok(result)
+    }
+
     /// Export API keys to CSV format
     pub async fn export_keys_to_csv(&self) -> AppResult<String> {
         debug!("Exporting API keys to CSV format");
@@ -619,6 +1011,8 @@ impl ApiManagerService {
                 name: api_key.name,
                 rate_limit: api_key.rate_limit,
                 usage_count: api_key.usage_count,
+                monthly_quota: api_key.monthly_quota,
+                expires_at: api_key.expires_at,
                 status: api_key.status,
                 created_at: api_key.created_at,
             });
@@ -650,12 +1044,12 @@ impl ApiManagerService {
         for api_key in api_keys {
             // Check if key needs reset
             let mut key = api_key.clone();
-            if key.needs_reset() {
+            if key.needs_reset(self.clock.now()) {
                 // Note: In a real implementation, we'd update the key in the database
                 // For now, we'll just mark it as needing reset
             }
 
-            let is_available = key.is_available();
+            let is_available = key.is_available(self.clock.now());
             keys_with_status.push((key, is_available));
         }
 
@@ -671,9 +1065,10 @@ impl ApiManagerService {
         let api_key = data_persistence.get_api_key_by_id(key_id).await?
             .ok_or_else(|| ApiError::key_not_found(key_id.to_string()))?;
 
-        if api_key.needs_reset() {
+        let now = self.clock.now();
+        if api_key.needs_reset(now) {
             let mut updated_key = api_key.clone();
-            updated_key.reset_usage();
+            updated_key.reset_usage(now);
 
             data_persistence.store_api_key(&updated_key).await?;
             drop(data_persistence);
@@ -716,11 +1111,12 @@ impl ApiManagerService {
         let api_keys = self.get_all_keys().await?;
 
         // Filter keys for the requested service and find available ones
+        let now = self.clock.now();
         let available_keys: Vec<_> = api_keys
             .into_iter()
             .filter(|key| {
                 let key_service = format!("{:?}", key.service).to_lowercase();
-                key_service == service.to_lowercase() && key.is_available()
+                key_service == service.to_lowercase() && key.is_available(now)
             })
             .collect();
 
@@ -782,11 +1178,19 @@ impl ApiManagerService {
         self.rate_limiter.get_usage_status(api_key_id).await
     }
 
-    /// Record a request and check for rate limit violations
+    /// Record a request and check for rate limit violations; quota alerts are
+    /// kept with the other recent alerts
     pub async fn record_api_request(&self, api_key_id: Uuid, success: bool) -> AppResult<Option<RateLimitAlert>> {
+        self.rate_limiter.record_tenant_usage(KeyScope::current().tenant_id, api_key_id, success).await;
+        self.rate_limiter.record_quota_usage(api_key_id).await?;
         self.rate_limiter.record_request(api_key_id, success).await
     }
 
+    /// Requests a tenant has sent with each key, including system pool keys it used
+    pub async fn get_tenant_key_usage(&self, tenant_id: Option<Uuid>) -> Vec<TenantKeyUsage> {
+        self.rate_limiter.get_tenant_usage(tenant_id).await
+    }
+
     /// Enable or disable emergency stop
     pub async fn set_emergency_stop(&self, enabled: bool) -> AppResult<()> {
         self.rate_limiter.set_emergency_stop(enabled).await
@@ -837,9 +1241,91 @@ impl ApiManagerService {
         self.rate_limiter.get_all_configs().await
     }
 
-    /// Select the best available API key for a service using intelligent rotation
+    /// Update the monthly quota configuration for a service
+    pub async fn update_quota_config(&self, service: crate::models::api_key::ServiceProvider, config: QuotaConfig) -> AppResult<()> {
+        self.rate_limiter.update_quota_config(service, config).await
+    }
+
+    /// Get the monthly quota configuration for a service
+    pub async fn get_quota_config(&self, service: crate::models::api_key::ServiceProvider) -> QuotaConfig {
+        self.rate_limiter.get_quota_config(service).await
+    }
+
+    /// Get all monthly quota configurations
+    pub async fn get_all_quota_configs(&self) -> std::collections::HashMap<crate::models::api_key::ServiceProvider, QuotaConfig> {
+        self.rate_limiter.get_all_quota_configs().await
+    }
+
+    /// Replay recorded usage against a proposed rate limit configuration without applying it
+    pub async fn simulate_rate_limit_config(&self, service: crate::models::api_key::ServiceProvider, config: RateLimitConfig, lookback_days: u32) -> AppResult<RateLimitSimulation> {
+        self.rate_limiter.simulate_config(service, config, lookback_days).await
+    }
+
+    /// Requests the calling tenant's usable keys for `service` can still make before a
+    /// rate limit, key quota or the service's monthly cap stops them
+    pub async fn get_service_capacity(&self, service: crate::models::api_key::ServiceProvider) -> AppResult<ServiceCapacity> {
+        let config = self.rate_limiter.get_quota_config(service).await;
+        let cycle_start = usage_quota::billing_cycle_start(config.billing_cycle_day, chrono::Utc::now());
+
+        // The service cap is shared by every key of the service, whoever owns it
+        let service_used: u32 = {
+            let data_persistence = self.data_persistence.read().await;
+            data_persistence.get_all_api_keys().await?
+                .iter()
+                .filter(|key| key.service == service)
+                .map(|key| key.quota_used_in(cycle_start))
+                .sum()
+        };
+        let service_remaining = config.monthly_cap.map(|cap| cap.saturating_sub(service_used));
+
+        let mut usable = Vec::new();
+        for key in self.get_all_keys().await? {
+            if key.service != service || !key.is_available(self.clock.now()) {
+                continue;
+            }
+            let healthy = self.key_rotator.get_key_performance(key.id).await
+                .map_or(true, |metrics| metrics.is_available());
+            if healthy {
+                usable.push(key);
+            }
+        }
+
+        Ok(ServiceCapacity {
+            service,
+            usable_keys: usable.len() as u32,
+            remaining_requests: usage_quota::remaining_requests(&usable, cycle_start, service_remaining),
+        })
+    }
+
+    /// Select the best available API key of the calling tenant for a service using intelligent rotation
     pub async fn select_best_key_for_service(&self, service: crate::models::api_key::ServiceProvider) -> AppResult<Option<ApiKey>> {
-        self.key_rotator.select_best_key(service).await
+        self.key_rotator.select_best_key(service, &KeyScope::current()).await
+    }
+
+    /// The key a request to `service` would use now, leaving the rotation where it is
+    pub async fn preview_key_for_service(&self, service: crate::models::api_key::ServiceProvider) -> AppResult<Option<ApiKey>> {
+        self.key_rotator.preview_best_key(service, &KeyScope::current()).await
+    }
+
+    /// Circuit breaker state of a provider, as the fallback chains see it
+    pub async fn get_provider_circuit_state(&self, service: crate::models::api_key::ServiceProvider) -> CircuitState {
+        self.fallback_router.circuit_state(service, chrono::Utc::now()).await
+    }
+
+    /// Every circuit breaker, with its state, failure count and time to the next probe
+    pub async fn get_circuit_breaker_states(&self) -> Vec<CircuitBreakerStatus> {
+        self.fallback_router.breaker_statuses(chrono::Utc::now()).await
+    }
+
+    /// Close the breaker with `id` by hand, letting requests through again
+    pub async fn reset_circuit_breaker(&self, id: &str) -> AppResult<CircuitBreakerStatus> {
+        let provider = fallback_router::parse_provider_breaker_id(id)
+            .ok_or_else(|| AppError::NotFound { resource: format!("circuit breaker {}", id) })?;
+        self.fallback_router.reset_breaker(provider).await;
+        self.get_circuit_breaker_states().await
+            .into_iter()
+            .find(|status| status.id == id)
+            .ok_or_else(|| AppError::internal(format!("circuit breaker {} vanished after reset", id)))
     }
 
     /// Record request performance for key rotation optimization
@@ -897,30 +1383,178 @@ impl ApiManagerService {
         self.key_rotator.generate_rotation_report().await
     }
 
-    /// Make a service request through the integration framework
+    /// Decrypt a stored key for a single outbound call; never store the result
+    async fn decrypt_api_key(&self, api_key: &ApiKey) -> AppResult<SecretString> {
+        let security = self.security.read().await;
+        security.decrypt_secret(&api_key.encrypted_key).await
+    }
+
+    /// Make a service request through the integration framework, failing over to another
+    /// of the service's keys, up to the rotation config's `max_failover_rotations`, when the
+    /// selected key is rate limited or rejected
     pub async fn make_service_request(&self, service: crate::models::api_key::ServiceProvider, request: ServiceRequest) -> AppResult<ServiceResponse> {
-        // Get the best available key for the service
-        let api_key = self.select_best_key_for_service(service).await?
-            .ok_or_else(|| ApiError::key_not_found(format!("No available keys for service: {:?}", service)))?;
+        // Mocked and replayed steps are served without spending keys or quota
+        if let Some(settings) = response_recorder::mock_settings() {
+            let mocked = mock_providers::respond(service, &request, settings).await;
+            call_meter::record(&mocked);
+            return content_policy::reject_refusals(service, mocked);
+        }
+        if let Some(response) = self.response_recorder.replay(&request)? {
+            let replayed = Ok(response);
+            call_meter::record(&replayed);
+            return content_policy::reject_refusals(service, replayed);
+        }
+        air_gap::ensure_online(&format!("{:?} requests", service))?;
+
+        // Get the best available key for the service among those the calling tenant may use
+        let scope = KeyScope::current();
+        let mut api_key = self.select_best_key_for_service(service).await?
+            .ok_or_else(|| ApiError::key_not_found(format!("No available keys for service {:?} for {}", service, scope.label())))?;
+
+        // A key that is rate limited or rejected hands the request to the next healthy key,
+        // as long as the provider's circuit stays closed and the service cap has room
+        let max_rotations = self.key_rotator.get_rotation_config(service).await.max_failover_rotations;
+        let mut tried = Vec::new();
+        loop {
+            let quota = self.get_key_usage_status(api_key.id).await?.quota;
+            if quota.service_remaining == Some(0) {
+                // The cap is shared by every key of the service, so another key cannot help
+                return Err(ApiError::quota_exceeded(format!("{:?}", service)).into());
+            }
+
+            let result = if quota.is_exhausted() {
+                Err(ApiError::quota_exceeded(format!("{:?}", service)).into())
+            } else if !self.can_make_request(api_key.id).await? {
+                Err(ApiError::rate_limit_exceeded(format!("Rate limit exceeded for service: {:?}", service)))
+            } else {
+                self.make_request_with_key(service, request.clone(), &api_key).await
+            };
 
-        // Check rate limits
-        if !self.can_make_request(api_key.id).await? {
-            return Err(ApiError::rate_limit_exceeded(format!("Rate limit exceeded for service: {:?}", service)));
+            let Some(reason) = Self::failover_reason(service, &result) else {
+                return result;
+            };
+            tried.push(api_key.id);
+            // Rotating keys is pointless once the failures have opened the circuit
+            let circuit_open = matches!(
+                self.fallback_router.circuit_state(service, chrono::Utc::now()).await,
+                CircuitState::Open { .. }
+            );
+            if tried.len() as u32 > max_rotations || circuit_open {
+                return result;
+            }
+            let Some(next_key) = self.key_rotator.select_best_key_excluding(service, &scope, &tried).await? else {
+                return result;
+            };
+
+            self.key_rotator.record_failover(KeyFailover {
+                service,
+                request_id: request.request_id,
+                from_key_id: api_key.id,
+                to_key_id: next_key.id,
+                reason,
+                failed_over_at: chrono::Utc::now(),
+            }).await;
+            api_key = next_key;
         }
+    }
+
+    /// Why a request's result should be retried with another key of its service, if it should
+    fn failover_reason(service: crate::models::api_key::ServiceProvider, result: &AppResult<ServiceResponse>) -> Option<FailureCategory> {
+        let category = match result {
+            Ok(response) if response.success => return None,
+            Ok(response) => FailureCategory::of(&ApiError::request_failed(
+                format!("{:?}", service),
+                response.status_code,
+                response.error_message.clone().unwrap_or_default(),
+            )),
+            Err(crate::error::AppError::Api(e)) => FailureCategory::of(e),
+            Err(_) => return None,
+        };
+        category.warrants_failover().then_some(category)
+    }
+
+    /// Send a request with `api_key`, recording the outcome against the key
+    async fn make_request_with_key(
+        &self,
+        service: crate::models::api_key::ServiceProvider,
+        request: ServiceRequest,
+        api_key: &ApiKey,
+    ) -> AppResult<ServiceResponse> {
+        let decrypted_key = self.decrypt_api_key(api_key).await?;
+        let endpoint = request.endpoint.clone();
+
+        let span = info_span!(
+            "provider.request",
+            provider = ?service,
+            request.id = %request.request_id,
+            http.method = %request.method,
+            endpoint = %request.endpoint,
+            http.status_code = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
 
         let start_time = std::time::Instant::now();
 
+        let recorded_request = response_recorder::is_recording().then(|| request.clone());
+
         // Make the request through service integration
         let service_integration = self.service_integration.read().await;
-        let result = service_integration.make_service_request(service, request, &api_key).await;
+        let result = service_integration.make_service_request(service, request, &decrypted_key)
+            .instrument(span.clone())
+            .await;
         drop(service_integration);
+        drop(decrypted_key);
+        call_meter::record(&result);
 
         let response_time = start_time.elapsed().as_millis() as u32;
-        let success = result.is_ok();
+        // A response the provider answered with an error status did not succeed either
+        let success = matches!(&result, Ok(response) if response.success);
 
-        // Record performance metrics
-        if let Err(e) = self.record_key_performance(api_key.id, success, response_time).await {
-            error!("Failed to record key performance: {}", e);
+        span.record("duration_ms", response_time);
+        if let Ok(response) = &result {
+            span.record("http.status_code", response.status_code);
+        }
+
+        if let Some(recorded_request) = &recorded_request {
+            self.response_recorder.record(recorded_request, &result);
+        }
+
+        // A query the provider refuses on content grounds fails the request, though the
+        // key itself worked
+        let result = content_policy::reject_refusals(service, result);
+        let refused = match &result {
+            Err(crate::error::AppError::Api(e)) if e.is_content_policy_rejection() => {
+                warn!("{}", e);
+                true
+            }
+            _ => false,
+        };
+
+        // Record performance metrics; failures are categorised so a rejected key is
+        // demoted while rate limits and provider outages only cool it down
+        let response_failure = match &result {
+            Ok(response) if !response.success => Some(ApiError::request_failed(
+                format!("{:?}", service),
+                response.status_code,
+                response.error_message.clone().unwrap_or_default(),
+            )),
+            _ => None,
+        };
+        let failure = match &result {
+            Err(crate::error::AppError::Api(e)) if !refused => Some(e),
+            _ => response_failure.as_ref(),
+        };
+        match failure {
+            Some(error) => match self.key_rotator.record_request_error(api_key.id, error, response_time).await {
+                Ok(Some(demotion)) => self.notify_key_demoted(api_key, &demotion).await,
+                Ok(None) => {}
+                Err(e) => error!("Failed to record key performance: {}", e),
+            },
+            None => {
+                if let Err(e) = self.record_key_performance(api_key.id, success || refused, response_time).await {
+                    error!("Failed to record key performance: {}", e);
+                }
+            }
         }
 
         // Record rate limiting
@@ -928,17 +1562,541 @@ impl ApiManagerService {
             error!("Failed to record API request: {}", e);
         }
 
+        // Record what we counted, and what the provider says it counted, for reconciliation
+        let service_name = format!("{:?}", service).to_lowercase();
+        let reported = result.as_ref().ok().and_then(|response| usage_reconciliation::provider_reported_usage(&response.headers));
+        let mut data_persistence = self.data_persistence.write().await;
+        if let Err(e) = data_persistence.record_api_usage(api_key.id, &service_name, Some(&endpoint), success, response_time).await {
+            error!("Failed to record API usage statistics: {}", e);
+        }
+        if let Some(reported) = reported {
+            let now = chrono::Utc::now();
+            let snapshot = crate::models::usage_report::ProviderUsageSnapshot {
+                api_key_id: api_key.id,
+                service: service_name,
+                date_bucket: now.format("%Y-%m-%d").to_string(),
+                reported_used: reported.used,
+                reported_limit: reported.limit,
+                observed_at: now,
+            };
+            if let Err(e) = data_persistence.record_provider_usage_snapshot(&snapshot).await {
+                error!("Failed to record provider-reported usage: {}", e);
+            }
+        }
+        drop(data_persistence);
+
         result
     }
 
+    /// Per provider and per key usage from `from` through `to`, with the provider's own
+    /// counts beside ours, for reconciling against provider bills
+    pub async fn get_usage_reconciliation_report(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> AppResult<UsageReconciliationReport> {
+        let keys = self.get_all_keys().await?;
+        self.usage_reconciler.build_report(from, to, &keys).await
+    }
+
+    /// The usage reconciliation report as CSV
+    pub async fn export_usage_reconciliation_csv(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> AppResult<String> {
+        Ok(self.get_usage_reconciliation_report(from, to).await?.to_csv())
+    }
+
+    /// The usage reconciliation report as JSON
+    pub async fn export_usage_reconciliation_json(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> AppResult<String> {
+        let report = self.get_usage_reconciliation_report(from, to).await?;
+        serde_json::to_string_pretty(&report)
+            .map_err(|e| ApiError::invalid_configuration("json".to_string(), format!("JSON serialization error: {}", e)).into())
+    }
+
+    /// Compare counted and provider-reported usage of every key now, alerting on keys
+    /// past the divergence threshold
+    pub async fn check_usage_divergence(&self) -> AppResult<Vec<crate::models::UsageDivergence>> {
+        self.usage_reconciler.check_divergence().await
+    }
+
+    /// Recorded divergence checks of the last `days`, optionally for one key
+    pub async fn get_usage_divergence_history(&self, days: u32, api_key_id: Option<Uuid>) -> AppResult<Vec<crate::models::UsageDivergence>> {
+        self.usage_reconciler.get_history(days, api_key_id).await
+    }
+
+    /// Get the usage divergence alerting configuration
+    pub async fn get_divergence_config(&self) -> DivergenceConfig {
+        self.usage_reconciler.get_config().await
+    }
+
+    /// Update the usage divergence alerting configuration
+    pub async fn update_divergence_config(&self, config: DivergenceConfig) -> AppResult<()> {
+        self.usage_reconciler.update_config(config).await
+    }
+
+    /// Get a user's notification preferences, the defaults if they have set none
+    pub async fn get_notification_preferences(&self, user_id: Uuid) -> AppResult<crate::models::NotificationPreferences> {
+        self.rate_limiter.notifications().get_preferences(user_id).await
+    }
+
+    /// Update a user's notification preferences
+    pub async fn update_notification_preferences(&self, preferences: crate::models::NotificationPreferences) -> AppResult<()> {
+        self.rate_limiter.notifications().update_preferences(preferences).await
+    }
+
+    /// Get a user's in-app notifications, newest first
+    pub async fn get_notification_inbox(&self, user_id: Uuid) -> AppResult<Vec<crate::models::Notification>> {
+        self.rate_limiter.notifications().inbox(user_id).await
+    }
+
+    /// Deliver a notification channel, such as email, through `sink`
+    pub async fn register_notification_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.rate_limiter.notifications().register_sink(sink).await
+    }
+
+    /// Look up the users a tenant's notifications go to in `directory`
+    pub async fn register_notification_directory(&self, directory: Arc<dyn RecipientDirectory>) {
+        self.rate_limiter.notifications().register_directory(directory).await
+    }
+
+    /// Get the egress profiles workflows can select
+    pub async fn get_egress_profiles(&self) -> Vec<EgressProfile> {
+        self.egress_registry.get_profiles().await
+    }
+
+    /// Replace the egress profiles
+    pub async fn update_egress_profiles(&self, profiles: Vec<EgressProfile>) -> AppResult<()> {
+        self.egress_registry.set_profiles(profiles.clone()).await?;
+        self.data_persistence.read().await.save_setting(egress::EGRESS_PROFILES_SETTING, &profiles).await
+    }
+
+    /// Extracted page content shared across workflows, for methodologies to extract through
+    pub fn content_cache(&self) -> &ContentCache {
+        &self.content_cache
+    }
+
+    /// Get the content cache's hit rate and the provider requests it saved
+    pub fn get_content_cache_stats(&self) -> ContentCacheStats {
+        self.content_cache.stats()
+    }
+
+    pub fn get_content_cache_config(&self) -> ContentCacheConfig {
+        self.content_cache.config()
+    }
+
+    /// Replace the content cache configuration; turning it off drops what it holds
+    pub fn update_content_cache_config(&self, config: ContentCacheConfig) -> AppResult<()> {
+        self.content_cache.set_config(config)
+    }
+
+    /// Robots.txt checks and per-host spacing, for methodologies to fetch pages through
+    pub fn crawl_policy(&self) -> &CrawlPolicy {
+        &self.crawl_policy
+    }
+
+    pub fn get_crawl_policy_config(&self) -> CrawlPolicyConfig {
+        self.crawl_policy.config()
+    }
+
+    /// Replace the crawl policy configuration
+    pub fn update_crawl_policy_config(&self, config: CrawlPolicyConfig) -> AppResult<()> {
+        self.crawl_policy.set_config(config)
+    }
+
+    /// The clients of the named egress profile, for scoping a workflow's requests with
+    /// `egress::egress_scope`
+    pub async fn egress_clients(&self, name: &str) -> AppResult<Arc<EgressClients>> {
+        self.egress_registry.resolve(name).await
+    }
+
+    /// Make a request for `step_type`, moving down the step's fallback chain when a
+    /// provider's circuit is open, it is rate limited or over the cost ceiling, or the
+    /// request fails. A content-policy refusal ends the chain, since every provider would
+    /// be sent the same query. `build_request` shapes the request for each provider and
+    /// returns `None` for providers that cannot serve the step.
+    pub async fn make_request_with_fallback<F>(
+        &self,
+        step_type: &str,
+        primary: crate::models::api_key::ServiceProvider,
+        build_request: F,
+    ) -> AppResult<FallbackResponse>
+    where
+        F: Fn(crate::models::api_key::ServiceProvider) -> Option<ServiceRequest>,
+    {
+        let mut attempts = Vec::new();
+
+        for provider in self.fallback_router.chain_for(step_type, primary).await {
+            if let Some(outcome) = self.fallback_router.exceeds_cost_ceiling(step_type, provider).await {
+                attempts.push(ProviderAttempt { provider, outcome });
+                continue;
+            }
+            let request = match build_request(provider) {
+                Some(request) => request,
+                None => {
+                    attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::Unsupported });
+                    continue;
+                }
+            };
+            // Checked last: a half-open circuit hands out its single probe here
+            if !self.fallback_router.allow_request(provider, chrono::Utc::now()).await {
+                attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::CircuitOpen });
+                continue;
+            }
+
+            let outcome = match self.make_service_request(provider, request).await {
+                Ok(response) if response.success => {
+                    self.fallback_router.record_success(provider).await;
+                    attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::Served });
+                    if attempts.len() > 1 {
+                        info!("{} served by fallback provider {:?} after {:?}", step_type, provider, attempts);
+                    }
+                    return Ok(FallbackResponse { served_by: provider, response, attempts });
+                }
+                // Another provider would be sent the same query
+                Err(crate::error::AppError::Api(e)) if e.is_content_policy_rejection() => {
+                    self.fallback_router.release_probe(provider).await;
+                    return Err(e.into());
+                }
+                // No provider is reachable in offline mode, and none is at fault
+                Err(e) if e.is_offline() => {
+                    self.fallback_router.release_probe(provider).await;
+                    return Err(e);
+                }
+                result => self.failed_attempt_outcome(provider, result).await,
+            };
+            debug!("Provider {:?} did not serve {}: {:?}", provider, step_type, outcome);
+            attempts.push(ProviderAttempt { provider, outcome });
+        }
+
+        Err(Self::chain_exhausted(step_type, &attempts))
+    }
+
+    /// Make a request for `step_type` the way its chain is configured: racing its
+    /// providers, or trying them one after another
+    pub async fn make_step_request<F>(
+        &self,
+        step_type: &str,
+        primary: crate::models::api_key::ServiceProvider,
+        build_request: F,
+    ) -> AppResult<FallbackResponse>
+    where
+        F: Fn(crate::models::api_key::ServiceProvider) -> Option<ServiceRequest>,
+    {
+        match self.fallback_router.mode_for(step_type).await {
+            ChainMode::Race(race) => self.make_request_racing(step_type, primary, &race, build_request).await,
+            ChainMode::Sequential => self.make_request_with_fallback(step_type, primary, build_request).await,
+        }
+    }
+
+    /// Search the web for up to `num_results` results. The first page comes from the web
+    /// search chain as any step's would; when it falls short, the provider that answered
+    /// is paged through while it has more, then the rest of the chain tops the results up.
+    /// Links repeated across pages or providers are kept once, sources published before
+    /// `recency` allows are dropped, and the outcome reports how many results were fetched
+    /// against how many were asked for.
+    pub async fn search_web(
+        &self,
+        primary: crate::models::api_key::ServiceProvider,
+        query: &str,
+        num_results: u32,
+        recency: crate::models::research_workflow::SearchRecency,
+    ) -> AppResult<WebSearchOutcome> {
+        let mut search = WebSearch::new(query, num_results).with_recency(recency);
+
+        let first = self.make_step_request(fallback_router::WEB_SEARCH, primary, |provider| search.page_request(provider)).await?;
+        let asked_for = search.page_size(first.served_by);
+        search.record_attempts(first.attempts);
+        let mut more = search.record_page(first.served_by, asked_for, &first.response);
+        let mut provider = first.served_by;
+
+        loop {
+            // Further pages go to the provider that just answered, through the same key
+            // rotation and rate limits as any request to it
+            while more && search.remaining() > 0 {
+                let Some(request) = search.page_request(provider) else { break };
+                let asked_for = search.page_size(provider);
+                match self.make_service_request(provider, request).await {
+                    Ok(response) if response.success => {
+                        self.fallback_router.record_success(provider).await;
+                        more = search.record_page(provider, asked_for, &response);
+                    }
+                    Err(e) if e.is_offline() => return Err(e),
+                    result => {
+                        let outcome = self.failed_attempt_outcome(provider, result).await;
+                        debug!("Stopped paging {:?} web search results: {:?}", provider, outcome);
+                        search.note(format!("{:?} stopped paging: {:?}", provider, outcome));
+                        search.record_attempts(vec![ProviderAttempt { provider, outcome }]);
+                        more = false;
+                    }
+                }
+            }
+            if search.remaining() == 0 {
+                break;
+            }
+
+            // Top up from the providers further down the chain that have not answered yet
+            let tried = search.tried().to_vec();
+            let top_up = self.make_request_with_fallback(fallback_router::WEB_SEARCH, primary, |candidate| {
+                if tried.contains(&candidate) { None } else { search.page_request(candidate) }
+            }).await;
+            match top_up {
+                Ok(response) => {
+                    let asked_for = search.page_size(response.served_by);
+                    search.record_attempts(response.attempts);
+                    more = search.record_page(response.served_by, asked_for, &response.response);
+                    provider = response.served_by;
+                }
+                Err(e) if e.is_offline() => return Err(e),
+                Err(e) => {
+                    debug!("No provider left to top up web search results: {}", e);
+                    search.note("no other provider could add results");
+                    break;
+                }
+            }
+        }
+
+        let outcome = search.finish();
+        if let Some(reason) = &outcome.shortfall_reason {
+            info!("Web search fetched {} of {} requested results: {}", outcome.fetched, outcome.requested, reason);
+        }
+        Ok(outcome)
+    }
+
+    /// Race `step_type`'s request across its chain: up to `race.width` providers are in
+    /// flight at once and the first response scoring `race.min_quality` or better wins,
+    /// cancelling the rest. A provider that fails or answers poorly frees its slot for the
+    /// next one in the chain. Circuit breakers, rate limits and the per-request cost ceiling
+    /// apply to each provider as in `make_request_with_fallback`, and the race's estimated
+    /// total spend stays within `race.max_total_cost`.
+    pub async fn make_request_racing<F>(
+        &self,
+        step_type: &str,
+        primary: crate::models::api_key::ServiceProvider,
+        race: &RaceConfig,
+        build_request: F,
+    ) -> AppResult<FallbackResponse>
+    where
+        F: Fn(crate::models::api_key::ServiceProvider) -> Option<ServiceRequest>,
+    {
+        use futures::stream::{FuturesUnordered, StreamExt};
+
+        let mut attempts = Vec::new();
+        let mut chain = self.fallback_router.chain_for(step_type, primary).await.into_iter();
+        let mut in_flight = FuturesUnordered::new();
+        let mut racing = Vec::new();
+        let mut spent = 0.0;
+
+        loop {
+            while racing.len() < race.width.max(1) {
+                let Some(provider) = chain.next() else { break };
+                if let Some(outcome) = self.fallback_router.exceeds_cost_ceiling(step_type, provider).await {
+                    attempts.push(ProviderAttempt { provider, outcome });
+                    continue;
+                }
+                let cost = self.fallback_router.provider_cost(provider).await;
+                if let Some(outcome) = race.exceeds_total_cost(spent, cost) {
+                    attempts.push(ProviderAttempt { provider, outcome });
+                    continue;
+                }
+                let Some(request) = build_request(provider) else {
+                    attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::Unsupported });
+                    continue;
+                };
+                if !self.fallback_router.allow_request(provider, chrono::Utc::now()).await {
+                    attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::CircuitOpen });
+                    continue;
+                }
+
+                spent += cost;
+                racing.push(provider);
+                in_flight.push(async move { (provider, self.make_service_request(provider, request).await) });
+            }
+
+            let Some((provider, result)) = in_flight.next().await else { break };
+            racing.retain(|p| *p != provider);
+
+            let outcome = match result {
+                Ok(response) if response.success => {
+                    self.fallback_router.record_success(provider).await;
+                    let quality = fallback_router::response_quality(&response);
+                    if quality >= race.min_quality {
+                        attempts.push(ProviderAttempt { provider, outcome: AttemptOutcome::Served });
+                        // Dropping the losers' futures aborts their requests
+                        drop(in_flight);
+                        for loser in &racing {
+                            self.fallback_router.release_probe(*loser).await;
+                        }
+                        attempts.extend(racing.into_iter().map(|provider| ProviderAttempt { provider, outcome: AttemptOutcome::Cancelled }));
+                        info!("{} race won by {:?} ({:?})", step_type, provider, attempts);
+                        return Ok(FallbackResponse { served_by: provider, response, attempts });
+                    }
+                    AttemptOutcome::BelowQuality { quality, threshold: race.min_quality }
+                }
+                Err(crate::error::AppError::Api(e)) if e.is_content_policy_rejection() => {
+                    self.fallback_router.release_probe(provider).await;
+                    return Err(e.into());
+                }
+                Err(e) if e.is_offline() => {
+                    self.fallback_router.release_probe(provider).await;
+                    return Err(e);
+                }
+                result => self.failed_attempt_outcome(provider, result).await,
+            };
+            debug!("Provider {:?} did not win the {} race: {:?}", provider, step_type, outcome);
+            attempts.push(ProviderAttempt { provider, outcome });
+        }
+
+        Err(Self::chain_exhausted(step_type, &attempts))
+    }
+
+    /// Outcome of a request that did not come back successful. Quota and missing keys say
+    /// nothing about the provider's health, so only other failures count against its circuit.
+    async fn failed_attempt_outcome(
+        &self,
+        provider: crate::models::api_key::ServiceProvider,
+        result: AppResult<ServiceResponse>,
+    ) -> AttemptOutcome {
+        match result {
+            Ok(response) => {
+                self.fallback_router.record_failure(provider, self.clock.now()).await;
+                AttemptOutcome::Failed(response.error_message.unwrap_or_else(|| format!("HTTP {}", response.status_code)))
+            }
+            Err(crate::error::AppError::Api(e)) if e.is_rate_limit() => {
+                self.fallback_router.release_probe(provider).await;
+                AttemptOutcome::RateLimited(e.to_string())
+            }
+            Err(crate::error::AppError::Api(ApiError::KeyNotFound { .. } | ApiError::KeyExpired { .. })) => {
+                self.fallback_router.release_probe(provider).await;
+                AttemptOutcome::NoAvailableKey
+            }
+            Err(e) => {
+                self.fallback_router.record_failure(provider, self.clock.now()).await;
+                AttemptOutcome::Failed(e.to_string())
+            }
+        }
+    }
+
+    fn chain_exhausted(step_type: &str, attempts: &[ProviderAttempt]) -> crate::error::AppError {
+        ApiError::ServiceUnavailable {
+            service: format!("{} (tried {})", step_type, attempts.iter()
+                .map(|attempt| format!("{:?}: {:?}", attempt.provider, attempt.outcome))
+                .collect::<Vec<_>>()
+                .join(", ")),
+        }.into()
+    }
+
+    /// Make an OpenRouter chat request for a model `role`, moving down the role's models
+    /// when one errors or is unavailable. Rate limits, missing keys and content-policy
+    /// refusals apply to every model of the provider, so they end the attempt instead.
+    pub async fn make_chat_request_with_model_fallback<F>(
+        &self,
+        role: &str,
+        build_request: F,
+    ) -> AppResult<ModelFallbackResponse>
+    where
+        F: Fn(&str) -> ServiceRequest,
+    {
+        let provider = crate::models::api_key::ServiceProvider::OpenRouter;
+        let mut attempts = Vec::new();
+
+        for model in self.model_router.models_for(role).await? {
+            let outcome = match self.make_service_request(provider, build_request(&model)).await {
+                Ok(response) if response.success => {
+                    attempts.push(ModelAttempt { model: model.clone(), outcome: AttemptOutcome::Served });
+                    if attempts.len() > 1 {
+                        info!("{} served by fallback model {} after {:?}", role, model, attempts);
+                    }
+                    return Ok(ModelFallbackResponse { served_by_model: model, response, attempts });
+                }
+                Ok(response) => {
+                    AttemptOutcome::Failed(response.error_message.unwrap_or_else(|| format!("HTTP {}", response.status_code)))
+                }
+                Err(crate::error::AppError::Api(e)) if e.is_rate_limit() || e.is_content_policy_rejection() => return Err(e.into()),
+                Err(e @ crate::error::AppError::Api(ApiError::KeyNotFound { .. } | ApiError::KeyExpired { .. })) => return Err(e),
+                Err(e) if e.is_offline() => return Err(e),
+                Err(e) => AttemptOutcome::Failed(e.to_string()),
+            };
+            debug!("Model {} did not serve {}: {:?}", model, role, outcome);
+            attempts.push(ModelAttempt { model, outcome });
+        }
+
+        Err(ApiError::ServiceUnavailable {
+            service: format!("{} models (tried {})", role, attempts.iter()
+                .map(|attempt| format!("{}: {:?}", attempt.model, attempt.outcome))
+                .collect::<Vec<_>>()
+                .join(", ")),
+        }.into())
+    }
+
+    /// Get the model routing policy
+    pub async fn get_model_routing_policy(&self) -> ModelRoutingPolicy {
+        self.model_router.get_policy().await
+    }
+
+    /// Token budget of a request for a model `role` producing up to `max_tokens`
+    pub async fn model_context_budget(&self, role: &str, max_tokens: u32) -> ContextBudget {
+        self.model_router.get_policy().await.context_budget_for(role, max_tokens)
+    }
+
+    /// Update the model routing policy
+    pub async fn update_model_routing_policy(&self, policy: ModelRoutingPolicy) -> AppResult<()> {
+        self.model_router.update_policy(policy).await
+    }
+
+    /// Get the provider calls recorded for a workflow
+    pub async fn get_provider_recording(&self, workflow_id: Uuid) -> AppResult<Vec<RecordedExchange>> {
+        self.response_recorder.get_recording(workflow_id)
+    }
+
+    /// Store provider calls recorded by another installation as a workflow's recording
+    pub async fn import_provider_recording(&self, workflow_id: Uuid, exchanges: &[RecordedExchange]) -> AppResult<()> {
+        self.response_recorder.import_recording(workflow_id, exchanges)
+    }
+
+    /// Delete the provider calls recorded for a workflow
+    pub async fn delete_provider_recording(&self, workflow_id: Uuid) -> AppResult<bool> {
+        self.response_recorder.delete_recording(workflow_id)
+    }
+
+    /// Get the provider fallback chains
+    pub async fn get_fallback_config(&self) -> FallbackConfig {
+        self.fallback_router.get_config().await
+    }
+
+    /// Update the provider fallback chains
+    pub async fn update_fallback_config(&self, config: FallbackConfig) -> AppResult<()> {
+        self.fallback_router.update_config(config).await;
+        Ok(())
+    }
+
+    /// Raise an alert and audit event for a key taken out of rotation
+    async fn notify_key_demoted(&self, api_key: &ApiKey, demotion: &KeyDemotion) {
+        let message = format!("API key '{}' ({:?}) demoted: {}", api_key.name, api_key.service, demotion.reason);
+
+        if let Err(e) = self.rate_limiter.raise_alert(api_key.id, AlertType::KeyDemoted, message.clone()).await {
+            error!("Failed to raise key demotion alert: {}", e);
+        }
+
+        let monitoring = self.monitoring.read().await;
+        if let Err(e) = monitoring.log_audit_event(
+            "api_key_demoted".to_string(),
+            message,
+            Some(api_key.id.to_string())
+        ).await {
+            error!("Failed to log audit event: {}", e);
+        }
+    }
+
     /// Check service health
     pub async fn check_service_health(&self, service: crate::models::api_key::ServiceProvider) -> AppResult<ServiceHealth> {
+        air_gap::ensure_online("service health checks")?;
+
         // Get the best available key for the service
         let api_key = self.select_best_key_for_service(service).await?
             .ok_or_else(|| ApiError::key_not_found(format!("No available keys for service: {:?}", service)))?;
 
+        let decrypted_key = self.decrypt_api_key(&api_key).await?;
+
         let service_integration = self.service_integration.read().await;
-        let health = service_integration.check_service_health(service, &api_key).await?;
+        let health = service_integration.check_service_health(service, &decrypted_key).await?;
         drop(service_integration);
 
         Ok(health)
@@ -976,8 +2134,10 @@ impl ApiManagerService {
 
     /// Validate API key for a service
     pub async fn validate_service_api_key(&self, service: crate::models::api_key::ServiceProvider, api_key: &ApiKey) -> AppResult<bool> {
+        air_gap::ensure_online("API key validation")?;
+        let decrypted_key = self.decrypt_api_key(api_key).await?;
         let service_integration = self.service_integration.read().await;
-        service_integration.validate_service_api_key(service, api_key).await
+        service_integration.validate_service_api_key(service, &decrypted_key).await
     }
 
     /// Get available endpoints for a service
@@ -1002,8 +2162,9 @@ impl ApiManagerService {
     pub async fn start_background_tasks(&self) -> AppResult<()> {
         info!("Starting API manager background tasks...");
 
-        // Start rate limit monitoring task
+        // Start rate limit and key expiry monitoring task
         let rate_limiter = self.rate_limiter.clone();
+        let key_rotator = self.key_rotator.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // Check every 5 minutes
 
@@ -1015,6 +2176,37 @@ impl ApiManagerService {
                     error!("Failed to check rate limit thresholds: {}", e);
                 }
 
+                // Send notifications held for quiet hours or digests that are now due
+                if let Err(e) = rate_limiter.notifications().flush_held(chrono::Utc::now()).await {
+                    error!("Failed to send held notifications: {}", e);
+                }
+
+                // Remind about keys nearing expiry so they are rotated before they fail
+                match key_rotator.check_key_expiry().await {
+                    Ok(notices) => {
+                        for notice in notices {
+                            let (alert_type, message) = if notice.expired {
+                                (AlertType::KeyExpired, format!(
+                                    "API key '{}' expired at {} and is no longer used",
+                                    notice.name, notice.expires_at.format("%Y-%m-%d %H:%M UTC")
+                                ))
+                            } else {
+                                (AlertType::KeyExpiring, format!(
+                                    "API key '{}' expires in {} days ({}); rotate it before then",
+                                    notice.name,
+                                    (notice.expires_at - chrono::Utc::now()).num_days(),
+                                    notice.expires_at.format("%Y-%m-%d")
+                                ))
+                            };
+                            warn!("{}", message);
+                            if let Err(e) = rate_limiter.raise_alert(notice.api_key_id, alert_type, message).await {
+                                error!("Failed to raise key expiry alert: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => error!("Failed to check API key expiry: {}", e),
+                }
+
                 // Clear old alerts (older than 24 hours)
                 if let Err(e) = rate_limiter.clear_old_alerts(24).await {
                     error!("Failed to clear old alerts: {}", e);
@@ -1024,6 +2216,7 @@ impl ApiManagerService {
 
         // Start daily report generation task
         let rate_limiter_report = self.rate_limiter.clone();
+        let usage_reconciler = self.usage_reconciler.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(86400)); // Daily
 
@@ -1040,6 +2233,11 @@ impl ApiManagerService {
                         error!("Failed to generate daily usage report: {}", e);
                     }
                 }
+
+                // Compare our counts with the providers' so drift is caught before billing
+                if let Err(e) = usage_reconciler.check_divergence().await {
+                    error!("Failed to check usage divergence: {}", e);
+                }
             }
         });
 
@@ -1071,6 +2269,9 @@ impl ApiManagerService {
 impl Service for ApiManagerService {
     async fn health_check(&self) -> AppResult<()> {
         debug!("Performing API manager health check");
+        if air_gap::is_air_gapped() {
+            info!("API manager is in offline mode; provider requests are disabled");
+        }
 
         // Check data persistence connection
         let data_persistence = self.data_persistence.read().await;
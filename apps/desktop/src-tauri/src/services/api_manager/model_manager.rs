@@ -7,7 +7,8 @@ use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
 use crate::services::api_manager::integrations::openrouter::{OpenRouterModel, OpenRouterIntegration};
 
 /// Enhanced model management service for V1.1.0
@@ -181,7 +182,7 @@ impl ModelManager {
     }
 
     /// Get all available models with caching
-    pub async fn get_available_models(&self, api_key: &ApiKey) -> AppResult<Vec<OpenRouterModel>> {
+    pub async fn get_available_models(&self, api_key: &SecretString) -> AppResult<Vec<OpenRouterModel>> {
         let cache = self.model_cache.read().await;
         let provider = ServiceProvider::OpenRouter;
 
@@ -213,7 +214,7 @@ impl ModelManager {
     }
 
     /// Get latest high-performance models
-    pub async fn get_latest_models(&self, api_key: &ApiKey) -> AppResult<Vec<OpenRouterModel>> {
+    pub async fn get_latest_models(&self, api_key: &SecretString) -> AppResult<Vec<OpenRouterModel>> {
         self.openrouter_integration.get_latest_models(api_key).await
     }
 
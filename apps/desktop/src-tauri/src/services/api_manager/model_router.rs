@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use tokio::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::{ApiError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use super::fallback_router::AttemptOutcome;
+use super::service_integration::{ServiceRequest, ServiceResponse};
+
+/// Model role for analyzing gathered research material
+pub const ANALYSIS: &str = "analysis";
+/// Model role for synthesizing a full research report
+pub const SYNTHESIS: &str = "synthesis";
+/// Model role for short summaries
+pub const SUMMARY: &str = "summary";
+/// Model role for rewriting passages of a report
+pub const REWRITE: &str = "rewrite";
+
+/// Step input key naming the model role a step runs under
+pub const MODEL_ROLE_KEY: &str = "model_role";
+
+/// The model role a step's inputs name under [`MODEL_ROLE_KEY`], or `default` when
+/// they name none, so a stored step can be moved to another role's models
+pub fn step_model_role(input_data: &HashMap<String, serde_json::Value>, default: &str) -> String {
+    input_data.get(MODEL_ROLE_KEY)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or(default)
+        .to_string()
+}
+
+/// Context window assumed for models missing from the policy's `context_windows`
+pub const DEFAULT_CONTEXT_WINDOW: u32 = 8192;
+
+/// How a model is asked for output conforming to a JSON schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutputMode {
+    /// `response_format` with a strict JSON schema
+    JsonSchema,
+    /// A forced call to a function taking the schema as its parameters
+    ToolCall,
+}
+
+/// Ordered concrete models to try for each logical model role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRoutingPolicy {
+    pub roles: HashMap<String, Vec<String>>,
+    /// Context window in tokens of each model
+    #[serde(default)]
+    pub context_windows: HashMap<String, u32>,
+    /// Tokens kept free for the model's output when fitting context into the window
+    #[serde(default = "default_output_reserve_tokens")]
+    pub output_reserve_tokens: u32,
+    /// Models able to return schema-conforming JSON, and how to ask them for it
+    #[serde(default = "default_structured_output")]
+    pub structured_output: HashMap<String, StructuredOutputMode>,
+}
+
+fn default_output_reserve_tokens() -> u32 {
+    4000
+}
+
+fn default_structured_output() -> HashMap<String, StructuredOutputMode> {
+    HashMap::from([
+        ("anthropic/claude-3-sonnet".to_string(), StructuredOutputMode::ToolCall),
+        ("anthropic/claude-3-haiku".to_string(), StructuredOutputMode::ToolCall),
+        ("openai/gpt-4o".to_string(), StructuredOutputMode::JsonSchema),
+        ("openai/gpt-4o-mini".to_string(), StructuredOutputMode::JsonSchema),
+        ("google/gemini-pro-1.5".to_string(), StructuredOutputMode::JsonSchema),
+    ])
+}
+
+impl Default for ModelRoutingPolicy {
+    fn default() -> Self {
+        let models = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let roles = HashMap::from([
+            (ANALYSIS.to_string(), models(&["anthropic/claude-3-sonnet", "openai/gpt-4o", "google/gemini-pro-1.5"])),
+            (SYNTHESIS.to_string(), models(&["anthropic/claude-3-sonnet", "openai/gpt-4o", "google/gemini-pro-1.5"])),
+            (SUMMARY.to_string(), models(&["anthropic/claude-3-haiku", "openai/gpt-4o-mini"])),
+            (REWRITE.to_string(), models(&["anthropic/claude-3-haiku", "openai/gpt-4o-mini"])),
+        ]);
+        let context_windows = HashMap::from([
+            ("anthropic/claude-3-sonnet".to_string(), 200_000),
+            ("anthropic/claude-3-haiku".to_string(), 200_000),
+            ("openai/gpt-4o".to_string(), 128_000),
+            ("openai/gpt-4o-mini".to_string(), 128_000),
+            ("google/gemini-pro-1.5".to_string(), 1_000_000),
+        ]);
+        Self {
+            roles,
+            context_windows,
+            output_reserve_tokens: default_output_reserve_tokens(),
+            structured_output: default_structured_output(),
+        }
+    }
+}
+
+impl ModelRoutingPolicy {
+    /// The smallest context window among a role's models, so context assembled for the
+    /// role still fits when a fallback model serves it
+    pub fn context_window_for(&self, role: &str) -> u32 {
+        self.roles.get(role)
+            .and_then(|models| models.iter()
+                .map(|model| self.context_windows.get(model).copied().unwrap_or(DEFAULT_CONTEXT_WINDOW))
+                .min())
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+    }
+
+    /// How to request structured output from `model`, if it supports it
+    pub fn structured_output_mode(&self, model: &str) -> Option<StructuredOutputMode> {
+        self.structured_output.get(model).copied()
+    }
+
+    /// Budget of a `role` request; the output reserve is at least the request's `max_tokens`
+    pub fn context_budget_for(&self, role: &str, max_tokens: u32) -> ContextBudget {
+        ContextBudget {
+            context_window: self.context_window_for(role),
+            output_reserve_tokens: self.output_reserve_tokens.max(max_tokens),
+        }
+    }
+}
+
+/// Tokens a request for a role can spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextBudget {
+    pub context_window: u32,
+    pub output_reserve_tokens: u32,
+}
+
+impl ContextBudget {
+    /// Tokens left for the prompt once the output reserve is set aside
+    pub fn prompt_tokens(&self) -> u32 {
+        self.context_window.saturating_sub(self.output_reserve_tokens)
+    }
+}
+
+/// One model tried while serving a step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelAttempt {
+    pub model: String,
+    pub outcome: AttemptOutcome,
+}
+
+/// Response from whichever model of the role served the request
+#[derive(Debug, Clone)]
+pub struct ModelFallbackResponse {
+    pub served_by_model: String,
+    pub response: ServiceResponse,
+    pub attempts: Vec<ModelAttempt>,
+}
+
+/// Resolves logical model roles to concrete models at execution time
+pub struct ModelRouter {
+    policy: RwLock<ModelRoutingPolicy>,
+}
+
+impl ModelRouter {
+    pub fn new(policy: ModelRoutingPolicy) -> Self {
+        Self { policy: RwLock::new(policy) }
+    }
+
+    pub async fn get_policy(&self) -> ModelRoutingPolicy {
+        self.policy.read().await.clone()
+    }
+
+    pub async fn update_policy(&self, policy: ModelRoutingPolicy) -> AppResult<()> {
+        if let Some((role, _)) = policy.roles.iter().find(|(_, models)| models.iter().all(|m| m.trim().is_empty())) {
+            return Err(ApiError::invalid_configuration(
+                "model_routing".to_string(),
+                format!("Role '{}' has no models", role),
+            ).into());
+        }
+        info!("Updating model routing policy");
+        *self.policy.write().await = policy;
+        Ok(())
+    }
+
+    /// Models to try for `role`, in order
+    pub async fn models_for(&self, role: &str) -> AppResult<Vec<String>> {
+        let policy = self.policy.read().await;
+        match policy.roles.get(role) {
+            Some(models) if !models.is_empty() => Ok(models.clone()),
+            _ => Err(ApiError::invalid_configuration(
+                "model_routing".to_string(),
+                format!("No models configured for role '{}'", role),
+            ).into()),
+        }
+    }
+}
+
+/// Build an OpenRouter chat completion request for `model`
+pub fn chat_completion_request(
+    model: &str,
+    system_prompt: &str,
+    user_prompt: &str,
+    temperature: f64,
+    max_tokens: u32,
+    timeout_ms: u32,
+) -> ServiceRequest {
+    let request_body = serde_json::json!({
+        "model": model,
+        "messages": [
+            {
+                "role": "system",
+                "content": system_prompt
+            },
+            {
+                "role": "user",
+                "content": user_prompt
+            }
+        ],
+        "temperature": temperature,
+        "max_tokens": max_tokens
+    });
+
+    ServiceRequest {
+        request_id: Uuid::new_v4(),
+        service: ServiceProvider::OpenRouter,
+        endpoint: "/chat/completions".to_string(),
+        method: "POST".to_string(),
+        headers: HashMap::new(),
+        body: Some(request_body.to_string()),
+        timeout_ms,
+        retry_count: 0,
+        metadata: HashMap::new(),
+    }
+}
+
+/// Ask for the chat completion `request` to conform to `schema`. With `ToolCall` the
+/// normalized completion content is the forced call's JSON arguments.
+pub fn with_structured_output(
+    mut request: ServiceRequest,
+    mode: StructuredOutputMode,
+    schema_name: &str,
+    schema: &serde_json::Value,
+) -> ServiceRequest {
+    let Some(mut body) = request.body.as_deref()
+        .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok())
+    else {
+        return request;
+    };
+
+    match mode {
+        StructuredOutputMode::JsonSchema => {
+            body["response_format"] = serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": schema_name, "strict": true, "schema": schema }
+            });
+        }
+        StructuredOutputMode::ToolCall => {
+            body["tools"] = serde_json::json!([{
+                "type": "function",
+                "function": { "name": schema_name, "parameters": schema }
+            }]);
+            body["tool_choice"] = serde_json::json!({ "type": "function", "function": { "name": schema_name } });
+        }
+    }
+
+    request.body = Some(body.to_string());
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_roles_resolve_to_ordered_models() {
+        let router = ModelRouter::new(ModelRoutingPolicy::default());
+        let analysis = router.models_for(ANALYSIS).await.unwrap();
+        assert_eq!(analysis[0], "anthropic/claude-3-sonnet");
+        assert!(analysis.len() > 1);
+        assert!(router.models_for("translation").await.is_err());
+
+        let inputs = HashMap::from([(MODEL_ROLE_KEY.to_string(), serde_json::json!(SUMMARY))]);
+        assert_eq!(step_model_role(&inputs, ANALYSIS), SUMMARY);
+        assert_eq!(step_model_role(&HashMap::new(), ANALYSIS), ANALYSIS);
+
+        let mut policy = router.get_policy().await;
+        policy.roles.insert(SUMMARY.to_string(), vec!["  ".to_string()]);
+        assert!(router.update_policy(policy.clone()).await.is_err());
+
+        policy.roles.insert(SUMMARY.to_string(), vec!["mistralai/mistral-large".to_string()]);
+        router.update_policy(policy).await.unwrap();
+        assert_eq!(router.models_for(SUMMARY).await.unwrap(), vec!["mistralai/mistral-large".to_string()]);
+
+        assert_eq!(router.get_policy().await.context_window_for(SUMMARY), DEFAULT_CONTEXT_WINDOW);
+        let budget = ModelRoutingPolicy::default().context_budget_for(ANALYSIS, 6000);
+        assert_eq!(budget.context_window, 128_000);
+        assert_eq!(budget.prompt_tokens(), 122_000);
+
+        let request = chat_completion_request("openai/gpt-4o", "system", "user", 0.2, 100, 1000);
+        let body: serde_json::Value = serde_json::from_str(request.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["model"], "openai/gpt-4o");
+
+        let schema = serde_json::json!({ "type": "object" });
+        let policy = ModelRoutingPolicy::default();
+        let mode = policy.structured_output_mode("anthropic/claude-3-sonnet").unwrap();
+        let structured = with_structured_output(request, mode, "record_analysis", &schema);
+        let body: serde_json::Value = serde_json::from_str(structured.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["tool_choice"]["function"]["name"], "record_analysis");
+        assert!(body.get("response_format").is_none());
+        assert_eq!(policy.structured_output_mode("mistralai/mistral-large"), None);
+    }
+}
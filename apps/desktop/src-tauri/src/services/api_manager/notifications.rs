@@ -0,0 +1,459 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tracing::{info, debug, warn, error};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::notification::{DeliveryMode, Notification, NotificationChannel, NotificationPreferences};
+use crate::services::DataPersistenceService;
+use crate::utils::{air_gap, inject_trace_context};
+use super::egress;
+use super::rate_limiter::RateLimitAlert;
+
+/// Notifications kept per user by the in-app channel
+const MAX_INBOX_SIZE: usize = 200;
+
+/// Timeout of a webhook or email relay delivery
+const DELIVERY_TIMEOUT_MS: u32 = 15_000;
+
+/// Delivers notifications over one channel; register one per channel with
+/// `NotificationDispatcher::register_sink`
+#[async_trait::async_trait]
+pub trait NotificationSink: Send + Sync {
+    fn channel(&self) -> NotificationChannel;
+
+    /// Send `notifications` to the user as one message; more than one is a digest
+    async fn deliver(&self, preferences: &NotificationPreferences, notifications: &[Notification]) -> AppResult<()>;
+}
+
+/// The users notifications about a tenant go to, whether or not they have set preferences.
+/// Register one with `NotificationDispatcher::register_directory`.
+#[async_trait::async_trait]
+pub trait RecipientDirectory: Send + Sync {
+    /// Users of `tenant_id`, or the users of system pool keys when `None`
+    async fn tenant_users(&self, tenant_id: Option<Uuid>) -> AppResult<Vec<Uuid>>;
+}
+
+/// Keeps each user's latest notifications in the database for the app to show
+pub struct InAppSink {
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+}
+
+impl InAppSink {
+    pub fn new(data_persistence: Arc<RwLock<DataPersistenceService>>) -> Self {
+        Self { data_persistence }
+    }
+
+    /// The user's notifications, newest first
+    pub async fn inbox(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        self.data_persistence.read().await.get_inbox_notifications(user_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for InAppSink {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::InApp
+    }
+
+    async fn deliver(&self, preferences: &NotificationPreferences, notifications: &[Notification]) -> AppResult<()> {
+        self.data_persistence.read().await
+            .add_inbox_notifications(preferences.user_id, notifications, MAX_INBOX_SIZE)
+            .await
+    }
+}
+
+/// Posts notifications as JSON to the user's webhook URL
+pub struct WebhookSink {
+    http_client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new() -> AppResult<Self> {
+        Ok(Self { http_client: delivery_client()? })
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for WebhookSink {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Webhook
+    }
+
+    async fn deliver(&self, preferences: &NotificationPreferences, notifications: &[Notification]) -> AppResult<()> {
+        let url = preferences.webhook_url.as_deref()
+            .ok_or_else(|| AppError::validation("webhook_url", "no webhook URL is set"))?;
+        let payload = serde_json::json!({
+            "event": "notifications",
+            "user_id": preferences.user_id,
+            "notifications": notifications,
+        });
+        post(&self.http_client, "notification webhook", url, &payload).await
+    }
+}
+
+/// Sends notifications to the user's email address through an HTTP mail relay, which
+/// takes `to`, `subject` and `text` as JSON
+pub struct EmailSink {
+    http_client: reqwest::Client,
+    relay_url: String,
+}
+
+impl EmailSink {
+    pub fn new(relay_url: String) -> AppResult<Self> {
+        validate_url("relay_url", &relay_url)?;
+        Ok(Self { http_client: delivery_client()?, relay_url })
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for EmailSink {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Email
+    }
+
+    async fn deliver(&self, preferences: &NotificationPreferences, notifications: &[Notification]) -> AppResult<()> {
+        let to = preferences.email.as_deref()
+            .ok_or_else(|| AppError::validation("email", "no email address is set"))?;
+        let subject = match notifications {
+            [notification] => format!("{} alert", notification.alert_type),
+            _ => format!("{} alerts", notifications.len()),
+        };
+        let text = notifications.iter()
+            .map(|notification| format!("[{}] {}: {}", notification.created_at.format("%Y-%m-%d %H:%M UTC"), notification.alert_type, notification.message))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let payload = serde_json::json!({ "to": to, "subject": subject, "text": text });
+        post(&self.http_client, "notification email", &self.relay_url, &payload).await
+    }
+}
+
+fn delivery_client() -> AppResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_millis(DELIVERY_TIMEOUT_MS as u64))
+        .build()
+        .map_err(|e| AppError::internal(format!("Failed to build notification client: {}", e)))
+}
+
+/// Post `payload` through the egress client, and not at all offline
+async fn post(default_client: &reqwest::Client, operation: &str, url: &str, payload: &serde_json::Value) -> AppResult<()> {
+    air_gap::ensure_online(operation)?;
+    let client = egress::client_for(default_client, DELIVERY_TIMEOUT_MS)?;
+    let response = inject_trace_context(client.post(url).json(payload)).send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::internal(format!("{} returned {}", operation, response.status())));
+    }
+    Ok(())
+}
+
+fn validate_url(field: &str, url: &str) -> AppResult<()> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| AppError::validation(field, format!("invalid URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AppError::validation(field, "must use http or https"));
+    }
+    Ok(())
+}
+
+/// A recipient a notification could not be delivered to or held for
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryFailure {
+    pub user_id: Uuid,
+    /// `None` when the notification could not be held for later
+    pub channel: Option<NotificationChannel>,
+    pub error: String,
+}
+
+/// The saved preferences of `tenant_id`'s users, plus the defaults for those of `users`
+/// who have set none
+pub fn recipients(saved: Vec<NotificationPreferences>, users: &[Uuid], tenant_id: Option<Uuid>) -> Vec<NotificationPreferences> {
+    let mut recipients: Vec<NotificationPreferences> = saved.into_iter()
+        .filter(|preferences| preferences.tenant_id == tenant_id)
+        .collect();
+    for user_id in users {
+        if !recipients.iter().any(|preferences| preferences.user_id == *user_id) {
+            recipients.push(NotificationPreferences::new(*user_id, tenant_id));
+        }
+    }
+    recipients
+}
+
+/// What a user's preferences make of one notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Routing {
+    Skip,
+    Deliver,
+    /// Keep until quiet hours end or the digest is due
+    Hold,
+}
+
+/// Route a notification for one user. Critical notifications go out at once on every
+/// chosen channel; others wait out quiet hours and are batched for digest users.
+pub fn route(preferences: &NotificationPreferences, notification: &Notification, at: DateTime<Utc>) -> Routing {
+    if preferences.channels.is_empty() || !preferences.wants(&notification.alert_type) {
+        Routing::Skip
+    } else if notification.critical {
+        Routing::Deliver
+    } else if preferences.delivery == DeliveryMode::Immediate && !preferences.in_quiet_hours(at) {
+        Routing::Deliver
+    } else {
+        Routing::Hold
+    }
+}
+
+/// The notification users receive for a rate limiter alert
+pub fn alert_notification(alert: &RateLimitAlert) -> Notification {
+    Notification {
+        id: alert.id,
+        alert_type: format!("{:?}", alert.alert_type),
+        critical: alert.alert_type.is_critical(),
+        message: alert.message.clone(),
+        api_key_id: Some(alert.api_key_id),
+        service: Some(format!("{:?}", alert.service).to_lowercase()),
+        created_at: alert.timestamp,
+    }
+}
+
+/// Sends alerts to the users of the tenant they concern, as each user's notification
+/// preferences allow
+pub struct NotificationDispatcher {
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+    in_app: Arc<InAppSink>,
+    sinks: RwLock<Vec<Arc<dyn NotificationSink>>>,
+    directory: RwLock<Option<Arc<dyn RecipientDirectory>>>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(data_persistence: Arc<RwLock<DataPersistenceService>>) -> AppResult<Self> {
+        let in_app = Arc::new(InAppSink::new(data_persistence.clone()));
+        let webhook = Arc::new(WebhookSink::new()?);
+        Ok(Self {
+            data_persistence,
+            sinks: RwLock::new(vec![in_app.clone() as Arc<dyn NotificationSink>, webhook]),
+            in_app,
+            directory: RwLock::new(None),
+        })
+    }
+
+    /// Deliver a channel through `sink`, replacing any sink registered for it before
+    pub async fn register_sink(&self, sink: Arc<dyn NotificationSink>) {
+        let mut sinks = self.sinks.write().await;
+        sinks.retain(|registered| registered.channel() != sink.channel());
+        info!("Registered notification sink for {:?}", sink.channel());
+        sinks.push(sink);
+    }
+
+    /// Look up each tenant's users in `directory`, so users who have set no preferences
+    /// are notified with the defaults. Without one only users with saved preferences are.
+    pub async fn register_directory(&self, directory: Arc<dyn RecipientDirectory>) {
+        *self.directory.write().await = Some(directory);
+    }
+
+    /// The user's preferences, or the defaults if they have set none
+    pub async fn get_preferences(&self, user_id: Uuid) -> AppResult<NotificationPreferences> {
+        let preferences = self.data_persistence.read().await.get_notification_preferences(user_id).await?;
+        Ok(preferences.unwrap_or_else(|| NotificationPreferences::new(user_id, None)))
+    }
+
+    pub async fn update_preferences(&self, mut preferences: NotificationPreferences) -> AppResult<()> {
+        if let Some(quiet_hours) = preferences.quiet_hours {
+            if quiet_hours.start_hour > 23 || quiet_hours.end_hour > 23 {
+                return Err(AppError::validation("quiet_hours", "hours must be between 0 and 23"));
+            }
+            if quiet_hours.utc_offset_minutes.abs() > 14 * 60 {
+                return Err(AppError::validation("quiet_hours", "UTC offset must be within 14 hours"));
+            }
+        }
+        let mut channels = Vec::new();
+        for channel in preferences.channels.drain(..) {
+            if !channels.contains(&channel) {
+                channels.push(channel);
+            }
+        }
+        preferences.channels = channels;
+        if preferences.channels.contains(&NotificationChannel::Email) && preferences.email.is_none() {
+            return Err(AppError::validation("email", "the email channel needs an address"));
+        }
+        match &preferences.webhook_url {
+            Some(url) => validate_url("webhook_url", url)?,
+            None if preferences.channels.contains(&NotificationChannel::Webhook) => {
+                return Err(AppError::validation("webhook_url", "the webhook channel needs a URL"));
+            }
+            None => {}
+        }
+        preferences.updated_at = Utc::now();
+        self.data_persistence.read().await.save_notification_preferences(&preferences).await
+    }
+
+    /// The user's in-app notifications, newest first
+    pub async fn inbox(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        self.in_app.inbox(user_id).await
+    }
+
+    /// Deliver or hold a notification for every user of `tenant_id`. A recipient it fails
+    /// for does not stop the others; the failures are returned.
+    pub async fn notify(&self, tenant_id: Option<Uuid>, notification: Notification) -> AppResult<Vec<DeliveryFailure>> {
+        let now = Utc::now();
+        let saved = self.data_persistence.read().await.get_all_notification_preferences().await?;
+        let directory = self.directory.read().await.clone();
+        let users = match directory {
+            Some(directory) => directory.tenant_users(tenant_id).await.unwrap_or_else(|e| {
+                warn!("Failed to look up the users of tenant {:?}; notifying those with preferences only: {}", tenant_id, e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        let mut failures = Vec::new();
+        for preferences in recipients(saved, &users, tenant_id) {
+            match route(&preferences, &notification, now) {
+                Routing::Skip => {}
+                Routing::Deliver => {
+                    let (_, failed) = self.deliver(&preferences, std::slice::from_ref(&notification)).await;
+                    failures.extend(failed);
+                }
+                Routing::Hold => {
+                    debug!("Holding {} notification for user {}", notification.alert_type, preferences.user_id);
+                    if let Err(e) = self.data_persistence.read().await.hold_notification(preferences.user_id, &notification).await {
+                        error!("Failed to hold notification for user {}: {}", preferences.user_id, e);
+                        failures.push(DeliveryFailure { user_id: preferences.user_id, channel: None, error: e.to_string() });
+                    }
+                }
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Send held notifications of every user whose quiet hours are over or digest is
+    /// due, returning how many users were sent any. A user whose held notifications
+    /// cannot be read or kept is logged and skipped.
+    pub async fn flush_held(&self, at: DateTime<Utc>) -> AppResult<usize> {
+        let preferences = self.data_persistence.read().await.get_all_notification_preferences().await?;
+        let mut flushed = 0;
+
+        for preferences in preferences.into_iter().filter(|preferences| preferences.held_due(at)) {
+            match self.flush_user(preferences, at).await {
+                Ok(true) => flushed += 1,
+                Ok(false) => {}
+                Err(e) => error!("Failed to send held notifications: {}", e),
+            }
+        }
+
+        if flushed > 0 {
+            info!("Sent held notifications to {} users", flushed);
+        }
+        Ok(flushed)
+    }
+
+    /// Send one user's held notifications, returning whether there were any to send
+    async fn flush_user(&self, mut preferences: NotificationPreferences, at: DateTime<Utc>) -> AppResult<bool> {
+        let held = self.data_persistence.read().await.take_held_notifications(preferences.user_id).await?;
+        let mut sent = false;
+        if !held.is_empty() {
+            if self.deliver(&preferences, &held).await.0 == 0 {
+                // Not one channel took them; keep them for the next flush
+                let data_persistence = self.data_persistence.read().await;
+                for notification in &held {
+                    data_persistence.hold_notification(preferences.user_id, notification).await?;
+                }
+                return Ok(false);
+            }
+            sent = true;
+        }
+
+        // The next digest period starts now, whether or not this one had anything
+        if matches!(preferences.delivery, DeliveryMode::Digest(_)) {
+            preferences.last_digest_at = Some(at);
+            self.data_persistence.read().await.save_notification_preferences(&preferences).await?;
+        }
+        Ok(sent)
+    }
+
+    /// Send on each of the user's channels, returning how many took the notifications
+    /// and the channels that failed
+    async fn deliver(&self, preferences: &NotificationPreferences, notifications: &[Notification]) -> (usize, Vec<DeliveryFailure>) {
+        let sinks = self.sinks.read().await.clone();
+        let mut delivered = 0;
+        let mut failures = Vec::new();
+        for channel in &preferences.channels {
+            let Some(sink) = sinks.iter().find(|sink| sink.channel() == *channel) else {
+                warn!("No notification sink registered for {:?}; user {} not notified there", channel, preferences.user_id);
+                failures.push(DeliveryFailure {
+                    user_id: preferences.user_id,
+                    channel: Some(*channel),
+                    error: format!("no sink registered for {:?}", channel),
+                });
+                continue;
+            };
+            match sink.deliver(preferences, notifications).await {
+                Ok(()) => delivered += 1,
+                Err(e) => {
+                    error!("Failed to deliver notifications to user {} by {:?}: {}", preferences.user_id, channel, e);
+                    failures.push(DeliveryFailure { user_id: preferences.user_id, channel: Some(*channel), error: e.to_string() });
+                }
+            }
+        }
+        (delivered, failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::models::notification::{DigestFrequency, QuietHours};
+
+    fn notification(alert_type: &str, critical: bool) -> Notification {
+        Notification {
+            id: Uuid::new_v4(),
+            alert_type: alert_type.to_string(),
+            critical,
+            message: "usage is high".to_string(),
+            api_key_id: None,
+            service: Some("serpapi".to_string()),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_and_digests_hold_only_non_critical_alerts() {
+        let mut preferences = NotificationPreferences::new(Uuid::new_v4(), None);
+        // 22:00 to 07:00 in UTC+2
+        preferences.quiet_hours = Some(QuietHours { start_hour: 22, end_hour: 7, utc_offset_minutes: 120 });
+        let night = Utc.with_ymd_and_hms(2024, 3, 4, 23, 30, 0).unwrap();
+        let afternoon = Utc.with_ymd_and_hms(2024, 3, 4, 13, 0, 0).unwrap();
+
+        assert_eq!(route(&preferences, &notification("Warning", false), afternoon), Routing::Deliver);
+        assert_eq!(route(&preferences, &notification("Warning", false), night), Routing::Hold);
+        assert_eq!(route(&preferences, &notification("Exhausted", true), night), Routing::Deliver);
+        assert!(!preferences.held_due(night));
+        assert!(preferences.held_due(afternoon));
+
+        preferences.delivery = DeliveryMode::Digest(DigestFrequency::Weekly);
+        preferences.last_digest_at = Some(afternoon - chrono::Duration::days(3));
+        assert_eq!(route(&preferences, &notification("Warning", false), afternoon), Routing::Hold);
+        assert!(!preferences.held_due(afternoon));
+        assert!(preferences.held_due(afternoon + chrono::Duration::days(4)));
+
+        preferences.alert_types = vec!["QuotaExhausted".to_string()];
+        assert_eq!(route(&preferences, &notification("Exhausted", true), afternoon), Routing::Skip);
+    }
+
+    #[test]
+    fn test_users_without_preferences_get_the_defaults() {
+        let tenant = Some(Uuid::new_v4());
+        let (configured, unconfigured) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut saved = NotificationPreferences::new(configured, tenant);
+        saved.channels = vec![NotificationChannel::Webhook];
+        let other_tenant = NotificationPreferences::new(Uuid::new_v4(), Some(Uuid::new_v4()));
+
+        let found = recipients(vec![saved.clone(), other_tenant], &[configured, unconfigured], tenant);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], saved);
+        assert_eq!((found[1].user_id, found[1].tenant_id), (unconfigured, tenant));
+        assert_eq!(found[1].channels, vec![NotificationChannel::InApp]);
+    }
+}
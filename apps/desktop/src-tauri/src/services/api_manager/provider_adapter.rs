@@ -0,0 +1,531 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::error::{ApiError, AppError, AppResult};
+use crate::models::api_key::{CustomProvider, CustomProviderSpec, ResetPeriod, ServiceProvider};
+use crate::services::security::SecretString;
+use crate::utils::inject_trace_context;
+use super::egress;
+use super::fallback_router::{FallbackConfig, WEB_SEARCH};
+use super::integrations::json_with_api_key_field;
+use super::response_schema::{NormalizedPayload, SchemaViolation};
+use super::service_integration::{ServiceConfig, ServiceHealth, ServiceIntegration, ServiceRequest, ServiceResponse};
+use super::web_search::PageSupport;
+
+/// How a provider expects to be sent the API key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderAuth {
+    /// `Authorization: Bearer <key>`
+    Bearer,
+    /// The key as the value of the named header, such as `x-api-key`
+    Header(String),
+    /// The key as the named query parameter
+    QueryParam(String),
+    /// The key as the named field of the JSON request body
+    BodyField(String),
+    /// The provider takes no key
+    None,
+}
+
+/// A provider request mapped onto HTTP, before the API key is added
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+}
+
+/// What a search or extraction provider implements to be used like the built-in ones.
+///
+/// Once registered with [`register_adapter`], the provider's keys are stored, rotated
+/// and rate limited as any other, its requests go through the same concurrency limits,
+/// circuit breaker and metrics, and a provider with [`PageSupport`] joins the end of
+/// the web search chain the methodologies search through. Only `name`, `base_url` and
+/// `endpoints` are required; see `docs/development/provider-adapters.md`.
+pub trait ProviderAdapter: Send + Sync {
+    /// Lowercase identifier the provider is stored and configured under, and named by in
+    /// workflow steps: letters, digits, `-` and `_`
+    fn name(&self) -> &str;
+
+    fn display_name(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// URL the endpoints are relative to, without a trailing slash
+    fn base_url(&self) -> String;
+
+    /// Endpoints the provider serves, such as `/search`
+    fn endpoints(&self) -> Vec<String>;
+
+    fn auth(&self) -> ProviderAuth {
+        ProviderAuth::Bearer
+    }
+
+    /// Requests one key may make per reset period
+    fn rate_limit(&self) -> u32 {
+        1000
+    }
+
+    fn reset_period(&self) -> ResetPeriod {
+        ResetPeriod::Monthly
+    }
+
+    /// Estimated cost in USD of one request, checked against the chains' cost ceilings
+    fn cost_per_request(&self) -> Option<f64> {
+        None
+    }
+
+    /// Timeouts, retries and limits for the provider
+    fn service_config(&self, service: ServiceProvider) -> ServiceConfig {
+        ServiceConfig { base_url: self.base_url(), ..ServiceConfig::for_custom_provider(service) }
+    }
+
+    /// Map a request onto HTTP: by default the base URL plus the endpoint and any
+    /// `query_params` metadata, with the request's method, headers and body
+    fn map_request(&self, config: &ServiceConfig, request: &ServiceRequest) -> AppResult<HttpRequest> {
+        let mut url = format!("{}{}", config.base_url, request.endpoint);
+        if let Some(query_params) = request.metadata.get("query_params") {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(query_params);
+        }
+        let mut headers = config.custom_headers.clone();
+        headers.extend(request.headers.clone());
+        if request.body.is_some() {
+            headers.entry("Content-Type".to_string()).or_insert_with(|| "application/json".to_string());
+        }
+        Ok(HttpRequest { method: request.method.clone(), url, headers, body: request.body.clone() })
+    }
+
+    /// Check a successful response body and map it into a [`NormalizedPayload`], as the
+    /// built-in providers' schemas do; `Ok(None)` passes the body through unchecked
+    fn normalize_response(&self, _endpoint: &str, _body: &str) -> Result<Option<NormalizedPayload>, SchemaViolation> {
+        Ok(None)
+    }
+
+    /// Results one search returns and whether it can skip ahead; `None` for providers
+    /// that do not search the web
+    fn page_support(&self) -> Option<PageSupport> {
+        None
+    }
+
+    /// A web search for `num_results` results after the first `offset`; `None` when the
+    /// provider cannot serve it
+    fn search_request(&self, _service: ServiceProvider, _query: &str, _num_results: u32, _offset: u32) -> Option<ServiceRequest> {
+        None
+    }
+
+    /// The cheapest request that proves a key works: a one-result search by default
+    fn health_check_request(&self, service: ServiceProvider) -> Option<ServiceRequest> {
+        self.search_request(service, "test", 1, 0)
+    }
+
+    /// Body the mock providers answer `endpoint` with when a workflow runs in mock mode
+    fn mock_body(&self, _endpoint: &str) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+/// Adapters of the registered custom providers, by name
+static ADAPTERS: Lazy<RwLock<HashMap<String, Arc<dyn ProviderAdapter>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Register a custom provider, replacing any adapter registered under the same name.
+/// Adapters registered before the API manager starts are picked up by it; after that,
+/// use `ApiManagerService::register_provider_adapter`.
+pub fn register_adapter(adapter: Arc<dyn ProviderAdapter>) -> AppResult<ServiceProvider> {
+    let name = adapter.name().to_string();
+    if ServiceProvider::BUILT_IN.iter().any(|built_in| built_in.name() == name) {
+        return Err(AppError::validation("name", format!("'{}' is a built-in provider", name)));
+    }
+    if !CustomProvider::is_valid_name(&name) {
+        return Err(AppError::validation("name", format!("'{}' must be lowercase letters, digits, '-' or '_'", name)));
+    }
+
+    let provider = CustomProvider::define(CustomProviderSpec {
+        name: name.clone(),
+        display_name: adapter.display_name(),
+        rate_limit: adapter.rate_limit(),
+        reset_period: adapter.reset_period(),
+    });
+    ADAPTERS.write().unwrap_or_else(|e| e.into_inner()).insert(name, adapter);
+    info!("Registered provider adapter: {}", provider.name());
+    Ok(ServiceProvider::Custom(provider))
+}
+
+/// The adapter of a custom provider
+pub fn adapter_for(provider: CustomProvider) -> Option<Arc<dyn ProviderAdapter>> {
+    ADAPTERS.read().unwrap_or_else(|e| e.into_inner()).get(provider.name()).cloned()
+}
+
+/// Every registered adapter with its provider, by name
+pub fn registered_adapters() -> Vec<(ServiceProvider, Arc<dyn ProviderAdapter>)> {
+    CustomProvider::all().into_iter()
+        .filter_map(|provider| adapter_for(provider).map(|adapter| (ServiceProvider::Custom(provider), adapter)))
+        .collect()
+}
+
+/// Put `service` at the end of the chains it can serve, with its cost
+pub fn join_fallback_chains(config: &mut FallbackConfig, service: ServiceProvider, adapter: &dyn ProviderAdapter) {
+    if let Some(cost) = adapter.cost_per_request() {
+        config.provider_costs.insert(service, cost);
+    }
+    if adapter.page_support().is_some() {
+        if let Some(chain) = config.chains.get_mut(WEB_SEARCH) {
+            if !chain.providers.contains(&service) {
+                chain.providers.push(service);
+            }
+        }
+    }
+}
+
+/// A request to `service` with no body, headers or metadata yet
+pub fn new_request(service: ServiceProvider, method: &str, endpoint: &str) -> ServiceRequest {
+    ServiceRequest {
+        request_id: Uuid::new_v4(),
+        service,
+        endpoint: endpoint.to_string(),
+        method: method.to_string(),
+        headers: HashMap::new(),
+        body: None,
+        timeout_ms: 15000,
+        retry_count: 0,
+        metadata: HashMap::new(),
+    }
+}
+
+/// Service integration that calls a custom provider through its adapter
+pub struct AdapterIntegration {
+    service: ServiceProvider,
+    adapter: Arc<dyn ProviderAdapter>,
+    config: ServiceConfig,
+    http_client: reqwest::Client,
+}
+
+impl AdapterIntegration {
+    pub fn new(service: ServiceProvider, adapter: Arc<dyn ProviderAdapter>) -> Self {
+        let config = adapter.service_config(service);
+        let http_client = egress::client_builder(config.default_timeout_ms)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { service, adapter, config, http_client }
+    }
+
+    /// The client for the running workflow's egress profile, or this integration's own
+    fn client(&self) -> AppResult<reqwest::Client> {
+        egress::client_for(&self.http_client, self.config.default_timeout_ms)
+    }
+
+    /// The HTTP request the adapter maps `request` onto, carrying the key as it asks
+    fn build_request(&self, client: &reqwest::Client, request: &ServiceRequest, api_key: &SecretString) -> AppResult<reqwest::RequestBuilder> {
+        let mapped = self.adapter.map_request(&self.config, request)?;
+        let method = reqwest::Method::from_bytes(mapped.method.to_uppercase().as_bytes())
+            .map_err(|_| ApiError::invalid_configuration("method".to_string(), "Unsupported HTTP method".to_string()))?;
+
+        let mut builder = client.request(method, &mapped.url);
+        for (key, value) in &mapped.headers {
+            builder = builder.header(key, value);
+        }
+
+        let builder = match (self.adapter.auth(), mapped.body) {
+            (ProviderAuth::BodyField(field), body) => {
+                let body = match body {
+                    Some(body) => serde_json::from_str(&body)
+                        .map_err(|e| ApiError::invalid_configuration("body".to_string(), e.to_string()))?,
+                    None => serde_json::json!({}),
+                };
+                json_with_api_key_field(builder, body, &field, api_key)
+            }
+            (auth, body) => {
+                let builder = match auth {
+                    ProviderAuth::Bearer => builder.bearer_auth(api_key.expose()),
+                    ProviderAuth::Header(name) => builder.header(name, api_key.expose()),
+                    ProviderAuth::QueryParam(name) => builder.query(&[(name.as_str(), api_key.expose())]),
+                    ProviderAuth::BodyField(_) | ProviderAuth::None => builder,
+                };
+                match body {
+                    Some(body) => builder.body(body),
+                    None => builder,
+                }
+            }
+        };
+        Ok(builder)
+    }
+}
+
+#[async_trait]
+impl ServiceIntegration for AdapterIntegration {
+    fn service_provider(&self) -> ServiceProvider {
+        self.service
+    }
+
+    async fn make_request(&self, mut request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
+        debug!("Making {:?} request: {}", self.service, request.endpoint);
+
+        let start_time = std::time::Instant::now();
+        let mut response = ServiceResponse {
+            request_id: request.request_id,
+            service: self.service,
+            status_code: 0,
+            headers: HashMap::new(),
+            body: String::new(),
+            response_time_ms: 0,
+            success: false,
+            error_message: None,
+            metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: None,
+        };
+
+        self.transform_request(&mut request).await?;
+        let req_builder = self.build_request(&self.client()?, &request, api_key)?;
+
+        match inject_trace_context(req_builder).send().await {
+            Ok(http_response) => {
+                response.status_code = http_response.status().as_u16();
+                response.success = http_response.status().is_success();
+
+                for (key, value) in http_response.headers() {
+                    if let Ok(value_str) = value.to_str() {
+                        response.headers.insert(key.to_string(), value_str.to_string());
+                    }
+                }
+
+                match http_response.text().await {
+                    Ok(body) => response.body = body,
+                    Err(e) => {
+                        response.error_message = Some(format!("Failed to read response body: {}", e));
+                        response.success = false;
+                    }
+                }
+            }
+            Err(e) => {
+                response.status_code = 500;
+                response.error_message = Some(e.to_string());
+                response.success = false;
+            }
+        }
+
+        response.response_time_ms = start_time.elapsed().as_millis() as u32;
+        self.transform_response(&mut response).await?;
+
+        Ok(response)
+    }
+
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth> {
+        let Some(request) = self.adapter.health_check_request(self.service) else {
+            return Ok(ServiceHealth::Unknown);
+        };
+        match self.make_request(request, api_key).await {
+            Ok(response) if response.success => Ok(ServiceHealth::Healthy),
+            _ => Ok(ServiceHealth::Unhealthy),
+        }
+    }
+
+    fn get_config(&self) -> &ServiceConfig {
+        &self.config
+    }
+
+    async fn update_config(&mut self, config: ServiceConfig) -> AppResult<()> {
+        self.config = config;
+        self.http_client = egress::client_builder(self.config.default_timeout_ms)
+            .build()
+            .map_err(|e| ApiError::invalid_configuration("timeout".to_string(), e.to_string()))?;
+        Ok(())
+    }
+
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
+        match self.adapter.health_check_request(self.service) {
+            Some(request) => Ok(self.make_request(request, api_key).await.map_or(false, |response| response.success)),
+            None => Ok(!api_key.is_empty()),
+        }
+    }
+
+    fn get_endpoints(&self) -> Vec<String> {
+        self.adapter.endpoints()
+    }
+
+    async fn transform_request(&self, _request: &mut ServiceRequest) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn transform_response(&self, response: &mut ServiceResponse) -> AppResult<()> {
+        if response.success {
+            response.metadata.insert("provider".to_string(), self.adapter.display_name());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::api_manager::{fallback_router, mock_providers, response_schema, web_search};
+    use crate::services::api_manager::mock_providers::MockSettings;
+    use crate::services::api_manager::rate_limiter::RateLimitConfig;
+
+    /// A search provider over a niche index, as a user would add one
+    struct NicheSearch;
+
+    impl ProviderAdapter for NicheSearch {
+        fn name(&self) -> &str {
+            "niche"
+        }
+
+        fn display_name(&self) -> String {
+            "Niche Search".to_string()
+        }
+
+        fn base_url(&self) -> String {
+            "https://api.niche.example/v1".to_string()
+        }
+
+        fn endpoints(&self) -> Vec<String> {
+            vec!["/search".to_string()]
+        }
+
+        fn auth(&self) -> ProviderAuth {
+            ProviderAuth::Header("x-api-key".to_string())
+        }
+
+        fn rate_limit(&self) -> u32 {
+            200
+        }
+
+        fn reset_period(&self) -> ResetPeriod {
+            ResetPeriod::Daily
+        }
+
+        fn cost_per_request(&self) -> Option<f64> {
+            Some(0.002)
+        }
+
+        fn normalize_response(&self, endpoint: &str, body: &str) -> Result<Option<NormalizedPayload>, SchemaViolation> {
+            if endpoint != "/search" {
+                return Ok(None);
+            }
+            let body = response_schema::json_object(body)?;
+            response_schema::search_results(&body, "items", "href", &["summary"], "date").map(Some)
+        }
+
+        fn page_support(&self) -> Option<PageSupport> {
+            Some(PageSupport { page_size: 25, paginates: true })
+        }
+
+        fn search_request(&self, service: ServiceProvider, query: &str, num_results: u32, offset: u32) -> Option<ServiceRequest> {
+            let mut request = new_request(service, "GET", "/search");
+            request.metadata.insert(
+                "query_params".to_string(),
+                format!("q={}&limit={}&offset={}", urlencoding::encode(query), num_results, offset),
+            );
+            Some(request)
+        }
+
+        fn mock_body(&self, _endpoint: &str) -> Option<serde_json::Value> {
+            Some(serde_json::json!({
+                "items": [{ "name": "Mock niche result", "href": "https://niche.example/1", "summary": "Mock summary" }]
+            }))
+        }
+    }
+
+    #[test]
+    fn test_a_custom_adapter_is_used_like_a_built_in_provider() {
+        let niche = register_adapter(Arc::new(NicheSearch)).unwrap();
+
+        // The provider is stored, configured and rate limited under its name
+        assert_eq!(ServiceProvider::from_str("Niche"), Some(niche));
+        assert_eq!(format!("{:?}", niche), "niche");
+        assert_eq!(serde_json::to_string(&niche).unwrap(), "\"niche\"");
+        assert_eq!(serde_json::from_str::<ServiceProvider>("\"niche\"").unwrap(), niche);
+        let unregistered = serde_json::from_str::<ServiceProvider>("\"unregistered\"").unwrap();
+        assert!(!ServiceProvider::all().contains(&unregistered));
+        assert_eq!((niche.display_name(), niche.default_rate_limit()), ("Niche Search", 200));
+        assert!(ServiceProvider::all().contains(&niche));
+        let limits = RateLimitConfig::default_for_service(niche);
+        assert_eq!((limits.requests_per_period, limits.period_duration_hours), (200, 24));
+        assert_eq!(ServiceConfig::default_for_service(niche).base_url, "https://api.niche.example/v1");
+
+        // It searches the web through the chain, page after page
+        let mut fallback = FallbackConfig::default();
+        join_fallback_chains(&mut fallback, niche, &NicheSearch);
+        assert_eq!(fallback.chains[WEB_SEARCH].providers.last(), Some(&niche));
+        assert_eq!(fallback.provider_costs[&niche], 0.002);
+        assert_eq!(web_search::page_support(niche), Some(PageSupport { page_size: 25, paginates: true }));
+        let second_page = fallback_router::web_search_page_request(niche, "rust crates", 25, 25).unwrap();
+
+        let integration = AdapterIntegration::new(niche, Arc::new(NicheSearch));
+        let key = SecretString::new("niche-key".to_string());
+        let http = integration.build_request(&integration.http_client, &second_page, &key).unwrap().build().unwrap();
+        assert_eq!(http.method(), reqwest::Method::GET);
+        assert_eq!(http.url().as_str(), "https://api.niche.example/v1/search?q=rust%20crates&limit=25&offset=25");
+        assert_eq!(http.headers()["x-api-key"], "niche-key");
+
+        // Its responses are normalized like the built-in providers'
+        let body = r#"{"items": [{"name": "Crates", "href": "https://crates.io", "summary": "Registry", "date": "2024-03-01"}]}"#;
+        let Some(NormalizedPayload::SearchResults { hits }) = response_schema::validate_response(niche, "/search", body).unwrap() else {
+            panic!("expected search results");
+        };
+        assert_eq!((hits[0].link.as_str(), hits[0].snippet.as_str()), ("https://crates.io", "Registry"));
+        assert!(hits[0].published.is_some());
+        assert!(response_schema::validate_response(niche, "/search", r#"{"items": [{"name": "no link"}]}"#).is_err());
+        assert_eq!(response_schema::validate_response(niche, "/other", "not json").unwrap(), None);
+
+        // Names that are not identifiers, or that a built-in provider has, are refused
+        struct Named(&'static str);
+        impl ProviderAdapter for Named {
+            fn name(&self) -> &str {
+                self.0
+            }
+            fn base_url(&self) -> String {
+                String::new()
+            }
+            fn endpoints(&self) -> Vec<String> {
+                Vec::new()
+            }
+        }
+        assert!(register_adapter(Arc::new(Named("exa"))).is_err());
+        assert!(register_adapter(Arc::new(Named("Niche Search"))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_providers_have_mock_responses_and_carry_the_key_as_asked() {
+        let niche = register_adapter(Arc::new(NicheSearch)).unwrap();
+        let settings = MockSettings { latency_ms: 0, failure_percent: 0 };
+        let request = fallback_router::web_search_request(niche, "query", 10).unwrap();
+        let response = mock_providers::respond(niche, &request, settings).await.unwrap();
+        assert!(matches!(response.normalized, Some(NormalizedPayload::SearchResults { ref hits }) if hits.len() == 1));
+
+        struct BodyKeyed;
+        impl ProviderAdapter for BodyKeyed {
+            fn name(&self) -> &str {
+                "body-keyed"
+            }
+            fn base_url(&self) -> String {
+                "https://extract.example".to_string()
+            }
+            fn endpoints(&self) -> Vec<String> {
+                vec!["/extract".to_string()]
+            }
+            fn auth(&self) -> ProviderAuth {
+                ProviderAuth::BodyField("token".to_string())
+            }
+        }
+        let service = register_adapter(Arc::new(BodyKeyed)).unwrap();
+        assert_eq!(web_search::page_support(service), None);
+        assert!(fallback_router::web_search_request(service, "query", 10).is_none());
+
+        let integration = AdapterIntegration::new(service, Arc::new(BodyKeyed));
+        let mut request = new_request(service, "POST", "/extract");
+        request.body = Some(serde_json::json!({ "url": "https://example.com" }).to_string());
+        let key = SecretString::new("body-key".to_string());
+        let http = integration.build_request(&integration.http_client, &request, &key).unwrap().build().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(http.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({ "url": "https://example.com", "token": "body-key" }));
+        assert!(http.headers().get("authorization").is_none());
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::models::api_key::ServiceProvider;
+use super::rate_limiter::{admits, limit_status, LimitStatus, RateLimitConfig};
+
+/// Longest history a simulation may replay
+pub const MAX_LOOKBACK_DAYS: u32 = 365;
+
+/// Requests one key made on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    pub api_key_id: Uuid,
+    pub date: NaiveDate,
+    pub requests: u32,
+}
+
+/// What recorded traffic would have met under a proposed rate limit configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitSimulation {
+    pub service: ServiceProvider,
+    pub config: RateLimitConfig,
+    pub lookback_days: u32,
+    pub window_start: DateTime<Utc>,
+    pub keys_simulated: u32,
+    pub total_requests: u64,
+    pub allowed_requests: u64,
+    /// Refused in the emergency zone with 1% or less of the limit left
+    pub throttled_requests: u64,
+    /// Refused because the limit was reached
+    pub blocked_requests: u64,
+    pub warning_alerts: u64,
+    pub emergency_alerts: u64,
+    pub exhausted_alerts: u64,
+    /// Periods in which at least one request was throttled or blocked
+    pub periods_with_refusals: u32,
+}
+
+impl RateLimitSimulation {
+    pub fn alerts_fired(&self) -> u64 {
+        self.warning_alerts + self.emergency_alerts + self.exhausted_alerts
+    }
+}
+
+/// Replay recorded usage against `config`, key by key, as the live limiter would
+/// have handled it.
+///
+/// Usage is only recorded per day, so each day's requests are spread evenly
+/// across it. Every key gets `requests_per_period` requests per period of
+/// `period_duration_hours`, with periods counted from `window_start`. Refused
+/// requests are dropped rather than retried, and alerts are counted the way
+/// `RateLimiter::record_request` raises them: one per admitted request made
+/// once usage is at or above the warning threshold.
+pub fn simulate(
+    service: ServiceProvider,
+    config: &RateLimitConfig,
+    usage: &[DailyUsage],
+    window_start: DateTime<Utc>,
+    lookback_days: u32,
+) -> RateLimitSimulation {
+    let mut result = RateLimitSimulation {
+        service,
+        config: config.clone(),
+        lookback_days,
+        window_start,
+        keys_simulated: 0,
+        total_requests: 0,
+        allowed_requests: 0,
+        throttled_requests: 0,
+        blocked_requests: 0,
+        warning_alerts: 0,
+        emergency_alerts: 0,
+        exhausted_alerts: 0,
+        periods_with_refusals: 0,
+    };
+
+    let mut by_key: BTreeMap<Uuid, Vec<&DailyUsage>> = BTreeMap::new();
+    for day in usage {
+        by_key.entry(day.api_key_id).or_default().push(day);
+    }
+
+    let limit = config.requests_per_period;
+    let period_ms = Duration::hours(config.period_duration_hours.max(1) as i64).num_milliseconds();
+
+    for days in by_key.values_mut() {
+        days.sort_by_key(|day| day.date);
+        result.keys_simulated += 1;
+
+        let mut period = None;
+        let mut used = 0u32;
+        let mut refused_this_period = false;
+
+        for day in days.iter() {
+            let day_start = day.date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+            let spacing_ms = Duration::days(1).num_milliseconds() / day.requests.max(1) as i64;
+
+            for i in 0..day.requests {
+                let at = day_start + Duration::milliseconds(spacing_ms * i as i64 + spacing_ms / 2);
+                let request_period = (at - window_start).num_milliseconds().div_euclid(period_ms);
+                if period != Some(request_period) {
+                    period = Some(request_period);
+                    used = 0;
+                    refused_this_period = false;
+                }
+
+                result.total_requests += 1;
+                let status = limit_status(used, limit, config);
+                if !admits(&status, percentage(used, limit)) {
+                    if status == LimitStatus::Exhausted {
+                        result.blocked_requests += 1;
+                    } else {
+                        result.throttled_requests += 1;
+                    }
+                    if !refused_this_period {
+                        refused_this_period = true;
+                        result.periods_with_refusals += 1;
+                    }
+                    continue;
+                }
+
+                used += 1;
+                result.allowed_requests += 1;
+                match limit_status(used, limit, config) {
+                    LimitStatus::Exhausted => result.exhausted_alerts += 1,
+                    LimitStatus::Emergency => result.emergency_alerts += 1,
+                    LimitStatus::Warning => result.warning_alerts += 1,
+                    LimitStatus::Safe | LimitStatus::Blocked => {}
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn percentage(used: u32, limit: u32) -> f64 {
+    if limit > 0 { (used as f64 / limit as f64) * 100.0 } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(requests_per_period: u32, period_duration_hours: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_period,
+            period_duration_hours,
+            ..RateLimitConfig::default_for_service(ServiceProvider::SerpApi)
+        }
+    }
+
+    #[test]
+    fn test_replay_counts_refusals_and_alerts() {
+        let key = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let window_start = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let usage = vec![
+            DailyUsage { api_key_id: key, date, requests: 15 },
+            DailyUsage { api_key_id: key, date: date.succ_opt().unwrap(), requests: 5 },
+        ];
+
+        // 10 per day: warning at 80%, emergency at 95%, then the 11th request is blocked
+        let result = simulate(ServiceProvider::SerpApi, &config(10, 24), &usage, window_start, 2);
+        assert_eq!(result.total_requests, 20);
+        assert_eq!(result.allowed_requests, 15);
+        assert_eq!(result.blocked_requests, 5);
+        assert_eq!(result.throttled_requests, 0);
+        assert_eq!(result.periods_with_refusals, 1);
+        assert_eq!((result.warning_alerts, result.emergency_alerts, result.exhausted_alerts), (2, 0, 1));
+
+        // A two-day period carries the first day's usage into the second
+        let result = simulate(ServiceProvider::SerpApi, &config(10, 48), &usage, window_start, 2);
+        assert_eq!(result.allowed_requests, 10);
+        assert_eq!(result.blocked_requests, 10);
+
+        // Large limits stop in the emergency zone before the limit is reached
+        let usage = vec![DailyUsage { api_key_id: key, date, requests: 300 }];
+        let result = simulate(ServiceProvider::SerpApi, &config(200, 24), &usage, window_start, 1);
+        assert_eq!(result.allowed_requests, 198);
+        assert_eq!(result.throttled_requests, 102);
+        assert_eq!(result.blocked_requests, 0);
+    }
+}
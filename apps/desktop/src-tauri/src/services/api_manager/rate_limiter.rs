@@ -2,13 +2,18 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, debug, warn, error};
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, NaiveDate, Utc, Duration};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ResetPeriod};
+use crate::models::api_key::{ApiKey, ServiceProvider, ResetPeriod};
 use crate::services::DataPersistenceService;
+use crate::services::data_persistence::{BoundedHistory, HistoryEntry, HistoryLimit};
+use crate::utils::clock::{self, SharedClock};
+use super::usage_quota::{self, QuotaConfig, QuotaUsage};
+use super::rate_limit_simulation::{self, DailyUsage, RateLimitSimulation};
+use super::notifications::{self, NotificationDispatcher};
 
 /// Rate limit configuration for a service
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +78,17 @@ impl RateLimitConfig {
                 emergency_threshold_percent: 90.0,
                 buffer_zone_percent: 5.0,
             },
+            ServiceProvider::Custom(_) => Self {
+                service,
+                requests_per_period: service.default_rate_limit(),
+                period_duration_hours: match service.reset_period() {
+                    ResetPeriod::Daily => 24,
+                    ResetPeriod::Monthly => 24 * 30,
+                },
+                warning_threshold_percent: 80.0,
+                emergency_threshold_percent: 95.0,
+                buffer_zone_percent: 3.0,
+            },
         }
     }
 }
@@ -87,6 +103,8 @@ pub struct UsageStatus {
     pub reset_time: DateTime<Utc>,
     pub time_until_reset: Duration,
     pub status: LimitStatus,
+    /// Monthly quota remaining for the key and its service
+    pub quota: QuotaUsage,
 }
 
 /// Rate limit status levels
@@ -99,11 +117,39 @@ pub enum LimitStatus {
     Blocked,
 }
 
+/// Status of a key that has made `current_usage` of its `limit` requests this period
+pub(crate) fn limit_status(current_usage: u32, limit: u32, config: &RateLimitConfig) -> LimitStatus {
+    let usage_percentage = if limit > 0 { (current_usage as f64 / limit as f64) * 100.0 } else { 0.0 };
+
+    if current_usage >= limit {
+        LimitStatus::Exhausted
+    } else if usage_percentage >= config.emergency_threshold_percent {
+        LimitStatus::Emergency
+    } else if usage_percentage >= config.warning_threshold_percent {
+        LimitStatus::Warning
+    } else {
+        LimitStatus::Safe
+    }
+}
+
+/// Whether a key in `status` may make another request
+pub(crate) fn admits(status: &LimitStatus, usage_percentage: f64) -> bool {
+    match status {
+        LimitStatus::Safe | LimitStatus::Warning => true,
+        // Allow with caution in emergency status, while more than 1% remains
+        LimitStatus::Emergency => 100.0 - usage_percentage > 1.0,
+        LimitStatus::Exhausted | LimitStatus::Blocked => false,
+    }
+}
+
 /// Rate limit violation alert
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitAlert {
     pub id: Uuid,
     pub api_key_id: Uuid,
+    /// Tenant owning the key, whose users are notified; `None` for system pool keys
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
     pub service: ServiceProvider,
     pub alert_type: AlertType,
     pub message: String,
@@ -113,6 +159,15 @@ pub struct RateLimitAlert {
     pub timestamp: DateTime<Utc>,
 }
 
+impl HistoryEntry for RateLimitAlert {
+    fn recorded_at(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+/// Alerts kept in memory unless `FDR_HISTORY_RATE_LIMIT_ALERTS_MAX` says otherwise
+pub const DEFAULT_ALERT_HISTORY_SIZE: usize = 1000;
+
 /// Types of rate limit alerts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AlertType {
@@ -121,6 +176,30 @@ pub enum AlertType {
     Exhausted,
     Violation,
     Reset,
+    /// The key was taken out of rotation after repeated authentication failures
+    KeyDemoted,
+    /// Usage crossed one of the configured percentages of a monthly quota
+    QuotaThreshold,
+    /// A monthly quota was reached and requests are blocked until the next billing cycle
+    QuotaExhausted,
+    /// The key expires within its service's reminder window
+    KeyExpiring,
+    /// The key's expiry date has passed and it is refused
+    KeyExpired,
+    /// Our count of the key's requests is off from the provider's by more than the threshold
+    UsageDivergence,
+}
+
+impl AlertType {
+    /// Whether the alert is sent at once regardless of quiet hours and digests: the key
+    /// is refusing requests or about to
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            AlertType::Emergency | AlertType::Exhausted | AlertType::QuotaExhausted
+                | AlertType::KeyDemoted | AlertType::KeyExpired
+        )
+    }
 }
 
 /// Usage forecast data
@@ -136,12 +215,36 @@ pub struct UsageForecast {
     pub recommendations: Vec<String>,
 }
 
+/// Requests one tenant has sent with one API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantKeyUsage {
+    /// `None` for requests made by the system itself
+    pub tenant_id: Option<Uuid>,
+    pub api_key_id: Uuid,
+    pub requests: u64,
+    pub failed_requests: u64,
+    pub last_request: DateTime<Utc>,
+}
+
+/// Setting a service's monthly quota config is saved under
+fn quota_setting_name(service: ServiceProvider) -> String {
+    format!("quota_config.{}", service.name())
+}
+
+/// Setting the per-tenant key usage is saved under
+const TENANT_USAGE_SETTING: &str = "tenant_key_usage";
+
 /// Rate limiter service for tracking and preventing limit violations
 pub struct RateLimiter {
     data_persistence: Arc<RwLock<DataPersistenceService>>,
     configs: Arc<RwLock<HashMap<ServiceProvider, RateLimitConfig>>>,
-    alerts: Arc<RwLock<Vec<RateLimitAlert>>>,
+    quota_configs: Arc<RwLock<HashMap<ServiceProvider, QuotaConfig>>>,
+    alerts: Arc<RwLock<BoundedHistory<RateLimitAlert>>>,
     emergency_stop_enabled: Arc<RwLock<bool>>,
+    /// Usage per tenant per key, so tenants sharing system pool keys are told apart
+    tenant_usage: Arc<RwLock<HashMap<(Option<Uuid>, Uuid), TenantKeyUsage>>>,
+    notifications: Arc<NotificationDispatcher>,
+    clock: SharedClock,
 }
 
 impl RateLimiter {
@@ -150,34 +253,81 @@ impl RateLimiter {
         info!("Initializing rate limiter...");
 
         let mut configs = HashMap::new();
+        let mut quota_configs = HashMap::new();
         
         // Initialize default configurations for all services
-        for service in [
-            ServiceProvider::OpenRouter,
-            ServiceProvider::SerpApi,
-            ServiceProvider::Jina,
-            ServiceProvider::Firecrawl,
-            ServiceProvider::Tavily,
-            ServiceProvider::Exa,
-        ] {
+        for service in ServiceProvider::all() {
             configs.insert(service.clone(), RateLimitConfig::default_for_service(service));
+            quota_configs.insert(service, QuotaConfig::default_for_service(service));
         }
 
+        // Caps changed at runtime must survive a restart, or a reached cap would stop blocking
+        {
+            let data_persistence = data_persistence.read().await;
+            for (service, config) in quota_configs.iter_mut() {
+                match data_persistence.get_setting::<QuotaConfig>(&quota_setting_name(*service)).await {
+                    Ok(Some(saved)) => *config = saved,
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to load monthly quota config for {:?}, using the default: {}", service, e),
+                }
+            }
+        }
+
+        // Usage is what tenants are billed on, so it outlives the process
+        let tenant_usage: HashMap<_, _> = match data_persistence.read().await
+            .get_setting::<Vec<TenantKeyUsage>>(TENANT_USAGE_SETTING).await
+        {
+            Ok(saved) => saved.unwrap_or_default().into_iter()
+                .map(|usage| ((usage.tenant_id, usage.api_key_id), usage))
+                .collect(),
+            Err(e) => {
+                warn!("Failed to load tenant key usage, starting from zero: {}", e);
+                HashMap::new()
+            }
+        };
+
+        let notifications = Arc::new(NotificationDispatcher::new(data_persistence.clone())?);
+        let alerts = BoundedHistory::new(
+            "rate_limit_alerts",
+            HistoryLimit::from_env("rate_limit_alerts", DEFAULT_ALERT_HISTORY_SIZE),
+        ).with_store(data_persistence.clone());
+
         let rate_limiter = Self {
             data_persistence,
             configs: Arc::new(RwLock::new(configs)),
-            alerts: Arc::new(RwLock::new(Vec::new())),
+            quota_configs: Arc::new(RwLock::new(quota_configs)),
+            alerts: Arc::new(RwLock::new(alerts)),
             emergency_stop_enabled: Arc::new(RwLock::new(false)),
+            tenant_usage: Arc::new(RwLock::new(tenant_usage)),
+            notifications,
+            clock: clock::system_clock(),
         };
 
         info!("Rate limiter initialized successfully");
         Ok(rate_limiter)
     }
 
-    /// Check if a request can be made for a specific API key
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Check if a request can be made for a specific API key; an expired key
+    /// is an error rather than a refusal, since waiting will not help
     pub async fn can_make_request(&self, api_key_id: Uuid) -> AppResult<bool> {
         debug!("Checking if request can be made for API key: {}", api_key_id);
 
+        let data_persistence = self.data_persistence.read().await;
+        let api_key = data_persistence.get_api_key_by_id(api_key_id).await?
+            .ok_or_else(|| ApiError::key_not_found(api_key_id.to_string()))?;
+        drop(data_persistence);
+
+        if let Some(expires_at) = api_key.expires_at.filter(|_| api_key.is_expired(self.clock.now())) {
+            warn!("API key {} expired at {} - refusing request", api_key_id, expires_at);
+            return Err(ApiError::key_expired(api_key_id.to_string(), expires_at).into());
+        }
+
         // Check emergency stop
         let emergency_stop = *self.emergency_stop_enabled.read().await;
         if emergency_stop {
@@ -186,16 +336,11 @@ impl RateLimiter {
         }
 
         let usage_status = self.get_usage_status(api_key_id).await?;
-        
-        match usage_status.status {
-            LimitStatus::Safe | LimitStatus::Warning => Ok(true),
-            LimitStatus::Emergency => {
-                // Allow with caution in emergency status
-                let remaining_percentage = 100.0 - usage_status.usage_percentage;
-                Ok(remaining_percentage > 1.0) // Allow if more than 1% remaining
-            },
-            LimitStatus::Exhausted | LimitStatus::Blocked => Ok(false),
+        if usage_status.status == LimitStatus::Blocked {
+            warn!("Monthly quota reached for API key {} - blocking request", api_key_id);
         }
+
+        Ok(admits(&usage_status.status, usage_status.usage_percentage))
     }
 
     /// Get current usage status for an API key
@@ -239,17 +384,17 @@ impl RateLimiter {
             },
         };
 
-        let time_until_reset = reset_time - Utc::now();
+        let time_until_reset = reset_time - self.clock.now();
+        let threshold_status = limit_status(current_usage, limit, config);
+        drop(configs);
+
+        let quota = self.quota_usage(&api_key).await?;
 
-        // Determine status based on thresholds
-        let status = if current_usage >= limit {
-            LimitStatus::Exhausted
-        } else if usage_percentage >= config.emergency_threshold_percent {
-            LimitStatus::Emergency
-        } else if usage_percentage >= config.warning_threshold_percent {
-            LimitStatus::Warning
+        // A reached quota blocks outright, whatever the rate limit says
+        let status = if quota.is_exhausted() {
+            LimitStatus::Blocked
         } else {
-            LimitStatus::Safe
+            threshold_status
         };
 
         Ok(UsageStatus {
@@ -260,9 +405,117 @@ impl RateLimiter {
             reset_time,
             time_until_reset,
             status,
+            quota,
         })
     }
 
+    /// Quota used by `api_key` and by all keys of its service in the current billing cycle
+    async fn quota_usage(&self, api_key: &ApiKey) -> AppResult<QuotaUsage> {
+        let config = self.get_quota_config(api_key.service).await;
+        let cycle_start = usage_quota::billing_cycle_start(config.billing_cycle_day, self.clock.now());
+
+        let data_persistence = self.data_persistence.read().await;
+        let service_used = data_persistence.get_all_api_keys().await?
+            .iter()
+            .filter(|key| key.service == api_key.service)
+            .map(|key| key.quota_used_in(cycle_start))
+            .sum();
+        drop(data_persistence);
+
+        Ok(QuotaUsage::new(
+            api_key.monthly_quota,
+            api_key.quota_used_in(cycle_start),
+            config.monthly_cap,
+            service_used,
+            cycle_start,
+            usage_quota::next_billing_cycle_start(cycle_start),
+        ))
+    }
+
+    /// Count a request sent with the key against its monthly quota and alert on
+    /// any configured percentage of the key or service cap it crossed.
+    ///
+    /// Every request sent counts, failed or not, since providers may bill for them.
+    pub async fn record_quota_usage(&self, api_key_id: Uuid) -> AppResult<Vec<RateLimitAlert>> {
+        let mut data_persistence = self.data_persistence.write().await;
+        let mut api_key = data_persistence.get_api_key_by_id(api_key_id).await?
+            .ok_or_else(|| ApiError::key_not_found(api_key_id.to_string()))?;
+
+        let config = self.get_quota_config(api_key.service).await;
+        let cycle_start = usage_quota::billing_cycle_start(config.billing_cycle_day, self.clock.now());
+        api_key.record_quota_usage(cycle_start);
+        data_persistence.store_api_key(&api_key).await?;
+        drop(data_persistence);
+
+        let quota = self.quota_usage(&api_key).await?;
+        let mut new_alerts = Vec::new();
+
+        let caps = [
+            (quota.key_quota, quota.key_used, "API key"),
+            (quota.service_cap, quota.service_used, "Service"),
+        ];
+        for (cap, used, scope) in caps {
+            let Some(cap) = cap else { continue };
+            let percentage = if cap > 0 { used as f64 / cap as f64 * 100.0 } else { 100.0 };
+
+            let alert = if used == cap {
+                Some((AlertType::QuotaExhausted, format!(
+                    "{} has reached its monthly quota ({}/{}); requests are blocked until {}",
+                    scope, used, cap, quota.cycle_end.format("%Y-%m-%d")
+                )))
+            } else {
+                usage_quota::crossed_threshold(&config.alert_thresholds_percent, used, cap)
+                    .map(|threshold| (AlertType::QuotaThreshold, format!(
+                        "{} has used {:.0}% of its monthly quota ({}/{})", scope, threshold, used, cap
+                    )))
+            };
+
+            if let Some((alert_type, message)) = alert {
+                warn!("{}", message);
+                new_alerts.push(self.create_alert(api_key_id, alert_type, message, percentage, used, cap).await?);
+            }
+        }
+
+        self.publish_alerts(&new_alerts).await;
+
+        Ok(new_alerts)
+    }
+
+    /// Count a request a tenant sent with the key
+    pub async fn record_tenant_usage(&self, tenant_id: Option<Uuid>, api_key_id: Uuid, success: bool) {
+        let now = self.clock.now();
+        let mut tenant_usage = self.tenant_usage.write().await;
+        let usage = tenant_usage.entry((tenant_id, api_key_id)).or_insert_with(|| TenantKeyUsage {
+            tenant_id,
+            api_key_id,
+            requests: 0,
+            failed_requests: 0,
+            last_request: now,
+        });
+        usage.requests += 1;
+        if !success {
+            usage.failed_requests += 1;
+        }
+        usage.last_request = now;
+        let snapshot: Vec<TenantKeyUsage> = tenant_usage.values().cloned().collect();
+        drop(tenant_usage);
+
+        if let Err(e) = self.data_persistence.read().await.save_setting(TENANT_USAGE_SETTING, &snapshot).await {
+            warn!("Failed to save tenant key usage: {}", e);
+        }
+    }
+
+    /// Per-key usage of one tenant, busiest key first
+    pub async fn get_tenant_usage(&self, tenant_id: Option<Uuid>) -> Vec<TenantKeyUsage> {
+        let tenant_usage = self.tenant_usage.read().await;
+        let mut usage: Vec<_> = tenant_usage.values()
+            .filter(|usage| usage.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        usage.sort_by(|a, b| b.requests.cmp(&a.requests));
+        usage
+    }
+
     /// Record a request and check for threshold violations
     pub async fn record_request(&self, api_key_id: Uuid, success: bool) -> AppResult<Option<RateLimitAlert>> {
         debug!("Recording request for API key: {}", api_key_id);
@@ -301,16 +554,54 @@ impl RateLimiter {
         }
 
         if let Some(ref alert) = alert {
+            self.publish_alerts(std::slice::from_ref(alert)).await;
+        }
+
+        Ok(alert)
+    }
+
+    /// Raise an alert about a key outside of the usage thresholds
+    pub async fn raise_alert(&self, api_key_id: Uuid, alert_type: AlertType, message: String) -> AppResult<RateLimitAlert> {
+        let usage_status = self.get_usage_status(api_key_id).await?;
+        let alert = self.create_alert(
+            api_key_id,
+            alert_type,
+            message,
+            usage_status.usage_percentage,
+            usage_status.current_usage,
+            usage_status.limit,
+        ).await?;
+
+        self.publish_alerts(std::slice::from_ref(&alert)).await;
+
+        Ok(alert)
+    }
+
+    /// Keep new alerts for `get_recent_alerts` and notify the users who want them
+    async fn publish_alerts(&self, new_alerts: &[RateLimitAlert]) {
+        if new_alerts.is_empty() {
+            return;
+        }
+
+        {
             let mut alerts = self.alerts.write().await;
-            alerts.push(alert.clone());
+            alerts.extend(new_alerts.iter().cloned()).await;
+        }
 
-            // Keep only last 1000 alerts
-            if alerts.len() > 1000 {
-                alerts.drain(0..alerts.len() - 1000);
+        for alert in new_alerts {
+            match self.notifications.notify(alert.tenant_id, notifications::alert_notification(alert)).await {
+                Ok(failures) if !failures.is_empty() => {
+                    warn!("Alert {} did not reach {} recipient channels", alert.id, failures.len());
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to notify users of alert {}: {}", alert.id, e),
             }
         }
+    }
 
-        Ok(alert)
+    /// Delivery of alerts according to each user's notification preferences
+    pub fn notifications(&self) -> Arc<NotificationDispatcher> {
+        self.notifications.clone()
     }
 
     /// Create a rate limit alert
@@ -331,13 +622,14 @@ impl RateLimiter {
         Ok(RateLimitAlert {
             id: Uuid::new_v4(),
             api_key_id,
+            tenant_id: api_key.tenant_id,
             service: api_key.service,
             alert_type,
             message,
             usage_percentage,
             current_usage,
             limit,
-            timestamp: Utc::now(),
+            timestamp: self.clock.now(),
         })
     }
 
@@ -360,24 +652,31 @@ impl RateLimiter {
         *self.emergency_stop_enabled.read().await
     }
 
-    /// Get recent alerts
+    /// Get recent alerts, newest first, including spilled ones beyond the in-memory window
     pub async fn get_recent_alerts(&self, limit: usize) -> Vec<RateLimitAlert> {
         let alerts = self.alerts.read().await;
-        alerts.iter()
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect()
+        match alerts.recent(limit).await {
+            Ok(recent) => recent,
+            Err(e) => {
+                warn!("Failed to read spilled rate limit alerts: {}", e);
+                alerts.iter().rev().take(limit).cloned().collect()
+            }
+        }
+    }
+
+    /// Change how many alerts are kept in memory and whether older ones are spilled
+    pub async fn set_alert_history_limit(&self, limit: HistoryLimit) {
+        self.alerts.write().await.set_limit(limit).await;
     }
 
     /// Clear old alerts
     pub async fn clear_old_alerts(&self, older_than_hours: u32) -> AppResult<u32> {
-        let cutoff_time = Utc::now() - Duration::hours(older_than_hours as i64);
+        let cutoff_time = self.clock.now() - Duration::hours(older_than_hours as i64);
         let mut alerts = self.alerts.write().await;
 
         let initial_count = alerts.len();
         alerts.retain(|alert| alert.timestamp > cutoff_time);
-        let removed_count = initial_count - alerts.len();
+        let removed_count = initial_count - alerts.len() + alerts.purge_spilled(cutoff_time).await? as usize;
 
         if removed_count > 0 {
             info!("Cleared {} old alerts (older than {} hours)", removed_count, older_than_hours);
@@ -410,6 +709,82 @@ impl RateLimiter {
         self.configs.read().await.clone()
     }
 
+    /// Replay the last `lookback_days` of recorded usage for a service against
+    /// a proposed configuration, without applying it or touching live usage
+    pub async fn simulate_config(
+        &self,
+        service: ServiceProvider,
+        config: RateLimitConfig,
+        lookback_days: u32,
+    ) -> AppResult<RateLimitSimulation> {
+        let invalid = |message: String| ApiError::invalid_configuration(format!("{:?}", service), message);
+        if !(1..=rate_limit_simulation::MAX_LOOKBACK_DAYS).contains(&lookback_days) {
+            return Err(invalid(format!("lookback_days must be between 1 and {}", rate_limit_simulation::MAX_LOOKBACK_DAYS)).into());
+        }
+        if config.requests_per_period == 0 || config.period_duration_hours == 0 {
+            return Err(invalid("requests_per_period and period_duration_hours must be greater than zero".to_string()).into());
+        }
+
+        debug!("Simulating rate limit config for {:?} over {} days", service, lookback_days);
+
+        let history = self.data_persistence.read().await
+            .get_service_usage_history(&format!("{:?}", service).to_lowercase(), lookback_days)
+            .await?;
+
+        let usage: Vec<DailyUsage> = history.into_iter()
+            .filter_map(|(api_key_id, date_bucket, requests)| {
+                match NaiveDate::parse_from_str(&date_bucket, "%Y-%m-%d") {
+                    Ok(date) => Some(DailyUsage { api_key_id, date, requests }),
+                    Err(_) => {
+                        warn!("Skipping usage bucket with unparseable date: {}", date_bucket);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let window_start = (self.clock.now() - Duration::days(lookback_days as i64))
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        Ok(rate_limit_simulation::simulate(service, &config, &usage, window_start, lookback_days))
+    }
+
+    /// Update the monthly quota configuration for a service
+    pub async fn update_quota_config(&self, service: ServiceProvider, config: QuotaConfig) -> AppResult<()> {
+        config.validate()
+            .map_err(|message| ApiError::invalid_configuration(format!("{:?}", service), message))?;
+
+        info!("Updating monthly quota config for service: {:?}", service);
+        self.data_persistence.read().await.save_setting(&quota_setting_name(service), &config).await?;
+        self.quota_configs.write().await.insert(service, config);
+        Ok(())
+    }
+
+    /// Track the monthly quota of a service added after startup, under its saved config
+    /// when an earlier run left one
+    pub async fn register_quota_config(&self, service: ServiceProvider) -> AppResult<()> {
+        let saved = self.data_persistence.read().await.get_setting::<QuotaConfig>(&quota_setting_name(service)).await?;
+        let config = saved.unwrap_or_else(|| QuotaConfig::default_for_service(service));
+        self.quota_configs.write().await.entry(service).or_insert(config);
+        Ok(())
+    }
+
+    /// Get the monthly quota configuration for a service
+    pub async fn get_quota_config(&self, service: ServiceProvider) -> QuotaConfig {
+        self.quota_configs.read().await
+            .get(&service)
+            .cloned()
+            .unwrap_or_else(|| QuotaConfig::default_for_service(service))
+    }
+
+    /// Get all monthly quota configurations
+    pub async fn get_all_quota_configs(&self) -> HashMap<ServiceProvider, QuotaConfig> {
+        self.quota_configs.read().await.clone()
+    }
+
     /// Generate usage forecast for an API key
     pub async fn generate_usage_forecast(&self, api_key_id: Uuid) -> AppResult<UsageForecast> {
         debug!("Generating usage forecast for API key: {}", api_key_id);
@@ -466,13 +841,13 @@ impl RateLimiter {
 
         // Calculate predicted exhaustion time
         let predicted_exhaustion_time = if predicted_24h >= api_key.rate_limit {
-            Some(Utc::now() + Duration::hours(24))
+            Some(self.clock.now() + Duration::hours(24))
         } else if predicted_7d >= api_key.rate_limit {
             // Estimate based on current usage rate
             let remaining = api_key.rate_limit.saturating_sub(current_usage);
             let daily_rate = (predicted_24h - current_usage).max(1);
             let days_until_exhaustion = remaining / daily_rate;
-            Some(Utc::now() + Duration::days(days_until_exhaustion as i64))
+            Some(self.clock.now() + Duration::days(days_until_exhaustion as i64))
         } else {
             None
         };
@@ -546,7 +921,7 @@ impl RateLimiter {
 
         let mut report = String::new();
         report.push_str("# API Usage Report\n\n");
-        report.push_str(&format!("Generated: {}\n\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+        report.push_str(&format!("Generated: {}\n\n", self.clock.now().format("%Y-%m-%d %H:%M:%S UTC")));
 
         // Summary statistics
         let total_keys = analytics.len();
@@ -636,12 +1011,8 @@ impl RateLimiter {
         let alerts = self.alerts.read().await;
 
         // Filter alerts from the last hour
-        let one_hour_ago = chrono::Utc::now() - chrono::Duration::hours(1);
-        let recent_alerts: Vec<RateLimitAlert> = alerts
-            .iter()
-            .filter(|alert| alert.timestamp > one_hour_ago)
-            .cloned()
-            .collect();
+        let one_hour_ago = self.clock.now() - chrono::Duration::hours(1);
+        let recent_alerts = alerts.since(one_hour_ago).await?;
 
         debug!("Found {} recent alerts", recent_alerts.len());
         Ok(recent_alerts)
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::error::{ApiError, AppError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use crate::models::research_workflow::ProviderRecording;
+use super::mock_providers::MockSettings;
+use super::response_schema;
+use super::service_integration::{ServiceRequest, ServiceResponse};
+
+tokio::task_local! {
+    static STEP_SCOPE: StepScope;
+}
+
+/// The workflow step a provider call is made on behalf of
+struct StepScope {
+    workflow_id: Uuid,
+    step_number: u32,
+    recording: ProviderRecording,
+    next_sequence: AtomicU32,
+}
+
+/// Run a step's provider calls under its workflow's recording setting.
+/// Calls made outside a step scope are never recorded or replayed.
+pub async fn step_scope<F: Future>(workflow_id: Uuid, step_number: u32, recording: ProviderRecording, call: F) -> F::Output {
+    STEP_SCOPE.scope(StepScope { workflow_id, step_number, recording, next_sequence: AtomicU32::new(0) }, call).await
+}
+
+/// Whether the current step is recording its provider calls
+pub fn is_recording() -> bool {
+    STEP_SCOPE.try_with(|scope| scope.recording == ProviderRecording::Record).unwrap_or(false)
+}
+
+/// How the mock providers answer the current step, when its workflow runs in mock mode
+pub fn mock_settings() -> Option<MockSettings> {
+    STEP_SCOPE.try_with(|scope| match scope.recording {
+        ProviderRecording::Mock { latency_ms, failure_percent } => Some(MockSettings { latency_ms, failure_percent }),
+        _ => None,
+    }).ok().flatten()
+}
+
+/// Whether the current step's provider calls go to the mock providers
+pub fn is_mocked() -> bool {
+    mock_settings().is_some()
+}
+
+/// One provider call captured during a recorded run, with credentials masked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub workflow_id: Uuid,
+    pub step_number: u32,
+    /// Order of the call within its step
+    pub sequence: u32,
+    pub service: ServiceProvider,
+    pub endpoint: String,
+    pub method: String,
+    pub request_body: Option<String>,
+    /// `None` when the call failed before a response arrived
+    pub response: Option<ServiceResponse>,
+    pub error_message: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Persists provider responses per workflow step and feeds them back on replay.
+///
+/// Each recorded workflow gets a directory holding one JSON-lines file per step, so a
+/// recording can be checked in as a regression fixture as-is.
+pub struct ResponseRecorder {
+    root: PathBuf,
+}
+
+impl ResponseRecorder {
+    pub fn new() -> AppResult<Self> {
+        Ok(Self::with_root(crate::utils::get_app_data_dir()?.join("recordings")))
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Directory holding a workflow's recording
+    pub fn recording_dir(&self, workflow_id: Uuid) -> PathBuf {
+        self.root.join(workflow_id.to_string())
+    }
+
+    fn step_file(&self, workflow_id: Uuid, step_number: u32) -> PathBuf {
+        self.recording_dir(workflow_id).join(format!("step_{:03}.jsonl", step_number))
+    }
+
+    /// Serve the call from the recording when the current step is replaying.
+    /// `Ok(None)` means the call should go to the live provider.
+    pub fn replay(&self, request: &ServiceRequest) -> AppResult<Option<ServiceResponse>> {
+        let replayed = STEP_SCOPE.try_with(|scope| -> AppResult<Option<ServiceResponse>> {
+            let source_workflow_id = match scope.recording {
+                ProviderRecording::Replay { source_workflow_id } => source_workflow_id,
+                _ => return Ok(None),
+            };
+
+            let sequence = scope.next_sequence.load(Ordering::SeqCst);
+            let exchanges = self.read_step(source_workflow_id, scope.step_number)?;
+            let exchange = exchanges.into_iter().find(|exchange| exchange.sequence == sequence)
+                .ok_or_else(|| ApiError::ServiceUnavailable {
+                    service: format!(
+                        "{:?} (recording of workflow {} has no call {} for step {})",
+                        request.service, source_workflow_id, sequence, scope.step_number
+                    ),
+                })?;
+
+            // A fallback chain tries providers in order; one the recorded run did not
+            // reach here is refused without consuming the recorded call
+            if exchange.service != request.service {
+                return Err(ApiError::ServiceUnavailable {
+                    service: format!("{:?} (replaying a {:?} response)", request.service, exchange.service),
+                }.into());
+            }
+            scope.next_sequence.fetch_add(1, Ordering::SeqCst);
+            debug!("Replaying call {} of step {} from workflow {}", sequence, scope.step_number, source_workflow_id);
+
+            match exchange.response {
+                Some(mut response) => {
+                    response.request_id = request.request_id;
+                    Ok(Some(response))
+                }
+                None => Err(ApiError::request_failed(
+                    format!("{:?}", exchange.service),
+                    0,
+                    exchange.error_message.unwrap_or_else(|| "recorded call failed".to_string()),
+                ).into()),
+            }
+        });
+
+        replayed.unwrap_or(Ok(None))
+    }
+
+    /// Append the outcome of a live call to the current step's recording, if recording.
+    /// Recording failures are logged rather than failing the call.
+    pub fn record(&self, request: &ServiceRequest, result: &AppResult<ServiceResponse>) {
+        let _ = STEP_SCOPE.try_with(|scope| {
+            if scope.recording != ProviderRecording::Record {
+                return;
+            }
+
+            let exchange = RecordedExchange {
+                workflow_id: scope.workflow_id,
+                step_number: scope.step_number,
+                sequence: scope.next_sequence.fetch_add(1, Ordering::SeqCst),
+                service: request.service,
+                endpoint: request.endpoint.clone(),
+                method: request.method.clone(),
+                request_body: request.body.as_deref().map(response_schema::redact_credentials),
+                response: result.as_ref().ok().map(redact_response),
+                error_message: result.as_ref().err().map(|e| e.to_string()),
+                recorded_at: Utc::now(),
+            };
+
+            if let Err(e) = self.append(&exchange) {
+                warn!("Failed to record provider response for workflow {} step {}: {}",
+                    scope.workflow_id, scope.step_number, e);
+            }
+        });
+    }
+
+    fn append(&self, exchange: &RecordedExchange) -> AppResult<()> {
+        std::fs::create_dir_all(self.recording_dir(exchange.workflow_id))
+            .map_err(|e| AppError::io(e.to_string()))?;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.step_file(exchange.workflow_id, exchange.step_number))
+            .map_err(|e| AppError::io(e.to_string()))?;
+        writeln!(file, "{}", serde_json::to_string(exchange)?)
+            .map_err(|e| AppError::io(e.to_string()))
+    }
+
+    fn read_step(&self, workflow_id: Uuid, step_number: u32) -> AppResult<Vec<RecordedExchange>> {
+        let path = self.step_file(workflow_id, step_number);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&path).map_err(|e| AppError::io(e.to_string()))?;
+        content.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(Into::into))
+            .collect()
+    }
+
+    /// Every call recorded for a workflow, ordered by step and sequence
+    pub fn get_recording(&self, workflow_id: Uuid) -> AppResult<Vec<RecordedExchange>> {
+        let mut exchanges = Vec::new();
+        for path in self.recording_files(workflow_id)? {
+            let content = std::fs::read_to_string(&path).map_err(|e| AppError::io(e.to_string()))?;
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                exchanges.push(serde_json::from_str::<RecordedExchange>(line)?);
+            }
+        }
+        exchanges.sort_by_key(|exchange| (exchange.step_number, exchange.sequence));
+        Ok(exchanges)
+    }
+
+    /// The per-step files of a workflow's recording; empty when it was not recorded
+    pub fn recording_files(&self, workflow_id: Uuid) -> AppResult<Vec<PathBuf>> {
+        let dir = self.recording_dir(workflow_id);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = std::fs::read_dir(&dir)
+            .map_err(|e| AppError::io(e.to_string()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "jsonl"))
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Store calls recorded elsewhere, such as in an imported bundle, as `workflow_id`'s recording
+    pub fn import_recording(&self, workflow_id: Uuid, exchanges: &[RecordedExchange]) -> AppResult<()> {
+        for exchange in exchanges {
+            self.append(&RecordedExchange { workflow_id, ..exchange.clone() })?;
+        }
+        Ok(())
+    }
+
+    /// Delete a workflow's recording; returns whether there was one
+    pub fn delete_recording(&self, workflow_id: Uuid) -> AppResult<bool> {
+        let dir = self.recording_dir(workflow_id);
+        if !dir.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(&dir).map_err(|e| AppError::io(e.to_string()))?;
+        Ok(true)
+    }
+}
+
+fn redact_response(response: &ServiceResponse) -> ServiceResponse {
+    let mut response = response.clone();
+    response.body = response_schema::redact_credentials(&response.body);
+    response.headers = redact_headers(&response.headers);
+    response
+}
+
+fn redact_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
+    headers.iter()
+        .map(|(name, value)| {
+            let lower = name.to_lowercase();
+            let sensitive = response_schema::is_sensitive_key(&lower)
+                || ["key", "token", "auth", "cookie"].iter().any(|marker| lower.contains(marker));
+            (name.clone(), if sensitive { "***".to_string() } else { value.clone() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(service: ServiceProvider) -> ServiceRequest {
+        ServiceRequest {
+            request_id: Uuid::new_v4(),
+            service,
+            endpoint: "/search".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: Some(r#"{"q":"rust","api_key":"serp-secret"}"#.to_string()),
+            timeout_ms: 1000,
+            retry_count: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn response(service: ServiceProvider, body: &str) -> ServiceResponse {
+        ServiceResponse {
+            request_id: Uuid::new_v4(),
+            service,
+            status_code: 200,
+            headers: HashMap::from([("Set-Cookie".to_string(), "session=abc".to_string())]),
+            body: body.to_string(),
+            response_time_ms: 12,
+            success: true,
+            error_message: None,
+            metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_step_replays_redacted_responses() {
+        let recorder = ResponseRecorder::with_root(std::env::temp_dir().join(format!("recordings_{}", Uuid::new_v4())));
+        let recorded_workflow = Uuid::new_v4();
+
+        step_scope(recorded_workflow, 1, ProviderRecording::Record, async {
+            recorder.record(&request(ServiceProvider::SerpApi), &Err(ApiError::ServiceUnavailable { service: "SerpApi".to_string() }.into()));
+            recorder.record(&request(ServiceProvider::Tavily), &Ok(response(ServiceProvider::Tavily, r#"{"results":[],"token":"t-secret"}"#)));
+        }).await;
+
+        // Outside a step scope nothing is recorded
+        recorder.record(&request(ServiceProvider::SerpApi), &Ok(response(ServiceProvider::SerpApi, "{}")));
+
+        let recording = recorder.get_recording(recorded_workflow).unwrap();
+        assert_eq!(recording.len(), 2);
+        assert!(!recording[1].request_body.as_ref().unwrap().contains("serp-secret"));
+        let recorded_response = recording[1].response.as_ref().unwrap();
+        assert!(!recorded_response.body.contains("t-secret"));
+        assert_eq!(recorded_response.headers["Set-Cookie"], "***");
+
+        let replay = ProviderRecording::Replay { source_workflow_id: recorded_workflow };
+        step_scope(Uuid::new_v4(), 1, replay, async {
+            assert!(recorder.replay(&request(ServiceProvider::SerpApi)).is_err());
+            // A provider the recorded run did not call is refused without consuming the call
+            assert!(recorder.replay(&request(ServiceProvider::Exa)).is_err());
+            let replayed = recorder.replay(&request(ServiceProvider::Tavily)).unwrap().unwrap();
+            assert_eq!(replayed.body, recorded_response.body);
+            assert!(recorder.replay(&request(ServiceProvider::Tavily)).is_err());
+        }).await;
+
+        assert!(recorder.replay(&request(ServiceProvider::SerpApi)).unwrap().is_none());
+        assert!(recorder.delete_recording(recorded_workflow).unwrap());
+    }
+}
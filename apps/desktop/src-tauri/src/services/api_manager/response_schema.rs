@@ -0,0 +1,343 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::models::api_key::ServiceProvider;
+use super::provider_adapter;
+
+/// Longest payload excerpt written to logs when a response fails validation
+const MAX_LOGGED_PAYLOAD_CHARS: usize = 2000;
+
+/// Object keys whose values are masked before a payload is logged
+const SENSITIVE_KEYS: &[&str] = &["api_key", "apikey", "key", "token", "access_token", "authorization", "password", "secret"];
+
+/// One search result, whichever provider returned it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub title: String,
+    pub link: String,
+    pub snippet: String,
+    /// When the source was published, if the provider says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// Provider response mapped into a provider-independent shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NormalizedPayload {
+    SearchResults { hits: Vec<SearchHit> },
+    Completion { content: String, model: Option<String>, total_tokens: Option<u64> },
+    Embeddings { model: Option<String>, vectors: Vec<Vec<f64>> },
+    ScrapedPage { markdown: String, title: Option<String> },
+    Links { links: Vec<String> },
+}
+
+impl NormalizedPayload {
+    /// Search hits in SerpApi's `organic_results` layout, which the step
+    /// handlers have always consumed
+    pub fn search_results_json(hits: &[SearchHit]) -> Value {
+        serde_json::json!({ "organic_results": hits })
+    }
+}
+
+/// Where a provider response departed from its expected schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+fn violation(path: impl Into<String>, message: impl Into<String>) -> SchemaViolation {
+    SchemaViolation { path: path.into(), message: message.into() }
+}
+
+fn array<'a>(body: &'a Value, key: &str) -> Result<&'a Vec<Value>, SchemaViolation> {
+    body.get(key)
+        .ok_or_else(|| violation(key, "missing"))?
+        .as_array()
+        .ok_or_else(|| violation(key, "expected an array"))
+}
+
+fn string(item: &Value, key: &str, path: &str) -> Result<String, SchemaViolation> {
+    item.get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| violation(format!("{}.{}", path, key), "expected a string"))
+}
+
+fn optional_string(item: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| item.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A provider's publication date: RFC 3339, RFC 2822, a bare date, Google's "Mar 3, 2024",
+/// or a relative "3 days ago"
+pub fn parse_published_date(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d", "%b %d, %Y", "%B %d, %Y", "%d %b %Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return date.and_hms_opt(0, 0, 0).map(|date| date.and_utc());
+        }
+    }
+    let mut words = text.strip_suffix(" ago")?.split_whitespace();
+    let amount: i64 = words.next()?.parse().ok()?;
+    let unit = words.next()?.trim_end_matches('s');
+    let window = match unit {
+        "minute" | "min" => chrono::Duration::minutes(amount),
+        "hour" => chrono::Duration::hours(amount),
+        "day" => chrono::Duration::days(amount),
+        "week" => chrono::Duration::weeks(amount),
+        "month" => chrono::Duration::days(amount * 30),
+        "year" => chrono::Duration::days(amount * 365),
+        _ => return None,
+    };
+    Some(now - window)
+}
+
+fn search_hits(items: &[Value], root: &str, link_key: &str, snippet_keys: &[&str], date_key: &str) -> Result<Vec<SearchHit>, SchemaViolation> {
+    let now = Utc::now();
+    items.iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let path = format!("{}[{}]", root, i);
+            if !item.is_object() {
+                return Err(violation(path, "expected an object"));
+            }
+            Ok(SearchHit {
+                title: optional_string(item, &["title"]),
+                link: string(item, link_key, &path)?,
+                snippet: optional_string(item, snippet_keys),
+                published: item.get(date_key).and_then(Value::as_str).and_then(|date| parse_published_date(date, now)),
+            })
+        })
+        .collect()
+}
+
+/// Parse a response body that must be a JSON object without an `error` set
+pub fn json_object(body: &str) -> Result<Value, SchemaViolation> {
+    let body: Value = serde_json::from_str(body).map_err(|e| violation("$", format!("invalid JSON: {}", e)))?;
+    if !body.is_object() {
+        return Err(violation("$", "expected an object"));
+    }
+    if let Some(error) = body.get("error").filter(|error| !error.is_null()) {
+        return Err(violation("error", format!("provider returned an error: {}", error)));
+    }
+    Ok(body)
+}
+
+/// Search hits from the array of objects at `root`, reading each hit's link, snippet
+/// and publication date from the first of the given keys it has
+pub fn search_results(body: &Value, root: &str, link_key: &str, snippet_keys: &[&str], date_key: &str) -> Result<NormalizedPayload, SchemaViolation> {
+    Ok(NormalizedPayload::SearchResults {
+        hits: search_hits(array(body, root)?, root, link_key, snippet_keys, date_key)?,
+    })
+}
+
+/// Check a successful response body against the schema for `provider` and
+/// `endpoint`, mapping it into a [`NormalizedPayload`]. Custom providers are checked
+/// by their adapter.
+///
+/// Returns `Ok(None)` for endpoints without a known schema; their bodies are
+/// passed through untouched.
+pub fn validate_response(provider: ServiceProvider, endpoint: &str, body: &str) -> Result<Option<NormalizedPayload>, SchemaViolation> {
+    let endpoint = endpoint.split('?').next().unwrap_or(endpoint).trim_end_matches('/');
+    if let ServiceProvider::Custom(custom) = provider {
+        return match provider_adapter::adapter_for(custom) {
+            Some(adapter) => adapter.normalize_response(endpoint, body),
+            None => Ok(None),
+        };
+    }
+    let known = matches!(
+        (provider, endpoint),
+        (ServiceProvider::SerpApi, "/search")
+            | (ServiceProvider::Tavily, "/search")
+            | (ServiceProvider::Exa, "/search")
+            | (ServiceProvider::Jina, "/embeddings")
+            | (ServiceProvider::Firecrawl, "/scrape")
+            | (ServiceProvider::Firecrawl, "/map")
+            | (ServiceProvider::OpenRouter, "/chat/completions")
+    );
+    if !known {
+        return Ok(None);
+    }
+
+    let body = json_object(body)?;
+
+    let payload = match (provider, endpoint) {
+        (ServiceProvider::SerpApi, _) => {
+            // A search with no hits omits `organic_results` entirely
+            let hits = match body.get("organic_results") {
+                Some(_) => search_hits(array(&body, "organic_results")?, "organic_results", "link", &["snippet"], "date")?,
+                None => Vec::new(),
+            };
+            NormalizedPayload::SearchResults { hits }
+        }
+        (ServiceProvider::Tavily, _) => search_results(&body, "results", "url", &["content"], "published_date")?,
+        (ServiceProvider::Exa, _) => search_results(&body, "results", "url", &["text", "highlight"], "publishedDate")?,
+        (ServiceProvider::Jina, _) => {
+            let vectors = array(&body, "data")?
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    item.get("embedding")
+                        .and_then(Value::as_array)
+                        .and_then(|values| values.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+                        .ok_or_else(|| violation(format!("data[{}].embedding", i), "expected an array of numbers"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            NormalizedPayload::Embeddings {
+                model: body.get("model").and_then(Value::as_str).map(str::to_string),
+                vectors,
+            }
+        }
+        (ServiceProvider::Firecrawl, "/scrape") => {
+            let data = body.get("data").filter(|data| data.is_object())
+                .ok_or_else(|| violation("data", "expected an object"))?;
+            NormalizedPayload::ScrapedPage {
+                markdown: string(data, "markdown", "data")?,
+                title: data.pointer("/metadata/title").and_then(Value::as_str).map(str::to_string),
+            }
+        }
+        (ServiceProvider::Firecrawl, _) => NormalizedPayload::Links {
+            links: array(&body, "links")?
+                .iter()
+                .enumerate()
+                .map(|(i, link)| link.as_str().map(str::to_string)
+                    .ok_or_else(|| violation(format!("links[{}]", i), "expected a string")))
+                .collect::<Result<Vec<_>, _>>()?,
+        },
+        (ServiceProvider::OpenRouter, _) => {
+            let choice = array(&body, "choices")?
+                .first()
+                .ok_or_else(|| violation("choices", "expected at least one choice"))?;
+            // A forced tool call answers in the call's arguments, usually next to an empty
+            // or null message content
+            let content = choice.pointer("/message/tool_calls/0/function/arguments")
+                .and_then(Value::as_str)
+                .or_else(|| choice.pointer("/message/content").and_then(Value::as_str));
+            NormalizedPayload::Completion {
+                content: content
+                    .map(str::to_string)
+                    .ok_or_else(|| violation("choices[0].message.content", "expected a string"))?,
+                model: body.get("model").and_then(Value::as_str).map(str::to_string),
+                total_tokens: body.pointer("/usage/total_tokens").and_then(Value::as_u64),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(payload))
+}
+
+/// Payload excerpt safe to log: credentials are masked and the text is truncated
+pub fn redact_payload(body: &str) -> String {
+    let redacted = redact_credentials(body);
+
+    if redacted.chars().count() > MAX_LOGGED_PAYLOAD_CHARS {
+        let excerpt: String = redacted.chars().take(MAX_LOGGED_PAYLOAD_CHARS).collect();
+        format!("{}... ({} bytes total)", excerpt, redacted.len())
+    } else {
+        redacted
+    }
+}
+
+/// Full payload with credentials masked; bodies that are not JSON are returned unchanged
+pub fn redact_credentials(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+/// Whether a JSON field or header with this name carries a credential
+pub fn is_sensitive_key(key: &str) -> bool {
+    SENSITIVE_KEYS.contains(&key.to_lowercase().as_str())
+}
+
+/// Mask credential fields anywhere in a JSON value
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value = Value::String("***".to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_providers_normalize_to_common_hits() {
+        let serpapi = r#"{"organic_results":[{"title":"A","link":"https://a.example","snippet":"a"}]}"#;
+        let tavily = r#"{"results":[{"title":"A","url":"https://a.example","content":"a"}]}"#;
+
+        let expected = Some(NormalizedPayload::SearchResults {
+            hits: vec![SearchHit { title: "A".to_string(), link: "https://a.example".to_string(), snippet: "a".to_string(), published: None }],
+        });
+        assert_eq!(validate_response(ServiceProvider::SerpApi, "/search", serpapi).unwrap(), expected);
+        assert_eq!(validate_response(ServiceProvider::Tavily, "/search", tavily).unwrap(), expected);
+        assert_eq!(validate_response(ServiceProvider::OpenRouter, "/models", "[]").unwrap(), None);
+
+        let now = Utc::now();
+        let march = NaiveDate::from_ymd_opt(2024, 3, 3).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        assert_eq!(parse_published_date("Mar 3, 2024", now), Some(march));
+        assert_eq!(parse_published_date("2024-03-03T00:00:00Z", now), Some(march));
+        assert_eq!(parse_published_date("2 days ago", now), Some(now - chrono::Duration::days(2)));
+        assert_eq!(parse_published_date("sometime", now), None);
+    }
+
+    #[test]
+    fn test_malformed_responses_are_rejected() {
+        let renamed = r#"{"items":[{"url":"https://a.example"}]}"#;
+        assert_eq!(validate_response(ServiceProvider::Exa, "/search", renamed).unwrap_err().path, "results");
+
+        let missing_link = r#"{"results":[{"title":"A"}]}"#;
+        assert_eq!(validate_response(ServiceProvider::Tavily, "/search", missing_link).unwrap_err().path, "results[0].url");
+
+        let empty_choices = r#"{"choices":[]}"#;
+        assert!(validate_response(ServiceProvider::OpenRouter, "/chat/completions", empty_choices).is_err());
+    }
+
+    #[test]
+    fn test_tool_call_arguments_win_over_empty_content() {
+        let tool_call = r#"{"choices":[{"message":{"content":"","tool_calls":[{"function":{"name":"record","arguments":"{\"report\":\"r\"}"}}]}}]}"#;
+        let Some(NormalizedPayload::Completion { content, .. }) = validate_response(ServiceProvider::OpenRouter, "/chat/completions", tool_call).unwrap() else {
+            panic!("expected a completion");
+        };
+        assert_eq!(content, r#"{"report":"r"}"#);
+    }
+
+    #[test]
+    fn test_redact_payload_masks_credentials() {
+        let redacted = redact_payload(r#"{"search_parameters":{"api_key":"serp-secret","q":"rust"}}"#);
+        assert!(!redacted.contains("serp-secret"));
+        assert!(redacted.contains("rust"));
+    }
+}
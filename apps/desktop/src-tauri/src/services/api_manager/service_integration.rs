@@ -9,7 +9,11 @@ use async_trait::async_trait;
 use rand;
 
 use crate::error::{AppResult, ApiError};
-use crate::models::api_key::{ServiceProvider, ApiKey};
+use crate::models::api_key::ServiceProvider;
+use crate::services::security::SecretString;
+use super::concurrency::ConcurrencyLimiter;
+use super::provider_adapter;
+use super::response_schema::{self, NormalizedPayload};
 
 /// Standard request structure for all services
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +42,9 @@ pub struct ServiceResponse {
     pub error_message: Option<String>,
     pub metadata: HashMap<String, String>,
     pub timestamp: DateTime<Utc>,
+    /// Body mapped into a provider-independent shape once it passes schema validation
+    #[serde(default)]
+    pub normalized: Option<NormalizedPayload>,
 }
 
 /// Service health status
@@ -61,10 +68,17 @@ pub struct ServiceConfig {
     pub health_check_endpoint: Option<String>,
     pub health_check_interval_minutes: u32,
     pub rate_limit_per_minute: u32,
+    /// Most requests in flight to the service at once, independent of the rate limit
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: u32,
     pub custom_headers: HashMap<String, String>,
     pub enabled: bool,
 }
 
+fn default_max_concurrent_requests() -> u32 {
+    5
+}
+
 impl ServiceConfig {
     /// Get default configuration for a service
     pub fn default_for_service(service: ServiceProvider) -> Self {
@@ -78,6 +92,7 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/models".to_string()),
                 health_check_interval_minutes: 5,
                 rate_limit_per_minute: 50,
+                max_concurrent_requests: 10,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
@@ -90,6 +105,7 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/search".to_string()),
                 health_check_interval_minutes: 10,
                 rate_limit_per_minute: 100,
+                max_concurrent_requests: 5,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
@@ -102,6 +118,7 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/embeddings".to_string()),
                 health_check_interval_minutes: 5,
                 rate_limit_per_minute: 1000,
+                max_concurrent_requests: 10,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
@@ -114,6 +131,7 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/scrape".to_string()),
                 health_check_interval_minutes: 10,
                 rate_limit_per_minute: 500,
+                max_concurrent_requests: 2,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
@@ -126,6 +144,7 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/search".to_string()),
                 health_check_interval_minutes: 5,
                 rate_limit_per_minute: 1000,
+                max_concurrent_requests: 5,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
@@ -138,9 +157,31 @@ impl ServiceConfig {
                 health_check_endpoint: Some("/search".to_string()),
                 health_check_interval_minutes: 5,
                 rate_limit_per_minute: 1000,
+                max_concurrent_requests: 5,
                 custom_headers: HashMap::new(),
                 enabled: true,
             },
+            ServiceProvider::Custom(provider) => match provider_adapter::adapter_for(provider) {
+                Some(adapter) => adapter.service_config(service),
+                None => Self::for_custom_provider(service),
+            },
+        }
+    }
+
+    /// Defaults for a custom provider, before its adapter sets the base URL
+    pub fn for_custom_provider(service: ServiceProvider) -> Self {
+        Self {
+            service,
+            base_url: String::new(),
+            default_timeout_ms: 30000,
+            max_retries: 2,
+            retry_delay_ms: 1000,
+            health_check_endpoint: None,
+            health_check_interval_minutes: 10,
+            rate_limit_per_minute: 60,
+            max_concurrent_requests: 5,
+            custom_headers: HashMap::new(),
+            enabled: true,
         }
     }
 }
@@ -161,6 +202,9 @@ impl MockServiceIntegration {
                 ServiceProvider::Firecrawl => "https://api.firecrawl.dev/v0".to_string(),
                 ServiceProvider::Jina => "https://api.jina.ai/v1".to_string(),
                 ServiceProvider::Exa => "https://api.exa.ai".to_string(),
+                ServiceProvider::Custom(provider) => provider_adapter::adapter_for(provider)
+                    .map(|adapter| adapter.base_url())
+                    .unwrap_or_default(),
             },
             timeout_ms: 30000,
             max_retries: 3,
@@ -172,6 +216,9 @@ impl MockServiceIntegration {
                 ServiceProvider::Firecrawl => vec!["/scrape".to_string(), "/map".to_string()],
                 ServiceProvider::Jina => vec!["/embeddings".to_string()],
                 ServiceProvider::Exa => vec!["/search".to_string()],
+                ServiceProvider::Custom(provider) => provider_adapter::adapter_for(provider)
+                    .map(|adapter| adapter.endpoints())
+                    .unwrap_or_default(),
             },
             headers: HashMap::new(),
         };
@@ -183,7 +230,7 @@ impl MockServiceIntegration {
     }
 
     /// Generate mock response based on service and endpoint
-    fn generate_mock_response(&self, request: &ServiceRequest) -> ServiceResponse {
+    pub fn generate_mock_response(&self, request: &ServiceRequest) -> ServiceResponse {
         let mock_data = match (&self.service_provider, request.endpoint.as_str()) {
             (ServiceProvider::SerpApi, _) => {
                 serde_json::json!({
@@ -288,6 +335,14 @@ impl MockServiceIntegration {
                     ]
                 })
             },
+            (ServiceProvider::Custom(provider), endpoint) => {
+                provider_adapter::adapter_for(*provider)
+                    .and_then(|adapter| adapter.mock_body(endpoint))
+                    .unwrap_or_else(|| serde_json::json!({
+                        "message": "Mock response",
+                        "status": "success"
+                    }))
+            },
             _ => {
                 serde_json::json!({
                     "message": "Mock response",
@@ -298,6 +353,7 @@ impl MockServiceIntegration {
 
         ServiceResponse {
             request_id: request.request_id,
+            service: self.service_provider,
             success: true,
             status_code: 200,
             headers: HashMap::new(),
@@ -305,6 +361,8 @@ impl MockServiceIntegration {
             response_time_ms: rand::random::<u32>() % 1000 + 200, // Random response time 200-1200ms
             error_message: None,
             metadata: HashMap::new(),
+            timestamp: Utc::now(),
+            normalized: None,
         }
     }
 }
@@ -315,7 +373,7 @@ impl ServiceIntegration for MockServiceIntegration {
         self.service_provider
     }
 
-    async fn make_request(&self, request: ServiceRequest, _api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    async fn make_request(&self, request: ServiceRequest, _api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making mock request to {:?} endpoint: {}", self.service_provider, request.endpoint);
 
         // Simulate network delay
@@ -329,7 +387,7 @@ impl ServiceIntegration for MockServiceIntegration {
         Ok(response)
     }
 
-    async fn health_check(&self, _api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    async fn health_check(&self, _api_key: &SecretString) -> AppResult<ServiceHealth> {
         // Simulate health check delay
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
@@ -352,9 +410,9 @@ impl ServiceIntegration for MockServiceIntegration {
         Ok(())
     }
 
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool> {
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool> {
         // Mock validation - just check if key is not empty
-        Ok(!api_key.encrypted_key.is_empty())
+        Ok(!api_key.is_empty())
     }
 
     fn get_endpoints(&self) -> Vec<String> {
@@ -377,6 +435,11 @@ pub struct ServiceMetrics {
     pub uptime_percentage: f64,
     pub error_rate: f64,
     pub requests_per_minute: f64,
+    /// Requests to the service in flight now
+    #[serde(default)]
+    pub in_flight_requests: u32,
+    #[serde(default)]
+    pub max_concurrent_requests: u32,
 }
 
 impl ServiceMetrics {
@@ -395,6 +458,8 @@ impl ServiceMetrics {
             uptime_percentage: 100.0,
             error_rate: 0.0,
             requests_per_minute: 0.0,
+            in_flight_requests: 0,
+            max_concurrent_requests: 0,
         }
     }
 
@@ -448,10 +513,10 @@ pub trait ServiceIntegration: Send + Sync {
     fn service_provider(&self) -> ServiceProvider;
 
     /// Make a request to the service
-    async fn make_request(&self, request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse>;
+    async fn make_request(&self, request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse>;
 
     /// Perform health check
-    async fn health_check(&self, api_key: &ApiKey) -> AppResult<ServiceHealth>;
+    async fn health_check(&self, api_key: &SecretString) -> AppResult<ServiceHealth>;
 
     /// Get service configuration
     fn get_config(&self) -> &ServiceConfig;
@@ -460,7 +525,7 @@ pub trait ServiceIntegration: Send + Sync {
     async fn update_config(&mut self, config: ServiceConfig) -> AppResult<()>;
 
     /// Validate API key for this service
-    async fn validate_api_key(&self, api_key: &ApiKey) -> AppResult<bool>;
+    async fn validate_api_key(&self, api_key: &SecretString) -> AppResult<bool>;
 
     /// Get service-specific endpoints
     fn get_endpoints(&self) -> Vec<String>;
@@ -477,6 +542,7 @@ pub struct ServiceIntegrationManager {
     integrations: HashMap<ServiceProvider, Box<dyn ServiceIntegration>>,
     metrics: Arc<RwLock<HashMap<ServiceProvider, ServiceMetrics>>>,
     configs: Arc<RwLock<HashMap<ServiceProvider, ServiceConfig>>>,
+    concurrency: ConcurrencyLimiter,
 }
 
 impl ServiceIntegrationManager {
@@ -486,18 +552,14 @@ impl ServiceIntegrationManager {
 
         let mut metrics = HashMap::new();
         let mut configs = HashMap::new();
+        let concurrency = ConcurrencyLimiter::default();
 
         // Initialize metrics and configs for all services
-        for service in [
-            ServiceProvider::OpenRouter,
-            ServiceProvider::SerpApi,
-            ServiceProvider::Jina,
-            ServiceProvider::Firecrawl,
-            ServiceProvider::Tavily,
-            ServiceProvider::Exa,
-        ] {
+        for service in ServiceProvider::all() {
+            let config = ServiceConfig::default_for_service(service);
+            concurrency.set_limit(service, config.max_concurrent_requests)?;
             metrics.insert(service.clone(), ServiceMetrics::new(service.clone()));
-            configs.insert(service.clone(), ServiceConfig::default_for_service(service));
+            configs.insert(service.clone(), config);
         }
 
         // Initialize mock service integrations for development/testing
@@ -513,6 +575,7 @@ impl ServiceIntegrationManager {
             integrations,
             metrics: Arc::new(RwLock::new(metrics)),
             configs: Arc::new(RwLock::new(configs)),
+            concurrency,
         };
 
         info!("Service integration manager initialized successfully");
@@ -523,6 +586,14 @@ impl ServiceIntegrationManager {
     pub async fn register_integration(&mut self, integration: Box<dyn ServiceIntegration>) -> AppResult<()> {
         let service = integration.service_provider();
         info!("Registering integration for service: {:?}", service);
+
+        // A custom provider registered after start-up has no config or metrics yet
+        if !self.configs.read().await.contains_key(&service) {
+            let config = integration.get_config().clone();
+            self.concurrency.set_limit(service, config.max_concurrent_requests)?;
+            self.metrics.write().await.insert(service, ServiceMetrics::new(service));
+            self.configs.write().await.insert(service, config);
+        }
         
         self.integrations.insert(service, integration);
         
@@ -531,7 +602,7 @@ impl ServiceIntegrationManager {
     }
 
     /// Make a request through the appropriate service integration
-    pub async fn make_service_request(&self, service: ServiceProvider, request: ServiceRequest, api_key: &ApiKey) -> AppResult<ServiceResponse> {
+    pub async fn make_service_request(&self, service: ServiceProvider, request: ServiceRequest, api_key: &SecretString) -> AppResult<ServiceResponse> {
         debug!("Making request to service: {:?}", service);
 
         let integration = self.integrations.get(&service)
@@ -540,9 +611,16 @@ impl ServiceIntegrationManager {
                 "No integration found for service".to_string()
             ))?;
 
+        // Held until the response is in, so a burst queues here instead of being
+        // turned away by the provider
+        let permit = self.concurrency.acquire(service).await;
+
+        let endpoint = request.endpoint.clone();
         let start_time = std::time::Instant::now();
-        let result = integration.make_request(request, api_key).await;
+        let result = integration.make_request(request, api_key).await
+            .and_then(|response| Self::validate_response(service, &endpoint, response));
         let response_time = start_time.elapsed().as_millis() as u32;
+        drop(permit);
 
         // Update metrics
         match &result {
@@ -565,6 +643,7 @@ impl ServiceIntegrationManager {
                     error_message: Some("Request failed".to_string()),
                     metadata: HashMap::new(),
                     timestamp: Utc::now(),
+                    normalized: None,
                 };
 
                 let mut metrics = self.metrics.write().await;
@@ -577,8 +656,33 @@ impl ServiceIntegrationManager {
         result
     }
 
+    /// Reject successful responses whose body does not match the provider's schema,
+    /// so a provider API change fails loudly instead of breaking extraction downstream
+    fn validate_response(service: ServiceProvider, endpoint: &str, mut response: ServiceResponse) -> AppResult<ServiceResponse> {
+        if !response.success {
+            return Ok(response);
+        }
+
+        match response_schema::validate_response(service, endpoint, &response.body) {
+            Ok(normalized) => {
+                response.normalized = normalized;
+                Ok(response)
+            }
+            Err(violation) => {
+                warn!(
+                    "Malformed {:?} response from {} ({}): {}",
+                    service, endpoint, violation, response_schema::redact_payload(&response.body)
+                );
+                Err(ApiError::InvalidResponse {
+                    service: format!("{:?}", service),
+                    message: format!("unexpected response schema at {}", violation),
+                }.into())
+            }
+        }
+    }
+
     /// Perform health check for a service
-    pub async fn check_service_health(&self, service: ServiceProvider, api_key: &ApiKey) -> AppResult<ServiceHealth> {
+    pub async fn check_service_health(&self, service: ServiceProvider, api_key: &SecretString) -> AppResult<ServiceHealth> {
         debug!("Checking health for service: {:?}", service);
 
         let integration = self.integrations.get(&service)
@@ -593,16 +697,29 @@ impl ServiceIntegrationManager {
     /// Get metrics for a service
     pub async fn get_service_metrics(&self, service: ServiceProvider) -> Option<ServiceMetrics> {
         let metrics = self.metrics.read().await;
-        metrics.get(&service).cloned()
+        metrics.get(&service).cloned().map(|metrics| self.with_concurrency(metrics))
     }
 
     /// Get metrics for all services
     pub async fn get_all_service_metrics(&self) -> HashMap<ServiceProvider, ServiceMetrics> {
-        self.metrics.read().await.clone()
+        let metrics = self.metrics.read().await;
+        metrics.iter()
+            .map(|(service, metrics)| (*service, self.with_concurrency(metrics.clone())))
+            .collect()
+    }
+
+    fn with_concurrency(&self, mut metrics: ServiceMetrics) -> ServiceMetrics {
+        if let Some((in_flight, max)) = self.concurrency.usage(metrics.service) {
+            metrics.in_flight_requests = in_flight;
+            metrics.max_concurrent_requests = max;
+        }
+        metrics
     }
 
     /// Update service configuration
     pub async fn update_service_config(&mut self, service: ServiceProvider, config: ServiceConfig) -> AppResult<()> {
+        self.concurrency.set_limit(service, config.max_concurrent_requests)?;
+
         // Update stored config
         let mut configs = self.configs.write().await;
         configs.insert(service, config.clone());
@@ -629,7 +746,7 @@ impl ServiceIntegrationManager {
     }
 
     /// Validate API key for a service
-    pub async fn validate_service_api_key(&self, service: ServiceProvider, api_key: &ApiKey) -> AppResult<bool> {
+    pub async fn validate_service_api_key(&self, service: ServiceProvider, api_key: &SecretString) -> AppResult<bool> {
         debug!("Validating API key for service: {:?}", service);
 
         let integration = self.integrations.get(&service)
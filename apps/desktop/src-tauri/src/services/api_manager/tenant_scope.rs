@@ -0,0 +1,184 @@
+use std::future::Future;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::api_key::ApiKey;
+use crate::models::research_workflow::ResearchWorkflow;
+
+tokio::task_local! {
+    static CURRENT_SCOPE: KeyScope;
+}
+
+/// Workflow metadata key holding the scope of the caller that created the workflow
+pub const KEY_SCOPE_METADATA_KEY: &str = "key_scope";
+
+/// The API keys a caller may see and spend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyScope {
+    /// Calling tenant; `None` is the system itself, as in single-tenant mode
+    pub tenant_id: Option<Uuid>,
+    /// Let the tenant use the shared system pool when none of its own keys are available
+    pub use_system_pool: bool,
+}
+
+impl KeyScope {
+    /// The system pool, which is every key in single-tenant mode
+    pub fn system() -> Self {
+        Self { tenant_id: None, use_system_pool: true }
+    }
+
+    /// A tenant's own keys only
+    pub fn tenant(tenant_id: Uuid) -> Self {
+        Self { tenant_id: Some(tenant_id), use_system_pool: false }
+    }
+
+    /// Opt the tenant in to the shared system pool
+    pub fn with_system_pool(mut self) -> Self {
+        self.use_system_pool = true;
+        self
+    }
+
+    /// Scope of the running task, the system scope outside `run_scoped`
+    pub fn current() -> Self {
+        CURRENT_SCOPE.try_with(Clone::clone).unwrap_or_else(|_| Self::system())
+    }
+
+    /// Whether the key belongs to the caller
+    pub fn owns(&self, api_key: &ApiKey) -> bool {
+        api_key.tenant_id == self.tenant_id
+    }
+
+    /// Whether the caller may use the key, either its own or from the system pool it opted in to
+    pub fn permits(&self, api_key: &ApiKey) -> bool {
+        self.owns(api_key) || (self.use_system_pool && api_key.tenant_id.is_none())
+    }
+
+    /// Name of the caller in logs and audit events
+    pub fn label(&self) -> String {
+        match self.tenant_id {
+            Some(tenant_id) => format!("tenant {}", tenant_id),
+            None => "system".to_string(),
+        }
+    }
+}
+
+/// Tie a workflow to the keys of `scope`, overwriting whatever scope its metadata
+/// carried in. Workflows are bound where they are created, from the caller's
+/// authenticated scope, never from their parameters.
+pub fn bind_workflow(workflow: &mut ResearchWorkflow, scope: &KeyScope) -> AppResult<()> {
+    if *scope == KeyScope::system() {
+        workflow.metadata.remove(KEY_SCOPE_METADATA_KEY);
+    } else {
+        workflow.metadata.insert(KEY_SCOPE_METADATA_KEY.to_string(), serde_json::to_string(scope)?);
+    }
+    Ok(())
+}
+
+/// The keys a workflow's provider requests may spend; unbound workflows run on the system pool
+pub fn workflow_scope(workflow: &ResearchWorkflow) -> AppResult<KeyScope> {
+    match workflow.metadata.get(KEY_SCOPE_METADATA_KEY) {
+        Some(scope) => Ok(serde_json::from_str(scope)?),
+        None => Ok(KeyScope::system()),
+    }
+}
+
+/// Run `future` with its API key lookups and provider requests scoped to `scope`.
+/// Task-local, so work spawned onto other tasks has to be scoped again.
+pub async fn run_scoped<F: Future>(scope: KeyScope, future: F) -> F::Output {
+    CURRENT_SCOPE.scope(scope, future).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use chrono::Utc;
+    use tokio::sync::RwLock;
+    use crate::models::PageRequest;
+    use crate::models::api_key::{CreateApiKeyRequest, ServiceProvider};
+    use crate::services::{ApiManagerService, DataPersistenceService, MonitoringService, SecurityService};
+    use crate::services::data_persistence::DatabaseConfig;
+    use crate::services::enterprise::{AuthenticationMethod, EnterpriseSession};
+
+    fn key(tenant_id: Option<Uuid>) -> ApiKey {
+        let mut api_key = ApiKey::new(ServiceProvider::Tavily, "test".to_string(), "ciphertext".to_string());
+        api_key.tenant_id = tenant_id;
+        api_key
+    }
+
+    #[tokio::test]
+    async fn test_tenants_only_reach_the_system_pool_when_opted_in() {
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let system_key = key(None);
+
+        let isolated = KeyScope::tenant(acme);
+        assert!(isolated.permits(&key(Some(acme))));
+        assert!(!isolated.permits(&key(Some(globex))));
+        assert!(!isolated.permits(&system_key));
+        assert!(isolated.clone().with_system_pool().permits(&system_key));
+        assert!(!KeyScope::system().permits(&key(Some(acme))));
+
+        assert_eq!(KeyScope::current(), KeyScope::system());
+        let scoped = run_scoped(isolated.clone(), async { KeyScope::current() }).await;
+        assert_eq!(scoped, isolated);
+    }
+
+    #[tokio::test]
+    async fn test_a_tenant_session_cannot_reach_another_tenants_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let security = Arc::new(RwLock::new(
+            SecurityService::with_paths(dir.path().join("key_vault.db"), dir.path().join("audit_log.db")).await.unwrap(),
+        ));
+        let config = DatabaseConfig::Sqlite { path: dir.path().join("app.db"), encryption: None };
+        let data_persistence = Arc::new(RwLock::new(DataPersistenceService::with_config(security.clone(), config).await.unwrap()));
+        let monitoring = Arc::new(RwLock::new(MonitoringService::new(data_persistence.clone()).await.unwrap()));
+        let mut api_manager = ApiManagerService::new(data_persistence, security, monitoring).await.unwrap();
+
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let add = |service: ServiceProvider, tenant_id: Uuid| CreateApiKeyRequest {
+            service,
+            name: format!("{:?} key", service),
+            api_key: "secret".to_string(),
+            rate_limit: None,
+            monthly_quota: None,
+            expires_at: None,
+            tenant_id: Some(tenant_id),
+        };
+        let acme_key = api_manager.add_key(add(ServiceProvider::Exa, acme)).await.unwrap();
+        let globex_key = api_manager.add_key(add(ServiceProvider::Tavily, globex)).await.unwrap();
+
+        // The scope comes from the session acme signed in with, not from the caller
+        let now = Utc::now();
+        let session = EnterpriseSession {
+            session_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            tenant_id: Some(acme),
+            authentication_method: AuthenticationMethod::Password,
+            ip_address: "192.0.2.10".to_string(),
+            user_agent: "test".to_string(),
+            created_at: now,
+            last_activity: now,
+            expires_at: now + chrono::Duration::hours(1),
+            permissions: Vec::new(),
+            roles: Vec::new(),
+            mfa_verified: false,
+            risk_score: 0.0,
+        };
+        let scope = session.key_scope(now, false).unwrap();
+        assert_eq!(scope, KeyScope::tenant(acme));
+
+        let listed = run_scoped(scope.clone(), api_manager.get_all_keys()).await.unwrap();
+        assert_eq!(listed.iter().map(|key| key.id).collect::<Vec<_>>(), vec![acme_key.id]);
+        let page = run_scoped(scope.clone(), api_manager.list_keys(&PageRequest::default())).await.unwrap();
+        assert_eq!(page.items.iter().map(|key| key.id).collect::<Vec<_>>(), vec![acme_key.id]);
+
+        assert!(run_scoped(scope.clone(), api_manager.select_best_key_for_service(ServiceProvider::Tavily)).await.unwrap().is_none());
+        let selected = run_scoped(scope.clone(), api_manager.select_best_key_for_service(ServiceProvider::Exa)).await.unwrap();
+        assert_eq!(selected.map(|key| key.id), Some(acme_key.id));
+        assert!(run_scoped(scope.clone(), api_manager.delete_key(globex_key.id)).await.is_err());
+
+        // An expired session reaches no keys at all
+        assert!(session.key_scope(now + chrono::Duration::hours(1), false).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::models::api_key::{ApiKey, ServiceProvider};
+
+/// Latest day of the month a billing cycle may start on, so every month has it
+pub const MAX_BILLING_CYCLE_DAY: u32 = 28;
+
+/// Hard monthly usage caps for a service.
+///
+/// Unlike rate limits, which only pace requests, a reached cap blocks every
+/// further request until the next billing cycle starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    pub service: ServiceProvider,
+    /// Cap on requests across all of the service's keys per billing cycle
+    pub monthly_cap: Option<u32>,
+    /// Day of the month (1-28) on which the billing cycle restarts
+    pub billing_cycle_day: u32,
+    /// Percentages of a cap at which an alert is raised, once per cycle each
+    pub alert_thresholds_percent: Vec<f64>,
+}
+
+impl QuotaConfig {
+    pub fn default_for_service(service: ServiceProvider) -> Self {
+        Self {
+            service,
+            monthly_cap: None,
+            billing_cycle_day: 1,
+            alert_thresholds_percent: vec![50.0, 80.0, 95.0],
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_BILLING_CYCLE_DAY).contains(&self.billing_cycle_day) {
+            return Err(format!("billing_cycle_day must be between 1 and {}", MAX_BILLING_CYCLE_DAY));
+        }
+        if self.alert_thresholds_percent.iter().any(|t| !(*t > 0.0 && *t <= 100.0)) {
+            return Err("alert thresholds must be percentages in (0, 100]".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Remaining quota for a key and for its service in the current billing cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub key_quota: Option<u32>,
+    pub key_used: u32,
+    pub key_remaining: Option<u32>,
+    pub service_cap: Option<u32>,
+    pub service_used: u32,
+    pub service_remaining: Option<u32>,
+    pub cycle_start: DateTime<Utc>,
+    pub cycle_end: DateTime<Utc>,
+}
+
+impl QuotaUsage {
+    pub fn new(
+        key_quota: Option<u32>,
+        key_used: u32,
+        service_cap: Option<u32>,
+        service_used: u32,
+        cycle_start: DateTime<Utc>,
+        cycle_end: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            key_quota,
+            key_used,
+            key_remaining: key_quota.map(|quota| quota.saturating_sub(key_used)),
+            service_cap,
+            service_used,
+            service_remaining: service_cap.map(|cap| cap.saturating_sub(service_used)),
+            cycle_start,
+            cycle_end,
+        }
+    }
+
+    /// Whether either cap has been reached
+    pub fn is_exhausted(&self) -> bool {
+        self.key_remaining == Some(0) || self.service_remaining == Some(0)
+    }
+}
+
+/// What a service's usable keys can still serve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceCapacity {
+    pub service: ServiceProvider,
+    /// Active, unexpired keys that the rotator does not consider failing
+    pub usable_keys: u32,
+    pub remaining_requests: u32,
+}
+
+/// Requests `keys` can still make: each key until its rate limit window or monthly
+/// quota runs out, and all of them together until `service_remaining` does
+pub fn remaining_requests(keys: &[ApiKey], cycle_start: DateTime<Utc>, service_remaining: Option<u32>) -> u32 {
+    let total = keys.iter()
+        .map(|key| {
+            let window = key.remaining_requests();
+            key.monthly_quota.map_or(window, |quota| window.min(quota.saturating_sub(key.quota_used_in(cycle_start))))
+        })
+        .fold(0u32, u32::saturating_add);
+    service_remaining.map_or(total, |remaining| total.min(remaining))
+}
+
+/// Start of the billing cycle that `now` falls in
+pub fn billing_cycle_start(billing_cycle_day: u32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let day = billing_cycle_day.clamp(1, MAX_BILLING_CYCLE_DAY);
+    let (year, month) = if now.day() >= day {
+        (now.year(), now.month())
+    } else if now.month() == 1 {
+        (now.year() - 1, 12)
+    } else {
+        (now.year(), now.month() - 1)
+    };
+    Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+}
+
+/// Start of the billing cycle after the one starting at `cycle_start`
+pub fn next_billing_cycle_start(cycle_start: DateTime<Utc>) -> DateTime<Utc> {
+    // Cycle days stop at 28, so a month past the start always lands in the next cycle
+    billing_cycle_start(cycle_start.day(), cycle_start + Duration::days(31))
+}
+
+/// The highest alert threshold the latest request crossed, taking usage from
+/// `used - 1` to `used`
+pub fn crossed_threshold(thresholds: &[f64], used: u32, cap: u32) -> Option<f64> {
+    if cap == 0 || used == 0 {
+        return None;
+    }
+    let before = (used - 1) as f64 / cap as f64 * 100.0;
+    let after = used as f64 / cap as f64 * 100.0;
+    thresholds.iter()
+        .copied()
+        .filter(|threshold| before < *threshold && *threshold <= after)
+        .fold(None, |highest: Option<f64>, threshold| Some(highest.map_or(threshold, |h| h.max(threshold))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_billing_cycle_boundaries() {
+        let at = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        let midnight = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+
+        assert_eq!(billing_cycle_start(1, at(2024, 3, 31)), midnight(2024, 3, 1));
+        assert_eq!(billing_cycle_start(15, at(2024, 3, 14)), midnight(2024, 2, 15));
+        assert_eq!(billing_cycle_start(15, at(2024, 1, 2)), midnight(2023, 12, 15));
+        assert_eq!(next_billing_cycle_start(midnight(2024, 1, 28)), midnight(2024, 2, 28));
+        assert_eq!(next_billing_cycle_start(midnight(2023, 12, 15)), midnight(2024, 1, 15));
+    }
+
+    #[test]
+    fn test_each_threshold_is_crossed_once() {
+        let thresholds = [50.0, 80.0, 100.0];
+        let crossed: Vec<_> = (1..=10).filter_map(|used| crossed_threshold(&thresholds, used, 10)).collect();
+        assert_eq!(crossed, vec![50.0, 80.0, 100.0]);
+
+        // A cap of one jumps straight past every threshold
+        assert_eq!(crossed_threshold(&thresholds, 1, 1), Some(100.0));
+        assert_eq!(crossed_threshold(&thresholds, 2, 1), None);
+
+        let usage = QuotaUsage::new(Some(10), 10, None, 10, Utc::now(), Utc::now());
+        assert!(usage.is_exhausted());
+        assert_eq!(usage.service_remaining, None);
+    }
+
+    #[test]
+    fn test_remaining_requests_respects_every_limit() {
+        let cycle_start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let key = |rate_limit, used, quota: Option<u32>, quota_used| {
+            let mut key = ApiKey::new(ServiceProvider::SerpApi, "key".to_string(), String::new());
+            key.rate_limit = rate_limit;
+            key.usage_count = used;
+            key.monthly_quota = quota;
+            key.quota_usage = quota_used;
+            key.quota_period_start = cycle_start;
+            key
+        };
+
+        // 60 left in the window but only 5 of the monthly quota, plus 10 from an uncapped key
+        let keys = [key(100, 40, Some(50), 45), key(20, 10, None, 0)];
+        assert_eq!(remaining_requests(&keys, cycle_start, None), 15);
+        assert_eq!(remaining_requests(&keys, cycle_start, Some(12)), 12);
+        assert_eq!(remaining_requests(&[], cycle_start, None), 0);
+    }
+}
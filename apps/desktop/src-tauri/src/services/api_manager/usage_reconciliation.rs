@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn, error};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::api_key::{ApiKey, ServiceProvider};
+use crate::models::usage_report::{KeyUsageTotals, ProviderUsageSnapshot, UsageDivergence};
+use crate::services::DataPersistenceService;
+use super::fallback_router::FallbackRouter;
+use super::rate_limiter::{AlertType, RateLimiter};
+
+/// A provider's own count of a key's usage, read from its response headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderReportedUsage {
+    pub used: u32,
+    pub limit: Option<u32>,
+}
+
+/// Usage the provider reported in `x-ratelimit-*` or `ratelimit-*` headers: the used
+/// count if sent, otherwise the limit less what remains
+pub fn provider_reported_usage(headers: &HashMap<String, String>) -> Option<ProviderReportedUsage> {
+    let header = |name: &str| {
+        headers.iter()
+            .find(|(key, _)| {
+                let key = key.to_ascii_lowercase();
+                key == format!("x-ratelimit-{}", name) || key == format!("ratelimit-{}", name)
+            })
+            .and_then(|(_, value)| value.trim().parse::<u32>().ok())
+    };
+
+    let limit = header("limit");
+    let used = header("used").or_else(|| Some(limit?.saturating_sub(header("remaining")?)))?;
+    Some(ProviderReportedUsage { used, limit })
+}
+
+/// Requests the provider counted for one key from `from` onwards. `snapshots` are that
+/// key's reports in the order they were observed; the last one before `from` is the baseline. A
+/// counter lower than the one before it was reset, so its whole value counts. Without a
+/// baseline the first report in range counts in full, which overstates the provider's
+/// count if its window began before `from`.
+pub fn reported_usage_since(snapshots: &[ProviderUsageSnapshot], from: &str) -> Option<u32> {
+    let mut previous = snapshots.iter()
+        .filter(|snapshot| snapshot.date_bucket.as_str() < from)
+        .last()
+        .map(|snapshot| snapshot.reported_used);
+
+    let mut total = None;
+    for snapshot in snapshots.iter().filter(|snapshot| snapshot.date_bucket.as_str() >= from) {
+        let growth = match previous {
+            Some(previous) if snapshot.reported_used >= previous => snapshot.reported_used - previous,
+            _ => snapshot.reported_used,
+        };
+        total = Some(total.unwrap_or(0u32).saturating_add(growth));
+        previous = Some(snapshot.reported_used);
+    }
+    total
+}
+
+/// Counted and provider-reported usage of one key, or of a whole provider when
+/// `api_key_id` is `None`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReconciliationRow {
+    pub service: String,
+    pub api_key_id: Option<Uuid>,
+    pub key_name: Option<String>,
+    pub request_count: u32,
+    pub success_count: u32,
+    pub error_count: u32,
+    /// Requests counted times the provider's configured cost per request, in USD
+    pub estimated_cost_usd: f64,
+    /// `None` when the provider sent no usage headers for the key
+    pub provider_reported: Option<u32>,
+    /// Our request count less the provider's, over the keys the provider reported on
+    pub delta: Option<i64>,
+    /// Whether a provider report from before the range anchors `provider_reported`. Without
+    /// one the provider's count may include usage from before the range.
+    #[serde(default)]
+    pub provider_baseline: bool,
+}
+
+/// Per provider and per key usage over a date range, for checking against provider bills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReconciliationReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub generated_at: DateTime<Utc>,
+    pub providers: Vec<UsageReconciliationRow>,
+    pub keys: Vec<UsageReconciliationRow>,
+}
+
+impl UsageReconciliationReport {
+    /// Build the report for `keys` from the usage counted over the range and every
+    /// provider report up to its end
+    pub fn build(
+        from: NaiveDate,
+        to: NaiveDate,
+        keys: &[ApiKey],
+        totals: &[KeyUsageTotals],
+        snapshots: &[ProviderUsageSnapshot],
+        provider_costs: &HashMap<ServiceProvider, f64>,
+    ) -> Self {
+        let from_bucket = from.format("%Y-%m-%d").to_string();
+        let cost_per_request = |service: &str| {
+            ServiceProvider::from_str(service)
+                .and_then(|provider| provider_costs.get(&provider).copied())
+                .unwrap_or(0.0)
+        };
+
+        let key_rows: Vec<UsageReconciliationRow> = totals.iter()
+            .filter_map(|total| {
+                let key = keys.iter().find(|key| key.id == total.api_key_id)?;
+                let key_snapshots: Vec<ProviderUsageSnapshot> = snapshots.iter()
+                    .filter(|snapshot| snapshot.api_key_id == total.api_key_id)
+                    .cloned()
+                    .collect();
+                let provider_reported = reported_usage_since(&key_snapshots, &from_bucket);
+                let provider_baseline = key_snapshots.iter().any(|snapshot| snapshot.date_bucket < from_bucket);
+                Some(UsageReconciliationRow {
+                    service: total.service.clone(),
+                    api_key_id: Some(total.api_key_id),
+                    key_name: Some(key.name.clone()),
+                    request_count: total.request_count,
+                    success_count: total.success_count,
+                    error_count: total.error_count,
+                    estimated_cost_usd: total.request_count as f64 * cost_per_request(&total.service),
+                    provider_reported,
+                    delta: provider_reported.map(|reported| total.request_count as i64 - reported as i64),
+                    provider_baseline,
+                })
+            })
+            .collect();
+
+        let mut provider_rows: Vec<UsageReconciliationRow> = Vec::new();
+        for row in &key_rows {
+            let provider = match provider_rows.iter_mut().find(|provider| provider.service == row.service) {
+                Some(provider) => provider,
+                None => {
+                    provider_rows.push(UsageReconciliationRow {
+                        service: row.service.clone(),
+                        api_key_id: None,
+                        key_name: None,
+                        request_count: 0,
+                        success_count: 0,
+                        error_count: 0,
+                        estimated_cost_usd: 0.0,
+                        provider_reported: None,
+                        delta: None,
+                        provider_baseline: true,
+                    });
+                    provider_rows.last_mut().expect("row was just pushed")
+                }
+            };
+            provider.request_count += row.request_count;
+            provider.success_count += row.success_count;
+            provider.error_count += row.error_count;
+            provider.estimated_cost_usd += row.estimated_cost_usd;
+            if let (Some(reported), Some(delta)) = (row.provider_reported, row.delta) {
+                provider.provider_reported = Some(provider.provider_reported.unwrap_or(0) + reported);
+                provider.delta = Some(provider.delta.unwrap_or(0) + delta);
+                provider.provider_baseline &= row.provider_baseline;
+            }
+        }
+
+        Self {
+            from,
+            to,
+            generated_at: Utc::now(),
+            providers: provider_rows,
+            keys: key_rows,
+        }
+    }
+
+    /// One line per provider total, then one per key; the key columns of a total are empty
+    pub fn to_csv(&self) -> String {
+        let mut csv_content = String::from(
+            "from,to,service,api_key_id,key_name,request_count,success_count,error_count,estimated_cost_usd,provider_reported,delta\n",
+        );
+        for row in self.providers.iter().chain(&self.keys) {
+            csv_content.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.4},{},{}\n",
+                self.from,
+                self.to,
+                row.service,
+                row.api_key_id.map(|id| id.to_string()).unwrap_or_default(),
+                row.key_name.as_deref().map(csv_field).unwrap_or_default(),
+                row.request_count,
+                row.success_count,
+                row.error_count,
+                row.estimated_cost_usd,
+                row.provider_reported.map(|reported| reported.to_string()).unwrap_or_default(),
+                row.delta.map(|delta| delta.to_string()).unwrap_or_default(),
+            ));
+        }
+        csv_content
+    }
+}
+
+/// Setting the divergence config is saved under
+const DIVERGENCE_CONFIG_SETTING: &str = "usage_divergence_config";
+
+/// When a key's counted usage is far enough from the provider's to alert on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivergenceConfig {
+    /// Largest divergence, as a percentage of the provider's count, that goes unreported
+    pub threshold_percent: f64,
+    /// Keys with fewer requests than this on both sides are never alerted on, since a
+    /// few requests either way makes for a large percentage
+    pub min_requests: u32,
+    /// Days of usage, up to today, each check compares
+    pub lookback_days: u32,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            threshold_percent: 10.0,
+            min_requests: 20,
+            lookback_days: 7,
+        }
+    }
+}
+
+/// The divergence of every key in the report the provider reported usage for. Keys without a
+/// provider report from before the range are left out, since the provider's count could
+/// include earlier usage and would read as a divergence.
+pub fn divergences(report: &UsageReconciliationReport, config: &DivergenceConfig) -> Vec<UsageDivergence> {
+    report.keys.iter()
+        .filter(|row| row.provider_baseline)
+        .filter_map(|row| {
+            let (api_key_id, reported, delta) = (row.api_key_id?, row.provider_reported?, row.delta?);
+            let divergence_percent = match reported {
+                0 if delta == 0 => 0.0,
+                0 => 100.0,
+                reported => delta.unsigned_abs() as f64 / reported as f64 * 100.0,
+            };
+            Some(UsageDivergence {
+                api_key_id,
+                service: row.service.clone(),
+                from: report.from,
+                to: report.to,
+                counted: row.request_count,
+                reported,
+                delta,
+                divergence_percent,
+                threshold_exceeded: row.request_count.max(reported) >= config.min_requests
+                    && divergence_percent > config.threshold_percent,
+                checked_at: report.generated_at,
+            })
+        })
+        .collect()
+}
+
+/// Builds usage reports and watches them for counts drifting from the providers'
+pub struct UsageReconciler {
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+    rate_limiter: Arc<RateLimiter>,
+    fallback_router: Arc<FallbackRouter>,
+    config: RwLock<DivergenceConfig>,
+}
+
+impl UsageReconciler {
+    pub async fn new(
+        data_persistence: Arc<RwLock<DataPersistenceService>>,
+        rate_limiter: Arc<RateLimiter>,
+        fallback_router: Arc<FallbackRouter>,
+    ) -> Self {
+        let config = match data_persistence.read().await.get_setting::<DivergenceConfig>(DIVERGENCE_CONFIG_SETTING).await {
+            Ok(saved) => saved.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to load usage divergence config, using the default: {}", e);
+                DivergenceConfig::default()
+            }
+        };
+
+        Self {
+            data_persistence,
+            rate_limiter,
+            fallback_router,
+            config: RwLock::new(config),
+        }
+    }
+
+    pub async fn get_config(&self) -> DivergenceConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn update_config(&self, config: DivergenceConfig) -> AppResult<()> {
+        if config.threshold_percent.is_nan() || config.threshold_percent < 0.0 {
+            return Err(AppError::validation("threshold_percent", "must be zero or more"));
+        }
+        if config.lookback_days == 0 {
+            return Err(AppError::validation("lookback_days", "must be at least 1"));
+        }
+        self.data_persistence.read().await.save_setting(DIVERGENCE_CONFIG_SETTING, &config).await?;
+        *self.config.write().await = config;
+        Ok(())
+    }
+
+    /// Report on `keys` from `from` through `to`
+    pub async fn build_report(&self, from: NaiveDate, to: NaiveDate, keys: &[ApiKey]) -> AppResult<UsageReconciliationReport> {
+        if from > to {
+            return Err(AppError::validation("date_range", format!("{} is after {}", from, to)));
+        }
+        let (from_bucket, to_bucket) = (from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string());
+
+        let (totals, snapshots) = {
+            let data_persistence = self.data_persistence.read().await;
+            (
+                data_persistence.get_key_usage_totals(&from_bucket, &to_bucket).await?,
+                data_persistence.get_provider_usage_snapshots(&to_bucket).await?,
+            )
+        };
+        let provider_costs = self.fallback_router.get_config().await.provider_costs;
+
+        Ok(UsageReconciliationReport::build(from, to, keys, &totals, &snapshots, &provider_costs))
+    }
+
+    /// Compare every key's counted usage over the lookback window with the provider's,
+    /// keep the results and alert on each key past the threshold
+    pub async fn check_divergence(&self) -> AppResult<Vec<UsageDivergence>> {
+        let config = self.get_config().await;
+        let to = Utc::now().date_naive();
+        let from = to - Duration::days(config.lookback_days.saturating_sub(1) as i64);
+
+        let keys = self.data_persistence.read().await.get_all_api_keys().await?;
+        let report = self.build_report(from, to, &keys).await?;
+        let divergences = divergences(&report, &config);
+        self.data_persistence.read().await.record_usage_divergences(&divergences).await?;
+
+        for divergence in divergences.iter().filter(|divergence| divergence.threshold_exceeded) {
+            let message = format!(
+                "{} usage of key {} is {:.1}% off the provider's count from {} to {}: we counted {}, the provider {}",
+                divergence.service, divergence.api_key_id, divergence.divergence_percent,
+                divergence.from, divergence.to, divergence.counted, divergence.reported
+            );
+            warn!("{}", message);
+            if let Err(e) = self.rate_limiter.raise_alert(divergence.api_key_id, AlertType::UsageDivergence, message).await {
+                error!("Failed to raise usage divergence alert: {}", e);
+            }
+        }
+
+        info!("Checked usage divergence of {} keys", divergences.len());
+        Ok(divergences)
+    }
+
+    /// Divergence checks of the last `days`, oldest first, optionally for one key
+    pub async fn get_history(&self, days: u32, api_key_id: Option<Uuid>) -> AppResult<Vec<UsageDivergence>> {
+        let since = Utc::now() - Duration::days(days as i64);
+        let history = self.data_persistence.read().await.get_usage_divergence_history(since).await?;
+        Ok(history.into_iter()
+            .filter(|divergence| api_key_id.map_or(true, |id| divergence.api_key_id == id))
+            .collect())
+    }
+}
+
+/// Quote a free-text field if it would otherwise break the CSV row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(api_key_id: Uuid, date_bucket: &str, reported_used: u32) -> ProviderUsageSnapshot {
+        ProviderUsageSnapshot {
+            api_key_id,
+            service: "serpapi".to_string(),
+            date_bucket: date_bucket.to_string(),
+            reported_used,
+            reported_limit: Some(1000),
+            observed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_report_compares_counted_usage_with_provider_counters() {
+        let headers = HashMap::from([
+            ("X-RateLimit-Limit".to_string(), "1000".to_string()),
+            ("X-RateLimit-Remaining".to_string(), "880".to_string()),
+        ]);
+        assert_eq!(provider_reported_usage(&headers), Some(ProviderReportedUsage { used: 120, limit: Some(1000) }));
+        assert_eq!(provider_reported_usage(&HashMap::new()), None);
+
+        let search = ApiKey::new(ServiceProvider::SerpApi, "search, primary".to_string(), String::new());
+        let spare = ApiKey::new(ServiceProvider::SerpApi, "spare".to_string(), String::new());
+        // Baseline of 100 before the range, then a reset between the 3rd and the 4th
+        let snapshots = [
+            snapshot(search.id, "2024-02-29", 100),
+            snapshot(search.id, "2024-03-02", 130),
+            snapshot(search.id, "2024-03-03", 150),
+            snapshot(search.id, "2024-03-04", 12),
+        ];
+        assert_eq!(reported_usage_since(&snapshots, "2024-03-01"), Some(62));
+        // Every report of a day counts, so a reset within the day keeps the requests before it
+        let same_day = [snapshot(search.id, "2024-03-05", 40), snapshot(search.id, "2024-03-05", 5)];
+        assert_eq!(reported_usage_since(&same_day, "2024-03-05"), Some(45));
+
+        let totals = [
+            KeyUsageTotals { api_key_id: search.id, service: "serpapi".to_string(), request_count: 60, success_count: 58, error_count: 2 },
+            KeyUsageTotals { api_key_id: spare.id, service: "serpapi".to_string(), request_count: 5, success_count: 5, error_count: 0 },
+        ];
+        let costs = HashMap::from([(ServiceProvider::SerpApi, 0.01)]);
+        let report = UsageReconciliationReport::build(
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            &[search.clone(), spare],
+            &totals,
+            &snapshots,
+            &costs,
+        );
+
+        assert_eq!(report.keys[0].delta, Some(-2));
+        assert!(report.keys[0].provider_baseline);
+        assert_eq!(report.keys[1].provider_reported, None);
+        let provider = &report.providers[0];
+        assert_eq!((provider.request_count, provider.provider_reported, provider.delta), (65, Some(62), Some(-2)));
+        assert!((provider.estimated_cost_usd - 0.65).abs() < 1e-9);
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains(&format!("{},\"search, primary\",60,58,2,0.6000,62,-2", search.id)));
+    }
+
+    #[test]
+    fn test_divergence_past_the_threshold_is_flagged() {
+        let row = |request_count, provider_reported: Option<u32>, provider_baseline| UsageReconciliationRow {
+            service: "serpapi".to_string(),
+            api_key_id: Some(Uuid::new_v4()),
+            key_name: Some("search".to_string()),
+            request_count,
+            success_count: request_count,
+            error_count: 0,
+            estimated_cost_usd: 0.0,
+            provider_reported,
+            delta: provider_reported.map(|reported| request_count as i64 - reported as i64),
+            provider_baseline,
+        };
+        let report = UsageReconciliationReport {
+            from: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(),
+            generated_at: Utc::now(),
+            providers: Vec::new(),
+            // Provider charged for retries we did not count; close enough; too few to judge; unreported;
+            // first reported inside the range, so its count may include earlier usage
+            keys: vec![
+                row(100, Some(125), true),
+                row(100, Some(105), true),
+                row(2, Some(5), true),
+                row(40, None, false),
+                row(100, Some(400), false),
+            ],
+        };
+
+        let found = divergences(&report, &DivergenceConfig::default());
+        assert_eq!(found.len(), 3);
+        assert_eq!((found[0].delta, found[0].divergence_percent), (-25, 20.0));
+        assert_eq!(found.iter().map(|d| d.threshold_exceeded).collect::<Vec<_>>(), vec![true, false, false]);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::models::api_key::ServiceProvider;
+use crate::models::research_workflow::SearchRecency;
+use super::fallback_router::{self, AttemptOutcome, ProviderAttempt};
+use super::provider_adapter;
+use super::response_schema::SearchHit;
+use super::service_integration::{ServiceRequest, ServiceResponse};
+
+/// Most results one search may ask for, across every page and provider
+pub const MAX_SEARCH_RESULTS: u32 = 100;
+
+/// Pages fetched from one provider before giving up on filling the request
+const MAX_PAGES_PER_PROVIDER: u32 = 10;
+
+/// How many results a provider returns per request, and whether it can skip ahead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageSupport {
+    pub page_size: u32,
+    pub paginates: bool,
+}
+
+/// Page limits of the providers that search the web
+pub fn page_support(provider: ServiceProvider) -> Option<PageSupport> {
+    match provider {
+        // Google returns up to 100 results a page and skips ahead with `start`
+        ServiceProvider::SerpApi => Some(PageSupport { page_size: 100, paginates: true }),
+        ServiceProvider::Tavily => Some(PageSupport { page_size: 20, paginates: false }),
+        ServiceProvider::Exa => Some(PageSupport { page_size: 100, paginates: false }),
+        ServiceProvider::Custom(custom) => provider_adapter::adapter_for(custom)?.page_support(),
+        _ => None,
+    }
+}
+
+/// Results of a web search, with how many were fetched against how many were asked for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebSearchOutcome {
+    pub hits: Vec<SearchHit>,
+    pub requested: u32,
+    pub fetched: u32,
+    /// Requests made, counting every page from every provider
+    pub pages: u32,
+    /// Hits dropped because an earlier page or provider already returned their link
+    pub duplicates_removed: u32,
+    /// Hits dropped for being published before the search's cutoff
+    #[serde(default)]
+    pub outside_time_range: u32,
+    /// Providers that contributed hits, in the order they did
+    pub served_by: Vec<ServiceProvider>,
+    pub attempts: Vec<ProviderAttempt>,
+    /// Why fewer results were fetched than requested
+    pub shortfall_reason: Option<String>,
+}
+
+/// Key identifying a link regardless of case, fragment, trailing slash or tracking parameters
+pub fn link_key(link: &str) -> String {
+    let trimmed = link.trim();
+    let Ok(mut url) = url::Url::parse(trimmed) else {
+        return trimmed.trim_end_matches('/').to_lowercase();
+    };
+    url.set_fragment(None);
+    let query: Vec<(String, String)> = url.query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_") && name != "gclid" && name != "fbclid")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if query.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+    let host = url.host_str().unwrap_or_default().trim_start_matches("www.").to_lowercase();
+    let rest = &url[url::Position::BeforePath..];
+    format!("{}{}", host, rest.trim_end_matches('/'))
+}
+
+/// A hit with whitespace tidied, or `None` when it has no link to follow
+fn normalize_hit(hit: SearchHit) -> Option<SearchHit> {
+    let tidy = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let link = hit.link.trim().to_string();
+    if link.is_empty() {
+        return None;
+    }
+    Some(SearchHit { title: tidy(&hit.title), link, snippet: tidy(&hit.snippet), published: hit.published })
+}
+
+/// One web search being filled page by page, possibly from several providers
+pub struct WebSearch {
+    query: String,
+    recency: SearchRecency,
+    /// Oldest publication date kept; hits without a date are kept, since they cannot be judged
+    cutoff: Option<DateTime<Utc>>,
+    seen: HashSet<String>,
+    tried: Vec<ServiceProvider>,
+    /// Pages asked of each provider, and the results they returned before deduping
+    paged: HashMap<ServiceProvider, (u32, u32)>,
+    notes: Vec<String>,
+    outcome: WebSearchOutcome,
+}
+
+impl WebSearch {
+    pub fn new(query: &str, num_results: u32) -> Self {
+        Self {
+            query: query.to_string(),
+            recency: SearchRecency::default(),
+            cutoff: None,
+            seen: HashSet::new(),
+            tried: Vec::new(),
+            paged: HashMap::new(),
+            notes: Vec::new(),
+            outcome: WebSearchOutcome { requested: num_results.clamp(1, MAX_SEARCH_RESULTS), ..Default::default() },
+        }
+    }
+
+    /// Ask providers for, and keep only, sources as recent as `recency` allows
+    pub fn with_recency(mut self, recency: SearchRecency) -> Self {
+        let now = Utc::now();
+        self.cutoff = recency.cutoff(now);
+        self.recency = recency;
+        self
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.outcome.requested.saturating_sub(self.outcome.hits.len() as u32)
+    }
+
+    /// Providers that have already answered, and so have nothing more to add
+    pub fn tried(&self) -> &[ServiceProvider] {
+        &self.tried
+    }
+
+    /// Results to ask `provider` for next: what is still missing, up to its page size
+    pub fn page_size(&self, provider: ServiceProvider) -> u32 {
+        page_support(provider).map_or(0, |support| support.page_size.min(self.remaining()).max(1))
+    }
+
+    /// The request for `provider`'s next page, following on from the results it already returned
+    pub fn page_request(&self, provider: ServiceProvider) -> Option<ServiceRequest> {
+        page_support(provider)?;
+        let offset = self.paged.get(&provider).map_or(0, |(_, returned)| *returned);
+        let mut request = fallback_router::web_search_page_request(provider, &self.query, self.page_size(provider), offset)?;
+        fallback_router::restrict_web_search(&mut request, &self.recency, Utc::now());
+        Some(request)
+    }
+
+    /// Chain attempts made finding a provider, minus those skipped for having answered already
+    pub fn record_attempts(&mut self, attempts: Vec<ProviderAttempt>) {
+        let tried = &self.tried;
+        self.outcome.attempts.extend(attempts.into_iter().filter(|attempt| {
+            !(tried.contains(&attempt.provider) && matches!(attempt.outcome, AttemptOutcome::Unsupported))
+        }));
+    }
+
+    /// Add a page `provider` returned for `asked_for` results, returning whether another
+    /// page from it could add more
+    pub fn record_page(&mut self, provider: ServiceProvider, asked_for: u32, response: &ServiceResponse) -> bool {
+        if !self.tried.contains(&provider) {
+            self.tried.push(provider);
+        }
+        self.outcome.pages += 1;
+
+        let hits = fallback_router::search_hits(response);
+        let returned = hits.len() as u32;
+        let paged = self.paged.entry(provider).or_default();
+        paged.0 += 1;
+        paged.1 += returned;
+        let pages_from_provider = paged.0;
+        let mut added = 0;
+        for hit in hits.into_iter().filter_map(normalize_hit) {
+            if self.remaining() == 0 {
+                break;
+            }
+            if self.cutoff.zip(hit.published).is_some_and(|(cutoff, published)| published < cutoff) {
+                self.outcome.outside_time_range += 1;
+                continue;
+            }
+            if self.seen.insert(link_key(&hit.link)) {
+                self.outcome.hits.push(hit);
+                added += 1;
+            } else {
+                self.outcome.duplicates_removed += 1;
+            }
+        }
+        if added > 0 && !self.outcome.served_by.contains(&provider) {
+            self.outcome.served_by.push(provider);
+        }
+        self.outcome.fetched = self.outcome.hits.len() as u32;
+
+        // A short page means the provider has run out; a page of nothing new means it is repeating itself
+        let more = returned >= asked_for
+            && added > 0
+            && page_support(provider).map_or(false, |support| support.paginates)
+            && pages_from_provider < MAX_PAGES_PER_PROVIDER;
+        if !more && self.remaining() > 0 {
+            self.note(format!("{:?} had no more results after {}", provider, self.outcome.fetched));
+        }
+        more
+    }
+
+    /// Remember why the search is falling short
+    pub fn note(&mut self, reason: impl Into<String>) {
+        self.notes.push(reason.into());
+    }
+
+    pub fn finish(mut self) -> WebSearchOutcome {
+        if self.outcome.outside_time_range > 0 {
+            let dropped = self.outcome.outside_time_range;
+            self.note(format!("{} results were published before the time range", dropped));
+        }
+        if self.outcome.fetched < self.outcome.requested {
+            self.outcome.shortfall_reason = Some(if self.notes.is_empty() {
+                "providers returned fewer results than requested".to_string()
+            } else {
+                self.notes.join("; ")
+            });
+        }
+        self.outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::SearchTimeRange;
+    use crate::services::api_manager::response_schema::NormalizedPayload;
+
+    fn page(links: &[&str]) -> ServiceResponse {
+        ServiceResponse {
+            request_id: uuid::Uuid::new_v4(),
+            service: ServiceProvider::SerpApi,
+            status_code: 200,
+            headers: Default::default(),
+            body: String::new(),
+            response_time_ms: 100,
+            success: true,
+            error_message: None,
+            metadata: Default::default(),
+            timestamp: chrono::Utc::now(),
+            normalized: Some(NormalizedPayload::SearchResults {
+                hits: links.iter()
+                    .map(|link| SearchHit {
+                        title: format!("  {} \n", link),
+                        link: link.to_string(),
+                        snippet: "A  result".to_string(),
+                        published: None,
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_pages_fill_the_request_without_repeating_links() {
+        assert_eq!(link_key("https://www.Example.com/a/?utm_source=x#top"), link_key("https://example.com/a"));
+        assert_ne!(link_key("https://example.com/a?id=1"), link_key("https://example.com/a?id=2"));
+
+        let mut search = WebSearch::new("grid storage", 5);
+        assert_eq!(search.page_size(ServiceProvider::SerpApi), 5);
+        assert!(!search.page_request(ServiceProvider::SerpApi).unwrap().metadata["query_params"].contains("start"));
+
+        // A full page from a provider that paginates asks for another
+        assert!(search.record_page(ServiceProvider::SerpApi, 3, &page(&["https://a.com", "https://b.com", "https://www.a.com/"])));
+        assert_eq!((search.outcome.fetched, search.outcome.duplicates_removed, search.remaining()), (2, 1, 3));
+        assert_eq!(search.outcome.hits[0].title, "https://a.com");
+        let next = search.page_request(ServiceProvider::SerpApi).unwrap();
+        assert!(next.metadata["query_params"].ends_with("&num=3&start=3"));
+
+        // Tavily tops up once, and cannot be asked for a second page
+        assert!(!search.record_page(ServiceProvider::Tavily, 3, &page(&["https://c.com", "https://b.com", "https://d.com"])));
+        assert_eq!(search.tried(), &[ServiceProvider::SerpApi, ServiceProvider::Tavily]);
+        assert!(search.page_request(ServiceProvider::Tavily).is_none());
+
+        let outcome = search.finish();
+        assert_eq!((outcome.requested, outcome.fetched, outcome.pages), (5, 4, 2));
+        assert_eq!(outcome.served_by, vec![ServiceProvider::SerpApi, ServiceProvider::Tavily]);
+        assert!(outcome.shortfall_reason.unwrap().contains("Tavily"));
+
+        assert_eq!(WebSearch::new("q", 1000).outcome.requested, MAX_SEARCH_RESULTS);
+    }
+
+    #[test]
+    fn test_recency_is_asked_of_providers_and_enforced_on_their_hits() {
+        let now = Utc::now();
+        let past_week = SearchRecency { time_range: SearchTimeRange::PastWeek, published_after: None };
+        let mut search = WebSearch::new("grid storage", 3).with_recency(past_week);
+        assert!(search.page_request(ServiceProvider::SerpApi).unwrap().metadata["query_params"].ends_with("&tbs=qdr%3Aw"));
+        let tavily = search.page_request(ServiceProvider::Tavily).unwrap();
+        assert!(tavily.body.unwrap().contains(r#""time_range":"week""#));
+
+        // A date stricter than the range is sent as a date
+        let after = now - chrono::Duration::days(2);
+        let both = SearchRecency { published_after: Some(after), ..past_week };
+        assert_eq!(both.cutoff(now), Some(after));
+        assert_eq!(both.relative_range(now), None);
+        let exa = WebSearch::new("q", 3).with_recency(both).page_request(ServiceProvider::Exa).unwrap();
+        assert!(exa.body.unwrap().contains("startPublishedDate"));
+
+        // A provider ignoring the hint still has its stale hits dropped; undated hits stay
+        let mut response = page(&["https://new.com", "https://old.com", "https://undated.com"]);
+        if let Some(NormalizedPayload::SearchResults { hits }) = &mut response.normalized {
+            hits[0].published = Some(now - chrono::Duration::days(1));
+            hits[1].published = Some(now - chrono::Duration::days(30));
+        }
+        search.record_page(ServiceProvider::Tavily, 3, &response);
+        let outcome = search.finish();
+        assert_eq!(outcome.hits.iter().map(|hit| hit.link.as_str()).collect::<Vec<_>>(), vec!["https://new.com", "https://undated.com"]);
+        assert_eq!(outcome.outside_time_range, 1);
+        assert!(outcome.shortfall_reason.unwrap().contains("before the time range"));
+    }
+}
@@ -454,6 +454,8 @@ impl BMadIntegrationService {
             query: request.query.clone(),
             methodology: Some(methodology),
             parameters: Some(parameters),
+            data_region: Default::default(),
+            idempotency_key: None,
         })
     }
 
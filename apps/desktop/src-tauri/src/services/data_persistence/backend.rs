@@ -0,0 +1,342 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppResult, StorageError};
+use crate::models::{ApiKey, audit::AuditEvent};
+use crate::models::api_key::ApiKeyStatus;
+use crate::models::research_workflow::ResearchWorkflow;
+use crate::models::research_schedule::{ResearchSchedule, ScheduleRun};
+use crate::models::prompt_template::{PromptExperiment, PromptTemplate};
+use crate::models::idempotency::IdempotencyRecord;
+use crate::models::saga::SagaState;
+use crate::models::notification::{Notification, NotificationPreferences};
+use crate::models::ai_marketplace::{ContentKind, InstalledContent};
+use crate::models::workflow_rating::{QueryDomain, WorkflowRating};
+use crate::models::usage_report::{KeyUsageTotals, ProviderUsageSnapshot, UsageDivergence};
+use crate::models::execution_metrics::StepExecutionMetrics;
+use crate::models::pagination::{Page, PagePosition};
+use crate::utils::file_utils::ensure_dir_exists;
+use super::database_encryption::DatabaseKeySource;
+use super::history_store::HistoryRecord;
+use super::key_rotation::{ApiKeyCiphertext, ApiKeyReencryption};
+use super::migrations::{MigrationMode, MigrationReport};
+use super::workflow_search::{WorkflowOrganization, WorkflowSearchFilters, WorkflowSearchMatch};
+
+/// Environment variable holding the database connection string
+pub const DATABASE_URL_ENV: &str = "FDR_DATABASE_URL";
+
+/// Environment variable overriding the Postgres pool size
+pub const DATABASE_MAX_CONNECTIONS_ENV: &str = "FDR_DATABASE_MAX_CONNECTIONS";
+
+/// Environment variable enabling SQLite encryption at rest: `passphrase` or `keystore`
+pub const DATABASE_KEY_SOURCE_ENV: &str = "FDR_DATABASE_KEY_SOURCE";
+
+/// Environment variable holding the passphrase for `FDR_DATABASE_KEY_SOURCE=passphrase`
+pub const DATABASE_PASSPHRASE_ENV: &str = "FDR_DATABASE_PASSPHRASE";
+
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+/// Which database the persistence layer runs against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum DatabaseConfig {
+    /// Local SQLite file, the default for desktop installs; encrypted at rest
+    /// when a key source is set
+    Sqlite {
+        path: PathBuf,
+        #[serde(default)]
+        encryption: Option<DatabaseKeySource>,
+    },
+    /// Shared Postgres database for multi-node deployments
+    Postgres { url: String, max_connections: u32 },
+}
+
+/// Short name of a storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatabaseBackendKind {
+    Sqlite,
+    Postgres,
+}
+
+impl DatabaseConfig {
+    /// Resolve the database from `FDR_DATABASE_URL`, falling back to the local SQLite file
+    pub fn from_env() -> AppResult<Self> {
+        let config = match std::env::var(DATABASE_URL_ENV) {
+            Ok(url) if !url.trim().is_empty() => Self::from_connection_string(url.trim())?,
+            _ => Self::default_sqlite()?,
+        };
+
+        config.with_encryption(Self::key_source_from_env()?)
+    }
+
+    /// Encryption key source from `FDR_DATABASE_KEY_SOURCE`, if encryption is enabled
+    pub(crate) fn key_source_from_env() -> AppResult<Option<DatabaseKeySource>> {
+        match std::env::var(DATABASE_KEY_SOURCE_ENV).ok().as_deref().map(str::trim) {
+            None | Some("") | Some("none") => Ok(None),
+            Some("keystore") => Ok(Some(DatabaseKeySource::os_keystore())),
+            Some("passphrase") => {
+                let passphrase = std::env::var(DATABASE_PASSPHRASE_ENV).unwrap_or_default();
+                if passphrase.is_empty() {
+                    return Err(StorageError::Database {
+                        message: format!("{} is required when {}=passphrase", DATABASE_PASSPHRASE_ENV, DATABASE_KEY_SOURCE_ENV)
+                    }.into());
+                }
+                Ok(Some(DatabaseKeySource::passphrase(passphrase)))
+            }
+            Some(other) => Err(StorageError::Database {
+                message: format!("Unknown database key source: {}", other)
+            }.into()),
+        }
+    }
+
+    /// Enable encryption at rest; only SQLite databases are encrypted by the
+    /// application, Postgres relies on server-side encryption
+    pub fn with_encryption(self, key_source: Option<DatabaseKeySource>) -> AppResult<Self> {
+        match (self, key_source) {
+            (config, None) => Ok(config),
+            (Self::Sqlite { path, .. }, Some(key_source)) => Ok(Self::Sqlite { path, encryption: Some(key_source) }),
+            (Self::Postgres { .. }, Some(_)) => Err(StorageError::Database {
+                message: "Application-level encryption is only supported for SQLite; use server-side encryption for Postgres".to_string()
+            }.into()),
+        }
+    }
+
+    /// Parse a connection string: `postgres://`/`postgresql://` URLs select Postgres,
+    /// `sqlite://<path>` or a bare path selects SQLite
+    pub fn from_connection_string(connection_string: &str) -> AppResult<Self> {
+        if connection_string.starts_with("postgres://") || connection_string.starts_with("postgresql://") {
+            let max_connections = std::env::var(DATABASE_MAX_CONNECTIONS_ENV)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+
+            return Ok(Self::Postgres {
+                url: connection_string.to_string(),
+                max_connections,
+            });
+        }
+
+        let path = connection_string.strip_prefix("sqlite://").unwrap_or(connection_string);
+        if path.is_empty() {
+            return Err(StorageError::Database {
+                message: "Empty SQLite database path".to_string()
+            }.into());
+        }
+
+        Ok(Self::Sqlite { path: PathBuf::from(path), encryption: None })
+    }
+
+    /// The SQLite database in the user's data directory
+    pub fn default_sqlite() -> AppResult<Self> {
+        let db_dir = dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("free-deep-research");
+
+        ensure_dir_exists(&db_dir)?;
+        Ok(Self::Sqlite { path: db_dir.join("app_data.db"), encryption: None })
+    }
+
+    pub fn kind(&self) -> DatabaseBackendKind {
+        match self {
+            Self::Sqlite { .. } => DatabaseBackendKind::Sqlite,
+            Self::Postgres { .. } => DatabaseBackendKind::Postgres,
+        }
+    }
+}
+
+/// Storage operations shared by every database backend.
+///
+/// Implementations must behave identically for the same sequence of calls so
+/// services can run against either database without knowing which one is used.
+#[async_trait::async_trait]
+pub trait StorageBackend: Send + Sync {
+    fn kind(&self) -> DatabaseBackendKind;
+
+    /// Validate and, in `Apply` mode, bring the schema up to date
+    async fn migrate(&self, mode: MigrationMode) -> AppResult<MigrationReport>;
+
+    async fn store_api_key(&self, api_key: &ApiKey) -> AppResult<()>;
+    async fn get_all_api_keys(&self) -> AppResult<Vec<ApiKey>>;
+    /// Up to `limit` API keys after `after`, newest first, owned by `tenant_id` or, with
+    /// `include_system_pool`, by no tenant
+    async fn get_api_keys_page(&self, tenant_id: Option<Uuid>, include_system_pool: bool, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<ApiKey>>;
+    async fn get_api_key_by_id(&self, key_id: Uuid) -> AppResult<Option<ApiKey>>;
+    async fn delete_api_key(&self, key_id: Uuid) -> AppResult<()>;
+    /// Set the status of several API keys in a single transaction; fails
+    /// without changing anything if any key no longer exists
+    async fn update_api_key_statuses(&self, key_ids: &[Uuid], status: &ApiKeyStatus) -> AppResult<()>;
+    /// Delete several API keys in a single transaction; fails without
+    /// deleting anything if any key no longer exists
+    async fn delete_api_keys(&self, key_ids: &[Uuid]) -> AppResult<()>;
+
+    /// Ciphertext and key version of every stored API key
+    async fn get_api_key_ciphertexts(&self) -> AppResult<Vec<ApiKeyCiphertext>>;
+    /// Swap in re-encrypted API keys in a single transaction; fails without
+    /// changing anything if any record no longer holds its previous ciphertext
+    async fn replace_api_key_ciphertexts(&self, updates: &[ApiKeyReencryption]) -> AppResult<()>;
+
+    async fn save_research_workflow(&self, workflow: &ResearchWorkflow) -> AppResult<()>;
+    async fn delete_research_workflow(&self, workflow_id: Uuid) -> AppResult<()>;
+    /// Up to `limit` IDs of stored workflows after `after`, newest first
+    async fn get_research_workflow_ids_page(&self, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<Uuid>>;
+    async fn search_workflows(&self, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>>;
+    /// Workflows matching the filters without a text query, newest first
+    async fn browse_workflows(&self, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>>;
+    async fn get_workflow_organization(&self) -> AppResult<WorkflowOrganization>;
+
+    async fn record_api_usage(
+        &self,
+        api_key_id: Uuid,
+        service: &str,
+        endpoint: Option<&str>,
+        success: bool,
+        response_time_ms: u32,
+    ) -> AppResult<()>;
+    async fn get_api_key_usage_stats(&self, api_key_id: Uuid, days: u32) -> AppResult<Vec<(String, u32, u32, u32, f64)>>;
+    /// Daily request counts of every key of `service` over the last `days`,
+    /// as (api_key_id, date_bucket, request_count) ordered by date
+    async fn get_service_usage_history(&self, service: &str, days: u32) -> AppResult<Vec<(Uuid, String, u32)>>;
+    /// Request counts of every key from `from` through `to`, inclusive (`YYYY-MM-DD`)
+    async fn get_key_usage_totals(&self, from: &str, to: &str) -> AppResult<Vec<KeyUsageTotals>>;
+    /// Append `snapshot` to its key's usage reports
+    async fn record_provider_usage_snapshot(&self, snapshot: &ProviderUsageSnapshot) -> AppResult<()>;
+    /// Provider usage reports up to and including `to`, ordered by key and then as observed
+    async fn get_provider_usage_snapshots(&self, to: &str) -> AppResult<Vec<ProviderUsageSnapshot>>;
+    async fn record_usage_divergences(&self, divergences: &[UsageDivergence]) -> AppResult<()>;
+    /// Divergence checks made since `since`, oldest first
+    async fn get_usage_divergence_history(&self, since: DateTime<Utc>) -> AppResult<Vec<UsageDivergence>>;
+
+    async fn store_audit_event(&self, event: &AuditEvent) -> AppResult<()>;
+
+    async fn save_research_schedule(&self, schedule: &ResearchSchedule) -> AppResult<()>;
+    async fn get_research_schedules(&self) -> AppResult<Vec<ResearchSchedule>>;
+    /// Delete a schedule together with its run history
+    async fn delete_research_schedule(&self, schedule_id: Uuid) -> AppResult<()>;
+    async fn save_schedule_run(&self, run: &ScheduleRun) -> AppResult<()>;
+    /// Most recent runs of a schedule, newest first
+    async fn get_schedule_runs(&self, schedule_id: Uuid, limit: u32) -> AppResult<Vec<ScheduleRun>>;
+    /// Runs of every schedule whose workflow has not finished yet
+    async fn get_running_schedule_runs(&self) -> AppResult<Vec<ScheduleRun>>;
+
+    async fn save_prompt_template(&self, template: &PromptTemplate) -> AppResult<()>;
+    /// Every version of every prompt template
+    async fn get_prompt_templates(&self) -> AppResult<Vec<PromptTemplate>>;
+    /// Store an experiment, replacing the previous one on the same prompt
+    async fn save_prompt_experiment(&self, experiment: &PromptExperiment) -> AppResult<()>;
+    async fn get_prompt_experiments(&self) -> AppResult<Vec<PromptExperiment>>;
+
+    /// Store an idempotency key, replacing an expired record of the same key
+    async fn save_idempotency_record(&self, record: &IdempotencyRecord) -> AppResult<()>;
+    async fn get_idempotency_record(&self, key: &str) -> AppResult<Option<IdempotencyRecord>>;
+    /// Delete records that expired before `now`, returning how many were removed
+    async fn purge_expired_idempotency_records(&self, now: DateTime<Utc>) -> AppResult<u64>;
+
+    /// Insert or replace a saga's state
+    async fn save_saga_state(&self, saga: &SagaState) -> AppResult<()>;
+    /// Sagas still running or compensating, oldest first
+    async fn get_unfinished_sagas(&self) -> AppResult<Vec<SagaState>>;
+
+    /// Insert or replace a user's notification preferences
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> AppResult<()>;
+    async fn get_notification_preferences(&self, user_id: Uuid) -> AppResult<Option<NotificationPreferences>>;
+    async fn get_all_notification_preferences(&self) -> AppResult<Vec<NotificationPreferences>>;
+    /// Keep a notification for the user until their quiet hours end or digest is due
+    async fn hold_notification(&self, user_id: Uuid, notification: &Notification) -> AppResult<()>;
+    /// Remove and return the notifications held for the user, oldest first
+    async fn take_held_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>>;
+    /// Add notifications to the user's in-app inbox, keeping only the newest `keep`
+    async fn add_inbox_notifications(&self, user_id: Uuid, notifications: &[Notification], keep: usize) -> AppResult<()>;
+    /// The user's in-app notifications, newest first
+    async fn get_inbox_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>>;
+
+    /// Insert or replace a user's installation of marketplace content
+    async fn save_marketplace_installation(&self, installation: &InstalledContent) -> AppResult<()>;
+    async fn get_marketplace_installations(&self, user_id: Uuid) -> AppResult<Vec<InstalledContent>>;
+    /// Remove an installation, returning whether there was one
+    async fn delete_marketplace_installation(&self, user_id: Uuid, kind: ContentKind, content_id: Uuid) -> AppResult<bool>;
+
+    /// Insert or replace the rating of a workflow
+    async fn save_workflow_rating(&self, rating: &WorkflowRating) -> AppResult<()>;
+    /// Get a user's ratings of workflows in a query domain, oldest first
+    async fn get_workflow_ratings(&self, rated_by: &str, query_domain: QueryDomain) -> AppResult<Vec<WorkflowRating>>;
+
+    /// Append entries spilled out of an in-memory history, oldest first
+    async fn append_history(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()>;
+    /// Spilled entries of a history, newest first, recorded at or after `since` when given
+    async fn get_history(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>>;
+    /// Delete a history's spilled entries recorded before `before`, returning how many
+    async fn purge_history(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64>;
+
+    /// Record what a finished step attempt used
+    async fn save_step_metrics(&self, metrics: &StepExecutionMetrics) -> AppResult<()>;
+    /// Get the recorded step attempts of a workflow, in the order they started
+    async fn get_step_metrics(&self, workflow_id: Uuid) -> AppResult<Vec<StepExecutionMetrics>>;
+
+    /// Insert or replace a named runtime setting
+    async fn save_service_setting(&self, name: &str, definition: &serde_json::Value) -> AppResult<()>;
+    async fn get_service_setting(&self, name: &str) -> AppResult<Option<serde_json::Value>>;
+
+    /// Check connectivity and that the schema is fully migrated
+    async fn health_check(&self) -> AppResult<()>;
+
+    /// Re-encrypt the database in place under a new key
+    async fn rotate_encryption_key(&self, _old: &DatabaseKeySource, _new: &DatabaseKeySource) -> AppResult<()> {
+        Err(StorageError::Database {
+            message: format!("Encryption key rotation is not supported by the {:?} backend", self.kind())
+        }.into())
+    }
+}
+
+/// Open the configured backend and apply pending migrations
+pub async fn connect(config: &DatabaseConfig) -> AppResult<Box<dyn StorageBackend>> {
+    let backend: Box<dyn StorageBackend> = match config {
+        DatabaseConfig::Sqlite { path, encryption } => {
+            Box::new(super::sqlite_backend::SqliteBackend::open(path, encryption.as_ref())?)
+        }
+        #[cfg(feature = "postgres")]
+        DatabaseConfig::Postgres { url, max_connections } => {
+            Box::new(super::postgres_backend::PostgresBackend::connect(url, *max_connections).await?)
+        }
+        #[cfg(not(feature = "postgres"))]
+        DatabaseConfig::Postgres { .. } => {
+            return Err(StorageError::Database {
+                message: "Postgres support is not enabled in this build (enable the `postgres` feature)".to_string()
+            }.into());
+        }
+    };
+
+    backend.migrate(MigrationMode::Apply).await?;
+    Ok(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_string_selects_backend() {
+        match DatabaseConfig::from_connection_string("postgres://fdr@db:5432/research").unwrap() {
+            DatabaseConfig::Postgres { url, .. } => assert_eq!(url, "postgres://fdr@db:5432/research"),
+            other => panic!("expected postgres, got {:?}", other),
+        }
+        assert_eq!(
+            DatabaseConfig::from_connection_string("postgresql://db/research").unwrap().kind(),
+            DatabaseBackendKind::Postgres
+        );
+
+        match DatabaseConfig::from_connection_string("sqlite:///var/lib/fdr/app.db").unwrap() {
+            DatabaseConfig::Sqlite { path, .. } => assert_eq!(path, PathBuf::from("/var/lib/fdr/app.db")),
+            other => panic!("expected sqlite, got {:?}", other),
+        }
+        assert_eq!(
+            DatabaseConfig::from_connection_string("app.db").unwrap().kind(),
+            DatabaseBackendKind::Sqlite
+        );
+        assert!(DatabaseConfig::from_connection_string("sqlite://").is_err());
+
+        let postgres = DatabaseConfig::from_connection_string("postgres://db/research").unwrap();
+        assert!(postgres.with_encryption(Some(DatabaseKeySource::os_keystore())).is_err());
+    }
+}
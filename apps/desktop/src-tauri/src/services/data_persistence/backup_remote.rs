@@ -0,0 +1,179 @@
+use chrono::Utc;
+use ring::hmac;
+use serde::{Serialize, Deserialize};
+use tracing::debug;
+
+use crate::error::{AppResult, StorageError};
+use crate::utils::air_gap;
+use crate::utils::crypto::hash_sha256;
+
+/// S3-compatible bucket that backups are copied to after they are verified, and that
+/// exports can be written to
+#[derive(Clone, Serialize, Deserialize)]
+pub struct S3Target {
+    /// Endpoint URL, e.g. `https://s3.eu-west-1.amazonaws.com` or a MinIO address
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    /// Key prefix inside the bucket
+    #[serde(default)]
+    pub prefix: String,
+    pub access_key_id: String,
+    #[serde(skip_serializing, default)]
+    pub secret_access_key: String,
+}
+
+impl std::fmt::Debug for S3Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Target")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("prefix", &self.prefix)
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Target {
+    /// Read the target from `FDR_BACKUP_S3_*`; `None` when no bucket is configured
+    pub fn from_env() -> Option<Self> {
+        Self::from_env_with_suffix("")
+    }
+
+    /// Read the target from `FDR_BACKUP_S3_*_<suffix>`, e.g. `FDR_BACKUP_S3_BUCKET_EU`
+    pub fn from_env_with_suffix(suffix: &str) -> Option<Self> {
+        let var = |name: &str| std::env::var(format!("{}{}", name, suffix)).ok().filter(|v| !v.trim().is_empty());
+
+        Some(Self {
+            bucket: var("FDR_BACKUP_S3_BUCKET")?,
+            endpoint: var("FDR_BACKUP_S3_ENDPOINT").unwrap_or_else(|| "https://s3.amazonaws.com".to_string()),
+            region: var("FDR_BACKUP_S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            prefix: var("FDR_BACKUP_S3_PREFIX").unwrap_or_default(),
+            access_key_id: var("FDR_BACKUP_S3_ACCESS_KEY_ID")?,
+            secret_access_key: var("FDR_BACKUP_S3_SECRET_ACCESS_KEY")?,
+        })
+    }
+
+    pub(crate) fn object_key(&self, name: &str) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) }
+    }
+
+    /// Upload one object with a path-style, SigV4-signed `PUT`
+    pub async fn put_object(&self, name: &str, body: Vec<u8>) -> AppResult<()> {
+        air_gap::ensure_online("remote backup upload")?;
+        let key = self.object_key(name);
+        debug!("Uploading backup object {} to bucket {}", key, self.bucket);
+
+        let response = self.send_signed(&reqwest::Client::new(), reqwest::Method::PUT, &key, &[], &[], body).await
+            .map_err(|e| StorageError::backup_failed(format!("S3 upload failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(StorageError::backup_failed(format!("S3 upload of {} returned {}: {}", key, status, detail)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Send a path-style, SigV4-signed request for the object `key`. `headers`, such as
+    /// `content-type` or `x-amz-meta-*`, are signed along with the request, which goes out
+    /// on `client`.
+    pub(crate) async fn send_signed(
+        &self,
+        client: &reqwest::Client,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, String)],
+        headers: &[(String, String)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, String> {
+        let endpoint = url::Url::parse(&self.endpoint).map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+        let host = match (endpoint.host_str(), endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("S3 endpoint has no host".to_string()),
+        };
+
+        let canonical_uri = format!("/{}/{}", uri_encode(&self.bucket), key.split('/').map(uri_encode).collect::<Vec<_>>().join("/"));
+        let mut query: Vec<(String, String)> = query.iter().map(|(name, value)| (uri_encode(name), uri_encode(value))).collect();
+        query.sort();
+        let canonical_query = query.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("&");
+        let payload_hash = hex(&hash_sha256(&body));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut signed_headers: Vec<(String, String)> = headers.iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        signed_headers.push(("host".to_string(), host));
+        signed_headers.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+        signed_headers.push(("x-amz-date".to_string(), amz_date.clone()));
+        signed_headers.sort();
+        let authorization = self.authorization(method.as_str(), &canonical_uri, &canonical_query, &signed_headers, &payload_hash, &amz_date);
+
+        let mut url = format!("{}{}", self.endpoint.trim_end_matches('/'), canonical_uri);
+        if !canonical_query.is_empty() {
+            url = format!("{}?{}", url, canonical_query);
+        }
+        let mut request = client.request(method, &url);
+        for (name, value) in &signed_headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request.header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// AWS Signature Version 4 `Authorization` header value; `headers` are lowercase and sorted
+    fn authorization(
+        &self,
+        method: &str,
+        canonical_uri: &str,
+        canonical_query: &str,
+        headers: &[(String, String)],
+        payload_hash: &str,
+        amz_date: &str,
+    ) -> String {
+        let date = &amz_date[..8];
+        let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex(&hash_sha256(canonical_request.as_bytes()))
+        );
+
+        let sign = |key: &[u8], data: &str| hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes());
+        let k_date = sign(format!("AWS4{}", self.secret_access_key).as_bytes(), date);
+        let k_region = sign(k_date.as_ref(), &self.region);
+        let k_service = sign(k_region.as_ref(), "s3");
+        let k_signing = sign(k_service.as_ref(), "aws4_request");
+        let signature = hex(sign(k_signing.as_ref(), &string_to_sign).as_ref());
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// RFC 3986 encoding of a single path segment, as SigV4 requires
+fn uri_encode(segment: &str) -> String {
+    segment.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
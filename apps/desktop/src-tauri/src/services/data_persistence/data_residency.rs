@@ -0,0 +1,97 @@
+//! Data residency: pinning a tenant's records and backups to storage in its region.
+//!
+//! `Global` data lives in the primary database. Every other region needs its
+//! own database, configured with `FDR_DATABASE_URL_<REGION>` (for example
+//! `FDR_DATABASE_URL_EU`); a region without one has nowhere to store data and
+//! is refused rather than falling back to the primary database.
+
+use std::collections::HashMap;
+
+use crate::error::{AppResult, StorageError};
+use crate::models::research_workflow::ResearchWorkflow;
+use super::backend::{DatabaseConfig, DATABASE_URL_ENV};
+
+pub use crate::models::data_region::DataRegion;
+
+/// Workflow metadata key recording the region a workflow belongs to
+pub const DATA_REGION_METADATA_KEY: &str = "data_region";
+
+/// Region a workflow is pinned to; untagged workflows are `Global`
+pub fn workflow_region(workflow: &ResearchWorkflow) -> AppResult<DataRegion> {
+    match workflow.metadata.get(DATA_REGION_METADATA_KEY) {
+        Some(code) => Ok(code.parse()?),
+        None => Ok(DataRegion::Global),
+    }
+}
+
+/// Pin a workflow to `region`
+pub fn tag_workflow(workflow: &mut ResearchWorkflow, region: DataRegion) {
+    if region == DataRegion::Global {
+        workflow.metadata.remove(DATA_REGION_METADATA_KEY);
+    } else {
+        workflow.metadata.insert(DATA_REGION_METADATA_KEY.to_string(), region.code().to_string());
+    }
+}
+
+/// Refuse to let data pinned to `data_region` be read or written through `storage_region`
+pub fn ensure_residency(storage_region: DataRegion, data_region: DataRegion) -> AppResult<()> {
+    if storage_region != data_region {
+        return Err(StorageError::ResidencyViolation {
+            message: format!("{} data cannot be accessed through {} storage", data_region, storage_region)
+        }.into());
+    }
+    Ok(())
+}
+
+/// Whether `region` has somewhere to store data: `Global` always does, pinned
+/// regions only with `FDR_DATABASE_URL_<REGION>` set
+pub fn region_configured(region: DataRegion) -> bool {
+    region == DataRegion::Global
+        || std::env::var(format!("{}{}", DATABASE_URL_ENV, region.env_suffix()))
+            .map_or(false, |url| !url.trim().is_empty())
+}
+
+/// Databases for pinned regions from `FDR_DATABASE_URL_<REGION>`; encryption
+/// follows the primary database's `FDR_DATABASE_KEY_SOURCE`
+pub fn regional_configs_from_env() -> AppResult<HashMap<DataRegion, DatabaseConfig>> {
+    let mut configs = HashMap::new();
+
+    for region in DataRegion::PINNED {
+        let name = format!("{}{}", DATABASE_URL_ENV, region.env_suffix());
+        let url = match std::env::var(&name) {
+            Ok(url) if !url.trim().is_empty() => url,
+            _ => continue,
+        };
+
+        let config = DatabaseConfig::from_connection_string(url.trim())?
+            .with_encryption(DatabaseConfig::key_source_from_env()?)?;
+        configs.insert(region, config);
+    }
+
+    Ok(configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::WorkflowParameters;
+
+    #[test]
+    fn test_workflow_region_roundtrip() {
+        let mut workflow = ResearchWorkflow::new(
+            "eu workflow".to_string(),
+            "query".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        assert_eq!(workflow_region(&workflow).unwrap(), DataRegion::Global);
+
+        tag_workflow(&mut workflow, DataRegion::Eu);
+        assert_eq!(workflow_region(&workflow).unwrap(), DataRegion::Eu);
+        assert!(ensure_residency(DataRegion::Eu, DataRegion::Eu).is_ok());
+        assert!(ensure_residency(DataRegion::Global, DataRegion::Eu).is_err());
+
+        workflow.metadata.insert(DATA_REGION_METADATA_KEY.to_string(), "mars".to_string());
+        assert!(workflow_region(&workflow).is_err());
+    }
+}
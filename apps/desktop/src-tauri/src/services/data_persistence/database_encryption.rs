@@ -0,0 +1,572 @@
+//! Encryption at rest for the SQLite database.
+//!
+//! When enabled, the whole database file is encrypted page by page with
+//! SQLCipher (AES-256, HMAC-SHA512 per page). The raw 256-bit key is derived
+//! by the application, either from a user passphrase with PBKDF2-HMAC-SHA256
+//! and a per-database salt stored next to the file (`<db>.salt`), or from a
+//! random key kept in the OS keystore. Keys are handed to SQLCipher in raw
+//! form so its own KDF is skipped on every open.
+//!
+//! This is opt-in: it requires the `sqlcipher` build feature and a key source
+//! in the database configuration. Expect roughly 5-15% overhead on query
+//! throughput, a one-off passphrase derivation of a few hundred milliseconds on
+//! open, and key rotation time proportional to the database size since every
+//! page is re-encrypted.
+
+use std::path::{Path, PathBuf};
+use std::num::NonZeroU32;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::pbkdf2;
+use rusqlite::Connection;
+use serde::{Serialize, Deserialize};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::error::{AppResult, StorageError};
+
+/// PBKDF2 iterations for passphrase-derived database keys
+const PASSPHRASE_ITERATIONS: u32 = 600_000;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// First bytes of every unencrypted SQLite database file
+const SQLITE_PLAINTEXT_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Where the database key comes from
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum DatabaseKeySource {
+    /// Derived from a user passphrase; the passphrase itself is never serialized
+    Passphrase {
+        #[serde(skip_serializing, default)]
+        passphrase: String,
+    },
+    /// Random key stored in the operating system keystore
+    OsKeystore { service: String, account: String },
+}
+
+impl std::fmt::Debug for DatabaseKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passphrase { .. } => f.write_str("Passphrase { .. }"),
+            Self::OsKeystore { service, account } => f.debug_struct("OsKeystore")
+                .field("service", service)
+                .field("account", account)
+                .finish(),
+        }
+    }
+}
+
+impl DatabaseKeySource {
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        Self::Passphrase { passphrase: passphrase.into() }
+    }
+
+    /// Default keystore entry for the application database
+    pub fn os_keystore() -> Self {
+        Self::OsKeystore {
+            service: "free-deep-research".to_string(),
+            account: "database-key".to_string(),
+        }
+    }
+}
+
+/// A raw database key, wiped from memory on drop
+pub struct DatabaseKey(Zeroizing<[u8; KEY_LEN]>);
+
+impl DatabaseKey {
+    /// SQLCipher raw key literal (`x'<hex>'`), which bypasses its internal KDF
+    fn pragma_value(&self) -> Zeroizing<String> {
+        let hex: String = self.0.iter().map(|b| format!("{:02x}", b)).collect();
+        Zeroizing::new(format!("x'{}'", hex))
+    }
+}
+
+fn encryption_error(message: impl Into<String>) -> StorageError {
+    StorageError::Database { message: message.into() }
+}
+
+fn salt_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(".salt");
+    PathBuf::from(name)
+}
+
+fn pending_salt_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(".salt.new");
+    PathBuf::from(name)
+}
+
+/// Salt files that must travel with a copy of the database
+pub fn salt_files(db_path: &Path) -> Vec<PathBuf> {
+    let path = salt_path(db_path);
+    if path.exists() { vec![path] } else { Vec::new() }
+}
+
+fn random_bytes<const N: usize>() -> AppResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| encryption_error("Failed to generate random bytes"))?;
+    Ok(bytes)
+}
+
+fn read_salt(path: &Path) -> AppResult<Option<[u8; SALT_LEN]>> {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            let salt: [u8; SALT_LEN] = bytes.as_slice().try_into()
+                .map_err(|_| StorageError::Corruption { resource: path.display().to_string() })?;
+            Ok(Some(salt))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(encryption_error(format!("Failed to read {}: {}", path.display(), e)).into()),
+    }
+}
+
+fn write_salt(path: &Path, salt: &[u8; SALT_LEN]) -> AppResult<()> {
+    std::fs::write(path, salt)
+        .map_err(|e| encryption_error(format!("Failed to write {}: {}", path.display(), e)).into())
+}
+
+fn derive_passphrase_key(passphrase: &str, salt: &[u8; SALT_LEN]) -> AppResult<DatabaseKey> {
+    if passphrase.is_empty() {
+        return Err(encryption_error("Database passphrase must not be empty").into());
+    }
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PASSPHRASE_ITERATIONS).expect("non-zero iterations"),
+        salt,
+        passphrase.as_bytes(),
+        key.as_mut(),
+    );
+    Ok(DatabaseKey(key))
+}
+
+/// Where keystore-held database keys live. The OS keystore in production;
+/// the seam exists so rotation can be exercised without one.
+trait CredentialStore {
+    fn get(&self, service: &str, account: &str) -> AppResult<Option<Zeroizing<String>>>;
+    fn set(&self, service: &str, account: &str, secret: &str) -> AppResult<()>;
+    fn delete(&self, service: &str, account: &str) -> AppResult<()>;
+}
+
+struct OsCredentialStore;
+
+#[cfg(feature = "sqlcipher")]
+impl CredentialStore for OsCredentialStore {
+    fn get(&self, service: &str, account: &str) -> AppResult<Option<Zeroizing<String>>> {
+        let entry = keyring::Entry::new(service, account)
+            .map_err(|e| encryption_error(format!("OS keystore unavailable: {}", e)))?;
+        match entry.get_password() {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(encryption_error(format!("Failed to read database key from OS keystore: {}", e)).into()),
+        }
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> AppResult<()> {
+        keyring::Entry::new(service, account)
+            .and_then(|entry| entry.set_password(secret))
+            .map_err(|e| encryption_error(format!("Failed to store database key in OS keystore: {}", e)).into())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> AppResult<()> {
+        let entry = keyring::Entry::new(service, account)
+            .map_err(|e| encryption_error(format!("OS keystore unavailable: {}", e)))?;
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(encryption_error(format!("Failed to delete database key from OS keystore: {}", e)).into()),
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+impl CredentialStore for OsCredentialStore {
+    fn get(&self, _service: &str, _account: &str) -> AppResult<Option<Zeroizing<String>>> {
+        Err(encryption_error("Database encryption requires the `sqlcipher` feature").into())
+    }
+
+    fn set(&self, _service: &str, _account: &str, _secret: &str) -> AppResult<()> {
+        Err(encryption_error("Database encryption requires the `sqlcipher` feature").into())
+    }
+
+    fn delete(&self, _service: &str, _account: &str) -> AppResult<()> {
+        Err(encryption_error("Database encryption requires the `sqlcipher` feature").into())
+    }
+}
+
+/// Keystore account that holds a new key until a rotation commits
+fn pending_account(account: &str) -> String {
+    format!("{}.pending", account)
+}
+
+fn key_to_hex(key: &DatabaseKey) -> Zeroizing<String> {
+    Zeroizing::new(key.0.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn key_from_hex(hex: &str, service: &str, account: &str) -> AppResult<DatabaseKey> {
+    let corrupt = || StorageError::Corruption { resource: format!("keystore entry {}/{}", service, account) };
+    if hex.len() != KEY_LEN * 2 {
+        return Err(corrupt().into());
+    }
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| corrupt())?;
+    }
+    Ok(DatabaseKey(key))
+}
+
+fn keystore_key(store: &dyn CredentialStore, service: &str, account: &str, create: bool) -> AppResult<DatabaseKey> {
+    match store.get(service, account)? {
+        Some(hex) => key_from_hex(&hex, service, account),
+        None if create => {
+            let key = DatabaseKey(Zeroizing::new(random_bytes::<KEY_LEN>()?));
+            store.set(service, account, &key_to_hex(&key))?;
+            info!("Generated new database key in OS keystore");
+            Ok(key)
+        }
+        None => Err(encryption_error(format!("No database key in OS keystore for {}/{}", service, account)).into()),
+    }
+}
+
+/// Resolve the key for an existing (or new) database file
+fn resolve_key(
+    db_path: &Path,
+    source: &DatabaseKeySource,
+    salt: Option<[u8; SALT_LEN]>,
+    store: &dyn CredentialStore,
+) -> AppResult<DatabaseKey> {
+    match source {
+        DatabaseKeySource::Passphrase { passphrase } => {
+            let salt = match salt {
+                Some(salt) => salt,
+                None => {
+                    let salt = random_bytes::<SALT_LEN>()?;
+                    write_salt(&salt_path(db_path), &salt)?;
+                    salt
+                }
+            };
+            derive_passphrase_key(passphrase, &salt)
+        }
+        DatabaseKeySource::OsKeystore { service, account } => keystore_key(store, service, account, true),
+    }
+}
+
+/// Apply a key to a fresh connection and confirm it decrypts the database
+fn apply_key(conn: &Connection, key: &DatabaseKey) -> AppResult<bool> {
+    conn.pragma_update(None, "key", key.pragma_value().as_str())
+        .map_err(|e| encryption_error(e.to_string()))?;
+
+    Ok(conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)).is_ok())
+}
+
+fn is_plaintext_database(db_path: &Path) -> bool {
+    use std::io::Read;
+    let mut header = [0u8; 16];
+    std::fs::File::open(db_path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| &header == SQLITE_PLAINTEXT_HEADER)
+        .unwrap_or(false)
+}
+
+/// Encrypt an existing plaintext database into place via `sqlcipher_export`
+fn encrypt_plaintext_database(db_path: &Path, key: &DatabaseKey) -> AppResult<()> {
+    warn!("Encrypting existing plaintext database at {:?}", db_path);
+
+    let mut encrypted_name = db_path.as_os_str().to_os_string();
+    encrypted_name.push(".encrypting");
+    let encrypted_path = PathBuf::from(encrypted_name);
+    let _ = std::fs::remove_file(&encrypted_path);
+
+    {
+        let plain = Connection::open(db_path).map_err(|e| encryption_error(e.to_string()))?;
+        plain.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            rusqlite::params![encrypted_path.to_string_lossy(), key.pragma_value().as_str()],
+        ).map_err(|e| encryption_error(e.to_string()))?;
+        plain.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .map_err(|e| encryption_error(format!("Failed to export plaintext database: {}", e)))?;
+        plain.execute("DETACH DATABASE encrypted", []).map_err(|e| encryption_error(e.to_string()))?;
+    }
+
+    std::fs::rename(&encrypted_path, db_path)
+        .map_err(|e| encryption_error(format!("Failed to replace plaintext database: {}", e)))?;
+
+    info!("Database encrypted at rest");
+    Ok(())
+}
+
+/// Open an encrypted database, creating it or encrypting a plaintext one as needed
+pub fn open_encrypted(db_path: &Path, source: &DatabaseKeySource) -> AppResult<Connection> {
+    open_encrypted_with(db_path, source, &OsCredentialStore)
+}
+
+fn open_encrypted_with(db_path: &Path, source: &DatabaseKeySource, store: &dyn CredentialStore) -> AppResult<Connection> {
+    if !cfg!(feature = "sqlcipher") {
+        return Err(encryption_error("Database encryption requires the `sqlcipher` feature").into());
+    }
+
+    let salt = read_salt(&salt_path(db_path))?;
+    let key = resolve_key(db_path, source, salt, store)?;
+
+    if is_plaintext_database(db_path) {
+        encrypt_plaintext_database(db_path, &key)?;
+    }
+
+    let conn = Connection::open(db_path).map_err(|e| encryption_error(e.to_string()))?;
+    if apply_key(&conn, &key)? {
+        finish_interrupted_rotation(db_path, source, store);
+        return Ok(conn);
+    }
+
+    // A rotation may have re-keyed the file but crashed before the new salt
+    // replaced the old one
+    if let (DatabaseKeySource::Passphrase { passphrase }, Some(pending)) =
+        (source, read_salt(&pending_salt_path(db_path))?)
+    {
+        let pending_key = derive_passphrase_key(passphrase, &pending)?;
+        let conn = Connection::open(db_path).map_err(|e| encryption_error(e.to_string()))?;
+        if apply_key(&conn, &pending_key)? {
+            warn!("Completing interrupted database key rotation");
+            std::fs::rename(pending_salt_path(db_path), salt_path(db_path))
+                .map_err(|e| encryption_error(format!("Failed to finalize key rotation: {}", e)))?;
+            return Ok(conn);
+        }
+    }
+
+    // Likewise for a keystore key that was re-keyed but never swapped in
+    if let DatabaseKeySource::OsKeystore { service, account } = source {
+        let pending = pending_account(account);
+        if let Some(hex) = store.get(service, &pending)? {
+            let pending_key = key_from_hex(&hex, service, &pending)?;
+            let conn = Connection::open(db_path).map_err(|e| encryption_error(e.to_string()))?;
+            if apply_key(&conn, &pending_key)? {
+                warn!("Completing interrupted database key rotation");
+                store.set(service, account, &hex)?;
+                store.delete(service, &pending)?;
+                return Ok(conn);
+            }
+        }
+    }
+
+    Err(encryption_error("Invalid database key: unable to decrypt database").into())
+}
+
+/// The old key is still current, so a leftover pending salt or keystore
+/// entry is stale
+fn finish_interrupted_rotation(db_path: &Path, source: &DatabaseKeySource, store: &dyn CredentialStore) {
+    let pending = pending_salt_path(db_path);
+    if pending.exists() {
+        warn!("Discarding stale pending database salt from an interrupted key rotation");
+        let _ = std::fs::remove_file(pending);
+    }
+    if let DatabaseKeySource::OsKeystore { service, account } = source {
+        let pending = pending_account(account);
+        if matches!(store.get(service, &pending), Ok(Some(_))) {
+            warn!("Discarding stale pending keystore key from an interrupted key rotation");
+            let _ = store.delete(service, &pending);
+        }
+    }
+}
+
+/// Re-encrypt the database in place under a new key.
+///
+/// The old key is verified against a separate connection first. New keys
+/// are staged before the rekey and only swapped in afterwards: passphrase
+/// salts go to `<db>.salt.new` and keystore keys to a `<account>.pending`
+/// entry. A failed rekey discards the staged key and leaves the old one in
+/// place, and a crash at any point leaves a database that either the old or
+/// the staged key can open.
+pub fn rotate_key(conn: &Connection, db_path: &Path, old: &DatabaseKeySource, new: &DatabaseKeySource) -> AppResult<()> {
+    rotate_key_with(conn, db_path, old, new, &OsCredentialStore, &|conn, key| {
+        conn.pragma_update(None, "rekey", key.pragma_value().as_str())
+    })
+}
+
+type Rekey<'a> = &'a dyn Fn(&Connection, &DatabaseKey) -> rusqlite::Result<()>;
+
+fn rotate_key_with(
+    conn: &Connection,
+    db_path: &Path,
+    old: &DatabaseKeySource,
+    new: &DatabaseKeySource,
+    store: &dyn CredentialStore,
+    rekey: Rekey<'_>,
+) -> AppResult<()> {
+    info!("Rotating database encryption key");
+
+    if is_plaintext_database(db_path) {
+        return Err(encryption_error("Database is not encrypted; enable encryption before rotating keys").into());
+    }
+
+    let current_salt = read_salt(&salt_path(db_path))?;
+    let old_key = match old {
+        DatabaseKeySource::Passphrase { passphrase } => {
+            let salt = current_salt.ok_or_else(|| encryption_error("Database salt file is missing"))?;
+            derive_passphrase_key(passphrase, &salt)?
+        }
+        DatabaseKeySource::OsKeystore { service, account } => keystore_key(store, service, account, false)?,
+    };
+
+    let verify = Connection::open(db_path).map_err(|e| encryption_error(e.to_string()))?;
+    if !apply_key(&verify, &old_key)? {
+        return Err(encryption_error("Current database key is incorrect").into());
+    }
+    drop(verify);
+
+    let new_key = match new {
+        DatabaseKeySource::Passphrase { passphrase } => {
+            let salt = random_bytes::<SALT_LEN>()?;
+            write_salt(&pending_salt_path(db_path), &salt)?;
+            derive_passphrase_key(passphrase, &salt)?
+        }
+        DatabaseKeySource::OsKeystore { service, account } => {
+            // Stage under a temporary account so the current entry survives a failed rekey
+            let pending = pending_account(account);
+            store.delete(service, &pending)?;
+            keystore_key(store, service, &pending, true)?
+        }
+    };
+
+    let discard_staged = || {
+        match new {
+            DatabaseKeySource::Passphrase { .. } => {
+                let _ = std::fs::remove_file(pending_salt_path(db_path));
+            }
+            DatabaseKeySource::OsKeystore { service, account } => {
+                let _ = store.delete(service, &pending_account(account));
+            }
+        }
+    };
+
+    if let Err(e) = rekey(conn, &new_key) {
+        discard_staged();
+        return Err(encryption_error(format!("Failed to re-encrypt database: {}", e)).into());
+    }
+
+    match new {
+        DatabaseKeySource::Passphrase { .. } => {
+            std::fs::rename(pending_salt_path(db_path), salt_path(db_path))
+                .map_err(|e| encryption_error(format!("Failed to finalize key rotation: {}", e)))?;
+        }
+        DatabaseKeySource::OsKeystore { service, account } => {
+            if let Err(e) = store.set(service, account, &key_to_hex(&new_key)) {
+                // The old credential is still in place, so put the database back under it
+                if let Err(restore) = rekey(conn, &old_key) {
+                    warn!("Failed to restore the previous database key ({}); the new key remains in {}/{}",
+                        restore, service, pending_account(account));
+                    return Err(e);
+                }
+                discard_staged();
+                return Err(e);
+            }
+            let _ = store.delete(service, &pending_account(account));
+            // Keystore keys carry no salt
+            let _ = std::fs::remove_file(salt_path(db_path));
+        }
+    }
+
+    info!("Database encryption key rotated");
+    Ok(())
+}
+
+#[cfg(all(test, feature = "sqlcipher"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_rotation_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        let old = DatabaseKeySource::passphrase("correct horse");
+        let new = DatabaseKeySource::passphrase("battery staple");
+
+        let conn = open_encrypted(&db_path, &old).unwrap();
+        conn.execute("CREATE TABLE notes (body TEXT)", []).unwrap();
+        conn.execute("INSERT INTO notes VALUES ('secret')", []).unwrap();
+        assert!(!is_plaintext_database(&db_path));
+
+        assert!(rotate_key(&conn, &db_path, &new, &new).is_err());
+        rotate_key(&conn, &db_path, &old, &new).unwrap();
+        drop(conn);
+
+        assert!(open_encrypted(&db_path, &old).is_err());
+        let conn = open_encrypted(&db_path, &new).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "secret");
+    }
+
+    #[derive(Default)]
+    struct MemoryCredentialStore(std::sync::Mutex<std::collections::HashMap<String, String>>);
+
+    impl CredentialStore for MemoryCredentialStore {
+        fn get(&self, service: &str, account: &str) -> AppResult<Option<Zeroizing<String>>> {
+            Ok(self.0.lock().unwrap().get(&format!("{}/{}", service, account)).cloned().map(Zeroizing::new))
+        }
+
+        fn set(&self, service: &str, account: &str, secret: &str) -> AppResult<()> {
+            self.0.lock().unwrap().insert(format!("{}/{}", service, account), secret.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, service: &str, account: &str) -> AppResult<()> {
+            self.0.lock().unwrap().remove(&format!("{}/{}", service, account));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_failed_keystore_rekey_keeps_old_credential() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        let store = MemoryCredentialStore::default();
+        let source = DatabaseKeySource::os_keystore();
+        let (service, account) = ("free-deep-research", "database-key");
+
+        let conn = open_encrypted_with(&db_path, &source, &store).unwrap();
+        conn.execute("CREATE TABLE notes (body TEXT)", []).unwrap();
+        conn.execute("INSERT INTO notes VALUES ('secret')", []).unwrap();
+        let original = store.get(service, account).unwrap().unwrap().to_string();
+
+        let failing: Rekey<'_> = &|_, _| Err(rusqlite::Error::InvalidQuery);
+        assert!(rotate_key_with(&conn, &db_path, &source, &source, &store, failing).is_err());
+        assert_eq!(store.get(service, account).unwrap().unwrap().as_str(), original);
+        assert!(store.get(service, &pending_account(account)).unwrap().is_none());
+        drop(conn);
+
+        let conn = open_encrypted_with(&db_path, &source, &store).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "secret");
+
+        let rekey: Rekey<'_> = &|conn, key| conn.pragma_update(None, "rekey", key.pragma_value().as_str());
+        rotate_key_with(&conn, &db_path, &source, &source, &store, rekey).unwrap();
+        assert_ne!(store.get(service, account).unwrap().unwrap().as_str(), original);
+        assert!(store.get(service, &pending_account(account)).unwrap().is_none());
+        drop(conn);
+
+        let conn = open_encrypted_with(&db_path, &source, &store).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "secret");
+    }
+
+    #[test]
+    fn test_plaintext_database_is_encrypted_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        {
+            let plain = Connection::open(&db_path).unwrap();
+            plain.execute("CREATE TABLE notes (body TEXT)", []).unwrap();
+            plain.execute("INSERT INTO notes VALUES ('kept')", []).unwrap();
+        }
+        assert!(is_plaintext_database(&db_path));
+
+        let conn = open_encrypted(&db_path, &DatabaseKeySource::passphrase("pw")).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "kept");
+        assert!(!is_plaintext_database(&db_path));
+    }
+}
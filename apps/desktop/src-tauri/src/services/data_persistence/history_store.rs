@@ -0,0 +1,328 @@
+//! Bounded in-memory histories that can spill their oldest entries to the database.
+//!
+//! Services keep recent records (outputs, rate limit alerts, finished workflows) in
+//! memory for fast queries. A `BoundedHistory` caps how many it holds; once full, the
+//! oldest entries are either dropped or, with spilling on, appended to the
+//! `history_entries` table, and queries continue into them past the in-memory window.
+
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::error::{AppResult, StorageError};
+use super::DataPersistenceService;
+
+/// Turns spilling on for every history that does not set its own `FDR_HISTORY_<NAME>_SPILL`
+pub const HISTORY_SPILL_ENV: &str = "FDR_HISTORY_SPILL_TO_DISK";
+
+/// Most spilled entries a single query reads back
+const MAX_SPILLED_READ: usize = 10_000;
+
+/// How many entries a history keeps in memory, and what happens to older ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryLimit {
+    pub max_in_memory: usize,
+    /// Write entries pushed out of memory to the database instead of dropping them
+    pub spill_to_disk: bool,
+}
+
+impl HistoryLimit {
+    pub fn new(max_in_memory: usize) -> Self {
+        Self { max_in_memory, spill_to_disk: false }
+    }
+
+    /// `default_max` entries, overridden by `FDR_HISTORY_<NAME>_MAX`, spilling per
+    /// `FDR_HISTORY_<NAME>_SPILL` or else `FDR_HISTORY_SPILL_TO_DISK`
+    pub fn from_env(name: &str, default_max: usize) -> Self {
+        let prefix = format!("FDR_HISTORY_{}", name.to_uppercase());
+        let flag = |name: &str| std::env::var(name).ok().map(|v| matches!(v.trim(), "1" | "true" | "yes" | "on"));
+
+        Self {
+            max_in_memory: std::env::var(format!("{}_MAX", prefix)).ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default_max),
+            spill_to_disk: flag(&format!("{}_SPILL", prefix))
+                .or_else(|| flag(HISTORY_SPILL_ENV))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// A record kept in a history
+pub trait HistoryEntry: Serialize + DeserializeOwned + Clone + Send + Sync {
+    /// When the record was made; spilled entries are queried by it
+    fn recorded_at(&self) -> DateTime<Utc>;
+}
+
+/// An entry as stored in the database once spilled
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    pub recorded_at: DateTime<Utc>,
+    pub definition: String,
+}
+
+/// Where spilled entries are kept
+#[async_trait::async_trait]
+pub trait HistoryStore: Send + Sync {
+    async fn append(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()>;
+    /// Entries recorded at or after `since`, newest first
+    async fn read(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>>;
+    /// Delete entries recorded before `before`, returning how many
+    async fn purge(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64>;
+}
+
+#[async_trait::async_trait]
+impl HistoryStore for RwLock<DataPersistenceService> {
+    async fn append(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()> {
+        RwLock::read(self).await.append_history(kind, records).await
+    }
+
+    async fn read(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>> {
+        RwLock::read(self).await.get_history(kind, since, limit).await
+    }
+
+    async fn purge(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64> {
+        RwLock::read(self).await.purge_history(kind, before).await
+    }
+}
+
+/// The latest entries of one kind of record, oldest first, capped by a `HistoryLimit`
+pub struct BoundedHistory<T> {
+    /// Names this history's rows in the database
+    kind: &'static str,
+    limit: HistoryLimit,
+    entries: VecDeque<T>,
+    store: Option<Arc<dyn HistoryStore>>,
+}
+
+impl<T: HistoryEntry> BoundedHistory<T> {
+    /// A history that only ever keeps `limit.max_in_memory` entries
+    pub fn new(kind: &'static str, limit: HistoryLimit) -> Self {
+        Self { kind, limit, entries: VecDeque::new(), store: None }
+    }
+
+    /// Spill entries pushed out of memory to `store` when the limit asks for it
+    pub fn with_store(mut self, store: Arc<dyn HistoryStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn limit(&self) -> HistoryLimit {
+        self.limit
+    }
+
+    fn spills(&self) -> bool {
+        self.limit.spill_to_disk && self.store.is_some()
+    }
+
+    /// Add an entry, spilling or dropping the oldest ones beyond the limit
+    pub async fn push(&mut self, entry: T) {
+        self.entries.push_back(entry);
+        let evicted = self.evict();
+        self.spill(evicted).await;
+    }
+
+    /// Add several entries, oldest first
+    pub async fn extend(&mut self, entries: impl IntoIterator<Item = T>) {
+        self.entries.extend(entries);
+        let evicted = self.evict();
+        self.spill(evicted).await;
+    }
+
+    /// Keep only the in-memory entries `keep` accepts
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.entries.retain(keep);
+    }
+
+    /// Change the limit, evicting entries that no longer fit
+    pub async fn set_limit(&mut self, limit: HistoryLimit) {
+        self.limit = limit;
+        let evicted = self.evict();
+        self.spill(evicted).await;
+    }
+
+    /// Take the oldest entries beyond the limit out of memory
+    fn evict(&mut self) -> Vec<T> {
+        let excess = self.entries.len().saturating_sub(self.limit.max_in_memory);
+        self.entries.drain(..excess).collect()
+    }
+
+    async fn spill(&self, evicted: Vec<T>) {
+        let store = match &self.store {
+            Some(store) if self.limit.spill_to_disk && !evicted.is_empty() => store,
+            _ => return,
+        };
+
+        let records: Result<Vec<HistoryRecord>, _> = evicted.iter()
+            .map(|entry| serde_json::to_string(entry).map(|definition| HistoryRecord {
+                recorded_at: entry.recorded_at(),
+                definition,
+            }))
+            .collect();
+        let result = match records {
+            Ok(records) => store.append(self.kind, &records).await,
+            Err(e) => Err(StorageError::Database { message: format!("Failed to serialize {} history: {}", self.kind, e) }.into()),
+        };
+        // The entries are already out of memory; losing them is what happened before spilling
+        if let Err(e) = result {
+            warn!("Dropped {} {} history entries that could not be spilled: {}", evicted.len(), self.kind, e);
+        }
+    }
+
+    /// The newest `limit` entries, newest first, continuing into spilled ones when
+    /// memory holds fewer
+    pub async fn recent(&self, limit: usize) -> AppResult<Vec<T>> {
+        let mut recent: Vec<T> = self.entries.iter().rev().take(limit).cloned().collect();
+        if recent.len() < limit && self.spills() {
+            let remaining = (limit - recent.len()).min(MAX_SPILLED_READ);
+            recent.extend(self.read_spilled(None, remaining).await?);
+        }
+        Ok(recent)
+    }
+
+    /// Entries recorded at or after `since`, newest first, spilled ones included
+    pub async fn since(&self, since: DateTime<Utc>) -> AppResult<Vec<T>> {
+        let mut matching: Vec<T> = self.entries.iter().rev()
+            .filter(|entry| entry.recorded_at() >= since)
+            .cloned()
+            .collect();
+        if self.spills() {
+            matching.extend(self.read_spilled(Some(since), MAX_SPILLED_READ).await?);
+        }
+        Ok(matching)
+    }
+
+    /// The newest entry `matches` accepts, looking past the in-memory window into
+    /// spilled entries
+    pub async fn find(&self, matches: impl Fn(&T) -> bool) -> AppResult<Option<T>> {
+        if let Some(entry) = self.entries.iter().rev().find(|entry| matches(entry)) {
+            return Ok(Some(entry.clone()));
+        }
+        if !self.spills() {
+            return Ok(None);
+        }
+        Ok(self.read_spilled(None, MAX_SPILLED_READ).await?.into_iter().find(|entry| matches(entry)))
+    }
+
+    /// Delete spilled entries recorded before `before`, returning how many
+    pub async fn purge_spilled(&self, before: DateTime<Utc>) -> AppResult<u64> {
+        match &self.store {
+            Some(store) => store.purge(self.kind, before).await,
+            None => Ok(0),
+        }
+    }
+
+    async fn read_spilled(&self, since: Option<DateTime<Utc>>, limit: usize) -> AppResult<Vec<T>> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return Ok(Vec::new()),
+        };
+        let records = store.read(self.kind, since, limit as u32).await?;
+        records.iter()
+            .map(|record| serde_json::from_str(&record.definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize {} history: {}", self.kind, e) }.into()))
+            .collect()
+    }
+}
+
+/// The in-memory entries, oldest first
+impl<T> Deref for BoundedHistory<T> {
+    type Target = VecDeque<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Spilled records, newest last
+    #[derive(Default)]
+    struct MemoryStore {
+        records: Mutex<Vec<(String, HistoryRecord)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HistoryStore for MemoryStore {
+        async fn append(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()> {
+            self.records.lock().unwrap().extend(records.iter().map(|record| (kind.to_string(), record.clone())));
+            Ok(())
+        }
+
+        async fn read(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>> {
+            Ok(self.records.lock().unwrap().iter().rev()
+                .filter(|(k, record)| k == kind && since.map_or(true, |since| record.recorded_at >= since))
+                .take(limit as usize)
+                .map(|(_, record)| record.clone())
+                .collect())
+        }
+
+        async fn purge(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64> {
+            let mut records = self.records.lock().unwrap();
+            let count = records.len();
+            records.retain(|(k, record)| k != kind || record.recorded_at >= before);
+            Ok((count - records.len()) as u64)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Entry(u32, DateTime<Utc>);
+
+    impl HistoryEntry for Entry {
+        fn recorded_at(&self) -> DateTime<Utc> {
+            self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_keeps_the_newest_entries_up_to_its_limit() {
+        let start = Utc::now();
+        let at = |n: u32| Entry(n, start + chrono::Duration::seconds(n as i64));
+        let mut history = BoundedHistory::new("test", HistoryLimit::new(3));
+        for n in 0..5 {
+            history.push(at(n)).await;
+        }
+        assert_eq!(history.iter().map(|entry| entry.0).collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        // Without a store there is nothing beyond the in-memory window
+        assert_eq!(history.recent(10).await.unwrap(), vec![at(4), at(3), at(2)]);
+        assert_eq!(history.since(start + chrono::Duration::seconds(3)).await.unwrap(), vec![at(4), at(3)]);
+
+        history.extend([at(5), at(6)]).await;
+        assert_eq!(history.front(), Some(&at(4)));
+        history.set_limit(HistoryLimit { max_in_memory: 1, spill_to_disk: true }).await;
+        assert_eq!(history.len(), 1);
+        assert!(!history.spills());
+    }
+
+    #[tokio::test]
+    async fn test_spilled_entries_continue_queries_past_the_in_memory_window() {
+        let start = Utc::now();
+        let at = |n: u32| Entry(n, start + chrono::Duration::seconds(n as i64));
+        let store = Arc::new(MemoryStore::default());
+        let mut history = BoundedHistory::new("test", HistoryLimit { max_in_memory: 2, spill_to_disk: true })
+            .with_store(store.clone());
+        for n in 0..5 {
+            history.push(at(n)).await;
+        }
+        assert_eq!(history.iter().map(|entry| entry.0).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(store.records.lock().unwrap().len(), 3);
+
+        assert_eq!(history.recent(4).await.unwrap(), vec![at(4), at(3), at(2), at(1)]);
+        assert_eq!(history.since(start + chrono::Duration::seconds(1)).await.unwrap(), vec![at(4), at(3), at(2), at(1)]);
+        assert_eq!(history.find(|entry| entry.0 == 0).await.unwrap(), Some(at(0)));
+        assert_eq!(history.find(|entry| entry.0 == 9).await.unwrap(), None);
+
+        assert_eq!(history.purge_spilled(start + chrono::Duration::seconds(2)).await.unwrap(), 2);
+        assert_eq!(history.recent(10).await.unwrap(), vec![at(4), at(3), at(2)]);
+        assert_eq!(history.find(|entry| entry.0 == 0).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,177 @@
+use std::future::Future;
+use serde::{Serialize, Deserialize};
+use tracing::{info, debug};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use super::backend::StorageBackend;
+
+/// Stored ciphertext of an API key and the master key version it was written with
+#[derive(Debug, Clone)]
+pub struct ApiKeyCiphertext {
+    pub id: Uuid,
+    pub encrypted_key: String,
+    /// `None` for keys stored before versions were tracked
+    pub key_version: Option<String>,
+}
+
+/// A replacement ciphertext, applied only if the stored value is still `previous_encrypted_key`
+#[derive(Debug, Clone)]
+pub struct ApiKeyReencryption {
+    pub id: Uuid,
+    pub previous_encrypted_key: String,
+    pub encrypted_key: String,
+    pub key_version: String,
+}
+
+/// Outcome of re-encrypting stored API keys under the current master key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasterKeyRotationReport {
+    pub key_version: String,
+    pub reencrypted: usize,
+    /// Keys that were already under `key_version`, e.g. from an earlier interrupted run
+    pub already_current: usize,
+}
+
+/// Re-encrypt every stored API key that is not yet under `target_version`.
+///
+/// All replacements are committed in one transaction, so an interrupted run
+/// leaves every record on its previous key; per-record versions let a rerun
+/// pick up exactly the records that still need it.
+pub async fn reencrypt_api_keys<F, Fut>(
+    backend: &dyn StorageBackend,
+    target_version: &str,
+    reencrypt: F,
+) -> AppResult<MasterKeyRotationReport>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = AppResult<String>>,
+{
+    let records = backend.get_api_key_ciphertexts().await?;
+    let total = records.len();
+
+    let mut updates = Vec::new();
+    for record in records {
+        if record.key_version.as_deref() == Some(target_version) {
+            continue;
+        }
+
+        debug!("Re-encrypting API key {} ({:?} -> {})", record.id, record.key_version, target_version);
+        let encrypted_key = reencrypt(record.encrypted_key.clone()).await?;
+        updates.push(ApiKeyReencryption {
+            id: record.id,
+            previous_encrypted_key: record.encrypted_key,
+            encrypted_key,
+            key_version: target_version.to_string(),
+        });
+    }
+
+    if !updates.is_empty() {
+        backend.replace_api_key_ciphertexts(&updates).await?;
+    }
+
+    info!("Re-encrypted {} of {} API keys under key version {}", updates.len(), total, target_version);
+    Ok(MasterKeyRotationReport {
+        key_version: target_version.to_string(),
+        reencrypted: updates.len(),
+        already_current: total - updates.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+    use crate::models::api_key::ServiceProvider;
+    use crate::models::ApiKey;
+    use crate::services::data_persistence::migrations::MigrationMode;
+    use crate::services::data_persistence::sqlite_backend::SqliteBackend;
+    use crate::services::security::encryption_manager::EncryptionManager;
+
+    async fn encrypt(manager: &Arc<RwLock<EncryptionManager>>, plaintext: &str) -> String {
+        base64::encode(manager.read().await.encrypt(plaintext.as_bytes()).await.unwrap())
+    }
+
+    async fn decrypt(manager: &Arc<RwLock<EncryptionManager>>, encrypted: &str) -> String {
+        let bytes = base64::decode(encrypted).unwrap();
+        String::from_utf8(manager.read().await.decrypt(&bytes).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rotation_roundtrips_api_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = SqliteBackend::open(&dir.path().join("app.db"), None).unwrap();
+        backend.migrate(MigrationMode::Apply).await.unwrap();
+
+        let mut manager = EncryptionManager::new().await.unwrap();
+        manager.initialize_with_password("old master key").await.unwrap();
+        let manager = Arc::new(RwLock::new(manager));
+
+        let secrets = ["sk-or-1111111111", "serp-2222222222", "jina-3333333333"];
+        let mut ids = Vec::new();
+        for (i, secret) in secrets.iter().enumerate() {
+            let key = ApiKey::new(ServiceProvider::OpenRouter, format!("key {}", i), encrypt(&manager, secret).await);
+            backend.store_api_key(&key).await.unwrap();
+            ids.push(key.id);
+        }
+
+        let new_version = manager.write().await.rotate_master_key("new master key").await.unwrap();
+        let reencrypt = |ciphertext: String| {
+            let manager = manager.clone();
+            async move {
+                let bytes = base64::decode(&ciphertext).unwrap();
+                Ok(base64::encode(manager.read().await.reencrypt(&bytes).await?))
+            }
+        };
+
+        let report = reencrypt_api_keys(&backend, &new_version, &reencrypt).await.unwrap();
+        assert_eq!(report.reencrypted, secrets.len());
+        assert_eq!(report.already_current, 0);
+
+        for (id, secret) in ids.iter().zip(secrets) {
+            let stored = backend.get_api_key_by_id(*id).await.unwrap().unwrap();
+            assert_eq!(crate::services::security::key_version_of(&stored.encrypted_key), Some(new_version.clone()));
+            assert_eq!(decrypt(&manager, &stored.encrypted_key).await, secret);
+        }
+        assert!(backend.get_api_key_ciphertexts().await.unwrap()
+            .iter()
+            .all(|record| record.key_version.as_deref() == Some(new_version.as_str())));
+
+        // Resuming after completion is a no-op
+        let again = reencrypt_api_keys(&backend, &new_version, &reencrypt).await.unwrap();
+        assert_eq!(again.reencrypted, 0);
+        assert_eq!(again.already_current, secrets.len());
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_update_rolls_back_whole_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = SqliteBackend::open(&dir.path().join("app.db"), None).unwrap();
+        backend.migrate(MigrationMode::Apply).await.unwrap();
+
+        let first = ApiKey::new(ServiceProvider::Exa, "first".to_string(), "ciphertext-a".to_string());
+        let second = ApiKey::new(ServiceProvider::Exa, "second".to_string(), "ciphertext-b".to_string());
+        backend.store_api_key(&first).await.unwrap();
+        backend.store_api_key(&second).await.unwrap();
+
+        let updates = vec![
+            ApiKeyReencryption {
+                id: first.id,
+                previous_encrypted_key: "ciphertext-a".to_string(),
+                encrypted_key: "rotated-a".to_string(),
+                key_version: "v2".to_string(),
+            },
+            ApiKeyReencryption {
+                id: second.id,
+                previous_encrypted_key: "stale".to_string(),
+                encrypted_key: "rotated-b".to_string(),
+                key_version: "v2".to_string(),
+            },
+        ];
+        assert!(backend.replace_api_key_ciphertexts(&updates).await.is_err());
+
+        let stored = backend.get_api_key_by_id(first.id).await.unwrap().unwrap();
+        assert_eq!(stored.encrypted_key, "ciphertext-a");
+    }
+}
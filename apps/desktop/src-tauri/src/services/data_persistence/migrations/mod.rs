@@ -0,0 +1,492 @@
+use rusqlite::{Connection, params};
+use serde::{Serialize, Deserialize};
+use tracing::{info, debug, error};
+
+use crate::error::{AppResult, StorageError};
+
+/// SQL dialect a migration is applied with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SqlDialect {
+    Sqlite,
+    Postgres,
+}
+
+/// A single versioned schema migration, with one script per supported dialect
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sqlite: &'static str,
+    pub postgres: &'static str,
+}
+
+impl Migration {
+    /// Migration script for the given dialect
+    pub fn sql(&self, dialect: SqlDialect) -> &'static str {
+        match dialect {
+            SqlDialect::Sqlite => self.sqlite,
+            SqlDialect::Postgres => self.postgres,
+        }
+    }
+
+    /// SHA-256 of the migration script, used to detect edits to applied migrations
+    pub fn checksum(&self, dialect: SqlDialect) -> String {
+        ring::digest::digest(&ring::digest::SHA256, self.sql(dialect).as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Ordered list of all schema migrations. Append only: never edit or reorder
+/// an entry once it has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sqlite: include_str!("sql/sqlite/0001_initial_schema.sql"),
+        postgres: include_str!("sql/postgres/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "workflow_search_index",
+        sqlite: include_str!("sql/sqlite/0002_workflow_search_index.sql"),
+        postgres: include_str!("sql/postgres/0002_workflow_search_index.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "api_key_key_version",
+        sqlite: include_str!("sql/sqlite/0003_api_key_key_version.sql"),
+        postgres: include_str!("sql/postgres/0003_api_key_key_version.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "api_key_monthly_quota",
+        sqlite: include_str!("sql/sqlite/0004_api_key_monthly_quota.sql"),
+        postgres: include_str!("sql/postgres/0004_api_key_monthly_quota.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "api_key_expires_at",
+        sqlite: include_str!("sql/sqlite/0005_api_key_expires_at.sql"),
+        postgres: include_str!("sql/postgres/0005_api_key_expires_at.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "research_schedules",
+        sqlite: include_str!("sql/sqlite/0006_research_schedules.sql"),
+        postgres: include_str!("sql/postgres/0006_research_schedules.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "prompt_templates",
+        sqlite: include_str!("sql/sqlite/0007_prompt_templates.sql"),
+        postgres: include_str!("sql/postgres/0007_prompt_templates.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "api_key_tenant",
+        sqlite: include_str!("sql/sqlite/0008_api_key_tenant.sql"),
+        postgres: include_str!("sql/postgres/0008_api_key_tenant.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "workflow_idempotency_keys",
+        sqlite: include_str!("sql/sqlite/0009_workflow_idempotency_keys.sql"),
+        postgres: include_str!("sql/postgres/0009_workflow_idempotency_keys.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "workflow_sagas",
+        sqlite: include_str!("sql/sqlite/0010_workflow_sagas.sql"),
+        postgres: include_str!("sql/postgres/0010_workflow_sagas.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "workflow_organization",
+        sqlite: include_str!("sql/sqlite/0011_workflow_organization.sql"),
+        postgres: include_str!("sql/postgres/0011_workflow_organization.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "provider_usage_snapshots",
+        sqlite: include_str!("sql/sqlite/0012_provider_usage_snapshots.sql"),
+        postgres: include_str!("sql/postgres/0012_provider_usage_snapshots.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "usage_divergence_history",
+        sqlite: include_str!("sql/sqlite/0013_usage_divergence_history.sql"),
+        postgres: include_str!("sql/postgres/0013_usage_divergence_history.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "notification_preferences",
+        sqlite: include_str!("sql/sqlite/0014_notification_preferences.sql"),
+        postgres: include_str!("sql/postgres/0014_notification_preferences.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "marketplace_installations",
+        sqlite: include_str!("sql/sqlite/0015_marketplace_installations.sql"),
+        postgres: include_str!("sql/postgres/0015_marketplace_installations.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "workflow_ratings",
+        sqlite: include_str!("sql/sqlite/0016_workflow_ratings.sql"),
+        postgres: include_str!("sql/postgres/0016_workflow_ratings.sql"),
+    },
+    Migration {
+        version: 17,
+        name: "history_entries",
+        sqlite: include_str!("sql/sqlite/0017_history_entries.sql"),
+        postgres: include_str!("sql/postgres/0017_history_entries.sql"),
+    },
+    Migration {
+        version: 18,
+        name: "step_execution_metrics",
+        sqlite: include_str!("sql/sqlite/0018_step_execution_metrics.sql"),
+        postgres: include_str!("sql/postgres/0018_step_execution_metrics.sql"),
+    },
+    Migration {
+        version: 19,
+        name: "service_settings",
+        sqlite: include_str!("sql/sqlite/0019_service_settings.sql"),
+        postgres: include_str!("sql/postgres/0019_service_settings.sql"),
+    },
+    Migration {
+        version: 20,
+        name: "provider_usage_snapshot_log",
+        sqlite: include_str!("sql/sqlite/0020_provider_usage_snapshot_log.sql"),
+        postgres: include_str!("sql/postgres/0020_provider_usage_snapshot_log.sql"),
+    },
+    Migration {
+        version: 21,
+        name: "notification_inbox",
+        sqlite: include_str!("sql/sqlite/0021_notification_inbox.sql"),
+        postgres: include_str!("sql/postgres/0021_notification_inbox.sql"),
+    },
+    Migration {
+        version: 22,
+        name: "list_pagination_indexes",
+        sqlite: include_str!("sql/sqlite/0022_list_pagination_indexes.sql"),
+        postgres: include_str!("sql/postgres/0022_list_pagination_indexes.sql"),
+    },
+];
+
+/// How the runner should treat pending migrations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationMode {
+    /// Apply all pending migrations
+    Apply,
+    /// Validate state and report pending migrations without writing anything
+    DryRun,
+}
+
+/// A migration recorded in `schema_migrations`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub dirty: bool,
+    pub applied_at: String,
+}
+
+/// Outcome of validating or running migrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub mode: MigrationMode,
+    pub dialect: SqlDialect,
+    pub current_version: u32,
+    pub target_version: u32,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<u32>,
+    pub applied_now: Vec<u32>,
+}
+
+/// Applies versioned migrations and tracks them in `schema_migrations`
+pub struct MigrationRunner {
+    migrations: &'static [Migration],
+}
+
+impl MigrationRunner {
+    /// Create a runner, rejecting migration lists that are not strictly ordered
+    pub fn new(migrations: &'static [Migration]) -> AppResult<Self> {
+        for pair in migrations.windows(2) {
+            if pair[1].version <= pair[0].version {
+                return Err(StorageError::MigrationFailed {
+                    from_version: pair[0].version.to_string(),
+                    to_version: pair[1].version.to_string(),
+                    message: "Migrations must be listed in strictly increasing version order".to_string(),
+                }.into());
+            }
+        }
+
+        Ok(Self { migrations })
+    }
+
+    /// Validate the recorded state and bring a SQLite schema up to date
+    pub fn run(&self, conn: &Connection, mode: MigrationMode) -> AppResult<MigrationReport> {
+        let mut report = self.validate(conn, mode)?;
+
+        if mode == MigrationMode::DryRun || report.pending.is_empty() {
+            debug!("Schema at version {}, {} pending migrations", report.current_version, report.pending.len());
+            return Ok(report);
+        }
+
+        self.ensure_tracking_table(conn)?;
+
+        for migration in self.pending_migrations(&report) {
+            self.apply(conn, migration, report.current_version)?;
+            report.current_version = migration.version;
+            report.applied_now.push(migration.version);
+        }
+
+        report.applied = self.load_applied(conn)?;
+        report.pending.clear();
+
+        info!("Applied {} migrations, schema now at version {}", report.applied_now.len(), report.current_version);
+        Ok(report)
+    }
+
+    /// Check a SQLite database's recorded migrations without writing anything
+    pub fn validate(&self, conn: &Connection, mode: MigrationMode) -> AppResult<MigrationReport> {
+        let applied = if self.tracking_table_exists(conn)? {
+            self.load_applied(conn)?
+        } else {
+            Vec::new()
+        };
+
+        self.plan(applied, SqlDialect::Sqlite, mode)
+    }
+
+    /// Compare recorded migrations against the known list.
+    ///
+    /// Fails on a dirty migration (interrupted mid-apply), on a recorded version
+    /// this build does not know about, on checksum mismatches, and on gaps.
+    /// Backends that cannot use a rusqlite connection drive their own apply
+    /// loop from the resulting plan.
+    pub fn plan(&self, applied: Vec<AppliedMigration>, dialect: SqlDialect, mode: MigrationMode) -> AppResult<MigrationReport> {
+        if let Some(dirty) = applied.iter().find(|m| m.dirty) {
+            return Err(StorageError::MigrationFailed {
+                from_version: dirty.version.saturating_sub(1).to_string(),
+                to_version: dirty.version.to_string(),
+                message: format!(
+                    "Migration {} ({}) was interrupted and the database is in a dirty state; restore from backup or repair manually",
+                    dirty.version, dirty.name
+                ),
+            }.into());
+        }
+
+        for record in &applied {
+            let known = self.migrations.iter()
+                .find(|m| m.version == record.version)
+                .ok_or_else(|| StorageError::MigrationFailed {
+                    from_version: record.version.to_string(),
+                    to_version: self.target_version().to_string(),
+                    message: format!(
+                        "Database has migration {} ({}) which this version does not know about",
+                        record.version, record.name
+                    ),
+                })?;
+
+            if known.checksum(dialect) != record.checksum {
+                return Err(StorageError::MigrationFailed {
+                    from_version: record.version.to_string(),
+                    to_version: record.version.to_string(),
+                    message: format!("Checksum mismatch for applied migration {} ({})", record.version, record.name),
+                }.into());
+            }
+        }
+
+        let current_version = applied.iter().map(|m| m.version).max().unwrap_or(0);
+        let pending: Vec<u32> = self.migrations.iter()
+            .map(|m| m.version)
+            .filter(|v| !applied.iter().any(|a| a.version == *v))
+            .collect();
+
+        if let Some(out_of_order) = pending.iter().find(|v| **v < current_version) {
+            return Err(StorageError::MigrationFailed {
+                from_version: current_version.to_string(),
+                to_version: out_of_order.to_string(),
+                message: format!("Migration {} is missing but later migrations are applied", out_of_order),
+            }.into());
+        }
+
+        Ok(MigrationReport {
+            mode,
+            dialect,
+            current_version,
+            target_version: self.target_version(),
+            applied,
+            pending,
+            applied_now: Vec::new(),
+        })
+    }
+
+    /// Migrations still to apply for a report, in order
+    pub fn pending_migrations(&self, report: &MigrationReport) -> Vec<&'static Migration> {
+        self.migrations.iter().filter(|m| report.pending.contains(&m.version)).collect()
+    }
+
+    fn target_version(&self) -> u32 {
+        self.migrations.last().map(|m| m.version).unwrap_or(0)
+    }
+
+    /// Apply one migration. The version is first recorded as dirty so an
+    /// interrupted run is detected on the next start instead of silently retried.
+    fn apply(&self, conn: &Connection, migration: &Migration, from_version: u32) -> AppResult<()> {
+        info!("Applying migration {} ({})", migration.version, migration.name);
+
+        let failed = |message: String| StorageError::MigrationFailed {
+            from_version: from_version.to_string(),
+            to_version: migration.version.to_string(),
+            message,
+        };
+
+        conn.execute(
+            "INSERT INTO schema_migrations (version, name, checksum, dirty) VALUES (?1, ?2, ?3, 1)",
+            params![migration.version, migration.name, migration.checksum(SqlDialect::Sqlite)],
+        ).map_err(|e| failed(e.to_string()))?;
+
+        let result = conn.execute_batch("BEGIN IMMEDIATE")
+            .and_then(|_| conn.execute_batch(migration.sqlite))
+            .and_then(|_| conn.execute(
+                "UPDATE schema_migrations SET dirty = 0, applied_at = CURRENT_TIMESTAMP WHERE version = ?1",
+                params![migration.version],
+            ))
+            .and_then(|_| conn.execute_batch("COMMIT"));
+
+        if let Err(e) = result {
+            error!("Migration {} ({}) failed: {}", migration.version, migration.name, e);
+            let _ = conn.execute_batch("ROLLBACK");
+            // The transaction rolled back cleanly, so the schema is unchanged
+            let _ = conn.execute("DELETE FROM schema_migrations WHERE version = ?1", params![migration.version]);
+            return Err(failed(e.to_string()).into());
+        }
+
+        Ok(())
+    }
+
+    fn tracking_table_exists(&self, conn: &Connection) -> AppResult<bool> {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type='table' AND name='schema_migrations'"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(stmt.exists([]).map_err(|e| StorageError::Database { message: e.to_string() })?)
+    }
+
+    fn ensure_tracking_table(&self, conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                dirty BOOLEAN NOT NULL DEFAULT 0,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    fn load_applied(&self, conn: &Connection) -> AppResult<Vec<AppliedMigration>> {
+        let mut stmt = conn.prepare(
+            "SELECT version, name, checksum, dirty, applied_at FROM schema_migrations ORDER BY version"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                checksum: row.get(2)?,
+                dirty: row.get(3)?,
+                applied_at: row.get(4)?,
+            })
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut applied = Vec::new();
+        for row in rows {
+            applied.push(row.map_err(|e| StorageError::Database { message: e.to_string() })?);
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_tables(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' ORDER BY name").unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(MIGRATIONS).unwrap();
+
+        let first = runner.run(&conn, MigrationMode::Apply).unwrap();
+        assert_eq!(first.applied_now.len(), MIGRATIONS.len());
+        assert_eq!(first.current_version, first.target_version);
+
+        let second = runner.run(&conn, MigrationMode::Apply).unwrap();
+        assert!(second.applied_now.is_empty());
+        assert!(second.pending.is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_does_not_write() {
+        let conn = Connection::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(MIGRATIONS).unwrap();
+
+        let report = runner.run(&conn, MigrationMode::DryRun).unwrap();
+        assert_eq!(report.pending.len(), MIGRATIONS.len());
+        assert!(schema_tables(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_dirty_state_fails() {
+        let conn = Connection::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(MIGRATIONS).unwrap();
+        runner.run(&conn, MigrationMode::Apply).unwrap();
+
+        conn.execute("UPDATE schema_migrations SET dirty = 1 WHERE version = 2", []).unwrap();
+        assert!(runner.run(&conn, MigrationMode::Apply).is_err());
+        assert!(runner.validate(&conn, MigrationMode::DryRun).is_err());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_fails() {
+        let conn = Connection::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(MIGRATIONS).unwrap();
+        runner.run(&conn, MigrationMode::Apply).unwrap();
+
+        conn.execute("UPDATE schema_migrations SET checksum = 'edited' WHERE version = 1", []).unwrap();
+        assert!(runner.run(&conn, MigrationMode::Apply).is_err());
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        static BROKEN: &[Migration] = &[
+            Migration { version: 1, name: "ok", sqlite: "CREATE TABLE a (id INTEGER);", postgres: "" },
+            Migration { version: 2, name: "broken", sqlite: "CREATE TABLE b (id INTEGER); NOT VALID SQL;", postgres: "" },
+        ];
+        let conn = Connection::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(BROKEN).unwrap();
+
+        assert!(runner.run(&conn, MigrationMode::Apply).is_err());
+        let tables = schema_tables(&conn);
+        assert!(tables.contains(&"a".to_string()));
+        assert!(!tables.contains(&"b".to_string()));
+
+        let report = runner.validate(&conn, MigrationMode::DryRun).unwrap();
+        assert_eq!(report.current_version, 1);
+        assert_eq!(report.pending, vec![2]);
+    }
+}
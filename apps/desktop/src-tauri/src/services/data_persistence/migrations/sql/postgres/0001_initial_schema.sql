@@ -0,0 +1,75 @@
+-- Baseline schema (Postgres). Mirrors sqlite/0001_initial_schema.sql.
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    service TEXT NOT NULL,
+    name TEXT NOT NULL,
+    encrypted_key BYTEA NOT NULL,
+    usage_count BIGINT DEFAULT 0,
+    rate_limit BIGINT NOT NULL,
+    reset_period TEXT NOT NULL,
+    last_used TIMESTAMPTZ,
+    last_reset TIMESTAMPTZ NOT NULL,
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS system_config (
+    id TEXT PRIMARY KEY,
+    backup_interval BIGINT NOT NULL,
+    encryption_enabled BOOLEAN NOT NULL,
+    rate_limit_buffer BIGINT NOT NULL,
+    monitoring_enabled BOOLEAN NOT NULL,
+    log_level TEXT NOT NULL,
+    ui_theme TEXT NOT NULL,
+    auto_start_monitoring BOOLEAN NOT NULL,
+    max_concurrent_research BIGINT NOT NULL,
+    data_retention_days BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS research_workflows (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    status TEXT NOT NULL,
+    methodology TEXT NOT NULL,
+    parameters TEXT,
+    results TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS api_usage_stats (
+    id TEXT PRIMARY KEY,
+    api_key_id TEXT NOT NULL REFERENCES api_keys (id) ON DELETE CASCADE,
+    service TEXT NOT NULL,
+    endpoint TEXT,
+    request_count BIGINT DEFAULT 1,
+    success_count BIGINT DEFAULT 0,
+    error_count BIGINT DEFAULT 0,
+    total_response_time_ms BIGINT DEFAULT 0,
+    avg_response_time_ms DOUBLE PRECISION DEFAULT 0,
+    last_used TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    date_bucket TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    description TEXT NOT NULL,
+    resource_id TEXT,
+    user_id TEXT,
+    timestamp TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    metadata TEXT DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_service ON api_keys(service);
+CREATE INDEX IF NOT EXISTS idx_api_keys_status ON api_keys(status);
+CREATE INDEX IF NOT EXISTS idx_workflows_status ON research_workflows(status);
+CREATE INDEX IF NOT EXISTS idx_usage_stats_api_key ON api_usage_stats(api_key_id);
+CREATE INDEX IF NOT EXISTS idx_usage_stats_date ON api_usage_stats(date_bucket);
@@ -0,0 +1,21 @@
+-- Full-text index over workflow names, queries, summaries and sources (Postgres).
+-- Equivalent of the SQLite FTS5 table, using a weighted tsvector.
+
+CREATE TABLE IF NOT EXISTS workflow_search_index (
+    workflow_id TEXT PRIMARY KEY REFERENCES research_workflows (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    summary TEXT NOT NULL DEFAULT '',
+    sources TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL,
+    methodology TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    document TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(query, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(summary, '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(sources, '')), 'C')
+    ) STORED
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_search_document ON workflow_search_index USING GIN (document);
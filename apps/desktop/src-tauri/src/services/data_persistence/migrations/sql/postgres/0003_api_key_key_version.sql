@@ -0,0 +1,6 @@
+-- Records which master key version each API key ciphertext was written with.
+-- Mirrors sqlite/0003_api_key_key_version.sql.
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS key_version TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_key_version ON api_keys(key_version);
@@ -0,0 +1,6 @@
+-- Hard monthly request quota per API key.
+-- Mirrors sqlite/0004_api_key_monthly_quota.sql.
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS monthly_quota BIGINT;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS quota_usage BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS quota_period_start TIMESTAMPTZ;
@@ -0,0 +1,6 @@
+-- Provider-side expiry date of each API key.
+-- Mirrors sqlite/0005_api_key_expires_at.sql.
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_expires_at ON api_keys(expires_at);
@@ -0,0 +1,23 @@
+-- Recurring research schedules and the runs they fire.
+-- Mirrors sqlite/0006_research_schedules.sql.
+
+CREATE TABLE IF NOT EXISTS research_schedules (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    next_run_at TIMESTAMPTZ,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS research_schedule_runs (
+    id TEXT PRIMARY KEY,
+    schedule_id TEXT NOT NULL REFERENCES research_schedules(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    details TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON research_schedule_runs(schedule_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_status ON research_schedule_runs(status);
@@ -0,0 +1,17 @@
+-- Versioned prompt templates for LLM steps and the A/B experiments run on them.
+-- Mirrors sqlite/0007_prompt_templates.sql.
+
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (id, version)
+);
+
+CREATE TABLE IF NOT EXISTS prompt_experiments (
+    prompt_id TEXT PRIMARY KEY,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    definition TEXT NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
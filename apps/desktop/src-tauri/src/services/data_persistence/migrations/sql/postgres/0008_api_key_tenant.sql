@@ -0,0 +1,6 @@
+-- Tenant owning each API key.
+-- Mirrors sqlite/0008_api_key_tenant.sql.
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_tenant_service ON api_keys(tenant_id, service);
@@ -0,0 +1,12 @@
+-- Idempotency keys sent with workflow creation requests.
+-- Mirrors sqlite/0009_workflow_idempotency_keys.sql.
+
+CREATE TABLE IF NOT EXISTS workflow_idempotency_keys (
+    key TEXT PRIMARY KEY,
+    workflow_id TEXT NOT NULL,
+    request_fingerprint TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_idempotency_keys_expires_at ON workflow_idempotency_keys(expires_at);
@@ -0,0 +1,12 @@
+-- Progress of multi-service workflow operations.
+-- Mirrors sqlite/0010_workflow_sagas.sql.
+
+CREATE TABLE IF NOT EXISTS workflow_sagas (
+    id TEXT PRIMARY KEY,
+    workflow_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_sagas_status ON workflow_sagas(status);
@@ -0,0 +1,32 @@
+-- Folders and tags for organizing workflows (Postgres). Tags are also stored
+-- one row per tag so workflows can be listed by tag.
+
+ALTER TABLE research_workflows ADD COLUMN IF NOT EXISTS folder TEXT;
+ALTER TABLE research_workflows ADD COLUMN IF NOT EXISTS tags TEXT NOT NULL DEFAULT '[]';
+
+CREATE INDEX IF NOT EXISTS idx_workflows_folder ON research_workflows(folder);
+
+CREATE TABLE IF NOT EXISTS workflow_tags (
+    workflow_id TEXT NOT NULL REFERENCES research_workflows (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (workflow_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_tags_tag ON workflow_tags(tag);
+
+-- A generated column's expression cannot be changed, so the document is
+-- recreated to weight tags alongside names and queries
+ALTER TABLE workflow_search_index ADD COLUMN IF NOT EXISTS tags TEXT NOT NULL DEFAULT '';
+ALTER TABLE workflow_search_index ADD COLUMN IF NOT EXISTS folder TEXT;
+
+DROP INDEX IF EXISTS idx_workflow_search_document;
+ALTER TABLE workflow_search_index DROP COLUMN IF EXISTS document;
+ALTER TABLE workflow_search_index ADD COLUMN document TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', coalesce(name, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(query, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(tags, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(summary, '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(sources, '')), 'C')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_workflow_search_document ON workflow_search_index USING GIN (document);
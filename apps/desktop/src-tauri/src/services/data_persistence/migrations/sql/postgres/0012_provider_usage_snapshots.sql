@@ -0,0 +1,14 @@
+-- Usage counters providers report in their response headers.
+-- Mirrors sqlite/0012_provider_usage_snapshots.sql.
+
+CREATE TABLE IF NOT EXISTS provider_usage_snapshots (
+    api_key_id TEXT NOT NULL REFERENCES api_keys (id) ON DELETE CASCADE,
+    service TEXT NOT NULL,
+    date_bucket TEXT NOT NULL,
+    reported_used BIGINT NOT NULL,
+    reported_limit BIGINT,
+    observed_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (api_key_id, date_bucket)
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_usage_snapshots_date ON provider_usage_snapshots(date_bucket);
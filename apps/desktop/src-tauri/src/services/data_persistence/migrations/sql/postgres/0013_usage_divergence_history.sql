@@ -0,0 +1,18 @@
+-- Results of each usage divergence check.
+-- Mirrors sqlite/0013_usage_divergence_history.sql.
+
+CREATE TABLE IF NOT EXISTS usage_divergence_history (
+    id BIGSERIAL PRIMARY KEY,
+    api_key_id TEXT NOT NULL REFERENCES api_keys (id) ON DELETE CASCADE,
+    service TEXT NOT NULL,
+    range_start TEXT NOT NULL,
+    range_end TEXT NOT NULL,
+    counted BIGINT NOT NULL,
+    reported BIGINT NOT NULL,
+    delta BIGINT NOT NULL,
+    divergence_percent DOUBLE PRECISION NOT NULL,
+    threshold_exceeded BOOLEAN NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_divergence_checked_at ON usage_divergence_history(checked_at);
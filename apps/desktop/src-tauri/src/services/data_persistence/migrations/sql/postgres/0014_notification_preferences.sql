@@ -0,0 +1,18 @@
+-- Per-user notification preferences and held notifications.
+-- Mirrors sqlite/0014_notification_preferences.sql.
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id TEXT PRIMARY KEY,
+    tenant_id TEXT,
+    definition TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS held_notifications (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_held_notifications_user ON held_notifications(user_id, created_at);
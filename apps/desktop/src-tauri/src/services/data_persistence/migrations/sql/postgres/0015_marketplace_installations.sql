@@ -0,0 +1,11 @@
+-- Marketplace content installed per user.
+-- Mirrors sqlite/0015_marketplace_installations.sql.
+
+CREATE TABLE IF NOT EXISTS marketplace_installations (
+    user_id TEXT NOT NULL,
+    content_kind TEXT NOT NULL,
+    content_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    installed_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, content_kind, content_id)
+);
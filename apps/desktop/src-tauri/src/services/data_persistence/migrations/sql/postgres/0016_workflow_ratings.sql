@@ -0,0 +1,13 @@
+-- Users' ratings of finished workflows.
+-- Mirrors sqlite/0016_workflow_ratings.sql.
+
+CREATE TABLE IF NOT EXISTS workflow_ratings (
+    workflow_id TEXT PRIMARY KEY,
+    rated_by TEXT NOT NULL,
+    query_domain TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    rated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_ratings_user_domain
+    ON workflow_ratings (rated_by, query_domain);
@@ -0,0 +1,12 @@
+-- Entries spilled out of the services' bounded in-memory histories.
+-- Mirrors sqlite/0017_history_entries.sql.
+
+CREATE TABLE IF NOT EXISTS history_entries (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_history_entries_kind_recorded_at
+    ON history_entries (kind, recorded_at);
@@ -0,0 +1,13 @@
+-- Actual resource use of each workflow step attempt.
+-- Mirrors sqlite/0018_step_execution_metrics.sql.
+
+CREATE TABLE IF NOT EXISTS step_execution_metrics (
+    id BIGSERIAL PRIMARY KEY,
+    workflow_id TEXT NOT NULL,
+    step_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_step_execution_metrics_workflow
+    ON step_execution_metrics (workflow_id, started_at);
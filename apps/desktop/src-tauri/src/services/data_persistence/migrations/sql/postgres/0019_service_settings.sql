@@ -0,0 +1,8 @@
+-- Settings the services change at runtime.
+-- Mirrors sqlite/0019_service_settings.sql.
+
+CREATE TABLE IF NOT EXISTS service_settings (
+    name TEXT PRIMARY KEY,
+    definition TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
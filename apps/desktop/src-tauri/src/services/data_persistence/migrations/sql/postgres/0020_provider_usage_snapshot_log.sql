@@ -0,0 +1,7 @@
+-- Keep every usage report a provider sends instead of only the day's latest.
+-- Mirrors sqlite/0020_provider_usage_snapshot_log.sql.
+
+ALTER TABLE provider_usage_snapshots DROP CONSTRAINT IF EXISTS provider_usage_snapshots_pkey;
+ALTER TABLE provider_usage_snapshots ADD COLUMN id BIGSERIAL PRIMARY KEY;
+
+CREATE INDEX IF NOT EXISTS idx_provider_usage_snapshots_key ON provider_usage_snapshots(api_key_id, observed_at);
@@ -0,0 +1,11 @@
+-- In-app notifications per user.
+-- Mirrors sqlite/0021_notification_inbox.sql.
+
+CREATE TABLE IF NOT EXISTS notification_inbox (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_inbox_user ON notification_inbox(user_id, created_at);
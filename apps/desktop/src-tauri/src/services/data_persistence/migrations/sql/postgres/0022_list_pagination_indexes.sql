@@ -0,0 +1,5 @@
+-- Indexes for paged lists of API keys and workflows.
+-- Mirrors sqlite/0022_list_pagination_indexes.sql.
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_created_at ON api_keys(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_workflows_created_at ON research_workflows(created_at, id);
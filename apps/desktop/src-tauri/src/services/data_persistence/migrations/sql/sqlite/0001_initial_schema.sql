@@ -0,0 +1,77 @@
+-- Baseline schema. Statements are idempotent so databases created before
+-- migrations were tracked can adopt this version without changes.
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    service TEXT NOT NULL,
+    name TEXT NOT NULL,
+    encrypted_key BLOB NOT NULL,
+    usage_count INTEGER DEFAULT 0,
+    rate_limit INTEGER NOT NULL,
+    reset_period TEXT NOT NULL,
+    last_used DATETIME,
+    last_reset DATETIME NOT NULL,
+    status TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS system_config (
+    id TEXT PRIMARY KEY,
+    backup_interval INTEGER NOT NULL,
+    encryption_enabled BOOLEAN NOT NULL,
+    rate_limit_buffer INTEGER NOT NULL,
+    monitoring_enabled BOOLEAN NOT NULL,
+    log_level TEXT NOT NULL,
+    ui_theme TEXT NOT NULL,
+    auto_start_monitoring BOOLEAN NOT NULL,
+    max_concurrent_research INTEGER NOT NULL,
+    data_retention_days INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS research_workflows (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    status TEXT NOT NULL,
+    methodology TEXT NOT NULL,
+    parameters TEXT,
+    results TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME
+);
+
+CREATE TABLE IF NOT EXISTS api_usage_stats (
+    id TEXT PRIMARY KEY,
+    api_key_id TEXT NOT NULL,
+    service TEXT NOT NULL,
+    endpoint TEXT,
+    request_count INTEGER DEFAULT 1,
+    success_count INTEGER DEFAULT 0,
+    error_count INTEGER DEFAULT 0,
+    total_response_time_ms INTEGER DEFAULT 0,
+    avg_response_time_ms REAL DEFAULT 0,
+    last_used DATETIME DEFAULT CURRENT_TIMESTAMP,
+    date_bucket TEXT NOT NULL,
+    FOREIGN KEY (api_key_id) REFERENCES api_keys (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    description TEXT NOT NULL,
+    resource_id TEXT,
+    user_id TEXT,
+    timestamp TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    metadata TEXT DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_service ON api_keys(service);
+CREATE INDEX IF NOT EXISTS idx_api_keys_status ON api_keys(status);
+CREATE INDEX IF NOT EXISTS idx_workflows_status ON research_workflows(status);
+CREATE INDEX IF NOT EXISTS idx_usage_stats_api_key ON api_usage_stats(api_key_id);
+CREATE INDEX IF NOT EXISTS idx_usage_stats_date ON api_usage_stats(date_bucket);
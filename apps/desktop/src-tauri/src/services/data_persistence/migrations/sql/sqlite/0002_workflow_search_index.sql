@@ -0,0 +1,21 @@
+-- Full-text index over workflow names, queries, summaries and sources
+
+CREATE VIRTUAL TABLE IF NOT EXISTS workflow_search_index USING fts5(
+    workflow_id UNINDEXED,
+    name,
+    query,
+    summary,
+    sources,
+    status UNINDEXED,
+    methodology UNINDEXED,
+    created_at UNINDEXED,
+    tokenize = 'porter unicode61'
+);
+
+-- Index rows are maintained alongside the workflow row, so deleting the row
+-- directly must not leave a stale entry behind
+CREATE TRIGGER IF NOT EXISTS research_workflows_search_delete
+AFTER DELETE ON research_workflows
+BEGIN
+    DELETE FROM workflow_search_index WHERE workflow_id = old.id;
+END;
@@ -0,0 +1,7 @@
+-- Records which master key version each API key ciphertext was written with,
+-- so a master key rotation can re-encrypt only the records it has not reached.
+-- NULL marks keys stored before versions were tracked.
+
+ALTER TABLE api_keys ADD COLUMN key_version TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_key_version ON api_keys(key_version);
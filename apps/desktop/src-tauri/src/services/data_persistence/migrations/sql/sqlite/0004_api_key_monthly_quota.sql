@@ -0,0 +1,7 @@
+-- Hard monthly request quota per API key. quota_usage counts requests in the
+-- billing cycle that started at quota_period_start; NULL monthly_quota means
+-- the key is uncapped.
+
+ALTER TABLE api_keys ADD COLUMN monthly_quota INTEGER;
+ALTER TABLE api_keys ADD COLUMN quota_usage INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE api_keys ADD COLUMN quota_period_start DATETIME;
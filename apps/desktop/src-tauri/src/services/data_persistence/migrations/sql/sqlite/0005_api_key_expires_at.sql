@@ -0,0 +1,5 @@
+-- Provider-side expiry date of each API key; NULL means the key does not expire.
+
+ALTER TABLE api_keys ADD COLUMN expires_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_expires_at ON api_keys(expires_at);
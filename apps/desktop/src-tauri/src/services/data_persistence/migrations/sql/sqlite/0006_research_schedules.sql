@@ -0,0 +1,23 @@
+-- Recurring research schedules and the runs they fire. Schedules and runs are
+-- stored as JSON documents; the extra columns only serve lookups.
+
+CREATE TABLE IF NOT EXISTS research_schedules (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    paused INTEGER NOT NULL DEFAULT 0,
+    next_run_at DATETIME,
+    definition TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS research_schedule_runs (
+    id TEXT PRIMARY KEY,
+    schedule_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    started_at DATETIME NOT NULL,
+    details TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON research_schedule_runs(schedule_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_status ON research_schedule_runs(status);
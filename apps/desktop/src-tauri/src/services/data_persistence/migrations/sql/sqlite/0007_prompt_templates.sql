@@ -0,0 +1,17 @@
+-- Versioned prompt templates for LLM steps and the A/B experiments run on
+-- them. Both are stored as JSON documents; the extra columns only serve lookups.
+
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id, version)
+);
+
+CREATE TABLE IF NOT EXISTS prompt_experiments (
+    prompt_id TEXT PRIMARY KEY,
+    active INTEGER NOT NULL DEFAULT 1,
+    definition TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
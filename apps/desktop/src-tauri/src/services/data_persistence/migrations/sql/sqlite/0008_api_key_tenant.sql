@@ -0,0 +1,5 @@
+-- Tenant owning each API key; NULL keeps the key in the shared system pool.
+
+ALTER TABLE api_keys ADD COLUMN tenant_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_tenant_service ON api_keys(tenant_id, service);
@@ -0,0 +1,12 @@
+-- Idempotency keys sent with workflow creation requests, each bound to the
+-- workflow its first request created until it expires.
+
+CREATE TABLE IF NOT EXISTS workflow_idempotency_keys (
+    key TEXT PRIMARY KEY,
+    workflow_id TEXT NOT NULL,
+    request_fingerprint TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_idempotency_keys_expires_at ON workflow_idempotency_keys(expires_at);
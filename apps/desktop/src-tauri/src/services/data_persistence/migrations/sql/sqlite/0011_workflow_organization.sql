@@ -0,0 +1,49 @@
+-- Folders and tags for organizing workflows. Tags are also stored one row per
+-- tag so workflows can be listed by tag without scanning every row's JSON.
+
+ALTER TABLE research_workflows ADD COLUMN folder TEXT;
+ALTER TABLE research_workflows ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+
+CREATE INDEX IF NOT EXISTS idx_workflows_folder ON research_workflows(folder);
+
+CREATE TABLE IF NOT EXISTS workflow_tags (
+    workflow_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (workflow_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_tags_tag ON workflow_tags(tag);
+
+-- FTS5 tables cannot gain columns, so the search index is rebuilt with tags
+-- and folder. Existing entries carry over untagged, as their workflows are.
+DROP TRIGGER IF EXISTS research_workflows_search_delete;
+
+CREATE VIRTUAL TABLE workflow_search_index_new USING fts5(
+    workflow_id UNINDEXED,
+    name,
+    query,
+    summary,
+    sources,
+    tags,
+    status UNINDEXED,
+    methodology UNINDEXED,
+    created_at UNINDEXED,
+    folder UNINDEXED,
+    tokenize = 'porter unicode61'
+);
+
+INSERT INTO workflow_search_index_new (
+    workflow_id, name, query, summary, sources, tags, status, methodology, created_at, folder
+)
+SELECT workflow_id, name, query, summary, sources, '', status, methodology, created_at, NULL
+FROM workflow_search_index;
+
+DROP TABLE workflow_search_index;
+ALTER TABLE workflow_search_index_new RENAME TO workflow_search_index;
+
+CREATE TRIGGER IF NOT EXISTS research_workflows_search_delete
+AFTER DELETE ON research_workflows
+BEGIN
+    DELETE FROM workflow_search_index WHERE workflow_id = old.id;
+    DELETE FROM workflow_tags WHERE workflow_id = old.id;
+END;
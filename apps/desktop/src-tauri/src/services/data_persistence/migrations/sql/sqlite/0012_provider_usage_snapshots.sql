@@ -0,0 +1,16 @@
+-- Usage counters providers report in their response headers, kept per key and
+-- day to reconcile against our own api_usage_stats counts. Only the day's latest
+-- report is kept.
+
+CREATE TABLE IF NOT EXISTS provider_usage_snapshots (
+    api_key_id TEXT NOT NULL,
+    service TEXT NOT NULL,
+    date_bucket TEXT NOT NULL,
+    reported_used INTEGER NOT NULL,
+    reported_limit INTEGER,
+    observed_at TEXT NOT NULL,
+    PRIMARY KEY (api_key_id, date_bucket),
+    FOREIGN KEY (api_key_id) REFERENCES api_keys (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_usage_snapshots_date ON provider_usage_snapshots(date_bucket);
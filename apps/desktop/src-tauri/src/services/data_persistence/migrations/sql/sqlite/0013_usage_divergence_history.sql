@@ -0,0 +1,19 @@
+-- Results of each check comparing our request counts with provider-reported
+-- usage, kept for trend analysis whether or not they raised an alert.
+
+CREATE TABLE IF NOT EXISTS usage_divergence_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    api_key_id TEXT NOT NULL,
+    service TEXT NOT NULL,
+    range_start TEXT NOT NULL,
+    range_end TEXT NOT NULL,
+    counted INTEGER NOT NULL,
+    reported INTEGER NOT NULL,
+    delta INTEGER NOT NULL,
+    divergence_percent REAL NOT NULL,
+    threshold_exceeded BOOLEAN NOT NULL,
+    checked_at TEXT NOT NULL,
+    FOREIGN KEY (api_key_id) REFERENCES api_keys (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_divergence_checked_at ON usage_divergence_history(checked_at);
@@ -0,0 +1,18 @@
+-- Per-user notification preferences, and the notifications held back for quiet
+-- hours or a digest. Both are stored as JSON; the extra columns only serve lookups.
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id TEXT PRIMARY KEY,
+    tenant_id TEXT,
+    definition TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS held_notifications (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_held_notifications_user ON held_notifications(user_id, created_at);
@@ -0,0 +1,12 @@
+-- Marketplace agents and methodologies installed per user, with the dependency
+-- manifest each was installed with, so uninstalls can find dependents.
+-- Stored as JSON; the extra columns only serve lookups.
+
+CREATE TABLE IF NOT EXISTS marketplace_installations (
+    user_id TEXT NOT NULL,
+    content_kind TEXT NOT NULL,
+    content_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    installed_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, content_kind, content_id)
+);
@@ -0,0 +1,14 @@
+-- Users' ratings of finished workflows, used to tune which methodology is
+-- recommended for similar queries. Stored as JSON; the extra columns only
+-- serve lookups.
+
+CREATE TABLE IF NOT EXISTS workflow_ratings (
+    workflow_id TEXT PRIMARY KEY,
+    rated_by TEXT NOT NULL,
+    query_domain TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    rated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_ratings_user_domain
+    ON workflow_ratings (rated_by, query_domain);
@@ -0,0 +1,13 @@
+-- Entries pushed out of the services' bounded in-memory histories when spilling
+-- is on, so history queries can read past the in-memory window. Stored as JSON;
+-- `kind` names the history and `id` keeps insertion order.
+
+CREATE TABLE IF NOT EXISTS history_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_history_entries_kind_recorded_at
+    ON history_entries (kind, recorded_at);
@@ -0,0 +1,14 @@
+-- Actual resource use of each workflow step attempt: wall time, provider calls,
+-- tokens and bytes fetched, for performance analysis and benchmarking. Stored as
+-- JSON; the extra columns only serve lookups.
+
+CREATE TABLE IF NOT EXISTS step_execution_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workflow_id TEXT NOT NULL,
+    step_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    started_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_step_execution_metrics_workflow
+    ON step_execution_metrics (workflow_id, started_at);
@@ -0,0 +1,8 @@
+-- Settings the services change at runtime, such as quota and redaction
+-- configs, so they survive a restart. One JSON document per setting name.
+
+CREATE TABLE IF NOT EXISTS service_settings (
+    name TEXT PRIMARY KEY,
+    definition TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
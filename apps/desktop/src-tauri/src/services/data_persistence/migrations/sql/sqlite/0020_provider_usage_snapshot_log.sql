@@ -0,0 +1,25 @@
+-- Keep every usage report a provider sends instead of only the day's latest, so
+-- a counter that resets within a day still counts the requests before the reset.
+
+CREATE TABLE provider_usage_snapshots_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    api_key_id TEXT NOT NULL,
+    service TEXT NOT NULL,
+    date_bucket TEXT NOT NULL,
+    reported_used INTEGER NOT NULL,
+    reported_limit INTEGER,
+    observed_at TEXT NOT NULL,
+    FOREIGN KEY (api_key_id) REFERENCES api_keys (id) ON DELETE CASCADE
+);
+
+INSERT INTO provider_usage_snapshots_new
+    (api_key_id, service, date_bucket, reported_used, reported_limit, observed_at)
+SELECT api_key_id, service, date_bucket, reported_used, reported_limit, observed_at
+FROM provider_usage_snapshots
+ORDER BY observed_at;
+
+DROP TABLE provider_usage_snapshots;
+ALTER TABLE provider_usage_snapshots_new RENAME TO provider_usage_snapshots;
+
+CREATE INDEX IF NOT EXISTS idx_provider_usage_snapshots_date ON provider_usage_snapshots(date_bucket);
+CREATE INDEX IF NOT EXISTS idx_provider_usage_snapshots_key ON provider_usage_snapshots(api_key_id, observed_at);
@@ -0,0 +1,11 @@
+-- In-app notifications, so a user's inbox survives a restart. Stored as JSON;
+-- older entries beyond the inbox size are pruned as new ones arrive.
+
+CREATE TABLE IF NOT EXISTS notification_inbox (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_inbox_user ON notification_inbox(user_id, created_at);
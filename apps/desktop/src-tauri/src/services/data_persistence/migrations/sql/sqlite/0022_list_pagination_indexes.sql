@@ -0,0 +1,5 @@
+-- Paged lists of API keys and workflows read newest first by creation time, then
+-- ID, continuing from the last row of the previous page.
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_created_at ON api_keys(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_workflows_created_at ON research_workflows(created_at, id);
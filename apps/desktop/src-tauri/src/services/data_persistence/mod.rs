@@ -1,421 +1,431 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::path::PathBuf;
 use tokio::sync::RwLock;
-use tracing::{info, debug, error};
-use rusqlite::{Connection, params};
-use dirs;
-use serde_json;
+use tracing::{info, debug, warn, error};
+use rusqlite::Connection;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::error::{AppResult, StorageError};
 use crate::services::{Service, SecurityService};
-use crate::models::{ApiKey, SystemConfiguration, audit::AuditEvent};
-use crate::utils::file_utils::ensure_dir_exists;
+use crate::models::{ApiKey, audit::AuditEvent};
+use crate::models::api_key::ApiKeyStatus;
+use crate::models::research_workflow::{ResearchResults, ResearchWorkflow};
+use crate::models::research_schedule::{ResearchSchedule, ScheduleRun};
+use crate::models::prompt_template::{PromptExperiment, PromptTemplate};
+use crate::models::idempotency::IdempotencyRecord;
+use crate::models::saga::SagaState;
+use crate::models::notification::{Notification, NotificationPreferences};
+use crate::models::ai_marketplace::{ContentKind, InstalledContent};
+use crate::models::workflow_rating::{QueryDomain, WorkflowRating};
+use crate::models::usage_report::{KeyUsageTotals, ProviderUsageSnapshot, UsageDivergence};
+use crate::models::execution_metrics::{StepExecutionMetrics, WorkflowExecutionMetrics};
+use crate::models::pagination::{Page, PagePosition};
 
 pub mod encrypted_storage;
 pub mod backup_manager;
 pub mod config_store;
+pub mod workflow_search;
+pub mod migrations;
+pub mod backend;
+pub mod database_encryption;
+pub mod database_backup;
+pub mod backup_remote;
+pub mod key_rotation;
+pub mod data_residency;
+pub mod redaction;
+pub mod history_store;
+pub mod sqlite_backend;
+#[cfg(feature = "postgres")]
+pub mod postgres_backend;
+
+pub use history_store::{BoundedHistory, HistoryEntry, HistoryLimit, HistoryRecord, HistoryStore};
+pub use workflow_search::{WorkflowGroup, WorkflowOrganization, WorkflowSearchFilters, WorkflowSearchMatch};
+pub use migrations::{MigrationMode, MigrationReport, MigrationRunner, SqlDialect, MIGRATIONS};
+pub use backend::{DatabaseBackendKind, DatabaseConfig, StorageBackend};
+pub use database_encryption::DatabaseKeySource;
+pub use database_backup::{BackupManifest, BackupPolicy, BackupStatus, DatabaseBackups};
+pub use backup_remote::S3Target;
+pub use key_rotation::MasterKeyRotationReport;
+pub use data_residency::DataRegion;
+pub use redaction::{RedactionCategory, RedactionConfig, RedactionFinding, RedactionReport};
+
+/// Setting the result redaction config is saved under
+const REDACTION_SETTING: &str = "redaction_config";
 
 /// Data Persistence Service that manages encrypted storage and backups
 pub struct DataPersistenceService {
     security: Arc<RwLock<SecurityService>>,
-    db_path: PathBuf,
-    connection: Option<Connection>,
+    config: DatabaseConfig,
+    backend: Box<dyn StorageBackend>,
+    backups: Option<DatabaseBackups>,
+    /// Databases pinned to a data region, keyed by region; `Global` data uses `backend`
+    regions: HashMap<DataRegion, RegionalStore>,
+    /// Personal data scrubbed from research results before they are stored
+    redaction: RedactionConfig,
+}
+
+/// Database and backups that hold one region's data
+struct RegionalStore {
+    backend: Box<dyn StorageBackend>,
+    backups: Option<DatabaseBackups>,
 }
 
 impl DataPersistenceService {
-    /// Create a new data persistence service
+    /// Create a new data persistence service using the database selected by `FDR_DATABASE_URL`
     pub async fn new(security: Arc<RwLock<SecurityService>>) -> AppResult<Self> {
-        info!("Initializing data persistence service...");
+        Self::with_config(security, DatabaseConfig::from_env()?).await
+    }
 
-        // Determine database path
-        let mut db_path = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("free-deep-research");
+    /// Create a data persistence service against an explicit database
+    pub async fn with_config(security: Arc<RwLock<SecurityService>>, config: DatabaseConfig) -> AppResult<Self> {
+        info!("Initializing data persistence service ({:?} backend)...", config.kind());
+
+        // Open the database and apply pending schema migrations
+        let backend = backend::connect(&config).await?;
+
+        // File snapshots only apply to the local SQLite database; a shared
+        // Postgres server is backed up by its own tooling
+        let backups = match &config {
+            DatabaseConfig::Sqlite { path, encryption } => Some(DatabaseBackups::new(
+                security.clone(),
+                path.clone(),
+                encryption.clone(),
+                BackupPolicy::from_env(),
+            )),
+            DatabaseConfig::Postgres { .. } => None,
+        };
 
-        ensure_dir_exists(&db_path)?;
-        db_path.push("app_data.db");
+        let mut regions = HashMap::new();
+        for (region, regional_config) in data_residency::regional_configs_from_env()? {
+            info!("Connecting {} data region ({:?} backend)", region, regional_config.kind());
+            let regional_backups = match &regional_config {
+                DatabaseConfig::Sqlite { path, encryption } => Some(
+                    DatabaseBackups::new(security.clone(), path.clone(), encryption.clone(), BackupPolicy::from_env())
+                        .for_region(region, S3Target::from_env_with_suffix(&region.env_suffix())),
+                ),
+                DatabaseConfig::Postgres { .. } => None,
+            };
+            regions.insert(region, RegionalStore {
+                backend: backend::connect(&regional_config).await?,
+                backups: regional_backups,
+            });
+        }
 
-        let mut service = Self {
-            security,
-            db_path,
-            connection: None,
+        let redaction = match backend.get_service_setting(REDACTION_SETTING).await {
+            Ok(Some(saved)) => serde_json::from_value(saved).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable redaction config, using the default: {}", e);
+                RedactionConfig::default()
+            }),
+            Ok(None) => RedactionConfig::default(),
+            Err(e) => {
+                warn!("Failed to load redaction config, using the default: {}", e);
+                RedactionConfig::default()
+            }
         };
 
-        // Initialize database
-        service.initialize_database().await?;
-
         info!("Data persistence service initialized successfully");
-        Ok(service)
+        Ok(Self {
+            security,
+            config,
+            backend,
+            backups,
+            regions,
+            redaction,
+        })
     }
 
-    /// Initialize the database schema
-    async fn initialize_database(&mut self) -> AppResult<()> {
-        debug!("Initializing application database");
+    /// Backend the service is running against
+    pub fn backend_kind(&self) -> DatabaseBackendKind {
+        self.backend.kind()
+    }
 
-        let conn = Connection::open(&self.db_path)
-            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Path of the local database file, when running on SQLite
+    fn sqlite_path(&self) -> Option<PathBuf> {
+        match &self.config {
+            DatabaseConfig::Sqlite { path, .. } => Some(path.clone()),
+            DatabaseConfig::Postgres { .. } => None,
+        }
+    }
 
-        // Create API keys table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS api_keys (
-                id TEXT PRIMARY KEY,
-                service TEXT NOT NULL,
-                name TEXT NOT NULL,
-                encrypted_key BLOB NOT NULL,
-                usage_count INTEGER DEFAULT 0,
-                rate_limit INTEGER NOT NULL,
-                reset_period TEXT NOT NULL,
-                last_used DATETIME,
-                last_reset DATETIME NOT NULL,
-                status TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Key source of the local database, when it is encrypted at rest
+    fn sqlite_encryption(&self) -> Option<DatabaseKeySource> {
+        match &self.config {
+            DatabaseConfig::Sqlite { encryption, .. } => encryption.clone(),
+            DatabaseConfig::Postgres { .. } => None,
+        }
+    }
 
-        // Create system configuration table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS system_config (
-                id TEXT PRIMARY KEY,
-                backup_interval INTEGER NOT NULL,
-                encryption_enabled BOOLEAN NOT NULL,
-                rate_limit_buffer INTEGER NOT NULL,
-                monitoring_enabled BOOLEAN NOT NULL,
-                log_level TEXT NOT NULL,
-                ui_theme TEXT NOT NULL,
-                auto_start_monitoring BOOLEAN NOT NULL,
-                max_concurrent_research INTEGER NOT NULL,
-                data_retention_days INTEGER NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Whether the database is encrypted at rest by the application
+    pub fn is_encrypted(&self) -> bool {
+        self.sqlite_encryption().is_some()
+    }
 
-        // Create research workflows table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS research_workflows (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                query TEXT NOT NULL,
-                status TEXT NOT NULL,
-                methodology TEXT NOT NULL,
-                parameters TEXT,
-                results TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                completed_at DATETIME
-            )",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Re-encrypt the database in place, replacing `old` with `new`.
+    ///
+    /// Existing backups keep the key they were taken with.
+    pub async fn rotate_database_key(&mut self, old: DatabaseKeySource, new: DatabaseKeySource) -> AppResult<()> {
+        if !self.is_encrypted() {
+            return Err(StorageError::Database {
+                message: "Database encryption is not enabled".to_string()
+            }.into());
+        }
 
-        // Create API usage statistics table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS api_usage_stats (
-                id TEXT PRIMARY KEY,
-                api_key_id TEXT NOT NULL,
-                service TEXT NOT NULL,
-                endpoint TEXT,
-                request_count INTEGER DEFAULT 1,
-                success_count INTEGER DEFAULT 0,
-                error_count INTEGER DEFAULT 0,
-                total_response_time_ms INTEGER DEFAULT 0,
-                avg_response_time_ms REAL DEFAULT 0,
-                last_used DATETIME DEFAULT CURRENT_TIMESTAMP,
-                date_bucket TEXT NOT NULL,
-                FOREIGN KEY (api_key_id) REFERENCES api_keys (id) ON DELETE CASCADE
-            )",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        self.backend.rotate_encryption_key(&old, &new).await?;
 
-        // Create audit events table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS audit_events (
-                id TEXT PRIMARY KEY,
-                event_type TEXT NOT NULL,
-                description TEXT NOT NULL,
-                resource_id TEXT,
-                user_id TEXT,
-                timestamp TEXT NOT NULL,
-                severity TEXT NOT NULL,
-                metadata TEXT DEFAULT '{}'
-            )",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        if let Some(backups) = &self.backups {
+            backups.set_encryption(Some(new.clone()));
+        }
+        if let DatabaseConfig::Sqlite { encryption, .. } = &mut self.config {
+            *encryption = Some(new);
+        }
+        Ok(())
+    }
 
-        // Create indexes for better performance
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_api_keys_service ON api_keys(service)",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Switch to a new master key and re-encrypt every stored API key under it.
+    ///
+    /// Rotation keeps the previous key for decryption, so if re-encryption is
+    /// interrupted the old ciphertexts stay readable and
+    /// [`Self::resume_master_key_rotation`] finishes the job.
+    pub async fn rotate_master_key(&self, new_key: &str) -> AppResult<MasterKeyRotationReport> {
+        info!("Rotating master encryption key");
+        self.security.read().await.rotate_master_key(new_key).await?;
+        self.resume_master_key_rotation().await
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_api_keys_status ON api_keys(status)",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Re-encrypt any API keys still under an older master key
+    pub async fn resume_master_key_rotation(&self) -> AppResult<MasterKeyRotationReport> {
+        let key_version = self.security.read().await.current_key_version().await;
+
+        let report = key_rotation::reencrypt_api_keys(self.backend.as_ref(), &key_version, |ciphertext| {
+            let security = self.security.clone();
+            async move { security.read().await.reencrypt_string(&ciphertext).await }
+        }).await?;
+
+        if report.reencrypted > 0 {
+            let event = AuditEvent::warning(
+                "master_key_rotated".to_string(),
+                format!("Re-encrypted {} API keys under master key version {}", report.reencrypted, report.key_version),
+            )
+            .with_resource_id(report.key_version.clone())
+            .with_metadata("key_version".to_string(), report.key_version.clone())
+            .with_metadata("reencrypted".to_string(), report.reencrypted.to_string())
+            .with_metadata("already_current".to_string(), report.already_current.to_string());
+            self.backend.store_audit_event(&event).await?;
+        }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_workflows_status ON research_workflows(status)",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        Ok(report)
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_usage_stats_api_key ON api_usage_stats(api_key_id)",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Data regions with their own storage, besides `Global`
+    pub fn configured_regions(&self) -> Vec<DataRegion> {
+        let mut regions: Vec<DataRegion> = self.regions.keys().copied().collect();
+        regions.sort();
+        regions
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_usage_stats_date ON api_usage_stats(date_bucket)",
-            [],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Storage holding `region`'s data; a region without its own database is
+    /// refused instead of falling back to the primary one
+    fn backend_for(&self, region: DataRegion) -> AppResult<&dyn StorageBackend> {
+        if region == DataRegion::Global {
+            return Ok(self.backend.as_ref());
+        }
 
-        self.connection = Some(conn);
-        debug!("Application database initialized");
-        Ok(())
+        self.regions.get(&region)
+            .map(|store| store.backend.as_ref())
+            .ok_or_else(|| StorageError::ResidencyViolation {
+                message: format!("No storage is configured for the {} data region", region)
+            }.into())
     }
 
-    /// Store an API key
-    pub async fn store_api_key(&mut self, api_key: &ApiKey) -> AppResult<()> {
-        debug!("Storing API key: {}", api_key.id);
+    /// Fail unless `region` has storage to hold its data
+    pub fn ensure_region_available(&self, region: DataRegion) -> AppResult<()> {
+        self.backend_for(region).map(|_| ())
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Every database, the primary one first
+    fn all_backends(&self) -> impl Iterator<Item = &dyn StorageBackend> {
+        std::iter::once(self.backend.as_ref()).chain(self.regions.values().map(|store| store.backend.as_ref()))
+    }
 
-        conn.execute(
-            "INSERT OR REPLACE INTO api_keys (
-                id, service, name, encrypted_key, usage_count, rate_limit,
-                reset_period, last_used, last_reset, status, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP)",
-            params![
-                api_key.id.to_string(),
-                format!("{:?}", api_key.service),
-                api_key.name,
-                api_key.encrypted_key.as_bytes(),
-                api_key.usage_count,
-                api_key.rate_limit,
-                format!("{:?}", api_key.reset_period),
-                api_key.last_used.map(|dt| dt.to_rfc3339()),
-                api_key.last_reset.to_rfc3339(),
-                format!("{:?}", api_key.status),
-            ],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Backups of `region`'s database
+    fn backups_for(&self, region: DataRegion) -> AppResult<&DatabaseBackups> {
+        if region == DataRegion::Global {
+            return self.backups();
+        }
 
-        debug!("API key stored successfully");
-        Ok(())
+        self.regions.get(&region)
+            .ok_or_else(|| StorageError::ResidencyViolation {
+                message: format!("No storage is configured for the {} data region", region)
+            })?
+            .backups.as_ref()
+            .ok_or_else(|| StorageError::BackupFailed {
+                message: format!("Backups are not managed by the application for the {} data region", region)
+            }.into())
     }
 
-    /// Get all API keys
-    pub async fn get_all_api_keys(&self) -> AppResult<Vec<ApiKey>> {
-        debug!("Retrieving all API keys");
+    /// Take a backup of one region's database
+    pub async fn create_backup_in(&self, region: DataRegion) -> AppResult<BackupManifest> {
+        self.backups_for(region)?.create_backup().await
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Available backups of one region's database, newest first
+    pub fn list_backups_in(&self, region: DataRegion) -> AppResult<Vec<BackupManifest>> {
+        self.backups_for(region)?.list_backups()
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
-                    reset_period, last_used, last_reset, status, created_at, updated_at
-             FROM api_keys ORDER BY created_at DESC"
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    fn backups(&self) -> AppResult<&DatabaseBackups> {
+        self.backups.as_ref().ok_or_else(|| StorageError::BackupFailed {
+            message: format!("Backups are not managed by the application for the {:?} backend", self.backend_kind())
+        }.into())
+    }
 
-        let key_iter = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,  // id
-                row.get::<_, String>(1)?,  // service
-                row.get::<_, String>(2)?,  // name
-                row.get::<_, Vec<u8>>(3)?, // encrypted_key
-                row.get::<_, u32>(4)?,     // usage_count
-                row.get::<_, u32>(5)?,     // rate_limit
-                row.get::<_, String>(6)?,  // reset_period
-                row.get::<_, Option<String>>(7)?, // last_used
-                row.get::<_, String>(8)?,  // last_reset
-                row.get::<_, String>(9)?,  // status
-                row.get::<_, String>(10)?, // created_at
-                row.get::<_, String>(11)?, // updated_at
-            ))
-        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-        let mut api_keys = Vec::new();
-
-        for key_result in key_iter {
-            let (id_str, service_str, name, encrypted_key_bytes, usage_count, rate_limit,
-                 reset_period_str, last_used_str, last_reset_str, status_str, created_at_str, updated_at_str) =
-                key_result.map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-            // Parse the data (simplified parsing for now)
-            let id = id_str.parse()
-                .map_err(|_| StorageError::Database { message: "Invalid UUID in API key".to_string() })?;
-
-            let encrypted_key = String::from_utf8(encrypted_key_bytes)
-                .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?;
-
-            let last_used = if let Some(last_used_str) = last_used_str {
-                Some(chrono::DateTime::parse_from_rfc3339(&last_used_str)
-                    .map_err(|_| StorageError::Database { message: "Invalid last_used timestamp".to_string() })?
-                    .with_timezone(&Utc))
-            } else {
-                None
-            };
+    /// Take an encrypted, verified snapshot of the database and key vault
+    pub async fn create_backup(&self) -> AppResult<BackupManifest> {
+        self.backups()?.create_backup().await
+    }
 
-            let last_reset = chrono::DateTime::parse_from_rfc3339(&last_reset_str)
-                .map_err(|_| StorageError::Database { message: "Invalid last_reset timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
-                .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
-                .map_err(|_| StorageError::Database { message: "Invalid updated_at timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            // Parse enums properly
-            use crate::models::api_key::{ServiceProvider, ResetPeriod, ApiKeyStatus};
-
-            let service = ServiceProvider::from_str(&service_str)
-                .unwrap_or(ServiceProvider::OpenRouter);
-
-            let reset_period = ResetPeriod::from_str(&reset_period_str)
-                .unwrap_or(ResetPeriod::Daily);
-
-            let status = ApiKeyStatus::from_str(&status_str)
-                .unwrap_or(ApiKeyStatus::Active);
-
-            api_keys.push(ApiKey {
-                id,
-                service,
-                name,
-                encrypted_key,
-                usage_count,
-                rate_limit,
-                reset_period,
-                last_used,
-                last_reset,
-                status,
-                created_at,
-                updated_at,
-            });
-        }
+    /// Available backups, newest first
+    pub fn list_backups(&self) -> AppResult<Vec<BackupManifest>> {
+        self.backups()?.list_backups()
+    }
 
-        debug!("Retrieved {} API keys", api_keys.len());
-        Ok(api_keys)
+    /// Check a backup's checksums and test-open its snapshots
+    pub async fn verify_backup(&self, backup_id: &str) -> AppResult<BackupManifest> {
+        self.backups()?.verify_backup(backup_id).await
     }
 
-    /// Delete an API key
-    pub async fn delete_api_key(&mut self, key_id: Uuid) -> AppResult<()> {
-        debug!("Deleting API key: {}", key_id);
+    /// Verify a backup and restore it over the live database and key vault
+    pub async fn restore_backup(&self, backup_id: &str) -> AppResult<BackupManifest> {
+        self.backups()?.restore_backup(backup_id).await
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Result of the most recent backup runs, `None` when backups are not managed here
+    pub fn backup_status(&self) -> Option<BackupStatus> {
+        self.backups.as_ref().map(|b| b.status())
+    }
 
-        let rows_affected = conn.execute(
-            "DELETE FROM api_keys WHERE id = ?1",
-            params![key_id.to_string()],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Validate the schema migration state without applying anything
+    pub async fn validate_migrations(&self) -> AppResult<MigrationReport> {
+        self.backend.migrate(MigrationMode::DryRun).await
+    }
 
-        if rows_affected == 0 {
-            return Err(StorageError::Database {
-                message: format!("API key with ID {} not found", key_id)
-            }.into());
-        }
+    /// Store an API key
+    pub async fn store_api_key(&mut self, api_key: &ApiKey) -> AppResult<()> {
+        self.backend.store_api_key(api_key).await
+    }
 
-        debug!("API key deleted successfully");
-        Ok(())
+    /// Get all API keys
+    pub async fn get_all_api_keys(&self) -> AppResult<Vec<ApiKey>> {
+        self.backend.get_all_api_keys().await
+    }
+
+    /// A page of API keys, newest first, owned by `tenant_id` or, with
+    /// `include_system_pool`, by no tenant
+    pub async fn get_api_keys_page(&self, tenant_id: Option<Uuid>, include_system_pool: bool, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<ApiKey>> {
+        self.backend.get_api_keys_page(tenant_id, include_system_pool, after, limit).await
+    }
+
+    /// Delete an API key
+    pub async fn delete_api_key(&mut self, key_id: Uuid) -> AppResult<()> {
+        self.backend.delete_api_key(key_id).await
     }
 
     /// Get API key by ID
     pub async fn get_api_key_by_id(&self, key_id: Uuid) -> AppResult<Option<ApiKey>> {
-        debug!("Retrieving API key by ID: {}", key_id);
+        self.backend.get_api_key_by_id(key_id).await
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Set the status of several API keys atomically
+    pub async fn update_api_key_statuses(&mut self, key_ids: &[Uuid], status: &ApiKeyStatus) -> AppResult<()> {
+        self.backend.update_api_key_statuses(key_ids, status).await
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
-                    reset_period, last_used, last_reset, status, created_at, updated_at
-             FROM api_keys WHERE id = ?1"
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Delete several API keys atomically
+    pub async fn delete_api_keys(&mut self, key_ids: &[Uuid]) -> AppResult<()> {
+        self.backend.delete_api_keys(key_ids).await
+    }
 
-        let mut key_iter = stmt.query_map([key_id.to_string()], |row| {
-            Ok((
-                row.get::<_, String>(0)?,  // id
-                row.get::<_, String>(1)?,  // service
-                row.get::<_, String>(2)?,  // name
-                row.get::<_, Vec<u8>>(3)?, // encrypted_key
-                row.get::<_, u32>(4)?,     // usage_count
-                row.get::<_, u32>(5)?,     // rate_limit
-                row.get::<_, String>(6)?,  // reset_period
-                row.get::<_, Option<String>>(7)?, // last_used
-                row.get::<_, String>(8)?,  // last_reset
-                row.get::<_, String>(9)?,  // status
-                row.get::<_, String>(10)?, // created_at
-                row.get::<_, String>(11)?, // updated_at
-            ))
-        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-        if let Some(key_result) = key_iter.next() {
-            let (id_str, service_str, name, encrypted_key_bytes, usage_count, rate_limit,
-                 reset_period_str, last_used_str, last_reset_str, status_str, created_at_str, updated_at_str) =
-                key_result.map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-            // Parse the data
-            let id = id_str.parse()
-                .map_err(|_| StorageError::Database { message: "Invalid UUID in API key".to_string() })?;
-
-            let encrypted_key = String::from_utf8(encrypted_key_bytes)
-                .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?;
-
-            let last_used = if let Some(last_used_str) = last_used_str {
-                Some(chrono::DateTime::parse_from_rfc3339(&last_used_str)
-                    .map_err(|_| StorageError::Database { message: "Invalid last_used timestamp".to_string() })?
-                    .with_timezone(&Utc))
-            } else {
-                None
-            };
+    /// Store a research workflow in its data region's database and refresh its search index entry
+    pub async fn save_research_workflow(&self, workflow: &ResearchWorkflow) -> AppResult<()> {
+        let region = data_residency::workflow_region(workflow)?;
+        let workflow = redaction::redact_workflow(&self.redaction, workflow);
+        self.backend_for(region)?.save_research_workflow(&workflow).await
+    }
 
-            let last_reset = chrono::DateTime::parse_from_rfc3339(&last_reset_str)
-                .map_err(|_| StorageError::Database { message: "Invalid last_reset timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
-                .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
-                .map_err(|_| StorageError::Database { message: "Invalid updated_at timestamp".to_string() })?
-                .with_timezone(&Utc);
-
-            // Parse enums properly
-            use crate::models::api_key::{ServiceProvider, ResetPeriod, ApiKeyStatus};
-
-            let service = ServiceProvider::from_str(&service_str)
-                .unwrap_or(ServiceProvider::OpenRouter);
-
-            let reset_period = ResetPeriod::from_str(&reset_period_str)
-                .unwrap_or(ResetPeriod::Daily);
-
-            let status = ApiKeyStatus::from_str(&status_str)
-                .unwrap_or(ApiKeyStatus::Active);
-
-            Ok(Some(ApiKey {
-                id,
-                service,
-                name,
-                encrypted_key,
-                usage_count,
-                rate_limit,
-                reset_period,
-                last_used,
-                last_reset,
-                status,
-                created_at,
-                updated_at,
-            }))
-        } else {
-            Ok(None)
+    /// Store a research workflow on behalf of a tenant pinned to `region`
+    pub async fn save_research_workflow_in(&self, region: DataRegion, workflow: &ResearchWorkflow) -> AppResult<()> {
+        data_residency::ensure_residency(region, data_residency::workflow_region(workflow)?)?;
+        let workflow = redaction::redact_workflow(&self.redaction, workflow);
+        self.backend_for(region)?.save_research_workflow(&workflow).await
+    }
+
+    /// Categories of personal data scrubbed from results before they are stored
+    pub fn get_redaction_config(&self) -> RedactionConfig {
+        self.redaction.clone()
+    }
+
+    /// Change what is scrubbed from results stored from now on; stored results are left as they are
+    pub async fn set_redaction_config(&mut self, config: RedactionConfig) -> AppResult<()> {
+        info!("Result redaction {} for {} categories", if config.enabled { "enabled" } else { "disabled" }, config.categories.len());
+        self.save_setting(REDACTION_SETTING, &config).await?;
+        self.redaction = config;
+        Ok(())
+    }
+
+    /// Report what `config`, or the current configuration, would redact from
+    /// results without changing them
+    pub fn preview_redaction(&self, results: &ResearchResults, config: Option<&RedactionConfig>) -> RedactionReport {
+        redaction::dry_run(config.unwrap_or(&self.redaction), results)
+    }
+
+    /// Delete a research workflow and its search index entry from whichever
+    /// region's database holds it
+    pub async fn delete_research_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
+        for backend in self.all_backends() {
+            backend.delete_research_workflow(workflow_id).await?;
         }
+        Ok(())
+    }
+
+    /// Delete a research workflow from `region`'s database
+    pub async fn delete_research_workflow_in(&self, region: DataRegion, workflow_id: Uuid) -> AppResult<()> {
+        self.backend_for(region)?.delete_research_workflow(workflow_id).await
+    }
+
+    /// A page of the IDs of workflows stored in the global database, newest first
+    pub async fn get_research_workflow_ids_page(&self, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<Uuid>> {
+        self.backend.get_research_workflow_ids_page(after, limit).await
+    }
+
+    /// Full-text search across stored workflows' names, queries, summaries and
+    /// sources, in the database of the region the filters name
+    pub async fn search_workflows(&self, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        self.backend_for(filters.data_region)?.search_workflows(query, filters).await
+    }
+
+    /// Full-text search on behalf of a tenant pinned to `region`
+    pub async fn search_workflows_in(&self, region: DataRegion, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        data_residency::ensure_residency(region, filters.data_region)?;
+        self.backend_for(region)?.search_workflows(query, filters).await
+    }
+
+    /// Stored workflows in a folder or with given tags, newest first, from the
+    /// database of the region the filters name
+    pub async fn browse_workflows(&self, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        self.backend_for(filters.data_region)?.browse_workflows(filters).await
+    }
+
+    /// Stored workflows in a folder or with given tags on behalf of a tenant pinned to `region`
+    pub async fn browse_workflows_in(&self, region: DataRegion, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        data_residency::ensure_residency(region, filters.data_region)?;
+        self.backend_for(region)?.browse_workflows(filters).await
+    }
+
+    /// Folders and tags in use in `region`'s database, with how many workflows each holds
+    pub async fn get_workflow_organization(&self, region: DataRegion) -> AppResult<WorkflowOrganization> {
+        self.backend_for(region)?.get_workflow_organization().await
     }
 
     /// Record API usage statistics
@@ -426,152 +436,274 @@ impl DataPersistenceService {
         success: bool,
         response_time_ms: u32
     ) -> AppResult<()> {
-        debug!("Recording API usage for key: {}", api_key_id);
-
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
-
-        let date_bucket = Utc::now().format("%Y-%m-%d").to_string();
-        let endpoint_str = endpoint.unwrap_or("unknown");
-
-        // Try to update existing record first
-        let rows_affected = conn.execute(
-            "UPDATE api_usage_stats SET
-                request_count = request_count + 1,
-                success_count = success_count + CASE WHEN ?5 THEN 1 ELSE 0 END,
-                error_count = error_count + CASE WHEN ?5 THEN 0 ELSE 1 END,
-                total_response_time_ms = total_response_time_ms + ?6,
-                avg_response_time_ms = CAST(total_response_time_ms + ?6 AS REAL) / (request_count + 1),
-                last_used = CURRENT_TIMESTAMP
-             WHERE api_key_id = ?1 AND service = ?2 AND endpoint = ?3 AND date_bucket = ?4",
-            params![
-                api_key_id.to_string(),
-                service,
-                endpoint_str,
-                date_bucket,
-                success,
-                response_time_ms
-            ],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        self.backend.record_api_usage(api_key_id, service, endpoint, success, response_time_ms).await
+    }
 
-        // If no existing record, create new one
-        if rows_affected == 0 {
-            conn.execute(
-                "INSERT INTO api_usage_stats (
-                    id, api_key_id, service, endpoint, request_count, success_count,
-                    error_count, total_response_time_ms, avg_response_time_ms, date_bucket
-                ) VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7, ?7, ?8)",
-                params![
-                    Uuid::new_v4().to_string(),
-                    api_key_id.to_string(),
-                    service,
-                    endpoint_str,
-                    if success { 1 } else { 0 },
-                    if success { 0 } else { 1 },
-                    response_time_ms,
-                    date_bucket
-                ],
-            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
-        }
+    /// Request counts of every key from `from` through `to`, inclusive (`YYYY-MM-DD`)
+    pub async fn get_key_usage_totals(&self, from: &str, to: &str) -> AppResult<Vec<KeyUsageTotals>> {
+        self.backend.get_key_usage_totals(from, to).await
+    }
 
-        debug!("API usage recorded successfully");
-        Ok(())
+    /// Append a provider's usage report to its key's reports
+    pub async fn record_provider_usage_snapshot(&self, snapshot: &ProviderUsageSnapshot) -> AppResult<()> {
+        self.backend.record_provider_usage_snapshot(snapshot).await
+    }
+
+    /// Provider usage reports up to and including `to`, ordered by key and then as observed
+    pub async fn get_provider_usage_snapshots(&self, to: &str) -> AppResult<Vec<ProviderUsageSnapshot>> {
+        self.backend.get_provider_usage_snapshots(to).await
+    }
+
+    /// Keep the results of a usage divergence check
+    pub async fn record_usage_divergences(&self, divergences: &[UsageDivergence]) -> AppResult<()> {
+        self.backend.record_usage_divergences(divergences).await
+    }
+
+    /// Usage divergence checks made since `since`, oldest first
+    pub async fn get_usage_divergence_history(&self, since: DateTime<Utc>) -> AppResult<Vec<UsageDivergence>> {
+        self.backend.get_usage_divergence_history(since).await
     }
 
     /// Store audit event
     pub async fn store_audit_event(&self, event: &AuditEvent) -> AppResult<()> {
-        debug!("Storing audit event: {} - {}", event.event_type, event.description);
+        self.backend.store_audit_event(event).await
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Store a research schedule
+    pub async fn save_research_schedule(&self, schedule: &ResearchSchedule) -> AppResult<()> {
+        self.backend.save_research_schedule(schedule).await
+    }
 
-        // Serialize metadata to JSON
-        let metadata_json = serde_json::to_string(&event.metadata)
-            .map_err(|e| StorageError::Database { message: format!("Failed to serialize metadata: {}", e) })?;
+    /// Get all research schedules
+    pub async fn get_research_schedules(&self) -> AppResult<Vec<ResearchSchedule>> {
+        self.backend.get_research_schedules().await
+    }
 
-        // Insert audit event into database
-        conn.execute(
-            "INSERT INTO audit_events (
-                id, event_type, description, resource_id, user_id,
-                timestamp, severity, metadata
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                event.id.to_string(),
-                event.event_type,
-                event.description,
-                event.resource_id,
-                event.user_id,
-                event.timestamp.to_rfc3339(),
-                event.severity.as_str(),
-                metadata_json
-            ],
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Delete a research schedule and its run history
+    pub async fn delete_research_schedule(&self, schedule_id: Uuid) -> AppResult<()> {
+        self.backend.delete_research_schedule(schedule_id).await
+    }
 
-        debug!("Audit event stored successfully: {}", event.id);
-        Ok(())
+    /// Store a scheduled run
+    pub async fn save_schedule_run(&self, run: &ScheduleRun) -> AppResult<()> {
+        self.backend.save_schedule_run(run).await
     }
 
-    /// Get usage statistics for an API key
-    pub async fn get_api_key_usage_stats(&self, api_key_id: Uuid, days: u32) -> AppResult<Vec<(String, u32, u32, u32, f64)>> {
-        debug!("Getting usage statistics for API key: {}", api_key_id);
+    /// Most recent runs of a schedule, newest first
+    pub async fn get_schedule_runs(&self, schedule_id: Uuid, limit: u32) -> AppResult<Vec<ScheduleRun>> {
+        self.backend.get_schedule_runs(schedule_id, limit).await
+    }
 
-        let conn = self.connection.as_ref()
-            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+    /// Scheduled runs whose workflows are still in flight
+    pub async fn get_running_schedule_runs(&self) -> AppResult<Vec<ScheduleRun>> {
+        self.backend.get_running_schedule_runs().await
+    }
 
-        let start_date = (Utc::now() - chrono::Duration::days(days as i64))
-            .format("%Y-%m-%d").to_string();
+    /// Store a prompt template version
+    pub async fn save_prompt_template(&self, template: &PromptTemplate) -> AppResult<()> {
+        self.backend.save_prompt_template(template).await
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT date_bucket, request_count, success_count, error_count, avg_response_time_ms
-             FROM api_usage_stats
-             WHERE api_key_id = ?1 AND date_bucket >= ?2
-             ORDER BY date_bucket DESC"
-        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    /// Get every version of every prompt template
+    pub async fn get_prompt_templates(&self) -> AppResult<Vec<PromptTemplate>> {
+        self.backend.get_prompt_templates().await
+    }
 
-        let stats_iter = stmt.query_map([api_key_id.to_string(), start_date], |row| {
-            Ok((
-                row.get::<_, String>(0)?,  // date_bucket
-                row.get::<_, u32>(1)?,     // request_count
-                row.get::<_, u32>(2)?,     // success_count
-                row.get::<_, u32>(3)?,     // error_count
-                row.get::<_, f64>(4)?,     // avg_response_time_ms
-            ))
-        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-        let mut stats = Vec::new();
-        for stat_result in stats_iter {
-            stats.push(stat_result.map_err(|e| StorageError::Database { message: e.to_string() })?);
-        }
+    /// Store a prompt experiment with its metrics
+    pub async fn save_prompt_experiment(&self, experiment: &PromptExperiment) -> AppResult<()> {
+        self.backend.save_prompt_experiment(experiment).await
+    }
+
+    /// Get all prompt experiments, running and stopped
+    pub async fn get_prompt_experiments(&self) -> AppResult<Vec<PromptExperiment>> {
+        self.backend.get_prompt_experiments().await
+    }
+
+    /// Bind an idempotency key to the workflow its request created
+    pub async fn save_idempotency_record(&self, record: &IdempotencyRecord) -> AppResult<()> {
+        self.backend.save_idempotency_record(record).await
+    }
+
+    /// Get the workflow an idempotency key is bound to, expired or not
+    pub async fn get_idempotency_record(&self, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+        self.backend.get_idempotency_record(key).await
+    }
+
+    /// Drop idempotency keys past their window
+    pub async fn purge_expired_idempotency_records(&self, now: DateTime<Utc>) -> AppResult<u64> {
+        self.backend.purge_expired_idempotency_records(now).await
+    }
+
+    /// Record a saga's progress
+    pub async fn save_saga_state(&self, saga: &SagaState) -> AppResult<()> {
+        self.backend.save_saga_state(saga).await
+    }
+
+    /// Sagas a restart interrupted before they finished
+    pub async fn get_unfinished_sagas(&self) -> AppResult<Vec<SagaState>> {
+        self.backend.get_unfinished_sagas().await
+    }
+
+    /// Save a user's notification preferences
+    pub async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> AppResult<()> {
+        self.backend.save_notification_preferences(preferences).await
+    }
+
+    /// Get a user's notification preferences, if they have set any
+    pub async fn get_notification_preferences(&self, user_id: Uuid) -> AppResult<Option<NotificationPreferences>> {
+        self.backend.get_notification_preferences(user_id).await
+    }
+
+    /// Get the notification preferences of every user
+    pub async fn get_all_notification_preferences(&self) -> AppResult<Vec<NotificationPreferences>> {
+        self.backend.get_all_notification_preferences().await
+    }
+
+    /// Hold a notification back from a user for later delivery
+    pub async fn hold_notification(&self, user_id: Uuid, notification: &Notification) -> AppResult<()> {
+        self.backend.hold_notification(user_id, notification).await
+    }
+
+    /// Take the notifications held for a user
+    pub async fn take_held_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        self.backend.take_held_notifications(user_id).await
+    }
+
+    /// Add notifications to a user's in-app inbox, keeping its newest `keep`
+    pub async fn add_inbox_notifications(&self, user_id: Uuid, notifications: &[Notification], keep: usize) -> AppResult<()> {
+        self.backend.add_inbox_notifications(user_id, notifications, keep).await
+    }
 
-        debug!("Retrieved {} usage statistics records", stats.len());
-        Ok(stats)
+    /// Get a user's in-app notifications, newest first
+    pub async fn get_inbox_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        self.backend.get_inbox_notifications(user_id).await
+    }
+
+    /// Save a user's installation of marketplace content
+    pub async fn save_marketplace_installation(&self, installation: &InstalledContent) -> AppResult<()> {
+        self.backend.save_marketplace_installation(installation).await
+    }
+
+    /// Get the marketplace content a user has installed
+    pub async fn get_marketplace_installations(&self, user_id: Uuid) -> AppResult<Vec<InstalledContent>> {
+        self.backend.get_marketplace_installations(user_id).await
+    }
+
+    /// Remove a user's installation of marketplace content
+    pub async fn delete_marketplace_installation(&self, user_id: Uuid, kind: ContentKind, content_id: Uuid) -> AppResult<bool> {
+        self.backend.delete_marketplace_installation(user_id, kind, content_id).await
+    }
+
+    /// Save a user's rating of a workflow, replacing any earlier rating of it
+    pub async fn save_workflow_rating(&self, rating: &WorkflowRating) -> AppResult<()> {
+        self.backend.save_workflow_rating(rating).await
+    }
+
+    /// Get a user's ratings of workflows in a query domain
+    pub async fn get_workflow_ratings(&self, rated_by: &str, query_domain: QueryDomain) -> AppResult<Vec<WorkflowRating>> {
+        self.backend.get_workflow_ratings(rated_by, query_domain).await
+    }
+
+    /// Append entries spilled out of an in-memory history
+    pub async fn append_history(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()> {
+        self.backend.append_history(kind, records).await
+    }
+
+    /// Get a history's spilled entries, newest first
+    pub async fn get_history(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>> {
+        self.backend.get_history(kind, since, limit).await
+    }
+
+    /// Delete a history's spilled entries recorded before `before`
+    pub async fn purge_history(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64> {
+        self.backend.purge_history(kind, before).await
+    }
+
+    /// Record what a finished step attempt used
+    pub async fn save_step_metrics(&self, metrics: &StepExecutionMetrics) -> AppResult<()> {
+        self.backend.save_step_metrics(metrics).await
+    }
+
+    /// Get a workflow's recorded step actuals and their totals
+    pub async fn get_workflow_execution_metrics(&self, workflow_id: Uuid) -> AppResult<WorkflowExecutionMetrics> {
+        let steps = self.backend.get_step_metrics(workflow_id).await?;
+        Ok(WorkflowExecutionMetrics::from_steps(workflow_id, steps))
+    }
+
+    /// Save a runtime setting so it survives a restart
+    pub async fn save_setting<T: serde::Serialize>(&self, name: &str, value: &T) -> AppResult<()> {
+        let definition = serde_json::to_value(value)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize setting {}: {}", name, e) })?;
+        self.backend.save_service_setting(name, &definition).await
+    }
+
+    /// Get a saved runtime setting, `None` when it was never changed from its default
+    pub async fn get_setting<T: serde::de::DeserializeOwned>(&self, name: &str) -> AppResult<Option<T>> {
+        self.backend.get_service_setting(name).await?
+            .map(|definition| serde_json::from_value(definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize setting {}: {}", name, e) }.into()))
+            .transpose()
+    }
+
+    /// Get usage statistics for an API key
+    pub async fn get_api_key_usage_stats(&self, api_key_id: Uuid, days: u32) -> AppResult<Vec<(String, u32, u32, u32, f64)>> {
+        self.backend.get_api_key_usage_stats(api_key_id, days).await
+    }
+
+    /// Get daily request counts for every key of a service
+    pub async fn get_service_usage_history(&self, service: &str, days: u32) -> AppResult<Vec<(Uuid, String, u32)>> {
+        self.backend.get_service_usage_history(service, days).await
     }
 
     /// Start background tasks
     pub async fn start_background_tasks(&self) -> AppResult<()> {
         info!("Starting data persistence background tasks...");
 
-        // Start database cleanup task
-        let db_path = self.db_path.clone();
+        // Each region's database is backed up into its own region
+        for (region, store) in &self.regions {
+            if let Some(backups) = store.backups.clone() {
+                let region = *region;
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(86400)); // Daily
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = backups.create_backup().await {
+                            error!("Backup of {} data region failed: {}", region, e);
+                        }
+                    }
+                });
+            }
+        }
+
+        // File-level cleanup and backups only apply to the local SQLite database;
+        // a shared Postgres server is maintained by its own tooling
+        let (db_path, backups) = match (self.sqlite_path(), self.backups.clone()) {
+            (Some(path), Some(backups)) => (path, backups),
+            _ => {
+                info!("Skipping file backup tasks for {:?} backend", self.backend_kind());
+                return Ok(());
+            }
+        };
+
+        // Start database cleanup task; the key is re-read each run to follow rotations
+        let backups_cleanup = backups.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // Every hour
             loop {
                 interval.tick().await;
-                if let Err(e) = Self::cleanup_old_data(&db_path).await {
+                let encryption = backups_cleanup.encryption();
+                if let Err(e) = Self::cleanup_old_data(&db_path, encryption.as_ref()).await {
                     error!("Database cleanup failed: {}", e);
                 }
             }
         });
 
-        // Start backup task
-        let db_path_backup = self.db_path.clone();
+        // Start backup task; failures are recorded in the backup status
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(86400)); // Daily
             loop {
                 interval.tick().await;
-                if let Err(e) = Self::create_backup(&db_path_backup).await {
-                    error!("Database backup failed: {}", e);
-                }
+                let _ = backups.create_backup().await;
             }
         });
 
@@ -580,11 +712,14 @@ impl DataPersistenceService {
     }
 
     /// Cleanup old data from database
-    async fn cleanup_old_data(db_path: &std::path::Path) -> AppResult<()> {
+    async fn cleanup_old_data(db_path: &std::path::Path, encryption: Option<&DatabaseKeySource>) -> AppResult<()> {
         debug!("Performing database cleanup");
 
-        let conn = Connection::open(db_path)
-            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let conn = match encryption {
+            Some(key_source) => database_encryption::open_encrypted(db_path, key_source)?,
+            None => Connection::open(db_path)
+                .map_err(|e| StorageError::Database { message: e.to_string() })?,
+        };
 
         // Clean up old audit logs (keep last 30 days)
         conn.execute(
@@ -598,25 +733,13 @@ impl DataPersistenceService {
             [],
         ).map_err(|e| StorageError::Database { message: e.to_string() })?;
 
-        debug!("Database cleanup completed");
-        Ok(())
-    }
-
-    /// Create database backup
-    async fn create_backup(db_path: &std::path::Path) -> AppResult<()> {
-        debug!("Creating database backup");
-
-        let backup_dir = db_path.parent().unwrap_or_else(|| std::path::Path::new(".")).join("backups");
-        std::fs::create_dir_all(&backup_dir)
-            .map_err(|e| StorageError::Io { message: e.to_string() })?;
-
-        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_path = backup_dir.join(format!("research_db_backup_{}.db", timestamp));
-
-        std::fs::copy(db_path, &backup_path)
-            .map_err(|e| StorageError::Io { message: e.to_string() })?;
+        // Spilled in-memory history entries are kept as long (timestamps are RFC 3339 text)
+        conn.execute(
+            "DELETE FROM history_entries WHERE recorded_at < strftime('%Y-%m-%dT%H:%M:%S', 'now', '-90 days')",
+            [],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
 
-        info!("Database backup created: {:?}", backup_path);
+        debug!("Database cleanup completed");
         Ok(())
     }
 }
@@ -626,25 +749,9 @@ impl Service for DataPersistenceService {
     async fn health_check(&self) -> AppResult<()> {
         debug!("Performing data persistence health check");
 
-        // Check database connectivity
-        let conn = Connection::open(&self.db_path)
-            .map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-        // Test basic query
-        let _result: i64 = conn.query_row("SELECT COUNT(*) FROM api_keys", [], |row| row.get(0))
-            .map_err(|e| StorageError::Database { message: e.to_string() })?;
-
-        // Check database file permissions
-        if !self.db_path.exists() {
-            return Err(StorageError::Database {
-                message: "Database file does not exist".to_string()
-            }.into());
-        }
-
-        // Check available disk space
-        if let Ok(metadata) = std::fs::metadata(&self.db_path) {
-            let file_size = metadata.len();
-            debug!("Database file size: {} bytes", file_size);
+        self.backend.health_check().await?;
+        for store in self.regions.values() {
+            store.backend.health_check().await?;
         }
 
         debug!("Data persistence health check passed");
@@ -655,11 +762,21 @@ impl Service for DataPersistenceService {
         info!("Shutting down data persistence service...");
 
         // Create final backup before shutdown
-        if let Err(e) = Self::create_backup(&self.db_path).await {
-            error!("Failed to create shutdown backup: {}", e);
+        if let Some(backups) = &self.backups {
+            if let Err(e) = backups.create_backup().await {
+                error!("Failed to create shutdown backup: {}", e);
+            }
+        }
+
+        for (region, store) in &self.regions {
+            if let Some(backups) = &store.backups {
+                if let Err(e) = backups.create_backup().await {
+                    error!("Failed to create shutdown backup of {} data region: {}", region, e);
+                }
+            }
         }
 
-        // Close any open connections (SQLite handles this automatically)
+        // Connections are closed when the backend is dropped
         info!("Data persistence service shutdown completed");
         Ok(())
     }
@@ -0,0 +1,1748 @@
+use std::time::Duration;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::Row;
+use tracing::{info, debug, error, warn};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppResult, StorageError};
+use crate::models::{ApiKey, audit::AuditEvent};
+use crate::models::api_key::ApiKeyStatus;
+use crate::models::research_workflow::ResearchWorkflow;
+use crate::models::research_schedule::{ResearchSchedule, ScheduleRun};
+use crate::models::prompt_template::{PromptExperiment, PromptTemplate};
+use crate::models::idempotency::IdempotencyRecord;
+use crate::models::saga::SagaState;
+use crate::models::notification::{Notification, NotificationPreferences};
+use crate::models::ai_marketplace::{ContentKind, InstalledContent};
+use crate::models::workflow_rating::{QueryDomain, WorkflowRating};
+use crate::models::usage_report::{KeyUsageTotals, ProviderUsageSnapshot, UsageDivergence};
+use crate::models::execution_metrics::StepExecutionMetrics;
+use crate::models::pagination::{Page, PagePosition};
+use super::backend::{DatabaseBackendKind, StorageBackend};
+use super::history_store::HistoryRecord;
+use super::key_rotation::{ApiKeyCiphertext, ApiKeyReencryption};
+use super::migrations::{AppliedMigration, Migration, MigrationMode, MigrationReport, MigrationRunner, SqlDialect, MIGRATIONS};
+use super::workflow_search::{self, WorkflowGroup, WorkflowOrganization, WorkflowSearchFilters, WorkflowSearchMatch};
+
+/// Advisory lock key held while migrating, so nodes sharing a database never
+/// apply migrations concurrently
+const MIGRATION_LOCK_KEY: i64 = 0x4644_525f_4d49_4752; // "FDR_MIGR"
+
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn db_error(e: sqlx::Error) -> StorageError {
+    StorageError::Database { message: e.to_string() }
+}
+
+/// Postgres storage backend for shared, multi-node deployments
+pub struct PostgresBackend {
+    pool: PgPool,
+}
+
+impl PostgresBackend {
+    /// Connect a pool to the given database
+    pub async fn connect(url: &str, max_connections: u32) -> AppResult<Self> {
+        debug!("Connecting to Postgres with up to {} connections", max_connections);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .acquire_timeout(ACQUIRE_TIMEOUT)
+            .connect(url)
+            .await
+            .map_err(db_error)?;
+
+        Ok(Self { pool })
+    }
+
+    async fn load_applied(&self, conn: &mut sqlx::PgConnection) -> AppResult<Vec<AppliedMigration>> {
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass('schema_migrations') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(db_error)?;
+
+        if !exists {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT version, name, checksum, dirty, applied_at::TEXT FROM schema_migrations ORDER BY version"
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AppliedMigration {
+                    version: row.try_get::<i64, _>(0).map_err(db_error)? as u32,
+                    name: row.try_get(1).map_err(db_error)?,
+                    checksum: row.try_get(2).map_err(db_error)?,
+                    dirty: row.try_get(3).map_err(db_error)?,
+                    applied_at: row.try_get::<Option<String>, _>(4).map_err(db_error)?.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Apply one migration, recording it as dirty first so an interrupted run
+    /// is detected on the next start
+    async fn apply(&self, conn: &mut sqlx::PgConnection, migration: &Migration, from_version: u32) -> AppResult<()> {
+        info!("Applying migration {} ({})", migration.version, migration.name);
+
+        let failed = |message: String| StorageError::MigrationFailed {
+            from_version: from_version.to_string(),
+            to_version: migration.version.to_string(),
+            message,
+        };
+
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum, dirty) VALUES ($1, $2, $3, TRUE)")
+            .bind(migration.version as i64)
+            .bind(migration.name)
+            .bind(migration.checksum(SqlDialect::Postgres))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+
+        let result: Result<(), sqlx::Error> = async {
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+            sqlx::raw_sql(migration.postgres).execute(&mut *tx).await?;
+            sqlx::query("UPDATE schema_migrations SET dirty = FALSE, applied_at = NOW() WHERE version = $1")
+                .bind(migration.version as i64)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await
+        }.await;
+
+        if let Err(e) = result {
+            error!("Migration {} ({}) failed: {}", migration.version, migration.name, e);
+            // The transaction rolled back, so the schema is unchanged
+            let _ = sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+                .bind(migration.version as i64)
+                .execute(&mut *conn)
+                .await;
+            return Err(failed(e.to_string()).into());
+        }
+
+        Ok(())
+    }
+
+    async fn migrate_locked(&self, conn: &mut sqlx::PgConnection, mode: MigrationMode) -> AppResult<MigrationReport> {
+        let runner = MigrationRunner::new(MIGRATIONS)?;
+        let applied = self.load_applied(conn).await?;
+        let mut report = runner.plan(applied, SqlDialect::Postgres, mode)?;
+
+        if mode == MigrationMode::DryRun || report.pending.is_empty() {
+            debug!("Schema at version {}, {} pending migrations", report.current_version, report.pending.len());
+            return Ok(report);
+        }
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                dirty BOOLEAN NOT NULL DEFAULT FALSE,
+                applied_at TIMESTAMPTZ DEFAULT NOW()
+            )"
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error)?;
+
+        for migration in runner.pending_migrations(&report) {
+            self.apply(conn, migration, report.current_version).await?;
+            report.current_version = migration.version;
+            report.applied_now.push(migration.version);
+        }
+
+        report.applied = self.load_applied(conn).await?;
+        report.pending.clear();
+
+        info!("Applied {} migrations, schema now at version {}", report.applied_now.len(), report.current_version);
+        Ok(report)
+    }
+}
+
+fn timestamp(row: &PgRow, column: &str) -> AppResult<DateTime<Utc>> {
+    Ok(row.try_get::<DateTime<Utc>, _>(column).map_err(db_error)?)
+}
+
+fn api_key_from_row(row: &PgRow) -> AppResult<ApiKey> {
+    use crate::models::api_key::{ServiceProvider, ResetPeriod, ApiKeyStatus};
+
+    let id_str: String = row.try_get("id").map_err(db_error)?;
+    let id = id_str.parse()
+        .map_err(|_| StorageError::Database { message: "Invalid UUID in API key".to_string() })?;
+
+    let encrypted_key = String::from_utf8(row.try_get::<Vec<u8>, _>("encrypted_key").map_err(db_error)?)
+        .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?;
+
+    let service_str: String = row.try_get("service").map_err(db_error)?;
+    let reset_period_str: String = row.try_get("reset_period").map_err(db_error)?;
+    let status_str: String = row.try_get("status").map_err(db_error)?;
+    let created_at = timestamp(row, "created_at")?;
+
+    Ok(ApiKey {
+        id,
+        service: ServiceProvider::from_str(&service_str)
+            .ok_or_else(|| StorageError::Database { message: format!("Unknown service provider '{}'", service_str) })?,
+        name: row.try_get("name").map_err(db_error)?,
+        encrypted_key,
+        usage_count: row.try_get::<i64, _>("usage_count").map_err(db_error)? as u32,
+        rate_limit: row.try_get::<i64, _>("rate_limit").map_err(db_error)? as u32,
+        reset_period: ResetPeriod::from_str(&reset_period_str).unwrap_or(ResetPeriod::Daily),
+        last_used: row.try_get("last_used").map_err(db_error)?,
+        last_reset: timestamp(row, "last_reset")?,
+        status: ApiKeyStatus::from_str(&status_str).unwrap_or(ApiKeyStatus::Active),
+        created_at,
+        updated_at: timestamp(row, "updated_at")?,
+        monthly_quota: row.try_get::<Option<i64>, _>("monthly_quota").map_err(db_error)?.map(|quota| quota as u32),
+        quota_usage: row.try_get::<i64, _>("quota_usage").map_err(db_error)? as u32,
+        // Keys stored before quotas were tracked start their period at creation
+        quota_period_start: row.try_get::<Option<DateTime<Utc>>, _>("quota_period_start").map_err(db_error)?.unwrap_or(created_at),
+        expires_at: row.try_get("expires_at").map_err(db_error)?,
+        tenant_id: row.try_get::<Option<String>, _>("tenant_id").map_err(db_error)?
+            .map(|tenant_id| tenant_id.parse())
+            .transpose()
+            .map_err(|_| StorageError::Database { message: "Invalid tenant UUID in API key".to_string() })?,
+    })
+}
+
+/// The key a row holds, `None` for a key of a custom provider whose adapter is not
+/// registered; such keys are left out rather than handed to another provider
+fn stored_api_key(row: &PgRow) -> AppResult<Option<ApiKey>> {
+    let service: String = row.try_get("service").map_err(db_error)?;
+    if crate::models::api_key::ServiceProvider::from_str(&service).is_none() {
+        warn!("Skipping an API key of unregistered provider '{}'", service);
+        return Ok(None);
+    }
+    api_key_from_row(row).map(Some)
+}
+
+/// Where a stored row sits in a list, from its `created_at` and ID
+fn stored_position(row: &PgRow) -> AppResult<PagePosition> {
+    let id: String = row.try_get("id").map_err(db_error)?;
+    Ok(PagePosition::new(timestamp(row, "created_at")?, id))
+}
+
+/// Build a prefix-matching `to_tsquery` expression from free-form input
+fn build_tsquery(query: &str) -> String {
+    let terms: Vec<String> = workflow_search::search_terms(query)
+        .into_iter()
+        .map(|term| term.chars().filter(|c| c.is_alphanumeric() || *c == '_').collect::<String>())
+        .filter(|term| !term.is_empty())
+        .collect();
+
+    let last = terms.len().saturating_sub(1);
+    terms.iter()
+        .enumerate()
+        .map(|(i, term)| if i == last { format!("{}:*", term) } else { term.clone() })
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+fn search_match(row: &PgRow) -> AppResult<WorkflowSearchMatch> {
+    let id_str: String = row.try_get("workflow_id").map_err(db_error)?;
+    let tags: String = row.try_get("tags").map_err(db_error)?;
+    Ok(WorkflowSearchMatch {
+        workflow_id: Uuid::parse_str(&id_str)
+            .map_err(|_| StorageError::Database { message: "Invalid UUID in search index".to_string() })?,
+        name: row.try_get("name").map_err(db_error)?,
+        query: row.try_get("query").map_err(db_error)?,
+        status: row.try_get("status").map_err(db_error)?,
+        methodology: row.try_get("methodology").map_err(db_error)?,
+        created_at: timestamp(row, "created_at")?,
+        tags: workflow_search::split_tags(&tags),
+        folder: row.try_get("folder").map_err(db_error)?,
+        rank: row.try_get("score").map_err(db_error)?,
+        snippet: row.try_get("snippet").map_err(db_error)?,
+        highlighted_name: row.try_get("highlighted_name").map_err(db_error)?,
+    })
+}
+
+fn workflow_group(row: &PgRow) -> AppResult<WorkflowGroup> {
+    Ok(WorkflowGroup {
+        name: row.try_get("name").map_err(db_error)?,
+        workflow_count: row.try_get::<i64, _>("workflow_count").map_err(db_error)? as u32,
+    })
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for PostgresBackend {
+    fn kind(&self) -> DatabaseBackendKind {
+        DatabaseBackendKind::Postgres
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> AppResult<MigrationReport> {
+        let mut conn = self.pool.acquire().await.map_err(db_error)?;
+
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error)?;
+
+        let result = self.migrate_locked(&mut conn, mode).await;
+
+        if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+        {
+            error!("Failed to release migration lock: {}", e);
+        }
+
+        result
+    }
+
+    async fn store_api_key(&self, api_key: &ApiKey) -> AppResult<()> {
+        debug!("Storing API key: {}", api_key.id);
+
+        sqlx::query(
+            "INSERT INTO api_keys (
+                id, service, name, encrypted_key, usage_count, rate_limit,
+                reset_period, last_used, last_reset, status, key_version,
+                monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, NOW())
+            ON CONFLICT (id) DO UPDATE SET
+                service = EXCLUDED.service,
+                name = EXCLUDED.name,
+                encrypted_key = EXCLUDED.encrypted_key,
+                usage_count = EXCLUDED.usage_count,
+                rate_limit = EXCLUDED.rate_limit,
+                reset_period = EXCLUDED.reset_period,
+                last_used = EXCLUDED.last_used,
+                last_reset = EXCLUDED.last_reset,
+                status = EXCLUDED.status,
+                key_version = EXCLUDED.key_version,
+                monthly_quota = EXCLUDED.monthly_quota,
+                quota_usage = EXCLUDED.quota_usage,
+                quota_period_start = EXCLUDED.quota_period_start,
+                expires_at = EXCLUDED.expires_at,
+                tenant_id = EXCLUDED.tenant_id,
+                updated_at = NOW()"
+        )
+        .bind(api_key.id.to_string())
+        .bind(format!("{:?}", api_key.service))
+        .bind(&api_key.name)
+        .bind(api_key.encrypted_key.as_bytes())
+        .bind(api_key.usage_count as i64)
+        .bind(api_key.rate_limit as i64)
+        .bind(format!("{:?}", api_key.reset_period))
+        .bind(api_key.last_used)
+        .bind(api_key.last_reset)
+        .bind(format!("{:?}", api_key.status))
+        .bind(crate::services::security::key_version_of(&api_key.encrypted_key))
+        .bind(api_key.monthly_quota.map(|quota| quota as i64))
+        .bind(api_key.quota_usage as i64)
+        .bind(api_key.quota_period_start)
+        .bind(api_key.expires_at)
+        .bind(api_key.tenant_id.map(|id| id.to_string()))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        debug!("API key stored successfully");
+        Ok(())
+    }
+
+    async fn get_all_api_keys(&self) -> AppResult<Vec<ApiKey>> {
+        debug!("Retrieving all API keys");
+
+        let rows = sqlx::query(
+            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
+                    reset_period, last_used, last_reset, status, created_at, updated_at,
+                    monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id
+             FROM api_keys ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut api_keys = Vec::new();
+        for row in &rows {
+            api_keys.extend(stored_api_key(row)?);
+        }
+
+        debug!("Retrieved {} API keys", api_keys.len());
+        Ok(api_keys)
+    }
+
+    async fn get_api_keys_page(&self, tenant_id: Option<Uuid>, include_system_pool: bool, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<ApiKey>> {
+        let tenant_id = tenant_id.map(|id| id.to_string());
+        let rows = sqlx::query(
+            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
+                    reset_period, last_used, last_reset, status, created_at, updated_at,
+                    monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id
+             FROM api_keys
+             WHERE (tenant_id = $1 OR ($2 AND tenant_id IS NULL))
+               AND ($3::TIMESTAMPTZ IS NULL OR (created_at, id) < ($3, $4))
+             ORDER BY created_at DESC, id DESC
+             LIMIT $5"
+        )
+        .bind(&tenant_id)
+        .bind(include_system_pool)
+        .bind(after.map(|after| after.created_at))
+        .bind(after.map(|after| after.id.clone()))
+        .bind(limit as i64 + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        let total_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_keys WHERE tenant_id = $1 OR ($2 AND tenant_id IS NULL)")
+            .bind(&tenant_id)
+            .bind(include_system_pool)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Page::from_stored_rows(rows, limit, total_count as u64, stored_position, |row| stored_api_key(&row))
+    }
+
+    async fn get_api_key_by_id(&self, key_id: Uuid) -> AppResult<Option<ApiKey>> {
+        debug!("Retrieving API key by ID: {}", key_id);
+
+        let row = sqlx::query(
+            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
+                    reset_period, last_used, last_reset, status, created_at, updated_at,
+                    monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id
+             FROM api_keys WHERE id = $1"
+        )
+        .bind(key_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.as_ref().map(api_key_from_row).transpose()
+    }
+
+    async fn delete_api_key(&self, key_id: Uuid) -> AppResult<()> {
+        debug!("Deleting API key: {}", key_id);
+
+        let result = sqlx::query("DELETE FROM api_keys WHERE id = $1")
+            .bind(key_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(StorageError::Database {
+                message: format!("API key with ID {} not found", key_id)
+            }.into());
+        }
+
+        debug!("API key deleted successfully");
+        Ok(())
+    }
+
+    async fn update_api_key_statuses(&self, key_ids: &[Uuid], status: &ApiKeyStatus) -> AppResult<()> {
+        debug!("Setting status {:?} on {} API keys", status, key_ids.len());
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        for key_id in key_ids {
+            let result = sqlx::query("UPDATE api_keys SET status = $1, updated_at = NOW() WHERE id = $2")
+                .bind(format!("{:?}", status))
+                .bind(key_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+
+            // Dropping the transaction rolls back every update made so far
+            if result.rows_affected() != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key with ID {} not found", key_id)
+                }.into());
+            }
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn delete_api_keys(&self, key_ids: &[Uuid]) -> AppResult<()> {
+        debug!("Deleting {} API keys", key_ids.len());
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        for key_id in key_ids {
+            let result = sqlx::query("DELETE FROM api_keys WHERE id = $1")
+                .bind(key_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+
+            if result.rows_affected() != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key with ID {} not found", key_id)
+                }.into());
+            }
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get_api_key_ciphertexts(&self) -> AppResult<Vec<ApiKeyCiphertext>> {
+        let rows = sqlx::query("SELECT id, encrypted_key, key_version FROM api_keys")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(|row| {
+            let id: String = row.try_get("id").map_err(db_error)?;
+            Ok(ApiKeyCiphertext {
+                id: Uuid::parse_str(&id)
+                    .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                encrypted_key: String::from_utf8(row.try_get::<Vec<u8>, _>("encrypted_key").map_err(db_error)?)
+                    .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?,
+                key_version: row.try_get("key_version").map_err(db_error)?,
+            })
+        }).collect()
+    }
+
+    async fn replace_api_key_ciphertexts(&self, updates: &[ApiKeyReencryption]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        for update in updates {
+            let result = sqlx::query(
+                "UPDATE api_keys SET encrypted_key = $1, key_version = $2, updated_at = NOW()
+                 WHERE id = $3 AND encrypted_key = $4"
+            )
+            .bind(update.encrypted_key.as_bytes())
+            .bind(&update.key_version)
+            .bind(update.id.to_string())
+            .bind(update.previous_encrypted_key.as_bytes())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+            // Dropping the transaction rolls back every update made so far
+            if result.rows_affected() != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key {} changed during re-encryption", update.id)
+                }.into());
+            }
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn save_research_workflow(&self, workflow: &ResearchWorkflow) -> AppResult<()> {
+        debug!("Saving research workflow: {}", workflow.id);
+
+        let parameters_json = serde_json::to_string(&workflow.parameters)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize parameters: {}", e) })?;
+        let results_json = workflow.results.as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize results: {}", e) })?;
+        let tags_json = serde_json::to_string(&workflow.tags)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize tags: {}", e) })?;
+        let (summary, sources) = workflow_search::index_fields(workflow);
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        sqlx::query(
+            "INSERT INTO research_workflows (
+                id, name, query, status, methodology, parameters, results,
+                created_at, updated_at, completed_at, folder, tags
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), $9, $10, $11)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                query = EXCLUDED.query,
+                status = EXCLUDED.status,
+                methodology = EXCLUDED.methodology,
+                parameters = EXCLUDED.parameters,
+                results = EXCLUDED.results,
+                updated_at = NOW(),
+                completed_at = EXCLUDED.completed_at,
+                folder = EXCLUDED.folder,
+                tags = EXCLUDED.tags"
+        )
+        .bind(workflow.id.to_string())
+        .bind(&workflow.name)
+        .bind(&workflow.query)
+        .bind(format!("{:?}", workflow.status))
+        .bind(format!("{:?}", workflow.parameters.methodology))
+        .bind(parameters_json)
+        .bind(results_json)
+        .bind(workflow.created_at)
+        .bind(workflow.completed_at)
+        .bind(&workflow.folder)
+        .bind(tags_json)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            "INSERT INTO workflow_search_index (
+                workflow_id, name, query, summary, sources, tags, status, methodology, created_at, folder
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (workflow_id) DO UPDATE SET
+                name = EXCLUDED.name,
+                query = EXCLUDED.query,
+                summary = EXCLUDED.summary,
+                sources = EXCLUDED.sources,
+                tags = EXCLUDED.tags,
+                status = EXCLUDED.status,
+                methodology = EXCLUDED.methodology,
+                created_at = EXCLUDED.created_at,
+                folder = EXCLUDED.folder"
+        )
+        .bind(workflow.id.to_string())
+        .bind(&workflow.name)
+        .bind(&workflow.query)
+        .bind(summary)
+        .bind(sources)
+        .bind(workflow.tags.join("\n"))
+        .bind(format!("{:?}", workflow.status))
+        .bind(format!("{:?}", workflow.parameters.methodology))
+        .bind(workflow.created_at)
+        .bind(&workflow.folder)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query("DELETE FROM workflow_tags WHERE workflow_id = $1")
+            .bind(workflow.id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query(
+            "INSERT INTO workflow_tags (workflow_id, tag)
+             SELECT $1, tag FROM UNNEST($2::TEXT[]) AS tag
+             ON CONFLICT DO NOTHING"
+        )
+        .bind(workflow.id.to_string())
+        .bind(&workflow.tags)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        debug!("Research workflow saved successfully");
+        Ok(())
+    }
+
+    async fn delete_research_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
+        debug!("Deleting research workflow: {}", workflow_id);
+
+        // The search index row is removed by ON DELETE CASCADE
+        sqlx::query("DELETE FROM research_workflows WHERE id = $1")
+            .bind(workflow_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        sqlx::query("DELETE FROM step_execution_metrics WHERE workflow_id = $1")
+            .bind(workflow_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        debug!("Research workflow deleted successfully");
+        Ok(())
+    }
+
+    async fn get_research_workflow_ids_page(&self, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<Uuid>> {
+        let rows = sqlx::query(
+            "SELECT id, created_at FROM research_workflows
+             WHERE $1::TIMESTAMPTZ IS NULL OR (created_at, id) < ($1, $2)
+             ORDER BY created_at DESC, id DESC
+             LIMIT $3"
+        )
+        .bind(after.map(|after| after.created_at))
+        .bind(after.map(|after| after.id.clone()))
+        .bind(limit as i64 + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        let total_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM research_workflows")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Page::from_stored_rows(rows, limit, total_count as u64, stored_position, |row| {
+            let id: String = row.try_get("id").map_err(db_error)?;
+            Uuid::parse_str(&id)
+                .map(Some)
+                .map_err(|_| StorageError::Database { message: "Invalid UUID in research workflow".to_string() }.into())
+        })
+    }
+
+    async fn search_workflows(&self, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        debug!("Searching workflows with query: {}", query);
+
+        let tsquery = build_tsquery(query);
+        if tsquery.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (limit, offset) = workflow_search::page(filters);
+        let (folder, tags) = workflow_search::organization_filters(filters);
+
+        // Ranks are negated so that, as with SQLite's bm25(), lower is better
+        let rows = sqlx::query(
+            "SELECT workflow_id, name, query, status, methodology, created_at, tags, folder,
+                    -ts_rank_cd(document, q)::FLOAT8 AS score,
+                    ts_headline('english', name || ' ' || query || ' ' || summary || ' ' || sources, q,
+                        'StartSel=<mark>, StopSel=</mark>, MaxWords=24, MinWords=8') AS snippet,
+                    ts_headline('english', name, q, 'StartSel=<mark>, StopSel=</mark>, HighlightAll=true') AS highlighted_name
+             FROM workflow_search_index, to_tsquery('english', $1) AS q
+             WHERE document @@ q
+               AND ($2::TEXT IS NULL OR status = $2)
+               AND ($3::TEXT IS NULL OR methodology = $3)
+               AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+               AND ($5::TIMESTAMPTZ IS NULL OR created_at <= $5)
+               AND ($6::TEXT IS NULL OR folder = $6 OR left(folder, length($6) + 1) = $6 || '/')
+               AND (cardinality($7::TEXT[]) = 0 OR workflow_id IN (
+                    SELECT workflow_id FROM workflow_tags WHERE tag = ANY($7)
+                    GROUP BY workflow_id HAVING COUNT(*) = cardinality($7::TEXT[])))
+             ORDER BY score
+             LIMIT $8 OFFSET $9"
+        )
+        .bind(tsquery)
+        .bind(&filters.status)
+        .bind(&filters.methodology)
+        .bind(filters.created_after)
+        .bind(filters.created_before)
+        .bind(folder)
+        .bind(tags)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let matches = rows.iter().map(search_match).collect::<AppResult<Vec<_>>>()?;
+
+        debug!("Found {} workflows matching query", matches.len());
+        Ok(matches)
+    }
+
+    async fn browse_workflows(&self, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        let (limit, offset) = workflow_search::page(filters);
+        let (folder, tags) = workflow_search::organization_filters(filters);
+
+        let rows = sqlx::query(
+            "SELECT workflow_id, name, query, status, methodology, created_at, tags, folder,
+                    0.0::FLOAT8 AS score, '' AS snippet, name AS highlighted_name
+             FROM workflow_search_index
+             WHERE ($1::TEXT IS NULL OR status = $1)
+               AND ($2::TEXT IS NULL OR methodology = $2)
+               AND ($3::TIMESTAMPTZ IS NULL OR created_at >= $3)
+               AND ($4::TIMESTAMPTZ IS NULL OR created_at <= $4)
+               AND ($5::TEXT IS NULL OR folder = $5 OR left(folder, length($5) + 1) = $5 || '/')
+               AND (cardinality($6::TEXT[]) = 0 OR workflow_id IN (
+                    SELECT workflow_id FROM workflow_tags WHERE tag = ANY($6)
+                    GROUP BY workflow_id HAVING COUNT(*) = cardinality($6::TEXT[])))
+             ORDER BY created_at DESC
+             LIMIT $7 OFFSET $8"
+        )
+        .bind(&filters.status)
+        .bind(&filters.methodology)
+        .bind(filters.created_after)
+        .bind(filters.created_before)
+        .bind(folder)
+        .bind(tags)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(search_match).collect()
+    }
+
+    async fn get_workflow_organization(&self) -> AppResult<WorkflowOrganization> {
+        let folders = sqlx::query(
+            "SELECT folder AS name, COUNT(*) AS workflow_count FROM workflow_search_index
+             WHERE folder IS NOT NULL GROUP BY folder ORDER BY folder"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        let tags = sqlx::query(
+            "SELECT tag AS name, COUNT(*) AS workflow_count FROM workflow_tags GROUP BY tag ORDER BY tag"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(WorkflowOrganization {
+            folders: folders.iter().map(workflow_group).collect::<AppResult<_>>()?,
+            tags: tags.iter().map(workflow_group).collect::<AppResult<_>>()?,
+        })
+    }
+
+    async fn record_api_usage(
+        &self,
+        api_key_id: Uuid,
+        service: &str,
+        endpoint: Option<&str>,
+        success: bool,
+        response_time_ms: u32,
+    ) -> AppResult<()> {
+        debug!("Recording API usage for key: {}", api_key_id);
+
+        let date_bucket = Utc::now().format("%Y-%m-%d").to_string();
+        let endpoint_str = endpoint.unwrap_or("unknown");
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        // Try to update existing record first
+        let updated = sqlx::query(
+            "UPDATE api_usage_stats SET
+                request_count = request_count + 1,
+                success_count = success_count + CASE WHEN $5 THEN 1 ELSE 0 END,
+                error_count = error_count + CASE WHEN $5 THEN 0 ELSE 1 END,
+                total_response_time_ms = total_response_time_ms + $6,
+                avg_response_time_ms = CAST(total_response_time_ms + $6 AS DOUBLE PRECISION) / (request_count + 1),
+                last_used = NOW()
+             WHERE api_key_id = $1 AND service = $2 AND endpoint = $3 AND date_bucket = $4"
+        )
+        .bind(api_key_id.to_string())
+        .bind(service)
+        .bind(endpoint_str)
+        .bind(&date_bucket)
+        .bind(success)
+        .bind(response_time_ms as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        // If no existing record, create new one
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO api_usage_stats (
+                    id, api_key_id, service, endpoint, request_count, success_count,
+                    error_count, total_response_time_ms, avg_response_time_ms, date_bucket
+                ) VALUES ($1, $2, $3, $4, 1, $5, $6, $7, $7, $8)"
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(api_key_id.to_string())
+            .bind(service)
+            .bind(endpoint_str)
+            .bind(if success { 1i64 } else { 0 })
+            .bind(if success { 0i64 } else { 1 })
+            .bind(response_time_ms as i64)
+            .bind(&date_bucket)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+
+        debug!("API usage recorded successfully");
+        Ok(())
+    }
+
+    async fn get_api_key_usage_stats(&self, api_key_id: Uuid, days: u32) -> AppResult<Vec<(String, u32, u32, u32, f64)>> {
+        debug!("Getting usage statistics for API key: {}", api_key_id);
+
+        let start_date = (Utc::now() - chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d").to_string();
+
+        let rows = sqlx::query(
+            "SELECT date_bucket, request_count, success_count, error_count, avg_response_time_ms
+             FROM api_usage_stats
+             WHERE api_key_id = $1 AND date_bucket >= $2
+             ORDER BY date_bucket DESC"
+        )
+        .bind(api_key_id.to_string())
+        .bind(start_date)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let stats = rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<String, _>(0).map_err(db_error)?,
+                    row.try_get::<i64, _>(1).map_err(db_error)? as u32,
+                    row.try_get::<i64, _>(2).map_err(db_error)? as u32,
+                    row.try_get::<i64, _>(3).map_err(db_error)? as u32,
+                    row.try_get::<f64, _>(4).map_err(db_error)?,
+                ))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        debug!("Retrieved {} usage statistics records", stats.len());
+        Ok(stats)
+    }
+
+    async fn get_service_usage_history(&self, service: &str, days: u32) -> AppResult<Vec<(Uuid, String, u32)>> {
+        debug!("Getting usage history for service: {}", service);
+
+        let start_date = (Utc::now() - chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d").to_string();
+
+        let rows = sqlx::query(
+            "SELECT api_key_id, date_bucket, SUM(request_count)::BIGINT
+             FROM api_usage_stats
+             WHERE service = $1 AND date_bucket >= $2
+             GROUP BY api_key_id, date_bucket
+             ORDER BY date_bucket ASC"
+        )
+        .bind(service)
+        .bind(start_date)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let history = rows.iter()
+            .map(|row| {
+                let id_str: String = row.try_get(0).map_err(db_error)?;
+                Ok((
+                    Uuid::parse_str(&id_str)
+                        .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                    row.try_get::<String, _>(1).map_err(db_error)?,
+                    row.try_get::<i64, _>(2).map_err(db_error)? as u32,
+                ))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        debug!("Retrieved {} usage history records", history.len());
+        Ok(history)
+    }
+
+    async fn get_key_usage_totals(&self, from: &str, to: &str) -> AppResult<Vec<KeyUsageTotals>> {
+        let rows = sqlx::query(
+            "SELECT api_key_id, service, SUM(request_count)::BIGINT, SUM(success_count)::BIGINT, SUM(error_count)::BIGINT
+             FROM api_usage_stats
+             WHERE date_bucket >= $1 AND date_bucket <= $2
+             GROUP BY api_key_id, service
+             ORDER BY service, api_key_id"
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let id_str: String = row.try_get(0).map_err(db_error)?;
+                Ok(KeyUsageTotals {
+                    api_key_id: Uuid::parse_str(&id_str)
+                        .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                    service: row.try_get(1).map_err(db_error)?,
+                    request_count: row.try_get::<i64, _>(2).map_err(db_error)? as u32,
+                    success_count: row.try_get::<i64, _>(3).map_err(db_error)? as u32,
+                    error_count: row.try_get::<i64, _>(4).map_err(db_error)? as u32,
+                })
+            })
+            .collect()
+    }
+
+    async fn record_provider_usage_snapshot(&self, snapshot: &ProviderUsageSnapshot) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO provider_usage_snapshots
+                (api_key_id, service, date_bucket, reported_used, reported_limit, observed_at)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+        .bind(snapshot.api_key_id.to_string())
+        .bind(&snapshot.service)
+        .bind(&snapshot.date_bucket)
+        .bind(snapshot.reported_used as i64)
+        .bind(snapshot.reported_limit.map(|limit| limit as i64))
+        .bind(snapshot.observed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn record_usage_divergences(&self, divergences: &[UsageDivergence]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for divergence in divergences {
+            sqlx::query(
+                "INSERT INTO usage_divergence_history (
+                    api_key_id, service, range_start, range_end, counted, reported,
+                    delta, divergence_percent, threshold_exceeded, checked_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+            )
+            .bind(divergence.api_key_id.to_string())
+            .bind(&divergence.service)
+            .bind(divergence.from.format("%Y-%m-%d").to_string())
+            .bind(divergence.to.format("%Y-%m-%d").to_string())
+            .bind(divergence.counted as i64)
+            .bind(divergence.reported as i64)
+            .bind(divergence.delta)
+            .bind(divergence.divergence_percent)
+            .bind(divergence.threshold_exceeded)
+            .bind(divergence.checked_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_usage_divergence_history(&self, since: DateTime<Utc>) -> AppResult<Vec<UsageDivergence>> {
+        let rows = sqlx::query(
+            "SELECT api_key_id, service, range_start, range_end, counted, reported,
+                    delta, divergence_percent, threshold_exceeded, checked_at
+             FROM usage_divergence_history
+             WHERE checked_at >= $1
+             ORDER BY checked_at, id"
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let parse_date = |date: String| chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| StorageError::Database { message: e.to_string() });
+        rows.iter()
+            .map(|row| {
+                let id_str: String = row.try_get(0).map_err(db_error)?;
+                Ok(UsageDivergence {
+                    api_key_id: Uuid::parse_str(&id_str)
+                        .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                    service: row.try_get(1).map_err(db_error)?,
+                    from: parse_date(row.try_get(2).map_err(db_error)?)?,
+                    to: parse_date(row.try_get(3).map_err(db_error)?)?,
+                    counted: row.try_get::<i64, _>(4).map_err(db_error)? as u32,
+                    reported: row.try_get::<i64, _>(5).map_err(db_error)? as u32,
+                    delta: row.try_get(6).map_err(db_error)?,
+                    divergence_percent: row.try_get(7).map_err(db_error)?,
+                    threshold_exceeded: row.try_get(8).map_err(db_error)?,
+                    checked_at: row.try_get::<DateTime<Utc>, _>(9).map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_provider_usage_snapshots(&self, to: &str) -> AppResult<Vec<ProviderUsageSnapshot>> {
+        let rows = sqlx::query(
+            "SELECT api_key_id, service, date_bucket, reported_used, reported_limit, observed_at
+             FROM provider_usage_snapshots
+             WHERE date_bucket <= $1
+             ORDER BY api_key_id, observed_at, id"
+        )
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let id_str: String = row.try_get(0).map_err(db_error)?;
+                Ok(ProviderUsageSnapshot {
+                    api_key_id: Uuid::parse_str(&id_str)
+                        .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                    service: row.try_get(1).map_err(db_error)?,
+                    date_bucket: row.try_get(2).map_err(db_error)?,
+                    reported_used: row.try_get::<i64, _>(3).map_err(db_error)? as u32,
+                    reported_limit: row.try_get::<Option<i64>, _>(4).map_err(db_error)?.map(|limit| limit as u32),
+                    observed_at: row.try_get::<DateTime<Utc>, _>(5).map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn store_audit_event(&self, event: &AuditEvent) -> AppResult<()> {
+        debug!("Storing audit event: {} - {}", event.event_type, event.description);
+
+        let metadata_json = serde_json::to_string(&event.metadata)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize metadata: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO audit_events (
+                id, event_type, description, resource_id, user_id,
+                timestamp, severity, metadata
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        )
+        .bind(event.id.to_string())
+        .bind(&event.event_type)
+        .bind(&event.description)
+        .bind(&event.resource_id)
+        .bind(&event.user_id)
+        .bind(event.timestamp.to_rfc3339())
+        .bind(event.severity.as_str())
+        .bind(metadata_json)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        debug!("Audit event stored successfully: {}", event.id);
+        Ok(())
+    }
+
+    async fn save_research_schedule(&self, schedule: &ResearchSchedule) -> AppResult<()> {
+        debug!("Saving research schedule: {}", schedule.id);
+
+        let definition = serde_json::to_string(schedule)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize schedule: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO research_schedules (
+                id, name, paused, next_run_at, definition, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                paused = EXCLUDED.paused,
+                next_run_at = EXCLUDED.next_run_at,
+                definition = EXCLUDED.definition,
+                updated_at = NOW()"
+        )
+        .bind(schedule.id.to_string())
+        .bind(&schedule.name)
+        .bind(schedule.paused)
+        .bind(schedule.next_run_at)
+        .bind(definition)
+        .bind(schedule.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_research_schedules(&self) -> AppResult<Vec<ResearchSchedule>> {
+        let rows = sqlx::query("SELECT definition FROM research_schedules ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize schedule: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn delete_research_schedule(&self, schedule_id: Uuid) -> AppResult<()> {
+        debug!("Deleting research schedule: {}", schedule_id);
+
+        // Its runs are removed by ON DELETE CASCADE
+        sqlx::query("DELETE FROM research_schedules WHERE id = $1")
+            .bind(schedule_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn save_schedule_run(&self, run: &ScheduleRun) -> AppResult<()> {
+        debug!("Saving run {} of schedule {}", run.id, run.schedule_id);
+
+        let details = serde_json::to_string(run)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize schedule run: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO research_schedule_runs (id, schedule_id, status, started_at, details)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                details = EXCLUDED.details"
+        )
+        .bind(run.id.to_string())
+        .bind(run.schedule_id.to_string())
+        .bind(run.status.as_str())
+        .bind(run.started_at)
+        .bind(details)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_schedule_runs(&self, schedule_id: Uuid, limit: u32) -> AppResult<Vec<ScheduleRun>> {
+        let rows = sqlx::query(
+            "SELECT details FROM research_schedule_runs
+             WHERE schedule_id = $1
+             ORDER BY started_at DESC
+             LIMIT $2"
+        )
+        .bind(schedule_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        collect_schedule_runs(&rows)
+    }
+
+    async fn get_running_schedule_runs(&self) -> AppResult<Vec<ScheduleRun>> {
+        let rows = sqlx::query(
+            "SELECT details FROM research_schedule_runs WHERE status = 'running' ORDER BY started_at"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        collect_schedule_runs(&rows)
+    }
+
+    async fn save_prompt_template(&self, template: &PromptTemplate) -> AppResult<()> {
+        debug!("Saving prompt template: {}", template.label());
+
+        let definition = serde_json::to_string(template)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize prompt template: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO prompt_templates (id, version, definition, created_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (id, version) DO UPDATE SET
+                definition = EXCLUDED.definition"
+        )
+        .bind(&template.id)
+        .bind(template.version as i32)
+        .bind(definition)
+        .bind(template.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_prompt_templates(&self) -> AppResult<Vec<PromptTemplate>> {
+        let rows = sqlx::query("SELECT definition FROM prompt_templates ORDER BY id, version")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize prompt template: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn save_prompt_experiment(&self, experiment: &PromptExperiment) -> AppResult<()> {
+        debug!("Saving prompt experiment on: {}", experiment.prompt_id);
+
+        let definition = serde_json::to_string(experiment)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize prompt experiment: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO prompt_experiments (prompt_id, active, definition, updated_at)
+             VALUES ($1, $2, $3, NOW())
+             ON CONFLICT (prompt_id) DO UPDATE SET
+                active = EXCLUDED.active,
+                definition = EXCLUDED.definition,
+                updated_at = NOW()"
+        )
+        .bind(&experiment.prompt_id)
+        .bind(experiment.active)
+        .bind(definition)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_prompt_experiments(&self) -> AppResult<Vec<PromptExperiment>> {
+        let rows = sqlx::query("SELECT definition FROM prompt_experiments ORDER BY prompt_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize prompt experiment: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn save_idempotency_record(&self, record: &IdempotencyRecord) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO workflow_idempotency_keys (key, workflow_id, request_fingerprint, created_at, expires_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (key) DO UPDATE SET
+                workflow_id = EXCLUDED.workflow_id,
+                request_fingerprint = EXCLUDED.request_fingerprint,
+                created_at = EXCLUDED.created_at,
+                expires_at = EXCLUDED.expires_at"
+        )
+        .bind(&record.key)
+        .bind(record.workflow_id.to_string())
+        .bind(&record.request_fingerprint)
+        .bind(record.created_at)
+        .bind(record.expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+        let row = sqlx::query(
+            "SELECT workflow_id, request_fingerprint, created_at, expires_at
+             FROM workflow_idempotency_keys WHERE key = $1"
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let Some(row) = row else { return Ok(None) };
+        let workflow_id: String = row.try_get("workflow_id").map_err(db_error)?;
+
+        Ok(Some(IdempotencyRecord {
+            key: key.to_string(),
+            workflow_id: workflow_id.parse()
+                .map_err(|_| StorageError::Database { message: "Invalid UUID in idempotency key".to_string() })?,
+            request_fingerprint: row.try_get("request_fingerprint").map_err(db_error)?,
+            created_at: row.try_get("created_at").map_err(db_error)?,
+            expires_at: row.try_get("expires_at").map_err(db_error)?,
+        }))
+    }
+
+    async fn purge_expired_idempotency_records(&self, now: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM workflow_idempotency_keys WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn save_saga_state(&self, saga: &SagaState) -> AppResult<()> {
+        let definition = serde_json::to_string(saga)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize saga: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO workflow_sagas (id, workflow_id, status, definition, updated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                definition = EXCLUDED.definition,
+                updated_at = EXCLUDED.updated_at"
+        )
+        .bind(saga.id.to_string())
+        .bind(saga.workflow_id.to_string())
+        .bind(saga.status.as_str())
+        .bind(definition)
+        .bind(saga.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_unfinished_sagas(&self) -> AppResult<Vec<SagaState>> {
+        let rows = sqlx::query(
+            "SELECT definition FROM workflow_sagas
+             WHERE status IN ('running', 'compensating') ORDER BY updated_at"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize saga: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> AppResult<()> {
+        let definition = serde_json::to_string(preferences)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification preferences: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO notification_preferences (user_id, tenant_id, definition, updated_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id) DO UPDATE SET
+                tenant_id = EXCLUDED.tenant_id,
+                definition = EXCLUDED.definition,
+                updated_at = EXCLUDED.updated_at"
+        )
+        .bind(preferences.user_id.to_string())
+        .bind(preferences.tenant_id.map(|id| id.to_string()))
+        .bind(definition)
+        .bind(preferences.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_notification_preferences(&self, user_id: Uuid) -> AppResult<Option<NotificationPreferences>> {
+        let row = sqlx::query("SELECT definition FROM notification_preferences WHERE user_id = $1")
+            .bind(user_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| {
+            let definition: String = row.try_get("definition").map_err(db_error)?;
+            serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification preferences: {}", e) }.into())
+        })
+        .transpose()
+    }
+
+    async fn get_all_notification_preferences(&self) -> AppResult<Vec<NotificationPreferences>> {
+        let rows = sqlx::query("SELECT definition FROM notification_preferences ORDER BY user_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification preferences: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn hold_notification(&self, user_id: Uuid, notification: &Notification) -> AppResult<()> {
+        let definition = serde_json::to_string(notification)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification: {}", e) })?;
+
+        sqlx::query("INSERT INTO held_notifications (id, user_id, definition, created_at) VALUES ($1, $2, $3, $4)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id.to_string())
+            .bind(definition)
+            .bind(notification.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn take_held_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        let rows = sqlx::query(
+            "DELETE FROM held_notifications WHERE user_id = $1 RETURNING definition, created_at"
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        // RETURNING gives no order
+        let mut notifications = rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification: {}", e) }.into())
+            })
+            .collect::<AppResult<Vec<Notification>>>()?;
+        notifications.sort_by_key(|notification| notification.created_at);
+
+        Ok(notifications)
+    }
+
+    async fn add_inbox_notifications(&self, user_id: Uuid, notifications: &[Notification], keep: usize) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        for notification in notifications {
+            let definition = serde_json::to_string(notification)
+                .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification: {}", e) })?;
+            sqlx::query(
+                "INSERT INTO notification_inbox (id, user_id, definition, created_at) VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (id) DO UPDATE SET definition = EXCLUDED.definition"
+            )
+            .bind(notification.id.to_string())
+            .bind(user_id.to_string())
+            .bind(definition)
+            .bind(notification.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        sqlx::query(
+            "DELETE FROM notification_inbox WHERE user_id = $1 AND id NOT IN (
+                SELECT id FROM notification_inbox WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2
+            )"
+        )
+        .bind(user_id.to_string())
+        .bind(keep as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_inbox_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        let rows = sqlx::query(
+            "SELECT definition FROM notification_inbox WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn save_marketplace_installation(&self, installation: &InstalledContent) -> AppResult<()> {
+        let definition = serde_json::to_string(installation)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize marketplace installation: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO marketplace_installations (user_id, content_kind, content_id, definition, installed_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (user_id, content_kind, content_id) DO UPDATE SET
+                definition = EXCLUDED.definition,
+                installed_at = EXCLUDED.installed_at"
+        )
+        .bind(installation.user_id.to_string())
+        .bind(installation.kind.as_str())
+        .bind(installation.content_id.to_string())
+        .bind(definition)
+        .bind(installation.installed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_marketplace_installations(&self, user_id: Uuid) -> AppResult<Vec<InstalledContent>> {
+        let rows = sqlx::query(
+            "SELECT definition FROM marketplace_installations WHERE user_id = $1 ORDER BY installed_at"
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize marketplace installation: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn delete_marketplace_installation(&self, user_id: Uuid, kind: ContentKind, content_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM marketplace_installations WHERE user_id = $1 AND content_kind = $2 AND content_id = $3"
+        )
+        .bind(user_id.to_string())
+        .bind(kind.as_str())
+        .bind(content_id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn save_workflow_rating(&self, rating: &WorkflowRating) -> AppResult<()> {
+        let definition = serde_json::to_string(rating)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize workflow rating: {}", e) })?;
+
+        sqlx::query(
+            "INSERT INTO workflow_ratings (workflow_id, rated_by, query_domain, definition, rated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (workflow_id) DO UPDATE SET
+                rated_by = EXCLUDED.rated_by,
+                query_domain = EXCLUDED.query_domain,
+                definition = EXCLUDED.definition,
+                rated_at = EXCLUDED.rated_at"
+        )
+        .bind(rating.workflow_id.to_string())
+        .bind(&rating.rated_by)
+        .bind(rating.query_domain.as_str())
+        .bind(definition)
+        .bind(rating.rated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_workflow_ratings(&self, rated_by: &str, query_domain: QueryDomain) -> AppResult<Vec<WorkflowRating>> {
+        let rows = sqlx::query(
+            "SELECT definition FROM workflow_ratings WHERE rated_by = $1 AND query_domain = $2 ORDER BY rated_at"
+        )
+        .bind(rated_by)
+        .bind(query_domain.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize workflow rating: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn append_history(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for record in records {
+            sqlx::query("INSERT INTO history_entries (kind, definition, recorded_at) VALUES ($1, $2, $3)")
+                .bind(kind)
+                .bind(&record.definition)
+                .bind(record.recorded_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_history(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>> {
+        let rows = sqlx::query(
+            "SELECT definition, recorded_at FROM history_entries
+             WHERE kind = $1 AND ($2::TIMESTAMPTZ IS NULL OR recorded_at >= $2)
+             ORDER BY id DESC
+             LIMIT $3"
+        )
+        .bind(kind)
+        .bind(since)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| -> AppResult<HistoryRecord> {
+                Ok(HistoryRecord {
+                    definition: row.try_get("definition").map_err(db_error)?,
+                    recorded_at: row.try_get("recorded_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn purge_history(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM history_entries WHERE kind = $1 AND recorded_at < $2")
+            .bind(kind)
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn save_step_metrics(&self, metrics: &StepExecutionMetrics) -> AppResult<()> {
+        let definition = serde_json::to_string(metrics)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize step metrics: {}", e) })?;
+
+        sqlx::query("INSERT INTO step_execution_metrics (workflow_id, step_id, definition, started_at) VALUES ($1, $2, $3, $4)")
+            .bind(metrics.workflow_id.to_string())
+            .bind(metrics.step_id.to_string())
+            .bind(definition)
+            .bind(metrics.started_at)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_step_metrics(&self, workflow_id: Uuid) -> AppResult<Vec<StepExecutionMetrics>> {
+        let rows = sqlx::query("SELECT definition FROM step_execution_metrics WHERE workflow_id = $1 ORDER BY started_at, id")
+            .bind(workflow_id.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let definition: String = row.try_get("definition").map_err(db_error)?;
+                serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize step metrics: {}", e) }.into())
+            })
+            .collect()
+    }
+
+    async fn save_service_setting(&self, name: &str, definition: &serde_json::Value) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO service_settings (name, definition, updated_at) VALUES ($1, $2, $3)
+             ON CONFLICT (name) DO UPDATE SET definition = EXCLUDED.definition, updated_at = EXCLUDED.updated_at"
+        )
+        .bind(name)
+        .bind(definition.to_string())
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_service_setting(&self, name: &str) -> AppResult<Option<serde_json::Value>> {
+        let row = sqlx::query("SELECT definition FROM service_settings WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| {
+            let definition: String = row.try_get("definition").map_err(db_error)?;
+            serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize setting {}: {}", name, e) }.into())
+        })
+        .transpose()
+    }
+
+    async fn health_check(&self) -> AppResult<()> {
+        let _count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_keys")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        // Check schema migrations are clean and complete. This only reads
+        // schema_migrations, so it must not wait on the migration lock held
+        // by another instance that is mid-migration.
+        let mut conn = self.pool.acquire().await.map_err(db_error)?;
+        let applied = self.load_applied(&mut conn).await?;
+        let report = MigrationRunner::new(MIGRATIONS)?.plan(applied, SqlDialect::Postgres, MigrationMode::DryRun)?;
+        if !report.pending.is_empty() {
+            return Err(StorageError::Database {
+                message: format!("{} schema migrations pending", report.pending.len())
+            }.into());
+        }
+
+        debug!("Postgres pool: {} connections, {} idle", self.pool.size(), self.pool.num_idle());
+        Ok(())
+    }
+}
+
+fn collect_schedule_runs(rows: &[PgRow]) -> AppResult<Vec<ScheduleRun>> {
+    rows.iter()
+        .map(|row| {
+            let details: String = row.try_get("details").map_err(db_error)?;
+            serde_json::from_str(&details)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize schedule run: {}", e) }.into())
+        })
+        .collect()
+}
@@ -0,0 +1,322 @@
+//! Redaction: scrubbing personal data out of research results before they are stored.
+//!
+//! Results are built from web content of unknown provenance, so emails, phone
+//! numbers and names scraped from sources end up in reports. Every enabled
+//! category is replaced with a token such as `[REDACTED_EMAIL]` when a workflow
+//! is saved; exports read stored workflows and so only ever see redacted text.
+//! Names are found with pattern-based entity recognition (honorifics and author
+//! or contact cues), which favours precision over catching every name.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::models::research_workflow::{ResearchResults, ResearchWorkflow};
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+});
+static PHONE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?)?\b\d{2,4}[\s.-]\d{3,4}[\s.-]?\d{3,4}\b").unwrap()
+});
+static IP_ADDRESS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b").unwrap()
+});
+static PAYMENT_CARD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap());
+static NATIONAL_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").unwrap());
+static HONORIFIC_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:Mr|Mrs|Ms|Miss|Mx|Dr|Prof|Sir|Dame)\.?\s+[A-Z][a-z'-]+(?:\s+[A-Z][a-z'-]+){0,2}").unwrap()
+});
+static CUED_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:[Cc]ontact|[Pp]osted by|[Ww]ritten by|[Rr]eported by|[Aa]uthor:?|[Ss]poke with|[Aa]ccording to)\s+([A-Z][a-z'-]+(?:\s+[A-Z]\.)?\s+[A-Z][a-z'-]+)").unwrap()
+});
+
+/// Kind of personal data a redaction pass looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionCategory {
+    Email,
+    Phone,
+    PersonName,
+    IpAddress,
+    /// Card numbers that pass the Luhn check
+    PaymentCard,
+    /// US social security numbers
+    NationalId,
+}
+
+impl RedactionCategory {
+    pub const ALL: [RedactionCategory; 6] = [
+        RedactionCategory::Email,
+        RedactionCategory::Phone,
+        RedactionCategory::PersonName,
+        RedactionCategory::IpAddress,
+        RedactionCategory::PaymentCard,
+        RedactionCategory::NationalId,
+    ];
+
+    /// Token that replaces a match
+    pub fn token(&self) -> &'static str {
+        match self {
+            RedactionCategory::Email => "[REDACTED_EMAIL]",
+            RedactionCategory::Phone => "[REDACTED_PHONE]",
+            RedactionCategory::PersonName => "[REDACTED_NAME]",
+            RedactionCategory::IpAddress => "[REDACTED_IP]",
+            RedactionCategory::PaymentCard => "[REDACTED_CARD]",
+            RedactionCategory::NationalId => "[REDACTED_ID]",
+        }
+    }
+}
+
+/// Which categories are scrubbed from results before they are stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub categories: HashSet<RedactionCategory>,
+}
+
+impl Default for RedactionConfig {
+    /// Only the categories whose patterns rarely match anything else. Phone numbers,
+    /// IP addresses and names are opt-in: their patterns also catch figures, version
+    /// numbers and the names a report is about.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            categories: [RedactionCategory::Email, RedactionCategory::PaymentCard, RedactionCategory::NationalId]
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// One piece of personal data found in a result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionFinding {
+    pub category: RedactionCategory,
+    /// Where the match is: `content`, `sources[2]` or `metadata.<key>`
+    pub location: String,
+    /// Byte offsets of the match in the original text
+    pub start: usize,
+    pub end: usize,
+    /// The match with all but its first character masked, so a report does not leak it
+    pub preview: String,
+}
+
+/// What a redaction pass replaced, or would replace in a dry run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionReport {
+    pub total_matches: usize,
+    pub matches_by_category: HashMap<RedactionCategory, usize>,
+    pub findings: Vec<RedactionFinding>,
+}
+
+impl RedactionReport {
+    pub fn add(&mut self, finding: RedactionFinding) {
+        self.total_matches += 1;
+        *self.matches_by_category.entry(finding.category).or_insert(0) += 1;
+        self.findings.push(finding);
+    }
+}
+
+/// Replace the enabled categories in `text`, returning the scrubbed text and what was found
+pub fn redact_text(config: &RedactionConfig, text: &str, location: &str) -> (String, Vec<RedactionFinding>) {
+    if !config.enabled || config.categories.is_empty() {
+        return (text.to_string(), Vec::new());
+    }
+
+    let mut spans = find_spans(config, text);
+    // Earliest match wins; on a tie the longer one covers more
+    spans.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)));
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut findings = Vec::new();
+    let mut cursor = 0;
+    for (category, start, end) in spans {
+        if start < cursor {
+            continue;
+        }
+        redacted.push_str(&text[cursor..start]);
+        redacted.push_str(category.token());
+        findings.push(RedactionFinding {
+            category,
+            location: location.to_string(),
+            start,
+            end,
+            preview: mask(&text[start..end]),
+        });
+        cursor = end;
+    }
+    redacted.push_str(&text[cursor..]);
+
+    (redacted, findings)
+}
+
+/// Scrub a result's content, sources and string metadata in place
+pub fn redact_results(config: &RedactionConfig, results: &mut ResearchResults) -> RedactionReport {
+    let mut report = RedactionReport::default();
+
+    let (content, findings) = redact_text(config, &results.content, "content");
+    results.content = content;
+    findings.into_iter().for_each(|finding| report.add(finding));
+
+    for (index, source) in results.sources.iter_mut().enumerate() {
+        let (redacted, findings) = redact_text(config, source, &format!("sources[{}]", index));
+        *source = redacted;
+        findings.into_iter().for_each(|finding| report.add(finding));
+    }
+
+    for (key, value) in results.metadata.iter_mut() {
+        redact_value(config, value, &format!("metadata.{}", key), &mut report);
+    }
+
+    report
+}
+
+/// Report what `config` would redact from a result without changing it
+pub fn dry_run(config: &RedactionConfig, results: &ResearchResults) -> RedactionReport {
+    redact_results(config, &mut results.clone())
+}
+
+/// The workflow as it should be stored: unchanged when there is nothing to redact
+pub fn redact_workflow<'a>(config: &RedactionConfig, workflow: &'a ResearchWorkflow) -> Cow<'a, ResearchWorkflow> {
+    let Some(results) = &workflow.results else {
+        return Cow::Borrowed(workflow);
+    };
+    if !config.enabled {
+        return Cow::Borrowed(workflow);
+    }
+
+    let mut redacted_results = results.clone();
+    if redact_results(config, &mut redacted_results).total_matches == 0 {
+        return Cow::Borrowed(workflow);
+    }
+    let mut redacted = workflow.clone();
+    redacted.results = Some(redacted_results);
+    Cow::Owned(redacted)
+}
+
+/// Scrub every string in a JSON value in place, adding what was found to `report`
+pub fn redact_value(config: &RedactionConfig, value: &mut serde_json::Value, location: &str, report: &mut RedactionReport) {
+    match value {
+        serde_json::Value::String(text) => {
+            let (redacted, findings) = redact_text(config, text, location);
+            *text = redacted;
+            findings.into_iter().for_each(|finding| report.add(finding));
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                redact_value(config, item, &format!("{}[{}]", location, index), report);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                redact_value(config, item, &format!("{}.{}", location, key), report);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn find_spans(config: &RedactionConfig, text: &str) -> Vec<(RedactionCategory, usize, usize)> {
+    let mut spans = Vec::new();
+    let enabled = |category| config.categories.contains(&category);
+
+    if enabled(RedactionCategory::Email) {
+        spans.extend(EMAIL.find_iter(text).map(|m| (RedactionCategory::Email, m.start(), m.end())));
+    }
+    if enabled(RedactionCategory::NationalId) {
+        spans.extend(NATIONAL_ID.find_iter(text).map(|m| (RedactionCategory::NationalId, m.start(), m.end())));
+    }
+    if enabled(RedactionCategory::PaymentCard) {
+        spans.extend(PAYMENT_CARD.find_iter(text)
+            .filter(|m| passes_luhn(m.as_str()))
+            .map(|m| (RedactionCategory::PaymentCard, m.start(), m.end())));
+    }
+    if enabled(RedactionCategory::IpAddress) {
+        spans.extend(IP_ADDRESS.find_iter(text).map(|m| (RedactionCategory::IpAddress, m.start(), m.end())));
+    }
+    if enabled(RedactionCategory::Phone) {
+        // Short digit groups are more often figures or dates than phone numbers
+        spans.extend(PHONE.find_iter(text)
+            .filter(|m| m.as_str().chars().filter(char::is_ascii_digit).count() >= 9)
+            .map(|m| (RedactionCategory::Phone, m.start(), m.end())));
+    }
+    if enabled(RedactionCategory::PersonName) {
+        spans.extend(HONORIFIC_NAME.find_iter(text).map(|m| (RedactionCategory::PersonName, m.start(), m.end())));
+        spans.extend(CUED_NAME.captures_iter(text)
+            .filter_map(|captures| captures.get(1))
+            .map(|m| (RedactionCategory::PersonName, m.start(), m.end())));
+    }
+
+    spans
+}
+
+fn passes_luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits.iter().rev().enumerate()
+        .map(|(index, &digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+fn mask(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}", first, "*".repeat(chars.count().min(12))),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_text_replaces_enabled_categories() {
+        let config = RedactionConfig { enabled: true, categories: RedactionCategory::ALL.into_iter().collect() };
+        let text = "Written by Jane Doe. Email jane.doe@example.org or call +1 415-555-0132. \
+                    Dr. Alan Turing noted a 12% rise in 2024-05-01 figures; card 4111 1111 1111 1111.";
+
+        let (redacted, findings) = redact_text(&config, text, "content");
+        assert!(!redacted.contains("jane.doe@example.org"));
+        assert!(!redacted.contains("555-0132"));
+        assert!(!redacted.contains("Jane Doe"));
+        assert!(!redacted.contains("Alan Turing"));
+        assert!(!redacted.contains("4111"));
+        assert!(redacted.contains("[REDACTED_EMAIL]"));
+        assert!(redacted.contains("2024-05-01"));
+        assert!(redacted.contains("12%"));
+        assert_eq!(findings.len(), 5);
+        assert!(findings.iter().all(|finding| !finding.preview.contains("example.org")));
+
+        let emails_only = RedactionConfig {
+            enabled: true,
+            categories: [RedactionCategory::Email].into_iter().collect(),
+        };
+        let (redacted, findings) = redact_text(&emails_only, text, "content");
+        assert_eq!(findings.len(), 1);
+        assert!(redacted.contains("Jane Doe"));
+
+        let disabled = RedactionConfig { enabled: false, ..RedactionConfig::default() };
+        assert_eq!(redact_text(&disabled, text, "content").0, text);
+
+        // The defaults leave figures that merely look like phone numbers or addresses alone
+        let (redacted, _) = redact_text(&RedactionConfig::default(), "Installs rose 415 555 0132-fold on 10.0.0.1", "content");
+        assert!(redacted.contains("415 555 0132"));
+        assert!(redacted.contains("10.0.0.1"));
+        assert!(!redact_text(&RedactionConfig::default(), text, "content").0.contains("jane.doe@example.org"));
+    }
+}
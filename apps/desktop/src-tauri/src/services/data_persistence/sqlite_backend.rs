@@ -0,0 +1,1684 @@
+use std::path::{Path, PathBuf};
+use parking_lot::Mutex;
+use tracing::{debug, error, warn};
+use rusqlite::{Connection, params};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppResult, StorageError};
+use crate::models::{ApiKey, audit::AuditEvent};
+use crate::models::api_key::ApiKeyStatus;
+use crate::models::research_workflow::ResearchWorkflow;
+use crate::models::research_schedule::{ResearchSchedule, ScheduleRun};
+use crate::models::prompt_template::{PromptExperiment, PromptTemplate};
+use crate::models::idempotency::IdempotencyRecord;
+use crate::models::saga::SagaState;
+use crate::models::notification::{Notification, NotificationPreferences};
+use crate::models::ai_marketplace::{ContentKind, InstalledContent};
+use crate::models::workflow_rating::{QueryDomain, WorkflowRating};
+use crate::models::usage_report::{KeyUsageTotals, ProviderUsageSnapshot, UsageDivergence};
+use crate::models::execution_metrics::StepExecutionMetrics;
+use crate::models::pagination::{Page, PagePosition};
+use super::backend::{DatabaseBackendKind, StorageBackend};
+use super::database_encryption::{self, DatabaseKeySource};
+use super::history_store::HistoryRecord;
+use super::key_rotation::{ApiKeyCiphertext, ApiKeyReencryption};
+use super::migrations::{MigrationMode, MigrationReport, MigrationRunner, MIGRATIONS};
+use super::workflow_search::{self, WorkflowOrganization, WorkflowSearchFilters, WorkflowSearchMatch};
+
+/// SQLite storage backend backed by a single local database file
+pub struct SqliteBackend {
+    db_path: PathBuf,
+    connection: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    /// Open (or create) the database file, encrypted at rest when a key source is given
+    pub fn open(db_path: &Path, encryption: Option<&DatabaseKeySource>) -> AppResult<Self> {
+        debug!("Opening SQLite database at {:?}", db_path);
+
+        let conn = match encryption {
+            Some(key_source) => database_encryption::open_encrypted(db_path, key_source)?,
+            None => Connection::open(db_path)
+                .map_err(|e| StorageError::Database { message: e.to_string() })?,
+        };
+
+        Ok(Self {
+            db_path: db_path.to_path_buf(),
+            connection: Mutex::new(conn),
+        })
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for SqliteBackend {
+    fn kind(&self) -> DatabaseBackendKind {
+        DatabaseBackendKind::Sqlite
+    }
+
+    async fn rotate_encryption_key(&self, old: &DatabaseKeySource, new: &DatabaseKeySource) -> AppResult<()> {
+        let conn = self.connection.lock();
+        database_encryption::rotate_key(&conn, &self.db_path, old, new)
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> AppResult<MigrationReport> {
+        let conn = self.connection.lock();
+        MigrationRunner::new(MIGRATIONS)?.run(&conn, mode)
+    }
+
+    /// Store an API key
+    async fn store_api_key(&self, api_key: &ApiKey) -> AppResult<()> {
+        debug!("Storing API key: {}", api_key.id);
+
+        let conn = self.connection.lock();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO api_keys (
+                id, service, name, encrypted_key, usage_count, rate_limit,
+                reset_period, last_used, last_reset, status, key_version,
+                monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+            params![
+                api_key.id.to_string(),
+                format!("{:?}", api_key.service),
+                api_key.name,
+                api_key.encrypted_key.as_bytes(),
+                api_key.usage_count,
+                api_key.rate_limit,
+                format!("{:?}", api_key.reset_period),
+                api_key.last_used.map(|dt| dt.to_rfc3339()),
+                api_key.last_reset.to_rfc3339(),
+                format!("{:?}", api_key.status),
+                crate::services::security::key_version_of(&api_key.encrypted_key),
+                api_key.monthly_quota,
+                api_key.quota_usage,
+                api_key.quota_period_start.to_rfc3339(),
+                api_key.expires_at.map(|dt| dt.to_rfc3339()),
+                api_key.tenant_id.map(|id| id.to_string()),
+                // Written as RFC 3339 like the other timestamps; the column defaults are
+                // not, and replacing the row would otherwise move the key in paged lists
+                api_key.created_at.to_rfc3339(),
+                Utc::now().to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        debug!("API key stored successfully");
+        Ok(())
+    }
+
+    /// Get all API keys
+    async fn get_all_api_keys(&self) -> AppResult<Vec<ApiKey>> {
+        debug!("Retrieving all API keys");
+
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM api_keys ORDER BY created_at DESC", API_KEY_COLUMNS)
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let key_iter = stmt.query_map([], api_key_columns)
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut api_keys = Vec::new();
+        for key_result in key_iter {
+            let columns = key_result.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            api_keys.extend(api_key_from_columns(columns)?);
+        }
+
+        debug!("Retrieved {} API keys", api_keys.len());
+        Ok(api_keys)
+    }
+
+    async fn get_api_keys_page(&self, tenant_id: Option<Uuid>, include_system_pool: bool, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<ApiKey>> {
+        let conn = self.connection.lock();
+        let tenant_id = tenant_id.map(|id| id.to_string());
+        // Timestamps are RFC 3339 text in UTC, so they sort as they compare
+        let after_created_at = after.map(|after| after.created_at.to_rfc3339());
+        let after_id = after.map(|after| after.id.clone());
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM api_keys
+             WHERE (tenant_id = ?1 OR (?2 AND tenant_id IS NULL))
+               AND (?3 IS NULL OR created_at < ?3 OR (created_at = ?3 AND id < ?4))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?5",
+            API_KEY_COLUMNS
+        )).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(
+            params![tenant_id, include_system_pool, after_created_at, after_id, limit + 1],
+            api_key_columns,
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let total_count: u64 = conn.query_row(
+            "SELECT COUNT(*) FROM api_keys WHERE tenant_id = ?1 OR (?2 AND tenant_id IS NULL)",
+            params![tenant_id, include_system_pool],
+            |row| row.get(0),
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Page::from_stored_rows(rows, limit, total_count, |columns| stored_position(&columns.10, &columns.0), api_key_from_columns)
+    }
+
+    async fn get_api_key_ciphertexts(&self) -> AppResult<Vec<ApiKeyCiphertext>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT id, encrypted_key, key_version FROM api_keys")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (id_str, encrypted_key, key_version) = row
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+            records.push(ApiKeyCiphertext {
+                id: Uuid::parse_str(&id_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                encrypted_key: String::from_utf8(encrypted_key)
+                    .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?,
+                key_version,
+            });
+        }
+
+        Ok(records)
+    }
+
+    async fn replace_api_key_ciphertexts(&self, updates: &[ApiKeyReencryption]) -> AppResult<()> {
+        let mut conn = self.connection.lock();
+
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+
+        for update in updates {
+            let rows_affected = tx.execute(
+                "UPDATE api_keys SET encrypted_key = ?1, key_version = ?2, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?3 AND encrypted_key = ?4",
+                params![
+                    update.encrypted_key.as_bytes(),
+                    update.key_version,
+                    update.id.to_string(),
+                    update.previous_encrypted_key.as_bytes(),
+                ],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+            // Dropping the transaction rolls back every update made so far
+            if rows_affected != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key {} changed during re-encryption", update.id)
+                }.into());
+            }
+        }
+
+        tx.commit().map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+        Ok(())
+    }
+
+    /// Delete an API key
+    async fn delete_api_key(&self, key_id: Uuid) -> AppResult<()> {
+        debug!("Deleting API key: {}", key_id);
+
+        let conn = self.connection.lock();
+
+        let rows_affected = conn.execute(
+            "DELETE FROM api_keys WHERE id = ?1",
+            params![key_id.to_string()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        if rows_affected == 0 {
+            return Err(StorageError::Database {
+                message: format!("API key with ID {} not found", key_id)
+            }.into());
+        }
+
+        debug!("API key deleted successfully");
+        Ok(())
+    }
+
+    async fn update_api_key_statuses(&self, key_ids: &[Uuid], status: &ApiKeyStatus) -> AppResult<()> {
+        debug!("Setting status {:?} on {} API keys", status, key_ids.len());
+
+        let mut conn = self.connection.lock();
+
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+
+        for key_id in key_ids {
+            let rows_affected = tx.execute(
+                "UPDATE api_keys SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                params![format!("{:?}", status), key_id.to_string()],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+            // Dropping the transaction rolls back every update made so far
+            if rows_affected != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key with ID {} not found", key_id)
+                }.into());
+            }
+        }
+
+        tx.commit().map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+        Ok(())
+    }
+
+    async fn delete_api_keys(&self, key_ids: &[Uuid]) -> AppResult<()> {
+        debug!("Deleting {} API keys", key_ids.len());
+
+        let mut conn = self.connection.lock();
+
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+
+        for key_id in key_ids {
+            let rows_affected = tx.execute(
+                "DELETE FROM api_keys WHERE id = ?1",
+                params![key_id.to_string()],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+            if rows_affected != 1 {
+                return Err(StorageError::TransactionFailed {
+                    message: format!("API key with ID {} not found", key_id)
+                }.into());
+            }
+        }
+
+        tx.commit().map_err(|e| StorageError::TransactionFailed { message: e.to_string() })?;
+        Ok(())
+    }
+
+    /// Get API key by ID
+    async fn get_api_key_by_id(&self, key_id: Uuid) -> AppResult<Option<ApiKey>> {
+        debug!("Retrieving API key by ID: {}", key_id);
+
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, service, name, encrypted_key, usage_count, rate_limit,
+                    reset_period, last_used, last_reset, status, created_at, updated_at,
+                    monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id
+             FROM api_keys WHERE id = ?1"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut key_iter = stmt.query_map([key_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,  // id
+                row.get::<_, String>(1)?,  // service
+                row.get::<_, String>(2)?,  // name
+                row.get::<_, Vec<u8>>(3)?, // encrypted_key
+                row.get::<_, u32>(4)?,     // usage_count
+                row.get::<_, u32>(5)?,     // rate_limit
+                row.get::<_, String>(6)?,  // reset_period
+                row.get::<_, Option<String>>(7)?, // last_used
+                row.get::<_, String>(8)?,  // last_reset
+                row.get::<_, String>(9)?,  // status
+                row.get::<_, String>(10)?, // created_at
+                row.get::<_, String>(11)?, // updated_at
+                row.get::<_, Option<u32>>(12)?, // monthly_quota
+                row.get::<_, u32>(13)?,    // quota_usage
+                row.get::<_, Option<String>>(14)?, // quota_period_start
+                row.get::<_, Option<String>>(15)?, // expires_at
+                row.get::<_, Option<String>>(16)?, // tenant_id
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        if let Some(key_result) = key_iter.next() {
+            let (id_str, service_str, name, encrypted_key_bytes, usage_count, rate_limit,
+                 reset_period_str, last_used_str, last_reset_str, status_str, created_at_str, updated_at_str,
+                 monthly_quota, quota_usage, quota_period_start_str, expires_at_str, tenant_id_str) =
+                key_result.map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+            // Parse the data
+            let id = id_str.parse()
+                .map_err(|_| StorageError::Database { message: "Invalid UUID in API key".to_string() })?;
+
+            let encrypted_key = String::from_utf8(encrypted_key_bytes)
+                .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?;
+
+            let last_used = if let Some(last_used_str) = last_used_str {
+                Some(chrono::DateTime::parse_from_rfc3339(&last_used_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid last_used timestamp".to_string() })?
+                    .with_timezone(&Utc))
+            } else {
+                None
+            };
+
+            let last_reset = chrono::DateTime::parse_from_rfc3339(&last_reset_str)
+                .map_err(|_| StorageError::Database { message: "Invalid last_reset timestamp".to_string() })?
+                .with_timezone(&Utc);
+
+            let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
+                .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp".to_string() })?
+                .with_timezone(&Utc);
+
+            let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
+                .map_err(|_| StorageError::Database { message: "Invalid updated_at timestamp".to_string() })?
+                .with_timezone(&Utc);
+
+            // Keys stored before quotas were tracked start their period at creation
+            let quota_period_start = match quota_period_start_str {
+                Some(quota_period_start_str) => chrono::DateTime::parse_from_rfc3339(&quota_period_start_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid quota_period_start timestamp".to_string() })?
+                    .with_timezone(&Utc),
+                None => created_at,
+            };
+
+            let expires_at = match expires_at_str {
+                Some(expires_at_str) => Some(chrono::DateTime::parse_from_rfc3339(&expires_at_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid expires_at timestamp".to_string() })?
+                    .with_timezone(&Utc)),
+                None => None,
+            };
+
+            let tenant_id = tenant_id_str
+                .map(|tenant_id_str| tenant_id_str.parse())
+                .transpose()
+                .map_err(|_| StorageError::Database { message: "Invalid tenant UUID in API key".to_string() })?;
+
+            // Parse enums properly
+            use crate::models::api_key::{ServiceProvider, ResetPeriod, ApiKeyStatus};
+
+            let service = ServiceProvider::from_str(&service_str)
+                .ok_or_else(|| StorageError::Database { message: format!("Unknown service provider '{}'", service_str) })?;
+
+            let reset_period = ResetPeriod::from_str(&reset_period_str)
+                .unwrap_or(ResetPeriod::Daily);
+
+            let status = ApiKeyStatus::from_str(&status_str)
+                .unwrap_or(ApiKeyStatus::Active);
+
+            Ok(Some(ApiKey {
+                id,
+                service,
+                name,
+                encrypted_key,
+                usage_count,
+                rate_limit,
+                reset_period,
+                last_used,
+                last_reset,
+                status,
+                created_at,
+                updated_at,
+                monthly_quota,
+                quota_usage,
+                quota_period_start,
+                expires_at,
+                tenant_id,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Store a research workflow and refresh its search index entry
+    async fn save_research_workflow(&self, workflow: &ResearchWorkflow) -> AppResult<()> {
+        debug!("Saving research workflow: {}", workflow.id);
+
+        let conn = self.connection.lock();
+
+        let parameters_json = serde_json::to_string(&workflow.parameters)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize parameters: {}", e) })?;
+        let results_json = workflow.results.as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize results: {}", e) })?;
+        let tags_json = serde_json::to_string(&workflow.tags)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize tags: {}", e) })?;
+
+        conn.execute("BEGIN TRANSACTION", [])
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let result: AppResult<()> = conn.execute(
+            "INSERT OR REPLACE INTO research_workflows (
+                id, name, query, status, methodology, parameters, results,
+                created_at, updated_at, completed_at, folder, tags
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, CURRENT_TIMESTAMP, ?9, ?10, ?11)",
+            params![
+                workflow.id.to_string(),
+                workflow.name,
+                workflow.query,
+                format!("{:?}", workflow.status),
+                format!("{:?}", workflow.parameters.methodology),
+                parameters_json,
+                results_json,
+                workflow.created_at.to_rfc3339(),
+                workflow.completed_at.map(|dt| dt.to_rfc3339()),
+                workflow.folder,
+                tags_json,
+            ],
+        )
+        .map_err(|e| StorageError::Database { message: e.to_string() }.into())
+        .and_then(|_| workflow_search::index_workflow(&conn, workflow));
+
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])
+                    .map_err(|e| StorageError::Database { message: e.to_string() })?;
+                debug!("Research workflow saved successfully");
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to save research workflow {}: {}", workflow.id, e);
+                let _ = conn.execute("ROLLBACK", []);
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a research workflow and its search index entry
+    async fn delete_research_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
+        debug!("Deleting research workflow: {}", workflow_id);
+
+        let conn = self.connection.lock();
+
+        conn.execute(
+            "DELETE FROM research_workflows WHERE id = ?1",
+            params![workflow_id.to_string()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        conn.execute(
+            "DELETE FROM step_execution_metrics WHERE workflow_id = ?1",
+            params![workflow_id.to_string()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        // The delete trigger covers indexed rows; this also clears entries for
+        // workflows that were indexed without a backing row
+        workflow_search::remove_workflow(&conn, workflow_id)?;
+
+        debug!("Research workflow deleted successfully");
+        Ok(())
+    }
+
+    async fn get_research_workflow_ids_page(&self, after: Option<&PagePosition>, limit: u32) -> AppResult<Page<Uuid>> {
+        let conn = self.connection.lock();
+        let after_created_at = after.map(|after| after.created_at.to_rfc3339());
+        let after_id = after.map(|after| after.id.clone());
+
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at FROM research_workflows
+             WHERE ?1 IS NULL OR created_at < ?1 OR (created_at = ?1 AND id < ?2)
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?3"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(
+            params![after_created_at, after_id, limit + 1],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let total_count: u64 = conn.query_row("SELECT COUNT(*) FROM research_workflows", [], |row| row.get(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Page::from_stored_rows(rows, limit, total_count, |(id, created_at)| stored_position(created_at, id), |(id, _)| {
+            Uuid::parse_str(&id)
+                .map(Some)
+                .map_err(|_| StorageError::Database { message: "Invalid UUID in research workflow".to_string() }.into())
+        })
+    }
+
+    /// Full-text search across stored workflows' names, queries, summaries and sources
+    async fn search_workflows(&self, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        debug!("Searching workflows with query: {}", query);
+
+        let conn = self.connection.lock();
+
+        let matches = workflow_search::search(&conn, query, filters)?;
+
+        debug!("Found {} workflows matching query", matches.len());
+        Ok(matches)
+    }
+
+    /// Stored workflows matching the filters, newest first
+    async fn browse_workflows(&self, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+        let conn = self.connection.lock();
+        workflow_search::browse(&conn, filters)
+    }
+
+    /// Folders and tags in use, with workflow counts
+    async fn get_workflow_organization(&self) -> AppResult<WorkflowOrganization> {
+        let conn = self.connection.lock();
+        workflow_search::organization(&conn)
+    }
+
+    /// Record API usage statistics
+    async fn record_api_usage(&self,
+        api_key_id: Uuid,
+        service: &str,
+        endpoint: Option<&str>,
+        success: bool,
+        response_time_ms: u32
+    ) -> AppResult<()> {
+        debug!("Recording API usage for key: {}", api_key_id);
+
+        let conn = self.connection.lock();
+
+        let date_bucket = Utc::now().format("%Y-%m-%d").to_string();
+        let endpoint_str = endpoint.unwrap_or("unknown");
+
+        // Try to update existing record first
+        let rows_affected = conn.execute(
+            "UPDATE api_usage_stats SET
+                request_count = request_count + 1,
+                success_count = success_count + CASE WHEN ?5 THEN 1 ELSE 0 END,
+                error_count = error_count + CASE WHEN ?5 THEN 0 ELSE 1 END,
+                total_response_time_ms = total_response_time_ms + ?6,
+                avg_response_time_ms = CAST(total_response_time_ms + ?6 AS REAL) / (request_count + 1),
+                last_used = CURRENT_TIMESTAMP
+             WHERE api_key_id = ?1 AND service = ?2 AND endpoint = ?3 AND date_bucket = ?4",
+            params![
+                api_key_id.to_string(),
+                service,
+                endpoint_str,
+                date_bucket,
+                success,
+                response_time_ms
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        // If no existing record, create new one
+        if rows_affected == 0 {
+            conn.execute(
+                "INSERT INTO api_usage_stats (
+                    id, api_key_id, service, endpoint, request_count, success_count,
+                    error_count, total_response_time_ms, avg_response_time_ms, date_bucket
+                ) VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7, ?7, ?8)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    api_key_id.to_string(),
+                    service,
+                    endpoint_str,
+                    if success { 1 } else { 0 },
+                    if success { 0 } else { 1 },
+                    response_time_ms,
+                    date_bucket
+                ],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        }
+
+        debug!("API usage recorded successfully");
+        Ok(())
+    }
+
+    /// Store audit event
+    async fn store_audit_event(&self, event: &AuditEvent) -> AppResult<()> {
+        debug!("Storing audit event: {} - {}", event.event_type, event.description);
+
+        let conn = self.connection.lock();
+
+        // Serialize metadata to JSON
+        let metadata_json = serde_json::to_string(&event.metadata)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize metadata: {}", e) })?;
+
+        // Insert audit event into database
+        conn.execute(
+            "INSERT INTO audit_events (
+                id, event_type, description, resource_id, user_id,
+                timestamp, severity, metadata
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                event.id.to_string(),
+                event.event_type,
+                event.description,
+                event.resource_id,
+                event.user_id,
+                event.timestamp.to_rfc3339(),
+                event.severity.as_str(),
+                metadata_json
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        debug!("Audit event stored successfully: {}", event.id);
+        Ok(())
+    }
+
+    async fn save_research_schedule(&self, schedule: &ResearchSchedule) -> AppResult<()> {
+        debug!("Saving research schedule: {}", schedule.id);
+
+        let definition = serde_json::to_string(schedule)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize schedule: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO research_schedules (
+                id, name, paused, next_run_at, definition, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)",
+            params![
+                schedule.id.to_string(),
+                schedule.name,
+                schedule.paused,
+                schedule.next_run_at.map(|dt| dt.to_rfc3339()),
+                definition,
+                schedule.created_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_research_schedules(&self) -> AppResult<Vec<ResearchSchedule>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM research_schedules ORDER BY created_at")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut schedules = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            schedules.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize schedule: {}", e) })?);
+        }
+
+        Ok(schedules)
+    }
+
+    async fn delete_research_schedule(&self, schedule_id: Uuid) -> AppResult<()> {
+        debug!("Deleting research schedule: {}", schedule_id);
+
+        let mut conn = self.connection.lock();
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        tx.execute("DELETE FROM research_schedule_runs WHERE schedule_id = ?1", params![schedule_id.to_string()])
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        tx.execute("DELETE FROM research_schedules WHERE id = ?1", params![schedule_id.to_string()])
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        tx.commit().map_err(|e| StorageError::Database { message: e.to_string() })?;
+        Ok(())
+    }
+
+    async fn save_schedule_run(&self, run: &ScheduleRun) -> AppResult<()> {
+        debug!("Saving run {} of schedule {}", run.id, run.schedule_id);
+
+        let details = serde_json::to_string(run)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize schedule run: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO research_schedule_runs (id, schedule_id, status, started_at, details)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run.id.to_string(),
+                run.schedule_id.to_string(),
+                run.status.as_str(),
+                run.started_at.to_rfc3339(),
+                details,
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_schedule_runs(&self, schedule_id: Uuid, limit: u32) -> AppResult<Vec<ScheduleRun>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT details FROM research_schedule_runs
+             WHERE schedule_id = ?1
+             ORDER BY started_at DESC
+             LIMIT ?2"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(params![schedule_id.to_string(), limit], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        collect_schedule_runs(rows)
+    }
+
+    async fn get_running_schedule_runs(&self) -> AppResult<Vec<ScheduleRun>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT details FROM research_schedule_runs WHERE status = 'running' ORDER BY started_at"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        collect_schedule_runs(rows)
+    }
+
+    async fn save_prompt_template(&self, template: &PromptTemplate) -> AppResult<()> {
+        debug!("Saving prompt template: {}", template.label());
+
+        let definition = serde_json::to_string(template)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize prompt template: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO prompt_templates (id, version, definition, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                template.id,
+                template.version,
+                definition,
+                template.created_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_prompt_templates(&self) -> AppResult<Vec<PromptTemplate>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM prompt_templates ORDER BY id, version")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut templates = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            templates.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize prompt template: {}", e) })?);
+        }
+
+        Ok(templates)
+    }
+
+    async fn save_prompt_experiment(&self, experiment: &PromptExperiment) -> AppResult<()> {
+        debug!("Saving prompt experiment on: {}", experiment.prompt_id);
+
+        let definition = serde_json::to_string(experiment)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize prompt experiment: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO prompt_experiments (prompt_id, active, definition, updated_at)
+             VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)",
+            params![experiment.prompt_id, experiment.active, definition],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_prompt_experiments(&self) -> AppResult<Vec<PromptExperiment>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM prompt_experiments ORDER BY prompt_id")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut experiments = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            experiments.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize prompt experiment: {}", e) })?);
+        }
+
+        Ok(experiments)
+    }
+
+    async fn save_idempotency_record(&self, record: &IdempotencyRecord) -> AppResult<()> {
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO workflow_idempotency_keys (key, workflow_id, request_fingerprint, created_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.key,
+                record.workflow_id.to_string(),
+                record.request_fingerprint,
+                record.created_at.to_rfc3339(),
+                record.expires_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT workflow_id, request_fingerprint, created_at, expires_at
+             FROM workflow_idempotency_keys WHERE key = ?1"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let mut rows = stmt.query_map([key], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let Some(row) = rows.next() else { return Ok(None) };
+        let (workflow_id_str, request_fingerprint, created_at_str, expires_at_str) =
+            row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let timestamp = |value: &str| DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|_| StorageError::Database { message: "Invalid idempotency key timestamp".to_string() });
+
+        Ok(Some(IdempotencyRecord {
+            key: key.to_string(),
+            workflow_id: workflow_id_str.parse()
+                .map_err(|_| StorageError::Database { message: "Invalid UUID in idempotency key".to_string() })?,
+            request_fingerprint,
+            created_at: timestamp(&created_at_str)?,
+            expires_at: timestamp(&expires_at_str)?,
+        }))
+    }
+
+    async fn purge_expired_idempotency_records(&self, now: DateTime<Utc>) -> AppResult<u64> {
+        let conn = self.connection.lock();
+        let removed = conn.execute(
+            "DELETE FROM workflow_idempotency_keys WHERE expires_at <= ?1",
+            params![now.to_rfc3339()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(removed as u64)
+    }
+
+    async fn save_saga_state(&self, saga: &SagaState) -> AppResult<()> {
+        let definition = serde_json::to_string(saga)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize saga: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO workflow_sagas (id, workflow_id, status, definition, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                saga.id.to_string(),
+                saga.workflow_id.to_string(),
+                saga.status.as_str(),
+                definition,
+                saga.updated_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_unfinished_sagas(&self) -> AppResult<Vec<SagaState>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT definition FROM workflow_sagas
+             WHERE status IN ('running', 'compensating') ORDER BY updated_at"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut sagas = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            sagas.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize saga: {}", e) })?);
+        }
+
+        Ok(sagas)
+    }
+
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> AppResult<()> {
+        let definition = serde_json::to_string(preferences)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification preferences: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO notification_preferences (user_id, tenant_id, definition, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                preferences.user_id.to_string(),
+                preferences.tenant_id.map(|id| id.to_string()),
+                definition,
+                preferences.updated_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_notification_preferences(&self, user_id: Uuid) -> AppResult<Option<NotificationPreferences>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM notification_preferences WHERE user_id = ?1")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let mut rows = stmt.query_map(params![user_id.to_string()], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        match rows.next() {
+            Some(row) => {
+                let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+                Ok(Some(serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification preferences: {}", e) })?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_all_notification_preferences(&self) -> AppResult<Vec<NotificationPreferences>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM notification_preferences ORDER BY user_id")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut preferences = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            preferences.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification preferences: {}", e) })?);
+        }
+
+        Ok(preferences)
+    }
+
+    async fn hold_notification(&self, user_id: Uuid, notification: &Notification) -> AppResult<()> {
+        let definition = serde_json::to_string(notification)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT INTO held_notifications (id, user_id, definition, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                Uuid::new_v4().to_string(),
+                user_id.to_string(),
+                definition,
+                notification.created_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn take_held_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        let mut conn = self.connection.lock();
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut notifications = Vec::new();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT definition FROM held_notifications WHERE user_id = ?1 ORDER BY created_at"
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+            let rows = stmt.query_map(params![user_id.to_string()], |row| row.get::<_, String>(0))
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+            for row in rows {
+                let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+                notifications.push(serde_json::from_str(&definition)
+                    .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification: {}", e) })?);
+            }
+        }
+        tx.execute("DELETE FROM held_notifications WHERE user_id = ?1", params![user_id.to_string()])
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        tx.commit().map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(notifications)
+    }
+
+    async fn add_inbox_notifications(&self, user_id: Uuid, notifications: &[Notification], keep: usize) -> AppResult<()> {
+        let mut conn = self.connection.lock();
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        for notification in notifications {
+            let definition = serde_json::to_string(notification)
+                .map_err(|e| StorageError::Database { message: format!("Failed to serialize notification: {}", e) })?;
+            tx.execute(
+                "INSERT OR REPLACE INTO notification_inbox (id, user_id, definition, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    notification.id.to_string(),
+                    user_id.to_string(),
+                    definition,
+                    notification.created_at.to_rfc3339(),
+                ],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        }
+        tx.execute(
+            "DELETE FROM notification_inbox WHERE user_id = ?1 AND id NOT IN (
+                SELECT id FROM notification_inbox WHERE user_id = ?1 ORDER BY created_at DESC LIMIT ?2
+            )",
+            params![user_id.to_string(), keep as i64],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        tx.commit().map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_inbox_notifications(&self, user_id: Uuid) -> AppResult<Vec<Notification>> {
+        let conn = self.connection.lock();
+        let mut stmt = conn.prepare(
+            "SELECT definition FROM notification_inbox WHERE user_id = ?1 ORDER BY created_at DESC"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(params![user_id.to_string()], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut notifications = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            notifications.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize notification: {}", e) })?);
+        }
+
+        Ok(notifications)
+    }
+
+    async fn save_marketplace_installation(&self, installation: &InstalledContent) -> AppResult<()> {
+        let definition = serde_json::to_string(installation)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize marketplace installation: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO marketplace_installations (user_id, content_kind, content_id, definition, installed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                installation.user_id.to_string(),
+                installation.kind.as_str(),
+                installation.content_id.to_string(),
+                definition,
+                installation.installed_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_marketplace_installations(&self, user_id: Uuid) -> AppResult<Vec<InstalledContent>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT definition FROM marketplace_installations WHERE user_id = ?1 ORDER BY installed_at"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(params![user_id.to_string()], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut installations = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            installations.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize marketplace installation: {}", e) })?);
+        }
+
+        Ok(installations)
+    }
+
+    async fn delete_marketplace_installation(&self, user_id: Uuid, kind: ContentKind, content_id: Uuid) -> AppResult<bool> {
+        let conn = self.connection.lock();
+        let deleted = conn.execute(
+            "DELETE FROM marketplace_installations WHERE user_id = ?1 AND content_kind = ?2 AND content_id = ?3",
+            params![user_id.to_string(), kind.as_str(), content_id.to_string()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn save_workflow_rating(&self, rating: &WorkflowRating) -> AppResult<()> {
+        let definition = serde_json::to_string(rating)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize workflow rating: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO workflow_ratings (workflow_id, rated_by, query_domain, definition, rated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                rating.workflow_id.to_string(),
+                rating.rated_by,
+                rating.query_domain.as_str(),
+                definition,
+                rating.rated_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_workflow_ratings(&self, rated_by: &str, query_domain: QueryDomain) -> AppResult<Vec<WorkflowRating>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT definition FROM workflow_ratings WHERE rated_by = ?1 AND query_domain = ?2 ORDER BY rated_at"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(params![rated_by, query_domain.as_str()], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut ratings = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            ratings.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize workflow rating: {}", e) })?);
+        }
+
+        Ok(ratings)
+    }
+
+    async fn append_history(&self, kind: &str, records: &[HistoryRecord]) -> AppResult<()> {
+        let mut conn = self.connection.lock();
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        for record in records {
+            tx.execute(
+                "INSERT INTO history_entries (kind, definition, recorded_at) VALUES (?1, ?2, ?3)",
+                params![kind, record.definition, record.recorded_at.to_rfc3339()],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        }
+        tx.commit().map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_history(&self, kind: &str, since: Option<DateTime<Utc>>, limit: u32) -> AppResult<Vec<HistoryRecord>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT definition, recorded_at FROM history_entries
+             WHERE kind = ?1 AND (?2 IS NULL OR recorded_at >= ?2)
+             ORDER BY id DESC
+             LIMIT ?3"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(
+            params![kind, since.map(|since| since.to_rfc3339()), limit],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (definition, recorded_at) = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            records.push(HistoryRecord {
+                recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                    .map_err(|e| StorageError::Database { message: format!("Invalid history timestamp: {}", e) })?
+                    .with_timezone(&Utc),
+                definition,
+            });
+        }
+
+        Ok(records)
+    }
+
+    async fn purge_history(&self, kind: &str, before: DateTime<Utc>) -> AppResult<u64> {
+        let conn = self.connection.lock();
+        let removed = conn.execute(
+            "DELETE FROM history_entries WHERE kind = ?1 AND recorded_at < ?2",
+            params![kind, before.to_rfc3339()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(removed as u64)
+    }
+
+    async fn save_step_metrics(&self, metrics: &StepExecutionMetrics) -> AppResult<()> {
+        let definition = serde_json::to_string(metrics)
+            .map_err(|e| StorageError::Database { message: format!("Failed to serialize step metrics: {}", e) })?;
+
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT INTO step_execution_metrics (workflow_id, step_id, definition, started_at) VALUES (?1, ?2, ?3, ?4)",
+            params![metrics.workflow_id.to_string(), metrics.step_id.to_string(), definition, metrics.started_at.to_rfc3339()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_step_metrics(&self, workflow_id: Uuid) -> AppResult<Vec<StepExecutionMetrics>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT definition FROM step_execution_metrics WHERE workflow_id = ?1 ORDER BY started_at, id"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map(params![workflow_id.to_string()], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut metrics = Vec::new();
+        for row in rows {
+            let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+            metrics.push(serde_json::from_str(&definition)
+                .map_err(|e| StorageError::Database { message: format!("Failed to deserialize step metrics: {}", e) })?);
+        }
+
+        Ok(metrics)
+    }
+
+    /// Get usage statistics for an API key
+    async fn get_api_key_usage_stats(&self, api_key_id: Uuid, days: u32) -> AppResult<Vec<(String, u32, u32, u32, f64)>> {
+        debug!("Getting usage statistics for API key: {}", api_key_id);
+
+        let conn = self.connection.lock();
+
+        let start_date = (Utc::now() - chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d").to_string();
+
+        let mut stmt = conn.prepare(
+            "SELECT date_bucket, request_count, success_count, error_count, avg_response_time_ms
+             FROM api_usage_stats
+             WHERE api_key_id = ?1 AND date_bucket >= ?2
+             ORDER BY date_bucket DESC"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let stats_iter = stmt.query_map([api_key_id.to_string(), start_date], |row| {
+            Ok((
+                row.get::<_, String>(0)?,  // date_bucket
+                row.get::<_, u32>(1)?,     // request_count
+                row.get::<_, u32>(2)?,     // success_count
+                row.get::<_, u32>(3)?,     // error_count
+                row.get::<_, f64>(4)?,     // avg_response_time_ms
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut stats = Vec::new();
+        for stat_result in stats_iter {
+            stats.push(stat_result.map_err(|e| StorageError::Database { message: e.to_string() })?);
+        }
+
+        debug!("Retrieved {} usage statistics records", stats.len());
+        Ok(stats)
+    }
+
+    async fn get_service_usage_history(&self, service: &str, days: u32) -> AppResult<Vec<(Uuid, String, u32)>> {
+        debug!("Getting usage history for service: {}", service);
+
+        let conn = self.connection.lock();
+
+        let start_date = (Utc::now() - chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d").to_string();
+
+        let mut stmt = conn.prepare(
+            "SELECT api_key_id, date_bucket, SUM(request_count)
+             FROM api_usage_stats
+             WHERE service = ?1 AND date_bucket >= ?2
+             GROUP BY api_key_id, date_bucket
+             ORDER BY date_bucket ASC"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([service.to_string(), start_date], |row| {
+            Ok((
+                row.get::<_, String>(0)?, // api_key_id
+                row.get::<_, String>(1)?, // date_bucket
+                row.get::<_, u32>(2)?,    // request_count
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut history = Vec::new();
+        for row in rows {
+            let (id_str, date_bucket, request_count) = row
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+            let api_key_id = Uuid::parse_str(&id_str)
+                .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?;
+            history.push((api_key_id, date_bucket, request_count));
+        }
+
+        debug!("Retrieved {} usage history records", history.len());
+        Ok(history)
+    }
+
+    async fn get_key_usage_totals(&self, from: &str, to: &str) -> AppResult<Vec<KeyUsageTotals>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT api_key_id, service, SUM(request_count), SUM(success_count), SUM(error_count)
+             FROM api_usage_stats
+             WHERE date_bucket >= ?1 AND date_bucket <= ?2
+             GROUP BY api_key_id, service
+             ORDER BY service, api_key_id"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut totals = Vec::new();
+        for row in rows {
+            let (id_str, service, request_count, success_count, error_count) = row
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+            totals.push(KeyUsageTotals {
+                api_key_id: Uuid::parse_str(&id_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                service,
+                request_count,
+                success_count,
+                error_count,
+            });
+        }
+        Ok(totals)
+    }
+
+    async fn record_provider_usage_snapshot(&self, snapshot: &ProviderUsageSnapshot) -> AppResult<()> {
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT INTO provider_usage_snapshots
+                (api_key_id, service, date_bucket, reported_used, reported_limit, observed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                snapshot.api_key_id.to_string(),
+                snapshot.service,
+                snapshot.date_bucket,
+                snapshot.reported_used,
+                snapshot.reported_limit,
+                snapshot.observed_at.to_rfc3339(),
+            ],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn record_usage_divergences(&self, divergences: &[UsageDivergence]) -> AppResult<()> {
+        let mut conn = self.connection.lock();
+        let tx = conn.transaction()
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        for divergence in divergences {
+            tx.execute(
+                "INSERT INTO usage_divergence_history (
+                    api_key_id, service, range_start, range_end, counted, reported,
+                    delta, divergence_percent, threshold_exceeded, checked_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    divergence.api_key_id.to_string(),
+                    divergence.service,
+                    divergence.from.format("%Y-%m-%d").to_string(),
+                    divergence.to.format("%Y-%m-%d").to_string(),
+                    divergence.counted,
+                    divergence.reported,
+                    divergence.delta,
+                    divergence.divergence_percent,
+                    divergence.threshold_exceeded,
+                    divergence.checked_at.to_rfc3339(),
+                ],
+            ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        }
+        tx.commit().map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_usage_divergence_history(&self, since: DateTime<Utc>) -> AppResult<Vec<UsageDivergence>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT api_key_id, service, range_start, range_end, counted, reported,
+                    delta, divergence_percent, threshold_exceeded, checked_at
+             FROM usage_divergence_history
+             WHERE checked_at >= ?1
+             ORDER BY checked_at, id"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([since.to_rfc3339()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, u32>(5)?,
+                row.get::<_, i64>(6)?,
+                row.get::<_, f64>(7)?,
+                row.get::<_, bool>(8)?,
+                row.get::<_, String>(9)?,
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let parse_date = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| StorageError::Database { message: e.to_string() });
+        let mut history = Vec::new();
+        for row in rows {
+            let (id_str, service, from, to, counted, reported, delta, divergence_percent, threshold_exceeded, checked_at) = row
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+            history.push(UsageDivergence {
+                api_key_id: Uuid::parse_str(&id_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                service,
+                from: parse_date(&from)?,
+                to: parse_date(&to)?,
+                counted,
+                reported,
+                delta,
+                divergence_percent,
+                threshold_exceeded,
+                checked_at: DateTime::parse_from_rfc3339(&checked_at)
+                    .map_err(|e| StorageError::Database { message: e.to_string() })?
+                    .with_timezone(&Utc),
+            });
+        }
+        Ok(history)
+    }
+
+    async fn get_provider_usage_snapshots(&self, to: &str) -> AppResult<Vec<ProviderUsageSnapshot>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare(
+            "SELECT api_key_id, service, date_bucket, reported_used, reported_limit, observed_at
+             FROM provider_usage_snapshots
+             WHERE date_bucket <= ?1
+             ORDER BY api_key_id, observed_at, id"
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let rows = stmt.query_map([to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, Option<u32>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let (id_str, service, date_bucket, reported_used, reported_limit, observed_at) = row
+                .map_err(|e| StorageError::Database { message: e.to_string() })?;
+            snapshots.push(ProviderUsageSnapshot {
+                api_key_id: Uuid::parse_str(&id_str)
+                    .map_err(|_| StorageError::Database { message: "Invalid UUID format".to_string() })?,
+                service,
+                date_bucket,
+                reported_used,
+                reported_limit,
+                observed_at: DateTime::parse_from_rfc3339(&observed_at)
+                    .map_err(|e| StorageError::Database { message: e.to_string() })?
+                    .with_timezone(&Utc),
+            });
+        }
+        Ok(snapshots)
+    }
+
+    async fn save_service_setting(&self, name: &str, definition: &serde_json::Value) -> AppResult<()> {
+        let conn = self.connection.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO service_settings (name, definition, updated_at) VALUES (?1, ?2, ?3)",
+            params![name, definition.to_string(), Utc::now().to_rfc3339()],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn get_service_setting(&self, name: &str) -> AppResult<Option<serde_json::Value>> {
+        let conn = self.connection.lock();
+
+        let mut stmt = conn.prepare("SELECT definition FROM service_settings WHERE name = ?1")
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let mut rows = stmt.query_map([name], |row| row.get::<_, String>(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let Some(row) = rows.next() else { return Ok(None) };
+        let definition = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+        serde_json::from_str(&definition)
+            .map(Some)
+            .map_err(|e| StorageError::Database { message: format!("Failed to deserialize setting {}: {}", name, e) }.into())
+    }
+
+    async fn health_check(&self) -> AppResult<()> {
+        let conn = self.connection.lock();
+
+        // Test basic query
+        let _result: i64 = conn.query_row("SELECT COUNT(*) FROM api_keys", [], |row| row.get(0))
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        // Check schema migrations are clean and complete
+        let report = MigrationRunner::new(MIGRATIONS)?.validate(&conn, MigrationMode::DryRun)?;
+        if !report.pending.is_empty() {
+            return Err(StorageError::Database {
+                message: format!("{} schema migrations pending", report.pending.len())
+            }.into());
+        }
+
+        // Check database file permissions
+        if !self.db_path.exists() {
+            return Err(StorageError::Database {
+                message: "Database file does not exist".to_string()
+            }.into());
+        }
+
+        // Check available disk space
+        if let Ok(metadata) = std::fs::metadata(&self.db_path) {
+            let file_size = metadata.len();
+            debug!("Database file size: {} bytes", file_size);
+        }
+
+        Ok(())
+    }
+}
+
+/// Columns of `api_keys` that `api_key_columns` reads, in order
+const API_KEY_COLUMNS: &str = "id, service, name, encrypted_key, usage_count, rate_limit,
+    reset_period, last_used, last_reset, status, created_at, updated_at,
+    monthly_quota, quota_usage, quota_period_start, expires_at, tenant_id";
+
+/// An `api_keys` row as stored
+type ApiKeyColumns = (
+    String, String, String, Vec<u8>, u32, u32, String, Option<String>, String, String, String, String,
+    Option<u32>, u32, Option<String>, Option<String>, Option<String>,
+);
+
+fn api_key_columns(row: &rusqlite::Row) -> rusqlite::Result<ApiKeyColumns> {
+    Ok((
+        row.get::<_, String>(0)?,  // id
+        row.get::<_, String>(1)?,  // service
+        row.get::<_, String>(2)?,  // name
+        row.get::<_, Vec<u8>>(3)?, // encrypted_key
+        row.get::<_, u32>(4)?,     // usage_count
+        row.get::<_, u32>(5)?,     // rate_limit
+        row.get::<_, String>(6)?,  // reset_period
+        row.get::<_, Option<String>>(7)?, // last_used
+        row.get::<_, String>(8)?,  // last_reset
+        row.get::<_, String>(9)?,  // status
+        row.get::<_, String>(10)?, // created_at
+        row.get::<_, String>(11)?, // updated_at
+        row.get::<_, Option<u32>>(12)?, // monthly_quota
+        row.get::<_, u32>(13)?,    // quota_usage
+        row.get::<_, Option<String>>(14)?, // quota_period_start
+        row.get::<_, Option<String>>(15)?, // expires_at
+        row.get::<_, Option<String>>(16)?, // tenant_id
+    ))
+}
+
+/// The key a row holds, `None` for a key of a custom provider whose adapter is not
+/// registered; such keys are left out rather than handed to another provider
+fn api_key_from_columns(columns: ApiKeyColumns) -> AppResult<Option<ApiKey>> {
+    let (id_str, service_str, name, encrypted_key_bytes, usage_count, rate_limit,
+         reset_period_str, last_used_str, last_reset_str, status_str, created_at_str, updated_at_str,
+         monthly_quota, quota_usage, quota_period_start_str, expires_at_str, tenant_id_str) = columns;
+
+    // Parse the data (simplified parsing for now)
+    let id = id_str.parse()
+        .map_err(|_| StorageError::Database { message: "Invalid UUID in API key".to_string() })?;
+
+    let encrypted_key = String::from_utf8(encrypted_key_bytes)
+        .map_err(|_| StorageError::Database { message: "Invalid encrypted key format".to_string() })?;
+
+    let last_used = if let Some(last_used_str) = last_used_str {
+        Some(chrono::DateTime::parse_from_rfc3339(&last_used_str)
+            .map_err(|_| StorageError::Database { message: "Invalid last_used timestamp".to_string() })?
+            .with_timezone(&Utc))
+    } else {
+        None
+    };
+
+    let last_reset = chrono::DateTime::parse_from_rfc3339(&last_reset_str)
+        .map_err(|_| StorageError::Database { message: "Invalid last_reset timestamp".to_string() })?
+        .with_timezone(&Utc);
+
+    let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
+        .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp".to_string() })?
+        .with_timezone(&Utc);
+
+    let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
+        .map_err(|_| StorageError::Database { message: "Invalid updated_at timestamp".to_string() })?
+        .with_timezone(&Utc);
+
+    // Keys stored before quotas were tracked start their period at creation
+    let quota_period_start = match quota_period_start_str {
+        Some(quota_period_start_str) => chrono::DateTime::parse_from_rfc3339(&quota_period_start_str)
+            .map_err(|_| StorageError::Database { message: "Invalid quota_period_start timestamp".to_string() })?
+            .with_timezone(&Utc),
+        None => created_at,
+    };
+
+    let expires_at = match expires_at_str {
+        Some(expires_at_str) => Some(chrono::DateTime::parse_from_rfc3339(&expires_at_str)
+            .map_err(|_| StorageError::Database { message: "Invalid expires_at timestamp".to_string() })?
+            .with_timezone(&Utc)),
+        None => None,
+    };
+
+    let tenant_id = tenant_id_str
+        .map(|tenant_id_str| tenant_id_str.parse())
+        .transpose()
+        .map_err(|_| StorageError::Database { message: "Invalid tenant UUID in API key".to_string() })?;
+
+    // Parse enums properly
+    use crate::models::api_key::{ServiceProvider, ResetPeriod, ApiKeyStatus};
+
+    let Some(service) = ServiceProvider::from_str(&service_str) else {
+        warn!("Skipping API key {} of unregistered provider '{}'", id_str, service_str);
+        return Ok(None);
+    };
+
+    let reset_period = ResetPeriod::from_str(&reset_period_str)
+        .unwrap_or(ResetPeriod::Daily);
+
+    let status = ApiKeyStatus::from_str(&status_str)
+        .unwrap_or(ApiKeyStatus::Active);
+
+    Ok(Some(ApiKey {
+        id,
+        service,
+        name,
+        encrypted_key,
+        usage_count,
+        rate_limit,
+        reset_period,
+        last_used,
+        last_reset,
+        status,
+        created_at,
+        updated_at,
+        monthly_quota,
+        quota_usage,
+        quota_period_start,
+        expires_at,
+        tenant_id,
+    }))
+}
+
+/// Where a stored row sits in a list, from its RFC 3339 `created_at` and ID
+fn stored_position(created_at: &str, id: &str) -> AppResult<PagePosition> {
+    let created_at = DateTime::parse_from_rfc3339(created_at)
+        .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp".to_string() })?;
+    Ok(PagePosition::new(created_at.with_timezone(&Utc), id))
+}
+
+fn collect_schedule_runs(rows: impl Iterator<Item = rusqlite::Result<String>>) -> AppResult<Vec<ScheduleRun>> {
+    let mut runs = Vec::new();
+    for row in rows {
+        let details = row.map_err(|e| StorageError::Database { message: e.to_string() })?;
+        runs.push(serde_json::from_str(&details)
+            .map_err(|e| StorageError::Database { message: format!("Failed to deserialize schedule run: {}", e) })?);
+    }
+    Ok(runs)
+}
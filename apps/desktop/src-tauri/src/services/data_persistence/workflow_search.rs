@@ -0,0 +1,455 @@
+use rusqlite::{Connection, params};
+use serde::{Serialize, Deserialize};
+use tracing::debug;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppResult, StorageError};
+use crate::models::data_region::DataRegion;
+use crate::models::research_workflow::{normalize_folder, normalize_tag, ResearchWorkflow};
+
+/// Maximum length of the result content stored in the index as the workflow summary
+const SUMMARY_MAX_CHARS: usize = 4000;
+
+/// Default and maximum number of matches returned by a search
+const DEFAULT_SEARCH_LIMIT: u32 = 25;
+const MAX_SEARCH_LIMIT: u32 = 200;
+
+/// Filters applied to a workflow search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowSearchFilters {
+    pub status: Option<String>,
+    pub methodology: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only workflows carrying every one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only workflows in this folder or its subfolders
+    pub folder: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Region whose database is searched; tenants pinned to a region must pass it
+    #[serde(default)]
+    pub data_region: DataRegion,
+}
+
+/// A ranked workflow search match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSearchMatch {
+    pub workflow_id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub status: String,
+    pub methodology: String,
+    pub created_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    /// Relevance rank; lower is a better match
+    pub rank: f64,
+    /// Highlighted fragment from the best matching column, matches wrapped in `<mark>`
+    pub snippet: String,
+    pub highlighted_name: String,
+}
+
+/// A folder or tag and how many workflows it holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowGroup {
+    pub name: String,
+    pub workflow_count: u32,
+}
+
+/// Every folder and tag in use
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowOrganization {
+    pub folders: Vec<WorkflowGroup>,
+    pub tags: Vec<WorkflowGroup>,
+}
+
+/// Index columns read for a match: id, name, query, status, methodology, created_at,
+/// tags, folder, rank, snippet and highlighted name
+type IndexRow = (String, String, String, String, String, String, String, Option<String>, f64, String, String);
+
+/// Insert or refresh the index entry and tag rows for a workflow
+pub fn index_workflow(conn: &Connection, workflow: &ResearchWorkflow) -> AppResult<()> {
+    debug!("Indexing workflow for search: {}", workflow.id);
+
+    let (summary, sources) = index_fields(workflow);
+
+    conn.execute(
+        "DELETE FROM workflow_search_index WHERE workflow_id = ?1",
+        params![workflow.id.to_string()],
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    conn.execute(
+        "INSERT INTO workflow_search_index (
+            workflow_id, name, query, summary, sources, tags, status, methodology, created_at, folder
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            workflow.id.to_string(),
+            workflow.name,
+            workflow.query,
+            summary,
+            sources,
+            workflow.tags.join("\n"),
+            format!("{:?}", workflow.status),
+            format!("{:?}", workflow.parameters.methodology),
+            workflow.created_at.to_rfc3339(),
+            workflow.folder,
+        ],
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    conn.execute(
+        "DELETE FROM workflow_tags WHERE workflow_id = ?1",
+        params![workflow.id.to_string()],
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    for tag in &workflow.tags {
+        conn.execute(
+            "INSERT OR IGNORE INTO workflow_tags (workflow_id, tag) VALUES (?1, ?2)",
+            params![workflow.id.to_string(), tag],
+        ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    }
+
+    Ok(())
+}
+
+/// Remove a workflow from the index
+pub fn remove_workflow(conn: &Connection, workflow_id: Uuid) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM workflow_search_index WHERE workflow_id = ?1",
+        params![workflow_id.to_string()],
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+    conn.execute(
+        "DELETE FROM workflow_tags WHERE workflow_id = ?1",
+        params![workflow_id.to_string()],
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    Ok(())
+}
+
+/// Run a ranked full-text search over the SQLite FTS5 index
+pub fn search(conn: &Connection, query: &str, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+    let match_expression = build_match_expression(query);
+    if match_expression.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (limit, offset) = page(filters);
+    let (folder, tags) = organization_filters(filters);
+
+    // Column weights favour names, queries and tags over long-form summaries and sources
+    let mut stmt = conn.prepare(
+        "SELECT workflow_id, name, query, status, methodology, created_at, tags, folder,
+                bm25(workflow_search_index, 0.0, 10.0, 5.0, 2.0, 1.0, 8.0) AS score,
+                snippet(workflow_search_index, -1, '<mark>', '</mark>', '…', 24),
+                highlight(workflow_search_index, 1, '<mark>', '</mark>')
+         FROM workflow_search_index
+         WHERE workflow_search_index MATCH ?1
+           AND (?2 IS NULL OR status = ?2)
+           AND (?3 IS NULL OR methodology = ?3)
+           AND (?4 IS NULL OR created_at >= ?4)
+           AND (?5 IS NULL OR created_at <= ?5)
+           AND (?6 IS NULL OR folder = ?6 OR substr(folder, 1, length(?6) + 1) = ?6 || '/')
+           AND (?7 = 0 OR workflow_id IN (
+                SELECT workflow_id FROM workflow_tags
+                WHERE tag IN (SELECT value FROM json_each(?8))
+                GROUP BY workflow_id HAVING COUNT(*) = ?7))
+         ORDER BY score
+         LIMIT ?9 OFFSET ?10"
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    let rows = stmt.query_map(
+        params![
+            match_expression,
+            filters.status,
+            filters.methodology,
+            filters.created_after.map(|dt| dt.to_rfc3339()),
+            filters.created_before.map(|dt| dt.to_rfc3339()),
+            folder,
+            tags.len() as u32,
+            serde_json::to_string(&tags)?,
+            limit,
+            offset,
+        ],
+        index_row,
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    let matches = rows
+        .map(|row| into_match(row.map_err(|e| StorageError::Database { message: e.to_string() })?))
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(matches)
+}
+
+/// Workflows matching the filters without a text query, newest first, for listing a
+/// folder or tag. Matches carry no rank or highlighting.
+pub fn browse(conn: &Connection, filters: &WorkflowSearchFilters) -> AppResult<Vec<WorkflowSearchMatch>> {
+    let (limit, offset) = page(filters);
+    let (folder, tags) = organization_filters(filters);
+
+    let mut stmt = conn.prepare(
+        "SELECT workflow_id, name, query, status, methodology, created_at, tags, folder,
+                0.0, '', name
+         FROM workflow_search_index
+         WHERE (?1 IS NULL OR status = ?1)
+           AND (?2 IS NULL OR methodology = ?2)
+           AND (?3 IS NULL OR created_at >= ?3)
+           AND (?4 IS NULL OR created_at <= ?4)
+           AND (?5 IS NULL OR folder = ?5 OR substr(folder, 1, length(?5) + 1) = ?5 || '/')
+           AND (?6 = 0 OR workflow_id IN (
+                SELECT workflow_id FROM workflow_tags
+                WHERE tag IN (SELECT value FROM json_each(?7))
+                GROUP BY workflow_id HAVING COUNT(*) = ?6))
+         ORDER BY created_at DESC
+         LIMIT ?8 OFFSET ?9"
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    let rows = stmt.query_map(
+        params![
+            filters.status,
+            filters.methodology,
+            filters.created_after.map(|dt| dt.to_rfc3339()),
+            filters.created_before.map(|dt| dt.to_rfc3339()),
+            folder,
+            tags.len() as u32,
+            serde_json::to_string(&tags)?,
+            limit,
+            offset,
+        ],
+        index_row,
+    ).map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+    let matches = rows
+        .map(|row| into_match(row.map_err(|e| StorageError::Database { message: e.to_string() })?))
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(matches)
+}
+
+/// Folders and tags in use, with workflow counts, in name order
+pub fn organization(conn: &Connection) -> AppResult<WorkflowOrganization> {
+    let groups = |sql: &str| -> AppResult<Vec<WorkflowGroup>> {
+        let mut stmt = conn.prepare(sql)
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+        let rows = stmt.query_map([], |row| {
+            Ok(WorkflowGroup { name: row.get(0)?, workflow_count: row.get(1)? })
+        }).map_err(|e| StorageError::Database { message: e.to_string() })?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database { message: e.to_string() }.into())
+    };
+
+    Ok(WorkflowOrganization {
+        folders: groups(
+            "SELECT folder, COUNT(*) FROM workflow_search_index
+             WHERE folder IS NOT NULL GROUP BY folder ORDER BY folder",
+        )?,
+        tags: groups("SELECT tag, COUNT(*) FROM workflow_tags GROUP BY tag ORDER BY tag")?,
+    })
+}
+
+fn index_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<IndexRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+        row.get(7)?,
+        row.get(8)?,
+        row.get(9)?,
+        row.get(10)?,
+    ))
+}
+
+fn into_match(row: IndexRow) -> AppResult<WorkflowSearchMatch> {
+    let (id_str, name, query, status, methodology, created_at_str, tags, folder, rank, snippet, highlighted_name) = row;
+
+    let workflow_id = Uuid::parse_str(&id_str)
+        .map_err(|_| StorageError::Database { message: "Invalid UUID in search index".to_string() })?;
+
+    let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+        .map_err(|_| StorageError::Database { message: "Invalid created_at timestamp in search index".to_string() })?
+        .with_timezone(&Utc);
+
+    Ok(WorkflowSearchMatch {
+        workflow_id,
+        name,
+        query,
+        status,
+        methodology,
+        created_at,
+        tags: split_tags(&tags),
+        folder,
+        rank,
+        snippet,
+        highlighted_name,
+    })
+}
+
+/// Tags as stored in the index, one per line
+pub(crate) fn split_tags(tags: &str) -> Vec<String> {
+    tags.lines().filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+}
+
+/// The folder and tag filters normalized the way workflows store them
+pub(crate) fn organization_filters(filters: &WorkflowSearchFilters) -> (Option<String>, Vec<String>) {
+    let mut tags: Vec<String> = filters.tags.iter().filter_map(|tag| normalize_tag(tag)).collect();
+    tags.sort();
+    tags.dedup();
+    (filters.folder.as_deref().and_then(normalize_folder), tags)
+}
+
+/// Summary and source text indexed for a workflow
+pub(crate) fn index_fields(workflow: &ResearchWorkflow) -> (String, String) {
+    match &workflow.results {
+        Some(results) => (
+            results.content.chars().take(SUMMARY_MAX_CHARS).collect::<String>(),
+            results.sources.join("\n"),
+        ),
+        None => (String::new(), String::new()),
+    }
+}
+
+/// Clamped `(limit, offset)` for a search
+pub(crate) fn page(filters: &WorkflowSearchFilters) -> (u32, u32) {
+    (
+        filters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT),
+        filters.offset.unwrap_or(0),
+    )
+}
+
+/// Whitespace-separated search terms with quotes stripped
+pub(crate) fn search_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Turn free-form user input into a safe FTS5 expression.
+///
+/// Each term is quoted so punctuation and FTS operators in the input cannot
+/// produce syntax errors; the final term is a prefix match to support
+/// search-as-you-type.
+fn build_match_expression(query: &str) -> String {
+    let terms = search_terms(query);
+
+    let last = terms.len().saturating_sub(1);
+    terms.iter()
+        .enumerate()
+        .map(|(i, term)| if i == last { format!("\"{}\"*", term) } else { format!("\"{}\"", term) })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::{ResearchResults, WorkflowParameters, WorkflowStatus};
+    use crate::services::data_persistence::migrations::{MigrationMode, MigrationRunner, MIGRATIONS};
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        MigrationRunner::new(MIGRATIONS).unwrap().run(&conn, MigrationMode::Apply).unwrap();
+        conn
+    }
+
+    fn workflow(name: &str, query: &str, content: &str, sources: &[&str]) -> ResearchWorkflow {
+        let mut workflow = ResearchWorkflow::new(
+            name.to_string(),
+            query.to_string(),
+            WorkflowParameters::default(),
+            "test".to_string(),
+        );
+        workflow.status = WorkflowStatus::Completed;
+        workflow.results = Some(ResearchResults {
+            content: content.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            metadata: Default::default(),
+            word_count: content.split_whitespace().count() as u32,
+            source_count: sources.len() as u32,
+            methodology_used: workflow.parameters.methodology.clone(),
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        });
+        workflow
+    }
+
+    #[test]
+    fn test_search_ranks_and_highlights_matches() {
+        let conn = setup();
+        let quantum = workflow("Quantum computing survey", "state of quantum error correction", "Surface codes dominate.", &["arxiv.org/abs/quantum"]);
+        let rust = workflow("Async runtimes", "compare rust async executors", "Tokio mentions quantum only once.", &["tokio.rs"]);
+        index_workflow(&conn, &quantum).unwrap();
+        index_workflow(&conn, &rust).unwrap();
+
+        let matches = search(&conn, "quantum", &WorkflowSearchFilters::default()).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].workflow_id, quantum.id);
+        assert!(matches[0].highlighted_name.contains("<mark>Quantum</mark>"));
+        assert!(matches[1].snippet.contains("<mark>quantum</mark>"));
+    }
+
+    #[test]
+    fn test_index_stays_in_sync() {
+        let conn = setup();
+        let mut w = workflow("Battery chemistry", "solid state batteries", "", &[]);
+        index_workflow(&conn, &w).unwrap();
+
+        w.name = "Grid storage".to_string();
+        index_workflow(&conn, &w).unwrap();
+        assert!(search(&conn, "battery", &WorkflowSearchFilters::default()).unwrap().is_empty());
+        assert_eq!(search(&conn, "grid", &WorkflowSearchFilters::default()).unwrap().len(), 1);
+
+        remove_workflow(&conn, w.id).unwrap();
+        assert!(search(&conn, "grid", &WorkflowSearchFilters::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tags_and_folders_filter_searches_and_listings() {
+        let conn = setup();
+        let mut sodium = workflow("Sodium-ion cells", "sodium ion battery prices", "", &[]);
+        sodium.retag(&["Batteries".to_string(), "cost".to_string()], &[]);
+        sodium.move_to_folder(Some("Energy/Storage"));
+        let mut lithium = workflow("Lithium supply", "lithium battery supply chain", "", &[]);
+        lithium.retag(&["batteries".to_string()], &[]);
+        lithium.move_to_folder(Some("Energy"));
+        let untagged = workflow("Battery recycling", "battery recycling yields", "", &[]);
+        for w in [&sodium, &lithium, &untagged] {
+            index_workflow(&conn, w).unwrap();
+        }
+
+        let tagged = WorkflowSearchFilters { tags: vec!["BATTERIES".to_string(), "cost".to_string()], ..Default::default() };
+        let matches = search(&conn, "battery", &tagged).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].workflow_id, sodium.id);
+        assert_eq!(matches[0].tags, vec!["batteries", "cost"]);
+
+        // Tags are searchable text as well as filters
+        assert_eq!(search(&conn, "cost", &WorkflowSearchFilters::default()).unwrap().len(), 1);
+
+        let in_energy = WorkflowSearchFilters { folder: Some("Energy".to_string()), ..Default::default() };
+        assert_eq!(browse(&conn, &in_energy).unwrap().len(), 2);
+        let in_storage = WorkflowSearchFilters { folder: Some("Energy/Storage/".to_string()), ..Default::default() };
+        assert_eq!(browse(&conn, &in_storage).unwrap()[0].folder.as_deref(), Some("Energy/Storage"));
+        assert_eq!(browse(&conn, &WorkflowSearchFilters::default()).unwrap().len(), 3);
+
+        let organization = organization(&conn).unwrap();
+        assert_eq!(organization.folders, vec![
+            WorkflowGroup { name: "Energy".to_string(), workflow_count: 1 },
+            WorkflowGroup { name: "Energy/Storage".to_string(), workflow_count: 1 },
+        ]);
+        assert_eq!(organization.tags[0], WorkflowGroup { name: "batteries".to_string(), workflow_count: 2 });
+
+        remove_workflow(&conn, sodium.id).unwrap();
+        assert_eq!(organization(&conn).unwrap().tags.len(), 1);
+    }
+
+    #[test]
+    fn test_match_expression_is_escaped() {
+        assert_eq!(build_match_expression("rust \"async\" OR"), "\"rust\" \"async\" \"OR\"*");
+        assert_eq!(build_match_expression("   "), "");
+    }
+}
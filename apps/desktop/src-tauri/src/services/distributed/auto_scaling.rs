@@ -0,0 +1,532 @@
+use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use crate::services::api_manager::ServiceMetrics;
+use super::NodeResources;
+
+/// Scale events kept for inspection, newest last
+const DECISION_HISTORY: usize = 200;
+
+/// When a scaled service gains or loses replicas. Scaling up reacts to any threshold
+/// being crossed; scaling down waits until every metric is well below its threshold,
+/// so load hovering around one does not flap the replica count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingPolicy {
+    pub min_replicas: u32,
+    pub max_replicas: u32,
+    /// Replicas added or removed per scale event
+    pub scale_step: u32,
+    /// Queued workflows per replica above which to scale up
+    pub max_queue_depth_per_replica: f64,
+    /// Mean provider response time above which to scale up
+    pub max_latency_ms: f64,
+    pub max_cpu_percent: f32,
+    pub max_memory_percent: f32,
+    /// Scale down only once every metric is below this fraction of its threshold
+    pub scale_down_ratio: f64,
+    /// Provider error rate, in percent, above which a growing queue or latency is put
+    /// down to failing providers, which more replicas would not fix
+    pub max_provider_error_rate_percent: f64,
+    /// Least time after any scale event before scaling up again
+    pub scale_up_cooldown_secs: u64,
+    /// Least time after any scale event before scaling down
+    pub scale_down_cooldown_secs: u64,
+    /// Providers whose latency and errors reflect this service's load; empty for all
+    #[serde(default)]
+    pub providers: Vec<ServiceProvider>,
+    /// Whether the research queue is this service's work, so its depth calls for
+    /// replicas
+    #[serde(default = "default_scale_on_queue")]
+    pub scale_on_queue: bool,
+}
+
+fn default_scale_on_queue() -> bool {
+    true
+}
+
+impl Default for ScalingPolicy {
+    fn default() -> Self {
+        Self {
+            min_replicas: 1,
+            max_replicas: 10,
+            scale_step: 1,
+            max_queue_depth_per_replica: 5.0,
+            max_latency_ms: 5000.0,
+            max_cpu_percent: 75.0,
+            max_memory_percent: 80.0,
+            scale_down_ratio: 0.5,
+            max_provider_error_rate_percent: 25.0,
+            scale_up_cooldown_secs: 60,
+            scale_down_cooldown_secs: 300,
+            providers: Vec::new(),
+            scale_on_queue: true,
+        }
+    }
+}
+
+impl ScalingPolicy {
+    fn validate(&self) -> AppResult<()> {
+        if self.min_replicas == 0 || self.min_replicas > self.max_replicas {
+            return Err(AppError::validation("min_replicas", "must be at least 1 and at most max_replicas"));
+        }
+        if self.scale_step == 0 {
+            return Err(AppError::validation("scale_step", "must be at least 1"));
+        }
+        if !(0.0..1.0).contains(&self.scale_down_ratio) {
+            return Err(AppError::validation("scale_down_ratio", "must be at least 0 and below 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Requests a provider served since the previous evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProviderActivity {
+    pub provider: ServiceProvider,
+    pub requests: u64,
+    pub failed: u64,
+    /// Response time summed over the requests
+    pub total_latency_ms: f64,
+}
+
+impl ProviderActivity {
+    fn totals(metrics: &ServiceMetrics) -> Self {
+        Self {
+            provider: metrics.service,
+            requests: metrics.total_requests as u64,
+            failed: metrics.failed_requests as u64,
+            total_latency_ms: metrics.average_response_time_ms * metrics.total_requests as f64,
+        }
+    }
+
+    /// The activity between `previous` and `current` running totals. Totals that went
+    /// down were reset, so everything since is new.
+    fn since(previous: &Self, current: &Self) -> Self {
+        if current.requests < previous.requests || current.failed < previous.failed {
+            return *current;
+        }
+        Self {
+            provider: current.provider,
+            requests: current.requests - previous.requests,
+            failed: current.failed - previous.failed,
+            total_latency_ms: (current.total_latency_ms - previous.total_latency_ms).max(0.0),
+        }
+    }
+}
+
+/// Load on a scaled service as the auto-scaler sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingMetrics {
+    /// Research workflows waiting to run, when the queue is the service's work
+    pub queue_depth: u32,
+    /// Response time of the service's providers, averaged over this tick's requests
+    pub latency_ms: f64,
+    /// Share of this tick's provider requests that failed, in percent
+    pub provider_error_rate_percent: f64,
+    /// Mean over the nodes running the service
+    pub cpu_usage_percent: f32,
+    pub memory_usage_percent: f32,
+    pub collected_at: DateTime<Utc>,
+}
+
+impl ScalingMetrics {
+    /// Combine the research queue, the providers' activity this tick and node resource
+    /// usage
+    pub fn collect<'a>(
+        queue_depth: usize,
+        providers: impl IntoIterator<Item = &'a ProviderActivity>,
+        nodes: &[&NodeResources],
+    ) -> Self {
+        let (requests, failed, total_latency) = providers.into_iter()
+            .fold((0u64, 0u64, 0.0f64), |(requests, failed, latency), activity| (
+                requests + activity.requests,
+                failed + activity.failed,
+                latency + activity.total_latency_ms,
+            ));
+        let mean = |usage: fn(&NodeResources) -> f32| {
+            if nodes.is_empty() { 0.0 } else { nodes.iter().map(|node| usage(node)).sum::<f32>() / nodes.len() as f32 }
+        };
+        Self {
+            queue_depth: queue_depth as u32,
+            latency_ms: if requests == 0 { 0.0 } else { total_latency / requests as f64 },
+            provider_error_rate_percent: if requests == 0 { 0.0 } else { failed as f64 / requests as f64 * 100.0 },
+            cpu_usage_percent: mean(|node| node.cpu_usage_percent),
+            memory_usage_percent: mean(|node| node.memory_usage_percent),
+            collected_at: Utc::now(),
+        }
+    }
+}
+
+/// What to do with a service's replica count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScalingAction {
+    ScaleUp { replicas: u32 },
+    ScaleDown { replicas: u32 },
+    Hold,
+}
+
+/// A metric that called for scaling, with the value and threshold it was compared to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum ScalingTrigger {
+    QueueDepth { per_replica: f64, threshold: f64 },
+    Latency { latency_ms: f64, threshold: f64 },
+    Cpu { usage_percent: f32, threshold: f32 },
+    Memory { usage_percent: f32, threshold: f32 },
+    /// Every metric is below its scale-down threshold
+    LowLoad,
+}
+
+/// Why a service that triggered scaling was held instead
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum HoldReason {
+    Cooldown { remaining_secs: i64 },
+    AtReplicaLimit { limit: u32 },
+    ProviderErrors { error_rate_percent: f64 },
+}
+
+/// A change the auto-scaler decided on for a service, and the metrics it decided on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingDecision {
+    pub service_name: String,
+    pub deployment_id: Uuid,
+    pub current_replicas: u32,
+    pub action: ScalingAction,
+    pub triggers: Vec<ScalingTrigger>,
+    pub metrics: ScalingMetrics,
+    pub decided_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct ScaledService {
+    deployment_id: Uuid,
+    policy: ScalingPolicy,
+    replicas: u32,
+    /// Nodes the service was deployed to
+    nodes: Vec<Uuid>,
+    last_scaled_at: Option<DateTime<Utc>>,
+}
+
+impl ScaledService {
+    /// The metrics of this service: the queue if it is its work, the activity of its
+    /// providers, and the usage of the nodes running it, or of every node when none of
+    /// those reports
+    fn metrics(
+        &self,
+        queue_depth: usize,
+        activity: &HashMap<ServiceProvider, ProviderActivity>,
+        nodes: &HashMap<Uuid, NodeResources>,
+    ) -> ScalingMetrics {
+        let queue_depth = if self.policy.scale_on_queue { queue_depth } else { 0 };
+        let providers = activity.values()
+            .filter(|a| self.policy.providers.is_empty() || self.policy.providers.contains(&a.provider));
+        let own: Vec<&NodeResources> = self.nodes.iter().filter_map(|id| nodes.get(id)).collect();
+        let nodes = if own.is_empty() { nodes.values().collect() } else { own };
+        ScalingMetrics::collect(queue_depth, providers, &nodes)
+    }
+}
+
+fn decide(
+    service: &ScaledService,
+    metrics: &ScalingMetrics,
+    now: DateTime<Utc>,
+) -> (ScalingAction, Vec<ScalingTrigger>, Option<HoldReason>) {
+    let policy = &service.policy;
+    let per_replica = metrics.queue_depth as f64 / service.replicas.max(1) as f64;
+    let cooldown = |secs: u64| {
+        service.last_scaled_at
+            .map(|at| (at + Duration::seconds(secs as i64) - now).num_seconds())
+            .filter(|remaining| *remaining > 0)
+            .map(|remaining_secs| HoldReason::Cooldown { remaining_secs })
+    };
+
+    let mut load_triggers = Vec::new();
+    if per_replica > policy.max_queue_depth_per_replica {
+        load_triggers.push(ScalingTrigger::QueueDepth { per_replica, threshold: policy.max_queue_depth_per_replica });
+    }
+    if metrics.latency_ms > policy.max_latency_ms {
+        load_triggers.push(ScalingTrigger::Latency { latency_ms: metrics.latency_ms, threshold: policy.max_latency_ms });
+    }
+    let mut resource_triggers = Vec::new();
+    if metrics.cpu_usage_percent > policy.max_cpu_percent {
+        resource_triggers.push(ScalingTrigger::Cpu { usage_percent: metrics.cpu_usage_percent, threshold: policy.max_cpu_percent });
+    }
+    if metrics.memory_usage_percent > policy.max_memory_percent {
+        resource_triggers.push(ScalingTrigger::Memory { usage_percent: metrics.memory_usage_percent, threshold: policy.max_memory_percent });
+    }
+
+    if !load_triggers.is_empty() || !resource_triggers.is_empty() {
+        let providers_failing = metrics.provider_error_rate_percent > policy.max_provider_error_rate_percent;
+        let triggers: Vec<_> = load_triggers.into_iter().chain(resource_triggers.iter().cloned()).collect();
+        let hold = if providers_failing && resource_triggers.is_empty() {
+            Some(HoldReason::ProviderErrors { error_rate_percent: metrics.provider_error_rate_percent })
+        } else if service.replicas >= policy.max_replicas {
+            Some(HoldReason::AtReplicaLimit { limit: policy.max_replicas })
+        } else {
+            cooldown(policy.scale_up_cooldown_secs)
+        };
+        return match hold {
+            Some(reason) => (ScalingAction::Hold, triggers, Some(reason)),
+            None => {
+                let replicas = (service.replicas + policy.scale_step).min(policy.max_replicas);
+                (ScalingAction::ScaleUp { replicas }, triggers, None)
+            }
+        };
+    }
+
+    let ratio = policy.scale_down_ratio;
+    let low_load = per_replica <= policy.max_queue_depth_per_replica * ratio
+        && metrics.latency_ms <= policy.max_latency_ms * ratio
+        && metrics.cpu_usage_percent as f64 <= policy.max_cpu_percent as f64 * ratio
+        && metrics.memory_usage_percent as f64 <= policy.max_memory_percent as f64 * ratio;
+    // At the minimum, low load is the steady state rather than a trigger
+    if !low_load || service.replicas <= policy.min_replicas {
+        return (ScalingAction::Hold, Vec::new(), None);
+    }
+    match cooldown(policy.scale_down_cooldown_secs) {
+        Some(reason) => (ScalingAction::Hold, vec![ScalingTrigger::LowLoad], Some(reason)),
+        None => {
+            let replicas = service.replicas.saturating_sub(policy.scale_step).max(policy.min_replicas);
+            (ScalingAction::ScaleDown { replicas }, vec![ScalingTrigger::LowLoad], None)
+        }
+    }
+}
+
+/// Decides replica counts for the services deployed with a scaling policy
+pub struct AutoScaler {
+    services: HashMap<String, ScaledService>,
+    /// Each provider's running totals at the previous evaluation
+    provider_totals: HashMap<ServiceProvider, ProviderActivity>,
+    decisions: VecDeque<ScalingDecision>,
+}
+
+impl AutoScaler {
+    pub async fn new() -> AppResult<Self> {
+        Ok(Self { services: HashMap::new(), provider_totals: HashMap::new(), decisions: VecDeque::new() })
+    }
+
+    /// Scale `service_name`, deployed with `replicas` to `nodes`, by `policy`
+    pub async fn configure_scaling(
+        &mut self,
+        deployment_id: Uuid,
+        service_name: String,
+        replicas: u32,
+        nodes: Vec<Uuid>,
+        policy: ScalingPolicy,
+    ) -> AppResult<()> {
+        policy.validate()?;
+        info!("Auto-scaling {} between {} and {} replicas", service_name, policy.min_replicas, policy.max_replicas);
+        self.services.insert(service_name, ScaledService { deployment_id, policy, replicas, nodes, last_scaled_at: None });
+        Ok(())
+    }
+
+    /// The providers' activity since the previous call, given their running totals. A
+    /// provider seen for the first time has none yet; its totals are the baseline.
+    fn provider_activity<'a>(&mut self, providers: impl IntoIterator<Item = &'a ServiceMetrics>) -> HashMap<ServiceProvider, ProviderActivity> {
+        providers.into_iter()
+            .filter_map(|metrics| {
+                let current = ProviderActivity::totals(metrics);
+                let previous = self.provider_totals.insert(current.provider, current)?;
+                Some((current.provider, ProviderActivity::since(&previous, &current)))
+            })
+            .collect()
+    }
+
+    /// Decide for every scaled service from the research queue, the providers' running
+    /// totals and the usage of ready nodes. Only decisions to change a replica count are
+    /// returned; the caller applies them and reports each back with `record_scaled`.
+    pub fn evaluate<'a>(
+        &mut self,
+        queue_depth: usize,
+        providers: impl IntoIterator<Item = &'a ServiceMetrics>,
+        nodes: &HashMap<Uuid, NodeResources>,
+        now: DateTime<Utc>,
+    ) -> Vec<ScalingDecision> {
+        let activity = self.provider_activity(providers);
+        let mut decisions = Vec::new();
+        for (name, service) in &self.services {
+            let metrics = service.metrics(queue_depth, &activity, nodes);
+            let (action, triggers, hold_reason) = decide(service, &metrics, now);
+            if action == ScalingAction::Hold {
+                match hold_reason {
+                    Some(reason) => debug!("Holding {} at {} replicas despite {:?}: {:?}", name, service.replicas, triggers, reason),
+                    None => debug!("No scaling needed for {} at {} replicas", name, service.replicas),
+                }
+                continue;
+            }
+            info!(
+                "Scaling decision for {}: {:?} from {} replicas, triggered by {:?} (queue {}, latency {:.0}ms, provider errors {:.1}%, cpu {:.1}%, memory {:.1}%)",
+                name, action, service.replicas, triggers,
+                metrics.queue_depth, metrics.latency_ms, metrics.provider_error_rate_percent,
+                metrics.cpu_usage_percent, metrics.memory_usage_percent,
+            );
+            decisions.push(ScalingDecision {
+                service_name: name.clone(),
+                deployment_id: service.deployment_id,
+                current_replicas: service.replicas,
+                action,
+                triggers,
+                metrics,
+                decided_at: now,
+            });
+        }
+        decisions
+    }
+
+    /// Note that the service `decision` was for now runs the replicas it decided on,
+    /// starting its cooldown, and keep the decision
+    pub fn record_scaled(&mut self, decision: &ScalingDecision, at: DateTime<Utc>) {
+        let replicas = match decision.action {
+            ScalingAction::ScaleUp { replicas } | ScalingAction::ScaleDown { replicas } => replicas,
+            ScalingAction::Hold => return,
+        };
+        if let Some(service) = self.services.get_mut(&decision.service_name) {
+            service.replicas = replicas;
+            service.last_scaled_at = Some(at);
+        }
+        self.decisions.push_back(decision.clone());
+        while self.decisions.len() > DECISION_HISTORY {
+            self.decisions.pop_front();
+        }
+    }
+
+    /// Recent scale events, oldest first
+    pub fn recent_decisions(&self) -> Vec<ScalingDecision> {
+        self.decisions.iter().cloned().collect()
+    }
+
+    pub async fn health_check(&self) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(queue_depth: u32, cpu_usage_percent: f32, provider_error_rate_percent: f64) -> ScalingMetrics {
+        ScalingMetrics {
+            queue_depth,
+            latency_ms: 800.0,
+            provider_error_rate_percent,
+            cpu_usage_percent,
+            memory_usage_percent: 30.0,
+            collected_at: Utc::now(),
+        }
+    }
+
+    fn node(cpu_usage_percent: f32) -> NodeResources {
+        NodeResources {
+            cpu_usage_percent,
+            memory_usage_percent: 30.0,
+            storage_usage_percent: 10.0,
+            network_usage_mbps: 5.0,
+            pod_count: 1,
+            max_pods: 10,
+        }
+    }
+
+    /// SerpApi's running totals
+    fn serpapi(total_requests: u32, failed_requests: u32, average_response_time_ms: f64) -> Vec<ServiceMetrics> {
+        let mut metrics = ServiceMetrics::new(ServiceProvider::SerpApi);
+        metrics.total_requests = total_requests;
+        metrics.failed_requests = failed_requests;
+        metrics.successful_requests = total_requests - failed_requests;
+        metrics.average_response_time_ms = average_response_time_ms;
+        vec![metrics]
+    }
+
+    fn actions(decisions: &[ScalingDecision]) -> Vec<(&str, ScalingAction)> {
+        let mut actions: Vec<_> = decisions.iter().map(|d| (d.service_name.as_str(), d.action)).collect();
+        actions.sort_by_key(|(name, _)| *name);
+        actions
+    }
+
+    #[tokio::test]
+    async fn test_scaling_follows_each_services_load_with_cooldowns_and_a_hysteresis_band() {
+        let mut scaler = AutoScaler::new().await.unwrap();
+        let (processor_node, inference_node) = (Uuid::new_v4(), Uuid::new_v4());
+        let policy = ScalingPolicy { max_replicas: 3, ..ScalingPolicy::default() };
+        scaler.configure_scaling(Uuid::new_v4(), "research-processor".to_string(), 1, vec![processor_node], policy.clone()).await.unwrap();
+        // Inference does not run the research queue and calls only OpenRouter
+        let inference_policy = ScalingPolicy { scale_on_queue: false, providers: vec![ServiceProvider::OpenRouter], ..policy };
+        scaler.configure_scaling(Uuid::new_v4(), "ml-inference".to_string(), 1, vec![inference_node], inference_policy).await.unwrap();
+        let mut nodes = HashMap::from([(processor_node, node(40.0)), (inference_node, node(20.0))]);
+        let start = Utc::now();
+
+        // Twelve queued workflows is well past five per replica. The providers' lifetime
+        // totals are only a baseline, so their past failures do not hold scaling back.
+        let decisions = scaler.evaluate(12, &serpapi(1000, 900, 9000.0), &nodes, start);
+        assert_eq!(actions(&decisions), vec![("research-processor", ScalingAction::ScaleUp { replicas: 2 })]);
+        assert!(matches!(decisions[0].triggers[0], ScalingTrigger::QueueDepth { per_replica, .. } if per_replica == 12.0));
+        assert_eq!(decisions[0].metrics.provider_error_rate_percent, 0.0);
+        scaler.record_scaled(&decisions[0], start);
+
+        // Still loaded, but inside the scale-up cooldown, which is not a change to keep
+        assert!(scaler.evaluate(12, &serpapi(1000, 900, 9000.0), &nodes, start + Duration::seconds(30)).is_empty());
+        let (_, _, hold) = decide(&scaler.services["research-processor"], &metrics(12, 40.0, 2.0), start + Duration::seconds(30));
+        assert_eq!(hold, Some(HoldReason::Cooldown { remaining_secs: 30 }));
+
+        // 60 of this tick's 100 requests fail: the queue is put down to failing providers,
+        // while inference scales for the CPU on its own node
+        let later = start + Duration::seconds(90);
+        nodes.insert(inference_node, node(90.0));
+        let decisions = scaler.evaluate(12, &serpapi(1100, 960, 8254.0), &nodes, later);
+        assert_eq!(actions(&decisions), vec![("ml-inference", ScalingAction::ScaleUp { replicas: 2 })]);
+        scaler.record_scaled(&decisions[0], later);
+        let (_, _, hold) = decide(&scaler.services["research-processor"], &metrics(12, 40.0, 60.0), later);
+        assert!(matches!(hold, Some(HoldReason::ProviderErrors { .. })));
+
+        // CPU pressure scales up even while providers fail
+        nodes.insert(processor_node, node(90.0));
+        let decisions = scaler.evaluate(12, &serpapi(1200, 1020, 8000.0), &nodes, later);
+        assert_eq!(actions(&decisions), vec![("research-processor", ScalingAction::ScaleUp { replicas: 3 })]);
+        assert_eq!(decisions[0].metrics.provider_error_rate_percent, 60.0);
+        scaler.record_scaled(&decisions[0], later);
+
+        nodes.insert(inference_node, node(20.0));
+        assert!(scaler.evaluate(30, &serpapi(1200, 1020, 8000.0), &nodes, later + Duration::seconds(120)).is_empty());
+        let (_, _, hold) = decide(&scaler.services["research-processor"], &metrics(30, 90.0, 0.0), later + Duration::seconds(120));
+        assert_eq!(hold, Some(HoldReason::AtReplicaLimit { limit: 3 }));
+
+        // Load just under the processor's thresholds is inside the band, so only the idle
+        // inference service, past its scale-down cooldown, changes
+        nodes.insert(processor_node, node(60.0));
+        let decisions = scaler.evaluate(12, &serpapi(1200, 1020, 8000.0), &nodes, later + Duration::seconds(600));
+        assert_eq!(actions(&decisions), vec![("ml-inference", ScalingAction::ScaleDown { replicas: 1 })]);
+        scaler.record_scaled(&decisions[0], later + Duration::seconds(600));
+
+        nodes.insert(processor_node, node(10.0));
+        let decisions = scaler.evaluate(0, &serpapi(1200, 1020, 8000.0), &nodes, later + Duration::seconds(700));
+        assert_eq!(actions(&decisions), vec![("research-processor", ScalingAction::ScaleDown { replicas: 2 })]);
+        assert_eq!(decisions[0].triggers, vec![ScalingTrigger::LowLoad]);
+        scaler.record_scaled(&decisions[0], later + Duration::seconds(700));
+
+        // Only the scale events are kept
+        assert_eq!(scaler.recent_decisions().len(), 5);
+    }
+
+    #[test]
+    fn test_provider_activity_is_the_change_since_the_previous_tick() {
+        let previous = ProviderActivity { provider: ServiceProvider::SerpApi, requests: 1000, failed: 900, total_latency_ms: 9_000_000.0 };
+        let current = ProviderActivity { provider: ServiceProvider::SerpApi, requests: 1100, failed: 905, total_latency_ms: 9_080_000.0 };
+        let activity = ProviderActivity::since(&previous, &current);
+        assert_eq!((activity.requests, activity.failed, activity.total_latency_ms), (100, 5, 80_000.0));
+
+        let metrics = ScalingMetrics::collect(0, [&activity], &[&node(40.0), &node(60.0)]);
+        assert_eq!(metrics.provider_error_rate_percent, 5.0);
+        assert_eq!(metrics.latency_ms, 800.0);
+        assert_eq!(metrics.cpu_usage_percent, 50.0);
+
+        // Totals that went down were reset
+        let reset = ProviderActivity { requests: 10, failed: 1, total_latency_ms: 5000.0, ..current };
+        assert_eq!(ProviderActivity::since(&current, &reset), reset);
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
 use crate::services::Service;
+use crate::services::api_manager::ServiceMetrics;
 
 pub mod microservices;
 pub mod service_mesh;
@@ -21,7 +22,7 @@ pub mod service_discovery;
 use microservices::{MicroserviceManager, MicroserviceConfig, ServiceInstance, ServiceHealth};
 use service_mesh::{ServiceMesh, MeshConfig, ServiceCommunication, TrafficPolicy};
 use load_balancer::{LoadBalancer, LoadBalancingStrategy, HealthCheck, BackendPool};
-use auto_scaling::{AutoScaler, ScalingPolicy, ScalingMetrics, ScalingAction};
+use auto_scaling::{AutoScaler, ScalingPolicy, ScalingAction, ScalingDecision};
 use distributed_cache::{DistributedCacheManager, CacheNode, CacheStrategy, CacheReplication};
 use database_sharding::{ShardingManager, ShardConfig, ShardKey, ShardDistribution};
 use container_orchestration::{ContainerOrchestrator, PodSpec, DeploymentConfig, ServiceSpec};
@@ -427,7 +428,7 @@ impl DistributedService {
 
         // Deploy to container orchestrator
         let container_orchestrator = self.container_orchestrator.write().await;
-        let deployment_id = container_orchestrator.deploy_service(request.clone(), selected_nodes).await?;
+        let deployment_id = container_orchestrator.deploy_service(request.clone(), selected_nodes.clone()).await?;
         drop(container_orchestrator);
 
         // Register service in service registry
@@ -437,8 +438,8 @@ impl DistributedService {
 
         // Configure auto-scaling if enabled
         if let Some(scaling_policy) = request.scaling_policy {
-            let auto_scaler = self.auto_scaler.write().await;
-            auto_scaler.configure_scaling(deployment_id, scaling_policy).await?;
+            let mut auto_scaler = self.auto_scaler.write().await;
+            auto_scaler.configure_scaling(deployment_id, request.service_name.clone(), request.replicas, selected_nodes, scaling_policy).await?;
         }
 
         // Update service mesh configuration
@@ -466,6 +467,51 @@ impl DistributedService {
         Ok(())
     }
 
+    /// Decide replica counts for scaled services from the research queue depth, the
+    /// providers' running totals and the CPU and memory of the nodes running each, and
+    /// apply them. Returns the changes that were applied.
+    pub async fn run_auto_scaling<'a>(
+        &self,
+        queue_depth: usize,
+        providers: impl IntoIterator<Item = &'a ServiceMetrics>,
+    ) -> AppResult<Vec<ScalingDecision>> {
+        if !self.distributed_config.enable_auto_scaling {
+            return Ok(Vec::new());
+        }
+
+        let nodes: HashMap<Uuid, NodeResources> = {
+            let cluster_nodes = self.cluster_nodes.read().await;
+            cluster_nodes.values()
+                .filter(|node| matches!(node.status, NodeStatus::Ready))
+                .map(|node| (node.node_id, node.resources.clone()))
+                .collect()
+        };
+        let now = Utc::now();
+        let decisions = self.auto_scaler.write().await.evaluate(queue_depth, providers, &nodes, now);
+
+        let mut applied = Vec::new();
+        for decision in decisions {
+            let replicas = match decision.action {
+                ScalingAction::ScaleUp { replicas } | ScalingAction::ScaleDown { replicas } => replicas,
+                ScalingAction::Hold => continue,
+            };
+            match self.scale_service(decision.service_name.clone(), replicas).await {
+                Ok(()) => {
+                    self.auto_scaler.write().await.record_scaled(&decision, now);
+                    applied.push(decision);
+                }
+                // Left at its old count, the service is decided on again next round
+                Err(e) => error!("Failed to scale {} to {} replicas: {}", decision.service_name, replicas, e),
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Recent auto-scaling decisions and the metrics that triggered them
+    pub async fn get_scaling_decisions(&self) -> Vec<ScalingDecision> {
+        self.auto_scaler.read().await.recent_decisions()
+    }
+
     /// Get cluster status
     pub async fn get_cluster_status(&self) -> AppResult<ClusterStats> {
         debug!("Getting cluster status");
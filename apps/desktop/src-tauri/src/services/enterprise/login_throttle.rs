@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Login attempt throttling configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginThrottleConfig {
+    /// Failed attempts from one IP before backoff starts
+    pub ip_free_attempts: u32,
+    /// Failed attempts against one account before backoff starts
+    pub account_free_attempts: u32,
+    pub base_backoff_seconds: u64,
+    pub max_backoff_seconds: u64,
+    /// Failures older than this no longer count towards an IP's total
+    pub failure_window_minutes: u32,
+    /// Failures from one IP within the window that trigger a temporary ban
+    pub ip_ban_threshold: u32,
+    pub ip_ban_duration_minutes: u32,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        Self {
+            ip_free_attempts: 10,
+            account_free_attempts: 3,
+            base_backoff_seconds: 1,
+            max_backoff_seconds: 300,
+            failure_window_minutes: 15,
+            ip_ban_threshold: 50,
+            ip_ban_duration_minutes: 60,
+        }
+    }
+}
+
+/// Why a login attempt was refused before credentials were checked
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoginRejection {
+    AccountLocked { until: DateTime<Utc> },
+    AccountBackoff { retry_at: DateTime<Utc> },
+    IpBackoff { retry_at: DateTime<Utc> },
+}
+
+impl LoginRejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            LoginRejection::AccountLocked { .. } => "account_locked",
+            LoginRejection::AccountBackoff { .. } => "account_backoff",
+            LoginRejection::IpBackoff { .. } => "ip_backoff",
+        }
+    }
+}
+
+impl std::fmt::Display for LoginRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoginRejection::AccountLocked { until } => write!(f, "Account is locked until {}", until),
+            LoginRejection::AccountBackoff { retry_at } | LoginRejection::IpBackoff { retry_at } => {
+                write!(f, "Too many failed login attempts, retry after {}", retry_at)
+            }
+        }
+    }
+}
+
+/// An attempt that may go ahead to credential verification
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginGate {
+    /// Set while the source IP is banned; the attempt got through because the
+    /// account signed in from the IP before, or the IP's backoff had passed
+    pub ip_banned_until: Option<DateTime<Utc>>,
+}
+
+/// State after recording a failed attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginFailure {
+    pub ip_failures: u32,
+    pub account_failures: u32,
+    pub ip_banned_until: Option<DateTime<Utc>>,
+    pub account_locked_until: Option<DateTime<Utc>>,
+    pub risk_score: f32,
+}
+
+#[derive(Debug, Clone)]
+struct FailureTracker {
+    failures: u32,
+    first_failure_at: DateTime<Utc>,
+    retry_at: Option<DateTime<Utc>>,
+    blocked_until: Option<DateTime<Utc>>,
+}
+
+impl FailureTracker {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self { failures: 0, first_failure_at: now, retry_at: None, blocked_until: None }
+    }
+
+    fn blocked(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.blocked_until.filter(|until| *until > now)
+    }
+
+    fn backing_off(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.retry_at.filter(|at| *at > now)
+    }
+}
+
+/// Per-IP and per-account failed login tracking with exponential backoff,
+/// temporary IP bans and account lockout.
+///
+/// IP restrictions never outlast `ip_ban_duration_minutes`. Accounts that
+/// signed in from an IP before are exempt from its restrictions, so users
+/// sharing a NAT address with an attacker keep signing in; every other
+/// attempt from the IP stays under its backoff, ban or not.
+pub struct LoginThrottle {
+    config: LoginThrottleConfig,
+    lockout_attempts: u32,
+    lockout_duration: Duration,
+    by_ip: HashMap<String, FailureTracker>,
+    by_account: HashMap<String, FailureTracker>,
+    /// Accounts that signed in successfully, by the IP they signed in from
+    known_logins: HashMap<String, HashSet<String>>,
+}
+
+impl LoginThrottle {
+    pub fn new(config: LoginThrottleConfig, lockout_attempts: u32, lockout_duration_minutes: u32) -> Self {
+        Self {
+            config,
+            lockout_attempts,
+            lockout_duration: Duration::minutes(lockout_duration_minutes as i64),
+            by_ip: HashMap::new(),
+            by_account: HashMap::new(),
+            known_logins: HashMap::new(),
+        }
+    }
+
+    fn account_key(username: &str) -> String {
+        username.trim().to_lowercase()
+    }
+
+    /// Decide whether an attempt may proceed to credential verification
+    pub fn check_attempt(&mut self, ip_address: &str, username: &str, now: DateTime<Utc>) -> Result<LoginGate, LoginRejection> {
+        self.prune(now);
+
+        if let Some(account) = self.by_account.get(&Self::account_key(username)) {
+            if let Some(until) = account.blocked(now) {
+                return Err(LoginRejection::AccountLocked { until });
+            }
+            if let Some(retry_at) = account.backing_off(now) {
+                return Err(LoginRejection::AccountBackoff { retry_at });
+            }
+        }
+
+        let ip = self.by_ip.get(ip_address);
+        let ip_banned_until = ip.and_then(|ip| ip.blocked(now));
+        if !self.is_known_login(ip_address, username) {
+            if let Some(retry_at) = ip.and_then(|ip| ip.backing_off(now)) {
+                return Err(LoginRejection::IpBackoff { retry_at });
+            }
+        }
+
+        Ok(LoginGate { ip_banned_until })
+    }
+
+    /// Record a failed attempt against both the source IP and the account
+    pub fn record_failure(&mut self, ip_address: &str, username: &str, now: DateTime<Utc>) -> LoginFailure {
+        let window = Duration::minutes(self.config.failure_window_minutes as i64);
+
+        let ip = self.by_ip.entry(ip_address.to_string()).or_insert_with(|| FailureTracker::new(now));
+        // A banned IP keeps its count, so its backoff does not start over mid-ban
+        if now - ip.first_failure_at > window && ip.blocked(now).is_none() {
+            *ip = FailureTracker { blocked_until: ip.blocked_until, ..FailureTracker::new(now) };
+        }
+        ip.failures += 1;
+        ip.retry_at = backoff(&self.config, ip.failures, self.config.ip_free_attempts).map(|delay| now + delay);
+        if ip.failures >= self.config.ip_ban_threshold && ip.blocked(now).is_none() {
+            ip.blocked_until = Some(now + Duration::minutes(self.config.ip_ban_duration_minutes as i64));
+        }
+        let (ip_failures, ip_banned_until) = (ip.failures, ip.blocked(now));
+
+        let account = self.by_account.entry(Self::account_key(username)).or_insert_with(|| FailureTracker::new(now));
+        account.failures += 1;
+        account.retry_at = backoff(&self.config, account.failures, self.config.account_free_attempts).map(|delay| now + delay);
+        if self.lockout_attempts > 0 && account.failures >= self.lockout_attempts {
+            account.blocked_until = Some(now + self.lockout_duration);
+            account.failures = 0;
+        }
+        let (account_failures, account_locked_until) = (account.failures, account.blocked(now));
+
+        LoginFailure {
+            ip_failures,
+            account_failures,
+            ip_banned_until,
+            account_locked_until,
+            risk_score: self.risk_score(ip_address, username, now),
+        }
+    }
+
+    /// Clear the account's failures and exempt it from the IP's restrictions
+    /// from now on; the IP's history is kept since other users behind the same
+    /// address may still be attacking
+    pub fn record_success(&mut self, ip_address: &str, username: &str) {
+        let account = Self::account_key(username);
+        self.by_account.remove(&account);
+        self.known_logins.entry(ip_address.to_string()).or_default().insert(account);
+    }
+
+    fn is_known_login(&self, ip_address: &str, username: &str) -> bool {
+        self.known_logins.get(ip_address).is_some_and(|accounts| accounts.contains(&Self::account_key(username)))
+    }
+
+    /// Risk contributed by recent failures from this IP and against this account
+    pub fn risk_score(&self, ip_address: &str, username: &str, now: DateTime<Utc>) -> f32 {
+        let mut risk_score = 0.0;
+
+        if let Some(ip) = self.by_ip.get(ip_address) {
+            risk_score += 0.5 * (ip.failures as f32 / self.config.ip_ban_threshold.max(1) as f32).min(1.0);
+            if ip.blocked(now).is_some() {
+                risk_score += 0.3;
+            }
+        }
+        if let Some(account) = self.by_account.get(&Self::account_key(username)) {
+            risk_score += 0.3 * (account.failures as f32 / self.lockout_attempts.max(1) as f32).min(1.0);
+            if account.blocked(now).is_some() {
+                risk_score += 0.2;
+            }
+        }
+
+        risk_score.min(1.0)
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let window = Duration::minutes(self.config.failure_window_minutes as i64);
+        let stale = |tracker: &FailureTracker| {
+            tracker.blocked(now).is_none()
+                && tracker.backing_off(now).is_none()
+                && now - tracker.first_failure_at > window
+        };
+        self.by_ip.retain(|_, tracker| !stale(tracker));
+        self.by_account.retain(|_, tracker| !stale(tracker));
+    }
+}
+
+/// Delay before the next attempt, doubling with each failure past `free_attempts`
+fn backoff(config: &LoginThrottleConfig, failures: u32, free_attempts: u32) -> Option<Duration> {
+    if failures <= free_attempts {
+        return None;
+    }
+    let exponent = (failures - free_attempts - 1).min(32);
+    let seconds = config.base_backoff_seconds.saturating_mul(1u64 << exponent).min(config.max_backoff_seconds);
+    Some(Duration::seconds(seconds as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle::new(LoginThrottleConfig {
+            ip_free_attempts: 3,
+            account_free_attempts: 2,
+            base_backoff_seconds: 1,
+            max_backoff_seconds: 60,
+            failure_window_minutes: 15,
+            ip_ban_threshold: 6,
+            ip_ban_duration_minutes: 60,
+        }, 5, 30)
+    }
+
+    #[test]
+    fn test_account_backoff_doubles_and_locks_out() {
+        let mut throttle = throttle();
+        let now = Utc::now();
+
+        throttle.record_failure("10.0.0.1", "alice", now);
+        throttle.record_failure("10.0.0.2", "alice", now);
+        assert!(throttle.check_attempt("10.0.0.3", "alice", now).is_ok());
+
+        throttle.record_failure("10.0.0.3", "alice", now);
+        assert_eq!(
+            throttle.check_attempt("10.0.0.4", "Alice", now),
+            Err(LoginRejection::AccountBackoff { retry_at: now + Duration::seconds(1) })
+        );
+
+        let later = now + Duration::seconds(1);
+        throttle.record_failure("10.0.0.4", "alice", later);
+        assert_eq!(
+            throttle.check_attempt("10.0.0.5", "alice", later),
+            Err(LoginRejection::AccountBackoff { retry_at: later + Duration::seconds(2) })
+        );
+
+        let failure = throttle.record_failure("10.0.0.5", "alice", later + Duration::seconds(2));
+        assert!(failure.account_locked_until.is_some());
+        assert!(matches!(
+            throttle.check_attempt("10.0.0.6", "alice", later + Duration::seconds(10)),
+            Err(LoginRejection::AccountLocked { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ip_ban_admits_other_accounts_until_it_expires() {
+        let mut throttle = throttle();
+        let start = Utc::now();
+
+        // One IP spraying many accounts trips the IP limits, not the accounts'
+        let mut now = start;
+        let mut last = None;
+        for i in 0..6 {
+            let failure = throttle.record_failure("203.0.113.7", &format!("user{}", i), now);
+            now = now + Duration::seconds(60);
+            last = Some(failure);
+        }
+        let ban_until = last.unwrap().ip_banned_until.expect("ip should be banned");
+
+        // Banned, but a legitimate user behind the same NAT may still try
+        let gate = throttle.check_attempt("203.0.113.7", "bob", now).unwrap();
+        assert_eq!(gate.ip_banned_until, Some(ban_until));
+
+        // The ban is temporary
+        let after_ban = ban_until + Duration::seconds(1);
+        assert_eq!(
+            throttle.check_attempt("203.0.113.7", "bob", after_ban),
+            Ok(LoginGate { ip_banned_until: None })
+        );
+    }
+
+    #[test]
+    fn test_banned_ip_spraying_new_accounts_stays_throttled() {
+        let mut throttle = throttle();
+        let mut now = Utc::now();
+
+        // Dave signed in from the office NAT before the attack started
+        throttle.record_success("203.0.113.7", "dave");
+
+        for i in 0..6 {
+            throttle.record_failure("203.0.113.7", &format!("user{}", i), now);
+            now = now + Duration::seconds(60);
+        }
+        assert!(throttle.check_attempt("203.0.113.7", "user6", now).unwrap().ip_banned_until.is_some());
+
+        // While banned, each further failure still pushes the IP's backoff out
+        let failure = throttle.record_failure("203.0.113.7", "user6", now);
+        assert_eq!(failure.ip_failures, 7);
+        let soon = now + Duration::seconds(1);
+        assert!(matches!(
+            throttle.check_attempt("203.0.113.7", "user7", soon),
+            Err(LoginRejection::IpBackoff { .. })
+        ));
+
+        // The count survives the failure window for as long as the ban lasts
+        let later = now + Duration::minutes(20);
+        assert_eq!(throttle.record_failure("203.0.113.7", "user8", later).ip_failures, 8);
+        assert!(matches!(
+            throttle.check_attempt("203.0.113.7", "user9", later + Duration::seconds(1)),
+            Err(LoginRejection::IpBackoff { .. })
+        ));
+
+        // An account that signed in from the IP before is not held back
+        let gate = throttle.check_attempt("203.0.113.7", "Dave", soon).unwrap();
+        assert!(gate.ip_banned_until.is_some());
+    }
+
+    #[test]
+    fn test_success_resets_account_but_not_ip() {
+        let mut throttle = throttle();
+        let now = Utc::now();
+
+        for _ in 0..3 {
+            throttle.record_failure("198.51.100.1", "carol", now);
+        }
+        throttle.record_success("198.51.100.1", "carol");
+
+        assert!(throttle.risk_score("198.51.100.1", "carol", now) > 0.0);
+        assert_eq!(throttle.risk_score("192.0.2.1", "carol", now), 0.0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, debug, warn, error};
@@ -6,8 +6,11 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
-use crate::error::{AppResult, ResearchError};
+use crate::error::{AppError, AppResult, ResearchError};
 use crate::services::Service;
+use crate::services::api_manager::KeyScope;
+use crate::services::data_persistence::data_residency::{self, DataRegion};
+use crate::utils::clock::{self, SharedClock};
 
 pub mod rbac_system;
 pub mod multi_tenant;
@@ -15,6 +18,8 @@ pub mod audit_logging;
 pub mod compliance;
 pub mod sso_integration;
 pub mod user_management;
+pub mod login_throttle;
+pub mod role_assignments;
 
 use rbac_system::{RBACManager, Role, Permission, PolicyEngine, AccessControl};
 use multi_tenant::{TenantManager, Tenant, TenantConfig, ResourceIsolation};
@@ -22,6 +27,8 @@ use audit_logging::{AuditLogger, AuditEvent, AuditTrail, ComplianceReport};
 use compliance::{ComplianceManager, ComplianceFramework, ComplianceCheck};
 use sso_integration::{SSOManager, SSOProvider, SSOConfig, AuthenticationResult};
 use user_management::{EnterpriseUserManager, EnterpriseUser, UserProfile, UserGroup};
+use login_throttle::{LoginThrottle, LoginThrottleConfig, LoginRejection};
+use role_assignments::{RoleAssignmentStore, RoleAssignment, AssignmentSource, ElevationRequest};
 
 /// Enterprise features service for advanced user management and compliance (V1.2.0)
 pub struct EnterpriseService {
@@ -32,7 +39,15 @@ pub struct EnterpriseService {
     sso_manager: Arc<RwLock<SSOManager>>,
     user_manager: Arc<RwLock<EnterpriseUserManager>>,
     active_sessions: Arc<RwLock<HashMap<Uuid, EnterpriseSession>>>,
+    login_throttle: Arc<RwLock<LoginThrottle>>,
+    role_assignments: Arc<RwLock<RoleAssignmentStore>>,
+    /// Region each tenant's data is pinned to, alongside its `TenantConfig`;
+    /// tenants without an entry are `Global`
+    tenant_regions: Arc<RwLock<HashMap<Uuid, DataRegion>>>,
+    /// Tenants whose users may fall back to the shared system pool of API keys
+    system_pool_tenants: Arc<RwLock<HashSet<Uuid>>>,
     enterprise_config: EnterpriseConfig,
+    clock: SharedClock,
 }
 
 /// Enterprise configuration
@@ -45,7 +60,12 @@ pub struct EnterpriseConfig {
     pub sso_enabled: bool,
     pub session_timeout_minutes: u32,
     pub max_concurrent_sessions: u32,
+    /// Longest time-boxed role a JIT elevation may grant
+    #[serde(default = "default_max_elevation_minutes")]
+    pub max_elevation_minutes: u32,
     pub password_policy: PasswordPolicy,
+    #[serde(default)]
+    pub login_throttle: LoginThrottleConfig,
     pub data_retention_days: u32,
 }
 
@@ -81,6 +101,22 @@ pub struct EnterpriseSession {
     pub risk_score: f32,
 }
 
+impl EnterpriseSession {
+    /// The API keys the session's holder may see and spend: those of the tenant it
+    /// signed in to, and the system pool if the tenant opted in. Users outside any
+    /// tenant act for the system.
+    pub fn key_scope(&self, now: DateTime<Utc>, tenant_uses_system_pool: bool) -> AppResult<KeyScope> {
+        if self.expires_at <= now {
+            return Err(ResearchError::authentication_failed("Session expired".to_string()).into());
+        }
+        Ok(match self.tenant_id {
+            Some(tenant_id) if tenant_uses_system_pool => KeyScope::tenant(tenant_id).with_system_pool(),
+            Some(tenant_id) => KeyScope::tenant(tenant_id),
+            None => KeyScope::system(),
+        })
+    }
+}
+
 /// Authentication methods
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AuthenticationMethod {
@@ -119,6 +155,42 @@ pub struct RoleAssignmentRequest {
     pub justification: Option<String>,
 }
 
+/// Role revocation request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleRevocationRequest {
+    pub user_id: Uuid,
+    pub role_id: String,
+    pub tenant_id: Option<Uuid>,
+    pub revoked_by: Uuid,
+    pub justification: Option<String>,
+}
+
+/// Active session as shown to its owner for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: Uuid,
+    pub device: String,
+    pub ip_address: String,
+    pub authentication_method: AuthenticationMethod,
+    pub created_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<&EnterpriseSession> for SessionSummary {
+    fn from(session: &EnterpriseSession) -> Self {
+        Self {
+            session_id: session.session_id,
+            device: session.user_agent.clone(),
+            ip_address: session.ip_address.clone(),
+            authentication_method: session.authentication_method.clone(),
+            created_at: session.created_at,
+            last_activity: session.last_activity,
+            expires_at: session.expires_at,
+        }
+    }
+}
+
 /// Access request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessRequest {
@@ -179,6 +251,12 @@ impl EnterpriseService {
         let sso_manager = Arc::new(RwLock::new(SSOManager::new().await?));
         let user_manager = Arc::new(RwLock::new(EnterpriseUserManager::new().await?));
         let active_sessions = Arc::new(RwLock::new(HashMap::new()));
+        let login_throttle = Arc::new(RwLock::new(LoginThrottle::new(
+            enterprise_config.login_throttle.clone(),
+            enterprise_config.password_policy.lockout_attempts,
+            enterprise_config.password_policy.lockout_duration_minutes,
+        )));
+        let role_assignments = Arc::new(RwLock::new(RoleAssignmentStore::new(enterprise_config.max_elevation_minutes)));
 
         let service = Self {
             rbac_manager,
@@ -188,7 +266,12 @@ impl EnterpriseService {
             sso_manager,
             user_manager,
             active_sessions,
+            login_throttle,
+            role_assignments,
+            tenant_regions: Arc::new(RwLock::new(HashMap::new())),
+            system_pool_tenants: Arc::new(RwLock::new(HashSet::new())),
             enterprise_config,
+            clock: clock::system_clock(),
         };
 
         // Initialize default roles and permissions
@@ -198,6 +281,12 @@ impl EnterpriseService {
         Ok(service)
     }
 
+    /// Expire sessions and throttle logins by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Create enterprise user
     pub async fn create_user(
         &self,
@@ -223,7 +312,7 @@ impl EnterpriseService {
                 user_id: user.id,
                 role_id: role_name.clone(),
                 tenant_id: request.tenant_id,
-                effective_from: Some(Utc::now()),
+                effective_from: Some(self.clock.now()),
                 effective_until: None,
                 assigned_by: created_by,
                 justification: Some("Initial user creation".to_string()),
@@ -242,7 +331,7 @@ impl EnterpriseService {
                 resource_type: "user".to_string(),
                 resource_id: user.id.to_string(),
                 action: "create".to_string(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 ip_address: None,
                 user_agent: None,
                 details: serde_json::to_value(&request)?,
@@ -271,18 +360,71 @@ impl EnterpriseService {
             AuthenticationMethod::Password
         };
 
+        // Refuse attempts from throttled IPs and accounts before touching credentials
+        let gate = {
+            let mut login_throttle = self.login_throttle.write().await;
+            login_throttle.check_attempt(&ip_address, &username, self.clock.now())
+        };
+        let gate = match gate {
+            Ok(gate) => gate,
+            Err(rejection) => {
+                warn!("Login attempt for {} from {} throttled: {}", username, ip_address, rejection);
+                let risk_score = self.login_throttle.read().await.risk_score(&ip_address, &username, self.clock.now());
+                self.log_login_failure(&username, &ip_address, &user_agent, rejection.reason(), risk_score).await?;
+                return Err(ResearchError::authentication_failed(rejection.to_string()).into());
+            }
+        };
+
         // Authenticate user
-        let user = if let Some(token) = sso_token {
+        let authenticated = if let Some(token) = sso_token {
             let sso_manager = self.sso_manager.read().await;
-            let auth_result = sso_manager.authenticate_token(token).await?;
-            auth_result.user
+            sso_manager.authenticate_token(token).await.map(|auth_result| auth_result.user)
         } else if let Some(pwd) = password {
             let user_manager = self.user_manager.read().await;
-            user_manager.authenticate_password(username, pwd).await?
+            user_manager.authenticate_password(username.clone(), pwd).await
         } else {
             return Err(ResearchError::authentication_failed("No authentication method provided".to_string()).into());
         };
 
+        let user = match authenticated {
+            Ok(user) => user,
+            Err(e) => {
+                let failure = {
+                    let mut login_throttle = self.login_throttle.write().await;
+                    login_throttle.record_failure(&ip_address, &username, self.clock.now())
+                };
+                warn!(
+                    "Failed login for {} from {} ({} failures from IP, {} against account)",
+                    username, ip_address, failure.ip_failures, failure.account_failures
+                );
+
+                let reason = if failure.account_locked_until.is_some() {
+                    "account_locked"
+                } else if failure.ip_banned_until.is_some() {
+                    "ip_banned"
+                } else {
+                    "invalid_credentials"
+                };
+                self.log_login_failure(&username, &ip_address, &user_agent, reason, failure.risk_score).await?;
+
+                if let Some(until) = failure.account_locked_until {
+                    return Err(ResearchError::authentication_failed(LoginRejection::AccountLocked { until }.to_string()).into());
+                }
+                if let Some(until) = failure.ip_banned_until {
+                    return Err(ResearchError::authentication_failed(format!("Too many failed login attempts from this address, retry after {}", until)).into());
+                }
+                return Err(e);
+            }
+        };
+
+        // Valid credentials for an unlocked account are admitted even while the
+        // source IP is banned, so shared-NAT users are not locked out; the
+        // throttle held every other attempt from the IP to its backoff
+        if let Some(until) = gate.ip_banned_until {
+            warn!("Admitting {} from banned IP {} (ban until {})", username, ip_address, until);
+        }
+        self.login_throttle.write().await.record_success(&ip_address, &username);
+
         // Check user status and permissions
         if !user.active {
             return Err(ResearchError::authentication_failed("User account is disabled".to_string()).into());
@@ -290,14 +432,15 @@ impl EnterpriseService {
 
         // Get user roles and permissions
         let rbac_manager = self.rbac_manager.read().await;
-        let user_roles = rbac_manager.get_user_roles(user.id).await?;
         let user_permissions = rbac_manager.get_user_permissions(user.id).await?;
         drop(rbac_manager);
+        let user_roles = self.get_user_roles(user.id, user.tenant_id).await?;
 
         // Calculate risk score
         let risk_score = self.calculate_risk_score(&user, &ip_address, &user_agent).await?;
 
         // Create session
+        let now = self.clock.now();
         let session = EnterpriseSession {
             session_id: Uuid::new_v4(),
             user_id: user.id,
@@ -305,9 +448,9 @@ impl EnterpriseService {
             authentication_method,
             ip_address: ip_address.clone(),
             user_agent: user_agent.clone(),
-            created_at: Utc::now(),
-            last_activity: Utc::now(),
-            expires_at: Utc::now() + chrono::Duration::minutes(self.enterprise_config.session_timeout_minutes as i64),
+            created_at: now,
+            last_activity: now,
+            expires_at: now + chrono::Duration::minutes(self.enterprise_config.session_timeout_minutes as i64),
             permissions: user_permissions,
             roles: user_roles,
             mfa_verified: false, // TODO: Implement MFA verification
@@ -331,7 +474,7 @@ impl EnterpriseService {
                 resource_type: "session".to_string(),
                 resource_id: session.session_id.to_string(),
                 action: "create".to_string(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 ip_address: Some(ip_address),
                 user_agent: Some(user_agent),
                 details: serde_json::json!({
@@ -346,6 +489,39 @@ impl EnterpriseService {
         Ok(session)
     }
 
+    /// Record a failed or throttled login attempt
+    async fn log_login_failure(
+        &self,
+        username: &str,
+        ip_address: &str,
+        user_agent: &str,
+        reason: &str,
+        risk_score: f32,
+    ) -> AppResult<()> {
+        if !self.enterprise_config.audit_logging_enabled {
+            return Ok(());
+        }
+
+        let audit_logger = self.audit_logger.write().await;
+        audit_logger.log_event(AuditEvent {
+            event_id: Uuid::new_v4(),
+            event_type: "user_login_failed".to_string(),
+            user_id: None,
+            tenant_id: None,
+            resource_type: "account".to_string(),
+            resource_id: username.to_string(),
+            action: "authenticate".to_string(),
+            timestamp: self.clock.now(),
+            ip_address: Some(ip_address.to_string()),
+            user_agent: Some(user_agent.to_string()),
+            details: serde_json::json!({
+                "reason": reason,
+                "risk_score": risk_score
+            }),
+            risk_score,
+        }).await
+    }
+
     /// Check access permissions
     pub async fn check_access(&self, request: AccessRequest) -> AppResult<AccessDecision> {
         debug!("Checking access for user: {} on resource: {}", request.user_id, request.resource_id);
@@ -359,7 +535,7 @@ impl EnterpriseService {
         drop(active_sessions);
 
         // Check session validity
-        if user_session.expires_at < Utc::now() {
+        if user_session.expires_at < self.clock.now() {
             return Ok(AccessDecision {
                 allowed: false,
                 reason: "Session expired".to_string(),
@@ -415,7 +591,7 @@ impl EnterpriseService {
                 resource_type: request.resource_type.clone(),
                 resource_id: request.resource_id.clone(),
                 action: request.action.clone(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 ip_address: Some(user_session.ip_address.clone()),
                 user_agent: Some(user_session.user_agent.clone()),
                 details: serde_json::to_value(&decision)?,
@@ -439,6 +615,25 @@ impl EnterpriseService {
             request.effective_from,
             request.effective_until,
         ).await?;
+        drop(rbac_manager);
+
+        // Permanent grants are tracked too, so a later time-boxed assignment
+        // of the same role lapsing does not take the permanent one with it
+        let mut role_assignments = self.role_assignments.write().await;
+        role_assignments.clear_ended(request.user_id, &request.role_id, request.tenant_id);
+        role_assignments.add(RoleAssignment {
+            assignment_id: Uuid::new_v4(),
+            user_id: request.user_id,
+            role_id: request.role_id.clone(),
+            tenant_id: request.tenant_id,
+            effective_from: request.effective_from.unwrap_or_else(Utc::now),
+            effective_until: request.effective_until,
+            assigned_by: request.assigned_by,
+            justification: request.justification.clone(),
+            source: AssignmentSource::Direct,
+            active: true,
+        });
+        drop(role_assignments);
 
         // Log audit event
         if self.enterprise_config.audit_logging_enabled {
@@ -451,7 +646,7 @@ impl EnterpriseService {
                 resource_type: "user".to_string(),
                 resource_id: request.user_id.to_string(),
                 action: "assign_role".to_string(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 ip_address: None,
                 user_agent: None,
                 details: serde_json::to_value(&request)?,
@@ -463,17 +658,359 @@ impl EnterpriseService {
         Ok(())
     }
 
+    /// Roles currently in effect for a user in `tenant_id`, excluding lapsed and
+    /// not-yet-effective assignments and those made in other tenants
+    pub async fn get_user_roles(&self, user_id: Uuid, tenant_id: Option<Uuid>) -> AppResult<Vec<Role>> {
+        let rbac_manager = self.rbac_manager.read().await;
+        let mut roles = rbac_manager.get_user_roles(user_id).await?;
+        drop(rbac_manager);
+
+        let role_assignments = self.role_assignments.read().await;
+        let now = self.clock.now();
+        roles.retain(|role| role_assignments.grants(user_id, &role.id, tenant_id, now));
+        Ok(roles)
+    }
+
+    /// Ask for a time-boxed role; nothing is granted until an approver accepts
+    pub async fn request_elevation(
+        &self,
+        user_id: Uuid,
+        role_id: String,
+        duration_minutes: u32,
+        justification: String,
+        tenant_id: Option<Uuid>,
+    ) -> AppResult<ElevationRequest> {
+        info!("User {} requesting elevation to role: {} for {} minutes", user_id, role_id, duration_minutes);
+
+        // Approving would replace the standing grant with a time-boxed one
+        if self.get_user_roles(user_id, tenant_id).await?.iter().any(|role| role.id == role_id) {
+            return Err(ResearchError::invalid_request(format!("User already holds role: {}", role_id)).into());
+        }
+
+        let request = {
+            let mut role_assignments = self.role_assignments.write().await;
+            role_assignments.request_elevation(user_id, role_id, tenant_id, duration_minutes, justification, self.clock.now())?
+        };
+
+        // Log audit event
+        if self.enterprise_config.audit_logging_enabled {
+            let audit_logger = self.audit_logger.write().await;
+            audit_logger.log_event(AuditEvent {
+                event_id: Uuid::new_v4(),
+                event_type: "elevation_requested".to_string(),
+                user_id: Some(user_id),
+                tenant_id: request.tenant_id,
+                resource_type: "role".to_string(),
+                resource_id: request.role_id.clone(),
+                action: "request_elevation".to_string(),
+                timestamp: self.clock.now(),
+                ip_address: None,
+                user_agent: None,
+                details: serde_json::to_value(&request)?,
+                risk_score: 0.4,
+            }).await?;
+        }
+
+        Ok(request)
+    }
+
+    /// Approve a pending elevation, granting the role for the requested duration from
+    /// now. The approver needs the approve or admin permission on the role in its tenant.
+    pub async fn approve_elevation(&self, request_id: Uuid, approver: Uuid) -> AppResult<RoleAssignment> {
+        info!("Approving elevation request: {} by: {}", request_id, approver);
+
+        let request = self.role_assignments.read().await.pending_request(request_id)?;
+        role_assignments::check_decider(&*self.rbac_manager, &request, approver).await?;
+
+        let assignment = {
+            let mut role_assignments = self.role_assignments.write().await;
+            role_assignments.approve_elevation(request_id, approver, self.clock.now())?
+        };
+
+        let rbac_manager = self.rbac_manager.write().await;
+        rbac_manager.assign_role(
+            assignment.user_id,
+            assignment.role_id.clone(),
+            assignment.tenant_id,
+            Some(assignment.effective_from),
+            assignment.effective_until,
+        ).await?;
+        drop(rbac_manager);
+
+        // Log audit event
+        if self.enterprise_config.audit_logging_enabled {
+            let audit_logger = self.audit_logger.write().await;
+            audit_logger.log_event(AuditEvent {
+                event_id: Uuid::new_v4(),
+                event_type: "elevation_approved".to_string(),
+                user_id: Some(approver),
+                tenant_id: assignment.tenant_id,
+                resource_type: "user".to_string(),
+                resource_id: assignment.user_id.to_string(),
+                action: "assign_role".to_string(),
+                timestamp: self.clock.now(),
+                ip_address: None,
+                user_agent: None,
+                details: serde_json::to_value(&assignment)?,
+                risk_score: 0.5,
+            }).await?;
+        }
+
+        self.role_expiry_reaper().refresh_sessions(assignment.user_id).await?;
+
+        info!("Elevation granted: {} to user: {} until {:?}", assignment.role_id, assignment.user_id, assignment.effective_until);
+        Ok(assignment)
+    }
+
+    /// Deny a pending elevation; as with approving, only a permitted approver may
+    pub async fn deny_elevation(&self, request_id: Uuid, approver: Uuid, reason: String) -> AppResult<ElevationRequest> {
+        info!("Denying elevation request: {} by: {}", request_id, approver);
+
+        let pending = self.role_assignments.read().await.pending_request(request_id)?;
+        role_assignments::check_decider(&*self.rbac_manager, &pending, approver).await?;
+
+        let request = {
+            let mut role_assignments = self.role_assignments.write().await;
+            role_assignments.deny_elevation(request_id, approver, reason, self.clock.now())?
+        };
+
+        // Log audit event
+        if self.enterprise_config.audit_logging_enabled {
+            let audit_logger = self.audit_logger.write().await;
+            audit_logger.log_event(AuditEvent {
+                event_id: Uuid::new_v4(),
+                event_type: "elevation_denied".to_string(),
+                user_id: Some(approver),
+                tenant_id: request.tenant_id,
+                resource_type: "role".to_string(),
+                resource_id: request.role_id.clone(),
+                action: "deny_elevation".to_string(),
+                timestamp: self.clock.now(),
+                ip_address: None,
+                user_agent: None,
+                details: serde_json::to_value(&request)?,
+                risk_score: 0.2,
+            }).await?;
+        }
+
+        Ok(request)
+    }
+
+    /// Elevation requests awaiting a decision
+    pub async fn pending_elevations(&self) -> Vec<ElevationRequest> {
+        self.role_assignments.read().await.pending_elevations()
+    }
+
+    /// Start the task that deactivates lapsed role assignments
+    pub async fn start_background_tasks(&self) -> AppResult<()> {
+        info!("Starting enterprise background tasks...");
+
+        let reaper = self.role_expiry_reaper();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // Every minute
+            loop {
+                interval.tick().await;
+                if let Err(e) = reaper.run(reaper.clock.now()).await {
+                    error!("Role assignment expiry failed: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn role_expiry_reaper(&self) -> RoleExpiryReaper {
+        RoleExpiryReaper {
+            rbac_manager: self.rbac_manager.clone(),
+            audit_logger: self.audit_logger.clone(),
+            active_sessions: self.active_sessions.clone(),
+            role_assignments: self.role_assignments.clone(),
+            audit_logging_enabled: self.enterprise_config.audit_logging_enabled,
+            clock: self.clock.clone(),
+        }
+    }
+
+    /// Revoke a role and end the user's sessions so the old permissions stop applying
+    pub async fn revoke_role(&self, request: RoleRevocationRequest) -> AppResult<()> {
+        info!("Revoking role: {} from user: {}", request.role_id, request.user_id);
+
+        // The RBAC manager ends assignments through their window, so close it now
+        let now = self.clock.now();
+        let rbac_manager = self.rbac_manager.write().await;
+        rbac_manager.assign_role(
+            request.user_id,
+            request.role_id.clone(),
+            request.tenant_id,
+            None,
+            Some(now),
+        ).await?;
+        drop(rbac_manager);
+
+        {
+            let mut role_assignments = self.role_assignments.write().await;
+            role_assignments.revoke(request.user_id, &request.role_id, request.tenant_id, request.revoked_by, now);
+        }
+
+        // Log audit event
+        if self.enterprise_config.audit_logging_enabled {
+            let audit_logger = self.audit_logger.write().await;
+            audit_logger.log_event(AuditEvent {
+                event_id: Uuid::new_v4(),
+                event_type: "role_revoked".to_string(),
+                user_id: Some(request.revoked_by),
+                tenant_id: request.tenant_id,
+                resource_type: "user".to_string(),
+                resource_id: request.user_id.to_string(),
+                action: "revoke_role".to_string(),
+                timestamp: self.clock.now(),
+                ip_address: None,
+                user_agent: None,
+                details: serde_json::to_value(&request)?,
+                risk_score: 0.3,
+            }).await?;
+        }
+
+        self.revoke_all_user_sessions(request.user_id, request.revoked_by, "role_revoked").await?;
+
+        info!("Role revoked successfully: {} from user: {}", request.role_id, request.user_id);
+        Ok(())
+    }
+
+    /// Sign a user out everywhere after their password changed.
+    ///
+    /// Passwords are owned by the user directory, so whatever changes one
+    /// calls this afterwards; sessions opened with the old password end here.
+    pub async fn password_changed(&self, user_id: Uuid, changed_by: Uuid) -> AppResult<usize> {
+        info!("Password changed for user: {}", user_id);
+        self.revoke_all_user_sessions(user_id, changed_by, "password_changed").await
+    }
+
+    /// Active sessions of a user, most recently used first
+    pub async fn list_user_sessions(&self, user_id: Uuid) -> AppResult<Vec<SessionSummary>> {
+        debug!("Listing active sessions for user: {}", user_id);
+
+        let active_sessions = self.active_sessions.read().await;
+        let now = self.clock.now();
+        let mut sessions: Vec<SessionSummary> = active_sessions.values()
+            .filter(|s| s.user_id == user_id && s.expires_at > now)
+            .map(SessionSummary::from)
+            .collect();
+        sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+
+        Ok(sessions)
+    }
+
+    /// Revoke a single session for the user signed in to `caller_session_id`
+    pub async fn revoke_session(&self, session_id: Uuid, caller_session_id: Uuid) -> AppResult<()> {
+        let target = self.active_sessions.read().await.get(&session_id).cloned()
+            .ok_or_else(|| ResearchError::invalid_request(format!("Session not found: {}", session_id)))?;
+        let revoked_by = self.session_revoker(caller_session_id, target.user_id, target.tenant_id).await?;
+        info!("Revoking session: {} by: {}", session_id, revoked_by);
+
+        let session = {
+            let mut active_sessions = self.active_sessions.write().await;
+            active_sessions.remove(&session_id)
+        }.ok_or_else(|| ResearchError::invalid_request(format!("Session not found: {}", session_id)))?;
+
+        self.log_session_revoked(&session, revoked_by, "revoked").await?;
+
+        info!("Session revoked: {} (user: {})", session_id, session.user_id);
+        Ok(())
+    }
+
+    /// Log a user out everywhere for the user signed in to `caller_session_id`
+    pub async fn log_out_everywhere(&self, user_id: Uuid, caller_session_id: Uuid) -> AppResult<usize> {
+        let tenant_id = self.active_sessions.read().await.get(&caller_session_id).and_then(|s| s.tenant_id);
+        let revoked_by = self.session_revoker(caller_session_id, user_id, tenant_id).await?;
+        self.revoke_all_user_sessions(user_id, revoked_by, "user_requested").await
+    }
+
+    /// The user signed in to `caller_session_id`, if they may end `target_user`'s
+    /// sessions in `tenant_id`: their own always, anyone else's with the revoke or
+    /// admin permission on sessions
+    async fn session_revoker(&self, caller_session_id: Uuid, target_user: Uuid, tenant_id: Option<Uuid>) -> AppResult<Uuid> {
+        let caller = self.active_sessions.read().await.get(&caller_session_id).cloned()
+            .filter(|s| s.expires_at > self.clock.now())
+            .ok_or_else(|| ResearchError::authentication_failed("No active session found".to_string()))?;
+        if caller.user_id == target_user {
+            return Ok(caller.user_id);
+        }
+
+        let rbac_manager = self.rbac_manager.read().await;
+        for action in ["revoke", "admin"] {
+            if rbac_manager.check_permission(caller.user_id, "session", &target_user.to_string(), action, tenant_id).await? {
+                return Ok(caller.user_id);
+            }
+        }
+        Err(AppError::PermissionDenied {
+            action: format!("revoke the sessions of user {}", target_user),
+        })
+    }
+
+    /// Revoke every session of a user ("log out everywhere")
+    pub async fn revoke_all_user_sessions(&self, user_id: Uuid, revoked_by: Uuid, reason: &str) -> AppResult<usize> {
+        info!("Revoking all sessions for user: {} ({})", user_id, reason);
+
+        let revoked: Vec<EnterpriseSession> = {
+            let mut active_sessions = self.active_sessions.write().await;
+            let session_ids: Vec<Uuid> = active_sessions.values()
+                .filter(|s| s.user_id == user_id)
+                .map(|s| s.session_id)
+                .collect();
+            session_ids.iter().filter_map(|id| active_sessions.remove(id)).collect()
+        };
+
+        for session in &revoked {
+            self.log_session_revoked(session, revoked_by, reason).await?;
+        }
+
+        info!("Revoked {} sessions for user: {}", revoked.len(), user_id);
+        Ok(revoked.len())
+    }
+
+    /// Record the end of a session that did not simply expire
+    async fn log_session_revoked(&self, session: &EnterpriseSession, revoked_by: Uuid, reason: &str) -> AppResult<()> {
+        if !self.enterprise_config.audit_logging_enabled {
+            return Ok(());
+        }
+
+        let audit_logger = self.audit_logger.write().await;
+        audit_logger.log_event(AuditEvent {
+            event_id: Uuid::new_v4(),
+            event_type: "session_revoked".to_string(),
+            user_id: Some(revoked_by),
+            tenant_id: session.tenant_id,
+            resource_type: "session".to_string(),
+            resource_id: session.session_id.to_string(),
+            action: "revoke".to_string(),
+            timestamp: self.clock.now(),
+            ip_address: Some(session.ip_address.clone()),
+            user_agent: Some(session.user_agent.clone()),
+            details: serde_json::json!({
+                "session_user_id": session.user_id,
+                "reason": reason
+            }),
+            risk_score: 0.2,
+        }).await
+    }
+
     /// Create tenant
     pub async fn create_tenant(
         &self,
         name: String,
         config: TenantConfig,
+        data_region: DataRegion,
         created_by: Uuid,
     ) -> AppResult<Tenant> {
-        info!("Creating tenant: {} by: {}", name, created_by);
+        info!("Creating tenant: {} in {} region by: {}", name, data_region, created_by);
 
         let tenant_manager = self.tenant_manager.write().await;
         let tenant = tenant_manager.create_tenant(name, config).await?;
+        drop(tenant_manager);
+
+        if data_region != DataRegion::Global {
+            self.tenant_regions.write().await.insert(tenant.id, data_region);
+        }
 
         // Log audit event
         if self.enterprise_config.audit_logging_enabled {
@@ -486,7 +1023,7 @@ impl EnterpriseService {
                 resource_type: "tenant".to_string(),
                 resource_id: tenant.id.to_string(),
                 action: "create".to_string(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 ip_address: None,
                 user_agent: None,
                 details: serde_json::to_value(&tenant)?,
@@ -507,12 +1044,94 @@ impl EnterpriseService {
         info!("Generating compliance report for framework: {:?}", framework);
 
         let compliance_manager = self.compliance_manager.read().await;
-        let report = compliance_manager.generate_report(framework, tenant_id).await?;
+        let mut report = compliance_manager.generate_report(framework.clone(), tenant_id).await?;
+        drop(compliance_manager);
+
+        // GDPR requires EU tenants' data to stay in the EU
+        if matches!(framework, ComplianceFramework::GDPR) {
+            let (passed, details) = self.check_data_residency(tenant_id).await?;
+            report.add_check("data_residency".to_string(), passed, details);
+        }
 
         info!("Compliance report generated: {} checks performed", report.total_checks);
         Ok(report)
     }
 
+    /// The API keys the holder of `session_id` may see and spend. Without a session the
+    /// caller acts for the system, which is only allowed while no tenant exists.
+    pub async fn key_scope(&self, session_id: Option<Uuid>) -> AppResult<KeyScope> {
+        let Some(session_id) = session_id else {
+            let tenant_count = self.tenant_manager.read().await.get_tenant_count().await?;
+            if self.enterprise_config.multi_tenant_enabled && tenant_count > 0 {
+                return Err(ResearchError::authentication_failed("Sign in to use API keys".to_string()).into());
+            }
+            return Ok(KeyScope::system());
+        };
+
+        let session = self.active_sessions.read().await.get(&session_id).cloned()
+            .ok_or_else(|| ResearchError::authentication_failed("No active session found".to_string()))?;
+        let uses_system_pool = match session.tenant_id {
+            Some(tenant_id) => self.system_pool_tenants.read().await.contains(&tenant_id),
+            None => false,
+        };
+        session.key_scope(self.clock.now(), uses_system_pool)
+    }
+
+    /// Let a tenant's users fall back to the system pool of API keys, or stop them
+    pub async fn set_tenant_system_pool(&self, tenant_id: Uuid, enabled: bool, changed_by: Uuid) -> AppResult<()> {
+        info!("Setting system pool access of tenant {} to {} by: {}", tenant_id, enabled, changed_by);
+
+        self.tenant_manager.read().await.validate_tenant(tenant_id).await?;
+        {
+            let mut system_pool_tenants = self.system_pool_tenants.write().await;
+            if enabled {
+                system_pool_tenants.insert(tenant_id);
+            } else {
+                system_pool_tenants.remove(&tenant_id);
+            }
+        }
+
+        // Log audit event
+        if self.enterprise_config.audit_logging_enabled {
+            let audit_logger = self.audit_logger.write().await;
+            audit_logger.log_event(AuditEvent {
+                event_id: Uuid::new_v4(),
+                event_type: "tenant_system_pool_changed".to_string(),
+                user_id: Some(changed_by),
+                tenant_id: Some(tenant_id),
+                resource_type: "tenant".to_string(),
+                resource_id: tenant_id.to_string(),
+                action: "update".to_string(),
+                timestamp: self.clock.now(),
+                ip_address: None,
+                user_agent: None,
+                details: serde_json::json!({ "use_system_pool": enabled }),
+                risk_score: 0.3,
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Region a tenant's data is pinned to; data without a tenant is `Global`
+    pub async fn tenant_data_region(&self, tenant_id: Option<Uuid>) -> AppResult<DataRegion> {
+        match tenant_id {
+            Some(tenant_id) => Ok(self.tenant_regions.read().await.get(&tenant_id).copied().unwrap_or_default()),
+            None => Ok(DataRegion::Global),
+        }
+    }
+
+    /// Whether the tenant's region has its own storage, so its data and backups stay in-region
+    async fn check_data_residency(&self, tenant_id: Option<Uuid>) -> AppResult<(bool, String)> {
+        let region = self.tenant_data_region(tenant_id).await?;
+
+        if data_residency::region_configured(region) {
+            Ok((true, format!("Tenant data is stored in the {} region", region)))
+        } else {
+            Ok((false, format!("Tenant is pinned to {} but no {} storage is configured", region, region)))
+        }
+    }
+
     /// Get enterprise statistics
     pub async fn get_enterprise_stats(&self, tenant_id: Option<Uuid>) -> AppResult<EnterpriseStats> {
         debug!("Getting enterprise statistics");
@@ -575,6 +1194,9 @@ impl EnterpriseService {
             risk_score += 0.3;
         }
 
+        // Recent failures from the same address, e.g. a shared NAT under attack
+        risk_score += self.login_throttle.read().await.risk_score(ip_address, &user.username, self.clock.now());
+
         // TODO: Add more sophisticated risk calculation
         // - Geolocation analysis
         // - Device fingerprinting
@@ -585,6 +1207,88 @@ impl EnterpriseService {
     }
 }
 
+/// Deactivates role assignments whose window has closed
+#[derive(Clone)]
+struct RoleExpiryReaper {
+    rbac_manager: Arc<RwLock<RBACManager>>,
+    audit_logger: Arc<RwLock<AuditLogger>>,
+    active_sessions: Arc<RwLock<HashMap<Uuid, EnterpriseSession>>>,
+    role_assignments: Arc<RwLock<RoleAssignmentStore>>,
+    audit_logging_enabled: bool,
+    clock: SharedClock,
+}
+
+impl RoleExpiryReaper {
+    /// Revoke lapsed assignments and refresh affected sessions; returns how many expired
+    async fn run(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let expired = {
+            let mut role_assignments = self.role_assignments.write().await;
+            role_assignments.expire_due(now)
+        };
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        // The RBAC manager was handed the window when the role was assigned and
+        // stops granting it on its own; the reaper retires the tracked record,
+        // audits the expiry and refreshes the permissions cached on sessions
+        for assignment in &expired {
+            info!("Role assignment expired: {} for user: {}", assignment.role_id, assignment.user_id);
+
+            if self.audit_logging_enabled {
+                let audit_logger = self.audit_logger.write().await;
+                audit_logger.log_event(AuditEvent {
+                    event_id: Uuid::new_v4(),
+                    event_type: "role_assignment_expired".to_string(),
+                    user_id: None,
+                    tenant_id: assignment.tenant_id,
+                    resource_type: "user".to_string(),
+                    resource_id: assignment.user_id.to_string(),
+                    action: "expire_role".to_string(),
+                    timestamp: now,
+                    ip_address: None,
+                    user_agent: None,
+                    details: serde_json::to_value(assignment)?,
+                    risk_score: 0.1,
+                }).await?;
+            }
+        }
+
+        let mut user_ids: Vec<Uuid> = expired.iter().map(|a| a.user_id).collect();
+        user_ids.sort();
+        user_ids.dedup();
+        for user_id in user_ids {
+            self.refresh_sessions(user_id).await?;
+        }
+
+        Ok(expired.len())
+    }
+
+    /// Replace the roles and permissions cached on a user's sessions
+    async fn refresh_sessions(&self, user_id: Uuid) -> AppResult<()> {
+        let rbac_manager = self.rbac_manager.read().await;
+        let roles = rbac_manager.get_user_roles(user_id).await?;
+        let permissions = rbac_manager.get_user_permissions(user_id).await?;
+        drop(rbac_manager);
+
+        let now = self.clock.now();
+        let role_assignments = self.role_assignments.read().await;
+        let mut active_sessions = self.active_sessions.write().await;
+        for session in active_sessions.values_mut().filter(|s| s.user_id == user_id) {
+            session.roles = roles.iter()
+                .filter(|role| role_assignments.grants(user_id, &role.id, session.tenant_id, now))
+                .cloned()
+                .collect();
+            session.permissions = permissions.clone();
+        }
+        Ok(())
+    }
+}
+
+fn default_max_elevation_minutes() -> u32 {
+    240
+}
+
 impl Default for EnterpriseConfig {
     fn default() -> Self {
         Self {
@@ -599,7 +1303,9 @@ impl Default for EnterpriseConfig {
             sso_enabled: true,
             session_timeout_minutes: 480, // 8 hours
             max_concurrent_sessions: 5,
+            max_elevation_minutes: default_max_elevation_minutes(),
             password_policy: PasswordPolicy::default(),
+            login_throttle: LoginThrottleConfig::default(),
             data_retention_days: 2555, // 7 years
         }
     }
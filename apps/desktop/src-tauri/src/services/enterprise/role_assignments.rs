@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ResearchError};
+use super::rbac_system::RBACManager;
+
+/// How an assignment came about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AssignmentSource {
+    Direct,
+    Elevation(Uuid),
+}
+
+/// A role assignment with an optional validity window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleAssignment {
+    pub assignment_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: String,
+    pub tenant_id: Option<Uuid>,
+    pub effective_from: DateTime<Utc>,
+    pub effective_until: Option<DateTime<Utc>>,
+    pub assigned_by: Uuid,
+    pub justification: Option<String>,
+    pub source: AssignmentSource,
+    /// Cleared once the assignment has been reaped
+    pub active: bool,
+}
+
+impl RoleAssignment {
+    /// Whether the assignment grants its role at `now`
+    pub fn is_effective(&self, now: DateTime<Utc>) -> bool {
+        self.active
+            && self.effective_from <= now
+            && self.effective_until.map_or(true, |until| now < until)
+    }
+}
+
+/// Elevation request status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ElevationStatus {
+    Pending,
+    Approved { assignment_id: Uuid },
+    Denied { reason: String },
+}
+
+/// Just-in-time request for a time-boxed role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElevationRequest {
+    pub request_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: String,
+    pub tenant_id: Option<Uuid>,
+    pub duration_minutes: u32,
+    pub justification: String,
+    pub requested_at: DateTime<Utc>,
+    pub status: ElevationStatus,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Who may decide elevation requests
+#[async_trait]
+pub trait ElevationAuthority: Send + Sync {
+    /// Whether `approver` may approve or deny elevations to `role_id` in `tenant_id`
+    async fn may_decide(&self, approver: Uuid, role_id: &str, tenant_id: Option<Uuid>) -> AppResult<bool>;
+}
+
+/// Holders of the approve or admin permission on a role decide elevations to it
+#[async_trait]
+impl ElevationAuthority for RwLock<RBACManager> {
+    async fn may_decide(&self, approver: Uuid, role_id: &str, tenant_id: Option<Uuid>) -> AppResult<bool> {
+        let rbac_manager = self.read().await;
+        for action in ["approve", "admin"] {
+            if rbac_manager.check_permission(approver, "role", role_id, action, tenant_id).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Refuse a decision on `request` by the requester, or by anyone `authority` does not
+/// let decide elevations to its role in its tenant
+pub async fn check_decider(authority: &dyn ElevationAuthority, request: &ElevationRequest, decider: Uuid) -> AppResult<()> {
+    if request.user_id == decider {
+        return Err(ResearchError::invalid_request("Elevation cannot be self-approved".to_string()).into());
+    }
+    if !authority.may_decide(decider, &request.role_id, request.tenant_id).await? {
+        return Err(AppError::PermissionDenied {
+            action: format!("decide elevations to role {}", request.role_id),
+        });
+    }
+    Ok(())
+}
+
+/// Time-bound role assignments and pending elevations.
+///
+/// Roles granted without going through here are permanent. Tracked
+/// assignments without an end date are permanent too and are never reaped.
+#[derive(Debug, Default)]
+pub struct RoleAssignmentStore {
+    assignments: HashMap<Uuid, RoleAssignment>,
+    elevations: HashMap<Uuid, ElevationRequest>,
+    max_elevation_minutes: u32,
+}
+
+impl RoleAssignmentStore {
+    pub fn new(max_elevation_minutes: u32) -> Self {
+        Self {
+            max_elevation_minutes,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, assignment: RoleAssignment) {
+        self.assignments.insert(assignment.assignment_id, assignment);
+    }
+
+    /// Whether `role_id` is granted to the user in `tenant_id` at `now`. Roles
+    /// with no tracked assignment in any tenant are treated as permanent; an
+    /// assignment in one tenant grants nothing in another.
+    pub fn grants(&self, user_id: Uuid, role_id: &str, tenant_id: Option<Uuid>, now: DateTime<Utc>) -> bool {
+        let mut tracked = self.assignments.values()
+            .filter(|a| a.user_id == user_id && a.role_id == role_id)
+            .peekable();
+
+        tracked.peek().is_none() || tracked.any(|a| a.tenant_id == tenant_id && a.is_effective(now))
+    }
+
+    /// End the user's assignments of `role_id` at `now` and return them.
+    ///
+    /// A permanent role has no tracked assignment, so a closed one is
+    /// recorded instead; that stops `grants` from falling back to treating
+    /// the role as permanent.
+    pub fn revoke(&mut self, user_id: Uuid, role_id: &str, tenant_id: Option<Uuid>, revoked_by: Uuid, now: DateTime<Utc>) -> Vec<RoleAssignment> {
+        let mut ended: Vec<RoleAssignment> = self.assignments.values_mut()
+            .filter(|a| a.active && a.user_id == user_id && a.role_id == role_id && a.tenant_id == tenant_id)
+            .map(|a| {
+                a.active = false;
+                a.effective_until = Some(a.effective_until.map_or(now, |until| until.min(now)));
+                a.clone()
+            })
+            .collect();
+
+        if ended.is_empty() {
+            let closed = RoleAssignment {
+                assignment_id: Uuid::new_v4(),
+                user_id,
+                role_id: role_id.to_string(),
+                tenant_id,
+                effective_from: now,
+                effective_until: Some(now),
+                assigned_by: revoked_by,
+                justification: None,
+                source: AssignmentSource::Direct,
+                active: false,
+            };
+            self.add(closed.clone());
+            ended.push(closed);
+        }
+        ended
+    }
+
+    /// Drop ended assignments of `role_id` in `tenant_id` so a new permanent grant takes effect
+    pub fn clear_ended(&mut self, user_id: Uuid, role_id: &str, tenant_id: Option<Uuid>) {
+        self.assignments.retain(|_, a| a.active || a.user_id != user_id || a.role_id != role_id || a.tenant_id != tenant_id);
+    }
+
+    /// Deactivate every assignment whose window has closed and return them
+    pub fn expire_due(&mut self, now: DateTime<Utc>) -> Vec<RoleAssignment> {
+        self.assignments.values_mut()
+            .filter(|a| a.active && a.effective_until.map_or(false, |until| until <= now))
+            .map(|a| {
+                a.active = false;
+                a.clone()
+            })
+            .collect()
+    }
+
+    pub fn request_elevation(
+        &mut self,
+        user_id: Uuid,
+        role_id: String,
+        tenant_id: Option<Uuid>,
+        duration_minutes: u32,
+        justification: String,
+        now: DateTime<Utc>,
+    ) -> AppResult<ElevationRequest> {
+        if justification.trim().is_empty() {
+            return Err(ResearchError::invalid_request("Elevation requires a justification".to_string()).into());
+        }
+        if duration_minutes == 0 || duration_minutes > self.max_elevation_minutes {
+            return Err(ResearchError::invalid_request(format!(
+                "Elevation duration must be between 1 and {} minutes", self.max_elevation_minutes
+            )).into());
+        }
+
+        let request = ElevationRequest {
+            request_id: Uuid::new_v4(),
+            user_id,
+            role_id,
+            tenant_id,
+            duration_minutes,
+            justification,
+            requested_at: now,
+            status: ElevationStatus::Pending,
+            decided_by: None,
+            decided_at: None,
+        };
+        self.elevations.insert(request.request_id, request.clone());
+        Ok(request)
+    }
+
+    /// Approve a pending elevation; the granted window starts at approval
+    pub fn approve_elevation(&mut self, request_id: Uuid, approver: Uuid, now: DateTime<Utc>) -> AppResult<RoleAssignment> {
+        let request = self.pending_elevation(request_id)?;
+        if request.user_id == approver {
+            return Err(ResearchError::invalid_request("Elevation cannot be self-approved".to_string()).into());
+        }
+
+        let assignment = RoleAssignment {
+            assignment_id: Uuid::new_v4(),
+            user_id: request.user_id,
+            role_id: request.role_id.clone(),
+            tenant_id: request.tenant_id,
+            effective_from: now,
+            effective_until: Some(now + Duration::minutes(request.duration_minutes as i64)),
+            assigned_by: approver,
+            justification: Some(request.justification.clone()),
+            source: AssignmentSource::Elevation(request_id),
+            active: true,
+        };
+
+        request.status = ElevationStatus::Approved { assignment_id: assignment.assignment_id };
+        request.decided_by = Some(approver);
+        request.decided_at = Some(now);
+        self.add(assignment.clone());
+        Ok(assignment)
+    }
+
+    pub fn deny_elevation(&mut self, request_id: Uuid, approver: Uuid, reason: String, now: DateTime<Utc>) -> AppResult<ElevationRequest> {
+        let request = self.pending_elevation(request_id)?;
+        request.status = ElevationStatus::Denied { reason };
+        request.decided_by = Some(approver);
+        request.decided_at = Some(now);
+        Ok(request.clone())
+    }
+
+    /// A pending elevation request, to check its decider against before deciding it
+    pub fn pending_request(&self, request_id: Uuid) -> AppResult<ElevationRequest> {
+        let request = self.elevations.get(&request_id)
+            .ok_or_else(|| ResearchError::invalid_request(format!("Elevation request not found: {}", request_id)))?;
+        if request.status != ElevationStatus::Pending {
+            return Err(ResearchError::invalid_request(format!("Elevation request already decided: {}", request_id)).into());
+        }
+        Ok(request.clone())
+    }
+
+    pub fn pending_elevations(&self) -> Vec<ElevationRequest> {
+        self.elevations.values()
+            .filter(|r| r.status == ElevationStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    fn pending_elevation(&mut self, request_id: Uuid) -> AppResult<&mut ElevationRequest> {
+        let request = self.elevations.get_mut(&request_id)
+            .ok_or_else(|| ResearchError::invalid_request(format!("Elevation request not found: {}", request_id)))?;
+        if request.status != ElevationStatus::Pending {
+            return Err(ResearchError::invalid_request(format!("Elevation request already decided: {}", request_id)).into());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_is_revoked_at_expiry() {
+        let mut store = RoleAssignmentStore::new(240);
+        let (user, approver) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        let request = store.request_elevation(user, "admin".to_string(), None, 60, "incident 4521".to_string(), now).unwrap();
+        assert!(store.approve_elevation(request.request_id, user, now).is_err());
+
+        let assignment = store.approve_elevation(request.request_id, approver, now).unwrap();
+        let expires_at = assignment.effective_until.unwrap();
+
+        assert!(store.grants(user, "admin", None, now));
+        assert!(store.grants(user, "admin", None, expires_at - Duration::seconds(1)));
+        assert!(!store.grants(user, "admin", None, expires_at));
+
+        assert!(store.expire_due(expires_at - Duration::seconds(1)).is_empty());
+        let expired = store.expire_due(expires_at);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].assignment_id, assignment.assignment_id);
+        assert!(store.expire_due(expires_at).is_empty());
+    }
+
+    #[test]
+    fn test_not_yet_effective_assignment_is_excluded() {
+        let mut store = RoleAssignmentStore::new(240);
+        let user = Uuid::new_v4();
+        let now = Utc::now();
+
+        store.add(RoleAssignment {
+            assignment_id: Uuid::new_v4(),
+            user_id: user,
+            role_id: "auditor".to_string(),
+            tenant_id: None,
+            effective_from: now + Duration::hours(1),
+            effective_until: None,
+            assigned_by: Uuid::new_v4(),
+            justification: None,
+            source: AssignmentSource::Direct,
+            active: true,
+        });
+
+        assert!(!store.grants(user, "auditor", None, now));
+        assert!(store.grants(user, "auditor", None, now + Duration::hours(1)));
+        // Untracked roles are permanent
+        assert!(store.grants(user, "viewer", None, now));
+    }
+
+    #[test]
+    fn test_revoked_permanent_role_stops_granting() {
+        let mut store = RoleAssignmentStore::new(240);
+        let (user, admin) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        assert!(store.grants(user, "editor", None, now));
+        let ended = store.revoke(user, "editor", None, admin, now);
+        assert_eq!(ended.len(), 1);
+        assert!(!store.grants(user, "editor", None, now));
+        // Nothing is left for the reaper to expire
+        assert!(store.expire_due(now).is_empty());
+
+        store.clear_ended(user, "editor", None);
+        assert!(store.grants(user, "editor", None, now));
+    }
+
+    #[test]
+    fn test_permanent_assignment_is_never_reaped() {
+        let mut store = RoleAssignmentStore::new(240);
+        let user = Uuid::new_v4();
+        let now = Utc::now();
+
+        store.add(RoleAssignment {
+            assignment_id: Uuid::new_v4(),
+            user_id: user,
+            role_id: "analyst".to_string(),
+            tenant_id: None,
+            effective_from: now,
+            effective_until: None,
+            assigned_by: Uuid::new_v4(),
+            justification: None,
+            source: AssignmentSource::Direct,
+            active: true,
+        });
+
+        assert!(store.expire_due(now + Duration::days(3650)).is_empty());
+        assert!(store.grants(user, "analyst", None, now + Duration::days(3650)));
+    }
+
+    /// Lets each approver decide elevations in the listed tenant only
+    struct FixtureAuthority(Vec<(Uuid, Option<Uuid>)>);
+
+    #[async_trait]
+    impl ElevationAuthority for FixtureAuthority {
+        async fn may_decide(&self, approver: Uuid, _role_id: &str, tenant_id: Option<Uuid>) -> AppResult<bool> {
+            Ok(self.0.contains(&(approver, tenant_id)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_privileged_approvers_decide_and_grants_stay_in_their_tenant() {
+        let mut store = RoleAssignmentStore::new(240);
+        let (user, approver, bystander) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (acme, globex) = (Some(Uuid::new_v4()), Some(Uuid::new_v4()));
+        let authority = FixtureAuthority(vec![(approver, acme), (bystander, globex)]);
+        let now = Utc::now();
+
+        let request = store.request_elevation(user, "admin".to_string(), acme, 60, "incident 4521".to_string(), now).unwrap();
+        let pending = store.pending_request(request.request_id).unwrap();
+        assert!(check_decider(&authority, &pending, user).await.is_err());
+        // Approving in another tenant does not carry over
+        assert!(matches!(check_decider(&authority, &pending, bystander).await, Err(AppError::PermissionDenied { .. })));
+        assert!(matches!(check_decider(&authority, &pending, Uuid::new_v4()).await, Err(AppError::PermissionDenied { .. })));
+
+        check_decider(&authority, &pending, approver).await.unwrap();
+        store.approve_elevation(request.request_id, approver, now).unwrap();
+        assert!(store.pending_request(request.request_id).is_err());
+
+        assert!(store.grants(user, "admin", acme, now));
+        assert!(!store.grants(user, "admin", globex, now));
+        assert!(!store.grants(user, "admin", None, now));
+    }
+}
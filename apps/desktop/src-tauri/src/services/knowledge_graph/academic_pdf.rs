@@ -0,0 +1,512 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::error::{AppError, AppResult};
+use crate::models::knowledge_graph::{Citation, DataFormat, DocumentFigure, DocumentSection, ExtractedDocument, ExtractionQuality};
+use super::content_extractor::ContentExtractor;
+
+/// Below this much text per page, not counting whitespace, a PDF is taken to be
+/// scanned page images
+const MIN_CHARS_PER_PAGE: usize = 200;
+
+/// Text per page of a fully extracted paper, for the quality score
+const FULL_CHARS_PER_PAGE: usize = 1500;
+
+/// Share of a page's lines that must be split by a column gap for the page to be
+/// read as two columns
+const TWO_COLUMN_LINE_SHARE: f64 = 0.4;
+
+/// Run of spaces that separates the two columns on one extracted line
+const COLUMN_GAP: &str = "    ";
+
+/// Longest heading, in words
+const MAX_HEADING_WORDS: usize = 12;
+
+/// Unnumbered headings of the sections papers commonly have
+const KNOWN_SECTIONS: &[&str] = &[
+    "abstract", "introduction", "background", "related work", "preliminaries", "method", "methods",
+    "methodology", "approach", "experiments", "experimental setup", "evaluation", "results",
+    "discussion", "conclusion", "conclusions", "limitations", "future work",
+    "acknowledgments", "acknowledgements", "references", "bibliography", "appendix",
+];
+
+/// Front matter lines that are not the title
+const FRONT_MATTER_MARKERS: &[&str] = &[
+    "arxiv", "doi", "http", "@", "©", "copyright", "preprint", "proceedings", "journal", "vol.", "conference",
+];
+
+static NUMBERED_HEADING: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d{1,2}(?:\.\d{1,2}){0,2})\.?\s+([A-Z][^.!?]{1,80})$").unwrap());
+static ROMAN_HEADING: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([IVX]{1,5})\.\s+([A-Z][^.!?]{1,80})$").unwrap());
+static INLINE_ABSTRACT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^abstract\s*[-—–:.]\s*(.+)$").unwrap());
+static FIGURE_CAPTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:Figure|Fig\.)\s*(\d+[a-z]?)\s*[.:|]\s*(.+)$").unwrap());
+static BRACKET_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[(\d{1,3})\]\s*").unwrap());
+static NUMBER_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d{1,3})\.\s+").unwrap());
+static DOI: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b(10\.\d{4,9}/[^\s,;]+)").unwrap());
+static ARXIV: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)arxiv[:\s]*(\d{4}\.\d{4,5})").unwrap());
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://[^\s,;]+").unwrap());
+static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b((?:19|20)\d{2})[a-z]?\b").unwrap());
+static YEAR_ONLY: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\(?(?:19|20)\d{2}[a-z]?\)?$").unwrap());
+static QUOTED_TITLE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["“]([^"”]{5,})["”]"#).unwrap());
+
+/// Parses academic papers: title, abstract, sections, figure captions and the
+/// reference list, reading two-column pages column by column
+pub struct AcademicPdfExtractor;
+
+impl ContentExtractor for AcademicPdfExtractor {
+    fn name(&self) -> &'static str {
+        "academic_pdf"
+    }
+
+    fn accepts(&self, format: &DataFormat, content: &[u8]) -> bool {
+        matches!(format, DataFormat::Pdf) && content.starts_with(b"%PDF")
+    }
+
+    fn extract(&self, content: &[u8]) -> AppResult<ExtractedDocument> {
+        let pages = pdf_extract::extract_text_from_mem_by_pages(content)
+            .map_err(|e| AppError::validation("document", format!("Unreadable PDF: {}", e)))?;
+        Ok(parse_pages(self.name(), &pages))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Front,
+    Abstract,
+    Body,
+    References,
+}
+
+/// Parse the text of a paper's pages
+pub fn parse_pages(extractor: &str, pages: &[String]) -> ExtractedDocument {
+    let text_chars: usize = pages.iter()
+        .map(|page| page.chars().filter(|c| !c.is_whitespace()).count())
+        .sum();
+    let mut document = ExtractedDocument {
+        extractor: extractor.to_string(),
+        title: None,
+        abstract_text: None,
+        sections: Vec::new(),
+        figures: Vec::new(),
+        citations: Vec::new(),
+        quality: ExtractionQuality {
+            score: 0.0,
+            page_count: pages.len() as u32,
+            text_chars,
+            two_column_pages: 0,
+            needs_ocr: false,
+            warnings: Vec::new(),
+        },
+        node_id: None,
+    };
+
+    if pages.is_empty() || text_chars < MIN_CHARS_PER_PAGE * pages.len() {
+        document.quality.needs_ocr = true;
+        document.quality.warnings.push("Too little text to parse; the PDF looks scanned and needs OCR".to_string());
+        return document;
+    }
+
+    let mut lines = Vec::new();
+    for page in pages {
+        let (page_lines, two_column) = reading_order(page);
+        if two_column {
+            document.quality.two_column_pages += 1;
+        }
+        lines.extend(page_lines);
+        // A page break ends a paragraph
+        lines.push(String::new());
+    }
+
+    let mut part = Part::Front;
+    let mut front = Vec::new();
+    let mut abstract_lines = Vec::new();
+    let mut reference_lines = Vec::new();
+    let mut sections: Vec<(DocumentSection, Vec<String>)> = Vec::new();
+
+    for line in lines {
+        if part != Part::References {
+            if let Some(caption) = FIGURE_CAPTION.captures(&line) {
+                if !document.figures.iter().any(|figure| figure.label == caption[1]) {
+                    document.figures.push(DocumentFigure { label: caption[1].to_string(), caption: caption[2].trim().to_string() });
+                }
+            }
+        }
+
+        // Reference entries are often numbered like headings; only a named section ends the list
+        let found = heading(&line).filter(|(number, _, _)| part != Part::References || number.is_none());
+        if let Some((number, title, level)) = found {
+            part = match title.to_lowercase().as_str() {
+                "abstract" => Part::Abstract,
+                "references" | "bibliography" => Part::References,
+                _ => {
+                    sections.push((DocumentSection { number, heading: title, level, text: String::new() }, Vec::new()));
+                    Part::Body
+                }
+            };
+            continue;
+        }
+
+        if part == Part::Front {
+            if let Some(inline) = INLINE_ABSTRACT.captures(&line) {
+                part = Part::Abstract;
+                abstract_lines.push(inline[1].to_string());
+                continue;
+            }
+        }
+
+        match part {
+            Part::Front => front.push(line),
+            Part::Abstract => abstract_lines.push(line),
+            Part::Body => if let Some((_, section_lines)) = sections.last_mut() {
+                section_lines.push(line);
+            },
+            Part::References => reference_lines.push(line),
+        }
+    }
+
+    document.title = find_title(&front);
+    document.abstract_text = Some(join_lines(&abstract_lines)).filter(|text| !text.is_empty());
+    document.sections = sections.into_iter()
+        .map(|(mut section, section_lines)| {
+            section.text = join_lines(&section_lines);
+            section
+        })
+        .collect();
+    document.citations = split_references(&reference_lines).iter()
+        .enumerate()
+        .map(|(i, entry)| parse_citation(i as u32 + 1, entry))
+        .collect();
+
+    score(&mut document);
+    document
+}
+
+/// The page's lines in reading order. Two-column pages come out of some PDFs with
+/// both columns side by side on each line; those are read left column first.
+fn reading_order(page: &str) -> (Vec<String>, bool) {
+    let raw: Vec<&str> = page.lines().map(str::trim_end).collect();
+    let non_empty = raw.iter().filter(|line| !line.trim().is_empty()).count();
+    let splits: Vec<Option<(&str, &str, usize)>> = raw.iter().map(|line| column_split(line)).collect();
+    let mut offsets: Vec<usize> = splits.iter().flatten().map(|(_, _, offset)| *offset).collect();
+
+    if non_empty == 0 || (offsets.len() as f64) < non_empty as f64 * TWO_COLUMN_LINE_SHARE {
+        return (raw.iter().map(|line| collapse(line)).collect(), false);
+    }
+
+    offsets.sort_unstable();
+    let boundary = offsets[offsets.len() / 2];
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    for (line, split) in raw.iter().zip(splits) {
+        match split {
+            Some((left_text, right_text, _)) => {
+                left.push(collapse(left_text));
+                right.push(collapse(right_text));
+            }
+            // A line with text only in the right column starts past the gap
+            None if !line.trim().is_empty() && line.len() - line.trim_start().len() + 2 >= boundary => {
+                right.push(collapse(line));
+            }
+            None => left.push(collapse(line)),
+        }
+    }
+    left.extend(right);
+    (left, true)
+}
+
+/// The left and right column text of a line, and where the right column starts
+fn column_split(line: &str) -> Option<(&str, &str, usize)> {
+    let indent = line.len() - line.trim_start().len();
+    let content = line.trim();
+    let gap = content.find(COLUMN_GAP)?;
+    let (left, right) = content.split_at(gap);
+    let offset = indent + gap + (right.len() - right.trim_start().len());
+    Some((left.trim(), right.trim(), offset))
+}
+
+fn collapse(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Section number, heading and level if `line` is a section heading
+fn heading(line: &str) -> Option<(Option<String>, String, u32)> {
+    let bare = line.trim_end_matches(':').trim();
+    if KNOWN_SECTIONS.contains(&bare.to_lowercase().as_str()) {
+        return Some((None, bare.to_string(), 1));
+    }
+    if bare.split_whitespace().count() > MAX_HEADING_WORDS {
+        return None;
+    }
+    if let Some(numbered) = NUMBERED_HEADING.captures(bare) {
+        let level = numbered[1].split('.').count() as u32;
+        return Some((Some(numbered[1].to_string()), numbered[2].trim().to_string(), level));
+    }
+    ROMAN_HEADING.captures(bare).map(|roman| (Some(roman[1].to_string()), roman[2].trim().to_string(), 1))
+}
+
+/// Join lines into text, rejoining words hyphenated across lines and keeping blank
+/// lines as paragraph breaks
+fn join_lines(lines: &[String]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        if line.is_empty() {
+            if !text.is_empty() && !text.ends_with("\n\n") {
+                text.push_str("\n\n");
+            }
+            continue;
+        }
+        if text.ends_with('-') && line.starts_with(|c: char| c.is_lowercase()) {
+            text.pop();
+        } else if !text.is_empty() && !text.ends_with('\n') {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+    text.trim().to_string()
+}
+
+/// The first front matter line that reads like a title, with any lines continuing it
+fn find_title(front: &[String]) -> Option<String> {
+    let start = front.iter().position(|line| {
+        let lowered = line.to_lowercase();
+        line.split_whitespace().count() >= 3
+            && line.len() <= 250
+            && !FRONT_MATTER_MARKERS.iter().any(|marker| lowered.contains(marker))
+    })?;
+
+    let mut title = front[start].clone();
+    for line in &front[start + 1..] {
+        if !line.starts_with(|c: char| c.is_lowercase()) {
+            break;
+        }
+        title.push(' ');
+        title.push_str(line);
+    }
+    Some(title)
+}
+
+/// Split the reference list into entries: by `[1]` or `1.` markers when the list has
+/// them, otherwise by blank lines, or else at lines starting a new sentence
+fn split_references(lines: &[String]) -> Vec<String> {
+    let marker = [&*BRACKET_MARKER, &*NUMBER_MARKER].into_iter()
+        .find(|marker| lines.iter().filter(|line| marker.is_match(line)).count() >= 2);
+    let blank_separated = marker.is_none() && lines.iter().filter(|line| line.is_empty()).count() >= 2;
+
+    let mut entries: Vec<Vec<String>> = Vec::new();
+    let mut entry_ended = true;
+    for line in lines {
+        if line.is_empty() {
+            entry_ended |= blank_separated;
+            continue;
+        }
+        let starts_entry = match marker {
+            Some(marker) => marker.is_match(line),
+            None if blank_separated => entry_ended,
+            None => entries.last()
+                .and_then(|entry| entry.last())
+                .map_or(true, |last| last.ends_with('.') && line.starts_with(|c: char| c.is_uppercase())),
+        };
+        // Text before the first marker is not an entry
+        if starts_entry {
+            entries.push(vec![line.clone()]);
+        } else if let Some(entry) = entries.last_mut() {
+            entry.push(line.clone());
+        }
+        entry_ended = false;
+    }
+
+    entries.into_iter()
+        .map(|entry| {
+            let text = join_lines(&entry);
+            let text = match marker {
+                Some(marker) => marker.replace(&text, "").into_owned(),
+                None => text,
+            };
+            text.trim().to_string()
+        })
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Parse one reference entry in IEEE (quoted title), author-year or numbered style
+fn parse_citation(index: u32, raw: &str) -> Citation {
+    let mut citation = Citation {
+        index,
+        raw: raw.to_string(),
+        authors: Vec::new(),
+        title: None,
+        year: YEAR.captures(raw).and_then(|year| year[1].parse().ok()),
+        venue: None,
+        doi: DOI.captures(raw).map(|doi| doi[1].trim_end_matches('.').to_string()),
+        arxiv_id: ARXIV.captures(raw).map(|arxiv| arxiv[1].to_string()),
+        url: URL.find(raw).map(|url| url.as_str().trim_end_matches('.').to_string()),
+        node_id: None,
+    };
+
+    let (authors, title, venue) = if let Some(quoted) = QUOTED_TITLE.captures(raw) {
+        let whole = quoted.get(0).unwrap();
+        let venue = raw[whole.end()..].trim().trim_start_matches(',').trim().trim_end_matches('.').to_string();
+        (raw[..whole.start()].trim().trim_end_matches(',').to_string(), quoted[1].trim().trim_end_matches(',').to_string(), venue)
+    } else {
+        let mut segments: Vec<String> = sentence_segments(raw).into_iter()
+            .filter(|segment| !YEAR_ONLY.is_match(segment))
+            .collect();
+        // Numbered styles end the author list with a colon: "Doe, J.: Title"
+        if let Some(first) = segments.first().cloned() {
+            if let Some((authors, title)) = first.split_once(": ").filter(|(authors, _)| authors.contains(',')) {
+                segments[0] = title.to_string();
+                segments.insert(0, authors.to_string());
+            }
+        }
+        let mut segments = segments.into_iter();
+        (segments.next().unwrap_or_default(), segments.next().unwrap_or_default(), segments.next().unwrap_or_default())
+    };
+
+    citation.authors = split_authors(&authors);
+    citation.title = Some(title).filter(|title| title.split_whitespace().count() >= 2);
+    citation.venue = Some(venue)
+        .filter(|venue| !venue.is_empty() && !venue.to_lowercase().starts_with("doi") && !venue.starts_with("http"));
+    citation
+}
+
+/// Split text into sentences at periods that do not end an initial or abbreviation
+fn sentence_segments(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    for (i, word) in words.iter().enumerate() {
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        if word.ends_with('.') && i + 1 < words.len() && !is_abbreviation(word) {
+            segments.push(current.trim_end_matches('.').to_string());
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        segments.push(current.trim_end_matches('.').to_string());
+    }
+    segments
+}
+
+/// "J.", "J.-P.", "et al." and the like
+fn is_abbreviation(word: &str) -> bool {
+    let stem = word.trim_end_matches([',', '.']).trim_start_matches('(');
+    matches!(stem.to_lowercase().as_str(), "al" | "ed" | "eds" | "vol" | "pp" | "no")
+        || stem.split(['.', '-']).all(|piece| piece.chars().count() <= 1 && piece.chars().all(char::is_uppercase))
+}
+
+fn split_authors(authors: &str) -> Vec<String> {
+    let authors = YEAR.replace_all(authors, "").replace("()", "");
+    let authors = authors.replace(" and ", ", ").replace(" & ", ", ").replace(';', ",");
+    let parts: Vec<&str> = authors.split(',')
+        .map(|part| part.trim().trim_end_matches("et al.").trim())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    // "Doe, J., Roe, A." gives surnames and initials as separate parts
+    let is_initials = |part: &str| part.split_whitespace().all(is_abbreviation);
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < parts.len() {
+        if i + 1 < parts.len() && !is_initials(parts[i]) && is_initials(parts[i + 1]) {
+            names.push(format!("{}, {}", parts[i], parts[i + 1]));
+            i += 2;
+        } else {
+            names.push(parts[i].to_string());
+            i += 1;
+        }
+    }
+    names
+}
+
+/// Score how much structure was recovered, noting what is missing
+fn score(document: &mut ExtractedDocument) {
+    let quality = &mut document.quality;
+    let mut score = 0.0;
+
+    if document.title.is_some() {
+        score += 0.15;
+    } else {
+        quality.warnings.push("No title found".to_string());
+    }
+    if document.abstract_text.is_some() {
+        score += 0.2;
+    } else {
+        quality.warnings.push("No abstract found".to_string());
+    }
+    if document.sections.is_empty() {
+        quality.warnings.push("No section headings found".to_string());
+    }
+    score += 0.25 * document.sections.len().min(4) as f64 / 4.0;
+
+    if document.citations.is_empty() {
+        quality.warnings.push("No reference list found".to_string());
+    } else {
+        let parsed = document.citations.iter()
+            .filter(|citation| citation.title.is_some() && (citation.year.is_some() || citation.doi.is_some()))
+            .count() as f64 / document.citations.len() as f64;
+        if parsed < 0.5 {
+            quality.warnings.push("Fewer than half of the references could be parsed".to_string());
+        }
+        score += 0.3 * parsed;
+    }
+
+    let density = quality.text_chars as f64 / (quality.page_count.max(1) as usize * FULL_CHARS_PER_PAGE) as f64;
+    score += 0.1 * density.min(1.0);
+    quality.score = (score * 100.0).round() / 100.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_column_paper_structure_and_references() {
+        let first_page = [
+            "Attention Is All You Need",
+            "Ashish Vaswani, Noam Shazeer",
+            "Abstract",
+            "The dominant sequence transduction models are based on complex recur-",
+            "rent networks. We propose a new simple network architecture, the Transformer.",
+            "1 Introduction",
+            "Recurrent neural networks have been firmly established as the state of the art [1].",
+            "Figure 1: The Transformer model architecture.",
+            "Attention mechanisms have become an integral part of sequence modeling in various tasks.",
+        ].join("\n");
+        let second_page = [
+            ("2 Background", "References"),
+            ("Reducing sequential computation is", "[1] S. Hochreiter and J. Schmidhuber,"),
+            ("a goal of many earlier models.", "\"Long short-term memory,\" Neural"),
+            ("", "Computation, 1997."),
+            ("Self-attention relates positions.", "[2] Vaswani, A., Shazeer, N. (2017)."),
+            ("", "Attention is all you need. NeurIPS."),
+            ("It is used in reading comprehension.", "doi:10.5555/3295222.3295349"),
+        ].iter().map(|(left, right)| format!("{:<40}{}", left, right)).collect::<Vec<_>>().join("\n");
+
+        let document = parse_pages("academic_pdf", &[first_page, second_page]);
+        assert!(!document.quality.needs_ocr);
+        assert_eq!(document.quality.two_column_pages, 1);
+        assert_eq!(document.title.as_deref(), Some("Attention Is All You Need"));
+        assert!(document.abstract_text.as_deref().unwrap().starts_with("The dominant sequence transduction models are based on complex recurrent"));
+
+        let headings: Vec<&str> = document.sections.iter().map(|section| section.heading.as_str()).collect();
+        assert_eq!(headings, ["Introduction", "Background"]);
+        assert!(document.sections[1].text.ends_with("It is used in reading comprehension."));
+        assert_eq!(document.figures[0].caption, "The Transformer model architecture.");
+
+        let [ieee, author_year] = &document.citations[..] else { panic!("{:?}", document.citations) };
+        assert_eq!(ieee.authors, ["S. Hochreiter", "J. Schmidhuber"]);
+        assert_eq!(ieee.title.as_deref(), Some("Long short-term memory"));
+        assert_eq!(ieee.year, Some(1997));
+        assert_eq!(author_year.authors, ["Vaswani, A.", "Shazeer, N."]);
+        assert_eq!(author_year.title.as_deref(), Some("Attention is all you need"));
+        assert_eq!(author_year.doi.as_deref(), Some("10.5555/3295222.3295349"));
+        assert!(document.quality.score > 0.6, "{:?}", document.quality);
+
+        // A scanned paper has next to no text layer
+        let scanned = parse_pages("academic_pdf", &["  \n".to_string(), "12\n".to_string()]);
+        assert!(scanned.quality.needs_ocr);
+        assert!(scanned.citations.is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+use crate::error::AppResult;
+use crate::models::knowledge_graph::{DataFormat, ExtractedDocument};
+
+/// Extracts the structure of one kind of document. Extractors are registered with
+/// `KnowledgeGraphService::register_content_extractor`; a document is parsed by the
+/// most recently registered extractor that accepts it.
+pub trait ContentExtractor: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether this extractor can parse `content`, declared to be in `format`
+    fn accepts(&self, format: &DataFormat, content: &[u8]) -> bool;
+
+    /// Parse `content`. CPU-bound; called off the async runtime.
+    fn extract(&self, content: &[u8]) -> AppResult<ExtractedDocument>;
+}
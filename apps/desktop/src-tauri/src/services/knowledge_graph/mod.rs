@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, debug};
+use chrono::Utc;
+use tracing::{info, debug, warn};
 use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::services::{Service, DataPersistenceService};
 use crate::models::knowledge_graph::*;
 
@@ -13,6 +15,8 @@ pub mod data_source_manager;
 pub mod visualization_engine;
 pub mod graph_traversal;
 pub mod knowledge_extractor;
+pub mod content_extractor;
+pub mod academic_pdf;
 
 use node_manager::NodeManager;
 use relationship_manager::RelationshipManager;
@@ -20,6 +24,8 @@ use data_source_manager::DataSourceManager;
 use visualization_engine::VisualizationEngine;
 use graph_traversal::GraphTraversalEngine;
 use knowledge_extractor::KnowledgeExtractor;
+use content_extractor::ContentExtractor;
+use academic_pdf::AcademicPdfExtractor;
 
 /// Global Knowledge Graph Service for interconnected knowledge representation
 pub struct KnowledgeGraphService {
@@ -30,6 +36,7 @@ pub struct KnowledgeGraphService {
     visualization_engine: Arc<RwLock<VisualizationEngine>>,
     graph_traversal: Arc<RwLock<GraphTraversalEngine>>,
     knowledge_extractor: Arc<RwLock<KnowledgeExtractor>>,
+    content_extractors: RwLock<Vec<Arc<dyn ContentExtractor>>>,
 }
 
 impl KnowledgeGraphService {
@@ -72,6 +79,7 @@ impl KnowledgeGraphService {
             visualization_engine,
             graph_traversal,
             knowledge_extractor,
+            content_extractors: RwLock::new(vec![Arc::new(AcademicPdfExtractor) as Arc<dyn ContentExtractor>]),
         })
     }
 
@@ -111,6 +119,169 @@ impl KnowledgeGraphService {
         knowledge_extractor.extract_from_source(source_id).await
     }
 
+    /// Parse documents with `extractor` before the extractors registered earlier
+    pub async fn register_content_extractor(&self, extractor: Arc<dyn ContentExtractor>) {
+        info!("Registering content extractor: {}", extractor.name());
+        self.content_extractors.write().await.insert(0, extractor);
+    }
+
+    /// Extract a document's structure and add it to the graph as a publication, with a
+    /// publication node for every work in its reference list that it cites. The
+    /// extraction quality is kept on the document's node, so documents that need OCR
+    /// can be found later. Works already in the graph, the document itself included,
+    /// are linked rather than added again, so extracting a document twice or citing a
+    /// work from several documents leaves one node per work.
+    pub async fn extract_document(&self, source_id: Option<String>, format: DataFormat, content: Vec<u8>) -> AppResult<ExtractedDocument> {
+        let extractor = self.content_extractors.read().await.iter()
+            .find(|extractor| extractor.accepts(&format, &content))
+            .cloned()
+            .ok_or_else(|| AppError::validation("document", format!("No content extractor accepts {:?} documents", format)))?;
+
+        let mut document = tokio::task::spawn_blocking(move || extractor.extract(&content))
+            .await
+            .map_err(|e| AppError::internal(format!("Content extraction failed: {}", e)))??;
+        info!(
+            "Extracted document {:?} with {}: {} sections, {} references, quality {:.2}",
+            document.title, document.extractor, document.sections.len(), document.citations.len(), document.quality.score
+        );
+        if document.quality.needs_ocr {
+            warn!("Document {:?} has no usable text layer and needs OCR", source_id);
+        }
+
+        let now = Utc::now();
+        let existing_document = self.find_document(source_id.as_deref(), document.title.as_deref()).await?;
+        let mut properties = HashMap::new();
+        properties.insert("extractor".to_string(), serde_json::json!(document.extractor));
+        properties.insert("extraction_quality".to_string(), serde_json::to_value(&document.quality)?);
+        properties.insert("needs_ocr".to_string(), serde_json::json!(document.quality.needs_ocr));
+        properties.insert("section_count".to_string(), serde_json::json!(document.sections.len()));
+        properties.insert("reference_count".to_string(), serde_json::json!(document.citations.len()));
+        let document_node = match existing_document {
+            Some(existing) => existing,
+            None => self.create_knowledge_node(KnowledgeNode {
+                id: Uuid::new_v4(),
+                node_type: NodeType::Publication,
+                name: document.title.clone().unwrap_or_else(|| "Untitled document".to_string()),
+                description: document.abstract_text.clone(),
+                properties,
+                embedding_vector: None,
+                confidence_score: document.quality.score,
+                source_type: SourceType::ImportedData,
+                source_id: source_id.clone(),
+                created_at: now,
+                updated_at: now,
+            }).await?,
+        };
+        document.node_id = Some(document_node.id);
+
+        // Works the document already links to, from an earlier extraction
+        let mut linked: Vec<Uuid> = self.get_node_neighbors(document_node.id, 1).await?
+            .into_iter()
+            .map(|node| node.id)
+            .collect();
+        let mut cited_works: Vec<(Citation, Uuid)> = Vec::new();
+        for citation in document.citations.iter_mut().filter(|citation| citation.title.is_some()) {
+            // A reference list can name the same work twice
+            if let Some((_, node_id)) = cited_works.iter().find(|(earlier, _)| same_work(earlier, citation)) {
+                citation.node_id = Some(*node_id);
+                continue;
+            }
+            let cited = match self.find_publication(citation).await? {
+                Some(existing) => existing,
+                None => self.create_knowledge_node(Self::citation_node(citation, now)).await?,
+            };
+            citation.node_id = Some(cited.id);
+            cited_works.push((citation.clone(), cited.id));
+            if cited.id == document_node.id || linked.contains(&cited.id) {
+                continue;
+            }
+            linked.push(cited.id);
+
+            self.create_relationship(KnowledgeRelationship {
+                id: Uuid::new_v4(),
+                source_node_id: cited.id,
+                target_node_id: document_node.id,
+                relationship_type: RelationshipType::CitedBy,
+                relationship_strength: 1.0,
+                relationship_properties: HashMap::from([("reference_index".to_string(), serde_json::json!(citation.index))]),
+                evidence_sources: vec![EvidenceSource {
+                    source_id: source_id.clone().unwrap_or_else(|| document_node.id.to_string()),
+                    source_type: "document".to_string(),
+                    source_url: citation.url.clone(),
+                    relevance_score: 1.0,
+                    extraction_method: document.extractor.clone(),
+                    timestamp: now,
+                }],
+                confidence_score: cited.confidence_score,
+                created_at: now,
+                updated_at: now,
+            }).await?;
+        }
+
+        Ok(document)
+    }
+
+    /// The publication node already in the graph for a cited work, searched by DOI, then
+    /// arXiv id, then title
+    async fn find_publication(&self, citation: &Citation) -> AppResult<Option<KnowledgeNode>> {
+        let queries = [citation.doi.clone(), citation.arxiv_id.clone(), citation.title.clone()];
+        for query in queries.into_iter().flatten() {
+            let candidates = self.search_nodes(query, Some(vec![NodeType::Publication])).await?;
+            if let Some(found) = candidates.into_iter().find(|node| node_is_work(node, citation)) {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The publication node of a document extracted before: the one from the same
+    /// source, or with the same title when the source is unknown
+    async fn find_document(&self, source_id: Option<&str>, title: Option<&str>) -> AppResult<Option<KnowledgeNode>> {
+        let Some(query) = source_id.or(title) else {
+            return Ok(None);
+        };
+        let candidates = self.search_nodes(query.to_string(), Some(vec![NodeType::Publication])).await?;
+        Ok(candidates.into_iter().find(|node| match source_id {
+            Some(source_id) => node.source_id.as_deref() == Some(source_id),
+            None => title.is_some_and(|title| normalized_title(&node.name) == normalized_title(title)),
+        }))
+    }
+
+    fn citation_node(citation: &Citation, now: chrono::DateTime<Utc>) -> KnowledgeNode {
+        let mut properties = HashMap::new();
+        properties.insert("authors".to_string(), serde_json::json!(citation.authors));
+        for (key, value) in [("doi", &citation.doi), ("arxiv_id", &citation.arxiv_id), ("url", &citation.url), ("venue", &citation.venue)] {
+            if let Some(value) = value {
+                properties.insert(key.to_string(), serde_json::json!(value));
+            }
+        }
+        if let Some(year) = citation.year {
+            properties.insert("year".to_string(), serde_json::json!(year));
+        }
+
+        // A DOI identifies the work; otherwise the parse may be off
+        let confidence_score = if citation.doi.is_some() {
+            1.0
+        } else if citation.year.is_some() {
+            0.7
+        } else {
+            0.4
+        };
+        KnowledgeNode {
+            id: Uuid::new_v4(),
+            node_type: NodeType::Publication,
+            name: citation.title.clone().unwrap_or_default(),
+            description: Some(citation.raw.clone()),
+            properties,
+            embedding_vector: None,
+            confidence_score,
+            source_type: SourceType::AutoGenerated,
+            source_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
     pub async fn get_graph_statistics(&self) -> AppResult<KnowledgeGraphStatistics> {
         debug!("Getting knowledge graph statistics");
         let node_manager = self.node_manager.read().await;
@@ -137,6 +308,50 @@ impl KnowledgeGraphService {
     }
 }
 
+/// Title compared case-, punctuation- and spacing-insensitively, since reference lists
+/// print the same title differently
+fn normalized_title(title: &str) -> String {
+    title.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether two citations name the same work: the same DOI or arXiv id when both have
+/// one, otherwise the same title, in the same year unless either lacks one
+fn same_work(a: &Citation, b: &Citation) -> bool {
+    if let (Some(a), Some(b)) = (&a.doi, &b.doi) {
+        return a.eq_ignore_ascii_case(b);
+    }
+    if let (Some(a), Some(b)) = (&a.arxiv_id, &b.arxiv_id) {
+        return a.eq_ignore_ascii_case(b);
+    }
+    let (Some(title_a), Some(title_b)) = (&a.title, &b.title) else {
+        return false;
+    };
+    normalized_title(title_a) == normalized_title(title_b)
+        && (a.year.is_none() || b.year.is_none() || a.year == b.year)
+}
+
+/// Whether `node` is the publication node of the cited work
+fn node_is_work(node: &KnowledgeNode, citation: &Citation) -> bool {
+    let property = |key: &str| node.properties.get(key).and_then(|value| value.as_str()).map(str::to_string);
+    let known = Citation {
+        index: 0,
+        raw: String::new(),
+        authors: Vec::new(),
+        title: Some(node.name.clone()),
+        year: node.properties.get("year").and_then(|value| value.as_u64()).map(|year| year as u16),
+        venue: None,
+        doi: property("doi"),
+        arxiv_id: property("arxiv_id"),
+        url: None,
+        node_id: Some(node.id),
+    };
+    same_work(&known, citation)
+}
+
 #[async_trait::async_trait]
 impl Service for KnowledgeGraphService {
     async fn health_check(&self) -> AppResult<()> {
@@ -152,3 +367,54 @@ impl Service for KnowledgeGraphService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(title: &str, year: Option<u16>, doi: Option<&str>, arxiv_id: Option<&str>) -> Citation {
+        Citation {
+            index: 1,
+            raw: title.to_string(),
+            authors: Vec::new(),
+            title: Some(title.to_string()),
+            year,
+            venue: None,
+            doi: doi.map(str::to_string),
+            arxiv_id: arxiv_id.map(str::to_string),
+            url: None,
+            node_id: None,
+        }
+    }
+
+    #[test]
+    fn test_cited_works_match_by_doi_arxiv_id_or_title_and_year() {
+        let attention = citation("Attention Is All You Need", Some(2017), None, None);
+
+        // Titles match however they are printed, unless the years differ
+        assert!(same_work(&attention, &citation("attention is all  you need.", Some(2017), None, None)));
+        assert!(same_work(&attention, &citation("Attention is all you need", None, None, None)));
+        assert!(!same_work(&attention, &citation("Attention is all you need", Some(2019), None, None)));
+        assert!(!same_work(&attention, &citation("Attention is not all you need", Some(2017), None, None)));
+
+        // Identifiers decide when both sides have one, whatever the titles say
+        assert!(same_work(
+            &citation("Attention Is All You Need", Some(2017), None, Some("1706.03762")),
+            &citation("Transformer paper", None, None, Some("1706.03762")),
+        ));
+        assert!(!same_work(
+            &citation("Attention Is All You Need", Some(2017), Some("10.1000/a"), None),
+            &citation("Attention Is All You Need", Some(2017), Some("10.1000/b"), None),
+        ));
+        assert!(same_work(
+            &citation("A", None, Some("10.1000/ABC"), None),
+            &citation("B", None, Some("10.1000/abc"), None),
+        ));
+
+        // A node is recognised from the properties it was created with
+        let node = KnowledgeGraphService::citation_node(&citation("Attention Is All You Need", Some(2017), None, Some("1706.03762")), Utc::now());
+        assert!(node_is_work(&node, &citation("Attention is all you need", Some(2017), None, None)));
+        assert!(node_is_work(&node, &citation("Other title", None, None, Some("1706.03762"))));
+        assert!(!node_is_work(&node, &citation("Attention is all you need", Some(2018), None, None)));
+    }
+}
@@ -7,6 +7,9 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
+use crate::models::research_workflow::{ResearchMethodology, WorkflowParameters};
+use crate::models::workflow_rating::{QueryDomain, WorkflowRating};
+use crate::services::research_engine::methodology_feedback;
 
 /// Intelligent recommendation system for research optimization
 #[derive(Clone)]
@@ -78,40 +81,34 @@ impl RecommendationEngine {
     async fn generate_methodology_recommendations(&self, profile: &UserProfile, context: &RecommendationContext) -> AppResult<Option<Vec<Recommendation>>> {
         let mut recommendations = Vec::new();
 
-        // Analyze user's methodology usage patterns
         let current_methodology = context.current_methodology.as_deref().unwrap_or("hybrid");
-        let success_rate = profile.methodology_success_rates.get(current_methodology).unwrap_or(&0.7);
 
-        // Recommend better methodology if current success rate is low
-        if *success_rate < 0.8 {
-            let best_methodology = profile.methodology_success_rates
-                .iter()
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
-                .map(|(method, rate)| (method.clone(), *rate));
-
-            if let Some((best_method, best_rate)) = best_methodology {
-                if best_rate > success_rate + 0.1 {
-                    recommendations.push(Recommendation {
-                        id: Uuid::new_v4(),
-                        recommendation_type: RecommendationType::Methodology,
-                        title: format!("Switch to {} Methodology", best_method.to_uppercase()),
-                        description: format!(
-                            "Based on your research patterns, {} methodology could improve your success rate from {:.1}% to {:.1}%",
-                            best_method, success_rate * 100.0, best_rate * 100.0
-                        ),
-                        relevance_score: 0.9,
-                        confidence: 0.85,
-                        impact_estimate: format!("{:.1}% improvement in success rate", (best_rate - success_rate) * 100.0),
-                        action_required: ActionType::MethodologyChange,
-                        parameters: serde_json::json!({
-                            "recommended_methodology": best_method,
-                            "current_success_rate": success_rate,
-                            "projected_success_rate": best_rate
-                        }),
-                        created_at: Utc::now(),
-                        expires_at: Some(Utc::now() + chrono::Duration::days(7)),
-                    });
-                }
+        // The user's own workflow ratings in the query's domain, weighed the same way the
+        // research engine weighs them when recommending a methodology for a new workflow
+        if let Some(query) = &context.query {
+            let domain = QueryDomain::classify(query);
+            let current: ResearchMethodology = serde_json::from_value(serde_json::json!(current_methodology))
+                .unwrap_or_else(|_| WorkflowParameters::default().methodology);
+            let rated = methodology_feedback::recommend(&profile.user_id.to_string(), domain, current, &context.methodology_ratings);
+            if rated.from_ratings {
+                let best_rating = rated.ratings.first().map(|summary| summary.mean_rating).unwrap_or_default();
+                recommendations.push(Recommendation {
+                    id: Uuid::new_v4(),
+                    recommendation_type: RecommendationType::Methodology,
+                    title: format!("Switch to {}", rated.methodology.display_name()),
+                    description: rated.rationale.clone(),
+                    relevance_score: 0.9,
+                    confidence: 0.85,
+                    impact_estimate: format!("Rated {:.1}/5 for your {} queries", best_rating, domain.as_str()),
+                    action_required: ActionType::MethodologyChange,
+                    parameters: serde_json::json!({
+                        "recommended_methodology": rated.methodology,
+                        "query_domain": rated.query_domain,
+                        "ratings": rated.ratings,
+                    }),
+                    created_at: Utc::now(),
+                    expires_at: Some(Utc::now() + chrono::Duration::days(7)),
+                });
             }
         }
 
@@ -410,6 +407,12 @@ impl Default for RecommendationConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecommendationContext {
     pub current_methodology: Option<String>,
+    /// The query methodology recommendations are for
+    #[serde(default)]
+    pub query: Option<String>,
+    /// The user's ratings of their earlier workflows in the query's domain
+    #[serde(default)]
+    pub methodology_ratings: Vec<WorkflowRating>,
     pub query_complexity: Option<f64>,
     pub recent_performance: Option<f64>,
     pub budget_constraints: Option<f64>,
@@ -455,7 +458,6 @@ pub enum ActionType {
 #[derive(Debug, Clone)]
 pub struct UserProfile {
     pub user_id: Uuid,
-    pub methodology_success_rates: HashMap<String, f64>,
     pub api_usage_stats: HashMap<String, u64>,
     pub api_error_rates: HashMap<String, f64>,
     pub peak_usage_hours: Vec<u32>,
@@ -469,11 +471,6 @@ pub struct UserProfile {
 
 impl UserProfile {
     fn new(user_id: Uuid) -> Self {
-        let mut methodology_success_rates = HashMap::new();
-        methodology_success_rates.insert("hybrid".to_string(), 0.75);
-        methodology_success_rates.insert("don_lim".to_string(), 0.70);
-        methodology_success_rates.insert("nick_scamara".to_string(), 0.72);
-
         let mut api_usage_stats = HashMap::new();
         api_usage_stats.insert("openrouter".to_string(), 45);
         api_usage_stats.insert("tavily".to_string(), 30);
@@ -486,7 +483,6 @@ impl UserProfile {
 
         Self {
             user_id,
-            methodology_success_rates,
             api_usage_stats,
             api_error_rates,
             peak_usage_hours: vec![14, 15, 16], // 2-4 PM
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
 pub mod api_manager;
 pub mod research_engine;
 pub mod template_manager;
+pub mod research_scheduler;
 pub mod data_persistence;
 pub mod monitoring;
 pub mod security;
@@ -39,6 +40,7 @@ use crate::error::{AppError, AppResult};
 use api_manager::ApiManagerService;
 use research_engine::ResearchEngineService;
 use template_manager::TemplateManagerService;
+use research_scheduler::ResearchSchedulerService;
 use data_persistence::DataPersistenceService;
 use monitoring::MonitoringService;
 use security::SecurityService;
@@ -69,12 +71,16 @@ use nlp_engine::NLPEngineService;
 use blockchain::BlockchainService;
 use knowledge_graph::KnowledgeGraphService;
 
+/// How often the auto-scaler looks at load
+const AUTO_SCALING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Central service manager that coordinates all application services
 #[derive(Clone)]
 pub struct ServiceManager {
     pub api_manager: Arc<RwLock<ApiManagerService>>,
     pub research_engine: Arc<RwLock<ResearchEngineService>>,
     pub template_manager: Arc<RwLock<TemplateManagerService>>,
+    pub research_scheduler: Arc<RwLock<ResearchSchedulerService>>,
     pub data_persistence: Arc<RwLock<DataPersistenceService>>,
     pub monitoring: Arc<RwLock<MonitoringService>>,
     pub security: Arc<RwLock<SecurityService>>,
@@ -117,6 +123,17 @@ impl ServiceManager {
         
         // Initialize data persistence service
         let data_persistence = DataPersistenceService::new(security.clone()).await?;
+
+        // Finish a master key rotation that was interrupted before every API
+        // key was re-encrypted; a failure here leaves the old ciphertexts
+        // readable, so startup continues
+        match data_persistence.resume_master_key_rotation().await {
+            Ok(report) if report.reencrypted > 0 => {
+                info!("Resumed master key rotation: re-encrypted {} API keys", report.reencrypted);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to resume master key rotation: {}", e),
+        }
         let data_persistence = Arc::new(RwLock::new(data_persistence));
         
         // Initialize monitoring service
@@ -146,8 +163,16 @@ impl ServiceManager {
         ).await?;
         let template_manager = Arc::new(RwLock::new(template_manager));
 
+        // Initialize research scheduler service
+        let research_scheduler = ResearchSchedulerService::new(
+            data_persistence.clone(),
+            research_engine.clone(),
+            template_manager.clone(),
+        ).await?;
+        let research_scheduler = Arc::new(RwLock::new(research_scheduler));
+
         // Initialize output processor service
-        let output_processor = OutputProcessorService::new().await?;
+        let output_processor = OutputProcessorService::new().await?.with_history_store(data_persistence.clone());
         let output_processor = Arc::new(RwLock::new(output_processor));
 
         // Initialize analytics service
@@ -180,6 +205,7 @@ impl ServiceManager {
             data_persistence.clone(),
         ).await?;
         let nlp_engine_service = Arc::new(RwLock::new(nlp_engine_service));
+        research_engine.read().await.set_nlp_engine(nlp_engine_service.clone()).await;
 
         let blockchain_service = BlockchainService::new(
             data_persistence.clone(),
@@ -190,6 +216,7 @@ impl ServiceManager {
             data_persistence.clone(),
         ).await?;
         let knowledge_graph_service = Arc::new(RwLock::new(knowledge_graph_service));
+        research_engine.read().await.set_knowledge_graph(knowledge_graph_service.clone()).await;
 
         // Initialize V2.0.0 services first
         let ai_orchestration = Arc::new(RwLock::new(AIOrchestrationService::new().await?));
@@ -208,6 +235,7 @@ impl ServiceManager {
             api_manager,
             research_engine,
             template_manager,
+            research_scheduler,
             data_persistence,
             monitoring,
             security,
@@ -264,12 +292,24 @@ impl ServiceManager {
             data_persistence.start_background_tasks().await?;
         }
 
+        // Start enterprise background tasks (role assignment expiry)
+        {
+            let enterprise = self.enterprise.read().await;
+            enterprise.start_background_tasks().await?;
+        }
+
         // Start template manager background monitoring
         {
             let template_manager = self.template_manager.read().await;
             template_manager.start_background_monitoring().await?;
         }
 
+        // Start research scheduler (recurring workflows)
+        {
+            let research_scheduler = self.research_scheduler.read().await;
+            research_scheduler.start_background_tasks().await?;
+        }
+
         // Start analytics service processing
         {
             let analytics = self.analytics.read().await;
@@ -307,10 +347,61 @@ impl ServiceManager {
             knowledge_graph.start_background_tasks().await?;
         }
 
+        // Start auto-scaling on research queue, provider and node metrics
+        self.start_auto_scaling();
+
         info!("Background services started successfully");
         Ok(())
     }
     
+    /// Feed the research queue depth and provider metrics to the distributed auto-scaler,
+    /// recording each change it makes in analytics
+    fn start_auto_scaling(&self) {
+        let research_engine = self.research_engine.clone();
+        let api_manager = self.api_manager.clone();
+        let distributed = self.distributed.clone();
+        let analytics = self.analytics.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(AUTO_SCALING_INTERVAL);
+            loop {
+                interval.tick().await;
+                let queue_depth = match research_engine.read().await.get_queue_statistics().await {
+                    Ok(stats) => stats.queue_length,
+                    Err(e) => {
+                        error!("Failed to read research queue depth for auto-scaling: {}", e);
+                        continue;
+                    }
+                };
+                let providers = api_manager.read().await.get_all_service_metrics().await;
+                let decisions = match distributed.read().await.run_auto_scaling(queue_depth, providers.values()).await {
+                    Ok(decisions) => decisions,
+                    Err(e) => {
+                        error!("Auto-scaling failed: {}", e);
+                        continue;
+                    }
+                };
+
+                for decision in decisions {
+                    let metadata = match serde_json::to_value(&decision) {
+                        Ok(serde_json::Value::Object(fields)) => fields.into_iter().collect(),
+                        _ => std::collections::HashMap::new(),
+                    };
+                    let event = analytics::AnalyticsEvent {
+                        event_type: analytics::EventType::ScalingDecision,
+                        timestamp: decision.decided_at,
+                        user_id: None,
+                        session_id: "auto-scaler".to_string(),
+                        metadata,
+                    };
+                    if let Err(e) = analytics.read().await.record_event(event).await {
+                        warn!("Failed to record scaling decision for {}: {}", decision.service_name, e);
+                    }
+                }
+            }
+        });
+    }
+
     /// Perform health check on all services
     pub async fn health_check(&self) -> AppResult<ServiceHealthStatus> {
         let mut status = ServiceHealthStatus::default();
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, MonitoringError};
+use crate::utils::air_gap;
 use super::{HealthStatus, HealthLevel, ComponentHealth};
 
 /// Health checker for monitoring system component health
@@ -71,6 +72,7 @@ impl HealthChecker {
             components,
             last_check: check_time,
             uptime_seconds,
+            offline_mode: air_gap::is_air_gapped(),
         };
         
         debug!("Health checks completed, overall status: {:?}", overall_status);
@@ -97,6 +99,7 @@ impl HealthChecker {
             components,
             last_check: Utc::now(),
             uptime_seconds: (Utc::now() - self.last_check).num_seconds() as u64,
+            offline_mode: air_gap::is_air_gapped(),
         })
     }
     
@@ -135,6 +138,9 @@ impl HealthChecker {
     /// Check API services health
     async fn check_api_services_health(&self) -> ComponentHealth {
         debug!("Checking API services health");
+        if air_gap::is_air_gapped() {
+            return Self::offline_component("external API services are not checked");
+        }
 
         let start_time = std::time::Instant::now();
 
@@ -313,6 +319,9 @@ impl HealthChecker {
     /// Check network connectivity health
     async fn check_network_health(&self) -> ComponentHealth {
         debug!("Checking network health");
+        if air_gap::is_air_gapped() {
+            return Self::offline_component("outbound network access is turned off");
+        }
         
         let start_time = std::time::Instant::now();
         
@@ -369,6 +378,17 @@ impl HealthChecker {
         results
     }
     
+    /// A component that is not checked in offline mode. Unknown rather than critical, so
+    /// an intentionally disconnected install still reports healthy overall.
+    fn offline_component(reason: &str) -> ComponentHealth {
+        ComponentHealth {
+            status: HealthLevel::Unknown,
+            message: format!("Offline mode: {}", reason),
+            last_check: Utc::now(),
+            response_time_ms: None,
+        }
+    }
+    
     /// Calculate overall health from component health
     fn calculate_overall_health(&self, components: &HashMap<String, ComponentHealth>) -> HealthLevel {
         if components.is_empty() {
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, MonitoringError};
+use crate::utils::air_gap;
 use super::SystemMetrics;
 
 /// Metrics collector for gathering system performance data
@@ -189,6 +190,9 @@ impl MetricsCollector {
     /// Check network connectivity
     async fn check_network_connectivity(&self) -> AppResult<bool> {
         debug!("Checking network connectivity");
+        if air_gap::is_air_gapped() {
+            return Ok(false);
+        }
         
         // Try to connect to a reliable external service
         let client = reqwest::Client::builder()
@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::error::{AppResult, MonitoringError};
 use crate::services::{Service, DataPersistenceService};
+use crate::utils::air_gap;
 
 pub mod metrics_collector;
 pub mod health_checker;
@@ -45,6 +46,9 @@ pub struct HealthStatus {
     pub components: HashMap<String, ComponentHealth>,
     pub last_check: DateTime<Utc>,
     pub uptime_seconds: u64,
+    /// Outbound network access is turned off; external services are not checked
+    #[serde(default)]
+    pub offline_mode: bool,
 }
 
 /// Health level indicators
@@ -210,6 +214,7 @@ impl MonitoringService {
             components,
             last_check: metrics.timestamp,
             uptime_seconds: metrics.uptime_seconds,
+            offline_mode: air_gap::is_air_gapped(),
         })
     }
 
@@ -0,0 +1,458 @@
+use std::collections::{HashMap, HashSet};
+use tracing::{info, debug};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::error::AppResult;
+use crate::models::execution_metrics::WorkflowExecutionMetrics;
+use crate::models::research_workflow::{ResearchWorkflow, WorkflowStatus};
+use super::performance_analyzer::{
+    PerformanceAnalyzer, PerformanceMetrics, PerformanceGrade, PerformanceDistribution,
+    AggregatePerformanceMetrics,
+};
+use super::similarity_detector::{SimilarityDetector, SimilarityOptions};
+
+/// Running aggregate for a numeric series (Welford's online algorithm)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningStatistics {
+    pub count: usize,
+    pub mean: f64,
+    pub m2: f64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+}
+
+impl Default for RunningStatistics {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::MAX,
+            max: f64::MIN,
+            sum: 0.0,
+        }
+    }
+}
+
+impl RunningStatistics {
+    /// Fold a new observation into the aggregate
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Population variance of the observed values
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Population standard deviation of the observed values
+    pub fn std_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    fn min_or_zero(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.min }
+    }
+
+    fn max_or_zero(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.max }
+    }
+}
+
+/// Cluster maintained incrementally around a seed workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalCluster {
+    pub cluster_id: u32,
+    pub seed_workflow: ResearchWorkflow,
+    pub workflow_ids: Vec<Uuid>,
+    pub execution_time: RunningStatistics,
+    pub step_count: RunningStatistics,
+    pub success_rate: RunningStatistics,
+}
+
+/// Summary view of a cluster, suitable for returning to clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalClusterSummary {
+    pub cluster_id: u32,
+    pub representative_workflow: Uuid,
+    pub workflow_ids: Vec<Uuid>,
+    pub average_execution_time: f64,
+    pub average_step_count: f64,
+    pub average_success_rate: f64,
+}
+
+/// Analysis state that can be updated as workflows complete without reprocessing the corpus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalAnalysisState {
+    pub id: Uuid,
+    pub processed_workflow_ids: HashSet<Uuid>,
+    pub completed_workflows: usize,
+    pub status_distribution: HashMap<String, u32>,
+    pub execution_time: RunningStatistics,
+    pub step_count: RunningStatistics,
+    pub success_rate: RunningStatistics,
+    pub throughput: RunningStatistics,
+    pub efficiency: RunningStatistics,
+    pub sorted_execution_times: Vec<f64>,
+    pub performance_distribution: PerformanceDistribution,
+    pub workflow_metrics: HashMap<Uuid, PerformanceMetrics>,
+    pub clusters: Vec<IncrementalCluster>,
+    pub similarity_threshold: f64,
+    pub created_at: DateTime<Utc>,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// Snapshot of the incremental analysis at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalAnalysisSummary {
+    pub state_id: Uuid,
+    pub workflow_count: usize,
+    pub completion_rate: f64,
+    pub mean_execution_time_minutes: f64,
+    pub execution_time_std_deviation: f64,
+    pub mean_step_count: f64,
+    pub mean_success_rate: f64,
+    pub status_distribution: HashMap<String, u32>,
+    pub aggregate_performance: AggregatePerformanceMetrics,
+    pub clusters: Vec<IncrementalClusterSummary>,
+    pub newly_processed: usize,
+    pub full_recompute: bool,
+    pub last_updated: DateTime<Utc>,
+    pub processing_time_ms: u64,
+}
+
+impl Default for IncrementalAnalysisState {
+    fn default() -> Self {
+        Self::new(SimilarityOptions::default().similarity_threshold)
+    }
+}
+
+impl IncrementalAnalysisState {
+    /// Create an empty analysis state
+    pub fn new(similarity_threshold: f64) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            processed_workflow_ids: HashSet::new(),
+            completed_workflows: 0,
+            status_distribution: HashMap::new(),
+            execution_time: RunningStatistics::default(),
+            step_count: RunningStatistics::default(),
+            success_rate: RunningStatistics::default(),
+            throughput: RunningStatistics::default(),
+            efficiency: RunningStatistics::default(),
+            sorted_execution_times: Vec::new(),
+            performance_distribution: PerformanceDistribution {
+                excellent_count: 0,
+                very_good_count: 0,
+                good_count: 0,
+                average_count: 0,
+                below_average_count: 0,
+                poor_count: 0,
+            },
+            workflow_metrics: HashMap::new(),
+            clusters: Vec::new(),
+            similarity_threshold,
+            created_at: now,
+            last_updated: now,
+        }
+    }
+
+    /// Number of workflows folded into the state
+    pub fn workflow_count(&self) -> usize {
+        self.processed_workflow_ids.len()
+    }
+
+    /// Fold newly-completed workflows into the running aggregates.
+    ///
+    /// Workflows that are still active or were already processed are skipped, so callers
+    /// can pass overlapping batches. A workflow's metrics come from the resource use in
+    /// `actuals` when it has any, and are estimated otherwise. Returns the number of
+    /// workflows actually folded in.
+    pub async fn apply(
+        &mut self,
+        new_workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+        performance_analyzer: &PerformanceAnalyzer,
+        similarity_detector: &SimilarityDetector,
+    ) -> AppResult<usize> {
+        let mut applied = 0;
+
+        for workflow in new_workflows {
+            if !workflow.is_completed() || self.processed_workflow_ids.contains(&workflow.id) {
+                continue;
+            }
+
+            let metrics = performance_analyzer.calculate_workflow_performance_metrics(workflow, actuals.get(&workflow.id)).await?;
+            self.fold_metrics(workflow, &metrics);
+            self.assign_cluster(workflow, &metrics, similarity_detector).await?;
+
+            self.workflow_metrics.insert(workflow.id, metrics);
+            self.processed_workflow_ids.insert(workflow.id);
+            applied += 1;
+        }
+
+        if applied > 0 {
+            self.last_updated = Utc::now();
+        }
+
+        debug!("Folded {} workflows into incremental analysis {}", applied, self.id);
+        Ok(applied)
+    }
+
+    /// Update running aggregates with one workflow's metrics
+    fn fold_metrics(&mut self, workflow: &ResearchWorkflow, metrics: &PerformanceMetrics) {
+        if workflow.status == WorkflowStatus::Completed {
+            self.completed_workflows += 1;
+        }
+        *self.status_distribution.entry(format!("{:?}", workflow.status)).or_insert(0) += 1;
+
+        self.execution_time.push(metrics.execution_time_minutes);
+        self.step_count.push(metrics.step_count as f64);
+        self.success_rate.push(metrics.success_rate);
+        self.throughput.push(metrics.throughput_score);
+        self.efficiency.push(metrics.efficiency_score);
+
+        let position = self.sorted_execution_times
+            .partition_point(|t| *t < metrics.execution_time_minutes);
+        self.sorted_execution_times.insert(position, metrics.execution_time_minutes);
+
+        let distribution = &mut self.performance_distribution;
+        match metrics.performance_grade {
+            PerformanceGrade::Excellent => distribution.excellent_count += 1,
+            PerformanceGrade::VeryGood => distribution.very_good_count += 1,
+            PerformanceGrade::Good => distribution.good_count += 1,
+            PerformanceGrade::Average => distribution.average_count += 1,
+            PerformanceGrade::BelowAverage => distribution.below_average_count += 1,
+            PerformanceGrade::Poor => distribution.poor_count += 1,
+        }
+    }
+
+    /// Assign a workflow to the first cluster whose seed is similar enough, or start a new one.
+    ///
+    /// Seeds are visited in creation order, which yields the same grouping as the greedy
+    /// threshold clustering used by the full similarity analysis.
+    async fn assign_cluster(
+        &mut self,
+        workflow: &ResearchWorkflow,
+        metrics: &PerformanceMetrics,
+        similarity_detector: &SimilarityDetector,
+    ) -> AppResult<()> {
+        let mut target = None;
+        for (index, cluster) in self.clusters.iter().enumerate() {
+            let score = similarity_detector
+                .calculate_pairwise_similarity(&cluster.seed_workflow, workflow)
+                .await?;
+            if score.overall_similarity >= self.similarity_threshold {
+                target = Some(index);
+                break;
+            }
+        }
+
+        let index = match target {
+            Some(index) => index,
+            None => {
+                self.clusters.push(IncrementalCluster {
+                    cluster_id: self.clusters.len() as u32,
+                    seed_workflow: workflow.clone(),
+                    workflow_ids: Vec::new(),
+                    execution_time: RunningStatistics::default(),
+                    step_count: RunningStatistics::default(),
+                    success_rate: RunningStatistics::default(),
+                });
+                self.clusters.len() - 1
+            }
+        };
+
+        let cluster = &mut self.clusters[index];
+        cluster.workflow_ids.push(workflow.id);
+        cluster.execution_time.push(metrics.execution_time_minutes);
+        cluster.step_count.push(metrics.step_count as f64);
+        cluster.success_rate.push(metrics.success_rate);
+
+        Ok(())
+    }
+
+    /// Median of the observed execution times
+    fn median_execution_time(&self) -> f64 {
+        let times = &self.sorted_execution_times;
+        if times.is_empty() {
+            0.0
+        } else if times.len() % 2 == 0 {
+            (times[times.len() / 2 - 1] + times[times.len() / 2]) / 2.0
+        } else {
+            times[times.len() / 2]
+        }
+    }
+
+    /// Benchmark aggregates equivalent to `PerformanceAnalyzer`'s full pass
+    pub fn aggregate_performance(&self) -> AggregatePerformanceMetrics {
+        AggregatePerformanceMetrics {
+            total_workflows: self.workflow_count(),
+            average_execution_time: self.execution_time.mean,
+            median_execution_time: self.median_execution_time(),
+            fastest_execution_time: self.execution_time.min_or_zero(),
+            slowest_execution_time: self.execution_time.max_or_zero(),
+            overall_success_rate: self.success_rate.mean,
+            average_throughput: self.throughput.mean,
+            resource_efficiency: self.efficiency.mean,
+            performance_distribution: self.performance_distribution.clone(),
+        }
+    }
+
+    /// Build a client-facing summary of the current state
+    pub fn summarize(&self, newly_processed: usize, full_recompute: bool, processing_time_ms: u64) -> IncrementalAnalysisSummary {
+        let workflow_count = self.workflow_count();
+        let completion_rate = if workflow_count == 0 {
+            0.0
+        } else {
+            self.completed_workflows as f64 / workflow_count as f64
+        };
+
+        let clusters = self.clusters.iter()
+            .map(|cluster| IncrementalClusterSummary {
+                cluster_id: cluster.cluster_id,
+                representative_workflow: cluster.seed_workflow.id,
+                workflow_ids: cluster.workflow_ids.clone(),
+                average_execution_time: cluster.execution_time.mean,
+                average_step_count: cluster.step_count.mean,
+                average_success_rate: cluster.success_rate.mean,
+            })
+            .collect();
+
+        IncrementalAnalysisSummary {
+            state_id: self.id,
+            workflow_count,
+            completion_rate,
+            mean_execution_time_minutes: self.execution_time.mean,
+            execution_time_std_deviation: self.execution_time.std_deviation(),
+            mean_step_count: self.step_count.mean,
+            mean_success_rate: self.success_rate.mean,
+            status_distribution: self.status_distribution.clone(),
+            aggregate_performance: self.aggregate_performance(),
+            clusters,
+            newly_processed,
+            full_recompute,
+            last_updated: self.last_updated,
+            processing_time_ms,
+        }
+    }
+}
+
+/// Rebuild an analysis state from scratch over the full corpus
+pub async fn recompute_state(
+    workflows: &[ResearchWorkflow],
+    actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    similarity_threshold: f64,
+    performance_analyzer: &PerformanceAnalyzer,
+    similarity_detector: &SimilarityDetector,
+) -> AppResult<IncrementalAnalysisState> {
+    info!("Recomputing incremental analysis state over {} workflows", workflows.len());
+
+    let mut state = IncrementalAnalysisState::new(similarity_threshold);
+    state.apply(workflows, actuals, performance_analyzer, similarity_detector).await?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::{StepStatus, WorkflowParameters, WorkflowStep};
+
+    fn completed_workflow(query: &str, minutes: i64, steps: usize, failed_steps: usize) -> ResearchWorkflow {
+        let mut workflow = ResearchWorkflow::new(
+            format!("Workflow {}", query),
+            query.to_string(),
+            WorkflowParameters::default(),
+            "test".to_string(),
+        );
+
+        for i in 0..steps {
+            let mut step = WorkflowStep::new(workflow.id, i as u32, format!("step_{}", i), String::new());
+            step.status = if i < failed_steps { StepStatus::Failed } else { StepStatus::Completed };
+            workflow.add_step(step);
+        }
+
+        let started = Utc::now() - chrono::Duration::minutes(minutes);
+        workflow.started_at = Some(started);
+        workflow.status = WorkflowStatus::Completed;
+        workflow.completed_at = Some(started + chrono::Duration::minutes(minutes));
+        workflow
+    }
+
+    fn workflow_success_rate(workflow: &ResearchWorkflow) -> f64 {
+        if workflow.steps.is_empty() {
+            0.0
+        } else {
+            let completed = workflow.steps.iter().filter(|s| s.status == StepStatus::Completed).count();
+            completed as f64 / workflow.steps.len() as f64
+        }
+    }
+
+    fn corpus() -> Vec<ResearchWorkflow> {
+        vec![
+            completed_workflow("rust async runtimes", 5, 4, 0),
+            completed_workflow("rust async executors", 7, 4, 1),
+            completed_workflow("quantum error correction", 42, 9, 2),
+            completed_workflow("quantum annealing hardware", 37, 8, 0),
+            completed_workflow("climate model ensembles", 18, 6, 3),
+            completed_workflow("rust async io", 6, 5, 0),
+            completed_workflow("climate model downscaling", 21, 6, 1),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_incremental_matches_full_recompute() {
+        let performance_analyzer = PerformanceAnalyzer::new().await.unwrap();
+        let similarity_detector = SimilarityDetector::new().await.unwrap();
+        let workflows = corpus();
+
+        let mut incremental = IncrementalAnalysisState::default();
+        for batch in workflows.chunks(2) {
+            incremental.apply(batch, &HashMap::new(), &performance_analyzer, &similarity_detector).await.unwrap();
+        }
+        // Re-applying an already processed batch must be a no-op
+        let reapplied = incremental.apply(&workflows[..2], &HashMap::new(), &performance_analyzer, &similarity_detector).await.unwrap();
+        assert_eq!(reapplied, 0);
+
+        let benchmark = performance_analyzer.analyze_performance(&workflows).await.unwrap();
+
+        let tolerance = 1e-9;
+        let incremental_aggregate = incremental.aggregate_performance();
+        assert_eq!(incremental_aggregate.total_workflows, benchmark.aggregate_metrics.total_workflows);
+        assert!((incremental_aggregate.average_execution_time - benchmark.aggregate_metrics.average_execution_time).abs() < tolerance);
+        assert!((incremental_aggregate.median_execution_time - benchmark.aggregate_metrics.median_execution_time).abs() < tolerance);
+        assert!((incremental_aggregate.overall_success_rate - benchmark.aggregate_metrics.overall_success_rate).abs() < tolerance);
+        assert!((incremental_aggregate.resource_efficiency - benchmark.aggregate_metrics.resource_efficiency).abs() < tolerance);
+
+        let expected_mean_success = workflows.iter().map(workflow_success_rate).sum::<f64>() / workflows.len() as f64;
+        assert!((incremental.success_rate.mean - expected_mean_success).abs() < tolerance);
+        let times: Vec<f64> = benchmark.workflow_metrics.iter().map(|m| m.execution_time_minutes).collect();
+        let mean_time = times.iter().sum::<f64>() / times.len() as f64;
+        let expected_variance = times.iter().map(|t| (t - mean_time).powi(2)).sum::<f64>() / times.len() as f64;
+        assert!((incremental.execution_time.variance() - expected_variance).abs() < 1e-6);
+
+        // The clusters are the ones the full similarity analysis finds over the whole corpus
+        let full = similarity_detector.detect_similarity(&workflows).await.unwrap();
+        let incremental_clusters: Vec<(Uuid, Vec<Uuid>)> = incremental.clusters.iter()
+            .map(|c| (c.seed_workflow.id, c.workflow_ids.clone()))
+            .collect();
+        let full_clusters: Vec<(Uuid, Vec<Uuid>)> = full.clusters.iter()
+            .map(|c| (c.representative_workflow.unwrap(), c.workflow_ids.clone()))
+            .collect();
+        assert_eq!(incremental_clusters, full_clusters);
+    }
+}
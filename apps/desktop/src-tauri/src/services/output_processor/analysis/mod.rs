@@ -7,17 +7,21 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
+use crate::models::execution_metrics::WorkflowExecutionMetrics;
 use crate::models::research_workflow::{ResearchWorkflow, StepStatus, WorkflowStatus};
 
 pub mod comparison_engine;
 pub mod analysis_engine;
 pub mod similarity_detector;
 pub mod performance_analyzer;
+pub mod incremental_analysis;
+pub mod result_diff;
 
 use self::comparison_engine::{ComparisonEngine, ComparisonRequest, ComparisonResult};
 use self::analysis_engine::{AnalysisEngine, AnalysisRequest, AnalysisResult};
 use self::similarity_detector::{SimilarityDetector, SimilarityScore, ClusterResult};
 use self::performance_analyzer::{PerformanceAnalyzer, PerformanceMetrics, BenchmarkResult};
+use self::incremental_analysis::{IncrementalAnalysisState, IncrementalAnalysisSummary};
 
 /// Analysis service for research workflow comparison and insights
 pub struct AnalysisService {
@@ -27,6 +31,7 @@ pub struct AnalysisService {
     performance_analyzer: Arc<PerformanceAnalyzer>,
     analysis_history: Arc<RwLock<Vec<AnalysisResult>>>,
     comparison_history: Arc<RwLock<Vec<ComparisonResult>>>,
+    incremental_state: Arc<RwLock<IncrementalAnalysisState>>,
 }
 
 /// Analysis request types
@@ -224,6 +229,7 @@ impl AnalysisService {
         let performance_analyzer = Arc::new(PerformanceAnalyzer::new().await?);
         let analysis_history = Arc::new(RwLock::new(Vec::new()));
         let comparison_history = Arc::new(RwLock::new(Vec::new()));
+        let incremental_state = Arc::new(RwLock::new(IncrementalAnalysisState::default()));
 
         let service = Self {
             comparison_engine,
@@ -232,6 +238,7 @@ impl AnalysisService {
             performance_analyzer,
             analysis_history,
             comparison_history,
+            incremental_state,
         };
 
         info!("Analysis service initialized successfully");
@@ -348,6 +355,51 @@ impl AnalysisService {
         Ok(result)
     }
 
+    /// Fold newly-completed workflows into the running analysis (incremental fast path)
+    pub async fn update_analysis(
+        &self,
+        new_workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    ) -> AppResult<IncrementalAnalysisSummary> {
+        let start_time = std::time::Instant::now();
+
+        let mut state = self.incremental_state.write().await;
+        let applied = state.apply(new_workflows, actuals, &self.performance_analyzer, &self.similarity_detector).await?;
+
+        info!("Incremental analysis updated with {} new workflows ({} total)", applied, state.workflow_count());
+        Ok(state.summarize(applied, false, start_time.elapsed().as_millis() as u64))
+    }
+
+    /// Discard the running analysis and rebuild it over the full corpus
+    pub async fn recompute_analysis(
+        &self,
+        workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    ) -> AppResult<IncrementalAnalysisSummary> {
+        let start_time = std::time::Instant::now();
+
+        let similarity_threshold = self.incremental_state.read().await.similarity_threshold;
+        let rebuilt = incremental_analysis::recompute_state(
+            workflows,
+            actuals,
+            similarity_threshold,
+            &self.performance_analyzer,
+            &self.similarity_detector,
+        ).await?;
+
+        let applied = rebuilt.workflow_count();
+        let summary = rebuilt.summarize(applied, true, start_time.elapsed().as_millis() as u64);
+        *self.incremental_state.write().await = rebuilt;
+
+        info!("Incremental analysis recomputed over {} workflows", applied);
+        Ok(summary)
+    }
+
+    /// Get the current incremental analysis without processing anything new
+    pub async fn get_incremental_analysis(&self) -> IncrementalAnalysisSummary {
+        self.incremental_state.read().await.summarize(0, false, 0)
+    }
+
     /// Apply filters to workflows
     fn apply_filters(&self, workflows: &[ResearchWorkflow], filters: &AnalysisFilters) -> Vec<ResearchWorkflow> {
         workflows.iter()
@@ -512,4 +564,7 @@ impl AnalysisService {
 pub use comparison_engine::{ComparisonEngine, ComparisonRequest, ComparisonResult};
 pub use analysis_engine::{AnalysisEngine, AnalysisRequest, AnalysisResult};
 pub use similarity_detector::{SimilarityDetector, SimilarityScore, ClusterResult};
-pub use performance_analyzer::{PerformanceAnalyzer, PerformanceMetrics, BenchmarkResult};
+pub use performance_analyzer::{PerformanceAnalyzer, PerformanceMetrics, BenchmarkResult, PerformanceExplanation};
+pub use incremental_analysis::{IncrementalAnalysisState, IncrementalAnalysisSummary, IncrementalClusterSummary};
+pub use result_diff::{diff_results, ResultDiffReport, SummaryDelta};
+pub use crate::models::research_insight::{InsightCategory, ResearchInsight};
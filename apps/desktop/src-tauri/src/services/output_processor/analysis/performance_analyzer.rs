@@ -5,6 +5,7 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
+use crate::models::execution_metrics::WorkflowExecutionMetrics;
 use crate::models::research_workflow::{ResearchWorkflow, StepStatus, WorkflowStatus};
 
 /// Performance analyzer for workflow benchmarking and optimization
@@ -89,10 +90,81 @@ pub struct BenchmarkResult {
     pub benchmark_insights: Vec<BenchmarkInsight>,
     pub optimization_recommendations: Vec<OptimizationRecommendation>,
     pub performance_trends: PerformanceTrends,
+    pub explanations: Vec<PerformanceExplanation>,
     pub created_at: DateTime<Utc>,
     pub processing_time_ms: u64,
 }
 
+/// Explanation of why a workflow received its benchmark score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceExplanation {
+    pub workflow_id: Uuid,
+    pub overall_score: f64,
+    pub performance_grade: PerformanceGrade,
+    pub score_factors: Vec<ScoreFactor>,
+    pub percentile_ranks: PercentileRanks,
+    pub step_attributions: Vec<StepAttribution>,
+    pub provider_attributions: Vec<ProviderAttribution>,
+    pub source_count_impact: SourceCountImpact,
+    pub summary: String,
+}
+
+/// Contribution of a single factor to the overall score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreFactor {
+    pub factor_name: String,
+    pub weight: f64,
+    pub raw_score: f64,
+    pub contribution: f64,
+    pub lost_points: f64,
+}
+
+/// Where a workflow sits relative to the corpus (0-100, higher is better)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileRanks {
+    pub execution_time: f64,
+    pub success_rate: f64,
+    pub efficiency: f64,
+    pub throughput: f64,
+    pub overall: f64,
+}
+
+/// Share of a workflow's duration attributable to one step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepAttribution {
+    pub step_index: usize,
+    pub step_name: String,
+    pub service_provider: Option<String>,
+    pub duration_ms: u64,
+    pub share_of_duration: f64,
+    pub failed: bool,
+    pub retries: u32,
+    pub dominant: bool,
+}
+
+/// Latency and error profile of a provider within one workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderAttribution {
+    pub provider: String,
+    pub step_count: u32,
+    pub average_latency_ms: f64,
+    pub error_rate: f64,
+    pub corpus_average_latency_ms: f64,
+    pub corpus_error_rate: f64,
+    pub high_latency: bool,
+    pub high_error_rate: bool,
+}
+
+/// How the number of sources explains execution time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceCountImpact {
+    pub source_count: u32,
+    pub corpus_minutes_per_source: f64,
+    pub expected_execution_time_minutes: f64,
+    pub actual_execution_time_minutes: f64,
+    pub residual_minutes: f64,
+}
+
 /// Aggregate performance metrics across all workflows
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatePerformanceMetrics {
@@ -235,6 +307,16 @@ impl PerformanceAnalyzer {
 
     /// Analyze performance of workflows
     pub async fn analyze_performance(&self, workflows: &[ResearchWorkflow]) -> AppResult<BenchmarkResult> {
+        self.analyze_performance_with_actuals(workflows, &HashMap::new()).await
+    }
+
+    /// Analyze performance of workflows, using the resource use recorded for them where
+    /// there is any instead of estimating it
+    pub async fn analyze_performance_with_actuals(
+        &self,
+        workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    ) -> AppResult<BenchmarkResult> {
         info!("Analyzing performance of {} workflows", workflows.len());
 
         let start_time = std::time::Instant::now();
@@ -248,7 +330,7 @@ impl PerformanceAnalyzer {
         // Calculate performance metrics for each workflow
         let mut workflow_metrics = Vec::new();
         for workflow in workflows {
-            let metrics = self.calculate_workflow_performance_metrics(workflow).await?;
+            let metrics = self.calculate_workflow_performance_metrics(workflow, actuals.get(&workflow.id)).await?;
             workflow_metrics.push(metrics);
         }
 
@@ -267,6 +349,9 @@ impl PerformanceAnalyzer {
         // Analyze performance trends
         let performance_trends = self.analyze_performance_trends(workflows).await?;
 
+        // Explain each workflow's score relative to the corpus
+        let explanations = self.explain_performance(workflows, &workflow_metrics);
+
         let processing_time = start_time.elapsed();
 
         Ok(BenchmarkResult {
@@ -277,16 +362,25 @@ impl PerformanceAnalyzer {
             benchmark_insights,
             optimization_recommendations,
             performance_trends,
+            explanations,
             created_at: Utc::now(),
             processing_time_ms: processing_time.as_millis() as u64,
         })
     }
 
     /// Calculate performance metrics for a single workflow
-    async fn calculate_workflow_performance_metrics(&self, workflow: &ResearchWorkflow) -> AppResult<PerformanceMetrics> {
+    pub(crate) async fn calculate_workflow_performance_metrics(
+        &self,
+        workflow: &ResearchWorkflow,
+        actuals: Option<&WorkflowExecutionMetrics>,
+    ) -> AppResult<PerformanceMetrics> {
+        let actuals = actuals.filter(|actuals| !actuals.is_empty());
+
         // Calculate execution time
         let execution_time_minutes = if let (Some(started), Some(completed)) = (workflow.started_at, workflow.completed_at) {
             (completed - started).num_minutes() as f64
+        } else if let Some(actuals) = actuals {
+            actuals.wall_time_ms as f64 / 60_000.0
         } else {
             0.0
         };
@@ -314,7 +408,7 @@ impl PerformanceAnalyzer {
         };
 
         // Estimate resource utilization (placeholder values)
-        let resource_utilization = ResourceMetrics {
+        let mut resource_utilization = ResourceMetrics {
             memory_usage_mb: 100.0 + (workflow.steps.len() as f64 * 10.0),
             cpu_usage_percent: 20.0 + (workflow.steps.len() as f64 * 2.0),
             api_calls_count: workflow.steps.len() as u32 * 3,
@@ -322,6 +416,14 @@ impl PerformanceAnalyzer {
             cache_hit_rate: 0.8,
             error_rate: 1.0 - success_rate,
         };
+        // Provider use recorded as the steps ran replaces the estimates
+        if let Some(actuals) = actuals {
+            resource_utilization.api_calls_count = actuals.provider_calls;
+            resource_utilization.bandwidth_mb = actuals.bytes_fetched as f64 / (1024.0 * 1024.0);
+            if actuals.provider_calls > 0 {
+                resource_utilization.error_rate = actuals.failed_provider_calls as f64 / actuals.provider_calls as f64;
+            }
+        }
 
         // Detect bottlenecks
         let bottlenecks = self.detect_workflow_bottlenecks(workflow).await?;
@@ -394,15 +496,41 @@ impl PerformanceAnalyzer {
         Ok(bottlenecks)
     }
 
-    /// Calculate performance grade based on metrics
-    fn calculate_performance_grade(&self, execution_time: f64, success_rate: f64, efficiency_score: f64) -> PerformanceGrade {
+    /// Weighted score factors that make up the overall performance score
+    fn calculate_score_factors(&self, execution_time: f64, success_rate: f64, efficiency_score: f64) -> Vec<ScoreFactor> {
         // Weighted scoring system
         let time_score = if execution_time <= 5.0 { 1.0 } else if execution_time <= 15.0 { 0.8 } else if execution_time <= 30.0 { 0.6 } else if execution_time <= 60.0 { 0.4 } else { 0.2 };
         let success_score = success_rate;
         let efficiency_normalized = efficiency_score.min(2.0) / 2.0; // Normalize to 0-1
 
-        let overall_score = (time_score * 0.3 + success_score * 0.5 + efficiency_normalized * 0.2);
+        [
+            ("Execution time", 0.3, time_score),
+            ("Step success rate", 0.5, success_score),
+            ("Efficiency", 0.2, efficiency_normalized),
+        ]
+        .iter()
+        .map(|(name, weight, raw_score)| ScoreFactor {
+            factor_name: name.to_string(),
+            weight: *weight,
+            raw_score: *raw_score,
+            contribution: raw_score * weight,
+            lost_points: (1.0 - raw_score) * weight,
+        })
+        .collect()
+    }
+
+    /// Calculate performance grade based on metrics
+    fn calculate_performance_grade(&self, execution_time: f64, success_rate: f64, efficiency_score: f64) -> PerformanceGrade {
+        let overall_score: f64 = self.calculate_score_factors(execution_time, success_rate, efficiency_score)
+            .iter()
+            .map(|f| f.contribution)
+            .sum();
 
+        Self::grade_for_score(overall_score)
+    }
+
+    /// Map an overall score (0-1) to a grade
+    fn grade_for_score(overall_score: f64) -> PerformanceGrade {
         match overall_score {
             s if s >= 0.95 => PerformanceGrade::Excellent,
             s if s >= 0.85 => PerformanceGrade::VeryGood,
@@ -674,6 +802,274 @@ impl PerformanceAnalyzer {
         })
     }
 
+    /// Build per-workflow factor attribution and corpus-relative ranks
+    fn explain_performance(
+        &self,
+        workflows: &[ResearchWorkflow],
+        workflow_metrics: &[PerformanceMetrics],
+    ) -> Vec<PerformanceExplanation> {
+        let execution_times: Vec<f64> = workflow_metrics.iter().map(|m| m.execution_time_minutes).collect();
+        let success_rates: Vec<f64> = workflow_metrics.iter().map(|m| m.success_rate).collect();
+        let efficiencies: Vec<f64> = workflow_metrics.iter().map(|m| m.efficiency_score).collect();
+        let throughputs: Vec<f64> = workflow_metrics.iter().map(|m| m.throughput_score).collect();
+        let overall_scores: Vec<f64> = workflow_metrics.iter()
+            .map(|m| {
+                self.calculate_score_factors(m.execution_time_minutes, m.success_rate, m.efficiency_score)
+                    .iter()
+                    .map(|f| f.contribution)
+                    .sum()
+            })
+            .collect();
+
+        let corpus_providers = self.corpus_provider_profile(workflows);
+        let (source_intercept, source_slope) = self.fit_source_count_model(workflows, workflow_metrics);
+
+        workflows.iter()
+            .zip(workflow_metrics.iter())
+            .enumerate()
+            .map(|(index, (workflow, metrics))| {
+                let score_factors = self.calculate_score_factors(
+                    metrics.execution_time_minutes,
+                    metrics.success_rate,
+                    metrics.efficiency_score,
+                );
+                let overall_score = overall_scores[index];
+
+                let percentile_ranks = PercentileRanks {
+                    // Lower execution time is better
+                    execution_time: 100.0 - Self::percentile_rank(&execution_times, metrics.execution_time_minutes),
+                    success_rate: Self::percentile_rank(&success_rates, metrics.success_rate),
+                    efficiency: Self::percentile_rank(&efficiencies, metrics.efficiency_score),
+                    throughput: Self::percentile_rank(&throughputs, metrics.throughput_score),
+                    overall: Self::percentile_rank(&overall_scores, overall_score),
+                };
+
+                let step_attributions = self.attribute_steps(workflow);
+                let provider_attributions = self.attribute_providers(workflow, &corpus_providers);
+
+                let source_count = workflow.results.as_ref().map(|r| r.source_count).unwrap_or(0);
+                let expected = (source_intercept + source_slope * source_count as f64).max(0.0);
+                let source_count_impact = SourceCountImpact {
+                    source_count,
+                    corpus_minutes_per_source: source_slope,
+                    expected_execution_time_minutes: expected,
+                    actual_execution_time_minutes: metrics.execution_time_minutes,
+                    residual_minutes: metrics.execution_time_minutes - expected,
+                };
+
+                let summary = self.summarize_explanation(
+                    &score_factors,
+                    &percentile_ranks,
+                    &step_attributions,
+                    &provider_attributions,
+                    &source_count_impact,
+                );
+
+                PerformanceExplanation {
+                    workflow_id: workflow.id,
+                    overall_score,
+                    performance_grade: Self::grade_for_score(overall_score),
+                    score_factors,
+                    percentile_ranks,
+                    step_attributions,
+                    provider_attributions,
+                    source_count_impact,
+                    summary,
+                }
+            })
+            .collect()
+    }
+
+    /// Percentage of the corpus strictly below `value`, with ties counted as half
+    fn percentile_rank(values: &[f64], value: f64) -> f64 {
+        if values.len() < 2 {
+            return 100.0;
+        }
+
+        let below = values.iter().filter(|v| **v < value).count() as f64;
+        let equal = values.iter().filter(|v| **v == value).count() as f64 - 1.0;
+        ((below + equal / 2.0) / (values.len() - 1) as f64 * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Duration of a single step in milliseconds
+    fn step_duration_ms(step: &crate::models::research_workflow::WorkflowStep) -> u64 {
+        if let Some(ms) = step.execution_time_ms {
+            return ms as u64;
+        }
+        match (step.started_at, step.completed_at) {
+            (Some(started), Some(completed)) => (completed - started).num_milliseconds().max(0) as u64,
+            _ => 0,
+        }
+    }
+
+    /// Attribute a workflow's duration to its steps
+    fn attribute_steps(&self, workflow: &ResearchWorkflow) -> Vec<StepAttribution> {
+        let durations: Vec<u64> = workflow.steps.iter().map(Self::step_duration_ms).collect();
+        let total: u64 = durations.iter().sum();
+
+        let mut attributions: Vec<StepAttribution> = workflow.steps.iter()
+            .zip(durations.iter())
+            .enumerate()
+            .map(|(index, (step, duration))| {
+                let share = if total > 0 { *duration as f64 / total as f64 } else { 0.0 };
+                StepAttribution {
+                    step_index: index,
+                    step_name: step.name.clone(),
+                    service_provider: step.service_provider.clone(),
+                    duration_ms: *duration,
+                    share_of_duration: share,
+                    failed: step.status == StepStatus::Failed,
+                    retries: step.retry_count,
+                    // A step dominates when it takes more than its fair share by a wide margin
+                    dominant: !workflow.steps.is_empty() && share >= (2.0 / workflow.steps.len() as f64).min(0.5),
+                }
+            })
+            .collect();
+
+        attributions.sort_by(|a, b| b.duration_ms.cmp(&a.duration_ms));
+        attributions
+    }
+
+    /// Average latency and error rate per provider across the corpus
+    fn corpus_provider_profile(&self, workflows: &[ResearchWorkflow]) -> HashMap<String, (f64, f64)> {
+        let mut totals: HashMap<String, (u64, u32, u32)> = HashMap::new();
+        for workflow in workflows {
+            for step in &workflow.steps {
+                if let Some(provider) = &step.service_provider {
+                    let entry = totals.entry(provider.clone()).or_insert((0, 0, 0));
+                    entry.0 += Self::step_duration_ms(step);
+                    entry.1 += 1;
+                    if step.status == StepStatus::Failed {
+                        entry.2 += 1;
+                    }
+                }
+            }
+        }
+
+        totals.into_iter()
+            .map(|(provider, (duration, count, failures))| {
+                (provider, (duration as f64 / count as f64, failures as f64 / count as f64))
+            })
+            .collect()
+    }
+
+    /// Compare each provider used by a workflow against its corpus profile
+    fn attribute_providers(
+        &self,
+        workflow: &ResearchWorkflow,
+        corpus_providers: &HashMap<String, (f64, f64)>,
+    ) -> Vec<ProviderAttribution> {
+        let mut totals: HashMap<String, (u64, u32, u32)> = HashMap::new();
+        for step in &workflow.steps {
+            if let Some(provider) = &step.service_provider {
+                let entry = totals.entry(provider.clone()).or_insert((0, 0, 0));
+                entry.0 += Self::step_duration_ms(step);
+                entry.1 += 1;
+                if step.status == StepStatus::Failed {
+                    entry.2 += 1;
+                }
+            }
+        }
+
+        let mut attributions: Vec<ProviderAttribution> = totals.into_iter()
+            .map(|(provider, (duration, count, failures))| {
+                let average_latency_ms = duration as f64 / count as f64;
+                let error_rate = failures as f64 / count as f64;
+                let (corpus_average_latency_ms, corpus_error_rate) = corpus_providers
+                    .get(&provider)
+                    .copied()
+                    .unwrap_or((average_latency_ms, error_rate));
+
+                ProviderAttribution {
+                    high_latency: corpus_average_latency_ms > 0.0 && average_latency_ms > corpus_average_latency_ms * 1.5,
+                    high_error_rate: error_rate > 0.0 && error_rate > corpus_error_rate.max(0.1),
+                    provider,
+                    step_count: count,
+                    average_latency_ms,
+                    error_rate,
+                    corpus_average_latency_ms,
+                    corpus_error_rate,
+                }
+            })
+            .collect();
+
+        attributions.sort_by(|a, b| b.average_latency_ms.partial_cmp(&a.average_latency_ms).unwrap_or(std::cmp::Ordering::Equal));
+        attributions
+    }
+
+    /// Least-squares fit of execution time against source count across the corpus
+    fn fit_source_count_model(&self, workflows: &[ResearchWorkflow], workflow_metrics: &[PerformanceMetrics]) -> (f64, f64) {
+        let points: Vec<(f64, f64)> = workflows.iter()
+            .zip(workflow_metrics.iter())
+            .filter_map(|(w, m)| w.results.as_ref().map(|r| (r.source_count as f64, m.execution_time_minutes)))
+            .collect();
+
+        if points.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let variance_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+        let slope = if variance_x > 0.0 { covariance / variance_x } else { 0.0 };
+        (mean_y - slope * mean_x, slope)
+    }
+
+    /// One-paragraph human readable explanation
+    fn summarize_explanation(
+        &self,
+        score_factors: &[ScoreFactor],
+        percentile_ranks: &PercentileRanks,
+        step_attributions: &[StepAttribution],
+        provider_attributions: &[ProviderAttribution],
+        source_count_impact: &SourceCountImpact,
+    ) -> String {
+        let mut parts = vec![format!(
+            "Ranks at the {:.0}th percentile overall ({:.0}th for execution time).",
+            percentile_ranks.overall, percentile_ranks.execution_time
+        )];
+
+        if let Some(weakest) = score_factors.iter()
+            .max_by(|a, b| a.lost_points.partial_cmp(&b.lost_points).unwrap_or(std::cmp::Ordering::Equal))
+            .filter(|f| f.lost_points > 0.0)
+        {
+            parts.push(format!("Most points were lost on {} ({:.2} of {:.2}).", weakest.factor_name.to_lowercase(), weakest.contribution, weakest.weight));
+        }
+
+        let dominant: Vec<String> = step_attributions.iter()
+            .filter(|s| s.dominant)
+            .map(|s| format!("'{}' ({:.0}%)", s.step_name, s.share_of_duration * 100.0))
+            .collect();
+        if !dominant.is_empty() {
+            parts.push(format!("Duration was dominated by {}.", dominant.join(", ")));
+        }
+
+        for provider in provider_attributions.iter().filter(|p| p.high_latency || p.high_error_rate) {
+            parts.push(format!(
+                "Provider {} averaged {:.0}ms (corpus {:.0}ms) with a {:.0}% error rate.",
+                provider.provider,
+                provider.average_latency_ms,
+                provider.corpus_average_latency_ms,
+                provider.error_rate * 100.0
+            ));
+        }
+
+        if source_count_impact.residual_minutes.abs() >= 1.0 {
+            parts.push(format!(
+                "With {} sources the corpus predicts {:.1} minutes; actual was {:.1} ({:+.1}).",
+                source_count_impact.source_count,
+                source_count_impact.expected_execution_time_minutes,
+                source_count_impact.actual_execution_time_minutes,
+                source_count_impact.residual_minutes
+            ));
+        }
+
+        parts.join(" ")
+    }
+
     /// Calculate variance of a dataset
     fn calculate_variance(&self, data: &[f64]) -> f64 {
         if data.len() < 2 {
@@ -684,4 +1080,84 @@ impl PerformanceAnalyzer {
         let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64;
         variance
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::{WorkflowParameters, WorkflowStep};
+
+    #[test]
+    fn test_percentile_rank_orders_corpus() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(PerformanceAnalyzer::percentile_rank(&values, 1.0), 0.0);
+        assert_eq!(PerformanceAnalyzer::percentile_rank(&values, 3.0), 50.0);
+        assert_eq!(PerformanceAnalyzer::percentile_rank(&values, 5.0), 100.0);
+        assert_eq!(PerformanceAnalyzer::percentile_rank(&[2.0], 2.0), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_steps_flags_dominant_step() {
+        let analyzer = PerformanceAnalyzer::new().await.unwrap();
+        let mut workflow = ResearchWorkflow::new(
+            "Workflow".to_string(),
+            "query".to_string(),
+            WorkflowParameters::default(),
+            "test".to_string(),
+        );
+
+        for (i, duration) in [100u32, 100, 1800].iter().enumerate() {
+            let mut step = WorkflowStep::new(workflow.id, i as u32, format!("step_{}", i), String::new());
+            step.execution_time_ms = Some(*duration);
+            step.status = StepStatus::Completed;
+            workflow.add_step(step);
+        }
+
+        let attributions = analyzer.attribute_steps(&workflow);
+        assert_eq!(attributions[0].step_name, "step_2");
+        assert!(attributions[0].dominant);
+        assert!((attributions[0].share_of_duration - 0.9).abs() < 1e-9);
+        assert!(attributions[1..].iter().all(|a| !a.dominant));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_actuals_replace_estimated_resource_use() {
+        let analyzer = PerformanceAnalyzer::new().await.unwrap();
+        let workflow = ResearchWorkflow::new(
+            "Workflow".to_string(),
+            "query".to_string(),
+            WorkflowParameters::default(),
+            "test".to_string(),
+        );
+        let actuals = WorkflowExecutionMetrics {
+            workflow_id: workflow.id,
+            wall_time_ms: 120_000,
+            step_time_ms: 120_000,
+            provider_calls: 8,
+            failed_provider_calls: 2,
+            tokens: 1500,
+            bytes_fetched: 2 * 1024 * 1024,
+            failed_attempts: 1,
+            steps: vec![crate::models::execution_metrics::StepExecutionMetrics {
+                workflow_id: workflow.id,
+                step_id: Uuid::new_v4(),
+                step_number: 0,
+                step_name: "search".to_string(),
+                service_provider: None,
+                started_at: Utc::now(),
+                completed_at: Utc::now(),
+                wall_time_ms: 120_000,
+                provider_calls: 8,
+                failed_provider_calls: 2,
+                tokens: 1500,
+                bytes_fetched: 2 * 1024 * 1024,
+                succeeded: true,
+            }],
+        };
+
+        let metrics = analyzer.calculate_workflow_performance_metrics(&workflow, Some(&actuals)).await.unwrap();
+        assert_eq!(metrics.execution_time_minutes, 2.0);
+        assert_eq!(metrics.resource_utilization.api_calls_count, 8);
+        assert_eq!(metrics.resource_utilization.bandwidth_mb, 2.0);
+        assert_eq!(metrics.resource_utilization.error_rate, 0.25);
+    }
+}
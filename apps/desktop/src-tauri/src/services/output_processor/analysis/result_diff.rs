@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_schedule::ResultDiff;
+use crate::models::research_insight::{InsightCategory, ResearchInsight, INSIGHTS_METADATA_KEY};
+use crate::models::research_workflow::{ResearchResults, ResearchWorkflow};
+
+/// How the report summary and headline counts moved between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryDelta {
+    pub previous_summary: Option<String>,
+    pub current_summary: Option<String>,
+    pub summary_changed: bool,
+    pub previous_word_count: u32,
+    pub current_word_count: u32,
+    pub word_count_change: i64,
+    pub previous_source_count: u32,
+    pub current_source_count: u32,
+    pub source_count_change: i64,
+}
+
+/// "What's new" report comparing a later run of a query with an earlier one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultDiffReport {
+    pub id: Uuid,
+    /// The earlier workflow
+    pub workflow_a: Uuid,
+    /// The later workflow, whose new material the report highlights
+    pub workflow_b: Uuid,
+    pub query: String,
+    pub new_sources: Vec<String>,
+    pub removed_sources: Vec<String>,
+    pub retained_source_count: usize,
+    /// Key findings in the later run that the earlier run did not report
+    pub new_key_findings: Vec<ResearchInsight>,
+    /// Other new insights: trends, contradictions, gaps and recommendations
+    pub new_insights: Vec<ResearchInsight>,
+    /// Insights of the earlier run no longer reported
+    pub removed_insights: Vec<ResearchInsight>,
+    /// Paragraphs of the later report that the earlier report did not contain
+    pub new_paragraphs: Vec<String>,
+    pub summary: SummaryDelta,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ResultDiffReport {
+    pub fn has_changes(&self) -> bool {
+        !self.new_sources.is_empty()
+            || !self.removed_sources.is_empty()
+            || !self.new_key_findings.is_empty()
+            || !self.new_insights.is_empty()
+            || !self.removed_insights.is_empty()
+            || !self.new_paragraphs.is_empty()
+            || self.summary.summary_changed
+    }
+}
+
+/// Compare the results of two workflows; `workflow_a` is the earlier run
+pub fn diff_results(workflow_a: &ResearchWorkflow, workflow_b: &ResearchWorkflow) -> AppResult<ResultDiffReport> {
+    if workflow_a.id == workflow_b.id {
+        return Err(AppError::validation("workflow_b", "Cannot diff a workflow's results against themselves"));
+    }
+
+    let previous = completed_results(workflow_a)?;
+    let current = completed_results(workflow_b)?;
+
+    let changes = ResultDiff::between(Some(workflow_a.id), Some(previous), current);
+
+    let previous_insights = report_insights(previous);
+    let current_insights = report_insights(current);
+    let previous_keys: HashSet<String> = previous_insights.iter().map(|i| insight_key(&i.insight)).collect();
+    let current_keys: HashSet<String> = current_insights.iter().map(|i| insight_key(&i.insight)).collect();
+
+    let (new_key_findings, new_insights): (Vec<_>, Vec<_>) = current_insights.into_iter()
+        .filter(|insight| !previous_keys.contains(&insight_key(&insight.insight)))
+        .partition(|insight| insight.category == InsightCategory::KeyFinding);
+    let removed_insights = previous_insights.into_iter()
+        .filter(|insight| !current_keys.contains(&insight_key(&insight.insight)))
+        .collect();
+
+    let previous_summary = extract_summary(&previous.content);
+    let current_summary = extract_summary(&current.content);
+
+    Ok(ResultDiffReport {
+        id: Uuid::new_v4(),
+        workflow_a: workflow_a.id,
+        workflow_b: workflow_b.id,
+        query: workflow_b.query.clone(),
+        retained_source_count: current.sources.len() - changes.new_sources.len(),
+        new_sources: changes.new_sources,
+        removed_sources: changes.removed_sources,
+        new_key_findings,
+        new_insights,
+        removed_insights,
+        new_paragraphs: changes.new_findings,
+        summary: SummaryDelta {
+            summary_changed: previous_summary.as_deref().map(insight_key) != current_summary.as_deref().map(insight_key),
+            previous_summary,
+            current_summary,
+            previous_word_count: previous.word_count,
+            current_word_count: current.word_count,
+            word_count_change: current.word_count as i64 - previous.word_count as i64,
+            previous_source_count: previous.source_count,
+            current_source_count: current.source_count,
+            source_count_change: current.source_count as i64 - previous.source_count as i64,
+        },
+        created_at: Utc::now(),
+    })
+}
+
+fn completed_results(workflow: &ResearchWorkflow) -> AppResult<&ResearchResults> {
+    workflow.results.as_ref().ok_or_else(|| AppError::validation(
+        "workflow_id",
+        format!("Workflow {} has no results to diff", workflow.id),
+    ))
+}
+
+/// The insights a report's analysis step returned as structured output, or those parsed
+/// from the report text when it returned none
+pub fn report_insights(results: &ResearchResults) -> Vec<ResearchInsight> {
+    results.metadata.get(INSIGHTS_METADATA_KEY)
+        .and_then(|insights| serde_json::from_value::<Vec<ResearchInsight>>(insights.clone()).ok())
+        .filter(|insights| !insights.is_empty())
+        .unwrap_or_else(|| extract_insights(&results.content))
+}
+
+/// Bulleted and numbered items of a report, categorized by the heading they sit under.
+/// Items under headings that match no category are not treated as insights.
+pub fn extract_insights(content: &str) -> Vec<ResearchInsight> {
+    let mut insights = Vec::new();
+    let mut seen = HashSet::new();
+    let mut category = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(heading) = heading_text(line) {
+            category = classify_heading(heading);
+            continue;
+        }
+
+        let (Some(category), Some(item)) = (category, list_item_text(line)) else {
+            continue;
+        };
+        let insight = item.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !insight.is_empty() && seen.insert(insight_key(&insight)) {
+            insights.push(ResearchInsight { insight, category, confidence: None, supporting_sources: Vec::new() });
+        }
+    }
+
+    insights
+}
+
+/// The paragraph under the report's summary heading, or its first prose paragraph
+fn extract_summary(content: &str) -> Option<String> {
+    let mut in_summary = false;
+    let mut first_paragraph = None;
+    let mut summary = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(heading) = heading_text(line) {
+            if !summary.is_empty() {
+                break;
+            }
+            in_summary = heading.to_lowercase().contains("summary");
+            continue;
+        }
+        if line.is_empty() {
+            if !summary.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if list_item_text(line).is_some() {
+            continue;
+        }
+        if in_summary {
+            summary.push(line);
+        } else if first_paragraph.is_none() {
+            first_paragraph = Some(line.to_string());
+        }
+    }
+
+    if summary.is_empty() {
+        first_paragraph
+    } else {
+        Some(summary.join(" "))
+    }
+}
+
+/// The report without its statements of any of `insights`: the list items, and the
+/// sentences of prose paragraphs, that contain one word for word. Headings, tables and
+/// code blocks are left alone, as are statements that reword the insight.
+pub fn remove_insight_statements(content: &str, insights: &[ResearchInsight]) -> String {
+    let keys: Vec<String> = insights.iter()
+        .map(|insight| format!(" {} ", statement_key(&insight.insight)))
+        .filter(|key| !key.trim().is_empty())
+        .collect();
+    let states_insight = |text: &str| {
+        let text = format!(" {} ", statement_key(text));
+        keys.iter().any(|key| text.contains(key.as_str()))
+    };
+
+    let mut in_code_block = false;
+    let mut kept = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || trimmed.starts_with("```") || trimmed.is_empty() || trimmed.starts_with('|') || heading_text(trimmed).is_some() {
+            kept.push(line.to_string());
+        } else if let Some(item) = list_item_text(trimmed) {
+            if !states_insight(item) {
+                kept.push(line.to_string());
+            }
+        } else {
+            let sentences: Vec<&str> = sentences(trimmed).into_iter().filter(|sentence| !states_insight(sentence)).collect();
+            if !sentences.is_empty() {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                kept.push(format!("{}{}", indent, sentences.join(" ")));
+            }
+        }
+    }
+    kept.join("\n")
+}
+
+/// The sentences of a line of prose, ending at `.`, `!` or `?` before whitespace
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_break = matches!(c, '.' | '!' | '?') && chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+        if at_break {
+            sentences.push(text[start..=i].trim());
+            start = i + 1;
+        }
+    }
+    if !text[start..].trim().is_empty() {
+        sentences.push(text[start..].trim());
+    }
+    sentences
+}
+
+/// Text of a markdown heading, or of a line that is entirely bold
+fn heading_text(line: &str) -> Option<&str> {
+    if line.starts_with('#') {
+        return Some(line.trim_start_matches('#').trim());
+    }
+    line.strip_prefix("**")
+        .and_then(|rest| rest.strip_suffix("**"))
+        .filter(|text| !text.contains("**"))
+        .map(|text| text.trim_end_matches(':').trim())
+}
+
+fn classify_heading(heading: &str) -> Option<InsightCategory> {
+    let heading = heading.to_lowercase();
+    if heading.contains("finding") || heading.contains("insight") || heading.contains("takeaway") {
+        Some(InsightCategory::KeyFinding)
+    } else if heading.contains("trend") {
+        Some(InsightCategory::Trend)
+    } else if heading.contains("contradict") || heading.contains("conflict") || heading.contains("disagree") {
+        Some(InsightCategory::Contradiction)
+    } else if heading.contains("gap") || heading.contains("limitation") || heading.contains("further research") || heading.contains("open question") {
+        Some(InsightCategory::GapInKnowledge)
+    } else if heading.contains("recommendation") || heading.contains("next step") {
+        Some(InsightCategory::Recommendation)
+    } else {
+        None
+    }
+}
+
+fn list_item_text(line: &str) -> Option<&str> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).or_else(|| line.strip_prefix("+ ")) {
+        return Some(item);
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        return rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "));
+    }
+    None
+}
+
+/// Comparison key, so rewording in case, emphasis or spacing does not count as a new insight
+fn insight_key(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c == '*' || c == '_' || c == '`').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Comparison key of a statement within running text, so the punctuation around its
+/// words does not keep it from matching
+fn statement_key(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::models::research_workflow::{ResearchMethodology, WorkflowParameters};
+
+    fn workflow_with_results(content: &str, sources: &[&str]) -> ResearchWorkflow {
+        let mut workflow = ResearchWorkflow::new(
+            "Monitor".to_string(),
+            "solid state batteries".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        workflow.results = Some(ResearchResults {
+            content: content.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            metadata: HashMap::new(),
+            word_count: content.split_whitespace().count() as u32,
+            source_count: sources.len() as u32,
+            methodology_used: ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        });
+        workflow
+    }
+
+    #[test]
+    fn test_diff_results_highlights_new_key_findings() {
+        let earlier = workflow_with_results(
+            "## Executive Summary\nProgress is steady.\n\n## Key Findings\n- Energy density doubled\n- Costs remain high\n\n## Recommendations\n1. Track pilot lines",
+            &["https://a.example", "https://b.example"],
+        );
+        let later = workflow_with_results(
+            "## Executive Summary\nA first production line opened.\n\n## Key Findings\n- **Energy density** doubled\n- First production line opened\n\n## Trends\n- Suppliers are consolidating",
+            &["https://b.example", "https://c.example"],
+        );
+
+        let report = diff_results(&earlier, &later).unwrap();
+        assert_eq!(report.new_sources, vec!["https://c.example".to_string()]);
+        assert_eq!(report.removed_sources, vec!["https://a.example".to_string()]);
+        assert_eq!(report.retained_source_count, 1);
+
+        let new_findings: Vec<&str> = report.new_key_findings.iter().map(|i| i.insight.as_str()).collect();
+        assert_eq!(new_findings, vec!["First production line opened"]);
+        assert_eq!(report.new_insights.len(), 1);
+        assert_eq!(report.new_insights[0].category, InsightCategory::Trend);
+        assert_eq!(report.removed_insights.len(), 2);
+
+        assert!(report.summary.summary_changed);
+        assert_eq!(report.summary.current_summary.as_deref(), Some("A first production line opened."));
+        assert!(report.has_changes());
+
+        assert!(!diff_results(&earlier, &workflow_with_results(
+            &earlier.results.as_ref().unwrap().content,
+            &["https://a.example", "https://b.example"],
+        )).unwrap().has_changes());
+    }
+
+    #[test]
+    fn test_insight_statements_leave_lists_and_prose() {
+        let insight = |text: &str| ResearchInsight {
+            insight: text.to_string(),
+            category: InsightCategory::KeyFinding,
+            confidence: None,
+            supporting_sources: Vec::new(),
+        };
+        let content = "## Summary\n\nAnalysts expect costs halve by 2027, though estimates vary. Pilot lines are on track.\n\n\
+            ## Key Findings\n\n- **Costs halve** by 2027\n- Pilot lines open in 2026\n\n\
+            | Year | Cost |\n| 2027 | Costs halve by 2027 |\n\n```\nCosts halve by 2027.\n```\n\n\
+            Costs halve by 2027.";
+
+        let stripped = remove_insight_statements(content, &[insight("Costs halve by 2027.")]);
+        assert_eq!(stripped, "## Summary\n\nPilot lines are on track.\n\n\
+            ## Key Findings\n\n- Pilot lines open in 2026\n\n\
+            | Year | Cost |\n| 2027 | Costs halve by 2027 |\n\n```\nCosts halve by 2027.\n```\n");
+
+        // Words that merely continue past the insight's do not match it
+        assert_eq!(remove_insight_statements("Costs halve by 20270 units.", &[insight("Costs halve by 2027")]), "Costs halve by 20270 units.");
+    }
+}
@@ -0,0 +1,252 @@
+use crate::error::{AppResult, ResearchError};
+use super::OutputFormat;
+use super::analysis::performance_analyzer::{BenchmarkResult, PerformanceExplanation, ProviderAttribution};
+
+/// Renders benchmark explanations as a diagnostic report
+pub struct DiagnosticReportRenderer;
+
+impl DiagnosticReportRenderer {
+    /// Render the explanations of a benchmark in the requested format
+    pub fn render(benchmark: &BenchmarkResult, format: OutputFormat) -> AppResult<String> {
+        match format {
+            OutputFormat::Markdown => Ok(Self::render_markdown(benchmark)),
+            OutputFormat::HTML => Ok(Self::render_html(benchmark)),
+            OutputFormat::TXT => Ok(Self::render_text(benchmark)),
+            OutputFormat::JSON => serde_json::to_string_pretty(&benchmark.explanations)
+                .map_err(|e| ResearchError::invalid_request(
+                    format!("Failed to serialize diagnostic report: {}", e)
+                ).into()),
+            other => Err(ResearchError::invalid_request(
+                format!("Diagnostic reports are not supported in format: {}", other)
+            ).into()),
+        }
+    }
+
+    fn render_markdown(benchmark: &BenchmarkResult) -> String {
+        let mut out = String::new();
+        out.push_str("# Performance Diagnostic Report\n\n");
+        out.push_str(&format!("Benchmark `{}` covering {} workflows, generated {}.\n\n",
+            benchmark.id, benchmark.explanations.len(), benchmark.created_at.format("%Y-%m-%d %H:%M UTC")));
+
+        for explanation in &benchmark.explanations {
+            out.push_str(&format!("## Workflow {}\n\n", explanation.workflow_id));
+            out.push_str(&format!("**Score:** {:.2} ({:?})\n\n", explanation.overall_score, explanation.performance_grade));
+            out.push_str(&format!("{}\n\n", explanation.summary));
+
+            out.push_str("### Score factors\n\n| Factor | Weight | Score | Contribution | Lost |\n|---|---|---|---|---|\n");
+            for factor in &explanation.score_factors {
+                out.push_str(&format!("| {} | {:.2} | {:.2} | {:.2} | {:.2} |\n",
+                    factor.factor_name, factor.weight, factor.raw_score, factor.contribution, factor.lost_points));
+            }
+
+            let ranks = &explanation.percentile_ranks;
+            out.push_str("\n### Percentile ranks\n\n");
+            out.push_str(&format!("- Overall: {:.0}\n- Execution time: {:.0}\n- Success rate: {:.0}\n- Efficiency: {:.0}\n- Throughput: {:.0}\n",
+                ranks.overall, ranks.execution_time, ranks.success_rate, ranks.efficiency, ranks.throughput));
+
+            if !explanation.step_attributions.is_empty() {
+                out.push_str("\n### Step attribution\n\n| Step | Provider | Duration (ms) | Share | Failed | Retries |\n|---|---|---|---|---|---|\n");
+                for step in &explanation.step_attributions {
+                    let name = if step.dominant { format!("**{}**", step.step_name) } else { step.step_name.clone() };
+                    out.push_str(&format!("| {} | {} | {} | {:.0}% | {} | {} |\n",
+                        name,
+                        step.service_provider.as_deref().unwrap_or("-"),
+                        step.duration_ms,
+                        step.share_of_duration * 100.0,
+                        if step.failed { "yes" } else { "no" },
+                        step.retries));
+                }
+            }
+
+            if !explanation.provider_attributions.is_empty() {
+                out.push_str("\n### Providers\n\n| Provider | Steps | Avg latency (ms) | Corpus latency (ms) | Error rate | Flags |\n|---|---|---|---|---|---|\n");
+                for provider in &explanation.provider_attributions {
+                    out.push_str(&format!("| {} | {} | {:.0} | {:.0} | {:.0}% | {} |\n",
+                        provider.provider,
+                        provider.step_count,
+                        provider.average_latency_ms,
+                        provider.corpus_average_latency_ms,
+                        provider.error_rate * 100.0,
+                        Self::provider_flags(provider)));
+                }
+            }
+
+            let sources = &explanation.source_count_impact;
+            out.push_str(&format!("\n### Source count\n\n{} sources; expected {:.1} min, actual {:.1} min ({:+.1}).\n\n",
+                sources.source_count,
+                sources.expected_execution_time_minutes,
+                sources.actual_execution_time_minutes,
+                sources.residual_minutes));
+        }
+
+        out
+    }
+
+    fn render_html(benchmark: &BenchmarkResult) -> String {
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Performance Diagnostic Report</title>\n</head>\n<body>\n");
+        out.push_str("<h1>Performance Diagnostic Report</h1>\n");
+        out.push_str(&format!("<p>Benchmark <code>{}</code> covering {} workflows, generated {}.</p>\n",
+            benchmark.id, benchmark.explanations.len(), benchmark.created_at.format("%Y-%m-%d %H:%M UTC")));
+
+        for explanation in &benchmark.explanations {
+            out.push_str(&Self::render_html_explanation(explanation));
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn render_html_explanation(explanation: &PerformanceExplanation) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("<section>\n<h2>Workflow {}</h2>\n", explanation.workflow_id));
+        out.push_str(&format!("<p><strong>Score:</strong> {:.2} ({:?})</p>\n", explanation.overall_score, explanation.performance_grade));
+        out.push_str(&format!("<p>{}</p>\n", Self::escape_html(&explanation.summary)));
+
+        out.push_str("<h3>Score factors</h3>\n<table>\n<tr><th>Factor</th><th>Weight</th><th>Score</th><th>Contribution</th><th>Lost</th></tr>\n");
+        for factor in &explanation.score_factors {
+            out.push_str(&format!("<tr><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>\n",
+                Self::escape_html(&factor.factor_name), factor.weight, factor.raw_score, factor.contribution, factor.lost_points));
+        }
+        out.push_str("</table>\n");
+
+        let ranks = &explanation.percentile_ranks;
+        out.push_str("<h3>Percentile ranks</h3>\n<ul>\n");
+        out.push_str(&format!("<li>Overall: {:.0}</li>\n<li>Execution time: {:.0}</li>\n<li>Success rate: {:.0}</li>\n<li>Efficiency: {:.0}</li>\n<li>Throughput: {:.0}</li>\n",
+            ranks.overall, ranks.execution_time, ranks.success_rate, ranks.efficiency, ranks.throughput));
+        out.push_str("</ul>\n");
+
+        if !explanation.step_attributions.is_empty() {
+            out.push_str("<h3>Step attribution</h3>\n<table>\n<tr><th>Step</th><th>Provider</th><th>Duration (ms)</th><th>Share</th><th>Failed</th><th>Retries</th></tr>\n");
+            for step in &explanation.step_attributions {
+                out.push_str(&format!("<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{:.0}%</td><td>{}</td><td>{}</td></tr>\n",
+                    if step.dominant { " class=\"dominant\"" } else { "" },
+                    Self::escape_html(&step.step_name),
+                    Self::escape_html(step.service_provider.as_deref().unwrap_or("-")),
+                    step.duration_ms,
+                    step.share_of_duration * 100.0,
+                    if step.failed { "yes" } else { "no" },
+                    step.retries));
+            }
+            out.push_str("</table>\n");
+        }
+
+        if !explanation.provider_attributions.is_empty() {
+            out.push_str("<h3>Providers</h3>\n<table>\n<tr><th>Provider</th><th>Steps</th><th>Avg latency (ms)</th><th>Corpus latency (ms)</th><th>Error rate</th><th>Flags</th></tr>\n");
+            for provider in &explanation.provider_attributions {
+                out.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}%</td><td>{}</td></tr>\n",
+                    Self::escape_html(&provider.provider),
+                    provider.step_count,
+                    provider.average_latency_ms,
+                    provider.corpus_average_latency_ms,
+                    provider.error_rate * 100.0,
+                    Self::provider_flags(provider)));
+            }
+            out.push_str("</table>\n");
+        }
+
+        let sources = &explanation.source_count_impact;
+        out.push_str(&format!("<h3>Source count</h3>\n<p>{} sources; expected {:.1} min, actual {:.1} min ({:+.1}).</p>\n",
+            sources.source_count,
+            sources.expected_execution_time_minutes,
+            sources.actual_execution_time_minutes,
+            sources.residual_minutes));
+        out.push_str("</section>\n");
+        out
+    }
+
+    fn render_text(benchmark: &BenchmarkResult) -> String {
+        let mut out = String::from("PERFORMANCE DIAGNOSTIC REPORT\n\n");
+        for explanation in &benchmark.explanations {
+            out.push_str(&format!("Workflow {} - score {:.2} ({:?})\n", explanation.workflow_id, explanation.overall_score, explanation.performance_grade));
+            out.push_str(&format!("  {}\n", explanation.summary));
+            for factor in &explanation.score_factors {
+                out.push_str(&format!("  {}: {:.2} of {:.2}\n", factor.factor_name, factor.contribution, factor.weight));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn provider_flags(provider: &ProviderAttribution) -> String {
+        let mut flags = Vec::new();
+        if provider.high_latency { flags.push("high latency"); }
+        if provider.high_error_rate { flags.push("high error rate"); }
+        if flags.is_empty() { "-".to_string() } else { flags.join(", ") }
+    }
+
+    fn escape_html(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::analysis::performance_analyzer::{
+        PercentileRanks, PerformanceGrade, ScoreFactor, SourceCountImpact, StepAttribution,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_html_explanation_includes_every_section() {
+        let explanation = PerformanceExplanation {
+            workflow_id: Uuid::new_v4(),
+            overall_score: 0.72,
+            performance_grade: PerformanceGrade::Good,
+            score_factors: vec![ScoreFactor {
+                factor_name: "execution_time".to_string(),
+                weight: 0.3,
+                raw_score: 0.5,
+                contribution: 0.15,
+                lost_points: 0.15,
+            }],
+            percentile_ranks: PercentileRanks {
+                execution_time: 40.0,
+                success_rate: 75.0,
+                efficiency: 60.0,
+                throughput: 55.0,
+                overall: 58.0,
+            },
+            step_attributions: vec![StepAttribution {
+                step_index: 0,
+                step_name: "search".to_string(),
+                service_provider: Some("tavily".to_string()),
+                duration_ms: 1800,
+                share_of_duration: 0.9,
+                failed: true,
+                retries: 2,
+                dominant: true,
+            }],
+            provider_attributions: vec![ProviderAttribution {
+                provider: "tavily".to_string(),
+                step_count: 1,
+                average_latency_ms: 1800.0,
+                error_rate: 1.0,
+                corpus_average_latency_ms: 600.0,
+                corpus_error_rate: 0.1,
+                high_latency: true,
+                high_error_rate: true,
+            }],
+            source_count_impact: SourceCountImpact {
+                source_count: 12,
+                corpus_minutes_per_source: 0.2,
+                expected_execution_time_minutes: 2.4,
+                actual_execution_time_minutes: 3.0,
+                residual_minutes: 0.6,
+            },
+            summary: "Search <dominated> the run".to_string(),
+        };
+
+        let html = DiagnosticReportRenderer::render_html_explanation(&explanation);
+        for heading in ["Score factors", "Percentile ranks", "Step attribution", "Providers", "Source count"] {
+            assert!(html.contains(&format!("<h3>{}</h3>", heading)), "missing section {}", heading);
+        }
+        assert!(html.contains("<td>0.15</td></tr>"));
+        assert!(html.contains("<li>Overall: 58</li>"));
+        assert!(html.contains("<td>tavily</td><td>1800</td><td>90%</td><td>yes</td><td>2</td>"));
+        assert!(html.contains("<td>high latency, high error rate</td>"));
+        assert!(html.contains("12 sources; expected 2.4 min, actual 3.0 min (+0.6)."));
+        assert!(html.contains("Search &lt;dominated&gt; the run"));
+    }
+}
@@ -101,6 +101,7 @@ impl OutputEngine {
             created_at: Utc::now(),
             file_size_bytes: content.len() as u64,
             processing_time_ms: processing_time.as_millis() as u64,
+            uri: None,
         };
 
         // Update statistics
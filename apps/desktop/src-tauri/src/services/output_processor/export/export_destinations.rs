@@ -19,6 +19,7 @@ pub struct ExportDestination {
 pub enum ExportDestinationType {
     LocalFileSystem,
     S3,
+    GoogleCloudStorage,
     GoogleDrive,
     Dropbox,
     OneDrive,
@@ -34,6 +35,7 @@ impl std::fmt::Display for ExportDestinationType {
         match self {
             ExportDestinationType::LocalFileSystem => write!(f, "local"),
             ExportDestinationType::S3 => write!(f, "s3"),
+            ExportDestinationType::GoogleCloudStorage => write!(f, "gcs"),
             ExportDestinationType::GoogleDrive => write!(f, "google_drive"),
             ExportDestinationType::Dropbox => write!(f, "dropbox"),
             ExportDestinationType::OneDrive => write!(f, "onedrive"),
@@ -402,6 +404,13 @@ impl DestinationFactory {
                     ).into());
                 }
             }
+            ExportDestinationType::GoogleCloudStorage => {
+                if destination.config.bucket.is_none() && !destination.path.starts_with("gs://") {
+                    return Err(ResearchError::invalid_request(
+                        "GCS destination requires a bucket name".to_string()
+                    ).into());
+                }
+            }
             ExportDestinationType::Email => {
                 if destination.config.endpoint.is_none() {
                     return Err(ResearchError::invalid_request(
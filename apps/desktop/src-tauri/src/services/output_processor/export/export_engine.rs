@@ -4,12 +4,27 @@ use std::fs;
 use tracing::{info, debug, error, warn};
 use uuid::Uuid;
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{AppResult, ResearchError};
 use crate::models::research_workflow::ResearchWorkflow;
+use crate::services::api_manager::ResponseRecorder;
 use super::{
     ExportRequest, ExportResult, ExportStatus, ExportedFile, CompressionType, PackageType
 };
+use super::export_destinations::ExportDestinationType;
+use super::output_sink::{self, SinkObject};
+use super::resumable_upload::{ExportProgressCallback, UploadProgress};
+
+/// Record of the files an export job rendered, kept in its export directory
+const WRITTEN_FILES_NAME: &str = "written_files.json";
+
+/// The files an export job rendered and packaged, ready to go to its destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WrittenExport {
+    files: Vec<ExportedFile>,
+    total_size_bytes: u64,
+}
 
 /// Export engine for processing export requests
 pub struct ExportEngine {
@@ -48,16 +63,57 @@ impl ExportEngine {
         &self,
         workflows: &[ResearchWorkflow],
         request: &ExportRequest,
+        progress: &ExportProgressCallback,
     ) -> AppResult<ExportResult> {
         info!("Processing export request: {}", request.id);
 
         let export_dir = self.create_export_directory(request).await?;
+        // A retry of the job sends the files its earlier attempt rendered, so their names
+        // and content match the upload checkpoints that attempt left
+        let written = match Self::load_written_files(&export_dir) {
+            Some(written) => {
+                info!("Resuming export {} with the {} files it already rendered", request.id, written.files.len());
+                written
+            }
+            None => {
+                let written = self.render_files(workflows, request, &export_dir).await?;
+                Self::save_written_files(&export_dir, &written)?;
+                written
+            }
+        };
+        let WrittenExport { files: mut compressed_files, total_size_bytes: total_size } = written;
+
+        // Move to final destination
+        self.move_to_destination(&mut compressed_files, &export_dir, request, progress).await?;
+
+        Ok(ExportResult {
+            id: Uuid::new_v4(),
+            request_id: request.id,
+            status: ExportStatus::Completed,
+            exported_files: compressed_files,
+            destination: request.destination.clone(),
+            total_size_bytes: total_size,
+            export_time_ms: 0, // Will be set by caller
+            created_at: Utc::now(),
+            completed_at: None, // Will be set by caller
+            error_message: None,
+            metadata: request.metadata.clone(),
+        })
+    }
+
+    /// Render, package and compress the request's files in `export_dir`
+    async fn render_files(
+        &self,
+        workflows: &[ResearchWorkflow],
+        request: &ExportRequest,
+        export_dir: &Path,
+    ) -> AppResult<WrittenExport> {
         let mut exported_files = Vec::new();
         let mut total_size = 0u64;
 
         // Export each workflow
         for workflow in workflows {
-            let workflow_files = self.export_single_workflow(workflow, request, &export_dir).await?;
+            let workflow_files = self.export_single_workflow(workflow, request, export_dir).await?;
             for file in workflow_files {
                 total_size += file.size_bytes;
                 exported_files.push(file);
@@ -67,10 +123,10 @@ impl ExportEngine {
         // Create package if requested
         let final_files = match request.options.package_type {
             PackageType::Archive => {
-                self.create_archive(&exported_files, &export_dir, request).await?
+                self.create_archive(&exported_files, export_dir, request).await?
             }
             PackageType::Manifest => {
-                self.create_manifest(&exported_files, &export_dir, request).await?;
+                self.create_manifest(&exported_files, export_dir, request).await?;
                 exported_files
             }
             _ => exported_files,
@@ -78,27 +134,33 @@ impl ExportEngine {
 
         // Apply compression if requested
         let compressed_files = if !matches!(request.options.compression, CompressionType::None) {
-            self.apply_compression(&final_files, &export_dir, request).await?
+            self.apply_compression(&final_files, export_dir, request).await?
         } else {
             final_files
         };
 
-        // Move to final destination
-        self.move_to_destination(&compressed_files, request).await?;
+        Ok(WrittenExport { files: compressed_files, total_size_bytes: total_size })
+    }
 
-        Ok(ExportResult {
-            id: Uuid::new_v4(),
-            request_id: request.id,
-            status: ExportStatus::Completed,
-            exported_files: compressed_files,
-            destination: request.destination.clone(),
-            total_size_bytes: total_size,
-            export_time_ms: 0, // Will be set by caller
-            created_at: Utc::now(),
-            completed_at: None, // Will be set by caller
-            error_message: None,
-            metadata: request.metadata.clone(),
-        })
+    /// The files an earlier attempt of the job rendered, if they are all still there
+    fn load_written_files(export_dir: &Path) -> Option<WrittenExport> {
+        let content = fs::read(export_dir.join(WRITTEN_FILES_NAME)).ok()?;
+        let written: WrittenExport = match serde_json::from_slice(&content) {
+            Ok(written) => written,
+            Err(e) => {
+                warn!("Ignoring unreadable record of rendered export files in {}: {}", export_dir.display(), e);
+                return None;
+            }
+        };
+        written.files.iter().all(|file| Path::new(&file.path).is_file()).then_some(written)
+    }
+
+    fn save_written_files(export_dir: &Path, written: &WrittenExport) -> AppResult<()> {
+        let content = serde_json::to_vec_pretty(written)
+            .map_err(|e| ResearchError::serialization_error(format!("Failed to serialize rendered export files: {}", e)))?;
+        fs::write(export_dir.join(WRITTEN_FILES_NAME), content)
+            .map_err(|e| ResearchError::io_error(format!("Failed to record rendered export files: {}", e)))?;
+        Ok(())
     }
 
     /// Create export directory for the request
@@ -144,6 +206,12 @@ impl ExportEngine {
             files.push(metadata_file);
         }
 
+        // Provider recordings are only exported on explicit request
+        if request.options.include_provider_recordings {
+            let recording_files = self.export_workflow_recording(workflow, &workflow_dir).await?;
+            files.extend(recording_files);
+        }
+
         Ok(files)
     }
 
@@ -192,6 +260,7 @@ impl ExportEngine {
             size_bytes: file_size,
             checksum: self.calculate_checksum(&file_path).await?,
             created_at: Utc::now(),
+            uri: None,
         })
     }
 
@@ -252,6 +321,7 @@ impl ExportEngine {
                     size_bytes: file_size,
                     checksum: self.calculate_checksum(&file_path).await?,
                     created_at: Utc::now(),
+                    uri: None,
                 });
             }
         }
@@ -259,6 +329,47 @@ impl ExportEngine {
         Ok(files)
     }
 
+    /// Copy the workflow's recorded provider responses, if it was recorded
+    async fn export_workflow_recording(
+        &self,
+        workflow: &ResearchWorkflow,
+        workflow_dir: &Path,
+    ) -> AppResult<Vec<ExportedFile>> {
+        let recorder = ResponseRecorder::new()?;
+        let recorded_files = recorder.recording_files(workflow.id)?;
+        if recorded_files.is_empty() {
+            return Ok(Vec::new());
+        }
+        debug!("Exporting {} recorded steps for workflow: {}", recorded_files.len(), workflow.id);
+
+        let recordings_dir = workflow_dir.join("recordings");
+        fs::create_dir_all(&recordings_dir)
+            .map_err(|e| ResearchError::io_error(format!("Failed to create recordings directory: {}", e)))?;
+
+        let mut files = Vec::new();
+        for source in recorded_files {
+            let filename = source.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let file_path = recordings_dir.join(&filename);
+
+            let size_bytes = fs::copy(&source, &file_path)
+                .map_err(|e| ResearchError::io_error(format!("Failed to copy recording file: {}", e)))?;
+
+            files.push(ExportedFile {
+                name: filename,
+                path: file_path.to_string_lossy().to_string(),
+                format: "provider_recording".to_string(),
+                size_bytes,
+                checksum: self.calculate_checksum(&file_path).await?,
+                created_at: Utc::now(),
+                uri: None,
+            });
+        }
+
+        Ok(files)
+    }
+
     /// Export workflow metadata
     async fn export_workflow_metadata(
         &self,
@@ -303,6 +414,7 @@ impl ExportEngine {
             size_bytes: file_size,
             checksum: self.calculate_checksum(&file_path).await?,
             created_at: Utc::now(),
+            uri: None,
         })
     }
 
@@ -370,6 +482,7 @@ impl ExportEngine {
             size_bytes: file_size,
             checksum: self.calculate_checksum(&archive_path).await?,
             created_at: Utc::now(),
+            uri: None,
         }])
     }
 
@@ -414,15 +527,59 @@ impl ExportEngine {
         Ok(files.to_vec())
     }
 
-    /// Move files to final destination
+    /// Write files to the destination's sink, recording where each one landed
     async fn move_to_destination(
         &self,
-        _files: &[ExportedFile],
-        _request: &ExportRequest,
+        files: &mut [ExportedFile],
+        export_dir: &Path,
+        request: &ExportRequest,
+        progress: &ExportProgressCallback,
     ) -> AppResult<()> {
-        // For now, files remain in temp directory
-        // In a real implementation, this would move files to the specified destination
-        info!("File movement to destination not yet implemented");
+        let destination = &request.destination;
+        // Without a directory to copy them to, local exports stay where they were written
+        if destination.destination_type == ExportDestinationType::LocalFileSystem && destination.path.trim().is_empty() {
+            return Ok(());
+        }
+
+        let sink = output_sink::sink_for_destination(destination)?;
+        let total_bytes: u64 = files.iter()
+            .map(|file| fs::metadata(&file.path).map(|m| m.len()).unwrap_or(file.size_bytes))
+            .sum();
+        let mut written_bytes = 0u64;
+        for file in files.iter_mut() {
+            let content = fs::read(&file.path)
+                .map_err(|e| ResearchError::io_error(format!("Failed to read {}: {}", file.path, e)))?;
+            // Keep the per-workflow folders so files from different workflows cannot collide
+            let name = Path::new(&file.path).strip_prefix(export_dir)
+                .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| file.name.clone());
+
+            let mut metadata = HashMap::new();
+            metadata.insert("export_request_id".to_string(), request.id.to_string());
+            metadata.insert("format".to_string(), file.format.clone());
+            metadata.insert("checksum".to_string(), file.checksum.clone());
+            let object = SinkObject {
+                content_type: output_sink::content_type_for_name(&name).to_string(),
+                // Retries of the job resume the uploads it started
+                checkpoint_key: Some(format!("export_{}/{}", request.id, name)),
+                name,
+                metadata,
+                content,
+            };
+            let size = object.content.len() as u64;
+            let current_file = file.name.clone();
+            let file_progress = |sent: u64, _: u64| progress(UploadProgress {
+                current_file: Some(current_file.clone()),
+                uploaded_bytes: written_bytes + sent,
+                total_bytes,
+            });
+            let uri = sink.write_with_progress(&object, &file_progress).await?;
+            written_bytes += size;
+            debug!("Exported {} to {}", file.name, uri);
+            file.uri = Some(uri);
+        }
+
+        info!("Wrote {} exported files to {} destination", files.len(), destination.destination_type);
         Ok(())
     }
 
@@ -8,6 +8,7 @@ use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
 use super::{ExportOptions, ExportDestination};
+use super::resumable_upload::UploadProgress;
 
 /// Export job for tracking export operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +24,9 @@ pub struct ExportJob {
     pub completed_at: Option<DateTime<Utc>>,
     pub progress_percentage: f64,
     pub error_message: Option<String>,
+    /// How far writing the files to the destination has got, once it has started
+    #[serde(default)]
+    pub upload: Option<UploadProgress>,
 }
 
 /// Export job status
@@ -123,6 +127,21 @@ impl ExportJobManager {
         }
     }
 
+    /// Record how far the job's files have been written to its destination. They are
+    /// generated first, so writing them counts for the second half of the job.
+    pub async fn update_upload_progress(&self, job_id: Uuid, upload: UploadProgress) -> AppResult<()> {
+        let mut jobs = self.jobs.write().await;
+
+        if let Some(job) = jobs.get_mut(&job_id) {
+            job.progress_percentage = (50.0 + upload.percentage() / 2.0).clamp(0.0, 100.0);
+            debug!("Job {} has written {} of {} bytes to its destination", job_id, upload.uploaded_bytes, upload.total_bytes);
+            job.upload = Some(upload);
+            Ok(())
+        } else {
+            Err(ResearchError::not_found(format!("Export job not found: {}", job_id)).into())
+        }
+    }
+
     /// Complete export job
     pub async fn complete_job(&self, job_id: Uuid) -> AppResult<()> {
         let mut jobs = self.jobs.write().await;
@@ -116,6 +116,7 @@ impl ExportTemplateManager {
                 package_type: PackageType::Folder,
                 include_metadata: true,
                 include_raw_data: false,
+                include_provider_recordings: false,
             },
             variables: HashMap::from([
                 ("report_title".to_string(), TemplateVariable {
@@ -171,6 +172,7 @@ impl ExportTemplateManager {
                 package_type: PackageType::Archive,
                 include_metadata: true,
                 include_raw_data: true,
+                include_provider_recordings: false,
             },
             variables: HashMap::from([
                 ("retention_period".to_string(), TemplateVariable {
@@ -225,6 +227,7 @@ impl ExportTemplateManager {
                 package_type: PackageType::Archive,
                 include_metadata: false,
                 include_raw_data: false,
+                include_provider_recordings: false,
             },
             variables: HashMap::from([
                 ("presentation_theme".to_string(), TemplateVariable {
@@ -280,6 +283,7 @@ impl ExportTemplateManager {
                 package_type: PackageType::SingleFile,
                 include_metadata: true,
                 include_raw_data: true,
+                include_provider_recordings: false,
             },
             variables: HashMap::from([
                 ("api_version".to_string(), TemplateVariable {
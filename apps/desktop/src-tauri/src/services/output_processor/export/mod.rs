@@ -13,11 +13,14 @@ pub mod export_engine;
 pub mod export_templates;
 pub mod export_destinations;
 pub mod export_jobs;
+pub mod output_sink;
+pub mod resumable_upload;
 
 use self::export_engine::ExportEngine;
 use self::export_templates::{ExportTemplate, ExportTemplateManager};
 use self::export_destinations::{ExportDestination, ExportDestinationType};
 use self::export_jobs::{ExportJob, ExportJobManager, ExportJobStatus};
+use self::resumable_upload::UploadProgress;
 
 /// Export service for research workflow results
 pub struct ExportService {
@@ -51,6 +54,9 @@ pub struct ExportOptions {
     pub package_type: PackageType,
     pub include_metadata: bool,
     pub include_raw_data: bool,
+    /// Include recorded provider responses; off by default since they hold raw source content
+    #[serde(default)]
+    pub include_provider_recordings: bool,
 }
 
 impl Default for ExportOptions {
@@ -65,6 +71,7 @@ impl Default for ExportOptions {
             package_type: PackageType::Folder,
             include_metadata: true,
             include_raw_data: false,
+            include_provider_recordings: false,
         }
     }
 }
@@ -192,6 +199,9 @@ pub struct ExportedFile {
     pub size_bytes: u64,
     pub checksum: String,
     pub created_at: DateTime<Utc>,
+    /// Where the file was written at the destination, once it has been
+    #[serde(default)]
+    pub uri: Option<String>,
 }
 
 /// Export statistics
@@ -251,6 +261,7 @@ impl ExportService {
             completed_at: None,
             progress_percentage: 0.0,
             error_message: None,
+            upload: None,
         };
 
         // Register job
@@ -259,8 +270,24 @@ impl ExportService {
             job_manager.add_job(export_job).await?;
         }
 
-        // Perform export
-        let result = self.export_engine.export_workflows(workflows, &request).await;
+        // Perform export, recording upload progress on the job as it comes in
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel::<UploadProgress>();
+        let progress_jobs = self.job_manager.clone();
+        let job_id = request.id;
+        let progress_task = tokio::spawn(async move {
+            while let Some(progress) = progress_rx.recv().await {
+                if let Err(e) = progress_jobs.read().await.update_upload_progress(job_id, progress).await {
+                    debug!("Dropped upload progress for export job {}: {}", job_id, e);
+                }
+            }
+        });
+        let report_progress = move |progress: UploadProgress| {
+            let _ = progress_tx.send(progress);
+        };
+        let result = self.export_engine.export_workflows(workflows, &request, &report_progress).await;
+        // Closing the channel lets the last updates land before the job is completed
+        drop(report_progress);
+        let _ = progress_task.await;
 
         let export_time = start_time.elapsed();
 
@@ -373,3 +400,5 @@ pub use export_engine::ExportEngine;
 pub use export_templates::{ExportTemplate, ExportTemplateManager};
 pub use export_destinations::{ExportDestination, ExportDestinationType};
 pub use export_jobs::{ExportJob, ExportJobManager, ExportJobStatus};
+pub use output_sink::{OutputSink, SinkObject, FileSystemSink, S3Sink, GcsSink};
+pub use resumable_upload::UploadProgress;
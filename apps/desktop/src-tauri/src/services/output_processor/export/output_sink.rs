@@ -0,0 +1,750 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::services::api_manager::egress;
+use crate::services::data_persistence::S3Target;
+use crate::utils::air_gap;
+use super::export_destinations::{ExportDestination, ExportDestinationType};
+use super::resumable_upload::{
+    composite_sha256, crc32c, parse_list_parts, part_sha256, retry_delay, xml_element, CheckpointStore,
+    ProgressCallback, UploadCheckpoint, UploadedPart, PART_ATTEMPTS,
+};
+
+/// Objects larger than this go up in parts rather than in one request
+pub const MULTIPART_THRESHOLD_BYTES: usize = 16 * 1024 * 1024;
+
+/// Size of each part of a multipart upload; above S3's 5 MiB minimum and a multiple of
+/// the 256 KiB GCS requires of resumable upload chunks
+pub const PART_SIZE_BYTES: usize = 8 * 1024 * 1024;
+
+const DEFAULT_GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+
+/// Longest a single upload request, such as one part, may take
+const UPLOAD_TIMEOUT_MS: u32 = 300_000;
+
+/// A report or exported file to persist
+#[derive(Debug, Clone)]
+pub struct SinkObject {
+    /// Path of the object below the sink's root or prefix, such as `grid_storage/report.md`
+    pub name: String,
+    pub content_type: String,
+    pub metadata: HashMap<String, String>,
+    pub content: Vec<u8>,
+    /// Identifies the upload across retries, such as the export job and file, so a
+    /// retry resumes it; the object's URI when `None`
+    pub checkpoint_key: Option<String>,
+}
+
+impl SinkObject {
+    fn checkpoint_key(&self, uri: &str) -> String {
+        self.checkpoint_key.clone().unwrap_or_else(|| uri.to_string())
+    }
+}
+
+/// Storage that export and output results are written to
+#[async_trait]
+pub trait OutputSink: Send + Sync {
+    /// URI scheme of the objects this sink writes, such as `s3`
+    fn scheme(&self) -> &'static str;
+
+    /// Write `object`, returning the URI it can be read back from
+    async fn write(&self, object: &SinkObject) -> AppResult<String>;
+
+    /// Write `object`, reporting the bytes sent as it goes
+    async fn write_with_progress(&self, object: &SinkObject, progress: &ProgressCallback) -> AppResult<String> {
+        let uri = self.write(object).await?;
+        let total = object.content.len() as u64;
+        progress(total, total);
+        Ok(uri)
+    }
+}
+
+/// The sink a destination writes through. S3 and GCS destinations take their bucket from
+/// the destination's config or from an `s3://bucket/prefix` or `gs://bucket/prefix` path,
+/// and fall back to `FDR_OUTPUT_S3_*` and `FDR_OUTPUT_GCS_*` for anything missing, so
+/// headless deployments need not pass credentials with every request.
+pub fn sink_for_destination(destination: &ExportDestination) -> AppResult<Box<dyn OutputSink>> {
+    match destination.destination_type {
+        ExportDestinationType::LocalFileSystem => Ok(Box::new(FileSystemSink::new(
+            &destination.path,
+            destination.options.overwrite_existing,
+        ))),
+        ExportDestinationType::S3 => Ok(Box::new(S3Sink::from_destination(destination)?)),
+        ExportDestinationType::GoogleCloudStorage => Ok(Box::new(GcsSink::from_destination(destination)?)),
+        other => Err(AppError::validation("destination_type", format!("{} destinations cannot be written to yet", other))),
+    }
+}
+
+/// Content type of a file from its extension
+pub fn content_type_for_name(name: &str) -> &'static str {
+    let extension = Path::new(name).extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => "text/markdown; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "csv" => "text/csv; charset=utf-8",
+        "xml" => "application/xml",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "txt" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "zip" => "application/zip",
+        "tar" => "application/x-tar",
+        "gz" | "tgz" => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Split `bucket/some/prefix` into the bucket and prefix
+fn split_bucket_path(path: &str) -> (String, String) {
+    let mut parts = path.trim_matches('/').splitn(2, '/');
+    let bucket = parts.next().unwrap_or_default().to_string();
+    (bucket, parts.next().unwrap_or_default().to_string())
+}
+
+/// Bucket and key prefix of an object storage destination
+fn bucket_and_prefix(destination: &ExportDestination, scheme: &str) -> (Option<String>, String) {
+    let path = destination.path.trim();
+    if let Some(rest) = path.strip_prefix(&format!("{}://", scheme)) {
+        let (bucket, prefix) = split_bucket_path(rest);
+        return (Some(bucket).filter(|b| !b.is_empty()), prefix);
+    }
+    let prefix = destination.config.folder.clone().unwrap_or_else(|| path.to_string());
+    (destination.config.bucket.clone(), prefix.trim_matches('/').to_string())
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Refuse names that would escape the sink's root
+fn relative_name(name: &str) -> AppResult<PathBuf> {
+    let path = Path::new(name);
+    if name.is_empty() || path.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(AppError::validation("name", format!("'{}' is not a relative path inside the destination", name)));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn join_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) }
+}
+
+/// Metadata as header values, which must be visible ASCII
+fn header_safe(value: &str) -> String {
+    if value.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+        value.to_string()
+    } else {
+        urlencoding::encode(value).into_owned()
+    }
+}
+
+/// Writes objects below a directory; metadata, when there is any, goes in a
+/// `<name>.metadata.json` file next to the object
+pub struct FileSystemSink {
+    root: PathBuf,
+    overwrite: bool,
+}
+
+impl FileSystemSink {
+    pub fn new(root: impl Into<PathBuf>, overwrite: bool) -> Self {
+        Self { root: root.into(), overwrite }
+    }
+}
+
+#[async_trait]
+impl OutputSink for FileSystemSink {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    async fn write(&self, object: &SinkObject) -> AppResult<String> {
+        let path = self.root.join(relative_name(&object.name)?);
+        if !self.overwrite && tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Err(AppError::io(format!("{} already exists and overwriting is disabled", path.display())));
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| AppError::io(format!("Failed to create {}: {}", parent.display(), e)))?;
+        }
+        tokio::fs::write(&path, &object.content).await
+            .map_err(|e| AppError::io(format!("Failed to write {}: {}", path.display(), e)))?;
+
+        if !object.metadata.is_empty() {
+            let sidecar = serde_json::json!({ "content_type": object.content_type, "metadata": object.metadata });
+            let mut sidecar_path = path.clone().into_os_string();
+            sidecar_path.push(".metadata.json");
+            tokio::fs::write(&sidecar_path, serde_json::to_vec_pretty(&sidecar)?).await
+                .map_err(|e| AppError::io(format!("Failed to write metadata for {}: {}", path.display(), e)))?;
+        }
+
+        let absolute = tokio::fs::canonicalize(&path).await.unwrap_or(path);
+        Ok(url::Url::from_file_path(&absolute)
+            .map(String::from)
+            .unwrap_or_else(|_| absolute.to_string_lossy().to_string()))
+    }
+}
+
+/// Writes objects to an S3-compatible bucket, in parts once they pass
+/// `MULTIPART_THRESHOLD_BYTES`. Parts sent are checkpointed, so a failed upload of the
+/// same content picks up where it stopped rather than starting over.
+pub struct S3Sink {
+    target: S3Target,
+    /// Used unless the workflow's egress profile sends uploads through its proxy
+    client: reqwest::Client,
+    checkpoints: CheckpointStore,
+}
+
+impl S3Sink {
+    pub fn new(target: S3Target) -> AppResult<Self> {
+        let client = egress::client_builder(UPLOAD_TIMEOUT_MS).build()?;
+        Ok(Self { target, client, checkpoints: CheckpointStore::default() })
+    }
+
+    /// Keep upload checkpoints in `dir` rather than the temp directory
+    pub fn with_checkpoint_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.checkpoints = CheckpointStore::new(dir);
+        self
+    }
+
+    fn from_destination(destination: &ExportDestination) -> AppResult<Self> {
+        let (bucket, prefix) = bucket_and_prefix(destination, "s3");
+        let credentials = destination.credentials.as_ref();
+        let region = destination.config.region.clone()
+            .or_else(|| env_var("FDR_OUTPUT_S3_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let target = S3Target {
+            bucket: bucket.or_else(|| env_var("FDR_OUTPUT_S3_BUCKET"))
+                .ok_or_else(|| AppError::validation("bucket", "S3 destination requires a bucket name"))?,
+            endpoint: destination.config.endpoint.clone()
+                .or_else(|| env_var("FDR_OUTPUT_S3_ENDPOINT"))
+                .unwrap_or_else(|| "https://s3.amazonaws.com".to_string()),
+            region,
+            prefix,
+            access_key_id: credentials.and_then(|c| c.access_key.clone())
+                .or_else(|| env_var("FDR_OUTPUT_S3_ACCESS_KEY_ID"))
+                .ok_or_else(|| AppError::configuration("S3 destination needs an access key or FDR_OUTPUT_S3_ACCESS_KEY_ID"))?,
+            secret_access_key: credentials.and_then(|c| c.secret_key.clone())
+                .or_else(|| env_var("FDR_OUTPUT_S3_SECRET_ACCESS_KEY"))
+                .ok_or_else(|| AppError::configuration("S3 destination needs a secret key or FDR_OUTPUT_S3_SECRET_ACCESS_KEY"))?,
+        };
+        let mut sink = Self::new(target)?;
+        if let Some(dir) = env_var("FDR_OUTPUT_UPLOAD_CHECKPOINT_DIR") {
+            sink = sink.with_checkpoint_dir(dir);
+        }
+        Ok(sink)
+    }
+
+    fn object_headers(object: &SinkObject) -> Vec<(String, String)> {
+        let mut headers = vec![("content-type".to_string(), object.content_type.clone())];
+        for (key, value) in &object.metadata {
+            let key: String = key.to_lowercase().chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+                .collect();
+            headers.push((format!("x-amz-meta-{}", key), header_safe(value)));
+        }
+        headers
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, String)],
+        headers: &[(String, String)],
+        body: Vec<u8>,
+    ) -> AppResult<reqwest::Response> {
+        let client = egress::client_for(&self.client, UPLOAD_TIMEOUT_MS)?;
+        let response = self.target.send_signed(&client, method, key, query, headers, body).await
+            .map_err(|e| AppError::external_service("s3", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(AppError::external_service("s3", format!("{} returned {}: {}", key, status, detail)));
+        }
+        Ok(response)
+    }
+
+    async fn abort(&self, key: &str, upload_id: &str) {
+        let query = [("uploadId", upload_id.to_string())];
+        if let Err(e) = self.send(reqwest::Method::DELETE, key, &query, &[], Vec::new()).await {
+            warn!("Failed to abort multipart upload of {}: {}", key, e);
+        }
+    }
+
+    /// The checkpoint of an interrupted upload of this content, keeping only the parts
+    /// S3 still holds with the ETag that was recorded for them
+    async fn resumable_checkpoint(&self, checkpoint_key: &str, uri: &str, key: &str, object: &SinkObject) -> Option<UploadCheckpoint> {
+        let mut checkpoint = self.checkpoints.load(checkpoint_key).await?;
+        if checkpoint.uri != uri || !checkpoint.matches(&object.content, PART_SIZE_BYTES) {
+            // The object or its content changed since, so the parts already sent are of no use
+            let bucket_prefix = format!("s3://{}/", self.target.bucket);
+            if let Some(sent_key) = checkpoint.uri.strip_prefix(&bucket_prefix) {
+                self.abort(sent_key, &checkpoint.upload_id).await;
+            }
+            self.checkpoints.remove(checkpoint_key).await;
+            return None;
+        }
+
+        let query = [("uploadId", checkpoint.upload_id.clone())];
+        let listed = match self.send(reqwest::Method::GET, key, &query, &[], Vec::new()).await {
+            Ok(response) => parse_list_parts(&response.text().await.unwrap_or_default()),
+            Err(e) => {
+                warn!("Upload of {} cannot be resumed and will start over: {}", key, e);
+                self.checkpoints.remove(checkpoint_key).await;
+                return None;
+            }
+        };
+        checkpoint.parts.retain(|part| listed.iter().any(|(number, etag)| *number == part.part_number && *etag == part.etag));
+        info!("Resuming upload of {} with {} parts already sent", key, checkpoint.parts.len());
+        Some(checkpoint)
+    }
+
+    async fn upload_part(&self, key: &str, upload_id: &str, part_number: u32, part: &[u8], sha256: &str) -> AppResult<String> {
+        let query = [("partNumber", part_number.to_string()), ("uploadId", upload_id.to_string())];
+        // S3 rejects a part whose content does not match this checksum
+        let headers = [("x-amz-checksum-sha256".to_string(), sha256.to_string())];
+        let mut attempt = 0;
+        loop {
+            match self.send(reqwest::Method::PUT, key, &query, &headers, part.to_vec()).await {
+                Ok(response) => {
+                    return response.headers().get("etag")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                        .ok_or_else(|| AppError::external_service("s3", format!("Part {} of {} was accepted without an ETag", part_number, key)));
+                }
+                Err(e) if attempt + 1 < PART_ATTEMPTS => {
+                    warn!("Part {} of {} failed, retrying: {}", part_number, key, e);
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn write_multipart(&self, key: &str, object: &SinkObject, progress: &ProgressCallback) -> AppResult<()> {
+        let uri = format!("s3://{}/{}", self.target.bucket, key);
+        let checkpoint_key = object.checkpoint_key(&uri);
+        let total = object.content.len() as u64;
+        let mut checkpoint = match self.resumable_checkpoint(&checkpoint_key, &uri, key, object).await {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let mut headers = Self::object_headers(object);
+                headers.push(("x-amz-checksum-algorithm".to_string(), "SHA256".to_string()));
+                let created = self.send(reqwest::Method::POST, key, &[("uploads", String::new())], &headers, Vec::new()).await?;
+                let created = created.text().await.map_err(|e| AppError::external_service("s3", e.to_string()))?;
+                let upload_id = xml_element(&created, "UploadId")
+                    .ok_or_else(|| AppError::external_service("s3", "Multipart upload was created without an UploadId"))?;
+                let checkpoint = UploadCheckpoint::new(checkpoint_key.clone(), uri.clone(), upload_id, &object.content, PART_SIZE_BYTES);
+                self.checkpoints.save(&checkpoint).await?;
+                checkpoint
+            }
+        };
+        progress(checkpoint.uploaded_bytes(), total);
+
+        for (index, part) in object.content.chunks(PART_SIZE_BYTES).enumerate() {
+            let part_number = index as u32 + 1;
+            if checkpoint.part(part_number).is_some() {
+                continue;
+            }
+            let sha256 = part_sha256(part);
+            // A part that keeps failing leaves the checkpoint for the next attempt to resume
+            let etag = self.upload_part(key, &checkpoint.upload_id, part_number, part, &sha256).await?;
+            checkpoint.record_part(UploadedPart { part_number, etag, sha256 });
+            self.checkpoints.save(&checkpoint).await?;
+            progress(checkpoint.uploaded_bytes(), total);
+        }
+
+        let parts: String = checkpoint.parts.iter()
+            .map(|part| format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag><ChecksumSHA256>{}</ChecksumSHA256></Part>",
+                part.part_number, part.etag, part.sha256
+            ))
+            .collect();
+        let complete = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let query = [("uploadId", checkpoint.upload_id.clone())];
+        let completed = self.send(reqwest::Method::POST, key, &query, &[], complete.into_bytes()).await?;
+        // S3 can report a failed completion in the body of a 200 response
+        let completed = completed.text().await.unwrap_or_default();
+        if completed.contains("<Error>") {
+            return Err(AppError::external_service("s3", format!("Completing the upload of {} failed: {}", key, completed)));
+        }
+        self.checkpoints.remove(&checkpoint_key).await;
+
+        // The object must be the parts that were sent, in order and each intact
+        let expected = composite_sha256(&checkpoint.parts)?;
+        let intact = match xml_element(&completed, "ChecksumSHA256") {
+            Some(reported) => reported == expected,
+            // Stores without additional checksums still report how many parts the object has
+            None => xml_element(&completed, "ETag")
+                .map_or(false, |etag| etag.trim_matches('"').ends_with(&format!("-{}", checkpoint.parts.len()))),
+        };
+        if !intact {
+            if let Err(e) = self.send(reqwest::Method::DELETE, key, &[], &[], Vec::new()).await {
+                warn!("Failed to remove {} after it failed its integrity check: {}", key, e);
+            }
+            return Err(AppError::external_service("s3", format!("Upload of {} failed its integrity check", key)));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputSink for S3Sink {
+    fn scheme(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn write(&self, object: &SinkObject) -> AppResult<String> {
+        self.write_with_progress(object, &|_, _| {}).await
+    }
+
+    async fn write_with_progress(&self, object: &SinkObject, progress: &ProgressCallback) -> AppResult<String> {
+        air_gap::ensure_online("S3 output upload")?;
+        relative_name(&object.name)?;
+        let key = self.target.object_key(&object.name);
+        debug!("Writing {} ({} bytes) to S3 bucket {}", key, object.content.len(), self.target.bucket);
+
+        let total = object.content.len() as u64;
+        if object.content.len() > MULTIPART_THRESHOLD_BYTES {
+            self.write_multipart(&key, object, progress).await?;
+        } else {
+            self.send(reqwest::Method::PUT, &key, &[], &Self::object_headers(object), object.content.clone()).await?;
+            progress(total, total);
+        }
+        Ok(format!("s3://{}/{}", self.target.bucket, key))
+    }
+}
+
+/// How far GCS has got with a resumable upload session
+enum SessionState {
+    /// Bytes it has persisted so far
+    Incomplete(u64),
+    /// The object's metadata, once every byte has arrived
+    Complete(serde_json::Value),
+    /// The session is gone, so the upload has to start over
+    Expired,
+}
+
+/// Writes objects to a Google Cloud Storage bucket with a resumable upload, sent in
+/// `PART_SIZE_BYTES` chunks. The session is checkpointed, so a failed upload of the same
+/// content continues from the last byte GCS has.
+pub struct GcsSink {
+    endpoint: String,
+    bucket: String,
+    prefix: String,
+    access_token: String,
+    /// Used unless the workflow's egress profile sends uploads through its proxy
+    client: reqwest::Client,
+    checkpoints: CheckpointStore,
+}
+
+impl std::fmt::Debug for GcsSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcsSink")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GcsSink {
+    pub fn new(bucket: impl Into<String>, prefix: impl Into<String>, access_token: impl Into<String>) -> AppResult<Self> {
+        Ok(Self {
+            endpoint: DEFAULT_GCS_ENDPOINT.to_string(),
+            bucket: bucket.into(),
+            prefix: prefix.into(),
+            access_token: access_token.into(),
+            client: egress::client_builder(UPLOAD_TIMEOUT_MS).build()?,
+            checkpoints: CheckpointStore::default(),
+        })
+    }
+
+    /// Keep upload checkpoints in `dir` rather than the temp directory
+    pub fn with_checkpoint_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.checkpoints = CheckpointStore::new(dir);
+        self
+    }
+
+    fn from_destination(destination: &ExportDestination) -> AppResult<Self> {
+        let (bucket, prefix) = bucket_and_prefix(destination, "gs");
+        let bucket = bucket.or_else(|| env_var("FDR_OUTPUT_GCS_BUCKET"))
+            .ok_or_else(|| AppError::validation("bucket", "GCS destination requires a bucket name"))?;
+        let access_token = destination.credentials.as_ref().and_then(|c| c.token.clone())
+            .or_else(|| env_var("FDR_OUTPUT_GCS_ACCESS_TOKEN"))
+            .ok_or_else(|| AppError::configuration("GCS destination needs an OAuth access token or FDR_OUTPUT_GCS_ACCESS_TOKEN"))?;
+        let mut sink = Self::new(bucket, prefix, access_token)?;
+        if let Some(endpoint) = destination.config.endpoint.clone().or_else(|| env_var("FDR_OUTPUT_GCS_ENDPOINT")) {
+            sink.endpoint = endpoint;
+        }
+        if let Some(dir) = env_var("FDR_OUTPUT_UPLOAD_CHECKPOINT_DIR") {
+            sink = sink.with_checkpoint_dir(dir);
+        }
+        Ok(sink)
+    }
+
+    /// Start a resumable upload carrying the object's content type and metadata,
+    /// returning the session URI its content is sent to
+    async fn start_upload(&self, client: &reqwest::Client, key: &str, object: &SinkObject) -> AppResult<String> {
+        let url = format!(
+            "{}/upload/storage/v1/b/{}/o?uploadType=resumable",
+            self.endpoint.trim_end_matches('/'),
+            urlencoding::encode(&self.bucket)
+        );
+        let response = client.post(&url)
+            .bearer_auth(&self.access_token)
+            .header("x-upload-content-type", &object.content_type)
+            .header("x-upload-content-length", object.content.len().to_string())
+            .json(&serde_json::json!({ "name": key, "contentType": object.content_type, "metadata": object.metadata }))
+            .send()
+            .await
+            .map_err(|e| AppError::external_service("gcs", e.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(AppError::external_service("gcs", format!("Starting the upload of {} returned {}: {}", key, status, detail)));
+        }
+        response.headers().get("location")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::external_service("gcs", "Resumable upload was started without a session URI"))
+    }
+
+    /// Read the session's state from a `308` or final response
+    async fn session_state(response: reqwest::Response) -> AppResult<SessionState> {
+        match response.status().as_u16() {
+            // `Range: bytes=0-<last byte persisted>`, absent when nothing has been
+            308 => Ok(SessionState::Incomplete(
+                response.headers().get("range")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|range| range.rsplit('-').next()?.parse::<u64>().ok())
+                    .map_or(0, |last| last + 1),
+            )),
+            404 | 410 => Ok(SessionState::Expired),
+            code if (200..300).contains(&code) => Ok(SessionState::Complete(response.json().await.unwrap_or_default())),
+            _ => {
+                let status = response.status();
+                let detail = response.text().await.unwrap_or_default();
+                Err(AppError::external_service("gcs", format!("Upload returned {}: {}", status, detail)))
+            }
+        }
+    }
+
+    /// Ask the session how many bytes it has
+    async fn query_session(client: &reqwest::Client, session: &str, total: u64) -> AppResult<SessionState> {
+        let response = client.put(session)
+            .header("content-range", format!("bytes */{}", total))
+            .body(Vec::new())
+            .send()
+            .await
+            .map_err(|e| AppError::external_service("gcs", e.to_string()))?;
+        Self::session_state(response).await
+    }
+
+    /// Send `content` from `offset` on, retrying from whatever GCS has persisted
+    async fn send_from(
+        &self,
+        client: &reqwest::Client,
+        session: &str,
+        key: &str,
+        content: &[u8],
+        mut offset: u64,
+        progress: &ProgressCallback,
+    ) -> AppResult<SessionState> {
+        let total = content.len() as u64;
+        let mut failures = 0;
+        loop {
+            let end = (offset + PART_SIZE_BYTES as u64).min(total);
+            let range = if total == 0 {
+                "bytes */0".to_string()
+            } else {
+                format!("bytes {}-{}/{}", offset, end - 1, total)
+            };
+            let sent = client.put(session)
+                .header("content-range", range)
+                .body(content[offset as usize..end as usize].to_vec())
+                .send()
+                .await
+                .map_err(|e| AppError::external_service("gcs", e.to_string()));
+            let state = match sent {
+                Ok(response) => Self::session_state(response).await,
+                Err(e) => Err(e),
+            };
+
+            match state {
+                Ok(SessionState::Incomplete(persisted)) if persisted > offset => {
+                    offset = persisted;
+                    failures = 0;
+                    progress(offset, total);
+                }
+                Ok(SessionState::Incomplete(_)) | Err(_) if failures + 1 < PART_ATTEMPTS => {
+                    failures += 1;
+                    warn!("Upload of {} stalled at byte {}, retrying", key, offset);
+                    tokio::time::sleep(retry_delay(failures)).await;
+                    match Self::query_session(client, session, total).await? {
+                        SessionState::Incomplete(persisted) => offset = persisted,
+                        done => return Ok(done),
+                    }
+                }
+                Ok(SessionState::Incomplete(_)) => {
+                    return Err(AppError::external_service("gcs", format!("Upload of {} made no progress past byte {}", key, offset)));
+                }
+                Err(e) => return Err(e),
+                Ok(done) => return Ok(done),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for GcsSink {
+    fn scheme(&self) -> &'static str {
+        "gs"
+    }
+
+    async fn write(&self, object: &SinkObject) -> AppResult<String> {
+        self.write_with_progress(object, &|_, _| {}).await
+    }
+
+    async fn write_with_progress(&self, object: &SinkObject, progress: &ProgressCallback) -> AppResult<String> {
+        air_gap::ensure_online("GCS output upload")?;
+        relative_name(&object.name)?;
+        let key = join_key(self.prefix.trim_matches('/'), &object.name);
+        let uri = format!("gs://{}/{}", self.bucket, key);
+        let checkpoint_key = object.checkpoint_key(&uri);
+        let total = object.content.len() as u64;
+        debug!("Writing {} ({} bytes) to GCS bucket {}", key, total, self.bucket);
+
+        let client = egress::client_for(&self.client, UPLOAD_TIMEOUT_MS)?;
+        let mut resumed = None;
+        if let Some(checkpoint) = self.checkpoints.load(&checkpoint_key).await {
+            if checkpoint.uri == uri && checkpoint.matches(&object.content, PART_SIZE_BYTES) {
+                match Self::query_session(&client, &checkpoint.upload_id, total).await {
+                    Ok(SessionState::Expired) | Err(_) => {}
+                    Ok(state) => resumed = Some((checkpoint.upload_id, state)),
+                }
+            }
+            if resumed.is_none() {
+                self.checkpoints.remove(&checkpoint_key).await;
+            }
+        }
+
+        let state = match resumed {
+            Some((_, SessionState::Complete(object_metadata))) => SessionState::Complete(object_metadata),
+            Some((session, SessionState::Incomplete(offset))) => {
+                info!("Resuming upload of {} from byte {}", key, offset);
+                progress(offset, total);
+                self.send_from(&client, &session, &key, &object.content, offset, progress).await?
+            }
+            _ => {
+                let session = self.start_upload(&client, &key, object).await?;
+                let checkpoint = UploadCheckpoint::new(checkpoint_key.clone(), uri.clone(), session.clone(), &object.content, PART_SIZE_BYTES);
+                self.checkpoints.save(&checkpoint).await?;
+                self.send_from(&client, &session, &key, &object.content, 0, progress).await?
+            }
+        };
+
+        let object_metadata = match state {
+            SessionState::Complete(object_metadata) => object_metadata,
+            _ => {
+                self.checkpoints.remove(&checkpoint_key).await;
+                return Err(AppError::external_service("gcs", format!("Upload session for {} expired; it will start over", key)));
+            }
+        };
+        self.checkpoints.remove(&checkpoint_key).await;
+
+        // GCS reports the CRC32C of what it stored, which must be what was sent
+        if object_metadata.get("crc32c").and_then(|v| v.as_str()).map_or(false, |crc| crc != crc32c(&object.content)) {
+            let url = format!(
+                "{}/storage/v1/b/{}/o/{}",
+                self.endpoint.trim_end_matches('/'),
+                urlencoding::encode(&self.bucket),
+                urlencoding::encode(&key)
+            );
+            if let Err(e) = client.delete(&url).bearer_auth(&self.access_token).send().await {
+                warn!("Failed to remove {} after it failed its integrity check: {}", key, e);
+            }
+            return Err(AppError::external_service("gcs", format!("Upload of {} failed its integrity check", key)));
+        }
+        progress(total, total);
+        Ok(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::export_destinations::{DestinationConfig, DestinationCredentials, DestinationOptions};
+
+    fn destination(destination_type: ExportDestinationType, path: &str) -> ExportDestination {
+        ExportDestination {
+            destination_type,
+            config: DestinationConfig {
+                endpoint: None,
+                region: None,
+                bucket: None,
+                folder: None,
+                host: None,
+                port: None,
+                database_name: None,
+                table_name: None,
+                custom_fields: HashMap::new(),
+            },
+            credentials: Some(DestinationCredentials {
+                access_key: Some("AKIDEXAMPLE".to_string()),
+                secret_key: Some("secret".to_string()),
+                token: Some("ya29.token".to_string()),
+                username: None,
+                password: None,
+                api_key: None,
+                certificate_path: None,
+                private_key_path: None,
+            }),
+            path: path.to_string(),
+            options: DestinationOptions::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_destinations_pick_their_sink_and_files_land_under_the_root() {
+        let s3 = sink_for_destination(&destination(ExportDestinationType::S3, "s3://reports/2026/q3")).unwrap();
+        assert_eq!(s3.scheme(), "s3");
+        let gcs = sink_for_destination(&destination(ExportDestinationType::GoogleCloudStorage, "gs://reports")).unwrap();
+        assert_eq!(gcs.scheme(), "gs");
+        assert!(sink_for_destination(&destination(ExportDestinationType::Email, "")).is_err());
+        assert_eq!(
+            bucket_and_prefix(&destination(ExportDestinationType::S3, "s3://reports/2026/q3/"), "s3"),
+            (Some("reports".to_string()), "2026/q3".to_string())
+        );
+
+        assert_eq!(content_type_for_name("grid/report.MD"), "text/markdown; charset=utf-8");
+        assert_eq!(content_type_for_name("chart"), "application/octet-stream");
+
+        let root = std::env::temp_dir().join(format!("fdr_sink_{}", uuid::Uuid::new_v4()));
+        let sink = FileSystemSink::new(&root, false);
+        let mut object = SinkObject {
+            name: "grid_storage/report.md".to_string(),
+            content_type: content_type_for_name("report.md").to_string(),
+            metadata: HashMap::from([("workflow_id".to_string(), "42".to_string())]),
+            content: b"# Grid storage".to_vec(),
+            checkpoint_key: None,
+        };
+        let uri = sink.write(&object).await.unwrap();
+        assert!(uri.starts_with("file://") && uri.ends_with("grid_storage/report.md"));
+        assert_eq!(std::fs::read(root.join("grid_storage/report.md")).unwrap(), b"# Grid storage");
+        assert!(root.join("grid_storage/report.md.metadata.json").exists());
+        // Existing files are kept unless the destination allows overwriting them
+        assert!(sink.write(&object).await.is_err());
+        assert!(FileSystemSink::new(&root, true).write(&object).await.is_ok());
+
+        object.name = "../escape.md".to_string();
+        assert!(sink.write(&object).await.is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
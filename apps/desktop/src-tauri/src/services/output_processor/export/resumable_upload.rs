@@ -0,0 +1,267 @@
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use tracing::warn;
+
+use crate::error::{AppError, AppResult};
+use crate::utils::crypto::hash_sha256;
+
+/// Attempts at sending one part before the upload is left to be resumed later
+pub const PART_ATTEMPTS: u32 = 3;
+
+/// Called with the bytes of an object uploaded so far and its total size
+pub type ProgressCallback = dyn Fn(u64, u64) + Send + Sync;
+
+/// Called as an export's files are written to its destination
+pub type ExportProgressCallback = dyn Fn(UploadProgress) + Send + Sync;
+
+/// How far an upload to a destination has got
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UploadProgress {
+    /// File being uploaded
+    pub current_file: Option<String>,
+    pub uploaded_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl UploadProgress {
+    pub fn percentage(&self) -> f64 {
+        if self.total_bytes == 0 {
+            100.0
+        } else {
+            self.uploaded_bytes as f64 / self.total_bytes as f64 * 100.0
+        }
+    }
+}
+
+/// A part the storage service has accepted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadedPart {
+    pub part_number: u32,
+    pub etag: String,
+    /// Base64 SHA-256 of the part, which S3 checks it against
+    pub sha256: String,
+}
+
+/// What is needed to pick an interrupted upload back up: the upload it belongs to, and
+/// the parts already sent. Only resumed for the same content split the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadCheckpoint {
+    /// What the checkpoint is found by: the export job and file, or else the object's URI
+    #[serde(default)]
+    pub key: String,
+    /// Object being written, such as `s3://reports/2026/bundle.zip`
+    pub uri: String,
+    /// S3 upload ID or GCS resumable session URI
+    pub upload_id: String,
+    pub content_sha256: String,
+    pub content_length: u64,
+    pub part_size: u64,
+    #[serde(default)]
+    pub parts: Vec<UploadedPart>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl UploadCheckpoint {
+    pub fn new(key: String, uri: String, upload_id: String, content: &[u8], part_size: usize) -> Self {
+        Self {
+            key,
+            uri,
+            upload_id,
+            content_sha256: content_sha256(content),
+            content_length: content.len() as u64,
+            part_size: part_size as u64,
+            parts: Vec::new(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Whether this checkpoint was made uploading `content` in `part_size` parts
+    pub fn matches(&self, content: &[u8], part_size: usize) -> bool {
+        self.content_length == content.len() as u64
+            && self.part_size == part_size as u64
+            && self.content_sha256 == content_sha256(content)
+    }
+
+    pub fn part(&self, part_number: u32) -> Option<&UploadedPart> {
+        self.parts.iter().find(|part| part.part_number == part_number)
+    }
+
+    pub fn record_part(&mut self, part: UploadedPart) {
+        self.parts.retain(|existing| existing.part_number != part.part_number);
+        self.parts.push(part);
+        self.parts.sort_by_key(|part| part.part_number);
+    }
+
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.parts.iter()
+            .map(|part| {
+                let start = (part.part_number as u64 - 1) * self.part_size;
+                self.content_length.saturating_sub(start).min(self.part_size)
+            })
+            .sum()
+    }
+}
+
+/// Checkpoints kept on disk, one per upload key, so an upload survives a restart
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl Default for CheckpointStore {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("fdr_upload_checkpoints"))
+    }
+}
+
+impl CheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", hex(&hash_sha256(key.as_bytes()))))
+    }
+
+    pub async fn load(&self, key: &str) -> Option<UploadCheckpoint> {
+        let content = tokio::fs::read(self.path(key)).await.ok()?;
+        match serde_json::from_slice::<UploadCheckpoint>(&content) {
+            Ok(checkpoint) if checkpoint.key == key => Some(checkpoint),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Ignoring unreadable upload checkpoint for {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    pub async fn save(&self, checkpoint: &UploadCheckpoint) -> AppResult<()> {
+        tokio::fs::create_dir_all(&self.dir).await
+            .map_err(|e| AppError::io(format!("Failed to create {}: {}", self.dir.display(), e)))?;
+        // Written aside and renamed, so an interruption cannot leave half a checkpoint
+        let path = self.path(&checkpoint.key);
+        let partial = path.with_extension("json.partial");
+        tokio::fs::write(&partial, serde_json::to_vec(checkpoint)?).await
+            .map_err(|e| AppError::io(format!("Failed to write upload checkpoint: {}", e)))?;
+        tokio::fs::rename(&partial, &path).await
+            .map_err(|e| AppError::io(format!("Failed to write upload checkpoint: {}", e)))
+    }
+
+    pub async fn remove(&self, key: &str) {
+        let path = self.path(key);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove upload checkpoint {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn content_sha256(content: &[u8]) -> String {
+    hex(&hash_sha256(content))
+}
+
+/// Base64 SHA-256 of a part, as S3's `x-amz-checksum-sha256` carries it
+pub fn part_sha256(part: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(hash_sha256(part))
+}
+
+/// The checksum S3 reports for a completed multipart object: the SHA-256 of the parts'
+/// SHA-256 digests, followed by the number of parts
+pub fn composite_sha256(parts: &[UploadedPart]) -> AppResult<String> {
+    let mut digests = Vec::with_capacity(parts.len() * 32);
+    for part in parts {
+        let digest = base64::engine::general_purpose::STANDARD.decode(&part.sha256)
+            .map_err(|e| AppError::internal(format!("Part {} has an invalid checksum: {}", part.part_number, e)))?;
+        digests.extend(digest);
+    }
+    let combined = base64::engine::general_purpose::STANDARD.encode(hash_sha256(&digests));
+    Ok(format!("{}-{}", combined, parts.len()))
+}
+
+/// Base64 big-endian CRC32C of `content`, as GCS reports an object's `crc32c`
+pub fn crc32c(content: &[u8]) -> String {
+    let mut crc = !0u32;
+    for byte in content {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0x82F6_3B78 } else { crc >> 1 };
+        }
+    }
+    base64::engine::general_purpose::STANDARD.encode((!crc).to_be_bytes())
+}
+
+/// Part numbers and ETags in an S3 `ListParts` response
+pub fn parse_list_parts(xml: &str) -> Vec<(u32, String)> {
+    xml.split("<Part>")
+        .skip(1)
+        .filter_map(|part| {
+            let number = xml_element(part, "PartNumber")?.trim().parse().ok()?;
+            Some((number, xml_element(part, "ETag")?))
+        })
+        .collect()
+}
+
+/// Text of the first `<name>` element in an XML document, with entities for quotes undone
+pub fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(xml[start..end].replace("&quot;", "\"").replace("&#34;", "\""))
+}
+
+/// Delay before retrying a part, doubling from one second
+pub fn retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(1 << attempt.min(5))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_checkpoints_resume_only_the_same_content_and_verify_what_was_sent() {
+        let content = vec![7u8; 20];
+        let mut checkpoint = UploadCheckpoint::new(
+            "export-7/bundle.zip".to_string(),
+            "s3://reports/bundle.zip".to_string(),
+            "upload-1".to_string(),
+            &content,
+            8,
+        );
+        assert!(checkpoint.matches(&content, 8));
+        assert!(!checkpoint.matches(&content, 16));
+        assert!(!checkpoint.matches(&[7u8; 19], 8));
+
+        checkpoint.record_part(UploadedPart { part_number: 3, etag: "\"c\"".to_string(), sha256: part_sha256(&content[16..]) });
+        checkpoint.record_part(UploadedPart { part_number: 1, etag: "\"a\"".to_string(), sha256: part_sha256(&content[..8]) });
+        assert_eq!(checkpoint.uploaded_bytes(), 8 + 4);
+        assert_eq!(checkpoint.parts.iter().map(|p| p.part_number).collect::<Vec<_>>(), vec![1, 3]);
+
+        let store = CheckpointStore::new(std::env::temp_dir().join(format!("fdr_checkpoints_{}", uuid::Uuid::new_v4())));
+        store.save(&checkpoint).await.unwrap();
+        let loaded = store.load("export-7/bundle.zip").await.unwrap();
+        assert_eq!(loaded.parts, checkpoint.parts);
+        assert!(store.load("export-8/bundle.zip").await.is_none());
+        store.remove("export-7/bundle.zip").await;
+        assert!(store.load("export-7/bundle.zip").await.is_none());
+        let _ = std::fs::remove_dir_all(store.dir());
+
+        // Known values: S3's composite of a single part, and the CRC32C check value
+        let single = vec![UploadedPart { part_number: 1, etag: String::new(), sha256: part_sha256(b"abc") }];
+        assert_eq!(composite_sha256(&single).unwrap(), format!("{}-1", part_sha256(&hash_sha256(b"abc"))));
+        assert_eq!(crc32c(b"123456789"), base64::engine::general_purpose::STANDARD.encode(0xE306_9283u32.to_be_bytes()));
+
+        let listed = "<ListPartsResult><Part><PartNumber>1</PartNumber><ETag>&quot;a&quot;</ETag></Part>\
+                      <Part><PartNumber>2</PartNumber><ETag>\"b\"</ETag></Part></ListPartsResult>";
+        assert_eq!(parse_list_parts(listed), vec![(1, "\"a\"".to_string()), (2, "\"b\"".to_string())]);
+    }
+}
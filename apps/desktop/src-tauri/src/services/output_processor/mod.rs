@@ -6,9 +6,12 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
-use crate::error::{AppResult, ResearchError};
+use crate::error::{AppResult, ResearchError, StorageError};
+use crate::models::execution_metrics::WorkflowExecutionMetrics;
 use crate::models::research_workflow::{ResearchWorkflow, ResearchResults};
-use crate::services::Service;
+use crate::services::{DataPersistenceService, Service};
+use crate::services::data_persistence::data_residency::{self, DataRegion};
+use crate::services::data_persistence::{BoundedHistory, HistoryEntry, HistoryLimit};
 
 pub mod formatters;
 pub mod templates;
@@ -16,19 +19,24 @@ pub mod engine;
 pub mod visualization;
 pub mod export;
 pub mod analysis;
+pub mod diagnostics;
+pub mod whats_new;
 
 use self::formatters::{OutputFormatter, MarkdownFormatter, HTMLFormatter, JSONFormatter, PDFFormatter, CSVFormatter, XMLFormatter, TXTFormatter, DOCXFormatter};
 use self::templates::{OutputTemplate, TemplateManager};
 use self::engine::OutputEngine;
 use self::visualization::{VisualizationEngine, VisualizationRequest, ChartType, ChartOutputFormat};
-use self::export::{ExportService, ExportRequest, ExportResult, ExportTemplate as ExportTemplateType};
+use self::export::{ExportService, ExportRequest, ExportResult, ExportTemplate as ExportTemplateType, ExportDestination, ExportDestinationType};
+use self::export::output_sink::{self, SinkObject};
 use self::analysis::{AnalysisService, ComprehensiveAnalysisRequest, ComprehensiveAnalysisResult};
+use self::diagnostics::DiagnosticReportRenderer;
+use self::whats_new::WhatsNewReportRenderer;
 
 /// Output processing service for research results
 pub struct OutputProcessorService {
     template_manager: Arc<RwLock<TemplateManager>>,
     output_engine: Arc<OutputEngine>,
-    output_history: Arc<RwLock<Vec<OutputResult>>>,
+    output_history: Arc<RwLock<BoundedHistory<OutputResult>>>,
     formatters: HashMap<OutputFormat, Box<dyn OutputFormatter>>,
     visualization_engine: Arc<VisualizationEngine>,
     export_service: Arc<RwLock<ExportService>>,
@@ -71,6 +79,9 @@ pub struct OutputRequest {
     pub template_id: Option<String>,
     pub options: OutputOptions,
     pub custom_template: Option<String>,
+    /// Where to also write the formatted output; it is only returned when unset
+    #[serde(default)]
+    pub destination: Option<ExportDestination>,
 }
 
 /// Output formatting options
@@ -176,8 +187,20 @@ pub struct OutputResult {
     pub created_at: DateTime<Utc>,
     pub file_size_bytes: u64,
     pub processing_time_ms: u64,
+    /// Where the output was written, when the request named a destination
+    #[serde(default)]
+    pub uri: Option<String>,
 }
 
+impl HistoryEntry for OutputResult {
+    fn recorded_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+/// Outputs kept in memory unless `FDR_HISTORY_OUTPUT_HISTORY_MAX` says otherwise
+pub const DEFAULT_OUTPUT_HISTORY_SIZE: usize = 1000;
+
 /// Output metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputMetadata {
@@ -211,7 +234,10 @@ impl OutputProcessorService {
 
         let template_manager = Arc::new(RwLock::new(TemplateManager::new().await?));
         let output_engine = Arc::new(OutputEngine::new().await?);
-        let output_history = Arc::new(RwLock::new(Vec::new()));
+        let output_history = Arc::new(RwLock::new(BoundedHistory::new(
+            "output_history",
+            HistoryLimit::from_env("output_history", DEFAULT_OUTPUT_HISTORY_SIZE),
+        )));
         let visualization_engine = Arc::new(VisualizationEngine::new().await?);
         let export_service = Arc::new(RwLock::new(ExportService::new().await?));
         let analysis_service = Arc::new(RwLock::new(AnalysisService::new().await?));
@@ -241,6 +267,25 @@ impl OutputProcessorService {
         Ok(service)
     }
 
+    /// Spill outputs beyond the in-memory history to `store` when the history limit asks for it
+    pub fn with_history_store(self, store: Arc<RwLock<DataPersistenceService>>) -> Self {
+        let limit = HistoryLimit::from_env("output_history", DEFAULT_OUTPUT_HISTORY_SIZE);
+        Self {
+            output_history: Arc::new(RwLock::new(BoundedHistory::new("output_history", limit).with_store(store))),
+            ..self
+        }
+    }
+
+    /// Change how many outputs are kept in memory and whether older ones are spilled
+    pub async fn set_history_limit(&self, limit: HistoryLimit) {
+        self.output_history.write().await.set_limit(limit).await;
+    }
+
+    /// The latest outputs, newest first, including spilled ones beyond the in-memory window
+    pub async fn get_output_history(&self, limit: usize) -> AppResult<Vec<OutputResult>> {
+        self.output_history.read().await.recent(limit).await
+    }
+
     /// Format research results into specified format
     pub async fn format_results(
         &self,
@@ -281,7 +326,7 @@ impl OutputProcessorService {
         let processing_time = start_time.elapsed();
 
         // Create output result
-        let output_result = OutputResult {
+        let mut output_result = OutputResult {
             id: Uuid::new_v4(),
             workflow_id: workflow.id,
             format: request.format,
@@ -300,17 +345,31 @@ impl OutputProcessorService {
             created_at: Utc::now(),
             file_size_bytes: content.len() as u64,
             processing_time_ms: processing_time.as_millis() as u64,
+            uri: None,
         };
 
+        if let Some(destination) = &request.destination {
+            Self::check_destination_residency(std::slice::from_ref(workflow), destination)?;
+            let mut metadata = HashMap::new();
+            metadata.insert("workflow_id".to_string(), workflow.id.to_string());
+            metadata.insert("title".to_string(), output_result.metadata.title.clone());
+            metadata.insert("format".to_string(), request.format.to_string());
+            let object = SinkObject {
+                name: format!("{}/{}.{}", workflow.id, output_result.id, formatter.file_extension()),
+                content_type: formatter.mime_type().to_string(),
+                metadata,
+                content: content.into_bytes(),
+                checkpoint_key: None,
+            };
+            let uri = output_sink::sink_for_destination(destination)?.write(&object).await?;
+            info!("Wrote output {} to {}", output_result.id, uri);
+            output_result.uri = Some(uri);
+        }
+
         // Store in history
         {
             let mut history = self.output_history.write().await;
-            history.push(output_result.clone());
-
-            // Keep only last 1000 outputs
-            if history.len() > 1000 {
-                history.remove(0);
-            }
+            history.push(output_result.clone()).await;
         }
 
         info!("Successfully formatted results for workflow: {} ({}ms)", 
@@ -374,7 +433,7 @@ impl OutputProcessorService {
         let mut successful_outputs = 0u64;
         let mut failed_outputs = 0u64;
 
-        for output in &*output_history {
+        for output in history.iter() {
             successful_outputs += 1;
             if let Some(template_name) = &output.metadata.template_used {
                 *template_usage.entry(template_name.clone()).or_insert(0) += 1;
@@ -547,10 +606,43 @@ impl OutputProcessorService {
         request: ExportRequest,
     ) -> AppResult<ExportResult> {
         info!("Exporting {} workflows", workflows.len());
+        Self::check_destination_residency(workflows, &request.destination)?;
         let export_service = self.export_service.read().await;
         export_service.export_workflows(workflows, request).await
     }
 
+    /// Refuse exports and outputs that would take region-pinned workflows out of their region
+    fn check_destination_residency(workflows: &[ResearchWorkflow], destination: &ExportDestination) -> AppResult<()> {
+        let mut regions = workflows.iter()
+            .map(data_residency::workflow_region)
+            .collect::<AppResult<Vec<_>>>()?;
+        regions.sort();
+        regions.dedup();
+
+        let region = match regions.as_slice() {
+            [] | [DataRegion::Global] => return Ok(()),
+            [region] => *region,
+            _ => return Err(StorageError::ResidencyViolation {
+                message: "Workflows from different data regions cannot be exported together".to_string()
+            }.into()),
+        };
+
+        let allowed = match destination.destination_type {
+            // Written on this machine, which already holds the data
+            ExportDestinationType::LocalFileSystem => true,
+            ExportDestinationType::S3 => destination.config.region.as_deref()
+                .map_or(false, |cloud_region| region.contains_cloud_region(cloud_region)),
+            _ => false,
+        };
+
+        if !allowed {
+            return Err(StorageError::ResidencyViolation {
+                message: format!("{:?} destination is not within the {} data region", destination.destination_type, region)
+            }.into());
+        }
+        Ok(())
+    }
+
     /// Get export templates
     pub async fn get_export_templates(&self) -> AppResult<Vec<ExportTemplateType>> {
         let export_service = self.export_service.read().await;
@@ -627,10 +719,141 @@ impl OutputProcessorService {
     pub async fn analyze_workflow_performance(
         &self,
         workflows: &[ResearchWorkflow],
+    ) -> AppResult<analysis::BenchmarkResult> {
+        self.analyze_workflow_performance_with_actuals(workflows, &HashMap::new()).await
+    }
+
+    /// Analyze workflow performance with the resource use recorded as they ran
+    pub async fn analyze_workflow_performance_with_actuals(
+        &self,
+        workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
     ) -> AppResult<analysis::BenchmarkResult> {
         info!("Analyzing performance of {} workflows", workflows.len());
         let analysis_service = self.analysis_service.read().await;
-        analysis_service.performance_analyzer.analyze_performance(workflows).await
+        analysis_service.performance_analyzer.analyze_performance_with_actuals(workflows, actuals).await
+    }
+
+    /// Benchmark workflows and render the score explanations as a diagnostic report
+    pub async fn render_performance_diagnostics(
+        &self,
+        workflows: &[ResearchWorkflow],
+        format: OutputFormat,
+    ) -> AppResult<OutputResult> {
+        info!("Rendering performance diagnostics for {} workflows in format: {}", workflows.len(), format);
+
+        let start_time = std::time::Instant::now();
+        let benchmark = self.analyze_workflow_performance(workflows).await?;
+        let content = DiagnosticReportRenderer::render(&benchmark, format)?;
+        let processing_time = start_time.elapsed();
+
+        let mut custom_fields = HashMap::new();
+        custom_fields.insert("benchmark_id".to_string(), benchmark.id.to_string());
+        custom_fields.insert("workflow_count".to_string(), workflows.len().to_string());
+
+        let output_result = OutputResult {
+            id: Uuid::new_v4(),
+            // A diagnostic report spans many workflows, so it is keyed by the benchmark
+            workflow_id: benchmark.id,
+            format,
+            content: content.clone(),
+            metadata: OutputMetadata {
+                title: "Performance Diagnostic Report".to_string(),
+                description: Some(format!("Score explanations for {} workflows", workflows.len())),
+                author: "Research Engine".to_string(),
+                created_at: Utc::now(),
+                workflow_name: "Performance Benchmark".to_string(),
+                template_used: None,
+                format_version: "1.0".to_string(),
+                tags: vec!["diagnostics".to_string(), format.to_string()],
+                custom_fields,
+            },
+            created_at: Utc::now(),
+            file_size_bytes: content.len() as u64,
+            processing_time_ms: processing_time.as_millis() as u64,
+            uri: None,
+        };
+
+        {
+            let mut history = self.output_history.write().await;
+            history.push(output_result.clone()).await;
+        }
+
+        Ok(output_result)
+    }
+
+    /// Diff the results of two runs of a query and render the "what's new" report;
+    /// `workflow_a` is the earlier run
+    pub async fn diff_results(
+        &self,
+        workflow_a: &ResearchWorkflow,
+        workflow_b: &ResearchWorkflow,
+        format: OutputFormat,
+    ) -> AppResult<OutputResult> {
+        info!("Diffing results of workflow {} against {} in format: {}", workflow_b.id, workflow_a.id, format);
+
+        let start_time = std::time::Instant::now();
+        let report = analysis::diff_results(workflow_a, workflow_b)?;
+        let content = WhatsNewReportRenderer::render(&report, format)?;
+        let processing_time = start_time.elapsed();
+
+        let mut custom_fields = HashMap::new();
+        custom_fields.insert("previous_workflow_id".to_string(), workflow_a.id.to_string());
+        custom_fields.insert("new_key_findings".to_string(), report.new_key_findings.len().to_string());
+        custom_fields.insert("new_sources".to_string(), report.new_sources.len().to_string());
+        custom_fields.insert("removed_sources".to_string(), report.removed_sources.len().to_string());
+
+        let output_result = OutputResult {
+            id: Uuid::new_v4(),
+            workflow_id: workflow_b.id,
+            format,
+            content: content.clone(),
+            metadata: OutputMetadata {
+                title: format!("What's New: {}", workflow_b.name),
+                description: Some(workflow_b.query.clone()),
+                author: "Research Engine".to_string(),
+                created_at: Utc::now(),
+                workflow_name: workflow_b.name.clone(),
+                template_used: None,
+                format_version: "1.0".to_string(),
+                tags: vec!["whats-new".to_string(), format.to_string()],
+                custom_fields,
+            },
+            created_at: Utc::now(),
+            file_size_bytes: content.len() as u64,
+            processing_time_ms: processing_time.as_millis() as u64,
+            uri: None,
+        };
+
+        {
+            let mut history = self.output_history.write().await;
+            history.push(output_result.clone()).await;
+        }
+
+        Ok(output_result)
+    }
+
+    /// Update the incremental analysis with newly-completed workflows, using the resource
+    /// use recorded for them where there is any
+    pub async fn update_analysis(
+        &self,
+        new_workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    ) -> AppResult<analysis::IncrementalAnalysisSummary> {
+        info!("Updating incremental analysis with {} workflows", new_workflows.len());
+        let analysis_service = self.analysis_service.read().await;
+        analysis_service.update_analysis(new_workflows, actuals).await
+    }
+
+    /// Rebuild the incremental analysis over the full workflow corpus
+    pub async fn recompute_analysis(
+        &self,
+        workflows: &[ResearchWorkflow],
+        actuals: &HashMap<Uuid, WorkflowExecutionMetrics>,
+    ) -> AppResult<analysis::IncrementalAnalysisSummary> {
+        info!("Recomputing incremental analysis over {} workflows", workflows.len());
+        let analysis_service = self.analysis_service.read().await;
+        analysis_service.recompute_analysis(workflows, actuals).await
     }
 
     /// Get analysis statistics
@@ -718,5 +941,8 @@ pub use export::{
 pub use analysis::{
     AnalysisService, ComprehensiveAnalysisRequest, ComprehensiveAnalysisResult,
     AnalysisType, AnalysisOptions, AnalysisFilters, ComparisonResult, ClusterResult,
-    BenchmarkResult, AnalysisStatistics, AnalysisInsight, AnalysisRecommendation
+    BenchmarkResult, AnalysisStatistics, AnalysisInsight, AnalysisRecommendation,
+    IncrementalAnalysisSummary, PerformanceExplanation, ResultDiffReport, InsightCategory, ResearchInsight
 };
+pub use diagnostics::DiagnosticReportRenderer;
+pub use whats_new::WhatsNewReportRenderer;
@@ -0,0 +1,198 @@
+use crate::error::{AppError, AppResult};
+use super::OutputFormat;
+use crate::models::research_insight::{InsightCategory, ResearchInsight};
+use super::analysis::result_diff::ResultDiffReport;
+
+/// Renders a result diff as a "what's new" report
+pub struct WhatsNewReportRenderer;
+
+impl WhatsNewReportRenderer {
+    /// Render a result diff in the requested format
+    pub fn render(report: &ResultDiffReport, format: OutputFormat) -> AppResult<String> {
+        match format {
+            OutputFormat::Markdown => Ok(Self::render_markdown(report)),
+            OutputFormat::HTML => Ok(Self::render_html(report)),
+            OutputFormat::TXT => Ok(Self::render_text(report)),
+            OutputFormat::JSON => serde_json::to_string_pretty(report)
+                .map_err(|e| AppError::Serialization {
+                    message: format!("Failed to serialize what's new report: {}", e),
+                }),
+            other => Err(AppError::validation(
+                "format",
+                format!("What's new reports are not supported in format: {}", other),
+            )),
+        }
+    }
+
+    fn render_markdown(report: &ResultDiffReport) -> String {
+        let mut out = String::new();
+        out.push_str("# What's New\n\n");
+        out.push_str(&format!("Changes in `{}` since `{}` for query: {}\n\n",
+            report.workflow_b, report.workflow_a, report.query));
+
+        if !report.has_changes() {
+            out.push_str("Nothing has changed since the previous run.\n");
+            return out;
+        }
+
+        if !report.new_key_findings.is_empty() {
+            out.push_str("## New key findings\n\n");
+            for finding in &report.new_key_findings {
+                out.push_str(&format!("- **{}**\n", finding.insight));
+            }
+            out.push('\n');
+        }
+
+        let summary = &report.summary;
+        out.push_str("## Summary\n\n");
+        if summary.summary_changed {
+            if let Some(current) = &summary.current_summary {
+                out.push_str(&format!("{}\n\n", current));
+            }
+            if let Some(previous) = &summary.previous_summary {
+                out.push_str(&format!("> Previously: {}\n\n", previous));
+            }
+        } else {
+            out.push_str("The summary is unchanged.\n\n");
+        }
+        out.push_str(&format!("- Words: {} ({:+})\n- Sources: {} ({:+})\n\n",
+            summary.current_word_count, summary.word_count_change,
+            summary.current_source_count, summary.source_count_change));
+
+        out.push_str(&format!("## Sources\n\n{} new, {} removed, {} retained.\n\n",
+            report.new_sources.len(), report.removed_sources.len(), report.retained_source_count));
+        for source in &report.new_sources {
+            out.push_str(&format!("- New: {}\n", source));
+        }
+        for source in &report.removed_sources {
+            out.push_str(&format!("- Removed: {}\n", source));
+        }
+        if !report.new_sources.is_empty() || !report.removed_sources.is_empty() {
+            out.push('\n');
+        }
+
+        if !report.new_insights.is_empty() || !report.removed_insights.is_empty() {
+            out.push_str("## Insight changes\n\n| Change | Category | Insight |\n|---|---|---|\n");
+            for insight in &report.new_insights {
+                out.push_str(&format!("| added | {} | {} |\n", Self::category_label(insight.category), insight.insight));
+            }
+            for insight in &report.removed_insights {
+                out.push_str(&format!("| removed | {} | {} |\n", Self::category_label(insight.category), insight.insight));
+            }
+            out.push('\n');
+        }
+
+        if !report.new_paragraphs.is_empty() {
+            out.push_str("## New material\n\n");
+            for paragraph in &report.new_paragraphs {
+                out.push_str(&format!("{}\n\n", paragraph));
+            }
+        }
+
+        out
+    }
+
+    fn render_html(report: &ResultDiffReport) -> String {
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>What's New</title>\n</head>\n<body>\n");
+        out.push_str("<h1>What's New</h1>\n");
+        out.push_str(&format!("<p>Changes since the previous run for query: {}</p>\n", Self::escape_html(&report.query)));
+
+        if !report.has_changes() {
+            out.push_str("<p>Nothing has changed since the previous run.</p>\n</body>\n</html>\n");
+            return out;
+        }
+
+        if !report.new_key_findings.is_empty() {
+            out.push_str("<section class=\"key-findings\">\n<h2>New key findings</h2>\n<ul>\n");
+            for finding in &report.new_key_findings {
+                out.push_str(&format!("<li><strong>{}</strong></li>\n", Self::escape_html(&finding.insight)));
+            }
+            out.push_str("</ul>\n</section>\n");
+        }
+
+        let summary = &report.summary;
+        out.push_str("<section>\n<h2>Summary</h2>\n");
+        if let (true, Some(current)) = (summary.summary_changed, &summary.current_summary) {
+            out.push_str(&format!("<p>{}</p>\n", Self::escape_html(current)));
+        }
+        out.push_str(&format!("<p>Words: {} ({:+}), sources: {} ({:+})</p>\n</section>\n",
+            summary.current_word_count, summary.word_count_change,
+            summary.current_source_count, summary.source_count_change));
+
+        if !report.new_sources.is_empty() || !report.removed_sources.is_empty() {
+            out.push_str("<section>\n<h2>Sources</h2>\n<ul>\n");
+            for source in &report.new_sources {
+                out.push_str(&format!("<li class=\"added\">{}</li>\n", Self::escape_html(source)));
+            }
+            for source in &report.removed_sources {
+                out.push_str(&format!("<li class=\"removed\">{}</li>\n", Self::escape_html(source)));
+            }
+            out.push_str("</ul>\n</section>\n");
+        }
+
+        if !report.new_insights.is_empty() || !report.removed_insights.is_empty() {
+            out.push_str("<section>\n<h2>Insight changes</h2>\n<table>\n<tr><th>Change</th><th>Category</th><th>Insight</th></tr>\n");
+            for (change, insights) in [("added", &report.new_insights), ("removed", &report.removed_insights)] {
+                for insight in insights {
+                    out.push_str(&format!("<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                        change, change, Self::category_label(insight.category), Self::escape_html(&insight.insight)));
+                }
+            }
+            out.push_str("</table>\n</section>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn render_text(report: &ResultDiffReport) -> String {
+        let mut out = String::from("WHAT'S NEW\n\n");
+        out.push_str(&format!("Query: {}\n\n", report.query));
+
+        if !report.has_changes() {
+            out.push_str("Nothing has changed since the previous run.\n");
+            return out;
+        }
+
+        Self::push_text_list(&mut out, "New key findings", &report.new_key_findings);
+        Self::push_text_list(&mut out, "Other new insights", &report.new_insights);
+        Self::push_text_list(&mut out, "No longer reported", &report.removed_insights);
+
+        out.push_str(&format!("Sources: {} new, {} removed, {} retained\n",
+            report.new_sources.len(), report.removed_sources.len(), report.retained_source_count));
+        for source in &report.new_sources {
+            out.push_str(&format!("  + {}\n", source));
+        }
+        for source in &report.removed_sources {
+            out.push_str(&format!("  - {}\n", source));
+        }
+        out.push_str(&format!("\nWords: {} ({:+})\n", report.summary.current_word_count, report.summary.word_count_change));
+        out
+    }
+
+    fn push_text_list(out: &mut String, title: &str, insights: &[ResearchInsight]) {
+        if insights.is_empty() {
+            return;
+        }
+        out.push_str(&format!("{}:\n", title));
+        for insight in insights {
+            out.push_str(&format!("  [{}] {}\n", Self::category_label(insight.category), insight.insight));
+        }
+        out.push('\n');
+    }
+
+    fn category_label(category: InsightCategory) -> &'static str {
+        match category {
+            InsightCategory::KeyFinding => "key finding",
+            InsightCategory::Trend => "trend",
+            InsightCategory::Contradiction => "contradiction",
+            InsightCategory::GapInKnowledge => "knowledge gap",
+            InsightCategory::Recommendation => "recommendation",
+        }
+    }
+
+    fn escape_html(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    }
+}
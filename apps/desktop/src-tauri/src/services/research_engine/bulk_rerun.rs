@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::{ResearchWorkflow, StepStatus, WorkflowStatus};
+use crate::services::research_engine::single_flight::COALESCED_WITH_KEY;
+use crate::services::research_engine::workflow_bundle::IMPORTED_FROM_KEY;
+
+/// Workflow metadata key naming the failed workflow a re-run was made from
+pub const RERUN_OF_KEY: &str = "rerun_of";
+/// Workflow metadata key naming the re-run a failed workflow was given
+pub const RERUN_AS_KEY: &str = "rerun_as";
+/// Workflow metadata key counting how many re-runs led to this workflow
+pub const RERUN_ATTEMPT_KEY: &str = "rerun_attempt";
+
+/// Re-runs a failure may go through before it is left for someone to look at
+pub const MAX_RERUN_ATTEMPTS: u32 = 3;
+
+/// Which failed workflows to re-run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RerunFilter {
+    /// Only workflows with a failed step that called this provider, such as `serpapi`
+    pub provider: Option<String>,
+    /// Only workflows whose error, or a failed step's error, contains this text
+    pub error_contains: Option<String>,
+    pub failed_after: Option<DateTime<Utc>>,
+    pub failed_before: Option<DateTime<Utc>>,
+    /// Most workflows to re-run in one go
+    pub limit: Option<usize>,
+    /// Report what would be re-run without re-running anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl RerunFilter {
+    /// A bulk re-run must be narrowed to a provider or an error
+    pub fn validate(&self) -> AppResult<()> {
+        let blank = |value: &Option<String>| value.as_deref().map_or(true, |v| v.trim().is_empty());
+        if blank(&self.provider) && blank(&self.error_contains) {
+            return Err(AppError::validation("filter", "name a provider or an error to re-run the workflows it failed"));
+        }
+        Ok(())
+    }
+
+    /// Whether `workflow` failed the way the filter describes
+    pub fn matches(&self, workflow: &ResearchWorkflow) -> bool {
+        if workflow.status != WorkflowStatus::Failed {
+            return false;
+        }
+        let failed_at = workflow.completed_at.unwrap_or(workflow.updated_at);
+        if self.failed_after.is_some_and(|after| failed_at < after)
+            || self.failed_before.is_some_and(|before| failed_at > before)
+        {
+            return false;
+        }
+
+        let failed_steps: Vec<_> = workflow.steps.iter().filter(|step| step.status == StepStatus::Failed).collect();
+        if let Some(provider) = self.provider.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            let called = failed_steps.iter()
+                .any(|step| step.service_provider.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(provider)));
+            if !called {
+                return false;
+            }
+        }
+        if let Some(text) = self.error_contains.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            let text = text.to_lowercase();
+            let contains = |error: &Option<String>| error.as_deref().is_some_and(|e| e.to_lowercase().contains(&text));
+            if !contains(&workflow.error_message) && !failed_steps.iter().any(|step| contains(&step.error_message)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Why a matching workflow was not re-run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum RerunSkipReason {
+    /// An earlier re-run already picked it up
+    AlreadyRerun { rerun_id: String },
+    /// It failed in a way re-running will not fix, such as a content policy refusal
+    NotRetryable,
+    /// It is the result of re-runs that kept failing
+    RerunLimitReached { attempts: u32 },
+}
+
+/// A failed workflow and the re-run it was given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequeuedWorkflow {
+    pub original_id: Uuid,
+    pub rerun_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedWorkflow {
+    pub workflow_id: Uuid,
+    #[serde(flatten)]
+    pub reason: RerunSkipReason,
+}
+
+/// What a bulk re-run did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RerunSummary {
+    /// Failed workflows the filter matched
+    pub matched: usize,
+    pub requeued: Vec<RequeuedWorkflow>,
+    pub skipped: Vec<SkippedWorkflow>,
+    /// Workflows whose re-run could not be queued, with the error
+    pub failed: Vec<(Uuid, String)>,
+    pub dry_run: bool,
+}
+
+fn rerun_attempt(workflow: &ResearchWorkflow) -> u32 {
+    workflow.metadata.get(RERUN_ATTEMPT_KEY).and_then(|attempt| attempt.parse().ok()).unwrap_or(0)
+}
+
+/// Why a failed workflow should not be re-run, if it should not
+pub fn skip_reason(workflow: &ResearchWorkflow) -> Option<RerunSkipReason> {
+    if let Some(rerun_id) = workflow.metadata.get(RERUN_AS_KEY) {
+        return Some(RerunSkipReason::AlreadyRerun { rerun_id: rerun_id.clone() });
+    }
+    if workflow.steps.iter().any(|step| step.status == StepStatus::Failed && step.non_retryable) {
+        return Some(RerunSkipReason::NotRetryable);
+    }
+    let attempts = rerun_attempt(workflow);
+    (attempts >= MAX_RERUN_ATTEMPTS).then_some(RerunSkipReason::RerunLimitReached { attempts })
+}
+
+/// A fresh workflow with `source`'s name, query, template and parameters, linked to it.
+/// Links `source` had to other workflows or bundles are not carried over.
+pub fn rerun_workflow(source: &ResearchWorkflow) -> ResearchWorkflow {
+    let mut workflow = ResearchWorkflow::new(
+        source.name.clone(),
+        source.query.clone(),
+        source.parameters.clone(),
+        source.created_by.clone(),
+    );
+    workflow.template_id = source.template_id;
+    workflow.tags = source.tags.clone();
+    workflow.folder = source.folder.clone();
+    workflow.metadata = source.metadata.clone();
+    workflow.metadata.remove(COALESCED_WITH_KEY);
+    workflow.metadata.remove(IMPORTED_FROM_KEY);
+    workflow.metadata.insert(RERUN_OF_KEY.to_string(), source.id.to_string());
+    workflow.metadata.insert(RERUN_ATTEMPT_KEY.to_string(), (rerun_attempt(source) + 1).to_string());
+    workflow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::{WorkflowParameters, WorkflowStep};
+
+    fn failed_workflow(provider: &str, error: &str) -> ResearchWorkflow {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "Grid-scale battery costs".to_string(),
+            WorkflowParameters::default(),
+            "analyst".to_string(),
+        );
+        let mut step = WorkflowStep::new(workflow.id, 1, "Initial Web Search".to_string(), String::new());
+        step.service_provider = Some(provider.to_string());
+        step.fail(error.to_string());
+        workflow.steps.push(step);
+        workflow.fail(format!("Step 1 failed: {}", error));
+        workflow
+    }
+
+    #[test]
+    fn test_reruns_pick_up_the_providers_failures_once_and_give_up_eventually() {
+        let filter = RerunFilter { provider: Some("SerpApi".to_string()), ..Default::default() };
+        assert!(filter.validate().is_ok());
+        assert!(RerunFilter::default().validate().is_err());
+
+        let mut outage = failed_workflow("serpapi", "Service unavailable: 503");
+        outage.metadata.insert(IMPORTED_FROM_KEY.to_string(), "bundle-7".to_string());
+        assert!(filter.matches(&outage));
+        assert!(!filter.matches(&failed_workflow("tavily", "Service unavailable: 503")));
+        let by_error = RerunFilter { error_contains: Some("SERVICE UNAVAILABLE".to_string()), ..Default::default() };
+        assert!(by_error.matches(&outage));
+        let too_early = RerunFilter { failed_after: Some(Utc::now() + chrono::Duration::hours(1)), ..filter.clone() };
+        assert!(!too_early.matches(&outage));
+
+        assert_eq!(skip_reason(&outage), None);
+        let rerun = rerun_workflow(&outage);
+        assert_ne!(rerun.id, outage.id);
+        assert_eq!(rerun.query, outage.query);
+        assert_eq!(rerun.status, WorkflowStatus::Created);
+        assert_eq!(rerun.metadata[RERUN_OF_KEY], outage.id.to_string());
+        assert_eq!(rerun.metadata[RERUN_ATTEMPT_KEY], "1");
+        assert!(!rerun.metadata.contains_key(IMPORTED_FROM_KEY));
+
+        // Once linked to its re-run, the original is not picked up again
+        let mut original = outage.clone();
+        original.metadata.insert(RERUN_AS_KEY.to_string(), rerun.id.to_string());
+        assert_eq!(skip_reason(&original), Some(RerunSkipReason::AlreadyRerun { rerun_id: rerun.id.to_string() }));
+
+        let mut refused = failed_workflow("serpapi", "Content policy");
+        refused.steps[0].non_retryable = true;
+        assert_eq!(skip_reason(&refused), Some(RerunSkipReason::NotRetryable));
+
+        let mut third = outage.clone();
+        third.metadata.insert(RERUN_ATTEMPT_KEY.to_string(), MAX_RERUN_ATTEMPTS.to_string());
+        assert_eq!(skip_reason(&third), Some(RerunSkipReason::RerunLimitReached { attempts: MAX_RERUN_ATTEMPTS }));
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::services::api_manager::ContextBudget;
+
+/// Step output key recording how much source context went into the prompt
+pub const CONTEXT_USAGE_KEY: &str = "context_usage";
+
+/// Share of the context budget held back for one-line summaries of sources that did not fit
+const OVERFLOW_SHARE: f64 = 0.1;
+
+/// Shortest remainder worth filling with a truncated source rather than a summary
+const MIN_TRUNCATED_TOKENS: u32 = 200;
+
+/// Longest summary line kept for an overflowing source
+const SUMMARY_CHARS: usize = 240;
+
+/// One piece of material competing for room in a prompt
+#[derive(Debug, Clone)]
+pub struct ContextItem {
+    /// URL of the source, or the name of the data section
+    pub id: String,
+    pub title: String,
+    pub text: String,
+    /// Higher scores are included first
+    pub score: f64,
+}
+
+impl ContextItem {
+    /// Search hits from `organic_results` or `results`, scored by their `relevance` or `score`
+    /// when the provider reports one and by rank otherwise
+    pub fn from_search_results(search_results: &serde_json::Value) -> Vec<ContextItem> {
+        let hits = search_results.get("organic_results")
+            .or_else(|| search_results.get("results"))
+            .and_then(|v| v.as_array());
+
+        hits.into_iter().flatten().enumerate().filter_map(|(rank, hit)| {
+            let url = hit.get("link").or_else(|| hit.get("url")).and_then(|v| v.as_str())?;
+            let text = ["snippet", "content", "text", "description"].iter()
+                .find_map(|key| hit.get(*key).and_then(|v| v.as_str()))
+                .unwrap_or_default();
+            let score = hit.get("relevance")
+                .or_else(|| hit.get("score"))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(1.0 / (rank as f64 + 1.0));
+            Some(ContextItem {
+                id: url.to_string(),
+                title: hit.get("title").and_then(|v| v.as_str()).unwrap_or(url).to_string(),
+                text: text.to_string(),
+                score,
+            })
+        }).collect()
+    }
+
+    /// Scraped pages, scored by the order they were scraped in
+    pub fn from_scraped_content(scraped_content: &serde_json::Value) -> Vec<ContextItem> {
+        scraped_content.as_array().into_iter().flatten().enumerate().filter_map(|(rank, page)| {
+            let page = page.get("data").unwrap_or(page);
+            let text = ["markdown", "content", "text"].iter()
+                .find_map(|key| page.get(*key).and_then(|v| v.as_str()))?;
+            let url = page.pointer("/metadata/sourceURL")
+                .or_else(|| page.get("url"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let title = page.pointer("/metadata/title").and_then(|v| v.as_str()).unwrap_or(url);
+            Some(ContextItem {
+                id: url.to_string(),
+                title: title.to_string(),
+                text: text.to_string(),
+                score: 1.0 / (rank as f64 + 1.0),
+            })
+        }).collect()
+    }
+
+    /// A whole data section as pretty-printed JSON
+    pub fn from_json(section: &str, value: &serde_json::Value, score: f64) -> ContextItem {
+        ContextItem {
+            id: section.to_string(),
+            title: section.to_string(),
+            text: serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+            score,
+        }
+    }
+}
+
+/// How much of the available material made it into a prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextUsage {
+    pub budget_tokens: u32,
+    pub included_tokens: u32,
+    pub omitted_tokens: u32,
+    pub included_items: usize,
+    /// Included with their text cut short
+    pub truncated_items: usize,
+    /// Reduced to a one-line summary
+    pub summarized_items: usize,
+    /// Left out entirely once even summaries no longer fit
+    pub omitted_items: usize,
+    pub omitted_sources: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssembledContext {
+    pub text: String,
+    pub usage: ContextUsage,
+}
+
+/// Rough token count; about four characters per token for English prose
+pub fn estimate_tokens(text: &str) -> u32 {
+    ((text.chars().count() + 3) / 4) as u32
+}
+
+/// Fit `items` into the prompt space `budget` leaves next to `instructions`: the best scored
+/// items go in whole, the next one is truncated to fill the remainder, and the rest are
+/// summarized in a line each. Whatever still does not fit is named in the usage record.
+pub fn assemble_context(mut items: Vec<ContextItem>, instructions: &str, budget: &ContextBudget) -> AssembledContext {
+    let budget_tokens = budget.prompt_tokens().saturating_sub(estimate_tokens(instructions));
+    let mut usage = ContextUsage { budget_tokens, ..Default::default() };
+    let mut text = String::new();
+
+    items.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let overflow_tokens = (budget_tokens as f64 * OVERFLOW_SHARE) as u32;
+    let mut remaining = budget_tokens - overflow_tokens;
+    let mut overflow = Vec::new();
+
+    for item in items {
+        let block = render_item(&item, &item.text);
+        let tokens = estimate_tokens(&block);
+        if tokens <= remaining {
+            text.push_str(&block);
+            remaining -= tokens;
+            usage.included_items += 1;
+            usage.included_tokens += tokens;
+        } else if overflow.is_empty() && remaining >= MIN_TRUNCATED_TOKENS {
+            let header_tokens = estimate_tokens(&render_item(&item, "")) + 4;
+            let kept = truncate_to_tokens(&item.text, remaining.saturating_sub(header_tokens));
+            let block = render_item(&item, &format!("{} [truncated]", kept));
+            let kept_tokens = estimate_tokens(&block);
+            text.push_str(&block);
+            usage.included_items += 1;
+            usage.truncated_items += 1;
+            usage.included_tokens += kept_tokens;
+            usage.omitted_tokens += tokens.saturating_sub(kept_tokens);
+            remaining = remaining.saturating_sub(kept_tokens);
+        } else {
+            overflow.push(item);
+        }
+    }
+
+    if !overflow.is_empty() {
+        let mut remaining = remaining + overflow_tokens;
+        let heading = "ADDITIONAL SOURCES (summarized to fit the context window):\n";
+        remaining = remaining.saturating_sub(estimate_tokens(heading));
+        text.push_str(heading);
+
+        for item in overflow {
+            let line = format!("- {} ({}): {}\n", item.title, item.id, summary_line(&item.text));
+            let tokens = estimate_tokens(&line);
+            let item_tokens = estimate_tokens(&render_item(&item, &item.text));
+            if tokens <= remaining {
+                text.push_str(&line);
+                remaining -= tokens;
+                usage.summarized_items += 1;
+                usage.included_tokens += tokens;
+                usage.omitted_tokens += item_tokens.saturating_sub(tokens);
+            } else {
+                usage.omitted_items += 1;
+                usage.omitted_tokens += item_tokens;
+                usage.omitted_sources.push(item.id);
+            }
+        }
+
+        if usage.omitted_items > 0 {
+            text.push_str(&format!("- {} further sources omitted\n", usage.omitted_items));
+        }
+    }
+
+    debug!(
+        "Assembled context: {} items in full or truncated, {} summarized, {} omitted ({} of {} tokens)",
+        usage.included_items, usage.summarized_items, usage.omitted_items, usage.included_tokens, budget_tokens
+    );
+    AssembledContext { text, usage }
+}
+
+fn render_item(item: &ContextItem, text: &str) -> String {
+    if item.id == item.title {
+        format!("### {}\n{}\n\n", item.title, text)
+    } else {
+        format!("### {}\nURL: {}\n{}\n\n", item.title, item.id, text)
+    }
+}
+
+/// The longest prefix of `text` within `tokens`, cut at a word boundary
+fn truncate_to_tokens(text: &str, tokens: u32) -> &str {
+    let max_chars = tokens as usize * 4;
+    match text.char_indices().nth(max_chars) {
+        None => text,
+        Some((end, _)) => {
+            let cut = text[..end].rfind(char::is_whitespace).unwrap_or(end);
+            text[..cut].trim_end()
+        }
+    }
+}
+
+/// First sentence of `text`, capped at `SUMMARY_CHARS`
+fn summary_line(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sentence = flat.find(". ").map_or(flat.as_str(), |end| &flat[..=end]);
+    if sentence.chars().count() <= SUMMARY_CHARS {
+        sentence.to_string()
+    } else {
+        format!("{}...", truncate_to_tokens(sentence, (SUMMARY_CHARS / 4) as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, words: usize, score: f64) -> ContextItem {
+        ContextItem {
+            id: format!("https://{}.example", id),
+            title: id.to_string(),
+            text: format!("{} opens the page. {}", id, "lorem ipsum ".repeat(words)),
+            score,
+        }
+    }
+
+    #[test]
+    fn test_context_is_ranked_truncated_and_summarized_to_fit() {
+        let budget = ContextBudget { context_window: 3000, output_reserve_tokens: 1000 };
+        let items = vec![
+            item("low", 400, 0.1),
+            item("best", 100, 0.9),
+            item("second", 100, 0.8),
+            item("third", 2000, 0.5),
+            item("fourth", 400, 0.4),
+        ];
+
+        let assembled = assemble_context(items, "Analyze the sources.", &budget);
+        let usage = &assembled.usage;
+        assert!(usage.included_tokens <= usage.budget_tokens);
+        assert_eq!(usage.included_items, 3);
+        assert_eq!(usage.truncated_items, 1);
+        assert_eq!(usage.summarized_items, 2);
+        assert!(usage.omitted_tokens > 0);
+
+        let text = &assembled.text;
+        assert!(text.find("### best").unwrap() < text.find("### second").unwrap());
+        assert!(text.contains("[truncated]"));
+        assert!(text.contains("- fourth (https://fourth.example): fourth opens the page."));
+
+        let tight = ContextBudget { context_window: 1020, output_reserve_tokens: 1000 };
+        let assembled = assemble_context(vec![item("a", 400, 0.9), item("b", 400, 0.8), item("c", 400, 0.7)], "", &tight);
+        assert!(assembled.usage.omitted_items > 0);
+        assert!(assembled.text.contains("further sources omitted"));
+    }
+}
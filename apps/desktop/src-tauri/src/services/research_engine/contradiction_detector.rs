@@ -0,0 +1,789 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::error::{AppError, AppResult};
+use crate::models::nlp_engine::{
+    EntityExtraction, EntityType, ModelCapability, ModelStatus, NLPProcessingRequest, ProcessingType, Sentiment, SentimentAnalysis,
+};
+use crate::models::research_workflow::{ContradictionCheck, InsightConfidenceFilter, ResearchResults};
+use crate::models::research_insight::{InsightCategory, ResearchInsight, INSIGHTS_METADATA_KEY};
+use crate::services::nlp_engine::NLPEngineService;
+use crate::services::output_processor::analysis::result_diff::report_insights;
+use super::overlap_checker::SourceText;
+
+/// Result metadata key holding the contradictions found between the sources
+pub const CONTRADICTIONS_METADATA_KEY: &str = "contradictions";
+
+/// Heading of the report section listing the contradictions
+const CONTRADICTIONS_HEADING: &str = "## Contradictions Across Sources";
+
+/// Relative difference beyond which two figures for the same fact disagree, so rounding
+/// and estimates made at different times do not count
+const NUMERIC_TOLERANCE: f64 = 0.25;
+
+/// Share of the shorter claim's topic words two figures must share to be about the same fact
+const MIN_TOPIC_OVERLAP: f64 = 0.5;
+
+/// Average sentiment score per sentence at which a text takes a side on an entity
+const MIN_STANCE: f64 = 0.5;
+
+/// Words that carry no claim: function words, reporting verbs, hedges and months
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "nor", "of", "in", "on", "at", "to", "for", "from", "by", "with", "as",
+    "into", "over", "under", "per", "after", "before", "during", "since", "until", "between", "among", "against",
+    "within", "without", "up", "down", "out", "off", "is", "are", "was", "were", "be", "been", "being", "has", "have",
+    "had", "do", "does", "did", "it", "its", "this", "that", "these", "those", "they", "their", "them", "there", "we",
+    "our", "he", "she", "his", "her", "you", "your", "i", "which", "who", "whom", "whose", "what", "when", "where",
+    "while", "than", "then", "also", "only", "just", "about", "around", "approximately", "nearly", "roughly",
+    "almost", "some", "many", "most", "more", "less", "least", "all", "each", "both", "other", "another", "such",
+    "very", "much", "can", "could", "may", "might", "will", "would", "should", "must", "not", "no", "according",
+    "reported", "reports", "report", "said", "says", "say", "found", "finds", "find", "showed", "shows", "show",
+    "noted", "notes", "estimated", "estimates", "reached", "reaches", "stood", "came", "rose", "fell", "grew",
+    "however", "although", "though", "yet", "so", "overall", "new", "year", "years", "january", "february",
+    "march", "april", "may", "june", "july", "august", "september", "october", "november", "december",
+];
+
+const SCALES: &[(&str, f64)] = &[("thousand", 1e3), ("million", 1e6), ("billion", 1e9), ("trillion", 1e12)];
+
+/// The NLP engine contradiction checks read entities and sentiment with, once the
+/// service manager has made it. Empty until then, and in tests.
+pub type NlpEngineSlot = Arc<RwLock<Option<Arc<RwLock<NLPEngineService>>>>>;
+
+/// What the NLP engine reads from a sentence
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SentenceAnalysis {
+    /// Names of the entities the sentence makes claims about
+    pub entities: Vec<String>,
+    pub sentiment: Option<Sentiment>,
+}
+
+/// Reads the entities and sentiment of the sentences a contradiction check compares
+#[async_trait]
+pub trait SentenceAnalyzer: Send + Sync {
+    /// The analysis of each of `sentences`, in order
+    async fn analyze_sentences(&self, sentences: &[&str]) -> AppResult<Vec<SentenceAnalysis>>;
+}
+
+#[async_trait]
+impl SentenceAnalyzer for RwLock<NLPEngineService> {
+    async fn analyze_sentences(&self, sentences: &[&str]) -> AppResult<Vec<SentenceAnalysis>> {
+        let nlp_engine = self.read().await;
+        let model = nlp_engine.get_available_models(None).await?.into_iter()
+            .find(|model| {
+                matches!(model.status, ModelStatus::Available)
+                    && model.capabilities.iter().any(|capability| matches!(capability, ModelCapability::NamedEntityRecognition))
+                    && model.capabilities.iter().any(|capability| matches!(capability, ModelCapability::SentimentAnalysis))
+            })
+            .ok_or_else(|| AppError::external_service("nlp_engine", "No available model recognizes both entities and sentiment"))?;
+
+        let mut analyses = Vec::with_capacity(sentences.len());
+        for sentence in sentences {
+            let result = nlp_engine.analyze_text(NLPProcessingRequest {
+                text: sentence.to_string(),
+                model_id: model.id,
+                processing_types: vec![ProcessingType::NamedEntityRecognition, ProcessingType::SentimentAnalysis],
+                parameters: HashMap::new(),
+                return_embeddings: false,
+                max_processing_time_ms: None,
+            }).await?;
+            if !result.success {
+                return Err(AppError::external_service(
+                    "nlp_engine",
+                    result.error_message.unwrap_or_else(|| "Text analysis failed".to_string()),
+                ));
+            }
+
+            let entities = result.results.get(&ProcessingType::NamedEntityRecognition)
+                .and_then(|value| serde_json::from_value::<EntityExtraction>(value.clone()).ok())
+                .map(|extraction| extraction.entities.into_iter()
+                    .filter(|entity| is_claim_subject(&entity.entity_type))
+                    .map(|entity| entity.normalized_form.unwrap_or(entity.text))
+                    .collect())
+                .unwrap_or_default();
+            let sentiment = result.results.get(&ProcessingType::SentimentAnalysis)
+                .and_then(|value| serde_json::from_value::<SentimentAnalysis>(value.clone()).ok())
+                .map(|analysis| analysis.overall_sentiment);
+            analyses.push(SentenceAnalysis { entities, sentiment });
+        }
+        Ok(analyses)
+    }
+}
+
+/// Entities claims are made about; dates, amounts and percentages are what is claimed
+fn is_claim_subject(entity_type: &EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::Person | EntityType::Organization | EntityType::Location | EntityType::Product | EntityType::Event | EntityType::Technology
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContradictionKind {
+    /// Different figures for the same fact
+    Numeric,
+    /// Opposing sentiment on the same entity
+    Sentiment,
+}
+
+/// One side of a contradiction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictingClaim {
+    /// The sentence making the claim
+    pub text: String,
+    /// The figure as stated, for numeric contradictions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<Sentiment>,
+    /// URLs of the sources making the claim; empty when only the report makes it
+    pub sources: Vec<String>,
+}
+
+/// Claims about the same entity that cannot all be true
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contradiction {
+    pub kind: ContradictionKind,
+    pub entity: String,
+    /// What the conflicting figures measure; empty for sentiment contradictions
+    pub topic: String,
+    pub claims: Vec<ConflictingClaim>,
+    /// From 0.0 to 1.0, higher the further apart the claims are
+    pub confidence: f32,
+}
+
+impl Contradiction {
+    /// Every source cited by the claims, in claim order
+    pub fn sources(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.claims.iter()
+            .flat_map(|claim| claim.sources.iter())
+            .filter(|url| seen.insert(url.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// The contradiction as a report insight citing the conflicting sources
+    pub fn to_insight(&self) -> ResearchInsight {
+        let positions: Vec<String> = self.claims.iter()
+            .map(|claim| {
+                let position = match self.kind {
+                    ContradictionKind::Numeric => claim.stated.clone().unwrap_or_default(),
+                    ContradictionKind::Sentiment => claim.sentiment.map(sentiment_label).unwrap_or_default().to_string(),
+                };
+                let cited = if claim.sources.is_empty() { "the report".to_string() } else { claim.sources.join(", ") };
+                format!("{} ({})", position, cited)
+            })
+            .collect();
+        let insight = match self.kind {
+            ContradictionKind::Numeric => format!("Sources disagree on {} {}: {}", self.entity, self.topic, positions.join(" vs ")),
+            ContradictionKind::Sentiment => format!("Sources disagree about {}: {}", self.entity, positions.join(" vs ")),
+        };
+
+        ResearchInsight {
+            insight,
+            category: InsightCategory::Contradiction,
+            confidence: Some(self.confidence),
+            supporting_sources: self.sources(),
+        }
+    }
+}
+
+/// Text making claims: a source, or one of the report's insights and the sources it cites
+struct ClaimText<'a> {
+    /// Claims from the same origin never contradict each other
+    origin: String,
+    text: &'a str,
+    sources: Vec<String>,
+}
+
+/// A word of a sentence
+struct Token<'a> {
+    raw: &'a str,
+    /// Without surrounding punctuation or a possessive
+    name: &'a str,
+    /// Lowercased alphanumerics
+    word: String,
+}
+
+/// A figure stated about an entity
+struct Figure<'a> {
+    claim: &'a ClaimText<'a>,
+    sentence: &'a str,
+    entity: String,
+    unit: String,
+    value: f64,
+    stated: String,
+    year: Option<u32>,
+    /// Content words of the sentence other than the entity and figures, in order
+    topic: Vec<String>,
+}
+
+/// A text's sentiment on an entity, over the sentences mentioning it
+struct Stance<'a> {
+    claim: &'a ClaimText<'a>,
+    entity: String,
+    score: i32,
+    scored_sentences: u32,
+    /// The sentence with the strongest sentiment
+    sentence: &'a str,
+    strongest: i32,
+}
+
+impl Stance<'_> {
+    fn average(&self) -> f64 {
+        self.score as f64 / self.scored_sentences.max(1) as f64
+    }
+}
+
+/// Claims that the sources, or the report's insights and the sources, disagree on:
+/// different figures for the same fact about an entity, and opposing sentiment on one.
+/// `analyzer` finds the entities and sentiment of the first `max_sentences_per_source`
+/// sentences of each text; only the first `max_figures_per_entity` figures stated about
+/// an entity are compared.
+pub async fn detect_contradictions(
+    sources: &[SourceText],
+    insights: &[ResearchInsight],
+    check: &ContradictionCheck,
+    analyzer: &dyn SentenceAnalyzer,
+) -> AppResult<Vec<Contradiction>> {
+    let texts: Vec<ClaimText> = sources.iter()
+        .map(|source| ClaimText { origin: source.url.clone(), text: &source.text, sources: vec![source.url.clone()] })
+        .chain(insights.iter().enumerate()
+            // A contradiction already reported states both sides itself
+            .filter(|(_, insight)| insight.category != InsightCategory::Contradiction)
+            .map(|(i, insight)| ClaimText {
+                origin: format!("insight:{}", i),
+                text: &insight.insight,
+                sources: insight.supporting_sources.clone(),
+            }))
+        .collect();
+
+    let sentences: Vec<(&ClaimText, &str, Vec<Token>)> = texts.iter()
+        .flat_map(|claim| split_sentences(claim.text).into_iter()
+            .take(check.max_sentences_per_source)
+            .map(move |sentence| (claim, sentence, tokenize(sentence))))
+        .collect();
+    let analyses = analyzer
+        .analyze_sentences(&sentences.iter().map(|(_, sentence, _)| *sentence).collect::<Vec<_>>())
+        .await?;
+
+    let mut figures: BTreeMap<(String, String), Vec<Figure>> = BTreeMap::new();
+    let mut compared_figures: HashMap<String, usize> = HashMap::new();
+    let mut stances: BTreeMap<String, Vec<Stance>> = BTreeMap::new();
+    let mut entity_names: BTreeMap<String, String> = BTreeMap::new();
+
+    for (&(claim, sentence, ref tokens), analysis) in sentences.iter().zip(&analyses) {
+        let mut entities: Vec<(String, String)> = Vec::new();
+        for name in &analysis.entities {
+            let key = tokenize(name).iter().map(|token| token.word.as_str()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ");
+            if !key.is_empty() && !entities.iter().any(|(existing, _)| *existing == key) {
+                entities.push((key, name.trim().to_string()));
+            }
+        }
+        if entities.is_empty() {
+            continue;
+        }
+
+        let mut stated = Vec::new();
+        let mut figure_words = Vec::new();
+        let mut year = None;
+        let mut i = 0;
+        while i < tokens.len() {
+            match read_figure(tokens, i) {
+                Some((value, unit, len)) => {
+                    match unit {
+                        Some(unit) => stated.push((value, unit, phrase(&tokens[i..i + len]))),
+                        None if value.fract() == 0.0 && (1900.0..=2100.0).contains(&value) => { year.get_or_insert(value as u32); }
+                        None => {}
+                    }
+                    figure_words.extend(i..i + len);
+                    i += len;
+                }
+                None => i += 1,
+            }
+        }
+
+        let score = analysis.sentiment.map(sentiment_score).unwrap_or(0);
+        for (key, name) in &entities {
+            entity_names.entry(key.clone()).or_insert_with(|| name.clone());
+
+            let entity_words: HashSet<&str> = key.split(' ').collect();
+            let topic = topic_words(tokens, |j| entity_words.contains(tokens[j].word.as_str()) || figure_words.contains(&j));
+            let compared = compared_figures.entry(key.clone()).or_default();
+            for (value, unit, text) in &stated {
+                if *compared >= check.max_figures_per_entity {
+                    break;
+                }
+                *compared += 1;
+                figures.entry((key.clone(), unit.clone())).or_default().push(Figure {
+                    claim,
+                    sentence,
+                    entity: key.clone(),
+                    unit: unit.clone(),
+                    value: *value,
+                    stated: text.clone(),
+                    year,
+                    topic: topic.clone(),
+                });
+            }
+
+            if score != 0 {
+                let entity_stances = stances.entry(key.clone()).or_default();
+                match entity_stances.iter_mut().find(|stance| stance.claim.origin == claim.origin) {
+                    Some(stance) => {
+                        stance.score += score;
+                        stance.scored_sentences += 1;
+                        if score.abs() > stance.strongest.abs() {
+                            stance.sentence = sentence;
+                            stance.strongest = score;
+                        }
+                    }
+                    None => entity_stances.push(Stance {
+                        claim,
+                        entity: key.clone(),
+                        score,
+                        scored_sentences: 1,
+                        sentence,
+                        strongest: score,
+                    }),
+                }
+            }
+        }
+    }
+
+    let mut contradictions = Vec::new();
+    for group in figures.values() {
+        contradictions.extend(numeric_contradictions(group, &entity_names));
+    }
+    for entity_stances in stances.values() {
+        contradictions.extend(sentiment_contradiction(entity_stances, &entity_names));
+    }
+    Ok(contradictions)
+}
+
+/// Add the contradictions found between `sources` and the report's insights to the
+/// report as `Contradiction` insights, under their own section, returning how many
+/// were added. Contradictions below the workflow's minimum insight confidence are left out.
+pub async fn surface_contradictions(
+    results: &mut ResearchResults,
+    sources: &[SourceText],
+    filter: &InsightConfidenceFilter,
+    check: &ContradictionCheck,
+    analyzer: &dyn SentenceAnalyzer,
+) -> AppResult<usize> {
+    let mut insights = report_insights(results);
+    let contradictions: Vec<Contradiction> = detect_contradictions(sources, &insights, check, analyzer).await?.into_iter()
+        .filter(|contradiction| filter.keeps(Some(contradiction.confidence)))
+        .collect();
+    results.metadata.insert(CONTRADICTIONS_METADATA_KEY.to_string(), serde_json::to_value(&contradictions)?);
+
+    let known: HashSet<String> = insights.iter().map(|insight| insight.insight.to_lowercase()).collect();
+    let found: Vec<ResearchInsight> = contradictions.iter()
+        .map(Contradiction::to_insight)
+        .filter(|insight| !known.contains(&insight.insight.to_lowercase()))
+        .collect();
+    if found.is_empty() {
+        return Ok(0);
+    }
+
+    results.content.push_str(&format!("\n\n{}\n\n", CONTRADICTIONS_HEADING));
+    for insight in &found {
+        results.content.push_str(&format!("- {}\n", insight.insight));
+    }
+    results.word_count = results.content.split_whitespace().count() as u32;
+
+    let added = found.len();
+    insights.extend(found);
+    results.metadata.insert(INSIGHTS_METADATA_KEY.to_string(), serde_json::to_value(&insights)?);
+    Ok(added)
+}
+
+/// Figures for the same fact, from different texts, too far apart to both hold
+fn numeric_contradictions(group: &[Figure], entity_names: &BTreeMap<String, String>) -> Vec<Contradiction> {
+    let mut cluster: Vec<usize> = (0..group.len()).collect();
+    fn root(cluster: &mut [usize], mut i: usize) -> usize {
+        while cluster[i] != i {
+            cluster[i] = cluster[cluster[i]];
+            i = cluster[i];
+        }
+        i
+    }
+
+    let mut conflicts = Vec::new();
+    for i in 0..group.len() {
+        for j in i + 1..group.len() {
+            let (a, b) = (&group[i], &group[j]);
+            if a.claim.origin == b.claim.origin || a.year.zip(b.year).is_some_and(|(a, b)| a != b) {
+                continue;
+            }
+            let Some(overlap) = topic_overlap(&a.topic, &b.topic).filter(|overlap| *overlap >= MIN_TOPIC_OVERLAP) else {
+                continue;
+            };
+            let difference = (a.value - b.value).abs() / a.value.abs().max(b.value.abs()).max(f64::EPSILON);
+            if difference > NUMERIC_TOLERANCE {
+                conflicts.push((i, j, difference * overlap));
+                let (ri, rj) = (root(&mut cluster, i), root(&mut cluster, j));
+                cluster[rj] = ri;
+            }
+        }
+    }
+
+    let mut by_root: BTreeMap<usize, (Vec<usize>, f64)> = BTreeMap::new();
+    for (i, j, strength) in conflicts {
+        let entry = by_root.entry(root(&mut cluster, i)).or_default();
+        for member in [i, j] {
+            if !entry.0.contains(&member) {
+                entry.0.push(member);
+            }
+        }
+        entry.1 = entry.1.max(strength);
+    }
+
+    by_root.into_values()
+        .map(|(mut members, strength)| {
+            members.sort_unstable();
+            let first = &group[members[0]];
+            let shared: HashSet<&String> = members.iter().skip(1)
+                .flat_map(|m| group[*m].topic.iter())
+                .collect();
+            let topic: Vec<&str> = first.topic.iter().filter(|word| shared.contains(word)).map(String::as_str).collect();
+
+            // Texts stating the same figure are one side of the contradiction
+            let mut claims: Vec<ConflictingClaim> = Vec::new();
+            for figure in members.iter().map(|m| &group[*m]) {
+                match claims.iter_mut().find(|claim| claim.stated.as_deref() == Some(figure.stated.as_str())) {
+                    Some(claim) => {
+                        for url in &figure.claim.sources {
+                            if !claim.sources.contains(url) {
+                                claim.sources.push(url.clone());
+                            }
+                        }
+                    }
+                    None => claims.push(ConflictingClaim {
+                        text: figure.sentence.to_string(),
+                        stated: Some(figure.stated.clone()),
+                        sentiment: None,
+                        sources: figure.claim.sources.clone(),
+                    }),
+                }
+            }
+
+            Contradiction {
+                kind: ContradictionKind::Numeric,
+                entity: entity_names.get(&first.entity).cloned().unwrap_or_else(|| first.entity.clone()),
+                topic: if topic.is_empty() { first.unit.clone() } else { topic.join(" ") },
+                claims,
+                confidence: (0.5 + 0.5 * strength.min(1.0)).min(0.95) as f32,
+            }
+        })
+        .collect()
+}
+
+/// Texts taking opposite sides on an entity
+fn sentiment_contradiction(stances: &[Stance], entity_names: &BTreeMap<String, String>) -> Option<Contradiction> {
+    let sided: Vec<&Stance> = stances.iter().filter(|stance| stance.average().abs() >= MIN_STANCE).collect();
+    let strongest_positive = sided.iter().map(|stance| stance.average()).fold(0.0, f64::max);
+    let strongest_negative = sided.iter().map(|stance| -stance.average()).fold(0.0, f64::max);
+    if strongest_positive == 0.0 || strongest_negative == 0.0 {
+        return None;
+    }
+
+    let entity = &sided[0].entity;
+    Some(Contradiction {
+        kind: ContradictionKind::Sentiment,
+        entity: entity_names.get(entity).cloned().unwrap_or_else(|| entity.clone()),
+        topic: String::new(),
+        claims: sided.iter()
+            .map(|stance| ConflictingClaim {
+                text: stance.sentence.to_string(),
+                stated: None,
+                sentiment: Some(to_sentiment(stance.average())),
+                sources: stance.claim.sources.clone(),
+            })
+            .collect(),
+        confidence: (0.5 + 0.25 * strongest_positive.min(strongest_negative)).min(0.95) as f32,
+    })
+}
+
+/// Shared words over the words of the shorter topic; `None` when either is empty
+fn topic_overlap(a: &[String], b: &[String]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let shared = a.iter().filter(|word| b.contains(word)).count();
+    Some(shared as f64 / a.len().min(b.len()) as f64)
+}
+
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let ends = match c {
+            '\n' => true,
+            '.' | '!' | '?' => !matches!(chars.peek(), Some((_, next)) if !next.is_whitespace()),
+            _ => false,
+        };
+        if ends {
+            sentences.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    sentences.push(&text[start..]);
+
+    sentences.into_iter()
+        .map(|sentence| sentence.trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '*' | '+' | '#' | '>')).trim())
+        .filter(|sentence| !sentence.is_empty())
+        .collect()
+}
+
+fn tokenize(sentence: &str) -> Vec<Token<'_>> {
+    sentence.split_whitespace()
+        .map(|raw| {
+            let core = raw.trim_matches(|c: char| !c.is_alphanumeric());
+            let name = core.strip_suffix("'s").or_else(|| core.strip_suffix("’s")).unwrap_or(core);
+            let word = name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+            Token { raw, name, word }
+        })
+        .filter(|token| !token.word.is_empty() || token.raw.chars().any(|c| matches!(c, '$' | '€' | '£')))
+        .collect()
+}
+
+/// The figure starting at `tokens[i]`: its value, unit when it has one, and how many
+/// words it spans. Percentages, currency amounts and counts of a unit have units; bare
+/// numbers, years among them, do not.
+fn read_figure(tokens: &[Token], i: usize) -> Option<(f64, Option<String>, usize)> {
+    let raw = tokens[i].raw.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '"' | '\'' | '“' | '”' | '–'));
+    let raw = raw.strip_suffix('.').unwrap_or(raw);
+
+    let (rest, currency) = [("$", "USD"), ("€", "EUR"), ("£", "GBP")].iter()
+        .find_map(|(symbol, code)| raw.strip_prefix(symbol).map(|rest| (rest, Some(*code))))
+        .unwrap_or((raw, None));
+    let (rest, mut percent) = match rest.strip_suffix('%') {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    let (digits, mut scale) = [("bn", 1e9), ("b", 1e9), ("m", 1e6), ("k", 1e3)].iter()
+        .find_map(|(suffix, scale)| {
+            let digits = rest.len().checked_sub(suffix.len()).and_then(|end| rest.get(..end))?;
+            (rest[digits.len()..].eq_ignore_ascii_case(suffix) && digits.ends_with(|c: char| c.is_ascii_digit()))
+                .then_some((digits, *scale))
+        })
+        .unwrap_or((rest, 1.0));
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: f64 = digits.replace(',', "").parse().ok()?;
+
+    let mut len = 1;
+    let next = |len: usize| tokens.get(i + len).map(|token| token.word.as_str());
+    if !percent && next(len) == Some("percent") {
+        percent = true;
+        len += 1;
+    }
+    if let Some((_, factor)) = SCALES.iter().find(|(word, _)| next(len) == Some(*word)) {
+        scale = *factor;
+        len += 1;
+    }
+
+    let unit = if percent {
+        Some("%".to_string())
+    } else if let Some(code) = currency {
+        Some(code.to_string())
+    } else {
+        match next(len) {
+            Some(word) if !word.is_empty() && word.chars().all(char::is_alphabetic) && !is_filler(word) => {
+                len += 1;
+                Some(singular(word))
+            }
+            _ => None,
+        }
+    };
+    Some((value * scale, unit, len))
+}
+
+/// Content words of a sentence, singular, outside the `skip`ped positions
+fn topic_words(tokens: &[Token], skip: impl Fn(usize) -> bool) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if skip(i) || token.word.len() < 3 || is_filler(&token.word) || token.word.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        let word = singular(&token.word);
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// A stopword, not naming what a figure measures
+fn is_filler(word: &str) -> bool {
+    STOPWORDS.contains(&word)
+}
+
+fn singular(word: &str) -> String {
+    match word.strip_suffix('s') {
+        Some(stem) if word.len() > 3 && !stem.ends_with('s') => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+/// A sentence's sentiment as a score, negative to positive
+fn sentiment_score(sentiment: Sentiment) -> i32 {
+    match sentiment {
+        Sentiment::VeryNegative => -2,
+        Sentiment::Negative => -1,
+        Sentiment::Neutral => 0,
+        Sentiment::Positive => 1,
+        Sentiment::VeryPositive => 2,
+    }
+}
+
+fn to_sentiment(average: f64) -> Sentiment {
+    match average {
+        a if a >= 1.5 => Sentiment::VeryPositive,
+        a if a >= MIN_STANCE => Sentiment::Positive,
+        a if a <= -1.5 => Sentiment::VeryNegative,
+        a if a <= -MIN_STANCE => Sentiment::Negative,
+        _ => Sentiment::Neutral,
+    }
+}
+
+fn sentiment_label(sentiment: Sentiment) -> &'static str {
+    match sentiment {
+        Sentiment::VeryNegative => "very negative",
+        Sentiment::Negative => "negative",
+        Sentiment::Neutral => "neutral",
+        Sentiment::Positive => "positive",
+        Sentiment::VeryPositive => "very positive",
+    }
+}
+
+/// The words as written, without trailing punctuation
+fn phrase(tokens: &[Token]) -> String {
+    let phrase = tokens.iter().map(|token| token.raw).collect::<Vec<_>>().join(" ");
+    phrase.trim_end_matches(['.', ',', ';', ':', ')']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::ResearchMethodology;
+
+    const A: &str = "https://a.example/acme";
+    const B: &str = "https://b.example/acme";
+
+    fn source(url: &str, text: &str) -> SourceText {
+        SourceText { url: url.to_string(), text: text.to_string() }
+    }
+
+    /// Reads the entities and sentiment the fixtures state, as the NLP engine would
+    struct FixtureAnalyzer;
+
+    #[async_trait]
+    impl SentenceAnalyzer for FixtureAnalyzer {
+        async fn analyze_sentences(&self, sentences: &[&str]) -> AppResult<Vec<SentenceAnalysis>> {
+            const SENTIMENTS: &[(&str, Sentiment)] = &[
+                ("unreliable", Sentiment::VeryNegative),
+                ("praised", Sentiment::VeryPositive),
+                ("not disappointing", Sentiment::Neutral),
+                ("reliable", Sentiment::Positive),
+            ];
+            Ok(sentences.iter()
+                .map(|sentence| SentenceAnalysis {
+                    entities: ["Acme Corp", "Globex"].iter().filter(|name| sentence.contains(*name)).map(|name| name.to_string()).collect(),
+                    sentiment: SENTIMENTS.iter().find(|(phrase, _)| sentence.contains(phrase)).map(|(_, sentiment)| *sentiment),
+                })
+                .collect())
+        }
+    }
+
+    fn check() -> ContradictionCheck {
+        ContradictionCheck { enabled: true, ..ContradictionCheck::default() }
+    }
+
+    fn fixture_sources() -> Vec<SourceText> {
+        vec![
+            source(A, "Acme Corp reported revenue growth of 40% in 2023. Analysts praised the Acme Corp battery as reliable and efficient."),
+            source(B, "Acme Corp revenue growth was only 12% in 2023. Reviewers found the Acme Corp battery unreliable and disappointing."),
+            // Another entity, and the same fact for another year, disagree with nothing
+            source("https://c.example/globex", "Revenue growth at Globex reached 15% in 2023. Acme Corp revenue growth was 13% in 2019."),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_contradictory_sources_surface_as_contradiction_insights() {
+        let insights = vec![ResearchInsight {
+            insight: "Acme Corp revenue growth reached 40% in 2023".to_string(),
+            category: InsightCategory::KeyFinding,
+            confidence: None,
+            supporting_sources: vec![A.to_string()],
+        }];
+        let contradictions = detect_contradictions(&fixture_sources(), &insights, &check(), &FixtureAnalyzer).await.unwrap();
+        assert_eq!(contradictions.len(), 2, "{:#?}", contradictions);
+
+        let numeric = &contradictions[0];
+        assert_eq!((numeric.kind, numeric.entity.as_str(), numeric.topic.as_str()), (ContradictionKind::Numeric, "Acme Corp", "revenue growth"));
+        // The insight repeating source A's figure sides with it
+        let stated: Vec<_> = numeric.claims.iter().map(|claim| (claim.stated.as_deref().unwrap(), claim.sources.clone())).collect();
+        assert_eq!(stated, vec![("40%", vec![A.to_string()]), ("12%", vec![B.to_string()])]);
+        assert!(numeric.confidence > 0.8);
+
+        let sentiment = &contradictions[1];
+        assert_eq!((sentiment.kind, sentiment.entity.as_str()), (ContradictionKind::Sentiment, "Acme Corp"));
+        assert_eq!(sentiment.claims.iter().map(|claim| claim.sentiment).collect::<Vec<_>>(),
+            vec![Some(Sentiment::VeryPositive), Some(Sentiment::VeryNegative)]);
+        assert_eq!(sentiment.sources(), vec![A.to_string(), B.to_string()]);
+
+        let insight = numeric.to_insight();
+        assert_eq!(insight.category, InsightCategory::Contradiction);
+        assert_eq!(insight.insight, format!("Sources disagree on Acme Corp revenue growth: 40% ({}) vs 12% ({})", A, B));
+        assert_eq!(insight.supporting_sources, vec![A.to_string(), B.to_string()]);
+
+        // Sources that agree, or one negated into agreement, raise nothing
+        let agreeing = vec![
+            source(A, "Acme Corp revenue growth was 40% last year. The Acme Corp battery is reliable."),
+            source(B, "Acme Corp revenue growth was 38% last year. The Acme Corp battery is not disappointing."),
+        ];
+        assert!(detect_contradictions(&agreeing, &[], &check(), &FixtureAnalyzer).await.unwrap().is_empty());
+
+        // Figures past the cap for their entity are not compared
+        let capped = ContradictionCheck { max_figures_per_entity: 1, ..check() };
+        let contradictions = detect_contradictions(&fixture_sources(), &insights, &capped, &FixtureAnalyzer).await.unwrap();
+        assert_eq!(contradictions.iter().map(|c| c.kind).collect::<Vec<_>>(), vec![ContradictionKind::Sentiment]);
+    }
+
+    #[tokio::test]
+    async fn test_contradictions_are_added_to_the_report() {
+        let mut results = ResearchResults {
+            content: "## Key Findings\n\n- Acme Corp revenue growth reached 40% in 2023".to_string(),
+            sources: vec![A.to_string(), B.to_string()],
+            metadata: Default::default(),
+            word_count: 0,
+            source_count: 2,
+            methodology_used: ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        };
+
+        let added = surface_contradictions(&mut results, &fixture_sources(), &InsightConfidenceFilter::default(), &check(), &FixtureAnalyzer).await.unwrap();
+        assert_eq!(added, 2);
+        let (_, section) = results.content.split_once(CONTRADICTIONS_HEADING).unwrap();
+        assert!(section.contains("Sources disagree about Acme Corp: very positive"));
+
+        let insights = report_insights(&results);
+        assert_eq!(insights.len(), 3);
+        assert_eq!(insights.iter().filter(|insight| insight.category == InsightCategory::Contradiction).count(), 2);
+        assert_eq!(results.metadata[CONTRADICTIONS_METADATA_KEY].as_array().unwrap().len(), 2);
+
+        // Running again finds the same contradictions and adds nothing new
+        assert_eq!(surface_contradictions(&mut results, &fixture_sources(), &InsightConfidenceFilter::default(), &check(), &FixtureAnalyzer).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::api_key::{ApiKey, ServiceProvider};
+use crate::models::research_workflow::{ProviderRecording, ResearchMethodology, ResearchWorkflow, StepStatus};
+use crate::services::api_manager::{CircuitState, FallbackConfig, ServiceCapacity};
+use crate::services::research_engine::preflight::{self, QuotaShortfall};
+
+/// Seconds a request is assumed to take before a provider has served any
+const DEFAULT_REQUEST_SECONDS: f64 = 2.0;
+
+/// A provider as the planner sees it right now
+#[derive(Debug, Clone)]
+pub struct ProviderState {
+    pub capacity: ServiceCapacity,
+    pub selected_key: Option<PlannedKey>,
+    pub circuit: CircuitState,
+    /// Mean response time so far; `None` before the provider has served a request
+    pub average_response_time_ms: Option<f64>,
+}
+
+/// The key a provider's next request would use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedKey {
+    pub id: Uuid,
+    pub name: String,
+}
+
+impl From<&ApiKey> for PlannedKey {
+    fn from(key: &ApiKey) -> Self {
+        Self { id: key.id, name: key.name.clone() }
+    }
+}
+
+/// One step as it would run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedStep {
+    pub step_number: u32,
+    pub name: String,
+    pub description: String,
+    pub provider: Option<ServiceProvider>,
+    /// Providers the step falls back to, in order, when its own cannot serve it
+    pub fallback_providers: Vec<ServiceProvider>,
+    /// Numbers of the steps that must finish first
+    pub depends_on: Vec<u32>,
+    pub estimated_requests: u32,
+    pub estimated_cost_usd: f64,
+    pub estimated_duration_seconds: f64,
+    pub timeout_seconds: u32,
+}
+
+/// What the run needs from one provider, and what the provider has
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderPlan {
+    pub service: ServiceProvider,
+    pub estimated_requests: u32,
+    pub estimated_cost_usd: f64,
+    pub usable_keys: u32,
+    pub remaining_requests: u32,
+    pub selected_key: Option<PlannedKey>,
+    pub circuit: CircuitState,
+}
+
+/// What a workflow would do if started now, worked out without running anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowPlan {
+    pub methodology: ResearchMethodology,
+    pub methodology_name: String,
+    pub steps: Vec<PlannedStep>,
+    /// In the order the run first uses them
+    pub providers: Vec<ProviderPlan>,
+    pub estimated_requests: u32,
+    pub estimated_cost_usd: f64,
+    /// Along the longest chain of dependent steps, ignoring the concurrent step limit
+    pub estimated_duration_seconds: f64,
+    pub quota_shortfalls: Vec<QuotaShortfall>,
+    pub warnings: Vec<String>,
+    /// Served from a recording or the mock providers, so no provider requests are made
+    pub replayed: bool,
+}
+
+/// Plan the steps of `workflow` still to run against the current provider states.
+/// Equal inputs give equal plans.
+pub fn build_plan(
+    workflow: &ResearchWorkflow,
+    providers: &HashMap<ServiceProvider, ProviderState>,
+    fallback: &FallbackConfig,
+) -> WorkflowPlan {
+    let methodology = workflow.parameters.methodology.clone();
+    let replayed = matches!(workflow.parameters.provider_recording, ProviderRecording::Replay { .. } | ProviderRecording::Mock { .. });
+    let numbers: HashMap<Uuid, u32> = workflow.steps.iter().map(|step| (step.id, step.step_number)).collect();
+    let mut finish_times: HashMap<Uuid, f64> = HashMap::new();
+
+    let mut steps = Vec::new();
+    for step in workflow.steps.iter().filter(|step| !matches!(step.status, StepStatus::Completed | StepStatus::Skipped)) {
+        let provider = step.service_provider.as_deref().and_then(ServiceProvider::from_str);
+        let estimated_requests = if replayed { 0 } else { preflight::estimated_calls(step) };
+        let cost_per_request = provider.and_then(|p| fallback.provider_costs.get(&p).copied()).unwrap_or(0.0);
+        let request_seconds = provider
+            .and_then(|p| providers.get(&p))
+            .and_then(|state| state.average_response_time_ms)
+            .filter(|ms| *ms > 0.0)
+            .map_or(DEFAULT_REQUEST_SECONDS, |ms| ms / 1000.0);
+        let timeout_seconds = step.effective_timeout_seconds(&methodology);
+        let duration = (estimated_requests as f64 * request_seconds).min(timeout_seconds as f64);
+
+        let started = step.depends_on.iter().filter_map(|dep| finish_times.get(dep)).fold(0.0, |a: f64, b| a.max(*b));
+        finish_times.insert(step.id, started + duration);
+
+        let mut fallback_providers: Vec<ServiceProvider> = Vec::new();
+        if let Some(primary) = provider {
+            let mut chain_types: Vec<&String> = fallback.chains.keys().collect();
+            chain_types.sort();
+            for chain in chain_types.into_iter().filter_map(|t| fallback.chains.get(t)) {
+                if chain.providers.contains(&primary) {
+                    for other in chain.providers.iter().filter(|p| **p != primary) {
+                        if !fallback_providers.contains(other) {
+                            fallback_providers.push(*other);
+                        }
+                    }
+                }
+            }
+        }
+
+        steps.push(PlannedStep {
+            step_number: step.step_number,
+            name: step.name.clone(),
+            description: step.description.clone(),
+            provider,
+            fallback_providers,
+            depends_on: step.depends_on.iter().filter_map(|dep| numbers.get(dep).copied()).collect(),
+            estimated_requests,
+            estimated_cost_usd: estimated_requests as f64 * cost_per_request,
+            estimated_duration_seconds: duration,
+            timeout_seconds,
+        });
+    }
+
+    let demand = preflight::estimate_provider_calls(workflow);
+    let capacities: Vec<ServiceCapacity> = demand.iter()
+        .filter_map(|(service, _)| providers.get(service).map(|state| state.capacity.clone()))
+        .collect();
+    let quota_shortfalls = preflight::shortfalls(&demand, &capacities);
+
+    let mut warnings = Vec::new();
+    if !quota_shortfalls.is_empty() {
+        warnings.push(format!("Not enough quota: {}", preflight::describe_shortfalls(&quota_shortfalls)));
+    }
+
+    let plans: Vec<ProviderPlan> = demand.iter()
+        .map(|(service, calls)| {
+            let state = providers.get(service);
+            let circuit = state.map_or(CircuitState::Closed, |s| s.circuit.clone());
+            if let CircuitState::Open { until } = &circuit {
+                warnings.push(format!("{} is failing and skipped until {}; its steps fall back or fail until then",
+                    service.display_name(), until.to_rfc3339()));
+            }
+            ProviderPlan {
+                service: *service,
+                estimated_requests: *calls,
+                estimated_cost_usd: *calls as f64 * fallback.provider_costs.get(service).copied().unwrap_or(0.0),
+                usable_keys: state.map_or(0, |s| s.capacity.usable_keys),
+                remaining_requests: state.map_or(0, |s| s.capacity.remaining_requests),
+                selected_key: state.and_then(|s| s.selected_key.clone()),
+                circuit,
+            }
+        })
+        .collect();
+
+    WorkflowPlan {
+        methodology_name: methodology.display_name().to_string(),
+        methodology,
+        estimated_requests: steps.iter().map(|s| s.estimated_requests).fold(0u32, u32::saturating_add),
+        estimated_cost_usd: steps.iter().map(|s| s.estimated_cost_usd).sum(),
+        estimated_duration_seconds: finish_times.values().fold(0.0, |a: f64, b| a.max(*b)),
+        steps,
+        providers: plans,
+        quota_shortfalls,
+        warnings,
+        replayed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use crate::models::research_workflow::{WorkflowParameters, WorkflowStep};
+
+    fn step(workflow_id: Uuid, number: u32, provider: &str, limit: Option<u64>, depends_on: &[Uuid]) -> WorkflowStep {
+        let mut step = WorkflowStep::new(workflow_id, number, format!("step {}", number), String::new());
+        step.service_provider = Some(provider.to_string());
+        step.depends_on = depends_on.to_vec();
+        if let Some(limit) = limit {
+            step.input_data.insert("limit".to_string(), serde_json::json!(limit));
+        }
+        step
+    }
+
+    fn state(service: ServiceProvider, usable_keys: u32, remaining_requests: u32, response_ms: Option<f64>) -> ProviderState {
+        ProviderState {
+            capacity: ServiceCapacity { service, usable_keys, remaining_requests },
+            selected_key: (usable_keys > 0).then(|| PlannedKey { id: Uuid::nil(), name: format!("{:?} key", service) }),
+            circuit: CircuitState::Closed,
+            average_response_time_ms: response_ms,
+        }
+    }
+
+    #[test]
+    fn test_plan_estimates_requests_cost_and_time_from_provider_state() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid storage costs".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        let id = workflow.id;
+        let search = step(id, 1, "serpapi", None, &[]);
+        let crawl = step(id, 2, "firecrawl", Some(10), &[search.id]);
+        let embed = step(id, 3, "jina", None, &[search.id]);
+        let synth = step(id, 4, "openrouter", None, &[crawl.id, embed.id]);
+        workflow.steps = vec![search, crawl, embed, synth];
+
+        let mut providers = HashMap::from([
+            (ServiceProvider::SerpApi, state(ServiceProvider::SerpApi, 1, 100, Some(1000.0))),
+            (ServiceProvider::Firecrawl, state(ServiceProvider::Firecrawl, 2, 5, Some(3000.0))),
+            (ServiceProvider::Jina, state(ServiceProvider::Jina, 1, 100, None)),
+            (ServiceProvider::OpenRouter, state(ServiceProvider::OpenRouter, 1, 100, Some(20000.0))),
+        ]);
+        let until = Utc::now() + Duration::seconds(30);
+        providers.get_mut(&ServiceProvider::Jina).unwrap().circuit = CircuitState::Open { until };
+        let fallback = FallbackConfig::default();
+
+        let plan = build_plan(&workflow, &providers, &fallback);
+        assert_eq!(plan.estimated_requests, 13);
+        assert!((plan.estimated_cost_usd - (0.01 + 10.0 * 0.003 + 0.0002 + 0.01)).abs() < 1e-9);
+        // Search, then the crawl (30s) rather than the embedding (2s), then synthesis
+        assert_eq!(plan.estimated_duration_seconds, 1.0 + 30.0 + 20.0);
+        assert_eq!(plan.steps[3].depends_on, vec![2, 3]);
+        assert_eq!(plan.steps[0].fallback_providers, vec![ServiceProvider::Tavily, ServiceProvider::Exa]);
+
+        assert_eq!(plan.providers.iter().map(|p| p.service).collect::<Vec<_>>(), vec![
+            ServiceProvider::SerpApi, ServiceProvider::Firecrawl, ServiceProvider::Jina, ServiceProvider::OpenRouter,
+        ]);
+        assert_eq!(plan.providers[0].selected_key.as_ref().unwrap().name, "SerpApi key");
+        assert_eq!(plan.quota_shortfalls.len(), 1);
+        assert_eq!(plan.quota_shortfalls[0].service, ServiceProvider::Firecrawl);
+        assert_eq!(plan.warnings.len(), 2);
+        assert!(plan.warnings[1].starts_with("Jina AI is failing"));
+
+        // The same state plans the same way
+        let again = build_plan(&workflow, &providers, &fallback);
+        assert_eq!(serde_json::to_value(&plan).unwrap(), serde_json::to_value(&again).unwrap());
+
+        // A replay makes no provider requests
+        workflow.parameters.provider_recording = ProviderRecording::Replay { source_workflow_id: Uuid::new_v4() };
+        let replay = build_plan(&workflow, &providers, &fallback);
+        assert_eq!(replay.estimated_requests, 0);
+        assert!(replay.providers.is_empty());
+        assert!(replay.replayed);
+    }
+}
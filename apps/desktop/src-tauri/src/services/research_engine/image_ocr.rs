@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::WorkflowStep;
+use crate::services::api_manager::egress;
+use crate::services::research_engine::workflow_engine::ExecutionContext;
+use crate::utils::inject_trace_context;
+use crate::utils::service_signature::ServiceSigner;
+
+/// Input key naming what kind of step a step is, for steps any methodology can include
+pub const STEP_TYPE_KEY: &str = "step_type";
+/// Step type that reads the text out of image sources through the ML inference function
+pub const IMAGE_OCR: &str = "image_ocr";
+
+/// Image URLs to read, besides the image links among the search results
+pub const IMAGE_SOURCES_KEY: &str = "image_sources";
+/// Languages the images are expected to be in, most likely first
+pub const LANGUAGE_HINTS_KEY: &str = "language_hints";
+/// Confidence below which an extraction is flagged as unreliable
+pub const MIN_CONFIDENCE_KEY: &str = "min_confidence";
+/// The step's per-image results, with their confidence and flags
+pub const OCR_EXTRACTIONS_KEY: &str = "ocr_extractions";
+
+/// Base URL of the ML inference function
+pub const ML_INFERENCE_URL_ENV: &str = "FDR_ML_INFERENCE_URL";
+/// Name of the OCR model loaded in the ML inference function
+pub const OCR_MODEL_ENV: &str = "FDR_OCR_MODEL";
+
+const DEFAULT_ML_INFERENCE_URL: &str = "http://localhost:8080";
+const DEFAULT_OCR_MODEL: &str = "ocr";
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.6;
+const MAX_IMAGES: usize = 10;
+const IMAGE_TIMEOUT_MS: u32 = 30_000;
+/// Tells the function how long we wait, so it stops inference we have given up on
+const DEADLINE_HEADER: &str = "grpc-timeout";
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp"];
+
+/// How an OCR step reads its images
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrSettings {
+    pub language_hints: Vec<String>,
+    pub min_confidence: f32,
+}
+
+impl OcrSettings {
+    pub fn from_input(input: &HashMap<String, serde_json::Value>) -> Self {
+        let language_hints = input.get(LANGUAGE_HINTS_KEY)
+            .and_then(|v| v.as_array())
+            .map(|hints| hints.iter().filter_map(|h| h.as_str()).map(str::to_string).collect())
+            .unwrap_or_default();
+        let min_confidence = input.get(MIN_CONFIDENCE_KEY)
+            .and_then(|v| v.as_f64())
+            .map(|c| (c as f32).clamp(0.0, 1.0))
+            .unwrap_or(DEFAULT_MIN_CONFIDENCE);
+        Self { language_hints, min_confidence }
+    }
+}
+
+/// Text read from one image source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrExtraction {
+    pub source: String,
+    pub text: String,
+    pub confidence: f32,
+    pub language: Option<String>,
+    /// The whole extraction is below the step's confidence threshold
+    pub low_confidence: bool,
+    /// Recognised blocks below the threshold, even when the whole is above it
+    pub low_confidence_blocks: usize,
+}
+
+impl OcrExtraction {
+    fn new(source: &str, reply: OcrReply, settings: &OcrSettings) -> Self {
+        Self {
+            source: source.to_string(),
+            low_confidence: reply.confidence < settings.min_confidence,
+            low_confidence_blocks: reply.blocks.iter().filter(|b| b.confidence < settings.min_confidence).count(),
+            text: reply.text,
+            confidence: reply.confidence,
+            language: reply.language,
+        }
+    }
+
+    /// The extraction in the shape of a scraped page, so analysis and synthesis use it
+    /// like any other content. Unreliable text says so, for the model to weigh it.
+    pub fn as_scraped_content(&self) -> serde_json::Value {
+        let markdown = if self.low_confidence {
+            format!("[Low-confidence OCR text, confidence {:.2}]\n\n{}", self.confidence, self.text)
+        } else {
+            self.text.clone()
+        };
+        serde_json::json!({
+            "url": self.source,
+            "markdown": markdown,
+            "metadata": {
+                "sourceURL": self.source,
+                "title": format!("Text read from image {}", self.source),
+            },
+            "ocr": {
+                "confidence": self.confidence,
+                "low_confidence": self.low_confidence,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InferenceReply {
+    status: String,
+    results: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+/// The ML inference function's OCR result
+#[derive(Debug, Deserialize)]
+struct OcrReply {
+    text: String,
+    confidence: f32,
+    language: Option<String>,
+    #[serde(default)]
+    blocks: Vec<OcrBlockReply>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcrBlockReply {
+    confidence: f32,
+}
+
+/// Reads images through the OCR model of the ML inference function
+pub struct OcrClient {
+    client: reqwest::Client,
+    endpoint: String,
+    model: String,
+    signer: Option<ServiceSigner>,
+}
+
+impl OcrClient {
+    pub fn from_env() -> AppResult<Self> {
+        let endpoint = std::env::var(ML_INFERENCE_URL_ENV).unwrap_or_else(|_| DEFAULT_ML_INFERENCE_URL.to_string());
+        Ok(Self {
+            client: egress::client_builder(IMAGE_TIMEOUT_MS).build()?,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model: std::env::var(OCR_MODEL_ENV).unwrap_or_else(|_| DEFAULT_OCR_MODEL.to_string()),
+            signer: ServiceSigner::from_env(),
+        })
+    }
+
+    async fn read_image(&self, image_url: &str, settings: &OcrSettings) -> AppResult<OcrReply> {
+        let body = serde_json::json!({
+            "model_name": self.model,
+            "inputs": { "image_url": image_url },
+            "parameters": { "language_hints": settings.language_hints },
+        });
+        let body = serde_json::to_vec(&body)?;
+        // Through the workflow's egress profile, and not at all in offline mode
+        let client = egress::client_for(&self.client, IMAGE_TIMEOUT_MS)?;
+        let mut request = client.post(format!("{}/infer", self.endpoint))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(DEADLINE_HEADER, format!("{}m", IMAGE_TIMEOUT_MS));
+        if let Some(signer) = &self.signer {
+            // Signed with the path the function routes, whatever prefix the endpoint has
+            for (name, value) in signer.headers("POST", "/infer", &body) {
+                request = request.header(name, value);
+            }
+        }
+        let response = inject_trace_context(request.body(body)).send().await?;
+        if !response.status().is_success() {
+            return Err(AppError::external_service("ml-inference", format!("OCR request failed with status {}", response.status())));
+        }
+
+        let reply: InferenceReply = response.json().await?;
+        if reply.status != "Completed" {
+            return Err(AppError::external_service("ml-inference", reply.error.unwrap_or_else(|| format!("OCR request {}", reply.status))));
+        }
+        let results = reply.results
+            .ok_or_else(|| AppError::external_service("ml-inference", "OCR request returned no results"))?;
+        serde_json::from_value(results)
+            .map_err(|e| AppError::external_service("ml-inference", format!("Model {} did not return OCR results: {}", self.model, e)))
+    }
+}
+
+/// Whether `step` is an OCR step
+pub fn is_image_ocr_step(step: &WorkflowStep) -> bool {
+    step.input_data.get(STEP_TYPE_KEY).and_then(|v| v.as_str()) == Some(IMAGE_OCR)
+}
+
+/// Whether `url` names an image file
+pub fn is_image_url(url: &str) -> bool {
+    url::Url::parse(url).ok()
+        .and_then(|parsed| parsed.path().rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()))
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+        .unwrap_or(false)
+}
+
+/// The images an OCR step reads: those it was given, then the image links among the
+/// search results
+pub fn image_sources(context: &ExecutionContext) -> Vec<String> {
+    let given = context.input_data.get(IMAGE_SOURCES_KEY)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str());
+    let searched = context.shared_data.get("search_results")
+        .and_then(|v| v.get("organic_results"))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|result| result.get("link").and_then(|v| v.as_str()))
+        .filter(|link| is_image_url(link));
+
+    let mut sources: Vec<String> = Vec::new();
+    for source in given.chain(searched) {
+        if !sources.iter().any(|s| s == source) {
+            sources.push(source.to_string());
+        }
+    }
+    sources.truncate(MAX_IMAGES);
+    sources
+}
+
+/// Run an `image_ocr` step. The text read from each image is added to
+/// `scraped_content`, and each extraction is reported under `ocr_extractions`.
+pub async fn execute_image_ocr_step(context: &ExecutionContext) -> AppResult<HashMap<String, serde_json::Value>> {
+    let settings = OcrSettings::from_input(&context.input_data);
+    let sources = image_sources(context);
+    debug!("Reading text from {} image sources", sources.len());
+
+    let client = OcrClient::from_env()?;
+    let mut extractions = Vec::new();
+    for source in &sources {
+        match client.read_image(source, &settings).await {
+            Ok(reply) if reply.text.trim().is_empty() => debug!("No text found in {}", source),
+            Ok(reply) => extractions.push(OcrExtraction::new(source, reply, &settings)),
+            Err(e) => warn!("Failed to read text from {}: {}", source, e),
+        }
+    }
+
+    let low_confidence = extractions.iter().filter(|e| e.low_confidence).count();
+    if low_confidence > 0 {
+        warn!("{} of {} OCR extractions are below confidence {}", low_confidence, extractions.len(), settings.min_confidence);
+    }
+
+    let mut scraped_content = context.shared_data.get("scraped_content")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    scraped_content.extend(extractions.iter().map(OcrExtraction::as_scraped_content));
+
+    let mut results = HashMap::new();
+    results.insert("scraped_content".to_string(), serde_json::Value::Array(scraped_content));
+    results.insert(OCR_EXTRACTIONS_KEY.to_string(), serde_json::to_value(&extractions)?);
+    results.insert("low_confidence_extractions".to_string(), serde_json::Value::Number(serde_json::Number::from(low_confidence)));
+    results.insert("methodology_step".to_string(), serde_json::Value::String(IMAGE_OCR.to_string()));
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::research_engine::context_assembler::ContextItem;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_ocr_sources_settings_and_low_confidence_flags() {
+        let mut input_data = HashMap::new();
+        input_data.insert(IMAGE_SOURCES_KEY.to_string(), serde_json::json!(["https://example.org/scan.TIFF"]));
+        input_data.insert(LANGUAGE_HINTS_KEY.to_string(), serde_json::json!(["de", "en"]));
+        input_data.insert(MIN_CONFIDENCE_KEY.to_string(), serde_json::json!(0.8));
+        let mut shared_data = HashMap::new();
+        shared_data.insert("search_results".to_string(), serde_json::json!({
+            "organic_results": [
+                { "link": "https://example.org/article.html" },
+                { "link": "https://example.org/scan.TIFF" },
+                { "link": "https://example.org/figures/table.png?size=large" },
+            ]
+        }));
+        let context = ExecutionContext {
+            workflow_id: Uuid::new_v4(),
+            step_id: Uuid::new_v4(),
+            input_data,
+            shared_data,
+            metadata: HashMap::new(),
+        };
+
+        // Given sources first, then image links from the search, without duplicates
+        assert_eq!(image_sources(&context), vec![
+            "https://example.org/scan.TIFF".to_string(),
+            "https://example.org/figures/table.png?size=large".to_string(),
+        ]);
+
+        let settings = OcrSettings::from_input(&context.input_data);
+        assert_eq!(settings.language_hints, vec!["de".to_string(), "en".to_string()]);
+        assert_eq!(settings.min_confidence, 0.8);
+        assert_eq!(OcrSettings::from_input(&HashMap::new()).min_confidence, DEFAULT_MIN_CONFIDENCE);
+
+        let reply: OcrReply = serde_json::from_value(serde_json::json!({
+            "text": "Tabelle 1: Ergebnisse",
+            "confidence": 0.55,
+            "language": "de",
+            "blocks": [{ "text": "Tabelle 1:", "confidence": 0.9 }, { "text": "Ergebnisse", "confidence": 0.2 }],
+        })).unwrap();
+        let extraction = OcrExtraction::new("https://example.org/scan.TIFF", reply, &settings);
+        assert!(extraction.low_confidence);
+        assert_eq!(extraction.low_confidence_blocks, 1);
+
+        // The text reaches the analysis pipeline as scraped content, flagged as unreliable
+        let items = ContextItem::from_scraped_content(&serde_json::json!([extraction.as_scraped_content()]));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "https://example.org/scan.TIFF");
+        assert!(items[0].text.starts_with("[Low-confidence OCR text"));
+        assert!(items[0].text.ends_with("Tabelle 1: Ergebnisse"));
+    }
+}
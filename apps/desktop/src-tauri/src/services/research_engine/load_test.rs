@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::{ProviderRecording, ResearchMethodology, WorkflowParameters};
+
+/// Most workflows a load test may keep running at once
+pub const MAX_LOAD_TEST_CONCURRENCY: usize = 200;
+/// Most workflows a load test may start
+pub const MAX_LOAD_TEST_WORKFLOWS: usize = 10_000;
+/// Longest a load test may keep starting workflows
+pub const MAX_LOAD_TEST_DURATION_SECS: u64 = 3600;
+
+/// Longest error message kept per distinct error in a report
+const ERROR_MESSAGE_CHARS: usize = 200;
+
+/// How a load test drives the engine. Workflows are started at `arrival_rate_per_sec`
+/// until `max_workflows` have been or `duration_secs` has passed, whichever comes first;
+/// at most `concurrency` run at once and the rest wait their turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadTestConfig {
+    pub concurrency: usize,
+    pub arrival_rate_per_sec: f64,
+    pub max_workflows: usize,
+    pub duration_secs: u64,
+    /// How long workflows still running once starting stops get to finish before they
+    /// are cancelled and counted as timed out
+    pub drain_timeout_secs: u64,
+    pub methodology: ResearchMethodology,
+    pub query: String,
+    /// Latency of every mock provider call
+    pub mock_latency_ms: u32,
+    /// Share of mock provider calls, from 0 to 100, that fail
+    pub mock_failure_percent: u8,
+    /// Keep the workflows the test created instead of deleting them afterwards
+    pub keep_workflows: bool,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 5,
+            arrival_rate_per_sec: 1.0,
+            max_workflows: 50,
+            duration_secs: 60,
+            drain_timeout_secs: 120,
+            methodology: ResearchMethodology::DonLim,
+            query: "Load test: recent advances in battery chemistry".to_string(),
+            mock_latency_ms: 250,
+            mock_failure_percent: 0,
+            keep_workflows: false,
+        }
+    }
+}
+
+impl LoadTestConfig {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.concurrency == 0 || self.concurrency > MAX_LOAD_TEST_CONCURRENCY {
+            return Err(AppError::validation("concurrency", format!("must be between 1 and {}", MAX_LOAD_TEST_CONCURRENCY)));
+        }
+        if !self.arrival_rate_per_sec.is_finite() || self.arrival_rate_per_sec <= 0.0 {
+            return Err(AppError::validation("arrival_rate_per_sec", "must be a positive number"));
+        }
+        if self.max_workflows == 0 || self.max_workflows > MAX_LOAD_TEST_WORKFLOWS {
+            return Err(AppError::validation("max_workflows", format!("must be between 1 and {}", MAX_LOAD_TEST_WORKFLOWS)));
+        }
+        if self.duration_secs == 0 || self.duration_secs > MAX_LOAD_TEST_DURATION_SECS {
+            return Err(AppError::validation("duration_secs", format!("must be between 1 and {}", MAX_LOAD_TEST_DURATION_SECS)));
+        }
+        if self.mock_failure_percent > 100 {
+            return Err(AppError::validation("mock_failure_percent", "must be between 0 and 100"));
+        }
+        if self.query.trim().is_empty() {
+            return Err(AppError::validation("query", "cannot be empty"));
+        }
+        Ok(())
+    }
+
+    /// Parameters of each workflow the test starts: every provider call goes to the mock
+    /// providers, and nothing is read from or written to the shared content cache
+    pub fn workflow_parameters(&self) -> WorkflowParameters {
+        WorkflowParameters {
+            methodology: self.methodology.clone(),
+            provider_recording: ProviderRecording::Mock {
+                latency_ms: self.mock_latency_ms,
+                failure_percent: self.mock_failure_percent,
+            },
+            enable_caching: false,
+            share_in_flight_runs: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// How one load test workflow ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeKind {
+    Completed,
+    Failed,
+    /// Still running at the end of the drain timeout, and cancelled
+    TimedOut,
+    /// Could not be created or started
+    NotStarted,
+}
+
+/// What happened to one workflow, timed from when the test submitted it
+#[derive(Debug, Clone)]
+pub struct WorkflowOutcome {
+    pub workflow_id: Option<Uuid>,
+    pub kind: OutcomeKind,
+    pub error: Option<String>,
+    /// Waiting for one of the `concurrency` slots
+    pub queue_wait_ms: f64,
+    /// From start to completion, for workflows that finished
+    pub run_ms: Option<f64>,
+}
+
+/// The `concurrency` slots workflows run in, and how deep the wait for them got
+#[derive(Debug)]
+pub struct Slots {
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+    waiting: AtomicUsize,
+    max_depth: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl Slots {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(capacity)),
+            capacity,
+            waiting: AtomicUsize::new(0),
+            max_depth: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a free slot, held until the permit is dropped
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        let depth = self.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_depth.fetch_max(depth, Ordering::SeqCst);
+        let permit = self.semaphore.clone().acquire_owned().await.expect("load test slots are never closed");
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+        let in_flight = self.capacity - self.semaphore.available_permits();
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        permit
+    }
+
+    /// The most workflows seen waiting and running at once
+    pub fn depths(&self) -> (usize, usize) {
+        (self.max_depth.load(Ordering::SeqCst), self.max_in_flight.load(Ordering::SeqCst))
+    }
+}
+
+/// Distribution of a set of durations, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(f64::total_cmp);
+        Self {
+            count: samples.len(),
+            min_ms: samples[0],
+            mean_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+            p50_ms: percentile(&samples, 50.0),
+            p90_ms: percentile(&samples, 90.0),
+            p95_ms: percentile(&samples, 95.0),
+            p99_ms: percentile(&samples, 99.0),
+            max_ms: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `samples`
+fn percentile(samples: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+    samples[rank.clamp(1, samples.len()) - 1]
+}
+
+/// How workflows waited for a slot
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueSummary {
+    /// Most workflows waiting for a slot at once
+    pub max_depth: usize,
+    /// Most workflows running at once
+    pub max_in_flight: usize,
+    pub wait: LatencySummary,
+}
+
+/// Capacity numbers from a load test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestReport {
+    pub load_test_id: Uuid,
+    pub config: LoadTestConfig,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub elapsed_secs: f64,
+    pub submitted: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    pub not_started: usize,
+    /// Workflows that did not complete, as a share of those submitted
+    pub error_rate: f64,
+    /// Completed workflows per second over the whole test
+    pub throughput_per_sec: f64,
+    /// Start to completion of the workflows that finished, completed or failed
+    pub run_time: LatencySummary,
+    pub queue: QueueSummary,
+    /// How often each error was seen
+    pub errors: BTreeMap<String, usize>,
+    /// Workflows deleted afterwards
+    pub cleaned_up: usize,
+}
+
+impl LoadTestReport {
+    pub fn new(
+        load_test_id: Uuid,
+        config: LoadTestConfig,
+        started_at: DateTime<Utc>,
+        elapsed_secs: f64,
+        outcomes: &[WorkflowOutcome],
+        queue_depths: (usize, usize),
+    ) -> Self {
+        let count = |kind: OutcomeKind| outcomes.iter().filter(|outcome| outcome.kind == kind).count();
+        let submitted = outcomes.len();
+        let completed = count(OutcomeKind::Completed);
+
+        let mut errors = BTreeMap::new();
+        for error in outcomes.iter().filter_map(|outcome| outcome.error.as_deref()) {
+            *errors.entry(error.chars().take(ERROR_MESSAGE_CHARS).collect()).or_insert(0) += 1;
+        }
+
+        let (max_depth, max_in_flight) = queue_depths;
+        Self {
+            load_test_id,
+            config,
+            started_at,
+            finished_at: Utc::now(),
+            elapsed_secs,
+            submitted,
+            completed,
+            failed: count(OutcomeKind::Failed),
+            timed_out: count(OutcomeKind::TimedOut),
+            not_started: count(OutcomeKind::NotStarted),
+            error_rate: if submitted > 0 { (submitted - completed) as f64 / submitted as f64 } else { 0.0 },
+            throughput_per_sec: if elapsed_secs > 0.0 { completed as f64 / elapsed_secs } else { 0.0 },
+            run_time: LatencySummary::from_samples(outcomes.iter().filter_map(|outcome| outcome.run_ms).collect()),
+            queue: QueueSummary {
+                max_depth,
+                max_in_flight,
+                wait: LatencySummary::from_samples(outcomes.iter().map(|outcome| outcome.queue_wait_ms).collect()),
+            },
+            errors,
+            cleaned_up: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(kind: OutcomeKind, queue_wait_ms: f64, run_ms: Option<f64>, error: Option<&str>) -> WorkflowOutcome {
+        WorkflowOutcome { workflow_id: Some(Uuid::new_v4()), kind, error: error.map(str::to_string), queue_wait_ms, run_ms }
+    }
+
+    #[test]
+    fn test_report_summarizes_outcomes_into_rates_and_percentiles() {
+        let latency = LatencySummary::from_samples((1..=100).rev().map(f64::from).collect());
+        assert_eq!((latency.min_ms, latency.p50_ms, latency.p95_ms, latency.p99_ms, latency.max_ms), (1.0, 50.0, 95.0, 99.0, 100.0));
+        assert_eq!(latency.mean_ms, 50.5);
+        assert_eq!(LatencySummary::from_samples(vec![7.0]).p99_ms, 7.0);
+        assert_eq!(LatencySummary::from_samples(Vec::new()), LatencySummary::default());
+
+        let outcomes = [
+            outcome(OutcomeKind::Completed, 0.0, Some(1000.0), None),
+            outcome(OutcomeKind::Completed, 400.0, Some(3000.0), None),
+            outcome(OutcomeKind::Failed, 800.0, Some(500.0), Some("Simulated mock provider failure")),
+            outcome(OutcomeKind::TimedOut, 1200.0, None, Some("Still running after the drain timeout")),
+        ];
+        let report = LoadTestReport::new(Uuid::new_v4(), LoadTestConfig::default(), Utc::now(), 4.0, &outcomes, (2, 5));
+        assert_eq!((report.submitted, report.completed, report.failed, report.timed_out), (4, 2, 1, 1));
+        assert_eq!(report.error_rate, 0.5);
+        assert_eq!(report.throughput_per_sec, 0.5);
+        assert_eq!(report.run_time.count, 3);
+        assert_eq!(report.queue.wait.max_ms, 1200.0);
+        assert_eq!(report.errors.len(), 2);
+
+        let mut config = LoadTestConfig::default();
+        assert!(config.validate().is_ok());
+        assert!(matches!(config.workflow_parameters().provider_recording, ProviderRecording::Mock { latency_ms: 250, .. }));
+        config.concurrency = 0;
+        assert!(config.validate().is_err());
+        config = LoadTestConfig { arrival_rate_per_sec: f64::NAN, ..Default::default() };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slots_track_queue_depth_and_in_flight() {
+        let slots = Slots::new(2);
+        let first = slots.acquire().await;
+        let _second = slots.acquire().await;
+        assert_eq!(slots.depths(), (1, 2));
+
+        let (third, fourth) = (slots.acquire(), slots.acquire());
+        tokio::pin!(third, fourth);
+        assert!(futures::poll!(third.as_mut()).is_pending());
+        assert!(futures::poll!(fourth.as_mut()).is_pending());
+        assert_eq!(slots.depths(), (2, 2));
+
+        drop(first);
+        let _third = third.await;
+        assert_eq!(slots.depths(), (2, 2));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use tracing::info;
+use uuid::Uuid;
+use serde_json;
+
+use crate::error::AppResult;
+use crate::models::research_workflow::{
+    ResearchWorkflow, WorkflowStep, ResearchMethodology, ResearchResults
+};
+use crate::services::api_manager::ApiManagerService;
+use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext};
+use crate::services::research_engine::methodology_hybrid::HybridMethodology;
+use crate::services::research_engine::image_ocr::{self, OcrExtraction};
+use crate::services::research_engine::pdf_extraction::{self, KnowledgeGraphSlot};
+
+/// Document analysis methodology implementation
+/// The hybrid steps, plus an OCR step that reads scanned documents and images among
+/// the sources and a PDF step that parses papers into the knowledge graph, so their
+/// text is analysed and synthesized with the scraped pages
+pub struct DocumentAnalysisMethodology {
+    hybrid: HybridMethodology,
+    knowledge_graph: KnowledgeGraphSlot,
+}
+
+impl DocumentAnalysisMethodology {
+    pub fn new(knowledge_graph: KnowledgeGraphSlot) -> Self {
+        Self { hybrid: HybridMethodology::new(), knowledge_graph }
+    }
+
+    /// Create the OCR step, reading images after the pages are scraped
+    fn create_ocr_step(workflow: &ResearchWorkflow, depends_on: Vec<Uuid>) -> WorkflowStep {
+        let mut step = WorkflowStep::new(
+            workflow.id,
+            3,
+            "Image OCR".to_string(),
+            "Extract text from scanned documents and images using the ML inference function".to_string(),
+        );
+
+        step.service_provider = Some("ml_inference".to_string());
+        step.endpoint = Some("/infer".to_string());
+        step.depends_on = depends_on;
+        step.input_data.insert(image_ocr::STEP_TYPE_KEY.to_string(), serde_json::Value::String(image_ocr::IMAGE_OCR.to_string()));
+        // Language hints, the confidence threshold and extra images come from the workflow
+        for key in [image_ocr::IMAGE_SOURCES_KEY, image_ocr::LANGUAGE_HINTS_KEY, image_ocr::MIN_CONFIDENCE_KEY] {
+            if let Some(value) = workflow.parameters.custom_parameters.get(key) {
+                step.input_data.insert(key.to_string(), value.clone());
+            }
+        }
+
+        step
+    }
+
+    /// Create the PDF step, parsing documents after the images are read
+    fn create_pdf_step(workflow: &ResearchWorkflow, depends_on: Vec<Uuid>) -> WorkflowStep {
+        let mut step = WorkflowStep::new(
+            workflow.id,
+            4,
+            "PDF Extraction".to_string(),
+            "Parse PDF sources and link the works they cite into the knowledge graph".to_string(),
+        );
+
+        step.service_provider = Some("knowledge_graph".to_string());
+        step.depends_on = depends_on;
+        step.input_data.insert(image_ocr::STEP_TYPE_KEY.to_string(), serde_json::Value::String(pdf_extraction::PDF_EXTRACTION.to_string()));
+        if let Some(value) = workflow.parameters.custom_parameters.get(pdf_extraction::DOCUMENT_SOURCES_KEY) {
+            step.input_data.insert(pdf_extraction::DOCUMENT_SOURCES_KEY.to_string(), value.clone());
+        }
+
+        step
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkflowExecutor for DocumentAnalysisMethodology {
+    fn methodology(&self) -> ResearchMethodology {
+        ResearchMethodology::DocumentAnalysis
+    }
+
+    async fn prepare_steps(&self, workflow: &mut ResearchWorkflow) -> AppResult<()> {
+        info!("Preparing Document Analysis methodology steps for workflow: {}", workflow.id);
+
+        self.hybrid.prepare_steps(workflow).await?;
+
+        let scraping = workflow.steps.iter().position(|step| step.name == "Content Scraping")
+            .ok_or_else(|| crate::error::ApiError::invalid_operation(
+                "Hybrid steps have no scraping step to read images after".to_string()
+            ))?;
+        let scraping_id = workflow.steps[scraping].id;
+        let ocr_step = Self::create_ocr_step(workflow, workflow.steps[scraping].depends_on.iter().copied().chain([scraping_id]).collect());
+        let ocr_id = ocr_step.id;
+        // After the OCR step rather than beside it, since both add to the scraped content
+        let pdf_step = Self::create_pdf_step(workflow, ocr_step.depends_on.iter().copied().chain([ocr_id]).collect());
+        let pdf_id = pdf_step.id;
+
+        // Whatever used the scraped pages now waits for the text read from images and PDFs too
+        for step in workflow.steps.iter_mut() {
+            if step.depends_on.contains(&scraping_id) {
+                for id in [ocr_id, pdf_id] {
+                    if !step.depends_on.contains(&id) {
+                        step.depends_on.push(id);
+                    }
+                }
+            }
+        }
+        workflow.steps.insert(scraping + 1, ocr_step);
+        workflow.steps.insert(scraping + 2, pdf_step);
+        for (index, step) in workflow.steps.iter_mut().enumerate() {
+            step.step_number = index as u32 + 1;
+        }
+
+        info!("Prepared {} steps for Document Analysis methodology", workflow.steps.len());
+        Ok(())
+    }
+
+    async fn execute_step(
+        &self,
+        step: &mut WorkflowStep,
+        context: &ExecutionContext,
+        api_manager: &ApiManagerService,
+    ) -> AppResult<HashMap<String, serde_json::Value>> {
+        if image_ocr::is_image_ocr_step(step) {
+            return image_ocr::execute_image_ocr_step(context).await;
+        }
+        if pdf_extraction::is_pdf_extraction_step(step) {
+            return pdf_extraction::execute_pdf_extraction_step(context, &self.knowledge_graph).await;
+        }
+        self.hybrid.execute_step(step, context, api_manager).await
+    }
+
+    async fn post_process_results(
+        &self,
+        workflow: &ResearchWorkflow,
+        step_results: &[HashMap<String, serde_json::Value>],
+    ) -> AppResult<ResearchResults> {
+        info!("Post-processing Document Analysis methodology results");
+
+        let mut results = self.hybrid.post_process_results(workflow, step_results).await?;
+        results.methodology_used = ResearchMethodology::DocumentAnalysis;
+        results.metadata.insert("methodology".to_string(), serde_json::Value::String("document_analysis".to_string()));
+
+        let extractions: Vec<OcrExtraction> = step_results.iter()
+            .find_map(|result| result.get(image_ocr::OCR_EXTRACTIONS_KEY))
+            .map(|value| serde_json::from_value(value.clone()))
+            .transpose()?
+            .unwrap_or_default();
+
+        for extraction in &extractions {
+            if !results.sources.contains(&extraction.source) {
+                results.sources.push(extraction.source.clone());
+            }
+        }
+        let documents: Vec<serde_json::Value> = step_results.iter()
+            .find_map(|result| result.get(pdf_extraction::EXTRACTED_DOCUMENTS_KEY))
+            .and_then(|value| value.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for source in documents.iter().filter_map(|document| document.get("source").and_then(|v| v.as_str())) {
+            if !results.sources.iter().any(|s| s == source) {
+                results.sources.push(source.to_string());
+            }
+        }
+        results.source_count = results.sources.len() as u32;
+
+        // Readers should know which sources were read from images, and which unreliably
+        let low_confidence: Vec<serde_json::Value> = extractions.iter()
+            .filter(|e| e.low_confidence)
+            .map(|e| serde_json::Value::String(e.source.clone()))
+            .collect();
+        results.metadata.insert(image_ocr::OCR_EXTRACTIONS_KEY.to_string(), serde_json::Value::Number(serde_json::Number::from(extractions.len())));
+        results.metadata.insert("low_confidence_ocr_sources".to_string(), serde_json::Value::Array(low_confidence));
+        results.metadata.insert(pdf_extraction::EXTRACTED_DOCUMENTS_KEY.to_string(), serde_json::Value::Array(documents));
+        if let Some(serde_json::Value::Array(services)) = results.metadata.get_mut("services_used") {
+            services.push(serde_json::Value::String("ml_inference".to_string()));
+        }
+
+        info!("Document Analysis methodology results processed successfully");
+        Ok(results)
+    }
+}
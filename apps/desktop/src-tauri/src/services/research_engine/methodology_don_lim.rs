@@ -7,8 +7,11 @@ use crate::error::AppResult;
 use crate::models::research_workflow::{
     ResearchWorkflow, WorkflowStep, ResearchMethodology, ResearchResults
 };
-use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService};
-use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext};
+use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService, NormalizedPayload, ContextBudget, model_router};
+use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext, PROVIDER_ATTEMPTS_KEY, SEARCH_RECENCY_KEY, SERVED_BY_KEY, SERVED_BY_MODEL_KEY};
+use crate::services::research_engine::prompt_library;
+use crate::services::research_engine::structured_output;
+use crate::services::research_engine::context_assembler::{assemble_context, ContextItem, ContextUsage, CONTEXT_USAGE_KEY};
 
 /// Don Lim methodology implementation
 /// Uses OpenRouter.ai + SerpApi + Jina AI for cost-optimized comprehensive research
@@ -66,65 +69,54 @@ impl DonLimMethodology {
         step.service_provider = Some("openrouter".to_string());
         step.endpoint = Some("/chat/completions".to_string());
         step.depends_on = depends_on;
-        step.input_data.insert("model".to_string(), serde_json::Value::String("anthropic/claude-3-sonnet".to_string()));
+        step.input_data.insert(model_router::MODEL_ROLE_KEY.to_string(), serde_json::Value::String(model_router::ANALYSIS.to_string()));
+        step.input_data.insert(prompt_library::PROMPT_ID_KEY.to_string(), serde_json::Value::String(prompt_library::DON_LIM_ANALYSIS.to_string()));
         step.input_data.insert("temperature".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(0.3).unwrap()));
         step.input_data.insert("max_tokens".to_string(), serde_json::Value::Number(serde_json::Number::from(4000)));
         
         step
     }
 
-    /// Execute SerpApi search step
+    /// Execute web search step
     async fn execute_search_step(
         &self,
         step: &mut WorkflowStep,
         context: &ExecutionContext,
         api_manager: &ApiManagerService,
     ) -> AppResult<HashMap<String, serde_json::Value>> {
-        debug!("Executing SerpApi search step");
+        debug!("Executing web search step");
 
         let query = context.input_data.get("query")
             .and_then(|v| v.as_str())
             .unwrap_or("research query");
 
-        // Create service request
-        let mut request = ServiceRequest {
-            request_id: Uuid::new_v4(),
-            service: crate::models::api_key::ServiceProvider::SerpApi,
-            endpoint: "/search".to_string(),
-            method: "GET".to_string(),
-            headers: HashMap::new(),
-            body: None,
-            timeout_ms: 15000,
-            retry_count: 0,
-            metadata: HashMap::new(),
-        };
-
-        // Add query parameters to metadata
-        let query_params = format!("q={}&engine=google&num=20", urlencoding::encode(query));
-        request.metadata.insert("query_params".to_string(), query_params);
-
-        // Make request
-        let response = api_manager.make_service_request(
-            crate::models::api_key::ServiceProvider::SerpApi,
-            request,
-        ).await?;
-
-        if !response.success {
-            return Err(crate::error::ApiError::external_service_error(
-                "SerpApi".to_string(),
-                response.error_message.unwrap_or_default(),
-            ));
-        }
+        // SerpApi first, then the rest of the web search chain, paging and topping up until
+        // the step's `num_results` distinct results are in or no provider has more
+        let num_results = context.input_data.get("num_results")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(20) as u32;
+        let recency = context.input_data.get(SEARCH_RECENCY_KEY)
+            .map(|recency| serde_json::from_value(recency.clone()))
+            .transpose()?
+            .unwrap_or_default();
+        let outcome = api_manager.search_web(crate::models::api_key::ServiceProvider::SerpApi, query, num_results, recency).await?;
+        let served_by = outcome.served_by.first().copied().unwrap_or(crate::models::api_key::ServiceProvider::SerpApi);
 
-        // Parse search results
-        let search_results: serde_json::Value = serde_json::from_str(&response.body)?;
-        
         let mut results = HashMap::new();
-        results.insert("search_results".to_string(), search_results);
-        results.insert("result_count".to_string(), serde_json::Value::Number(serde_json::Number::from(20)));
+        results.insert("search_results".to_string(), NormalizedPayload::search_results_json(&outcome.hits));
+        results.insert("result_count".to_string(), serde_json::Value::from(outcome.fetched));
+        results.insert("requested_results".to_string(), serde_json::Value::from(outcome.requested));
         results.insert("search_query".to_string(), serde_json::Value::String(query.to_string()));
+        if outcome.outside_time_range > 0 {
+            results.insert("outside_time_range".to_string(), serde_json::Value::from(outcome.outside_time_range));
+        }
+        if let Some(reason) = &outcome.shortfall_reason {
+            results.insert("search_shortfall".to_string(), serde_json::Value::String(reason.clone()));
+        }
+        results.insert(SERVED_BY_KEY.to_string(), serde_json::to_value(served_by)?);
+        results.insert(PROVIDER_ATTEMPTS_KEY.to_string(), serde_json::to_value(&outcome.attempts)?);
 
-        debug!("SerpApi search completed successfully");
+        debug!("Web search completed with {} of {} results, served by {:?}", outcome.fetched, outcome.requested, outcome.served_by);
         Ok(results)
     }
 
@@ -220,65 +212,40 @@ impl DonLimMethodology {
                 "No embeddings available for analysis".to_string()
             ))?;
 
-        // Create analysis prompt
-        let analysis_prompt = self.create_analysis_prompt(search_results, embeddings)?;
-
-        // Create chat completion request
-        let request_body = serde_json::json!({
-            "model": "anthropic/claude-3-sonnet",
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are a research analyst. Analyze the provided search results and embeddings to create a comprehensive research report. Focus on accuracy, relevance, and actionable insights."
-                },
-                {
-                    "role": "user",
-                    "content": analysis_prompt
-                }
-            ],
-            "temperature": 0.3,
-            "max_tokens": 4000
-        });
+        // Create analysis prompt, fitted to the analysis models' context window
+        let model_role = model_router::step_model_role(&step.input_data, model_router::ANALYSIS);
+        let budget = api_manager.model_context_budget(&model_role, 4000).await;
+        let (analysis_prompt, context_usage) = self.create_analysis_prompt(search_results, embeddings, &budget);
 
-        let mut request = ServiceRequest {
-            request_id: Uuid::new_v4(),
-            service: crate::models::api_key::ServiceProvider::OpenRouter,
-            endpoint: "/chat/completions".to_string(),
-            method: "POST".to_string(),
-            headers: HashMap::new(),
-            body: Some(request_body.to_string()),
-            timeout_ms: 30000,
-            retry_count: 0,
-            metadata: HashMap::new(),
-        };
+        let system_prompt = structured_output::with_confidence_guidance(
+            prompt_library::system_prompt(context, prompt_library::DON_LIM_ANALYSIS),
+            context,
+        );
 
-        // Make request
-        let response = api_manager.make_service_request(
-            crate::models::api_key::ServiceProvider::OpenRouter,
-            request,
+        // Request the report and its insights as structured output from the analysis role's models
+        let output = structured_output::run_analysis(
+            api_manager,
+            &model_role,
+            &system_prompt,
+            &analysis_prompt,
+            0.3,
+            4000,
+            30000,
         ).await?;
 
-        if !response.success {
-            return Err(crate::error::ApiError::external_service_error(
-                "OpenRouter".to_string(),
-                response.error_message.unwrap_or_default(),
-            ));
-        }
-
         // Parse AI response
-        let ai_response: serde_json::Value = serde_json::from_str(&response.body)?;
-        
-        let analysis_content = ai_response["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("Analysis failed")
-            .to_string();
+        let ai_response: serde_json::Value = serde_json::from_str(&output.response.body)?;
 
         let mut results = HashMap::new();
-        results.insert("analysis".to_string(), serde_json::Value::String(analysis_content));
+        results.insert("analysis".to_string(), serde_json::Value::String(output.analysis.report));
+        results.insert(structured_output::INSIGHTS_KEY.to_string(), serde_json::to_value(&output.analysis.insights)?);
+        results.insert(structured_output::INSIGHTS_SOURCE_KEY.to_string(), serde_json::to_value(output.source)?);
         results.insert("ai_response".to_string(), ai_response);
         results.insert("methodology".to_string(), serde_json::Value::String("don_lim".to_string()));
+        results.insert(SERVED_BY_MODEL_KEY.to_string(), serde_json::Value::String(output.served_by_model.clone()));
+        results.insert(CONTEXT_USAGE_KEY.to_string(), serde_json::to_value(&context_usage)?);
 
-        debug!("OpenRouter AI analysis completed successfully");
+        debug!("OpenRouter AI analysis completed successfully, served by {}", output.served_by_model);
         Ok(results)
     }
 
@@ -312,16 +279,11 @@ impl DonLimMethodology {
         &self,
         search_results: &serde_json::Value,
         embeddings: &serde_json::Value,
-    ) -> AppResult<String> {
-        let prompt = format!(
-            r#"Please analyze the following research data and provide a comprehensive report:
-
-SEARCH RESULTS:
-{}
-
-CONTENT EMBEDDINGS:
-{}
+        budget: &ContextBudget,
+    ) -> (String, ContextUsage) {
+        let instructions = r#"Please analyze the following research data and provide a comprehensive report:
 
+{context}
 Please provide:
 1. Executive Summary
 2. Key Findings
@@ -329,12 +291,14 @@ Please provide:
 4. Sources and References
 5. Conclusions and Recommendations
 
-Format the response in clear, well-structured markdown."#,
-            serde_json::to_string_pretty(search_results)?,
-            serde_json::to_string_pretty(embeddings)?
-        );
+Format the response in clear, well-structured markdown."#;
 
-        Ok(prompt)
+        // Embeddings are the least readable material, so they give way first
+        let mut items = ContextItem::from_search_results(search_results);
+        items.push(ContextItem::from_json("CONTENT EMBEDDINGS", embeddings, 0.0));
+
+        let context = assemble_context(items, instructions, budget);
+        (instructions.replace("{context}", &context.text), context.usage)
     }
 }
 
@@ -410,8 +374,18 @@ impl WorkflowExecutor for DonLimMethodology {
         // Create metadata
         let mut metadata = HashMap::new();
         metadata.insert("methodology".to_string(), serde_json::Value::String("don_lim".to_string()));
+        // Keep the structured insights so comparisons and exports need not re-parse the report
+        for key in [structured_output::INSIGHTS_KEY, structured_output::INSIGHTS_SOURCE_KEY] {
+            if let Some(value) = analysis_result.get(key) {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
+        let search_provider = step_results.iter()
+            .find_map(|result| result.get(SERVED_BY_KEY))
+            .cloned()
+            .unwrap_or_else(|| serde_json::Value::String("serpapi".to_string()));
         metadata.insert("services_used".to_string(), serde_json::Value::Array(vec![
-            serde_json::Value::String("serpapi".to_string()),
+            search_provider,
             serde_json::Value::String("jina".to_string()),
             serde_json::Value::String("openrouter".to_string()),
         ]));
@@ -427,6 +401,8 @@ impl WorkflowExecutor for DonLimMethodology {
             source_count: sources.len() as u32,
             methodology_used: ResearchMethodology::DonLim,
             execution_time_ms: workflow.execution_duration_ms().unwrap_or(0),
+            partial: false,
+            failed_steps: Vec::new(),
         };
 
         info!("Don Lim methodology results processed successfully");
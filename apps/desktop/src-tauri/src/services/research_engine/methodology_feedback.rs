@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::models::research_workflow::ResearchMethodology;
+use crate::models::workflow_rating::{
+    MethodologyRatingSummary, MethodologyRecommendation, QueryDomain, WorkflowRating, MAX_RATING, MIN_RATING,
+};
+
+/// Ratings a methodology needs within a domain before it can displace the default
+pub const MIN_RATINGS_TO_RECOMMEND: u32 = 2;
+
+/// How many imagined midpoint ratings each methodology's mean is blended with, so
+/// one enthusiastic rating does not outweigh a long, steady record
+const PRIOR_WEIGHT: f64 = 3.0;
+
+fn prior_mean() -> f64 {
+    (MIN_RATING as f64 + MAX_RATING as f64) / 2.0
+}
+
+/// Summarize `ratings` per methodology, best first
+pub fn summarize(ratings: &[WorkflowRating]) -> Vec<MethodologyRatingSummary> {
+    let mut totals: HashMap<ResearchMethodology, (u32, u32)> = HashMap::new();
+    for rating in ratings {
+        let (count, sum) = totals.entry(rating.methodology.clone()).or_default();
+        *count += 1;
+        *sum += rating.rating as u32;
+    }
+
+    let mut summaries: Vec<MethodologyRatingSummary> = totals.into_iter()
+        .map(|(methodology, (count, sum))| MethodologyRatingSummary {
+            methodology,
+            rating_count: count,
+            mean_rating: sum as f64 / count as f64,
+            adjusted_score: (sum as f64 + PRIOR_WEIGHT * prior_mean()) / (count as f64 + PRIOR_WEIGHT),
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.adjusted_score.total_cmp(&a.adjusted_score)
+        .then(b.rating_count.cmp(&a.rating_count)));
+    summaries
+}
+
+/// The methodology to suggest for `user`'s query in `domain`, given their ratings of
+/// earlier workflows in that domain; other users' ratings are ignored. The default
+/// holds until another methodology with enough ratings scores above it.
+pub fn recommend(
+    user: &str,
+    domain: QueryDomain,
+    default_methodology: ResearchMethodology,
+    ratings: &[WorkflowRating],
+) -> MethodologyRecommendation {
+    let ratings: Vec<WorkflowRating> = ratings.iter()
+        .filter(|r| r.rated_by == user && r.query_domain == domain)
+        .cloned()
+        .collect();
+    let summaries = summarize(&ratings);
+
+    let default_summary = summaries.iter().find(|s| s.methodology == default_methodology);
+    let default_score = default_summary.map(|s| s.adjusted_score).unwrap_or_else(prior_mean);
+    let best = summaries.iter()
+        .find(|s| s.rating_count >= MIN_RATINGS_TO_RECOMMEND)
+        .filter(|s| s.methodology != default_methodology && s.adjusted_score > default_score);
+
+    let rationale = match (best, default_summary) {
+        (Some(best), Some(default)) => format!(
+            "{} is rated {:.1}/5 over {} of your {} workflows, above the default {} at {:.1}/5 over {}",
+            best.methodology.display_name(), best.mean_rating, best.rating_count, domain.as_str(),
+            default_methodology.display_name(), default.mean_rating, default.rating_count,
+        ),
+        (Some(best), None) => format!(
+            "{} is rated {:.1}/5 over {} of your {} workflows; the default {} has no ratings there yet",
+            best.methodology.display_name(), best.mean_rating, best.rating_count, domain.as_str(),
+            default_methodology.display_name(),
+        ),
+        (None, Some(default)) if default.rating_count >= MIN_RATINGS_TO_RECOMMEND => format!(
+            "The default {} is rated {:.1}/5 over {} of your {} workflows, and no other methodology scores higher",
+            default_methodology.display_name(), default.mean_rating, default.rating_count, domain.as_str(),
+        ),
+        (None, _) => format!(
+            "No other methodology has at least {} ratings scoring above the default for {} queries; using the default {}",
+            MIN_RATINGS_TO_RECOMMEND, domain.as_str(), default_methodology.display_name(),
+        ),
+    };
+
+    MethodologyRecommendation {
+        methodology: best.map(|s| s.methodology.clone()).unwrap_or_else(|| default_methodology.clone()),
+        query_domain: domain,
+        default_methodology,
+        from_ratings: best.is_some(),
+        rationale,
+        ratings: summaries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn rating(methodology: ResearchMethodology, domain: QueryDomain, rating: u8) -> WorkflowRating {
+        rating_by("analyst", methodology, domain, rating)
+    }
+
+    fn rating_by(user: &str, methodology: ResearchMethodology, domain: QueryDomain, rating: u8) -> WorkflowRating {
+        WorkflowRating {
+            workflow_id: Uuid::new_v4(),
+            rated_by: user.to_string(),
+            methodology,
+            query_domain: domain,
+            rating,
+            notes: None,
+            rated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_ratings_in_the_domain_displace_the_default_once_there_are_enough() {
+        assert_eq!(QueryDomain::classify("Drug pricing and patient outcomes in clinical trials"), QueryDomain::Medical);
+        assert_eq!(QueryDomain::classify("History of the printing press"), QueryDomain::General);
+
+        // A single glowing rating is not enough
+        let mut ratings = vec![rating(ResearchMethodology::NickScamara, QueryDomain::Finance, 5)];
+        let recommendation = recommend("analyst", QueryDomain::Finance, ResearchMethodology::Hybrid, &ratings);
+        assert_eq!(recommendation.methodology, ResearchMethodology::Hybrid);
+        assert!(!recommendation.from_ratings);
+
+        // Nor are other users' ratings
+        let mut others = ratings.clone();
+        others.push(rating_by("someone_else", ResearchMethodology::NickScamara, QueryDomain::Finance, 5));
+        others.push(rating_by("someone_else", ResearchMethodology::NickScamara, QueryDomain::Finance, 5));
+        assert!(!recommend("analyst", QueryDomain::Finance, ResearchMethodology::Hybrid, &others).from_ratings);
+
+        ratings.push(rating(ResearchMethodology::NickScamara, QueryDomain::Finance, 4));
+        ratings.push(rating(ResearchMethodology::Hybrid, QueryDomain::Finance, 2));
+        // Ratings from other domains do not count
+        ratings.push(rating(ResearchMethodology::Hybrid, QueryDomain::Legal, 5));
+        ratings.push(rating(ResearchMethodology::Hybrid, QueryDomain::Legal, 5));
+
+        let recommendation = recommend("analyst", QueryDomain::Finance, ResearchMethodology::Hybrid, &ratings);
+        assert_eq!(recommendation.methodology, ResearchMethodology::NickScamara);
+        assert!(recommendation.from_ratings);
+        assert!(recommendation.rationale.contains("4.5/5 over 2 of your finance workflows"));
+        assert_eq!(recommendation.ratings.len(), 2);
+        assert_eq!(recommendation.ratings[0].methodology, ResearchMethodology::NickScamara);
+
+        // A poorly rated alternative leaves the default in place
+        let ratings = vec![
+            rating(ResearchMethodology::DonLim, QueryDomain::Legal, 2),
+            rating(ResearchMethodology::DonLim, QueryDomain::Legal, 1),
+        ];
+        assert_eq!(recommend("analyst", QueryDomain::Legal, ResearchMethodology::Hybrid, &ratings).methodology, ResearchMethodology::Hybrid);
+    }
+}
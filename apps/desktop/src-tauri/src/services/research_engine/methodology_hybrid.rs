@@ -7,8 +7,11 @@ use crate::error::AppResult;
 use crate::models::research_workflow::{
     ResearchWorkflow, WorkflowStep, ResearchMethodology, ResearchResults
 };
-use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService};
-use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext};
+use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService, NormalizedPayload, ContextBudget, model_router};
+use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext, BYPASS_CONTENT_CACHE_KEY, PROVIDER_ATTEMPTS_KEY, SEARCH_RECENCY_KEY, SERVED_BY_KEY, SERVED_BY_MODEL_KEY};
+use crate::services::research_engine::prompt_library;
+use crate::services::research_engine::structured_output;
+use crate::services::research_engine::context_assembler::{assemble_context, ContextItem, ContextUsage, CONTEXT_USAGE_KEY};
 
 /// Hybrid methodology implementation
 /// Combines Don Lim (OpenRouter + SerpApi + Jina AI) and Nick Scamara (Firecrawl + AI SDK) approaches
@@ -105,63 +108,55 @@ impl HybridMethodology {
         step.service_provider = Some("openrouter".to_string());
         step.endpoint = Some("/chat/completions".to_string());
         step.depends_on = depends_on;
-        step.input_data.insert("model".to_string(), serde_json::Value::String("anthropic/claude-3-sonnet".to_string()));
+        step.input_data.insert(model_router::MODEL_ROLE_KEY.to_string(), serde_json::Value::String(model_router::SYNTHESIS.to_string()));
+        step.input_data.insert(prompt_library::PROMPT_ID_KEY.to_string(), serde_json::Value::String(prompt_library::HYBRID_SYNTHESIS.to_string()));
         step.input_data.insert("temperature".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(0.25).unwrap()));
         step.input_data.insert("max_tokens".to_string(), serde_json::Value::Number(serde_json::Number::from(8000)));
         
         step
     }
 
-    /// Execute SerpApi search step
+    /// Execute web search step
     async fn execute_search_step(
         &self,
         step: &mut WorkflowStep,
         context: &ExecutionContext,
         api_manager: &ApiManagerService,
     ) -> AppResult<HashMap<String, serde_json::Value>> {
-        debug!("Executing hybrid SerpApi search step");
+        debug!("Executing hybrid web search step");
 
         let query = context.input_data.get("query")
             .and_then(|v| v.as_str())
             .unwrap_or("research query");
 
-        // Create service request
-        let mut request = ServiceRequest {
-            request_id: Uuid::new_v4(),
-            service: crate::models::api_key::ServiceProvider::SerpApi,
-            endpoint: "/search".to_string(),
-            method: "GET".to_string(),
-            headers: HashMap::new(),
-            body: None,
-            timeout_ms: 15000,
-            retry_count: 0,
-            metadata: HashMap::new(),
-        };
-
-        // Add query parameters
-        let query_params = format!("q={}&engine=google&num=30", urlencoding::encode(query));
-        request.metadata.insert("query_params".to_string(), query_params);
-
-        let response = api_manager.make_service_request(
-            crate::models::api_key::ServiceProvider::SerpApi,
-            request,
-        ).await?;
-
-        if !response.success {
-            return Err(crate::error::ApiError::external_service_error(
-                "SerpApi".to_string(),
-                response.error_message.unwrap_or_default(),
-            ));
-        }
+        // SerpApi first, then the rest of the web search chain, paging and topping up until
+        // the step's `num_results` distinct results are in or no provider has more
+        let num_results = context.input_data.get("num_results")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30) as u32;
+        let recency = context.input_data.get(SEARCH_RECENCY_KEY)
+            .map(|recency| serde_json::from_value(recency.clone()))
+            .transpose()?
+            .unwrap_or_default();
+        let outcome = api_manager.search_web(crate::models::api_key::ServiceProvider::SerpApi, query, num_results, recency).await?;
+        let served_by = outcome.served_by.first().copied().unwrap_or(crate::models::api_key::ServiceProvider::SerpApi);
 
-        let search_results: serde_json::Value = serde_json::from_str(&response.body)?;
-        
         let mut results = HashMap::new();
-        results.insert("search_results".to_string(), search_results);
+        results.insert("search_results".to_string(), NormalizedPayload::search_results_json(&outcome.hits));
+        results.insert("result_count".to_string(), serde_json::Value::from(outcome.fetched));
+        results.insert("requested_results".to_string(), serde_json::Value::from(outcome.requested));
         results.insert("search_query".to_string(), serde_json::Value::String(query.to_string()));
         results.insert("methodology_step".to_string(), serde_json::Value::String("hybrid_search".to_string()));
+        if outcome.outside_time_range > 0 {
+            results.insert("outside_time_range".to_string(), serde_json::Value::from(outcome.outside_time_range));
+        }
+        if let Some(reason) = &outcome.shortfall_reason {
+            results.insert("search_shortfall".to_string(), serde_json::Value::String(reason.clone()));
+        }
+        results.insert(SERVED_BY_KEY.to_string(), serde_json::to_value(served_by)?);
+        results.insert(PROVIDER_ATTEMPTS_KEY.to_string(), serde_json::to_value(&outcome.attempts)?);
 
-        debug!("Hybrid SerpApi search completed successfully");
+        debug!("Hybrid web search completed with {} of {} results, served by {:?}", outcome.fetched, outcome.requested, outcome.served_by);
         Ok(results)
     }
 
@@ -185,12 +180,31 @@ impl HybridMethodology {
         
         let mut all_scraped_content = Vec::new();
         let mut successful_scrapes = 0;
-
-        // Scrape each URL
-        for url in urls {
-            match self.scrape_single_url(&url, api_manager).await {
-                Ok(content) => {
-                    all_scraped_content.push(content);
+        let mut cached_pages = 0;
+        let mut skipped_urls = Vec::new();
+        let bypass_cache = context.input_data.get(BYPASS_CONTENT_CACHE_KEY)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Scrape each URL, reusing pages other workflows already extracted
+        for url in &urls {
+            if let Err(skipped) = api_manager.crawl_policy().check(url).await {
+                debug!("Skipping {}: {}", url, skipped.reason);
+                skipped_urls.push(skipped);
+                continue;
+            }
+            let extraction = api_manager.content_cache()
+                .get_or_extract(url, crate::models::api_key::ServiceProvider::Firecrawl, bypass_cache, move || async move {
+                    let _slot = api_manager.crawl_policy().host_slot(url).await;
+                    self.scrape_single_url(url, api_manager).await
+                })
+                .await;
+            match extraction {
+                Ok(extraction) => {
+                    if extraction.from_cache {
+                        cached_pages += 1;
+                    }
+                    all_scraped_content.push(extraction.content);
                     successful_scrapes += 1;
                 }
                 Err(e) => {
@@ -202,6 +216,8 @@ impl HybridMethodology {
         let mut results = HashMap::new();
         results.insert("scraped_content".to_string(), serde_json::Value::Array(all_scraped_content));
         results.insert("successful_scrapes".to_string(), serde_json::Value::Number(serde_json::Number::from(successful_scrapes)));
+        results.insert("cached_pages".to_string(), serde_json::Value::Number(serde_json::Number::from(cached_pages)));
+        results.insert("skipped_urls".to_string(), serde_json::to_value(&skipped_urls)?);
         results.insert("methodology_step".to_string(), serde_json::Value::String("hybrid_scraping".to_string()));
 
         debug!("Hybrid Firecrawl scraping completed successfully");
@@ -290,12 +306,20 @@ impl HybridMethodology {
 
         let base_urls = self.extract_base_urls_from_content(scraped_content)?;
         let mut all_mapped_urls = Vec::new();
+        let mut skipped_urls = Vec::new();
 
         // Map each base URL
         for base_url in base_urls.iter().take(5) {
+            if let Err(skipped) = api_manager.crawl_policy().check(base_url).await {
+                debug!("Skipping {}: {}", base_url, skipped.reason);
+                skipped_urls.push(skipped);
+                continue;
+            }
+            let _slot = api_manager.crawl_policy().host_slot(base_url).await;
             match self.map_single_url(base_url, api_manager).await {
                 Ok(mapped_urls) => {
-                    all_mapped_urls.extend(mapped_urls);
+                    let allowed = api_manager.crawl_policy().retain_allowed(mapped_urls, &mut skipped_urls).await;
+                    all_mapped_urls.extend(allowed);
                 }
                 Err(e) => {
                     debug!("Failed to map {}: {}", base_url, e);
@@ -308,6 +332,7 @@ impl HybridMethodology {
             all_mapped_urls.into_iter().map(serde_json::Value::String).collect()
         ));
         results.insert("base_urls_mapped".to_string(), serde_json::Value::Number(serde_json::Number::from(base_urls.len())));
+        results.insert("skipped_urls".to_string(), serde_json::to_value(&skipped_urls)?);
         results.insert("methodology_step".to_string(), serde_json::Value::String("hybrid_mapping".to_string()));
 
         debug!("Hybrid Firecrawl mapping completed successfully");
@@ -329,66 +354,44 @@ impl HybridMethodology {
         let embeddings = context.shared_data.get("embeddings");
         let mapped_urls = context.shared_data.get("mapped_urls");
 
-        // Create comprehensive synthesis prompt
-        let synthesis_prompt = self.create_hybrid_synthesis_prompt(
+        // Create comprehensive synthesis prompt, fitted to the synthesis models' context window
+        let model_role = model_router::step_model_role(&step.input_data, model_router::SYNTHESIS);
+        let budget = api_manager.model_context_budget(&model_role, 8000).await;
+        let (synthesis_prompt, context_usage) = self.create_hybrid_synthesis_prompt(
             search_results,
             scraped_content,
             embeddings,
             mapped_urls,
-        )?;
-
-        // Create chat completion request
-        let request_body = serde_json::json!({
-            "model": "anthropic/claude-3-sonnet",
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are an expert research analyst specializing in comprehensive multi-source research synthesis. You excel at combining data from multiple methodologies (search engines, web scraping, content analysis, and content mapping) to create authoritative, well-structured research reports."
-                },
-                {
-                    "role": "user",
-                    "content": synthesis_prompt
-                }
-            ],
-            "temperature": 0.25,
-            "max_tokens": 8000
-        });
+            &budget,
+        );
 
-        let mut request = ServiceRequest {
-            request_id: Uuid::new_v4(),
-            service: crate::models::api_key::ServiceProvider::OpenRouter,
-            endpoint: "/chat/completions".to_string(),
-            method: "POST".to_string(),
-            headers: HashMap::new(),
-            body: Some(request_body.to_string()),
-            timeout_ms: 60000,
-            retry_count: 0,
-            metadata: HashMap::new(),
-        };
+        let system_prompt = structured_output::with_confidence_guidance(
+            prompt_library::system_prompt(context, prompt_library::HYBRID_SYNTHESIS),
+            context,
+        );
 
-        let response = api_manager.make_service_request(
-            crate::models::api_key::ServiceProvider::OpenRouter,
-            request,
+        // Request the report and its insights as structured output from the synthesis role's models
+        let output = structured_output::run_analysis(
+            api_manager,
+            &model_role,
+            &system_prompt,
+            &synthesis_prompt,
+            0.25,
+            8000,
+            60000,
         ).await?;
 
-        if !response.success {
-            return Err(crate::error::ApiError::external_service_error(
-                "OpenRouter".to_string(),
-                response.error_message.unwrap_or_default(),
-            ));
-        }
-
-        let ai_response: serde_json::Value = serde_json::from_str(&response.body)?;
-        
-        let synthesis_content = ai_response["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("Hybrid synthesis failed")
-            .to_string();
+        // Parse AI response
+        let ai_response: serde_json::Value = serde_json::from_str(&output.response.body)?;
 
         let mut results = HashMap::new();
-        results.insert("hybrid_synthesis".to_string(), serde_json::Value::String(synthesis_content));
+        results.insert("hybrid_synthesis".to_string(), serde_json::Value::String(output.analysis.report));
+        results.insert(structured_output::INSIGHTS_KEY.to_string(), serde_json::to_value(&output.analysis.insights)?);
+        results.insert(structured_output::INSIGHTS_SOURCE_KEY.to_string(), serde_json::to_value(output.source)?);
         results.insert("ai_response".to_string(), ai_response);
         results.insert("methodology".to_string(), serde_json::Value::String("hybrid".to_string()));
+        results.insert(SERVED_BY_MODEL_KEY.to_string(), serde_json::Value::String(output.served_by_model));
+        results.insert(CONTEXT_USAGE_KEY.to_string(), serde_json::to_value(&context_usage)?);
 
         debug!("Hybrid synthesis completed successfully");
         Ok(results)
@@ -537,46 +540,15 @@ impl HybridMethodology {
         scraped_content: Option<&serde_json::Value>,
         embeddings: Option<&serde_json::Value>,
         mapped_urls: Option<&serde_json::Value>,
-    ) -> AppResult<String> {
-        let mut prompt = String::from(
-            r#"Please create a comprehensive research report by synthesizing data from multiple research methodologies:
+        budget: &ContextBudget,
+    ) -> (String, ContextUsage) {
+        let instructions = r#"Please create a comprehensive research report by synthesizing data from multiple research methodologies:
 
 METHODOLOGY OVERVIEW:
 This research combines the Don Lim approach (cost-optimized using SerpApi + Jina AI + OpenRouter) with the Nick Scamara approach (professional interface using Firecrawl + AI SDK) for maximum coverage and accuracy.
 
-"#
-        );
-
-        if let Some(search_data) = search_results {
-            prompt.push_str(&format!(
-                "SEARCH RESULTS (SerpApi):\n{}\n\n",
-                serde_json::to_string_pretty(search_data)?
-            ));
-        }
-
-        if let Some(scraped_data) = scraped_content {
-            prompt.push_str(&format!(
-                "SCRAPED CONTENT (Firecrawl):\n{}\n\n",
-                serde_json::to_string_pretty(scraped_data)?
-            ));
-        }
-
-        if let Some(embeddings_data) = embeddings {
-            prompt.push_str(&format!(
-                "CONTENT ANALYSIS (Jina AI):\n{}\n\n",
-                serde_json::to_string_pretty(embeddings_data)?
-            ));
-        }
-
-        if let Some(mapped_data) = mapped_urls {
-            prompt.push_str(&format!(
-                "MAPPED CONTENT (Firecrawl):\n{}\n\n",
-                serde_json::to_string_pretty(mapped_data)?
-            ));
-        }
-
-        prompt.push_str(
-            r#"Please provide a comprehensive research report with:
+{context}
+Please provide a comprehensive research report with:
 
 1. **Executive Summary** - Key findings and insights
 2. **Methodology Analysis** - How the hybrid approach enhanced research quality
@@ -588,10 +560,29 @@ This research combines the Don Lim approach (cost-optimized using SerpApi + Jina
 8. **Future Research Directions** - Suggested areas for deeper investigation
 9. **Complete Bibliography** - All sources with quality ratings
 
-Format in professional markdown with proper headings, citations, and visual elements."#
-        );
+Format in professional markdown with proper headings, citations, and visual elements."#;
 
-        Ok(prompt)
+        // Full scraped pages outrank search snippets of the same sources; raw embeddings
+        // and mapped URL lists give way first
+        let mut items = Vec::new();
+        if let Some(scraped_data) = scraped_content {
+            items.extend(ContextItem::from_scraped_content(scraped_data).into_iter().map(|mut item| {
+                item.score += 1.0;
+                item
+            }));
+        }
+        if let Some(search_data) = search_results {
+            items.extend(ContextItem::from_search_results(search_data));
+        }
+        if let Some(mapped_data) = mapped_urls {
+            items.push(ContextItem::from_json("MAPPED CONTENT (Firecrawl)", mapped_data, 0.0));
+        }
+        if let Some(embeddings_data) = embeddings {
+            items.push(ContextItem::from_json("CONTENT ANALYSIS (Jina AI)", embeddings_data, -1.0));
+        }
+
+        let context = assemble_context(items, instructions, budget);
+        (instructions.replace("{context}", &context.text), context.usage)
     }
 }
 
@@ -695,6 +686,12 @@ impl WorkflowExecutor for HybridMethodology {
         // Create metadata
         let mut metadata = HashMap::new();
         metadata.insert("methodology".to_string(), serde_json::Value::String("hybrid".to_string()));
+        // Keep the structured insights so comparisons and exports need not re-parse the report
+        for key in [structured_output::INSIGHTS_KEY, structured_output::INSIGHTS_SOURCE_KEY] {
+            if let Some(value) = synthesis_result.get(key) {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
         metadata.insert("services_used".to_string(), serde_json::Value::Array(vec![
             serde_json::Value::String("serpapi".to_string()),
             serde_json::Value::String("firecrawl".to_string()),
@@ -714,6 +711,8 @@ impl WorkflowExecutor for HybridMethodology {
             source_count: sources.len() as u32,
             methodology_used: ResearchMethodology::Hybrid,
             execution_time_ms: workflow.execution_duration_ms().unwrap_or(0),
+            partial: false,
+            failed_steps: Vec::new(),
         };
 
         info!("Hybrid methodology results processed successfully");
@@ -7,8 +7,11 @@ use crate::error::AppResult;
 use crate::models::research_workflow::{
     ResearchWorkflow, WorkflowStep, ResearchMethodology, ResearchResults
 };
-use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService};
-use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext};
+use crate::services::api_manager::{ServiceRequest, ServiceResponse, ApiManagerService, ContextBudget, model_router};
+use crate::services::research_engine::workflow_engine::{WorkflowExecutor, ExecutionContext, BYPASS_CONTENT_CACHE_KEY, SERVED_BY_MODEL_KEY};
+use crate::services::research_engine::prompt_library;
+use crate::services::research_engine::structured_output;
+use crate::services::research_engine::context_assembler::{assemble_context, ContextItem, ContextUsage, CONTEXT_USAGE_KEY};
 
 /// Nick Scamara methodology implementation
 /// Uses Firecrawl + AI SDK for professional interface approach with advanced web scraping
@@ -70,7 +73,8 @@ impl NickScamaraMethodology {
         step.service_provider = Some("openrouter".to_string());
         step.endpoint = Some("/chat/completions".to_string());
         step.depends_on = depends_on;
-        step.input_data.insert("model".to_string(), serde_json::Value::String("anthropic/claude-3-sonnet".to_string()));
+        step.input_data.insert(model_router::MODEL_ROLE_KEY.to_string(), serde_json::Value::String(model_router::SYNTHESIS.to_string()));
+        step.input_data.insert(prompt_library::PROMPT_ID_KEY.to_string(), serde_json::Value::String(prompt_library::NICK_SCAMARA_SYNTHESIS.to_string()));
         step.input_data.insert("temperature".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(0.2).unwrap()));
         step.input_data.insert("max_tokens".to_string(), serde_json::Value::Number(serde_json::Number::from(6000)));
         
@@ -96,12 +100,31 @@ impl NickScamaraMethodology {
 
         let mut all_scraped_content = Vec::new();
         let mut successful_scrapes = 0;
+        let mut cached_pages = 0;
+        let mut skipped_urls = Vec::new();
+        let bypass_cache = context.input_data.get(BYPASS_CONTENT_CACHE_KEY)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-        // Scrape each URL
+        // Scrape each URL, reusing pages other workflows already extracted
         for url in urls_to_scrape.iter().take(10) { // Limit to 10 URLs
-            match self.scrape_single_url(url, api_manager).await {
-                Ok(content) => {
-                    all_scraped_content.push(content);
+            if let Err(skipped) = api_manager.crawl_policy().check(url).await {
+                debug!("Skipping {}: {}", url, skipped.reason);
+                skipped_urls.push(skipped);
+                continue;
+            }
+            let extraction = api_manager.content_cache()
+                .get_or_extract(url, crate::models::api_key::ServiceProvider::Firecrawl, bypass_cache, move || async move {
+                    let _slot = api_manager.crawl_policy().host_slot(url).await;
+                    self.scrape_single_url(url, api_manager).await
+                })
+                .await;
+            match extraction {
+                Ok(extraction) => {
+                    if extraction.from_cache {
+                        cached_pages += 1;
+                    }
+                    all_scraped_content.push(extraction.content);
                     successful_scrapes += 1;
                 }
                 Err(e) => {
@@ -121,6 +144,8 @@ impl NickScamaraMethodology {
         results.insert("scraped_content".to_string(), serde_json::Value::Array(all_scraped_content));
         results.insert("successful_scrapes".to_string(), serde_json::Value::Number(serde_json::Number::from(successful_scrapes)));
         results.insert("total_urls".to_string(), serde_json::Value::Number(serde_json::Number::from(urls_to_scrape.len())));
+        results.insert("cached_pages".to_string(), serde_json::Value::Number(serde_json::Number::from(cached_pages)));
+        results.insert("skipped_urls".to_string(), serde_json::to_value(&skipped_urls)?);
         results.insert("scraping_method".to_string(), serde_json::Value::String("firecrawl_advanced".to_string()));
 
         debug!("Firecrawl web scraping completed successfully");
@@ -146,12 +171,20 @@ impl NickScamaraMethodology {
         let base_urls = self.extract_base_urls_from_content(scraped_content)?;
         
         let mut all_mapped_urls = Vec::new();
+        let mut skipped_urls = Vec::new();
 
         // Map each base URL to discover additional content
         for base_url in base_urls.iter().take(5) { // Limit to 5 base URLs
+            if let Err(skipped) = api_manager.crawl_policy().check(base_url).await {
+                debug!("Skipping {}: {}", base_url, skipped.reason);
+                skipped_urls.push(skipped);
+                continue;
+            }
+            let _slot = api_manager.crawl_policy().host_slot(base_url).await;
             match self.map_single_url(base_url, api_manager).await {
                 Ok(mapped_urls) => {
-                    all_mapped_urls.extend(mapped_urls);
+                    let allowed = api_manager.crawl_policy().retain_allowed(mapped_urls, &mut skipped_urls).await;
+                    all_mapped_urls.extend(allowed);
                 }
                 Err(e) => {
                     debug!("Failed to map {}: {}", base_url, e);
@@ -164,6 +197,7 @@ impl NickScamaraMethodology {
             all_mapped_urls.into_iter().map(serde_json::Value::String).collect()
         ));
         results.insert("base_urls_mapped".to_string(), serde_json::Value::Number(serde_json::Number::from(base_urls.len())));
+        results.insert("skipped_urls".to_string(), serde_json::to_value(&skipped_urls)?);
         results.insert("mapping_method".to_string(), serde_json::Value::String("firecrawl_mapping".to_string()));
 
         debug!("Firecrawl content mapping completed successfully");
@@ -190,63 +224,38 @@ impl NickScamaraMethodology {
                 "No mapped URLs available for synthesis".to_string()
             ))?;
 
-        // Create synthesis prompt
-        let synthesis_prompt = self.create_synthesis_prompt(scraped_content, mapped_urls)?;
-
-        // Create chat completion request
-        let request_body = serde_json::json!({
-            "model": "anthropic/claude-3-sonnet",
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are a professional research analyst specializing in comprehensive content synthesis. Create detailed, well-structured research reports with professional formatting and actionable insights."
-                },
-                {
-                    "role": "user",
-                    "content": synthesis_prompt
-                }
-            ],
-            "temperature": 0.2,
-            "max_tokens": 6000
-        });
+        // Create synthesis prompt, fitted to the synthesis models' context window
+        let model_role = model_router::step_model_role(&step.input_data, model_router::SYNTHESIS);
+        let budget = api_manager.model_context_budget(&model_role, 6000).await;
+        let (synthesis_prompt, context_usage) = self.create_synthesis_prompt(scraped_content, mapped_urls, &budget);
 
-        let mut request = ServiceRequest {
-            request_id: Uuid::new_v4(),
-            service: crate::models::api_key::ServiceProvider::OpenRouter,
-            endpoint: "/chat/completions".to_string(),
-            method: "POST".to_string(),
-            headers: HashMap::new(),
-            body: Some(request_body.to_string()),
-            timeout_ms: 45000,
-            retry_count: 0,
-            metadata: HashMap::new(),
-        };
+        let system_prompt = structured_output::with_confidence_guidance(
+            prompt_library::system_prompt(context, prompt_library::NICK_SCAMARA_SYNTHESIS),
+            context,
+        );
 
-        // Make request
-        let response = api_manager.make_service_request(
-            crate::models::api_key::ServiceProvider::OpenRouter,
-            request,
+        // Request the report and its insights as structured output from the synthesis role's models
+        let output = structured_output::run_analysis(
+            api_manager,
+            &model_role,
+            &system_prompt,
+            &synthesis_prompt,
+            0.2,
+            6000,
+            45000,
         ).await?;
 
-        if !response.success {
-            return Err(crate::error::ApiError::external_service_error(
-                "OpenRouter".to_string(),
-                response.error_message.unwrap_or_default(),
-            ));
-        }
-
         // Parse AI response
-        let ai_response: serde_json::Value = serde_json::from_str(&response.body)?;
-        
-        let synthesis_content = ai_response["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("Synthesis failed")
-            .to_string();
+        let ai_response: serde_json::Value = serde_json::from_str(&output.response.body)?;
 
         let mut results = HashMap::new();
-        results.insert("synthesis".to_string(), serde_json::Value::String(synthesis_content));
+        results.insert("synthesis".to_string(), serde_json::Value::String(output.analysis.report));
+        results.insert(structured_output::INSIGHTS_KEY.to_string(), serde_json::to_value(&output.analysis.insights)?);
+        results.insert(structured_output::INSIGHTS_SOURCE_KEY.to_string(), serde_json::to_value(output.source)?);
         results.insert("ai_response".to_string(), ai_response);
         results.insert("methodology".to_string(), serde_json::Value::String("nick_scamara".to_string()));
+        results.insert(SERVED_BY_MODEL_KEY.to_string(), serde_json::Value::String(output.served_by_model));
+        results.insert(CONTEXT_USAGE_KEY.to_string(), serde_json::to_value(&context_usage)?);
 
         debug!("AI synthesis completed successfully");
         Ok(results)
@@ -378,16 +387,11 @@ impl NickScamaraMethodology {
         &self,
         scraped_content: &serde_json::Value,
         mapped_urls: &serde_json::Value,
-    ) -> AppResult<String> {
-        let prompt = format!(
-            r#"Please synthesize the following research data into a comprehensive, professional report:
-
-SCRAPED CONTENT:
-{}
-
-DISCOVERED URLS:
-{}
+        budget: &ContextBudget,
+    ) -> (String, ContextUsage) {
+        let instructions = r#"Please synthesize the following research data into a comprehensive, professional report:
 
+{context}
 Please provide a detailed research report with:
 1. Executive Summary
 2. Methodology Overview
@@ -398,12 +402,13 @@ Please provide a detailed research report with:
 7. Future Research Directions
 8. Complete Bibliography
 
-Format the response in professional markdown with proper headings, bullet points, and citations."#,
-            serde_json::to_string_pretty(scraped_content)?,
-            serde_json::to_string_pretty(mapped_urls)?
-        );
+Format the response in professional markdown with proper headings, bullet points, and citations."#;
+
+        let mut items = ContextItem::from_scraped_content(scraped_content);
+        items.push(ContextItem::from_json("DISCOVERED URLS", mapped_urls, 0.0));
 
-        Ok(prompt)
+        let context = assemble_context(items, instructions, budget);
+        (instructions.replace("{context}", &context.text), context.usage)
     }
 }
 
@@ -479,6 +484,12 @@ impl WorkflowExecutor for NickScamaraMethodology {
         // Create metadata
         let mut metadata = HashMap::new();
         metadata.insert("methodology".to_string(), serde_json::Value::String("nick_scamara".to_string()));
+        // Keep the structured insights so comparisons and exports need not re-parse the report
+        for key in [structured_output::INSIGHTS_KEY, structured_output::INSIGHTS_SOURCE_KEY] {
+            if let Some(value) = synthesis_result.get(key) {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
         metadata.insert("services_used".to_string(), serde_json::Value::Array(vec![
             serde_json::Value::String("firecrawl".to_string()),
             serde_json::Value::String("openrouter".to_string()),
@@ -495,6 +506,8 @@ impl WorkflowExecutor for NickScamaraMethodology {
             source_count: sources.len() as u32,
             methodology_used: ResearchMethodology::NickScamara,
             execution_time_ms: workflow.execution_duration_ms().unwrap_or(0),
+            partial: false,
+            failed_steps: Vec::new(),
         };
 
         info!("Nick Scamara methodology results processed successfully");
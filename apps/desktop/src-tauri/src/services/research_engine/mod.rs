@@ -1,18 +1,32 @@
 use std::sync::Arc;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
-use tracing::{info, debug, error};
+use tracing::{info, debug, warn, error};
 use uuid::Uuid;
 use chrono::Utc;
 use serde_json;
 
-use crate::error::{AppResult, ResearchError};
+use crate::error::{AppError, AppResult, ResearchError};
 use crate::services::{Service, ApiManagerService, DataPersistenceService, MonitoringService};
+use crate::services::data_persistence::{data_residency, WorkflowSearchFilters};
+use crate::services::api_manager::{tenant_scope, KeyScope};
 use crate::models::research_workflow::{
     ResearchWorkflow, WorkflowStatus, ResearchMethodology, WorkflowParameters,
     CreateWorkflowRequest, ResearchResult, ResearchStep, StepStatus
 };
-
+use crate::models::pagination::{Page, PageRequest};
+use crate::models::idempotency::{self, IdempotencyRecord, MAX_IDEMPOTENCY_KEY_LEN};
+use crate::models::workflow_rating::{MethodologyRecommendation, QueryDomain, WorkflowRating};
+use crate::models::saga::SagaState;
+
+use self::prompt_library::PromptLibrary;
+use self::workflow_bundle::{ImportedBundle, WorkflowBundle};
+use self::preflight::QuotaCheck;
+use self::explain_plan::{PlannedKey, ProviderState, WorkflowPlan};
+use self::result_stream::{PartialResults, ResultStream};
+use self::bulk_rerun::{RequeuedWorkflow, RerunFilter, RerunSummary, SkippedWorkflow};
+use self::load_test::{LoadTestConfig, LoadTestReport, OutcomeKind, Slots, WorkflowOutcome};
+use self::saga::{AllocateResourcesStep, SagaCoordinator, SagaStep, StartExecutionStep};
 use self::queue_manager::{
     QueueManager, QueuedWorkflow, WorkflowPriority, QueueStats, ConcurrencyConfig,
     WorkflowProgress, QueueProgress, StepProgress, ProgressUpdate, ProgressUpdateType,
@@ -28,6 +42,23 @@ pub mod workflow_engine;
 pub mod methodology_don_lim;
 pub mod methodology_nick_scamara;
 pub mod methodology_hybrid;
+pub mod methodology_document;
+pub mod methodology_feedback;
+pub mod image_ocr;
+pub mod pdf_extraction;
+pub mod overlap_checker;
+pub mod contradiction_detector;
+pub mod context_assembler;
+pub mod prompt_library;
+pub mod structured_output;
+pub mod workflow_bundle;
+pub mod saga;
+pub mod preflight;
+pub mod explain_plan;
+pub mod result_stream;
+pub mod single_flight;
+pub mod bulk_rerun;
+pub mod load_test;
 
 // Re-export queue types for external use
 pub use queue_manager::{
@@ -73,6 +104,15 @@ pub struct ResearchEngineService {
     methodologies: Arc<RwLock<HashMap<String, ResearchMethodology>>>,
     workflow_engine: Arc<workflow_engine::WorkflowEngine>,
     queue_manager: Arc<QueueManager>,
+    prompt_library: Arc<PromptLibrary>,
+    /// Serializes creation requests carrying an idempotency key, so concurrent retries
+    /// cannot both miss the key and create two workflows
+    idempotency_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Serializes claiming failed workflows for a re-run, so overlapping bulk re-runs
+    /// cannot both queue one
+    rerun_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Undoes the side effects of workflow starts that fail partway
+    sagas: Arc<SagaCoordinator>,
 }
 
 impl ResearchEngineService {
@@ -84,14 +124,20 @@ impl ResearchEngineService {
     ) -> AppResult<Self> {
         info!("Initializing research engine service...");
 
+        // Load prompt templates for LLM steps
+        let prompt_library = Arc::new(PromptLibrary::new(data_persistence.clone()).await?);
+
         // Create workflow engine
         let workflow_engine = Arc::new(workflow_engine::WorkflowEngine::new(
             data_persistence.clone(),
             api_manager.clone(),
+            prompt_library.clone(),
         ).await?);
 
         // Create queue manager with default max concurrent workflows
-        let queue_manager = Arc::new(QueueManager::new(5).await?);
+        let queue_manager = Arc::new(QueueManager::new(5).await?.with_history_store(data_persistence.clone()));
+
+        let sagas = Arc::new(SagaCoordinator::new(data_persistence.clone()));
 
         let service = Self {
             api_manager,
@@ -101,11 +147,28 @@ impl ResearchEngineService {
             methodologies: Arc::new(RwLock::new(HashMap::new())),
             workflow_engine,
             queue_manager,
+            prompt_library,
+            idempotency_lock: Arc::new(tokio::sync::Mutex::new(())),
+            rerun_lock: Arc::new(tokio::sync::Mutex::new(())),
+            sagas,
         };
 
         // Initialize default methodologies
         service.initialize_default_methodologies().await?;
 
+        // Undo workflow runs a previous shutdown interrupted; a saga that cannot be
+        // recovered needs manual cleanup, but must not keep the engine from starting
+        match service.sagas.recover(&service.workflow_start_steps(Default::default())).await {
+            Ok(0) => {}
+            Ok(recovered) => info!("Compensated {} interrupted workflow runs", recovered),
+            Err(e) => error!("Failed to recover interrupted workflow runs: {}", e),
+        }
+        match service.workflow_engine.reconcile_followers().await {
+            Ok(0) => {}
+            Ok(settled) => info!("Settled {} workflows that were sharing a run at shutdown", settled),
+            Err(e) => error!("Failed to settle workflows that were sharing a run: {}", e),
+        }
+
         info!("Research engine service initialized successfully");
         Ok(service)
     }
@@ -127,8 +190,7 @@ impl ResearchEngineService {
                     step_type: "web_search".to_string(),
                     provider: "serpapi".to_string(),
                     parameters: serde_json::json!({
-                        "num_results": 20,
-                        "time_range": "last_year"
+                        "num_results": 20
                     }),
                     status: StepStatus::Pending,
                     result: None,
@@ -155,7 +217,7 @@ impl ResearchEngineService {
                     step_type: "ai_analysis".to_string(),
                     provider: "openrouter".to_string(),
                     parameters: serde_json::json!({
-                        "model": "anthropic/claude-3-sonnet",
+                        "model_role": "analysis",
                         "temperature": 0.3,
                         "max_tokens": 4000
                     }),
@@ -196,7 +258,7 @@ impl ResearchEngineService {
                     step_type: "ai_summary".to_string(),
                     provider: "openrouter".to_string(),
                     parameters: serde_json::json!({
-                        "model": "anthropic/claude-3-haiku",
+                        "model_role": "summary",
                         "temperature": 0.2,
                         "max_tokens": 2000
                     }),
@@ -252,10 +314,10 @@ impl ResearchEngineService {
                     step_type: "academic_analysis".to_string(),
                     provider: "openrouter".to_string(),
                     parameters: serde_json::json!({
-                        "model": "anthropic/claude-3-sonnet",
+                        "model_role": "analysis",
                         "temperature": 0.1,
                         "max_tokens": 6000,
-                        "system_prompt": "You are an academic researcher. Analyze the provided sources with scholarly rigor."
+                        "prompt_id": prompt_library::ACADEMIC_ANALYSIS
                     }),
                     status: StepStatus::Pending,
                     result: None,
@@ -273,8 +335,51 @@ impl ResearchEngineService {
         Ok(())
     }
 
-    /// Create a new research workflow from request
+    /// Create a new research workflow from request.
+    /// A request carrying an idempotency key that was already used within its window
+    /// returns the workflow the first request created instead of creating another one.
     pub async fn create_workflow_from_request(&self, request: CreateWorkflowRequest) -> AppResult<ResearchWorkflow> {
+        let Some(key) = request.idempotency_key.clone() else {
+            return self.create_new_workflow(request).await;
+        };
+
+        if key.trim().is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(AppError::validation(
+                "idempotency_key",
+                format!("must be between 1 and {} characters", MAX_IDEMPOTENCY_KEY_LEN),
+            ));
+        }
+        let fingerprint = idempotency::request_fingerprint(&request);
+
+        let _guard = self.idempotency_lock.lock().await;
+        let now = Utc::now();
+        let existing = {
+            let data_persistence = self.data_persistence.read().await;
+            let purged = data_persistence.purge_expired_idempotency_records(now).await?;
+            if purged > 0 {
+                debug!("Purged {} expired idempotency keys", purged);
+            }
+            data_persistence.get_idempotency_record(&key).await?
+        };
+
+        if let Some(record) = existing.filter(|record| !record.is_expired(now)) {
+            if record.request_fingerprint != fingerprint {
+                return Err(ResearchError::idempotency_key_conflict(key).into());
+            }
+            // The workflow may have been deleted since; the key then starts over
+            if let Some(workflow) = self.get_workflow(record.workflow_id).await? {
+                info!("Idempotency key {} already used, returning workflow {}", key, workflow.id);
+                return Ok(workflow);
+            }
+        }
+
+        let workflow = self.create_new_workflow(request).await?;
+        let data_persistence = self.data_persistence.read().await;
+        data_persistence.save_idempotency_record(&IdempotencyRecord::new(key, workflow.id, fingerprint)).await?;
+        Ok(workflow)
+    }
+
+    async fn create_new_workflow(&self, request: CreateWorkflowRequest) -> AppResult<ResearchWorkflow> {
         info!("Creating new research workflow: {}", request.name);
 
         // Validate request
@@ -286,6 +391,18 @@ impl ResearchEngineService {
             return Err(ResearchError::invalid_request("Research query cannot be empty".to_string()).into());
         }
 
+        if let Some(published_after) = request.parameters.as_ref().and_then(|parameters| parameters.published_after) {
+            if published_after > Utc::now() {
+                return Err(AppError::validation("published_after", "cannot be in the future"));
+            }
+        }
+
+        if let Some(min_confidence) = request.parameters.as_ref().and_then(|parameters| parameters.insight_confidence.min_confidence) {
+            if !(0.0..=1.0).contains(&min_confidence) {
+                return Err(AppError::validation("insight_confidence.min_confidence", "must be between 0 and 1"));
+            }
+        }
+
         // Get methodology
         let methodologies = self.methodologies.read().await;
         let methodology_name = request.template_id
@@ -297,8 +414,10 @@ impl ResearchEngineService {
             .clone();
         drop(methodologies);
 
+        let data_region = request.data_region;
+
         // Create workflow
-        let workflow = ResearchWorkflow {
+        let mut workflow = ResearchWorkflow {
             id: Uuid::new_v4(),
             name: request.name,
             query: request.query,
@@ -313,13 +432,19 @@ impl ResearchEngineService {
             completed_at: None,
         };
 
+        // Pin the workflow to the tenant's region; the persistence layer
+        // routes it to that region's database whenever it is stored. A region
+        // without storage is refused now rather than when the first save fails.
+        self.data_persistence.read().await.ensure_region_available(data_region)?;
+        data_residency::tag_workflow(&mut workflow, data_region);
+        // The workflow spends the keys of the caller creating it
+        tenant_scope::bind_workflow(&mut workflow, &KeyScope::current())?;
+
         // Store in active workflows
         let mut active_workflows = self.active_workflows.write().await;
         active_workflows.insert(workflow.id, workflow.clone());
         drop(active_workflows);
 
-        // TODO: Store in database
-
         info!("Research workflow created: {} ({})", workflow.name, workflow.id);
         Ok(workflow)
     }
@@ -341,6 +466,8 @@ impl ResearchEngineService {
                 created_by: Some(created_by),
                 ..Default::default()
             }),
+            data_region: Default::default(),
+            idempotency_key: None,
         };
         self.create_workflow_from_request(request).await
     }
@@ -350,12 +477,136 @@ impl ResearchEngineService {
         self.create_workflow_from_request(request).await
     }
 
-    /// Start workflow execution (called by Tauri command)
+    /// Start workflow execution (called by Tauri command). Resources are reserved before
+    /// the engine starts the run, and released again if the start fails.
     pub async fn start_workflow_execution(&self, workflow_id: Uuid) -> AppResult<()> {
+        self.start_workflow_execution_with(workflow_id, QuotaCheck::Enforce).await
+    }
+
+    /// Start workflow execution, refusing up front under `QuotaCheck::Enforce` when a
+    /// service the workflow's steps call cannot serve the calls they are estimated to make
+    pub async fn start_workflow_execution_with(&self, workflow_id: Uuid, quota_check: QuotaCheck) -> AppResult<()> {
         info!("Starting workflow execution: {}", workflow_id);
 
-        // Use the workflow engine to start execution
-        self.workflow_engine.start_workflow(workflow_id).await
+        let workflow = self.get_workflow(workflow_id).await?
+            .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+        // An unknown egress profile would fail every step; refuse the run up front
+        if let Some(profile) = &workflow.parameters.egress_profile {
+            self.api_manager.read().await.egress_clients(profile).await?;
+        }
+        let shortfalls = self.quota_shortfalls(&workflow).await?;
+        if !shortfalls.is_empty() {
+            let message = preflight::describe_shortfalls(&shortfalls);
+            match quota_check {
+                QuotaCheck::Enforce => return Err(ResearchError::insufficient_quota(message).into()),
+                QuotaCheck::Override => warn!("Starting workflow {} despite insufficient quota: {}", workflow_id, message),
+            }
+        }
+
+        let limits = self.queue_manager.get_resource_status().await?.limits;
+        let slots = self.queue_manager.get_concurrency_config().await?.max_concurrent;
+        let steps = self.workflow_start_steps(limits.share(slots));
+        let saga = self.sagas.run(workflow_id, &steps).await?;
+        self.close_start_saga(saga, steps);
+        Ok(())
+    }
+
+    /// Close a workflow's start saga once its run has ended: the reserved resources are
+    /// released when it completes, and the start is compensated when it fails or is cancelled
+    fn close_start_saga(&self, mut saga: SagaState, steps: Vec<Arc<dyn SagaStep>>) {
+        let sagas = self.sagas.clone();
+        let data_persistence = self.data_persistence.clone();
+        let result_stream = self.workflow_engine.result_stream();
+
+        tokio::spawn(async move {
+            let workflow_id = saga.workflow_id;
+            let (mut updates, _) = result_stream.subscribe(workflow_id);
+            let status = loop {
+                // The stream can lag or close, so the saved status decides when the run has ended
+                match tokio::time::timeout(std::time::Duration::from_secs(5), updates.recv()).await {
+                    Ok(Ok(snapshot)) if snapshot.workflow_id != workflow_id || !snapshot.is_final => continue,
+                    Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) => {
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    }
+                    _ => {}
+                }
+                match data_persistence.read().await.get_research_workflow(workflow_id).await {
+                    Ok(Some(workflow)) if workflow.is_completed() => break workflow.status,
+                    Ok(Some(_)) => {}
+                    // Deleted while running; nothing is left to keep the reservation for
+                    Ok(None) => break WorkflowStatus::Cancelled,
+                    Err(e) => warn!("Could not read the status of running workflow {}: {}", workflow_id, e),
+                }
+            };
+
+            let closed = if status == WorkflowStatus::Completed {
+                sagas.complete(&mut saga, &steps).await
+            } else {
+                sagas.abort(&mut saga, &steps, format!("Workflow run ended as {:?}", status)).await
+            };
+            if let Err(e) = closed {
+                error!("Failed to close the start saga of workflow {}: {}", workflow_id, e);
+            }
+        });
+    }
+
+    /// Services that cannot cover the provider calls the workflow's steps need. Starting
+    /// the run prepares its steps afresh, so the estimate is taken from a prepared copy
+    /// rather than from the steps stored with the workflow, which may not exist yet.
+    async fn quota_shortfalls(&self, workflow: &ResearchWorkflow) -> AppResult<Vec<preflight::QuotaShortfall>> {
+        let mut planned = workflow.clone();
+        self.workflow_engine.prepare_steps(&mut planned).await?;
+        let demand = preflight::estimate_provider_calls(&planned);
+        let mut capacities = Vec::with_capacity(demand.len());
+        let api_manager = self.api_manager.read().await;
+        for (service, _) in &demand {
+            capacities.push(api_manager.get_service_capacity(*service).await?);
+        }
+        Ok(preflight::shortfalls(&demand, &capacities))
+    }
+
+    /// What a workflow created from `request` would do if started now: its methodology,
+    /// steps, providers and the keys they would use, and the requests, cost and time it
+    /// would take. Nothing is saved, sent or counted against a key.
+    pub async fn explain_workflow(&self, request: CreateWorkflowRequest) -> AppResult<WorkflowPlan> {
+        let mut workflow = ResearchWorkflow::new(
+            request.name,
+            request.query,
+            request.parameters.unwrap_or_default(),
+            String::new(),
+        );
+        self.workflow_engine.prepare_steps(&mut workflow).await?;
+
+        let api_manager = self.api_manager.read().await;
+        let mut providers = HashMap::new();
+        for (service, _) in preflight::estimate_provider_calls(&workflow) {
+            let selected_key = api_manager.preview_key_for_service(service).await?;
+            providers.insert(service, ProviderState {
+                capacity: api_manager.get_service_capacity(service).await?,
+                selected_key: selected_key.as_ref().map(PlannedKey::from),
+                circuit: api_manager.get_provider_circuit_state(service).await,
+                average_response_time_ms: api_manager.get_service_metrics(service).await
+                    .filter(|metrics| metrics.total_requests > 0)
+                    .map(|metrics| metrics.average_response_time_ms),
+            });
+        }
+
+        let mut plan = explain_plan::build_plan(&workflow, &providers, &api_manager.get_fallback_config().await);
+        if crate::utils::air_gap::is_air_gapped() && !plan.replayed {
+            plan.warnings.push("Offline mode is on; every provider request would be refused".to_string());
+        }
+        if api_manager.is_emergency_stop_enabled().await {
+            plan.warnings.push("The emergency stop is on; provider requests are blocked until it is lifted".to_string());
+        }
+        Ok(plan)
+    }
+
+    /// The saga steps that start a workflow, in order
+    fn workflow_start_steps(&self, requirements: ResourceLimits) -> Vec<Arc<dyn SagaStep>> {
+        vec![
+            Arc::new(AllocateResourcesStep::new(self.queue_manager.clone(), requirements)),
+            Arc::new(StartExecutionStep::new(self.workflow_engine.clone(), self.data_persistence.clone())),
+        ]
     }
 
     /// Get workflow by ID (called by Tauri command)
@@ -409,6 +660,38 @@ impl ResearchEngineService {
         }
     }
 
+    /// The results a running workflow has so far, as of its last completed step.
+    /// `None` once it has finished; its full results are then in `get_workflow_results`.
+    pub fn get_partial_workflow_results(&self, workflow_id: Uuid) -> Option<PartialResults> {
+        self.workflow_engine.result_stream().latest(workflow_id)
+    }
+
+    /// Follow a workflow's results as its steps complete. The receiver carries the
+    /// snapshots of every workflow, to be filtered by `workflow_id`; the snapshot
+    /// returned with it is where the workflow is now. A workflow that has already
+    /// finished gets its final snapshot from the stored report, and nothing follows it.
+    pub async fn subscribe_workflow_results(
+        &self,
+        workflow_id: Uuid,
+    ) -> AppResult<(tokio::sync::broadcast::Receiver<PartialResults>, Option<PartialResults>)> {
+        let (receiver, current) = self.workflow_engine.result_stream().subscribe(workflow_id);
+        if current.is_some() {
+            return Ok((receiver, current));
+        }
+
+        let workflow = self.get_workflow(workflow_id).await?
+            .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+        let current = match workflow.status {
+            WorkflowStatus::Completed | WorkflowStatus::Failed | WorkflowStatus::Cancelled => Some(ResultStream::finished(
+                workflow_id,
+                workflow.results.as_ref(),
+                workflow.status != WorkflowStatus::Completed,
+            )),
+            _ => None,
+        };
+        Ok((receiver, current))
+    }
+
     /// Enqueue a workflow for execution with priority
     pub async fn enqueue_workflow(
         &self,
@@ -432,6 +715,11 @@ impl ResearchEngineService {
         Ok(())
     }
 
+    /// Prompt templates and experiments used by LLM steps
+    pub fn prompt_library(&self) -> Arc<PromptLibrary> {
+        self.prompt_library.clone()
+    }
+
     /// Get queue statistics
     pub async fn get_queue_statistics(&self) -> AppResult<QueueStats> {
         self.queue_manager.get_queue_stats().await
@@ -587,6 +875,30 @@ impl ResearchEngineService {
         Ok(active_workflows.values().cloned().collect())
     }
 
+    /// A page of the stored workflows, newest first, running ones as they are in memory
+    pub async fn list_workflows(&self, page: &PageRequest) -> AppResult<Page<ResearchWorkflow>> {
+        let after = page.after()?;
+        let data_persistence = self.data_persistence.read().await;
+        let ids = data_persistence.get_research_workflow_ids_page(after.as_ref(), page.limit()).await?;
+
+        // Running workflows are fresher in memory than in their last saved state
+        let mut active: HashMap<Uuid, ResearchWorkflow> = {
+            let active_workflows = self.active_workflows.read().await;
+            ids.items.iter()
+                .filter_map(|id| active_workflows.get(id).map(|workflow| (*id, workflow.clone())))
+                .collect()
+        };
+        let mut workflows = Vec::with_capacity(ids.items.len());
+        for workflow_id in &ids.items {
+            let workflow = match active.remove(workflow_id) {
+                Some(workflow) => Some(workflow),
+                None => data_persistence.get_research_workflow(*workflow_id).await?,
+            };
+            workflows.extend(workflow);
+        }
+        Ok(Page { items: workflows, next_cursor: ids.next_cursor, total_count: ids.total_count })
+    }
+
     /// Get workflows by status
     pub async fn get_workflows_by_status(&self, status: WorkflowStatus) -> AppResult<Vec<ResearchWorkflow>> {
         let active_workflows = self.active_workflows.read().await;
@@ -596,11 +908,386 @@ impl ResearchEngineService {
             .collect())
     }
 
+    /// Add and remove tags on a workflow, returning it as saved
+    pub async fn tag_workflow(&self, workflow_id: Uuid, add: &[String], remove: &[String]) -> AppResult<ResearchWorkflow> {
+        self.organize_workflow(workflow_id, |workflow| workflow.retag(add, remove)).await
+    }
+
+    /// Move a workflow into a folder, or to the top level with `None`
+    pub async fn move_workflow(&self, workflow_id: Uuid, folder: Option<&str>) -> AppResult<ResearchWorkflow> {
+        self.organize_workflow(workflow_id, |workflow| workflow.move_to_folder(folder)).await
+    }
+
+    /// Record how well a completed workflow's methodology worked, replacing any earlier
+    /// rating of it. Only the user who ran the workflow can rate it. Ratings tune the
+    /// methodology recommended for that user's similar queries.
+    pub async fn rate_workflow(&self, workflow_id: Uuid, rated_by: &str, rating: u8, notes: Option<String>) -> AppResult<WorkflowRating> {
+        let workflow = self.get_workflow(workflow_id).await?
+            .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+        if workflow.status != WorkflowStatus::Completed {
+            return Err(AppError::validation("workflow_id", "only completed workflows can be rated"));
+        }
+
+        let rating = WorkflowRating::new(&workflow, rated_by, rating, notes)?;
+        let data_persistence = self.data_persistence.read().await;
+        data_persistence.save_workflow_rating(&rating).await?;
+
+        info!("Workflow {} rated {}/5 for {:?} in the {} domain",
+            workflow_id, rating.rating, rating.methodology, rating.query_domain.as_str());
+        Ok(rating)
+    }
+
+    /// The methodology to use for `query`, from the user's own ratings of earlier
+    /// workflows in the same domain, with the reasoning behind it
+    pub async fn recommend_methodology(&self, query: &str, user: &str) -> AppResult<MethodologyRecommendation> {
+        let domain = QueryDomain::classify(query);
+        let ratings = {
+            let data_persistence = self.data_persistence.read().await;
+            data_persistence.get_workflow_ratings(user, domain).await?
+        };
+
+        let recommendation = methodology_feedback::recommend(user, domain, WorkflowParameters::default().methodology, &ratings);
+        debug!("Recommending {:?} for a {} query: {}", recommendation.methodology, domain.as_str(), recommendation.rationale);
+        Ok(recommendation)
+    }
+
+    async fn organize_workflow(
+        &self,
+        workflow_id: Uuid,
+        change: impl FnOnce(&mut ResearchWorkflow),
+    ) -> AppResult<ResearchWorkflow> {
+        let active = self.active_workflows.read().await.contains_key(&workflow_id);
+        let workflow = if active {
+            let mut active_workflows = self.active_workflows.write().await;
+            let workflow = active_workflows.get_mut(&workflow_id)
+                .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+            change(workflow);
+            workflow.clone()
+        } else {
+            // Finished workflows are only in storage, in the database of their region
+            let mut workflow = self.data_persistence.read().await.get_research_workflow(workflow_id).await?
+                .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+            change(&mut workflow);
+            workflow
+        };
+
+        let data_persistence = self.data_persistence.read().await;
+        data_persistence.save_research_workflow(&workflow).await?;
+        Ok(workflow)
+    }
+
     /// Delete a workflow
     pub async fn delete_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
         let mut active_workflows = self.active_workflows.write().await;
         active_workflows.remove(&workflow_id);
-        Ok(())
+        drop(active_workflows);
+
+        let data_persistence = self.data_persistence.read().await;
+        data_persistence.delete_research_workflow(workflow_id).await
+    }
+
+    /// Package a workflow with its recorded provider responses for sharing, redacted
+    pub async fn export_workflow_bundle(&self, workflow_id: Uuid) -> AppResult<String> {
+        let workflow = self.get_workflow(workflow_id).await?
+            .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+        let recording = {
+            let api_manager = self.api_manager.read().await;
+            api_manager.get_provider_recording(workflow_id).await?
+        };
+
+        let redaction_config = self.data_persistence.read().await.get_redaction_config();
+        let bundle = WorkflowBundle::new(&redaction_config, &workflow, &recording)?;
+        info!("Exported workflow {} with {} recorded provider calls", workflow_id, bundle.recording.len());
+        bundle.to_archive()
+    }
+
+    /// Reconstruct a workflow from an exported bundle under a new ID. With `rerun`, a
+    /// second workflow is started that replays the bundle's recorded provider responses.
+    pub async fn import_workflow_bundle(&self, archive: &str, rerun: bool) -> AppResult<ImportedBundle> {
+        let bundle = WorkflowBundle::from_archive(archive)?;
+        if rerun && bundle.recording.is_empty() {
+            return Err(AppError::validation(
+                "rerun",
+                "the bundled workflow was not recorded, so there are no captured inputs to re-run from",
+            ));
+        }
+
+        let mut workflow = bundle.imported_workflow();
+        // A bundle carries the scope it was exported under; the importer's applies instead
+        tenant_scope::bind_workflow(&mut workflow, &KeyScope::current())?;
+        {
+            let data_persistence = self.data_persistence.read().await;
+            data_persistence.save_research_workflow(&workflow).await?;
+        }
+        {
+            let api_manager = self.api_manager.read().await;
+            api_manager.import_provider_recording(workflow.id, &bundle.recording).await?;
+        }
+        info!("Imported workflow {} from bundle of workflow {}", workflow.id, bundle.workflow.id);
+
+        let rerun_workflow_id = if rerun {
+            let replay = workflow_bundle::replay_workflow(&workflow);
+            {
+                let data_persistence = self.data_persistence.read().await;
+                data_persistence.save_research_workflow(&replay).await?;
+            }
+            self.start_workflow_execution(replay.id).await?;
+            Some(replay.id)
+        } else {
+            None
+        };
+
+        Ok(ImportedBundle { workflow, recorded_calls: bundle.recording.len(), rerun_workflow_id })
+    }
+
+    /// Re-queue the failed workflows `filter` matches, such as those a provider failed
+    /// while it was down, as new workflows with the same query and parameters. A failure
+    /// is re-run once, never when retrying cannot help, and not once its re-runs have
+    /// failed `MAX_RERUN_ATTEMPTS` times over; those are reported as skipped.
+    pub async fn rerun_failed_workflows(&self, filter: RerunFilter) -> AppResult<RerunSummary> {
+        filter.validate()?;
+        let mut summary = RerunSummary { dry_run: filter.dry_run, ..Default::default() };
+
+        let mut candidates = Vec::new();
+        {
+            let data_persistence = self.data_persistence.read().await;
+            let mut search = WorkflowSearchFilters {
+                status: Some(format!("{:?}", WorkflowStatus::Failed)),
+                // A workflow that failed before then was created before then too
+                created_before: filter.failed_before,
+                limit: Some(200),
+                offset: Some(0),
+                ..Default::default()
+            };
+            loop {
+                let page = data_persistence.browse_workflows(&search).await?;
+                for listed in &page {
+                    if let Some(workflow) = data_persistence.get_research_workflow(listed.workflow_id).await? {
+                        if filter.matches(&workflow) {
+                            candidates.push(workflow);
+                        }
+                    }
+                }
+                if page.len() < 200 {
+                    break;
+                }
+                search.offset = search.offset.map(|offset| offset + 200);
+            }
+        }
+        summary.matched = candidates.len();
+
+        let limit = filter.limit.unwrap_or(usize::MAX);
+        for mut original in candidates {
+            if let Some(reason) = bulk_rerun::skip_reason(&original) {
+                summary.skipped.push(SkippedWorkflow { workflow_id: original.id, reason });
+                continue;
+            }
+            if summary.requeued.len() >= limit {
+                break;
+            }
+
+            if filter.dry_run {
+                let rerun = bulk_rerun::rerun_workflow(&original);
+                summary.requeued.push(RequeuedWorkflow { original_id: original.id, rerun_id: rerun.id });
+                continue;
+            }
+
+            // Claim the original by linking it to its re-run before queuing anything, so
+            // a bulk re-run running alongside this one skips it instead of queuing it again
+            let rerun = {
+                let _guard = self.rerun_lock.lock().await;
+                let data_persistence = self.data_persistence.read().await;
+                if let Some(stored) = data_persistence.get_research_workflow(original.id).await? {
+                    original = stored;
+                }
+                if let Some(reason) = bulk_rerun::skip_reason(&original) {
+                    summary.skipped.push(SkippedWorkflow { workflow_id: original.id, reason });
+                    continue;
+                }
+                let rerun = bulk_rerun::rerun_workflow(&original);
+                original.metadata.insert(bulk_rerun::RERUN_AS_KEY.to_string(), rerun.id.to_string());
+                original.updated_at = Utc::now();
+                if let Err(e) = data_persistence.save_research_workflow(&original).await {
+                    error!("Failed to claim workflow {} for a re-run: {}", original.id, e);
+                    summary.failed.push((original.id, e.to_string()));
+                    continue;
+                }
+                rerun
+            };
+
+            let queued = async {
+                self.data_persistence.read().await.save_research_workflow(&rerun).await?;
+                self.queue_manager.enqueue_workflow(rerun.clone(), WorkflowPriority::Normal, None).await
+            }.await;
+            if let Err(e) = queued {
+                error!("Failed to re-queue workflow {}: {}", original.id, e);
+                // Release the claim so a later re-run can pick the workflow up
+                original.metadata.remove(bulk_rerun::RERUN_AS_KEY);
+                original.updated_at = Utc::now();
+                if let Err(e) = self.data_persistence.read().await.save_research_workflow(&original).await {
+                    warn!("Could not release the re-run claim on workflow {}: {}", original.id, e);
+                }
+                summary.failed.push((original.id, e.to_string()));
+                continue;
+            }
+            summary.requeued.push(RequeuedWorkflow { original_id: original.id, rerun_id: rerun.id });
+        }
+
+        info!(
+            "Re-run of failed workflows{}: {} matched, {} re-queued, {} skipped, {} failed",
+            if filter.dry_run { " (dry run)" } else { "" },
+            summary.matched, summary.requeued.len(), summary.skipped.len(), summary.failed.len(),
+        );
+        Ok(summary)
+    }
+
+    /// Drive the engine with workflows whose provider calls all go to the mock providers,
+    /// and report the throughput, latency, queueing and errors seen. The workflows the
+    /// test created are deleted afterwards unless the config keeps them.
+    pub async fn run_load_test(&self, config: LoadTestConfig) -> AppResult<LoadTestReport> {
+        use futures::stream::{FuturesUnordered, StreamExt};
+
+        config.validate()?;
+        let load_test_id = Uuid::new_v4();
+        info!(
+            "Starting load test {}: up to {} workflows at {}/s, {} at once, for {}s",
+            load_test_id, config.max_workflows, config.arrival_rate_per_sec, config.concurrency, config.duration_secs,
+        );
+
+        let started_at = Utc::now();
+        let start = tokio::time::Instant::now();
+        let stop_submitting_at = start + std::time::Duration::from_secs(config.duration_secs);
+        let drain_deadline = stop_submitting_at + std::time::Duration::from_secs(config.drain_timeout_secs);
+        let slots = Slots::new(config.concurrency);
+
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs_f64(1.0 / config.arrival_rate_per_sec));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut running = FuturesUnordered::new();
+        let mut outcomes = Vec::with_capacity(config.max_workflows);
+        let mut submitted = 0;
+        loop {
+            let submitting = submitted < config.max_workflows && tokio::time::Instant::now() < stop_submitting_at;
+            tokio::select! {
+                _ = ticker.tick(), if submitting => {
+                    if tokio::time::Instant::now() < stop_submitting_at {
+                        submitted += 1;
+                        running.push(self.run_load_test_workflow(load_test_id, submitted, &config, &slots, drain_deadline));
+                    }
+                }
+                Some(outcome) = running.next() => outcomes.push(outcome),
+                else => break,
+            }
+        }
+        let elapsed_secs = start.elapsed().as_secs_f64();
+
+        let mut report = LoadTestReport::new(load_test_id, config.clone(), started_at, elapsed_secs, &outcomes, slots.depths());
+        if !report.config.keep_workflows {
+            for workflow_id in outcomes.iter().filter_map(|outcome| outcome.workflow_id) {
+                match self.delete_workflow(workflow_id).await {
+                    Ok(()) => report.cleaned_up += 1,
+                    Err(e) => warn!("Load test {} could not delete workflow {}: {}", load_test_id, workflow_id, e),
+                }
+            }
+        }
+
+        info!(
+            "Load test {} finished in {:.1}s: {} submitted, {} completed, {} failed, {} timed out, {} not started, {:.2} workflows/s",
+            load_test_id, report.elapsed_secs, report.submitted, report.completed, report.failed,
+            report.timed_out, report.not_started, report.throughput_per_sec,
+        );
+        Ok(report)
+    }
+
+    /// Run the `number`th workflow of a load test once a slot is free, until it ends or
+    /// `drain_deadline` passes
+    async fn run_load_test_workflow(
+        &self,
+        load_test_id: Uuid,
+        number: usize,
+        config: &LoadTestConfig,
+        slots: &Slots,
+        drain_deadline: tokio::time::Instant,
+    ) -> WorkflowOutcome {
+        let submitted = tokio::time::Instant::now();
+        let _slot = slots.acquire().await;
+        let started = tokio::time::Instant::now();
+        let mut outcome = WorkflowOutcome {
+            workflow_id: None,
+            kind: OutcomeKind::NotStarted,
+            error: None,
+            queue_wait_ms: (started - submitted).as_secs_f64() * 1000.0,
+            run_ms: None,
+        };
+
+        let request = CreateWorkflowRequest {
+            name: format!("Load test {} #{}", load_test_id, number),
+            query: config.query.clone(),
+            template_id: None,
+            parameters: Some(config.workflow_parameters()),
+            data_region: Default::default(),
+            idempotency_key: None,
+        };
+        let workflow_id = match self.create_workflow_from_request(request).await {
+            Ok(workflow) => workflow.id,
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                return outcome;
+            }
+        };
+        outcome.workflow_id = Some(workflow_id);
+
+        // Subscribe before starting so the final snapshot cannot be missed
+        let mut updates = match self.subscribe_workflow_results(workflow_id).await {
+            Ok((updates, _)) => updates,
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                return outcome;
+            }
+        };
+        if let Err(e) = self.start_workflow_execution_with(workflow_id, QuotaCheck::Enforce).await {
+            outcome.error = Some(e.to_string());
+            return outcome;
+        }
+
+        loop {
+            if tokio::time::Instant::now() >= drain_deadline {
+                if let Err(e) = self.cancel_workflow(workflow_id).await {
+                    warn!("Load test {} could not cancel workflow {}: {}", load_test_id, workflow_id, e);
+                }
+                outcome.kind = OutcomeKind::TimedOut;
+                outcome.error = Some("Still running after the drain timeout".to_string());
+                break;
+            }
+
+            // The stream can lag or close, so the saved status decides when it has ended
+            match tokio::time::timeout(std::time::Duration::from_secs(1), updates.recv()).await {
+                Ok(Ok(snapshot)) if snapshot.workflow_id != workflow_id || !snapshot.is_final => continue,
+                Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) => {
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+                _ => {}
+            }
+            let workflow = match self.data_persistence.read().await.get_research_workflow(workflow_id).await {
+                Ok(workflow) => workflow,
+                Err(e) => {
+                    warn!("Load test {} could not read workflow {}: {}", load_test_id, workflow_id, e);
+                    continue;
+                }
+            };
+            match workflow {
+                Some(workflow) if workflow.status == WorkflowStatus::Completed => {
+                    outcome.kind = OutcomeKind::Completed;
+                }
+                Some(workflow) if matches!(workflow.status, WorkflowStatus::Failed | WorkflowStatus::Cancelled) => {
+                    outcome.kind = OutcomeKind::Failed;
+                    outcome.error = Some(workflow.error_message.unwrap_or_else(|| format!("{:?}", workflow.status)));
+                }
+                _ => continue,
+            }
+            outcome.run_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+            break;
+        }
+        outcome
     }
 
     /// Get workflow status
@@ -622,7 +1309,10 @@ impl ResearchEngineService {
                 .count() as f64;
             Ok(Some(completed_steps / total_steps * 100.0))
         } else {
-            Ok(None)
+            drop(active_workflows);
+            // Finished workflows live on in the queue's history, including spilled ones
+            let progress = self.queue_manager.get_workflow_progress(workflow_id).await?;
+            Ok(progress.map(|progress| progress.progress_percentage))
         }
     }
 
@@ -700,6 +1390,16 @@ impl ResearchEngineService {
         self.workflow_engine.cancel_workflow(workflow_id).await
     }
 
+    /// Link the PDF sources methodologies parse into the knowledge graph
+    pub async fn set_knowledge_graph(&self, knowledge_graph: Arc<RwLock<crate::services::knowledge_graph::KnowledgeGraphService>>) {
+        self.workflow_engine.set_knowledge_graph(knowledge_graph).await;
+    }
+
+    /// Check the sources of workflows asking for it for contradictions with the NLP engine
+    pub async fn set_nlp_engine(&self, nlp_engine: Arc<RwLock<crate::services::nlp_engine::NLPEngineService>>) {
+        self.workflow_engine.set_nlp_engine(nlp_engine).await;
+    }
+
     /// Start background monitoring
     pub async fn start_background_monitoring(&self) -> AppResult<()> {
         info!("Starting research engine background monitoring...");
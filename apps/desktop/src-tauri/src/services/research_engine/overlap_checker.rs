@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::{ApiError, AppResult};
+use crate::models::research_workflow::OverlapCheck;
+use crate::services::api_manager::{ApiManagerService, NormalizedPayload, model_router};
+
+/// Result metadata key holding the overlap report
+pub const OVERLAP_METADATA_KEY: &str = "source_overlap";
+
+/// Uncovered words tolerated inside a span before it is split, so a copied passage with
+/// a word or two swapped is still reported as one span
+const MAX_SPAN_GAP_WORDS: usize = 2;
+
+/// Text a report was synthesized from, as extracted by a workflow step
+#[derive(Debug, Clone)]
+pub struct SourceText {
+    pub url: String,
+    pub text: String,
+}
+
+/// A passage of the report that near-verbatim repeats a source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopiedSpan {
+    pub source_url: String,
+    pub text: String,
+    /// Byte range of the span in the report content
+    pub start: usize,
+    pub end: usize,
+    pub word_count: usize,
+    /// Share of the span's words covered by n-grams found in the source
+    pub similarity: f64,
+    #[serde(default)]
+    pub paraphrased: bool,
+}
+
+/// How much of the report one source accounts for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceOverlap {
+    pub source_url: String,
+    /// Share of report words covered by n-grams found in the source
+    pub overlap_ratio: f64,
+    pub flagged_span_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlapReport {
+    pub ngram_size: usize,
+    pub threshold: f64,
+    pub checked_sources: usize,
+    pub max_overlap_ratio: f64,
+    pub sources: Vec<SourceOverlap>,
+    pub flagged_spans: Vec<CopiedSpan>,
+}
+
+impl OverlapReport {
+    pub fn has_flagged_spans(&self) -> bool {
+        !self.flagged_spans.is_empty()
+    }
+}
+
+/// Source texts found in step outputs: scraped pages and search result snippets
+pub fn collect_source_texts(step_results: &[HashMap<String, serde_json::Value>]) -> Vec<SourceText> {
+    let mut texts = Vec::new();
+
+    for result in step_results {
+        if let Some(pages) = result.get("scraped_content").and_then(|v| v.as_array()) {
+            for page in pages {
+                let page = page.get("data").unwrap_or(page);
+                let text = ["markdown", "content", "text"].iter()
+                    .find_map(|key| page.get(*key).and_then(|v| v.as_str()));
+                let url = page.pointer("/metadata/sourceURL")
+                    .or_else(|| page.get("url"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                if let Some(text) = text {
+                    texts.push(SourceText { url: url.to_string(), text: text.to_string() });
+                }
+            }
+        }
+
+        if let Some(hits) = result.get("search_results")
+            .and_then(|v| v.get("organic_results"))
+            .and_then(|v| v.as_array())
+        {
+            for hit in hits {
+                if let (Some(url), Some(snippet)) = (
+                    hit.get("link").and_then(|v| v.as_str()),
+                    hit.get("snippet").and_then(|v| v.as_str()),
+                ) {
+                    texts.push(SourceText { url: url.to_string(), text: snippet.to_string() });
+                }
+            }
+        }
+    }
+
+    texts
+}
+
+/// A report word with its byte range in the report
+struct Word {
+    normalized: String,
+    start: usize,
+    end: usize,
+}
+
+/// Find the report passages that repeat a source near-verbatim
+pub fn check_overlap(content: &str, sources: &[SourceText], settings: &OverlapCheck) -> OverlapReport {
+    let n = settings.ngram_size.max(1);
+    let words = tokenize(content);
+    let mut report = OverlapReport {
+        ngram_size: n,
+        threshold: settings.threshold,
+        checked_sources: sources.len(),
+        max_overlap_ratio: 0.0,
+        sources: Vec::new(),
+        flagged_spans: Vec::new(),
+    };
+    if words.len() < n {
+        return report;
+    }
+
+    for source in sources {
+        let source_words: Vec<String> = tokenize(&source.text).into_iter().map(|w| w.normalized).collect();
+        let source_ngrams: HashSet<&[String]> = source_words.windows(n).collect();
+        if source_ngrams.is_empty() {
+            continue;
+        }
+
+        let mut covered = vec![false; words.len()];
+        for (i, window) in words.windows(n).enumerate() {
+            let key: Vec<String> = window.iter().map(|w| w.normalized.clone()).collect();
+            if source_ngrams.contains(key.as_slice()) {
+                covered[i..i + n].iter_mut().for_each(|c| *c = true);
+            }
+        }
+
+        let covered_count = covered.iter().filter(|c| **c).count();
+        let overlap_ratio = covered_count as f64 / words.len() as f64;
+        let spans: Vec<CopiedSpan> = covered_runs(&covered)
+            .into_iter()
+            .filter_map(|(first, last)| {
+                let word_count = last - first + 1;
+                let similarity = covered[first..=last].iter().filter(|c| **c).count() as f64 / word_count as f64;
+                (word_count >= settings.min_span_words && similarity >= settings.threshold).then(|| CopiedSpan {
+                    source_url: source.url.clone(),
+                    text: content[words[first].start..words[last].end].to_string(),
+                    start: words[first].start,
+                    end: words[last].end,
+                    word_count,
+                    similarity,
+                    paraphrased: false,
+                })
+            })
+            .collect();
+
+        report.max_overlap_ratio = report.max_overlap_ratio.max(overlap_ratio);
+        report.sources.push(SourceOverlap {
+            source_url: source.url.clone(),
+            overlap_ratio,
+            flagged_span_count: spans.len(),
+        });
+        report.flagged_spans.extend(spans);
+    }
+
+    report.flagged_spans.sort_by_key(|span| span.start);
+    debug!("Overlap check flagged {} spans across {} sources", report.flagged_spans.len(), sources.len());
+    report
+}
+
+/// Ranges of covered words, bridging gaps of at most `MAX_SPAN_GAP_WORDS`
+fn covered_runs(covered: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in covered.iter().enumerate().filter(|(_, c)| **c) {
+        match runs.last_mut() {
+            Some((_, last)) if i - *last <= MAX_SPAN_GAP_WORDS + 1 => *last = i,
+            _ => runs.push((i, i)),
+        }
+    }
+    runs
+}
+
+fn tokenize(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                let normalized: String = text[s..i].chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                if !normalized.is_empty() {
+                    words.push(Word { normalized, start: s, end: i });
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    words
+}
+
+/// Rewrite each flagged span in the model's own words, instructed by `system_prompt`, and
+/// splice the rewrites into the report. A span whose rewrite fails stays as it was and
+/// remains flagged.
+pub async fn paraphrase_flagged_spans(
+    content: &str,
+    report: &mut OverlapReport,
+    system_prompt: &str,
+    api_manager: &ApiManagerService,
+) -> String {
+    let mut rewritten = content.to_string();
+    let mut last_start = usize::MAX;
+
+    // Splice from the end of the report so earlier byte ranges stay valid; spans overlapping
+    // an already rewritten one (the same passage copied from several sources) are skipped
+    for span in report.flagged_spans.iter_mut().rev() {
+        if span.end > last_start {
+            continue;
+        }
+        match paraphrase(&span.text, system_prompt, api_manager).await {
+            Ok(paraphrased) => {
+                rewritten.replace_range(span.start..span.end, paraphrased.trim());
+                span.paraphrased = true;
+                last_start = span.start;
+            }
+            Err(e) => warn!("Failed to paraphrase span copied from {}: {}", span.source_url, e),
+        }
+    }
+
+    rewritten
+}
+
+async fn paraphrase(text: &str, system_prompt: &str, api_manager: &ApiManagerService) -> AppResult<String> {
+    let served = api_manager.make_chat_request_with_model_fallback(model_router::REWRITE, |model| {
+        model_router::chat_completion_request(
+            model,
+            system_prompt,
+            text,
+            0.5,
+            1000,
+            30000,
+        )
+    }).await?;
+
+    match served.response.normalized {
+        Some(NormalizedPayload::Completion { content, .. }) if !content.trim().is_empty() => Ok(content),
+        _ => Err(ApiError::request_failed(
+            served.served_by_model,
+            served.response.status_code,
+            "empty paraphrase".to_string(),
+        ).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_overlap_flags_near_verbatim_spans() {
+        let source = SourceText {
+            url: "https://example.org/batteries".to_string(),
+            text: "Solid state batteries replace the liquid electrolyte with a solid ceramic or polymer layer, which removes the main fire risk of lithium ion cells and allows denser packs.".to_string(),
+        };
+        let content = "## Summary\nResearchers agree on one point. Solid state batteries replace the liquid electrolyte with a solid ceramic or glass layer, which removes the main fire risk of lithium ion cells and allows denser packs. Costs are still falling slowly.";
+
+        let report = check_overlap(content, &[source], &OverlapCheck::default());
+        assert_eq!(report.flagged_spans.len(), 1);
+        let span = &report.flagged_spans[0];
+        assert!(span.text.starts_with("Solid state batteries"));
+        assert!(span.text.ends_with("denser packs."));
+        assert_eq!(&content[span.start..span.end], span.text);
+        assert!(span.similarity >= 0.8 && span.similarity < 1.0);
+        assert!(report.sources[0].overlap_ratio > 0.5);
+
+        let paraphrased = "## Summary\nSwapping the liquid electrolyte for a solid layer removes most of the fire risk and lets cells pack more energy.";
+        let source = SourceText { url: "u".to_string(), text: content.to_string() };
+        assert!(!check_overlap(paraphrased, &[source], &OverlapCheck::default()).has_flagged_spans());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::models::knowledge_graph::{DataFormat, ExtractedDocument};
+use crate::models::research_workflow::WorkflowStep;
+use crate::services::api_manager::egress;
+use crate::services::knowledge_graph::KnowledgeGraphService;
+use crate::services::research_engine::image_ocr::STEP_TYPE_KEY;
+use crate::services::research_engine::workflow_engine::ExecutionContext;
+use crate::utils::inject_trace_context;
+
+/// Step type that parses PDF sources and links their references into the knowledge graph
+pub const PDF_EXTRACTION: &str = "pdf_extraction";
+
+/// PDF URLs to parse, besides the PDF links among the search results
+pub const DOCUMENT_SOURCES_KEY: &str = "document_sources";
+/// The step's per-document results: graph node, structure counts and quality
+pub const EXTRACTED_DOCUMENTS_KEY: &str = "extracted_documents";
+
+const MAX_DOCUMENTS: usize = 10;
+const MAX_DOCUMENT_BYTES: usize = 20 * 1024 * 1024;
+const DOCUMENT_TIMEOUT_MS: u32 = 60_000;
+
+/// The knowledge graph PDF steps link documents into, once the service manager has
+/// made it. Empty until then, and in tests.
+pub type KnowledgeGraphSlot = Arc<RwLock<Option<Arc<RwLock<KnowledgeGraphService>>>>>;
+
+/// Whether `step` is a PDF extraction step
+pub fn is_pdf_extraction_step(step: &WorkflowStep) -> bool {
+    step.input_data.get(STEP_TYPE_KEY).and_then(|v| v.as_str()) == Some(PDF_EXTRACTION)
+}
+
+/// Whether `url` names a PDF file
+pub fn is_pdf_url(url: &str) -> bool {
+    url::Url::parse(url).ok()
+        .and_then(|parsed| parsed.path().rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()))
+        .is_some_and(|ext| ext == "pdf")
+}
+
+/// The documents a PDF step parses: those it was given, then the PDF links among the
+/// search results
+pub fn document_sources(context: &ExecutionContext) -> Vec<String> {
+    let given = context.input_data.get(DOCUMENT_SOURCES_KEY)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str());
+    let searched = context.shared_data.get("search_results")
+        .and_then(|v| v.get("organic_results"))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|result| result.get("link").and_then(|v| v.as_str()))
+        .filter(|link| is_pdf_url(link));
+
+    let mut sources: Vec<String> = Vec::new();
+    for source in given.chain(searched) {
+        if !sources.iter().any(|s| s == source) {
+            sources.push(source.to_string());
+        }
+    }
+    sources.truncate(MAX_DOCUMENTS);
+    sources
+}
+
+/// The parsed document in the shape of a scraped page, so analysis and synthesis use
+/// it like any other content
+pub fn as_scraped_content(source: &str, document: &ExtractedDocument) -> serde_json::Value {
+    let mut markdown = String::new();
+    if let Some(title) = &document.title {
+        markdown.push_str(&format!("# {}\n\n", title));
+    }
+    if let Some(abstract_text) = &document.abstract_text {
+        markdown.push_str(&format!("## Abstract\n\n{}\n\n", abstract_text));
+    }
+    for section in &document.sections {
+        let heading = match &section.number {
+            Some(number) => format!("{} {}", number, section.heading),
+            None => section.heading.clone(),
+        };
+        markdown.push_str(&format!("{} {}\n\n{}\n\n", "#".repeat(section.level as usize + 1), heading, section.text));
+    }
+    serde_json::json!({
+        "url": source,
+        "markdown": markdown.trim_end(),
+        "metadata": {
+            "sourceURL": source,
+            "title": document.title.clone().unwrap_or_else(|| format!("Document {}", source)),
+        },
+    })
+}
+
+async fn fetch_document(client: &reqwest::Client, source: &str) -> AppResult<Vec<u8>> {
+    let response = inject_trace_context(client.get(source)).send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::external_service("document", format!("Fetching {} failed with status {}", source, response.status())));
+    }
+    if response.content_length().is_some_and(|length| length as usize > MAX_DOCUMENT_BYTES) {
+        return Err(AppError::validation("document", format!("{} is larger than {} bytes", source, MAX_DOCUMENT_BYTES)));
+    }
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_DOCUMENT_BYTES {
+        return Err(AppError::validation("document", format!("{} is larger than {} bytes", source, MAX_DOCUMENT_BYTES)));
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Run a `pdf_extraction` step. Each PDF is parsed by the knowledge graph's content
+/// extractors and linked into the graph with the works it cites; its text is added to
+/// `scraped_content`, and each document is reported under `extracted_documents`.
+pub async fn execute_pdf_extraction_step(
+    context: &ExecutionContext,
+    knowledge_graph: &KnowledgeGraphSlot,
+) -> AppResult<HashMap<String, serde_json::Value>> {
+    let sources = document_sources(context);
+    debug!("Parsing {} PDF sources", sources.len());
+
+    let mut scraped_content = context.shared_data.get("scraped_content")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut extracted = Vec::new();
+    let knowledge_graph = knowledge_graph.read().await.clone();
+    match &knowledge_graph {
+        None if !sources.is_empty() => warn!("No knowledge graph to link {} PDF sources into", sources.len()),
+        None => {}
+        Some(knowledge_graph) => {
+            let default_client = egress::client_builder(DOCUMENT_TIMEOUT_MS).build()?;
+            let client = egress::client_for(&default_client, DOCUMENT_TIMEOUT_MS)?;
+            for source in &sources {
+                let content = match fetch_document(&client, source).await {
+                    Ok(content) => content,
+                    Err(e) => {
+                        warn!("Failed to fetch document {}: {}", source, e);
+                        continue;
+                    }
+                };
+                let document = match knowledge_graph.read().await.extract_document(Some(source.clone()), DataFormat::Pdf, content).await {
+                    Ok(document) => document,
+                    Err(e) => {
+                        warn!("Failed to extract document {}: {}", source, e);
+                        continue;
+                    }
+                };
+                if !document.quality.needs_ocr {
+                    scraped_content.push(as_scraped_content(source, &document));
+                }
+                extracted.push(serde_json::json!({
+                    "source": source,
+                    "node_id": document.node_id,
+                    "title": document.title,
+                    "section_count": document.sections.len(),
+                    "reference_count": document.citations.len(),
+                    "linked_references": document.citations.iter().filter(|c| c.node_id.is_some()).count(),
+                    "quality": document.quality,
+                }));
+            }
+        }
+    }
+
+    let mut results = HashMap::new();
+    results.insert("scraped_content".to_string(), serde_json::Value::Array(scraped_content));
+    results.insert(EXTRACTED_DOCUMENTS_KEY.to_string(), serde_json::Value::Array(extracted));
+    results.insert("methodology_step".to_string(), serde_json::Value::String(PDF_EXTRACTION.to_string()));
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::knowledge_graph::{DocumentSection, ExtractionQuality};
+    use crate::services::research_engine::context_assembler::ContextItem;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_pdf_sources_and_scraped_content() {
+        let mut input_data = HashMap::new();
+        input_data.insert(DOCUMENT_SOURCES_KEY.to_string(), serde_json::json!(["https://arxiv.org/pdf/1706.03762.pdf"]));
+        let mut shared_data = HashMap::new();
+        shared_data.insert("search_results".to_string(), serde_json::json!({
+            "organic_results": [
+                { "link": "https://example.org/paper.html" },
+                { "link": "https://arxiv.org/pdf/1706.03762.pdf" },
+                { "link": "https://example.org/reports/annual.PDF?download=1" },
+            ]
+        }));
+        shared_data.insert("scraped_content".to_string(), serde_json::json!([{ "url": "https://example.org/paper.html", "markdown": "page" }]));
+        let context = ExecutionContext {
+            workflow_id: Uuid::new_v4(),
+            step_id: Uuid::new_v4(),
+            input_data,
+            shared_data,
+            metadata: HashMap::new(),
+        };
+
+        // Given sources first, then PDF links from the search, without duplicates
+        assert_eq!(document_sources(&context), vec![
+            "https://arxiv.org/pdf/1706.03762.pdf".to_string(),
+            "https://example.org/reports/annual.PDF?download=1".to_string(),
+        ]);
+
+        // Without a knowledge graph the step leaves the scraped pages as they were
+        let results = execute_pdf_extraction_step(&context, &KnowledgeGraphSlot::default()).await.unwrap();
+        assert_eq!(results["scraped_content"].as_array().unwrap().len(), 1);
+        assert_eq!(results[EXTRACTED_DOCUMENTS_KEY], serde_json::json!([]));
+
+        let document = ExtractedDocument {
+            extractor: "academic_pdf".to_string(),
+            title: Some("Attention Is All You Need".to_string()),
+            abstract_text: Some("We propose the Transformer.".to_string()),
+            sections: vec![DocumentSection {
+                number: Some("3.2".to_string()),
+                heading: "Attention".to_string(),
+                level: 2,
+                text: "Scaled dot-product attention.".to_string(),
+            }],
+            figures: Vec::new(),
+            citations: Vec::new(),
+            quality: ExtractionQuality {
+                score: 0.9,
+                page_count: 15,
+                text_chars: 40_000,
+                two_column_pages: 0,
+                needs_ocr: false,
+                warnings: Vec::new(),
+            },
+            node_id: None,
+        };
+        let items = ContextItem::from_scraped_content(&serde_json::json!([as_scraped_content("https://arxiv.org/pdf/1706.03762.pdf", &document)]));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "https://arxiv.org/pdf/1706.03762.pdf");
+        assert!(items[0].text.starts_with("# Attention Is All You Need"));
+        assert!(items[0].text.contains("### 3.2 Attention\n\nScaled dot-product attention."));
+    }
+}
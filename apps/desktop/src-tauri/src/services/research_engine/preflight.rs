@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::api_key::ServiceProvider;
+use crate::models::research_workflow::{ProviderRecording, ResearchWorkflow, StepStatus, WorkflowStep};
+use crate::services::api_manager::ServiceCapacity;
+
+/// Whether starting a workflow first checks that its providers have quota left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaCheck {
+    /// Refuse to start when a required service cannot serve the estimated calls
+    #[default]
+    Enforce,
+    /// Start anyway; steps that run out of quota fail as they reach their provider
+    Override,
+}
+
+/// A service the workflow needs more from than its keys can give
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaShortfall {
+    pub service: ServiceProvider,
+    pub estimated_calls: u32,
+    pub remaining_requests: u32,
+    pub usable_keys: u32,
+}
+
+/// Provider calls a step is expected to make: one per page for steps with a page
+/// `limit`, such as crawls, otherwise one
+pub fn estimated_calls(step: &WorkflowStep) -> u32 {
+    step.input_data.get("limit")
+        .and_then(|limit| limit.as_u64())
+        .map_or(1, |limit| limit.clamp(1, u32::MAX as u64) as u32)
+}
+
+/// Estimated calls per service for the steps the workflow has still to run, in the order
+/// the services are first used. A replayed or mocked workflow calls no provider and needs none.
+pub fn estimate_provider_calls(workflow: &ResearchWorkflow) -> Vec<(ServiceProvider, u32)> {
+    let mut demand: Vec<(ServiceProvider, u32)> = Vec::new();
+    if matches!(workflow.parameters.provider_recording, ProviderRecording::Replay { .. } | ProviderRecording::Mock { .. }) {
+        return demand;
+    }
+
+    for step in workflow.steps.iter().filter(|step| !matches!(step.status, StepStatus::Completed | StepStatus::Skipped)) {
+        let Some(service) = step.service_provider.as_deref().and_then(ServiceProvider::from_str) else {
+            continue;
+        };
+        match demand.iter_mut().find(|(known, _)| *known == service) {
+            Some((_, calls)) => *calls = calls.saturating_add(estimated_calls(step)),
+            None => demand.push((service, estimated_calls(step))),
+        }
+    }
+    demand
+}
+
+/// Services whose capacity is below the estimated demand; a service with no reported
+/// capacity counts as having none
+pub fn shortfalls(demand: &[(ServiceProvider, u32)], capacities: &[ServiceCapacity]) -> Vec<QuotaShortfall> {
+    demand.iter()
+        .filter_map(|(&service, &estimated_calls)| {
+            let capacity = capacities.iter().find(|capacity| capacity.service == service);
+            let (remaining_requests, usable_keys) = capacity.map_or((0, 0), |c| (c.remaining_requests, c.usable_keys));
+            (usable_keys == 0 || remaining_requests < estimated_calls).then_some(QuotaShortfall {
+                service,
+                estimated_calls,
+                remaining_requests,
+                usable_keys,
+            })
+        })
+        .collect()
+}
+
+/// One clause per service, for the error returned to the user
+pub fn describe_shortfalls(shortfalls: &[QuotaShortfall]) -> String {
+    shortfalls.iter()
+        .map(|shortfall| if shortfall.usable_keys == 0 {
+            format!("{} has no usable API keys (needs about {} requests)",
+                shortfall.service.display_name(), shortfall.estimated_calls)
+        } else {
+            format!("{} needs about {} requests but its {} usable key(s) have {} left",
+                shortfall.service.display_name(), shortfall.estimated_calls,
+                shortfall.usable_keys, shortfall.remaining_requests)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+    use crate::models::research_workflow::WorkflowParameters;
+    use crate::services::research_engine::methodology_don_lim::DonLimMethodology;
+    use crate::services::research_engine::workflow_engine::WorkflowExecutor;
+
+    fn step(workflow_id: Uuid, number: u32, provider: &str, limit: Option<u64>) -> WorkflowStep {
+        let mut step = WorkflowStep::new(workflow_id, number, format!("step {}", number), String::new());
+        step.service_provider = Some(provider.to_string());
+        if let Some(limit) = limit {
+            step.input_data.insert("limit".to_string(), serde_json::json!(limit));
+        }
+        step
+    }
+
+    #[test]
+    fn test_shortfalls_name_each_service_that_cannot_cover_the_run() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid storage costs".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        let id = workflow.id;
+        let mut done = step(id, 4, "jina", None);
+        done.status = StepStatus::Completed;
+        workflow.steps = vec![
+            step(id, 1, "serpapi", None),
+            step(id, 2, "firecrawl", Some(30)),
+            step(id, 3, "openrouter", None),
+            done,
+        ];
+
+        let demand = estimate_provider_calls(&workflow);
+        assert_eq!(demand, vec![
+            (ServiceProvider::SerpApi, 1), (ServiceProvider::Firecrawl, 30), (ServiceProvider::OpenRouter, 1),
+        ]);
+
+        let capacities = [
+            ServiceCapacity { service: ServiceProvider::SerpApi, usable_keys: 1, remaining_requests: 100 },
+            ServiceCapacity { service: ServiceProvider::Firecrawl, usable_keys: 2, remaining_requests: 12 },
+        ];
+        let found = shortfalls(&demand, &capacities);
+        assert_eq!(found.iter().map(|s| s.service).collect::<Vec<_>>(), vec![ServiceProvider::Firecrawl, ServiceProvider::OpenRouter]);
+        assert_eq!(found[1].usable_keys, 0);
+        assert!(describe_shortfalls(&found).contains("needs about 30 requests but its 2 usable key(s) have 12 left"));
+
+        workflow.parameters.provider_recording = ProviderRecording::Replay { source_workflow_id: Uuid::new_v4() };
+        assert!(estimate_provider_calls(&workflow).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prepared_steps_come_up_short_when_a_provider_is_out_of_quota() {
+        let mut workflow = ResearchWorkflow::new(
+            "Grid storage".to_string(),
+            "grid storage costs".to_string(),
+            WorkflowParameters::default(),
+            "tester".to_string(),
+        );
+        // A newly created workflow has no steps until its methodology prepares them
+        assert!(estimate_provider_calls(&workflow).is_empty());
+
+        DonLimMethodology::new().prepare_steps(&mut workflow).await.unwrap();
+        let demand = estimate_provider_calls(&workflow);
+        assert!(demand.iter().any(|(service, _)| *service == ServiceProvider::SerpApi));
+
+        let capacities: Vec<ServiceCapacity> = demand.iter()
+            .map(|&(service, _)| ServiceCapacity {
+                service,
+                usable_keys: 1,
+                remaining_requests: if service == ServiceProvider::SerpApi { 0 } else { 1_000 },
+            })
+            .collect();
+        let found = shortfalls(&demand, &capacities);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].service, ServiceProvider::SerpApi);
+        assert_eq!(found[0].remaining_requests, 0);
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::Utc;
+use tokio::sync::RwLock;
+use tracing::{info, debug};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ResearchError};
+use crate::models::prompt_template::{
+    ExperimentArm, ExperimentComparison, PromptExperiment, PromptTemplate,
+    SavePromptTemplateRequest, StartPromptExperimentRequest,
+};
+use crate::models::research_workflow::ResearchResults;
+use crate::services::DataPersistenceService;
+use super::workflow_engine::ExecutionContext;
+
+/// Step input key naming the prompt template an LLM step runs with
+pub const PROMPT_ID_KEY: &str = "prompt_id";
+/// Step input key pinning a prompt version; steps without one follow the latest
+/// version, or the experiment running on the prompt
+pub const PROMPT_VERSION_KEY: &str = "prompt_version";
+/// Step input key holding the rendered system prompt, filled in by the workflow engine
+pub const SYSTEM_PROMPT_KEY: &str = "system_prompt";
+/// Step metadata key recording which experiment arm served the step. The prompt id and
+/// version that served are recorded under `PROMPT_ID_KEY` and `PROMPT_VERSION_KEY`.
+pub const PROMPT_ARM_KEY: &str = "prompt_arm";
+
+pub const DON_LIM_ANALYSIS: &str = "don_lim_analysis";
+pub const NICK_SCAMARA_SYNTHESIS: &str = "nick_scamara_synthesis";
+pub const HYBRID_SYNTHESIS: &str = "hybrid_synthesis";
+pub const ACADEMIC_ANALYSIS: &str = "academic_analysis";
+pub const OVERLAP_REWRITE: &str = "overlap_rewrite";
+
+/// Version 1 of each prompt the methodologies ship with
+fn builtin_templates() -> Vec<PromptTemplate> {
+    vec![
+        PromptTemplate::new(
+            DON_LIM_ANALYSIS, 1,
+            "Don Lim analysis of search results and embeddings",
+            "You are a research analyst. Analyze the provided search results and embeddings to create a comprehensive research report. Focus on accuracy, relevance, and actionable insights.",
+        ),
+        PromptTemplate::new(
+            NICK_SCAMARA_SYNTHESIS, 1,
+            "Nick Scamara synthesis of scraped and mapped content",
+            "You are a professional research analyst specializing in comprehensive content synthesis. Create detailed, well-structured research reports with professional formatting and actionable insights.",
+        ),
+        PromptTemplate::new(
+            HYBRID_SYNTHESIS, 1,
+            "Hybrid synthesis across all gathered material",
+            "You are an expert research analyst specializing in comprehensive multi-source research synthesis. You excel at combining data from multiple methodologies (search engines, web scraping, content analysis, and content mapping) to create authoritative, well-structured research reports.",
+        ),
+        PromptTemplate::new(
+            ACADEMIC_ANALYSIS, 1,
+            "Academic analysis of scholarly sources",
+            "You are an academic researcher. Analyze the provided sources with scholarly rigor.",
+        ),
+        PromptTemplate::new(
+            OVERLAP_REWRITE, 1,
+            "Rewrite of report passages copied from a source",
+            "You rewrite passages of a research report that were copied from a source. Keep every fact, number and qualifier, change the wording and sentence structure, and reply with the rewritten passage only.",
+        ),
+    ]
+}
+
+/// A prompt rendered for one step
+#[derive(Debug, Clone)]
+pub struct ResolvedPrompt {
+    pub template: PromptTemplate,
+    /// Set when an experiment on the prompt picked the version
+    pub arm: Option<ExperimentArm>,
+    pub text: String,
+}
+
+/// The system prompt resolved for a step, or version 1 of `prompt_id` when the step
+/// runs outside the workflow engine
+pub fn system_prompt(context: &ExecutionContext, prompt_id: &str) -> String {
+    context.input_data.get(SYSTEM_PROMPT_KEY)
+        .and_then(|prompt| prompt.as_str())
+        .map(str::to_string)
+        .or_else(|| builtin_templates().into_iter()
+            .find(|template| template.id == prompt_id)
+            .map(|template| template.template))
+        .unwrap_or_default()
+}
+
+/// Named, versioned system prompts for LLM steps, with A/B experiments between versions
+pub struct PromptLibrary {
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+    /// Every version of each prompt, oldest first
+    templates: RwLock<HashMap<String, Vec<PromptTemplate>>>,
+    experiments: RwLock<HashMap<String, PromptExperiment>>,
+}
+
+impl PromptLibrary {
+    /// Load stored prompts and experiments. Built-in prompts missing from storage are
+    /// saved as version 1, so later edits to the defaults never rewrite a past version.
+    pub async fn new(data_persistence: Arc<RwLock<DataPersistenceService>>) -> AppResult<Self> {
+        let (stored, experiments) = {
+            let persistence = data_persistence.read().await;
+            (persistence.get_prompt_templates().await?, persistence.get_prompt_experiments().await?)
+        };
+
+        let mut templates: HashMap<String, Vec<PromptTemplate>> = HashMap::new();
+        for template in stored {
+            templates.entry(template.id.clone()).or_default().push(template);
+        }
+        for builtin in builtin_templates() {
+            if !templates.contains_key(&builtin.id) {
+                data_persistence.read().await.save_prompt_template(&builtin).await?;
+                templates.insert(builtin.id.clone(), vec![builtin]);
+            }
+        }
+        for versions in templates.values_mut() {
+            versions.sort_by_key(|template| template.version);
+        }
+
+        info!("Loaded {} prompt templates", templates.len());
+        Ok(Self {
+            data_persistence,
+            templates: RwLock::new(templates),
+            experiments: RwLock::new(experiments.into_iter().map(|e| (e.prompt_id.clone(), e)).collect()),
+        })
+    }
+
+    /// Latest version of every prompt
+    pub async fn list_templates(&self) -> Vec<PromptTemplate> {
+        let templates = self.templates.read().await;
+        let mut latest: Vec<PromptTemplate> = templates.values()
+            .filter_map(|versions| versions.last().cloned())
+            .collect();
+        latest.sort_by(|a, b| a.id.cmp(&b.id));
+        latest
+    }
+
+    /// Every version of a prompt, oldest first
+    pub async fn get_versions(&self, prompt_id: &str) -> AppResult<Vec<PromptTemplate>> {
+        self.templates.read().await.get(prompt_id)
+            .cloned()
+            .ok_or_else(|| ResearchError::prompt_not_found(prompt_id).into())
+    }
+
+    /// Save the request as the next version of its prompt, or version 1 of a new one
+    pub async fn save_template(&self, request: SavePromptTemplateRequest) -> AppResult<PromptTemplate> {
+        let id = request.id.trim();
+        if id.is_empty() || request.template.trim().is_empty() {
+            return Err(AppError::validation("prompt", "Prompt id and template are required"));
+        }
+
+        let mut templates = self.templates.write().await;
+        let versions = templates.entry(id.to_string()).or_default();
+        let mut template = PromptTemplate::new(
+            id,
+            versions.last().map_or(1, |latest| latest.version + 1),
+            &request.description,
+            &request.template,
+        );
+        template.defaults = request.defaults;
+
+        self.data_persistence.read().await.save_prompt_template(&template).await?;
+        versions.push(template.clone());
+
+        info!("Saved prompt {}", template.label());
+        Ok(template)
+    }
+
+    /// Render the version of `prompt_id` a step of `workflow_id` runs with: the pinned
+    /// version if there is one, otherwise the experiment's pick or the latest version
+    pub async fn resolve(
+        &self,
+        prompt_id: &str,
+        pinned_version: Option<u32>,
+        workflow_id: Uuid,
+        variables: &HashMap<String, String>,
+    ) -> AppResult<ResolvedPrompt> {
+        let (version, arm) = match pinned_version {
+            Some(version) => (Some(version), None),
+            None => match self.experiments.read().await.get(prompt_id).filter(|e| e.active) {
+                Some(experiment) => {
+                    let arm = experiment.arm_for(workflow_id);
+                    (Some(experiment.version_for(arm)), Some(arm))
+                }
+                None => (None, None),
+            },
+        };
+
+        let templates = self.templates.read().await;
+        let versions = templates.get(prompt_id)
+            .ok_or_else(|| ResearchError::prompt_not_found(prompt_id))?;
+        let template = match version {
+            Some(version) => versions.iter().find(|t| t.version == version),
+            None => versions.last(),
+        }.ok_or_else(|| ResearchError::prompt_not_found(format!("{}@{}", prompt_id, version.unwrap_or_default())))?;
+
+        let text = template.render(variables)?;
+        debug!("Resolved prompt {} for workflow {}", template.label(), workflow_id);
+        Ok(ResolvedPrompt { template: template.clone(), arm, text })
+    }
+
+    /// Start routing `variant_share` of runs of a prompt to another of its versions,
+    /// replacing any earlier experiment on the prompt
+    pub async fn start_experiment(&self, request: StartPromptExperimentRequest) -> AppResult<PromptExperiment> {
+        if !(0.0..=1.0).contains(&request.variant_share) {
+            return Err(ResearchError::invalid_prompt_experiment("variant_share must be between 0 and 1").into());
+        }
+
+        let versions = self.get_versions(&request.prompt_id).await?;
+        let latest = versions.last().map_or(1, |t| t.version);
+        let control_version = request.control_version.unwrap_or(latest);
+        for version in [control_version, request.variant_version] {
+            if !versions.iter().any(|t| t.version == version) {
+                return Err(ResearchError::prompt_not_found(format!("{}@{}", request.prompt_id, version)).into());
+            }
+        }
+        if control_version == request.variant_version {
+            return Err(ResearchError::invalid_prompt_experiment("Control and variant must be different versions").into());
+        }
+
+        let experiment = PromptExperiment {
+            prompt_id: request.prompt_id.clone(),
+            control_version,
+            variant_version: request.variant_version,
+            variant_share: request.variant_share,
+            active: true,
+            control: Default::default(),
+            variant: Default::default(),
+            started_at: Utc::now(),
+            stopped_at: None,
+        };
+        self.data_persistence.read().await.save_prompt_experiment(&experiment).await?;
+        self.experiments.write().await.insert(experiment.prompt_id.clone(), experiment.clone());
+
+        info!(
+            "Started prompt experiment on {}: v{} vs v{} at {:.0}%",
+            experiment.prompt_id, control_version, experiment.variant_version, experiment.variant_share * 100.0
+        );
+        Ok(experiment)
+    }
+
+    /// Stop routing runs to the variant; the metrics gathered so far are kept
+    pub async fn stop_experiment(&self, prompt_id: &str) -> AppResult<PromptExperiment> {
+        let mut experiments = self.experiments.write().await;
+        let experiment = experiments.get_mut(prompt_id)
+            .ok_or_else(|| ResearchError::invalid_prompt_experiment(format!("No experiment on prompt '{}'", prompt_id)))?;
+        experiment.active = false;
+        experiment.stopped_at = Some(Utc::now());
+        self.data_persistence.read().await.save_prompt_experiment(experiment).await?;
+
+        info!("Stopped prompt experiment on {}", prompt_id);
+        Ok(experiment.clone())
+    }
+
+    /// Add a finished run to the metrics of the arm that served it; `results` is `None`
+    /// for runs that failed
+    pub async fn record_outcome(
+        &self,
+        prompt_id: &str,
+        arm: ExperimentArm,
+        results: Option<&ResearchResults>,
+    ) -> AppResult<()> {
+        let mut experiments = self.experiments.write().await;
+        let Some(experiment) = experiments.get_mut(prompt_id).filter(|e| e.active) else {
+            return Ok(());
+        };
+        experiment.metrics_mut(arm).record(results);
+        self.data_persistence.read().await.save_prompt_experiment(experiment).await
+    }
+
+    /// Quality metrics of each experiment's arms side by side
+    pub async fn get_experiments(&self) -> Vec<ExperimentComparison> {
+        let experiments = self.experiments.read().await;
+        let mut comparisons: Vec<ExperimentComparison> = experiments.values()
+            .map(|experiment| experiment.comparison())
+            .collect();
+        comparisons.sort_by(|a, b| a.prompt_id.cmp(&b.prompt_id));
+        comparisons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompts_render_and_experiments_split_runs() {
+        let mut template = PromptTemplate::new("summary", 2, "", "Summarize {{ topic }} for {{audience}}. Cover {{topic}} fully.");
+        template.defaults.insert("audience".to_string(), "researchers".to_string());
+        assert_eq!(template.variables(), vec!["topic".to_string(), "audience".to_string()]);
+
+        let variables = HashMap::from([("topic".to_string(), "batteries".to_string())]);
+        assert_eq!(
+            template.render(&variables).unwrap(),
+            "Summarize batteries for researchers. Cover batteries fully."
+        );
+        assert!(template.render(&HashMap::new()).is_err());
+        assert!(builtin_templates().iter().all(|t| t.render(&HashMap::new()).is_ok()));
+
+        let experiment = PromptExperiment {
+            prompt_id: "summary".to_string(),
+            control_version: 1,
+            variant_version: 2,
+            variant_share: 0.25,
+            active: true,
+            control: Default::default(),
+            variant: Default::default(),
+            started_at: Utc::now(),
+            stopped_at: None,
+        };
+        let workflows: Vec<Uuid> = (0..2000).map(|_| Uuid::new_v4()).collect();
+        let variant_runs = workflows.iter().filter(|id| experiment.arm_for(**id) == ExperimentArm::Variant).count();
+        assert!((350..650).contains(&variant_runs), "{} variant runs", variant_runs);
+        assert_eq!(experiment.arm_for(workflows[0]), experiment.arm_for(workflows[0]));
+
+        let mut metrics = experiment.control.clone();
+        metrics.record(None);
+        assert_eq!(metrics.summary().completion_rate, 0.0);
+    }
+}
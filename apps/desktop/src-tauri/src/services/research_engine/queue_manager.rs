@@ -8,13 +8,18 @@ use serde::{Serialize, Deserialize};
 
 use crate::error::{AppResult, ResearchError};
 use crate::models::research_workflow::{ResearchWorkflow, WorkflowStatus, StepStatus};
+use crate::services::DataPersistenceService;
+use crate::services::data_persistence::{BoundedHistory, HistoryEntry, HistoryLimit};
+
+/// Finished workflows kept in memory unless `FDR_HISTORY_WORKFLOW_HISTORY_MAX` says otherwise
+pub const DEFAULT_WORKFLOW_HISTORY_SIZE: usize = 100;
 
 /// Queue manager for research workflow execution
 pub struct QueueManager {
     queue: Arc<Mutex<VecDeque<QueuedWorkflow>>>,
     active_workflows: Arc<RwLock<HashMap<Uuid, QueuedWorkflow>>>,
     max_concurrent: Arc<RwLock<usize>>,
-    workflow_history: Arc<RwLock<Vec<QueuedWorkflow>>>,
+    workflow_history: Arc<RwLock<BoundedHistory<QueuedWorkflow>>>,
     is_processing: Arc<RwLock<bool>>,
     queue_state: Arc<RwLock<QueueState>>,
     last_state_change: Arc<RwLock<DateTime<Utc>>>,
@@ -36,6 +41,12 @@ pub struct QueuedWorkflow {
     pub max_retries: u32,
 }
 
+impl HistoryEntry for QueuedWorkflow {
+    fn recorded_at(&self) -> DateTime<Utc> {
+        self.workflow.completed_at.unwrap_or(self.workflow.updated_at)
+    }
+}
+
 /// Workflow priority levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum WorkflowPriority {
@@ -54,7 +65,10 @@ impl QueueManager {
             queue: Arc::new(Mutex::new(VecDeque::new())),
             active_workflows: Arc::new(RwLock::new(HashMap::new())),
             max_concurrent: Arc::new(RwLock::new(max_concurrent)),
-            workflow_history: Arc::new(RwLock::new(Vec::new())),
+            workflow_history: Arc::new(RwLock::new(BoundedHistory::new(
+                "workflow_history",
+                HistoryLimit::from_env("workflow_history", DEFAULT_WORKFLOW_HISTORY_SIZE),
+            ))),
             is_processing: Arc::new(RwLock::new(false)),
             queue_state: Arc::new(RwLock::new(QueueState::Stopped)),
             last_state_change: Arc::new(RwLock::new(Utc::now())),
@@ -77,6 +91,21 @@ impl QueueManager {
         info!("Queue manager initialized successfully");
         Ok(manager)
     }
+
+    /// Spill finished workflows beyond the in-memory history to `store` when the
+    /// history limit asks for it
+    pub fn with_history_store(self, store: Arc<RwLock<DataPersistenceService>>) -> Self {
+        let limit = HistoryLimit::from_env("workflow_history", DEFAULT_WORKFLOW_HISTORY_SIZE);
+        Self {
+            workflow_history: Arc::new(RwLock::new(BoundedHistory::new("workflow_history", limit).with_store(store))),
+            ..self
+        }
+    }
+
+    /// Change how many finished workflows are kept in memory and whether older ones are spilled
+    pub async fn set_history_limit(&self, limit: HistoryLimit) {
+        self.workflow_history.write().await.set_limit(limit).await;
+    }
     
     /// Add a workflow to the queue
     pub async fn enqueue_workflow(
@@ -163,12 +192,7 @@ impl QueueManager {
             
             // Add to history
             let mut history = self.workflow_history.write().await;
-            history.push(queued_workflow.clone());
-            
-            // Keep only last 100 workflows in history
-            if history.len() > 100 {
-                history.remove(0);
-            }
+            history.push(queued_workflow).await;
             drop(history);
             
             info!("Workflow completed and moved to history: {}", workflow_id);
@@ -215,13 +239,14 @@ impl QueueManager {
                 // Max retries exceeded, move to history as failed
                 queued_workflow.workflow.status = WorkflowStatus::Failed;
                 drop(active_workflows);
+                let max_retries = queued_workflow.max_retries;
                 
                 let mut history = self.workflow_history.write().await;
-                history.push(queued_workflow);
+                history.push(queued_workflow).await;
                 drop(history);
                 
                 error!("Workflow failed permanently after {} retries: {}", 
-                    queued_workflow.max_retries, workflow_id);
+                    max_retries, workflow_id);
                 
                 Ok(false) // Workflow failed permanently
             }
@@ -243,7 +268,7 @@ impl QueueManager {
                 drop(active_workflows);
                 
                 let mut history = self.workflow_history.write().await;
-                history.push(queued_workflow);
+                history.push(queued_workflow).await;
                 drop(history);
                 
                 info!("Active workflow cancelled: {}", workflow_id);
@@ -260,7 +285,7 @@ impl QueueManager {
                 drop(queue);
                 
                 let mut history = self.workflow_history.write().await;
-                history.push(queued_workflow);
+                history.push(queued_workflow).await;
                 drop(history);
                 
                 info!("Queued workflow cancelled: {}", workflow_id);
@@ -318,16 +343,10 @@ impl QueueManager {
         Ok(queue.iter().cloned().collect())
     }
     
-    /// Get workflow history
+    /// Get workflow history, newest first, including spilled workflows beyond the in-memory window
     pub async fn get_workflow_history(&self, limit: Option<usize>) -> AppResult<Vec<QueuedWorkflow>> {
         let history = self.workflow_history.read().await;
-        let limit = limit.unwrap_or(50);
-
-        Ok(history.iter()
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect())
+        history.recent(limit.unwrap_or(50)).await
     }
 
     /// Update maximum concurrent workflows (configurable parallelism)
@@ -455,6 +474,8 @@ impl QueueManager {
                     started_at: step.started_at,
                     completed_at: step.completed_at,
                     error_message: step.error.clone(),
+                    elapsed_seconds: step.elapsed_seconds(),
+                    timeout_seconds: step.effective_timeout_seconds(&workflow.parameters.methodology),
                 })
                 .collect();
 
@@ -471,67 +492,15 @@ impl QueueManager {
                 elapsed_time_minutes,
                 remaining_time_minutes,
                 steps_progress,
+                retry_budget_remaining: workflow.remaining_retry_budget(),
             }));
         }
         drop(active_workflows);
 
-        // Check workflow history
+        // Check workflow history, spilled workflows included
         let history = self.workflow_history.read().await;
-        if let Some(queued_workflow) = history.iter().find(|w| w.workflow.id == workflow_id) {
-            let workflow = &queued_workflow.workflow;
-            let total_steps = workflow.steps.len();
-            let completed_steps = workflow.steps.iter()
-                .filter(|s| s.status == StepStatus::Completed)
-                .count();
-
-            let progress_percentage = match workflow.status {
-                WorkflowStatus::Completed => 100.0,
-                WorkflowStatus::Failed | WorkflowStatus::Cancelled => 0.0,
-                _ => if total_steps > 0 {
-                    (completed_steps as f64 / total_steps as f64) * 100.0
-                } else {
-                    0.0
-                }
-            };
-
-            let elapsed_time_minutes = if let (Some(started_at), Some(completed_at)) = (workflow.started_at, workflow.completed_at) {
-                (completed_at - started_at).num_minutes() as f64
-            } else {
-                0.0
-            };
-
-            let steps_progress: Vec<StepProgress> = workflow.steps.iter()
-                .map(|step| StepProgress {
-                    step_name: step.step_type.clone(),
-                    status: step.status,
-                    progress_percentage: match step.status {
-                        StepStatus::Completed => 100.0,
-                        StepStatus::Failed => 0.0,
-                        _ => 0.0,
-                    },
-                    started_at: step.started_at,
-                    completed_at: step.completed_at,
-                    error_message: step.error.clone(),
-                })
-                .collect();
-
-            return Ok(Some(WorkflowProgress {
-                workflow_id,
-                workflow_name: workflow.name.clone(),
-                status: workflow.status,
-                progress_percentage,
-                current_step: None,
-                current_step_index: completed_steps,
-                total_steps,
-                completed_steps,
-                estimated_completion_time: None,
-                elapsed_time_minutes,
-                remaining_time_minutes: None,
-                steps_progress,
-            }));
-        }
-
-        Ok(None)
+        let finished = history.find(|w| w.workflow.id == workflow_id).await?;
+        Ok(finished.map(|queued_workflow| finished_workflow_progress(&queued_workflow.workflow)))
     }
 
     /// Get queue-wide progress overview
@@ -636,18 +605,12 @@ impl QueueManager {
         let hours = hours.unwrap_or(24);
         let cutoff_time = Utc::now() - chrono::Duration::hours(hours as i64);
 
-        let history = self.workflow_history.read().await;
-        let mut progress_history = Vec::new();
-
-        for queued_workflow in history.iter() {
-            if let Some(completed_at) = queued_workflow.workflow.completed_at {
-                if completed_at >= cutoff_time {
-                    if let Ok(Some(progress)) = self.get_workflow_progress(queued_workflow.workflow.id).await {
-                        progress_history.push(progress);
-                    }
-                }
-            }
-        }
+        // Spilled workflows are included, so the window is not limited to those in memory
+        let finished = self.workflow_history.read().await.since(cutoff_time).await?;
+        let mut progress_history: Vec<WorkflowProgress> = finished.iter()
+            .filter(|queued_workflow| queued_workflow.workflow.completed_at.map_or(false, |completed_at| completed_at >= cutoff_time))
+            .map(|queued_workflow| finished_workflow_progress(&queued_workflow.workflow))
+            .collect();
 
         // Sort by completion time (most recent first)
         progress_history.sort_by(|a, b| {
@@ -1052,6 +1015,63 @@ impl QueueManager {
         Ok(())
     }
 }
+
+/// Progress of a workflow that has left the queue for good
+fn finished_workflow_progress(workflow: &ResearchWorkflow) -> WorkflowProgress {
+    let total_steps = workflow.steps.len();
+    let completed_steps = workflow.steps.iter()
+        .filter(|s| s.status == StepStatus::Completed)
+        .count();
+
+    let progress_percentage = match workflow.status {
+        WorkflowStatus::Completed => 100.0,
+        WorkflowStatus::Failed | WorkflowStatus::Cancelled => 0.0,
+        _ => if total_steps > 0 {
+            (completed_steps as f64 / total_steps as f64) * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    let elapsed_time_minutes = if let (Some(started_at), Some(completed_at)) = (workflow.started_at, workflow.completed_at) {
+        (completed_at - started_at).num_minutes() as f64
+    } else {
+        0.0
+    };
+
+    let steps_progress: Vec<StepProgress> = workflow.steps.iter()
+        .map(|step| StepProgress {
+            step_name: step.step_type.clone(),
+            status: step.status,
+            progress_percentage: match step.status {
+                StepStatus::Completed => 100.0,
+                StepStatus::Failed => 0.0,
+                _ => 0.0,
+            },
+            started_at: step.started_at,
+            completed_at: step.completed_at,
+            error_message: step.error.clone(),
+            elapsed_seconds: step.elapsed_seconds(),
+            timeout_seconds: step.effective_timeout_seconds(&workflow.parameters.methodology),
+        })
+        .collect();
+
+    WorkflowProgress {
+        workflow_id: workflow.id,
+        workflow_name: workflow.name.clone(),
+        status: workflow.status,
+        progress_percentage,
+        current_step: None,
+        current_step_index: completed_steps,
+        total_steps,
+        completed_steps,
+        estimated_completion_time: None,
+        elapsed_time_minutes,
+        remaining_time_minutes: None,
+        steps_progress,
+        retry_budget_remaining: workflow.remaining_retry_budget(),
+    }
+}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueStats {
     pub queue_length: usize,
@@ -1103,6 +1123,8 @@ pub struct WorkflowProgress {
     pub elapsed_time_minutes: f64,
     pub remaining_time_minutes: Option<f64>,
     pub steps_progress: Vec<StepProgress>,
+    /// Retries the workflow may still make across all its steps; `None` when unbudgeted
+    pub retry_budget_remaining: Option<u32>,
 }
 
 /// Individual step progress
@@ -1114,6 +1136,9 @@ pub struct StepProgress {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
+    /// Time spent in the step so far, to compare against `timeout_seconds`
+    pub elapsed_seconds: Option<f64>,
+    pub timeout_seconds: u32,
 }
 
 /// Queue-wide progress overview
@@ -1231,6 +1256,22 @@ impl Default for ResourceLimits {
     }
 }
 
+impl ResourceLimits {
+    /// An even share of these limits, one of `parts`
+    pub fn share(&self, parts: usize) -> Self {
+        let parts = parts.max(1);
+        Self {
+            max_memory_mb: self.max_memory_mb / parts as u64,
+            max_cpu_percentage: self.max_cpu_percentage / parts as f64,
+            max_api_calls_per_hour: self.max_api_calls_per_hour / parts as u32,
+            max_concurrent_requests: (self.max_concurrent_requests / parts as u32).max(1),
+            max_bandwidth_mbps: self.max_bandwidth_mbps / parts as f64,
+            max_storage_mb: self.max_storage_mb / parts as u64,
+            max_execution_time_minutes: self.max_execution_time_minutes,
+        }
+    }
+}
+
 /// Current resource usage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceUsage {
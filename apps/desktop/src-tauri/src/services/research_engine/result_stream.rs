@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::models::research_workflow::{ResearchResults, WorkflowStep};
+use crate::services::research_engine::context_assembler::ContextItem;
+use crate::services::research_engine::structured_output;
+
+/// Snapshots buffered per subscriber before it starts missing some
+const STREAM_CAPACITY: usize = 256;
+
+/// Longest excerpt of each extracted source carried in a snapshot
+const EXCERPT_CHARS: usize = 500;
+
+/// How far a workflow's results have come
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultStage {
+    SearchResults,
+    ExtractedContent,
+    Insights,
+    FinalSummary,
+}
+
+/// A source whose content a step extracted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedSource {
+    pub url: String,
+    pub title: String,
+    pub excerpt: String,
+}
+
+/// Everything a workflow has produced so far. Each snapshot is complete on its own,
+/// built from the results of a whole number of steps, so a consumer can replace what
+/// it shows with the latest one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialResults {
+    pub workflow_id: Uuid,
+    /// Goes up by one with each snapshot of the workflow; a gap means some were skipped
+    pub sequence: u64,
+    pub stage: ResultStage,
+    /// The step whose completion produced this snapshot
+    pub step_name: Option<String>,
+    pub completed_steps: u32,
+    pub total_steps: u32,
+    pub search_results: Option<serde_json::Value>,
+    pub extracted_content: Vec<ExtractedSource>,
+    pub insights: Option<serde_json::Value>,
+    pub summary: Option<String>,
+    /// No more snapshots follow for the workflow
+    pub is_final: bool,
+    /// The workflow ended without completing every step
+    pub failed: bool,
+    pub emitted_at: DateTime<Utc>,
+}
+
+impl PartialResults {
+    fn new(workflow_id: Uuid) -> Self {
+        Self {
+            workflow_id,
+            sequence: 0,
+            stage: ResultStage::SearchResults,
+            step_name: None,
+            completed_steps: 0,
+            total_steps: 0,
+            search_results: None,
+            extracted_content: Vec::new(),
+            insights: None,
+            summary: None,
+            is_final: false,
+            failed: false,
+            emitted_at: Utc::now(),
+        }
+    }
+}
+
+/// The stage a step's output takes the results to, if it has anything to show
+pub fn stage_of(output: &HashMap<String, serde_json::Value>) -> Option<ResultStage> {
+    if output.contains_key(structured_output::INSIGHTS_KEY) {
+        Some(ResultStage::Insights)
+    } else if output.contains_key("scraped_content") || output.contains_key("processed_urls") {
+        Some(ResultStage::ExtractedContent)
+    } else if output.contains_key("search_results") {
+        Some(ResultStage::SearchResults)
+    } else {
+        None
+    }
+}
+
+fn extracted_sources(shared_data: &HashMap<String, serde_json::Value>) -> Vec<ExtractedSource> {
+    if let Some(scraped) = shared_data.get("scraped_content") {
+        return ContextItem::from_scraped_content(scraped).into_iter()
+            .map(|item| ExtractedSource {
+                url: item.id,
+                title: item.title,
+                excerpt: item.text.chars().take(EXCERPT_CHARS).collect(),
+            })
+            .collect();
+    }
+    shared_data.get("processed_urls")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|url| url.as_str())
+        .map(|url| ExtractedSource { url: url.to_string(), title: url.to_string(), excerpt: String::new() })
+        .collect()
+}
+
+/// Partial results of running workflows, published as each step completes
+pub struct ResultStream {
+    sender: broadcast::Sender<PartialResults>,
+    latest: Mutex<HashMap<Uuid, PartialResults>>,
+}
+
+impl Default for ResultStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender, latest: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl ResultStream {
+    /// Receive the snapshots of every workflow from now on, and the latest snapshot of
+    /// `workflow_id` if it has one, to start from
+    pub fn subscribe(&self, workflow_id: Uuid) -> (broadcast::Receiver<PartialResults>, Option<PartialResults>) {
+        // Subscribe under the lock so no snapshot falls between the two
+        let latest = self.latest.lock();
+        (self.sender.subscribe(), latest.get(&workflow_id).cloned())
+    }
+
+    /// The latest snapshot of a running workflow
+    pub fn latest(&self, workflow_id: Uuid) -> Option<PartialResults> {
+        self.latest.lock().get(&workflow_id).cloned()
+    }
+
+    /// Publish a snapshot after `step` finished with `output`, which `shared_data`
+    /// already includes. Steps with nothing to show publish nothing.
+    pub fn publish_step(
+        &self,
+        workflow_id: Uuid,
+        step: &WorkflowStep,
+        output: &HashMap<String, serde_json::Value>,
+        shared_data: &HashMap<String, serde_json::Value>,
+        completed_steps: u32,
+        total_steps: u32,
+    ) -> Option<PartialResults> {
+        let stage = stage_of(output)?;
+
+        let mut latest = self.latest.lock();
+        let snapshot = latest.entry(workflow_id).or_insert_with(|| PartialResults::new(workflow_id));
+        snapshot.sequence += 1;
+        snapshot.stage = snapshot.stage.max(stage);
+        snapshot.step_name = Some(step.name.clone());
+        snapshot.completed_steps = completed_steps;
+        snapshot.total_steps = total_steps;
+        snapshot.search_results = shared_data.get("search_results").cloned();
+        snapshot.extracted_content = extracted_sources(shared_data);
+        snapshot.insights = shared_data.get(structured_output::INSIGHTS_KEY).cloned();
+        snapshot.emitted_at = Utc::now();
+
+        let snapshot = snapshot.clone();
+        // Sending fails only when nobody is subscribed
+        let _ = self.sender.send(snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// Publish the last snapshot of a workflow: its final report when there is one, and
+    /// whether it failed. The workflow's snapshots are dropped afterwards.
+    pub fn publish_final(&self, workflow_id: Uuid, results: Option<&ResearchResults>, failed: bool) -> PartialResults {
+        let mut latest = self.latest.lock();
+        let mut snapshot = latest.remove(&workflow_id).unwrap_or_else(|| PartialResults::new(workflow_id));
+        finish(&mut snapshot, results, failed);
+
+        let _ = self.sender.send(snapshot.clone());
+        snapshot
+    }
+
+    /// The last snapshot of a workflow that finished before anyone subscribed, from the
+    /// report stored with it
+    pub fn finished(workflow_id: Uuid, results: Option<&ResearchResults>, failed: bool) -> PartialResults {
+        let mut snapshot = PartialResults::new(workflow_id);
+        finish(&mut snapshot, results, failed);
+        snapshot
+    }
+}
+
+fn finish(snapshot: &mut PartialResults, results: Option<&ResearchResults>, failed: bool) {
+    snapshot.sequence += 1;
+    snapshot.step_name = None;
+    if let Some(results) = results {
+        snapshot.stage = ResultStage::FinalSummary;
+        snapshot.summary = Some(results.content.clone());
+        if let Some(insights) = results.metadata.get(structured_output::INSIGHTS_KEY) {
+            snapshot.insights = Some(insights.clone());
+        }
+    }
+    snapshot.is_final = true;
+    snapshot.failed = failed;
+    snapshot.emitted_at = Utc::now();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_workflow::ResearchMethodology;
+
+    #[test]
+    fn test_snapshots_accumulate_in_order_and_end_with_the_summary() {
+        let stream = ResultStream::default();
+        let workflow_id = Uuid::new_v4();
+        let (mut receiver, start) = stream.subscribe(workflow_id);
+        assert!(start.is_none());
+
+        let search = WorkflowStep::new(workflow_id, 1, "Initial Web Search".to_string(), String::new());
+        let mut shared_data = HashMap::new();
+        let output = HashMap::from([("search_results".to_string(), serde_json::json!({ "organic_results": [] }))]);
+        shared_data.extend(output.clone());
+        stream.publish_step(workflow_id, &search, &output, &shared_data, 1, 3).unwrap();
+
+        // Bookkeeping steps have nothing to show
+        let mapping = WorkflowStep::new(workflow_id, 2, "Content Mapping".to_string(), String::new());
+        assert!(stream.publish_step(workflow_id, &mapping, &HashMap::new(), &shared_data, 2, 3).is_none());
+
+        let scrape = WorkflowStep::new(workflow_id, 3, "Content Scraping".to_string(), String::new());
+        let output = HashMap::from([("scraped_content".to_string(), serde_json::json!([
+            { "url": "https://example.org/a", "markdown": "x".repeat(2000) },
+        ]))]);
+        shared_data.extend(output.clone());
+        stream.publish_step(workflow_id, &scrape, &output, &shared_data, 3, 3).unwrap();
+
+        // A late subscriber starts from the latest snapshot, which still has the search results
+        let (_, start) = stream.subscribe(workflow_id);
+        let start = start.unwrap();
+        assert_eq!(start.sequence, 2);
+        assert_eq!(start.stage, ResultStage::ExtractedContent);
+        assert!(start.search_results.is_some());
+        assert_eq!(start.extracted_content[0].excerpt.len(), EXCERPT_CHARS);
+
+        let results = ResearchResults {
+            content: "# Report".to_string(),
+            sources: Vec::new(),
+            metadata: HashMap::new(),
+            word_count: 2,
+            source_count: 0,
+            methodology_used: ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        };
+        stream.publish_final(workflow_id, Some(&results), false);
+        assert!(stream.latest(workflow_id).is_none());
+
+        let received: Vec<PartialResults> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(received.iter().map(|s| s.sequence).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(received[0].stage, ResultStage::SearchResults);
+        assert!(received[0].extracted_content.is_empty());
+        assert_eq!(received[2].stage, ResultStage::FinalSummary);
+        assert_eq!(received[2].summary.as_deref(), Some("# Report"));
+        assert!(received[2].is_final && !received[2].failed);
+
+        // A workflow that finished before the subscription gets the same final snapshot
+        let finished = ResultStream::finished(workflow_id, Some(&results), false);
+        assert!(finished.is_final && !finished.failed);
+        assert_eq!(finished.stage, ResultStage::FinalSummary);
+        assert_eq!(finished.summary.as_deref(), Some("# Report"));
+        assert!(ResultStream::finished(workflow_id, None, true).failed);
+    }
+}
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn, error};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::WorkflowStatus;
+use crate::models::saga::{SagaState, SagaStatus, SagaStepStatus};
+use crate::services::DataPersistenceService;
+use super::queue_manager::{QueueManager, ResourceLimits};
+use super::workflow_engine::WorkflowEngine;
+
+/// One side effect of a multi-service operation, with the action that undoes it
+#[async_trait::async_trait]
+pub trait SagaStep: Send + Sync {
+    /// Stable name, recorded in persisted saga state to find the step again after a restart
+    fn name(&self) -> &'static str;
+
+    async fn execute(&self, workflow_id: Uuid) -> AppResult<()>;
+
+    /// Undo `execute`. Must tolerate the side effect being partly or already gone, since
+    /// after a restart in-memory state such as allocations no longer exists.
+    async fn compensate(&self, workflow_id: Uuid) -> AppResult<()>;
+
+    /// Release what `execute` holds for the operation, once the operation has succeeded
+    async fn finish(&self, _workflow_id: Uuid) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+/// Where saga progress is kept between steps
+#[async_trait::async_trait]
+pub trait SagaStore: Send + Sync {
+    async fn save(&self, saga: &SagaState) -> AppResult<()>;
+    async fn unfinished(&self) -> AppResult<Vec<SagaState>>;
+}
+
+#[async_trait::async_trait]
+impl SagaStore for RwLock<DataPersistenceService> {
+    async fn save(&self, saga: &SagaState) -> AppResult<()> {
+        self.read().await.save_saga_state(saga).await
+    }
+
+    async fn unfinished(&self) -> AppResult<Vec<SagaState>> {
+        self.read().await.get_unfinished_sagas().await
+    }
+}
+
+/// Runs compensatable steps in order. When a step fails, the steps that completed
+/// before it are compensated in reverse order. A saga whose steps all ran stays open
+/// until the operation they started has ended: `complete` closes it, `abort` undoes it.
+/// State is persisted after every step, so sagas cut short by a restart are compensated
+/// by `recover`.
+pub struct SagaCoordinator {
+    store: Arc<dyn SagaStore>,
+}
+
+impl SagaCoordinator {
+    pub fn new(store: Arc<dyn SagaStore>) -> Self {
+        Self { store }
+    }
+
+    /// Run `steps` for a workflow, returning the saga still open. On failure the
+    /// completed steps are compensated and the step's error is returned.
+    pub async fn run(&self, workflow_id: Uuid, steps: &[Arc<dyn SagaStep>]) -> AppResult<SagaState> {
+        let names: Vec<&str> = steps.iter().map(|step| step.name()).collect();
+        let mut saga = SagaState::new(workflow_id, &names);
+        self.store.save(&saga).await?;
+
+        for (index, step) in steps.iter().enumerate() {
+            saga.set_step_status(index, SagaStepStatus::Running, None);
+            self.store.save(&saga).await?;
+
+            if let Err(e) = step.execute(workflow_id).await {
+                warn!("Saga {} step {} failed for workflow {}: {}", saga.id, step.name(), workflow_id, e);
+                saga.set_step_status(index, SagaStepStatus::Failed, Some(e.to_string()));
+                saga.failure = Some(e.to_string());
+                self.compensate(&mut saga, steps).await?;
+                return Err(e);
+            }
+            saga.set_step_status(index, SagaStepStatus::Completed, None);
+            self.store.save(&saga).await?;
+        }
+
+        Ok(saga)
+    }
+
+    /// Close a saga whose operation succeeded, letting each step release what it held.
+    /// A step failing to release is logged; the operation's outcome stands.
+    pub async fn complete(&self, saga: &mut SagaState, steps: &[Arc<dyn SagaStep>]) -> AppResult<()> {
+        for step in steps.iter().rev() {
+            if let Err(e) = step.finish(saga.workflow_id).await {
+                error!("Saga {} could not release {}: {}", saga.id, step.name(), e);
+            }
+        }
+        saga.set_status(SagaStatus::Completed);
+        self.store.save(saga).await
+    }
+
+    /// Undo a saga whose operation failed after its steps had all run
+    pub async fn abort(&self, saga: &mut SagaState, steps: &[Arc<dyn SagaStep>], reason: String) -> AppResult<()> {
+        warn!("Compensating saga {} for workflow {}: {}", saga.id, saga.workflow_id, reason);
+        saga.failure = Some(reason);
+        self.compensate(saga, steps).await
+    }
+
+    /// Compensate every saga a restart left running or compensating, returning how many
+    /// were finished off. `steps` must include every step those sagas may have run.
+    pub async fn recover(&self, steps: &[Arc<dyn SagaStep>]) -> AppResult<usize> {
+        let sagas = self.store.unfinished().await?;
+        for mut saga in sagas.iter().cloned() {
+            info!("Compensating saga {} for workflow {} interrupted by a restart", saga.id, saga.workflow_id);
+            if saga.failure.is_none() {
+                saga.failure = Some("Interrupted by application restart".to_string());
+            }
+            let steps_by_position: Vec<Arc<dyn SagaStep>> = saga.steps.iter()
+                .map(|record| steps.iter().find(|step| step.name() == record.name).cloned()
+                    .ok_or_else(|| AppError::validation("saga_step", format!("unknown step {}", record.name))))
+                .collect::<Result<_, _>>()?;
+            self.compensate(&mut saga, &steps_by_position).await?;
+        }
+        Ok(sagas.len())
+    }
+
+    async fn compensate(&self, saga: &mut SagaState, steps: &[Arc<dyn SagaStep>]) -> AppResult<()> {
+        saga.set_status(SagaStatus::Compensating);
+        self.store.save(saga).await?;
+
+        let mut all_compensated = true;
+        for index in saga.steps_to_compensate() {
+            let step = &steps[index];
+            match step.compensate(saga.workflow_id).await {
+                Ok(()) => saga.set_step_status(index, SagaStepStatus::Compensated, None),
+                Err(e) => {
+                    // Keep undoing the earlier steps; this one needs manual cleanup
+                    error!("Saga {} could not compensate {}: {}", saga.id, step.name(), e);
+                    saga.set_step_status(index, SagaStepStatus::CompensationFailed, Some(e.to_string()));
+                    all_compensated = false;
+                }
+            }
+            self.store.save(saga).await?;
+        }
+
+        saga.set_status(if all_compensated { SagaStatus::Compensated } else { SagaStatus::CompensationFailed });
+        self.store.save(saga).await
+    }
+}
+
+/// Reserves queue capacity for the workflow's run, held until the run ends
+pub struct AllocateResourcesStep {
+    queue_manager: Arc<QueueManager>,
+    requirements: ResourceLimits,
+}
+
+impl AllocateResourcesStep {
+    pub const NAME: &'static str = "allocate_resources";
+
+    pub fn new(queue_manager: Arc<QueueManager>, requirements: ResourceLimits) -> Self {
+        Self { queue_manager, requirements }
+    }
+}
+
+#[async_trait::async_trait]
+impl SagaStep for AllocateResourcesStep {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn execute(&self, workflow_id: Uuid) -> AppResult<()> {
+        self.queue_manager.allocate_resources(workflow_id, self.requirements.clone()).await?;
+        Ok(())
+    }
+
+    async fn compensate(&self, workflow_id: Uuid) -> AppResult<()> {
+        self.queue_manager.deallocate_resources(workflow_id).await
+    }
+
+    async fn finish(&self, workflow_id: Uuid) -> AppResult<()> {
+        self.queue_manager.deallocate_resources(workflow_id).await
+    }
+}
+
+/// Starts the workflow in the engine; undoing it stops the run and flags any results
+/// it kept as partial
+pub struct StartExecutionStep {
+    workflow_engine: Arc<WorkflowEngine>,
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+}
+
+impl StartExecutionStep {
+    pub const NAME: &'static str = "start_execution";
+
+    pub fn new(workflow_engine: Arc<WorkflowEngine>, data_persistence: Arc<RwLock<DataPersistenceService>>) -> Self {
+        Self { workflow_engine, data_persistence }
+    }
+}
+
+#[async_trait::async_trait]
+impl SagaStep for StartExecutionStep {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn execute(&self, workflow_id: Uuid) -> AppResult<()> {
+        self.workflow_engine.start_workflow(workflow_id).await
+    }
+
+    async fn compensate(&self, workflow_id: Uuid) -> AppResult<()> {
+        // Not active in the engine after a restart; the stored workflow is handled below
+        if let Err(e) = self.workflow_engine.cancel_workflow(workflow_id).await {
+            warn!("Workflow {} was not running in the engine: {}", workflow_id, e);
+        }
+
+        let data_persistence = self.data_persistence.write().await;
+        let Some(mut workflow) = data_persistence.get_research_workflow(workflow_id).await? else {
+            return Ok(());
+        };
+        // Finished before a restart could close the saga; there is nothing to undo
+        if workflow.status == WorkflowStatus::Completed {
+            return Ok(());
+        }
+        if workflow.is_active() {
+            workflow.cancel();
+        }
+        let failed_steps = workflow.failed_steps();
+        if let Some(results) = workflow.results.as_mut() {
+            results.partial = true;
+            results.failed_steps = failed_steps;
+        }
+        data_persistence.save_research_workflow(&workflow).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use crate::error::ResearchError;
+
+    #[derive(Default)]
+    struct MemorySagaStore {
+        sagas: Mutex<HashMap<Uuid, SagaState>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SagaStore for MemorySagaStore {
+        async fn save(&self, saga: &SagaState) -> AppResult<()> {
+            self.sagas.lock().unwrap().insert(saga.id, saga.clone());
+            Ok(())
+        }
+
+        async fn unfinished(&self) -> AppResult<Vec<SagaState>> {
+            Ok(self.sagas.lock().unwrap().values().filter(|saga| !saga.status.is_finished()).cloned().collect())
+        }
+    }
+
+    struct RecordingStep {
+        name: &'static str,
+        fails: bool,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SagaStep for RecordingStep {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn execute(&self, _workflow_id: Uuid) -> AppResult<()> {
+            if self.fails {
+                return Err(ResearchError::invalid_query("provider unavailable").into());
+            }
+            self.log.lock().unwrap().push(format!("execute {}", self.name));
+            Ok(())
+        }
+
+        async fn compensate(&self, _workflow_id: Uuid) -> AppResult<()> {
+            self.log.lock().unwrap().push(format!("compensate {}", self.name));
+            Ok(())
+        }
+    }
+
+    fn steps(log: &Arc<Mutex<Vec<String>>>, failing: Option<&str>) -> Vec<Arc<dyn SagaStep>> {
+        ["allocate", "reserve_quota", "start"].into_iter()
+            .map(|name| Arc::new(RecordingStep { name, fails: failing == Some(name), log: log.clone() }) as Arc<dyn SagaStep>)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_compensates_earlier_steps_in_reverse_and_restart_recovers() {
+        let store = Arc::new(MemorySagaStore::default());
+        let coordinator = SagaCoordinator::new(store.clone());
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        assert!(coordinator.run(Uuid::new_v4(), &steps(&log, Some("start"))).await.is_err());
+        assert_eq!(*log.lock().unwrap(), vec![
+            "execute allocate", "execute reserve_quota", "compensate reserve_quota", "compensate allocate",
+        ]);
+        let saga = store.sagas.lock().unwrap().values().next().unwrap().clone();
+        assert_eq!(saga.status, SagaStatus::Compensated);
+        assert_eq!(saga.steps[2].status, SagaStepStatus::Failed);
+
+        // Steps that all ran leave the saga open until the operation they started ends
+        store.sagas.lock().unwrap().clear();
+        log.lock().unwrap().clear();
+        let mut started = coordinator.run(Uuid::new_v4(), &steps(&log, None)).await.unwrap();
+        assert_eq!(started.status, SagaStatus::Running);
+        coordinator.abort(&mut started, &steps(&log, None), "workflow failed".to_string()).await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![
+            "execute allocate", "execute reserve_quota", "execute start",
+            "compensate start", "compensate reserve_quota", "compensate allocate",
+        ]);
+        assert_eq!(started.status, SagaStatus::Compensated);
+        let mut finished = coordinator.run(Uuid::new_v4(), &steps(&log, None)).await.unwrap();
+        coordinator.complete(&mut finished, &steps(&log, None)).await.unwrap();
+        assert_eq!(finished.status, SagaStatus::Completed);
+        assert!(store.unfinished().await.unwrap().is_empty());
+
+        // A saga persisted mid-run, as a crash would leave it
+        let mut interrupted = SagaState::new(Uuid::new_v4(), &["allocate", "reserve_quota", "start"]);
+        interrupted.set_step_status(0, SagaStepStatus::Completed, None);
+        interrupted.set_step_status(1, SagaStepStatus::Running, None);
+        store.save(&interrupted).await.unwrap();
+        log.lock().unwrap().clear();
+
+        assert_eq!(coordinator.recover(&steps(&log, None)).await.unwrap(), 1);
+        assert_eq!(*log.lock().unwrap(), vec!["compensate reserve_quota", "compensate allocate"]);
+        assert!(store.unfinished().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use parking_lot::Mutex;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::idempotency;
+use crate::models::research_workflow::{ResearchWorkflow, WorkflowParameters};
+
+/// Workflow metadata key naming the run whose execution a workflow shared
+pub const COALESCED_WITH_KEY: &str = "coalesced_with";
+
+#[derive(Serialize)]
+struct ExecutionInputs<'a> {
+    query: String,
+    template_id: Option<Uuid>,
+    parameters: &'a WorkflowParameters,
+}
+
+/// Digest of everything that decides what a workflow's run produces: its query, with
+/// case and spacing normalized, its template and its parameters. Workflows with equal
+/// keys would run the same pipeline.
+pub fn execution_key(workflow: &ResearchWorkflow) -> String {
+    idempotency::request_fingerprint(&ExecutionInputs {
+        query: workflow.query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase(),
+        template_id: workflow.template_id,
+        parameters: &workflow.parameters,
+    })
+}
+
+/// How a workflow starting with a given key takes part in the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flight {
+    /// Nothing identical is running, so the workflow runs and others may wait on it
+    Leader,
+    /// The workflow waits on the named run and gets its results
+    Follower(Uuid),
+}
+
+#[derive(Debug)]
+struct InFlight {
+    leader: Uuid,
+    followers: Vec<Uuid>,
+}
+
+/// In-flight workflow runs by execution key, and the workflows waiting on each
+#[derive(Debug, Default)]
+pub struct SingleFlight {
+    flights: Mutex<HashMap<String, InFlight>>,
+}
+
+impl SingleFlight {
+    /// Join the run for `key`, or lead it when there is none. The leader must `finish`
+    /// however its run ends, or its followers wait forever.
+    pub fn join(&self, key: String, workflow_id: Uuid) -> Flight {
+        let mut flights = self.flights.lock();
+        match flights.get_mut(&key) {
+            Some(flight) if flight.leader == workflow_id => Flight::Leader,
+            Some(flight) => {
+                if !flight.followers.contains(&workflow_id) {
+                    flight.followers.push(workflow_id);
+                }
+                Flight::Follower(flight.leader)
+            }
+            None => {
+                flights.insert(key, InFlight { leader: workflow_id, followers: Vec::new() });
+                Flight::Leader
+            }
+        }
+    }
+
+    /// Stop `follower` waiting on its run. Returns whether it was waiting on one.
+    pub fn leave(&self, follower: Uuid) -> bool {
+        let mut flights = self.flights.lock();
+        flights.values_mut().any(|flight| {
+            let waiting = flight.followers.len();
+            flight.followers.retain(|id| *id != follower);
+            flight.followers.len() != waiting
+        })
+    }
+
+    /// End the run `leader` leads, returning the workflows that waited on it
+    pub fn finish(&self, leader: Uuid) -> Vec<Uuid> {
+        let mut flights = self.flights.lock();
+        let Some(key) = flights.iter().find(|(_, flight)| flight.leader == leader).map(|(key, _)| key.clone()) else {
+            return Vec::new();
+        };
+        flights.remove(&key).map(|flight| flight.followers).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(query: &str) -> ResearchWorkflow {
+        ResearchWorkflow::new("Heat pumps".to_string(), query.to_string(), WorkflowParameters::default(), "analyst".to_string())
+    }
+
+    #[test]
+    fn test_identical_workflows_share_one_run_until_it_finishes() {
+        let first = workflow("Heat pump  efficiency in cold climates");
+        let second = workflow("heat pump efficiency in cold climates");
+        let key = execution_key(&first);
+        assert_eq!(key, execution_key(&second));
+
+        let mut other = workflow("Heat pump efficiency in cold climates");
+        other.parameters.methodology = crate::models::research_workflow::ResearchMethodology::DonLim;
+        assert_ne!(key, execution_key(&other));
+
+        let flights = SingleFlight::default();
+        let third = Uuid::new_v4();
+        assert_eq!(flights.join(key.clone(), first.id), Flight::Leader);
+        assert_eq!(flights.join(key.clone(), second.id), Flight::Follower(first.id));
+        assert_eq!(flights.join(key.clone(), third), Flight::Follower(first.id));
+        // A leader resuming from a pause stays the leader
+        assert_eq!(flights.join(key.clone(), first.id), Flight::Leader);
+
+        assert!(flights.leave(third));
+        assert!(!flights.leave(third));
+        assert_eq!(flights.finish(first.id), vec![second.id]);
+        assert!(flights.finish(first.id).is_empty());
+
+        // Once the run finishes, the next identical workflow runs afresh
+        assert_eq!(flights.join(key, third), Flight::Leader);
+    }
+}
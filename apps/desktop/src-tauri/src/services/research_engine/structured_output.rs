@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::{ApiError, AppResult};
+use crate::models::api_key::ServiceProvider;
+use crate::models::research_workflow::{InsightConfidenceFilter, LowConfidenceInsights, ResearchResults};
+use crate::services::api_manager::{ApiManagerService, NormalizedPayload, ServiceResponse, model_router};
+use crate::models::research_insight::ResearchInsight;
+use crate::services::output_processor::analysis::result_diff::{extract_insights, remove_insight_statements};
+use super::workflow_engine::ExecutionContext;
+
+pub use crate::models::research_insight::INSIGHTS_METADATA_KEY as INSIGHTS_KEY;
+
+/// Step output and result metadata key recording how the insights were obtained
+pub const INSIGHTS_SOURCE_KEY: &str = "insights_source";
+
+/// Step input carrying the workflow's insight confidence filter to its analysis steps
+pub const INSIGHT_CONFIDENCE_KEY: &str = "insight_confidence";
+
+/// Result metadata key recording the confidence threshold applied and what it filtered
+pub const INSIGHT_FILTER_KEY: &str = "insight_confidence_filter";
+
+/// Result metadata key holding the insights moved to the low confidence section
+pub const LOW_CONFIDENCE_INSIGHTS_KEY: &str = "low_confidence_insights";
+
+/// Name of the schema, and of the function models are made to call in tool-call mode
+const SCHEMA_NAME: &str = "record_research_analysis";
+
+const CATEGORIES: [&str; 5] = ["KeyFinding", "Trend", "Contradiction", "GapInKnowledge", "Recommendation"];
+
+const REPAIR_SYSTEM_PROMPT: &str = "You fix JSON documents that failed validation. Reply with the corrected document only, keeping all of its content.";
+
+/// How an analysis step's insights were obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsightsSource {
+    /// Valid structured output on the first try
+    Structured,
+    /// Structured output that validated after a repair request
+    Repaired,
+    /// Parsed from the report text; the serving model has no structured mode
+    TextParsed,
+}
+
+/// The report and insights of an analysis step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuredAnalysis {
+    /// The full report in markdown
+    pub report: String,
+    pub insights: Vec<ResearchInsight>,
+}
+
+/// An analysis step's output together with the response that produced it
+pub struct AnalysisOutput {
+    pub analysis: StructuredAnalysis,
+    pub source: InsightsSource,
+    pub served_by_model: String,
+    pub response: ServiceResponse,
+}
+
+/// What a workflow's confidence filter did to its report's insights
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsightFilterSummary {
+    pub min_confidence: f32,
+    pub low_confidence: LowConfidenceInsights,
+    pub kept_count: u32,
+    /// Insights below the threshold, omitted or moved to their own section
+    pub filtered_count: u32,
+}
+
+/// `system_prompt` with the workflow's confidence threshold spelled out when it has
+/// one, so the model rates its insights knowing what the rating decides and flags
+/// uncertainty in the report itself
+pub fn with_confidence_guidance(system_prompt: String, context: &ExecutionContext) -> String {
+    let filter = context.input_data.get(INSIGHT_CONFIDENCE_KEY)
+        .and_then(|filter| serde_json::from_value::<InsightConfidenceFilter>(filter.clone()).ok())
+        .filter(InsightConfidenceFilter::is_active);
+    let Some(min_confidence) = filter.and_then(|filter| filter.min_confidence) else {
+        return system_prompt;
+    };
+    let fate = match filter.map(|filter| filter.low_confidence).unwrap_or_default() {
+        LowConfidenceInsights::Omit => "left out of the report",
+        LowConfidenceInsights::Separate => "moved to a separate low confidence section",
+    };
+    format!(
+        "{}\n\nRate each insight's confidence honestly from 0 to 1; insights below {:.2} will be {}. \
+         Where evidence is thin or sources disagree, say so in the report instead of stating the point as settled.",
+        system_prompt, min_confidence, fate,
+    )
+}
+
+/// Keep the insights of `results` below the filter's minimum confidence out of the
+/// report: the list items and prose sentences stating them are removed and, when the
+/// filter separates them, they are listed again in a closing section. Statements that
+/// reword an insight stay. Only structured insights carry a confidence, so results
+/// without them are left alone.
+pub fn apply_confidence_filter(results: &mut ResearchResults, filter: &InsightConfidenceFilter) -> AppResult<Option<InsightFilterSummary>> {
+    let Some(min_confidence) = filter.min_confidence.filter(|_| filter.is_active()) else {
+        return Ok(None);
+    };
+    let Some(insights) = results.metadata.get(INSIGHTS_KEY)
+        .and_then(|insights| serde_json::from_value::<Vec<ResearchInsight>>(insights.clone()).ok()) else {
+        return Ok(None);
+    };
+
+    let (kept, low): (Vec<_>, Vec<_>) = insights.into_iter().partition(|insight| filter.keeps(insight.confidence));
+    if !low.is_empty() {
+        results.content = remove_insight_statements(&results.content, &low);
+        if filter.low_confidence == LowConfidenceInsights::Separate {
+            results.content.push_str("\n\n## Low Confidence Insights\n\n");
+            for insight in &low {
+                results.content.push_str(&format!("- {} (confidence {:.2})\n", insight.insight, insight.confidence.unwrap_or_default()));
+            }
+            results.metadata.insert(LOW_CONFIDENCE_INSIGHTS_KEY.to_string(), serde_json::to_value(&low)?);
+        }
+        results.word_count = results.content.split_whitespace().count() as u32;
+    }
+
+    let summary = InsightFilterSummary {
+        min_confidence,
+        low_confidence: filter.low_confidence,
+        kept_count: kept.len() as u32,
+        filtered_count: low.len() as u32,
+    };
+    results.metadata.insert(INSIGHTS_KEY.to_string(), serde_json::to_value(&kept)?);
+    results.metadata.insert(INSIGHT_FILTER_KEY.to_string(), serde_json::to_value(&summary)?);
+    Ok(Some(summary))
+}
+
+/// JSON schema of `StructuredAnalysis`, strict enough for providers' strict modes
+pub fn analysis_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "report": {
+                "type": "string",
+                "description": "The full research report in markdown"
+            },
+            "insights": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "insight": { "type": "string" },
+                        "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                        "supporting_sources": {
+                            "type": "array",
+                            "items": { "type": "string", "description": "URL of a source backing the insight" }
+                        },
+                        "category": { "type": "string", "enum": CATEGORIES }
+                    },
+                    "required": ["insight", "confidence", "supporting_sources", "category"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["report", "insights"],
+        "additionalProperties": false
+    })
+}
+
+/// Parse model output against `analysis_schema`, listing every violation found
+pub fn parse_analysis(content: &str) -> Result<StructuredAnalysis, Vec<String>> {
+    let value: serde_json::Value = serde_json::from_str(strip_code_fence(content))
+        .map_err(|e| vec![format!("not valid JSON: {}", e)])?;
+
+    let mut errors = Vec::new();
+    match value.get("report").and_then(|v| v.as_str()) {
+        Some(report) if !report.trim().is_empty() => {}
+        Some(_) => errors.push("report: must not be empty".to_string()),
+        None => errors.push("report: expected a string".to_string()),
+    }
+
+    match value.get("insights").and_then(|v| v.as_array()) {
+        None => errors.push("insights: expected an array".to_string()),
+        Some(insights) => {
+            for (i, insight) in insights.iter().enumerate() {
+                let path = format!("insights[{}]", i);
+                if !insight.get("insight").and_then(|v| v.as_str()).is_some_and(|text| !text.trim().is_empty()) {
+                    errors.push(format!("{}.insight: expected a non-empty string", path));
+                }
+                if !insight.get("confidence").and_then(|v| v.as_f64()).is_some_and(|c| (0.0..=1.0).contains(&c)) {
+                    errors.push(format!("{}.confidence: expected a number from 0 to 1", path));
+                }
+                let sources_valid = insight.get("supporting_sources")
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|sources| sources.iter().all(|s| s.is_string()));
+                if !sources_valid {
+                    errors.push(format!("{}.supporting_sources: expected an array of strings", path));
+                }
+                if !insight.get("category").and_then(|v| v.as_str()).is_some_and(|c| CATEGORIES.contains(&c)) {
+                    errors.push(format!("{}.category: expected one of {}", path, CATEGORIES.join(", ")));
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    serde_json::from_value(value).map_err(|e| vec![e.to_string()])
+}
+
+/// Models in JSON mode sometimes still wrap their answer in a markdown code fence
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    trimmed.strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .map_or(trimmed, str::trim)
+}
+
+/// What is left of output that failed validation even after a repair: the report it
+/// holds, or failing that its whole text, with insights parsed from the text as for
+/// models without a structured mode
+fn text_analysis(content: &str) -> Option<StructuredAnalysis> {
+    let report = serde_json::from_str::<serde_json::Value>(strip_code_fence(content))
+        .ok()
+        .and_then(|value| value.get("report").and_then(|report| report.as_str()).map(str::to_string))
+        .unwrap_or_else(|| content.to_string());
+    if report.trim().is_empty() {
+        return None;
+    }
+    let insights = extract_insights(&report);
+    Some(StructuredAnalysis { report, insights })
+}
+
+fn completion_content(response: &ServiceResponse) -> Option<&str> {
+    match &response.normalized {
+        Some(NormalizedPayload::Completion { content, .. }) => Some(content.as_str()),
+        _ => None,
+    }
+}
+
+/// Run an analysis request for `role`, asking each model that supports it for output
+/// conforming to `analysis_schema`. Malformed output gets one repair request to the same
+/// model; models without a structured mode, and output the repair does not fix, have
+/// their insights parsed from the text.
+pub async fn run_analysis(
+    api_manager: &ApiManagerService,
+    role: &str,
+    system_prompt: &str,
+    user_prompt: &str,
+    temperature: f64,
+    max_tokens: u32,
+    timeout_ms: u32,
+) -> AppResult<AnalysisOutput> {
+    let policy = api_manager.get_model_routing_policy().await;
+    let schema = analysis_schema();
+
+    let served = api_manager.make_chat_request_with_model_fallback(role, |model| {
+        let request = model_router::chat_completion_request(model, system_prompt, user_prompt, temperature, max_tokens, timeout_ms);
+        match policy.structured_output_mode(model) {
+            Some(mode) => model_router::with_structured_output(request, mode, SCHEMA_NAME, &schema),
+            None => request,
+        }
+    }).await?;
+    let model = served.served_by_model;
+    let content = completion_content(&served.response).unwrap_or_default().to_string();
+
+    let Some(mode) = policy.structured_output_mode(&model) else {
+        debug!("{} has no structured output mode, parsing insights from the report text", model);
+        let insights = extract_insights(&content);
+        return Ok(AnalysisOutput {
+            analysis: StructuredAnalysis { report: content, insights },
+            source: InsightsSource::TextParsed,
+            served_by_model: model,
+            response: served.response,
+        });
+    };
+
+    let errors = match parse_analysis(&content) {
+        Ok(analysis) => {
+            return Ok(AnalysisOutput { analysis, source: InsightsSource::Structured, served_by_model: model, response: served.response });
+        }
+        Err(errors) => errors,
+    };
+
+    warn!("{} returned analysis output failing validation ({}), requesting a repair", model, errors.join("; "));
+    let repair_prompt = format!(
+        "This JSON document does not match the required schema.\n\nProblems:\n- {}\n\nDocument:\n{}",
+        errors.join("\n- "),
+        content,
+    );
+    let request = model_router::with_structured_output(
+        model_router::chat_completion_request(&model, REPAIR_SYSTEM_PROMPT, &repair_prompt, 0.0, max_tokens, timeout_ms),
+        mode,
+        SCHEMA_NAME,
+        &schema,
+    );
+    let repair_failure = match api_manager.make_service_request(ServiceProvider::OpenRouter, request).await {
+        Ok(response) => match completion_content(&response).map(parse_analysis) {
+            Some(Ok(analysis)) => {
+                return Ok(AnalysisOutput { analysis, source: InsightsSource::Repaired, served_by_model: model, response });
+            }
+            Some(Err(errors)) => format!("analysis output still invalid after repair: {}", errors.join("; ")),
+            None => "empty repair response".to_string(),
+        },
+        Err(e) => format!("repair request failed: {}", e),
+    };
+
+    // A report without validated insights beats failing the step
+    match text_analysis(&content) {
+        Some(analysis) => {
+            warn!("{}: {}, parsing insights from the report text", model, repair_failure);
+            Ok(AnalysisOutput { analysis, source: InsightsSource::TextParsed, served_by_model: model, response: served.response })
+        }
+        None => Err(ApiError::request_failed(model, served.response.status_code, repair_failure).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::research_insight::InsightCategory;
+
+    #[test]
+    fn test_parse_analysis_validates_against_the_insight_schema() {
+        let valid = r#"```json
+{"report": "Solid-state cells are nearing production.",
+ "insights": [{"insight": "Pilot lines open in 2026", "confidence": 0.7,
+               "supporting_sources": ["https://example.org/a"], "category": "Trend"}]}
+```"#;
+        let analysis = parse_analysis(valid).unwrap();
+        assert_eq!(analysis.insights.len(), 1);
+        assert_eq!(analysis.insights[0].category, InsightCategory::Trend);
+        assert_eq!(analysis.insights[0].confidence, Some(0.7));
+
+        let invalid = r#"{"report": "", "insights": [{"insight": "x", "confidence": 3, "category": "Guess"}]}"#;
+        let errors = parse_analysis(invalid).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().any(|e| e.starts_with("insights[0].category")));
+        assert!(errors.iter().any(|e| e.starts_with("insights[0].supporting_sources")));
+
+        assert!(parse_analysis("Here is the report: {").unwrap_err()[0].starts_with("not valid JSON"));
+    }
+
+    #[test]
+    fn test_unrepaired_output_degrades_to_its_report_text() {
+        let analysis = text_analysis(r###"{"report": "## Key Findings\n\n- Pilot lines open in 2026", "insights": "none"}"###).unwrap();
+        assert_eq!(analysis.report, "## Key Findings\n\n- Pilot lines open in 2026");
+        assert_eq!(analysis.insights.len(), 1);
+
+        assert_eq!(text_analysis("Plain prose report").unwrap().report, "Plain prose report");
+        assert!(text_analysis(r#"{"report": ""}"#).is_none());
+        assert!(text_analysis("   ").is_none());
+    }
+
+    #[test]
+    fn test_insights_below_the_minimum_confidence_leave_the_report() {
+        let insight = |text: &str, confidence| ResearchInsight {
+            insight: text.to_string(),
+            category: InsightCategory::KeyFinding,
+            confidence,
+            supporting_sources: Vec::new(),
+        };
+        let insights = vec![
+            insight("Pilot lines open in 2026", Some(0.9)),
+            insight("Costs halve by 2027", Some(0.3)),
+            insight("Recycling rates are unknown", None),
+        ];
+        let mut results = ResearchResults {
+            content: "## Key Findings\n\n- Pilot lines open in 2026\n- **Costs halve** by 2027\n- Recycling rates are unknown".to_string(),
+            sources: Vec::new(),
+            metadata: [(INSIGHTS_KEY.to_string(), serde_json::to_value(&insights).unwrap())].into_iter().collect(),
+            word_count: 0,
+            source_count: 0,
+            methodology_used: crate::models::research_workflow::ResearchMethodology::Hybrid,
+            execution_time_ms: 0,
+            partial: false,
+            failed_steps: Vec::new(),
+        };
+
+        let mut omitted = results.clone();
+        let filter = InsightConfidenceFilter { min_confidence: Some(0.5), low_confidence: LowConfidenceInsights::Omit };
+        let summary = apply_confidence_filter(&mut omitted, &filter).unwrap().unwrap();
+        assert_eq!((summary.kept_count, summary.filtered_count), (2, 1));
+        assert!(!omitted.content.contains("Costs halve"));
+        assert!(omitted.content.contains("Recycling rates are unknown"));
+        assert_eq!(omitted.metadata[INSIGHTS_KEY].as_array().unwrap().len(), 2);
+        assert_eq!(omitted.metadata[INSIGHT_FILTER_KEY]["filtered_count"], 1);
+
+        let filter = InsightConfidenceFilter { min_confidence: Some(0.5), low_confidence: LowConfidenceInsights::Separate };
+        apply_confidence_filter(&mut results, &filter).unwrap();
+        let (main, section) = results.content.split_once("## Low Confidence Insights").unwrap();
+        assert!(!main.contains("Costs halve") && section.contains("Costs halve by 2027 (confidence 0.30)"));
+        assert_eq!(results.metadata[LOW_CONFIDENCE_INSIGHTS_KEY].as_array().unwrap().len(), 1);
+
+        // Without a threshold nothing is filtered or recorded
+        let mut untouched = results.clone();
+        assert!(apply_confidence_filter(&mut untouched, &InsightConfidenceFilter::default()).unwrap().is_none());
+        assert_eq!(untouched.content, results.content);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::research_workflow::{ProviderRecording, ResearchWorkflow};
+use crate::services::api_manager::{RecordedExchange, response_schema};
+use crate::services::data_persistence::redaction::{self, RedactionCategory, RedactionConfig, RedactionReport};
+
+/// Version of the bundle layout; bundles from a newer version are refused on import
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Workflow metadata key naming the workflow an imported one was exported from
+pub const IMPORTED_FROM_KEY: &str = "imported_from";
+
+/// A completed research run packaged for someone else to inspect and re-execute.
+///
+/// The workflow carries its methodology, parameters, steps, sources and results; the
+/// recording holds every provider response the run received, so the bundle can be
+/// replayed without live API calls. Credentials and personal data are scrubbed on
+/// export, and the query is kept as-is since a re-run needs it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowBundle {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub workflow: ResearchWorkflow,
+    /// Empty when the workflow was not run with provider recording on
+    pub recording: Vec<RecordedExchange>,
+    /// Personal data replaced on export, by category
+    pub redactions: HashMap<RedactionCategory, usize>,
+    /// SHA-256 of the workflow and recording, checked on import
+    pub checksum: String,
+}
+
+/// Outcome of importing a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedBundle {
+    pub workflow: ResearchWorkflow,
+    pub recorded_calls: usize,
+    /// Workflow replaying the imported recording, when a re-run was requested
+    pub rerun_workflow_id: Option<Uuid>,
+}
+
+impl WorkflowBundle {
+    /// Package a workflow and its recording, redacting both with the configured redaction
+    pub fn new(config: &RedactionConfig, workflow: &ResearchWorkflow, recording: &[RecordedExchange]) -> AppResult<Self> {
+        let mut report = RedactionReport::default();
+        let workflow = redact_workflow(config, workflow, &mut report);
+        let recording: Vec<_> = recording.iter()
+            .map(|exchange| redact_exchange(config, exchange, &mut report))
+            .collect();
+
+        Ok(Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now(),
+            checksum: checksum(&workflow, &recording)?,
+            workflow,
+            recording,
+            redactions: report.matches_by_category,
+        })
+    }
+
+    /// The bundle as a single JSON document
+    pub fn to_archive(&self) -> AppResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Read a bundle, refusing newer formats and archives altered since export
+    pub fn from_archive(archive: &str) -> AppResult<Self> {
+        let bundle: Self = serde_json::from_str(archive)?;
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(AppError::validation(
+                "format_version",
+                format!("bundle format {} is newer than the supported {}", bundle.format_version, BUNDLE_FORMAT_VERSION),
+            ));
+        }
+        if checksum(&bundle.workflow, &bundle.recording)? != bundle.checksum {
+            return Err(AppError::validation("checksum", "bundle contents do not match its checksum"));
+        }
+        Ok(bundle)
+    }
+
+    /// The bundled workflow under a fresh ID, so importing never overwrites a local workflow
+    pub fn imported_workflow(&self) -> ResearchWorkflow {
+        let mut workflow = self.workflow.clone();
+        workflow.id = Uuid::new_v4();
+        for step in &mut workflow.steps {
+            step.workflow_id = workflow.id;
+        }
+        workflow.metadata.insert(IMPORTED_FROM_KEY.to_string(), self.workflow.id.to_string());
+        workflow.updated_at = Utc::now();
+        workflow
+    }
+}
+
+/// A new run of `source` that replays its recorded provider responses step by step
+pub fn replay_workflow(source: &ResearchWorkflow) -> ResearchWorkflow {
+    let mut parameters = source.parameters.clone();
+    parameters.provider_recording = ProviderRecording::Replay { source_workflow_id: source.id };
+
+    let mut workflow = ResearchWorkflow::new(
+        format!("{} (re-run)", source.name),
+        source.query.clone(),
+        parameters,
+        source.created_by.clone(),
+    );
+    workflow.template_id = source.template_id;
+    workflow.tags = source.tags.clone();
+    workflow.folder = source.folder.clone();
+    workflow.metadata = source.metadata.clone();
+    workflow
+}
+
+/// Digest of the canonical JSON form; going through `Value` sorts map keys, so the
+/// digest does not depend on `HashMap` iteration order
+fn checksum(workflow: &ResearchWorkflow, recording: &[RecordedExchange]) -> AppResult<String> {
+    let content = serde_json::to_value((workflow, recording))?.to_string();
+    let digest = ring::digest::digest(&ring::digest::SHA256, content.as_bytes());
+    Ok(digest.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn redact_string(config: &RedactionConfig, text: &str, location: &str, report: &mut RedactionReport) -> String {
+    let (redacted, findings) = redaction::redact_text(config, text, location);
+    findings.into_iter().for_each(|finding| report.add(finding));
+    redacted
+}
+
+fn redact_json(config: &RedactionConfig, value: &mut serde_json::Value, location: &str, report: &mut RedactionReport) {
+    response_schema::redact_value(value);
+    redaction::redact_value(config, value, location, report);
+}
+
+/// A recorded body redacted field by field when it is JSON, as plain text otherwise
+fn redact_body(config: &RedactionConfig, body: &str, location: &str, report: &mut RedactionReport) -> String {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact_json(config, &mut value, location, report);
+            value.to_string()
+        }
+        Err(_) => redact_string(config, body, location, report),
+    }
+}
+
+fn redact_metadata(metadata: &mut HashMap<String, String>) {
+    for (key, value) in metadata.iter_mut() {
+        if response_schema::is_sensitive_key(key) {
+            *value = "***".to_string();
+        }
+    }
+}
+
+fn redact_workflow(config: &RedactionConfig, workflow: &ResearchWorkflow, report: &mut RedactionReport) -> ResearchWorkflow {
+    let mut workflow = workflow.clone();
+
+    workflow.created_by = redact_string(config, &workflow.created_by, "created_by", report);
+    if let Some(message) = workflow.error_message.take() {
+        workflow.error_message = Some(redact_string(config, &message, "error_message", report));
+    }
+    redact_metadata(&mut workflow.metadata);
+    for (key, value) in workflow.parameters.custom_parameters.iter_mut() {
+        if response_schema::is_sensitive_key(key) {
+            *value = serde_json::Value::String("***".to_string());
+        } else {
+            redact_json(config, value, &format!("parameters.{}", key), report);
+        }
+    }
+
+    for step in &mut workflow.steps {
+        let location = format!("steps[{}]", step.step_number);
+        for (key, value) in step.input_data.iter_mut() {
+            redact_json(config, value, &format!("{}.input.{}", location, key), report);
+        }
+        for (key, value) in step.output_data.iter_mut().flatten() {
+            redact_json(config, value, &format!("{}.output.{}", location, key), report);
+        }
+        if let Some(message) = step.error_message.take() {
+            step.error_message = Some(redact_string(config, &message, &format!("{}.error", location), report));
+        }
+        redact_metadata(&mut step.metadata);
+    }
+
+    if let Some(results) = workflow.results.as_mut() {
+        for finding in redaction::redact_results(config, results).findings {
+            report.add(finding);
+        }
+    }
+
+    workflow
+}
+
+fn redact_exchange(config: &RedactionConfig, exchange: &RecordedExchange, report: &mut RedactionReport) -> RecordedExchange {
+    let mut exchange = exchange.clone();
+    let location = format!("recording.step_{}[{}]", exchange.step_number, exchange.sequence);
+
+    // Recordings mask credentials when written; masking again covers ones from older builds
+    if let Some(body) = exchange.request_body.take() {
+        exchange.request_body = Some(redact_body(config, &body, &format!("{}.request", location), report));
+    }
+    if let Some(response) = exchange.response.as_mut() {
+        response.body = redact_body(config, &response.body, &format!("{}.response", location), report);
+        response.error_message = response.error_message.take()
+            .map(|message| redact_string(config, &message, &format!("{}.response_error", location), report));
+    }
+    if let Some(message) = exchange.error_message.take() {
+        exchange.error_message = Some(redact_string(config, &message, &format!("{}.error", location), report));
+    }
+
+    exchange
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::api_key::ServiceProvider;
+    use crate::models::research_workflow::WorkflowParameters;
+
+    fn recorded_call(workflow_id: Uuid) -> RecordedExchange {
+        RecordedExchange {
+            workflow_id,
+            step_number: 1,
+            sequence: 0,
+            service: ServiceProvider::Tavily,
+            endpoint: "/search".to_string(),
+            method: "POST".to_string(),
+            request_body: Some(r#"{"query":"battery recycling","api_key":"tvly-secret"}"#.to_string()),
+            response: None,
+            error_message: Some("timed out contacting jane.doe@example.org".to_string()),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_redacts_on_export_and_rejects_tampering() {
+        let mut parameters = WorkflowParameters::default();
+        parameters.custom_parameters.insert("token".to_string(), serde_json::json!("sk-live"));
+        let mut workflow = ResearchWorkflow::new(
+            "Battery recycling".to_string(),
+            "battery recycling".to_string(),
+            parameters,
+            "jane.doe@example.org".to_string(),
+        );
+        workflow.metadata.insert("api_key".to_string(), "sk-live".to_string());
+
+        let bundle = WorkflowBundle::new(&RedactionConfig::default(), &workflow, &[recorded_call(workflow.id)]).unwrap();
+        let archive = bundle.to_archive().unwrap();
+        assert!(!archive.contains("sk-live"));
+        assert!(!archive.contains("tvly-secret"));
+        assert!(!archive.contains("jane.doe@example.org"));
+        assert_eq!(bundle.redactions[&RedactionCategory::Email], 2);
+        assert_eq!(bundle.workflow.query, "battery recycling");
+
+        let restored = WorkflowBundle::from_archive(&archive).unwrap();
+        let imported = restored.imported_workflow();
+        assert_ne!(imported.id, workflow.id);
+        assert_eq!(imported.metadata[IMPORTED_FROM_KEY], workflow.id.to_string());
+
+        let rerun = replay_workflow(&imported);
+        assert_eq!(rerun.parameters.provider_recording, ProviderRecording::Replay { source_workflow_id: imported.id });
+
+        let tampered = archive.replacen("battery recycling", "lithium mining", 1);
+        assert!(WorkflowBundle::from_archive(&tampered).is_err());
+    }
+}
@@ -1,16 +1,49 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
-use tracing::{info, debug, warn, error};
+use tracing::{info, debug, warn, error, info_span, Instrument};
 use uuid::Uuid;
 use chrono::Utc;
 
-use crate::error::{AppResult, ApiError};
+use crate::error::{AppError, AppResult, ApiError};
+use crate::models::prompt_template::ExperimentArm;
 use crate::models::research_workflow::{
-    ResearchWorkflow, WorkflowStep, WorkflowStatus, StepStatus, ResearchMethodology
+    ResearchWorkflow, ResearchResults, WorkflowStep, WorkflowStatus, StepStatus, ResearchMethodology, FailureMode
 };
 use crate::services::{DataPersistenceService, ApiManagerService};
-use crate::services::api_manager::{ServiceRequest, ServiceResponse};
+use crate::services::knowledge_graph::KnowledgeGraphService;
+use crate::services::nlp_engine::NLPEngineService;
+use crate::services::data_persistence::WorkflowSearchFilters;
+use crate::models::execution_metrics::StepExecutionMetrics;
+use crate::services::api_manager::{ServiceRequest, ServiceResponse, response_recorder, egress, call_meter, CallMeter, run_scoped, tenant_scope};
+use super::overlap_checker;
+use super::contradiction_detector::{self, NlpEngineSlot};
+use super::structured_output;
+use super::prompt_library::{self, PromptLibrary, ResolvedPrompt};
+use super::result_stream::ResultStream;
+use super::single_flight::{self, Flight, SingleFlight, COALESCED_WITH_KEY};
+use super::pdf_extraction::KnowledgeGraphSlot;
+
+/// Step output key naming the provider that actually served the step
+pub const SERVED_BY_KEY: &str = "served_by";
+
+/// Step output key listing every provider tried for the step and how each fared
+pub const PROVIDER_ATTEMPTS_KEY: &str = "provider_attempts";
+
+/// Step output key naming the model that actually served an AI step
+pub const SERVED_BY_MODEL_KEY: &str = "served_by_model";
+
+/// Step metadata set when a provider refused the step's query on content-policy grounds
+pub const POLICY_REFUSED_KEY: &str = "policy_refused";
+
+/// Step input carrying the recency the workflow asks of its web search sources
+pub const SEARCH_RECENCY_KEY: &str = "search_recency";
+
+/// Running workflows loaded at a time when settling shared runs after a restart
+const RECONCILE_PAGE_SIZE: u32 = 200;
+
+/// Step input set when the workflow turned caching off, so pages are extracted afresh
+pub const BYPASS_CONTENT_CACHE_KEY: &str = "bypass_content_cache";
 
 /// Execution context for workflow steps
 #[derive(Debug, Clone)]
@@ -53,6 +86,11 @@ pub struct WorkflowEngine {
     api_manager: Arc<RwLock<ApiManagerService>>,
     active_workflows: Arc<RwLock<HashMap<Uuid, Arc<Mutex<ResearchWorkflow>>>>>,
     executors: HashMap<ResearchMethodology, Box<dyn WorkflowExecutor>>,
+    prompt_library: Arc<PromptLibrary>,
+    result_stream: Arc<ResultStream>,
+    single_flight: Arc<SingleFlight>,
+    knowledge_graph: KnowledgeGraphSlot,
+    nlp_engine: NlpEngineSlot,
 }
 
 impl WorkflowEngine {
@@ -60,10 +98,12 @@ impl WorkflowEngine {
     pub async fn new(
         data_persistence: Arc<RwLock<DataPersistenceService>>,
         api_manager: Arc<RwLock<ApiManagerService>>,
+        prompt_library: Arc<PromptLibrary>,
     ) -> AppResult<Self> {
         info!("Initializing workflow engine...");
 
         let mut executors: HashMap<ResearchMethodology, Box<dyn WorkflowExecutor>> = HashMap::new();
+        let knowledge_graph = KnowledgeGraphSlot::default();
         
         // Register methodology executors
         executors.insert(
@@ -78,18 +118,55 @@ impl WorkflowEngine {
             ResearchMethodology::Hybrid,
             Box::new(super::methodology_hybrid::HybridMethodology::new()),
         );
+        executors.insert(
+            ResearchMethodology::DocumentAnalysis,
+            Box::new(super::methodology_document::DocumentAnalysisMethodology::new(knowledge_graph.clone())),
+        );
 
         let engine = Self {
             data_persistence,
             api_manager,
             active_workflows: Arc::new(RwLock::new(HashMap::new())),
             executors,
+            prompt_library,
+            result_stream: Arc::new(ResultStream::default()),
+            single_flight: Arc::new(SingleFlight::default()),
+            knowledge_graph,
+            nlp_engine: NlpEngineSlot::default(),
         };
 
         info!("Workflow engine initialized successfully");
         Ok(engine)
     }
 
+    /// Link the PDF sources of workflows into `knowledge_graph`, which is made after the
+    /// research engine
+    pub async fn set_knowledge_graph(&self, knowledge_graph: Arc<RwLock<KnowledgeGraphService>>) {
+        *self.knowledge_graph.write().await = Some(knowledge_graph);
+    }
+
+    /// Check the sources of workflows asking for it for contradictions with `nlp_engine`,
+    /// which is made after the research engine
+    pub async fn set_nlp_engine(&self, nlp_engine: Arc<RwLock<NLPEngineService>>) {
+        *self.nlp_engine.write().await = Some(nlp_engine);
+    }
+
+    /// Lay out the steps its methodology would run for `workflow`, without saving or
+    /// starting it
+    pub async fn prepare_steps(&self, workflow: &mut ResearchWorkflow) -> AppResult<()> {
+        let executor = self.executors.get(&workflow.parameters.methodology)
+            .ok_or_else(|| ApiError::invalid_configuration(
+                "methodology".to_string(),
+                format!("No executor found for methodology: {:?}", workflow.parameters.methodology)
+            ))?;
+        executor.prepare_steps(workflow).await
+    }
+
+    /// Partial results of running workflows, published as their steps complete
+    pub fn result_stream(&self) -> Arc<ResultStream> {
+        self.result_stream.clone()
+    }
+
     /// Start executing a workflow
     pub async fn start_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
         info!("Starting workflow execution: {}", workflow_id);
@@ -114,8 +191,24 @@ impl WorkflowEngine {
                 format!("No executor found for methodology: {:?}", workflow.parameters.methodology)
             ))?;
 
+        // An identical run already in flight serves this workflow too, when it opts in
+        if workflow.parameters.share_in_flight_runs {
+            let key = single_flight::execution_key(&workflow);
+            if let Flight::Follower(leader_id) = self.single_flight.join(key, workflow_id) {
+                info!("Workflow {} shares the in-flight run of identical workflow {}", workflow_id, leader_id);
+                workflow.metadata.insert(COALESCED_WITH_KEY.to_string(), leader_id.to_string());
+                workflow.start();
+                let data_persistence = self.data_persistence.write().await;
+                data_persistence.save_research_workflow(&workflow).await?;
+                return Ok(());
+            }
+        }
+
         // Prepare workflow steps
-        executor.prepare_steps(&mut workflow).await?;
+        if let Err(e) = executor.prepare_steps(&mut workflow).await {
+            self.finish_followers(workflow_id, None, Some(&e.to_string())).await;
+            return Err(e);
+        }
 
         // Mark workflow as running
         workflow.start();
@@ -131,13 +224,21 @@ impl WorkflowEngine {
         data_persistence.save_research_workflow(&workflow).await?;
         drop(data_persistence);
 
-        // Start execution in background
+        // Start execution in background under a root span for the whole run
+        let workflow_span = info_span!(
+            parent: None,
+            "research.workflow",
+            workflow.id = %workflow_id,
+            workflow.methodology = ?workflow.parameters.methodology,
+            workflow.steps = workflow.steps.len(),
+        );
         let engine_clone = self.clone_for_execution();
-        tokio::spawn(async move {
+        let scope = tenant_scope::workflow_scope(&workflow)?;
+        tokio::spawn(run_scoped(scope, async move {
             if let Err(e) = engine_clone.execute_workflow_steps(workflow_id).await {
                 error!("Workflow execution failed: {}", e);
             }
-        });
+        }).instrument(workflow_span));
 
         info!("Workflow execution started: {}", workflow_id);
         Ok(())
@@ -177,7 +278,29 @@ impl WorkflowEngine {
                     let retryable_steps = workflow.get_retryable_steps();
                     if retryable_steps.is_empty() {
                         error!("Workflow {} has failed steps with no retries available", workflow_id);
-                        self.fail_workflow(workflow_id, "Workflow failed with non-retryable errors".to_string()).await?;
+                        let failure_mode = workflow.parameters.failure_mode;
+                        // A refused query fails the run even when partial results are kept
+                        // otherwise, so a refusal never passes for an incomplete report
+                        let refused = workflow.steps.iter()
+                            .any(|step| step.status == StepStatus::Failed && step.metadata.contains_key(POLICY_REFUSED_KEY));
+                        let error = if let Some(step) = workflow.permanently_failed_step() {
+                            format!("Step '{}' failed and cannot be retried: {}",
+                                step.name, step.error_message.as_deref().unwrap_or("unknown error"))
+                        } else {
+                            match workflow.parameters.retry_budget {
+                                Some(budget) if workflow.retry_budget_exhausted() => {
+                                    format!("Workflow failed after spending its retry budget of {} retries", budget)
+                                }
+                                _ => "Workflow failed with non-retryable errors".to_string(),
+                            }
+                        };
+                        drop(workflow);
+
+                        if failure_mode == FailureMode::Lenient && !refused && !step_results.is_empty() {
+                            self.complete_workflow(workflow_id, step_results, Some(error)).await?;
+                        } else {
+                            self.fail_workflow(workflow_id, error).await?;
+                        }
                         return Ok(());
                     }
                 } else {
@@ -203,9 +326,17 @@ impl WorkflowEngine {
                     Ok(result) => {
                         step_results.push(result.clone());
                         // Merge step output into shared data
-                        for (key, value) in result {
+                        for (key, value) in result.clone() {
                             shared_data.insert(key, value);
                         }
+
+                        // Publish what the run has so far, now that the step's output is all in
+                        let (completed, total) = {
+                            let workflow = workflow_arc.lock().await;
+                            let completed = workflow.steps.iter().filter(|s| s.status == StepStatus::Completed).count();
+                            (completed as u32, workflow.steps.len() as u32)
+                        };
+                        self.result_stream.publish_step(workflow_id, &step, &result, &shared_data, completed, total);
                     }
                     Err(e) => {
                         warn!("Step {} failed: {}", step.id, e);
@@ -229,7 +360,7 @@ impl WorkflowEngine {
         }
 
         // Complete workflow
-        self.complete_workflow(workflow_id, step_results).await?;
+        self.complete_workflow(workflow_id, step_results, None).await?;
         Ok(())
     }
 
@@ -256,12 +387,22 @@ impl WorkflowEngine {
             }
         }
 
-        let (step, methodology) = {
+        let (step, methodology, provider_recording, egress_profile, query, recency, enable_caching, insight_confidence, scope) = {
             let workflow = workflow_arc.lock().await;
             let step = workflow.get_step(step_id)
                 .ok_or_else(|| ApiError::not_found("Step".to_string(), step_id.to_string()))?
                 .clone();
-            (step, workflow.parameters.methodology.clone())
+            (
+                step,
+                workflow.parameters.methodology.clone(),
+                workflow.parameters.provider_recording.clone(),
+                workflow.parameters.egress_profile.clone(),
+                workflow.query.clone(),
+                workflow.parameters.search_recency(),
+                workflow.parameters.enable_caching,
+                workflow.parameters.insight_confidence,
+                tenant_scope::workflow_scope(&workflow)?,
+            )
         };
 
         // Get executor
@@ -272,7 +413,7 @@ impl WorkflowEngine {
             ))?;
 
         // Create execution context
-        let context = ExecutionContext {
+        let mut context = ExecutionContext {
             workflow_id,
             step_id,
             input_data: step.input_data.clone(),
@@ -280,29 +421,160 @@ impl WorkflowEngine {
             metadata: step.metadata.clone(),
         };
 
+        // LLM steps name their system prompt; render the version this run gets
+        let prompt = match self.resolve_step_prompt(workflow_id, &query, &step).await {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                let mut workflow = workflow_arc.lock().await;
+                if let Some(workflow_step) = workflow.get_step_mut(step_id) {
+                    workflow_step.fail(e.to_string());
+                }
+                return Err(e);
+            }
+        };
+        if let Some(prompt) = &prompt {
+            context.input_data.insert(prompt_library::SYSTEM_PROMPT_KEY.to_string(), serde_json::Value::String(prompt.text.clone()));
+        }
+        if !recency.is_any() {
+            context.input_data.insert(SEARCH_RECENCY_KEY.to_string(), serde_json::to_value(recency)?);
+        }
+        if !enable_caching {
+            context.input_data.insert(BYPASS_CONTENT_CACHE_KEY.to_string(), serde_json::Value::Bool(true));
+        }
+        if insight_confidence.is_active() {
+            context.input_data.insert(structured_output::INSIGHT_CONFIDENCE_KEY.to_string(), serde_json::to_value(insight_confidence)?);
+        }
+
         // Execute step
+        let step_span = info_span!(
+            "research.step",
+            workflow.id = %workflow_id,
+            workflow.methodology = ?methodology,
+            step.id = %step_id,
+            step.number = step.step_number,
+            "step.type" = %step.name,
+            step.provider = step.service_provider.as_deref().unwrap_or("none"),
+            step.served_by = tracing::field::Empty,
+            step.model = tracing::field::Empty,
+        );
         let api_manager = self.api_manager.read().await;
+        // Every step of the workflow goes out through its profile; a profile removed
+        // mid-run fails the step rather than letting it fall back to the default egress
+        let egress_clients = match &egress_profile {
+            Some(profile) => match api_manager.egress_clients(profile).await {
+                Ok(clients) => Some(clients),
+                Err(e) => {
+                    drop(api_manager);
+                    let mut workflow = workflow_arc.lock().await;
+                    if let Some(workflow_step) = workflow.get_step_mut(step_id) {
+                        workflow_step.fail_permanently(e.to_string());
+                    }
+                    return Err(e);
+                }
+            },
+            None => None,
+        };
         let mut step_copy = step.clone();
-        let result = executor.execute_step(&mut step_copy, &context, &*api_manager).await;
+        let timeout_seconds = step.effective_timeout_seconds(&methodology);
+        // Steps are matched to their recording by number, since a replayed run gets fresh step IDs
+        let execution = executor.execute_step(&mut step_copy, &context, &*api_manager);
+        let execution = response_recorder::step_scope(workflow_id, step.step_number, provider_recording, execution);
+        let execution = egress::egress_scope(egress_clients, execution);
+        // Key lookups spend the keys of the tenant the workflow runs for
+        let execution = run_scoped(scope, execution);
+        // Count the provider calls, tokens and bytes the attempt uses
+        let meter = Arc::new(CallMeter::default());
+        let execution = call_meter::meter_scope(meter.clone(), execution)
+            .instrument(step_span.clone());
+        let started_at = Utc::now();
+        let timer = std::time::Instant::now();
+        // Timing out drops the execution future, and with it the in-flight provider request
+        let result = match tokio::time::timeout(std::time::Duration::from_secs(timeout_seconds as u64), execution).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("Step {} exceeded its {}s timeout", step_id, timeout_seconds);
+                Err(AppError::Timeout {
+                    operation: format!("step '{}' after {}s", step.name, timeout_seconds),
+                })
+            }
+        };
         drop(api_manager);
 
+        // Steps with a fallback chain report which provider served them
+        let served_by = result.as_ref().ok()
+            .and_then(|output| output.get(SERVED_BY_KEY))
+            .and_then(|provider| provider.as_str())
+            .map(str::to_string);
+        if let Some(provider) = &served_by {
+            step_span.record("step.served_by", provider.as_str());
+        }
+        let served_by_model = result.as_ref().ok()
+            .and_then(|output| output.get(SERVED_BY_MODEL_KEY))
+            .and_then(|model| model.as_str())
+            .map(str::to_string);
+        if let Some(model) = &served_by_model {
+            step_span.record("step.model", model.as_str());
+        }
+
+        let counts = meter.counts();
+        self.record_step_metrics(StepExecutionMetrics {
+            workflow_id,
+            step_id,
+            step_number: step.step_number,
+            step_name: step.name.clone(),
+            service_provider: served_by.clone().or_else(|| step.service_provider.clone()),
+            started_at,
+            completed_at: Utc::now(),
+            wall_time_ms: timer.elapsed().as_millis() as u64,
+            provider_calls: counts.calls,
+            failed_provider_calls: counts.failed_calls,
+            tokens: counts.tokens,
+            bytes_fetched: counts.bytes_fetched,
+            succeeded: result.is_ok(),
+        }).await;
+
         // Update step with result
         {
             let mut workflow = workflow_arc.lock().await;
+            let retry_budget_left = !workflow.retry_budget_exhausted();
             if let Some(workflow_step) = workflow.get_step_mut(step_id) {
                 match result {
                     Ok(ref output) => {
+                        if let Some(provider) = served_by {
+                            workflow_step.metadata.insert(SERVED_BY_KEY.to_string(), provider);
+                        }
+                        if let Some(model) = served_by_model {
+                            workflow_step.metadata.insert(SERVED_BY_MODEL_KEY.to_string(), model);
+                        }
+                        if let Some(prompt) = &prompt {
+                            record_prompt(&mut workflow_step.metadata, prompt);
+                        }
                         workflow_step.complete(output.clone());
                         debug!("Step {} completed successfully", step_id);
                     }
                     Err(ref e) => {
-                        workflow_step.fail(e.to_string());
+                        if let Some(prompt) = &prompt {
+                            record_prompt(&mut workflow_step.metadata, prompt);
+                        }
+                        // A refused query is refused again however often it is sent, and
+                        // offline mode refuses every provider request
+                        let refused = matches!(e, AppError::Api(api_error) if api_error.is_content_policy_rejection());
+                        if refused {
+                            workflow_step.metadata.insert(POLICY_REFUSED_KEY.to_string(), "true".to_string());
+                        }
+                        if e.is_offline() || refused {
+                            workflow_step.fail_permanently(e.to_string());
+                        } else {
+                            workflow_step.fail(e.to_string());
+                        }
                         error!("Step {} failed: {}", step_id, e);
                         
                         // Check if step should be retried
-                        if workflow_step.should_retry() {
+                        if workflow_step.should_retry() && retry_budget_left {
                             workflow_step.increment_retry();
                             info!("Step {} will be retried (attempt {})", step_id, workflow_step.retry_count);
+                        } else if workflow_step.should_retry() {
+                            warn!("Step {} will not be retried: the workflow's retry budget is spent", step_id);
                         }
                     }
                 }
@@ -312,11 +584,27 @@ impl WorkflowEngine {
         result
     }
 
-    /// Complete a workflow
+    /// Complete a workflow. With `failure`, the workflow fails instead and keeps the results
+    /// of its completed steps, marked partial.
     async fn complete_workflow(
         &self,
         workflow_id: Uuid,
         step_results: Vec<HashMap<String, serde_json::Value>>,
+        failure: Option<String>,
+    ) -> AppResult<()> {
+        let completed = self.finalize_workflow(workflow_id, step_results, failure).await;
+        // Workflows sharing the run must not wait on one that could not finish
+        if let Err(e) = &completed {
+            self.finish_followers(workflow_id, None, Some(&e.to_string())).await;
+        }
+        completed
+    }
+
+    async fn finalize_workflow(
+        &self,
+        workflow_id: Uuid,
+        step_results: Vec<HashMap<String, serde_json::Value>>,
+        failure: Option<String>,
     ) -> AppResult<()> {
         info!("Completing workflow: {}", workflow_id);
 
@@ -339,12 +627,77 @@ impl WorkflowEngine {
             ))?;
 
         // Post-process results
-        let final_results = executor.post_process_results(&workflow, &step_results).await?;
+        let mut final_results = executor.post_process_results(&workflow, &step_results).await?;
+
+        // Keep insights below the workflow's minimum confidence out of the report
+        if let Some(summary) = structured_output::apply_confidence_filter(&mut final_results, &workflow.parameters.insight_confidence)? {
+            if summary.filtered_count > 0 {
+                info!("Filtered {} of {} insights of workflow {} below confidence {:.2}",
+                    summary.filtered_count, summary.filtered_count + summary.kept_count, workflow_id, summary.min_confidence);
+            }
+        }
+
+        let sources = overlap_checker::collect_source_texts(&step_results);
+
+        // Flag report passages copied near-verbatim from the sources
+        let overlap_check = &workflow.parameters.overlap_check;
+        if overlap_check.enabled {
+            let mut report = overlap_checker::check_overlap(&final_results.content, &sources, overlap_check);
+            if report.has_flagged_spans() {
+                warn!("Workflow {} report repeats its sources in {} spans", workflow_id, report.flagged_spans.len());
+                if overlap_check.paraphrase_flagged {
+                    let rewrite_prompt = self.prompt_library
+                        .resolve(prompt_library::OVERLAP_REWRITE, None, workflow_id, &HashMap::new())
+                        .await?;
+                    let api_manager = self.api_manager.read().await;
+                    final_results.content = overlap_checker::paraphrase_flagged_spans(
+                        &final_results.content,
+                        &mut report,
+                        &rewrite_prompt.text,
+                        &*api_manager,
+                    ).await;
+                    final_results.word_count = final_results.content.split_whitespace().count() as u32;
+                }
+            }
+            final_results.metadata.insert(overlap_checker::OVERLAP_METADATA_KEY.to_string(), serde_json::to_value(&report)?);
+        }
+
+        // Surface claims the sources disagree on; a report goes out without them rather
+        // than fail over the check
+        let contradiction_check = &workflow.parameters.contradiction_check;
+        if contradiction_check.enabled {
+            let nlp_engine = self.nlp_engine.read().await.clone();
+            match nlp_engine {
+                Some(nlp_engine) => match contradiction_detector::surface_contradictions(
+                    &mut final_results,
+                    &sources,
+                    &workflow.parameters.insight_confidence,
+                    contradiction_check,
+                    &*nlp_engine,
+                ).await {
+                    Ok(0) => {}
+                    Ok(contradictions) => info!("Found {} contradictions between the sources of workflow {}", contradictions, workflow_id),
+                    Err(e) => warn!("Failed to check the sources of workflow {} for contradictions: {}", workflow_id, e),
+                },
+                None => warn!("No NLP engine to check the sources of workflow {} for contradictions", workflow_id),
+            }
+        }
+
+        // Count the run toward any prompt experiment that served it; a partial run counts as a failure
+        self.record_prompt_outcomes(&workflow, failure.is_none().then_some(&final_results)).await;
+
+        self.result_stream.publish_final(workflow_id, Some(&final_results), failure.is_some());
 
         // Update workflow with results
         {
             let mut workflow = workflow_arc.lock().await;
-            workflow.complete(final_results.into());
+            match failure {
+                None => workflow.complete(final_results.into()),
+                Some(error) => {
+                    warn!("Workflow {} failed; keeping results from its completed steps", workflow_id);
+                    workflow.fail_with_partial_results(final_results.into(), error);
+                }
+            }
         }
 
         // Remove from active workflows
@@ -358,7 +711,13 @@ impl WorkflowEngine {
         data_persistence.save_research_workflow(&workflow).await?;
         drop(data_persistence);
 
-        info!("Workflow completed successfully: {}", workflow_id);
+        if workflow.status == WorkflowStatus::Completed {
+            info!("Workflow completed successfully: {}", workflow_id);
+        }
+        let (results, error) = (workflow.results.clone(), workflow.error_message.clone());
+        drop(workflow);
+
+        self.finish_followers(workflow_id, results.as_ref(), error.as_deref()).await;
         Ok(())
     }
 
@@ -376,7 +735,11 @@ impl WorkflowEngine {
         {
             let mut workflow = workflow_arc.lock().await;
             workflow.fail(error);
+            self.record_prompt_outcomes(&workflow, None).await;
         }
+        self.result_stream.publish_final(workflow_id, None, true);
+        let error = workflow_arc.lock().await.error_message.clone();
+        self.finish_followers(workflow_id, None, error.as_deref()).await;
 
         // Remove from active workflows
         let mut active_workflows = self.active_workflows.write().await;
@@ -392,6 +755,170 @@ impl WorkflowEngine {
         Ok(())
     }
 
+    /// Give the workflows that waited on `leader_id`'s run its outcome: its results, and
+    /// the error it failed with, if any
+    async fn finish_followers(&self, leader_id: Uuid, results: Option<&ResearchResults>, error: Option<&str>) {
+        for follower_id in self.single_flight.finish(leader_id) {
+            let data_persistence = self.data_persistence.write().await;
+            let mut follower = match data_persistence.get_research_workflow(follower_id).await {
+                Ok(Some(follower)) if follower.status == WorkflowStatus::Running => follower,
+                Ok(_) => continue,
+                Err(e) => {
+                    error!("Failed to load workflow {} waiting on run {}: {}", follower_id, leader_id, e);
+                    continue;
+                }
+            };
+
+            settle_follower(&mut follower, leader_id, results, error);
+            if let Err(e) = data_persistence.save_research_workflow(&follower).await {
+                error!("Failed to save workflow {} waiting on run {}: {}", follower_id, leader_id, e);
+                continue;
+            }
+            drop(data_persistence);
+
+            self.result_stream.publish_final(follower_id, results, error.is_some());
+            info!("Workflow {} finished with the shared run of workflow {}", follower_id, leader_id);
+        }
+    }
+
+    /// Start the workflows that waited on a run that will not finish, each on its own
+    /// or sharing whichever of them starts first
+    async fn restart_followers(&self, leader_id: Uuid) {
+        for follower_id in self.single_flight.finish(leader_id) {
+            self.restart_follower(follower_id, leader_id).await;
+        }
+    }
+
+    async fn restart_follower(&self, follower_id: Uuid, leader_id: Uuid) {
+        let data_persistence = self.data_persistence.write().await;
+        let saved = match data_persistence.get_research_workflow(follower_id).await {
+            Ok(Some(mut follower)) if follower.status == WorkflowStatus::Running => {
+                follower.status = WorkflowStatus::Created;
+                follower.metadata.remove(COALESCED_WITH_KEY);
+                data_persistence.save_research_workflow(&follower).await.map(|_| true)
+            }
+            Ok(_) => Ok(false),
+            Err(e) => Err(e),
+        };
+        drop(data_persistence);
+
+        match saved {
+            Ok(true) => {
+                if let Err(e) = self.start_workflow(follower_id).await {
+                    error!("Failed to restart workflow {} after run {} ended without results: {}", follower_id, leader_id, e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => error!("Failed to reset workflow {} after run {} ended without results: {}", follower_id, leader_id, e),
+        }
+    }
+
+    /// Settle the workflows that were waiting on another's run when the app last
+    /// stopped: which run serves which is only kept in memory, so nothing would finish
+    /// them otherwise. Those whose run completed or failed get its outcome; the rest,
+    /// whose run was cancelled or cut off by the shutdown, are started on their own.
+    /// Returns how many were settled.
+    pub async fn reconcile_followers(&self) -> AppResult<usize> {
+        let mut followers = Vec::new();
+        {
+            let data_persistence = self.data_persistence.read().await;
+            let mut search = WorkflowSearchFilters {
+                status: Some(format!("{:?}", WorkflowStatus::Running)),
+                limit: Some(RECONCILE_PAGE_SIZE),
+                offset: Some(0),
+                ..Default::default()
+            };
+            loop {
+                let page = data_persistence.browse_workflows(&search).await?;
+                for listed in &page {
+                    let Some(workflow) = data_persistence.get_research_workflow(listed.workflow_id).await? else {
+                        continue;
+                    };
+                    let leader_id = workflow.metadata.get(COALESCED_WITH_KEY).and_then(|id| Uuid::parse_str(id).ok());
+                    if let Some(leader_id) = leader_id {
+                        followers.push((workflow, leader_id));
+                    }
+                }
+                if page.len() < RECONCILE_PAGE_SIZE as usize {
+                    break;
+                }
+                search.offset = search.offset.map(|offset| offset + RECONCILE_PAGE_SIZE);
+            }
+        }
+
+        let settled = followers.len();
+        for (mut follower, leader_id) in followers {
+            let leader = self.data_persistence.read().await.get_research_workflow(leader_id).await?;
+            let outcome = match &leader {
+                Some(leader) if leader.status == WorkflowStatus::Completed => Some((leader.results.as_ref(), None)),
+                Some(leader) if leader.status == WorkflowStatus::Failed => {
+                    Some((leader.results.as_ref(), Some(leader.error_message.as_deref().unwrap_or("unknown error"))))
+                }
+                _ => None,
+            };
+            let Some((results, error)) = outcome else {
+                info!("Restarting workflow {}, whose shared run of workflow {} did not finish", follower.id, leader_id);
+                self.restart_follower(follower.id, leader_id).await;
+                continue;
+            };
+
+            settle_follower(&mut follower, leader_id, results, error);
+            self.data_persistence.read().await.save_research_workflow(&follower).await?;
+            info!("Workflow {} finished with the shared run of workflow {} after a restart", follower.id, leader_id);
+        }
+        Ok(settled)
+    }
+
+    /// The prompt a step runs with, if it names one in `PROMPT_ID_KEY`. Placeholders are
+    /// filled from the step's string inputs and the workflow `query`.
+    async fn resolve_step_prompt(
+        &self,
+        workflow_id: Uuid,
+        query: &str,
+        step: &WorkflowStep,
+    ) -> AppResult<Option<ResolvedPrompt>> {
+        let Some(prompt_id) = step.input_data.get(prompt_library::PROMPT_ID_KEY).and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        let pinned_version = step.input_data.get(prompt_library::PROMPT_VERSION_KEY)
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32);
+
+        let mut variables: HashMap<String, String> = step.input_data.iter()
+            .filter_map(|(key, value)| value.as_str().map(|value| (key.clone(), value.to_string())))
+            .collect();
+        variables.insert("query".to_string(), query.to_string());
+
+        let prompt = self.prompt_library.resolve(prompt_id, pinned_version, workflow_id, &variables).await?;
+        Ok(Some(prompt))
+    }
+
+    /// Persist what a step attempt used; analysis goes without it rather than fail the step
+    async fn record_step_metrics(&self, metrics: StepExecutionMetrics) {
+        let data_persistence = self.data_persistence.read().await;
+        if let Err(e) = data_persistence.save_step_metrics(&metrics).await {
+            warn!("Failed to record metrics of step {} of workflow {}: {}", metrics.step_id, metrics.workflow_id, e);
+        }
+    }
+
+    /// Add a finished run to the experiment arms its steps were served by
+    async fn record_prompt_outcomes(&self, workflow: &ResearchWorkflow, results: Option<&ResearchResults>) {
+        let mut recorded = std::collections::HashSet::new();
+        for step in &workflow.steps {
+            let (Some(prompt_id), Some(arm)) = (
+                step.metadata.get(prompt_library::PROMPT_ID_KEY),
+                step.metadata.get(prompt_library::PROMPT_ARM_KEY).and_then(|arm| ExperimentArm::parse(arm)),
+            ) else {
+                continue;
+            };
+            if recorded.insert(prompt_id.clone()) {
+                if let Err(e) = self.prompt_library.record_outcome(prompt_id, arm, results).await {
+                    warn!("Failed to record prompt experiment outcome for {}: {}", prompt_id, e);
+                }
+            }
+        }
+    }
+
     /// Clone engine for background execution
     fn clone_for_execution(&self) -> WorkflowEngineClone {
         WorkflowEngineClone {
@@ -451,6 +978,18 @@ impl WorkflowEngine {
     pub async fn cancel_workflow(&self, workflow_id: Uuid) -> AppResult<()> {
         info!("Cancelling workflow: {}", workflow_id);
 
+        // A workflow sharing another's run just stops waiting on it
+        if self.single_flight.leave(workflow_id) {
+            let data_persistence = self.data_persistence.write().await;
+            let mut workflow = data_persistence.get_research_workflow(workflow_id).await?
+                .ok_or_else(|| ApiError::not_found("Workflow".to_string(), workflow_id.to_string()))?;
+            workflow.cancel();
+            data_persistence.save_research_workflow(&workflow).await?;
+            drop(data_persistence);
+            self.result_stream.publish_final(workflow_id, None, true);
+            return Ok(());
+        }
+
         let workflow_arc = {
             let active_workflows = self.active_workflows.read().await;
             active_workflows.get(&workflow_id).cloned()
@@ -471,7 +1010,12 @@ impl WorkflowEngine {
         let data_persistence = self.data_persistence.write().await;
         data_persistence.save_research_workflow(&workflow).await?;
         drop(data_persistence);
+        drop(workflow);
+        // Subscribers stop waiting for snapshots that will not come
+        self.result_stream.publish_final(workflow_id, None, true);
 
+        // The workflows that shared the cancelled run still want results
+        self.restart_followers(workflow_id).await;
         Ok(())
     }
 
@@ -489,6 +1033,27 @@ impl WorkflowEngine {
     }
 }
 
+/// Give a workflow that waited on `leader_id`'s run that run's outcome. The shared
+/// results already carry the leader's partial flag and failed steps.
+fn settle_follower(follower: &mut ResearchWorkflow, leader_id: Uuid, results: Option<&ResearchResults>, error: Option<&str>) {
+    match (results, error) {
+        (Some(results), None) => follower.complete(results.clone()),
+        (results, error) => {
+            follower.results = results.cloned();
+            follower.fail(format!("Shared run of workflow {} failed: {}", leader_id, error.unwrap_or("no results")));
+        }
+    }
+}
+
+/// Record on a step which prompt version, and experiment arm, served it
+fn record_prompt(metadata: &mut HashMap<String, String>, prompt: &ResolvedPrompt) {
+    metadata.insert(prompt_library::PROMPT_ID_KEY.to_string(), prompt.template.id.clone());
+    metadata.insert(prompt_library::PROMPT_VERSION_KEY.to_string(), prompt.template.version.to_string());
+    if let Some(arm) = prompt.arm {
+        metadata.insert(prompt_library::PROMPT_ARM_KEY.to_string(), arm.as_str().to_string());
+    }
+}
+
 /// Simplified clone for background execution
 #[derive(Clone)]
 struct WorkflowEngineClone {
@@ -504,3 +1069,204 @@ impl WorkflowEngineClone {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::api_key::ServiceProvider;
+    use crate::models::research_workflow::{ProviderRecording, WorkflowParameters};
+    use crate::services::{MonitoringService, SecurityService};
+    use crate::services::data_persistence::DatabaseConfig;
+
+    fn request(service: ServiceProvider, endpoint: &str) -> ServiceRequest {
+        ServiceRequest {
+            request_id: Uuid::new_v4(),
+            service,
+            endpoint: endpoint.to_string(),
+            method: "POST".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            timeout_ms: 1000,
+            retry_count: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// A search step calling two providers, then a synthesis step calling one
+    struct ProviderCallsExecutor;
+
+    #[async_trait::async_trait]
+    impl WorkflowExecutor for ProviderCallsExecutor {
+        async fn execute_step(
+            &self,
+            step: &mut WorkflowStep,
+            _context: &ExecutionContext,
+            api_manager: &ApiManagerService,
+        ) -> AppResult<HashMap<String, serde_json::Value>> {
+            let calls: &[(ServiceProvider, &str)] = match step.step_number {
+                1 => &[(ServiceProvider::Exa, "/search"), (ServiceProvider::Tavily, "/search")],
+                _ => &[(ServiceProvider::OpenRouter, "/chat/completions")],
+            };
+            for (service, endpoint) in calls {
+                api_manager.make_service_request(*service, request(*service, endpoint)).await?;
+            }
+            Ok(HashMap::from([("methodology_step".to_string(), serde_json::Value::String(step.name.clone()))]))
+        }
+
+        fn methodology(&self) -> ResearchMethodology {
+            ResearchMethodology::Hybrid
+        }
+
+        async fn prepare_steps(&self, workflow: &mut ResearchWorkflow) -> AppResult<()> {
+            workflow.steps = vec![
+                WorkflowStep::new(workflow.id, 1, "Web Search".to_string(), String::new()),
+                WorkflowStep::new(workflow.id, 2, "Synthesis".to_string(), String::new()),
+            ];
+            Ok(())
+        }
+
+        async fn post_process_results(
+            &self,
+            workflow: &ResearchWorkflow,
+            _step_results: &[HashMap<String, serde_json::Value>],
+        ) -> AppResult<ResearchResults> {
+            Ok(ResearchResults {
+                content: "# Report".to_string(),
+                sources: Vec::new(),
+                metadata: HashMap::new(),
+                word_count: 2,
+                source_count: 0,
+                methodology_used: workflow.parameters.methodology.clone(),
+                execution_time_ms: 0,
+                partial: false,
+                failed_steps: Vec::new(),
+            })
+        }
+    }
+
+    /// A single search step that never finishes
+    struct HangingExecutor;
+
+    #[async_trait::async_trait]
+    impl WorkflowExecutor for HangingExecutor {
+        async fn execute_step(
+            &self,
+            _step: &mut WorkflowStep,
+            _context: &ExecutionContext,
+            _api_manager: &ApiManagerService,
+        ) -> AppResult<HashMap<String, serde_json::Value>> {
+            std::future::pending().await
+        }
+
+        fn methodology(&self) -> ResearchMethodology {
+            ResearchMethodology::Hybrid
+        }
+
+        async fn prepare_steps(&self, workflow: &mut ResearchWorkflow) -> AppResult<()> {
+            let mut step = WorkflowStep::new(workflow.id, 1, "Web Search".to_string(), String::new());
+            step.timeout_seconds = Some(30);
+            workflow.steps = vec![step];
+            Ok(())
+        }
+
+        async fn post_process_results(
+            &self,
+            _workflow: &ResearchWorkflow,
+            _step_results: &[HashMap<String, serde_json::Value>],
+        ) -> AppResult<ResearchResults> {
+            unreachable!("the step never completes")
+        }
+    }
+
+    /// An engine over services in `dir`, running Hybrid workflows with `executor`
+    async fn engine_with(dir: &std::path::Path, executor: Box<dyn WorkflowExecutor>) -> (WorkflowEngine, Arc<RwLock<DataPersistenceService>>) {
+        let security = Arc::new(RwLock::new(
+            SecurityService::with_paths(dir.join("key_vault.db"), dir.join("audit_log.db")).await.unwrap(),
+        ));
+        let config = DatabaseConfig::Sqlite { path: dir.join("app.db"), encryption: None };
+        let data_persistence = Arc::new(RwLock::new(DataPersistenceService::with_config(security.clone(), config).await.unwrap()));
+        let monitoring = Arc::new(RwLock::new(MonitoringService::new(data_persistence.clone()).await.unwrap()));
+        let api_manager = ApiManagerService::new(data_persistence.clone(), security, monitoring).await.unwrap();
+        let prompt_library = Arc::new(PromptLibrary::new(data_persistence.clone()).await.unwrap());
+        let mut engine = WorkflowEngine::new(data_persistence.clone(), Arc::new(RwLock::new(api_manager)), prompt_library).await.unwrap();
+        engine.executors.insert(ResearchMethodology::Hybrid, executor);
+        (engine, data_persistence)
+    }
+
+    #[tokio::test]
+    async fn test_a_mocked_workflow_records_the_actuals_of_each_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let (engine, data_persistence) = engine_with(dir.path(), Box::new(ProviderCallsExecutor)).await;
+
+        let parameters = WorkflowParameters {
+            methodology: ResearchMethodology::Hybrid,
+            provider_recording: ProviderRecording::Mock { latency_ms: 0, failure_percent: 0 },
+            ..Default::default()
+        };
+        let mut workflow = ResearchWorkflow::new("Metered".to_string(), "query".to_string(), parameters, "test".to_string());
+        engine.prepare_steps(&mut workflow).await.unwrap();
+        workflow.start();
+        let workflow_id = workflow.id;
+        engine.active_workflows.write().await.insert(workflow_id, Arc::new(Mutex::new(workflow)));
+
+        // Run the steps in the foreground rather than on start_workflow's background task
+        engine.execute_workflow_steps(workflow_id).await.unwrap();
+
+        let metrics = data_persistence.read().await.get_workflow_execution_metrics(workflow_id).await.unwrap();
+        assert_eq!(metrics.steps.iter().map(|s| s.step_number).collect::<Vec<_>>(), vec![1, 2]);
+        let (search, synthesis) = (&metrics.steps[0], &metrics.steps[1]);
+        assert_eq!(search.step_name, "Web Search");
+        assert_eq!(search.provider_calls, 2);
+        assert!(search.bytes_fetched > 0);
+        assert_eq!(search.tokens, 0);
+        assert!(synthesis.succeeded && synthesis.tokens > 0);
+        assert_eq!(metrics.provider_calls, 3);
+        assert_eq!(metrics.tokens, synthesis.tokens);
+        assert_eq!(metrics.bytes_fetched, search.bytes_fetched + synthesis.bytes_fetched);
+        assert_eq!(metrics.failed_attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_a_step_that_never_finishes_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let (engine, data_persistence) = engine_with(dir.path(), Box::new(HangingExecutor)).await;
+
+        let parameters = WorkflowParameters {
+            methodology: ResearchMethodology::Hybrid,
+            retry_budget: Some(0),
+            ..Default::default()
+        };
+        let mut workflow = ResearchWorkflow::new("Hanging".to_string(), "query".to_string(), parameters, "test".to_string());
+        engine.prepare_steps(&mut workflow).await.unwrap();
+        workflow.start();
+        let (workflow_id, step_id) = (workflow.id, workflow.steps[0].id);
+        let workflow_arc = Arc::new(Mutex::new(workflow));
+        engine.active_workflows.write().await.insert(workflow_id, workflow_arc.clone());
+
+        // The runtime jumps to the timeout once the step is the only thing left waiting
+        tokio::time::pause();
+        let started = tokio::time::Instant::now();
+        let result = engine.execute_single_step(workflow_id, step_id, &HashMap::new()).await;
+        assert!(matches!(result, Err(AppError::Timeout { .. })), "expected a timeout, got {:?}", result);
+        let waited = started.elapsed();
+        assert!(waited >= std::time::Duration::from_secs(30) && waited < std::time::Duration::from_secs(31), "waited {:?}", waited);
+
+        {
+            let workflow = workflow_arc.lock().await;
+            let step = workflow.get_step(step_id).unwrap();
+            assert_eq!(step.status, StepStatus::Failed);
+            let expected = AppError::Timeout { operation: "step 'Web Search' after 30s".to_string() }.to_string();
+            assert_eq!(step.error_message.as_deref(), Some(expected.as_str()));
+            assert!(step.elapsed_seconds().is_some());
+            assert_eq!(step.effective_timeout_seconds(&workflow.parameters.methodology), 30);
+        }
+
+        // With no retries left, the timed out step fails the workflow
+        engine.execute_workflow_steps(workflow_id).await.unwrap();
+        let workflow = workflow_arc.lock().await;
+        assert_eq!(workflow.status, WorkflowStatus::Failed);
+        assert!(!engine.active_workflows.read().await.contains_key(&workflow_id));
+        let metrics = data_persistence.read().await.get_workflow_execution_metrics(workflow_id).await.unwrap();
+        assert!(!metrics.steps[0].succeeded);
+    }
+}
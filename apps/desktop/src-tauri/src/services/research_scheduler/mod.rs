@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use tracing::{info, debug, warn, error};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ResearchError};
+use crate::models::research_schedule::{
+    CreateScheduleRequest, ResearchSchedule, ResultDiff, ScheduleRun, ScheduleRunStatus, ScheduleTarget,
+};
+use crate::models::research_template::TemplateExecutionContext;
+use crate::models::research_workflow::{CreateWorkflowRequest, ResearchResults, WorkflowStatus};
+use crate::services::{DataPersistenceService, ResearchEngineService, TemplateManagerService};
+use crate::services::api_manager::{egress, run_scoped, tenant_scope};
+use crate::utils::{air_gap, inject_trace_context};
+
+/// How often due schedules and in-flight runs are checked
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+const WEBHOOK_TIMEOUT_MS: u32 = 15_000;
+
+/// How long a run may stay running before it is failed, so a workflow lost to a
+/// crash does not make every later firing skip
+const STALE_RUN_TIMEOUT_HOURS: i64 = 6;
+
+fn stale_run_timeout() -> chrono::Duration {
+    chrono::Duration::hours(STALE_RUN_TIMEOUT_HOURS)
+}
+
+/// Runs saved workflows and templates on cron schedules, records every run,
+/// and diffs each run's results against the previous one
+#[derive(Clone)]
+pub struct ResearchSchedulerService {
+    data_persistence: Arc<RwLock<DataPersistenceService>>,
+    research_engine: Arc<RwLock<ResearchEngineService>>,
+    template_manager: Arc<RwLock<TemplateManagerService>>,
+    http_client: reqwest::Client,
+}
+
+impl ResearchSchedulerService {
+    /// Create a new research scheduler service
+    pub async fn new(
+        data_persistence: Arc<RwLock<DataPersistenceService>>,
+        research_engine: Arc<RwLock<ResearchEngineService>>,
+        template_manager: Arc<RwLock<TemplateManagerService>>,
+    ) -> AppResult<Self> {
+        info!("Initializing research scheduler service...");
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(WEBHOOK_TIMEOUT_MS as u64))
+            .build()
+            .map_err(|e| ResearchError::invalid_schedule(format!("Failed to build webhook client: {}", e)))?;
+
+        Ok(Self {
+            data_persistence,
+            research_engine,
+            template_manager,
+            http_client,
+        })
+    }
+
+    /// Create a schedule; its first run is the next time the cron expression fires
+    pub async fn create_schedule(&self, request: CreateScheduleRequest) -> AppResult<ResearchSchedule> {
+        info!("Creating research schedule: {}", request.name);
+
+        if request.name.trim().is_empty() {
+            return Err(ResearchError::invalid_schedule("Schedule name cannot be empty").into());
+        }
+        if let Some(url) = &request.webhook_url {
+            validate_webhook_url(url)?;
+        }
+        self.validate_target(&request.target).await?;
+
+        let schedule = ResearchSchedule::new(request, Utc::now())
+            .map_err(ResearchError::invalid_schedule)?;
+
+        self.data_persistence.read().await.save_research_schedule(&schedule).await?;
+
+        info!("Created research schedule {} (next run {:?})", schedule.id, schedule.next_run_at);
+        Ok(schedule)
+    }
+
+    /// Get all schedules
+    pub async fn get_schedules(&self) -> AppResult<Vec<ResearchSchedule>> {
+        self.data_persistence.read().await.get_research_schedules().await
+    }
+
+    /// Get a schedule by ID
+    pub async fn get_schedule(&self, schedule_id: Uuid) -> AppResult<ResearchSchedule> {
+        self.get_schedules().await?
+            .into_iter()
+            .find(|schedule| schedule.id == schedule_id)
+            .ok_or_else(|| ResearchError::schedule_not_found(schedule_id.to_string()).into())
+    }
+
+    /// Stop a schedule firing until it is resumed
+    pub async fn pause_schedule(&self, schedule_id: Uuid) -> AppResult<ResearchSchedule> {
+        info!("Pausing research schedule: {}", schedule_id);
+
+        let mut schedule = self.get_schedule(schedule_id).await?;
+        schedule.paused = true;
+        schedule.updated_at = Utc::now();
+
+        self.data_persistence.read().await.save_research_schedule(&schedule).await?;
+        Ok(schedule)
+    }
+
+    /// Resume a paused schedule; runs missed while paused are not replayed
+    pub async fn resume_schedule(&self, schedule_id: Uuid) -> AppResult<ResearchSchedule> {
+        info!("Resuming research schedule: {}", schedule_id);
+
+        let mut schedule = self.get_schedule(schedule_id).await?;
+        schedule.paused = false;
+        schedule.advance(Utc::now()).map_err(ResearchError::invalid_schedule)?;
+
+        self.data_persistence.read().await.save_research_schedule(&schedule).await?;
+        Ok(schedule)
+    }
+
+    /// Delete a schedule and its run history; workflows it created are kept
+    pub async fn delete_schedule(&self, schedule_id: Uuid) -> AppResult<()> {
+        info!("Deleting research schedule: {}", schedule_id);
+
+        self.get_schedule(schedule_id).await?;
+        self.data_persistence.read().await.delete_research_schedule(schedule_id).await
+    }
+
+    /// Most recent runs of a schedule, newest first
+    pub async fn get_schedule_runs(&self, schedule_id: Uuid, limit: u32) -> AppResult<Vec<ScheduleRun>> {
+        self.data_persistence.read().await.get_schedule_runs(schedule_id, limit).await
+    }
+
+    /// Fire a schedule now, outside its cron timing; paused schedules can be run this way too
+    pub async fn run_schedule_now(&self, schedule_id: Uuid) -> AppResult<ScheduleRun> {
+        let mut schedule = self.get_schedule(schedule_id).await?;
+        self.fire(&mut schedule, Utc::now()).await
+    }
+
+    /// Start the background task that fires due schedules and finishes completed runs
+    pub async fn start_background_tasks(&self) -> AppResult<()> {
+        info!("Starting research scheduler background tasks...");
+
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = Utc::now();
+                if let Err(e) = scheduler.finish_completed_runs(now).await {
+                    error!("Failed to check scheduled runs: {}", e);
+                }
+                if let Err(e) = scheduler.run_due_schedules(now).await {
+                    error!("Failed to run due schedules: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Fire every schedule whose next run time has passed
+    pub async fn run_due_schedules(&self, now: DateTime<Utc>) -> AppResult<Vec<ScheduleRun>> {
+        let due: Vec<_> = self.get_schedules().await?
+            .into_iter()
+            .filter(|schedule| schedule.is_due(now))
+            .collect();
+
+        let mut runs = Vec::new();
+        for mut schedule in due {
+            match self.fire(&mut schedule, now).await {
+                Ok(run) => runs.push(run),
+                Err(e) => error!("Failed to run schedule {}: {}", schedule.id, e),
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Start one run of a schedule and move the schedule to its next run time
+    async fn fire(&self, schedule: &mut ResearchSchedule, now: DateTime<Utc>) -> AppResult<ScheduleRun> {
+        info!("Running research schedule: {} ({})", schedule.name, schedule.id);
+
+        let mut run = ScheduleRun::new(schedule.id, now);
+
+        // Overlapping runs would diff against a half-finished report
+        let in_flight = self.data_persistence.read().await
+            .get_running_schedule_runs().await?
+            .into_iter()
+            .any(|other| other.schedule_id == schedule.id && !other.is_stale(now, stale_run_timeout()));
+
+        if in_flight {
+            warn!("Skipping schedule {}: previous run still in progress", schedule.id);
+            run.finish(ScheduleRunStatus::Skipped, Some("Previous run still in progress".to_string()), now);
+        } else {
+            match self.start_workflow(schedule, now).await {
+                Ok(workflow_id) => run.workflow_id = Some(workflow_id),
+                Err(e) => {
+                    error!("Schedule {} failed to start: {}", schedule.id, e);
+                    run.finish(ScheduleRunStatus::Failed, Some(e.to_string()), now);
+                }
+            }
+        }
+
+        schedule.last_run_at = Some(now);
+        schedule.last_run_status = Some(run.status.clone());
+        if let Err(e) = schedule.advance(now) {
+            // An expression that no longer fires stops the schedule instead of retrying every tick
+            warn!("Pausing schedule {}: {}", schedule.id, e);
+            schedule.paused = true;
+            schedule.next_run_at = None;
+        }
+
+        let data_persistence = self.data_persistence.read().await;
+        data_persistence.save_schedule_run(&run).await?;
+        data_persistence.save_research_schedule(schedule).await?;
+        drop(data_persistence);
+
+        if run.status == ScheduleRunStatus::Failed {
+            self.deliver(schedule, &mut run).await?;
+        }
+
+        Ok(run)
+    }
+
+    /// Create and start the workflow for one run
+    async fn start_workflow(&self, schedule: &ResearchSchedule, now: DateTime<Utc>) -> AppResult<Uuid> {
+        let workflow_name = format!("{} ({})", schedule.name, now.format("%Y-%m-%d %H:%M UTC"));
+
+        let workflow = match &schedule.target {
+            ScheduleTarget::Workflow { workflow_id } => {
+                let research_engine = self.research_engine.read().await;
+                let saved = research_engine.get_workflow(*workflow_id).await?
+                    .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+
+                // Each run spends the keys of the tenant the saved workflow belongs to
+                run_scoped(tenant_scope::workflow_scope(&saved)?, research_engine.create_workflow_from_request(CreateWorkflowRequest {
+                    name: workflow_name,
+                    query: saved.query.clone(),
+                    template_id: saved.template_id,
+                    parameters: Some(saved.parameters.clone()),
+                    data_region: crate::services::data_persistence::data_residency::workflow_region(&saved)?,
+                    idempotency_key: None,
+                })).await?
+            }
+            ScheduleTarget::Template { template_id, parameters } => {
+                let template_manager = self.template_manager.read().await;
+                template_manager.execute_template(TemplateExecutionContext {
+                    template_id: *template_id,
+                    parameters: parameters.clone(),
+                    workflow_name,
+                    created_by: schedule.created_by.clone(),
+                    execution_metadata: HashMap::from([
+                        ("schedule_id".to_string(), serde_json::Value::String(schedule.id.to_string())),
+                    ]),
+                }).await?
+            }
+        };
+
+        self.research_engine.read().await.start_workflow_execution(workflow.id).await?;
+
+        debug!("Schedule {} started workflow {}", schedule.id, workflow.id);
+        Ok(workflow.id)
+    }
+
+    /// Close out runs whose workflows have finished, diffing and delivering their results
+    pub async fn finish_completed_runs(&self, now: DateTime<Utc>) -> AppResult<()> {
+        let running = self.data_persistence.read().await.get_running_schedule_runs().await?;
+        if running.is_empty() {
+            return Ok(());
+        }
+
+        let schedules: HashMap<Uuid, ResearchSchedule> = self.get_schedules().await?
+            .into_iter()
+            .map(|schedule| (schedule.id, schedule))
+            .collect();
+
+        for mut run in running {
+            let Some(mut schedule) = schedules.get(&run.schedule_id).cloned() else {
+                continue;
+            };
+            let Some(workflow_id) = run.workflow_id else {
+                run.finish(ScheduleRunStatus::Failed, Some("Run has no workflow".to_string()), now);
+                self.record_finished(&mut schedule, &mut run).await?;
+                continue;
+            };
+
+            let workflow = self.research_engine.read().await.get_workflow(workflow_id).await?;
+            match workflow.map(|workflow| (workflow.status.clone(), workflow)) {
+                Some((WorkflowStatus::Completed, workflow)) => {
+                    if schedule.diff_against_previous {
+                        if let Some(results) = &workflow.results {
+                            run.diff = Some(self.diff_with_previous_run(&run, results).await?);
+                        }
+                    }
+                    run.finish(ScheduleRunStatus::Completed, None, now);
+                }
+                Some((WorkflowStatus::Failed | WorkflowStatus::Cancelled, workflow)) => {
+                    let message = workflow.error_message
+                        .unwrap_or_else(|| format!("Workflow ended as {:?}", workflow.status));
+                    run.finish(ScheduleRunStatus::Failed, Some(message), now);
+                }
+                Some(_) if run.is_stale(now, stale_run_timeout()) => {
+                    warn!("Scheduled run {} of {} timed out; cancelling workflow {}", run.id, schedule.id, workflow_id);
+                    if let Err(e) = self.research_engine.read().await.cancel_workflow(workflow_id).await {
+                        warn!("Failed to cancel timed-out workflow {}: {}", workflow_id, e);
+                    }
+                    let message = format!("Run still in progress after {} hours", STALE_RUN_TIMEOUT_HOURS);
+                    run.finish(ScheduleRunStatus::Failed, Some(message), now);
+                }
+                Some(_) => continue,
+                None => run.finish(ScheduleRunStatus::Failed, Some(format!("Workflow {} no longer exists", workflow_id)), now),
+            }
+
+            self.record_finished(&mut schedule, &mut run).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn diff_with_previous_run(&self, run: &ScheduleRun, results: &ResearchResults) -> AppResult<ResultDiff> {
+        let previous = self.data_persistence.read().await
+            .get_schedule_runs(run.schedule_id, 20).await?
+            .into_iter()
+            .find(|other| other.id != run.id && other.status == ScheduleRunStatus::Completed);
+
+        let previous_results = match previous.as_ref().and_then(|previous| previous.workflow_id) {
+            Some(workflow_id) => self.research_engine.read().await.get_workflow_results(workflow_id).await?,
+            None => None,
+        };
+
+        Ok(ResultDiff::between(previous.map(|previous| previous.id), previous_results.as_ref(), results))
+    }
+
+    async fn record_finished(&self, schedule: &mut ResearchSchedule, run: &mut ScheduleRun) -> AppResult<()> {
+        info!("Scheduled run {} of {} finished: {:?}", run.id, schedule.id, run.status);
+
+        // A later run of the same schedule may already have been recorded
+        if schedule.last_run_at.map_or(true, |last| last <= run.started_at) {
+            schedule.last_run_status = Some(run.status.clone());
+            self.data_persistence.read().await.save_research_schedule(schedule).await?;
+        }
+
+        self.deliver(schedule, run).await
+    }
+
+    /// Post the run summary to the schedule's webhook, if it has one, and save the run
+    async fn deliver(&self, schedule: &ResearchSchedule, run: &mut ScheduleRun) -> AppResult<()> {
+        if let Some(url) = &schedule.webhook_url {
+            let payload = serde_json::json!({
+                "event": "research_schedule.run_finished",
+                "schedule_id": schedule.id,
+                "schedule_name": schedule.name,
+                "run": run,
+            });
+
+            // Webhooks leave through the same egress as provider requests, and not at all offline
+            let client = air_gap::ensure_online("webhook delivery")
+                .and_then(|_| egress::client_for(&self.http_client, WEBHOOK_TIMEOUT_MS));
+            let delivery = match client {
+                Ok(client) => inject_trace_context(client.post(url).json(&payload)).send().await.map_err(AppError::from),
+                Err(e) => Err(e),
+            };
+            match delivery {
+                Ok(response) if response.status().is_success() => {
+                    run.delivered_at = Some(Utc::now());
+                    run.delivery_error = None;
+                }
+                Ok(response) => {
+                    warn!("Webhook for schedule {} returned {}", schedule.id, response.status());
+                    run.delivery_error = Some(format!("Webhook returned {}", response.status()));
+                }
+                Err(e) => {
+                    warn!("Webhook for schedule {} failed: {}", schedule.id, e);
+                    run.delivery_error = Some(e.to_string());
+                }
+            }
+        }
+
+        self.data_persistence.read().await.save_schedule_run(run).await
+    }
+
+    /// Check the schedule's target exists before accepting it
+    async fn validate_target(&self, target: &ScheduleTarget) -> AppResult<()> {
+        match target {
+            ScheduleTarget::Workflow { workflow_id } => {
+                self.research_engine.read().await.get_workflow(*workflow_id).await?
+                    .ok_or_else(|| ResearchError::workflow_not_found(workflow_id.to_string()))?;
+            }
+            ScheduleTarget::Template { template_id, parameters } => {
+                let template = self.template_manager.read().await.get_template(*template_id).await?
+                    .ok_or_else(|| ResearchError::invalid_schedule(format!("Template {} not found", template_id)))?;
+                template.validate_parameters(parameters)
+                    .map_err(|errors| ResearchError::invalid_schedule(errors.join(", ")))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_webhook_url(url: &str) -> AppResult<()> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| ResearchError::invalid_schedule(format!("Invalid webhook URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ResearchError::invalid_schedule("Webhook URL must use http or https").into());
+    }
+    Ok(())
+}
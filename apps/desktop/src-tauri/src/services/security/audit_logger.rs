@@ -15,6 +15,7 @@ use serde::{Serialize, Deserialize};
 use crate::error::{AppResult, StorageError};
 use crate::services::security::EncryptionManager;
 use crate::models::security::{SecurityAuditEntry, SecurityEventType, SecuritySeverity, SecurityResult};
+use crate::models::pagination::{Page, PagePosition, PageRequest};
 use crate::utils::file_utils::ensure_dir_exists;
 
 // Type alias for compatibility
@@ -80,15 +81,28 @@ impl AuditLogger {
         encryption_manager: Arc<RwLock<EncryptionManager>>,
         compliance_config: ComplianceConfiguration,
     ) -> AppResult<Self> {
-        info!("Initializing enhanced audit logger with tamper-proof features...");
+        Self::open_with_config(encryption_manager, compliance_config, Self::default_db_path()).await
+    }
 
-        // Determine database path
-        let mut db_path = dirs::data_dir()
+    /// Location of the audit log database in the user's data directory
+    pub fn default_db_path() -> PathBuf {
+        dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("."))
-            .join("free-deep-research");
+            .join("free-deep-research")
+            .join("audit_log.db")
+    }
+
+    /// Open the audit log database at `db_path` with custom compliance configuration
+    pub async fn open_with_config(
+        encryption_manager: Arc<RwLock<EncryptionManager>>,
+        compliance_config: ComplianceConfiguration,
+        db_path: PathBuf,
+    ) -> AppResult<Self> {
+        info!("Initializing enhanced audit logger with tamper-proof features...");
 
-        ensure_dir_exists(&db_path)?;
-        db_path.push("audit_log.db");
+        if let Some(parent) = db_path.parent() {
+            ensure_dir_exists(parent)?;
+        }
 
         // Generate integrity key for tamper-proof logging
         let integrity_key = if compliance_config.tamper_proof_enabled {
@@ -297,10 +311,44 @@ impl AuditLogger {
             limit_clause
         );
 
-        let mut stmt = conn.prepare(&query)
+        let events = Self::query_events(conn, &query, &[])?;
+        debug!("Retrieved {} audit events", events.len());
+        Ok(events)
+    }
+
+    /// A page of audit logs, newest first
+    pub async fn get_logs_page(&self, page: &PageRequest) -> AppResult<Page<AuditEvent>> {
+        let conn = self.connection.as_ref()
+            .ok_or_else(|| StorageError::Database { message: "Database not initialized".to_string() })?;
+        let after = page.after()?;
+        let limit = page.limit();
+
+        // Timestamps are RFC 3339 text in UTC, so they sort as they compare
+        let after_timestamp = after.as_ref().map(|after| after.created_at.to_rfc3339());
+        let after_id = after.as_ref().map(|after| after.id.clone());
+        let rows = Self::query_events(
+            conn,
+            "SELECT id, timestamp, event_type, severity, user_id, source_ip,
+                    resource, action, result, details, risk_score
+             FROM audit_events
+             WHERE ?1 IS NULL OR timestamp < ?1 OR (timestamp = ?1 AND id < ?2)
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?3",
+            &[&after_timestamp, &after_id, &(limit + 1)],
+        )?;
+        let total_count: u64 = conn.query_row("SELECT COUNT(*) FROM audit_events", [], |row| row.get(0))
             .map_err(|e| StorageError::Database { message: e.to_string() })?;
 
-        let event_iter = stmt.query_map([], |row| {
+        debug!("Retrieved a page of {} audit events", rows.len().min(limit as usize));
+        Ok(Page::from_rows(rows, limit, total_count, |event| PagePosition::new(event.timestamp, event.id)))
+    }
+
+    /// Run a query selecting the columns `get_logs` reads
+    fn query_events(conn: &Connection, query: &str, params: &[&dyn rusqlite::ToSql]) -> AppResult<Vec<AuditEvent>> {
+        let mut stmt = conn.prepare(query)
+            .map_err(|e| StorageError::Database { message: e.to_string() })?;
+
+        let event_iter = stmt.query_map(params, |row| {
             Ok((
                 row.get::<_, String>(0)?,  // id
                 row.get::<_, String>(1)?,  // timestamp
@@ -349,7 +397,6 @@ impl AuditLogger {
             });
         }
 
-        debug!("Retrieved {} audit events", events.len());
         Ok(events)
     }
 
@@ -9,9 +9,10 @@ use base64;
 use chrono::{DateTime, Utc, Duration};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::error::{AppResult, SecurityError};
+use crate::utils::clock::{self, SharedClock};
 
 /// Enhanced encryption manager for handling AES-256-GCM encryption with key rotation
 #[derive(Debug)]
@@ -80,6 +81,7 @@ pub struct SessionManager {
     active_sessions: HashMap<String, UserSession>,
     session_timeout_minutes: u64,
     max_concurrent_sessions: usize,
+    clock: SharedClock,
 }
 
 /// User session information
@@ -268,6 +270,74 @@ impl EncryptionManager {
         Ok(new_key_version)
     }
 
+    /// Rotate to a master key derived from `new_password`.
+    ///
+    /// Unlike `rotate_keys`, previous keys are never pruned here: they stay in the
+    /// history so existing ciphertexts remain readable until re-encrypted.
+    pub async fn rotate_master_key(&mut self, new_password: &str) -> AppResult<String> {
+        info!("Rotating master encryption key");
+
+        if self.master_key.is_none() {
+            return Err(SecurityError::encryption_failed("Encryption not initialized").into());
+        }
+        if new_password.is_empty() {
+            return Err(SecurityError::encryption_failed("New master key must not be empty").into());
+        }
+
+        let salt = self.generate_random_bytes(32).await?;
+        let iterations = 100_000;
+        let new_key_version = format!("v{}", Uuid::new_v4().to_string().split('-').next().unwrap_or("unknown"));
+
+        let mut new_key_bytes = Zeroizing::new([0u8; 32]);
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(iterations).unwrap(),
+            &salt,
+            new_password.as_bytes(),
+            new_key_bytes.as_mut(),
+        );
+
+        let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, new_key_bytes.as_ref())
+            .map_err(|_| SecurityError::encryption_failed("Failed to create new encryption key"))?;
+
+        if let Some(old_key_entry) = self.key_history.get_mut(&self.current_key_version) {
+            old_key_entry.expires_at = Some(Utc::now());
+        }
+
+        self.master_key = Some(aead::LessSafeKey::new(unbound_key));
+        self.key_history.insert(new_key_version.clone(), EncryptionKey {
+            version: new_key_version.clone(),
+            created_at: Utc::now(),
+            expires_at: Some(Utc::now() + Duration::hours(self.rotation_settings.max_key_age_hours as i64)),
+            key_data: new_key_bytes.to_vec(),
+            algorithm: "AES-256-GCM".to_string(),
+            key_derivation_params: KeyDerivationParams {
+                salt,
+                iterations,
+                algorithm: "PBKDF2-HMAC-SHA256".to_string(),
+            },
+        });
+        self.integrity_keys.insert(new_key_version.clone(), hmac::Key::new(hmac::HMAC_SHA256, new_key_bytes.as_ref()));
+
+        let old_version = std::mem::replace(&mut self.current_key_version, new_key_version.clone());
+        info!("Master key rotated: {} -> {}", old_version, new_key_version);
+        Ok(new_key_version)
+    }
+
+    /// Decrypt data written under any retained key version and encrypt it under the current key
+    pub async fn reencrypt(&self, encrypted_data: &[u8]) -> AppResult<Vec<u8>> {
+        let plaintext = Zeroizing::new(self.decrypt(encrypted_data).await?);
+        self.encrypt(&plaintext).await
+    }
+
+    /// Key version an `encrypt` output was produced with, read from its header
+    /// without decrypting
+    pub fn ciphertext_key_version(encrypted_data: &[u8]) -> Option<String> {
+        let sig_len = u32::from_le_bytes(encrypted_data.get(..4)?.try_into().ok()?) as usize;
+        let package_data = encrypted_data.get(4 + sig_len..)?;
+        bincode::deserialize::<EncryptedPackage>(package_data).ok().map(|package| package.version)
+    }
+
     /// Clean up old expired keys
     async fn cleanup_old_keys(&mut self) -> AppResult<()> {
         debug!("Cleaning up old encryption keys");
@@ -436,12 +506,17 @@ impl EncryptionManager {
         let mut in_out = encrypted_package.ciphertext;
 
         // Decrypt the data
-        let plaintext = key.open_in_place(nonce, aead::Aad::empty(), &mut in_out)
-            .map_err(|_| SecurityError::decryption_failed("Decryption operation failed"))?;
+        let plaintext_len = key.open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+            .map_err(|_| SecurityError::decryption_failed("Decryption operation failed"))?
+            .len();
+
+        // Hand back the buffer that was decrypted in place rather than a copy,
+        // so no stray plaintext is left behind for callers that zeroize
+        in_out.truncate(plaintext_len);
 
         debug!("Data decrypted successfully using version {}, output length: {}",
-               encrypted_package.version, plaintext.len());
-        Ok(plaintext.to_vec())
+               encrypted_package.version, plaintext_len);
+        Ok(in_out)
     }
 
     /// Decrypt data with historical key
@@ -462,11 +537,13 @@ impl EncryptionManager {
 
         let mut in_out = encrypted_package.ciphertext;
 
-        let plaintext = key.open_in_place(nonce, aead::Aad::empty(), &mut in_out)
-            .map_err(|_| SecurityError::decryption_failed("Historical decryption operation failed"))?;
+        let plaintext_len = key.open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+            .map_err(|_| SecurityError::decryption_failed("Historical decryption operation failed"))?
+            .len();
+        in_out.truncate(plaintext_len);
 
-        debug!("Data decrypted successfully with historical key, output length: {}", plaintext.len());
-        Ok(plaintext.to_vec())
+        debug!("Data decrypted successfully with historical key, output length: {}", plaintext_len);
+        Ok(in_out)
     }
 
     /// Create integrity signature for data
@@ -636,9 +713,16 @@ impl SessionManager {
             active_sessions: HashMap::new(),
             session_timeout_minutes,
             max_concurrent_sessions,
+            clock: clock::system_clock(),
         }
     }
 
+    /// Time sessions out by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Create a new user session
     pub async fn create_session(
         &mut self,
@@ -656,7 +740,7 @@ impl SessionManager {
         }
 
         let session_id = Uuid::new_v4().to_string();
-        let now = Utc::now();
+        let now = self.clock.now();
 
         let session = UserSession {
             session_id: session_id.clone(),
@@ -677,7 +761,7 @@ impl SessionManager {
     /// Validate and update session activity
     pub async fn validate_session(&mut self, session_id: &str) -> AppResult<bool> {
         if let Some(session) = self.active_sessions.get_mut(session_id) {
-            let now = Utc::now();
+            let now = self.clock.now();
             let age = now.signed_duration_since(session.last_activity);
 
             if age.num_minutes() > self.session_timeout_minutes as i64 {
@@ -709,7 +793,7 @@ impl SessionManager {
 
     /// Clean up expired sessions
     pub async fn cleanup_expired_sessions(&mut self) -> AppResult<()> {
-        let now = Utc::now();
+        let now = self.clock.now();
         let timeout_duration = Duration::minutes(self.session_timeout_minutes as i64);
 
         let expired_sessions: Vec<String> = self.active_sessions
@@ -741,3 +825,30 @@ impl SessionManager {
         self.active_sessions.get(session_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::ManualClock;
+
+    #[tokio::test]
+    async fn test_sessions_expire_after_the_timeout_without_sleeping() {
+        let clock = ManualClock::new(Utc::now());
+        let mut sessions = SessionManager::new(30, 2).await.with_clock(clock.clone());
+
+        let kept = sessions.create_session(None, None, None, false).await.unwrap();
+        let idle = sessions.create_session(None, None, None, false).await.unwrap();
+        assert!(sessions.create_session(None, None, None, false).await.is_err());
+
+        // Activity keeps a session alive past the timeout measured from creation
+        clock.advance(Duration::minutes(20));
+        assert!(sessions.validate_session(&kept).await.unwrap());
+        clock.advance(Duration::minutes(11));
+        assert!(sessions.validate_session(&kept).await.unwrap());
+        assert!(!sessions.validate_session(&idle).await.unwrap());
+
+        // The expired session no longer counts against the limit
+        assert!(sessions.create_session(None, None, None, false).await.is_ok());
+        assert_eq!(sessions.get_active_session_count(), 2);
+    }
+}
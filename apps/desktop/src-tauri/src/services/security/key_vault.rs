@@ -20,15 +20,16 @@ pub struct KeyVault {
 impl KeyVault {
     /// Create a new key vault
     pub async fn new(encryption_manager: Arc<RwLock<EncryptionManager>>) -> AppResult<Self> {
-        info!("Initializing key vault...");
+        Self::open(encryption_manager, Self::default_db_path()).await
+    }
 
-        // Determine database path
-        let mut db_path = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("free-deep-research");
+    /// Open the key vault database at `db_path`
+    pub async fn open(encryption_manager: Arc<RwLock<EncryptionManager>>, db_path: PathBuf) -> AppResult<Self> {
+        info!("Initializing key vault...");
 
-        ensure_dir_exists(&db_path)?;
-        db_path.push("key_vault.db");
+        if let Some(parent) = db_path.parent() {
+            ensure_dir_exists(parent)?;
+        }
 
         let mut vault = Self {
             encryption_manager,
@@ -43,6 +44,14 @@ impl KeyVault {
         Ok(vault)
     }
 
+    /// Location of the key vault database in the user's data directory
+    pub fn default_db_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("free-deep-research")
+            .join("key_vault.db")
+    }
+
     /// Initialize the database schema
     async fn initialize_database(&mut self) -> AppResult<()> {
         debug!("Initializing key vault database");
@@ -4,19 +4,29 @@ use tracing::{info, error, debug};
 use ring::{aead, pbkdf2, rand};
 use ring::rand::SecureRandom;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 
 use crate::error::{AppResult, SecurityError};
 use crate::services::Service;
+use crate::models::pagination::{Page, PageRequest};
 
 pub mod encryption_manager;
 pub mod authentication;
 pub mod audit_logger;
 pub mod key_vault;
+pub mod secret;
 
 use encryption_manager::EncryptionManager;
 use authentication::AuthenticationManager;
-use audit_logger::AuditLogger;
+use audit_logger::{AuditLogger, ComplianceConfiguration};
 use key_vault::KeyVault;
+pub use secret::SecretString;
+
+/// Master key version an `encrypt_string` output was written with, if it can be read
+pub fn key_version_of(encrypted_data: &str) -> Option<String> {
+    let encrypted_bytes = base64::decode(encrypted_data).ok()?;
+    EncryptionManager::ciphertext_key_version(&encrypted_bytes)
+}
 
 /// Security service that manages encryption, authentication, and audit logging
 pub struct SecurityService {
@@ -29,6 +39,11 @@ pub struct SecurityService {
 impl SecurityService {
     /// Create a new security service
     pub async fn new() -> AppResult<Self> {
+        Self::with_paths(KeyVault::default_db_path(), AuditLogger::default_db_path()).await
+    }
+
+    /// Create a security service keeping its key vault and audit log at the given paths
+    pub async fn with_paths(key_vault_path: PathBuf, audit_log_path: PathBuf) -> AppResult<Self> {
         info!("Initializing security service...");
         
         // Initialize encryption manager
@@ -36,7 +51,7 @@ impl SecurityService {
         let encryption_manager = Arc::new(RwLock::new(encryption_manager));
         
         // Initialize key vault
-        let key_vault = KeyVault::new(encryption_manager.clone()).await?;
+        let key_vault = KeyVault::open(encryption_manager.clone(), key_vault_path).await?;
         let key_vault = Arc::new(RwLock::new(key_vault));
         
         // Initialize authentication manager
@@ -44,7 +59,7 @@ impl SecurityService {
         let authentication_manager = Arc::new(RwLock::new(authentication_manager));
         
         // Initialize audit logger
-        let audit_logger = AuditLogger::new(encryption_manager.clone()).await?;
+        let audit_logger = AuditLogger::open_with_config(encryption_manager.clone(), ComplianceConfiguration::default(), audit_log_path).await?;
         let audit_logger = Arc::new(RwLock::new(audit_logger));
         
         let service = Self {
@@ -85,6 +100,37 @@ impl SecurityService {
             .map_err(|e| SecurityError::decryption_failed(format!("Invalid UTF-8: {}", e)).into())
     }
     
+    /// Decrypt a stored secret such as an API key into memory that is scrubbed on drop
+    pub async fn decrypt_secret(&self, encrypted_data: &str) -> AppResult<SecretString> {
+        let encrypted_bytes = base64::decode(encrypted_data)
+            .map_err(|e| SecurityError::decryption_failed(format!("Invalid base64: {}", e)))?;
+        let decrypted = self.decrypt(&encrypted_bytes).await?;
+        SecretString::from_utf8(decrypted)
+    }
+    
+    /// Make a master key derived from `new_key` current, keeping previous keys for decryption.
+    ///
+    /// Stored API keys are re-encrypted by `DataPersistenceService::rotate_master_key`.
+    pub async fn rotate_master_key(&self, new_key: &str) -> AppResult<String> {
+        let mut encryption_manager = self.encryption_manager.write().await;
+        encryption_manager.rotate_master_key(new_key).await
+    }
+
+    /// Version of the key new ciphertexts are written with
+    pub async fn current_key_version(&self) -> String {
+        let encryption_manager = self.encryption_manager.read().await;
+        encryption_manager.get_current_key_version().to_string()
+    }
+
+    /// Re-encrypt an `encrypt_string` output under the current master key
+    pub async fn reencrypt_string(&self, encrypted_data: &str) -> AppResult<String> {
+        let encrypted_bytes = base64::decode(encrypted_data)
+            .map_err(|e| SecurityError::decryption_failed(format!("Invalid base64: {}", e)))?;
+        let encryption_manager = self.encryption_manager.read().await;
+        let reencrypted = encryption_manager.reencrypt(&encrypted_bytes).await?;
+        Ok(base64::encode(reencrypted))
+    }
+    
     /// Authenticate with master password
     pub async fn authenticate(&self, password: &str) -> AppResult<bool> {
         let auth_manager = self.authentication_manager.read().await;
@@ -114,6 +160,12 @@ impl SecurityService {
         let audit_logger = self.audit_logger.read().await;
         audit_logger.get_logs(limit).await
     }
+
+    /// A page of audit logs, newest first
+    pub async fn get_audit_logs_page(&self, page: &PageRequest) -> AppResult<Page<audit_logger::AuditEvent>> {
+        let audit_logger = self.audit_logger.read().await;
+        audit_logger.get_logs_page(page).await
+    }
     
     /// Store a secret in the key vault
     pub async fn store_secret(&self, key: &str, value: &str) -> AppResult<()> {
@@ -0,0 +1,85 @@
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::{AppResult, SecurityError};
+
+/// A decrypted secret, such as an API key, that is wiped from memory when dropped.
+///
+/// Deliberately not `Clone`, `Serialize` or `Display`, and `Debug` is redacted, so the
+/// plaintext cannot be copied into long-lived structures or logs by accident.
+/// Borrow it with [`SecretString::expose`] for the duration of a single request.
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    /// Take ownership of decrypted bytes without copying them; the bytes are
+    /// scrubbed if they are not valid UTF-8
+    pub fn from_utf8(bytes: Vec<u8>) -> AppResult<Self> {
+        String::from_utf8(bytes)
+            .map(Self)
+            .map_err(|e| {
+                e.into_bytes().zeroize();
+                SecurityError::decryption_failed("Invalid UTF-8 in decrypted secret").into()
+            })
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Zeroize for SecretString {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretString {}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_is_zeroed_on_drop_path() {
+        let mut secret = SecretString::from_utf8(b"sk-live-0123456789".to_vec()).unwrap();
+        let ptr = secret.expose().as_ptr();
+        let len = secret.len();
+
+        // `Drop` runs exactly this; calling it directly keeps the allocation
+        // alive so the scrubbed bytes can be inspected
+        secret.zeroize();
+
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(bytes.iter().all(|&b| b == 0));
+        assert!(secret.is_empty());
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretString::new("sk-live-0123456789".to_string());
+        assert_eq!(format!("{:?}", secret), "SecretString(***)");
+    }
+}
@@ -1,5 +1,5 @@
 use crate::models::research_template::{ResearchTemplate, TemplateCategory};
-use crate::models::research_workflow::{ResearchMethodology, WorkflowParameters, OutputFormat};
+use crate::models::research_workflow::{ResearchMethodology, WorkflowParameters, OutputFormat, ProviderRecording, OverlapCheck, ContradictionCheck, FailureMode, SearchTimeRange, InsightConfidenceFilter};
 use crate::services::template_manager::template_builder::TemplateBuilder;
 
 /// Predefined research templates for common use cases
@@ -47,6 +47,16 @@ impl PredefinedTemplates {
             save_intermediate_results: true,
             enable_caching: true,
             custom_parameters: std::collections::HashMap::new(),
+            provider_recording: ProviderRecording::Off,
+            overlap_check: OverlapCheck::default(),
+            contradiction_check: ContradictionCheck::default(),
+            failure_mode: FailureMode::default(),
+            retry_budget: None,
+            egress_profile: None,
+            share_in_flight_runs: false,
+            time_range: SearchTimeRange::Any,
+            published_after: None,
+            insight_confidence: InsightConfidenceFilter::default(),
         })
         .add_text_parameter(
             "research_topic".to_string(),
@@ -119,7 +129,7 @@ impl PredefinedTemplates {
             Some("/chat/completions".to_string()),
             vec!["academic_search".to_string(), "content_extraction".to_string()],
         )
-        .add_step_input("model".to_string(), "\"anthropic/claude-3-sonnet\"".to_string())
+        .add_step_input("model_role".to_string(), "\"synthesis\"".to_string())
         .add_step_input("temperature".to_string(), "0.2".to_string())
         .add_step_input("max_tokens".to_string(), "8000".to_string())
         .build()
@@ -194,7 +204,7 @@ impl PredefinedTemplates {
             Some("/chat/completions".to_string()),
             vec!["market_search".to_string(), "competitive_search".to_string()],
         )
-        .add_step_input("model".to_string(), "\"anthropic/claude-3-sonnet\"".to_string())
+        .add_step_input("model_role".to_string(), "\"synthesis\"".to_string())
         .add_step_input("temperature".to_string(), "0.3".to_string())
         .build()
     }
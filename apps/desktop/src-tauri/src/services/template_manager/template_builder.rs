@@ -62,6 +62,12 @@ impl TemplateBuilder {
         self
     }
 
+    /// Set the query pattern, with `{{parameter_id}}` placeholders
+    pub fn query_pattern(mut self, pattern: String) -> Self {
+        self.template.query_pattern = Some(pattern);
+        self
+    }
+
     /// Set workflow configuration
     pub fn workflow_config(mut self, config: WorkflowParameters) -> Self {
         self.template.workflow_config = config;
@@ -8,6 +8,7 @@ use crate::error::{AppResult, ApiError};
 use crate::models::research_template::{ResearchTemplate, TemplateExecutionContext};
 use crate::models::research_workflow::{ResearchWorkflow, WorkflowStep, WorkflowParameters};
 use crate::services::{DataPersistenceService, ResearchEngineService};
+use crate::services::api_manager::{tenant_scope, KeyScope};
 
 /// Template executor that converts templates into executable workflows
 pub struct TemplateExecutor {
@@ -88,7 +89,7 @@ impl TemplateExecutor {
         // Create workflow
         let mut workflow = ResearchWorkflow::new(
             context.workflow_name.clone(),
-            self.extract_query_from_parameters(parameters)?,
+            self.resolve_query(template, parameters)?,
             workflow_params,
             context.created_by.clone(),
         );
@@ -103,6 +104,8 @@ impl TemplateExecutor {
         for (key, value) in &context.execution_metadata {
             workflow.metadata.insert(key.clone(), value.to_string());
         }
+        // The workflow spends the keys of the caller executing the template
+        tenant_scope::bind_workflow(&mut workflow, &KeyScope::current())?;
 
         // Create workflow steps from template steps
         let workflow_steps = self.create_workflow_steps_from_template(
@@ -187,6 +190,15 @@ impl TemplateExecutor {
         Ok(workflow_steps)
     }
 
+    /// The workflow query: the template's query pattern filled from
+    /// parameters, or the query parameter itself when there is no pattern
+    fn resolve_query(&self, template: &ResearchTemplate, parameters: &HashMap<String, serde_json::Value>) -> AppResult<String> {
+        match template.render_query(parameters).map_err(|errors| ApiError::invalid_input(errors.join(", ")))? {
+            Some(query) => Ok(query),
+            None => self.extract_query_from_parameters(parameters),
+        }
+    }
+
     /// Extract the main research query from parameters
     fn extract_query_from_parameters(&self, parameters: &HashMap<String, serde_json::Value>) -> AppResult<String> {
         // Look for common query parameter names
@@ -260,7 +272,7 @@ impl TemplateExecutor {
         let final_parameters = template.get_parameters_with_defaults(&context.parameters);
 
         // Create preview
-        let query = self.extract_query_from_parameters(&final_parameters)?;
+        let query = self.resolve_query(&template, &final_parameters)?;
         
         // Count steps that would be executed
         let executable_steps = template.steps.iter()
@@ -275,6 +287,7 @@ impl TemplateExecutor {
             workflow_name: context.workflow_name.clone(),
             methodology: template.methodology.clone(),
             query,
+            query_pattern: template.query_pattern.clone(),
             total_steps: executable_steps,
             estimated_time_minutes,
             parameters_used: final_parameters.len(),
@@ -292,6 +305,7 @@ impl TemplateExecutor {
             crate::models::research_workflow::ResearchMethodology::DonLim => 2, // Faster, cost-optimized
             crate::models::research_workflow::ResearchMethodology::NickScamara => 3, // More thorough
             crate::models::research_workflow::ResearchMethodology::Hybrid => 4, // Most comprehensive
+            crate::models::research_workflow::ResearchMethodology::DocumentAnalysis => 5, // Hybrid plus OCR of image sources
             crate::models::research_workflow::ResearchMethodology::Custom => 3,
         };
 
@@ -328,6 +342,13 @@ impl TemplateExecutor {
                 services.insert("firecrawl".to_string());
                 services.insert("openrouter".to_string());
             }
+            crate::models::research_workflow::ResearchMethodology::DocumentAnalysis => {
+                services.insert("serpapi".to_string());
+                services.insert("jina".to_string());
+                services.insert("firecrawl".to_string());
+                services.insert("openrouter".to_string());
+                services.insert("ml_inference".to_string());
+            }
             crate::models::research_workflow::ResearchMethodology::Custom => {}
         }
 
@@ -341,7 +362,9 @@ pub struct WorkflowPreview {
     pub template_name: String,
     pub workflow_name: String,
     pub methodology: crate::models::research_workflow::ResearchMethodology,
+    /// Query the workflow would run, with every placeholder substituted
     pub query: String,
+    pub query_pattern: Option<String>,
     pub total_steps: usize,
     pub estimated_time_minutes: u32,
     pub parameters_used: usize,
@@ -330,6 +330,14 @@ impl TemplateManagerService {
             }
         }
 
+        // Every query placeholder must name a parameter
+        let undeclared = template.undeclared_query_placeholders();
+        if !undeclared.is_empty() {
+            return Err(crate::error::ApiError::invalid_input(
+                format!("Query pattern uses undefined parameters: {}", undeclared.join(", "))
+            ));
+        }
+
         // Validate steps
         for step in &template.steps {
             if step.id.trim().is_empty() {
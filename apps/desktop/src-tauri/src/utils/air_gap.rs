@@ -0,0 +1,55 @@
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+use crate::error::{AppError, AppResult};
+
+/// Turns on offline (air-gapped) mode: every outbound network call is refused up
+/// front, while local models and cached or recorded results keep working
+pub const AIR_GAPPED_ENV: &str = "FDR_AIR_GAPPED";
+
+static AIR_GAPPED: Lazy<bool> = Lazy::new(|| {
+    let enabled = std::env::var(AIR_GAPPED_ENV).map(|value| parse_flag(&value)).unwrap_or(false);
+    if enabled {
+        warn!("Offline mode is on ({} is set); all outbound network calls are disabled", AIR_GAPPED_ENV);
+    }
+    enabled
+});
+
+/// Whether `value` turns the flag on: `1`, `true`, `yes` or `on`, in any case
+pub fn parse_flag(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+/// Whether offline mode is on. Read once; changing the variable needs a restart.
+pub fn is_air_gapped() -> bool {
+    *AIR_GAPPED
+}
+
+/// Fail fast with an offline mode error if `operation` would reach the network
+pub fn ensure_online(operation: &str) -> AppResult<()> {
+    if is_air_gapped() {
+        Err(AppError::offline(operation))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_values() {
+        for on in ["1", "true", "TRUE", " yes ", "On"] {
+            assert!(parse_flag(on), "{}", on);
+        }
+        for off in ["", "0", "false", "off", "no", "airgapped"] {
+            assert!(!parse_flag(off), "{}", off);
+        }
+
+        let error = AppError::offline("test_key");
+        assert!(error.is_offline());
+        assert!(!error.is_retryable());
+        assert_eq!(error.error_code(), "OFFLINE_MODE");
+    }
+}
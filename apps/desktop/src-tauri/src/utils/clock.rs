@@ -0,0 +1,75 @@
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Source of the current time for logic that depends on it: rate limit resets,
+/// cooldowns, forecast windows and session expiry. Production uses the system
+/// clock; tests use a `ManualClock` and advance it instead of sleeping.
+pub trait Clock: Send + Sync + Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A clock shared between the services that read it
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real clock
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self { now: Mutex::new(start) })
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock() += by;
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let shared: SharedClock = clock.clone();
+        assert_eq!(shared.now(), start);
+
+        clock.advance(Duration::minutes(2));
+        assert_eq!(shared.now(), Utc.with_ymd_and_hms(2024, 2, 1, 0, 1, 0).unwrap());
+
+        clock.set(start);
+        assert_eq!(shared.now(), start);
+        assert!(system_clock().now() > start);
+    }
+}
@@ -2,8 +2,15 @@ pub mod crypto;
 pub mod http_client;
 pub mod file_utils;
 pub mod validation;
+pub mod telemetry;
+pub mod air_gap;
+pub mod service_signature;
+pub mod clock;
 
 pub use crypto::*;
 pub use http_client::*;
 pub use file_utils::*;
 pub use validation::*;
+pub use telemetry::*;
+pub use air_gap::*;
+pub use clock::*;
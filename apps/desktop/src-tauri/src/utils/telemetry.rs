@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// OTLP collector endpoint; spans are only exported when this is set
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Service name reported to the collector
+pub const OTEL_SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+
+const DEFAULT_SERVICE_NAME: &str = "free-deep-research";
+
+/// Flushes and shuts down the span exporter when dropped
+pub struct TelemetryGuard {
+    provider: Option<TracerProvider>,
+}
+
+impl TelemetryGuard {
+    pub fn exporting(&self) -> bool {
+        self.provider.is_some()
+    }
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("Failed to shut down trace exporter: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: console logging filtered by `RUST_LOG`, plus
+/// OTLP span export when `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+///
+/// A collector that cannot be set up is reported and skipped rather than
+/// stopping the application from starting.
+pub fn init_tracing() -> TelemetryGuard {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let (provider, setup_error) = match otlp_endpoint() {
+        Some(endpoint) => match build_provider(&endpoint) {
+            Ok(provider) => (Some(provider), None),
+            Err(e) => (None, Some(format!("{} ({})", e, endpoint))),
+        },
+        None => (None, None),
+    };
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        global::set_tracer_provider(provider.clone());
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .init();
+
+    if let Some(error) = setup_error {
+        tracing::warn!("OpenTelemetry export disabled: {}", error);
+    }
+
+    TelemetryGuard { provider }
+}
+
+/// Propagate the current span's trace context to an outgoing request as W3C
+/// `traceparent`/`tracestate` headers
+pub fn inject_trace_context(mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request
+}
+
+fn otlp_endpoint() -> Option<String> {
+    std::env::var(OTLP_ENDPOINT_ENV)
+        .ok()
+        .map(|endpoint| endpoint.trim().to_string())
+        .filter(|endpoint| !endpoint.is_empty())
+}
+
+fn service_name() -> String {
+    std::env::var(OTEL_SERVICE_NAME_ENV)
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string())
+}
+
+fn build_provider(endpoint: &str) -> Result<TracerProvider, opentelemetry::trace::TraceError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![
+            KeyValue::new("service.name", service_name()),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]))
+        .build())
+}
@@ -41,7 +41,7 @@ export default function ResearchWorkflowManager() {
     name: string;
     description: string;
     template_id: string | null;
-    methodology: 'don_lim' | 'nick_scamara' | 'hybrid';
+    methodology: 'don_lim' | 'nick_scamara' | 'hybrid' | 'document_analysis';
     parameters: Partial<WorkflowParameters>;
   }>({
     name: '',
@@ -547,6 +547,7 @@ export default function ResearchWorkflowManager() {
                       <option value="don_lim">Don Lim (OpenRouter + SerpApi + Jina AI)</option>
                       <option value="nick_scamara">Nick Scamara (Firecrawl + AI SDK)</option>
                       <option value="hybrid">Hybrid (Best of Both)</option>
+                      <option value="document_analysis">Document Analysis (Hybrid + OCR)</option>
                     </select>
                   </div>
                 </div>
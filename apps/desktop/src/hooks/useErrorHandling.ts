@@ -16,6 +16,14 @@ export interface ErrorInfo {
   maxRetries: number
 }
 
+// Error payload rejected by Tauri commands; codes are listed in docs/api/errors.md
+export interface CommandErrorPayload {
+  code: string
+  message: string
+  retryable: boolean
+  details: Record<string, any>
+}
+
 export interface ErrorHandlingOptions {
   maxRetries?: number
   retryDelay?: number
@@ -91,7 +99,7 @@ export function useErrorHandling(options: ErrorHandlingOptions = {}) {
 
   // Handle error with full context
   const handleError = useCallback(async (
-    error: Error | string,
+    error: Error | CommandErrorPayload | string,
     context?: {
       component?: string
       action?: string
@@ -101,7 +109,7 @@ export function useErrorHandling(options: ErrorHandlingOptions = {}) {
     }
   ) => {
     const errorMessage = typeof error === 'string' ? error : error.message
-    const errorStack = typeof error === 'string' ? undefined : error.stack
+    const errorStack = typeof error === 'string' || !('stack' in error) ? undefined : error.stack
 
     const errorInfo: ErrorInfo = {
       id: generateErrorId(),
@@ -113,7 +121,7 @@ export function useErrorHandling(options: ErrorHandlingOptions = {}) {
       stack: errorStack,
       component: context?.component,
       action: context?.action,
-      retryable: context?.retryable ?? false,
+      retryable: context?.retryable ?? (typeof error === 'object' && 'retryable' in error ? error.retryable : false),
       retryCount: 0,
       maxRetries: config.maxRetries || 3
     }
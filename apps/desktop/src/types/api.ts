@@ -164,7 +164,7 @@ export interface ResearchWorkflow {
   description: string;
   template_id: string | null;
   status: WorkflowStatus;
-  methodology: 'don_lim' | 'nick_scamara' | 'hybrid';
+  methodology: 'don_lim' | 'nick_scamara' | 'hybrid' | 'document_analysis';
   parameters: WorkflowParameters;
   created_at: string;
   started_at: string | null;
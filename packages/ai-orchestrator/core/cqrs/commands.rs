@@ -10,7 +10,7 @@ use std::sync::Arc;
 use std::time::Instant;
 use uuid::Uuid;
 
-use super::error::{CQRSError, CQRSResult};
+use super::error::{CQRSError, CQRSResult, FieldError};
 use crate::event_store::events::ResearchMethodology;
 
 /// Base trait for all commands
@@ -19,6 +19,11 @@ pub trait Command: Send + Sync + std::fmt::Debug {
     fn validate(&self) -> CQRSResult<()> {
         Ok(())
     }
+
+    /// Every problem with the command's fields; empty when it may be dispatched
+    fn field_errors(&self) -> Vec<FieldError> {
+        Vec::new()
+    }
     
     /// Get command name for logging/metrics
     fn command_name(&self) -> &'static str;
@@ -44,6 +49,27 @@ pub trait CommandHandler<C: Command>: Send + Sync {
     }
 }
 
+/// A command whose handler returns a known result type rather than a `CommandResult`
+pub trait TypedCommand: Command {
+    type Output: Send + 'static;
+}
+
+/// Executes one typed command. Handlers only see commands that passed validation.
+#[async_trait]
+pub trait TypedCommandHandler<C: TypedCommand>: Send + Sync {
+    async fn handle(&self, command: C) -> CQRSResult<C::Output>;
+}
+
+/// Reject a command with field errors, or one failing its own checks, before it reaches
+/// a handler, so nothing is written to the event store on its behalf
+pub fn validate_command<C: Command>(command: &C) -> CQRSResult<()> {
+    let errors = command.field_errors();
+    if !errors.is_empty() {
+        return Err(CQRSError::InvalidCommand { command: command.command_name(), errors });
+    }
+    command.validate()
+}
+
 /// Command execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResult {
@@ -254,6 +280,8 @@ impl Command for FailWorkflowCommand {
 /// Command bus for routing commands to handlers
 pub struct CommandBus {
     handlers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    // Each value is an `Arc<dyn TypedCommandHandler<C>>` for the command type keying it
+    typed_handlers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
     metrics: CommandBusMetrics,
 }
 
@@ -269,6 +297,7 @@ impl CommandBus {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            typed_handlers: HashMap::new(),
             metrics: CommandBusMetrics::default(),
         }
     }
@@ -280,7 +309,43 @@ impl CommandBus {
         H: CommandHandler<C> + 'static,
     {
         let type_id = TypeId::of::<C>();
-        self.handlers.insert(type_id, Box::new(Arc::new(handler)));
+        let handler: Arc<dyn CommandHandler<C>> = Arc::new(handler);
+        self.handlers.insert(type_id, Box::new(handler));
+    }
+
+    /// Register the handler for a typed command, replacing any earlier one
+    pub async fn register_typed_handler<C, H>(&mut self, handler: H)
+    where
+        C: TypedCommand + 'static,
+        H: TypedCommandHandler<C> + 'static,
+    {
+        let handler: Arc<dyn TypedCommandHandler<C>> = Arc::new(handler);
+        self.typed_handlers.insert(TypeId::of::<C>(), Box::new(handler));
+    }
+
+    /// Whether a handler is registered for the typed command
+    pub fn is_registered<C: TypedCommand + 'static>(&self) -> bool {
+        self.typed_handlers.contains_key(&TypeId::of::<C>())
+    }
+
+    /// Pass a typed command to its handler; errors are returned as-is rather than
+    /// folded into a failed `CommandResult`
+    pub async fn dispatch<C>(&self, command: C) -> CQRSResult<C::Output>
+    where
+        C: TypedCommand + 'static,
+    {
+        let start_time = Instant::now();
+        let command_name = command.command_name();
+
+        let handler = self.typed_handlers.get(&TypeId::of::<C>())
+            .ok_or_else(|| CQRSError::HandlerNotFound(command_name.to_string()))?
+            .downcast_ref::<Arc<dyn TypedCommandHandler<C>>>()
+            .ok_or_else(|| CQRSError::HandlerCastError(command_name.to_string()))?
+            .clone();
+
+        let result = handler.handle(command).await;
+        self.update_metrics(result.is_ok(), start_time.elapsed().as_millis() as u64).await;
+        result
     }
     
     /// Execute a command
@@ -0,0 +1,237 @@
+// Optimistic Concurrency for CQRS Aggregates
+// Phase 4.2: CQRS Pattern Implementation
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::error::{CQRSError, CQRSResult};
+use crate::event_store::{
+    AggregateRoot, DomainEvent, EventStore, ResearchWorkflowAggregate, ResearchWorkflowEvent,
+};
+use crate::event_store::aggregates::ResearchWorkflowState;
+
+/// Attempts a command gets to land on an aggregate before a conflict is returned
+pub const DEFAULT_REBASE_ATTEMPTS: u32 = 5;
+
+/// Loads workflow aggregates and appends their events with expected-version checks
+#[async_trait]
+pub trait WorkflowAggregateStore: Send + Sync {
+    /// Rebuild the aggregate from its events
+    async fn load_workflow(&self, workflow_id: Uuid) -> CQRSResult<ResearchWorkflowAggregate>;
+
+    /// Append events written against `expected_version`, returning the new version.
+    /// Fails with `CQRSError::VersionConflict` if the stream has moved past it.
+    async fn append_workflow_events(
+        &self,
+        workflow_id: Uuid,
+        events: Vec<ResearchWorkflowEvent>,
+        expected_version: u64,
+    ) -> CQRSResult<u64>;
+}
+
+/// Persist an aggregate's uncommitted events against the version it was loaded at
+pub async fn save_workflow<S>(store: &S, aggregate: &mut ResearchWorkflowAggregate) -> CQRSResult<u64>
+where
+    S: WorkflowAggregateStore + ?Sized,
+{
+    let events = aggregate.get_uncommitted_events().to_vec();
+    if events.is_empty() {
+        return Ok(aggregate.get_version());
+    }
+
+    let expected_version = aggregate.get_version() - events.len() as u64;
+    let version = store
+        .append_workflow_events(aggregate.get_id(), events, expected_version)
+        .await?;
+    aggregate.mark_events_as_committed();
+    Ok(version)
+}
+
+/// Load the aggregate, apply `decide` and save, reloading and reapplying `decide` on a
+/// version conflict so the command is rebased onto whatever landed first. `decide` runs
+/// against fresh state every attempt, so business rules are rechecked after a rebase.
+pub async fn execute_with_rebase<S, F>(
+    store: &S,
+    workflow_id: Uuid,
+    max_attempts: u32,
+    mut decide: F,
+) -> CQRSResult<u64>
+where
+    S: WorkflowAggregateStore + ?Sized,
+    F: FnMut(&mut ResearchWorkflowAggregate) -> CQRSResult<()> + Send,
+{
+    let mut attempt = 1;
+    loop {
+        let mut aggregate = store.load_workflow(workflow_id).await?;
+        decide(&mut aggregate)?;
+
+        match save_workflow(store, &mut aggregate).await {
+            Err(CQRSError::VersionConflict { .. }) if attempt < max_attempts => attempt += 1,
+            result => return result,
+        }
+    }
+}
+
+#[async_trait]
+impl WorkflowAggregateStore for EventStore {
+    async fn load_workflow(&self, workflow_id: Uuid) -> CQRSResult<ResearchWorkflowAggregate> {
+        let events = self
+            .read_events(workflow_id, None, None)
+            .await
+            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
+
+        if events.is_empty() {
+            return Err(CQRSError::not_found(format!("Workflow {}", workflow_id)));
+        }
+
+        // Rebuild aggregate from events
+        let mut aggregate = ResearchWorkflowAggregate::restore_from_state(
+            workflow_id,
+            ResearchWorkflowState::default(),
+            0,
+        );
+
+        for event in events {
+            if let Ok(workflow_event) = event.serialize() {
+                if let Ok(deserialized_event) = serde_json::from_value::<ResearchWorkflowEvent>(workflow_event) {
+                    aggregate.apply_event(&deserialized_event);
+                }
+            }
+        }
+
+        Ok(aggregate)
+    }
+
+    async fn append_workflow_events(
+        &self,
+        workflow_id: Uuid,
+        events: Vec<ResearchWorkflowEvent>,
+        expected_version: u64,
+    ) -> CQRSResult<u64> {
+        let events: Vec<Box<dyn DomainEvent>> = events
+            .into_iter()
+            .map(|e| Box::new(e) as Box<dyn DomainEvent>)
+            .collect();
+
+        self.append_events(workflow_id, events, Some(expected_version))
+            .await
+            .map_err(|e| CQRSError::from_event_store(workflow_id, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_store::events::ResearchMethodology;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::sync::{Barrier, Mutex};
+
+    /// Event streams in memory; the version check and append happen under one lock, as
+    /// they do inside the event store's transaction
+    #[derive(Default)]
+    struct InMemoryWorkflowStore {
+        streams: Mutex<HashMap<Uuid, Vec<ResearchWorkflowEvent>>>,
+    }
+
+    #[async_trait]
+    impl WorkflowAggregateStore for InMemoryWorkflowStore {
+        async fn load_workflow(&self, workflow_id: Uuid) -> CQRSResult<ResearchWorkflowAggregate> {
+            let streams = self.streams.lock().await;
+            let events = streams
+                .get(&workflow_id)
+                .ok_or_else(|| CQRSError::not_found(format!("Workflow {}", workflow_id)))?;
+            let mut aggregate = ResearchWorkflowAggregate::restore_from_state(
+                workflow_id,
+                ResearchWorkflowState::default(),
+                0,
+            );
+            events.iter().for_each(|event| aggregate.apply_event(event));
+            Ok(aggregate)
+        }
+
+        async fn append_workflow_events(
+            &self,
+            workflow_id: Uuid,
+            events: Vec<ResearchWorkflowEvent>,
+            expected_version: u64,
+        ) -> CQRSResult<u64> {
+            let mut streams = self.streams.lock().await;
+            let stream = streams.entry(workflow_id).or_default();
+            let actual = stream.len() as u64;
+            if actual != expected_version {
+                return Err(CQRSError::version_conflict(workflow_id, expected_version, actual));
+            }
+            stream.extend(events);
+            Ok(stream.len() as u64)
+        }
+    }
+
+    fn rename(aggregate: &mut ResearchWorkflowAggregate, name: &str) -> CQRSResult<()> {
+        aggregate
+            .update_workflow(serde_json::json!({ "name": name }))
+            .map_err(|e| CQRSError::event_store_error(e.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_commands_on_one_aggregate_conflict_and_rebase() {
+        let store = Arc::new(InMemoryWorkflowStore::default());
+        let workflow_id = Uuid::new_v4();
+        let methodology = ResearchMethodology {
+            name: "Systematic review".to_string(),
+            steps: vec!["search".to_string()],
+            ai_agents: vec!["researcher".to_string()],
+            estimated_duration_minutes: 30,
+        };
+        let mut created = ResearchWorkflowAggregate::create_workflow(
+            workflow_id,
+            "Battery recycling".to_string(),
+            "battery recycling yields".to_string(),
+            methodology,
+        )
+        .unwrap();
+        assert_eq!(save_workflow(&*store, &mut created).await.unwrap(), 1);
+
+        for round in 0..3u64 {
+            // Both commands load the same version before either saves
+            let barrier = Arc::new(Barrier::new(2));
+            let attempts = (0..2).map(|writer| {
+                let (store, barrier) = (store.clone(), barrier.clone());
+                tokio::spawn(async move {
+                    let mut aggregate = store.load_workflow(workflow_id).await.unwrap();
+                    barrier.wait().await;
+                    rename(&mut aggregate, &format!("round {} writer {}", round, writer)).unwrap();
+                    save_workflow(&*store, &mut aggregate).await
+                })
+            });
+
+            let mut results = Vec::new();
+            for attempt in attempts.collect::<Vec<_>>() {
+                results.push(attempt.await.unwrap());
+            }
+            let succeeded = results.iter().filter(|result| result.is_ok()).count();
+            let conflicts = results
+                .iter()
+                .filter(|result| matches!(result, Err(CQRSError::VersionConflict { .. })))
+                .count();
+            assert_eq!((succeeded, conflicts), (1, 1), "round {}", round);
+            assert_eq!(store.load_workflow(workflow_id).await.unwrap().get_version(), 2 + round);
+        }
+
+        // A command that lost the race lands once it is rebased onto the winner
+        let mut stale = store.load_workflow(workflow_id).await.unwrap();
+        let mut winner = store.load_workflow(workflow_id).await.unwrap();
+        rename(&mut winner, "winner").unwrap();
+        save_workflow(&*store, &mut winner).await.unwrap();
+        rename(&mut stale, "rebased").unwrap();
+        assert!(save_workflow(&*store, &mut stale).await.is_err());
+
+        let version = execute_with_rebase(&*store, workflow_id, DEFAULT_REBASE_ATTEMPTS, |aggregate| {
+            rename(aggregate, "rebased")
+        })
+        .await
+        .unwrap();
+        assert_eq!(version, 6);
+        assert_eq!(store.load_workflow(workflow_id).await.unwrap().get_state().name, "rebased");
+    }
+}
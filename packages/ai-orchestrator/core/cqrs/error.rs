@@ -1,15 +1,40 @@
 // CQRS Error Types and Handling
 // Phase 4.2: CQRS Pattern Implementation
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
 
+/// A problem with one field of a command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+/// Every field error in one line, for the error message
+fn describe_field_errors(errors: &[FieldError]) -> String {
+    errors.iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// CQRS specific errors
 #[derive(Error, Debug)]
 pub enum CQRSError {
     #[error("Command validation failed: {0}")]
     ValidationError(String),
 
+    #[error("Invalid {command} command: {}", describe_field_errors(.errors))]
+    InvalidCommand { command: &'static str, errors: Vec<FieldError> },
+
     #[error("Query validation failed: {0}")]
     QueryValidationError(String),
 
@@ -52,6 +77,9 @@ pub enum CQRSError {
     #[error("Concurrency error: {0}")]
     ConcurrencyError(String),
 
+    #[error("Aggregate {aggregate_id} was modified concurrently: expected version {expected}, found {actual}")]
+    VersionConflict { aggregate_id: uuid::Uuid, expected: u64, actual: u64 },
+
     #[error("Authorization error: {0}")]
     AuthorizationError(String),
 
@@ -120,6 +148,21 @@ impl CQRSError {
         Self::ConcurrencyError(message.into())
     }
 
+    pub fn version_conflict(aggregate_id: uuid::Uuid, expected: u64, actual: u64) -> Self {
+        Self::VersionConflict { aggregate_id, expected, actual }
+    }
+
+    /// Map an event store failure while writing `aggregate_id`, keeping version
+    /// conflicts distinct so callers can reload and retry
+    pub fn from_event_store(aggregate_id: uuid::Uuid, error: crate::event_store::EventStoreError) -> Self {
+        match error {
+            crate::event_store::EventStoreError::ConcurrencyConflict { expected, actual } => {
+                Self::version_conflict(aggregate_id, expected, actual)
+            }
+            other => Self::event_store_error(other.to_string()),
+        }
+    }
+
     pub fn authorization_error(message: impl Into<String>) -> Self {
         Self::AuthorizationError(message.into())
     }
@@ -152,6 +195,7 @@ impl CQRSError {
             CQRSError::DatabaseError(_) => true,
             CQRSError::ServiceUnavailable(_) => true,
             CQRSError::ConcurrencyError(_) => true,
+            CQRSError::VersionConflict { .. } => true,
             CQRSError::RateLimitExceeded(_) => true,
             _ => false,
         }
@@ -161,6 +205,7 @@ impl CQRSError {
     pub fn category(&self) -> ErrorCategory {
         match self {
             CQRSError::ValidationError(_) => ErrorCategory::Validation,
+            CQRSError::InvalidCommand { .. } => ErrorCategory::Validation,
             CQRSError::QueryValidationError(_) => ErrorCategory::Validation,
             CQRSError::HandlerNotFound(_) => ErrorCategory::Configuration,
             CQRSError::QueryHandlerNotFound(_) => ErrorCategory::Configuration,
@@ -175,6 +220,7 @@ impl CQRSError {
             CQRSError::CacheError(_) => ErrorCategory::Infrastructure,
             CQRSError::Configuration(_) => ErrorCategory::Configuration,
             CQRSError::ConcurrencyError(_) => ErrorCategory::Concurrency,
+            CQRSError::VersionConflict { .. } => ErrorCategory::Concurrency,
             CQRSError::AuthorizationError(_) => ErrorCategory::Security,
             CQRSError::NotFound(_) => ErrorCategory::NotFound,
             CQRSError::Conflict(_) => ErrorCategory::Business,
@@ -188,6 +234,7 @@ impl CQRSError {
     pub fn http_status_code(&self) -> u16 {
         match self {
             CQRSError::ValidationError(_) => 400,
+            CQRSError::InvalidCommand { .. } => 400,
             CQRSError::QueryValidationError(_) => 400,
             CQRSError::HandlerNotFound(_) => 501,
             CQRSError::QueryHandlerNotFound(_) => 501,
@@ -202,6 +249,7 @@ impl CQRSError {
             CQRSError::CacheError(_) => 500,
             CQRSError::Configuration(_) => 500,
             CQRSError::ConcurrencyError(_) => 409,
+            CQRSError::VersionConflict { .. } => 409,
             CQRSError::AuthorizationError(_) => 403,
             CQRSError::NotFound(_) => 404,
             CQRSError::Conflict(_) => 409,
@@ -215,6 +263,7 @@ impl CQRSError {
     pub fn severity(&self) -> ErrorSeverity {
         match self {
             CQRSError::ValidationError(_) => ErrorSeverity::Low,
+            CQRSError::InvalidCommand { .. } => ErrorSeverity::Low,
             CQRSError::QueryValidationError(_) => ErrorSeverity::Low,
             CQRSError::HandlerNotFound(_) => ErrorSeverity::High,
             CQRSError::QueryHandlerNotFound(_) => ErrorSeverity::High,
@@ -229,6 +278,7 @@ impl CQRSError {
             CQRSError::CacheError(_) => ErrorSeverity::Low,
             CQRSError::Configuration(_) => ErrorSeverity::High,
             CQRSError::ConcurrencyError(_) => ErrorSeverity::Medium,
+            CQRSError::VersionConflict { .. } => ErrorSeverity::Low,
             CQRSError::AuthorizationError(_) => ErrorSeverity::Medium,
             CQRSError::NotFound(_) => ErrorSeverity::Low,
             CQRSError::Conflict(_) => ErrorSeverity::Medium,
@@ -19,11 +19,9 @@ use super::read_models::{
     ReadModelStore, ResearchWorkflowReadModel, WorkflowListReadModel,
     WorkflowStatsReadModel, TaskReadModel,
 };
+use super::concurrency::{execute_with_rebase, save_workflow, DEFAULT_REBASE_ATTEMPTS};
 use super::error::{CQRSError, CQRSResult};
-use crate::event_store::{
-    EventStore, ResearchWorkflowAggregate, AggregateRoot, DomainEvent,
-    EventFactory, ResearchWorkflowEvent,
-};
+use crate::event_store::{EventStore, ResearchWorkflowAggregate};
 
 /// Command Handlers
 
@@ -50,22 +48,8 @@ impl CommandHandler<CreateResearchWorkflowCommand> for CreateResearchWorkflowHan
         )
         .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
 
-        // Get uncommitted events
-        let events: Vec<Box<dyn DomainEvent>> = aggregate
-            .get_uncommitted_events()
-            .iter()
-            .map(|e| Box::new(e.clone()) as Box<dyn DomainEvent>)
-            .collect();
-
-        // Save events to event store
-        let version = self
-            .event_store
-            .append_events(command.workflow_id, events, None)
-            .await
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Mark events as committed
-        aggregate.mark_events_as_committed();
+        // Saving against version 0 makes a second create of the same ID a conflict
+        let version = save_workflow(&*self.event_store, &mut aggregate).await?;
 
         Ok(CommandResult::success(
             command.command_id,
@@ -75,6 +59,14 @@ impl CommandHandler<CreateResearchWorkflowCommand> for CreateResearchWorkflowHan
     }
 }
 
+/// Run `decide` against the workflow, rebasing onto concurrent writes
+async fn execute_on_workflow<F>(event_store: &EventStore, workflow_id: Uuid, decide: F) -> CQRSResult<u64>
+where
+    F: FnMut(&mut ResearchWorkflowAggregate) -> CQRSResult<()> + Send,
+{
+    execute_with_rebase(event_store, workflow_id, DEFAULT_REBASE_ATTEMPTS, decide).await
+}
+
 /// Start workflow execution command handler
 pub struct StartWorkflowExecutionHandler {
     event_store: Arc<EventStore>,
@@ -89,16 +81,12 @@ impl StartWorkflowExecutionHandler {
 #[async_trait]
 impl CommandHandler<StartWorkflowExecutionCommand> for StartWorkflowExecutionHandler {
     async fn handle(&self, command: StartWorkflowExecutionCommand) -> CQRSResult<CommandResult> {
-        // Load existing aggregate
-        let mut aggregate = self.load_aggregate(command.workflow_id).await?;
-
-        // Start execution
-        aggregate
-            .start_execution()
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Save events
-        let version = self.save_aggregate(&mut aggregate).await?;
+        let version = execute_on_workflow(&self.event_store, command.workflow_id, |aggregate| {
+            aggregate
+                .start_execution()
+                .map_err(|e| CQRSError::event_store_error(e.to_string()))
+        })
+        .await?;
 
         Ok(CommandResult::success(
             command.command_id,
@@ -108,63 +96,6 @@ impl CommandHandler<StartWorkflowExecutionCommand> for StartWorkflowExecutionHan
     }
 }
 
-impl StartWorkflowExecutionHandler {
-    async fn load_aggregate(&self, workflow_id: Uuid) -> CQRSResult<ResearchWorkflowAggregate> {
-        // Load events from event store
-        let events = self
-            .event_store
-            .read_events(workflow_id, None, None)
-            .await
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        if events.is_empty() {
-            return Err(CQRSError::not_found(format!("Workflow {}", workflow_id)));
-        }
-
-        // Rebuild aggregate from events
-        let mut aggregate = ResearchWorkflowAggregate::restore_from_state(
-            workflow_id,
-            crate::event_store::aggregates::ResearchWorkflowState::default(),
-            0,
-        );
-
-        for event in events {
-            if let Ok(workflow_event) = event.serialize() {
-                if let Ok(deserialized_event) = serde_json::from_value::<ResearchWorkflowEvent>(workflow_event) {
-                    aggregate.apply_event(&deserialized_event);
-                }
-            }
-        }
-
-        Ok(aggregate)
-    }
-
-    async fn save_aggregate(&self, aggregate: &mut ResearchWorkflowAggregate) -> CQRSResult<u64> {
-        let events: Vec<Box<dyn DomainEvent>> = aggregate
-            .get_uncommitted_events()
-            .iter()
-            .map(|e| Box::new(e.clone()) as Box<dyn DomainEvent>)
-            .collect();
-
-        if events.is_empty() {
-            return Ok(aggregate.get_version());
-        }
-
-        let version = self
-            .event_store
-            .append_events(
-                aggregate.get_id(),
-                events,
-                Some(aggregate.get_version() - aggregate.get_uncommitted_events().len() as u64),
-            )
-            .await
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        aggregate.mark_events_as_committed();
-        Ok(version)
-    }
-}
-
 /// Create task command handler
 pub struct CreateTaskHandler {
     event_store: Arc<EventStore>,
@@ -179,16 +110,12 @@ impl CreateTaskHandler {
 #[async_trait]
 impl CommandHandler<CreateTaskCommand> for CreateTaskHandler {
     async fn handle(&self, command: CreateTaskCommand) -> CQRSResult<CommandResult> {
-        let handler = StartWorkflowExecutionHandler::new(Arc::clone(&self.event_store));
-        let mut aggregate = handler.load_aggregate(command.workflow_id).await?;
-
-        // Create task
-        aggregate
-            .create_task(command.task_id, command.task_type, command.agent_type)
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Save events
-        let version = handler.save_aggregate(&mut aggregate).await?;
+        let version = execute_on_workflow(&self.event_store, command.workflow_id, |aggregate| {
+            aggregate
+                .create_task(command.task_id, command.task_type.clone(), command.agent_type.clone())
+                .map_err(|e| CQRSError::event_store_error(e.to_string()))
+        })
+        .await?;
 
         Ok(CommandResult::success(
             command.command_id,
@@ -212,16 +139,12 @@ impl CompleteTaskHandler {
 #[async_trait]
 impl CommandHandler<CompleteTaskCommand> for CompleteTaskHandler {
     async fn handle(&self, command: CompleteTaskCommand) -> CQRSResult<CommandResult> {
-        let handler = StartWorkflowExecutionHandler::new(Arc::clone(&self.event_store));
-        let mut aggregate = handler.load_aggregate(command.workflow_id).await?;
-
-        // Complete task
-        aggregate
-            .complete_task(command.task_id, command.results)
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Save events
-        let version = handler.save_aggregate(&mut aggregate).await?;
+        let version = execute_on_workflow(&self.event_store, command.workflow_id, |aggregate| {
+            aggregate
+                .complete_task(command.task_id, command.results.clone())
+                .map_err(|e| CQRSError::event_store_error(e.to_string()))
+        })
+        .await?;
 
         Ok(CommandResult::success(
             command.command_id,
@@ -245,16 +168,12 @@ impl CompleteWorkflowHandler {
 #[async_trait]
 impl CommandHandler<CompleteWorkflowCommand> for CompleteWorkflowHandler {
     async fn handle(&self, command: CompleteWorkflowCommand) -> CQRSResult<CommandResult> {
-        let handler = StartWorkflowExecutionHandler::new(Arc::clone(&self.event_store));
-        let mut aggregate = handler.load_aggregate(command.workflow_id).await?;
-
-        // Complete workflow
-        aggregate
-            .complete_execution(command.results)
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Save events
-        let version = handler.save_aggregate(&mut aggregate).await?;
+        let version = execute_on_workflow(&self.event_store, command.workflow_id, |aggregate| {
+            aggregate
+                .complete_execution(command.results.clone())
+                .map_err(|e| CQRSError::event_store_error(e.to_string()))
+        })
+        .await?;
 
         Ok(CommandResult::success(
             command.command_id,
@@ -278,16 +197,12 @@ impl FailWorkflowHandler {
 #[async_trait]
 impl CommandHandler<FailWorkflowCommand> for FailWorkflowHandler {
     async fn handle(&self, command: FailWorkflowCommand) -> CQRSResult<CommandResult> {
-        let handler = StartWorkflowExecutionHandler::new(Arc::clone(&self.event_store));
-        let mut aggregate = handler.load_aggregate(command.workflow_id).await?;
-
-        // Fail workflow
-        aggregate
-            .fail_execution(command.error_message)
-            .map_err(|e| CQRSError::event_store_error(e.to_string()))?;
-
-        // Save events
-        let version = handler.save_aggregate(&mut aggregate).await?;
+        let version = execute_on_workflow(&self.event_store, command.workflow_id, |aggregate| {
+            aggregate
+                .fail_execution(command.error_message.clone())
+                .map_err(|e| CQRSError::event_store_error(e.to_string()))
+        })
+        .await?;
 
         Ok(CommandResult::success(
             command.command_id,
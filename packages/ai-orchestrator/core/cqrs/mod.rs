@@ -14,6 +14,7 @@ pub mod handlers;
 pub mod projections;
 pub mod read_models;
 pub mod error;
+pub mod concurrency;
 
 #[cfg(test)]
 pub mod tests;
@@ -21,13 +22,19 @@ pub mod tests;
 use error::{CQRSError, CQRSResult};
 
 // Re-export key types
-pub use commands::{Command, CommandBus, CommandHandler, CommandResult};
+pub use commands::{
+    validate_command, Command, CommandBus, CommandHandler, CommandResult, TypedCommand, TypedCommandHandler
+};
+pub use error::FieldError;
 pub use queries::{Query, QueryBus, QueryHandler, QueryResult};
 pub use handlers::{
     CreateResearchWorkflowHandler, StartWorkflowExecutionHandler,
     CreateTaskHandler, CompleteTaskHandler, CompleteWorkflowHandler,
     GetResearchWorkflowHandler, GetWorkflowListHandler, GetWorkflowStatsHandler
 };
+pub use concurrency::{
+    execute_with_rebase, save_workflow, WorkflowAggregateStore, DEFAULT_REBASE_ATTEMPTS
+};
 pub use projections::{ProjectionManager, ProjectionBuilder, ProjectionCheckpoint};
 pub use read_models::{
     ResearchWorkflowReadModel, WorkflowListReadModel, WorkflowStatsReadModel,
@@ -97,6 +104,16 @@ impl CQRSService {
         command_bus.register_handler::<C, H>(handler).await;
     }
 
+    /// Register the handler for a typed command
+    pub async fn register_typed_command_handler<C, H>(&self, handler: H)
+    where
+        C: TypedCommand + 'static,
+        H: TypedCommandHandler<C> + 'static,
+    {
+        let mut command_bus = self.command_bus.write().await;
+        command_bus.register_typed_handler::<C, H>(handler).await;
+    }
+
     /// Register a query handler
     pub async fn register_query_handler<Q, H>(&self, handler: H)
     where
@@ -123,7 +140,7 @@ impl CQRSService {
     {
         // Validate command if enabled
         if self.config.enable_command_validation {
-            command.validate()?;
+            validate_command(&command)?;
         }
 
         // Execute with timeout
@@ -137,6 +154,26 @@ impl CQRSService {
         .map_err(|_| CQRSError::CommandTimeout)?
     }
 
+    /// Validate a typed command and run it through its handler. A command that fails
+    /// validation is rejected with its field errors and never reaches the handler.
+    pub async fn dispatch_command<C>(&self, command: C) -> CQRSResult<C::Output>
+    where
+        C: TypedCommand + 'static,
+    {
+        if self.config.enable_command_validation {
+            validate_command(&command)?;
+        }
+
+        let timeout = tokio::time::Duration::from_secs(self.config.command_timeout_seconds);
+
+        tokio::time::timeout(timeout, async {
+            let command_bus = self.command_bus.read().await;
+            command_bus.dispatch(command).await
+        })
+        .await
+        .map_err(|_| CQRSError::CommandTimeout)?
+    }
+
     /// Execute a query
     pub async fn execute_query<Q>(&self, query: Q) -> CQRSResult<Q::Result>
     where
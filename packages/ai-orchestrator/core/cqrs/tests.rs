@@ -13,11 +13,13 @@ mod tests {
         },
         read_models::{MockReadModelStore, ResearchWorkflowReadModel, WorkflowStatus, WorkflowMetrics},
         projections::{ResearchWorkflowProjectionBuilder, ProjectionManager},
-        error::CQRSError,
+        commands::{Command, TypedCommand, TypedCommandHandler},
+        error::{CQRSError, CQRSResult, FieldError},
     };
     use crate::event_store::{EventStore, EventStoreConfig, JsonEventSerializer};
     use crate::event_store::events::ResearchMethodology;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio::sync::RwLock;
     use uuid::Uuid;
     use chrono::Utc;
@@ -194,6 +196,79 @@ mod tests {
         assert!(invalid_command.validate().is_err());
     }
 
+    #[derive(Debug)]
+    struct RenameWorkflowCommand {
+        command_id: Uuid,
+        workflow_id: Option<Uuid>,
+        name: String,
+    }
+
+    impl Command for RenameWorkflowCommand {
+        fn field_errors(&self) -> Vec<FieldError> {
+            let mut errors = Vec::new();
+            if self.workflow_id.is_none() {
+                errors.push(FieldError::new("workflow_id", "is required"));
+            }
+            if self.name.trim().is_empty() {
+                errors.push(FieldError::new("name", "cannot be blank"));
+            }
+            errors
+        }
+
+        fn command_name(&self) -> &'static str {
+            "RenameWorkflow"
+        }
+
+        fn command_id(&self) -> Uuid {
+            self.command_id
+        }
+    }
+
+    impl TypedCommand for RenameWorkflowCommand {
+        type Output = String;
+    }
+
+    struct RenameWorkflowHandler {
+        handled: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl TypedCommandHandler<RenameWorkflowCommand> for RenameWorkflowHandler {
+        async fn handle(&self, command: RenameWorkflowCommand) -> CQRSResult<String> {
+            self.handled.fetch_add(1, Ordering::SeqCst);
+            Ok(command.name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_typed_command_is_rejected_before_its_handler() {
+        let cqrs_service = setup_cqrs_service().await;
+        let handled = Arc::new(AtomicUsize::new(0));
+        cqrs_service
+            .register_typed_command_handler::<RenameWorkflowCommand, _>(RenameWorkflowHandler { handled: handled.clone() })
+            .await;
+
+        let renamed = cqrs_service
+            .dispatch_command(RenameWorkflowCommand {
+                command_id: Uuid::new_v4(),
+                workflow_id: Some(Uuid::new_v4()),
+                name: "Solid-state batteries".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(renamed, "Solid-state batteries");
+
+        let rejected = cqrs_service
+            .dispatch_command(RenameWorkflowCommand { command_id: Uuid::new_v4(), workflow_id: None, name: "  ".to_string() })
+            .await;
+        let Err(CQRSError::InvalidCommand { command, errors }) = rejected else {
+            panic!("expected the command to fail validation");
+        };
+        assert_eq!(command, "RenameWorkflow");
+        assert_eq!(errors.iter().map(|e| e.field.as_str()).collect::<Vec<_>>(), vec!["workflow_id", "name"]);
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn test_query_validation() {
         let query_factory = QueryFactory::new();
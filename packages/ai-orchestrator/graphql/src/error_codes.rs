@@ -0,0 +1,124 @@
+// Machine-readable codes on GraphQL errors
+// Codes match the desktop commands' error payloads; see docs/api/errors.md
+
+use std::sync::Arc;
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute};
+use async_graphql::{ErrorExtensionValues, ErrorExtensions, Response, ServerError};
+
+use crate::GraphQLError;
+
+impl GraphQLError {
+    /// The stable code clients see in the error's `extensions.code`
+    pub fn code(&self) -> &'static str {
+        match self {
+            GraphQLError::MissingContext | GraphQLError::MissingConfig => "CONFIGURATION_ERROR",
+            GraphQLError::ServerStart(_) | GraphQLError::UnhandledCommand(_) => "INTERNAL_ERROR",
+            GraphQLError::Database(_) => "STORAGE_ERROR",
+            GraphQLError::Auth(_) => "UNAUTHORIZED",
+            GraphQLError::Validation(_)
+            | GraphQLError::InvalidCommand { .. }
+            | GraphQLError::ComplexityLimit
+            | GraphQLError::DepthLimit => "INVALID_REQUEST",
+            GraphQLError::RateLimit => "RATE_LIMITED",
+            GraphQLError::ConcurrencyConflict { .. } => "CONFLICT",
+            GraphQLError::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        }
+    }
+
+    /// Whether sending the same request again later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, GraphQLError::RateLimit | GraphQLError::Database(_))
+    }
+
+    fn details(&self) -> serde_json::Value {
+        match self {
+            GraphQLError::ConcurrencyConflict { stream_id, expected, actual } => serde_json::json!({
+                "stream_id": stream_id,
+                "expected": expected,
+                "actual": actual,
+            }),
+            GraphQLError::InvalidCommand { command, errors } => serde_json::json!({
+                "command": command,
+                "errors": errors.iter()
+                    .map(|e| serde_json::json!({ "field": e.field, "message": e.message }))
+                    .collect::<Vec<_>>(),
+            }),
+            GraphQLError::UnhandledCommand(command) => serde_json::json!({ "command": command }),
+            _ => serde_json::json!({}),
+        }
+    }
+
+    fn write_extensions(&self, extensions: &mut ErrorExtensionValues) {
+        extensions.set("code", self.code());
+        extensions.set("retryable", self.is_retryable());
+        if let Ok(details) = async_graphql::Value::from_json(self.details()) {
+            extensions.set("details", details);
+        }
+    }
+}
+
+impl ErrorExtensions for GraphQLError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, extensions| self.write_extensions(extensions))
+    }
+}
+
+/// Adds `code`, `retryable` and `details` to every error a resolver returned as a
+/// `GraphQLError`, however it was converted on the way out
+pub struct ErrorCodes;
+
+impl ExtensionFactory for ErrorCodes {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ErrorCodesExtension)
+    }
+}
+
+struct ErrorCodesExtension;
+
+#[async_trait::async_trait]
+impl Extension for ErrorCodesExtension {
+    async fn execute(&self, ctx: &ExtensionContext<'_>, operation_name: Option<&str>, next: NextExecute<'_>) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+        for error in &mut response.errors {
+            tag(error);
+        }
+        response
+    }
+}
+
+fn tag(error: &mut ServerError) {
+    let Some(source) = error.source::<GraphQLError>() else {
+        return;
+    };
+    let mut extensions = error.extensions.take().unwrap_or_default();
+    source.write_extensions(&mut extensions);
+    error.extensions = Some(extensions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldError;
+
+    #[test]
+    fn test_errors_carry_their_code_however_they_were_converted() {
+        let invalid = GraphQLError::InvalidCommand {
+            command: "StartWorkflow",
+            errors: vec![FieldError::new("workflow_id", "is required")],
+        };
+        let error = invalid.extend();
+        let extensions = serde_json::to_value(error.extensions.as_ref().unwrap()).unwrap();
+        assert_eq!(extensions["code"], "INVALID_REQUEST");
+        assert_eq!(extensions["retryable"], false);
+        assert_eq!(extensions["details"]["errors"][0]["field"], "workflow_id");
+
+        // `?` keeps the GraphQLError as the source, which the extension reads back
+        let converted: async_graphql::Error = GraphQLError::RateLimit.into();
+        let mut server_error = converted.into_server_error(async_graphql::Pos::default());
+        tag(&mut server_error);
+        let extensions = serde_json::to_value(server_error.extensions.unwrap()).unwrap();
+        assert_eq!(extensions["code"], "RATE_LIMITED");
+        assert_eq!(extensions["retryable"], true);
+    }
+}
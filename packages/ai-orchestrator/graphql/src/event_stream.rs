@@ -0,0 +1,177 @@
+// Event stream positions, replay and live subscriptions
+// Phase 4.4: API Gateway & GraphQL
+
+use chrono::{DateTime, Utc};
+use futures_util::{stream, Stream, StreamExt};
+use std::{collections::HashMap, pin::Pin};
+use tokio::sync::{broadcast, RwLock};
+
+use crate::{DomainEvent, EventStoreService, GraphQLError};
+
+/// Live events buffered per subscriber before it is considered to have fallen behind
+const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// An event as stored, with its place in its stream and in the store as a whole
+#[derive(Debug, Clone)]
+pub struct RecordedEvent<E = DomainEvent> {
+    pub stream_id: String,
+    /// Position within the stream, starting at 1; a stream's version is its last position
+    pub position: u64,
+    /// Position across all streams, increasing with every append
+    pub global_position: u64,
+    pub recorded_at: DateTime<Utc>,
+    pub event: E,
+}
+
+/// Events of one stream: those already stored first, then new ones as they are appended
+pub type EventStream<E = DomainEvent> = Pin<Box<dyn Stream<Item = Result<RecordedEvent<E>, GraphQLError>> + Send>>;
+
+struct StoreState<E> {
+    streams: HashMap<String, Vec<RecordedEvent<E>>>,
+    last_global_position: u64,
+}
+
+/// Event store held in memory, for tests and single-node deployments
+pub struct InMemoryEventStore<E = DomainEvent> {
+    state: RwLock<StoreState<E>>,
+    live: broadcast::Sender<RecordedEvent<E>>,
+}
+
+impl<E: Clone + Send + Sync + 'static> InMemoryEventStore<E> {
+    pub fn new() -> Self {
+        let (live, _) = broadcast::channel(SUBSCRIBER_BUFFER);
+        Self {
+            state: RwLock::new(StoreState { streams: HashMap::new(), last_global_position: 0 }),
+            live,
+        }
+    }
+
+    /// Append an event, returning the stream's new version. With `expected_version`, the
+    /// append fails if another writer has moved the stream past that version.
+    pub async fn append(&self, stream_id: &str, event: E, expected_version: Option<u64>) -> Result<u64, GraphQLError> {
+        let mut state = self.state.write().await;
+        let version = state.streams.get(stream_id).map_or(0, |events| events.len() as u64);
+        if let Some(expected) = expected_version.filter(|&expected| expected != version) {
+            return Err(GraphQLError::ConcurrencyConflict {
+                stream_id: stream_id.to_string(),
+                expected,
+                actual: version,
+            });
+        }
+
+        state.last_global_position += 1;
+        let global_position = state.last_global_position;
+        let recorded = RecordedEvent {
+            stream_id: stream_id.to_string(),
+            position: version + 1,
+            global_position,
+            recorded_at: Utc::now(),
+            event,
+        };
+        state.streams.entry(stream_id.to_string()).or_default().push(recorded.clone());
+        // Published under the write lock, so subscribers see events in position order
+        let _ = self.live.send(recorded);
+        Ok(version + 1)
+    }
+
+    /// Events at positions `from` through `to`, inclusive; `to` defaults to the stream's end
+    pub async fn replay(&self, stream_id: &str, from: u64, to: Option<u64>) -> Vec<RecordedEvent<E>> {
+        let state = self.state.read().await;
+        state.streams.get(stream_id)
+            .map(|events| {
+                events.iter()
+                    .filter(|recorded| recorded.position >= from && to.map_or(true, |to| recorded.position <= to))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Events after `from_position`, then every new one. A subscriber that falls more than
+    /// `SUBSCRIBER_BUFFER` events behind gets an error and should resubscribe from its last position.
+    pub async fn subscribe(&self, stream_id: &str, from_position: u64) -> EventStream<E> {
+        // Subscribing under the read lock means no append lands between catch-up and live
+        let (stored, receiver) = {
+            let state = self.state.read().await;
+            let stored: Vec<RecordedEvent<E>> = state.streams.get(stream_id)
+                .map(|events| events.iter().filter(|recorded| recorded.position > from_position).cloned().collect())
+                .unwrap_or_default();
+            (stored, self.live.subscribe())
+        };
+        let last_position = stored.last().map_or(from_position, |recorded| recorded.position);
+
+        let live = stream::unfold(
+            (Some(receiver), last_position, stream_id.to_string()),
+            |(receiver, last_position, stream_id)| async move {
+                let mut receiver = receiver?;
+                loop {
+                    match receiver.recv().await {
+                        Ok(recorded) if recorded.stream_id == stream_id && recorded.position > last_position => {
+                            let position = recorded.position;
+                            return Some((Ok(recorded), (Some(receiver), position, stream_id)));
+                        }
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            let error = GraphQLError::Database(format!(
+                                "subscriber to {} fell {} events behind; resubscribe from position {}",
+                                stream_id, skipped, last_position
+                            ));
+                            return Some((Err(error), (None, last_position, stream_id)));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            },
+        );
+
+        stream::iter(stored.into_iter().map(Ok)).chain(live).boxed()
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStoreService for InMemoryEventStore<DomainEvent> {
+    async fn append_event(&self, stream_id: &str, event: DomainEvent, expected_version: Option<u64>) -> Result<u64, GraphQLError> {
+        self.append(stream_id, event, expected_version).await
+    }
+
+    async fn get_events(&self, stream_id: &str) -> Result<Vec<DomainEvent>, GraphQLError> {
+        Ok(self.replay(stream_id, 1, None).await.into_iter().map(|recorded| recorded.event).collect())
+    }
+
+    async fn replay(&self, stream_id: &str, from: u64, to: Option<u64>) -> Result<Vec<RecordedEvent>, GraphQLError> {
+        Ok(InMemoryEventStore::replay(self, stream_id, from, to).await)
+    }
+
+    async fn subscribe(&self, stream_id: &str, from_position: u64) -> Result<EventStream, GraphQLError> {
+        Ok(InMemoryEventStore::subscribe(self, stream_id, from_position).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscription_catches_up_then_follows_live_appends() {
+        let store = InMemoryEventStore::<String>::new();
+        store.append("workflow-1", "created".to_string(), Some(0)).await.unwrap();
+        store.append("workflow-2", "created".to_string(), None).await.unwrap();
+        store.append("workflow-1", "started".to_string(), Some(1)).await.unwrap();
+
+        let conflict = store.append("workflow-1", "cancelled".to_string(), Some(1)).await;
+        assert!(matches!(conflict, Err(GraphQLError::ConcurrencyConflict { expected: 1, actual: 2, .. })));
+
+        let mut subscription = store.subscribe("workflow-1", 1).await;
+        store.append("workflow-1", "completed".to_string(), Some(2)).await.unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let recorded = subscription.next().await.unwrap().unwrap();
+            received.push((recorded.position, recorded.global_position, recorded.event));
+        }
+        assert_eq!(received, vec![(2, 3, "started".to_string()), (3, 4, "completed".to_string())]);
+
+        let replayed: Vec<String> = store.replay("workflow-1", 2, Some(2)).await.into_iter().map(|r| r.event).collect();
+        assert_eq!(replayed, vec!["started".to_string()]);
+    }
+}
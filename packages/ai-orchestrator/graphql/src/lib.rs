@@ -18,6 +18,8 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
 pub mod resolvers;
@@ -26,10 +28,23 @@ pub mod dataloaders;
 pub mod subscriptions;
 pub mod middleware;
 pub mod federation;
+pub mod event_stream;
+pub mod error_codes;
+pub mod pagination;
+#[path = "../../../serverless-functions/shared/deadline.rs"]
+pub mod deadline;
+#[path = "../../../serverless-functions/shared/readiness.rs"]
+pub mod readiness;
+#[path = "../../../serverless-functions/shared/shutdown.rs"]
+pub mod shutdown;
 
 use resolvers::{QueryRoot, MutationRoot, SubscriptionRoot};
 use types::*;
 use dataloaders::*;
+use event_stream::{EventStream, RecordedEvent};
+use deadline::Deadline;
+use readiness::Readiness;
+use shutdown::Shutdown;
 
 // GraphQL Schema type
 pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
@@ -91,10 +106,18 @@ pub struct FederationConfig {
     pub schema_registry_url: Option<String>,
 }
 
+/// Readiness component for the schema being built
+const SCHEMA_COMPONENT: &str = "schema";
+/// Readiness component for the server listening for requests
+const LISTENER_COMPONENT: &str = "listener";
+/// Readiness component that fails once the server starts shutting down
+const SHUTDOWN_COMPONENT: &str = "shutdown";
+
 // GraphQL server builder
 pub struct GraphQLServerBuilder {
     context: Option<AppContext>,
     config: Option<AppConfig>,
+    critical_components: Vec<String>,
 }
 
 impl GraphQLServerBuilder {
@@ -102,9 +125,17 @@ impl GraphQLServerBuilder {
         Self {
             context: None,
             config: None,
+            critical_components: Vec::new(),
         }
     }
 
+    /// Hold `/ready` at 503 until `component`, such as `database`, is marked ready
+    /// through [`GraphQLServer::readiness`]
+    pub fn with_critical_component(mut self, component: impl Into<String>) -> Self {
+        self.critical_components.push(component.into());
+        self
+    }
+
     pub fn with_context(mut self, context: AppContext) -> Self {
         self.context = Some(context);
         self
@@ -119,7 +150,11 @@ impl GraphQLServerBuilder {
         let context = self.context.ok_or(GraphQLError::MissingContext)?;
         let config = self.config.ok_or(GraphQLError::MissingConfig)?;
 
-        Ok(GraphQLServer::new(context, config))
+        let server = GraphQLServer::new(context, config);
+        for component in &self.critical_components {
+            server.readiness.mark_starting(component);
+        }
+        Ok(server)
     }
 }
 
@@ -128,6 +163,7 @@ pub struct GraphQLServer {
     context: AppContext,
     config: AppConfig,
     schema: GraphQLSchema,
+    readiness: Arc<Readiness>,
 }
 
 impl GraphQLServer {
@@ -148,6 +184,9 @@ impl GraphQLServer {
         .data(api_key_loader)
         .data(workflow_loader);
 
+        // Every error tells clients its code and whether to retry
+        schema_builder = schema_builder.extension(error_codes::ErrorCodes);
+
         // Add extensions based on configuration
         if config.graphql.enable_tracing {
             schema_builder = schema_builder.extension(Tracing);
@@ -159,14 +198,23 @@ impl GraphQLServer {
             .limit_complexity(config.graphql.max_query_complexity);
 
         let schema = schema_builder.finish();
+        let readiness = Readiness::new([SCHEMA_COMPONENT, LISTENER_COMPONENT]);
+        readiness.mark_ready(SCHEMA_COMPONENT);
 
         Self {
             context,
             config,
             schema,
+            readiness,
         }
     }
 
+    /// What `/ready` reports; mark components registered with
+    /// [`GraphQLServerBuilder::with_critical_component`] ready as they come up
+    pub fn readiness(&self) -> Arc<Readiness> {
+        self.readiness.clone()
+    }
+
     // Create Axum router with GraphQL endpoints
     pub fn create_router(&self) -> Router {
         let cors = CorsLayer::new()
@@ -185,8 +233,10 @@ impl GraphQLServer {
             router = router.route("/graphql/ws", get(graphql_subscription_handler));
         }
 
-        // Add health check endpoint
+        // Liveness stays a cheap probe; readiness waits on the critical components
         router = router.route("/health", get(health_check));
+        let readiness = self.readiness.clone();
+        router = router.route("/ready", get(move || async move { readiness.report() }));
 
         // Add metrics endpoint
         router = router.route("/metrics", get(metrics_handler));
@@ -194,17 +244,26 @@ impl GraphQLServer {
         router
     }
 
-    // Start the GraphQL server
+    // Start the GraphQL server; on SIGTERM or SIGINT it drains in-flight requests for up
+    // to `FDR_SHUTDOWN_GRACE_SECS` before returning
     pub async fn start(&self, addr: &str) -> Result<(), GraphQLError> {
+        self.start_with_shutdown(addr, Shutdown::on_signals()).await
+    }
+
+    // Start the GraphQL server, shutting down gracefully once `shutdown` starts
+    pub async fn start_with_shutdown(&self, addr: &str, shutdown: Shutdown) -> Result<(), GraphQLError> {
         let app = self.create_router();
-        
+
         let listener = tokio::net::TcpListener::bind(addr)
             .await
             .map_err(|e| GraphQLError::ServerStart(e.to_string()))?;
 
         tracing::info!("GraphQL server starting on {}", addr);
-        
-        axum::serve(listener, app)
+        self.readiness.mark_ready(LISTENER_COMPONENT);
+        let readiness = self.readiness.clone();
+        shutdown.on_start(move || readiness.mark_failed(SHUTDOWN_COMPONENT, "shutting down"));
+
+        shutdown::serve(listener, app, shutdown, shutdown::grace_period_from_env())
             .await
             .map_err(|e| GraphQLError::ServerStart(e.to_string()))?;
 
@@ -254,7 +313,35 @@ async fn graphql_handler(
         }
     }
 
-    schema.execute(request).await.into()
+    // Continue the caller's trace when it sent a `traceparent` header
+    let carrier: HashMap<String, String> = headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+
+    let span = tracing::info_span!(
+        "graphql.request",
+        graphql.operation = request.operation_name.as_deref().unwrap_or("anonymous"),
+    );
+    span.set_parent(parent);
+
+    // Hold the request to the caller's deadline
+    let deadline = match Deadline::from_headers(&headers) {
+        Ok(Some(deadline)) if deadline.is_expired() => return error_response(GraphQLError::DeadlineExceeded),
+        Ok(Some(deadline)) => deadline,
+        Ok(None) => return schema.execute(request).instrument(span).await.into(),
+        Err(rejection) => return error_response(GraphQLError::Validation(rejection.to_string())),
+    };
+    match deadline.run(schema.execute(request).instrument(span)).await {
+        Ok(response) => response.into(),
+        Err(_) => error_response(GraphQLError::DeadlineExceeded),
+    }
+}
+
+fn error_response(error: GraphQLError) -> GraphQLResponse {
+    let error = async_graphql::ErrorExtensions::extend(&error).into_server_error(async_graphql::Pos::default());
+    async_graphql::Response::from_errors(vec![error]).into()
 }
 
 // GraphQL playground handler
@@ -301,6 +388,26 @@ pub struct AuthToken(pub String);
 pub struct UserAgent(pub String);
 
 // Error types
+/// A problem with one field of a command, as the CQRS service reports it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+fn describe_field_errors(errors: &[FieldError]) -> String {
+    errors.iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum GraphQLError {
     #[error("Missing application context")]
@@ -321,6 +428,15 @@ pub enum GraphQLError {
     ComplexityLimit,
     #[error("Query depth exceeded")]
     DepthLimit,
+    #[error("Stream {stream_id} is at version {actual}, expected {expected}")]
+    ConcurrencyConflict { stream_id: String, expected: u64, actual: u64 },
+    /// A command the CQRS service rejected during validation, before dispatch
+    #[error("Invalid {command} command: {}", describe_field_errors(.errors))]
+    InvalidCommand { command: &'static str, errors: Vec<FieldError> },
+    #[error("No handler registered for {0}")]
+    UnhandledCommand(&'static str),
+    #[error("Deadline exceeded")]
+    DeadlineExceeded,
 }
 
 // Service traits (to be implemented by actual services)
@@ -328,13 +444,20 @@ pub enum GraphQLError {
 pub trait DatabaseService: Send + Sync {
     async fn get_user(&self, id: Uuid) -> Result<Option<User>, GraphQLError>;
     async fn get_users(&self, filter: UserFilter) -> Result<Vec<User>, GraphQLError>;
+    async fn get_audit_logs(&self, filter: AuditLogFilter) -> Result<Vec<AuditLog>, GraphQLError>;
     // ... other database methods
 }
 
 #[async_trait::async_trait]
 pub trait EventStoreService: Send + Sync {
-    async fn append_event(&self, event: DomainEvent) -> Result<(), GraphQLError>;
+    /// Append to a stream and return its new version. With `expected_version`, fails with
+    /// `ConcurrencyConflict` unless the stream is still at that version.
+    async fn append_event(&self, stream_id: &str, event: DomainEvent, expected_version: Option<u64>) -> Result<u64, GraphQLError>;
     async fn get_events(&self, stream_id: &str) -> Result<Vec<DomainEvent>, GraphQLError>;
+    /// Events at positions `from` through `to`, inclusive
+    async fn replay(&self, stream_id: &str, from: u64, to: Option<u64>) -> Result<Vec<RecordedEvent>, GraphQLError>;
+    /// Events after `from_position`, followed by new events as they are appended
+    async fn subscribe(&self, stream_id: &str, from_position: u64) -> Result<EventStream, GraphQLError>;
 }
 
 #[async_trait::async_trait]
@@ -382,6 +505,8 @@ pub trait MetricsService: Send + Sync {
     async fn record_query(&self, query: &str, duration: u64, success: bool);
     async fn record_mutation(&self, mutation: &str, duration: u64, success: bool);
     async fn get_metrics(&self) -> Result<SystemMetrics, GraphQLError>;
+    async fn get_circuit_breaker_states(&self) -> Result<Vec<CircuitBreaker>, GraphQLError>;
+    async fn reset_circuit_breaker(&self, id: &str) -> Result<Option<CircuitBreaker>, GraphQLError>;
 }
 
 // Re-export important types
@@ -0,0 +1,145 @@
+// Keyset pagination for connection queries
+// Lists are ordered newest first by creation time, then ID. A cursor is the key of an
+// edge's node rather than its index, so nodes inserted while a client pages sort before
+// every cursor already handed out and later pages neither skip nor repeat nodes.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::types::{PageInfo, PaginationInput};
+use crate::GraphQLError;
+
+/// Nodes in a page when the query does not say
+pub const DEFAULT_PAGE_SIZE: i32 = 50;
+/// Most nodes a single page may hold
+pub const MAX_PAGE_SIZE: i32 = 500;
+
+/// The sort key of a node, newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PageKey {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl PageKey {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// The opaque cursor clients pass back
+    pub fn encode(&self) -> String {
+        base64::encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, GraphQLError> {
+        let invalid = || GraphQLError::Validation(format!("Invalid cursor: {}", cursor));
+        let key = base64::decode(cursor).map_err(|_| invalid())?;
+        let key = String::from_utf8(key).map_err(|_| invalid())?;
+        let (created_at, id) = key.split_once('|').ok_or_else(invalid)?;
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?.with_timezone(&Utc),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// One page of nodes with their cursors
+pub struct Page<T> {
+    pub edges: Vec<(T, String)>,
+    pub page_info: PageInfo,
+    pub total_count: i32,
+}
+
+/// Page through `nodes` per `pagination`: `first` nodes after the `after` cursor, or
+/// the `last` nodes before the `before` cursor
+pub fn paginate<T>(
+    mut nodes: Vec<T>,
+    pagination: Option<PaginationInput>,
+    key: impl Fn(&T) -> PageKey,
+) -> Result<Page<T>, GraphQLError> {
+    let pagination = pagination.unwrap_or(PaginationInput { first: None, after: None, last: None, before: None });
+    let after = pagination.after.as_deref().map(PageKey::decode).transpose()?;
+    let before = pagination.before.as_deref().map(PageKey::decode).transpose()?;
+    let size = |n: Option<i32>| -> Result<Option<usize>, GraphQLError> {
+        match n {
+            Some(n) if n < 0 => Err(GraphQLError::Validation("Page size must not be negative".to_string())),
+            Some(n) => Ok(Some(n.min(MAX_PAGE_SIZE) as usize)),
+            None => Ok(None),
+        }
+    };
+    let last = size(pagination.last)?;
+    let first = size(pagination.first)?.or(if last.is_none() { Some(DEFAULT_PAGE_SIZE as usize) } else { None });
+    let total_count = nodes.len() as i32;
+
+    nodes.sort_by_key(|node| std::cmp::Reverse(key(node)));
+    let mut window: Vec<T> = nodes.into_iter()
+        .filter(|node| after.map_or(true, |after| key(node) < after))
+        .filter(|node| before.map_or(true, |before| key(node) > before))
+        .collect();
+
+    let mut has_next_page = before.is_some();
+    let mut has_previous_page = after.is_some();
+    if let Some(first) = first {
+        has_next_page |= window.len() > first;
+        window.truncate(first);
+    }
+    if let Some(last) = last {
+        let excess = window.len().saturating_sub(last);
+        has_previous_page |= excess > 0;
+        window.drain(..excess);
+    }
+
+    let edges: Vec<(T, String)> = window.into_iter()
+        .map(|node| {
+            let cursor = key(&node).encode();
+            (node, cursor)
+        })
+        .collect();
+    Ok(Page {
+        page_info: PageInfo {
+            has_next_page,
+            has_previous_page,
+            start_cursor: edges.first().map(|(_, cursor)| cursor.clone()),
+            end_cursor: edges.last().map(|(_, cursor)| cursor.clone()),
+        },
+        edges,
+        total_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_cursors_stay_put_when_nodes_are_inserted_while_paging() {
+        let start = Utc::now();
+        let ids: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+        let node = |n: usize| PageKey::new(start + Duration::minutes(n as i64), ids[n]);
+        let mut nodes: Vec<PageKey> = (0..5).map(node).collect();
+        let page = |first, after| Some(PaginationInput { first: Some(first), after, last: None, before: None });
+
+        let one = paginate(nodes.clone(), page(2, None), |n| *n).unwrap();
+        assert_eq!(one.edges.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![node(4), node(3)]);
+        assert!(one.page_info.has_next_page);
+        assert!(!one.page_info.has_previous_page);
+        assert_eq!(one.total_count, 5);
+
+        // A newer node arriving between pages does not shift the next one
+        nodes.push(node(5));
+        let two = paginate(nodes.clone(), page(2, one.page_info.end_cursor), |n| *n).unwrap();
+        assert_eq!(two.edges.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![node(2), node(1)]);
+
+        let three = paginate(nodes.clone(), page(2, two.page_info.end_cursor), |n| *n).unwrap();
+        assert_eq!(three.edges.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![node(0)]);
+        assert!(!three.page_info.has_next_page);
+        assert!(three.page_info.has_previous_page);
+
+        let before = Some(PaginationInput { first: None, after: None, last: Some(1), before: Some(node(3).encode()) });
+        let back = paginate(nodes.clone(), before, |n| *n).unwrap();
+        assert_eq!(back.edges.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![node(4)]);
+
+        assert!(paginate(nodes, page(2, Some("not a cursor".to_string())), |n| *n).is_err());
+    }
+}
@@ -13,6 +13,7 @@ use uuid::Uuid;
 use crate::{
     types::*,
     dataloaders::*,
+    pagination::{paginate, PageKey},
     AppContext, GraphQLError,
 };
 
@@ -240,6 +241,31 @@ impl QueryRoot {
         app_ctx.metrics.get_performance_metrics(service, range).await.map_err(Into::into)
     }
 
+    /// Every circuit breaker with its state, failures and time to the next probe
+    async fn circuit_breakers(&self, ctx: &Context<'_>) -> Result<Vec<CircuitBreaker>> {
+        let current_user = self.require_auth(ctx).await?;
+        let app_ctx = ctx.data::<AppContext>()?;
+
+        app_ctx.auth_service.authorize(&current_user, "monitoring", "read").await?;
+        app_ctx.metrics.get_circuit_breaker_states().await.map_err(Into::into)
+    }
+
+    /// Audit log entries, newest first
+    async fn audit_logs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuditLogFilter>,
+        pagination: Option<PaginationInput>,
+    ) -> Result<AuditLogConnection> {
+        let current_user = self.require_auth(ctx).await?;
+        let app_ctx = ctx.data::<AppContext>()?;
+
+        app_ctx.auth_service.authorize(&current_user, "audit_logs", "read").await?;
+        let logs = app_ctx.database.get_audit_logs(filter.unwrap_or_default()).await?;
+        let connection = self.paginate_audit_logs(logs, pagination).await?;
+        Ok(connection)
+    }
+
     // V3.0.0 Features
     async fn federated_research(
         &self,
@@ -311,71 +337,38 @@ impl QueryRoot {
     }
 
     async fn paginate_users(&self, users: Vec<User>, pagination: Option<PaginationInput>) -> Result<UserConnection> {
-        // Implement pagination logic
-        let total_count = users.len();
-        let edges: Vec<UserEdge> = users.into_iter()
-            .enumerate()
-            .map(|(i, user)| UserEdge {
-                node: user,
-                cursor: base64::encode(i.to_string()),
-            })
-            .collect();
-
+        let page = paginate(users, pagination, |user| PageKey::new(user.created_at, user.id))?;
         Ok(UserConnection {
-            edges,
-            page_info: PageInfo {
-                has_next_page: false, // TODO: Implement proper pagination
-                has_previous_page: false,
-                start_cursor: None,
-                end_cursor: None,
-            },
-            total_count: total_count as i32,
+            edges: page.edges.into_iter().map(|(node, cursor)| UserEdge { node, cursor }).collect(),
+            page_info: page.page_info,
+            total_count: page.total_count,
         })
     }
 
     async fn paginate_api_keys(&self, api_keys: Vec<ApiKey>, pagination: Option<PaginationInput>) -> Result<ApiKeyConnection> {
-        // Similar pagination implementation for API keys
-        let total_count = api_keys.len();
-        let edges: Vec<ApiKeyEdge> = api_keys.into_iter()
-            .enumerate()
-            .map(|(i, api_key)| ApiKeyEdge {
-                node: api_key,
-                cursor: base64::encode(i.to_string()),
-            })
-            .collect();
-
+        let page = paginate(api_keys, pagination, |api_key| PageKey::new(api_key.created_at, api_key.id))?;
         Ok(ApiKeyConnection {
-            edges,
-            page_info: PageInfo {
-                has_next_page: false,
-                has_previous_page: false,
-                start_cursor: None,
-                end_cursor: None,
-            },
-            total_count: total_count as i32,
+            edges: page.edges.into_iter().map(|(node, cursor)| ApiKeyEdge { node, cursor }).collect(),
+            page_info: page.page_info,
+            total_count: page.total_count,
         })
     }
 
     async fn paginate_workflows(&self, workflows: Vec<ResearchWorkflow>, pagination: Option<PaginationInput>) -> Result<WorkflowConnection> {
-        // Similar pagination implementation for workflows
-        let total_count = workflows.len();
-        let edges: Vec<WorkflowEdge> = workflows.into_iter()
-            .enumerate()
-            .map(|(i, workflow)| WorkflowEdge {
-                node: workflow,
-                cursor: base64::encode(i.to_string()),
-            })
-            .collect();
-
+        let page = paginate(workflows, pagination, |workflow| PageKey::new(workflow.created_at, workflow.id))?;
         Ok(WorkflowConnection {
-            edges,
-            page_info: PageInfo {
-                has_next_page: false,
-                has_previous_page: false,
-                start_cursor: None,
-                end_cursor: None,
-            },
-            total_count: total_count as i32,
+            edges: page.edges.into_iter().map(|(node, cursor)| WorkflowEdge { node, cursor }).collect(),
+            page_info: page.page_info,
+            total_count: page.total_count,
+        })
+    }
+
+    async fn paginate_audit_logs(&self, logs: Vec<AuditLog>, pagination: Option<PaginationInput>) -> Result<AuditLogConnection> {
+        let page = paginate(logs, pagination, |log| PageKey::new(log.timestamp, log.id))?;
+        Ok(AuditLogConnection {
+            edges: page.edges.into_iter().map(|(node, cursor)| AuditLogEdge { node, cursor }).collect(),
+            page_info: page.page_info,
+            total_count: page.total_count,
         })
     }
 }
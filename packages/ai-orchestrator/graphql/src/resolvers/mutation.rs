@@ -33,7 +33,8 @@ impl MutationRoot {
             user_agent: ctx.data_opt::<crate::UserAgent>().map(|ua| ua.0.clone()),
         };
         
-        app_ctx.event_store.append_event(login_event).await?;
+        let stream_id = format!("user-{}", auth_result.user.id);
+        app_ctx.event_store.append_event(&stream_id, login_event, None).await?;
         
         Ok(auth_result)
     }
@@ -53,7 +54,8 @@ impl MutationRoot {
                 timestamp: chrono::Utc::now(),
             };
             
-            app_ctx.event_store.append_event(logout_event).await?;
+            let stream_id = format!("user-{}", user.id);
+            app_ctx.event_store.append_event(&stream_id, logout_event, None).await?;
             
             Ok(true)
         } else {
@@ -267,6 +269,16 @@ impl MutationRoot {
         }
     }
 
+    // Monitoring Mutations
+    async fn reset_circuit_breaker(&self, ctx: &Context<'_>, id: String) -> Result<CircuitBreaker> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let current_user = self.require_auth(ctx).await?;
+
+        app_ctx.auth_service.authorize(&current_user, "monitoring", "write").await?;
+        app_ctx.metrics.reset_circuit_breaker(&id).await?
+            .ok_or_else(|| GraphQLError::Validation("Circuit breaker not found".to_string()).into())
+    }
+
     // V3.0.0 Feature Mutations
     async fn create_federated_research(&self, ctx: &Context<'_>, input: FederatedResearchInput) -> Result<FederatedResearchNode> {
         let app_ctx = ctx.data::<AppContext>()?;
@@ -315,6 +315,32 @@ pub struct LiveMetrics {
     pub cache_hit_rate: f64,
 }
 
+#[derive(Enum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitBreakerKind {
+    Provider,
+}
+
+#[derive(Enum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitBreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(SimpleObject, Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitBreaker {
+    /// Identifies the breaker to reset, such as `provider:serpapi`
+    pub id: String,
+    pub kind: CircuitBreakerKind,
+    pub name: String,
+    pub state: CircuitBreakerState,
+    pub consecutive_failures: i32,
+    pub failure_threshold: i32,
+    /// Seconds until an open breaker lets a probe through
+    pub next_probe_in_seconds: Option<i64>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
 // V3.0.0 Feature types
 #[derive(SimpleObject, Clone, Debug, Serialize, Deserialize)]
 pub struct FederatedResearchNode {
@@ -422,6 +448,18 @@ pub struct BMadExecution {
     pub results: Option<JSON>,
 }
 
+#[derive(SimpleObject, Clone, Debug, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub resource: String,
+    pub result: String,
+    pub source_ip: Option<String>,
+    pub details: Option<JSON>,
+}
+
 // Connection types for pagination
 #[derive(SimpleObject, Clone, Debug)]
 pub struct UserConnection {
@@ -462,6 +500,19 @@ pub struct WorkflowEdge {
     pub cursor: String,
 }
 
+#[derive(SimpleObject, Clone, Debug)]
+pub struct AuditLogConnection {
+    pub edges: Vec<AuditLogEdge>,
+    pub page_info: PageInfo,
+    pub total_count: i32,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct AuditLogEdge {
+    pub node: AuditLog,
+    pub cursor: String,
+}
+
 #[derive(SimpleObject, Clone, Debug)]
 pub struct PageInfo {
     pub has_next_page: bool,
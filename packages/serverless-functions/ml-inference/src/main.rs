@@ -2,8 +2,9 @@
 // Phase 4.5: Serverless & Edge Computing
 
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
+    middleware,
     response::Json as ResponseJson,
     routing::{get, post},
     Router,
@@ -16,22 +17,62 @@ use tower_http::cors::CorsLayer;
 use tracing::{info, error, warn};
 use uuid::Uuid;
 
+mod model_loader;
+mod processing;
+mod request_queue;
+#[path = "../../shared/deadline.rs"]
+mod deadline;
+#[path = "../../shared/readiness.rs"]
+mod readiness;
+#[path = "../../shared/service_auth.rs"]
+mod service_auth;
+#[path = "../../shared/shutdown.rs"]
+mod shutdown;
+
+use deadline::Deadline;
+use model_loader::{ModelLoader, ModelReadiness};
+use processing::StepRegistry;
+use readiness::{Readiness, ReadinessReport};
+use request_queue::{InferencePriority, InferenceQueue, QueueFull};
+use service_auth::ServiceAuth;
+use shutdown::Shutdown;
+
 // Application state
 #[derive(Clone)]
 pub struct AppState {
     pub model_registry: Arc<dyn ModelRegistry>,
+    pub model_loader: Arc<ModelLoader>,
+    pub processing_steps: Arc<StepRegistry>,
+    pub request_queue: InferenceQueue,
     pub inference_engine: Arc<dyn InferenceEngine>,
     pub cache: Arc<dyn CacheService>,
     pub metrics: Arc<dyn MetricsService>,
     pub config: Arc<MLConfig>,
+    /// Whether the services and the default model are up, for `/ready`
+    pub readiness: Arc<Readiness>,
 }
 
+/// Readiness component for the backing services being initialized
+const SERVICES_COMPONENT: &str = "services";
+/// Readiness component for the default model being loaded and warmed
+const DEFAULT_MODEL_COMPONENT: &str = "default_model";
+/// Readiness component that fails once the process starts shutting down
+const SHUTDOWN_COMPONENT: &str = "shutdown";
+
+/// Delay between attempts at warming the default model on startup
+const STARTUP_WARMUP_RETRY: std::time::Duration = std::time::Duration::from_secs(5);
+
 // ML configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MLConfig {
     pub default_model: String,
     pub max_batch_size: usize,
+    /// Longest an inference may run before it is aborted, in milliseconds
     pub inference_timeout: u64,
+    /// Inferences run at once; further requests wait in the queue
+    pub max_concurrent_inferences: usize,
+    /// Requests allowed to wait; beyond that they are rejected with 429
+    pub max_queued_inferences: usize,
     pub enable_caching: bool,
     pub cache_ttl: u64,
     pub model_configs: HashMap<String, ModelConfig>,
@@ -58,6 +99,7 @@ pub enum ModelType {
     QuestionAnswering,
     ImageClassification,
     ObjectDetection,
+    OpticalCharacterRecognition,
     Custom(String),
 }
 
@@ -85,7 +127,7 @@ pub struct PerformanceConfig {
     pub precision: Precision,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Precision {
     Float32,
     Float16,
@@ -106,9 +148,11 @@ pub struct InferenceRequest {
     pub inputs: InferenceInputs,
     pub parameters: Option<InferenceParameters>,
     pub callback_url: Option<String>,
+    #[serde(default)]
+    pub priority: InferencePriority,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum InferenceInputs {
     Text(TextInput),
@@ -116,31 +160,33 @@ pub enum InferenceInputs {
     Batch(BatchInput),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TextInput {
     pub text: String,
     pub context: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ImageInput {
     pub image_url: String,
     pub image_data: Option<String>, // Base64 encoded
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct BatchInput {
     pub texts: Option<Vec<String>>,
     pub images: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct InferenceParameters {
     pub temperature: Option<f32>,
     pub top_k: Option<usize>,
     pub top_p: Option<f32>,
     pub max_tokens: Option<usize>,
     pub threshold: Option<f32>,
+    /// Languages the text is expected to be in, most likely first (OCR models)
+    pub language_hints: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -167,6 +213,7 @@ pub enum InferenceResults {
     Classification(ClassificationResult),
     Generation(GenerationResult),
     Detection(DetectionResult),
+    Ocr(OcrResult),
     Batch(BatchResult),
 }
 
@@ -211,6 +258,23 @@ pub struct BoundingBox {
     pub height: f32,
 }
 
+#[derive(Debug, Serialize)]
+pub struct OcrResult {
+    pub text: String,
+    /// Mean confidence over the recognised blocks
+    pub confidence: f32,
+    /// Language the text was recognised as
+    pub language: Option<String>,
+    pub blocks: Vec<TextBlock>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TextBlock {
+    pub text: String,
+    pub confidence: f32,
+    pub bounding_box: BoundingBox,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ImageMetadata {
     pub width: u32,
@@ -235,6 +299,8 @@ pub struct ModelInfo {
     pub input_format: String,
     pub output_format: String,
     pub performance_metrics: PerformanceMetrics,
+    /// Precision a loaded GPU model runs at, after any fallback from its configured one
+    pub effective_precision: Option<Precision>,
     pub last_updated: DateTime<Utc>,
 }
 
@@ -252,10 +318,19 @@ pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub loaded_models: Vec<String>,
+    /// Whether each loaded model has been warmed up for traffic
+    pub model_readiness: Vec<ModelReadiness>,
     pub memory_usage: MemoryUsage,
     pub active_requests: usize,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadModelParams {
+    /// Also run a warmup inference once the model is loaded
+    #[serde(default)]
+    pub warmup: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MemoryUsage {
     pub total_mb: u64,
@@ -265,15 +340,19 @@ pub struct MemoryUsage {
 }
 
 // Main function handler
-pub async fn create_app(state: AppState) -> Router {
+pub async fn create_app(state: AppState, auth: Arc<ServiceAuth>) -> Router {
     Router::new()
         .route("/infer", post(run_inference))
         .route("/models", get(list_models))
         .route("/models/:model_name", get(get_model_info))
         .route("/models/:model_name/load", post(load_model))
         .route("/models/:model_name/unload", post(unload_model))
+        .route("/models/:model_name/warmup", post(warmup_model))
         .route("/health", get(health_check))
+        .route("/ready", get(ready))
         .route("/metrics", get(metrics))
+        .layer(middleware::from_fn_with_state(auth, service_auth::require_signature))
+        .layer(middleware::from_fn(deadline::propagate_deadline))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
@@ -319,25 +398,79 @@ async fn run_inference(
         }
     }
 
-    // Load model if not already loaded
-    if !state.model_registry.is_model_loaded(&request.model_name).await.unwrap_or(false) {
-        info!("Loading model: {}", request.model_name);
-        if let Err(e) = state.model_registry.load_model(&request.model_name).await {
-            error!("Failed to load model {}: {}", request.model_name, e);
+    // Wait for an inference slot, higher priorities first; shed load once the queue is full
+    let permit = match state.request_queue.acquire(request.priority).await {
+        Ok(permit) => permit,
+        Err(QueueFull) => {
+            warn!("Inference queue full, rejecting request: {}", request_id);
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+    };
+
+    // Load model if not already loaded; concurrent requests share one load
+    if let Err(e) = state.model_loader.ensure_loaded(&request.model_name).await {
+        error!("Failed to load model {}: {}", request.model_name, e);
+        return Ok(ResponseJson(InferenceResponse {
+            request_id,
+            model_name: request.model_name,
+            status: InferenceStatus::Failed,
+            results: None,
+            error: Some(format!("Failed to load model: {}", e)),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            tokens_processed: None,
+        }));
+    }
+
+    // Apply the model's configured preprocessing
+    let model_config = state.config.model_configs.get(&request.model_name);
+    let prepared = match model_config {
+        Some(model_config) => processing::preprocess(&model_config.preprocessing, &state.processing_steps, &request.inputs),
+        None => Ok(processing::PreparedInputs { inputs: request.inputs.clone(), tokens: None }),
+    };
+    let prepared = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            warn!("Preprocessing failed for request {}: {}", request_id, e);
             return Ok(ResponseJson(InferenceResponse {
                 request_id,
                 model_name: request.model_name,
                 status: InferenceStatus::Failed,
                 results: None,
-                error: Some(format!("Failed to load model: {}", e)),
+                error: Some(format!("Preprocessing failed: {}", e)),
                 processing_time_ms: start_time.elapsed().as_millis() as u64,
                 tokens_processed: None,
             }));
         }
-    }
+    };
 
-    // Run inference
-    match state.inference_engine.run_inference(&request.model_name, &request.inputs, request.parameters).await {
+    // Run inference, aborting it past the timeout or the caller's deadline if that is sooner,
+    // then the model's configured postprocessing
+    let inference = state.inference_engine.run_inference(&request.model_name, &prepared.inputs, request.parameters.clone());
+    let configured_timeout = std::time::Duration::from_millis(state.config.inference_timeout);
+    let timeout = Deadline::current().map_or(configured_timeout, |deadline| deadline.cap(configured_timeout));
+    let outcome = match tokio::time::timeout(timeout, inference).await {
+        Ok(Ok(results)) => match model_config {
+            Some(model_config) => processing::postprocess(
+                &model_config.postprocessing,
+                &state.processing_steps,
+                request.parameters.as_ref(),
+                results,
+            ),
+            None => Ok(results),
+        },
+        Ok(Err(e)) => Err(e),
+        Err(_) => {
+            state.request_queue.record_timeout();
+            if timeout < configured_timeout {
+                Err("Deadline exceeded: inference did not finish before the request deadline and was aborted".to_string())
+            } else {
+                Err(format!("Inference exceeded the {}ms timeout and was aborted", state.config.inference_timeout))
+            }
+        }
+    };
+    drop(permit);
+
+    match outcome {
         Ok(results) => {
             let processing_time = start_time.elapsed().as_millis() as u64;
             
@@ -349,6 +482,8 @@ async fn run_inference(
 
             // Record metrics
             state.metrics.record_inference(&request.model_name, processing_time, true).await;
+            // A served request has done the lazy initialization a warmup would
+            state.model_loader.mark_warmed(&request.model_name).await;
 
             info!("Inference completed for request: {} in {}ms", request_id, processing_time);
 
@@ -359,7 +494,7 @@ async fn run_inference(
                 results: Some(results),
                 error: None,
                 processing_time_ms: processing_time,
-                tokens_processed: None, // TODO: Calculate tokens processed
+                tokens_processed: prepared.tokens,
             }))
         }
         Err(e) => {
@@ -385,7 +520,12 @@ async fn run_inference(
 // List available models
 async fn list_models(State(state): State<AppState>) -> Result<ResponseJson<Vec<ModelInfo>>, StatusCode> {
     match state.model_registry.list_models().await {
-        Ok(models) => Ok(ResponseJson(models)),
+        Ok(mut models) => {
+            for model in &mut models {
+                model.effective_precision = state.model_loader.effective_precision(&model.name).await;
+            }
+            Ok(ResponseJson(models))
+        }
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -396,18 +536,26 @@ async fn get_model_info(
     Path(model_name): Path<String>,
 ) -> Result<ResponseJson<ModelInfo>, StatusCode> {
     match state.model_registry.get_model_info(&model_name).await {
-        Ok(Some(info)) => Ok(ResponseJson(info)),
+        Ok(Some(mut info)) => {
+            info.effective_precision = state.model_loader.effective_precision(&model_name).await;
+            Ok(ResponseJson(info))
+        }
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
-// Load model
+// Load model, warming it up as well when asked to
 async fn load_model(
     State(state): State<AppState>,
     Path(model_name): Path<String>,
+    Query(params): Query<LoadModelParams>,
 ) -> Result<ResponseJson<serde_json::Value>, StatusCode> {
-    match state.model_registry.load_model(&model_name).await {
+    if params.warmup {
+        return warmup_model(State(state), Path(model_name)).await;
+    }
+
+    match state.model_loader.ensure_loaded(&model_name).await {
         Ok(_) => Ok(ResponseJson(serde_json::json!({"status": "loaded"}))),
         Err(e) => {
             error!("Failed to load model {}: {}", model_name, e);
@@ -416,13 +564,37 @@ async fn load_model(
     }
 }
 
+// Load model and run a dummy inference, so an orchestrator can pre-warm before routing traffic
+async fn warmup_model(
+    State(state): State<AppState>,
+    Path(model_name): Path<String>,
+) -> Result<ResponseJson<serde_json::Value>, StatusCode> {
+    if !state.model_registry.model_exists(&model_name).await.unwrap_or(false) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    match warm_model(&state, &model_name).await {
+        Ok(()) => Ok(ResponseJson(serde_json::json!({"status": "warmed"}))),
+        Err(e) => {
+            error!("Failed to warm up model {}: {}", model_name, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 // Unload model
 async fn unload_model(
     State(state): State<AppState>,
     Path(model_name): Path<String>,
 ) -> Result<ResponseJson<serde_json::Value>, StatusCode> {
-    match state.model_registry.unload_model(&model_name).await {
-        Ok(_) => Ok(ResponseJson(serde_json::json!({"status": "unloaded"}))),
+    match state.model_loader.unload(&model_name).await {
+        Ok(_) => {
+            // Traffic for the default model would wait on a reload; stop routing until it is warm again
+            if model_name == state.config.default_model {
+                state.readiness.mark_starting(DEFAULT_MODEL_COMPONENT);
+            }
+            Ok(ResponseJson(serde_json::json!({"status": "unloaded"})))
+        }
         Err(e) => {
             error!("Failed to unload model {}: {}", model_name, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -432,29 +604,105 @@ async fn unload_model(
 
 // Health check endpoint
 async fn health_check(State(state): State<AppState>) -> ResponseJson<HealthResponse> {
-    let loaded_models = state.model_registry.list_loaded_models().await.unwrap_or_default();
-    
+    let loaded_models = state.model_loader.loaded_models().await;
+    let model_readiness = state.model_loader.readiness().await;
+
     ResponseJson(HealthResponse {
         status: "healthy".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         loaded_models,
+        model_readiness,
         memory_usage: MemoryUsage {
-            total_mb: 0, // TODO: Get actual memory usage
-            used_mb: 0,
-            gpu_total_mb: None,
-            gpu_used_mb: None,
+            total_mb: state.model_loader.max_memory_mb(),
+            used_mb: state.model_loader.used_memory_mb().await,
+            gpu_total_mb: Some(state.model_loader.max_gpu_memory_mb()),
+            gpu_used_mb: Some(state.model_loader.used_gpu_memory_mb().await),
         },
         active_requests: 0, // TODO: Track active requests
     })
 }
 
+// Readiness probe: 200 once the services are up and the default model is warm, 503 until then
+async fn ready(State(state): State<AppState>) -> ReadinessReport {
+    state.readiness.report()
+}
+
 // Metrics endpoint
 async fn metrics(State(state): State<AppState>) -> String {
-    // TODO: Implement Prometheus metrics
-    "# ML inference metrics\n".to_string()
+    let queue = state.request_queue.stats();
+    let series = [
+        ("ml_inference_queue_depth", "gauge", "Requests waiting for an inference slot", queue.depth as u64),
+        ("ml_inference_running", "gauge", "Inferences running", queue.running as u64),
+        ("ml_inference_admitted_total", "counter", "Requests admitted to run", queue.admitted_total),
+        ("ml_inference_shed_total", "counter", "Requests rejected because the queue was full", queue.shed_total),
+        ("ml_inference_timeouts_total", "counter", "Inferences aborted for exceeding the timeout", queue.timeouts_total),
+        ("ml_inference_queue_wait_ms_sum", "counter", "Total time admitted requests waited in the queue", queue.wait_ms_sum),
+        ("ml_inference_queue_wait_ms_max", "gauge", "Longest time a request waited in the queue", queue.wait_ms_max),
+    ];
+
+    let mut output = String::from("# ML inference metrics\n");
+    for (name, kind, help, value) in series {
+        output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n{} {}\n", name, help, name, kind, name, value));
+    }
+    output
 }
 
 // Helper functions
+async fn warm_model(state: &AppState, model_name: &str) -> Result<(), String> {
+    state.model_loader.ensure_loaded(model_name).await?;
+    if state.model_loader.is_warmed(model_name).await {
+        return Ok(());
+    }
+
+    let start_time = std::time::Instant::now();
+    let model_type = state.config.model_configs.get(model_name).map(|config| &config.model_type);
+    state.inference_engine.run_inference(model_name, &warmup_inputs(model_type), None).await?;
+    state.model_loader.mark_warmed(model_name).await;
+    if model_name == state.config.default_model {
+        state.readiness.mark_ready(DEFAULT_MODEL_COMPONENT);
+    }
+
+    info!("Warmed up model {} in {}ms", model_name, start_time.elapsed().as_millis());
+    Ok(())
+}
+
+// Warm the default model in the background, retrying until it takes, so the server can
+// answer liveness probes while it loads
+fn spawn_startup_warmup(state: AppState) {
+    tokio::spawn(async move {
+        let model_name = state.config.default_model.clone();
+        loop {
+            match warm_model(&state, &model_name).await {
+                Ok(()) => break,
+                Err(e) => {
+                    warn!("Default model {} is not ready yet: {}", model_name, e);
+                    state.readiness.mark_failed(DEFAULT_MODEL_COMPONENT, e);
+                    tokio::time::sleep(STARTUP_WARMUP_RETRY).await;
+                }
+            }
+        }
+    });
+}
+
+// Smallest input the model accepts; its output is discarded
+fn warmup_inputs(model_type: Option<&ModelType>) -> InferenceInputs {
+    match model_type {
+        Some(ModelType::ImageClassification | ModelType::ObjectDetection | ModelType::OpticalCharacterRecognition) => InferenceInputs::Image(ImageInput {
+            image_url: String::new(),
+            // 1x1 transparent PNG
+            image_data: Some("iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=".to_string()),
+        }),
+        Some(ModelType::QuestionAnswering) => InferenceInputs::Text(TextInput {
+            text: "What is this?".to_string(),
+            context: Some("This is a warmup request.".to_string()),
+        }),
+        _ => InferenceInputs::Text(TextInput {
+            text: "This is a warmup request.".to_string(),
+            context: None,
+        }),
+    }
+}
+
 fn generate_cache_key(request: &InferenceRequest) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
@@ -485,6 +733,10 @@ fn generate_cache_key(request: &InferenceRequest) -> String {
             }
         }
     }
+    // The same image read as another language is a different result
+    if let Some(hints) = request.parameters.as_ref().and_then(|p| p.language_hints.as_ref()) {
+        hints.hash(&mut hasher);
+    }
     
     format!("ml_inference:{:x}", hasher.finish())
 }
@@ -494,7 +746,8 @@ fn generate_cache_key(request: &InferenceRequest) -> String {
 pub trait ModelRegistry: Send + Sync {
     async fn model_exists(&self, name: &str) -> Result<bool, String>;
     async fn is_model_loaded(&self, name: &str) -> Result<bool, String>;
-    async fn load_model(&self, name: &str) -> Result<(), String>;
+    /// Load a model; `precision` is set for models placed on the GPU
+    async fn load_model(&self, name: &str, precision: Option<Precision>) -> Result<(), String>;
     async fn unload_model(&self, name: &str) -> Result<(), String>;
     async fn list_models(&self) -> Result<Vec<ModelInfo>, String>;
     async fn get_model_info(&self, name: &str) -> Result<Option<ModelInfo>, String>;
@@ -535,9 +788,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         default_model: "text-classification".to_string(),
         max_batch_size: 32,
         inference_timeout: 30000, // 30 seconds
+        max_concurrent_inferences: 4,
+        max_queued_inferences: 64,
         enable_caching: true,
         cache_ttl: 3600, // 1 hour
-        model_configs: HashMap::new(),
+        model_configs: HashMap::from([("ocr".to_string(), ocr_model_config())]),
         resource_limits: ResourceLimits {
             max_memory_mb: 4096,
             max_gpu_memory_mb: 8192,
@@ -546,26 +801,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // TODO: Initialize actual services
+    let model_registry: Arc<dyn ModelRegistry> = Arc::new(MockModelRegistry);
+    let gpu_models = config.model_configs.iter()
+        .filter(|(_, model_config)| model_config.performance.use_gpu)
+        .map(|(name, model_config)| (name.clone(), model_config.performance.precision))
+        .collect();
+    let model_loader = Arc::new(
+        ModelLoader::new(model_registry.clone(), config.resource_limits.max_memory_mb)
+            .with_gpu(config.resource_limits.max_gpu_memory_mb, gpu_models),
+    );
     let state = AppState {
-        model_registry: Arc::new(MockModelRegistry),
-        inference_engine: Arc::new(MockInferenceEngine),
+        model_registry,
+        model_loader,
+        processing_steps: Arc::new(StepRegistry::with_builtin_steps()),
+        request_queue: InferenceQueue::new(config.max_concurrent_inferences, config.max_queued_inferences),
+        inference_engine: Arc::new(MockInferenceEngine::new(&config)),
         cache: Arc::new(MockCacheService),
         metrics: Arc::new(MockMetricsService),
         config,
+        readiness: Readiness::new([SERVICES_COMPONENT, DEFAULT_MODEL_COMPONENT]),
     };
+    state.readiness.mark_ready(SERVICES_COMPONENT);
+    spawn_startup_warmup(state.clone());
 
-    let app = create_app(state).await;
+    // Stop being routed to as soon as shutdown starts, while in-flight inferences drain
+    let shutdown = Shutdown::on_signals();
+    let readiness = state.readiness.clone();
+    shutdown.on_start(move || readiness.mark_failed(SHUTDOWN_COMPONENT, "shutting down"));
+
+    let auth = Arc::new(ServiceAuth::from_env().await?);
+    let app = create_app(state, auth).await;
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
     info!("ML inference function listening on 0.0.0.0:8080");
-    
-    axum::serve(listener, app).await?;
+
+    shutdown::serve(listener, app, shutdown, shutdown::grace_period_from_env()).await?;
     Ok(())
 }
 
+/// The OCR model the desktop app's image OCR step calls by default
+fn ocr_model_config() -> ModelConfig {
+    ModelConfig {
+        model_type: ModelType::OpticalCharacterRecognition,
+        model_path: "models/ocr".to_string(),
+        input_shape: vec![],
+        output_shape: vec![],
+        preprocessing: PreprocessingConfig { tokenizer: None, max_length: None, normalization: false, custom_steps: vec![] },
+        postprocessing: PostprocessingConfig { apply_softmax: false, threshold: None, top_k: None, custom_steps: vec![] },
+        performance: PerformanceConfig { batch_size: 1, max_sequence_length: 0, use_gpu: false, precision: Precision::Float32 },
+    }
+}
+
 // Mock implementations
 struct MockModelRegistry;
-struct MockInferenceEngine;
+/// Answers each model in the shape its type returns, so callers parse real payloads
+struct MockInferenceEngine {
+    model_types: HashMap<String, ModelType>,
+}
+
+impl MockInferenceEngine {
+    fn new(config: &MLConfig) -> Self {
+        Self {
+            model_types: config.model_configs.iter()
+                .map(|(name, model_config)| (name.clone(), model_config.model_type.clone()))
+                .collect(),
+        }
+    }
+}
 struct MockCacheService;
 struct MockMetricsService;
 
@@ -573,7 +875,7 @@ struct MockMetricsService;
 impl ModelRegistry for MockModelRegistry {
     async fn model_exists(&self, _name: &str) -> Result<bool, String> { Ok(true) }
     async fn is_model_loaded(&self, _name: &str) -> Result<bool, String> { Ok(true) }
-    async fn load_model(&self, _name: &str) -> Result<(), String> { Ok(()) }
+    async fn load_model(&self, _name: &str, _precision: Option<Precision>) -> Result<(), String> { Ok(()) }
     async fn unload_model(&self, _name: &str) -> Result<(), String> { Ok(()) }
     async fn list_models(&self) -> Result<Vec<ModelInfo>, String> { Ok(vec![]) }
     async fn get_model_info(&self, _name: &str) -> Result<Option<ModelInfo>, String> { Ok(None) }
@@ -584,14 +886,28 @@ impl ModelRegistry for MockModelRegistry {
 impl InferenceEngine for MockInferenceEngine {
     async fn run_inference(
         &self,
-        _model_name: &str,
-        _inputs: &InferenceInputs,
-        _parameters: Option<InferenceParameters>,
+        model_name: &str,
+        inputs: &InferenceInputs,
+        parameters: Option<InferenceParameters>,
     ) -> Result<InferenceResults, String> {
-        Ok(InferenceResults::Classification(ClassificationResult {
-            predictions: vec![],
-            confidence_scores: vec![],
-        }))
+        match (self.model_types.get(model_name), inputs) {
+            (Some(ModelType::OpticalCharacterRecognition), InferenceInputs::Image(_)) => {
+                // No text recognised, in the language the caller expected first
+                Ok(InferenceResults::Ocr(OcrResult {
+                    text: String::new(),
+                    confidence: 0.0,
+                    language: parameters.and_then(|p| p.language_hints).and_then(|hints| hints.into_iter().next()),
+                    blocks: vec![],
+                }))
+            }
+            (Some(ModelType::OpticalCharacterRecognition), _) => {
+                Err(format!("Model {} reads images, not text", model_name))
+            }
+            _ => Ok(InferenceResults::Classification(ClassificationResult {
+                predictions: vec![],
+                confidence_scores: vec![],
+            })),
+        }
     }
 }
 
@@ -607,3 +923,49 @@ impl CacheService for MockCacheService {
 impl MetricsService for MockMetricsService {
     async fn record_inference(&self, _model_name: &str, _duration_ms: u64, _success: bool) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ocr_models_return_ocr_payloads() {
+        let config = MLConfig {
+            default_model: "text-classification".to_string(),
+            max_batch_size: 1,
+            inference_timeout: 1000,
+            max_concurrent_inferences: 1,
+            max_queued_inferences: 1,
+            enable_caching: false,
+            cache_ttl: 0,
+            model_configs: HashMap::from([("ocr".to_string(), ocr_model_config())]),
+            resource_limits: ResourceLimits { max_memory_mb: 1, max_gpu_memory_mb: 0, max_execution_time: 1 },
+        };
+        let engine = MockInferenceEngine::new(&config);
+        let image = InferenceInputs::Image(ImageInput { image_url: "https://example.org/scan.png".to_string(), image_data: None });
+        let parameters = InferenceParameters {
+            temperature: None,
+            top_k: None,
+            top_p: None,
+            max_tokens: None,
+            threshold: None,
+            language_hints: Some(vec!["de".to_string(), "en".to_string()]),
+        };
+
+        // The fields the desktop app's OCR client reads, without an enum tag around them
+        let results = engine.run_inference("ocr", &image, Some(parameters)).await.unwrap();
+        assert_eq!(serde_json::to_value(&results).unwrap(), serde_json::json!({
+            "text": "",
+            "confidence": 0.0,
+            "language": "de",
+            "blocks": [],
+        }));
+
+        let text = InferenceInputs::Text(TextInput { text: "not an image".to_string(), context: None });
+        assert!(engine.run_inference("ocr", &text, None).await.is_err());
+        assert!(matches!(
+            engine.run_inference("text-classification", &text, None).await.unwrap(),
+            InferenceResults::Classification(_)
+        ));
+    }
+}
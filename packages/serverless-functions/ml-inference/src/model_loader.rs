@@ -0,0 +1,413 @@
+// On-demand model loading with single-flight loads, LRU eviction and GPU accounting
+
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{ModelRegistry, Precision};
+
+/// Memory accounting for the models currently held by the registry
+#[derive(Default)]
+struct Residency {
+    models: HashMap<String, ResidentModel>,
+    /// Memory reserved by loads still in progress
+    pending_mb: u64,
+    pending_gpu_mb: u64,
+    /// Monotonic counter ordering model use, for LRU eviction
+    clock: u64,
+}
+
+struct ResidentModel {
+    memory_mb: u64,
+    gpu_memory_mb: u64,
+    /// Precision the model was loaded at on the GPU; `None` for CPU models
+    precision: Option<Precision>,
+    last_used: u64,
+    /// A dummy inference has run since the load
+    warmed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelState {
+    /// In memory, but the first inference still pays for lazy initialization
+    Loaded,
+    /// Warmed up and ready for traffic
+    Warmed,
+}
+
+/// Readiness of a loaded model, as reported by the health check
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelReadiness {
+    pub name: String,
+    pub state: ModelState,
+    pub memory_mb: u64,
+    pub gpu_memory_mb: u64,
+    pub precision: Option<Precision>,
+}
+
+/// Memory set aside for a load in progress
+struct Reservation {
+    memory_mb: u64,
+    gpu_memory_mb: u64,
+    precision: Option<Precision>,
+    /// Models to unload before loading
+    evicted: Vec<String>,
+}
+
+impl Residency {
+    fn used_mb(&self) -> u64 {
+        self.models.values().map(|model| model.memory_mb).sum()
+    }
+
+    fn used_gpu_mb(&self) -> u64 {
+        self.models.values().map(|model| model.gpu_memory_mb).sum()
+    }
+
+    fn touch(&mut self, name: &str) -> bool {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.models.get_mut(name) {
+            Some(model) => {
+                model.last_used = clock;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Loads models into the registry on first use.
+///
+/// Concurrent first requests for a model wait on a per-model lock, so only one of
+/// them loads it. Before a load, least-recently-used models are unloaded until the
+/// new one fits in `max_memory_mb`. Models configured for the GPU count against
+/// `max_gpu_memory_mb` instead; one that does not fit at its configured precision is
+/// loaded at the next lower precision that fits, and refused if none does.
+pub struct ModelLoader {
+    registry: Arc<dyn ModelRegistry>,
+    max_memory_mb: u64,
+    max_gpu_memory_mb: u64,
+    /// Requested precision of every model configured to run on the GPU
+    gpu_models: HashMap<String, Precision>,
+    load_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+    residency: Mutex<Residency>,
+}
+
+impl ModelLoader {
+    pub fn new(registry: Arc<dyn ModelRegistry>, max_memory_mb: u64) -> Self {
+        Self {
+            registry,
+            max_memory_mb,
+            max_gpu_memory_mb: 0,
+            gpu_models: HashMap::new(),
+            load_locks: Mutex::new(HashMap::new()),
+            residency: Mutex::new(Residency::default()),
+        }
+    }
+
+    /// Place `gpu_models` on a GPU with `max_gpu_memory_mb` of memory
+    pub fn with_gpu(mut self, max_gpu_memory_mb: u64, gpu_models: HashMap<String, Precision>) -> Self {
+        self.max_gpu_memory_mb = max_gpu_memory_mb;
+        self.gpu_models = gpu_models;
+        self
+    }
+
+    /// Make sure a model is loaded, loading it if needed. Returns whether this call loaded it.
+    pub async fn ensure_loaded(&self, name: &str) -> Result<bool, String> {
+        if self.residency.lock().await.touch(name) {
+            return Ok(false);
+        }
+
+        let load_lock = self.load_locks.lock().await
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+        let _guard = load_lock.lock().await;
+
+        // Another request may have finished loading it while this one waited
+        if self.residency.lock().await.touch(name) {
+            return Ok(false);
+        }
+
+        let memory_mb = self.model_memory_mb(name).await?;
+        let reservation = match self.gpu_models.get(name) {
+            Some(&precision) => self.reserve_gpu(name, memory_mb, precision).await?,
+            None => self.reserve(name, memory_mb).await?,
+        };
+        for victim in &reservation.evicted {
+            info!("Unloading least recently used model {} to make room for {}", victim, name);
+            if let Err(e) = self.registry.unload_model(victim).await {
+                warn!("Failed to unload model {}: {}", victim, e);
+            }
+        }
+
+        match reservation.precision {
+            Some(precision) => info!("Loading model: {} on GPU at {:?} ({} MB)", name, precision, reservation.gpu_memory_mb),
+            None => info!("Loading model: {} ({} MB)", name, reservation.memory_mb),
+        }
+        let result = self.registry.load_model(name, reservation.precision).await;
+
+        let mut residency = self.residency.lock().await;
+        residency.pending_mb -= reservation.memory_mb;
+        residency.pending_gpu_mb -= reservation.gpu_memory_mb;
+        result?;
+        residency.clock += 1;
+        let last_used = residency.clock;
+        residency.models.insert(name.to_string(), ResidentModel {
+            memory_mb: reservation.memory_mb,
+            gpu_memory_mb: reservation.gpu_memory_mb,
+            precision: reservation.precision,
+            last_used,
+            warmed: false,
+        });
+        Ok(true)
+    }
+
+    /// Unload a model and release its memory
+    pub async fn unload(&self, name: &str) -> Result<(), String> {
+        self.registry.unload_model(name).await?;
+        self.residency.lock().await.models.remove(name);
+        Ok(())
+    }
+
+    /// Whether a loaded model has been warmed up
+    pub async fn is_warmed(&self, name: &str) -> bool {
+        self.residency.lock().await.models.get(name).map_or(false, |model| model.warmed)
+    }
+
+    /// Record that a model's warmup inference ran; a no-op if it was evicted meanwhile
+    pub async fn mark_warmed(&self, name: &str) {
+        if let Some(model) = self.residency.lock().await.models.get_mut(name) {
+            model.warmed = true;
+        }
+    }
+
+    /// Readiness of every loaded model, by name
+    pub async fn readiness(&self) -> Vec<ModelReadiness> {
+        let residency = self.residency.lock().await;
+        let mut readiness: Vec<ModelReadiness> = residency.models.iter()
+            .map(|(name, model)| ModelReadiness {
+                name: name.clone(),
+                state: if model.warmed { ModelState::Warmed } else { ModelState::Loaded },
+                memory_mb: model.memory_mb,
+                gpu_memory_mb: model.gpu_memory_mb,
+                precision: model.precision,
+            })
+            .collect();
+        readiness.sort_by(|a, b| a.name.cmp(&b.name));
+        readiness
+    }
+
+    /// Names of the models loaded through this loader
+    pub async fn loaded_models(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.residency.lock().await.models.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Memory held by loaded models, in MB
+    pub async fn used_memory_mb(&self) -> u64 {
+        self.residency.lock().await.used_mb()
+    }
+
+    pub fn max_memory_mb(&self) -> u64 {
+        self.max_memory_mb
+    }
+
+    /// GPU memory held by loaded models, in MB
+    pub async fn used_gpu_memory_mb(&self) -> u64 {
+        self.residency.lock().await.used_gpu_mb()
+    }
+
+    pub fn max_gpu_memory_mb(&self) -> u64 {
+        self.max_gpu_memory_mb
+    }
+
+    /// Precision a loaded GPU model actually runs at, which may be below the configured one
+    pub async fn effective_precision(&self, name: &str) -> Option<Precision> {
+        self.residency.lock().await.models.get(name).and_then(|model| model.precision)
+    }
+
+    async fn model_memory_mb(&self, name: &str) -> Result<u64, String> {
+        // A model without a memory estimate is accounted as free rather than refused
+        let info = self.registry.get_model_info(name).await?;
+        Ok(info.map_or(0, |info| info.performance_metrics.memory_usage_mb.ceil() as u64))
+    }
+
+    /// Reserve memory for a load, choosing the models to unload to make it fit
+    async fn reserve(&self, name: &str, memory_mb: u64) -> Result<Reservation, String> {
+        if memory_mb > self.max_memory_mb {
+            return Err(format!(
+                "model {} needs {} MB, more than the {} MB limit",
+                name, memory_mb, self.max_memory_mb
+            ));
+        }
+
+        let mut residency = self.residency.lock().await;
+        // Memory reserved by loads in progress cannot be freed by evicting
+        if residency.pending_mb + memory_mb > self.max_memory_mb {
+            return Err(format!(
+                "not enough memory to load model {}: {} MB reserved by loads in progress",
+                name, residency.pending_mb
+            ));
+        }
+
+        let mut evicted = Vec::new();
+        while residency.used_mb() + residency.pending_mb + memory_mb > self.max_memory_mb {
+            let victim = residency.models.iter()
+                .min_by_key(|(_, model)| model.last_used)
+                .map(|(victim, _)| victim.clone());
+            let Some(victim) = victim else { break };
+            residency.models.remove(&victim);
+            evicted.push(victim);
+        }
+        residency.pending_mb += memory_mb;
+        Ok(Reservation { memory_mb, gpu_memory_mb: 0, precision: None, evicted })
+    }
+
+    /// Reserve GPU memory at the highest precision, from the requested one down, that fits.
+    /// `memory_mb` is the model's Float32 footprint.
+    async fn reserve_gpu(&self, name: &str, memory_mb: u64, requested: Precision) -> Result<Reservation, String> {
+        let mut residency = self.residency.lock().await;
+        let free_mb = self.max_gpu_memory_mb
+            .saturating_sub(residency.used_gpu_mb() + residency.pending_gpu_mb);
+
+        let fitting = fallback_precisions(requested).iter()
+            .map(|&precision| (precision, gpu_footprint_mb(memory_mb, precision)))
+            .find(|&(_, gpu_memory_mb)| gpu_memory_mb <= free_mb);
+        let Some((precision, gpu_memory_mb)) = fitting else {
+            return Err(format!(
+                "model {} does not fit in GPU memory even at Int8: needs {} MB, {} of {} MB free",
+                name, gpu_footprint_mb(memory_mb, Precision::Int8), free_mb, self.max_gpu_memory_mb
+            ));
+        };
+        if precision != requested {
+            warn!("Model {} does not fit on the GPU at {:?}, falling back to {:?}", name, requested, precision);
+        }
+
+        residency.pending_gpu_mb += gpu_memory_mb;
+        Ok(Reservation { memory_mb: 0, gpu_memory_mb, precision: Some(precision), evicted: Vec::new() })
+    }
+}
+
+/// Precisions to try for a model, the requested one first
+fn fallback_precisions(requested: Precision) -> &'static [Precision] {
+    match requested {
+        Precision::Float32 => &[Precision::Float32, Precision::Float16, Precision::Int8],
+        Precision::Float16 => &[Precision::Float16, Precision::Int8],
+        Precision::Int8 => &[Precision::Int8],
+    }
+}
+
+/// GPU memory of a model at `precision`, given its Float32 footprint
+fn gpu_footprint_mb(float32_mb: u64, precision: Precision) -> u64 {
+    match precision {
+        Precision::Float32 => float32_mb,
+        Precision::Float16 => float32_mb.div_ceil(2),
+        Precision::Int8 => float32_mb.div_ceil(4),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::{ModelInfo, ModelType, PerformanceMetrics};
+
+    #[derive(Default)]
+    struct CountingRegistry {
+        loads: AtomicUsize,
+        unloaded: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ModelRegistry for CountingRegistry {
+        async fn model_exists(&self, _name: &str) -> Result<bool, String> { Ok(true) }
+        async fn is_model_loaded(&self, _name: &str) -> Result<bool, String> { Ok(false) }
+        async fn load_model(&self, _name: &str, _precision: Option<Precision>) -> Result<(), String> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            Ok(())
+        }
+        async fn unload_model(&self, name: &str) -> Result<(), String> {
+            self.unloaded.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+        async fn list_models(&self) -> Result<Vec<ModelInfo>, String> { Ok(vec![]) }
+        async fn get_model_info(&self, name: &str) -> Result<Option<ModelInfo>, String> {
+            Ok(Some(ModelInfo {
+                name: name.to_string(),
+                model_type: ModelType::TextClassification,
+                version: "1".to_string(),
+                description: String::new(),
+                input_format: "text".to_string(),
+                output_format: "labels".to_string(),
+                performance_metrics: PerformanceMetrics {
+                    average_latency_ms: 0.0,
+                    throughput_per_second: 0.0,
+                    accuracy: None,
+                    memory_usage_mb: 400.0,
+                },
+                effective_precision: None,
+                last_updated: chrono::Utc::now(),
+            }))
+        }
+        async fn list_loaded_models(&self) -> Result<Vec<String>, String> { Ok(vec![]) }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_for_a_cold_model_load_it_once() {
+        let registry = Arc::new(CountingRegistry::default());
+        let loader = Arc::new(ModelLoader::new(registry.clone(), 1000));
+
+        let requests: Vec<_> = (0..8)
+            .map(|_| {
+                let loader = loader.clone();
+                tokio::spawn(async move { loader.ensure_loaded("sentiment").await })
+            })
+            .collect();
+        let mut loaded_by_request = 0;
+        for request in requests {
+            if request.await.unwrap().unwrap() {
+                loaded_by_request += 1;
+            }
+        }
+        assert_eq!(registry.loads.load(Ordering::SeqCst), 1);
+        assert_eq!(loaded_by_request, 1);
+        assert_eq!(loader.used_memory_mb().await, 400);
+        assert_eq!(loader.readiness().await[0].state, ModelState::Loaded);
+        loader.mark_warmed("sentiment").await;
+        assert_eq!(loader.readiness().await[0].state, ModelState::Warmed);
+
+        // A third 400 MB model does not fit in 1000 MB, so the least recently used goes
+        loader.ensure_loaded("summarizer").await.unwrap();
+        loader.ensure_loaded("sentiment").await.unwrap();
+        loader.ensure_loaded("ner").await.unwrap();
+        assert_eq!(*registry.unloaded.lock().unwrap(), vec!["summarizer".to_string()]);
+        assert_eq!(loader.loaded_models().await, vec!["ner".to_string(), "sentiment".to_string()]);
+        assert_eq!(loader.used_memory_mb().await, 800);
+    }
+
+    #[tokio::test]
+    async fn test_gpu_models_fall_back_to_lower_precision_before_being_refused() {
+        let gpu_models = HashMap::from([
+            ("encoder".to_string(), Precision::Float16),
+            ("reranker".to_string(), Precision::Float16),
+            ("detector".to_string(), Precision::Float32),
+        ]);
+        let loader = ModelLoader::new(Arc::new(CountingRegistry::default()), 1000).with_gpu(300, gpu_models);
+
+        // Each is 400 MB at Float32: 200 MB at Float16, 100 MB at Int8
+        loader.ensure_loaded("encoder").await.unwrap();
+        assert_eq!(loader.effective_precision("encoder").await, Some(Precision::Float16));
+        loader.ensure_loaded("reranker").await.unwrap();
+        assert_eq!(loader.effective_precision("reranker").await, Some(Precision::Int8));
+        assert_eq!(loader.used_gpu_memory_mb().await, 300);
+        assert!(loader.ensure_loaded("detector").await.is_err());
+
+        // GPU models do not count against host memory
+        assert_eq!(loader.used_memory_mb().await, 0);
+    }
+}
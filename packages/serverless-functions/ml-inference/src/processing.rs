@@ -0,0 +1,275 @@
+// Pre- and postprocessing driven by a model's ModelConfig
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    BatchInput, InferenceInputs, InferenceParameters, InferenceResults, PostprocessingConfig,
+    PreprocessingConfig, TextInput,
+};
+
+pub type PreprocessStep = Arc<dyn Fn(String) -> String + Send + Sync>;
+pub type PostprocessStep = Arc<dyn Fn(&mut InferenceResults) + Send + Sync>;
+
+/// Named steps that `custom_steps` in a model's config refer to
+pub struct StepRegistry {
+    preprocess: HashMap<String, PreprocessStep>,
+    postprocess: HashMap<String, PostprocessStep>,
+}
+
+impl StepRegistry {
+    pub fn new() -> Self {
+        Self { preprocess: HashMap::new(), postprocess: HashMap::new() }
+    }
+
+    /// Registry holding the steps every deployment has
+    pub fn with_builtin_steps() -> Self {
+        let mut registry = Self::new();
+        registry.register_preprocess("lowercase", |text| text.to_lowercase());
+        registry.register_preprocess("strip_urls", |text| {
+            text.split_whitespace()
+                .filter(|word| !word.starts_with("http://") && !word.starts_with("https://"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+        registry.register_preprocess("strip_html", strip_html);
+        registry.register_postprocess("sort_by_confidence", |results| {
+            if let InferenceResults::Classification(classification) = results {
+                classification.predictions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+                classification.confidence_scores = classification.predictions.iter().map(|p| p.confidence).collect();
+            }
+        });
+        registry
+    }
+
+    pub fn register_preprocess<F>(&mut self, name: &str, step: F)
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.preprocess.insert(name.to_string(), Arc::new(step));
+    }
+
+    pub fn register_postprocess<F>(&mut self, name: &str, step: F)
+    where
+        F: Fn(&mut InferenceResults) + Send + Sync + 'static,
+    {
+        self.postprocess.insert(name.to_string(), Arc::new(step));
+    }
+
+    fn preprocess_step(&self, name: &str) -> Result<&PreprocessStep, String> {
+        self.preprocess.get(name).ok_or_else(|| format!("Unknown preprocessing step: {}", name))
+    }
+
+    fn postprocess_step(&self, name: &str) -> Result<&PostprocessStep, String> {
+        self.postprocess.get(name).ok_or_else(|| format!("Unknown postprocessing step: {}", name))
+    }
+}
+
+/// Text inputs after preprocessing, with the number of tokens they hold
+pub struct PreparedInputs {
+    pub inputs: InferenceInputs,
+    pub tokens: Option<usize>,
+}
+
+/// Apply a model's custom steps, normalization and tokenizer truncation to its text inputs.
+/// Image inputs pass through unchanged.
+pub fn preprocess(
+    config: &PreprocessingConfig,
+    steps: &StepRegistry,
+    inputs: &InferenceInputs,
+) -> Result<PreparedInputs, String> {
+    let custom_steps = config.custom_steps.iter()
+        .map(|name| steps.preprocess_step(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tokens = 0;
+    let mut prepare = |text: &str| -> Result<String, String> {
+        let mut text = custom_steps.iter().fold(text.to_string(), |text, step| step(text));
+        if config.normalization {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        let (text, count) = tokenize(config.tokenizer.as_deref(), &text, config.max_length)?;
+        tokens += count;
+        Ok(text)
+    };
+
+    let inputs = match inputs {
+        InferenceInputs::Text(input) => InferenceInputs::Text(TextInput {
+            text: prepare(&input.text)?,
+            context: input.context.as_deref().map(&mut prepare).transpose()?,
+        }),
+        InferenceInputs::Batch(batch) => InferenceInputs::Batch(BatchInput {
+            texts: batch.texts.as_ref()
+                .map(|texts| texts.iter().map(|text| prepare(text)).collect::<Result<Vec<_>, _>>())
+                .transpose()?,
+            images: batch.images.clone(),
+        }),
+        InferenceInputs::Image(image) => {
+            return Ok(PreparedInputs { inputs: InferenceInputs::Image(image.clone()), tokens: None });
+        }
+    };
+
+    Ok(PreparedInputs { inputs, tokens: Some(tokens) })
+}
+
+/// Split text with the named tokenizer and truncate it to `max_length` tokens.
+/// Without a tokenizer the text is split on whitespace.
+fn tokenize(tokenizer: Option<&str>, text: &str, max_length: Option<usize>) -> Result<(String, usize), String> {
+    let tokens: Vec<String> = match tokenizer.unwrap_or("whitespace") {
+        "whitespace" => text.split_whitespace().map(str::to_string).collect(),
+        "character" => text.chars().filter(|c| !c.is_whitespace()).map(String::from).collect(),
+        other => return Err(format!("Unknown tokenizer: {}", other)),
+    };
+
+    let Some(max_length) = max_length.filter(|&max| tokens.len() > max) else {
+        return Ok((text.to_string(), tokens.len()));
+    };
+    let separator = if tokenizer == Some("character") { "" } else { " " };
+    Ok((tokens[..max_length].join(separator), max_length))
+}
+
+/// Apply a model's softmax, threshold and top_k to raw outputs, then its custom steps.
+/// A request's own threshold and top_k take precedence over the config's.
+pub fn postprocess(
+    config: &PostprocessingConfig,
+    steps: &StepRegistry,
+    parameters: Option<&InferenceParameters>,
+    mut results: InferenceResults,
+) -> Result<InferenceResults, String> {
+    let threshold = parameters.and_then(|p| p.threshold).or(config.threshold);
+    let top_k = parameters.and_then(|p| p.top_k).or(config.top_k);
+    apply_scoring(&mut results, config.apply_softmax, threshold, top_k);
+
+    for name in &config.custom_steps {
+        steps.postprocess_step(name)?(&mut results);
+    }
+    Ok(results)
+}
+
+fn apply_scoring(results: &mut InferenceResults, apply_softmax: bool, threshold: Option<f32>, top_k: Option<usize>) {
+    match results {
+        InferenceResults::Classification(classification) => {
+            let predictions = &mut classification.predictions;
+            if apply_softmax {
+                let max = predictions.iter().map(|p| p.confidence).fold(f32::NEG_INFINITY, f32::max);
+                let exps: Vec<f32> = predictions.iter().map(|p| (p.confidence - max).exp()).collect();
+                let sum: f32 = exps.iter().sum();
+                for (prediction, exp) in predictions.iter_mut().zip(exps) {
+                    prediction.confidence = exp / sum;
+                }
+            }
+            if let Some(threshold) = threshold {
+                predictions.retain(|p| p.confidence >= threshold);
+            }
+            if let Some(top_k) = top_k {
+                predictions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+                predictions.truncate(top_k);
+            }
+            classification.confidence_scores = predictions.iter().map(|p| p.confidence).collect();
+        }
+        InferenceResults::Detection(detection) => {
+            if let Some(threshold) = threshold {
+                detection.detections.retain(|d| d.confidence >= threshold);
+            }
+            if let Some(top_k) = top_k {
+                detection.detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+                detection.detections.truncate(top_k);
+            }
+        }
+        InferenceResults::Batch(batch) => {
+            for item in &mut batch.results {
+                apply_scoring(item, apply_softmax, threshold, top_k);
+            }
+        }
+        // Low-confidence text is flagged by the caller rather than dropped, so the
+        // recognised text stays in reading order
+        InferenceResults::Generation(_) | InferenceResults::Ocr(_) => {}
+    }
+}
+
+fn strip_html(text: String) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                stripped.push(' ');
+            }
+            _ if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassificationResult, Prediction};
+
+    fn classification(scores: &[(&str, f32)]) -> InferenceResults {
+        InferenceResults::Classification(ClassificationResult {
+            predictions: scores.iter()
+                .map(|(label, confidence)| Prediction { label: label.to_string(), confidence: *confidence, metadata: None })
+                .collect(),
+            confidence_scores: scores.iter().map(|(_, confidence)| *confidence).collect(),
+        })
+    }
+
+    fn labels(results: &InferenceResults) -> Vec<String> {
+        match results {
+            InferenceResults::Classification(classification) => {
+                classification.predictions.iter().map(|p| p.label.clone()).collect()
+            }
+            _ => panic!("expected a classification result"),
+        }
+    }
+
+    fn config(threshold: Option<f32>, top_k: Option<usize>) -> PostprocessingConfig {
+        PostprocessingConfig { apply_softmax: false, threshold, top_k, custom_steps: Vec::new() }
+    }
+
+    #[test]
+    fn test_postprocess_applies_threshold_and_top_k() {
+        let steps = StepRegistry::with_builtin_steps();
+        let raw = || classification(&[("neutral", 0.2), ("positive", 0.5), ("negative", 0.05), ("mixed", 0.25)]);
+
+        let filtered = postprocess(&config(Some(0.1), None), &steps, None, raw()).unwrap();
+        assert_eq!(labels(&filtered), vec!["neutral", "positive", "mixed"]);
+
+        let truncated = postprocess(&config(None, Some(2)), &steps, None, raw()).unwrap();
+        assert_eq!(labels(&truncated), vec!["positive", "mixed"]);
+        if let InferenceResults::Classification(classification) = &truncated {
+            assert_eq!(classification.confidence_scores, vec![0.5, 0.25]);
+        }
+
+        // The request's threshold overrides the config's
+        let parameters = InferenceParameters { temperature: None, top_k: None, top_p: None, max_tokens: None, threshold: Some(0.3), language_hints: None };
+        let overridden = postprocess(&config(Some(0.1), Some(3)), &steps, Some(&parameters), raw()).unwrap();
+        assert_eq!(labels(&overridden), vec!["positive"]);
+
+        let mut unknown_step = config(None, None);
+        unknown_step.custom_steps.push("calibrate".to_string());
+        assert!(postprocess(&unknown_step, &steps, None, raw()).is_err());
+    }
+
+    #[test]
+    fn test_preprocess_normalizes_and_truncates_text() {
+        let config = PreprocessingConfig {
+            tokenizer: Some("whitespace".to_string()),
+            max_length: Some(4),
+            normalization: true,
+            custom_steps: vec!["strip_html".to_string(), "lowercase".to_string()],
+        };
+        let inputs = InferenceInputs::Text(TextInput {
+            text: "<p>Solid-State  Batteries</p> are nearing mass production".to_string(),
+            context: None,
+        });
+
+        let prepared = preprocess(&config, &StepRegistry::with_builtin_steps(), &inputs).unwrap();
+        let InferenceInputs::Text(text) = prepared.inputs else { panic!("expected text input") };
+        assert_eq!(text.text, "solid-state batteries are nearing");
+        assert_eq!(prepared.tokens, Some(4));
+    }
+}
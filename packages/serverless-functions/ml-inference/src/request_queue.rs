@@ -0,0 +1,211 @@
+// Bounded priority admission for inference requests
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+
+/// Inference priority levels, mirroring the research engine's workflow priorities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InferencePriority {
+    Low = 1,
+    #[default]
+    Normal = 2,
+    High = 3,
+    Critical = 4,
+}
+
+/// The queue is at capacity; the request should be retried later
+#[derive(Debug)]
+pub struct QueueFull;
+
+/// Queue counters exposed on the metrics endpoint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueStats {
+    pub depth: usize,
+    pub running: usize,
+    pub admitted_total: u64,
+    pub shed_total: u64,
+    pub timeouts_total: u64,
+    pub wait_ms_sum: u64,
+    pub wait_ms_max: u64,
+}
+
+struct Waiter {
+    priority: InferencePriority,
+    /// Arrival order, so equal priorities are served first come first served
+    sequence: u64,
+    enqueued_at: Instant,
+    admit: oneshot::Sender<QueuePermit>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+struct QueueState {
+    waiting: BinaryHeap<Waiter>,
+    next_sequence: u64,
+    stats: QueueStats,
+}
+
+struct QueueInner {
+    max_concurrent: usize,
+    capacity: usize,
+    state: Mutex<QueueState>,
+}
+
+/// Admits at most `max_concurrent` inferences at a time. Others wait, highest priority
+/// first, in a queue of at most `capacity` requests; beyond that requests are shed.
+#[derive(Clone)]
+pub struct InferenceQueue {
+    inner: Arc<QueueInner>,
+}
+
+/// A running inference's slot, handed to the next waiter when dropped
+pub struct QueuePermit {
+    inner: Arc<QueueInner>,
+    /// Cleared when the permit is discarded without having held a slot
+    holds_slot: bool,
+    pub waited: Duration,
+}
+
+impl InferenceQueue {
+    pub fn new(max_concurrent: usize, capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(QueueInner {
+                max_concurrent: max_concurrent.max(1),
+                capacity,
+                state: Mutex::new(QueueState {
+                    waiting: BinaryHeap::new(),
+                    next_sequence: 0,
+                    stats: QueueStats::default(),
+                }),
+            }),
+        }
+    }
+
+    /// Wait for a slot to run an inference. Dropping the returned future gives up the place in the queue.
+    pub async fn acquire(&self, priority: InferencePriority) -> Result<QueuePermit, QueueFull> {
+        let admitted = {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.stats.running < self.inner.max_concurrent && state.waiting.is_empty() {
+                state.stats.running += 1;
+                state.stats.admitted_total += 1;
+                None
+            } else if state.waiting.len() >= self.inner.capacity {
+                state.stats.shed_total += 1;
+                return Err(QueueFull);
+            } else {
+                let (admit, admitted) = oneshot::channel();
+                let sequence = state.next_sequence;
+                state.next_sequence += 1;
+                state.waiting.push(Waiter { priority, sequence, enqueued_at: Instant::now(), admit });
+                state.stats.depth = state.waiting.len();
+                Some(admitted)
+            }
+        };
+
+        match admitted {
+            None => Ok(QueuePermit { inner: self.inner.clone(), holds_slot: true, waited: Duration::ZERO }),
+            // The sender only goes away with the queue itself
+            Some(admitted) => admitted.await.map_err(|_| QueueFull),
+        }
+    }
+
+    /// Count an inference aborted for running past its timeout
+    pub fn record_timeout(&self) {
+        self.inner.state.lock().unwrap().stats.timeouts_total += 1;
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.inner.state.lock().unwrap().stats.clone()
+    }
+}
+
+impl QueueInner {
+    /// Pass a freed slot to the highest-priority waiter still listening, or release it
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = state.waiting.pop() {
+            state.stats.depth = state.waiting.len();
+            let waited = waiter.enqueued_at.elapsed();
+            let permit = QueuePermit { inner: self.clone(), holds_slot: true, waited };
+            match waiter.admit.send(permit) {
+                Ok(()) => {
+                    let waited_ms = waited.as_millis() as u64;
+                    state.stats.admitted_total += 1;
+                    state.stats.wait_ms_sum += waited_ms;
+                    state.stats.wait_ms_max = state.stats.wait_ms_max.max(waited_ms);
+                    return;
+                }
+                // The waiter gave up; its permit never held the slot
+                Err(mut permit) => permit.holds_slot = false,
+            }
+        }
+        state.stats.running -= 1;
+    }
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        if self.holds_slot {
+            self.inner.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_higher_priority_waiters_are_admitted_first_and_overflow_is_shed() {
+        let queue = InferenceQueue::new(1, 2);
+        let running = queue.acquire(InferencePriority::Normal).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiters = Vec::new();
+        for priority in [InferencePriority::Low, InferencePriority::Critical] {
+            let (queue, order) = (queue.clone(), order.clone());
+            waiters.push(tokio::spawn(async move {
+                let _permit = queue.acquire(priority).await.unwrap();
+                order.lock().unwrap().push(priority);
+            }));
+            tokio::task::yield_now().await;
+        }
+        while queue.stats().depth < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(queue.acquire(InferencePriority::High).await.is_err());
+        drop(running);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec![InferencePriority::Critical, InferencePriority::Low]);
+        let stats = queue.stats();
+        assert_eq!((stats.running, stats.depth, stats.shed_total, stats.admitted_total), (0, 0, 1, 3));
+    }
+}
@@ -0,0 +1,292 @@
+// Source credibility scoring
+// Scores sources by domain reputation, source type and optional external signals,
+// then filters or down-weights those below a workflow's threshold and ranks the rest.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+use crate::ranking::{RankingWeights, ScoreBreakdown};
+use crate::{ResearchInsight, ResearchSource, SourceType};
+
+/// Request parameter overriding the credibility threshold for one workflow
+pub const MIN_CREDIBILITY_PARAMETER: &str = "min_credibility";
+/// Request parameter selecting `filter` or `down_weight` for one workflow
+pub const CREDIBILITY_POLICY_PARAMETER: &str = "credibility_policy";
+
+/// What to do with sources scoring below the credibility threshold
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredibilityPolicy {
+    /// Drop the source from the results
+    Filter,
+    /// Keep the source but scale its relevance by its credibility
+    DownWeight,
+}
+
+/// Signals from outside the function, e.g. a citation index or fact-checking service
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CredibilitySignals {
+    pub peer_reviewed: Option<bool>,
+    pub citation_count: Option<u32>,
+    /// Rating from 0.0 (false) to 1.0 (accurate)
+    pub fact_check_rating: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredibilityConfig {
+    /// Reputation from 0.0 to 1.0 keyed by domain. An entry matches the domain and its
+    /// subdomains; entries starting with a dot (".gov") match by suffix.
+    pub domain_reputation: HashMap<String, f32>,
+    pub min_credibility: f32,
+    pub policy: CredibilityPolicy,
+}
+
+impl Default for CredibilityConfig {
+    fn default() -> Self {
+        let domain_reputation = [
+            ("arxiv.org", 0.8),
+            ("nature.com", 0.95),
+            ("science.org", 0.95),
+            ("nih.gov", 0.95),
+            ("pubmed.ncbi.nlm.nih.gov", 0.95),
+            ("ieee.org", 0.9),
+            ("acm.org", 0.9),
+            ("who.int", 0.9),
+            ("reuters.com", 0.85),
+            ("apnews.com", 0.85),
+            ("bbc.co.uk", 0.8),
+            ("wikipedia.org", 0.65),
+            ("medium.com", 0.35),
+            ("substack.com", 0.35),
+            ("reddit.com", 0.25),
+            ("twitter.com", 0.2),
+            ("x.com", 0.2),
+            (".gov", 0.85),
+            (".edu", 0.8),
+        ]
+        .into_iter()
+        .map(|(domain, reputation)| (domain.to_string(), reputation))
+        .collect();
+
+        Self {
+            domain_reputation,
+            min_credibility: 0.4,
+            policy: CredibilityPolicy::DownWeight,
+        }
+    }
+}
+
+impl CredibilityConfig {
+    /// Load the domain reputation list from the JSON file named by `DOMAIN_REPUTATION_PATH`,
+    /// falling back to the built-in list
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(path) = std::env::var("DOMAIN_REPUTATION_PATH") {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<HashMap<String, f32>>(&content).map_err(|e| e.to_string()))
+            {
+                Ok(domains) => {
+                    config.domain_reputation = domains
+                        .into_iter()
+                        .map(|(domain, reputation)| (domain.to_lowercase(), reputation.clamp(0.0, 1.0)))
+                        .collect();
+                }
+                Err(e) => warn!("Ignoring domain reputation list {}: {}", path, e),
+            }
+        }
+
+        if let Some(threshold) = std::env::var("MIN_SOURCE_CREDIBILITY").ok().and_then(|v| v.parse::<f32>().ok()) {
+            config.min_credibility = threshold.clamp(0.0, 1.0);
+        }
+
+        config
+    }
+
+    /// This config with a workflow's `min_credibility` and `credibility_policy` parameters applied
+    pub fn for_request(&self, parameters: &HashMap<String, serde_json::Value>) -> Self {
+        let mut config = self.clone();
+
+        if let Some(threshold) = parameters.get(MIN_CREDIBILITY_PARAMETER).and_then(|v| v.as_f64()) {
+            config.min_credibility = (threshold as f32).clamp(0.0, 1.0);
+        }
+        if let Some(policy) = parameters.get(CREDIBILITY_POLICY_PARAMETER) {
+            match serde_json::from_value(policy.clone()) {
+                Ok(policy) => config.policy = policy,
+                Err(_) => warn!("Unknown credibility policy {}, using {:?}", policy, config.policy),
+            }
+        }
+
+        config
+    }
+
+    /// Reputation of the most specific list entry matching the URL's host
+    pub fn domain_reputation(&self, url: &str) -> Option<f32> {
+        let host = host_of(url)?;
+
+        let mut domain = host.as_str();
+        loop {
+            if let Some(reputation) = self.domain_reputation.get(domain) {
+                return Some(*reputation);
+            }
+            match domain.find('.') {
+                Some(dot) => {
+                    if let Some(reputation) = self.domain_reputation.get(&domain[dot..]) {
+                        return Some(*reputation);
+                    }
+                    domain = &domain[dot + 1..];
+                }
+                None => return None,
+            }
+        }
+    }
+
+    /// Credibility from 0.0 to 1.0 of a source
+    pub fn score(&self, source: &ResearchSource) -> f32 {
+        let type_score = source_type_credibility(&source.source_type);
+        let mut score = match self.domain_reputation(&source.url) {
+            Some(reputation) => reputation * 0.6 + type_score * 0.4,
+            None => type_score,
+        };
+
+        if let Some(signals) = &source.credibility_signals {
+            if signals.peer_reviewed == Some(true) {
+                score += 0.1;
+            }
+            if let Some(citations) = signals.citation_count {
+                score += ((citations as f32 + 1.0).log10() / 30.0).min(0.1);
+            }
+            if let Some(rating) = signals.fact_check_rating {
+                score = score * 0.7 + rating.clamp(0.0, 1.0) * 0.3;
+            }
+        }
+
+        score.clamp(0.0, 1.0)
+    }
+
+    /// Score every source, apply the policy to those below the threshold and rank the rest
+    /// by `weights`. Each source's relevance becomes its final score, with the breakdown
+    /// of how it was reached.
+    pub fn apply(&self, sources: Vec<ResearchSource>, weights: &RankingWeights) -> Vec<ResearchSource> {
+        let total = sources.len();
+        let now = chrono::Utc::now();
+        let mut kept: Vec<ResearchSource> = sources
+            .into_iter()
+            .filter_map(|mut source| {
+                source.credibility_score = self.score(&source);
+                let penalty = if source.credibility_score >= self.min_credibility {
+                    None
+                } else {
+                    match self.policy {
+                        CredibilityPolicy::Filter => return None,
+                        CredibilityPolicy::DownWeight => Some(source.credibility_score),
+                    }
+                };
+                let breakdown = ScoreBreakdown::new(&source, weights, penalty, now);
+                source.relevance_score = breakdown.final_score;
+                source.score_breakdown = Some(breakdown);
+                Some(source)
+            })
+            .collect();
+
+        kept.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        debug!(
+            "Kept {} of {} sources at credibility threshold {:.2} ({:?})",
+            kept.len(),
+            total,
+            self.min_credibility,
+            self.policy
+        );
+        kept
+    }
+}
+
+fn source_type_credibility(source_type: &SourceType) -> f32 {
+    match source_type {
+        SourceType::Academic => 0.85,
+        SourceType::Documentation => 0.75,
+        SourceType::News => 0.6,
+        SourceType::Other => 0.4,
+        SourceType::Blog => 0.35,
+        SourceType::Social => 0.2,
+    }
+}
+
+/// Order insights so those backed by the most credible sources come first
+pub fn rank_insights(insights: &mut [ResearchInsight], sources: &[ResearchSource]) {
+    let credibility: HashMap<&str, f32> = sources
+        .iter()
+        .map(|source| (source.url.as_str(), source.credibility_score))
+        .collect();
+
+    let support = |insight: &ResearchInsight| -> f32 {
+        let scores: Vec<f32> = insight
+            .supporting_sources
+            .iter()
+            .filter_map(|url| credibility.get(url.as_str()).copied())
+            .collect();
+        if scores.is_empty() {
+            0.0
+        } else {
+            insight.confidence * scores.iter().sum::<f32>() / scores.len() as f32
+        }
+    };
+
+    insights.sort_by(|a, b| support(b).total_cmp(&support(a)));
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?.trim_start_matches("www.");
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(url: &str, source_type: SourceType) -> ResearchSource {
+        ResearchSource {
+            url: url.to_string(),
+            title: url.to_string(),
+            relevance_score: 0.9,
+            content_snippet: String::new(),
+            source_type,
+            credibility_score: 0.0,
+            credibility_signals: None,
+            embedding_similarity: None,
+            published_at: None,
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn test_low_credibility_sources_are_down_weighted_or_filtered() {
+        let config = CredibilityConfig::default();
+        assert_eq!(config.domain_reputation("https://www.ncbi.nlm.nih.gov/articles/1"), Some(0.95));
+        assert_eq!(config.domain_reputation("https://data.census.gov"), Some(0.85));
+        assert_eq!(config.domain_reputation("https://example.com"), None);
+
+        let sources = vec![
+            source("https://someone.medium.com/hot-take", SourceType::Blog),
+            source("https://www.nature.com/articles/x", SourceType::Academic),
+        ];
+
+        let ranked = config.apply(sources.clone(), &RankingWeights::default());
+        assert_eq!(ranked[0].url, "https://www.nature.com/articles/x");
+        assert!(ranked[1].credibility_score < config.min_credibility);
+        assert!(ranked[1].relevance_score < 0.9);
+
+        let parameters = HashMap::from([(CREDIBILITY_POLICY_PARAMETER.to_string(), serde_json::json!("filter"))]);
+        let filtered = config.for_request(&parameters).apply(sources, &RankingWeights::default());
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered[0].credibility_score > 0.9);
+    }
+}
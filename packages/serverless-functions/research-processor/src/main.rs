@@ -4,6 +4,7 @@
 use axum::{
     extract::{Json, Query, State},
     http::StatusCode,
+    middleware,
     response::Json as ResponseJson,
     routing::{get, post},
     Router,
@@ -16,6 +17,23 @@ use tower_http::cors::CorsLayer;
 use tracing::{info, error, warn};
 use uuid::Uuid;
 
+mod credibility;
+mod ranking;
+#[path = "../../shared/deadline.rs"]
+mod deadline;
+#[path = "../../shared/readiness.rs"]
+mod readiness;
+#[path = "../../shared/service_auth.rs"]
+mod service_auth;
+#[path = "../../shared/shutdown.rs"]
+mod shutdown;
+
+use credibility::{CredibilityConfig, CredibilitySignals};
+use ranking::{RankingWeights, ScoreBreakdown};
+use readiness::{Readiness, ReadinessReport};
+use service_auth::ServiceAuth;
+use shutdown::Shutdown;
+
 // Application state
 #[derive(Clone)]
 pub struct AppState {
@@ -24,8 +42,17 @@ pub struct AppState {
     pub ai_service: Arc<dyn AIService>,
     pub cache: Arc<dyn CacheService>,
     pub config: Arc<FunctionConfig>,
+    /// Whether every backing service has been initialized, for `/ready`
+    pub readiness: Arc<Readiness>,
+    /// Tells running jobs to checkpoint when the process is asked to stop
+    pub shutdown: Shutdown,
 }
 
+/// Services a research job cannot run without
+const CRITICAL_SERVICES: [&str; 4] = ["database", "event_store", "ai_service", "cache"];
+/// Readiness component that fails once the process starts shutting down
+const SHUTDOWN_COMPONENT: &str = "shutdown";
+
 // Function configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionConfig {
@@ -35,6 +62,8 @@ pub struct FunctionConfig {
     pub cache_ttl: u64,
     pub ai_model_config: AIModelConfig,
     pub resource_limits: ResourceLimits,
+    pub credibility: CredibilityConfig,
+    pub ranking: RankingWeights,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +135,9 @@ pub struct ResearchResults {
     pub confidence_score: f32,
     pub processing_time: u64,
     pub tokens_used: u32,
+    /// Weights the sources were ranked with, after the workflow's overrides
+    #[serde(default)]
+    pub ranking_weights: Option<RankingWeights>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -115,6 +147,19 @@ pub struct ResearchSource {
     pub relevance_score: f32,
     pub content_snippet: String,
     pub source_type: SourceType,
+    /// Credibility from 0.0 to 1.0, set after source discovery
+    #[serde(default)]
+    pub credibility_score: f32,
+    #[serde(default)]
+    pub credibility_signals: Option<CredibilitySignals>,
+    /// Embedding similarity to the query from 0.0 to 1.0, when content analysis computed it
+    #[serde(default)]
+    pub embedding_similarity: Option<f32>,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    /// How `relevance_score` was reached, set once sources are ranked
+    #[serde(default)]
+    pub score_breakdown: Option<ScoreBreakdown>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -155,12 +200,16 @@ pub struct HealthResponse {
 }
 
 // Main function handler
-pub async fn create_app(state: AppState) -> Router {
+pub async fn create_app(state: AppState, auth: Arc<ServiceAuth>) -> Router {
     Router::new()
         .route("/", post(process_research))
         .route("/status/:job_id", get(get_job_status))
+        .route("/ranking/weights", get(ranking_weights))
         .route("/health", get(health_check))
+        .route("/ready", get(ready))
         .route("/metrics", get(metrics))
+        .layer(middleware::from_fn_with_state(auth, service_auth::require_signature))
+        .layer(middleware::from_fn(deadline::propagate_deadline))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
@@ -178,6 +227,11 @@ async fn process_research(
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    // A job started now would only be checkpointed straight away
+    if state.shutdown.is_started() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     // Generate job ID
     let job_id = Uuid::new_v4();
 
@@ -201,10 +255,13 @@ async fn process_research(
         }
     }
 
-    // Start async processing
+    // Start async processing. The caller's deadline covers this handler only; the job
+    // outlives the request, and its progress is read back from `/status/:job_id`.
     let state_clone = state.clone();
     let request_clone = request.clone();
+    let job_guard = state.shutdown.track();
     tokio::spawn(async move {
+        let _job_guard = job_guard;
         if let Err(e) = process_research_async(state_clone, job_id, request_clone).await {
             error!("Research processing failed for job {}: {}", job_id, e);
         }
@@ -242,14 +299,26 @@ async fn process_research_async(
     // Step 1: Query expansion and planning (20% progress)
     let expanded_query = expand_research_query(&state, &request.research_query).await?;
     update_job_status(&state, job_id, ProcessingStatus::Processing, 0.2).await?;
+    if checkpoint_if_stopping(&state, job_id, &request, "query_expansion", 0.2).await? {
+        return Ok(());
+    }
 
     // Step 2: Source discovery (40% progress)
     let sources = discover_sources(&state, &expanded_query, &request.methodology).await?;
+    let ranking_weights = state.config.ranking.for_request(&request.parameters);
+    let sources = state.config.credibility.for_request(&request.parameters).apply(sources, &ranking_weights);
     update_job_status(&state, job_id, ProcessingStatus::Processing, 0.4).await?;
+    if checkpoint_if_stopping(&state, job_id, &request, "source_discovery", 0.4).await? {
+        return Ok(());
+    }
 
     // Step 3: Content analysis (70% progress)
-    let insights = analyze_content(&state, &sources).await?;
+    let mut insights = analyze_content(&state, &sources).await?;
+    credibility::rank_insights(&mut insights, &sources);
     update_job_status(&state, job_id, ProcessingStatus::Processing, 0.7).await?;
+    if checkpoint_if_stopping(&state, job_id, &request, "content_analysis", 0.7).await? {
+        return Ok(());
+    }
 
     // Step 4: Synthesis and summary (90% progress)
     let summary = synthesize_results(&state, &insights, &request.methodology).await?;
@@ -263,6 +332,7 @@ async fn process_research_async(
         confidence_score: calculate_confidence_score(&sources, &insights),
         processing_time: 0, // TODO: Calculate actual processing time
         tokens_used: 0, // TODO: Track token usage
+        ranking_weights: Some(ranking_weights),
     };
 
     // Cache results if enabled
@@ -294,6 +364,31 @@ async fn process_research_async(
     Ok(())
 }
 
+// Between stages, a job of a process that is shutting down records how far it got and
+// goes back to the queue to be picked up again, instead of being cut off mid-stage
+async fn checkpoint_if_stopping(
+    state: &AppState,
+    job_id: Uuid,
+    request: &ResearchProcessingRequest,
+    completed_stage: &str,
+    progress: f32,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    if !state.shutdown.is_started() {
+        return Ok(false);
+    }
+
+    update_job_status(state, job_id, ProcessingStatus::Queued, progress).await?;
+    state.event_store.append_event(DomainEvent::ResearchProcessingCheckpointed {
+        job_id,
+        workflow_id: request.workflow_id,
+        completed_stage: completed_stage.to_string(),
+        progress,
+        timestamp: Utc::now(),
+    }).await?;
+    warn!("Checkpointed job {} after {} to resume after shutdown", job_id, completed_stage);
+    Ok(true)
+}
+
 // Get job status
 async fn get_job_status(
     State(state): State<AppState>,
@@ -306,6 +401,11 @@ async fn get_job_status(
     }
 }
 
+// Default ranking weights, which a request's `ranking_weights` parameter overrides
+async fn ranking_weights(State(state): State<AppState>) -> ResponseJson<RankingWeights> {
+    ResponseJson(state.config.ranking)
+}
+
 // Health check endpoint
 async fn health_check(State(state): State<AppState>) -> ResponseJson<HealthResponse> {
     ResponseJson(HealthResponse {
@@ -317,6 +417,11 @@ async fn health_check(State(state): State<AppState>) -> ResponseJson<HealthRespo
     })
 }
 
+// Readiness probe: 200 once every critical service is initialized, 503 until then
+async fn ready(State(state): State<AppState>) -> ReadinessReport {
+    state.readiness.report()
+}
+
 // Metrics endpoint
 async fn metrics(State(state): State<AppState>) -> String {
     // TODO: Implement Prometheus metrics
@@ -441,6 +546,14 @@ pub enum DomainEvent {
         workflow_id: Uuid,
         timestamp: DateTime<Utc>,
     },
+    /// A job stopped between stages because the process was shutting down
+    ResearchProcessingCheckpointed {
+        job_id: Uuid,
+        workflow_id: Uuid,
+        completed_stage: String,
+        progress: f32,
+        timestamp: DateTime<Utc>,
+    },
 }
 
 // Main entry point
@@ -464,23 +577,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             max_cpu_cores: 2.0,
             max_execution_time: 1800,
         },
+        credibility: CredibilityConfig::from_env(),
+        ranking: RankingWeights::from_env(),
     });
 
     // TODO: Initialize actual services
+    let readiness = Readiness::new(CRITICAL_SERVICES);
+    let shutdown = Shutdown::on_signals();
     let state = AppState {
         database: Arc::new(MockDatabaseService),
         event_store: Arc::new(MockEventStoreService),
         ai_service: Arc::new(MockAIService),
         cache: Arc::new(MockCacheService),
         config,
+        readiness: readiness.clone(),
+        shutdown: shutdown.clone(),
     };
+    // Each service is marked once it is initialized; the mocks need no setup
+    for service in CRITICAL_SERVICES {
+        readiness.mark_ready(service);
+    }
+
+    let auth = Arc::new(ServiceAuth::from_env().await?);
+    let app = create_app(state, auth).await;
 
-    let app = create_app(state).await;
+    // Stop being routed to as soon as shutdown starts, while in-flight requests drain
+    shutdown.on_start(move || readiness.mark_failed(SHUTDOWN_COMPONENT, "shutting down"));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
     info!("Research processor function listening on 0.0.0.0:8080");
-    
-    axum::serve(listener, app).await?;
+
+    shutdown::serve(listener, app, shutdown, shutdown::grace_period_from_env()).await?;
     Ok(())
 }
 
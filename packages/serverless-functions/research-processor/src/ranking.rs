@@ -0,0 +1,194 @@
+// Explainable source ranking
+// A source's final score is a weighted mean of its component scores: the provider's own
+// relevance, embedding similarity to the query, credibility and recency. Each source keeps
+// the components and the weights that produced its score, so a ranking can be understood
+// and tuned. Components a source lacks are left out and the other weights rescaled.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::ResearchSource;
+
+/// Request parameter overriding some or all of the ranking weights for one workflow
+pub const RANKING_WEIGHTS_PARAMETER: &str = "ranking_weights";
+
+/// Age at which a source's recency score has halved
+const RECENCY_HALF_LIFE_DAYS: f32 = 365.0;
+
+/// How much each component counts towards a source's final score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankingWeights {
+    pub provider_relevance: f32,
+    pub embedding_similarity: f32,
+    pub credibility: f32,
+    pub recency: f32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            provider_relevance: 0.4,
+            embedding_similarity: 0.25,
+            credibility: 0.25,
+            recency: 0.1,
+        }
+    }
+}
+
+impl RankingWeights {
+    /// The weights from the JSON object in `RANKING_WEIGHTS`, over the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        match std::env::var("RANKING_WEIGHTS") {
+            Ok(value) => match serde_json::from_str(&value) {
+                Ok(overrides) => defaults.with_overrides(&overrides),
+                Err(e) => {
+                    warn!("Ignoring RANKING_WEIGHTS: {}", e);
+                    defaults
+                }
+            },
+            Err(_) => defaults,
+        }
+    }
+
+    /// These weights with a workflow's `ranking_weights` parameter applied
+    pub fn for_request(&self, parameters: &HashMap<String, serde_json::Value>) -> Self {
+        match parameters.get(RANKING_WEIGHTS_PARAMETER) {
+            Some(overrides) => self.with_overrides(overrides),
+            None => *self,
+        }
+    }
+
+    /// These weights with the fields of `overrides` replaced. Overrides that are not an
+    /// object of non-negative numbers with at least one above zero are ignored.
+    fn with_overrides(&self, overrides: &serde_json::Value) -> Self {
+        let mut merged = serde_json::to_value(self).unwrap_or_default();
+        if let (Some(merged), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+            merged.extend(overrides.iter().map(|(name, weight)| (name.clone(), weight.clone())));
+        }
+        match serde_json::from_value::<Self>(merged) {
+            Ok(weights) if weights.is_valid() => weights,
+            _ => {
+                warn!("Ignoring invalid ranking weights {}, using {:?}", overrides, self);
+                *self
+            }
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let weights = [self.provider_relevance, self.embedding_similarity, self.credibility, self.recency];
+        weights.iter().all(|weight| weight.is_finite() && *weight >= 0.0) && weights.iter().any(|weight| *weight > 0.0)
+    }
+}
+
+/// The scores, each from 0.0 to 1.0, that a source's final score combines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreComponents {
+    pub provider_relevance: f32,
+    pub embedding_similarity: Option<f32>,
+    pub credibility: f32,
+    /// Halves every year since publication; unknown without a publication date
+    pub recency: Option<f32>,
+}
+
+/// How a source's final score came about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub components: ScoreComponents,
+    /// The weights applied, rescaled to sum to 1 over the components the source has
+    pub weights: RankingWeights,
+    /// What the weighted score was multiplied by for falling below the credibility
+    /// threshold under the `down_weight` policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credibility_penalty: Option<f32>,
+    pub final_score: f32,
+}
+
+impl ScoreBreakdown {
+    pub fn new(source: &ResearchSource, weights: &RankingWeights, credibility_penalty: Option<f32>, now: DateTime<Utc>) -> Self {
+        let components = ScoreComponents {
+            provider_relevance: source.relevance_score.clamp(0.0, 1.0),
+            embedding_similarity: source.embedding_similarity.map(|similarity| similarity.clamp(0.0, 1.0)),
+            credibility: source.credibility_score.clamp(0.0, 1.0),
+            recency: source.published_at.map(|published| recency_score(published, now)),
+        };
+
+        let applied = RankingWeights {
+            provider_relevance: weights.provider_relevance,
+            embedding_similarity: components.embedding_similarity.map_or(0.0, |_| weights.embedding_similarity),
+            credibility: weights.credibility,
+            recency: components.recency.map_or(0.0, |_| weights.recency),
+        };
+        let total = applied.provider_relevance + applied.embedding_similarity + applied.credibility + applied.recency;
+        // With only absent components weighted, fall back to the provider's relevance
+        let applied = if total > 0.0 {
+            RankingWeights {
+                provider_relevance: applied.provider_relevance / total,
+                embedding_similarity: applied.embedding_similarity / total,
+                credibility: applied.credibility / total,
+                recency: applied.recency / total,
+            }
+        } else {
+            RankingWeights { provider_relevance: 1.0, embedding_similarity: 0.0, credibility: 0.0, recency: 0.0 }
+        };
+
+        let weighted = applied.provider_relevance * components.provider_relevance
+            + applied.embedding_similarity * components.embedding_similarity.unwrap_or_default()
+            + applied.credibility * components.credibility
+            + applied.recency * components.recency.unwrap_or_default();
+        let final_score = (weighted * credibility_penalty.unwrap_or(1.0)).clamp(0.0, 1.0);
+
+        Self { components, weights: applied, credibility_penalty, final_score }
+    }
+}
+
+fn recency_score(published: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+    let age_days = (now - published).num_seconds().max(0) as f32 / 86_400.0;
+    0.5f32.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SourceType;
+
+    #[test]
+    fn test_final_score_is_explained_by_its_components_and_weights() {
+        let now = Utc::now();
+        let mut source = ResearchSource {
+            url: "https://example.org/study".to_string(),
+            title: "Study".to_string(),
+            relevance_score: 0.8,
+            content_snippet: String::new(),
+            source_type: SourceType::Academic,
+            credibility_score: 0.6,
+            credibility_signals: None,
+            embedding_similarity: None,
+            published_at: None,
+            score_breakdown: None,
+        };
+
+        // Without similarity or a date, only relevance and credibility count
+        let breakdown = ScoreBreakdown::new(&source, &RankingWeights::default(), None, now);
+        assert_eq!(breakdown.weights.embedding_similarity, 0.0);
+        assert!((breakdown.weights.provider_relevance - 0.4 / 0.65).abs() < 1e-6);
+        assert!((breakdown.final_score - (0.8 * 0.4 + 0.6 * 0.25) / 0.65).abs() < 1e-6);
+
+        source.embedding_similarity = Some(0.9);
+        source.published_at = Some(now - chrono::Duration::days(365));
+        let breakdown = ScoreBreakdown::new(&source, &RankingWeights::default(), Some(0.5), now);
+        assert!((breakdown.components.recency.unwrap() - 0.5).abs() < 1e-3);
+        let expected = (0.8 * 0.4 + 0.9 * 0.25 + 0.6 * 0.25 + 0.5 * 0.1) * 0.5;
+        assert!((breakdown.final_score - expected).abs() < 1e-3);
+
+        // A workflow can override some weights; invalid overrides are ignored
+        let parameters = HashMap::from([(RANKING_WEIGHTS_PARAMETER.to_string(), serde_json::json!({ "recency": 0.6 }))]);
+        let weights = RankingWeights::default().for_request(&parameters);
+        assert_eq!(weights.recency, 0.6);
+        assert_eq!(weights.credibility, 0.25);
+        let parameters = HashMap::from([(RANKING_WEIGHTS_PARAMETER.to_string(), serde_json::json!({ "credibility": -1 }))]);
+        assert_eq!(RankingWeights::default().for_request(&parameters), RankingWeights::default());
+    }
+}
@@ -0,0 +1,212 @@
+// Per-request deadlines
+// A caller says how long it is prepared to wait, either as an absolute time in
+// `x-fdr-deadline` (milliseconds since the Unix epoch) or as a gRPC-style relative
+// `grpc-timeout` such as `2500m`. The middleware stops the request once that passes and
+// answers 504, and makes the deadline available to the handler through
+// `Deadline::current()`, so it can cut its own timeouts, such as inference's, to fit.
+// Work a handler spawns to outlive the request is not held to it.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+use std::{
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Absolute deadline, in milliseconds since the Unix epoch
+pub const DEADLINE_HEADER: &str = "x-fdr-deadline";
+
+/// Relative timeout as gRPC sends it: up to eight digits and a unit of H, M, S, m, u or n
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+tokio::task_local! {
+    static CURRENT: Deadline;
+}
+
+/// The point by which a request has to be answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadlineRejection {
+    Malformed(&'static str),
+    Exceeded,
+}
+
+impl std::fmt::Display for DeadlineRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(header) => write!(f, "Malformed {} header", header),
+            Self::Exceeded => write!(f, "Deadline exceeded"),
+        }
+    }
+}
+
+impl IntoResponse for DeadlineRejection {
+    fn into_response(self) -> Response {
+        let (status, code) = match self {
+            Self::Malformed(_) => (StatusCode::BAD_REQUEST, "INVALID_REQUEST"),
+            Self::Exceeded => (StatusCode::GATEWAY_TIMEOUT, "DEADLINE_EXCEEDED"),
+        };
+        let body = json!({
+            "error": {
+                "code": code,
+                "message": self.to_string(),
+                "retryable": false,
+            }
+        });
+        (status, Json(body)).into_response()
+    }
+}
+
+impl Deadline {
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    /// The deadline a request carries, if any. When both headers are sent the earlier wins.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Option<Self>, DeadlineRejection> {
+        let text = |name: &'static str| {
+            headers.get(name)
+                .map(|value| value.to_str().map(str::trim).map_err(|_| DeadlineRejection::Malformed(name)))
+                .transpose()
+        };
+
+        let absolute = match text(DEADLINE_HEADER)? {
+            Some(value) => {
+                let at_ms: u64 = value.parse().map_err(|_| DeadlineRejection::Malformed(DEADLINE_HEADER))?;
+                let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+                Some(Self::after(Duration::from_millis(at_ms.saturating_sub(now_ms))))
+            }
+            None => None,
+        };
+        let relative = match text(GRPC_TIMEOUT_HEADER)? {
+            Some(value) => Some(Self::after(
+                parse_grpc_timeout(value).ok_or(DeadlineRejection::Malformed(GRPC_TIMEOUT_HEADER))?,
+            )),
+            None => None,
+        };
+        Ok(absolute.into_iter().chain(relative).min())
+    }
+
+    /// The deadline of the request being handled, if it set one
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|deadline| *deadline).ok()
+    }
+
+    /// Run `work` with this as the current deadline
+    pub async fn scope<F: Future>(self, work: F) -> F::Output {
+        CURRENT.scope(self, work).await
+    }
+
+    /// Run `work` with this as the current deadline, giving up on it once the deadline passes
+    pub async fn run<F: Future>(self, work: F) -> Result<F::Output, DeadlineRejection> {
+        tokio::time::timeout_at(self.0, self.scope(work)).await.map_err(|_| DeadlineRejection::Exceeded)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// `timeout`, shortened to what is left before the deadline
+    pub fn cap(&self, timeout: Duration) -> Duration {
+        timeout.min(self.remaining())
+    }
+}
+
+/// A `grpc-timeout` value such as `30S` or `250m`
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || !value.is_ascii() {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if digits.len() > 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(amount * 3600),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+/// Middleware enforcing the deadline a request carries. Requests without one run as before.
+pub async fn propagate_deadline(request: Request, next: Next) -> Response {
+    let deadline = match Deadline::from_headers(request.headers()) {
+        Ok(Some(deadline)) => deadline,
+        Ok(None) => return next.run(request).await,
+        Err(rejection) => return rejection.into_response(),
+    };
+    if deadline.is_expired() {
+        return DeadlineRejection::Exceeded.into_response();
+    }
+
+    let path = request.uri().path().to_string();
+    match deadline.run(next.run(request)).await {
+        Ok(response) => response,
+        Err(rejection) => {
+            warn!("Gave up on {} at its deadline", path);
+            rejection.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[tokio::test]
+    async fn test_deadlines_are_read_from_headers_and_stop_work_that_runs_past_them() {
+        assert_eq!(parse_grpc_timeout("30S"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+        assert_eq!(parse_grpc_timeout("10s"), None);
+        assert_eq!(parse_grpc_timeout("S"), None);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(Deadline::from_headers(&headers), Ok(None));
+        headers.insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("soon"));
+        assert_eq!(Deadline::from_headers(&headers), Err(DeadlineRejection::Malformed(GRPC_TIMEOUT_HEADER)));
+
+        // The earlier of the two headers wins
+        let in_a_minute = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64 + 60_000;
+        headers.insert(DEADLINE_HEADER, HeaderValue::from_str(&in_a_minute.to_string()).unwrap());
+        headers.insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("5S"));
+        let deadline = Deadline::from_headers(&headers).unwrap().unwrap();
+        assert!(deadline.remaining() <= Duration::from_secs(5));
+        assert!(deadline.cap(Duration::from_secs(30)) <= Duration::from_secs(5));
+        assert_eq!(deadline.cap(Duration::ZERO), Duration::ZERO);
+
+        // A deadline already in the past is expired on arrival
+        headers.insert(DEADLINE_HEADER, HeaderValue::from_static("1000"));
+        assert!(Deadline::from_headers(&headers).unwrap().unwrap().is_expired());
+
+        assert_eq!(Deadline::current(), None);
+        let short = Deadline::after(Duration::from_millis(20));
+        assert_eq!(short.run(async { Deadline::current() }).await, Ok(Some(short)));
+        let slow = short.run(tokio::time::sleep(Duration::from_secs(5))).await;
+        assert_eq!(slow, Err(DeadlineRejection::Exceeded));
+    }
+}
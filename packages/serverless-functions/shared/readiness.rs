@@ -0,0 +1,131 @@
+// Readiness of a service to take traffic
+// `/health` only says the process is alive; `/ready` answers 200 once every component the
+// service cannot serve without has finished starting, and 503 until then or when one of
+// them fails. Load balancers and Kubernetes readiness probes route on the latter.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// Where a critical component is in starting up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", content = "detail", rename_all = "snake_case")]
+pub enum ComponentState {
+    Starting,
+    Ready,
+    /// Could not start, or stopped being able to serve, with the reason
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentReadiness {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: ComponentState,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub components: Vec<ComponentReadiness>,
+}
+
+impl IntoResponse for ReadinessReport {
+    fn into_response(self) -> Response {
+        let status = if self.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+        (status, Json(self)).into_response()
+    }
+}
+
+/// The critical components of a service and how far each has got
+#[derive(Debug)]
+pub struct Readiness {
+    components: Mutex<Vec<ComponentReadiness>>,
+}
+
+impl Readiness {
+    /// Track `components`, each starting out not ready
+    pub fn new<'a>(components: impl IntoIterator<Item = &'a str>) -> Arc<Self> {
+        let now = Utc::now();
+        let components = components.into_iter()
+            .map(|name| ComponentReadiness { name: name.to_string(), state: ComponentState::Starting, since: now })
+            .collect();
+        Arc::new(Self { components: Mutex::new(components) })
+    }
+
+    fn set(&self, name: &str, state: ComponentState) {
+        let mut components = self.components.lock().expect("readiness lock poisoned");
+        match components.iter_mut().find(|component| component.name == name) {
+            Some(component) if component.state == state => {}
+            Some(component) => {
+                component.state = state;
+                component.since = Utc::now();
+            }
+            // A component registered late is tracked from its first report
+            None => components.push(ComponentReadiness { name: name.to_string(), state, since: Utc::now() }),
+        }
+    }
+
+    pub fn mark_ready(&self, name: &str) {
+        self.set(name, ComponentState::Ready);
+    }
+
+    pub fn mark_failed(&self, name: &str, reason: impl Into<String>) {
+        self.set(name, ComponentState::Failed(reason.into()));
+    }
+
+    /// Take a component back out of rotation, such as while it reloads
+    pub fn mark_starting(&self, name: &str) {
+        self.set(name, ComponentState::Starting);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.components.lock().expect("readiness lock poisoned")
+            .iter()
+            .all(|component| component.state == ComponentState::Ready)
+    }
+
+    pub fn report(&self) -> ReadinessReport {
+        let components = self.components.lock().expect("readiness lock poisoned").clone();
+        ReadinessReport {
+            ready: components.iter().all(|component| component.state == ComponentState::Ready),
+            components,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ready_only_once_every_component_has_started() {
+        let readiness = Readiness::new(["database", "default_model"]);
+        assert!(!readiness.is_ready());
+        assert_eq!(readiness.report().into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        readiness.mark_ready("database");
+        assert!(!readiness.is_ready());
+        readiness.mark_ready("default_model");
+        assert!(readiness.is_ready());
+        assert_eq!(readiness.report().into_response().status(), StatusCode::OK);
+
+        readiness.mark_failed("default_model", "weights not found");
+        let report = readiness.report();
+        assert!(!report.ready);
+        assert_eq!(report.components[1].state, ComponentState::Failed("weights not found".to_string()));
+        assert_eq!(
+            serde_json::to_value(&report.components[1]).unwrap()["detail"],
+            serde_json::json!("weights not found")
+        );
+
+        readiness.mark_ready("default_model");
+        readiness.mark_starting("cache");
+        assert!(!readiness.is_ready());
+    }
+}
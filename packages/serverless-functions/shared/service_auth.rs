@@ -0,0 +1,339 @@
+// Signed requests between internal services
+// Callers sign each request with a secret shared by every internal service. The
+// signature covers the calling service, method, path, query, body, a timestamp and a
+// nonce, so a captured request cannot be altered, and cannot be replayed once it is
+// stale or its nonce has been seen by any replica.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ring::{digest, hmac};
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use tracing::{info, warn};
+
+pub const SERVICE_SECRET_ENV: &str = "FDR_SERVICE_SECRET";
+/// Redis every replica records used nonces in
+pub const NONCE_STORE_URL_ENV: &str = "REDIS_URL";
+
+pub const SERVICE_HEADER: &str = "x-fdr-service";
+pub const TIMESTAMP_HEADER: &str = "x-fdr-timestamp";
+pub const NONCE_HEADER: &str = "x-fdr-nonce";
+pub const SIGNATURE_HEADER: &str = "x-fdr-signature";
+
+/// Shortest secret accepted, in bytes
+const MIN_SECRET_LEN: usize = 32;
+
+/// How far a request's timestamp may be from the receiver's clock, in seconds
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// Largest body buffered to check its signature, in bytes
+const MAX_SIGNED_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// Paths probes and scrapers call without signing
+const UNSIGNED_PATHS: &[&str] = &["/health", "/ready", "/metrics"];
+
+/// Prefix of the Redis keys holding used nonces
+const NONCE_KEY_PREFIX: &str = "fdr:service-auth:nonce:";
+
+/// Why a request was not accepted as coming from an internal service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthRejection {
+    MissingHeader(&'static str),
+    InvalidTimestamp,
+    Stale,
+    Replayed,
+    BadSignature,
+    BodyTooLarge,
+    /// The nonce store could not be reached, so a replay cannot be ruled out
+    NonceStoreUnavailable,
+}
+
+impl std::fmt::Display for AuthRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthRejection::MissingHeader(name) => write!(f, "missing {} header", name),
+            AuthRejection::InvalidTimestamp => f.write_str("timestamp is not a number of seconds"),
+            AuthRejection::Stale => f.write_str("request timestamp is too far from the current time"),
+            AuthRejection::Replayed => f.write_str("request nonce was already used"),
+            AuthRejection::BadSignature => f.write_str("signature does not match the request"),
+            AuthRejection::BodyTooLarge => f.write_str("request body is too large to verify"),
+            AuthRejection::NonceStoreUnavailable => f.write_str("nonce store is unavailable"),
+        }
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AuthRejection::BodyTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AuthRejection::NonceStoreUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// A query string in the form it is signed in: its parameters sorted, so callers may
+/// send them in any order
+pub fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+    pairs.sort_unstable();
+    pairs.join("&")
+}
+
+/// The text a request's signature is computed over
+pub fn canonical_request(service: &str, method: &str, path: &str, query: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    let body_digest: String = digest::digest(&digest::SHA256, body)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+        service,
+        method.to_uppercase(),
+        path,
+        canonical_query(query),
+        timestamp,
+        nonce,
+        body_digest
+    )
+}
+
+/// Where the nonces of accepted requests are kept until they go stale
+#[async_trait::async_trait]
+pub trait NonceStore: Send + Sync {
+    /// Record `nonce` as used until `expires_at`; false when it was already used
+    async fn claim(&self, nonce: &str, expires_at: i64, now: i64) -> Result<bool, String>;
+}
+
+/// Nonces seen by this process only, for a single replica and for tests
+#[derive(Default)]
+pub struct MemoryNonceStore {
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+#[async_trait::async_trait]
+impl NonceStore for MemoryNonceStore {
+    async fn claim(&self, nonce: &str, expires_at: i64, now: i64) -> Result<bool, String> {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, expiry| *expiry > now);
+        if seen.contains_key(nonce) {
+            return Ok(false);
+        }
+        seen.insert(nonce.to_string(), expires_at);
+        Ok(true)
+    }
+}
+
+/// Nonces shared by every replica, so a request accepted by one cannot be replayed
+/// against another
+pub struct RedisNonceStore {
+    connection: redis::aio::ConnectionManager,
+}
+
+impl RedisNonceStore {
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| format!("Invalid {}: {}", NONCE_STORE_URL_ENV, e))?;
+        let connection = redis::aio::ConnectionManager::new(client).await
+            .map_err(|e| format!("Failed to connect to the nonce store: {}", e))?;
+        Ok(Self { connection })
+    }
+}
+
+#[async_trait::async_trait]
+impl NonceStore for RedisNonceStore {
+    async fn claim(&self, nonce: &str, expires_at: i64, now: i64) -> Result<bool, String> {
+        // SET NX claims the nonce atomically across replicas; it expires when stale
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(format!("{}{}", NONCE_KEY_PREFIX, nonce))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg((expires_at - now).max(1))
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(claimed.is_some())
+    }
+}
+
+/// Verifies signed requests and claims their nonces in the store until they go stale
+pub struct ServiceAuth {
+    key: hmac::Key,
+    nonces: Arc<dyn NonceStore>,
+}
+
+impl ServiceAuth {
+    pub fn new(secret: &[u8], nonces: Arc<dyn NonceStore>) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            nonces,
+        }
+    }
+
+    /// Read the shared secret from `FDR_SERVICE_SECRET`, which a service must not start
+    /// without, and record nonces in the Redis at `REDIS_URL`. Without one, nonces are
+    /// only checked within this process.
+    pub async fn from_env() -> Result<Self, String> {
+        let secret = std::env::var(SERVICE_SECRET_ENV)
+            .map_err(|_| format!("{} must be set to the secret shared by internal services", SERVICE_SECRET_ENV))?;
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(format!("{} must be at least {} bytes", SERVICE_SECRET_ENV, MIN_SECRET_LEN));
+        }
+        let nonces: Arc<dyn NonceStore> = match std::env::var(NONCE_STORE_URL_ENV) {
+            Ok(url) if !url.is_empty() => {
+                info!("Recording request nonces in the shared store");
+                Arc::new(RedisNonceStore::connect(&url).await?)
+            }
+            _ => {
+                warn!("{} is not set; replicas will not see each other's request nonces", NONCE_STORE_URL_ENV);
+                Arc::new(MemoryNonceStore::default())
+            }
+        };
+        Ok(Self::new(secret.as_bytes(), nonces))
+    }
+
+    /// The hex signature of a request, as a caller sends it
+    pub fn sign(&self, service: &str, method: &str, path: &str, query: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+        let canonical = canonical_request(service, method, path, query, timestamp, nonce, body);
+        hmac::sign(&self.key, canonical.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Check a request's signature and freshness at `now`, returning the calling service
+    pub async fn verify(&self, headers: &HeaderMap, method: &str, path: &str, query: &str, body: &[u8], now: i64) -> Result<String, AuthRejection> {
+        let header = |name: &'static str| {
+            headers.get(name)
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .ok_or(AuthRejection::MissingHeader(name))
+        };
+        let service = header(SERVICE_HEADER)?;
+        let timestamp: i64 = header(TIMESTAMP_HEADER)?.parse().map_err(|_| AuthRejection::InvalidTimestamp)?;
+        let nonce = header(NONCE_HEADER)?;
+        let signature = header(SIGNATURE_HEADER)?;
+
+        if (now - timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+            return Err(AuthRejection::Stale);
+        }
+        let signature = decode_hex(signature).ok_or(AuthRejection::BadSignature)?;
+        let canonical = canonical_request(service, method, path, query, timestamp, nonce, body);
+        hmac::verify(&self.key, canonical.as_bytes(), &signature).map_err(|_| AuthRejection::BadSignature)?;
+
+        // Only a correctly signed request may claim a nonce, so forged ones cannot use them
+        // up. Past its expiry the timestamp check rejects the request anyway.
+        let claimed = self.nonces.claim(nonce, timestamp + MAX_CLOCK_SKEW_SECS, now).await.map_err(|e| {
+            warn!("Failed to claim request nonce: {}", e);
+            AuthRejection::NonceStoreUnavailable
+        })?;
+        if !claimed {
+            return Err(AuthRejection::Replayed);
+        }
+        Ok(service.to_string())
+    }
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+/// Middleware rejecting requests not signed by an internal service, apart from health
+/// checks and metrics
+pub async fn require_signature(State(auth): State<Arc<ServiceAuth>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+    if UNSIGNED_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_SIGNED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => return AuthRejection::BodyTooLarge.into_response(),
+    };
+    let now = chrono::Utc::now().timestamp();
+    match auth.verify(&parts.headers, parts.method.as_str(), &path, &query, &body, now).await {
+        Ok(_) => next.run(Request::from_parts(parts, Body::from(body))).await,
+        Err(rejection) => {
+            warn!("Rejected {} {}: {}", parts.method, path, rejection);
+            rejection.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const SECRET: &[u8] = b"a-shared-secret-of-at-least-32-bytes";
+    const NONCE: &str = "3f1c2a9e-5b7d-4e8f-9a01-6c2d3e4f5a6b";
+    const BODY: &[u8] = br#"{"model_name":"ocr"}"#;
+
+    fn auth_with(secret: &[u8], nonces: &Arc<MemoryNonceStore>) -> ServiceAuth {
+        ServiceAuth::new(secret, nonces.clone())
+    }
+
+    fn signed_headers(auth: &ServiceAuth, query: &str, timestamp: i64, nonce: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(SERVICE_HEADER, HeaderValue::from_static("desktop"));
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from_str(&timestamp.to_string()).unwrap());
+        headers.insert(NONCE_HEADER, HeaderValue::from_str(nonce).unwrap());
+        let signature = auth.sign("desktop", "POST", "/infer", query, timestamp, nonce, BODY);
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&signature).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_only_fresh_unaltered_requests_are_accepted_once() {
+        let nonces = Arc::new(MemoryNonceStore::default());
+        let auth = auth_with(SECRET, &nonces);
+        let now = 1_700_000_000;
+
+        // Callers sign the same way; the desktop signer checks the same values
+        assert_eq!(
+            auth.sign("desktop", "POST", "/infer", "", now, NONCE, BODY),
+            "46d8d0af224723742624099470fc35c068b988afc12da580a8169b66296484fc"
+        );
+        assert_eq!(
+            auth.sign("desktop", "POST", "/infer", "version=2&model=ocr", now, NONCE, BODY),
+            "6b463c756938836e8b5f54ca41115d7061917eb9facde8b5c710c920ad88acb4"
+        );
+
+        let headers = signed_headers(&auth, "", now, NONCE);
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "", BODY, now + 10).await, Ok("desktop".to_string()));
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "", BODY, now + 20).await, Err(AuthRejection::Replayed));
+        // Another replica sharing the store turns the replay away too
+        assert_eq!(auth_with(SECRET, &nonces).verify(&headers, "POST", "/infer", "", BODY, now + 20).await, Err(AuthRejection::Replayed));
+
+        let headers = signed_headers(&auth, "model=ocr", now, "another-nonce");
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "model=ocr", br#"{"model_name":"x"}"#, now).await, Err(AuthRejection::BadSignature));
+        assert_eq!(auth.verify(&headers, "POST", "/models", "model=ocr", BODY, now).await, Err(AuthRejection::BadSignature));
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "model=other", BODY, now).await, Err(AuthRejection::BadSignature));
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "", BODY, now).await, Err(AuthRejection::BadSignature));
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "model=ocr", BODY, now + MAX_CLOCK_SKEW_SECS + 1).await, Err(AuthRejection::Stale));
+        assert_eq!(
+            auth_with(b"some-other-secret-of-32-bytes-or-more", &nonces).verify(&headers, "POST", "/infer", "model=ocr", BODY, now).await,
+            Err(AuthRejection::BadSignature)
+        );
+
+        let mut unsigned = headers.clone();
+        unsigned.remove(SIGNATURE_HEADER);
+        assert_eq!(auth.verify(&unsigned, "POST", "/infer", "model=ocr", BODY, now).await, Err(AuthRejection::MissingHeader(SIGNATURE_HEADER)));
+        assert_eq!(auth.verify(&headers, "POST", "/infer", "model=ocr", BODY, now).await, Ok("desktop".to_string()));
+    }
+}
@@ -0,0 +1,240 @@
+// Graceful shutdown for the services
+// On SIGTERM or SIGINT the server stops accepting connections and lets the requests in
+// flight finish, for up to a grace period, before the process exits. Background work
+// registers with the same `Shutdown` to be told to checkpoint, and to hold the exit
+// until it has, within the same grace period.
+
+use axum::Router;
+use std::{
+    future::IntoFuture,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{watch, Notify};
+use tracing::{info, warn};
+
+/// Seconds in-flight requests and background work get to finish once shutdown starts
+pub const GRACE_PERIOD_ENV: &str = "FDR_SHUTDOWN_GRACE_SECS";
+
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// The grace period from the environment, or 30 seconds
+pub fn grace_period_from_env() -> Duration {
+    match std::env::var(GRACE_PERIOD_ENV) {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                warn!("Ignoring {}={:?}: expected a number of seconds", GRACE_PERIOD_ENV, value);
+                DEFAULT_GRACE_PERIOD
+            }
+        },
+        Err(_) => DEFAULT_GRACE_PERIOD,
+    }
+}
+
+/// Shared between the server and its background work: whether shutdown has started, and
+/// how much work is still running
+#[derive(Clone)]
+pub struct Shutdown {
+    started: Arc<watch::Sender<bool>>,
+    in_flight: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+/// Held by a piece of background work; shutdown waits until every guard is dropped
+pub struct WorkGuard {
+    in_flight: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            started: Arc::new(watch::channel(false).0),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            idle: Arc::new(Notify::new()),
+        }
+    }
+
+    /// A `Shutdown` that starts when the process receives SIGTERM or SIGINT
+    pub fn on_signals() -> Self {
+        let shutdown = Self::new();
+        let trigger = shutdown.clone();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            trigger.start();
+        });
+        shutdown
+    }
+
+    pub fn start(&self) {
+        self.started.send_replace(true);
+    }
+
+    pub fn is_started(&self) -> bool {
+        *self.started.borrow()
+    }
+
+    /// Resolves once shutdown has started
+    pub async fn started(&self) {
+        let mut started = self.started.subscribe();
+        // The sender lives as long as `self`, so this only ends once the flag is set
+        let _ = started.wait_for(|started| *started).await;
+    }
+
+    /// Run `action` once shutdown starts, such as taking the service out of rotation
+    pub fn on_start(&self, action: impl FnOnce() + Send + 'static) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            shutdown.started().await;
+            action();
+        });
+    }
+
+    /// Count a piece of background work as running until the guard is dropped
+    pub fn track(&self) -> WorkGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        WorkGuard { in_flight: self.in_flight.clone(), idle: self.idle.clone() }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait for tracked work to finish, returning whether it did within `timeout`
+    pub async fn drained(&self, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+}
+
+async fn wait_for_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => info!("Received SIGINT, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Serve `app` until `shutdown` starts, then stop accepting connections and give the
+/// requests in flight and the tracked background work up to `grace` to finish. Whatever is
+/// still running after that is dropped.
+pub async fn serve(listener: tokio::net::TcpListener, app: Router, shutdown: Shutdown, grace: Duration) -> std::io::Result<()> {
+    let signal = shutdown.clone();
+    let mut server = tokio::spawn(
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async move { signal.started().await })
+            .into_future(),
+    );
+
+    let stopped = tokio::select! {
+        result = &mut server => Some(result),
+        _ = shutdown.started() => None,
+    };
+    let deadline = tokio::time::Instant::now() + grace;
+    match stopped {
+        // The server failed on its own; there is nothing to drain
+        Some(result) if !shutdown.is_started() => return joined(result),
+        Some(result) => joined(result)?,
+        None => {
+            info!("Draining in-flight requests for up to {}s", grace.as_secs());
+            match tokio::time::timeout_at(deadline, &mut server).await {
+                Ok(result) => joined(result)?,
+                Err(_) => {
+                    warn!("In-flight requests did not finish within the {}s grace period", grace.as_secs());
+                    server.abort();
+                }
+            }
+        }
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    if !shutdown.drained(remaining).await {
+        warn!("{} background jobs were still running at the end of the grace period", shutdown.in_flight());
+    }
+    info!("Shutdown complete");
+    Ok(())
+}
+
+fn joined(result: Result<std::io::Result<()>, tokio::task::JoinError>) -> std::io::Result<()> {
+    result.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_tracked_work_up_to_the_grace_period() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.is_started());
+
+        let guard = shutdown.track();
+        let job = {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                // Background work checkpoints once told to stop
+                shutdown.started().await;
+                drop(guard);
+            })
+        };
+        assert_eq!(shutdown.in_flight(), 1);
+        assert!(!shutdown.drained(Duration::from_millis(20)).await);
+
+        shutdown.start();
+        assert!(shutdown.is_started());
+        assert!(shutdown.drained(Duration::from_secs(1)).await);
+        job.await.unwrap();
+        assert_eq!(shutdown.in_flight(), 0);
+
+        // Work that never finishes is given up on
+        let _stuck = shutdown.track();
+        assert!(!shutdown.drained(Duration::from_millis(20)).await);
+    }
+}